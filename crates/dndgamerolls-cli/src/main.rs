@@ -2,19 +2,37 @@
 //!
 //! A command-line D&D dice roller with character sheet support.
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
-use rand::Rng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use surrealdb::engine::local::SurrealKv;
+use surrealdb::engine::local::{Db, SurrealKv};
 use surrealdb::sql::Value as SurrealValue;
 use surrealdb::Surreal;
 
+use dndgamerolls_core::cli::{
+    condition_edge, configure_output, pick_slot, print_condition_effect, quiet,
+    report_automatic_failure, roll_attack, roll_attack_sequence, roll_d20_test, roll_damage,
+    PlannedAttack, RiderOffer,
+};
+use dndgamerolls_core::say;
+use dndgamerolls_core::{
+    attuned_count, attunement_warning, carrying_capacity, check_character_sheet,
+    check_two_weapon_fighting, coin_side, ddb_to_sheet_json, deserialize_items,
+    format_item_bonuses, foundry_to_sheet_json, inventory_value, inventory_weight, item_bonus,
+    percentile_faces, sheet_json_to_foundry, spend_ammunition, weapon_damage, AmmunitionUse,
+    AttackMode, BonusRoll, CoinAction, ConditionEffect, Conditions, Currency, D20Test, DamageRider,
+    DamageRoll, DamageTypeRegistry, DiceExpr, DiceType, Encumbrance, InventoryItem, RollEngine,
+    Ruleset, SlotPool, WeaponAttack, WeaponStats, CHARACTER_SHEET_SCHEMA, DAMAGE_TYPES_SETTING_KEY,
+    DEFAULT_ATTUNEMENT_SLOTS, DEFAULT_EXPLOSION_CAP, MAX_CUSTOM_DIE_SIDES, RULESET_SETTING_KEY,
+};
+
 fn surreal_value_to_json(value: SurrealValue) -> Result<JsonValue, String> {
     serde_json::to_value(value).map_err(|e| format!("Failed to encode JSON: {e}"))
 }
@@ -44,9 +62,13 @@ struct Cli {
     #[arg(long)]
     character_id: Option<i64>,
 
-    /// Dice to roll (e.g., "2d6", "1d20", "d8"). Can specify multiple.
+    /// Dice to roll (e.g., "2d6", "1d20", "2d6+1d8+3", "(1d6+2)*2", "1d6!"). Can specify multiple.
     #[arg(short, long, value_parser = parse_dice_arg)]
-    dice: Option<Vec<(usize, DiceType)>>,
+    dice: Option<Vec<DiceExpr>>,
+
+    /// Most extra rolls one exploding die ("1d6!") may add
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_EXPLOSION_CAP)]
+    explode_cap: u32,
 
     /// Check to apply modifier for (skill, ability, or save name)
     #[arg(long)]
@@ -63,6 +85,18 @@ struct Cli {
     /// Roll with disadvantage (roll twice, take lower)
     #[arg(short = 'D', long)]
     disadvantage: bool,
+
+    /// Print only the total of each roll, for scripts and pipelines
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print without colors (also when NO_COLOR is set)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Database profile to read characters from (defaults to the active one)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -98,6 +132,10 @@ enum Commands {
     Skill {
         /// Skill name (e.g., stealth, perception, acrobatics)
         name: String,
+
+        /// Roll with a non-default ability (e.g., intimidation --with str)
+        #[arg(long = "with", value_name = "ABILITY")]
+        with: Option<String>,
     },
 
     /// Roll a saving throw
@@ -106,91 +144,194 @@ enum Commands {
         ability: String,
     },
 
-    /// Roll an attack
+    /// Roll an attack; ammunition weapons spend a piece of ammunition
     Attack {
         /// Weapon name
         weapon: String,
+
+        /// Wield a versatile weapon with two hands
+        #[arg(long, conflicts_with = "thrown")]
+        two_handed: bool,
+
+        /// Throw the weapon
+        #[arg(long)]
+        thrown: bool,
+
+        /// Make every attack of the Attack action (one plus Extra Attack)
+        #[arg(long)]
+        all: bool,
+
+        /// Follow up with an off-hand attack with this weapon (two-weapon
+        /// fighting)
+        #[arg(long, value_name = "WEAPON", conflicts_with = "two_handed")]
+        off_hand: Option<String>,
+
+        /// Add a damage rider to the hits it fits without asking ("Sneak
+        /// Attack", "Divine Smite", "Hunter's Mark", "Hex: 1d6 necrotic")
+        #[arg(long = "rider", value_name = "RIDER")]
+        riders: Vec<String>,
+
+        /// Don't offer the character's damage riders on hits
+        #[arg(long, conflicts_with = "riders")]
+        no_riders: bool,
+
+        /// Spell slot level Divine Smite spends (asked at a terminal; the
+        /// lowest left otherwise)
+        #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(1..=9))]
+        slot: Option<u32>,
+    },
+
+    /// Cast a known spell, spending a spell slot, and roll its damage
+    Cast {
+        /// Spell name
+        spell: String,
+
+        /// Spell slot level to cast it with, higher to upcast it (asked at a
+        /// terminal; the lowest left otherwise)
+        #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(1..=9))]
+        slot: Option<u32>,
+    },
+
+    /// Roll damage for a weapon, or a damage string like "1d8+3 slashing"
+    Damage {
+        /// Weapon name or damage string
+        weapon: String,
+
+        /// Critical hit: roll the damage dice twice
+        #[arg(long)]
+        crit: bool,
+
+        /// Wield a versatile weapon with two hands
+        #[arg(long)]
+        two_handed: bool,
     },
 
     /// Display character stats
     Stats,
+
+    /// Show the character's coins, or add, spend or exchange some
+    /// ("coins add 15gp", "coins spend 3gp 5sp", "coins convert 100cp --to gp")
+    Coins {
+        /// What to do with the coins (omit to show them)
+        #[arg(value_enum, requires = "amount")]
+        action: Option<CoinAction>,
+
+        /// Coins, e.g. 15gp or 3 gp 5 sp
+        amount: Vec<String>,
+
+        /// Coin to exchange for (with convert)
+        #[arg(long, value_name = "COIN", required_if_eq("action", "convert"))]
+        to: Option<String>,
+    },
+
+    /// Show the party's travel line-up and roll group Stealth
+    Travel {
+        /// Monsters' passive Perception to roll group Stealth against
+        #[arg(long = "vs", value_name = "PASSIVE_PERCEPTION")]
+        vs: Option<i32>,
+    },
+
+    /// Import character sheet JSON files into the local database
+    Import {
+        /// A character JSON file, or a directory of them
+        path: PathBuf,
+
+        /// Format of the files
+        #[arg(long, value_enum, default_value_t = ImportFormat::Sheet)]
+        format: ImportFormat,
+
+        /// Replace a stored character that has the same name
+        #[arg(long, conflicts_with = "rename")]
+        overwrite: bool,
+
+        /// Import under a numbered name ("Thorin (2)") when the name is taken
+        #[arg(long)]
+        rename: bool,
+    },
+
+    /// Export characters from the local database to JSON files
+    Export {
+        /// Output file, or a directory to write `<name>.json` files into
+        path: PathBuf,
+
+        /// Export every character (into the directory `path`) instead of the
+        /// one selected with --character / --character-id
+        #[arg(long)]
+        all: bool,
+
+        /// Format of the files
+        #[arg(long, value_enum, default_value_t = ExportFormat::Sheet)]
+        format: ExportFormat,
+
+        /// Replace existing files
+        #[arg(long, conflicts_with = "rename")]
+        overwrite: bool,
+
+        /// Write to a numbered file ("Thorin (2).json") when the file exists
+        #[arg(long)]
+        rename: bool,
+    },
+
+    /// Print the character sheet JSON Schema imports are checked against
+    Schema,
 }
 
-// ============================================================================
-// Dice Types
-// ============================================================================
+/// Format of imported character files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ImportFormat {
+    /// Character sheet JSON (as exported, or a `.dndchar` file)
+    Sheet,
+    /// D&D Beyond character export
+    Ddb,
+    /// Foundry VTT actor export (D&D 5e system)
+    Foundry,
+}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum DiceType {
-    D4,
-    D6,
-    D8,
-    D10,
-    D12,
-    D20,
-}
-
-impl DiceType {
-    pub fn max_value(&self) -> u32 {
-        match self {
-            DiceType::D4 => 4,
-            DiceType::D6 => 6,
-            DiceType::D8 => 8,
-            DiceType::D10 => 10,
-            DiceType::D12 => 12,
-            DiceType::D20 => 20,
-        }
-    }
+/// Format of exported character files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    /// Character sheet JSON, as the app imports it
+    Sheet,
+    /// Foundry VTT actor (D&D 5e system), for "Import Data" on an actor
+    Foundry,
+}
 
-    pub fn name(&self) -> &'static str {
-        match self {
-            DiceType::D4 => "D4",
-            DiceType::D6 => "D6",
-            DiceType::D8 => "D8",
-            DiceType::D10 => "D10",
-            DiceType::D12 => "D12",
-            DiceType::D20 => "D20",
-        }
-    }
+/// What to do when an imported or exported character collides with an
+/// existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnCollision {
+    /// Skip it and report an error
+    Fail,
+    Overwrite,
+    Rename,
+}
 
-    pub fn parse(s: &str) -> Option<DiceType> {
-        match s.to_lowercase().as_str() {
-            "d4" => Some(DiceType::D4),
-            "d6" => Some(DiceType::D6),
-            "d8" => Some(DiceType::D8),
-            "d10" => Some(DiceType::D10),
-            "d12" => Some(DiceType::D12),
-            "d20" => Some(DiceType::D20),
-            _ => None,
+impl OnCollision {
+    fn from_flags(overwrite: bool, rename: bool) -> Self {
+        if overwrite {
+            OnCollision::Overwrite
+        } else if rename {
+            OnCollision::Rename
+        } else {
+            OnCollision::Fail
         }
     }
 }
 
-fn parse_dice_arg(s: &str) -> Result<(usize, DiceType), String> {
-    let s = s.to_lowercase();
-
-    let (count_str, die_str) = if s.starts_with('d') {
-        ("1", s.as_str())
-    } else if let Some(pos) = s.find('d') {
-        (&s[..pos], &s[pos..])
-    } else {
-        return Err(format!(
-            "Invalid dice format: {}. Use format like '2d6' or 'd20'",
-            s
-        ));
-    };
-
-    let count: usize = count_str
-        .parse()
-        .map_err(|_| format!("Invalid count: {}", count_str))?;
-    let die_type = DiceType::parse(die_str).ok_or_else(|| {
+fn parse_dice_arg(s: &str) -> Result<DiceExpr, String> {
+    let expression = DiceExpr::parse(s).map_err(|e| {
         format!(
-            "Unknown die type: {}. Valid: d4, d6, d8, d10, d12, d20",
-            die_str
+            "{}. Use dice like '2d6', 'd20' or '2d6+1d8+3' (d2 to d{}, d% for d100, 4dF, 1coin)",
+            e, MAX_CUSTOM_DIE_SIDES
         )
     })?;
-
-    Ok((count, die_type))
+    if expression.dice().is_empty() {
+        return Err(format!(
+            "No dice in '{}'; use --modifier for a flat bonus",
+            s
+        ));
+    }
+    Ok(expression)
 }
 
 // ============================================================================
@@ -210,6 +351,14 @@ struct Character {
     skills: HashMap<String, Skill>,
     #[serde(default)]
     equipment: Option<Equipment>,
+    #[serde(rename = "toolProficiencies", default)]
+    tool_proficiencies: Vec<ToolProficiency>,
+    #[serde(default, skip_serializing_if = "Conditions::is_empty")]
+    conditions: Conditions,
+    #[serde(default)]
+    features: Vec<Feature>,
+    #[serde(default)]
+    spells: Option<Spells>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -248,8 +397,20 @@ struct Combat {
     #[serde(rename = "armorClass")]
     armor_class: i32,
     initiative: i32,
+    #[serde(default)]
+    speed: i32,
     #[serde(rename = "hitPoints", default)]
     hit_points: Option<HitPoints>,
+    /// Attacks the Attack action gives beyond the first (Extra Attack)
+    #[serde(rename = "extraAttacks", default)]
+    extra_attacks: u32,
+    /// Sneak Attack, Divine Smite and the like, offered on weapon hits
+    #[serde(
+        rename = "damageRiders",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    damage_riders: Vec<DamageRider>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -274,19 +435,150 @@ struct Skill {
     proficiency_type: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+struct ToolProficiency {
+    name: String,
+    #[serde(default)]
+    skills: Vec<String>,
+    #[serde(default)]
+    benefit: ToolSynergyBenefit,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+enum ToolSynergyBenefit {
+    #[default]
+    Advantage,
+    Bonus(i32),
+}
+
+/// Spell slots, spent by smites and the `cast` command, and the known spells
+/// with their damage; the rest of the spellcasting is the app's
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct Spells {
+    #[serde(rename = "spellSlots", default)]
+    spell_slots: HashMap<String, i32>,
+    /// Slots spent since the last long rest, by spell level ("1".."9")
+    #[serde(rename = "usedSlots", default)]
+    used_slots: HashMap<String, i32>,
+    #[serde(rename = "knownSpells", default)]
+    known_spells: Vec<String>,
+    /// Damage rolled by a spell (spell name -> "8d6 fire")
+    #[serde(rename = "spellDamage", default)]
+    spell_damage: HashMap<String, String>,
+}
+
+impl Spells {
+    /// Slots of a spell level left to spend.
+    fn slots_remaining(&self, level: u32) -> i32 {
+        let level = level.to_string();
+        let total = self.spell_slots.get(&level).copied().unwrap_or(0);
+        let used = self.used_slots.get(&level).copied().unwrap_or(0);
+        (total - used).max(0)
+    }
+
+    /// Lowest spell level with a slot left to spend.
+    fn lowest_slot_remaining(&self) -> Option<u32> {
+        (1..=9).find(|level| self.slots_remaining(*level) > 0)
+    }
+
+    /// Spend a slot of `level` on `what` ("Divine Smite", "Fireball"),
+    /// refusing when none of that level are left.
+    fn spend_slot_for(&mut self, level: u32, what: &str) -> Result<(), String> {
+        if self.slots_remaining(level) > 0 {
+            *self.used_slots.entry(level.to_string()).or_insert(0) += 1;
+            Ok(())
+        } else if self.slot_choices().is_empty() {
+            Err(format!("No spell slots left for {}", what))
+        } else {
+            Err(format!("No level {} spell slots left for {}", level, what))
+        }
+    }
+
+    /// The known spell called `name`, ignoring case.
+    fn known_spell(&self, name: &str) -> Option<&str> {
+        self.known_spells
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case(name.trim()))
+            .map(String::as_str)
+    }
+}
+
+/// Smites and upcast spells spend these slots
+impl SlotPool for Spells {
+    /// Spell levels with slots left to spend, as `(level, remaining)`; what
+    /// a smite or an upcast spell can be cast with.
+    fn slot_choices(&self) -> Vec<(u32, i32)> {
+        (1..=9)
+            .map(|level| (level, self.slots_remaining(level)))
+            .filter(|(_, remaining)| *remaining > 0)
+            .collect()
+    }
+
+    /// Spend the spell slot a rider takes (of `level`, or the lowest one
+    /// left, for Divine Smite) and return its level; 0 for riders that take
+    /// none.
+    fn spend_rider_slot(&mut self, rider: &DamageRider, level: Option<u32>) -> Result<u32, String> {
+        if !rider.spends_slot() {
+            return Ok(0);
+        }
+        let level = match level {
+            Some(level) => level,
+            None => self
+                .lowest_slot_remaining()
+                .ok_or_else(|| format!("No spell slots left for {}", rider.name()))?,
+        };
+        self.spend_slot_for(level, rider.name())?;
+        Ok(level)
+    }
+}
+
+/// Class feature, feat or fighting style; only the name is used
+#[derive(Debug, Deserialize, Serialize)]
+struct Feature {
+    name: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Equipment {
+    #[serde(default)]
     weapons: Vec<Weapon>,
+    /// Carried items; older sheets store "Arrows (20)" strings
+    #[serde(default, deserialize_with = "deserialize_items")]
+    items: Vec<InventoryItem>,
+    #[serde(default)]
+    currency: Currency,
+    /// Magic items the character can attune to, when not the usual three
+    #[serde(
+        rename = "attunementSlots",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    attunement_slots: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct Weapon {
     name: String,
-    #[serde(rename = "attackBonus")]
+    /// 0 when not stored; the attack then works it out from the properties
+    #[serde(rename = "attackBonus", default)]
     attack_bonus: i32,
     damage: String,
     #[serde(rename = "damageType")]
     damage_type: String,
+    #[serde(default)]
+    properties: Vec<String>,
+}
+
+impl Weapon {
+    fn stats(&self) -> WeaponStats<'_> {
+        WeaponStats {
+            name: &self.name,
+            attack_bonus: self.attack_bonus,
+            damage: &self.damage,
+            properties: &self.properties,
+        }
+    }
 }
 
 // ============================================================================
@@ -295,6 +587,11 @@ struct Weapon {
 
 fn main() {
     let cli = Cli::parse();
+    configure_output(cli.quiet, cli.no_color);
+
+    if let Some(profile) = &cli.profile {
+        let _ = PROFILE.set(profile.clone());
+    }
 
     // If using --dice or --checkon, do a direct roll
     if cli.dice.is_some() || cli.checkon.is_some() {
@@ -302,6 +599,64 @@ fn main() {
         return;
     }
 
+    // Party-wide commands load every character instead of one
+    if let Some(Commands::Travel { vs }) = &cli.command {
+        run_travel(*vs);
+        return;
+    }
+
+    if let Some(Commands::Import {
+        path,
+        format,
+        overwrite,
+        rename,
+    }) = &cli.command
+    {
+        run_import(path, *format, OnCollision::from_flags(*overwrite, *rename));
+        return;
+    }
+
+    if let Some(Commands::Schema) = &cli.command {
+        println!("{}", CHARACTER_SHEET_SCHEMA);
+        return;
+    }
+
+    if let Some(Commands::Export {
+        path,
+        all,
+        format,
+        overwrite,
+        rename,
+    }) = &cli.command
+    {
+        let selection = if *all {
+            None
+        } else {
+            Some((cli.character.as_deref(), cli.character_id))
+        };
+        run_export(
+            path,
+            selection,
+            *format,
+            OnCollision::from_flags(*overwrite, *rename),
+        );
+        return;
+    }
+
+    // Damage types are shown in the campaign's colors
+    let damage_types = load_damage_types();
+
+    // A damage string needs no character
+    if let Some(Commands::Damage { weapon, crit, .. }) = &cli.command {
+        if let Ok(damage) = DamageRoll::parse(weapon) {
+            if let Err(e) = roll_damage("Damage", damage, *crit, cli.explode_cap, &damage_types) {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+
     // Handle subcommands
     if let Some(command) = &cli.command {
         let character = match load_character(cli.character.as_deref(), cli.character_id) {
@@ -312,36 +667,62 @@ fn main() {
             }
         };
 
+        // Ability checks and skills get advantage, disadvantage or a penalty
+        // from conditions, as the campaign's rules have them
+        let ruleset = load_ruleset();
+        let check_edge = || {
+            condition_edge(
+                &character.conditions,
+                D20Test::AbilityCheck,
+                ruleset,
+                cli.advantage,
+                cli.disadvantage,
+            )
+        };
+
         match command {
             Commands::Strength => {
-                let modifier = character.modifiers.strength;
-                roll_ability_check("Strength", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.strength
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Strength", modifier + penalty, advantage, disadvantage);
             }
             Commands::Dexterity => {
-                let modifier = character.modifiers.dexterity;
-                roll_ability_check("Dexterity", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.dexterity
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Dexterity", modifier + penalty, advantage, disadvantage);
             }
             Commands::Constitution => {
-                let modifier = character.modifiers.constitution;
-                roll_ability_check("Constitution", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.constitution
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Constitution", modifier + penalty, advantage, disadvantage);
             }
             Commands::Intelligence => {
-                let modifier = character.modifiers.intelligence;
-                roll_ability_check("Intelligence", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.intelligence
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Intelligence", modifier + penalty, advantage, disadvantage);
             }
             Commands::Wisdom => {
-                let modifier = character.modifiers.wisdom;
-                roll_ability_check("Wisdom", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.wisdom
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Wisdom", modifier + penalty, advantage, disadvantage);
             }
             Commands::Charisma => {
-                let modifier = character.modifiers.charisma;
-                roll_ability_check("Charisma", modifier, cli.advantage, cli.disadvantage);
+                let modifier = character.modifiers.charisma
+                    + character_item_bonus(&character, BonusRoll::AbilityCheck);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Charisma", modifier + penalty, advantage, disadvantage);
             }
             Commands::Initiative => {
                 let modifier = character.combat.initiative;
-                roll_ability_check("Initiative", modifier, cli.advantage, cli.disadvantage);
+                let (advantage, disadvantage, penalty) = check_edge();
+                roll_ability_check("Initiative", modifier + penalty, advantage, disadvantage);
             }
-            Commands::Skill { name } => {
+            Commands::Skill { name, with } => {
                 if let Some((skill_name, skill)) = get_skill_by_name(&character.skills, name) {
                     let proficiency_str = if skill.expertise.unwrap_or(false) {
                         " (Expertise)"
@@ -350,11 +731,52 @@ fn main() {
                     } else {
                         ""
                     };
+
+                    let (modifier, ability_str) = match with.as_deref() {
+                        Some(ability) => {
+                            let Some((abbr, modifier)) =
+                                skill_modifier_with_ability(&character, skill, ability)
+                            else {
+                                eprintln!(
+                                    "{} Unknown ability '{}'",
+                                    "Error:".red().bold(),
+                                    ability
+                                );
+                                eprintln!("Use: str, dex, con, int, wis, cha");
+                                std::process::exit(1);
+                            };
+                            (modifier, format!(" ({})", abbr))
+                        }
+                        None => (skill.modifier, String::new()),
+                    };
+                    let modifier =
+                        modifier + character_item_bonus(&character, BonusRoll::Skill(skill_name));
+
+                    // Xanathar's tool/skill synergy from tagged tool proficiencies
+                    let (tool_advantage, tool_bonus, tools) =
+                        tool_synergy_for_skill(&character, skill_name);
+                    if !tools.is_empty() {
+                        let mut effects = Vec::new();
+                        if tool_advantage {
+                            effects.push("advantage".to_string());
+                        }
+                        if tool_bonus != 0 {
+                            effects.push(format!("{:+}", tool_bonus));
+                        }
+                        println!(
+                            "{} {}: {}",
+                            "Tool proficiency:".bold().white(),
+                            tools.join(", "),
+                            effects.join(", ")
+                        );
+                    }
+
+                    let (advantage, disadvantage, penalty) = check_edge();
                     roll_ability_check(
-                        &format!("{}{}", skill_name, proficiency_str),
-                        skill.modifier,
-                        cli.advantage,
-                        cli.disadvantage,
+                        &format!("{}{}{}", skill_name, ability_str, proficiency_str),
+                        modifier + tool_bonus + penalty,
+                        advantage || tool_advantage,
+                        disadvantage,
                     );
                 } else {
                     eprintln!("{} Unknown skill '{}'", "Error:".red().bold(), name);
@@ -393,15 +815,39 @@ fn main() {
                     }
                 };
                 let proficiency_str = if save.proficient { " (Proficient)" } else { "" };
-                roll_ability_check(
-                    &format!("{} Save{}", save_name, proficiency_str),
-                    save.modifier,
-                    cli.advantage,
-                    cli.disadvantage,
-                );
+                let name = format!("{} Save{}", save_name, proficiency_str);
+                let test = D20Test::SavingThrow(key.to_string());
+                // Paralyzed, stunned or unconscious: Strength and Dexterity
+                // saves fail without a roll
+                let effect = character.conditions.effect_on(&test, ruleset);
+                if !report_automatic_failure(&name, &effect) {
+                    let (advantage, disadvantage, penalty) = condition_edge(
+                        &character.conditions,
+                        test,
+                        ruleset,
+                        cli.advantage,
+                        cli.disadvantage,
+                    );
+                    roll_ability_check(
+                        &name,
+                        save.modifier
+                            + character_item_bonus(&character, BonusRoll::Save(key))
+                            + penalty,
+                        advantage,
+                        disadvantage,
+                    );
+                }
             }
-            Commands::Attack { weapon } => {
-                let weapon_lower = weapon.to_lowercase();
+            Commands::Attack {
+                weapon,
+                two_handed,
+                thrown,
+                all,
+                off_hand,
+                riders,
+                no_riders,
+                slot,
+            } => {
                 let Some(equipment) = character.equipment.as_ref() else {
                     eprintln!(
                         "{} No equipment/weapon data found for this character",
@@ -410,24 +856,299 @@ fn main() {
                     std::process::exit(1);
                 };
 
-                if let Some(wpn) = equipment
-                    .weapons
+                let find_weapon = |name: &str| {
+                    let name_lower = name.to_lowercase();
+                    let Some(wpn) = equipment
+                        .weapons
+                        .iter()
+                        .find(|w| w.name.to_lowercase() == name_lower)
+                    else {
+                        eprintln!("{} Weapon '{}' not found", "Error:".red().bold(), name);
+                        eprintln!("Available weapons:");
+                        for wpn in &equipment.weapons {
+                            eprintln!("  - {}", wpn.name);
+                        }
+                        std::process::exit(1);
+                    };
+                    wpn
+                };
+                let wpn = find_weapon(weapon);
+                let off_hand = off_hand.as_deref().map(find_weapon);
+
+                let fail = |e: String| -> ! {
+                    eprintln!("{} {}", "Error:".red().bold(), e);
+                    std::process::exit(1);
+                };
+                let mode = if *thrown {
+                    AttackMode::Thrown
+                } else if *two_handed {
+                    AttackMode::TwoHanded
+                } else {
+                    AttackMode::Normal
+                };
+                let attack = weapon_attack(&character, wpn, mode).unwrap_or_else(|e| fail(e));
+
+                // The attacks of the Attack action, then the off-hand attack
+                let count = if *all {
+                    1 + character.combat.extra_attacks
+                } else {
+                    1
+                };
+                let mut planned: Vec<PlannedAttack> = (1..=count)
+                    .map(|n| PlannedAttack {
+                        label: format!("Attack {}", n),
+                        weapon: wpn.name.clone(),
+                        damage_type: wpn.damage_type.clone(),
+                        attack: attack.clone(),
+                        ammunition: None,
+                    })
+                    .collect();
+                if let Some(off_hand) = off_hand {
+                    let attack =
+                        off_hand_attack(&character, wpn, off_hand).unwrap_or_else(|e| fail(e));
+                    planned.push(PlannedAttack {
+                        label: "Off Hand".to_string(),
+                        weapon: off_hand.name.clone(),
+                        damage_type: off_hand.damage_type.clone(),
+                        attack,
+                        ammunition: None,
+                    });
+                }
+
+                // Every shot spends a piece of ammunition from the items
+                let mut items = equipment.items.clone();
+                let mut spent = false;
+                for step in planned.iter_mut().filter(|p| p.attack.uses_ammunition) {
+                    let ammunition = spend_ammunition(&mut items, &step.weapon);
+                    let out = matches!(ammunition, AmmunitionUse::OutOf(_));
+                    if out && !spent {
+                        fail(ammunition.describe());
+                    }
+                    step.ammunition = Some(ammunition);
+                    if out {
+                        break;
+                    }
+                    spent = true;
+                }
+                if spent {
+                    if let Err(e) = save_character_part(
+                        cli.character.as_deref(),
+                        cli.character_id,
+                        "equipment",
+                        "items",
+                        serde_json::json!(items),
+                    ) {
+                        eprintln!("Warning: failed to save ammunition: {}", e);
+                    }
+                }
+
+                let (advantage, disadvantage, penalty) = condition_edge(
+                    &character.conditions,
+                    D20Test::AttackRoll,
+                    ruleset,
+                    cli.advantage,
+                    cli.disadvantage,
+                );
+                for step in planned.iter_mut() {
+                    step.attack.attack_bonus += penalty;
+                }
+
+                // The character's damage riders are offered on hits; --rider
+                // ones are added without asking
+                let always: Vec<DamageRider> = riders
                     .iter()
-                    .find(|w| w.name.to_lowercase() == weapon_lower)
-                {
-                    roll_attack(wpn, cli.advantage, cli.disadvantage);
+                    .map(|name| {
+                        character
+                            .combat
+                            .damage_riders
+                            .iter()
+                            .find(|rider| rider.name().eq_ignore_ascii_case(name.trim()))
+                            .cloned()
+                            .map_or_else(|| DamageRider::parse(name), Ok)
+                    })
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_else(|e| fail(e));
+                let mut offered = if *no_riders {
+                    Vec::new()
                 } else {
-                    eprintln!("{} Weapon '{}' not found", "Error:".red().bold(), weapon);
-                    eprintln!("Available weapons:");
-                    for wpn in &equipment.weapons {
-                        eprintln!("  - {}", wpn.name);
+                    character.combat.damage_riders.clone()
+                };
+                for rider in &always {
+                    if !offered.contains(rider) {
+                        offered.push(rider.clone());
+                    }
+                }
+                let mut spells = character.spells.clone().unwrap_or_default();
+                let mut riders = RiderOffer {
+                    level: character.character.level,
+                    proficiency_bonus: character.proficiency_bonus,
+                    slots: &mut spells,
+                    riders: offered,
+                    always,
+                    slot: *slot,
+                    ask: std::io::stdin().is_terminal() && !quiet(),
+                    used: Vec::new(),
+                    slots_spent: false,
+                    // Weapon mastery is the app's
+                    mastery: false,
+                    target_save: 0,
+                    vexed: false,
+                    cleaved: false,
+                };
+
+                match planned.as_slice() {
+                    [single] => roll_attack(
+                        single,
+                        advantage,
+                        disadvantage,
+                        cli.explode_cap,
+                        &damage_types,
+                        &mut riders,
+                    ),
+                    _ => roll_attack_sequence(
+                        &planned,
+                        advantage,
+                        disadvantage,
+                        cli.explode_cap,
+                        &damage_types,
+                        &mut riders,
+                    ),
+                }
+                if riders.slots_spent {
+                    if let Err(e) = save_character_part(
+                        cli.character.as_deref(),
+                        cli.character_id,
+                        "spells",
+                        "usedSlots",
+                        serde_json::json!(spells.used_slots),
+                    ) {
+                        eprintln!("Warning: failed to save the spell slots: {}", e);
                     }
+                }
+            }
+            Commands::Damage {
+                weapon,
+                crit,
+                two_handed,
+            } => {
+                let weapon_lower = weapon.to_lowercase();
+                let Some(wpn) = character.equipment.as_ref().and_then(|e| {
+                    e.weapons
+                        .iter()
+                        .find(|w| w.name.to_lowercase() == weapon_lower)
+                }) else {
+                    eprintln!(
+                        "{} '{}' is neither a weapon nor a damage roll like 1d8+3",
+                        "Error:".red().bold(),
+                        weapon
+                    );
+                    std::process::exit(1);
+                };
+                let mode = if *two_handed {
+                    AttackMode::TwoHanded
+                } else {
+                    AttackMode::Normal
+                };
+                let rolled = weapon_attack(&character, wpn, mode)
+                    .and_then(|attack| weapon_damage(&attack.damage, &wpn.damage_type))
+                    .and_then(|damage| {
+                        roll_damage(&wpn.name, damage, *crit, cli.explode_cap, &damage_types)
+                    });
+                if let Err(e) = rolled {
+                    eprintln!("{} {}", "Error:".red().bold(), e);
                     std::process::exit(1);
                 }
             }
             Commands::Stats => {
                 display_stats(&character);
             }
+            Commands::Cast { spell, slot } => {
+                let fail = |e: String| -> ! {
+                    eprintln!("{} {}", "Error:".red().bold(), e);
+                    std::process::exit(1);
+                };
+                let Some(mut spells) = character.spells.clone() else {
+                    fail(format!("{} doesn't cast spells", character.character.name));
+                };
+                let Some(spell) = spells.known_spell(spell).map(str::to_string) else {
+                    fail(format!("{} isn't one of the known spells", spell));
+                };
+                let choices = spells.slot_choices();
+                if choices.is_empty() {
+                    fail(format!("No spell slots left for {}", spell));
+                }
+                let level = match slot {
+                    Some(level) => *level,
+                    None if std::io::stdin().is_terminal() => {
+                        pick_slot(&choices, &spell).unwrap_or_else(|| std::process::exit(1))
+                    }
+                    None => choices[0].0,
+                };
+                spells
+                    .spend_slot_for(level, &spell)
+                    .unwrap_or_else(|e| fail(e));
+                println!(
+                    "{} {} cast with a level {} slot ({} left)",
+                    "✨".bold(),
+                    spell.bold().yellow(),
+                    level,
+                    spells.slots_remaining(level)
+                );
+                if let Err(e) = save_character_part(
+                    cli.character.as_deref(),
+                    cli.character_id,
+                    "spells",
+                    "usedSlots",
+                    serde_json::json!(spells.used_slots),
+                ) {
+                    fail(format!("failed to save the spell slots: {}", e));
+                }
+                if let Some(damage) = spells.spell_damage.get(&spell) {
+                    DamageRoll::parse(damage)
+                        .and_then(|damage| {
+                            roll_damage(&spell, damage, false, cli.explode_cap, &damage_types)
+                        })
+                        .unwrap_or_else(|e| fail(e));
+                }
+            }
+            Commands::Coins { action, amount, to } => {
+                let mut currency = character
+                    .equipment
+                    .as_ref()
+                    .map(|e| e.currency.clone())
+                    .unwrap_or_default();
+                if let Some(action) = action {
+                    match currency.update(*action, &amount.join(" "), to.as_deref()) {
+                        Ok(done) => println!("{}", done.green()),
+                        Err(e) => {
+                            eprintln!("{} {}", "Error:".red().bold(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                    if let Err(e) = save_character_part(
+                        cli.character.as_deref(),
+                        cli.character_id,
+                        "equipment",
+                        "currency",
+                        serde_json::json!(currency),
+                    ) {
+                        eprintln!("{} failed to save the coins: {}", "Error:".red().bold(), e);
+                        std::process::exit(1);
+                    }
+                }
+                println!(
+                    "{} {} ({} gp in all)",
+                    "Coins:".bold(),
+                    currency.describe(),
+                    currency.total_gold()
+                );
+            }
+            Commands::Travel { .. }
+            | Commands::Import { .. }
+            | Commands::Export { .. }
+            | Commands::Schema => {
+                unreachable!("handled before loading a character")
+            }
         }
     } else {
         // No command or dice specified - show help
@@ -438,6 +1159,7 @@ fn main() {
         eprintln!("  dndrolls --dice d20 --checkon stealth");
         eprintln!("  dndrolls skill stealth");
         eprintln!("  dndrolls stats");
+        eprintln!("  dndrolls export --all ./party");
         std::process::exit(1);
     }
 }
@@ -449,25 +1171,29 @@ fn main() {
 fn run_dice_roll(cli: &Cli) {
     let mut total_modifier = cli.modifier;
     let mut modifier_name = String::new();
-    let mut dice_to_roll: Vec<DiceType> = Vec::new();
 
-    // Parse dice
-    if let Some(dice_args) = &cli.dice {
-        for (count, die_type) in dice_args {
-            for _ in 0..*count {
-                dice_to_roll.push(*die_type);
-            }
-        }
-    }
+    // Parse dice; compound expressions are totalled from the dice they roll
+    let expression = cli
+        .dice
+        .clone()
+        .and_then(DiceExpr::sum)
+        .map(|e| e.with_explosion_cap(cli.explode_cap));
+    let mut dice_to_roll = expression.as_ref().map(|e| e.dice()).unwrap_or_default();
 
     // Apply checkon modifier from character file
+    let mut effect = ConditionEffect::default();
     if let Some(check) = &cli.checkon {
         if let Ok(character) = load_character(cli.character.as_deref(), cli.character_id) {
             let check_lower = check.to_lowercase();
+            let ruleset = load_ruleset();
 
-            if let Some((_, skill)) = get_skill_by_name(&character.skills, &check_lower) {
-                total_modifier += skill.modifier;
+            if let Some((skill_name, skill)) = get_skill_by_name(&character.skills, &check_lower) {
+                total_modifier +=
+                    skill.modifier + character_item_bonus(&character, BonusRoll::Skill(skill_name));
                 modifier_name = check.clone();
+                effect = character
+                    .conditions
+                    .effect_on(&D20Test::AbilityCheck, ruleset);
             } else {
                 // Try ability modifiers
                 let ability_mod = match check_lower.as_str() {
@@ -481,11 +1207,19 @@ fn run_dice_roll(cli: &Cli) {
                 };
 
                 if let Some(mod_val) = ability_mod {
-                    total_modifier += mod_val;
+                    total_modifier +=
+                        mod_val + character_item_bonus(&character, BonusRoll::AbilityCheck);
                     modifier_name = format!("{} check", check);
+                    effect = character
+                        .conditions
+                        .effect_on(&D20Test::AbilityCheck, ruleset);
                 } else if let Some(save) = character.saving_throws.get(&check_lower) {
-                    total_modifier += save.modifier;
+                    total_modifier += save.modifier
+                        + character_item_bonus(&character, BonusRoll::Save(&check_lower));
                     modifier_name = format!("{} save", check);
+                    effect = character
+                        .conditions
+                        .effect_on(&D20Test::SavingThrow(check_lower.clone()), ruleset);
                 } else {
                     modifier_name = check.clone();
                     eprintln!("Warning: '{}' not found in character sheet", check);
@@ -497,61 +1231,71 @@ fn run_dice_roll(cli: &Cli) {
         }
     }
 
+    // Conditions on the character give the check advantage, disadvantage or
+    // a penalty, or fail it outright
+    if report_automatic_failure(&modifier_name, &effect) {
+        return;
+    }
+    print_condition_effect(&effect);
+    total_modifier += effect.penalty();
+    let advantage = cli.advantage || !effect.advantage.is_empty();
+    let disadvantage = cli.disadvantage || !effect.disadvantage.is_empty();
+
     // Default to 1d20 if no dice specified
     if dice_to_roll.is_empty() {
         dice_to_roll.push(DiceType::D20);
     }
 
     // Roll the dice
-    let mut rng = rand::rng();
+    let mut engine = RollEngine::new();
     let mut results: Vec<(DiceType, u32)> = Vec::new();
     let mut total: i32 = 0;
 
     for die in &dice_to_roll {
-        let roll = rng.random_range(1..=die.max_value());
+        let roll = engine.roll_die(*die);
         results.push((*die, roll));
-        total += roll as i32;
+        total += die.face_value(roll);
     }
 
     // Handle advantage/disadvantage for d20 rolls
     if dice_to_roll.len() == 1 && dice_to_roll[0] == DiceType::D20 {
-        if cli.advantage && !cli.disadvantage {
-            let roll2 = rng.random_range(1..=20);
+        if advantage && !disadvantage {
+            let roll2 = engine.roll_die(DiceType::D20);
             let roll1 = results[0].1;
             let used = roll1.max(roll2);
             let dropped = roll1.min(roll2);
             results[0].1 = used;
             total = used as i32;
 
-            println!("\n{}", "═══════════════════════════════════════".cyan());
-            println!(
+            say!("\n{}", "═══════════════════════════════════════".cyan());
+            say!(
                 "{} {} {}",
                 "Rolling:".bold().white(),
                 modifier_name.yellow().bold(),
                 "(Advantage)".green()
             );
-            println!(
+            say!(
                 "{} {} (dropped {})",
                 "Dice:".bold().white(),
                 format!("[{}]", used).bright_green().bold(),
                 format!("[{}]", dropped).dimmed()
             );
-        } else if cli.disadvantage && !cli.advantage {
-            let roll2 = rng.random_range(1..=20);
+        } else if disadvantage && !advantage {
+            let roll2 = engine.roll_die(DiceType::D20);
             let roll1 = results[0].1;
             let used = roll1.min(roll2);
             let dropped = roll1.max(roll2);
             results[0].1 = used;
             total = used as i32;
 
-            println!("\n{}", "═══════════════════════════════════════".cyan());
-            println!(
+            say!("\n{}", "═══════════════════════════════════════".cyan());
+            say!(
                 "{} {} {}",
                 "Rolling:".bold().white(),
                 modifier_name.yellow().bold(),
                 "(Disadvantage)".red()
             );
-            println!(
+            say!(
                 "{} {} (dropped {})",
                 "Dice:".bold().white(),
                 format!("[{}]", used).bright_red().bold(),
@@ -564,6 +1308,31 @@ fn run_dice_roll(cli: &Cli) {
         print_normal_roll(&results, &modifier_name);
     }
 
+    if let Some(expression) = expression.filter(|e| !e.is_dice_sum()) {
+        // Exploding dice keep rolling while they show their maximum
+        let mut explosions = Vec::new();
+        expression
+            .roll_pending_explosions(&results, &mut explosions, |die| Some(engine.roll_die(die)));
+        match expression.roll_with_explosions(&results, &explosions) {
+            Ok(roll) => {
+                total = roll.total as i32;
+                say!(
+                    "{} {} = {}",
+                    "Expression:".bold().white(),
+                    expression,
+                    total
+                );
+                if let Some(exploded) = roll.explosion_summary() {
+                    say!("  {}", exploded.dimmed());
+                }
+                if let Some(kept) = roll.keep_summary() {
+                    say!("  {}", kept.dimmed());
+                }
+            }
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+    }
+
     // Print modifier and total
     if total_modifier != 0 {
         let modifier_str = if total_modifier >= 0 {
@@ -571,7 +1340,7 @@ fn run_dice_roll(cli: &Cli) {
         } else {
             format!("{}", total_modifier).cyan()
         };
-        println!("{} {}", "Modifier:".bold().white(), modifier_str);
+        say!("{} {}", "Modifier:".bold().white(), modifier_str);
     }
 
     let final_total = total + total_modifier;
@@ -581,6 +1350,11 @@ fn run_dice_roll(cli: &Cli) {
         None
     };
 
+    if quiet() {
+        println!("{}", final_total);
+        return;
+    }
+
     let total_color = match d20_roll {
         Some(20) => format!("{}", final_total).bright_green().bold(),
         Some(1) => format!("{}", final_total).bright_red().bold(),
@@ -590,28 +1364,28 @@ fn run_dice_roll(cli: &Cli) {
         _ => format!("{}", final_total).red(),
     };
 
-    println!("{} {}", "Total:".bold().white(), total_color);
+    say!("{} {}", "Total:".bold().white(), total_color);
 
     if let Some(20) = d20_roll {
-        println!("{}", "NATURAL 20! CRITICAL SUCCESS!".bright_green().bold());
+        say!("{}", "NATURAL 20! CRITICAL SUCCESS!".bright_green().bold());
     } else if let Some(1) = d20_roll {
-        println!("{}", "NATURAL 1! CRITICAL FAILURE!".bright_red().bold());
+        say!("{}", "NATURAL 1! CRITICAL FAILURE!".bright_red().bold());
     }
 
-    println!("{}", "═══════════════════════════════════════".cyan());
+    say!("{}", "═══════════════════════════════════════".cyan());
 }
 
 fn print_normal_roll(results: &[(DiceType, u32)], modifier_name: &str) {
-    println!("\n{}", "═══════════════════════════════════════".cyan());
+    say!("\n{}", "═══════════════════════════════════════".cyan());
     if !modifier_name.is_empty() {
-        println!(
+        say!(
             "{} {}",
             "Rolling:".bold().white(),
             modifier_name.yellow().bold()
         );
     } else {
-        let dice_str: Vec<String> = results.iter().map(|(d, _)| d.name().to_string()).collect();
-        println!(
+        let dice_str: Vec<String> = results.iter().map(|(d, _)| d.name()).collect();
+        say!(
             "{} {}",
             "Rolling:".bold().white(),
             dice_str.join(", ").yellow().bold()
@@ -630,34 +1404,27 @@ fn print_normal_roll(results: &[(DiceType, u32)], modifier_name: &str) {
             } else {
                 format!("[{}]", r).bright_white().bold().to_string()
             };
-            format!("{}: {}", d.name(), roll_color)
+            if d.is_coin() {
+                format!("{}: {}", d.name(), coin_side(*r).bright_white().bold())
+            } else if *d == DiceType::Fudge {
+                format!(
+                    "{}: {}",
+                    d.name(),
+                    format!("[{}]", d.face_text(*r)).bright_white().bold()
+                )
+            } else if d.is_percentile() {
+                let pair = format!("({})", percentile_faces(*r)).dimmed();
+                format!("{}: {} {}", d.name(), roll_color, pair)
+            } else {
+                format!("{}: {}", d.name(), roll_color)
+            }
         })
         .collect();
-    println!("{} {}", "Dice:".bold().white(), rolls_str.join(", "));
-}
-
-fn roll_d20() -> i32 {
-    rand::rng().random_range(1..=20)
-}
-
-fn roll_with_advantage_disadvantage(advantage: bool, disadvantage: bool) -> (i32, Option<i32>) {
-    if advantage && disadvantage {
-        (roll_d20(), None)
-    } else if advantage {
-        let roll1 = roll_d20();
-        let roll2 = roll_d20();
-        (roll1.max(roll2), Some(roll1.min(roll2)))
-    } else if disadvantage {
-        let roll1 = roll_d20();
-        let roll2 = roll_d20();
-        (roll1.min(roll2), Some(roll1.max(roll2)))
-    } else {
-        (roll_d20(), None)
-    }
+    say!("{} {}", "Dice:".bold().white(), rolls_str.join(", "));
 }
 
 fn roll_ability_check(name: &str, modifier: i32, advantage: bool, disadvantage: bool) {
-    let (dice_roll, dropped_roll) = roll_with_advantage_disadvantage(advantage, disadvantage);
+    let (dice_roll, dropped_roll) = roll_d20_test(advantage, disadvantage);
     let total = dice_roll + modifier;
     display_roll_result(
         name,
@@ -670,69 +1437,56 @@ fn roll_ability_check(name: &str, modifier: i32, advantage: bool, disadvantage:
     );
 }
 
-fn roll_attack(weapon: &Weapon, advantage: bool, disadvantage: bool) {
-    let (dice_roll, dropped_roll) = roll_with_advantage_disadvantage(advantage, disadvantage);
-    let total = dice_roll + weapon.attack_bonus;
+/// Work out an attack with one of the character's weapons from its
+/// properties.
+fn weapon_attack(
+    character: &Character,
+    weapon: &Weapon,
+    mode: AttackMode,
+) -> Result<WeaponAttack, String> {
+    let mut attack = WeaponAttack::new(
+        weapon.stats(),
+        mode,
+        character.modifiers.strength,
+        character.modifiers.dexterity,
+        character.proficiency_bonus,
+    )?;
+    attack.attack_bonus += character_item_bonus(character, BonusRoll::Attack);
+    Ok(attack)
+}
 
-    println!("\n{}", "═══════════════════════════════════════".cyan());
-    println!("{} Attack", weapon.name.bold().yellow());
+/// What the character's attuned and equipped magic items add to `roll`
+fn character_item_bonus(character: &Character, roll: BonusRoll) -> i32 {
+    character
+        .equipment
+        .as_ref()
+        .map_or(0, |equipment| item_bonus(&equipment.items, roll))
+}
 
-    if let Some(dropped) = dropped_roll {
-        if advantage {
-            println!(
-                "{} {} {} (dropped {})",
-                "Attack Roll:".bold().white(),
-                format!("[{}]", dice_roll).bright_green().bold(),
-                "(advantage)".green(),
-                format!("[{}]", dropped).dimmed()
-            );
-        } else if disadvantage {
-            println!(
-                "{} {} {} (dropped {})",
-                "Attack Roll:".bold().white(),
-                format!("[{}]", dice_roll).bright_red().bold(),
-                "(disadvantage)".red(),
-                format!("[{}]", dropped).dimmed()
-            );
-        }
-    } else {
-        let dice_color = match dice_roll {
-            20 => format!("[{}]", dice_roll).bright_green().bold(),
-            1 => format!("[{}]", dice_roll).bright_red().bold(),
-            _ => format!("[{}]", dice_roll).bright_white().bold(),
-        };
-        println!("{} {}", "Attack Roll:".bold().white(), dice_color);
-    }
-
-    let modifier_str = if weapon.attack_bonus >= 0 {
-        format!("+{}", weapon.attack_bonus).cyan()
-    } else {
-        format!("{}", weapon.attack_bonus).cyan()
-    };
-    println!("{} {}", "Attack Bonus:".bold().white(), modifier_str);
-
-    let total_color = if dice_roll == 20 {
-        format!("{}", total).bright_green().bold()
-    } else if dice_roll == 1 {
-        format!("{}", total).bright_red().bold()
-    } else {
-        format!("{}", total).white().bold()
-    };
-    println!("{} {}", "Total:".bold().white(), total_color);
-
-    if dice_roll == 20 {
-        println!("{}", "CRITICAL HIT!".bright_green().bold());
-    } else if dice_roll == 1 {
-        println!("{}", "CRITICAL MISS!".bright_red().bold());
-    }
+/// Whether one of the character's features, feats or fighting styles has
+/// this name ("Dual Wielder" matches "Feat: Dual Wielder").
+fn has_feature(character: &Character, name: &str) -> bool {
+    let name = name.to_lowercase();
+    character
+        .features
+        .iter()
+        .any(|f| f.name.to_lowercase().contains(&name))
+}
 
-    println!(
-        "{} {} ({})",
-        "Damage:".bold().white(),
-        weapon.damage.yellow(),
-        weapon.damage_type.dimmed()
-    );
-    println!("{}", "═══════════════════════════════════════".cyan());
+/// The off-hand attack when fighting with two weapons, checked against the
+/// Dual Wielder feat and using the Two-Weapon Fighting style.
+fn off_hand_attack(
+    character: &Character,
+    main_hand: &Weapon,
+    off_hand: &Weapon,
+) -> Result<WeaponAttack, String> {
+    check_two_weapon_fighting(
+        main_hand.stats(),
+        off_hand.stats(),
+        has_feature(character, "Dual Wielder"),
+    )?;
+    let attack = weapon_attack(character, off_hand, AttackMode::Normal)?;
+    Ok(attack.off_hand(has_feature(character, "Two-Weapon Fighting")))
 }
 
 fn display_roll_result(
@@ -744,6 +1498,11 @@ fn display_roll_result(
     advantage: bool,
     disadvantage: bool,
 ) {
+    if quiet() {
+        println!("{}", total);
+        return;
+    }
+
     println!("\n{}", "═══════════════════════════════════════".cyan());
     println!(
         "{} {}",
@@ -814,13 +1573,61 @@ fn display_roll_result(
 // Character Functions
 // ============================================================================
 
-fn load_character(
-    character_name: Option<&str>,
-    character_id: Option<i64>,
-) -> Result<Character, Box<dyn std::error::Error>> {
-    const NS: &str = "dndgamerolls";
-    const DB: &str = "dndgamerolls";
+const SURREAL_NS: &str = "dndgamerolls";
+const SURREAL_DB: &str = "dndgamerolls";
+
+/// Character list row (id + name) from the local database
+#[derive(Debug, Deserialize)]
+struct ListRow {
+    id: i64,
+    name: String,
+}
+
+/// Open the local SurrealDB character database.
+fn open_local_db() -> Result<(tokio::runtime::Runtime, Surreal<Db>), Box<dyn std::error::Error>> {
+    let db_path = get_surreal_path()?;
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let db = rt.block_on(async {
+        Surreal::new::<SurrealKv>(db_path.to_string_lossy().to_string()).await
+    })?;
+    rt.block_on(async {
+        db.use_ns(SURREAL_NS).use_db(SURREAL_DB).await?;
+        Ok::<(), surrealdb::Error>(())
+    })?;
+
+    Ok((rt, db))
+}
+
+/// List all characters (id + name), sorted by name.
+fn list_character_rows(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+) -> Result<Vec<ListRow>, Box<dyn std::error::Error>> {
+    let mut response = rt.block_on(async {
+        db.query("SELECT sid AS id, name FROM character ORDER BY name")
+            .await
+    })?;
+    let raw_rows: Vec<SurrealValue> = response.take(0)?;
+    let mut rows: Vec<ListRow> = Vec::with_capacity(raw_rows.len());
+    for raw in raw_rows {
+        rows.push(
+            from_surreal_value(raw)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+        );
+    }
+    Ok(rows)
+}
 
+/// Load a single character sheet by id.
+fn load_character_by_id(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    target_id: i64,
+) -> Result<Character, Box<dyn std::error::Error>> {
     #[derive(Debug, Deserialize)]
     struct Record<T> {
         #[allow(dead_code)]
@@ -833,7 +1640,6 @@ fn load_character(
     struct CharacterDoc {
         #[allow(dead_code)]
         sid: i64,
-        #[allow(dead_code)]
         name: String,
         #[allow(dead_code)]
         class: String,
@@ -842,69 +1648,12 @@ fn load_character(
         #[allow(dead_code)]
         level: i32,
         sheet: Character,
+        /// Present when the app encrypted the sheet; `sheet` then only has
+        /// the listing fields.
+        #[serde(default)]
+        sealed_sheet: Option<JsonValue>,
     }
 
-    #[derive(Debug, Deserialize)]
-    struct ListRow {
-        id: i64,
-        name: String,
-    }
-
-    let db_path = get_surreal_path()?;
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let rt = tokio::runtime::Runtime::new()?;
-    let db = rt.block_on(async {
-        Surreal::new::<SurrealKv>(db_path.to_string_lossy().to_string()).await
-    })?;
-    rt.block_on(async {
-        db.use_ns(NS).use_db(DB).await?;
-        Ok::<(), surrealdb::Error>(())
-    })?;
-
-    let target_id = if let Some(id) = character_id {
-        id
-    } else {
-        let mut response = rt.block_on(async {
-            db.query("SELECT sid AS id, name FROM character ORDER BY name")
-                .await
-        })?;
-        let raw_rows: Vec<SurrealValue> = response.take(0)?;
-        let mut rows: Vec<ListRow> = Vec::with_capacity(raw_rows.len());
-        for raw in raw_rows {
-            rows.push(
-                from_surreal_value(raw)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
-            );
-        }
-
-        if rows.is_empty() {
-            return Err("No characters found in local database".into());
-        }
-
-        if let Some(name) = character_name {
-            match rows.iter().find(|r| r.name.eq_ignore_ascii_case(name)) {
-                Some(r) => r.id,
-                None => {
-                    let hint = format!(
-                        "Character '{}' not found. Available (id:name): {}",
-                        name,
-                        rows.iter()
-                            .take(10)
-                            .map(|r| format!("{}:{}", r.id, r.name))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    );
-                    return Err(std::io::Error::new(std::io::ErrorKind::NotFound, hint).into());
-                }
-            }
-        } else {
-            rows[0].id
-        }
-    };
-
     let raw_record: Option<SurrealValue> =
         rt.block_on(async { db.select(("character", target_id)).await })?;
     let record: Option<Record<CharacterDoc>> = match raw_record {
@@ -922,9 +1671,495 @@ fn load_character(
         .into());
     };
 
+    if record.data.sealed_sheet.is_some() {
+        return Err(format!(
+            "Character '{}' is encrypted; use the 3D app or `dndgamerolls --cli` \
+             (with DNDGAMEROLLS_PASSPHRASE set) instead",
+            record.data.name
+        )
+        .into());
+    }
+
     Ok(record.data.sheet)
 }
 
+/// Id of the character picked by `--character-id` or `--character`, or the
+/// first one when neither is given.
+fn target_character_id(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    character_name: Option<&str>,
+    character_id: Option<i64>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    if let Some(id) = character_id {
+        return Ok(id);
+    }
+
+    let rows = list_character_rows(rt, db)?;
+
+    if rows.is_empty() {
+        return Err("No characters found in local database".into());
+    }
+
+    if let Some(name) = character_name {
+        match rows.iter().find(|r| r.name.eq_ignore_ascii_case(name)) {
+            Some(r) => Ok(r.id),
+            None => {
+                let hint = format!(
+                    "Character '{}' not found. Available (id:name): {}",
+                    name,
+                    rows.iter()
+                        .take(10)
+                        .map(|r| format!("{}:{}", r.id, r.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                Err(std::io::Error::new(std::io::ErrorKind::NotFound, hint).into())
+            }
+        }
+    } else {
+        Ok(rows[0].id)
+    }
+}
+
+fn load_character(
+    character_name: Option<&str>,
+    character_id: Option<i64>,
+) -> Result<Character, Box<dyn std::error::Error>> {
+    let (rt, db) = open_local_db()?;
+    let target_id = target_character_id(&rt, &db, character_name, character_id)?;
+    load_character_by_id(&rt, &db, target_id)
+}
+
+/// Replace one part of a section of the stored character's sheet
+/// ("equipment" "items", "spells" "usedSlots"), leaving the rest of it as
+/// the app wrote it.
+fn save_character_part(
+    character_name: Option<&str>,
+    character_id: Option<i64>,
+    section: &str,
+    key: &str,
+    value: JsonValue,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (rt, db) = open_local_db()?;
+    let target_id = target_character_id(&rt, &db, character_name, character_id)?;
+
+    let raw: Option<SurrealValue> =
+        rt.block_on(async { db.select(("character", target_id)).await })?;
+    let Some(raw) = raw else {
+        return Err(format!("Character with id {} not found", target_id).into());
+    };
+    let mut record = surreal_value_to_json(raw)?;
+    let mut sheet = record["sheet"].take();
+    if !sheet.is_object() {
+        return Err(format!("Character with id {} has no sheet", target_id).into());
+    }
+    if !sheet[section].is_object() {
+        sheet[section] = serde_json::json!({});
+    }
+    sheet[section][key] = value;
+    write_character_record(&rt, &db, target_id, sheet)
+}
+
+/// Load every character in the local database (used for party-wide rolls).
+fn load_all_characters() -> Result<Vec<Character>, Box<dyn std::error::Error>> {
+    let (rt, db) = open_local_db()?;
+    let rows = list_character_rows(&rt, &db)?;
+    if rows.is_empty() {
+        return Err("No characters found in local database".into());
+    }
+
+    rows.iter()
+        .map(|row| load_character_by_id(&rt, &db, row.id))
+        .collect()
+}
+
+/// A setting the app stores as a JSON string under `value`; the default
+/// when it was never set or can't be read.
+fn load_setting<T: DeserializeOwned + Default>(key: &str) -> T {
+    #[derive(Deserialize)]
+    struct SettingRecord {
+        value: String,
+    }
+
+    let Ok((rt, db)) = open_local_db() else {
+        return T::default();
+    };
+    let setting: Option<SettingRecord> = rt
+        .block_on(async { db.select(("setting", key)).await })
+        .ok()
+        .flatten();
+    setting
+        .and_then(|setting| serde_json::from_str(&setting.value).ok())
+        .unwrap_or_default()
+}
+
+/// The campaign's rules edition; 2014 when it was never set.
+fn load_ruleset() -> Ruleset {
+    load_setting(RULESET_SETTING_KEY)
+}
+
+/// The campaign's damage types; the standard ones when none were added.
+fn load_damage_types() -> DamageTypeRegistry {
+    load_setting(DAMAGE_TYPES_SETTING_KEY)
+}
+
+// ============================================================================
+// Import / Export
+// ============================================================================
+
+/// `setting` record present when the app encrypted the database
+const ENCRYPTION_SETTING_KEY: &str = "encryption";
+
+/// `base`, or `base (2)`, `base (3)`... for the first one `taken` rejects.
+fn numbered_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|candidate| !taken(candidate))
+        .expect("some number is free")
+}
+
+/// File name for a character's export (`Thorin Oakenshield.json`).
+fn export_file_name(name: &str) -> String {
+    let safe: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.' | '\'' | '(' | ')') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if safe.trim().is_empty() {
+        "character.json".to_string()
+    } else {
+        format!("{}.json", safe.trim())
+    }
+}
+
+/// The character sheet in a JSON document: a sheet itself, the `sheet` of a
+/// `.dndchar` file or a stored record, or the sheet mapped from a D&D Beyond
+/// export or a Foundry VTT actor. Also returns the fields of a Foundry actor
+/// that had no place on the sheet.
+fn sheet_from_json(
+    mut value: JsonValue,
+    format: ImportFormat,
+) -> Result<(JsonValue, Vec<String>), String> {
+    let mut unmapped = Vec::new();
+    match format {
+        ImportFormat::Ddb => value = ddb_to_sheet_json(&value)?,
+        ImportFormat::Foundry => {
+            let conversion = foundry_to_sheet_json(&value)?;
+            value = conversion.json;
+            unmapped = conversion.unmapped;
+        }
+        ImportFormat::Sheet => {
+            if value.get("character").is_none() {
+                if let Some(sheet) = value.get_mut("sheet") {
+                    value = sheet.take();
+                }
+            }
+            check_character_sheet(&value)
+                .map_err(|report| format!("Not a character sheet:\n{}", report))?;
+        }
+    }
+    // Check the fields the CLI and the app need, but keep everything else.
+    serde_json::from_value::<Character>(value.clone())
+        .map_err(|e| format!("Not a character sheet: {}", e))?;
+    Ok((value, unmapped))
+}
+
+/// Print the fields a Foundry VTT conversion left out.
+fn report_unmapped(name: &str, unmapped: &[String]) {
+    if unmapped.is_empty() {
+        return;
+    }
+    println!("  {} {}:", "Not converted for".yellow(), name);
+    for field in unmapped {
+        println!("    - {}", field);
+    }
+}
+
+/// JSON files to import: `path` itself, or the `.json`/`.dndchar` files in
+/// the directory `path`, by name.
+fn import_files(path: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.eq_ignore_ascii_case("json") || e.eq_ignore_ascii_case("dndchar"))
+                    .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn is_encrypted(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let setting: Option<SurrealValue> =
+        rt.block_on(async { db.select(("setting", ENCRYPTION_SETTING_KEY)).await })?;
+    Ok(setting.is_some())
+}
+
+/// Store `sheet` as character `sid`, in the record layout the app uses.
+fn write_character_record(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    sid: i64,
+    sheet: JsonValue,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let info = &sheet["character"];
+    let doc = serde_json::json!({
+        "sid": sid,
+        "legacy_sqlite_id": JsonValue::Null,
+        "name": info["name"],
+        "class": info["class"],
+        "race": info["race"],
+        "level": info["level"],
+        "sheet": sheet,
+    });
+    let _: Option<SurrealValue> =
+        rt.block_on(async { db.upsert(("character", sid)).content(doc).await })?;
+    Ok(())
+}
+
+fn next_character_id(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let mut response = rt.block_on(async {
+        db.query("SELECT VALUE sid FROM character ORDER BY sid DESC LIMIT 1")
+            .await
+    })?;
+    let rows: Vec<i64> = response.take(0)?;
+    Ok(rows.first().map(|sid| sid + 1).unwrap_or(1))
+}
+
+/// Import one file; returns the name it was stored under and the fields that
+/// weren't imported.
+fn import_file(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    file: &Path,
+    format: ImportFormat,
+    on_collision: OnCollision,
+) -> Result<(String, Vec<String>), Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(file)?;
+    let (mut sheet, unmapped) = sheet_from_json(serde_json::from_str(&text)?, format)?;
+    let name = sheet["character"]["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let rows = list_character_rows(rt, db)?;
+    let existing = rows.iter().find(|r| r.name.eq_ignore_ascii_case(&name));
+    let (sid, stored_name) = match (existing, on_collision) {
+        (None, _) => (next_character_id(rt, db)?, name),
+        (Some(row), OnCollision::Overwrite) => (row.id, name),
+        (Some(_), OnCollision::Rename) => {
+            let renamed = numbered_name(&name, |candidate| {
+                rows.iter().any(|r| r.name.eq_ignore_ascii_case(candidate))
+            });
+            sheet["character"]["name"] = JsonValue::String(renamed.clone());
+            (next_character_id(rt, db)?, renamed)
+        }
+        (Some(row), OnCollision::Fail) => {
+            return Err(format!(
+                "'{}' already exists (id {}); use --overwrite or --rename",
+                name, row.id
+            )
+            .into());
+        }
+    };
+
+    write_character_record(rt, db, sid, sheet)?;
+    Ok((stored_name, unmapped))
+}
+
+fn run_import(path: &Path, format: ImportFormat, on_collision: OnCollision) {
+    let fail = |e: Box<dyn std::error::Error>| -> ! {
+        eprintln!("{} Failed to import: {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    };
+    let files = import_files(path).unwrap_or_else(|e| fail(e));
+    if files.is_empty() {
+        fail(format!("No .json or .dndchar files in {}", path.display()).into());
+    }
+    let (rt, db) = open_local_db().unwrap_or_else(|e| fail(e));
+    if is_encrypted(&rt, &db).unwrap_or_else(|e| fail(e)) {
+        fail("The database is encrypted; import characters in the 3D app instead".into());
+    }
+
+    let mut failed = 0;
+    for file in &files {
+        match import_file(&rt, &db, file, format, on_collision) {
+            Ok((name, unmapped)) => {
+                println!("{} {} ({})", "Imported".green(), name, file.display());
+                report_unmapped(&name, &unmapped);
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {}", "Skipped".yellow(), file.display(), e);
+            }
+        }
+    }
+    println!(
+        "\n{} of {} character(s) imported",
+        files.len() - failed,
+        files.len()
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Stored sheet (as JSON) and name of every selected character.
+fn export_sheets(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    rows: &[ListRow],
+) -> Result<Vec<(String, JsonValue)>, Box<dyn std::error::Error>> {
+    let mut sheets = Vec::with_capacity(rows.len());
+    for row in rows {
+        let raw: Option<SurrealValue> =
+            rt.block_on(async { db.select(("character", row.id)).await })?;
+        let Some(raw) = raw else {
+            continue;
+        };
+        let record = surreal_value_to_json(raw)?;
+        if record.get("sealed_sheet").is_some_and(|v| !v.is_null()) {
+            eprintln!(
+                "{} '{}' is encrypted; export it from the 3D app",
+                "Skipped".yellow(),
+                row.name
+            );
+            continue;
+        }
+        sheets.push((row.name.clone(), record["sheet"].clone()));
+    }
+    Ok(sheets)
+}
+
+/// Export the selected character (`Some`) or every character (`None`).
+fn run_export(
+    path: &Path,
+    selection: Option<(Option<&str>, Option<i64>)>,
+    format: ExportFormat,
+    on_collision: OnCollision,
+) {
+    let fail = |e: Box<dyn std::error::Error>| -> ! {
+        eprintln!("{} Failed to export: {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    };
+    let (rt, db) = open_local_db().unwrap_or_else(|e| fail(e));
+    let mut rows = list_character_rows(&rt, &db).unwrap_or_else(|e| fail(e));
+    if rows.is_empty() {
+        fail("No characters found in local database".into());
+    }
+
+    // One character to a file, or into a directory when `path` is one
+    let into_dir = selection.is_none() || path.is_dir();
+    if let Some((name, id)) = selection {
+        let selected = match (id, name) {
+            (Some(id), _) => rows.iter().position(|r| r.id == id),
+            (None, Some(name)) => rows.iter().position(|r| r.name.eq_ignore_ascii_case(name)),
+            (None, None) => Some(0),
+        };
+        let Some(selected) = selected else {
+            fail("Character not found; check --character / --character-id".into());
+        };
+        rows = vec![rows.swap_remove(selected)];
+    }
+    if into_dir {
+        std::fs::create_dir_all(path).unwrap_or_else(|e| fail(e.into()));
+    }
+
+    let sheets = export_sheets(&rt, &db, &rows).unwrap_or_else(|e| fail(e));
+    let mut failed = rows.len() - sheets.len();
+    for (name, sheet) in &sheets {
+        let mut file = if into_dir {
+            path.join(export_file_name(name))
+        } else {
+            path.to_path_buf()
+        };
+        if file.exists() {
+            match on_collision {
+                OnCollision::Overwrite => {}
+                OnCollision::Rename => {
+                    let stem = file
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let extension = file
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "json".to_string());
+                    let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+                    let renamed = numbered_name(&stem, |candidate| {
+                        dir.join(format!("{}.{}", candidate, extension)).exists()
+                    });
+                    file = dir.join(format!("{}.{}", renamed, extension));
+                }
+                OnCollision::Fail => {
+                    failed += 1;
+                    eprintln!(
+                        "{} {} already exists; use --overwrite or --rename",
+                        "Skipped".yellow(),
+                        file.display()
+                    );
+                    continue;
+                }
+            }
+        }
+
+        let (document, unmapped) = match format {
+            ExportFormat::Sheet => (sheet.clone(), Vec::new()),
+            ExportFormat::Foundry => match sheet_json_to_foundry(sheet) {
+                Ok(conversion) => (conversion.json, conversion.unmapped),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("{} {}: {}", "Skipped".yellow(), name, e);
+                    continue;
+                }
+            },
+        };
+        let written = serde_json::to_string_pretty(&document)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&file, json).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => {
+                println!("{} {} ({})", "Exported".green(), name, file.display());
+                report_unmapped(name, &unmapped);
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {}", "Skipped".yellow(), name, e);
+            }
+        }
+    }
+    println!(
+        "\n{} of {} character(s) exported",
+        rows.len() - failed,
+        rows.len()
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
 fn get_skill_by_name<'a>(
     skills: &'a HashMap<String, Skill>,
     name: &str,
@@ -954,6 +2189,159 @@ fn get_skill_by_name<'a>(
     }
 }
 
+/// Compute a skill modifier using a non-default ability: the chosen ability's
+/// modifier plus proficiency (doubled with expertise).
+///
+/// Returns the ability abbreviation and the modifier, or `None` if the ability
+/// name is not recognized.
+fn skill_modifier_with_ability(
+    character: &Character,
+    skill: &Skill,
+    ability: &str,
+) -> Option<(&'static str, i32)> {
+    let mods = &character.modifiers;
+    let (abbr, ability_mod) = match ability.to_lowercase().as_str() {
+        "str" | "strength" => ("STR", mods.strength),
+        "dex" | "dexterity" => ("DEX", mods.dexterity),
+        "con" | "constitution" => ("CON", mods.constitution),
+        "int" | "intelligence" => ("INT", mods.intelligence),
+        "wis" | "wisdom" => ("WIS", mods.wisdom),
+        "cha" | "charisma" => ("CHA", mods.charisma),
+        _ => return None,
+    };
+
+    let proficiency = if skill.expertise.unwrap_or(false) {
+        character.proficiency_bonus * 2
+    } else if skill.proficient {
+        character.proficiency_bonus
+    } else {
+        0
+    };
+
+    Some((abbr, ability_mod + proficiency))
+}
+
+/// Combined tool/skill synergy for a skill: (advantage, best flat bonus, tool names).
+fn tool_synergy_for_skill(character: &Character, skill: &str) -> (bool, i32, Vec<String>) {
+    let normalize = |s: &str| s.to_lowercase().replace([' ', '_'], "");
+    let skill_key = normalize(skill);
+
+    let mut advantage = false;
+    let mut bonus = 0;
+    let mut tools = Vec::new();
+    for tool in &character.tool_proficiencies {
+        if !tool.skills.iter().any(|s| normalize(s) == skill_key) {
+            continue;
+        }
+        match tool.benefit {
+            ToolSynergyBenefit::Advantage => advantage = true,
+            ToolSynergyBenefit::Bonus(b) => bonus = bonus.max(b),
+        }
+        tools.push(tool.name.clone());
+    }
+    (advantage, bonus, tools)
+}
+
+/// Passive Perception: 10 + Perception (the Wisdom modifier without a skill entry).
+fn passive_perception(character: &Character) -> i32 {
+    10 + character
+        .skills
+        .get("perception")
+        .map(|s| s.modifier)
+        .unwrap_or(character.modifiers.wisdom)
+}
+
+/// Stealth modifier (the Dexterity modifier without a skill entry).
+fn stealth_modifier(character: &Character) -> i32 {
+    character
+        .skills
+        .get("stealth")
+        .map(|s| s.modifier)
+        .unwrap_or(character.modifiers.dexterity)
+}
+
+/// A group check succeeds when at least half of the group succeeds.
+fn group_check_succeeds(successes: usize, group_size: usize) -> bool {
+    group_size > 0 && successes * 2 >= group_size
+}
+
+fn run_travel(vs: Option<i32>) {
+    let party = match load_all_characters() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{} Failed to load party: {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("{}", "  TRAVEL MODE".bold().white());
+    println!("{}", "═══════════════════════════════════════".cyan());
+
+    println!("\n{}", "Passive Perception:".bold().white());
+    let mut line: Vec<&Character> = party.iter().collect();
+    line.sort_by_key(|c| std::cmp::Reverse(passive_perception(c)));
+    for character in line {
+        println!(
+            "  {:>3}  {}",
+            passive_perception(character),
+            character.character.name
+        );
+    }
+
+    // The slowest member sets the group's pace (unknown speeds are ignored)
+    println!("\n{}", "Group Stealth:".bold().white());
+    let slowest = party
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.combat.speed > 0)
+        .min_by_key(|(_, c)| c.combat.speed)
+        .map(|(i, _)| i);
+    for (index, character) in party.iter().enumerate() {
+        let line = format!(
+            "  {:>+3}  {} ({} ft)",
+            stealth_modifier(character),
+            character.character.name,
+            character.combat.speed
+        );
+        if slowest == Some(index) {
+            println!("{}  {}", line.yellow().bold(), "← slowest".yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    if let Some(vs) = vs {
+        println!(
+            "\n{} vs passive Perception {}",
+            "Group Stealth Roll".bold().white(),
+            vs
+        );
+        let mut successes = 0;
+        for character in &party {
+            let die = RollEngine::new().roll_d20();
+            let total = die + stealth_modifier(character);
+            // Passive Perception works like a DC: meeting it is enough.
+            let total_str = if total >= vs {
+                successes += 1;
+                total.to_string().green()
+            } else {
+                total.to_string().red()
+            };
+            println!("  {}: [{}] = {}", character.character.name, die, total_str);
+        }
+
+        let verdict = if group_check_succeeds(successes, party.len()) {
+            "The party slips by unnoticed".green().bold()
+        } else {
+            "The party is spotted".red().bold()
+        };
+        println!("\n{} ({}/{} succeeded)", verdict, successes, party.len());
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
 fn display_stats(character: &Character) {
     let info = &character.character;
     println!("\n{}", "═══════════════════════════════════════".cyan());
@@ -1030,11 +2418,23 @@ fn display_stats(character: &Character) {
         "Proficiency Bonus:".bold(),
         character.proficiency_bonus
     );
+    if character.combat.extra_attacks > 0 {
+        println!(
+            "  {} {} per Attack action",
+            "Attacks:".bold(),
+            1 + character.combat.extra_attacks
+        );
+    }
 
     if let Some(hp) = &character.combat.hit_points {
         println!("  {} {}/{}", "HP:".bold(), hp.current, hp.maximum);
     }
 
+    if !character.conditions.is_empty() {
+        println!("\n{}", "CONDITIONS".bold().yellow());
+        println!("  {}", character.conditions.names().join(", "));
+    }
+
     if let Some(equipment) = &character.equipment {
         println!("\n{}", "WEAPONS".bold().yellow());
         for weapon in &equipment.weapons {
@@ -1048,6 +2448,57 @@ fn display_stats(character: &Character) {
         }
     }
 
+    if let Some(equipment) = character
+        .equipment
+        .as_ref()
+        .filter(|e| !e.items.is_empty() || e.currency.total_copper() > 0)
+    {
+        println!("\n{}", "INVENTORY".bold().yellow());
+        for item in &equipment.items {
+            let mut tags = Vec::new();
+            if item.equipped {
+                tags.push("equipped");
+            }
+            if item.attuned {
+                tags.push("attuned");
+            } else if item.requires_attunement {
+                tags.push("needs attunement");
+            }
+            let bonuses = format_item_bonuses(&item.bonuses);
+            if !bonuses.is_empty() {
+                tags.push(&bonuses);
+            }
+            if tags.is_empty() {
+                println!("  {}", item);
+            } else {
+                println!("  {} {}", item, format!("({})", tags.join(", ")).dimmed());
+            }
+        }
+        let weight = inventory_weight(&equipment.items);
+        let strength = character.attributes.strength;
+        println!(
+            "  {} {} / {} lb, {} gp",
+            "Carrying:".bold(),
+            weight,
+            carrying_capacity(strength),
+            inventory_value(&equipment.items)
+        );
+        if let Some(warning) = Encumbrance::of(weight, strength).warning() {
+            println!("  {}", warning.red());
+        }
+        let slots = equipment
+            .attunement_slots
+            .map_or(DEFAULT_ATTUNEMENT_SLOTS, |slots| slots as usize);
+        let attuned = attuned_count(&equipment.items);
+        if attuned > 0 || equipment.attunement_slots.is_some() {
+            println!("  {} {} / {}", "Attuned:".bold(), attuned, slots);
+        }
+        if let Some(warning) = attunement_warning(&equipment.items, slots) {
+            println!("  {}", warning.red());
+        }
+        println!("  {} {}", "Coins:".bold(), equipment.currency.describe());
+    }
+
     println!("{}", "═══════════════════════════════════════".cyan());
 }
 
@@ -1057,21 +2508,75 @@ fn display_stats(character: &Character) {
 
 const DATABASE_FOLDER: &str = "characters.surrealdb";
 const APP_DATA_FOLDER: &str = "DnDGameRolls";
+const PROFILES_FILE: &str = "profiles.json";
+const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Profile requested with `--profile`
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Database profile entry in `profiles.json` (written by the 3D app)
+#[derive(Debug, Deserialize)]
+struct ProfileEntry {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfilesFile {
+    active: String,
+    profiles: Vec<ProfileEntry>,
+}
+
+/// Datastore folder (relative to the app data dir) for the requested profile,
+/// or the active one when none is requested.
+fn profile_datastore_folder(
+    profiles: Option<&ProfilesFile>,
+    requested: Option<&str>,
+) -> Result<String, String> {
+    let id = match (requested, profiles) {
+        (Some(wanted), profiles) => {
+            let found = profiles.and_then(|p| {
+                p.profiles
+                    .iter()
+                    .find(|e| e.id == wanted || e.name.eq_ignore_ascii_case(wanted))
+            });
+            match found {
+                Some(entry) => entry.id.clone(),
+                None if wanted == DEFAULT_PROFILE_ID => DEFAULT_PROFILE_ID.to_string(),
+                None => return Err(format!("Unknown profile '{}'", wanted)),
+            }
+        }
+        (None, Some(p)) => p.active.clone(),
+        (None, None) => DEFAULT_PROFILE_ID.to_string(),
+    };
+
+    if id == DEFAULT_PROFILE_ID {
+        Ok(DATABASE_FOLDER.to_string())
+    } else {
+        Ok(format!("profiles/{}.surrealdb", id))
+    }
+}
 
 fn get_surreal_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let data_dir = get_app_data_dir()?;
+    let profiles = std::fs::read_to_string(data_dir.join(PROFILES_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str::<ProfilesFile>(&text).ok());
+    let folder = profile_datastore_folder(profiles.as_ref(), PROFILE.get().map(String::as_str))?;
+    Ok(data_dir.join(folder))
+}
+
+fn get_app_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     #[cfg(target_os = "windows")]
     {
         if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
-            return Ok(PathBuf::from(local_app_data)
-                .join(APP_DATA_FOLDER)
-                .join(DATABASE_FOLDER));
+            return Ok(PathBuf::from(local_app_data).join(APP_DATA_FOLDER));
         }
         if let Ok(user_profile) = std::env::var("USERPROFILE") {
             return Ok(PathBuf::from(user_profile)
                 .join("AppData")
                 .join("Local")
-                .join(APP_DATA_FOLDER)
-                .join(DATABASE_FOLDER));
+                .join(APP_DATA_FOLDER));
         }
     }
 
@@ -1081,24 +2586,20 @@ fn get_surreal_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
             return Ok(PathBuf::from(home)
                 .join("Library")
                 .join("Application Support")
-                .join(APP_DATA_FOLDER)
-                .join(DATABASE_FOLDER));
+                .join(APP_DATA_FOLDER));
         }
     }
 
     #[cfg(target_os = "linux")]
     {
         if let Ok(data_home) = std::env::var("XDG_DATA_HOME") {
-            return Ok(PathBuf::from(data_home)
-                .join(APP_DATA_FOLDER)
-                .join(DATABASE_FOLDER));
+            return Ok(PathBuf::from(data_home).join(APP_DATA_FOLDER));
         }
         if let Ok(home) = std::env::var("HOME") {
             return Ok(PathBuf::from(home)
                 .join(".local")
                 .join("share")
-                .join(APP_DATA_FOLDER)
-                .join(DATABASE_FOLDER));
+                .join(APP_DATA_FOLDER));
         }
     }
 
@@ -1114,56 +2615,290 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_roll_d20_in_range() {
-        for _ in 0..100 {
-            let roll = roll_d20();
-            assert!(roll >= 1 && roll <= 20, "Roll {} out of range", roll);
-        }
+    fn test_weapon_attack() {
+        let mut character = test_character();
+        character.equipment = serde_json::from_value(serde_json::json!({
+            "weapons": [
+                { "name": "Longsword", "damage": "1d8", "damageType": "slashing",
+                  "properties": ["Versatile (1d10)"] },
+                { "name": "Light Crossbow", "damage": "1d8", "damageType": "piercing",
+                  "properties": ["Ammunition (range 80/320)", "Loading", "Two-Handed"] }
+            ],
+            "items": ["Crossbow Bolts (2)"]
+        }))
+        .unwrap();
+        let equipment = character.equipment.as_ref().unwrap();
+
+        let longsword = &equipment.weapons[0];
+        let attack = weapon_attack(&character, longsword, AttackMode::Normal).unwrap();
+        assert_eq!((attack.ability, attack.attack_bonus), ("strength", 7));
+        assert_eq!(attack.damage, "1d8+4");
+        assert_eq!(attack.versatile_damage.as_deref(), Some("1d10+4"));
+        assert!(weapon_attack(&character, longsword, AttackMode::Thrown).is_err());
+
+        let crossbow = &equipment.weapons[1];
+        let attack = weapon_attack(&character, crossbow, AttackMode::Normal).unwrap();
+        assert_eq!((attack.ability, attack.attack_bonus), ("dexterity", 4));
+        assert!(attack.uses_ammunition);
+
+        let mut items = equipment.items.clone();
+        assert_eq!(
+            spend_ammunition(&mut items, &crossbow.name).describe(),
+            "Crossbow Bolts left: 1"
+        );
+        assert_eq!(items, vec![InventoryItem::new("Crossbow Bolts")]);
     }
 
     #[test]
-    fn test_advantage_disadvantage_cancel() {
-        for _ in 0..10 {
-            let (_, dropped) = roll_with_advantage_disadvantage(true, true);
-            assert!(dropped.is_none(), "Should cancel out with no dropped roll");
-        }
+    fn test_off_hand_attack() {
+        let mut character = test_character();
+        character.equipment = serde_json::from_value(serde_json::json!({
+            "weapons": [
+                { "name": "Handaxe", "damage": "1d6", "damageType": "slashing",
+                  "properties": ["Light", "Thrown (range 20/60)"] },
+                { "name": "Battleaxe", "damage": "1d8", "damageType": "slashing",
+                  "properties": ["Versatile (1d10)"] }
+            ]
+        }))
+        .unwrap();
+        let equipment = character.equipment.take().unwrap();
+        let (handaxe, battleaxe) = (&equipment.weapons[0], &equipment.weapons[1]);
+
+        let attack = off_hand_attack(&character, handaxe, handaxe).unwrap();
+        assert_eq!((attack.attack_bonus, attack.damage.as_str()), (7, "1d6"));
+        assert_eq!(
+            off_hand_attack(&character, battleaxe, handaxe).unwrap_err(),
+            "Battleaxe isn't a light weapon"
+        );
+
+        character.features = serde_json::from_value(serde_json::json!([
+            { "name": "Dual Wielder" },
+            { "name": "Fighting Style: Two-Weapon Fighting" }
+        ]))
+        .unwrap();
+        let attack = off_hand_attack(&character, battleaxe, handaxe).unwrap();
+        assert_eq!(attack.damage, "1d6+4");
     }
 
     #[test]
-    fn test_normal_roll_no_dropped() {
-        for _ in 0..10 {
-            let (_, dropped) = roll_with_advantage_disadvantage(false, false);
-            assert!(dropped.is_none(), "Normal roll should have no dropped roll");
-        }
+    fn test_parse_dice_arg() {
+        let dice = |s: &str| parse_dice_arg(s).unwrap().dice();
+        assert_eq!(dice("d20"), vec![DiceType::D20]);
+        assert_eq!(dice("2d6"), vec![DiceType::D6, DiceType::D6]);
+        assert_eq!(dice("1d8"), vec![DiceType::D8]);
+        assert_eq!(dice("D20"), vec![DiceType::D20]);
+        assert_eq!(dice("2d100"), vec![DiceType::Custom(100); 2]);
+        assert_eq!(dice("d3"), vec![DiceType::Custom(3)]);
+        assert_eq!(dice("d%"), vec![DiceType::Custom(100)]);
+        assert_eq!(dice("2coin"), vec![DiceType::Custom(2); 2]);
+        assert_eq!(DiceType::Custom(2).name(), "Coin");
+        assert_eq!(coin_side(1), "Tails");
+        assert_eq!(dice("4dF"), vec![DiceType::Fudge; 4]);
+        assert_eq!(DiceType::Fudge.face_value(1), -1);
+        assert_eq!(
+            dice("2d6+1d8+3"),
+            vec![DiceType::D6, DiceType::D6, DiceType::D8]
+        );
+        assert!(parse_dice_arg("invalid").is_err());
+        assert!(parse_dice_arg("d1").is_err());
+        assert!(parse_dice_arg("5").is_err());
+    }
+
+    fn test_character() -> Character {
+        serde_json::from_value(serde_json::json!({
+            "character": { "name": "Thorin", "class": "Fighter", "race": "Dwarf", "level": 5 },
+            "attributes": {
+                "strength": 18, "dexterity": 12, "constitution": 16,
+                "intelligence": 10, "wisdom": 11, "charisma": 8
+            },
+            "modifiers": {
+                "strength": 4, "dexterity": 1, "constitution": 3,
+                "intelligence": 0, "wisdom": 0, "charisma": -1
+            },
+            "combat": { "armorClass": 18, "initiative": 1 },
+            "proficiencyBonus": 3,
+            "savingThrows": {},
+            "skills": {
+                "intimidation": { "proficient": true, "modifier": 2 },
+                "athletics": { "proficient": true, "expertise": true, "modifier": 10 }
+            },
+            "toolProficiencies": [
+                { "name": "Smith's Tools", "skills": ["athletics"], "benefit": { "bonus": 2 } },
+                { "name": "Thieves' Tools", "skills": ["sleightOfHand"] }
+            ]
+        }))
+        .unwrap()
     }
 
     #[test]
-    fn test_advantage_has_dropped_roll() {
-        for _ in 0..10 {
-            let (used, dropped) = roll_with_advantage_disadvantage(true, false);
-            assert!(dropped.is_some(), "Advantage should have dropped roll");
-            let dropped_val = dropped.unwrap();
-            assert!(used >= dropped_val, "Advantage should use higher roll");
-        }
+    fn test_damage_riders_and_smite_slots() {
+        let mut sheet = serde_json::to_value(test_character()).unwrap();
+        sheet["combat"]["damageRiders"] = serde_json::json!([{ "kind": "divineSmite" }]);
+        sheet["spells"] = serde_json::json!({
+            "spellSlots": { "1": 1, "2": 1 },
+            "usedSlots": { "1": 1 },
+            "knownSpells": ["Bless"]
+        });
+        let character: Character = serde_json::from_value(sheet).unwrap();
+        assert_eq!(
+            character.combat.damage_riders,
+            vec![DamageRider::DivineSmite]
+        );
+
+        let mut spells = character.spells.unwrap();
+        assert_eq!(spells.known_spell("bless"), Some("Bless"));
+        assert_eq!(spells.lowest_slot_remaining(), Some(2));
+        assert_eq!(spells.slot_choices(), vec![(2, 1)]);
+        let smite = DamageRider::DivineSmite;
+        assert_eq!(
+            spells.spend_rider_slot(&DamageRider::HuntersMark, None),
+            Ok(0)
+        );
+        assert_eq!(
+            spells.spend_rider_slot(&smite, Some(1)),
+            Err("No level 1 spell slots left for Divine Smite".to_string())
+        );
+        assert_eq!(spells.spend_rider_slot(&smite, None), Ok(2));
+        assert_eq!(
+            spells.spend_rider_slot(&smite, Some(2)),
+            Err("No spell slots left for Divine Smite".to_string())
+        );
     }
 
     #[test]
-    fn test_disadvantage_has_dropped_roll() {
-        for _ in 0..10 {
-            let (used, dropped) = roll_with_advantage_disadvantage(false, true);
-            assert!(dropped.is_some(), "Disadvantage should have dropped roll");
-            let dropped_val = dropped.unwrap();
-            assert!(used <= dropped_val, "Disadvantage should use lower roll");
-        }
+    fn test_skill_modifier_with_ability() {
+        let character = test_character();
+        let intimidation = &character.skills["intimidation"];
+        assert_eq!(
+            skill_modifier_with_ability(&character, intimidation, "str"),
+            Some(("STR", 7))
+        );
+
+        let athletics = &character.skills["athletics"];
+        assert_eq!(
+            skill_modifier_with_ability(&character, athletics, "con"),
+            Some(("CON", 9))
+        );
+        assert!(skill_modifier_with_ability(&character, athletics, "luck").is_none());
     }
 
     #[test]
-    fn test_parse_dice_arg() {
-        assert_eq!(parse_dice_arg("d20").unwrap(), (1, DiceType::D20));
-        assert_eq!(parse_dice_arg("2d6").unwrap(), (2, DiceType::D6));
-        assert_eq!(parse_dice_arg("1d8").unwrap(), (1, DiceType::D8));
-        assert_eq!(parse_dice_arg("D20").unwrap(), (1, DiceType::D20));
-        assert!(parse_dice_arg("invalid").is_err());
-        assert!(parse_dice_arg("2d100").is_err());
+    fn test_tool_synergy_for_skill() {
+        let character = test_character();
+        assert_eq!(
+            tool_synergy_for_skill(&character, "Sleight of Hand"),
+            (true, 0, vec!["Thieves' Tools".to_string()])
+        );
+        assert_eq!(
+            tool_synergy_for_skill(&character, "Athletics"),
+            (false, 2, vec!["Smith's Tools".to_string()])
+        );
+        assert_eq!(
+            tool_synergy_for_skill(&character, "Stealth"),
+            (false, 0, Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_travel_falls_back_to_ability_modifiers() {
+        let character = test_character();
+        assert_eq!(passive_perception(&character), 10);
+        assert_eq!(stealth_modifier(&character), 1);
+        assert_eq!(character.combat.speed, 0);
+    }
+
+    #[test]
+    fn test_group_check_succeeds() {
+        assert!(group_check_succeeds(2, 4));
+        assert!(!group_check_succeeds(1, 3));
+        assert!(group_check_succeeds(2, 3));
+        assert!(!group_check_succeeds(0, 0));
+    }
+
+    #[test]
+    fn test_numbered_name() {
+        let taken = ["Thorin", "Thorin (2)"];
+        assert_eq!(numbered_name("Elara", |n| taken.contains(&n)), "Elara");
+        assert_eq!(
+            numbered_name("Thorin", |n| taken.contains(&n)),
+            "Thorin (3)"
+        );
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(
+            export_file_name("Thorin Oakenshield"),
+            "Thorin Oakenshield.json"
+        );
+        assert_eq!(export_file_name("Bob/../../etc"), "Bob_.._.._etc.json");
+        assert_eq!(export_file_name("  "), "character.json");
+    }
+
+    #[test]
+    fn test_sheet_from_json_accepts_character_files() {
+        let sheet = serde_json::to_value(test_character()).unwrap();
+        assert_eq!(
+            sheet_from_json(sheet.clone(), ImportFormat::Sheet).unwrap(),
+            (sheet.clone(), Vec::new())
+        );
+
+        let file = serde_json::json!({ "version": 1, "sheet": sheet.clone() });
+        assert_eq!(sheet_from_json(file, ImportFormat::Sheet).unwrap().0, sheet);
+
+        assert!(
+            sheet_from_json(serde_json::json!({ "name": "Thorin" }), ImportFormat::Sheet).is_err()
+        );
+        let mut bad = sheet.clone();
+        bad["proficiencyBonus"] = serde_json::json!("+3");
+        assert_eq!(
+            sheet_from_json(bad, ImportFormat::Sheet).unwrap_err(),
+            "Not a character sheet:\n- proficiencyBonus: expected a whole number of at least 0, found text \"+3\" (e.g. 3)"
+        );
+        // A sheet isn't a D&D Beyond export
+        assert!(sheet_from_json(sheet, ImportFormat::Ddb).is_err());
+    }
+
+    #[test]
+    fn test_sheet_from_json_reads_foundry_actors() {
+        let sheet = serde_json::to_value(test_character()).unwrap();
+        let mut actor = sheet_json_to_foundry(&sheet).unwrap().json;
+        actor["effects"] = serde_json::json!([{ "name": "Bless" }]);
+
+        let (imported, unmapped) = sheet_from_json(actor, ImportFormat::Foundry).unwrap();
+        assert_eq!(imported["character"]["name"], sheet["character"]["name"]);
+        assert_eq!(imported["attributes"], sheet["attributes"]);
+        assert_eq!(unmapped, vec!["effects (1 active effects)"]);
+
+        // A sheet isn't a Foundry actor
+        assert!(sheet_from_json(sheet, ImportFormat::Foundry).is_err());
+    }
+
+    #[test]
+    fn test_profile_datastore_folder() {
+        let profiles: ProfilesFile = serde_json::from_str(
+            r#"{"active":"home-campaign","profiles":[
+                {"id":"default","name":"Default"},
+                {"id":"home-campaign","name":"Home campaign"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            profile_datastore_folder(None, None).unwrap(),
+            "characters.surrealdb"
+        );
+        assert_eq!(
+            profile_datastore_folder(Some(&profiles), None).unwrap(),
+            "profiles/home-campaign.surrealdb"
+        );
+        assert_eq!(
+            profile_datastore_folder(Some(&profiles), Some("default")).unwrap(),
+            "characters.surrealdb"
+        );
+        assert_eq!(
+            profile_datastore_folder(Some(&profiles), Some("HOME CAMPAIGN")).unwrap(),
+            "profiles/home-campaign.surrealdb"
+        );
+        assert!(profile_datastore_folder(Some(&profiles), Some("work")).is_err());
     }
 }
@@ -0,0 +1,32 @@
+//! Ability names
+//!
+//! The six abilities go by their full name ("dexterity") in sheets and by a
+//! three-letter abbreviation ("DEX") on roll labels; these convert between
+//! the two.
+
+/// Resolve a full ability name or 3-letter abbreviation (e.g. "str") to its
+/// canonical lowercase name.
+pub fn normalize_ability_name(name: &str) -> Option<&'static str> {
+    match name.trim().to_lowercase().as_str() {
+        "str" | "strength" => Some("strength"),
+        "dex" | "dexterity" => Some("dexterity"),
+        "con" | "constitution" => Some("constitution"),
+        "int" | "intelligence" => Some("intelligence"),
+        "wis" | "wisdom" => Some("wisdom"),
+        "cha" | "charisma" => Some("charisma"),
+        _ => None,
+    }
+}
+
+/// Three-letter display abbreviation for a canonical ability name
+pub fn ability_abbreviation(ability: &str) -> &'static str {
+    match normalize_ability_name(ability) {
+        Some("strength") => "STR",
+        Some("dexterity") => "DEX",
+        Some("constitution") => "CON",
+        Some("intelligence") => "INT",
+        Some("wisdom") => "WIS",
+        Some("charisma") => "CHA",
+        _ => "---",
+    }
+}
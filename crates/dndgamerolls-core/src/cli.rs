@@ -0,0 +1,801 @@
+//! Command line output
+//!
+//! What the `dndgamerolls` and `dndrolls` command lines print the same way:
+//! `--quiet` and `--no-color` ([`configure_output`]) with the [`say!`] macro
+//! for the details `--quiet` leaves out, the yes/no and spell slot
+//! questions, conditions on d20 tests, and weapon attacks: the attack roll,
+//! the weapon's damage, the damage riders taken on a hit, the weapon's
+//! mastery and the damage of a turn by type. Every roll draws from
+//! [`CliRng`], which `--seed` ([`seed_rolls`]) makes replayable. Argument
+//! parsing and the character sheets stay with each command line.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use colored::Colorize;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::abilities::ability_abbreviation;
+use crate::conditions::{ConditionEffect, Conditions, D20Test};
+use crate::damage_riders::{riders_for, DamageRider, SlotPool};
+use crate::damage_types::{damage_by_type, DamageTypeRegistry};
+use crate::dice_expression::DamageRoll;
+use crate::engine::RollEngine;
+use crate::ruleset::Ruleset;
+use crate::weapon_mastery::WeaponMastery;
+use crate::weapon_properties::{weapon_damage, AmmunitionUse, WeaponAttack};
+
+/// `--quiet`: rolls print only their total
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Apply `--quiet` and `--no-color`; colors are left out when `NO_COLOR` is
+/// set too.
+pub fn configure_output(quiet: bool, no_color: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    if no_color {
+        colored::control::set_override(false);
+    }
+}
+
+/// Whether rolls print only their total (`--quiet`)
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `--seed`: the generator the rolls draw from once seeded
+static SEEDED_RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Apply `--seed`: every roll after this draws from a generator seeded with
+/// `seed`, so the same command rolls the same dice every run.
+pub fn seed_rolls(seed: u64) {
+    *SEEDED_RNG.lock().unwrap_or_else(|e| e.into_inner()) = Some(StdRng::seed_from_u64(seed));
+}
+
+/// Random number generator of command line rolls: the `--seed` one when
+/// seeded, the thread's otherwise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CliRng;
+
+impl CliRng {
+    fn draw<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        let mut seeded = SEEDED_RNG.lock().unwrap_or_else(|e| e.into_inner());
+        match seeded.as_mut() {
+            Some(rng) => f(rng),
+            None => f(&mut rand::rng()),
+        }
+    }
+}
+
+impl RngCore for CliRng {
+    fn next_u32(&mut self) -> u32 {
+        Self::draw(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Self::draw(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Self::draw(|rng| rng.fill_bytes(dest))
+    }
+}
+
+/// A roll engine drawing from [`CliRng`]
+pub fn roll_engine() -> RollEngine<CliRng> {
+    RollEngine::with_rng(CliRng)
+}
+
+/// `println!` for the details of a roll, which `--quiet` leaves out
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::cli::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Roll a d20 test; returns the kept die and, with advantage or
+/// disadvantage, the dropped one.
+pub fn roll_d20_test(advantage: bool, disadvantage: bool) -> (i32, Option<i32>) {
+    roll_engine().roll_d20_test(advantage, disadvantage)
+}
+
+fn roll_d20() -> i32 {
+    roll_engine().roll_d20()
+}
+
+/// Ask a yes/no question on the terminal; anything but "y" or "yes" is no
+pub fn confirm(question: &str) -> bool {
+    print!("{} {} ", question.bold(), "[y/N]".dimmed());
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Ask which spell slot to spend on `what`, listing the levels with slots
+/// left; Enter takes the lowest. `None` when no slots are left or the answer
+/// isn't one of the levels.
+pub fn pick_slot(choices: &[(u32, i32)], what: &str) -> Option<u32> {
+    let (lowest, _) = *choices.first()?;
+    println!("{}", format!("Spell slot for {}:", what).bold());
+    for (level, remaining) in choices {
+        println!("  Level {} ({} left)", level, remaining);
+    }
+    print!("{} {} ", "Level?".bold(), format!("[{}]", lowest).dimmed());
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return None;
+    }
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Some(lowest);
+    }
+    match answer.parse::<u32>() {
+        Ok(level) if choices.iter().any(|(l, _)| *l == level) => Some(level),
+        _ => {
+            println!(
+                "{}",
+                format!("No level {} spell slots left", answer).yellow()
+            );
+            None
+        }
+    }
+}
+
+/// A damage type in its color; unknown types are dimmed.
+pub fn colored_damage_type(
+    damage_type: &str,
+    damage_types: &DamageTypeRegistry,
+) -> colored::ColoredString {
+    match damage_types.get(damage_type).and_then(|t| t.rgb()) {
+        Some([r, g, b]) => damage_type.truecolor(r, g, b),
+        None => damage_type.dimmed(),
+    }
+}
+
+/// Print what the character's conditions do to a roll, if anything.
+pub fn print_condition_effect(effect: &ConditionEffect) {
+    if !effect.is_empty() {
+        say!(
+            "{} {}",
+            "Conditions:".bold().white(),
+            effect.describe().yellow()
+        );
+    }
+}
+
+/// Report a d20 test the character's conditions fail without a roll, such
+/// as a paralyzed character's Dexterity save; returns whether it did, so
+/// the caller skips the roll.
+pub fn report_automatic_failure(name: &str, effect: &ConditionEffect) -> bool {
+    if !effect.fails_automatically() {
+        return false;
+    }
+    if quiet() {
+        println!("FAILURE");
+        return true;
+    }
+    println!("\n{}", "═══════════════════════════════════════".cyan());
+    println!("{} {}", "Rolling:".bold().white(), name.bold().yellow());
+    println!(
+        "{} {} ({})",
+        "Result:".bold().white(),
+        "fails automatically".bright_red().bold(),
+        effect.auto_fail.join(", ")
+    );
+    println!("{}", "═══════════════════════════════════════".cyan());
+    true
+}
+
+/// Combine --advantage/--disadvantage with what the character's conditions
+/// do to a d20 test, printing the conditions that apply; see
+/// [`ConditionEffect::edge`].
+pub fn condition_edge(
+    conditions: &Conditions,
+    test: D20Test,
+    ruleset: Ruleset,
+    advantage: bool,
+    disadvantage: bool,
+) -> (bool, bool, i32) {
+    let effect = conditions.effect_on(&test, ruleset);
+    print_condition_effect(&effect);
+    effect.edge(advantage, disadvantage)
+}
+
+/// One attack of a turn (Extra Attack, two-weapon fighting), or a lone one
+pub struct PlannedAttack {
+    /// "Attack 1", "Off Hand"
+    pub label: String,
+    /// Name of the weapon
+    pub weapon: String,
+    /// The weapon's damage type, for damage that doesn't name one
+    pub damage_type: String,
+    pub attack: WeaponAttack,
+    pub ammunition: Option<AmmunitionUse>,
+}
+
+impl PlannedAttack {
+    /// The weapon's damage type, when it has one
+    fn weapon_damage_type(&self) -> Option<&str> {
+        Some(self.damage_type.trim()).filter(|t| !t.is_empty())
+    }
+}
+
+/// The damage riders offered on the hits of a turn, and the weapon mastery
+/// of its attacks
+pub struct RiderOffer<'a> {
+    /// Character level, for the Sneak Attack dice
+    pub level: i32,
+    /// For the save Topple forces
+    pub proficiency_bonus: i32,
+    /// The character's spell slots, spent by smites
+    pub slots: &'a mut dyn SlotPool,
+    /// The character's riders and the --rider ones
+    pub riders: Vec<DamageRider>,
+    /// Added to the hits they fit without asking (--rider)
+    pub always: Vec<DamageRider>,
+    /// Spell slot level for smites (--slot)
+    pub slot: Option<u32>,
+    /// Ask about the rest, and which slots to spend (when reading from a
+    /// terminal)
+    pub ask: bool,
+    /// Dealt so far this turn
+    pub used: Vec<DamageRider>,
+    pub slots_spent: bool,
+    /// Weapon mastery properties apply (2024 rules, unless --no-mastery)
+    pub mastery: bool,
+    /// Save modifier of the target, for Topple (--target-save)
+    pub target_save: i32,
+    /// A Vex hit gives the next attack of the turn advantage
+    pub vexed: bool,
+    /// Cleave was used this turn
+    pub cleaved: bool,
+}
+
+impl RiderOffer<'_> {
+    /// Offer the riders that fit a hit with `attack` and roll the ones
+    /// taken; returns the damage they dealt and its types.
+    fn roll(
+        &mut self,
+        attack: &WeaponAttack,
+        weapon_damage_type: Option<&str>,
+        critical: bool,
+        explode_cap: u32,
+        damage_types: &DamageTypeRegistry,
+    ) -> Vec<(i32, Option<String>)> {
+        let slot_choices = self.slots.slot_choices();
+        let smite_slot = self
+            .slot
+            .or(slot_choices.first().map(|(level, _)| *level))
+            .unwrap_or(1);
+        let fitting: Vec<DamageRider> = riders_for(&self.riders, attack, &self.used)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let mut dealt = Vec::new();
+        let mut skipped = Vec::new();
+        for rider in fitting {
+            if rider.spends_slot() && slot_choices.is_empty() {
+                say!(
+                    "  {}",
+                    format!("No spell slots left for {}", rider.name()).yellow()
+                );
+                continue;
+            }
+            let offer = format!("{} ({})", rider.name(), rider.dice(self.level, smite_slot));
+            let taken = self.always.contains(&rider)
+                || (self.ask && confirm(&format!("Add {} to the hit?", offer)));
+            if !taken {
+                skipped.push(offer);
+                continue;
+            }
+
+            // Smites ask which slot to spend, listing what's left of each
+            let pick = if rider.spends_slot() && self.slot.is_none() && self.ask {
+                match pick_slot(&slot_choices, rider.name()) {
+                    Some(level) => Some(level),
+                    None => continue,
+                }
+            } else {
+                self.slot
+            };
+            let slot = match self.slots.spend_rider_slot(&rider, pick) {
+                Ok(slot) => slot,
+                Err(e) => {
+                    say!("{}", e.yellow());
+                    continue;
+                }
+            };
+            let damage = rider
+                .damage(self.level, slot, weapon_damage_type)
+                .and_then(|damage| hit_damage(damage, critical));
+            let damage = match damage {
+                Ok(damage) => damage,
+                Err(e) => {
+                    eprintln!("Warning: {}", e);
+                    continue;
+                }
+            };
+            say!("{}", format!("+ {}", rider.name()).bold().magenta());
+            let label = format!("{}:", rider.name());
+            match print_damage_roll(&damage, explode_cap, &label, damage_types) {
+                Ok(total) => dealt.push((total, damage.damage_type)),
+                Err(e) => eprintln!("Warning: {}", e),
+            }
+            if slot > 0 {
+                self.slots_spent = true;
+                say!(
+                    "  {}",
+                    format!(
+                        "Spent a level {} spell slot ({} left)",
+                        slot,
+                        self.slots.slots_remaining(slot)
+                    )
+                    .dimmed()
+                );
+            }
+            self.used.push(rider);
+        }
+        if !skipped.is_empty() && !self.ask {
+            say!(
+                "  {}",
+                format!("On a hit: {} (add with --rider)", skipped.join(", ")).dimmed()
+            );
+        }
+        dealt
+    }
+
+    /// Use the weapon's mastery after an attack roll: on a hit (anything
+    /// but a natural 1) Topple rolls the target's save, Vex gives the next
+    /// attack advantage and Cleave offers a second attack; Graze deals its
+    /// damage on a natural 1 and is noted otherwise. Returns the damage
+    /// dealt and its types.
+    fn mastery(
+        &mut self,
+        planned: &PlannedAttack,
+        dice_roll: i32,
+        explode_cap: u32,
+        damage_types: &DamageTypeRegistry,
+    ) -> Vec<(i32, Option<String>)> {
+        let attack = &planned.attack;
+        let Some(mastery) = attack.mastery.filter(|_| self.mastery) else {
+            return Vec::new();
+        };
+        let damage_type = planned.weapon_damage_type().map(str::to_string);
+        let label = format!("{}:", mastery.name()).bold().magenta();
+        let hit = dice_roll != 1;
+
+        match mastery {
+            WeaponMastery::Graze => {
+                let Some(damage) = mastery.graze_damage(attack) else {
+                    return Vec::new();
+                };
+                if hit {
+                    say!(
+                        "  {}",
+                        format!("Graze: {} damage on a miss", damage).dimmed()
+                    );
+                    return Vec::new();
+                }
+                match &damage_type {
+                    Some(t) => say!(
+                        "{} {} {}",
+                        label,
+                        damage,
+                        colored_damage_type(t, damage_types)
+                    ),
+                    None => say!("{} {}", label, damage),
+                }
+                vec![(damage, damage_type)]
+            }
+            _ if !hit => Vec::new(),
+            WeaponMastery::Nick => Vec::new(),
+            WeaponMastery::Topple => {
+                if self.ask && !confirm("Topple the target?") {
+                    return Vec::new();
+                }
+                let save = mastery
+                    .save(attack, self.proficiency_bonus, roll_d20(), self.target_save)
+                    .expect("Topple forces a save");
+                let outcome = if save.succeeded() {
+                    save.describe().normal()
+                } else {
+                    save.describe().yellow()
+                };
+                say!("{} {}", label, outcome);
+                Vec::new()
+            }
+            WeaponMastery::Vex => {
+                self.vexed = true;
+                say!("{} {}", label, mastery.description());
+                Vec::new()
+            }
+            WeaponMastery::Cleave if !self.cleaved => {
+                say!("{} {}", label, mastery.description());
+                if !self.ask || !confirm("Attack a second creature?") {
+                    return Vec::new();
+                }
+                self.cleaved = true;
+                self.cleave(planned, explode_cap, damage_types)
+            }
+            WeaponMastery::Cleave => Vec::new(),
+            _ => {
+                say!("{} {}", label, mastery.description());
+                Vec::new()
+            }
+        }
+    }
+
+    /// Cleave's second attack: the same attack roll, and the weapon's
+    /// damage without a positive ability modifier.
+    fn cleave(
+        &mut self,
+        planned: &PlannedAttack,
+        explode_cap: u32,
+        damage_types: &DamageTypeRegistry,
+    ) -> Vec<(i32, Option<String>)> {
+        let cleave = planned.attack.clone().off_hand(false);
+        let dice_roll = roll_d20();
+        say!(
+            "{} [{}] {:+} = {}",
+            "Cleave Attack:".bold().white(),
+            dice_roll,
+            cleave.attack_bonus,
+            dice_roll + cleave.attack_bonus
+        );
+        if dice_roll == 1 {
+            say!("{}", "💨 CRITICAL MISS! 💨".bright_red().bold());
+            return Vec::new();
+        }
+        let damage = weapon_damage(&cleave.damage, &planned.damage_type)
+            .and_then(|damage| hit_damage(damage, dice_roll == 20));
+        let damage = match damage {
+            Ok(damage) => damage,
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                return Vec::new();
+            }
+        };
+        match print_damage_roll(&damage, explode_cap, "Cleave Damage:", damage_types) {
+            Ok(total) => vec![(total, damage.damage_type)],
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// The damage of a hit, its dice doubled on a critical hit
+fn hit_damage(damage: DamageRoll, critical: bool) -> Result<DamageRoll, String> {
+    if critical {
+        damage.critical()
+    } else {
+        Ok(damage)
+    }
+}
+
+/// Roll one attack as its own result; with `--quiet` only its total and the
+/// damage dealt are printed.
+pub fn roll_attack(
+    planned: &PlannedAttack,
+    advantage: bool,
+    disadvantage: bool,
+    explode_cap: u32,
+    damage_types: &DamageTypeRegistry,
+    riders: &mut RiderOffer,
+) {
+    say!("\n{}", "═══════════════════════════════════════".cyan());
+    say!("{} {} Attack", "⚔️".bold(), planned.weapon.bold().yellow());
+    let (total, dealt) = print_attack_roll(
+        planned,
+        advantage,
+        disadvantage,
+        explode_cap,
+        damage_types,
+        riders,
+    );
+    if quiet() {
+        println!("{} {}", total, damage_dealt(&dealt));
+        return;
+    }
+    if let Some(ammunition) = &planned.ammunition {
+        say!("{} {}", "Ammunition:".bold().white(), ammunition.describe());
+    }
+    if dealt.len() > 1 {
+        say!("{}", "───────────────────────────────────────".cyan());
+        print_total_damage(&dealt, damage_types);
+    }
+    say!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// Roll the attacks of a turn as one grouped result, ending with the damage
+/// of the attacks that didn't miss. A turn stops at the attack that finds
+/// no ammunition left.
+pub fn roll_attack_sequence(
+    attacks: &[PlannedAttack],
+    advantage: bool,
+    disadvantage: bool,
+    explode_cap: u32,
+    damage_types: &DamageTypeRegistry,
+    riders: &mut RiderOffer,
+) {
+    say!("\n{}", "═══════════════════════════════════════".cyan());
+    say!(
+        "{} {} ({} attacks)",
+        "⚔️".bold(),
+        "Attack Action".bold().yellow(),
+        attacks.len()
+    );
+
+    let mut dealt: Vec<(i32, Option<String>)> = Vec::new();
+    for planned in attacks {
+        say!(
+            "{}",
+            format!("── {}: {} ──", planned.label, planned.weapon).cyan()
+        );
+        if let Some(ammunition @ AmmunitionUse::OutOf(_)) = &planned.ammunition {
+            say!("{}", ammunition.describe().red());
+            break;
+        }
+        let (total, damage) = print_attack_roll(
+            planned,
+            advantage,
+            disadvantage,
+            explode_cap,
+            damage_types,
+            riders,
+        );
+        if quiet() {
+            println!("{} {}", total, damage_dealt(&damage));
+        }
+        dealt.extend(damage);
+        if let Some(ammunition) = &planned.ammunition {
+            say!("{} {}", "Ammunition:".bold().white(), ammunition.describe());
+        }
+    }
+
+    say!("{}", "───────────────────────────────────────".cyan());
+    print_total_damage(&dealt, damage_types);
+    say!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// Total of the damage rolled, of any type
+pub fn damage_dealt(rolled: &[(i32, Option<String>)]) -> i32 {
+    rolled.iter().map(|(amount, _)| amount).sum()
+}
+
+/// Print the damage of a turn, with the amount of each type when there are
+/// several
+pub fn print_total_damage(rolled: &[(i32, Option<String>)], damage_types: &DamageTypeRegistry) {
+    let dealt = damage_by_type(rolled);
+    let total = damage_dealt(rolled);
+    let by_type: Vec<String> = dealt
+        .iter()
+        .filter_map(|(amount, damage_type)| {
+            damage_type.map(|t| format!("{} {}", amount, colored_damage_type(t, damage_types)))
+        })
+        .collect();
+    if dealt.len() > 1 && by_type.len() == dealt.len() {
+        say!(
+            "{} {} ({})",
+            "Total Damage:".bold().white(),
+            total.to_string().bright_white().bold(),
+            by_type.join(", ")
+        );
+    } else {
+        say!(
+            "{} {}",
+            "Total Damage:".bold().white(),
+            total.to_string().bright_white().bold()
+        );
+    }
+}
+
+/// Roll an attack and, unless it's a natural 1, its damage and the damage
+/// riders taken; returns the attack's total and each damage rolled with its
+/// type.
+pub fn print_attack_roll(
+    planned: &PlannedAttack,
+    advantage: bool,
+    disadvantage: bool,
+    explode_cap: u32,
+    damage_types: &DamageTypeRegistry,
+    riders: &mut RiderOffer,
+) -> (i32, Vec<(i32, Option<String>)>) {
+    let attack = &planned.attack;
+    // Vex from the turn's last hit
+    let vexed = std::mem::take(&mut riders.vexed);
+    if vexed {
+        say!("{}", "Vex: advantage on this attack".magenta());
+    }
+    let advantage = advantage || vexed;
+    let (dice_roll, dropped_roll) = roll_d20_test(advantage, disadvantage);
+    let total = dice_roll + attack.attack_bonus;
+
+    if let Some(dropped) = dropped_roll {
+        if advantage {
+            say!(
+                "{} {} {} (dropped {})",
+                "Attack Roll:".bold().white(),
+                format!("[{}]", dice_roll).bright_green().bold(),
+                "(advantage)".green(),
+                format!("[{}]", dropped).dimmed()
+            );
+        } else if disadvantage {
+            say!(
+                "{} {} {} (dropped {})",
+                "Attack Roll:".bold().white(),
+                format!("[{}]", dice_roll).bright_red().bold(),
+                "(disadvantage)".red(),
+                format!("[{}]", dropped).dimmed()
+            );
+        }
+    } else {
+        let dice_color = match dice_roll {
+            20 => format!("[{}]", dice_roll).bright_green().bold(),
+            1 => format!("[{}]", dice_roll).bright_red().bold(),
+            _ => format!("[{}]", dice_roll).bright_white().bold(),
+        };
+        say!("{} {}", "Attack Roll:".bold().white(), dice_color);
+    }
+
+    let modifier_str = if attack.attack_bonus >= 0 {
+        format!("+{}", attack.attack_bonus).cyan()
+    } else {
+        format!("{}", attack.attack_bonus).cyan()
+    };
+    say!(
+        "{} {} ({})",
+        "Attack Bonus:".bold().white(),
+        modifier_str,
+        ability_abbreviation(attack.ability)
+    );
+
+    let total_color = if dice_roll == 20 {
+        format!("{}", total).bright_green().bold()
+    } else if dice_roll == 1 {
+        format!("{}", total).bright_red().bold()
+    } else {
+        format!("{}", total).white().bold()
+    };
+    say!("{} {}", "Total:".bold().white(), total_color);
+
+    if dice_roll == 20 {
+        say!("{}", "🎯 CRITICAL HIT! 🎯".bright_green().bold());
+    } else if dice_roll == 1 {
+        say!("{}", "💨 CRITICAL MISS! 💨".bright_red().bold());
+    }
+
+    // A natural 1 always misses; otherwise roll damage, doubling the dice on
+    // a natural 20
+    if dice_roll == 1 {
+        let grazed = riders.mastery(planned, dice_roll, explode_cap, damage_types);
+        return (total, grazed);
+    }
+    let mut dealt = Vec::new();
+    match weapon_damage(&attack.damage, &planned.damage_type) {
+        Ok(damage) => {
+            let rolled = hit_damage(damage, dice_roll == 20).and_then(|damage| {
+                print_damage_roll(&damage, explode_cap, "Damage:", damage_types)
+                    .map(|total| (total, damage.damage_type))
+            });
+            match rolled {
+                Ok(rolled) => dealt.push(rolled),
+                Err(e) => eprintln!("Warning: {}", e),
+            }
+        }
+        Err(_) => {
+            say!(
+                "{} {} ({})",
+                "Damage:".bold().white(),
+                attack.damage.yellow(),
+                colored_damage_type(&planned.damage_type, damage_types)
+            );
+        }
+    }
+    if let Some(versatile) = &attack.versatile_damage {
+        say!(
+            "  {}",
+            format!("Two-handed: {} (use --two-handed)", versatile).dimmed()
+        );
+    }
+
+    // Sneak Attack, Divine Smite and the like; their dice double on a
+    // critical hit too
+    dealt.extend(riders.roll(
+        attack,
+        planned.weapon_damage_type(),
+        dice_roll == 20,
+        explode_cap,
+        damage_types,
+    ));
+
+    // Weapon mastery (2024 rules)
+    dealt.extend(riders.mastery(planned, dice_roll, explode_cap, damage_types));
+    (total, dealt)
+}
+
+/// Roll damage as its own result; a critical hit rolls the damage dice
+/// twice.
+pub fn roll_damage(
+    source: &str,
+    damage: DamageRoll,
+    crit: bool,
+    explode_cap: u32,
+    damage_types: &DamageTypeRegistry,
+) -> Result<(), String> {
+    let damage = hit_damage(damage, crit)?;
+
+    say!("\n{}", "═══════════════════════════════════════".cyan());
+    if crit {
+        say!(
+            "{} Damage {}",
+            source.bold().yellow(),
+            "(critical hit)".bright_green().bold()
+        );
+    } else {
+        say!("{} Damage", source.bold().yellow());
+    }
+    let total = print_damage_roll(&damage, explode_cap, "Total:", damage_types)?;
+    if quiet() {
+        println!("{}", total);
+    }
+    say!("{}", "═══════════════════════════════════════".cyan());
+    Ok(())
+}
+
+/// Roll `damage` and print its dice, expression and total after
+/// `total_label`; returns the total.
+pub fn print_damage_roll(
+    damage: &DamageRoll,
+    explode_cap: u32,
+    total_label: &str,
+    damage_types: &DamageTypeRegistry,
+) -> Result<i32, String> {
+    let expression = damage.expression.clone().with_explosion_cap(explode_cap);
+    let roll = roll_engine().roll_expression(&expression)?;
+
+    let rolls_str: Vec<String> = roll
+        .rolls
+        .iter()
+        .map(|r| {
+            let text = format!("[{}]", r.value);
+            if r.dropped {
+                text.dimmed().to_string()
+            } else {
+                text.bright_white().bold().to_string()
+            }
+        })
+        .collect();
+    say!("{} {}", "Damage Dice:".bold().white(), rolls_str.join(" "));
+    if let Some(exploded) = roll.explosion_summary() {
+        say!("  {}", exploded.dimmed());
+    }
+    if let Some(kept) = roll.keep_summary() {
+        say!("  {}", kept.dimmed());
+    }
+    say!("{} {}", "Expression:".bold().white(), expression);
+
+    let total = roll.total.max(0) as i32;
+    match &damage.damage_type {
+        Some(damage_type) => say!(
+            "{} {} {}",
+            total_label.bold().white(),
+            total.to_string().bright_white().bold(),
+            colored_damage_type(damage_type, damage_types)
+        ),
+        None => say!(
+            "{} {}",
+            total_label.bold().white(),
+            total.to_string().bright_white().bold()
+        ),
+    }
+    Ok(total)
+}
@@ -0,0 +1,440 @@
+//! Conditions
+//!
+//! The conditions a character can be under (prone, poisoned, restrained, ...)
+//! and their exhaustion level, kept on the character sheet, and what they do
+//! to the character's d20 tests: advantage, disadvantage or an automatic
+//! failure, or a penalty. Exhaustion and grappling follow the campaign's
+//! [`Ruleset`]: 2014 exhaustion gives disadvantage on ability checks from
+//! level 1 and on attack rolls and saving throws from level 3, while 2024
+//! exhaustion takes 2 per level off every d20 test and a grappled creature
+//! has disadvantage on its attacks.
+
+use serde::{Deserialize, Serialize};
+
+use crate::abilities::normalize_ability_name;
+use crate::ruleset::Ruleset;
+
+/// Highest exhaustion level; a character at this level dies
+pub const MAX_EXHAUSTION: u8 = 6;
+
+/// A condition from the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Condition {
+    Blinded,
+    Charmed,
+    Deafened,
+    Frightened,
+    Grappled,
+    Incapacitated,
+    Invisible,
+    Paralyzed,
+    Petrified,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+    Unconscious,
+}
+
+impl Condition {
+    /// Every condition, in alphabetical order
+    pub const ALL: [Condition; 14] = [
+        Condition::Blinded,
+        Condition::Charmed,
+        Condition::Deafened,
+        Condition::Frightened,
+        Condition::Grappled,
+        Condition::Incapacitated,
+        Condition::Invisible,
+        Condition::Paralyzed,
+        Condition::Petrified,
+        Condition::Poisoned,
+        Condition::Prone,
+        Condition::Restrained,
+        Condition::Stunned,
+        Condition::Unconscious,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Condition::Blinded => "Blinded",
+            Condition::Charmed => "Charmed",
+            Condition::Deafened => "Deafened",
+            Condition::Frightened => "Frightened",
+            Condition::Grappled => "Grappled",
+            Condition::Incapacitated => "Incapacitated",
+            Condition::Invisible => "Invisible",
+            Condition::Paralyzed => "Paralyzed",
+            Condition::Petrified => "Petrified",
+            Condition::Poisoned => "Poisoned",
+            Condition::Prone => "Prone",
+            Condition::Restrained => "Restrained",
+            Condition::Stunned => "Stunned",
+            Condition::Unconscious => "Unconscious",
+        }
+    }
+
+    /// Find a condition by name, ignoring case ("prone", "Poisoned").
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A d20 roll conditions can affect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum D20Test {
+    /// Ability checks, skill checks and initiative
+    AbilityCheck,
+    /// A saving throw of an ability ("dexterity", "dex", ...)
+    SavingThrow(String),
+    AttackRoll,
+}
+
+/// What a character's conditions do to one d20 test; each list holds the
+/// names of the conditions responsible
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionEffect {
+    pub advantage: Vec<String>,
+    pub disadvantage: Vec<String>,
+    /// The test fails without rolling
+    pub auto_fail: Vec<String>,
+    /// Flat penalties to the roll (2024 exhaustion), with their condition
+    pub penalties: Vec<(String, i32)>,
+}
+
+impl ConditionEffect {
+    pub fn is_empty(&self) -> bool {
+        self.advantage.is_empty()
+            && self.disadvantage.is_empty()
+            && self.auto_fail.is_empty()
+            && self.penalties.is_empty()
+    }
+
+    /// Sum of the penalties, added to the roll's modifier
+    pub fn penalty(&self) -> i32 {
+        self.penalties.iter().map(|(_, penalty)| penalty).sum()
+    }
+
+    /// Advantage with no disadvantage to cancel it
+    pub fn has_advantage(&self) -> bool {
+        !self.advantage.is_empty() && self.disadvantage.is_empty()
+    }
+
+    /// Disadvantage with no advantage to cancel it
+    pub fn has_disadvantage(&self) -> bool {
+        !self.disadvantage.is_empty() && self.advantage.is_empty()
+    }
+
+    pub fn fails_automatically(&self) -> bool {
+        !self.auto_fail.is_empty()
+    }
+
+    /// Combine the advantage and disadvantage a roll already has
+    /// (`--advantage`, `--disadvantage`) with this effect; advantage and
+    /// disadvantage from any source cancel out. The last value is the
+    /// penalty to the roll.
+    pub fn edge(&self, advantage: bool, disadvantage: bool) -> (bool, bool, i32) {
+        (
+            advantage || !self.advantage.is_empty(),
+            disadvantage || !self.disadvantage.is_empty(),
+            self.penalty(),
+        )
+    }
+
+    /// Short description for roll labels, e.g.
+    /// "Poisoned, Prone: disadvantage; Invisible: advantage; Exhaustion 2: -4"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (names, effect) in [
+            (&self.auto_fail, "fails automatically"),
+            (&self.advantage, "advantage"),
+            (&self.disadvantage, "disadvantage"),
+        ] {
+            if !names.is_empty() {
+                parts.push(format!("{}: {}", names.join(", "), effect));
+            }
+        }
+        for (name, penalty) in &self.penalties {
+            parts.push(format!("{}: {}", name, penalty));
+        }
+        parts.join("; ")
+    }
+}
+
+fn is_zero(level: &u8) -> bool {
+    *level == 0
+}
+
+/// The conditions a character is under
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conditions {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub active: Vec<Condition>,
+    /// Exhaustion level, 0 to [`MAX_EXHAUSTION`]
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub exhaustion: u8,
+}
+
+impl Conditions {
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty() && self.exhaustion == 0
+    }
+
+    pub fn has(&self, condition: Condition) -> bool {
+        self.active.contains(&condition)
+    }
+
+    /// Apply or remove a condition; returns `true` when it was applied.
+    pub fn toggle(&mut self, condition: Condition) -> bool {
+        match self.active.iter().position(|c| *c == condition) {
+            Some(index) => {
+                self.active.remove(index);
+                false
+            }
+            None => {
+                self.active.push(condition);
+                self.active.sort_by_key(|c| c.name());
+                true
+            }
+        }
+    }
+
+    /// Set the exhaustion level, capped at [`MAX_EXHAUSTION`].
+    pub fn set_exhaustion(&mut self, level: i32) {
+        self.exhaustion = level.clamp(0, MAX_EXHAUSTION as i32) as u8;
+    }
+
+    /// Names of the active conditions, with the exhaustion level last:
+    /// "Poisoned", "Prone", "Exhaustion 2"
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.active.iter().map(|c| c.name().to_string()).collect();
+        if self.exhaustion > 0 {
+            names.push(self.exhaustion_name());
+        }
+        names
+    }
+
+    fn exhaustion_name(&self) -> String {
+        format!("Exhaustion {}", self.exhaustion)
+    }
+
+    /// What the conditions do to a d20 test under a rules edition.
+    pub fn effect_on(&self, test: &D20Test, ruleset: Ruleset) -> ConditionEffect {
+        let mut effect = ConditionEffect::default();
+        for condition in &self.active {
+            let name = condition.name().to_string();
+            match (test, condition) {
+                (
+                    D20Test::AttackRoll,
+                    Condition::Blinded
+                    | Condition::Frightened
+                    | Condition::Poisoned
+                    | Condition::Prone
+                    | Condition::Restrained,
+                ) => effect.disadvantage.push(name),
+                (D20Test::AttackRoll, Condition::Grappled) if ruleset == Ruleset::Rules2024 => {
+                    effect.disadvantage.push(name)
+                }
+                (D20Test::AttackRoll, Condition::Invisible) => effect.advantage.push(name),
+                (D20Test::AbilityCheck, Condition::Frightened | Condition::Poisoned) => {
+                    effect.disadvantage.push(name)
+                }
+                (D20Test::SavingThrow(ability), condition) => {
+                    let ability = normalize_ability_name(ability);
+                    let strength_or_dexterity =
+                        matches!(ability, Some("strength") | Some("dexterity"));
+                    match condition {
+                        Condition::Restrained if ability == Some("dexterity") => {
+                            effect.disadvantage.push(name)
+                        }
+                        Condition::Paralyzed
+                        | Condition::Petrified
+                        | Condition::Stunned
+                        | Condition::Unconscious
+                            if strength_or_dexterity =>
+                        {
+                            effect.auto_fail.push(name)
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        match ruleset {
+            Ruleset::Rules2014 => {
+                let exhaustion_applies = match test {
+                    D20Test::AbilityCheck => self.exhaustion >= 1,
+                    D20Test::SavingThrow(_) | D20Test::AttackRoll => self.exhaustion >= 3,
+                };
+                if exhaustion_applies {
+                    effect.disadvantage.push(self.exhaustion_name());
+                }
+            }
+            Ruleset::Rules2024 => {
+                if self.exhaustion > 0 {
+                    effect.penalties.push((
+                        self.exhaustion_name(),
+                        ruleset.exhaustion_penalty(self.exhaustion),
+                    ));
+                }
+            }
+        }
+        effect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_and_names() {
+        let mut conditions = Conditions::default();
+        assert!(conditions.is_empty());
+        assert!(conditions.toggle(Condition::Prone));
+        assert!(conditions.toggle(Condition::from_name("poisoned").unwrap()));
+        conditions.set_exhaustion(9);
+        assert_eq!(conditions.exhaustion, MAX_EXHAUSTION);
+        conditions.set_exhaustion(2);
+        assert_eq!(
+            conditions.names(),
+            vec!["Poisoned", "Prone", "Exhaustion 2"]
+        );
+
+        assert!(!conditions.toggle(Condition::Prone));
+        assert!(!conditions.has(Condition::Prone));
+        assert_eq!(Condition::from_name("sleepy"), None);
+
+        let json = serde_json::to_value(&conditions).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "active": ["poisoned"], "exhaustion": 2 })
+        );
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut conditions = Conditions::default();
+        conditions.toggle(Condition::Poisoned);
+        conditions.toggle(Condition::Restrained);
+
+        let attack = conditions.effect_on(&D20Test::AttackRoll, Ruleset::Rules2014);
+        assert_eq!(attack.disadvantage, vec!["Poisoned", "Restrained"]);
+        assert!(attack.has_disadvantage());
+        assert_eq!(attack.describe(), "Poisoned, Restrained: disadvantage");
+
+        let check = conditions.effect_on(&D20Test::AbilityCheck, Ruleset::Rules2014);
+        assert_eq!(check.disadvantage, vec!["Poisoned"]);
+        assert!(conditions
+            .effect_on(&D20Test::SavingThrow("dex".to_string()), Ruleset::Rules2014)
+            .has_disadvantage());
+        assert!(conditions
+            .effect_on(
+                &D20Test::SavingThrow("wisdom".to_string()),
+                Ruleset::Rules2014
+            )
+            .is_empty());
+
+        // Advantage and disadvantage cancel out
+        conditions.toggle(Condition::Invisible);
+        let attack = conditions.effect_on(&D20Test::AttackRoll, Ruleset::Rules2014);
+        assert!(!attack.has_advantage() && !attack.has_disadvantage());
+
+        conditions.toggle(Condition::Stunned);
+        let save = conditions.effect_on(
+            &D20Test::SavingThrow("Strength".to_string()),
+            Ruleset::Rules2014,
+        );
+        assert!(save.fails_automatically());
+        assert_eq!(save.describe(), "Stunned: fails automatically");
+    }
+
+    #[test]
+    fn test_edge() {
+        let mut conditions = Conditions::default();
+        let edge = |conditions: &Conditions, test: D20Test, ruleset, advantage| {
+            conditions.effect_on(&test, ruleset).edge(advantage, false)
+        };
+        assert_eq!(
+            edge(&conditions, D20Test::AttackRoll, Ruleset::Rules2014, true),
+            (true, false, 0)
+        );
+
+        conditions.toggle(Condition::Poisoned);
+        conditions.set_exhaustion(1);
+        assert_eq!(
+            edge(
+                &conditions,
+                D20Test::AbilityCheck,
+                Ruleset::Rules2014,
+                false
+            ),
+            (false, true, 0)
+        );
+        // --advantage and the poisoned disadvantage cancel out
+        assert_eq!(
+            edge(&conditions, D20Test::AttackRoll, Ruleset::Rules2014, true),
+            (true, true, 0)
+        );
+        let wisdom = || D20Test::SavingThrow("wisdom".to_string());
+        assert_eq!(
+            edge(&conditions, wisdom(), Ruleset::Rules2014, false),
+            (false, false, 0)
+        );
+        // 2024 exhaustion takes 2 per level off every d20 test instead
+        assert_eq!(
+            edge(&conditions, wisdom(), Ruleset::Rules2024, false),
+            (false, false, -2)
+        );
+    }
+
+    #[test]
+    fn test_exhaustion_levels() {
+        let mut conditions = Conditions::default();
+        conditions.set_exhaustion(1);
+        assert!(conditions
+            .effect_on(&D20Test::AbilityCheck, Ruleset::Rules2014)
+            .has_disadvantage());
+        assert!(conditions
+            .effect_on(&D20Test::AttackRoll, Ruleset::Rules2014)
+            .is_empty());
+
+        conditions.set_exhaustion(3);
+        let attack = conditions.effect_on(&D20Test::AttackRoll, Ruleset::Rules2014);
+        assert_eq!(attack.disadvantage, vec!["Exhaustion 3"]);
+        assert!(conditions
+            .effect_on(&D20Test::SavingThrow("con".to_string()), Ruleset::Rules2014)
+            .has_disadvantage());
+    }
+
+    #[test]
+    fn test_2024_rules() {
+        let mut conditions = Conditions::default();
+        conditions.set_exhaustion(2);
+        conditions.toggle(Condition::Grappled);
+
+        let attack = conditions.effect_on(&D20Test::AttackRoll, Ruleset::Rules2024);
+        assert_eq!(attack.disadvantage, vec!["Grappled"]);
+        assert_eq!(attack.penalty(), -4);
+        assert_eq!(
+            attack.describe(),
+            "Grappled: disadvantage; Exhaustion 2: -4"
+        );
+
+        let save =
+            conditions.effect_on(&D20Test::SavingThrow("wis".to_string()), Ruleset::Rules2024);
+        assert!(!save.has_disadvantage());
+        assert_eq!(save.penalty(), -4);
+
+        // Under 2014 rules grappling doesn't touch attacks
+        assert!(conditions
+            .effect_on(&D20Test::AttackRoll, Ruleset::Rules2014)
+            .is_empty());
+    }
+}
@@ -0,0 +1,412 @@
+//! Currency
+//!
+//! The coins a character carries (copper, silver, electrum, gold and
+//! platinum pieces), kept in the sheet's equipment, and the purse operations
+//! used during play: adding coins, spending them, and exchanging one
+//! denomination for another. Spending makes change the way a shopkeeper
+//! would: the named coin and smaller ones are used first, then a larger coin
+//! is broken and the change comes back in gold, silver and copper.
+
+use serde::{Deserialize, Serialize};
+
+/// A coin denomination, ordered by worth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Coin {
+    Copper,
+    Silver,
+    Electrum,
+    Gold,
+    Platinum,
+}
+
+impl Coin {
+    /// Every coin, smallest first
+    pub const ALL: [Coin; 5] = [
+        Coin::Copper,
+        Coin::Silver,
+        Coin::Electrum,
+        Coin::Gold,
+        Coin::Platinum,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Coin::Copper => "Copper",
+            Coin::Silver => "Silver",
+            Coin::Electrum => "Electrum",
+            Coin::Gold => "Gold",
+            Coin::Platinum => "Platinum",
+        }
+    }
+
+    /// "cp", "sp", "ep", "gp" or "pp"
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Coin::Copper => "cp",
+            Coin::Silver => "sp",
+            Coin::Electrum => "ep",
+            Coin::Gold => "gp",
+            Coin::Platinum => "pp",
+        }
+    }
+
+    /// Worth of one coin in copper pieces
+    pub fn copper_value(&self) -> i64 {
+        match self {
+            Coin::Copper => 1,
+            Coin::Silver => 10,
+            Coin::Electrum => 50,
+            Coin::Gold => 100,
+            Coin::Platinum => 1000,
+        }
+    }
+
+    /// Find a coin by abbreviation or name, ignoring case ("gp", "Gold",
+    /// "silver pieces")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let name = name.trim_end_matches(" pieces").trim_end_matches(" piece");
+        Self::ALL
+            .into_iter()
+            .find(|c| c.abbreviation() == name || c.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Read amounts of coins: "15gp", "3 gp 5 sp", "2pp, 10cp"
+pub fn parse_coins(text: &str) -> Result<Vec<(Coin, i32)>, String> {
+    let mut coins = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i32 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("Expected an amount like 15gp, found \"{}\"", rest))?;
+        rest = rest[digits..].trim_start();
+        let letters = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let coin = Coin::from_name(&rest[..letters]).ok_or_else(|| {
+            format!(
+                "Unknown coin \"{}\" (use cp, sp, ep, gp or pp)",
+                &rest[..letters]
+            )
+        })?;
+        coins.push((coin, amount));
+        rest = rest[letters..].trim_start_matches([',', ' ']);
+    }
+    if coins.is_empty() {
+        return Err("No coins given".to_string());
+    }
+    Ok(coins)
+}
+
+/// Amounts of coins as parsed from "3 gp 5 sp", written back the same way
+pub fn format_coins(coins: &[(Coin, i32)]) -> String {
+    coins
+        .iter()
+        .map(|(coin, amount)| format!("{} {}", amount, coin.abbreviation()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Coins carried
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct Currency {
+    #[serde(default)]
+    pub copper: i32,
+    #[serde(default)]
+    pub silver: i32,
+    #[serde(default)]
+    pub electrum: i32,
+    #[serde(default)]
+    pub gold: i32,
+    #[serde(default)]
+    pub platinum: i32,
+}
+
+impl Currency {
+    pub fn get(&self, coin: Coin) -> i32 {
+        match coin {
+            Coin::Copper => self.copper,
+            Coin::Silver => self.silver,
+            Coin::Electrum => self.electrum,
+            Coin::Gold => self.gold,
+            Coin::Platinum => self.platinum,
+        }
+    }
+
+    fn get_mut(&mut self, coin: Coin) -> &mut i32 {
+        match coin {
+            Coin::Copper => &mut self.copper,
+            Coin::Silver => &mut self.silver,
+            Coin::Electrum => &mut self.electrum,
+            Coin::Gold => &mut self.gold,
+            Coin::Platinum => &mut self.platinum,
+        }
+    }
+
+    /// Worth of every coin, in copper pieces
+    pub fn total_copper(&self) -> i64 {
+        Coin::ALL
+            .iter()
+            .map(|c| self.get(*c).max(0) as i64 * c.copper_value())
+            .sum()
+    }
+
+    /// Worth of every coin, in gold pieces
+    pub fn total_gold(&self) -> f64 {
+        self.total_copper() as f64 / 100.0
+    }
+
+    pub fn set(&mut self, coin: Coin, amount: i32) {
+        *self.get_mut(coin) = amount.max(0);
+    }
+
+    pub fn add(&mut self, coin: Coin, amount: i32) {
+        *self.get_mut(coin) += amount.max(0);
+    }
+
+    /// Pay `amount` of a coin, making change when the exact coins aren't
+    /// there. Nothing is spent when the purse can't cover it.
+    pub fn spend(&mut self, coin: Coin, amount: i32) -> Result<(), String> {
+        let cost = amount.max(0) as i64 * coin.copper_value();
+        if cost > self.total_copper() {
+            return Err(format!(
+                "Not enough coins: {} {} costs more than the {}",
+                amount,
+                coin.abbreviation(),
+                self.describe()
+            ));
+        }
+
+        // The named coin, then smaller ones
+        let mut remaining = cost;
+        for smaller in Coin::ALL.iter().rev().filter(|c| **c <= coin) {
+            let take = (self.get(*smaller).max(0) as i64).min(remaining / smaller.copper_value());
+            *self.get_mut(*smaller) -= take as i32;
+            remaining -= take * smaller.copper_value();
+        }
+
+        // Break the smallest coin that covers the rest (or the largest one
+        // left while none does) and take the change
+        while remaining > 0 {
+            let held: Vec<Coin> = Coin::ALL.into_iter().filter(|c| self.get(*c) > 0).collect();
+            let Some(&broken) = held
+                .iter()
+                .find(|c| c.copper_value() >= remaining)
+                .or(held.last())
+            else {
+                break;
+            };
+            *self.get_mut(broken) -= 1;
+            remaining -= broken.copper_value();
+        }
+        self.give_change(-remaining);
+        Ok(())
+    }
+
+    /// Pay several amounts ("3 gp 5 sp"); nothing is spent unless all of it
+    /// can be paid.
+    pub fn spend_coins(&mut self, coins: &[(Coin, i32)]) -> Result<(), String> {
+        let mut purse = self.clone();
+        for (coin, amount) in coins {
+            purse.spend(*coin, *amount)?;
+        }
+        *self = purse;
+        Ok(())
+    }
+
+    /// Add copper pieces' worth in the fewest gold, silver and copper coins
+    fn give_change(&mut self, mut copper: i64) {
+        for coin in [Coin::Gold, Coin::Silver, Coin::Copper] {
+            let count = copper / coin.copper_value();
+            *self.get_mut(coin) += count as i32;
+            copper -= count * coin.copper_value();
+        }
+    }
+
+    /// Exchange `amount` of one coin for another; returns how many coins
+    /// were received. The amount has to come out even ("100cp" for "1gp").
+    pub fn convert(&mut self, from: Coin, amount: i32, to: Coin) -> Result<i32, String> {
+        if amount <= 0 {
+            return Err("Nothing to convert".to_string());
+        }
+        if self.get(from) < amount {
+            return Err(format!(
+                "Only {} {} to convert",
+                self.get(from),
+                from.abbreviation()
+            ));
+        }
+        let copper = amount as i64 * from.copper_value();
+        if copper % to.copper_value() != 0 {
+            return Err(format!(
+                "{} {} doesn't make a whole number of {}",
+                amount,
+                from.abbreviation(),
+                to.abbreviation()
+            ));
+        }
+        let received = (copper / to.copper_value()) as i32;
+        *self.get_mut(from) -= amount;
+        *self.get_mut(to) += received;
+        Ok(received)
+    }
+
+    /// Add, spend or exchange the coins written in `amount` ("15gp",
+    /// "3 gp 5 sp"); exchanging takes one kind of coin and the coin to get
+    /// for it. Returns what was done.
+    pub fn update(
+        &mut self,
+        action: CoinAction,
+        amount: &str,
+        to: Option<&str>,
+    ) -> Result<String, String> {
+        let coins = parse_coins(amount)?;
+        match action {
+            CoinAction::Add => {
+                for (coin, count) in &coins {
+                    self.add(*coin, *count);
+                }
+                Ok(format!("Added {}", format_coins(&coins)))
+            }
+            CoinAction::Spend => {
+                self.spend_coins(&coins)?;
+                Ok(format!("Spent {}", format_coins(&coins)))
+            }
+            CoinAction::Convert => {
+                let to = to
+                    .and_then(Coin::from_name)
+                    .ok_or("--to takes a coin: cp, sp, ep, gp or pp")?;
+                let [(from, count)] = coins[..] else {
+                    return Err("Convert one kind of coin at a time".to_string());
+                };
+                let received = self.convert(from, count, to)?;
+                Ok(format!(
+                    "Exchanged {} for {} {}",
+                    format_coins(&coins),
+                    received,
+                    to.abbreviation()
+                ))
+            }
+        }
+    }
+
+    /// "1 pp, 12 gp, 5 cp", largest coins first, or "no coins"
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = Coin::ALL
+            .iter()
+            .rev()
+            .filter(|c| self.get(**c) != 0)
+            .map(|c| format!("{} {}", self.get(*c), c.abbreviation()))
+            .collect();
+        if parts.is_empty() {
+            "no coins".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// What the `coins` command does with the coins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CoinAction {
+    Add,
+    /// Spend them, breaking larger coins for change
+    Spend,
+    /// Exchange them for the `--to` coin
+    Convert,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coins() {
+        assert_eq!(parse_coins("15gp"), Ok(vec![(Coin::Gold, 15)]));
+        assert_eq!(
+            parse_coins("3 gp 5 Silver, 2pp"),
+            Ok(vec![
+                (Coin::Gold, 3),
+                (Coin::Silver, 5),
+                (Coin::Platinum, 2)
+            ])
+        );
+        assert!(parse_coins("15").is_err());
+        assert!(parse_coins("gp").is_err());
+        assert!(parse_coins("5 doubloons").is_err());
+        assert_eq!(Coin::from_name("gold pieces"), Some(Coin::Gold));
+    }
+
+    #[test]
+    fn test_spend_makes_change() {
+        let mut purse = Currency {
+            gold: 1,
+            copper: 5,
+            ..Default::default()
+        };
+        purse.spend(Coin::Silver, 7).unwrap();
+        assert_eq!(purse.total_copper(), 35);
+        assert_eq!(purse.describe(), "3 sp, 5 cp");
+
+        // Exact coins are used when they're there
+        let mut purse = Currency {
+            platinum: 1,
+            gold: 5,
+            ..Default::default()
+        };
+        purse.spend(Coin::Gold, 3).unwrap();
+        assert_eq!((purse.platinum, purse.gold), (1, 2));
+        purse.spend(Coin::Gold, 4).unwrap();
+        assert_eq!(purse.describe(), "8 gp");
+
+        let before = purse.clone();
+        assert!(purse.spend(Coin::Platinum, 1).is_err());
+        assert!(purse
+            .spend_coins(&[(Coin::Gold, 5), (Coin::Gold, 5)])
+            .is_err());
+        assert_eq!(purse, before);
+        let coins = parse_coins("2gp 5sp").unwrap();
+        purse.spend_coins(&coins).unwrap();
+        assert_eq!(format_coins(&coins), "2 gp 5 sp");
+        assert_eq!(purse.describe(), "5 gp, 5 sp");
+    }
+
+    #[test]
+    fn test_convert() {
+        let mut purse = Currency {
+            copper: 250,
+            ..Default::default()
+        };
+        assert_eq!(purse.convert(Coin::Copper, 200, Coin::Gold), Ok(2));
+        assert_eq!(purse.describe(), "2 gp, 50 cp");
+        assert!(purse.convert(Coin::Copper, 5, Coin::Silver).is_err());
+        assert!(purse.convert(Coin::Gold, 3, Coin::Silver).is_err());
+        assert_eq!(purse.convert(Coin::Gold, 1, Coin::Electrum), Ok(2));
+        assert_eq!(purse.total_gold(), 2.5);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut purse = Currency::default();
+        assert_eq!(
+            purse.update(CoinAction::Add, "15gp 30cp", None).unwrap(),
+            "Added 15 gp 30 cp"
+        );
+        purse.update(CoinAction::Spend, "3 sp", None).unwrap();
+        assert_eq!(purse.describe(), "15 gp");
+        assert!(purse.update(CoinAction::Spend, "2pp", None).is_err());
+        assert!(purse.update(CoinAction::Convert, "5gp", None).is_err());
+        assert!(purse
+            .update(CoinAction::Convert, "5gp 1sp", Some("pp"))
+            .is_err());
+        purse
+            .update(CoinAction::Convert, "10gp", Some("pp"))
+            .unwrap();
+        assert_eq!(purse.describe(), "1 pp, 5 gp");
+    }
+}
@@ -0,0 +1,265 @@
+//! Damage riders
+//!
+//! Extra damage a class feature or spell adds to a weapon hit: Sneak Attack
+//! (a d6 per two character levels, once a turn, with a finesse or ranged
+//! weapon), Divine Smite (2d8 radiant for a 1st-level slot and 1d8 more per
+//! slot level above, up to 5d8, on a melee hit), Hunter's Mark (1d6 on every
+//! hit) and custom riders such as "Hex: 1d6 necrotic". A character's riders
+//! are kept with its combat stats and offered on the hits they qualify for;
+//! a critical hit doubles their dice like the weapon's.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice_expression::DamageRoll;
+use crate::weapon_properties::WeaponAttack;
+
+/// Most d8s a Divine Smite deals (before the extra die against undead and
+/// fiends)
+pub const MAX_SMITE_DICE: u32 = 5;
+
+/// Extra damage added to a weapon hit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DamageRider {
+    SneakAttack,
+    DivineSmite,
+    HuntersMark,
+    /// A rider of the player's own, added to every hit it's picked for
+    Custom {
+        name: String,
+        /// "1d6 necrotic"; the weapon's damage type when none is given
+        damage: String,
+    },
+}
+
+impl DamageRider {
+    /// The riders that come with their rules
+    pub const BUILT_IN: [DamageRider; 3] = [
+        DamageRider::SneakAttack,
+        DamageRider::DivineSmite,
+        DamageRider::HuntersMark,
+    ];
+
+    /// A rider by name ("Sneak Attack", "hunters mark"), or a custom one
+    /// written as "Hex: 1d6 necrotic"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let key: String = text
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        if let Some(rider) = Self::BUILT_IN.into_iter().find(|rider| {
+            rider
+                .name()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .eq(key.chars())
+        }) {
+            return Ok(rider);
+        }
+
+        let Some((name, damage)) = text.split_once(':') else {
+            return Err(format!(
+                "Unknown damage rider \"{}\" (use Sneak Attack, Divine Smite, Hunter's Mark or \"Name: 1d6 fire\")",
+                text.trim()
+            ));
+        };
+        let (name, damage) = (name.trim(), damage.trim());
+        if name.is_empty() {
+            return Err("A damage rider needs a name".to_string());
+        }
+        DamageRoll::parse(damage)?;
+        Ok(DamageRider::Custom {
+            name: name.to_string(),
+            damage: damage.to_string(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            DamageRider::SneakAttack => "Sneak Attack",
+            DamageRider::DivineSmite => "Divine Smite",
+            DamageRider::HuntersMark => "Hunter's Mark",
+            DamageRider::Custom { name, .. } => name,
+        }
+    }
+
+    /// Sneak Attack lands on one hit a turn
+    pub fn once_per_turn(&self) -> bool {
+        matches!(self, DamageRider::SneakAttack)
+    }
+
+    /// Divine Smite spends a spell slot on every hit it's used
+    pub fn spends_slot(&self) -> bool {
+        matches!(self, DamageRider::DivineSmite)
+    }
+
+    /// Whether the rider can be added to a hit with this attack
+    pub fn qualifies(&self, attack: &WeaponAttack) -> bool {
+        match self {
+            DamageRider::SneakAttack => attack.finesse || attack.ranged,
+            DamageRider::DivineSmite => !attack.ranged,
+            DamageRider::HuntersMark | DamageRider::Custom { .. } => true,
+        }
+    }
+
+    /// Damage dice of the rider for a character level, written out:
+    /// "3d6", "3d8 radiant", "1d6 necrotic". `slot_level` is the spell slot
+    /// spent on a smite.
+    pub fn dice(&self, level: i32, slot_level: u32) -> String {
+        match self {
+            DamageRider::SneakAttack => format!("{}d6", (level.clamp(1, 20) + 1) / 2),
+            DamageRider::DivineSmite => {
+                format!("{}d8 radiant", (1 + slot_level.max(1)).min(MAX_SMITE_DICE))
+            }
+            DamageRider::HuntersMark => "1d6".to_string(),
+            DamageRider::Custom { damage, .. } => damage.clone(),
+        }
+    }
+
+    /// The rider's damage on a hit; riders without a damage type of their
+    /// own deal the weapon's.
+    pub fn damage(
+        &self,
+        level: i32,
+        slot_level: u32,
+        weapon_damage_type: Option<&str>,
+    ) -> Result<DamageRoll, String> {
+        let mut damage = DamageRoll::parse(&self.dice(level, slot_level))?;
+        if damage.damage_type.is_none() {
+            damage.damage_type = weapon_damage_type.map(str::to_string);
+        }
+        Ok(damage)
+    }
+}
+
+/// The riders that can be added to a hit with `attack`, skipping the
+/// once-a-turn riders already `used` this turn
+pub fn riders_for<'a>(
+    riders: &'a [DamageRider],
+    attack: &WeaponAttack,
+    used: &[DamageRider],
+) -> Vec<&'a DamageRider> {
+    riders
+        .iter()
+        .filter(|rider| rider.qualifies(attack))
+        .filter(|rider| !(rider.once_per_turn() && used.contains(rider)))
+        .collect()
+}
+
+/// The spell slots smites are cast with: a character sheet's spells, in
+/// the app or the command lines
+pub trait SlotPool {
+    /// Spell levels with slots left to spend, lowest first, as
+    /// `(level, remaining)`
+    fn slot_choices(&self) -> Vec<(u32, i32)>;
+
+    /// Spend the spell slot a rider takes (of `level`, or the lowest one
+    /// left) and return its level; 0 for riders that take none.
+    fn spend_rider_slot(&mut self, rider: &DamageRider, level: Option<u32>) -> Result<u32, String>;
+
+    /// Slots of a spell level left to spend
+    fn slots_remaining(&self, level: u32) -> i32 {
+        self.slot_choices()
+            .into_iter()
+            .find(|(l, _)| *l == level)
+            .map_or(0, |(_, remaining)| remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon_properties::{AttackMode, WeaponStats};
+
+    fn attack(properties: &[&str], mode: AttackMode) -> WeaponAttack {
+        let properties: Vec<String> = properties.iter().map(|p| p.to_string()).collect();
+        WeaponAttack::new(
+            WeaponStats {
+                name: "Weapon",
+                attack_bonus: 0,
+                damage: "1d6",
+                properties: &properties,
+            },
+            mode,
+            3,
+            2,
+            2,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_riders() {
+        assert_eq!(
+            DamageRider::parse("sneak attack"),
+            Ok(DamageRider::SneakAttack)
+        );
+        assert_eq!(
+            DamageRider::parse("Hunters Mark"),
+            Ok(DamageRider::HuntersMark)
+        );
+        assert_eq!(
+            DamageRider::parse("Hex: 1d6 necrotic"),
+            Ok(DamageRider::Custom {
+                name: "Hex".to_string(),
+                damage: "1d6 necrotic".to_string()
+            })
+        );
+        assert!(DamageRider::parse("Rage").is_err());
+        assert!(DamageRider::parse("Hex: lots").is_err());
+
+        let json = serde_json::to_value([DamageRider::DivineSmite]).unwrap();
+        assert_eq!(json, serde_json::json!([{ "kind": "divineSmite" }]));
+    }
+
+    #[test]
+    fn test_rider_dice() {
+        assert_eq!(DamageRider::SneakAttack.dice(1, 0), "1d6");
+        assert_eq!(DamageRider::SneakAttack.dice(5, 0), "3d6");
+        assert_eq!(DamageRider::SneakAttack.dice(20, 0), "10d6");
+        assert_eq!(DamageRider::DivineSmite.dice(5, 1), "2d8 radiant");
+        assert_eq!(DamageRider::DivineSmite.dice(9, 3), "4d8 radiant");
+        assert_eq!(DamageRider::DivineSmite.dice(17, 5), "5d8 radiant");
+
+        let damage = DamageRider::HuntersMark
+            .damage(5, 0, Some("piercing"))
+            .unwrap()
+            .critical()
+            .unwrap();
+        assert_eq!(damage.expression.to_string(), "2d6");
+        assert_eq!(damage.damage_type.as_deref(), Some("piercing"));
+    }
+
+    #[test]
+    fn test_qualifying_riders() {
+        let riders = [
+            DamageRider::SneakAttack,
+            DamageRider::DivineSmite,
+            DamageRider::HuntersMark,
+        ];
+        let rapier = attack(&["Finesse"], AttackMode::Normal);
+        let longsword = attack(&["Versatile (1d10)"], AttackMode::Normal);
+        let bow = attack(&["Ammunition"], AttackMode::Normal);
+        let thrown = attack(&["Thrown"], AttackMode::Thrown);
+
+        assert_eq!(riders_for(&riders, &rapier, &[]).len(), 3);
+        assert_eq!(
+            riders_for(&riders, &longsword, &[]),
+            vec![&DamageRider::DivineSmite, &DamageRider::HuntersMark]
+        );
+        assert_eq!(
+            riders_for(&riders, &bow, &[]),
+            vec![&DamageRider::SneakAttack, &DamageRider::HuntersMark]
+        );
+        assert!(!DamageRider::DivineSmite.qualifies(&thrown));
+
+        // Sneak Attack once a turn
+        let used = [DamageRider::SneakAttack, DamageRider::HuntersMark];
+        assert_eq!(
+            riders_for(&riders, &rapier, &used),
+            vec![&DamageRider::DivineSmite, &DamageRider::HuntersMark]
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! Damage types
+//!
+//! The thirteen standard damage types plus any a campaign defines itself
+//! ("void", "sonic-echo"), each with a color and an icon. Custom types are
+//! kept per profile in the database's settings table under
+//! [`DAMAGE_TYPES_SETTING_KEY`], so each campaign has its own.
+//!
+//! Damage type names are matched by [`normalize_damage_type`]: case doesn't
+//! matter and spaces become dashes. Weapons, spell damage, resistances and
+//! the damage applied to a character all use these names.
+
+use serde::{Deserialize, Serialize};
+
+/// Database setting holding the campaign's custom damage types
+pub const DAMAGE_TYPES_SETTING_KEY: &str = "damage_types";
+
+/// Icon of custom damage types defined without one
+pub const DEFAULT_DAMAGE_ICON: &str = "flare";
+
+/// Name, color and icon of the standard damage types
+const STANDARD_DAMAGE_TYPES: [(&str, &str, &str); 13] = [
+    ("acid", "#8BC34A", "science"),
+    ("bludgeoning", "#A1887F", "gavel"),
+    ("cold", "#4FC3F7", "ac_unit"),
+    ("fire", "#FF7043", "local_fire_department"),
+    ("force", "#BA68C8", "blur_on"),
+    ("lightning", "#FFEB3B", "bolt"),
+    ("necrotic", "#78909C", "dangerous"),
+    ("piercing", "#90A4AE", "navigation"),
+    ("poison", "#66BB6A", "coronavirus"),
+    ("psychic", "#F06292", "psychology"),
+    ("radiant", "#FFD54F", "wb_sunny"),
+    ("slashing", "#B0BEC5", "content_cut"),
+    ("thunder", "#7986CB", "graphic_eq"),
+];
+
+/// Lowercase a damage type name and join its words with dashes:
+/// "Sonic Echo" becomes "sonic-echo".
+pub fn normalize_damage_type(name: &str) -> String {
+    name.split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Parse a `#RRGGBB` (or `RRGGBB`) color.
+pub fn parse_hex_color(text: &str) -> Option<[u8; 3]> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Label of a damage roll: "Fireball Damage (fire)" or "Longsword Damage".
+pub fn damage_roll_label(source: &str, damage_type: Option<&str>) -> String {
+    match damage_type.map(normalize_damage_type) {
+        Some(damage_type) if !damage_type.is_empty() => {
+            format!("{} Damage ({})", source, damage_type)
+        }
+        _ => format!("{} Damage", source),
+    }
+}
+
+/// Split a label made by [`damage_roll_label`] into its source and damage
+/// type; `None` when the label isn't a damage roll.
+pub fn parse_damage_label(label: &str) -> Option<(String, Option<String>)> {
+    let label = label.trim();
+    if let Some(source) = label.strip_suffix(" Damage") {
+        return Some((source.to_string(), None));
+    }
+    let (source, rest) = label.rsplit_once(" Damage (")?;
+    let damage_type = rest.strip_suffix(')')?;
+    Some((
+        source.to_string(),
+        Some(normalize_damage_type(damage_type)).filter(|t| !t.is_empty()),
+    ))
+}
+
+/// A damage type with how it is shown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DamageType {
+    /// Normalized name (see [`normalize_damage_type`])
+    pub name: String,
+    /// `#RRGGBB`
+    pub color: String,
+    /// Material icon name
+    #[serde(default = "default_damage_icon")]
+    pub icon: String,
+}
+
+fn default_damage_icon() -> String {
+    DEFAULT_DAMAGE_ICON.to_string()
+}
+
+impl DamageType {
+    /// The color as red, green and blue, or `None` when it isn't valid hex.
+    pub fn rgb(&self) -> Option<[u8; 3]> {
+        parse_hex_color(&self.color)
+    }
+}
+
+/// The campaign's damage types: the standard ones and its own
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DamageTypeRegistry {
+    /// Types defined by the campaign; one named like a standard type
+    /// replaces its color and icon
+    #[serde(default)]
+    pub custom: Vec<DamageType>,
+}
+
+impl DamageTypeRegistry {
+    /// The standard damage types.
+    pub fn standard() -> impl Iterator<Item = DamageType> {
+        STANDARD_DAMAGE_TYPES
+            .iter()
+            .map(|(name, color, icon)| DamageType {
+                name: name.to_string(),
+                color: color.to_string(),
+                icon: icon.to_string(),
+            })
+    }
+
+    pub fn is_standard(name: &str) -> bool {
+        let name = normalize_damage_type(name);
+        STANDARD_DAMAGE_TYPES.iter().any(|(n, _, _)| *n == name)
+    }
+
+    /// Look up a damage type by name.
+    pub fn get(&self, name: &str) -> Option<DamageType> {
+        let name = normalize_damage_type(name);
+        self.custom
+            .iter()
+            .find(|t| t.name == name)
+            .cloned()
+            .or_else(|| Self::standard().find(|t| t.name == name))
+    }
+
+    pub fn is_known(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Every damage type, standard ones first, sorted by name within each.
+    pub fn all(&self) -> Vec<DamageType> {
+        let mut custom = self.custom.clone();
+        custom.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut all: Vec<DamageType> = Self::standard()
+            .map(|standard| {
+                custom
+                    .iter()
+                    .find(|t| t.name == standard.name)
+                    .cloned()
+                    .unwrap_or(standard)
+            })
+            .collect();
+        all.extend(custom.into_iter().filter(|t| !Self::is_standard(&t.name)));
+        all
+    }
+
+    /// Define (or redefine) a custom damage type. Returns `true` when the
+    /// name wasn't known before.
+    pub fn define(&mut self, name: &str, color: &str, icon: Option<&str>) -> Result<bool, String> {
+        let name = normalize_damage_type(name);
+        if name.is_empty() {
+            return Err("Damage type needs a name".to_string());
+        }
+        if color.trim().is_empty() {
+            return Err(format!("Damage type {} needs a color (#RRGGBB)", name));
+        }
+        let rgb = parse_hex_color(color)
+            .ok_or_else(|| format!("'{}' is not a color; use #RRGGBB", color.trim()))?;
+        let icon = icon
+            .map(str::trim)
+            .filter(|icon| !icon.is_empty())
+            .unwrap_or(DEFAULT_DAMAGE_ICON);
+        let is_new = !self.is_known(&name);
+
+        let damage_type = DamageType {
+            name: name.clone(),
+            color: format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2]),
+            icon: icon.to_string(),
+        };
+        match self.custom.iter_mut().find(|t| t.name == name) {
+            Some(existing) => *existing = damage_type,
+            None => self.custom.push(damage_type),
+        }
+        Ok(is_new)
+    }
+
+    /// Remove a custom damage type (or a standard type's custom look).
+    /// Returns `false` when there was none.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let name = normalize_damage_type(name);
+        let before = self.custom.len();
+        self.custom.retain(|t| t.name != name);
+        self.custom.len() != before
+    }
+}
+
+/// Damage a character takes less, none or more of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefenseKind {
+    Resistance,
+    Immunity,
+    Vulnerability,
+}
+
+impl DefenseKind {
+    /// "resistance", "immunity" or "vulnerability"
+    pub fn name(&self) -> &'static str {
+        match self {
+            DefenseKind::Resistance => "resistance",
+            DefenseKind::Immunity => "immunity",
+            DefenseKind::Vulnerability => "vulnerability",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DefenseKind::Resistance => "Resistances",
+            DefenseKind::Immunity => "Immunities",
+            DefenseKind::Vulnerability => "Vulnerabilities",
+        }
+    }
+}
+
+/// A character's damage resistances, immunities and vulnerabilities, as
+/// normalized damage type names
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DamageDefenses {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resistances: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub immunities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vulnerabilities: Vec<String>,
+}
+
+impl DamageDefenses {
+    pub fn is_empty(&self) -> bool {
+        self.resistances.is_empty() && self.immunities.is_empty() && self.vulnerabilities.is_empty()
+    }
+
+    pub fn list(&self, kind: DefenseKind) -> &[String] {
+        match kind {
+            DefenseKind::Resistance => &self.resistances,
+            DefenseKind::Immunity => &self.immunities,
+            DefenseKind::Vulnerability => &self.vulnerabilities,
+        }
+    }
+
+    fn list_mut(&mut self, kind: DefenseKind) -> &mut Vec<String> {
+        match kind {
+            DefenseKind::Resistance => &mut self.resistances,
+            DefenseKind::Immunity => &mut self.immunities,
+            DefenseKind::Vulnerability => &mut self.vulnerabilities,
+        }
+    }
+
+    pub fn has(&self, kind: DefenseKind, damage_type: &str) -> bool {
+        let name = normalize_damage_type(damage_type);
+        self.list(kind).contains(&name)
+    }
+
+    /// Add or remove a defense; returns `true` when it was added.
+    pub fn toggle(&mut self, kind: DefenseKind, damage_type: &str) -> bool {
+        let name = normalize_damage_type(damage_type);
+        let list = self.list_mut(kind);
+        match list.iter().position(|t| *t == name) {
+            Some(index) => {
+                list.remove(index);
+                false
+            }
+            None => {
+                list.push(name);
+                true
+            }
+        }
+    }
+
+    /// Apply the defenses to `amount` damage of `damage_type`: immunity takes
+    /// it all, resistance halves it (rounding down) and vulnerability doubles
+    /// it, resistance first. Untyped damage is never adjusted.
+    pub fn adjust(&self, amount: i32, damage_type: Option<&str>) -> AdjustedDamage {
+        let amount = amount.max(0);
+        let Some(damage_type) = damage_type else {
+            return AdjustedDamage::unchanged(amount);
+        };
+        let immune = self.has(DefenseKind::Immunity, damage_type);
+        let resisted = !immune && self.has(DefenseKind::Resistance, damage_type);
+        let vulnerable = !immune && self.has(DefenseKind::Vulnerability, damage_type);
+
+        let mut adjusted = if immune { 0 } else { amount };
+        if resisted {
+            adjusted /= 2;
+        }
+        if vulnerable {
+            adjusted *= 2;
+        }
+        AdjustedDamage {
+            amount: adjusted,
+            immune,
+            resisted,
+            vulnerable,
+        }
+    }
+}
+
+/// Damage after resistances, immunities and vulnerabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjustedDamage {
+    pub amount: i32,
+    pub immune: bool,
+    pub resisted: bool,
+    pub vulnerable: bool,
+}
+
+impl AdjustedDamage {
+    fn unchanged(amount: i32) -> Self {
+        AdjustedDamage {
+            amount,
+            immune: false,
+            resisted: false,
+            vulnerable: false,
+        }
+    }
+
+    /// "resisted", "immune", ... or `None` when nothing applied.
+    pub fn note(&self) -> Option<&'static str> {
+        match (self.immune, self.resisted, self.vulnerable) {
+            (true, _, _) => Some("immune"),
+            (false, true, true) => Some("resisted and vulnerable"),
+            (false, true, false) => Some("resisted"),
+            (false, false, true) => Some("vulnerable"),
+            (false, false, false) => None,
+        }
+    }
+}
+
+/// Damage rolled in a turn added up per damage type, in the order the types
+/// were first rolled; untyped damage is one more entry.
+pub fn damage_by_type(rolled: &[(i32, Option<String>)]) -> Vec<(i32, Option<&str>)> {
+    let mut dealt: Vec<(i32, Option<&str>)> = Vec::new();
+    for (amount, damage_type) in rolled {
+        let damage_type = damage_type.as_deref();
+        match dealt.iter_mut().find(|(_, t)| *t == damage_type) {
+            Some((total, _)) => *total += amount,
+            None => dealt.push((*amount, damage_type)),
+        }
+    }
+    dealt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_and_colors() {
+        assert_eq!(normalize_damage_type(" Sonic Echo "), "sonic-echo");
+        assert_eq!(normalize_damage_type("sonic_echo"), "sonic-echo");
+        assert_eq!(normalize_damage_type("FIRE"), "fire");
+        assert_eq!(parse_hex_color("#6a0dad"), Some([0x6a, 0x0d, 0xad]));
+        assert_eq!(parse_hex_color("6A0DAD"), Some([0x6a, 0x0d, 0xad]));
+        assert_eq!(parse_hex_color("#6a0da"), None);
+        assert_eq!(parse_hex_color("purple"), None);
+    }
+
+    #[test]
+    fn test_damage_labels() {
+        let label = damage_roll_label("Fireball", Some("Fire"));
+        assert_eq!(label, "Fireball Damage (fire)");
+        assert_eq!(
+            parse_damage_label(&label),
+            Some(("Fireball".to_string(), Some("fire".to_string())))
+        );
+        assert_eq!(
+            parse_damage_label(&damage_roll_label("Longsword", None)),
+            Some(("Longsword".to_string(), None))
+        );
+        assert_eq!(parse_damage_label("Stealth"), None);
+        assert_eq!(parse_damage_label("Fireball Damage (fire"), None);
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = DamageTypeRegistry::default();
+        assert!(registry.is_known("Fire"));
+        assert!(!registry.is_known("void"));
+        assert_eq!(registry.all().len(), 13);
+
+        assert_eq!(
+            registry.define("Void", "#6a0dad", Some("blur_circular")),
+            Ok(true)
+        );
+        assert_eq!(registry.define("void", "#000000", None), Ok(false));
+        assert!(registry.define("void", "black", None).is_err());
+        assert!(registry.define("void", "", None).is_err());
+        let void = registry.get("VOID").unwrap();
+        assert_eq!(void.color, "#000000");
+        assert_eq!(void.icon, DEFAULT_DAMAGE_ICON);
+
+        // Restyling a standard type doesn't add a new one
+        assert_eq!(registry.define("fire", "#FF0000", None), Ok(false));
+        assert_eq!(registry.get("fire").unwrap().color, "#FF0000");
+        let all = registry.all();
+        assert_eq!(all.len(), 14);
+        assert_eq!(all.last().unwrap().name, "void");
+
+        assert!(registry.remove("fire"));
+        assert_eq!(registry.get("fire").unwrap().color, "#FF7043");
+        assert!(!registry.remove("fire"));
+    }
+
+    #[test]
+    fn test_damage_by_type() {
+        let rolled = [
+            (7, Some("slashing".to_string())),
+            (9, Some("radiant".to_string())),
+            (3, Some("slashing".to_string())),
+            (2, None),
+        ];
+        assert_eq!(
+            damage_by_type(&rolled),
+            vec![(10, Some("slashing")), (9, Some("radiant")), (2, None)]
+        );
+        assert!(damage_by_type(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_defenses() {
+        let mut defenses = DamageDefenses::default();
+        assert!(defenses.toggle(DefenseKind::Resistance, "Fire"));
+        assert!(defenses.toggle(DefenseKind::Immunity, "sonic echo"));
+        assert!(defenses.toggle(DefenseKind::Vulnerability, "fire"));
+
+        let fire = defenses.adjust(7, Some("fire"));
+        assert_eq!(fire.amount, 6);
+        assert_eq!(fire.note(), Some("resisted and vulnerable"));
+        let echo = defenses.adjust(12, Some("Sonic-Echo"));
+        assert_eq!(echo.amount, 0);
+        assert_eq!(echo.note(), Some("immune"));
+        assert_eq!(defenses.adjust(5, None).note(), None);
+        assert_eq!(defenses.adjust(5, Some("cold")).amount, 5);
+
+        assert!(!defenses.toggle(DefenseKind::Vulnerability, "FIRE"));
+        assert_eq!(defenses.adjust(7, Some("fire")).amount, 3);
+    }
+}
@@ -0,0 +1,781 @@
+//! D&D Beyond character import
+//!
+//! Maps a D&D Beyond character export (the JSON their character service
+//! returns, with or without its `{"success": .., "data": {..}}` envelope) to
+//! the character sheet JSON this app stores. Ability scores include racial,
+//! feat and equipped-item bonuses; saves, skills, armor class, hit points and
+//! weapon attacks are derived from them the way D&D Beyond shows them.
+//!
+//! Works on character sheet JSON rather than the app's `CharacterSheet`, so
+//! the app and both command lines share the mapping.
+
+use serde_json::{json, Map, Value};
+
+/// Abilities in D&D Beyond stat id order (`id` 1 is strength)
+const ABILITIES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intelligence",
+    "wisdom",
+    "charisma",
+];
+
+/// Skills: D&D Beyond `subType`, sheet key and ability (index into
+/// [`ABILITIES`])
+const SKILLS: [(&str, &str, usize); 18] = [
+    ("acrobatics", "acrobatics", 1),
+    ("animal-handling", "animalHandling", 4),
+    ("arcana", "arcana", 3),
+    ("athletics", "athletics", 0),
+    ("deception", "deception", 5),
+    ("history", "history", 3),
+    ("insight", "insight", 4),
+    ("intimidation", "intimidation", 5),
+    ("investigation", "investigation", 3),
+    ("medicine", "medicine", 4),
+    ("nature", "nature", 3),
+    ("perception", "perception", 4),
+    ("performance", "performance", 5),
+    ("persuasion", "persuasion", 5),
+    ("religion", "religion", 3),
+    ("sleight-of-hand", "sleightOfHand", 1),
+    ("stealth", "stealth", 1),
+    ("survival", "survival", 4),
+];
+
+/// Alignments by D&D Beyond `alignmentId`
+const ALIGNMENTS: [&str; 9] = [
+    "Lawful Good",
+    "Neutral Good",
+    "Chaotic Good",
+    "Lawful Neutral",
+    "True Neutral",
+    "Chaotic Neutral",
+    "Lawful Evil",
+    "Neutral Evil",
+    "Chaotic Evil",
+];
+
+/// D&D Beyond `armorTypeId`s
+const LIGHT_ARMOR: i64 = 1;
+const MEDIUM_ARMOR: i64 = 2;
+const HEAVY_ARMOR: i64 = 3;
+const SHIELD: i64 = 4;
+
+/// D&D Beyond `attackType` of ranged weapons
+const RANGED_ATTACK: i64 = 2;
+
+/// A bonus, proficiency, language... granted by the race, class, background,
+/// a feat or an equipped item
+struct Grant<'a> {
+    kind: &'a str,
+    sub_type: &'a str,
+    value: i64,
+    stat_id: Option<i64>,
+    name: &'a str,
+}
+
+/// Whether a JSON document looks like a D&D Beyond character export.
+pub fn is_ddb_character(value: &Value) -> bool {
+    let data = ddb_data(value);
+    data.get("stats").is_some_and(Value::is_array)
+        && data.get("classes").is_some_and(Value::is_array)
+}
+
+/// Convert a D&D Beyond character export to character sheet JSON.
+pub fn ddb_to_sheet_json(value: &Value) -> Result<Value, String> {
+    if !is_ddb_character(value) {
+        return Err("Not a D&D Beyond character export".to_string());
+    }
+    let data = ddb_data(value);
+    let grants = active_grants(data);
+
+    // Starting class first: it names the character's class and subclass
+    let mut classes: Vec<&Value> = array(data, "/classes").iter().collect();
+    classes.sort_by_key(|c| !c["isStartingClass"].as_bool().unwrap_or(false));
+    let level = classes.iter().map(|c| int(c, "/level")).sum::<i32>().max(1);
+    let class = classes
+        .iter()
+        .filter_map(|c| text(c, "/definition/name"))
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    let scores = ability_scores(data, &grants);
+    let mods = scores.map(ability_modifier);
+    let proficiency = 2 + (level - 1) / 4;
+
+    let mut saving_throws = Map::new();
+    for (i, ability) in ABILITIES.iter().enumerate() {
+        let proficient = has_grant(
+            &grants,
+            "proficiency",
+            &format!("{}-saving-throws", ability),
+        );
+        saving_throws.insert(
+            ability.to_string(),
+            json!({
+                "proficient": proficient,
+                "modifier": mods[i] + if proficient { proficiency } else { 0 },
+            }),
+        );
+    }
+
+    let mut skills = Map::new();
+    for (sub_type, key, ability) in SKILLS {
+        let expertise = has_grant(&grants, "expertise", sub_type);
+        let proficient = expertise || has_grant(&grants, "proficiency", sub_type);
+        let bonus = match (proficient, expertise) {
+            (_, true) => proficiency * 2,
+            (true, false) => proficiency,
+            _ => 0,
+        };
+        let mut skill = json!({ "proficient": proficient, "modifier": mods[ability] + bonus });
+        if expertise {
+            skill["expertise"] = json!(true);
+        }
+        skills.insert(key.to_string(), skill);
+    }
+
+    let alignment = data["alignmentId"]
+        .as_i64()
+        .and_then(|id| ALIGNMENTS.get((id as usize).wrapping_sub(1)));
+    let languages: Vec<&str> = grants
+        .iter()
+        .filter(|g| g.kind == "language" && !g.name.is_empty())
+        .map(|g| g.name)
+        .collect();
+
+    Ok(json!({
+        "character": {
+            "name": text(data, "/name").unwrap_or("Unnamed"),
+            "class": class,
+            "subclass": classes.first().and_then(|c| text(c, "/subclassDefinition/name")),
+            "race": text(data, "/race/fullName").unwrap_or(""),
+            "level": level,
+            "experience": int(data, "/currentXp"),
+            "alignment": alignment,
+            "background": text(data, "/background/definition/name"),
+            "languages": languages,
+        },
+        "attributes": ability_map(scores),
+        "modifiers": ability_map(mods),
+        "combat": combat(data, &grants, &classes, &mods, level),
+        "proficiencyBonus": proficiency,
+        "savingThrows": saving_throws,
+        "skills": skills,
+        "equipment": equipment(data, &mods, proficiency),
+        "features": features(data),
+        "spells": spellcasting(data, &classes, &mods, proficiency),
+    }))
+}
+
+/// The character object, inside the service's `data` envelope or not
+fn ddb_data(value: &Value) -> &Value {
+    match value.get("data") {
+        Some(data) if data.is_object() => data,
+        _ => value,
+    }
+}
+
+fn text<'a>(value: &'a Value, pointer: &str) -> Option<&'a str> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn int(value: &Value, pointer: &str) -> i32 {
+    value.pointer(pointer).and_then(Value::as_i64).unwrap_or(0) as i32
+}
+
+fn array<'a>(value: &'a Value, pointer: &str) -> &'a [Value] {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// D&D modifier for an ability score (rounds down, so 9 is -1)
+fn ability_modifier(score: i32) -> i32 {
+    (score - 10).div_euclid(2)
+}
+
+fn ability_map(values: [i32; 6]) -> Value {
+    let mut map = Map::new();
+    for (ability, value) in ABILITIES.iter().zip(values) {
+        map.insert(ability.to_string(), json!(value));
+    }
+    Value::Object(map)
+}
+
+/// `dice` plus a flat bonus: "1d8+3", "1d4-1", "2d6"
+fn with_bonus(dice: &str, bonus: i32) -> String {
+    match bonus {
+        0 => dice.to_string(),
+        b if b > 0 => format!("{}+{}", dice, b),
+        b => format!("{}{}", dice, b),
+    }
+}
+
+fn is_equipped(item: &Value) -> bool {
+    item["equipped"].as_bool().unwrap_or(false)
+}
+
+/// Grants that apply: everything but the grants of unequipped items.
+fn active_grants(data: &Value) -> Vec<Grant<'_>> {
+    let equipped: Vec<i64> = array(data, "/inventory")
+        .iter()
+        .filter(|item| is_equipped(item))
+        .filter_map(|item| item.pointer("/definition/id").and_then(Value::as_i64))
+        .collect();
+
+    let mut grants = Vec::new();
+    let Some(sources) = data["modifiers"].as_object() else {
+        return grants;
+    };
+    for (source, list) in sources {
+        for grant in list.as_array().map(Vec::as_slice).unwrap_or(&[]) {
+            if source == "item"
+                && !grant["componentId"]
+                    .as_i64()
+                    .is_some_and(|id| equipped.contains(&id))
+            {
+                continue;
+            }
+            grants.push(Grant {
+                kind: grant["type"].as_str().unwrap_or(""),
+                sub_type: grant["subType"].as_str().unwrap_or(""),
+                value: grant["value"].as_i64().unwrap_or(0),
+                stat_id: grant["statId"].as_i64(),
+                name: grant["friendlySubtypeName"].as_str().unwrap_or(""),
+            });
+        }
+    }
+    grants
+}
+
+fn has_grant(grants: &[Grant], kind: &str, sub_type: &str) -> bool {
+    grants
+        .iter()
+        .any(|g| g.kind == kind && g.sub_type == sub_type)
+}
+
+fn bonus_total(grants: &[Grant], sub_type: &str) -> i32 {
+    grants
+        .iter()
+        .filter(|g| g.kind == "bonus" && g.sub_type == sub_type)
+        .map(|g| g.value as i32)
+        .sum()
+}
+
+/// Base scores plus bonuses; "set" grants (a Belt of Giant Strength) raise a
+/// score to their value and overrides replace it.
+fn ability_scores(data: &Value, grants: &[Grant]) -> [i32; 6] {
+    let stat = |field: &str, id: i64| {
+        data[field]
+            .as_array()
+            .and_then(|stats| stats.iter().find(|s| s["id"].as_i64() == Some(id)))
+            .and_then(|s| s["value"].as_i64())
+            .map(|v| v as i32)
+    };
+
+    let mut scores = [10; 6];
+    for (i, ability) in ABILITIES.iter().enumerate() {
+        let id = i as i64 + 1;
+        let sub_type = format!("{}-score", ability);
+        let mut score = stat("stats", id).unwrap_or(10)
+            + stat("bonusStats", id).unwrap_or(0)
+            + bonus_total(grants, &sub_type);
+        for set in grants
+            .iter()
+            .filter(|g| g.kind == "set" && g.sub_type == sub_type)
+        {
+            score = score.max(set.value as i32);
+        }
+        scores[i] = stat("overrideStats", id).unwrap_or(score);
+    }
+    scores
+}
+
+/// Equipped armor of the given `armorTypeId`s
+fn equipped_armor<'a>(data: &'a Value, types: &'a [i64]) -> impl Iterator<Item = &'a Value> + 'a {
+    array(data, "/inventory").iter().filter(move |item| {
+        is_equipped(item)
+            && item
+                .pointer("/definition/armorTypeId")
+                .and_then(Value::as_i64)
+                .is_some_and(|t| types.contains(&t))
+    })
+}
+
+fn combat(
+    data: &Value,
+    grants: &[Grant],
+    classes: &[&Value],
+    mods: &[i32; 6],
+    level: i32,
+) -> Value {
+    let dex = mods[1];
+    let body_armor = equipped_armor(data, &[LIGHT_ARMOR, MEDIUM_ARMOR, HEAVY_ARMOR]).next();
+    let base_ac = match body_armor {
+        Some(armor) => {
+            let ac = int(armor, "/definition/armorClass");
+            match armor["definition"]["armorTypeId"].as_i64() {
+                Some(LIGHT_ARMOR) => ac + dex,
+                Some(MEDIUM_ARMOR) => ac + dex.min(2),
+                _ => ac,
+            }
+        }
+        // Unarmored Defense adds a second ability to 10 + DEX
+        None => grants
+            .iter()
+            .filter(|g| g.kind == "set" && g.sub_type == "unarmored-armor-class")
+            .filter_map(|g| g.stat_id)
+            .filter_map(|id| mods.get((id as usize).wrapping_sub(1)))
+            .map(|m| 10 + dex + m)
+            .max()
+            .unwrap_or(10 + dex),
+    };
+    let shields: i32 = equipped_armor(data, &[SHIELD])
+        .map(|shield| int(shield, "/definition/armorClass"))
+        .sum();
+    let armor_class = base_ac + shields + bonus_total(grants, "armor-class");
+
+    let con = mods[2];
+    let maximum = data["overrideHitPoints"]
+        .as_i64()
+        .map(|hp| hp as i32)
+        .unwrap_or_else(|| {
+            int(data, "/baseHitPoints")
+                + int(data, "/bonusHitPoints")
+                + (con + bonus_total(grants, "hit-points-per-level")) * level
+        })
+        .max(1);
+    let current = (maximum - int(data, "/removedHitPoints")).max(0);
+
+    let hit_dice = classes
+        .iter()
+        .map(|c| format!("{}d{}", int(c, "/level"), int(c, "/definition/hitDice")))
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let hit_dice_used: i32 = classes.iter().map(|c| int(c, "/hitDiceUsed")).sum();
+
+    let walk = data
+        .pointer("/race/weightSpeeds/normal/walk")
+        .and_then(Value::as_i64)
+        .unwrap_or(30) as i32;
+
+    json!({
+        "armorClass": armor_class,
+        "initiative": dex + bonus_total(grants, "initiative"),
+        "speed": walk + bonus_total(grants, "speed"),
+        "hitPoints": {
+            "current": current,
+            "maximum": maximum,
+            "temporary": int(data, "/temporaryHitPoints"),
+        },
+        "hitDice": {
+            "total": hit_dice,
+            "current": (level - hit_dice_used).max(0),
+        },
+        "deathSaves": {
+            "successes": int(data, "/deathSaves/successCount"),
+            "failures": int(data, "/deathSaves/failCount"),
+        },
+    })
+}
+
+/// Weapons (with attack bonus and damage, assuming proficiency), worn armor,
+/// the rest of the inventory and coins
+fn equipment(data: &Value, mods: &[i32; 6], proficiency: i32) -> Value {
+    let (strength, dex) = (mods[0], mods[1]);
+    let mut weapons = Vec::new();
+    let mut armor = Value::Null;
+    let mut items = Vec::new();
+
+    for item in array(data, "/inventory") {
+        let definition = &item["definition"];
+        let Some(name) = text(definition, "/name") else {
+            continue;
+        };
+
+        if definition["filterType"].as_str() == Some("Weapon") {
+            let properties: Vec<&str> = array(definition, "/properties")
+                .iter()
+                .filter_map(|p| text(p, "/name"))
+                .collect();
+            let ability = if properties.contains(&"Finesse") {
+                strength.max(dex)
+            } else if definition["attackType"].as_i64() == Some(RANGED_ATTACK) {
+                dex
+            } else {
+                strength
+            };
+            // +1/+2/+3 weapons
+            let magic: i32 = array(definition, "/grantedModifiers")
+                .iter()
+                .filter(|g| g["type"] == "bonus" && g["subType"] == "magic")
+                .filter_map(|g| g["value"].as_i64())
+                .map(|v| v as i32)
+                .sum();
+            weapons.push(json!({
+                "name": name,
+                "attackBonus": proficiency + ability + magic,
+                "damage": with_bonus(
+                    text(definition, "/damage/diceString").unwrap_or("1"),
+                    ability + magic,
+                ),
+                "damageType": text(definition, "/damageType").unwrap_or(""),
+                "properties": properties,
+            }));
+            continue;
+        }
+
+        let armor_type = definition["armorTypeId"].as_i64();
+        if armor.is_null()
+            && is_equipped(item)
+            && matches!(armor_type, Some(LIGHT_ARMOR | MEDIUM_ARMOR | HEAVY_ARMOR))
+        {
+            let ac = int(definition, "/armorClass");
+            let worn_ac = match armor_type {
+                Some(LIGHT_ARMOR) => ac + dex,
+                Some(MEDIUM_ARMOR) => ac + dex.min(2),
+                _ => ac,
+            };
+            armor = json!({
+                "name": name,
+                "armorClass": ac,
+                "armorClassWithDex": worn_ac,
+                "type": text(definition, "/type"),
+            });
+            continue;
+        }
+
+        match item["quantity"].as_i64() {
+            Some(quantity) if quantity > 1 => items.push(format!("{} ({})", name, quantity)),
+            _ => items.push(name.to_string()),
+        }
+    }
+
+    json!({
+        "weapons": weapons,
+        "armor": armor,
+        "items": items,
+        "currency": {
+            "copper": int(data, "/currencies/cp"),
+            "silver": int(data, "/currencies/sp"),
+            "electrum": int(data, "/currencies/ep"),
+            "gold": int(data, "/currencies/gp"),
+            "platinum": int(data, "/currencies/pp"),
+        },
+    })
+}
+
+/// Racial traits, class features reached so far and feats
+fn features(data: &Value) -> Vec<Value> {
+    let mut definitions: Vec<&Value> = array(data, "/race/racialTraits")
+        .iter()
+        .map(|t| &t["definition"])
+        .collect();
+    for class in array(data, "/classes") {
+        let class_level = int(class, "/level");
+        definitions.extend(
+            array(class, "/classFeatures")
+                .iter()
+                .map(|f| &f["definition"])
+                .filter(|d| int(d, "/requiredLevel") <= class_level),
+        );
+    }
+    definitions.extend(array(data, "/feats").iter().map(|f| &f["definition"]));
+
+    let mut names = Vec::new();
+    let mut features = Vec::new();
+    for definition in definitions {
+        let Some(name) = text(definition, "/name") else {
+            continue;
+        };
+        if names.contains(&name) {
+            continue;
+        }
+        names.push(name);
+        let description = text(definition, "/snippet")
+            .or_else(|| text(definition, "/description"))
+            .map(strip_html)
+            .unwrap_or_default();
+        features.push(json!({ "name": name, "description": description }));
+    }
+    features
+}
+
+/// Spellcasting of the first class that casts, with every known spell
+fn spellcasting(data: &Value, classes: &[&Value], mods: &[i32; 6], proficiency: i32) -> Value {
+    let mut known: Vec<&str> = array(data, "/classSpells")
+        .iter()
+        .flat_map(|c| array(c, "/spells"))
+        .chain(
+            ["race", "class", "item", "feat"]
+                .iter()
+                .flat_map(|source| array(data, &format!("/spells/{}", source))),
+        )
+        .filter_map(|spell| text(spell, "/definition/name"))
+        .collect();
+    known.sort_unstable();
+    known.dedup();
+
+    let caster = classes.iter().find_map(|c| {
+        let id = c
+            .pointer("/definition/spellCastingAbilityId")
+            .and_then(Value::as_i64)?;
+        let ability = (id as usize)
+            .checked_sub(1)
+            .filter(|i| *i < ABILITIES.len())?;
+        Some((c, ability))
+    });
+    if caster.is_none() && known.is_empty() {
+        return Value::Null;
+    }
+
+    let mut spells = json!({ "knownSpells": known });
+    if let Some((class, ability)) = caster {
+        spells["spellcastingAbility"] = json!(ABILITIES[ability]);
+        spells["spellSaveDC"] = json!(8 + proficiency + mods[ability]);
+        spells["spellAttackBonus"] = json!(proficiency + mods[ability]);
+
+        // Slots for the caster's level (a single-class table; multiclass
+        // casters get the table of their first casting class)
+        let level = int(class, "/level") as usize;
+        let mut slots = Map::new();
+        for (i, count) in array(class, "/definition/spellRules/levelSpellSlots")
+            .get(level)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+            .iter()
+            .enumerate()
+        {
+            if let Some(count) = count.as_i64().filter(|c| *c > 0) {
+                slots.insert((i + 1).to_string(), json!(count));
+            }
+        }
+        spells["spellSlots"] = Value::Object(slots);
+    }
+    spells
+}
+
+/// Plain text of a D&D Beyond HTML description
+pub(crate) fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&rsquo;", "'")
+        .replace("&lsquo;", "'")
+        .replace("&ldquo;", "\"")
+        .replace("&rdquo;", "\"")
+        .replace("&mdash;", "—")
+        .replace("&ndash;", "–")
+        .replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet_schema::check_character_sheet;
+
+    /// Trimmed D&D Beyond export of a level 5 hill dwarf fighter
+    fn ddb_export() -> Value {
+        json!({
+            "success": true,
+            "data": {
+                "name": "Thorin",
+                "race": {
+                    "fullName": "Hill Dwarf",
+                    "weightSpeeds": { "normal": { "walk": 25 } },
+                    "racialTraits": [
+                        { "definition": { "name": "Darkvision", "description": "<p>You can see in dim light&nbsp;within 60 feet.</p>" } }
+                    ]
+                },
+                "classes": [{
+                    "level": 5,
+                    "isStartingClass": true,
+                    "hitDiceUsed": 1,
+                    "definition": { "name": "Fighter", "hitDice": 10 },
+                    "subclassDefinition": { "name": "Champion" },
+                    "classFeatures": [
+                        { "definition": { "name": "Second Wind", "requiredLevel": 1, "snippet": "Regain 1d10 + 5 HP." } },
+                        { "definition": { "name": "Indomitable", "requiredLevel": 9, "snippet": "Reroll a save." } }
+                    ]
+                }],
+                "stats": [
+                    { "id": 1, "value": 15 }, { "id": 2, "value": 12 }, { "id": 3, "value": 14 },
+                    { "id": 4, "value": 9 }, { "id": 5, "value": 13 }, { "id": 6, "value": 8 }
+                ],
+                "bonusStats": [{ "id": 1, "value": null }],
+                "overrideStats": [{ "id": 6, "value": null }],
+                "modifiers": {
+                    "race": [
+                        { "type": "bonus", "subType": "constitution-score", "value": 2 },
+                        { "type": "bonus", "subType": "wisdom-score", "value": 1 },
+                        { "type": "bonus", "subType": "hit-points-per-level", "value": 1 },
+                        { "type": "language", "subType": "dwarvish", "friendlySubtypeName": "Dwarvish" }
+                    ],
+                    "class": [
+                        { "type": "proficiency", "subType": "strength-saving-throws" },
+                        { "type": "proficiency", "subType": "constitution-saving-throws" },
+                        { "type": "proficiency", "subType": "athletics" },
+                        { "type": "expertise", "subType": "perception" }
+                    ],
+                    "background": [],
+                    "item": [
+                        { "type": "bonus", "subType": "armor-class", "value": 1, "componentId": 900 },
+                        { "type": "bonus", "subType": "armor-class", "value": 5, "componentId": 901 }
+                    ]
+                },
+                "baseHitPoints": 44,
+                "bonusHitPoints": null,
+                "overrideHitPoints": null,
+                "removedHitPoints": 10,
+                "temporaryHitPoints": 3,
+                "currentXp": 6500,
+                "alignmentId": 1,
+                "background": { "definition": { "name": "Soldier" } },
+                "currencies": { "cp": 5, "sp": 0, "gp": 42, "ep": 0, "pp": 1 },
+                "inventory": [
+                    { "equipped": true, "quantity": 1, "definition": {
+                        "id": 100, "name": "Longsword", "filterType": "Weapon", "attackType": 1,
+                        "damage": { "diceString": "1d8" }, "damageType": "Slashing",
+                        "properties": [{ "name": "Versatile" }]
+                    } },
+                    { "equipped": true, "quantity": 1, "definition": {
+                        "id": 101, "name": "Rapier +1", "filterType": "Weapon", "attackType": 1,
+                        "damage": { "diceString": "1d8" }, "damageType": "Piercing",
+                        "properties": [{ "name": "Finesse" }],
+                        "grantedModifiers": [{ "type": "bonus", "subType": "magic", "value": 1 }]
+                    } },
+                    { "equipped": true, "quantity": 1, "definition": {
+                        "id": 102, "name": "Chain Mail", "filterType": "Armor", "armorTypeId": 3, "armorClass": 16, "type": "Heavy Armor"
+                    } },
+                    { "equipped": true, "quantity": 1, "definition": {
+                        "id": 103, "name": "Shield", "filterType": "Armor", "armorTypeId": 4, "armorClass": 2
+                    } },
+                    { "equipped": true, "quantity": 1, "definition": { "id": 900, "name": "Ring of Protection", "filterType": "Ring" } },
+                    { "equipped": false, "quantity": 1, "definition": { "id": 901, "name": "Cursed Amulet", "filterType": "Wondrous item" } },
+                    { "equipped": false, "quantity": 10, "definition": { "id": 104, "name": "Torch", "filterType": "Other Gear" } }
+                ],
+                "deathSaves": { "failCount": 1, "successCount": 0 },
+                "classSpells": [],
+                "spells": { "race": [], "class": [], "item": [], "feat": [] }
+            }
+        })
+    }
+
+    #[test]
+    fn test_is_ddb_character() {
+        assert!(is_ddb_character(&ddb_export()));
+        assert!(is_ddb_character(&ddb_export()["data"]));
+        assert!(!is_ddb_character(
+            &json!({ "character": { "name": "Thorin" } })
+        ));
+    }
+
+    #[test]
+    fn test_ddb_basic_info_and_scores() {
+        let sheet = ddb_to_sheet_json(&ddb_export()).unwrap();
+        assert_eq!(sheet["character"]["name"], "Thorin");
+        assert_eq!(sheet["character"]["class"], "Fighter");
+        assert_eq!(sheet["character"]["subclass"], "Champion");
+        assert_eq!(sheet["character"]["race"], "Hill Dwarf");
+        assert_eq!(sheet["character"]["level"], 5);
+        assert_eq!(sheet["character"]["alignment"], "Lawful Good");
+        assert_eq!(sheet["character"]["languages"], json!(["Dwarvish"]));
+
+        assert_eq!(sheet["attributes"]["constitution"], 16);
+        assert_eq!(sheet["attributes"]["wisdom"], 14);
+        assert_eq!(sheet["modifiers"]["intelligence"], -1);
+        assert_eq!(sheet["proficiencyBonus"], 3);
+    }
+
+    #[test]
+    fn test_ddb_proficiencies() {
+        let sheet = ddb_to_sheet_json(&ddb_export()).unwrap();
+        assert_eq!(sheet["savingThrows"]["strength"]["modifier"], 5);
+        assert_eq!(sheet["savingThrows"]["dexterity"]["proficient"], false);
+        assert_eq!(sheet["skills"]["athletics"]["modifier"], 5);
+        assert_eq!(sheet["skills"]["perception"]["modifier"], 8);
+        assert_eq!(sheet["skills"]["perception"]["expertise"], true);
+        assert_eq!(sheet["skills"]["sleightOfHand"]["modifier"], 1);
+    }
+
+    #[test]
+    fn test_ddb_combat_and_equipment() {
+        let sheet = ddb_to_sheet_json(&ddb_export()).unwrap();
+        // Chain mail 16 + shield 2 + ring 1; the unequipped amulet doesn't count
+        assert_eq!(sheet["combat"]["armorClass"], 19);
+        assert_eq!(sheet["combat"]["speed"], 25);
+        // 44 base + (CON 3 + hill dwarf 1) * 5
+        assert_eq!(sheet["combat"]["hitPoints"]["maximum"], 64);
+        assert_eq!(sheet["combat"]["hitPoints"]["current"], 54);
+        assert_eq!(sheet["combat"]["hitDice"]["total"], "5d10");
+        assert_eq!(sheet["combat"]["hitDice"]["current"], 4);
+
+        let weapons = &sheet["equipment"]["weapons"];
+        assert_eq!(weapons[0]["attackBonus"], 5);
+        assert_eq!(weapons[0]["damage"], "1d8+2");
+        // Finesse picks STR (+2) over DEX (+1); the +1 adds to both
+        assert_eq!(weapons[1]["attackBonus"], 6);
+        assert_eq!(weapons[1]["damage"], "1d8+3");
+        assert_eq!(sheet["equipment"]["armor"]["name"], "Chain Mail");
+        assert!(sheet["equipment"]["items"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("Torch (10)")));
+        assert_eq!(sheet["equipment"]["currency"]["gold"], 42);
+    }
+
+    #[test]
+    fn test_ddb_features_reached_so_far() {
+        let sheet = ddb_to_sheet_json(&ddb_export()).unwrap();
+        let features = sheet["features"].as_array().unwrap();
+        let names: Vec<&str> = features
+            .iter()
+            .map(|f| f["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["Darkvision", "Second Wind"]);
+        assert_eq!(
+            features[0]["description"],
+            "You can see in dim light within 60 feet."
+        );
+        assert!(sheet["spells"].is_null());
+    }
+
+    #[test]
+    fn test_ddb_sheet_matches_the_sheet_schema() {
+        let sheet = ddb_to_sheet_json(&ddb_export()).unwrap();
+        check_character_sheet(&sheet).unwrap();
+        assert_eq!(sheet["character"]["name"], "Thorin");
+        assert_eq!(sheet["skills"].as_object().unwrap().len(), 18);
+    }
+
+    #[test]
+    fn test_not_ddb_is_an_error() {
+        assert!(ddb_to_sheet_json(&json!({ "name": "Thorin" })).is_err());
+    }
+}
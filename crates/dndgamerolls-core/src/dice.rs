@@ -0,0 +1,231 @@
+//! Dice types
+//!
+//! The dice the app, the command lines and the roll engine know: the
+//! standard d4-d20, Fudge/FATE dice and any other number of sides (d2 coins,
+//! d3, d100 percentile dice and homebrew dice up to a d1000).
+
+use serde::{Deserialize, Serialize};
+
+/// Largest number of sides accepted for custom dice (`d1000`)
+pub const MAX_CUSTOM_DIE_SIDES: u32 = 1000;
+
+/// All supported dice types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiceType {
+    D4,
+    D6,
+    D8,
+    D10,
+    D12,
+    D20,
+    /// Fudge/FATE die (`dF`): a cube with two `-`, two blank and two `+`
+    /// faces. Its results are stored as faces 1-3 and count -1, 0 and +1;
+    /// see [`DiceType::face_value`].
+    Fudge,
+    /// Any other number of sides (d2, d3, d100, homebrew dice). There is no
+    /// 3D model for these, so they are rolled numerically next to the
+    /// physical dice - except the d100, thrown as a tens and a units d10,
+    /// and the d2, flipped as a coin.
+    Custom(u32),
+}
+
+impl DiceType {
+    pub fn max_value(&self) -> u32 {
+        match self {
+            DiceType::D4 => 4,
+            DiceType::D6 => 6,
+            DiceType::D8 => 8,
+            DiceType::D10 => 10,
+            DiceType::D12 => 12,
+            DiceType::D20 => 20,
+            DiceType::Fudge => 3,
+            DiceType::Custom(sides) => *sides,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        if self.is_coin() {
+            return "Coin".to_string();
+        }
+        if *self == DiceType::Fudge {
+            return "dF".to_string();
+        }
+        format!("D{}", self.max_value())
+    }
+
+    /// What a rolled face counts for: the face itself, except on a Fudge
+    /// die, whose faces 1-3 count -1, 0 and +1.
+    pub fn face_value(&self, face: u32) -> i32 {
+        match self {
+            DiceType::Fudge => face as i32 - 2,
+            _ => face as i32,
+        }
+    }
+
+    /// A rolled face as shown in results: "-", "0" or "+" on a Fudge die.
+    pub fn face_text(&self, face: u32) -> String {
+        match self {
+            DiceType::Fudge => fudge_symbol(face).to_string(),
+            _ => face.to_string(),
+        }
+    }
+
+    /// Die with the given number of sides (d4–d20 map to the physical dice).
+    pub fn from_sides(sides: u32) -> Option<DiceType> {
+        match sides {
+            4 => Some(DiceType::D4),
+            6 => Some(DiceType::D6),
+            8 => Some(DiceType::D8),
+            10 => Some(DiceType::D10),
+            12 => Some(DiceType::D12),
+            20 => Some(DiceType::D20),
+            2..=MAX_CUSTOM_DIE_SIDES => Some(DiceType::Custom(sides)),
+            _ => None,
+        }
+    }
+
+    /// Whether this die has a 3D model and is rolled by the physics simulation.
+    pub fn is_physical(&self) -> bool {
+        !matches!(self, DiceType::Custom(_)) || self.is_percentile() || self.is_coin()
+    }
+
+    /// Whether this is a d100, thrown as a pair of d10s.
+    pub fn is_percentile(&self) -> bool {
+        *self == DiceType::Custom(100)
+    }
+
+    /// Whether this is a d2, flipped as a coin (heads 2, tails 1).
+    pub fn is_coin(&self) -> bool {
+        *self == DiceType::Custom(2)
+    }
+
+    /// Parse `dN` notation (`d20`, `d100`, `D3`, `d%` for d100 and `dF`
+    /// for a Fudge die), or `coin` for a d2.
+    pub fn parse(s: &str) -> Option<DiceType> {
+        let lower = s.trim().to_lowercase();
+        if lower == "coin" {
+            return DiceType::from_sides(2);
+        }
+        let sides = lower.strip_prefix('d')?;
+        if sides == "%" {
+            return DiceType::from_sides(100);
+        }
+        if sides == "f" {
+            return Some(DiceType::Fudge);
+        }
+        if sides.is_empty() || !sides.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        DiceType::from_sides(sides.parse().ok()?)
+    }
+}
+
+/// "Heads" (2) or "Tails" (1), the side a coin landed on.
+pub fn coin_side(value: u32) -> &'static str {
+    if value == 2 {
+        "Heads"
+    } else {
+        "Tails"
+    }
+}
+
+/// "-", "0" or "+", the symbol on a Fudge die face (1-3).
+pub fn fudge_symbol(face: u32) -> &'static str {
+    match face {
+        1 => "-",
+        2 => "0",
+        _ => "+",
+    }
+}
+
+/// The d100 result of a percentile pair from the faces (1-10) of its tens
+/// and units d10: the tenth face reads 00 on the tens die and 0 on the units
+/// die, and 00 with 0 is 100.
+pub fn percentile_result(tens_face: u32, units_face: u32) -> u32 {
+    match (tens_face % 10) * 10 + units_face % 10 {
+        0 => 100,
+        value => value,
+    }
+}
+
+/// The two d10 faces behind a d100 result: "40 + 7", "00 + 0" for 100.
+pub fn percentile_faces(value: u32) -> String {
+    format!("{:02} + {}", value / 10 % 10 * 10, value % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dice_type_max_value() {
+        assert_eq!(DiceType::D4.max_value(), 4);
+        assert_eq!(DiceType::D6.max_value(), 6);
+        assert_eq!(DiceType::D8.max_value(), 8);
+        assert_eq!(DiceType::D10.max_value(), 10);
+        assert_eq!(DiceType::D12.max_value(), 12);
+        assert_eq!(DiceType::D20.max_value(), 20);
+    }
+
+    #[test]
+    fn test_dice_type_name() {
+        assert_eq!(DiceType::D4.name(), "D4");
+        assert_eq!(DiceType::D6.name(), "D6");
+        assert_eq!(DiceType::D20.name(), "D20");
+    }
+
+    #[test]
+    fn test_dice_type_parse() {
+        assert_eq!(DiceType::parse("d4"), Some(DiceType::D4));
+        assert_eq!(DiceType::parse("D4"), Some(DiceType::D4));
+        assert_eq!(DiceType::parse("d20"), Some(DiceType::D20));
+        assert_eq!(DiceType::parse("D20"), Some(DiceType::D20));
+        assert_eq!(DiceType::parse("invalid"), None);
+        assert_eq!(DiceType::parse("d100"), Some(DiceType::Custom(100)));
+    }
+
+    #[test]
+    fn test_dice_type_custom_sides() {
+        assert_eq!(DiceType::parse("d3"), Some(DiceType::Custom(3)));
+        assert_eq!(DiceType::parse("D2"), Some(DiceType::Custom(2)));
+        assert_eq!(DiceType::parse("d%"), Some(DiceType::Custom(100)));
+        assert_eq!(DiceType::parse("d1"), None);
+        assert_eq!(DiceType::parse("d0"), None);
+        assert_eq!(DiceType::parse("d+5"), None);
+        assert_eq!(DiceType::parse("d1001"), None);
+        assert_eq!(DiceType::from_sides(20), Some(DiceType::D20));
+        assert_eq!(DiceType::Custom(100).max_value(), 100);
+        assert_eq!(DiceType::Custom(100).name(), "D100");
+        assert!(!DiceType::Custom(3).is_physical());
+        assert!(DiceType::D6.is_physical());
+    }
+
+    #[test]
+    fn test_percentile_pair() {
+        assert!(DiceType::Custom(100).is_physical());
+        assert!(DiceType::Custom(100).is_percentile());
+        assert!(!DiceType::D10.is_percentile());
+
+        // 00 + 0 is 100, 00 + 1 is 1, 90 + 0 is 90
+        assert_eq!(percentile_result(10, 10), 100);
+        assert_eq!(percentile_result(10, 1), 1);
+        assert_eq!(percentile_result(9, 10), 90);
+        assert_eq!(percentile_result(4, 7), 47);
+        assert_eq!(percentile_result(9, 9), 99);
+
+        assert_eq!(percentile_faces(100), "00 + 0");
+        assert_eq!(percentile_faces(47), "40 + 7");
+        assert_eq!(percentile_faces(7), "00 + 7");
+    }
+
+    #[test]
+    fn test_coin() {
+        assert_eq!(DiceType::parse("coin"), Some(DiceType::Custom(2)));
+        assert!(DiceType::Custom(2).is_coin());
+        assert!(DiceType::Custom(2).is_physical());
+        assert!(!DiceType::Custom(3).is_coin());
+        assert_eq!(DiceType::Custom(2).name(), "Coin");
+        assert_eq!(coin_side(2), "Heads");
+        assert_eq!(coin_side(1), "Tails");
+    }
+}
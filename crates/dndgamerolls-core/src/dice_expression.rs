@@ -0,0 +1,1318 @@
+//! Dice expressions
+//!
+//! Parses compound roll expressions such as `2d6+1d8+3`, `(1d6+2)*2` or
+//! `1d20-1d4`: dice terms (`NdX`, `dX`, `d%`, `NdF` for Fudge/FATE dice
+//! and `Ncoin` for d2 coin flips), whole-number constants,
+//! `+ - * /` (division rounds down), unary minus and parentheses. A dice term
+//! can keep or drop its highest/lowest dice: `4d6kh3` (or `4d6k3`), `2d20kl1`,
+//! `4d6dl1` (or `4d6d1`) and `5d8dh2`. A `!` after the die makes it explode:
+//! `1d6!` rolls again and adds whenever a die shows its maximum, up to an
+//! explosion cap (`4d6!kh3` keeps the three highest exploded dice).
+//!
+//! Evaluation asks a closure for each die's value, so the same expression can
+//! be totalled from a random number generator (CLI) or from dice that were
+//! rolled physically in the 3D view.
+//!
+//! [`DamageRoll`] reads weapon damage strings (`1d8+3 slashing`): the
+//! expression followed by an optional damage type.
+
+use std::fmt;
+
+use crate::dice::DiceType;
+
+/// Most dice a single `NdX` term may roll
+pub const MAX_DICE_PER_TERM: u32 = 100;
+
+/// Extra rolls an exploding die may add unless configured otherwise
+pub const DEFAULT_EXPLOSION_CAP: u32 = 10;
+
+/// Highest explosion cap that can be configured
+pub const MAX_EXPLOSION_CAP: u32 = 100;
+
+/// Arithmetic operator between two sub-expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    /// Integer division, rounding down
+    Div,
+}
+
+impl BinaryOp {
+    fn symbol(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div => 2,
+        }
+    }
+
+    fn apply(self, lhs: i64, rhs: i64) -> Result<i64, String> {
+        let result = match self {
+            BinaryOp::Add => lhs.checked_add(rhs),
+            BinaryOp::Sub => lhs.checked_sub(rhs),
+            BinaryOp::Mul => lhs.checked_mul(rhs),
+            BinaryOp::Div => {
+                if rhs == 0 {
+                    return Err("Division by zero in dice expression".to_string());
+                }
+                lhs.checked_div(rhs).map(|q| {
+                    // Round toward negative infinity
+                    if lhs % rhs != 0 && ((lhs < 0) != (rhs < 0)) {
+                        q - 1
+                    } else {
+                        q
+                    }
+                })
+            }
+        };
+        result.ok_or_else(|| "Dice expression result is too large".to_string())
+    }
+}
+
+/// Which dice of a term count toward the total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepRule {
+    /// `kh3` / `k3`
+    KeepHighest(u32),
+    /// `kl1`
+    KeepLowest(u32),
+    /// `dl1` / `d1`
+    DropLowest(u32),
+    /// `dh1`
+    DropHighest(u32),
+}
+
+impl KeepRule {
+    /// How many of `count` dice this rule drops
+    fn drop_count(self, count: u32) -> u32 {
+        match self {
+            KeepRule::KeepHighest(n) | KeepRule::KeepLowest(n) => count.saturating_sub(n),
+            KeepRule::DropLowest(n) | KeepRule::DropHighest(n) => n.min(count),
+        }
+    }
+
+    /// Flag the dropped values. Ties drop the later die.
+    fn dropped(self, values: &[u32]) -> Vec<bool> {
+        let count = self.drop_count(values.len() as u32) as usize;
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        let drops_low = matches!(self, KeepRule::KeepHighest(_) | KeepRule::DropLowest(_));
+        if drops_low {
+            order.sort_by(|&a, &b| values[a].cmp(&values[b]).then(b.cmp(&a)));
+        } else {
+            order.sort_by(|&a, &b| values[b].cmp(&values[a]).then(b.cmp(&a)));
+        }
+
+        let mut dropped = vec![false; values.len()];
+        for &index in order.iter().take(count) {
+            dropped[index] = true;
+        }
+        dropped
+    }
+}
+
+impl KeepRule {
+    /// The same rule for twice as many dice.
+    fn doubled(self) -> KeepRule {
+        match self {
+            KeepRule::KeepHighest(n) => KeepRule::KeepHighest(n * 2),
+            KeepRule::KeepLowest(n) => KeepRule::KeepLowest(n * 2),
+            KeepRule::DropLowest(n) => KeepRule::DropLowest(n * 2),
+            KeepRule::DropHighest(n) => KeepRule::DropHighest(n * 2),
+        }
+    }
+}
+
+impl fmt::Display for KeepRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeepRule::KeepHighest(n) => write!(f, "kh{}", n),
+            KeepRule::KeepLowest(n) => write!(f, "kl{}", n),
+            KeepRule::DropLowest(n) => write!(f, "dl{}", n),
+            KeepRule::DropHighest(n) => write!(f, "dh{}", n),
+        }
+    }
+}
+
+/// A parsed dice expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiceExpr {
+    Constant(i64),
+    Dice {
+        count: u32,
+        die: DiceType,
+        keep: Option<KeepRule>,
+        /// `!`: a die showing its maximum is rolled again and added, at most
+        /// this many extra times
+        explode: Option<u32>,
+    },
+    Neg(Box<DiceExpr>),
+    Binary {
+        op: BinaryOp,
+        lhs: Box<DiceExpr>,
+        rhs: Box<DiceExpr>,
+    },
+}
+
+/// One die rolled while evaluating an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RolledDie {
+    pub die: DiceType,
+    pub value: u32,
+    /// Dropped by a keep/drop rule, so not part of the total
+    pub dropped: bool,
+    /// Extra roll added because the die before it exploded
+    pub explosion: bool,
+}
+
+/// A die thrown again in the 3D view because it exploded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExplosionResult {
+    /// Which die of the expression exploded, counting its dice in order
+    pub chain: usize,
+    pub die: DiceType,
+    pub value: u32,
+}
+
+/// Dice rolled while evaluating an expression, and the total
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionRoll {
+    pub total: i64,
+    pub rolls: Vec<RolledDie>,
+    /// Some die stopped exploding only because it reached the explosion cap
+    pub explosion_capped: bool,
+}
+
+impl ExpressionRoll {
+    /// Each die's rolls: the first roll followed by its explosions.
+    fn chains(&self) -> Vec<&[RolledDie]> {
+        let mut chains = Vec::new();
+        let mut start = 0;
+        for i in 1..=self.rolls.len() {
+            if i == self.rolls.len() || !self.rolls[i].explosion {
+                chains.push(&self.rolls[start..i]);
+                start = i;
+            }
+        }
+        chains
+    }
+
+    /// "kept 6+2, 5, 3; dropped 1", or `None` when no die was dropped.
+    pub fn keep_summary(&self) -> Option<String> {
+        let chains = self.chains();
+        let list = |dropped: bool| {
+            chains
+                .iter()
+                .filter(|chain| chain[0].dropped == dropped)
+                .map(|chain| chain_text(chain))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.rolls.iter().any(|r| r.dropped) {
+            Some(format!("kept {}; dropped {}", list(false), list(true)))
+        } else {
+            None
+        }
+    }
+
+    /// "exploded 6+6+2, 4+1", or `None` when no die exploded.
+    pub fn explosion_summary(&self) -> Option<String> {
+        let exploded: Vec<String> = self
+            .chains()
+            .into_iter()
+            .filter(|chain| chain.len() > 1)
+            .map(chain_text)
+            .collect();
+        if exploded.is_empty() {
+            return None;
+        }
+        let mut summary = format!("exploded {}", exploded.join(", "));
+        if self.explosion_capped {
+            summary.push_str(" (explosion cap reached)");
+        }
+        Some(summary)
+    }
+}
+
+fn chain_text(chain: &[RolledDie]) -> String {
+    chain
+        .iter()
+        .map(|r| r.die.face_text(r.value))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// A die the evaluation asks for
+#[derive(Debug, Clone, Copy)]
+struct DieDraw {
+    die: DiceType,
+    /// Index of the die within the expression; explosions share it
+    chain: usize,
+    explosion: bool,
+}
+
+/// State threaded through one evaluation
+struct Evaluation<'a> {
+    draw: &'a mut dyn FnMut(DieDraw) -> u32,
+    rolls: Vec<RolledDie>,
+    next_chain: usize,
+    explosion_capped: bool,
+}
+
+/// A roll evaluated from physical results, with the first rolls that were
+/// missing and the explosions still to throw as `(chain, die)`
+type PoolRoll = (ExpressionRoll, Vec<DiceType>, Vec<(usize, DiceType)>);
+
+impl DiceExpr {
+    /// Parse an expression like `2d6+1d8+3` (whitespace is ignored).
+    pub fn parse(input: &str) -> Result<DiceExpr, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Empty dice expression".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.expression()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!(
+                "Unexpected {} in dice expression '{}'",
+                parser.tokens[parser.pos].describe(),
+                input.trim()
+            ));
+        }
+        Ok(expr)
+    }
+
+    /// Add several expressions together (e.g. one per `--dice` flag).
+    pub fn sum(exprs: impl IntoIterator<Item = DiceExpr>) -> Option<DiceExpr> {
+        exprs.into_iter().reduce(|lhs, rhs| DiceExpr::Binary {
+            op: BinaryOp::Add,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        })
+    }
+
+    /// Every die the expression rolls, in order of appearance.
+    pub fn dice(&self) -> Vec<DiceType> {
+        let mut dice = Vec::new();
+        self.collect_dice(&mut dice);
+        dice
+    }
+
+    fn collect_dice(&self, out: &mut Vec<DiceType>) {
+        match self {
+            DiceExpr::Constant(_) => {}
+            DiceExpr::Dice { count, die, .. } => {
+                // Explosions aren't known up front; they are rolled as needed
+                for _ in 0..*count {
+                    out.push(*die);
+                }
+            }
+            DiceExpr::Neg(inner) => inner.collect_dice(out),
+            DiceExpr::Binary { lhs, rhs, .. } => {
+                lhs.collect_dice(out);
+                rhs.collect_dice(out);
+            }
+        }
+    }
+
+    /// Whether the expression only adds dice together (`2d6+1d8`), which a
+    /// plain list of dice already totals the same way.
+    pub fn is_dice_sum(&self) -> bool {
+        match self {
+            DiceExpr::Dice { keep, explode, .. } => keep.is_none() && explode.is_none(),
+            DiceExpr::Binary {
+                op: BinaryOp::Add,
+                lhs,
+                rhs,
+            } => lhs.is_dice_sum() && rhs.is_dice_sum(),
+            _ => false,
+        }
+    }
+
+    /// Whether any dice term explodes (`!`).
+    pub fn explodes(&self) -> bool {
+        match self {
+            DiceExpr::Constant(_) => false,
+            DiceExpr::Dice { explode, .. } => explode.is_some(),
+            DiceExpr::Neg(inner) => inner.explodes(),
+            DiceExpr::Binary { lhs, rhs, .. } => lhs.explodes() || rhs.explodes(),
+        }
+    }
+
+    /// Set the explosion cap of every exploding term (at most
+    /// [`MAX_EXPLOSION_CAP`]).
+    pub fn with_explosion_cap(self, cap: u32) -> DiceExpr {
+        let cap = cap.min(MAX_EXPLOSION_CAP);
+        match self {
+            DiceExpr::Dice {
+                count,
+                die,
+                keep,
+                explode: Some(_),
+            } => DiceExpr::Dice {
+                count,
+                die,
+                keep,
+                explode: Some(cap),
+            },
+            DiceExpr::Neg(inner) => DiceExpr::Neg(Box::new(inner.with_explosion_cap(cap))),
+            DiceExpr::Binary { op, lhs, rhs } => DiceExpr::Binary {
+                op,
+                lhs: Box::new(lhs.with_explosion_cap(cap)),
+                rhs: Box::new(rhs.with_explosion_cap(cap)),
+            },
+            other => other,
+        }
+    }
+
+    /// The expression for a critical hit: every dice term rolls twice as many
+    /// dice, while flat modifiers stay the same (`1d8+3` becomes `2d8+3`).
+    ///
+    /// Fails when a doubled term would roll more than `MAX_DICE_PER_TERM` dice.
+    pub fn critical(self) -> Result<DiceExpr, String> {
+        if let DiceExpr::Dice { count, .. } = &self {
+            if *count * 2 > MAX_DICE_PER_TERM {
+                return Err(format!(
+                    "A critical hit on {} would roll {} dice; at most {} can be rolled",
+                    self,
+                    count * 2,
+                    MAX_DICE_PER_TERM
+                ));
+            }
+        }
+        Ok(match self {
+            DiceExpr::Dice {
+                count,
+                die,
+                keep,
+                explode,
+            } => DiceExpr::Dice {
+                count: count * 2,
+                die,
+                keep: keep.map(KeepRule::doubled),
+                explode,
+            },
+            DiceExpr::Neg(inner) => DiceExpr::Neg(Box::new(inner.critical()?)),
+            DiceExpr::Binary { op, lhs, rhs } => DiceExpr::Binary {
+                op,
+                lhs: Box::new(lhs.critical()?),
+                rhs: Box::new(rhs.critical()?),
+            },
+            other => other,
+        })
+    }
+
+    /// Evaluate the expression, asking `roll_die` for each die in order.
+    pub fn evaluate(&self, roll_die: &mut dyn FnMut(DiceType) -> u32) -> Result<i64, String> {
+        self.roll(roll_die).map(|roll| roll.total)
+    }
+
+    fn evaluate_into(&self, eval: &mut Evaluation) -> Result<i64, String> {
+        match self {
+            DiceExpr::Constant(value) => Ok(*value),
+            DiceExpr::Dice {
+                count,
+                die,
+                keep,
+                explode,
+            } => {
+                let mut chains: Vec<Vec<u32>> = Vec::new();
+                for _ in 0..*count {
+                    let chain = eval.next_chain;
+                    eval.next_chain += 1;
+                    let mut values = vec![(eval.draw)(DieDraw {
+                        die: *die,
+                        chain,
+                        explosion: false,
+                    })];
+                    if let Some(cap) = explode {
+                        while values.last() == Some(&die.max_value()) {
+                            if values.len() as u32 > *cap {
+                                eval.explosion_capped = true;
+                                break;
+                            }
+                            values.push((eval.draw)(DieDraw {
+                                die: *die,
+                                chain,
+                                explosion: true,
+                            }));
+                        }
+                    }
+                    chains.push(values);
+                }
+
+                // Keep/drop rules compare whole exploded dice
+                let totals: Vec<u32> = chains.iter().map(|c| c.iter().sum()).collect();
+                let dropped = match keep {
+                    Some(rule) => rule.dropped(&totals),
+                    None => vec![false; totals.len()],
+                };
+
+                let mut total = 0;
+                for (chain, dropped) in chains.into_iter().zip(dropped) {
+                    for (i, value) in chain.into_iter().enumerate() {
+                        if !dropped {
+                            total += die.face_value(value) as i64;
+                        }
+                        eval.rolls.push(RolledDie {
+                            die: *die,
+                            value,
+                            dropped,
+                            explosion: i > 0,
+                        });
+                    }
+                }
+                Ok(total)
+            }
+            DiceExpr::Neg(inner) => inner
+                .evaluate_into(eval)?
+                .checked_neg()
+                .ok_or_else(|| "Dice expression result is too large".to_string()),
+            DiceExpr::Binary { op, lhs, rhs } => {
+                let lhs = lhs.evaluate_into(eval)?;
+                let rhs = rhs.evaluate_into(eval)?;
+                op.apply(lhs, rhs)
+            }
+        }
+    }
+
+    fn roll_draws(&self, draw: &mut dyn FnMut(DieDraw) -> u32) -> Result<ExpressionRoll, String> {
+        let mut eval = Evaluation {
+            draw,
+            rolls: Vec::new(),
+            next_chain: 0,
+            explosion_capped: false,
+        };
+        let total = self.evaluate_into(&mut eval)?;
+        Ok(ExpressionRoll {
+            total,
+            rolls: eval.rolls,
+            explosion_capped: eval.explosion_capped,
+        })
+    }
+
+    /// Roll the expression, recording every die.
+    pub fn roll(
+        &self,
+        mut roll_die: impl FnMut(DiceType) -> u32,
+    ) -> Result<ExpressionRoll, String> {
+        self.roll_draws(&mut |draw| roll_die(draw.die))
+    }
+
+    /// Evaluate from physical results, returning the roll along with the
+    /// dice that weren't there: missing first rolls, and explosions still to
+    /// throw as `(chain, die)`.
+    fn roll_from_pools(
+        &self,
+        results: &[(DiceType, u32)],
+        explosions: &[ExplosionResult],
+    ) -> Result<PoolRoll, String> {
+        let mut pool = results.to_vec();
+        let mut explosion_pool = explosions.to_vec();
+        let mut missing = Vec::new();
+        let mut pending = Vec::new();
+        let roll = self.roll_draws(&mut |draw| {
+            if draw.explosion {
+                let found = explosion_pool
+                    .iter()
+                    .position(|e| e.chain == draw.chain && e.die == draw.die);
+                match found {
+                    Some(index) => explosion_pool.remove(index).value,
+                    None => {
+                        // Ends the chain until the die has been thrown
+                        pending.push((draw.chain, draw.die));
+                        0
+                    }
+                }
+            } else {
+                match pool.iter().position(|(d, _)| *d == draw.die) {
+                    Some(index) => pool.remove(index).1,
+                    None => {
+                        missing.push(draw.die);
+                        0
+                    }
+                }
+            }
+        })?;
+        Ok((roll, missing, pending))
+    }
+
+    /// Evaluate the expression from dice that were already rolled.
+    ///
+    /// Each die term takes the next unused result of its die type, so the
+    /// results may be in any order.
+    pub fn roll_from_results(&self, results: &[(DiceType, u32)]) -> Result<ExpressionRoll, String> {
+        self.roll_with_explosions(results, &[])
+    }
+
+    /// Evaluate the expression from dice rolled in the 3D view, including
+    /// the dice thrown again for explosions.
+    pub fn roll_with_explosions(
+        &self,
+        results: &[(DiceType, u32)],
+        explosions: &[ExplosionResult],
+    ) -> Result<ExpressionRoll, String> {
+        let (roll, missing, pending) = self.roll_from_pools(results, explosions)?;
+        if let Some(die) = missing.first() {
+            return Err(format!("No rolled {} for the dice expression", die.name()));
+        }
+        if let Some((_, die)) = pending.first() {
+            return Err(format!(
+                "An exploding {} still has to be rolled",
+                die.name()
+            ));
+        }
+        Ok(roll)
+    }
+
+    /// Explosions the rolled dice call for that haven't been thrown yet, as
+    /// `(chain, die)`.
+    pub fn pending_explosions(
+        &self,
+        results: &[(DiceType, u32)],
+        explosions: &[ExplosionResult],
+    ) -> Vec<(usize, DiceType)> {
+        self.roll_from_pools(results, explosions)
+            .map(|(_, _, pending)| pending)
+            .unwrap_or_default()
+    }
+
+    /// Roll the explosions still to throw with `roll_die` until none are
+    /// left. Dice `roll_die` declines (`None`) stay pending, e.g. ones that
+    /// are thrown physically.
+    pub fn roll_pending_explosions(
+        &self,
+        results: &[(DiceType, u32)],
+        explosions: &mut Vec<ExplosionResult>,
+        mut roll_die: impl FnMut(DiceType) -> Option<u32>,
+    ) {
+        loop {
+            let mut rolled_any = false;
+            for (chain, die) in self.pending_explosions(results, explosions) {
+                if let Some(value) = roll_die(die) {
+                    explosions.push(ExplosionResult { chain, die, value });
+                    rolled_any = true;
+                }
+            }
+            if !rolled_any {
+                break;
+            }
+        }
+    }
+
+    /// Total the expression from dice that were already rolled.
+    pub fn total_from_results(&self, results: &[(DiceType, u32)]) -> Result<i64, String> {
+        self.roll_from_results(results).map(|roll| roll.total)
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            DiceExpr::Binary { op, .. } => op.precedence(),
+            DiceExpr::Neg(_) => 3,
+            DiceExpr::Constant(_) | DiceExpr::Dice { .. } => 4,
+        }
+    }
+}
+
+/// A weapon damage string: `1d8+3 slashing`, `2d6 + 1d4 fire`, `1d4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageRoll {
+    pub expression: DiceExpr,
+    /// Words after the expression (`slashing`), if any
+    pub damage_type: Option<String>,
+}
+
+impl DamageRoll {
+    /// Parse the longest leading dice expression and take the rest as the
+    /// damage type.
+    pub fn parse(text: &str) -> Result<DamageRoll, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        for split in (1..=words.len()).rev() {
+            if let Ok(expression) = DiceExpr::parse(&words[..split].join(" ")) {
+                let damage_type = words[split..].join(" ");
+                return Ok(DamageRoll {
+                    expression,
+                    damage_type: (!damage_type.is_empty()).then_some(damage_type),
+                });
+            }
+        }
+        Err(format!("Invalid damage '{}'", text.trim()))
+    }
+
+    /// The damage of a critical hit (damage dice doubled).
+    pub fn critical(self) -> Result<DamageRoll, String> {
+        Ok(DamageRoll {
+            expression: self.expression.critical()?,
+            ..self
+        })
+    }
+}
+
+fn write_operand(f: &mut fmt::Formatter<'_>, expr: &DiceExpr, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+impl fmt::Display for DiceExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiceExpr::Constant(value) => write!(f, "{}", value),
+            DiceExpr::Dice {
+                count,
+                die,
+                keep,
+                explode,
+            } => {
+                if *die == DiceType::Fudge {
+                    write!(f, "{}dF", count)?;
+                } else {
+                    write!(f, "{}d{}", count, die.max_value())?;
+                }
+                if explode.is_some() {
+                    write!(f, "!")?;
+                }
+                match keep {
+                    Some(rule) => write!(f, "{}", rule),
+                    None => Ok(()),
+                }
+            }
+            DiceExpr::Neg(inner) => {
+                write!(f, "-")?;
+                write_operand(f, inner, inner.precedence() < 3)
+            }
+            DiceExpr::Binary { op, lhs, rhs } => {
+                let prec = op.precedence();
+                write_operand(f, lhs, lhs.precedence() < prec)?;
+                write!(f, "{}", op.symbol())?;
+                // a-(b-c) and a/(b*c) need their parentheses kept
+                let right_needs_parens = rhs.precedence() < prec
+                    || (rhs.precedence() == prec && matches!(op, BinaryOp::Sub | BinaryOp::Div));
+                write_operand(f, rhs, right_needs_parens)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    D,
+    K,
+    H,
+    L,
+    Bang,
+    Percent,
+    Fudge,
+    Coin,
+    Op(BinaryOp),
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(n) => format!("'{}'", n),
+            Token::D => "'d'".to_string(),
+            Token::K => "'k'".to_string(),
+            Token::H => "'h'".to_string(),
+            Token::L => "'l'".to_string(),
+            Token::Bang => "'!'".to_string(),
+            Token::Percent => "'%'".to_string(),
+            Token::Fudge => "'F'".to_string(),
+            Token::Coin => "'coin'".to_string(),
+            Token::Op(op) => format!("'{}'", op.symbol()),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' => {
+                let mut digits = c.to_string();
+                while let Some(d) = chars.peek().copied().filter(char::is_ascii_digit) {
+                    digits.push(d);
+                    chars.next();
+                }
+                Token::Number(
+                    digits
+                        .parse()
+                        .map_err(|_| format!("Number too large: {}", digits))?,
+                )
+            }
+            'd' | 'D' => Token::D,
+            'k' | 'K' => Token::K,
+            'h' | 'H' => Token::H,
+            'l' | 'L' => Token::L,
+            '!' => Token::Bang,
+            '%' => Token::Percent,
+            'f' | 'F' => Token::Fudge,
+            'c' | 'C' => {
+                let rest: String = chars.clone().take(3).collect();
+                if !rest.eq_ignore_ascii_case("oin") {
+                    return Err(format!("Unexpected '{}' in dice expression", c));
+                }
+                chars.nth(2);
+                // `3coins` reads as `3coin`
+                if matches!(chars.peek(), Some('s' | 'S')) {
+                    chars.next();
+                }
+                Token::Coin
+            }
+            '+' => Token::Op(BinaryOp::Add),
+            '-' => Token::Op(BinaryOp::Sub),
+            '*' => Token::Op(BinaryOp::Mul),
+            '/' => Token::Op(BinaryOp::Div),
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            other => return Err(format!("Unexpected '{}' in dice expression", other)),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser:
+///
+/// ```text
+/// expression := term (('+' | '-') term)*
+/// term       := unary (('*' | '/') unary)*
+/// unary      := '-' unary | '+' unary | primary
+/// primary    := NUMBER | dice | coins | '(' expression ')'
+/// dice       := [NUMBER] 'd' (NUMBER | '%' | 'F') ['!'] [keep]
+/// coins      := [NUMBER] 'coin'
+/// keep       := 'k' ['h' | 'l'] NUMBER | 'd' ['l' | 'h'] NUMBER
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: [BinaryOp; 2],
+        operand: fn(&mut Self) -> Result<DiceExpr, String>,
+    ) -> Result<DiceExpr, String> {
+        let mut lhs = operand(self)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            if !ops.contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let rhs = operand(self)?;
+            lhs = DiceExpr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn expression(&mut self) -> Result<DiceExpr, String> {
+        self.binary_level([BinaryOp::Add, BinaryOp::Sub], Self::term)
+    }
+
+    fn term(&mut self) -> Result<DiceExpr, String> {
+        self.binary_level([BinaryOp::Mul, BinaryOp::Div], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<DiceExpr, String> {
+        match self.peek() {
+            Some(Token::Op(BinaryOp::Sub)) => {
+                self.pos += 1;
+                Ok(DiceExpr::Neg(Box::new(self.unary()?)))
+            }
+            Some(Token::Op(BinaryOp::Add)) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<DiceExpr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => {
+                if self.peek() == Some(Token::D) {
+                    self.pos += 1;
+                    self.dice(n)
+                } else if self.peek() == Some(Token::Coin) {
+                    self.pos += 1;
+                    self.coins(n)
+                } else {
+                    i64::try_from(n)
+                        .map(DiceExpr::Constant)
+                        .map_err(|_| format!("Number too large: {}", n))
+                }
+            }
+            Some(Token::D) => self.dice(1),
+            Some(Token::Coin) => self.coins(1),
+            Some(Token::LParen) => {
+                let inner = self.expression()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("Missing ')' in dice expression".to_string()),
+                }
+            }
+            Some(other) => Err(format!(
+                "Unexpected {} in dice expression",
+                other.describe()
+            )),
+            None => Err("Dice expression ends unexpectedly".to_string()),
+        }
+    }
+
+    fn dice(&mut self, count: u64) -> Result<DiceExpr, String> {
+        let sides = match self.next() {
+            Some(Token::Number(sides)) => Some(sides),
+            Some(Token::Percent) => Some(100),
+            Some(Token::Fudge) => None,
+            _ => return Err("Expected the number of sides after 'd'".to_string()),
+        };
+        let count = Self::dice_count(count)?;
+        let die = match sides {
+            Some(sides) => u32::try_from(sides)
+                .ok()
+                .and_then(DiceType::from_sides)
+                .ok_or_else(|| format!("Unsupported die: d{}", sides))?,
+            None => DiceType::Fudge,
+        };
+        let explode = if self.peek() == Some(Token::Bang) {
+            if die == DiceType::Fudge {
+                return Err("Fudge dice can't explode".to_string());
+            }
+            self.pos += 1;
+            Some(DEFAULT_EXPLOSION_CAP)
+        } else {
+            None
+        };
+        let keep = self.keep_rule(count)?;
+        Ok(DiceExpr::Dice {
+            count,
+            die,
+            keep,
+            explode,
+        })
+    }
+
+    /// `3coin`: coin flips, rolled as d2s (heads 2, tails 1).
+    fn coins(&mut self, count: u64) -> Result<DiceExpr, String> {
+        Ok(DiceExpr::Dice {
+            count: Self::dice_count(count)?,
+            die: DiceType::Custom(2),
+            keep: None,
+            explode: None,
+        })
+    }
+
+    fn dice_count(count: u64) -> Result<u32, String> {
+        if count == 0 || count > MAX_DICE_PER_TERM as u64 {
+            return Err(format!(
+                "Dice count must be between 1 and {}",
+                MAX_DICE_PER_TERM
+            ));
+        }
+        Ok(count as u32)
+    }
+
+    fn keep_rule(&mut self, count: u32) -> Result<Option<KeepRule>, String> {
+        let keeping = match self.peek() {
+            Some(Token::K) => true,
+            Some(Token::D) => false,
+            _ => return Ok(None),
+        };
+        self.pos += 1;
+
+        let highest = match self.peek() {
+            Some(Token::H) => {
+                self.pos += 1;
+                true
+            }
+            Some(Token::L) => {
+                self.pos += 1;
+                false
+            }
+            // `k3` keeps the highest, `d1` drops the lowest
+            _ => keeping,
+        };
+        let n = match self.next() {
+            Some(Token::Number(n)) => u32::try_from(n).unwrap_or(u32::MAX),
+            _ => return Err("Expected a number of dice to keep or drop".to_string()),
+        };
+
+        let rule = match (keeping, highest) {
+            (true, true) => KeepRule::KeepHighest(n),
+            (true, false) => KeepRule::KeepLowest(n),
+            (false, false) => KeepRule::DropLowest(n),
+            (false, true) => KeepRule::DropHighest(n),
+        };
+        let valid = if keeping {
+            (1..=count).contains(&n)
+        } else {
+            (1..count).contains(&n)
+        };
+        if !valid {
+            return Err(format!("Can't apply {} to {} dice", rule, count));
+        }
+        Ok(Some(rule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> DiceExpr {
+        DiceExpr::parse(s).unwrap()
+    }
+
+    /// Every die rolls its maximum.
+    fn max_roll(expr: &DiceExpr) -> i64 {
+        expr.evaluate(&mut |die| die.max_value()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_compound_expression() {
+        let expr = parse("2d6 + 1d8 + 3");
+        assert_eq!(expr.to_string(), "2d6+1d8+3");
+        assert_eq!(expr.dice(), vec![DiceType::D6, DiceType::D6, DiceType::D8]);
+        assert_eq!(max_roll(&expr), 23);
+    }
+
+    #[test]
+    fn test_precedence_and_parentheses() {
+        assert_eq!(max_roll(&parse("1d6+2*3")), 12);
+        assert_eq!(max_roll(&parse("(1d6+2)*2")), 16);
+        assert_eq!(parse("(1d6+2)*2").to_string(), "(1d6+2)*2");
+        assert_eq!(parse("10-(2-1)").to_string(), "10-(2-1)");
+        assert_eq!(max_roll(&parse("10-(2-1)")), 9);
+        assert_eq!(max_roll(&parse("-1d4+10")), 6);
+    }
+
+    #[test]
+    fn test_division_rounds_down() {
+        assert_eq!(max_roll(&parse("7/2")), 3);
+        assert_eq!(max_roll(&parse("-7/2")), -4);
+        assert!(DiceExpr::parse("1d6/0")
+            .unwrap()
+            .evaluate(&mut |_| 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_dice_shorthand() {
+        assert_eq!(
+            parse("d20"),
+            DiceExpr::Dice {
+                count: 1,
+                die: DiceType::D20,
+                keep: None,
+                explode: None
+            }
+        );
+        assert_eq!(
+            parse("D%"),
+            DiceExpr::Dice {
+                count: 1,
+                die: DiceType::Custom(100),
+                keep: None,
+                explode: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_fudge_dice() {
+        let expr = parse("4dF+1");
+        assert_eq!(expr.to_string(), "4dF+1");
+        assert_eq!(expr.dice(), vec![DiceType::Fudge; 4]);
+        assert_eq!(max_roll(&expr), 5);
+        assert_eq!(expr.total_from_results(&[(DiceType::Fudge, 1); 4]), Ok(-3));
+        assert_eq!(parse("df"), parse("1dF"));
+
+        let roll = parse("3dFkh2")
+            .roll_from_results(&[
+                (DiceType::Fudge, 1),
+                (DiceType::Fudge, 3),
+                (DiceType::Fudge, 2),
+            ])
+            .unwrap();
+        assert_eq!(roll.total, 1);
+        assert_eq!(roll.keep_summary().unwrap(), "kept +, 0; dropped -");
+    }
+
+    #[test]
+    fn test_coin_flips() {
+        assert_eq!(
+            parse("1coin"),
+            DiceExpr::Dice {
+                count: 1,
+                die: DiceType::Custom(2),
+                keep: None,
+                explode: None
+            }
+        );
+        assert_eq!(parse("Coin"), parse("1d2"));
+        assert_eq!(parse("3coins").dice(), vec![DiceType::Custom(2); 3]);
+        assert_eq!(max_roll(&parse("2coin+1")), 5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for bad in [
+            "", "2d", "d1", "0d6", "101d6", "2d6+", "(1d6", "1d6)", "2x3", "1d6 2", "4d6kh5",
+            "4d6dl4", "4d6k0", "4d6kh", "1d20k", "1d6!!", "!1d6", "1d6kh1!", "1co", "0coin",
+            "4dF!", "dFF", "f",
+        ] {
+            assert!(DiceExpr::parse(bad).is_err(), "'{}' should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_roll_records_dice_in_order() {
+        let mut next = 0;
+        let roll = parse("1d20+2d4-1")
+            .roll(|_| {
+                next += 1;
+                next
+            })
+            .unwrap();
+        let dice: Vec<_> = roll.rolls.iter().map(|r| (r.die, r.value)).collect();
+        assert_eq!(
+            dice,
+            vec![(DiceType::D20, 1), (DiceType::D4, 2), (DiceType::D4, 3)]
+        );
+        assert_eq!(roll.total, 5);
+    }
+
+    #[test]
+    fn test_total_from_results_in_any_order() {
+        let expr = parse("1d20-1d4");
+        let results = [(DiceType::D4, 3), (DiceType::D20, 15)];
+        assert_eq!(expr.total_from_results(&results).unwrap(), 12);
+        assert!(expr.total_from_results(&results[..1]).is_err());
+    }
+
+    #[test]
+    fn test_is_dice_sum() {
+        assert!(parse("2d6+1d8").is_dice_sum());
+        assert!(!parse("2d6+3").is_dice_sum());
+        assert!(!parse("1d20-1d4").is_dice_sum());
+
+        let combined = DiceExpr::sum([parse("1d20"), parse("1d6")]).unwrap();
+        assert!(combined.is_dice_sum());
+        assert_eq!(combined.to_string(), "1d20+1d6");
+    }
+
+    /// Roll `values` in order for the dice of `expr`.
+    fn roll_values(expr: &DiceExpr, values: &[u32]) -> ExpressionRoll {
+        let mut values = values.iter().copied();
+        expr.roll(|_| values.next().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_keep_and_drop() {
+        let stats = parse("4d6kh3");
+        assert!(!stats.is_dice_sum());
+        assert_eq!(stats.dice().len(), 4);
+        let roll = roll_values(&stats, &[3, 6, 1, 5]);
+        assert_eq!(roll.total, 14);
+        assert_eq!(
+            roll.keep_summary().as_deref(),
+            Some("kept 3, 6, 5; dropped 1")
+        );
+
+        assert_eq!(roll_values(&parse("4d6dl1"), &[3, 6, 1, 5]).total, 14);
+        assert_eq!(roll_values(&parse("4d6d1"), &[3, 6, 1, 5]).total, 14);
+        assert_eq!(roll_values(&parse("2d20kl1"), &[17, 4]).total, 4);
+        assert_eq!(roll_values(&parse("5d8dh2"), &[8, 2, 7, 3, 1]).total, 6);
+        assert_eq!(roll_values(&parse("2d20k1+5"), &[9, 12]).total, 17);
+
+        // Ties drop the later die
+        let tied = roll_values(&parse("3d6kh2"), &[4, 4, 4]);
+        assert_eq!(
+            tied.rolls.iter().map(|r| r.dropped).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+        assert!(roll_values(&parse("2d6"), &[1, 2]).keep_summary().is_none());
+    }
+
+    #[test]
+    fn test_keep_rule_display() {
+        assert_eq!(parse("4d6k3").to_string(), "4d6kh3");
+        assert_eq!(parse("4D6D1").to_string(), "4d6dl1");
+        assert_eq!(parse("2d20kl1+3").to_string(), "2d20kl1+3");
+        assert_eq!(parse("5d8dh2").to_string(), "5d8dh2");
+    }
+
+    #[test]
+    fn test_keep_from_physical_results() {
+        let expr = parse("4d6kh3");
+        let results = [
+            (DiceType::D6, 2),
+            (DiceType::D6, 6),
+            (DiceType::D6, 4),
+            (DiceType::D6, 4),
+        ];
+        let roll = expr.roll_from_results(&results).unwrap();
+        assert_eq!(roll.total, 14);
+        assert_eq!(roll.rolls.iter().filter(|r| r.dropped).count(), 1);
+    }
+
+    #[test]
+    fn test_exploding_dice() {
+        let expr = parse("2d6!+1");
+        assert_eq!(expr.to_string(), "2d6!+1");
+        assert!(expr.explodes());
+        assert!(!expr.is_dice_sum());
+
+        let roll = roll_values(&expr, &[6, 6, 2, 3]);
+        assert_eq!(roll.total, 18);
+        assert_eq!(roll.rolls.len(), 4);
+        assert_eq!(roll.explosion_summary().as_deref(), Some("exploded 6+6+2"));
+        assert!(roll_values(&expr, &[5, 3]).explosion_summary().is_none());
+
+        // Keep rules compare exploded dice as a whole
+        let roll = roll_values(&parse("3d6!kh2"), &[6, 1, 4, 5]);
+        assert_eq!(roll.total, 12);
+        assert_eq!(
+            roll.keep_summary().as_deref(),
+            Some("kept 6+1, 5; dropped 4")
+        );
+    }
+
+    #[test]
+    fn test_explosion_cap() {
+        let expr = parse("1d4!").with_explosion_cap(2);
+        assert_eq!(expr.to_string(), "1d4!");
+        let roll = expr.roll(|die| die.max_value()).unwrap();
+        assert_eq!(roll.total, 12);
+        assert!(roll.explosion_capped);
+        assert_eq!(
+            roll.explosion_summary().as_deref(),
+            Some("exploded 4+4+4 (explosion cap reached)")
+        );
+
+        // The default cap stops endless explosions
+        let rolls = parse("1d6!").roll(|die| die.max_value()).unwrap().rolls;
+        assert_eq!(rolls.len() as u32, DEFAULT_EXPLOSION_CAP + 1);
+        assert_eq!(
+            parse("1d6!").with_explosion_cap(u32::MAX),
+            parse("1d6!").with_explosion_cap(MAX_EXPLOSION_CAP)
+        );
+    }
+
+    #[test]
+    fn test_explosions_from_physical_results() {
+        let expr = parse("2d6!");
+        let results = [(DiceType::D6, 6), (DiceType::D6, 3)];
+        assert_eq!(
+            expr.pending_explosions(&results, &[]),
+            vec![(0, DiceType::D6)]
+        );
+        assert!(expr.roll_with_explosions(&results, &[]).is_err());
+
+        let mut explosions = vec![ExplosionResult {
+            chain: 0,
+            die: DiceType::D6,
+            value: 6,
+        }];
+        assert_eq!(
+            expr.pending_explosions(&results, &explosions),
+            vec![(0, DiceType::D6)]
+        );
+        explosions.push(ExplosionResult {
+            chain: 0,
+            die: DiceType::D6,
+            value: 1,
+        });
+        assert!(expr.pending_explosions(&results, &explosions).is_empty());
+
+        // Virtual dice explode numerically
+        let mut rolled = Vec::new();
+        let mut faces = [6, 6, 2].into_iter();
+        expr.roll_pending_explosions(&results, &mut rolled, |_| faces.next());
+        assert_eq!(rolled.len(), 3);
+        assert!(expr.pending_explosions(&results, &rolled).is_empty());
+        assert_eq!(
+            expr.roll_with_explosions(&results, &explosions)
+                .unwrap()
+                .total,
+            16
+        );
+    }
+
+    #[test]
+    fn test_critical_doubles_dice_only() {
+        let critical = |s: &str| parse(s).critical().unwrap();
+        assert_eq!(critical("1d8+3").to_string(), "2d8+3");
+        assert_eq!(critical("2d6+1d4-1").to_string(), "4d6+2d4-1");
+        assert_eq!(critical("4d6kh3").to_string(), "8d6kh6");
+        assert_eq!(max_roll(&critical("(1d6+2)*2")), 28);
+    }
+
+    #[test]
+    fn test_critical_over_dice_limit() {
+        assert_eq!(parse("50d6+2").critical().unwrap().to_string(), "100d6+2");
+        let err = parse("60d6kh50").critical().unwrap_err();
+        assert!(err.contains("120"), "{}", err);
+        assert!(parse("1d8+60d6").critical().is_err());
+    }
+
+    #[test]
+    fn test_parse_damage_roll() {
+        let damage = DamageRoll::parse("1d8+3 slashing").unwrap();
+        assert_eq!(damage.expression, parse("1d8+3"));
+        assert_eq!(damage.damage_type.as_deref(), Some("slashing"));
+
+        let damage = DamageRoll::parse(" 2d6 + 1d4 cold iron ").unwrap();
+        assert_eq!(damage.expression.to_string(), "2d6+1d4");
+        assert_eq!(damage.damage_type.as_deref(), Some("cold iron"));
+
+        let damage = DamageRoll::parse("1d4").unwrap().critical().unwrap();
+        assert_eq!(damage.expression.to_string(), "2d4");
+        assert_eq!(damage.damage_type, None);
+
+        for bad in ["", "slashing", "slashing 1d6"] {
+            assert!(
+                DamageRoll::parse(bad).is_err(),
+                "'{}' should not parse",
+                bad
+            );
+        }
+    }
+}
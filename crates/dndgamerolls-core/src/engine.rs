@@ -0,0 +1,268 @@
+//! Roll engine
+//!
+//! [`RollEngine`] rolls dice from a random number generator: single dice, d20
+//! tests with advantage or disadvantage, dice expressions, and whole
+//! [`RollRequest`]s (an expression, a modifier and advantage) into a
+//! [`RollResult`]. The 3D app, both command lines and third-party tools roll
+//! through it, so a d100 is always a tens and a units d10 and advantage
+//! always keeps the better of two d20s.
+
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+use crate::dice::{percentile_result, DiceType};
+use crate::dice_expression::{DiceExpr, ExpressionRoll, DEFAULT_EXPLOSION_CAP};
+
+/// A roll to make: a dice expression, a flat modifier and, for a d20 test,
+/// advantage or disadvantage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollRequest {
+    pub expression: DiceExpr,
+    pub modifier: i32,
+    pub advantage: bool,
+    pub disadvantage: bool,
+    /// Extra rolls an exploding die may add
+    pub explosion_cap: u32,
+}
+
+impl RollRequest {
+    pub fn new(expression: DiceExpr) -> Self {
+        Self {
+            expression,
+            modifier: 0,
+            advantage: false,
+            disadvantage: false,
+            explosion_cap: DEFAULT_EXPLOSION_CAP,
+        }
+    }
+
+    /// A plain d20 test.
+    pub fn d20() -> Self {
+        Self::new(DiceExpr::Dice {
+            count: 1,
+            die: DiceType::D20,
+            keep: None,
+            explode: None,
+        })
+    }
+
+    /// A request for an expression like `1d20+5` or `4d6kh3`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        DiceExpr::parse(input).map(Self::new)
+    }
+
+    pub fn modifier(mut self, modifier: i32) -> Self {
+        self.modifier = modifier;
+        self
+    }
+
+    pub fn advantage(mut self, advantage: bool) -> Self {
+        self.advantage = advantage;
+        self
+    }
+
+    pub fn disadvantage(mut self, disadvantage: bool) -> Self {
+        self.disadvantage = disadvantage;
+        self
+    }
+
+    pub fn explosion_cap(mut self, cap: u32) -> Self {
+        self.explosion_cap = cap;
+        self
+    }
+
+    /// Whether the expression rolls a single d20, the only roll advantage
+    /// and disadvantage apply to.
+    pub fn is_d20_test(&self) -> bool {
+        self.expression.dice() == [DiceType::D20]
+    }
+}
+
+/// What a [`RollRequest`] rolled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollResult {
+    /// Every die the expression rolled, with its explosions and dropped dice
+    pub roll: ExpressionRoll,
+    /// The other d20 of advantage or disadvantage
+    pub dropped: Option<u32>,
+    pub modifier: i32,
+    /// The expression's total plus the modifier
+    pub total: i32,
+}
+
+impl RollResult {
+    /// The face of the d20 of a d20 test, for natural 20s and 1s.
+    pub fn natural(&self) -> Option<u32> {
+        match self.roll.rolls.as_slice() {
+            [die] if die.die == DiceType::D20 => Some(die.value),
+            _ => None,
+        }
+    }
+
+    /// Each die and the face it landed on, explosions left out.
+    pub fn faces(&self) -> Vec<(DiceType, u32)> {
+        self.roll
+            .rolls
+            .iter()
+            .filter(|r| !r.explosion)
+            .map(|r| (r.die, r.value))
+            .collect()
+    }
+}
+
+/// Rolls dice from a random number generator (the thread's by default)
+#[derive(Debug, Clone)]
+pub struct RollEngine<R = ThreadRng> {
+    rng: R,
+}
+
+impl RollEngine {
+    pub fn new() -> Self {
+        Self { rng: rand::rng() }
+    }
+}
+
+impl Default for RollEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng> RollEngine<R> {
+    /// An engine drawing from `rng`, e.g. a seeded `StdRng` for rolls that
+    /// can be replayed.
+    pub fn with_rng(rng: R) -> Self {
+        Self { rng }
+    }
+
+    /// Roll one die; a d100 is rolled as a tens and a units d10.
+    pub fn roll_die(&mut self, die: DiceType) -> u32 {
+        if die.is_percentile() {
+            percentile_result(self.rng.random_range(1..=10), self.rng.random_range(1..=10))
+        } else {
+            self.rng.random_range(1..=die.max_value())
+        }
+    }
+
+    pub fn roll_d20(&mut self) -> i32 {
+        self.roll_die(DiceType::D20) as i32
+    }
+
+    /// Roll a d20 test: with advantage or disadvantage (not both, which
+    /// cancel out) two d20s are rolled and the higher or lower kept. Returns
+    /// the kept die and the dropped one.
+    pub fn roll_d20_test(&mut self, advantage: bool, disadvantage: bool) -> (i32, Option<i32>) {
+        let first = self.roll_d20();
+        if advantage == disadvantage {
+            return (first, None);
+        }
+        let second = self.roll_d20();
+        if advantage {
+            (first.max(second), Some(first.min(second)))
+        } else {
+            (first.min(second), Some(first.max(second)))
+        }
+    }
+
+    /// Roll an expression, recording every die.
+    pub fn roll_expression(&mut self, expression: &DiceExpr) -> Result<ExpressionRoll, String> {
+        expression.roll(|die| self.roll_die(die))
+    }
+
+    /// Roll a request. Advantage and disadvantage only change a d20 test's
+    /// die; other rolls ignore them.
+    pub fn roll(&mut self, request: &RollRequest) -> Result<RollResult, String> {
+        let expression = request
+            .expression
+            .clone()
+            .with_explosion_cap(request.explosion_cap);
+        let edge = request.is_d20_test() && request.advantage != request.disadvantage;
+
+        let mut dropped = None;
+        let roll = expression.roll(|die| {
+            // Only the first d20 gets advantage, not its explosions
+            if edge && die == DiceType::D20 && dropped.is_none() {
+                let (kept, other) = self.roll_d20_test(request.advantage, request.disadvantage);
+                dropped = other.map(|d| d as u32);
+                kept as u32
+            } else {
+                self.roll_die(die)
+            }
+        })?;
+
+        Ok(RollResult {
+            total: roll.total as i32 + request.modifier,
+            roll,
+            dropped,
+            modifier: request.modifier,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn engine() -> RollEngine<StdRng> {
+        RollEngine::with_rng(StdRng::seed_from_u64(7))
+    }
+
+    #[test]
+    fn test_roll_die_ranges() {
+        let mut engine = engine();
+        for die in [
+            DiceType::D4,
+            DiceType::D20,
+            DiceType::Fudge,
+            DiceType::Custom(100),
+        ] {
+            for _ in 0..200 {
+                let face = engine.roll_die(die);
+                assert!((1..=die.max_value()).contains(&face), "{:?} {}", die, face);
+            }
+        }
+    }
+
+    #[test]
+    fn test_d20_test_keeps_the_right_die() {
+        let mut engine = engine();
+        for _ in 0..50 {
+            let (kept, dropped) = engine.roll_d20_test(true, false);
+            assert!(kept >= dropped.unwrap());
+            let (kept, dropped) = engine.roll_d20_test(false, true);
+            assert!(kept <= dropped.unwrap());
+            assert_eq!(engine.roll_d20_test(true, true).1, None);
+        }
+    }
+
+    #[test]
+    fn test_roll_request() {
+        let mut engine = engine();
+        let request = RollRequest::parse("1d20+5")
+            .unwrap()
+            .modifier(2)
+            .advantage(true);
+        assert!(request.is_d20_test());
+        let result = engine.roll(&request).unwrap();
+        let natural = result.natural().unwrap();
+        assert!(natural >= result.dropped.unwrap());
+        assert_eq!(result.total, natural as i32 + 7);
+
+        // Advantage leaves other rolls alone
+        let damage = RollRequest::parse("2d6").unwrap().advantage(true);
+        assert!(!damage.is_d20_test());
+        let result = engine.roll(&damage).unwrap();
+        assert_eq!(result.dropped, None);
+        assert_eq!(result.faces().len(), 2);
+        assert_eq!(result.natural(), None);
+
+        // The same seed rolls the same dice
+        let request = RollRequest::parse("4d6kh3").unwrap();
+        assert_eq!(
+            RollEngine::with_rng(StdRng::seed_from_u64(1)).roll(&request),
+            RollEngine::with_rng(StdRng::seed_from_u64(1)).roll(&request)
+        );
+    }
+}
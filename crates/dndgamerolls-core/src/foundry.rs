@@ -0,0 +1,1288 @@
+//! Foundry VTT actor import and export
+//!
+//! Converts between the character sheet JSON this app stores and the actor
+//! JSON of Foundry VTT's D&D 5e system ("Export Data" on an actor, and
+//! "Import Data" to bring a file back). Abilities, saves, skills, hit points,
+//! armor class and weapons go both ways; anything without a counterpart is
+//! listed in [`FoundryConversion::unmapped`] so nothing disappears silently.
+//!
+//! Foundry computes most totals itself, so a sheet modifier that doesn't
+//! follow from the ability score and proficiency is exported as a bonus, and
+//! imported totals include those bonuses.
+//!
+//! Works on character sheet JSON rather than the app's `CharacterSheet`, so
+//! the app and both command lines share the mapping.
+
+use serde_json::{json, Map, Value};
+
+use crate::ddb_import::strip_html;
+
+/// Sheet ability names and Foundry ability keys
+const ABILITIES: [(&str, &str); 6] = [
+    ("strength", "str"),
+    ("dexterity", "dex"),
+    ("constitution", "con"),
+    ("intelligence", "int"),
+    ("wisdom", "wis"),
+    ("charisma", "cha"),
+];
+
+/// Skills: Foundry key, sheet key and default ability (index into
+/// [`ABILITIES`])
+const SKILLS: [(&str, &str, usize); 18] = [
+    ("acr", "acrobatics", 1),
+    ("ani", "animalHandling", 4),
+    ("arc", "arcana", 3),
+    ("ath", "athletics", 0),
+    ("dec", "deception", 5),
+    ("his", "history", 3),
+    ("ins", "insight", 4),
+    ("itm", "intimidation", 5),
+    ("inv", "investigation", 3),
+    ("med", "medicine", 4),
+    ("nat", "nature", 3),
+    ("prc", "perception", 4),
+    ("prf", "performance", 5),
+    ("per", "persuasion", 5),
+    ("rel", "religion", 3),
+    ("slt", "sleightOfHand", 1),
+    ("ste", "stealth", 1),
+    ("sur", "survival", 4),
+];
+
+/// Foundry weapon property codes and the names the sheet uses
+const WEAPON_PROPERTIES: [(&str, &str); 12] = [
+    ("amm", "Ammunition"),
+    ("fin", "Finesse"),
+    ("hvy", "Heavy"),
+    ("lgt", "Light"),
+    ("lod", "Loading"),
+    ("mgc", "Magical"),
+    ("rch", "Reach"),
+    ("rel", "Reload"),
+    ("spc", "Special"),
+    ("thr", "Thrown"),
+    ("two", "Two-Handed"),
+    ("ver", "Versatile"),
+];
+
+/// Item types kept in the sheet's item list
+const INVENTORY_TYPES: [&str; 6] = [
+    "equipment",
+    "loot",
+    "consumable",
+    "tool",
+    "container",
+    "backpack",
+];
+
+/// A converted document and the fields that had no counterpart
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoundryConversion {
+    pub json: Value,
+    /// Human-readable paths of the source fields that were not carried over
+    pub unmapped: Vec<String>,
+}
+
+/// Whether a JSON document looks like a Foundry VTT D&D 5e actor export.
+pub fn is_foundry_actor(value: &Value) -> bool {
+    system(value).get("abilities").is_some_and(Value::is_object)
+        && value.get("items").is_some_and(Value::is_array)
+}
+
+/// Convert a Foundry VTT actor export to character sheet JSON.
+pub fn foundry_to_sheet_json(actor: &Value) -> Result<FoundryConversion, String> {
+    if !is_foundry_actor(actor) {
+        return Err("Not a Foundry VTT actor export".to_string());
+    }
+    let system = system(actor);
+    let items = array(actor, "/items");
+    let mut unmapped = Vec::new();
+
+    let classes: Vec<&Value> = items_of_type(items, "class").collect();
+    let level = match classes.iter().map(|c| int(item_system(c), "/levels")).sum() {
+        0 => int(system, "/details/level").max(1),
+        level => level,
+    };
+    let class = classes
+        .iter()
+        .filter_map(|c| text(c, "/name"))
+        .collect::<Vec<_>>()
+        .join(" / ");
+    let subclass = items_of_type(items, "subclass")
+        .find_map(|s| text(s, "/name"))
+        .or_else(|| {
+            classes
+                .iter()
+                .find_map(|c| text(item_system(c), "/subclass"))
+        });
+    let race = items_of_type(items, "race")
+        .find_map(|r| text(r, "/name"))
+        .or_else(|| text(system, "/details/race"));
+    let background = items_of_type(items, "background")
+        .find_map(|b| text(b, "/name"))
+        .or_else(|| text(system, "/details/background"));
+
+    let scores = ABILITIES.map(|(_, key)| {
+        system
+            .pointer(&format!("/abilities/{}/value", key))
+            .and_then(Value::as_i64)
+            .unwrap_or(10) as i32
+    });
+    let mods = scores.map(ability_modifier);
+    let proficiency = system
+        .pointer("/attributes/prof")
+        .and_then(Value::as_i64)
+        .map(|p| p as i32)
+        .unwrap_or(2 + (level - 1) / 4);
+
+    let mut saving_throws = Map::new();
+    for (i, (name, key)) in ABILITIES.iter().enumerate() {
+        let ability = &system["abilities"][key];
+        let proficient = ability["proficient"].as_f64().unwrap_or(0.0) >= 1.0;
+        let bonus = bonus_value(
+            ability,
+            "/bonuses/save",
+            &format!("system.abilities.{}.bonuses.save", key),
+            &mut unmapped,
+        );
+        saving_throws.insert(
+            name.to_string(),
+            json!({
+                "proficient": proficient,
+                "modifier": mods[i] + if proficient { proficiency } else { 0 } + bonus,
+            }),
+        );
+    }
+
+    let mut skills = Map::new();
+    for (key, sheet_key, default_ability) in SKILLS {
+        let skill = &system["skills"][key];
+        let ability = skill["ability"]
+            .as_str()
+            .and_then(|a| ABILITIES.iter().position(|(_, k)| *k == a))
+            .unwrap_or(default_ability);
+        // 0.5 is Jack of All Trades, 1 proficient, 2 expertise
+        let multiplier = skill["value"].as_f64().unwrap_or(0.0);
+        let bonus = bonus_value(
+            skill,
+            "/bonuses/check",
+            &format!("system.skills.{}.bonuses.check", key),
+            &mut unmapped,
+        );
+        let mut entry = json!({
+            "proficient": multiplier >= 1.0,
+            "modifier": mods[ability] + (proficiency as f64 * multiplier).floor() as i32 + bonus,
+        });
+        if multiplier >= 2.0 {
+            entry["expertise"] = json!(true);
+        } else if multiplier > 0.0 && multiplier < 1.0 {
+            entry["proficiencyType"] = json!("half");
+        }
+        skills.insert(sheet_key.to_string(), entry);
+    }
+
+    let mut languages: Vec<String> = match system.pointer("/traits/languages/value") {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(capitalize)
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Some(custom) = text(system, "/traits/languages/custom") {
+        languages.extend(
+            custom
+                .split(';')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    let hit_dice = classes
+        .iter()
+        .map(|c| {
+            let system = item_system(c);
+            format!(
+                "{}{}",
+                int(system, "/levels"),
+                text(system, "/hitDice").unwrap_or("d8")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let hit_dice_used: i32 = classes
+        .iter()
+        .map(|c| int(item_system(c), "/hitDiceUsed"))
+        .sum();
+    let initiative_bonus = bonus_value(
+        system,
+        "/attributes/init/bonus",
+        "system.attributes.init.bonus",
+        &mut unmapped,
+    );
+
+    let combat = json!({
+        "armorClass": armor_class(system, items, &mods, &mut unmapped),
+        "initiative": mods[1] + initiative_bonus,
+        "speed": system
+            .pointer("/attributes/movement/walk")
+            .and_then(Value::as_i64)
+            .unwrap_or(30),
+        "hitPoints": {
+            "current": int(system, "/attributes/hp/value"),
+            "maximum": int(system, "/attributes/hp/max").max(1),
+            "temporary": int(system, "/attributes/hp/temp"),
+        },
+        "hitDice": {
+            "total": hit_dice,
+            "current": (level - hit_dice_used).max(0),
+        },
+        "deathSaves": {
+            "successes": int(system, "/attributes/death/success"),
+            "failures": int(system, "/attributes/death/failure"),
+        },
+    });
+
+    let mut features = Vec::new();
+    for feat in items_of_type(items, "feat") {
+        let Some(name) = text(feat, "/name") else {
+            continue;
+        };
+        features.push(json!({
+            "name": name,
+            "description": strip_html(text(item_system(feat), "/description/value").unwrap_or("")),
+        }));
+    }
+
+    let known: Vec<&str> = items_of_type(items, "spell")
+        .filter_map(|s| text(s, "/name"))
+        .collect();
+    let mut slots = Map::new();
+    for level in 1..=9 {
+        let slot = &system["spells"][format!("spell{}", level)];
+        let count = slot["override"]
+            .as_i64()
+            .or_else(|| slot["max"].as_i64())
+            .or_else(|| slot["value"].as_i64())
+            .unwrap_or(0);
+        if count > 0 {
+            slots.insert(level.to_string(), json!(count));
+        }
+    }
+    let caster = text(system, "/attributes/spellcasting")
+        .and_then(|key| ABILITIES.iter().position(|(_, k)| *k == key));
+    let spells = if known.is_empty() && slots.is_empty() && caster.is_none() {
+        Value::Null
+    } else {
+        let mut spells = json!({ "knownSpells": known, "spellSlots": slots });
+        if let Some(ability) = caster {
+            spells["spellcastingAbility"] = json!(ABILITIES[ability].0);
+            spells["spellSaveDC"] = json!(8 + proficiency + mods[ability]);
+            spells["spellAttackBonus"] = json!(proficiency + mods[ability]);
+        }
+        spells
+    };
+
+    for item in items {
+        let kind = item["type"].as_str().unwrap_or("");
+        let handled = matches!(
+            kind,
+            "class" | "subclass" | "race" | "background" | "feat" | "spell" | "weapon"
+        ) || INVENTORY_TYPES.contains(&kind);
+        if !handled {
+            unmapped.push(format!(
+                "items: {} ({})",
+                text(item, "/name").unwrap_or("unnamed"),
+                kind
+            ));
+        }
+    }
+    let effects = array(actor, "/effects").len();
+    if effects > 0 {
+        unmapped.push(format!("effects ({} active effects)", effects));
+    }
+    if let Some(Value::Object(bonuses)) = system.get("bonuses") {
+        if bonuses.values().any(has_formula) {
+            unmapped.push("system.bonuses (global bonuses)".to_string());
+        }
+    }
+    if let Some(Value::Object(resources)) = system.get("resources") {
+        for (key, resource) in resources {
+            if let Some(label) = text(resource, "/label") {
+                unmapped.push(format!("system.resources.{} ({})", key, label));
+            }
+        }
+    }
+
+    let sheet = json!({
+        "character": {
+            "name": text(actor, "/name").unwrap_or("Unnamed"),
+            "class": class,
+            "subclass": subclass,
+            "race": race.unwrap_or(""),
+            "level": level,
+            "experience": int(system, "/details/xp/value"),
+            "alignment": text(system, "/details/alignment"),
+            "background": background,
+            "languages": languages,
+        },
+        "attributes": ability_map(scores),
+        "modifiers": ability_map(mods),
+        "combat": combat,
+        "proficiencyBonus": proficiency,
+        "savingThrows": saving_throws,
+        "skills": skills,
+        "equipment": equipment(system, items, &mods, proficiency),
+        "features": features,
+        "spells": spells,
+    });
+    Ok(FoundryConversion {
+        json: sheet,
+        unmapped,
+    })
+}
+
+/// Convert character sheet JSON to a Foundry VTT actor export.
+pub fn sheet_json_to_foundry(sheet: &Value) -> Result<FoundryConversion, String> {
+    let name = text(sheet, "/character/name").ok_or("The character has no name")?;
+    let mut unmapped = Vec::new();
+
+    let level = int(sheet, "/character/level").max(1);
+    let proficiency = match int(sheet, "/proficiencyBonus") {
+        0 => 2 + (level - 1) / 4,
+        p => p,
+    };
+    let scores = ABILITIES.map(|(name, _)| int(sheet, &format!("/attributes/{}", name)));
+    let mods = scores.map(ability_modifier);
+
+    let mut abilities = Map::new();
+    for (i, (name, key)) in ABILITIES.iter().enumerate() {
+        let save = &sheet["savingThrows"][name];
+        let proficient = save["proficient"].as_bool().unwrap_or(false);
+        let mut ability = json!({
+            "value": scores[i],
+            "proficient": u8::from(proficient),
+        });
+        if let Some(modifier) = save["modifier"].as_i64() {
+            let expected = mods[i] + if proficient { proficiency } else { 0 };
+            let bonus = modifier as i32 - expected;
+            if bonus != 0 {
+                ability["bonuses"] = json!({ "check": "", "save": bonus.to_string() });
+            }
+        }
+        abilities.insert(key.to_string(), ability);
+    }
+
+    let mut skills = Map::new();
+    for (key, sheet_key, ability) in SKILLS {
+        let skill = &sheet["skills"][sheet_key];
+        let multiplier: f64 = if skill["expertise"].as_bool() == Some(true) {
+            2.0
+        } else if skill["proficient"].as_bool() == Some(true) {
+            1.0
+        } else if skill["proficiencyType"].as_str() == Some("half") {
+            0.5
+        } else {
+            0.0
+        };
+        let mut entry = json!({ "value": multiplier, "ability": ABILITIES[ability].1 });
+        if let Some(modifier) = skill["modifier"].as_i64() {
+            let expected = mods[ability] + (proficiency as f64 * multiplier).floor() as i32;
+            let bonus = modifier as i32 - expected;
+            if bonus != 0 {
+                entry["bonuses"] = json!({ "check": bonus.to_string(), "passive": "" });
+            }
+        }
+        skills.insert(key.to_string(), entry);
+    }
+
+    let combat = &sheet["combat"];
+    let spellcasting = text(sheet, "/spells/spellcastingAbility")
+        .and_then(|name| ABILITIES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)))
+        .map(|(_, key)| *key)
+        .unwrap_or("");
+
+    let mut slots = Map::new();
+    if let Some(Value::Object(sheet_slots)) = sheet.pointer("/spells/spellSlots") {
+        for (level, count) in sheet_slots {
+            let level = level.trim_start_matches(|c: char| !c.is_ascii_digit());
+            match (level.parse::<u32>(), count.as_i64()) {
+                (Ok(level @ 1..=9), Some(count)) => {
+                    slots.insert(
+                        format!("spell{}", level),
+                        json!({ "value": count, "override": count }),
+                    );
+                }
+                _ => unmapped.push(format!("spells.spellSlots.{}", level)),
+            }
+        }
+    }
+
+    let languages: Vec<String> = array(sheet, "/character/languages")
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_lowercase)
+        .collect();
+
+    let system = json!({
+        "abilities": abilities,
+        "skills": skills,
+        "attributes": {
+            "ac": { "calc": "flat", "flat": int(combat, "/armorClass") },
+            "hp": {
+                "value": int(combat, "/hitPoints/current"),
+                "max": int(combat, "/hitPoints/maximum"),
+                "temp": int(combat, "/hitPoints/temporary"),
+            },
+            "init": { "bonus": (int(combat, "/initiative") - mods[1]).to_string() },
+            "movement": { "walk": int(combat, "/speed"), "units": "ft" },
+            "death": {
+                "success": int(combat, "/deathSaves/successes"),
+                "failure": int(combat, "/deathSaves/failures"),
+            },
+            "spellcasting": spellcasting,
+        },
+        "details": {
+            "race": text(sheet, "/character/race").unwrap_or(""),
+            "background": text(sheet, "/character/background").unwrap_or(""),
+            "alignment": text(sheet, "/character/alignment").unwrap_or(""),
+            "xp": { "value": int(sheet, "/character/experience") },
+        },
+        "traits": { "languages": { "value": languages, "custom": "" } },
+        "currency": {
+            "cp": int(sheet, "/equipment/currency/copper"),
+            "sp": int(sheet, "/equipment/currency/silver"),
+            "ep": int(sheet, "/equipment/currency/electrum"),
+            "gp": int(sheet, "/equipment/currency/gold"),
+            "pp": int(sheet, "/equipment/currency/platinum"),
+        },
+        "spells": slots,
+    });
+
+    let mut items = Vec::new();
+
+    // Class levels aren't split per class on the sheet, so a multiclass
+    // character gets one class item with every level.
+    let class = text(sheet, "/character/class").unwrap_or("");
+    if !class.is_empty() {
+        let mut class_names = class.split('/').map(str::trim);
+        let first = class_names.next().unwrap_or(class);
+        if class_names.next().is_some() {
+            unmapped.push(format!("character.class (levels of {})", class));
+        }
+        let total = text(combat, "/hitDice/total").unwrap_or("");
+        let hit_die = total
+            .find('d')
+            .map(|i| {
+                total[i..]
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .next()
+                    .unwrap_or("d8")
+            })
+            .unwrap_or("d8");
+        let used = combat
+            .pointer("/hitDice/current")
+            .and_then(Value::as_i64)
+            .map(|current| (level - current as i32).max(0))
+            .unwrap_or(0);
+        items.push(json!({
+            "name": first,
+            "type": "class",
+            "system": {
+                "identifier": first.to_lowercase().replace(' ', "-"),
+                "levels": level,
+                "hitDice": hit_die,
+                "hitDiceUsed": used,
+            },
+        }));
+        if let Some(subclass) = text(sheet, "/character/subclass") {
+            items.push(json!({
+                "name": subclass,
+                "type": "subclass",
+                "system": { "classIdentifier": first.to_lowercase().replace(' ', "-") },
+            }));
+        }
+    }
+
+    for weapon in array(sheet, "/equipment/weapons") {
+        let Some(name) = text(weapon, "/name") else {
+            continue;
+        };
+        items.push(weapon_item(name, weapon, &mods, proficiency));
+    }
+
+    if let Some(armor) = sheet.pointer("/equipment/armor").filter(|a| a.is_object()) {
+        if let Some(name) = text(armor, "/name") {
+            let armor_type = text(armor, "/type")
+                .map(str::to_lowercase)
+                .and_then(|t| {
+                    ["light", "medium", "heavy"]
+                        .into_iter()
+                        .find(|kind| t.contains(kind))
+                })
+                .unwrap_or("light");
+            items.push(json!({
+                "name": name,
+                "type": "equipment",
+                "system": {
+                    "equipped": true,
+                    "type": { "value": armor_type },
+                    "armor": { "value": int(armor, "/armorClass"), "type": armor_type },
+                },
+            }));
+        }
+    }
+
+    // Items are "Name (N)" strings on older sheets
+    for item in array(sheet, "/equipment/items") {
+        let (name, quantity) = match item.as_str() {
+            Some(entry) => split_quantity(entry),
+            None => match text(item, "/name") {
+                Some(name) => (name, item["quantity"].as_i64().unwrap_or(1)),
+                None => continue,
+            },
+        };
+        let flag = |key: &str| item[key].as_bool().unwrap_or(false);
+        items.push(json!({
+            "name": name,
+            "type": "loot",
+            "system": {
+                "quantity": quantity,
+                "weight": { "value": number(item, "/weight").unwrap_or(0.0), "units": "lb" },
+                "price": { "value": number(item, "/value").unwrap_or(0.0), "denomination": "gp" },
+                "equipped": flag("equipped"),
+                "attunement": if flag("requiresAttunement") { "required" } else { "" },
+                "attuned": flag("attuned"),
+            },
+        }));
+    }
+
+    for feature in array(sheet, "/features") {
+        let Some(name) = text(feature, "/name") else {
+            continue;
+        };
+        if text(feature, "/damage").is_some() {
+            unmapped.push(format!("features.{}.damage", name));
+        }
+        items.push(json!({
+            "name": name,
+            "type": "feat",
+            "system": {
+                "description": {
+                    "value": format!("<p>{}</p>", escape_html(text(feature, "/description").unwrap_or(""))),
+                },
+            },
+        }));
+    }
+
+    for spell in array(sheet, "/spells/knownSpells") {
+        if let Some(name) = spell.as_str() {
+            items.push(json!({ "name": name, "type": "spell", "system": {} }));
+        }
+    }
+
+    for field in ["alterEgo", "familyName", "shopName"] {
+        if text(sheet, &format!("/character/{}", field)).is_some() {
+            unmapped.push(format!("character.{}", field));
+        }
+    }
+    for group in ["customBasicInfo", "customAttributes", "customCombat"] {
+        if let Some(Value::Object(fields)) = sheet.get(group) {
+            unmapped.extend(fields.keys().map(|key| format!("{}.{}", group, key)));
+        }
+    }
+    for tool in array(sheet, "/toolProficiencies") {
+        if let Some(name) = text(tool, "/name") {
+            unmapped.push(format!("toolProficiencies.{}", name));
+        }
+    }
+    if let Some(spell) = text(sheet, "/concentration") {
+        unmapped.push(format!("concentration ({})", spell));
+    }
+
+    let actor = json!({
+        "name": name,
+        "type": "character",
+        "system": system,
+        "items": items,
+        "effects": [],
+        "prototypeToken": { "name": name },
+    });
+    Ok(FoundryConversion {
+        json: actor,
+        unmapped,
+    })
+}
+
+/// The actor's or item's data: `system` since Foundry v10, `data` before
+fn system(value: &Value) -> &Value {
+    match value.get("system") {
+        Some(system) if system.is_object() => system,
+        _ => value.get("data").unwrap_or(&Value::Null),
+    }
+}
+
+fn item_system(item: &Value) -> &Value {
+    system(item)
+}
+
+fn items_of_type<'a>(items: &'a [Value], kind: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+    items
+        .iter()
+        .filter(move |item| item["type"].as_str() == Some(kind))
+}
+
+fn text<'a>(value: &'a Value, pointer: &str) -> Option<&'a str> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn int(value: &Value, pointer: &str) -> i32 {
+    match value.pointer(pointer) {
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0) as i32,
+        Some(Value::String(s)) => s.trim().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn array<'a>(value: &'a Value, pointer: &str) -> &'a [Value] {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// Whether a Foundry bonus field holds anything
+fn has_formula(value: &Value) -> bool {
+    match value {
+        Value::String(s) => !s.trim().is_empty(),
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::Object(map) => map.values().any(has_formula),
+        _ => false,
+    }
+}
+
+/// A flat Foundry bonus ("2", "+1", 3). Formulas like "@prof" or "1d4" can't
+/// be stored on the sheet and are reported under `path`.
+fn bonus_value(value: &Value, pointer: &str, path: &str, unmapped: &mut Vec<String>) -> i32 {
+    match value.pointer(pointer) {
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0) as i32,
+        Some(Value::String(s)) => {
+            let s = s.trim();
+            if s.is_empty() {
+                return 0;
+            }
+            match s.trim_start_matches('+').trim().parse::<i32>() {
+                Ok(bonus) => bonus,
+                Err(_) => {
+                    unmapped.push(format!("{} ({})", path, s));
+                    0
+                }
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// D&D modifier for an ability score (rounds down, so 9 is -1)
+fn ability_modifier(score: i32) -> i32 {
+    (score - 10).div_euclid(2)
+}
+
+fn ability_map(values: [i32; 6]) -> Value {
+    let mut map = Map::new();
+    for ((ability, _), value) in ABILITIES.iter().zip(values) {
+        map.insert(ability.to_string(), json!(value));
+    }
+    Value::Object(map)
+}
+
+/// `dice` plus a flat bonus: "1d8+3", "1d4-1", "2d6"
+fn with_bonus(dice: &str, bonus: i32) -> String {
+    match bonus {
+        0 => dice.to_string(),
+        b if b > 0 => format!("{}+{}", dice, b),
+        b => format!("{}{}", dice, b),
+    }
+}
+
+/// Split "1d8+3" into its dice and its flat bonus.
+fn split_bonus(damage: &str) -> (String, i32) {
+    let compact: String = damage.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut dice = Vec::new();
+    let mut bonus = 0;
+    let mut term = String::new();
+    let mut sign = 1;
+    for c in compact.chars().chain(std::iter::once('+')) {
+        if c == '+' || c == '-' {
+            if !term.is_empty() {
+                match term.parse::<i32>() {
+                    Ok(n) => bonus += sign * n,
+                    Err(_) if sign < 0 => dice.push(format!("-{}", term)),
+                    Err(_) => dice.push(term.clone()),
+                }
+                term.clear();
+            }
+            sign = if c == '-' { -1 } else { 1 };
+        } else {
+            term.push(c);
+        }
+    }
+    (dice.join("+").replace("+-", "-"), bonus)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// "Torch (10)" is 10 torches
+fn split_quantity(entry: &str) -> (&str, i64) {
+    if let Some(open) = entry.rfind(" (") {
+        if let Some(quantity) = entry[open + 2..]
+            .strip_suffix(')')
+            .and_then(|q| q.parse::<i64>().ok())
+        {
+            return (&entry[..open], quantity);
+        }
+    }
+    (entry, 1)
+}
+
+/// Property names of a Foundry weapon: a list of codes since dnd5e 3.0, an
+/// object of flags before.
+fn weapon_properties(system: &Value) -> Vec<&'static str> {
+    let codes: Vec<&str> = match system.get("properties") {
+        Some(Value::Array(codes)) => codes.iter().filter_map(Value::as_str).collect(),
+        Some(Value::Object(flags)) => flags
+            .iter()
+            .filter(|(_, on)| on.as_bool() == Some(true))
+            .map(|(code, _)| code.as_str())
+            .collect(),
+        _ => Vec::new(),
+    };
+    WEAPON_PROPERTIES
+        .iter()
+        .filter(|(code, _)| codes.contains(code))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+fn is_equipped(item: &Value) -> bool {
+    item_system(item)["equipped"].as_bool().unwrap_or(false)
+}
+
+/// Armor category of an equipment item ("light", "medium", "heavy",
+/// "shield"), from `system.type.value` (dnd5e 3.0+) or `system.armor.type`
+fn armor_type(item: &Value) -> Option<&str> {
+    let system = item_system(item);
+    text(system, "/type/value")
+        .or_else(|| text(system, "/armor/type"))
+        .filter(|t| matches!(*t, "light" | "medium" | "heavy" | "shield"))
+}
+
+/// Armor class as Foundry calculates it for the actor's `ac.calc`.
+fn armor_class(
+    system: &Value,
+    items: &[Value],
+    mods: &[i32; 6],
+    unmapped: &mut Vec<String>,
+) -> i32 {
+    let ac = &system["attributes"]["ac"];
+    let dex = mods[1];
+    let calc = ac["calc"].as_str().unwrap_or("default");
+    if calc == "flat" {
+        return int(ac, "/flat");
+    }
+
+    let equipped: Vec<&Value> = items_of_type(items, "equipment")
+        .filter(|i| is_equipped(i))
+        .collect();
+    let armor_value = |item: &Value| {
+        let system = item_system(item);
+        int(system, "/armor/value")
+            + int(system, "/armor/magicalBonus")
+            + int(system, "/magicalBonus")
+    };
+    let shields: i32 = equipped
+        .iter()
+        .filter(|i| armor_type(i) == Some("shield"))
+        .map(|i| armor_value(i))
+        .sum();
+
+    let base = match calc {
+        "natural" => int(ac, "/flat"),
+        "mage" | "draconic" => 13 + dex,
+        "unarmoredMonk" => 10 + dex + mods[4],
+        "unarmoredBarb" => 10 + dex + mods[2],
+        other => {
+            if other == "custom" {
+                unmapped.push(format!(
+                    "system.attributes.ac.formula ({})",
+                    text(ac, "/formula").unwrap_or("")
+                ));
+            }
+            equipped
+                .iter()
+                .find(|i| matches!(armor_type(i), Some("light" | "medium" | "heavy")))
+                .map(|armor| {
+                    let cap = item_system(armor)
+                        .pointer("/armor/dex")
+                        .and_then(Value::as_i64)
+                        .map(|d| d as i32)
+                        .or(match armor_type(armor) {
+                            Some("medium") => Some(2),
+                            Some("heavy") => Some(0),
+                            _ => None,
+                        });
+                    armor_value(armor) + cap.map(|c| dex.min(c)).unwrap_or(dex)
+                })
+                .unwrap_or(10 + dex)
+        }
+    };
+    let bonus = bonus_value(ac, "/bonus", "system.attributes.ac.bonus", unmapped);
+    base + shields + bonus
+}
+
+/// Weapons (attack bonus assumes proficiency unless the item says
+/// otherwise), the first worn armor, the rest of the inventory and coins
+fn equipment(system: &Value, items: &[Value], mods: &[i32; 6], proficiency: i32) -> Value {
+    let (strength, dex) = (mods[0], mods[1]);
+    let mut weapons = Vec::new();
+    let mut armor = Value::Null;
+    let mut inventory = Vec::new();
+
+    for item in items {
+        let Some(name) = text(item, "/name") else {
+            continue;
+        };
+        let kind = item["type"].as_str().unwrap_or("");
+        let system = item_system(item);
+
+        if kind == "weapon" {
+            let properties = weapon_properties(system);
+            let ranged = matches!(text(system, "/actionType"), Some("rwak"))
+                || text(system, "/type/value")
+                    .or_else(|| text(system, "/weaponType"))
+                    .is_some_and(|t| t.ends_with('R'));
+            let ability = match text(system, "/ability")
+                .and_then(|key| ABILITIES.iter().position(|(_, k)| *k == key))
+            {
+                Some(index) => mods[index],
+                None if properties.contains(&"Finesse") => strength.max(dex),
+                None if ranged => dex,
+                None => strength,
+            };
+            let proficient = system["proficient"].as_f64().unwrap_or(1.0) >= 1.0;
+            let magic = int(system, "/magicalBonus");
+            let attack = int(system, "/attackBonus") + int(system, "/attack/bonus");
+
+            // dnd5e 4.x: `damage.base`; before: `damage.parts` formulas
+            let (dice, flat, damage_type) = match system.pointer("/damage/base") {
+                Some(base) if base.is_object() => (
+                    format!(
+                        "{}d{}",
+                        int(base, "/number").max(1),
+                        int(base, "/denomination")
+                    ),
+                    int(base, "/bonus"),
+                    base.pointer("/types/0")
+                        .and_then(Value::as_str)
+                        .unwrap_or(""),
+                ),
+                _ => {
+                    let part = system.pointer("/damage/parts/0");
+                    let formula = part
+                        .and_then(|p| p.get(0))
+                        .and_then(Value::as_str)
+                        .unwrap_or("1");
+                    let without_mod: String = formula.replace("@mod", "0");
+                    let (dice, flat) = split_bonus(&without_mod);
+                    (
+                        if dice.is_empty() {
+                            "1".to_string()
+                        } else {
+                            dice
+                        },
+                        flat,
+                        part.and_then(|p| p.get(1))
+                            .and_then(Value::as_str)
+                            .unwrap_or(""),
+                    )
+                }
+            };
+
+            weapons.push(json!({
+                "name": name,
+                "attackBonus": ability + magic + attack + if proficient { proficiency } else { 0 },
+                "damage": with_bonus(&dice, ability + magic + flat),
+                "damageType": capitalize(damage_type),
+                "properties": properties,
+            }));
+            continue;
+        }
+
+        if kind == "equipment"
+            && armor.is_null()
+            && is_equipped(item)
+            && matches!(armor_type(item), Some("light" | "medium" | "heavy"))
+        {
+            let ac = int(system, "/armor/value") + int(system, "/armor/magicalBonus");
+            let with_dex = match armor_type(item) {
+                Some("light") => ac + dex,
+                Some("medium") => ac + dex.min(2),
+                _ => ac,
+            };
+            armor = json!({
+                "name": name,
+                "armorClass": ac,
+                "armorClassWithDex": with_dex,
+                "type": format!("{} Armor", capitalize(armor_type(item).unwrap_or(""))),
+            });
+            continue;
+        }
+
+        if INVENTORY_TYPES.contains(&kind) {
+            inventory.push(inventory_item(name, item));
+        }
+    }
+
+    json!({
+        "weapons": weapons,
+        "armor": armor,
+        "items": inventory,
+        "currency": {
+            "copper": int(system, "/currency/cp"),
+            "silver": int(system, "/currency/sp"),
+            "electrum": int(system, "/currency/ep"),
+            "gold": int(system, "/currency/gp"),
+            "platinum": int(system, "/currency/pp"),
+        },
+    })
+}
+
+/// A Foundry item as a sheet inventory item; values left at their defaults
+/// are left out, as the sheet saves them
+fn inventory_item(name: &str, item: &Value) -> Value {
+    let system = item_system(item);
+    let mut entry = json!({ "name": name });
+    if let Some(quantity) = system["quantity"].as_i64().filter(|q| *q > 1) {
+        entry["quantity"] = json!(quantity);
+    }
+
+    // dnd5e 4.x: `weight.value` and `price.value` in `price.denomination`;
+    // before: plain numbers, prices in gold
+    let weight = number(system, "/weight/value").or_else(|| number(system, "/weight"));
+    if let Some(weight) = weight.filter(|w| *w > 0.0) {
+        entry["weight"] = json!(weight);
+    }
+    let price = match number(system, "/price/value") {
+        Some(value) => {
+            let per_gold = match text(system, "/price/denomination") {
+                Some("cp") => 0.01,
+                Some("sp") => 0.1,
+                Some("ep") => 0.5,
+                Some("pp") => 10.0,
+                _ => 1.0,
+            };
+            Some(value * per_gold)
+        }
+        None => number(system, "/price"),
+    };
+    if let Some(price) = price.filter(|p| *p > 0.0) {
+        entry["value"] = json!(price);
+    }
+    if is_equipped(item) {
+        entry["equipped"] = json!(true);
+    }
+
+    // `attunement` is "required"/"optional" since dnd5e 3.0 with a separate
+    // `attuned` flag; before, 1 for required and 2 for attuned
+    let (requires, attuned) = match &system["attunement"] {
+        Value::String(s) => (s == "required", system["attuned"].as_bool() == Some(true)),
+        Value::Number(n) => (n.as_i64() >= Some(1), n.as_i64() == Some(2)),
+        _ => (false, false),
+    };
+    if requires {
+        entry["requiresAttunement"] = json!(true);
+    }
+    if attuned {
+        entry["attuned"] = json!(true);
+    }
+    entry
+}
+
+fn number(value: &Value, pointer: &str) -> Option<f64> {
+    value.pointer(pointer).and_then(Value::as_f64)
+}
+
+/// A sheet weapon as a Foundry weapon item. Foundry adds the ability
+/// modifier and proficiency itself, so only what's left over is stored.
+fn weapon_item(name: &str, weapon: &Value, mods: &[i32; 6], proficiency: i32) -> Value {
+    let properties: Vec<&str> = array(weapon, "/properties")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    let codes: Vec<&str> = WEAPON_PROPERTIES
+        .iter()
+        .filter(|(_, property)| properties.iter().any(|p| p.eq_ignore_ascii_case(property)))
+        .map(|(code, _)| *code)
+        .collect();
+    let ranged = properties
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case("Ammunition") || p.to_lowercase().starts_with("range"));
+    let (ability_key, ability) = if codes.contains(&"fin") {
+        if mods[1] > mods[0] {
+            ("dex", mods[1])
+        } else {
+            ("str", mods[0])
+        }
+    } else if ranged {
+        ("dex", mods[1])
+    } else {
+        ("str", mods[0])
+    };
+
+    let attack = int(weapon, "/attackBonus") - ability - proficiency;
+    let (dice, flat) = split_bonus(text(weapon, "/damage").unwrap_or("1"));
+    let extra = flat - ability;
+    let formula = match extra {
+        0 => format!("{} + @mod", dice),
+        e if e > 0 => format!("{} + @mod + {}", dice, e),
+        e => format!("{} + @mod - {}", dice, -e),
+    };
+
+    json!({
+        "name": name,
+        "type": "weapon",
+        "system": {
+            "equipped": true,
+            "proficient": 1,
+            "ability": ability_key,
+            "actionType": if ranged { "rwak" } else { "mwak" },
+            "attackBonus": if attack == 0 { String::new() } else { attack.to_string() },
+            "damage": {
+                "parts": [[formula, text(weapon, "/damageType").unwrap_or("").to_lowercase()]],
+            },
+            "properties": codes,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet_schema::check_character_sheet;
+
+    /// Trimmed Foundry export of a level 5 hill dwarf fighter
+    fn foundry_actor() -> Value {
+        json!({
+            "name": "Thorin",
+            "type": "character",
+            "system": {
+                "abilities": {
+                    "str": { "value": 15, "proficient": 1 },
+                    "dex": { "value": 12, "proficient": 0 },
+                    "con": { "value": 16, "proficient": 1, "bonuses": { "save": "+1" } },
+                    "int": { "value": 9, "proficient": 0 },
+                    "wis": { "value": 14, "proficient": 0 },
+                    "cha": { "value": 8, "proficient": 0 }
+                },
+                "skills": {
+                    "ath": { "value": 1, "ability": "str" },
+                    "prc": { "value": 2, "ability": "wis" },
+                    "ste": { "value": 0, "ability": "dex", "bonuses": { "check": "@prof" } }
+                },
+                "attributes": {
+                    "ac": { "calc": "default", "bonus": "1" },
+                    "hp": { "value": 54, "max": 64, "temp": 3 },
+                    "init": { "bonus": "" },
+                    "movement": { "walk": 25, "units": "ft" },
+                    "death": { "success": 0, "failure": 1 }
+                },
+                "details": {
+                    "alignment": "Lawful Good",
+                    "xp": { "value": 6500 }
+                },
+                "traits": { "languages": { "value": ["common", "dwarvish"], "custom": "Thieves' Cant" } },
+                "currency": { "pp": 1, "gp": 42, "ep": 0, "sp": 0, "cp": 5 },
+                "resources": { "primary": { "label": "Superiority Dice", "value": 4, "max": 4 } }
+            },
+            "items": [
+                { "name": "Fighter", "type": "class", "system": { "levels": 5, "hitDice": "d10", "hitDiceUsed": 1 } },
+                { "name": "Champion", "type": "subclass", "system": {} },
+                { "name": "Hill Dwarf", "type": "race", "system": {} },
+                { "name": "Soldier", "type": "background", "system": {} },
+                { "name": "Longsword", "type": "weapon", "system": {
+                    "equipped": true, "proficient": 1, "actionType": "mwak",
+                    "damage": { "parts": [["1d8 + @mod", "slashing"]] },
+                    "properties": ["ver"]
+                } },
+                { "name": "Rapier +1", "type": "weapon", "system": {
+                    "equipped": true, "proficient": 1, "actionType": "mwak", "magicalBonus": 1,
+                    "damage": { "parts": [["1d8 + @mod", "piercing"]] },
+                    "properties": ["fin"]
+                } },
+                { "name": "Chain Mail", "type": "equipment", "system": {
+                    "equipped": true, "armor": { "value": 16, "type": "heavy", "dex": 0 }
+                } },
+                { "name": "Shield", "type": "equipment", "system": {
+                    "equipped": true, "armor": { "value": 2, "type": "shield" }
+                } },
+                { "name": "Torch", "type": "loot", "system": {
+                    "quantity": 10, "weight": { "value": 1, "units": "lb" }, "price": { "value": 1, "denomination": "cp" }
+                } },
+                { "name": "Second Wind", "type": "feat", "system": { "description": { "value": "<p>Regain 1d10 + 5 HP.</p>" } } },
+                { "name": "Lucky Charm", "type": "facility", "system": {} }
+            ],
+            "effects": [{ "name": "Blessed" }]
+        })
+    }
+
+    #[test]
+    fn test_is_foundry_actor() {
+        assert!(is_foundry_actor(&foundry_actor()));
+        assert!(!is_foundry_actor(
+            &json!({ "character": { "name": "Thorin" } })
+        ));
+    }
+
+    #[test]
+    fn test_foundry_import() {
+        let FoundryConversion {
+            json: sheet,
+            unmapped,
+        } = foundry_to_sheet_json(&foundry_actor()).unwrap();
+        assert_eq!(sheet["character"]["name"], "Thorin");
+        assert_eq!(sheet["character"]["class"], "Fighter");
+        assert_eq!(sheet["character"]["subclass"], "Champion");
+        assert_eq!(sheet["character"]["race"], "Hill Dwarf");
+        assert_eq!(sheet["character"]["background"], "Soldier");
+        assert_eq!(
+            sheet["character"]["languages"],
+            json!(["Common", "Dwarvish", "Thieves' Cant"])
+        );
+        assert_eq!(sheet["proficiencyBonus"], 3);
+
+        assert_eq!(sheet["savingThrows"]["strength"]["modifier"], 5);
+        // CON +3, proficiency +3, flat save bonus +1
+        assert_eq!(sheet["savingThrows"]["constitution"]["modifier"], 7);
+        assert_eq!(sheet["skills"]["athletics"]["modifier"], 5);
+        assert_eq!(sheet["skills"]["perception"]["modifier"], 8);
+        assert_eq!(sheet["skills"]["perception"]["expertise"], true);
+        assert_eq!(sheet["skills"]["stealth"]["modifier"], 1);
+
+        // Chain mail 16 + shield 2 + bonus 1
+        assert_eq!(sheet["combat"]["armorClass"], 19);
+        assert_eq!(sheet["combat"]["hitPoints"]["current"], 54);
+        assert_eq!(sheet["combat"]["hitDice"]["total"], "5d10");
+        assert_eq!(sheet["combat"]["hitDice"]["current"], 4);
+
+        let weapons = &sheet["equipment"]["weapons"];
+        assert_eq!(weapons[0]["attackBonus"], 5);
+        assert_eq!(weapons[0]["damage"], "1d8+2");
+        assert_eq!(weapons[0]["damageType"], "Slashing");
+        assert_eq!(weapons[1]["attackBonus"], 6);
+        assert_eq!(weapons[1]["damage"], "1d8+3");
+        assert_eq!(weapons[1]["properties"], json!(["Finesse"]));
+        assert_eq!(sheet["equipment"]["armor"]["name"], "Chain Mail");
+        assert_eq!(
+            sheet["equipment"]["items"],
+            json!([
+                { "name": "Shield", "equipped": true },
+                { "name": "Torch", "quantity": 10, "weight": 1.0, "value": 0.01 }
+            ])
+        );
+        assert_eq!(sheet["features"][0]["description"], "Regain 1d10 + 5 HP.");
+
+        assert!(unmapped.contains(&"system.skills.ste.bonuses.check (@prof)".to_string()));
+        assert!(unmapped.contains(&"items: Lucky Charm (facility)".to_string()));
+        assert!(unmapped.contains(&"effects (1 active effects)".to_string()));
+        assert!(unmapped.contains(&"system.resources.primary (Superiority Dice)".to_string()));
+
+        check_character_sheet(&sheet).unwrap();
+        assert_eq!(sheet["character"]["level"], 5);
+    }
+
+    #[test]
+    fn test_foundry_round_trip() {
+        let sheet = foundry_to_sheet_json(&foundry_actor()).unwrap().json;
+        let FoundryConversion {
+            json: actor,
+            unmapped,
+        } = sheet_json_to_foundry(&sheet).unwrap();
+        assert!(unmapped.is_empty(), "{:?}", unmapped);
+        assert!(is_foundry_actor(&actor));
+        assert_eq!(actor["system"]["abilities"]["con"]["bonuses"]["save"], "1");
+        assert_eq!(actor["system"]["skills"]["prc"]["value"], 2.0);
+        assert_eq!(actor["system"]["attributes"]["ac"]["flat"], 19);
+
+        let back = foundry_to_sheet_json(&actor).unwrap().json;
+        for pointer in [
+            "/character/name",
+            "/character/class",
+            "/character/subclass",
+            "/attributes",
+            "/savingThrows",
+            "/skills",
+            "/combat",
+            "/equipment/weapons",
+            "/equipment/items",
+            "/equipment/currency",
+        ] {
+            assert_eq!(back.pointer(pointer), sheet.pointer(pointer), "{}", pointer);
+        }
+    }
+
+    #[test]
+    fn test_foundry_export_reports_unmapped_fields() {
+        let sheet = json!({
+            "character": { "name": "Lyra", "class": "Bard / Warlock", "level": 4, "alterEgo": "The Masked Lute" },
+            "attributes": { "strength": 8, "dexterity": 14, "constitution": 12, "intelligence": 10, "wisdom": 10, "charisma": 17 },
+            "combat": { "armorClass": 13, "initiative": 2, "speed": 30 },
+            "customCombat": { "Bardic Inspiration": "d6" },
+            "toolProficiencies": [{ "name": "Lute" }],
+            "equipment": { "weapons": [{ "name": "Rapier", "attackBonus": 4, "damage": "1d8+2", "damageType": "Piercing", "properties": ["Finesse"] }] }
+        });
+        let FoundryConversion {
+            json: actor,
+            unmapped,
+        } = sheet_json_to_foundry(&sheet).unwrap();
+        assert_eq!(
+            unmapped,
+            vec![
+                "character.class (levels of Bard / Warlock)",
+                "character.alterEgo",
+                "customCombat.Bardic Inspiration",
+                "toolProficiencies.Lute",
+            ]
+        );
+        let rapier = &actor["items"][1]["system"];
+        assert_eq!(rapier["ability"], "dex");
+        assert_eq!(rapier["attackBonus"], "");
+        assert_eq!(rapier["damage"]["parts"][0][0], "1d8 + @mod");
+    }
+
+    #[test]
+    fn test_split_bonus() {
+        assert_eq!(split_bonus("1d8 + 3"), ("1d8".to_string(), 3));
+        assert_eq!(split_bonus("2d6-1"), ("2d6".to_string(), -1));
+        assert_eq!(split_bonus("1d8+1d6+2"), ("1d8+1d6".to_string(), 2));
+        assert_eq!(split_bonus("1d8 + 0"), ("1d8".to_string(), 0));
+    }
+}
@@ -0,0 +1,581 @@
+//! Inventory
+//!
+//! The items a character carries besides weapons and armor: how many, what
+//! one weighs (pounds) and is worth (gold pieces), whether it's equipped and
+//! whether it's attuned, plus the carrying capacity a Strength score gives
+//! and the variant encumbrance rules. Older sheets store items as plain
+//! strings ("Arrows (20)"); those still load, as one item each.
+//!
+//! Magic items can carry bonuses ("+1 saves", "+2 spell attacks") that add
+//! to the character's rolls while the item is attuned, or equipped for items
+//! that don't need attunement.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Magic items a character can be attuned to at once, unless their sheet
+/// gives them more slots (an artificer's, say)
+pub const DEFAULT_ATTUNEMENT_SLOTS: usize = 3;
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(quantity: &u32) -> bool {
+    *quantity == 1
+}
+
+fn is_zero(amount: &f32) -> bool {
+    *amount == 0.0
+}
+
+fn is_false(flag: &bool) -> bool {
+    !*flag
+}
+
+/// An item in the inventory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryItem {
+    pub name: String,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub quantity: u32,
+    /// Weight of one, in pounds
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub weight: f32,
+    /// Value of one, in gold pieces
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub value: f32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub equipped: bool,
+    /// The item only works for a character attuned to it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub requires_attunement: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub attuned: bool,
+    /// What the item adds to rolls while it's in use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bonuses: Vec<ItemBonus>,
+}
+
+impl InventoryItem {
+    /// One of an item, weightless and worthless until filled in
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            quantity: 1,
+            weight: 0.0,
+            value: 0.0,
+            equipped: false,
+            requires_attunement: false,
+            attuned: false,
+            bonuses: Vec::new(),
+        }
+    }
+
+    /// Whether the item's bonuses apply: attuned when it needs attunement,
+    /// equipped otherwise
+    pub fn is_active(&self) -> bool {
+        if self.requires_attunement {
+            self.attuned
+        } else {
+            self.equipped
+        }
+    }
+
+    /// An item from the "Name (N)" form the importers and older sheets use
+    pub fn parse(entry: &str) -> Self {
+        let (name, quantity) = split_item_count(entry.trim());
+        Self {
+            quantity,
+            ..Self::new(name)
+        }
+    }
+
+    /// Weight of the whole stack
+    pub fn total_weight(&self) -> f32 {
+        self.weight * self.quantity as f32
+    }
+
+    /// Value of the whole stack
+    pub fn total_value(&self) -> f32 {
+        self.value * self.quantity as f32
+    }
+}
+
+impl fmt::Display for InventoryItem {
+    /// "Arrows (20)", or just the name for a single item
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.quantity {
+            1 => write!(f, "{}", self.name),
+            n => write!(f, "{} ({})", self.name, n),
+        }
+    }
+}
+
+/// "Arrows (20)" is 20 arrows; an item without a count is one
+pub fn split_item_count(item: &str) -> (&str, u32) {
+    if let Some(open) = item.rfind(" (") {
+        if let Some(count) = item[open + 2..]
+            .strip_suffix(')')
+            .and_then(|n| n.trim().parse::<u32>().ok())
+        {
+            return (&item[..open], count);
+        }
+    }
+    (item, 1)
+}
+
+/// A stored item: a full record or an older "Name (N)" string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ItemEntry {
+    Name(String),
+    Item(InventoryItem),
+}
+
+/// Read an item list that may mix records and "Name (N)" strings; used as
+/// the `deserialize_with` of `Equipment::items`.
+pub fn deserialize_items<'de, D>(deserializer: D) -> Result<Vec<InventoryItem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries = Vec::<ItemEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            ItemEntry::Name(name) => InventoryItem::parse(&name),
+            ItemEntry::Item(item) => item,
+        })
+        .collect())
+}
+
+/// Total weight of the items, in pounds
+pub fn inventory_weight(items: &[InventoryItem]) -> f32 {
+    items.iter().map(InventoryItem::total_weight).sum()
+}
+
+/// Total value of the items, in gold pieces
+pub fn inventory_value(items: &[InventoryItem]) -> f32 {
+    items.iter().map(InventoryItem::total_value).sum()
+}
+
+/// How many items the character is attuned to
+pub fn attuned_count(items: &[InventoryItem]) -> usize {
+    items.iter().filter(|item| item.attuned).count()
+}
+
+/// Attune to an item or end the attunement; returns `true` when the
+/// character is now attuned. Fails for items that don't need attunement and
+/// once every one of the character's `slots` is taken.
+pub fn toggle_attunement(
+    items: &mut [InventoryItem],
+    name: &str,
+    slots: usize,
+) -> Result<bool, String> {
+    let attuned = attuned_count(items);
+    let item = items
+        .iter_mut()
+        .find(|item| item.name == name)
+        .ok_or_else(|| format!("No item named {}", name))?;
+    if item.attuned {
+        item.attuned = false;
+        return Ok(false);
+    }
+    if !item.requires_attunement {
+        return Err(format!("{} doesn't require attunement", item.name));
+    }
+    if attuned >= slots {
+        return Err(format!(
+            "Already attuned to {} of {} items; end an attunement first",
+            attuned, slots
+        ));
+    }
+    item.attuned = true;
+    Ok(true)
+}
+
+/// Warning to show when more items are attuned than the character has
+/// slots for (after an import, or when slots were taken away)
+pub fn attunement_warning(items: &[InventoryItem], slots: usize) -> Option<String> {
+    let attuned = attuned_count(items);
+    (attuned > slots).then(|| {
+        format!(
+            "Attuned to {} items but has {} slots: end {} attunement{}",
+            attuned,
+            slots,
+            attuned - slots,
+            if attuned - slots == 1 { "" } else { "s" }
+        )
+    })
+}
+
+/// What a magic item's bonus adds to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BonusTarget {
+    /// Every saving throw
+    SavingThrows,
+    /// Saving throws of one ability ("dexterity")
+    Save(&'static str),
+    /// Every ability check, skill checks included
+    AbilityChecks,
+    /// Checks of one skill, as written ("stealth")
+    Skill(String),
+    AttackRolls,
+    SpellAttacks,
+    SpellSaveDc,
+}
+
+/// A roll (or the spell save DC) item bonuses can add to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BonusRoll<'a> {
+    /// A saving throw of this ability ("dex" or "dexterity")
+    Save(&'a str),
+    AbilityCheck,
+    /// A check of this skill ("sleightOfHand" or "sleight of hand")
+    Skill(&'a str),
+    Attack,
+    SpellAttack,
+    SpellSaveDc,
+}
+
+/// A bonus a magic item gives, written "+1 saves" or "+2 dex save"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ItemBonus {
+    pub amount: i32,
+    pub target: BonusTarget,
+}
+
+/// Full lowercase name of an ability or its abbreviation
+fn ability_name(name: &str) -> Option<&'static str> {
+    match name.trim().to_lowercase().as_str() {
+        "str" | "strength" => Some("strength"),
+        "dex" | "dexterity" => Some("dexterity"),
+        "con" | "constitution" => Some("constitution"),
+        "int" | "intelligence" => Some("intelligence"),
+        "wis" | "wisdom" => Some("wisdom"),
+        "cha" | "charisma" => Some("charisma"),
+        _ => None,
+    }
+}
+
+/// A skill name without case or spacing, so "sleightOfHand" and "Sleight
+/// of Hand" match
+fn skill_key(name: &str) -> String {
+    name.to_lowercase().replace([' ', '_'], "")
+}
+
+impl ItemBonus {
+    /// Read a bonus: "+1 saves", "+1 dex save", "+1 checks", "+2 stealth",
+    /// "+1 attacks", "+2 spell attacks" or "+1 spell dc".
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (amount, target) = text
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("Expected a bonus like +1 saves, found \"{}\"", text))?;
+        let amount: i32 = amount
+            .trim_start_matches('+')
+            .parse()
+            .map_err(|_| format!("Expected a bonus like +1 saves, found \"{}\"", text))?;
+        let target = target.trim().to_lowercase();
+        let target = match target.as_str() {
+            "saves" | "saving throws" => BonusTarget::SavingThrows,
+            "checks" | "ability checks" => BonusTarget::AbilityChecks,
+            "attacks" | "attack rolls" => BonusTarget::AttackRolls,
+            "spell attacks" => BonusTarget::SpellAttacks,
+            "spell dc" | "spell save dc" => BonusTarget::SpellSaveDc,
+            "ac" | "armor class" => {
+                return Err(
+                    "AC isn't rolled; add the bonus to the sheet's Armor Class instead".to_string(),
+                )
+            }
+            other => match other
+                .strip_suffix(" saves")
+                .or_else(|| other.strip_suffix(" save"))
+                .and_then(ability_name)
+            {
+                Some(ability) => BonusTarget::Save(ability),
+                None => BonusTarget::Skill(other.to_string()),
+            },
+        };
+        Ok(Self { amount, target })
+    }
+
+    /// Whether the bonus adds to `roll`
+    pub fn applies_to(&self, roll: BonusRoll) -> bool {
+        match (&self.target, roll) {
+            (BonusTarget::SavingThrows, BonusRoll::Save(_)) => true,
+            (BonusTarget::Save(ability), BonusRoll::Save(save)) => {
+                ability_name(save) == Some(*ability)
+            }
+            (BonusTarget::AbilityChecks, BonusRoll::AbilityCheck | BonusRoll::Skill(_)) => true,
+            (BonusTarget::Skill(skill), BonusRoll::Skill(rolled)) => {
+                skill_key(skill) == skill_key(rolled)
+            }
+            (BonusTarget::AttackRolls, BonusRoll::Attack) => true,
+            (BonusTarget::SpellAttacks, BonusRoll::SpellAttack) => true,
+            (BonusTarget::SpellSaveDc, BonusRoll::SpellSaveDc) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ItemBonus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:+} ", self.amount)?;
+        match &self.target {
+            BonusTarget::SavingThrows => write!(f, "saves"),
+            BonusTarget::Save(ability) => write!(f, "{} save", &ability[..3]),
+            BonusTarget::AbilityChecks => write!(f, "checks"),
+            BonusTarget::Skill(skill) => write!(f, "{}", skill),
+            BonusTarget::AttackRolls => write!(f, "attacks"),
+            BonusTarget::SpellAttacks => write!(f, "spell attacks"),
+            BonusTarget::SpellSaveDc => write!(f, "spell dc"),
+        }
+    }
+}
+
+impl TryFrom<String> for ItemBonus {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Self::parse(&text)
+    }
+}
+
+impl From<ItemBonus> for String {
+    fn from(bonus: ItemBonus) -> Self {
+        bonus.to_string()
+    }
+}
+
+/// Read a comma separated list of bonuses ("+1 saves, +1 dex save"); an
+/// empty list clears them.
+pub fn parse_item_bonuses(text: &str) -> Result<Vec<ItemBonus>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|bonus| !bonus.is_empty())
+        .map(ItemBonus::parse)
+        .collect()
+}
+
+/// Bonuses written back the way [`parse_item_bonuses`] reads them
+pub fn format_item_bonuses(bonuses: &[ItemBonus]) -> String {
+    bonuses
+        .iter()
+        .map(ItemBonus::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Total the bonuses of the items in use add to `roll`
+pub fn item_bonus(items: &[InventoryItem], roll: BonusRoll) -> i32 {
+    items
+        .iter()
+        .filter(|item| item.is_active())
+        .flat_map(|item| &item.bonuses)
+        .filter(|bonus| bonus.applies_to(roll))
+        .map(|bonus| bonus.amount)
+        .sum()
+}
+
+/// Pounds a Strength score can carry (15 × Strength)
+pub fn carrying_capacity(strength: i32) -> f32 {
+    15.0 * strength.max(0) as f32
+}
+
+/// How weighed down a character is, by the variant encumbrance rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encumbrance {
+    #[default]
+    Unencumbered,
+    /// Over 5 × Strength: speed drops by 10 feet
+    Encumbered,
+    /// Over 10 × Strength: speed drops by 20 feet, and disadvantage on
+    /// Strength, Dexterity and Constitution ability checks, attack rolls and
+    /// saving throws
+    HeavilyEncumbered,
+    /// Over the carrying capacity: the character can't move
+    OverCapacity,
+}
+
+impl Encumbrance {
+    /// Encumbrance of carrying `weight` pounds with a Strength score
+    pub fn of(weight: f32, strength: i32) -> Self {
+        let capacity = carrying_capacity(strength);
+        let strength = strength.max(0) as f32;
+        if weight > capacity {
+            Encumbrance::OverCapacity
+        } else if weight > 10.0 * strength {
+            Encumbrance::HeavilyEncumbered
+        } else if weight > 5.0 * strength {
+            Encumbrance::Encumbered
+        } else {
+            Encumbrance::Unencumbered
+        }
+    }
+
+    /// Feet taken off the character's speed
+    pub fn speed_penalty(&self) -> i32 {
+        match self {
+            Encumbrance::Unencumbered => 0,
+            Encumbrance::Encumbered => 10,
+            Encumbrance::HeavilyEncumbered => 20,
+            Encumbrance::OverCapacity => 0,
+        }
+    }
+
+    /// Warning to show the player; `None` while unencumbered
+    pub fn warning(&self) -> Option<&'static str> {
+        match self {
+            Encumbrance::Unencumbered => None,
+            Encumbrance::Encumbered => Some("Encumbered: speed -10 ft"),
+            Encumbrance::HeavilyEncumbered => {
+                Some("Heavily encumbered: speed -20 ft, disadvantage on Str, Dex and Con rolls")
+            }
+            Encumbrance::OverCapacity => Some("Over carrying capacity: can't move"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_items_load() {
+        let json = serde_json::json!([
+            "Rope",
+            "Arrows (20)",
+            { "name": "Cloak of Protection", "weight": 1, "value": 3500, "requiresAttunement": true, "attuned": true }
+        ]);
+        let items = deserialize_items(json).unwrap();
+        assert_eq!(items[0], InventoryItem::new("Rope"));
+        assert_eq!((items[1].name.as_str(), items[1].quantity), ("Arrows", 20));
+        assert_eq!(items[1].to_string(), "Arrows (20)");
+        assert!(items[2].attuned && items[2].requires_attunement);
+
+        // Defaults are left out when saved
+        assert_eq!(
+            serde_json::to_value(&items[1]).unwrap(),
+            serde_json::json!({ "name": "Arrows", "quantity": 20 })
+        );
+        assert_eq!(split_item_count("Rope (50 feet)"), ("Rope (50 feet)", 1));
+    }
+
+    #[test]
+    fn test_weight_value_and_attunement() {
+        let mut items = vec![
+            InventoryItem {
+                quantity: 10,
+                weight: 1.0,
+                value: 0.01,
+                ..InventoryItem::new("Torch")
+            },
+            InventoryItem {
+                weight: 20.0,
+                value: 50.0,
+                ..InventoryItem::new("Backpack")
+            },
+            InventoryItem {
+                requires_attunement: true,
+                ..InventoryItem::new("Ring of Protection")
+            },
+        ];
+        assert_eq!(inventory_weight(&items), 30.0);
+        assert!((inventory_value(&items) - 50.1).abs() < 0.001);
+
+        let slots = DEFAULT_ATTUNEMENT_SLOTS;
+        assert_eq!(
+            toggle_attunement(&mut items, "Ring of Protection", slots),
+            Ok(true)
+        );
+        assert_eq!(attuned_count(&items), 1);
+        assert!(toggle_attunement(&mut items, "Torch", slots).is_err());
+        assert_eq!(
+            toggle_attunement(&mut items, "Ring of Protection", slots),
+            Ok(false)
+        );
+
+        let mut rings: Vec<InventoryItem> = (1..=4)
+            .map(|n| InventoryItem {
+                requires_attunement: true,
+                ..InventoryItem::new(format!("Ring {}", n))
+            })
+            .collect();
+        for n in 1..=3 {
+            assert_eq!(
+                toggle_attunement(&mut rings, &format!("Ring {}", n), slots),
+                Ok(true)
+            );
+        }
+        assert!(toggle_attunement(&mut rings, "Ring 4", slots).is_err());
+        assert_eq!(attunement_warning(&rings, slots), None);
+
+        // More slots let the fourth ring in; fewer warn
+        assert_eq!(toggle_attunement(&mut rings, "Ring 4", 4), Ok(true));
+        assert_eq!(
+            attunement_warning(&rings, 2).as_deref(),
+            Some("Attuned to 4 items but has 2 slots: end 2 attunements")
+        );
+    }
+
+    #[test]
+    fn test_item_bonuses() {
+        let bonuses = parse_item_bonuses("+1 saves, +2 DEX save, -1 Sleight of Hand").unwrap();
+        assert_eq!(
+            format_item_bonuses(&bonuses),
+            "+1 saves, +2 dex save, -1 sleight of hand"
+        );
+        assert!(ItemBonus::parse("+1 ac").is_err());
+        assert!(ItemBonus::parse("saves").is_err());
+        assert_eq!(parse_item_bonuses(" ").unwrap(), Vec::new());
+
+        let mut items = vec![
+            InventoryItem {
+                requires_attunement: true,
+                bonuses: bonuses.clone(),
+                ..InventoryItem::new("Cloak of Protection")
+            },
+            InventoryItem {
+                bonuses: parse_item_bonuses("+1 attacks, +1 checks").unwrap(),
+                ..InventoryItem::new("Luckstone")
+            },
+        ];
+        // Nothing applies until attuned or equipped
+        assert_eq!(item_bonus(&items, BonusRoll::Save("dex")), 0);
+
+        items[0].attuned = true;
+        assert_eq!(item_bonus(&items, BonusRoll::Save("dexterity")), 3);
+        assert_eq!(item_bonus(&items, BonusRoll::Save("wis")), 1);
+        assert_eq!(item_bonus(&items, BonusRoll::Skill("sleightOfHand")), -1);
+        assert_eq!(item_bonus(&items, BonusRoll::Attack), 0);
+
+        items[1].equipped = true;
+        assert_eq!(item_bonus(&items, BonusRoll::Attack), 1);
+        assert_eq!(item_bonus(&items, BonusRoll::Skill("stealth")), 1);
+        assert_eq!(item_bonus(&items, BonusRoll::Skill("sleight of hand")), 0);
+        assert_eq!(item_bonus(&items, BonusRoll::SpellSaveDc), 0);
+
+        // Saved as the text they're typed as
+        assert_eq!(
+            serde_json::to_value(&items[1]).unwrap(),
+            serde_json::json!({
+                "name": "Luckstone",
+                "equipped": true,
+                "bonuses": ["+1 attacks", "+1 checks"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_encumbrance() {
+        assert_eq!(carrying_capacity(10), 150.0);
+        assert_eq!(Encumbrance::of(50.0, 10), Encumbrance::Unencumbered);
+        assert_eq!(Encumbrance::of(51.0, 10), Encumbrance::Encumbered);
+        assert_eq!(Encumbrance::of(101.0, 10).speed_penalty(), 20);
+        assert_eq!(Encumbrance::of(151.0, 10), Encumbrance::OverCapacity);
+        assert_eq!(Encumbrance::Unencumbered.warning(), None);
+    }
+}
@@ -0,0 +1,68 @@
+//! DnD Game Rolls core
+//!
+//! The dice, the rolling and the rules shared by the 3D app, the
+//! `dndgamerolls` and `dndrolls` command lines and third-party tools:
+//! - `abilities` - Ability names and their abbreviations
+//! - `cli` - What both command lines print: `--quiet`, attack and damage
+//!   rolls, damage riders and weapon mastery (`cli` feature)
+//! - `conditions` - Conditions and exhaustion, and the advantage,
+//!   disadvantage or penalty they give d20 tests
+//! - `currency` - Coins, and adding, spending and exchanging them
+//! - `damage_riders` - Sneak Attack, Divine Smite, Hunter's Mark and custom
+//!   extra damage on weapon hits
+//! - `damage_types` - The standard and custom damage types, resistances,
+//!   immunities and vulnerabilities
+//! - `ddb_import` - Importing D&D Beyond character exports
+//! - `dice` - Dice types: d4-d20, Fudge/FATE dice, coins and custom dice
+//! - `dice_expression` - Compound dice expressions (`2d6+1d8+3`, `4d6kh3`)
+//! - `engine` - `RollEngine`, rolling a `RollRequest` into a `RollResult`
+//! - `foundry` - Foundry VTT actor import and export
+//! - `inventory` - Inventory items, attunement, carrying capacity and
+//!   encumbrance
+//! - `ruleset` - The campaign's rules edition (2014 or 2024)
+//! - `sheet_schema` - The character sheet JSON Schema and friendly validation
+//! - `weapon_mastery` - Weapon mastery properties of the 2024 rules
+//! - `weapon_properties` - Weapon properties, the attacks they give and the
+//!   ammunition they spend
+//!
+//! ```
+//! use dndgamerolls_core::{RollEngine, RollRequest};
+//!
+//! let request = RollRequest::parse("1d20+5").unwrap().advantage(true);
+//! let result = RollEngine::new().roll(&request).unwrap();
+//! assert!((6..=25).contains(&result.total));
+//! ```
+
+pub mod abilities;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod conditions;
+pub mod currency;
+pub mod damage_riders;
+pub mod damage_types;
+pub mod ddb_import;
+pub mod dice;
+pub mod dice_expression;
+pub mod engine;
+pub mod foundry;
+pub mod inventory;
+pub mod ruleset;
+pub mod sheet_schema;
+pub mod weapon_mastery;
+pub mod weapon_properties;
+
+pub use abilities::*;
+pub use conditions::*;
+pub use currency::*;
+pub use damage_riders::*;
+pub use damage_types::*;
+pub use ddb_import::*;
+pub use dice::*;
+pub use dice_expression::*;
+pub use engine::*;
+pub use foundry::*;
+pub use inventory::*;
+pub use ruleset::*;
+pub use sheet_schema::*;
+pub use weapon_mastery::*;
+pub use weapon_properties::*;
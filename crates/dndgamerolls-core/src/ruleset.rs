@@ -0,0 +1,97 @@
+//! Rules edition
+//!
+//! Each campaign plays either the 2014 or the 2024 rules, and the mechanics
+//! the app automates follow the one it picked:
+//!
+//! - Exhaustion: 2014 gives disadvantage on ability checks from level 1 and
+//!   on attacks and saves from level 3; 2024 takes 2 per level off every d20
+//!   test instead.
+//! - Grappled: only 2024 gives disadvantage on attack rolls.
+//! - Surprise: a surprised creature skips its first turn under 2014, and
+//!   rolls initiative with disadvantage under 2024.
+//! - Weapon mastery properties only exist in 2024.
+//!
+//! The edition is kept per profile in the database's settings table under
+//! [`RULESET_SETTING_KEY`].
+
+use serde::{Deserialize, Serialize};
+
+/// Database setting holding the campaign's rules edition
+pub const RULESET_SETTING_KEY: &str = "ruleset";
+
+/// Edition of the 5e rules a campaign plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(bevy_ecs::prelude::Resource))]
+pub enum Ruleset {
+    #[default]
+    #[serde(rename = "2014")]
+    Rules2014,
+    #[serde(rename = "2024")]
+    Rules2024,
+}
+
+impl Ruleset {
+    pub const ALL: [Ruleset; 2] = [Ruleset::Rules2014, Ruleset::Rules2024];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Ruleset::Rules2014 => "2014 rules",
+            Ruleset::Rules2024 => "2024 rules",
+        }
+    }
+
+    /// "2014", "2024", "5e2024" or "5.5e" ("2024" and "5.5" both mean the
+    /// revised rules).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        if name.contains("2014") || name == "5e" {
+            Some(Ruleset::Rules2014)
+        } else if name.contains("2024") || name.contains("5.5") {
+            Some(Ruleset::Rules2024)
+        } else {
+            None
+        }
+    }
+
+    /// What exhaustion takes off every d20 test (2024); 0 under 2014, where
+    /// it gives disadvantage instead.
+    pub fn exhaustion_penalty(&self, level: u8) -> i32 {
+        match self {
+            Ruleset::Rules2014 => 0,
+            Ruleset::Rules2024 => -2 * level as i32,
+        }
+    }
+
+    /// Whether weapons have mastery properties.
+    pub fn weapon_mastery(&self) -> bool {
+        *self == Ruleset::Rules2024
+    }
+
+    /// Whether surprise gives disadvantage on initiative (2024) rather than
+    /// a lost first turn (2014).
+    pub fn surprise_disadvantage(&self) -> bool {
+        *self == Ruleset::Rules2024
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ruleset_names_and_rules() {
+        assert_eq!(Ruleset::from_name("2024"), Some(Ruleset::Rules2024));
+        assert_eq!(Ruleset::from_name("5.5e"), Some(Ruleset::Rules2024));
+        assert_eq!(Ruleset::from_name(" 5e2014 "), Some(Ruleset::Rules2014));
+        assert_eq!(Ruleset::from_name("pathfinder"), None);
+        assert_eq!(
+            serde_json::to_value(Ruleset::Rules2024).unwrap(),
+            serde_json::json!("2024")
+        );
+
+        assert_eq!(Ruleset::Rules2014.exhaustion_penalty(3), 0);
+        assert_eq!(Ruleset::Rules2024.exhaustion_penalty(3), -6);
+        assert!(!Ruleset::Rules2014.weapon_mastery());
+        assert!(Ruleset::Rules2024.surprise_disadvantage());
+    }
+}
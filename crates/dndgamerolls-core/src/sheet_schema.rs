@@ -0,0 +1,363 @@
+//! Character sheet JSON Schema
+//!
+//! [`CHARACTER_SHEET_SCHEMA`] is the published JSON Schema of the character
+//! sheet JSON the app, the CLI and `.dndchar` files store. Checking a sheet
+//! against it before decoding turns "invalid type: string "five", expected
+//! i32 at line 1 column 312" into a list of what is wrong where:
+//!
+//! ```text
+//! character.level: expected a whole number, found text "five" (e.g. 5)
+//! attributes.wisdom: missing; expected a whole number from 0 to 30 (e.g. 14)
+//! ```
+//!
+//! Only the parts of JSON Schema the published schema uses are understood:
+//! `type`, `properties`, `required`, `additionalProperties`, `items`, `enum`,
+//! `minimum`, `maximum`, `examples` and `$ref` into `$defs`.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+/// The character sheet JSON Schema (draft 2020-12)
+pub const CHARACTER_SHEET_SCHEMA: &str = include_str!("../schema/character-sheet.schema.json");
+
+/// Where the schema is published
+pub const CHARACTER_SHEET_SCHEMA_URL: &str = "https://raw.githubusercontent.com/edgarhsanchez/dndgamerolls/main/crates/dndgamerolls-core/schema/character-sheet.schema.json";
+
+/// Most problems listed in a report; the rest are counted
+pub const MAX_REPORTED_SCHEMA_ERRORS: usize = 10;
+
+/// Something in a sheet that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Where, e.g. `equipment.weapons[0].damage`; empty for the whole sheet
+    pub path: String,
+    /// What the schema wants there, e.g. "a whole number from 0 to 30"
+    pub expected: String,
+    /// What the sheet has there, `None` when the field is missing
+    pub found: Option<String>,
+    /// An example of a valid value, as JSON
+    pub example: Option<String>,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(the sheet)"
+        } else {
+            self.path.as_str()
+        };
+        match &self.found {
+            Some(found) => write!(f, "{}: expected {}, found {}", path, self.expected, found)?,
+            None => write!(f, "{}: missing; expected {}", path, self.expected)?,
+        }
+        if let Some(example) = &self.example {
+            write!(f, " (e.g. {})", example)?;
+        }
+        Ok(())
+    }
+}
+
+fn sheet_schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        serde_json::from_str(CHARACTER_SHEET_SCHEMA).expect("the character sheet schema is JSON")
+    })
+}
+
+/// Everything in `sheet` that doesn't match the character sheet schema,
+/// ordered by path.
+pub fn validate_character_sheet(sheet: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    validate(sheet_schema(), sheet, "", &mut errors);
+    errors.sort_by(|a, b| a.path.cmp(&b.path));
+    errors
+}
+
+/// Check `sheet` against the schema, describing each problem on its own line
+/// when it doesn't match.
+pub fn check_character_sheet(sheet: &Value) -> Result<(), String> {
+    let errors = validate_character_sheet(sheet);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let mut lines: Vec<String> = errors
+        .iter()
+        .take(MAX_REPORTED_SCHEMA_ERRORS)
+        .map(|e| format!("- {}", e))
+        .collect();
+    if errors.len() > MAX_REPORTED_SCHEMA_ERRORS {
+        lines.push(format!(
+            "- and {} more",
+            errors.len() - MAX_REPORTED_SCHEMA_ERRORS
+        ));
+    }
+    Err(lines.join("\n"))
+}
+
+/// The schema `$ref` points at, or `schema` itself.
+fn resolve(schema: &Value) -> &Value {
+    match schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => &sheet_schema()["$defs"][name],
+        None => schema,
+    }
+}
+
+/// A keyword of the schema, looking through its `$ref` when the schema
+/// itself doesn't have it.
+fn keyword<'a>(schema: &'a Value, name: &str) -> Option<&'a Value> {
+    schema.get(name).or_else(|| resolve(schema).get(name))
+}
+
+fn validate(schema: &Value, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    if !type_matches(schema, value) {
+        errors.push(SchemaError {
+            path: path.to_string(),
+            expected: expected(schema),
+            found: Some(describe(value)),
+            example: example(schema),
+        });
+        return;
+    }
+
+    if let Some(allowed) = keyword(schema, "enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(SchemaError {
+                path: path.to_string(),
+                expected: expected(schema),
+                found: Some(describe(value)),
+                example: None,
+            });
+            return;
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        let below = keyword(schema, "minimum")
+            .and_then(Value::as_f64)
+            .is_some_and(|min| number < min);
+        let above = keyword(schema, "maximum")
+            .and_then(Value::as_f64)
+            .is_some_and(|max| number > max);
+        if below || above {
+            errors.push(SchemaError {
+                path: path.to_string(),
+                expected: expected(schema),
+                found: Some(describe(value)),
+                example: example(schema),
+            });
+        }
+        return;
+    }
+
+    if let Some(items) = value.as_array() {
+        if let Some(item_schema) = keyword(schema, "items") {
+            for (i, item) in items.iter().enumerate() {
+                validate(item_schema, item, &format!("{}[{}]", path, i), errors);
+            }
+        }
+        return;
+    }
+
+    let Some(fields) = value.as_object() else {
+        return;
+    };
+    let properties = keyword(schema, "properties").and_then(Value::as_object);
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+
+    for name in keyword(schema, "required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !fields.contains_key(name) {
+            let field_schema = properties.and_then(|p| p.get(name));
+            errors.push(SchemaError {
+                path: child_path(name),
+                expected: field_schema
+                    .map(expected)
+                    .unwrap_or_else(|| "a value".into()),
+                found: None,
+                example: field_schema.and_then(example),
+            });
+        }
+    }
+
+    let additional = keyword(schema, "additionalProperties").filter(|s| s.is_object());
+    for (name, field) in fields {
+        let field_schema = properties.and_then(|p| p.get(name)).or(additional);
+        if let Some(field_schema) = field_schema {
+            validate(field_schema, field, &child_path(name), errors);
+        }
+    }
+}
+
+/// The JSON Schema type names `schema` accepts; empty for any type.
+fn types(schema: &Value) -> Vec<&str> {
+    match keyword(schema, "type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn type_matches(schema: &Value, value: &Value) -> bool {
+    let types = types(schema);
+    types.is_empty()
+        || types.iter().any(|name| match *name {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        })
+}
+
+/// What `schema` wants, in words: "a whole number from 0 to 30".
+fn expected(schema: &Value) -> String {
+    if let Some(allowed) = keyword(schema, "enum").and_then(Value::as_array) {
+        let names: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return format!("one of {}", names.join(", "));
+    }
+
+    let names: Vec<&str> = types(schema)
+        .into_iter()
+        .filter(|name| *name != "null")
+        .map(|name| match name {
+            "object" => "an object",
+            "array" => "a list",
+            "string" => "text",
+            "integer" => "a whole number",
+            "number" => "a number",
+            "boolean" => "true or false",
+            other => other,
+        })
+        .collect();
+    let mut expected = if names.is_empty() {
+        "a value".to_string()
+    } else {
+        names.join(" or ")
+    };
+
+    let min = keyword(schema, "minimum").and_then(Value::as_f64);
+    let max = keyword(schema, "maximum").and_then(Value::as_f64);
+    match (min, max) {
+        (Some(min), Some(max)) => expected.push_str(&format!(" from {} to {}", min, max)),
+        (Some(min), None) => expected.push_str(&format!(" of at least {}", min)),
+        (None, Some(max)) => expected.push_str(&format!(" of at most {}", max)),
+        (None, None) => {}
+    }
+    if types(schema).contains(&"null") {
+        expected.push_str(" (or null)");
+    }
+    expected
+}
+
+fn example(schema: &Value) -> Option<String> {
+    keyword(schema, "examples")
+        .and_then(Value::as_array)
+        .and_then(|examples| examples.first())
+        .map(Value::to_string)
+}
+
+/// A found value, in words: `text "five"`, `a list`.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) if s.chars().count() > 30 => {
+            format!("text \"{}…\"", s.chars().take(30).collect::<String>())
+        }
+        Value::String(s) => format!("text \"{}\"", s),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sheet() -> Value {
+        json!({
+            "character": { "name": "Thorin", "class": "Fighter", "race": "Dwarf", "level": 5 },
+            "attributes": { "strength": 16, "dexterity": 12, "constitution": 14,
+                            "intelligence": 10, "wisdom": 13, "charisma": 8 },
+            "modifiers": { "strength": 3, "dexterity": 1, "constitution": 2,
+                           "intelligence": 0, "wisdom": 1, "charisma": -1 },
+            "combat": { "armorClass": 16, "initiative": 1, "speed": 25, "hitPoints": null },
+            "proficiencyBonus": 3,
+            "savingThrows": { "strength": { "proficient": true, "modifier": 6 } },
+            "skills": { "athletics": { "proficient": true, "modifier": 6 } },
+            "equipment": null,
+            "homebrewNotes": "kept as they are"
+        })
+    }
+
+    #[test]
+    fn test_valid_sheet() {
+        assert!(validate_character_sheet(&sheet()).is_empty());
+        assert!(check_character_sheet(&sheet()).is_ok());
+    }
+
+    #[test]
+    fn test_friendly_errors() {
+        let mut sheet = sheet();
+        sheet["character"]["level"] = json!("five");
+        sheet["attributes"]
+            .as_object_mut()
+            .unwrap()
+            .remove("wisdom");
+        sheet["attributes"]["charisma"] = json!(45);
+        sheet["skills"]["stealth"] = json!({ "proficient": "yes", "modifier": 2 });
+        sheet["equipment"] =
+            json!({ "weapons": [{ "name": "Axe", "damage": 8, "damageType": "slashing" }] });
+
+        let errors: Vec<String> = validate_character_sheet(&sheet)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "attributes.charisma: expected a whole number from 0 to 30, found 45 (e.g. 14)",
+                "attributes.wisdom: missing; expected a whole number from 0 to 30 (e.g. 14)",
+                "character.level: expected a whole number from 0 to 20, found text \"five\" (e.g. 5)",
+                "equipment.weapons[0].damage: expected text, found 8 (e.g. \"1d8+3\")",
+                "skills.stealth.proficient: expected true or false, found text \"yes\" (e.g. true)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_a_sheet() {
+        let errors = validate_character_sheet(&json!([1, 2]));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "(the sheet): expected an object, found a list"
+        );
+
+        // Every required top-level field is listed, the rest counted
+        let report = check_character_sheet(&json!({})).unwrap_err();
+        assert_eq!(report.lines().count(), 7);
+        assert!(report.starts_with("- attributes: missing; expected an object (e.g. {"));
+    }
+}
@@ -0,0 +1,268 @@
+//! Weapon mastery (2024 rules)
+//!
+//! Under the 2024 rules every weapon has a mastery property that does
+//! something extra when the weapon is used: Vex gives advantage on the next
+//! attack after a hit, Topple makes the target save or fall prone, Graze
+//! deals damage even on a miss, and so on. A weapon can name its mastery
+//! in its properties ("Mastery (Topple)" or just "Topple"); otherwise the
+//! mastery of the weapon on the 2024 weapon table is used.
+
+use crate::abilities::ability_abbreviation;
+use crate::weapon_properties::WeaponAttack;
+
+/// A weapon mastery property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeaponMastery {
+    Cleave,
+    Graze,
+    Nick,
+    Push,
+    Sap,
+    Slow,
+    Topple,
+    Vex,
+}
+
+/// Masteries of the weapons on the 2024 weapon table
+const SRD_MASTERIES: &[(&str, WeaponMastery)] = &[
+    ("club", WeaponMastery::Slow),
+    ("dagger", WeaponMastery::Nick),
+    ("greatclub", WeaponMastery::Push),
+    ("handaxe", WeaponMastery::Vex),
+    ("javelin", WeaponMastery::Slow),
+    ("light hammer", WeaponMastery::Nick),
+    ("mace", WeaponMastery::Sap),
+    ("quarterstaff", WeaponMastery::Topple),
+    ("sickle", WeaponMastery::Nick),
+    ("spear", WeaponMastery::Sap),
+    ("dart", WeaponMastery::Vex),
+    ("light crossbow", WeaponMastery::Slow),
+    ("shortbow", WeaponMastery::Vex),
+    ("sling", WeaponMastery::Slow),
+    ("battleaxe", WeaponMastery::Topple),
+    ("flail", WeaponMastery::Sap),
+    ("glaive", WeaponMastery::Graze),
+    ("greataxe", WeaponMastery::Cleave),
+    ("greatsword", WeaponMastery::Graze),
+    ("halberd", WeaponMastery::Cleave),
+    ("lance", WeaponMastery::Topple),
+    ("longsword", WeaponMastery::Sap),
+    ("maul", WeaponMastery::Topple),
+    ("morningstar", WeaponMastery::Sap),
+    ("pike", WeaponMastery::Push),
+    ("rapier", WeaponMastery::Vex),
+    ("scimitar", WeaponMastery::Nick),
+    ("shortsword", WeaponMastery::Vex),
+    ("trident", WeaponMastery::Topple),
+    ("warhammer", WeaponMastery::Push),
+    ("war pick", WeaponMastery::Sap),
+    ("whip", WeaponMastery::Slow),
+    ("blowgun", WeaponMastery::Vex),
+    ("hand crossbow", WeaponMastery::Vex),
+    ("heavy crossbow", WeaponMastery::Push),
+    ("longbow", WeaponMastery::Slow),
+    ("musket", WeaponMastery::Slow),
+    ("pistol", WeaponMastery::Vex),
+];
+
+impl WeaponMastery {
+    pub const ALL: [WeaponMastery; 8] = [
+        WeaponMastery::Cleave,
+        WeaponMastery::Graze,
+        WeaponMastery::Nick,
+        WeaponMastery::Push,
+        WeaponMastery::Sap,
+        WeaponMastery::Slow,
+        WeaponMastery::Topple,
+        WeaponMastery::Vex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponMastery::Cleave => "Cleave",
+            WeaponMastery::Graze => "Graze",
+            WeaponMastery::Nick => "Nick",
+            WeaponMastery::Push => "Push",
+            WeaponMastery::Sap => "Sap",
+            WeaponMastery::Slow => "Slow",
+            WeaponMastery::Topple => "Topple",
+            WeaponMastery::Vex => "Vex",
+        }
+    }
+
+    /// Find a mastery by name, ignoring case ("vex", "Topple").
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|m| m.name().eq_ignore_ascii_case(name))
+    }
+
+    /// What the mastery does
+    pub fn description(&self) -> &'static str {
+        match self {
+            WeaponMastery::Cleave => {
+                "attack a second creature within 5 feet of the first (once per turn); \
+                 a positive ability modifier isn't added to its damage"
+            }
+            WeaponMastery::Graze => "on a miss, deal damage equal to the ability modifier",
+            WeaponMastery::Nick => {
+                "the light weapon's extra attack is part of the Attack action, \
+                 not a bonus action (once per turn)"
+            }
+            WeaponMastery::Push => "push a Large or smaller target up to 10 feet away",
+            WeaponMastery::Sap => {
+                "the target has disadvantage on its next attack roll before your next turn"
+            }
+            WeaponMastery::Slow => "the target's Speed drops by 10 feet until your next turn",
+            WeaponMastery::Topple => "the target makes a Constitution save or falls Prone",
+            WeaponMastery::Vex => {
+                "advantage on your next attack roll against the target before the end of \
+                 your next turn"
+            }
+        }
+    }
+
+    /// Mastery of a weapon on the 2024 weapon table, by name ("+1 Light
+    /// Crossbow" is a Light Crossbow).
+    pub fn of_srd_weapon(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        SRD_MASTERIES
+            .iter()
+            .filter(|(weapon, _)| name.contains(weapon))
+            .max_by_key(|(weapon, _)| weapon.len())
+            .map(|(_, mastery)| *mastery)
+    }
+
+    /// DC of the save the mastery forces: 8 + the attack's ability modifier
+    /// + proficiency bonus.
+    pub fn save_dc(attack: &WeaponAttack, proficiency: i32) -> i32 {
+        8 + attack.ability_modifier + proficiency
+    }
+
+    /// The save a hit makes the target roll (Topple's Constitution save),
+    /// given the d20 face and the target's save modifier.
+    pub fn save(
+        &self,
+        attack: &WeaponAttack,
+        proficiency: i32,
+        roll: i32,
+        modifier: i32,
+    ) -> Option<MasterySave> {
+        (*self == WeaponMastery::Topple).then(|| MasterySave {
+            ability: "constitution",
+            dc: Self::save_dc(attack, proficiency),
+            roll,
+            modifier,
+        })
+    }
+
+    /// Damage Graze deals on a miss: the attack's ability modifier, when it
+    /// is positive.
+    pub fn graze_damage(&self, attack: &WeaponAttack) -> Option<i32> {
+        (*self == WeaponMastery::Graze && attack.ability_modifier > 0)
+            .then_some(attack.ability_modifier)
+    }
+}
+
+/// A saving throw a weapon mastery forces on its target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MasterySave {
+    /// Canonical ability name ("constitution")
+    pub ability: &'static str,
+    pub dc: i32,
+    /// The d20 face
+    pub roll: i32,
+    pub modifier: i32,
+}
+
+impl MasterySave {
+    pub fn total(&self) -> i32 {
+        self.roll + self.modifier
+    }
+
+    /// Meeting the DC saves
+    pub fn succeeded(&self) -> bool {
+        self.total() >= self.dc
+    }
+
+    /// e.g. "CON save 9 vs DC 13: falls Prone"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} save {} vs DC {}: {}",
+            ability_abbreviation(self.ability),
+            self.total(),
+            self.dc,
+            if self.succeeded() {
+                "stays standing"
+            } else {
+                "falls Prone"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon_properties::{AttackMode, WeaponStats};
+
+    fn attack(name: &str, properties: &[&str], strength: i32) -> WeaponAttack {
+        let properties: Vec<String> = properties.iter().map(|p| p.to_string()).collect();
+        let weapon = WeaponStats {
+            name,
+            attack_bonus: 0,
+            damage: "1d8",
+            properties: &properties,
+        };
+        WeaponAttack::new(weapon, AttackMode::Normal, strength, 0, 2).unwrap()
+    }
+
+    #[test]
+    fn test_srd_and_named_masteries() {
+        assert_eq!(
+            WeaponMastery::of_srd_weapon("+1 Light Crossbow"),
+            Some(WeaponMastery::Slow)
+        );
+        assert_eq!(
+            WeaponMastery::of_srd_weapon("Heavy Crossbow"),
+            Some(WeaponMastery::Push)
+        );
+        assert_eq!(
+            WeaponMastery::of_srd_weapon("Greatclub"),
+            Some(WeaponMastery::Push)
+        );
+        assert_eq!(WeaponMastery::of_srd_weapon("Frying Pan"), None);
+        assert_eq!(WeaponMastery::from_name("vex"), Some(WeaponMastery::Vex));
+
+        assert_eq!(
+            attack("Longsword", &[], 3).mastery,
+            Some(WeaponMastery::Sap)
+        );
+        assert_eq!(
+            attack("Longsword", &["Mastery (Topple)"], 3).mastery,
+            Some(WeaponMastery::Topple)
+        );
+        assert_eq!(
+            attack("Frying Pan", &["Graze"], 3).mastery,
+            Some(WeaponMastery::Graze)
+        );
+    }
+
+    #[test]
+    fn test_topple_save_and_graze_damage() {
+        let maul = attack("Maul", &["Heavy", "Two-Handed"], 3);
+        let mastery = maul.mastery.unwrap();
+        let save = mastery.save(&maul, 2, 8, 1).unwrap();
+        assert_eq!(save.dc, 13);
+        assert!(!save.succeeded());
+        assert_eq!(save.describe(), "CON save 9 vs DC 13: falls Prone");
+        assert!(mastery.save(&maul, 2, 12, 1).unwrap().succeeded());
+        assert!(WeaponMastery::Vex.save(&maul, 2, 1, 0).is_none());
+
+        let greatsword = attack("Greatsword", &["Heavy", "Two-Handed"], 3);
+        assert_eq!(WeaponMastery::Graze.graze_damage(&greatsword), Some(3));
+        let weak = attack("Greatsword", &[], -1);
+        assert_eq!(WeaponMastery::Graze.graze_damage(&weak), None);
+    }
+}
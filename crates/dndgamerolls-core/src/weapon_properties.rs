@@ -0,0 +1,609 @@
+//! Weapon properties
+//!
+//! Reads the property names stored on a weapon ("Finesse", "Versatile (1d10)",
+//! "Two-Handed", "Thrown (range 20/60)", "Ammunition (range 80/320)") and
+//! works out an attack from them: the ability modifier it uses, the damage
+//! dice for the way it's wielded, and the ammunition it spends from the
+//! character's inventory.
+//! Two-weapon fighting is checked and worked out here too: an off-hand
+//! attack with a light weapon that leaves the ability modifier out of its
+//! damage. The weapon's mastery (2024 rules) comes along with the attack.
+
+use crate::dice_expression::DamageRoll;
+use crate::inventory::InventoryItem;
+use crate::weapon_mastery::WeaponMastery;
+
+/// A weapon property, with the detail in parentheses where the rules give one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WeaponProperty {
+    Ammunition,
+    Finesse,
+    Heavy,
+    Light,
+    Loading,
+    Reach,
+    Thrown,
+    TwoHanded,
+    /// Damage dice when wielded with two hands, if written down
+    /// ("Versatile (1d10)")
+    Versatile(Option<String>),
+    /// "Mastery (Vex)", or the mastery's name on its own
+    Mastery(WeaponMastery),
+    Other(String),
+}
+
+impl WeaponProperty {
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let (name, detail) = match text.split_once('(') {
+            Some((name, rest)) => (name.trim(), rest.trim_end_matches(')').trim()),
+            None => (text, ""),
+        };
+        let key: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match key.as_str() {
+            "ammunition" => WeaponProperty::Ammunition,
+            "finesse" => WeaponProperty::Finesse,
+            "heavy" => WeaponProperty::Heavy,
+            "light" => WeaponProperty::Light,
+            "loading" => WeaponProperty::Loading,
+            "reach" => WeaponProperty::Reach,
+            "thrown" => WeaponProperty::Thrown,
+            "twohanded" => WeaponProperty::TwoHanded,
+            "versatile" => WeaponProperty::Versatile(
+                (!detail.is_empty() && find_die(detail).is_some()).then(|| detail.to_string()),
+            ),
+            "mastery" => match WeaponMastery::from_name(detail) {
+                Some(mastery) => WeaponProperty::Mastery(mastery),
+                None => WeaponProperty::Other(text.to_string()),
+            },
+            _ => match WeaponMastery::from_name(name) {
+                Some(mastery) => WeaponProperty::Mastery(mastery),
+                None => WeaponProperty::Other(text.to_string()),
+            },
+        }
+    }
+}
+
+/// How a weapon is used for an attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AttackMode {
+    #[default]
+    Normal,
+    /// A versatile weapon held in both hands
+    TwoHanded,
+    Thrown,
+}
+
+/// The numbers of a weapon as stored on the sheet
+#[derive(Debug, Clone, Copy)]
+pub struct WeaponStats<'a> {
+    pub name: &'a str,
+    /// Total attack bonus; 0 when it wasn't filled in
+    pub attack_bonus: i32,
+    pub damage: &'a str,
+    pub properties: &'a [String],
+}
+
+/// An attack worked out from a weapon's properties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeaponAttack {
+    /// "strength" or "dexterity"
+    pub ability: &'static str,
+    pub ability_modifier: i32,
+    pub attack_bonus: i32,
+    pub damage: String,
+    /// Two-handed damage of a versatile weapon used one-handed, to offer
+    pub versatile_damage: Option<String>,
+    pub uses_ammunition: bool,
+    pub finesse: bool,
+    /// Shot or thrown rather than swung
+    pub ranged: bool,
+    /// The weapon's mastery property, applied under the 2024 rules
+    pub mastery: Option<WeaponMastery>,
+}
+
+impl WeaponAttack {
+    /// Work out an attack with a weapon.
+    ///
+    /// Finesse weapons use the better of Strength and Dexterity, ammunition
+    /// weapons Dexterity, everything else (thrown weapons included)
+    /// Strength. A weapon stored without an attack bonus gets proficiency
+    /// plus that ability plus any "+1" in its name, and the same added to
+    /// its damage; stored bonuses are used as they are.
+    pub fn new(
+        weapon: WeaponStats,
+        mode: AttackMode,
+        strength: i32,
+        dexterity: i32,
+        proficiency: i32,
+    ) -> Result<Self, String> {
+        let properties: Vec<WeaponProperty> = weapon
+            .properties
+            .iter()
+            .map(|p| WeaponProperty::parse(p))
+            .collect();
+        let has = |property: &WeaponProperty| properties.contains(property);
+        let versatile = properties.iter().find_map(|p| match p {
+            WeaponProperty::Versatile(dice) => Some(dice.clone()),
+            _ => None,
+        });
+
+        match mode {
+            AttackMode::TwoHanded if versatile.is_none() && !has(&WeaponProperty::TwoHanded) => {
+                return Err(format!("{} isn't versatile", weapon.name));
+            }
+            AttackMode::Thrown if !has(&WeaponProperty::Thrown) => {
+                return Err(format!("{} can't be thrown", weapon.name));
+            }
+            _ => {}
+        }
+
+        let (ability, ability_modifier) = if has(&WeaponProperty::Finesse) {
+            if dexterity > strength {
+                ("dexterity", dexterity)
+            } else {
+                ("strength", strength)
+            }
+        } else if has(&WeaponProperty::Ammunition) {
+            ("dexterity", dexterity)
+        } else {
+            ("strength", strength)
+        };
+
+        let (attack_bonus, base_damage) = if weapon.attack_bonus == 0 {
+            let bonus = ability_modifier + magic_bonus(weapon.name);
+            let damage = if has_flat_bonus(weapon.damage) {
+                weapon.damage.to_string()
+            } else {
+                add_bonus(weapon.damage, bonus)
+            };
+            (proficiency + bonus, damage)
+        } else {
+            (weapon.attack_bonus, weapon.damage.to_string())
+        };
+
+        let two_handed_damage = versatile.map(|dice| {
+            let die = dice
+                .as_deref()
+                .and_then(find_die)
+                .map(|(_, die)| die.to_string())
+                .or_else(|| larger_die(&base_damage));
+            match die {
+                Some(die) => replace_die(&base_damage, &die),
+                None => base_damage.clone(),
+            }
+        });
+
+        let (damage, versatile_damage) = match (mode, two_handed_damage) {
+            (AttackMode::TwoHanded, Some(two_handed)) => (two_handed, None),
+            (_, two_handed) => (base_damage, two_handed),
+        };
+
+        Ok(WeaponAttack {
+            ability,
+            ability_modifier,
+            attack_bonus,
+            damage,
+            versatile_damage,
+            uses_ammunition: has(&WeaponProperty::Ammunition) && mode != AttackMode::Thrown,
+            finesse: has(&WeaponProperty::Finesse),
+            ranged: has(&WeaponProperty::Ammunition) || mode == AttackMode::Thrown,
+            mastery: properties
+                .iter()
+                .find_map(|p| match p {
+                    WeaponProperty::Mastery(mastery) => Some(*mastery),
+                    _ => None,
+                })
+                .or_else(|| WeaponMastery::of_srd_weapon(weapon.name)),
+        })
+    }
+
+    /// The attack made with the off hand when fighting with two weapons: a
+    /// positive ability modifier isn't added to its damage unless the
+    /// character has the Two-Weapon Fighting style.
+    pub fn off_hand(mut self, fighting_style: bool) -> Self {
+        if !fighting_style && self.ability_modifier > 0 && has_flat_bonus(&self.damage) {
+            self.damage = add_bonus(&self.damage, -self.ability_modifier);
+        }
+        self.versatile_damage = None;
+        self
+    }
+}
+
+/// Check that two weapons can be fought with together: both melee weapons,
+/// and both light unless the character has the Dual Wielder feat (which
+/// still rules out two-handed weapons).
+pub fn check_two_weapon_fighting(
+    main_hand: WeaponStats,
+    off_hand: WeaponStats,
+    dual_wielder: bool,
+) -> Result<(), String> {
+    for weapon in [main_hand, off_hand] {
+        let properties: Vec<WeaponProperty> = weapon
+            .properties
+            .iter()
+            .map(|p| WeaponProperty::parse(p))
+            .collect();
+        if properties.contains(&WeaponProperty::Ammunition) {
+            return Err(format!("{} isn't a melee weapon", weapon.name));
+        }
+        if properties.contains(&WeaponProperty::TwoHanded) {
+            return Err(format!("{} is two-handed", weapon.name));
+        }
+        if !dual_wielder && !properties.contains(&WeaponProperty::Light) {
+            return Err(format!("{} isn't a light weapon", weapon.name));
+        }
+    }
+    Ok(())
+}
+
+/// "+1" in "+1 Longsword" or "Longsword, +2"
+fn magic_bonus(name: &str) -> i32 {
+    name.split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|word| word.strip_prefix('+'))
+        .filter_map(|n| n.parse::<i32>().ok())
+        .find(|n| (1..=3).contains(n))
+        .unwrap_or(0)
+}
+
+/// Byte range and text of the first dice term ("1d8" in "1d8+3 slashing")
+fn find_die(text: &str) -> Option<((usize, usize), &str)> {
+    let bytes = text.as_bytes();
+    for (index, byte) in bytes.iter().enumerate() {
+        if !byte.eq_ignore_ascii_case(&b'd') {
+            continue;
+        }
+        let sides_end = bytes[index + 1..]
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .map(|n| index + 1 + n)
+            .unwrap_or(bytes.len());
+        if sides_end == index + 1 {
+            continue;
+        }
+        let start = bytes[..index]
+            .iter()
+            .rposition(|b| !b.is_ascii_digit())
+            .map(|n| n + 1)
+            .unwrap_or(0);
+        // Not part of a word ("Shield1d4")
+        if start > 0 && bytes[start - 1].is_ascii_alphabetic() {
+            continue;
+        }
+        return Some(((start, sides_end), &text[start..sides_end]));
+    }
+    None
+}
+
+/// Replace the first dice term of `damage` with `die`.
+fn replace_die(damage: &str, die: &str) -> String {
+    match find_die(damage) {
+        Some(((start, end), _)) => format!("{}{}{}", &damage[..start], die, &damage[end..]),
+        None => damage.to_string(),
+    }
+}
+
+/// The first dice term one die size up (1d8 -> 1d10), as versatile weapons
+/// go when no two-handed dice are written down.
+fn larger_die(damage: &str) -> Option<String> {
+    let (_, die) = find_die(damage)?;
+    let (count, sides) = die.split_once(['d', 'D'])?;
+    let larger = match sides.parse::<u32>().ok()? {
+        4 => 6,
+        6 => 8,
+        8 => 10,
+        10 => 12,
+        _ => return None,
+    };
+    Some(format!("{}d{}", count, larger))
+}
+
+/// Whether a damage string already adds a flat bonus ("1d8+3", "1d6 - 1")
+fn has_flat_bonus(damage: &str) -> bool {
+    match find_die(damage) {
+        Some(((_, end), _)) => damage[end..].trim_start().starts_with(['+', '-']),
+        None => false,
+    }
+}
+
+/// Add a flat bonus right after the first dice term, folding it into the
+/// flat bonus already there ("1d6+3" and -3 give "1d6").
+fn add_bonus(damage: &str, bonus: i32) -> String {
+    if bonus == 0 {
+        return damage.to_string();
+    }
+    let Some(((_, end), _)) = find_die(damage) else {
+        return damage.to_string();
+    };
+
+    let rest = &damage[end..];
+    let sign_at = rest.len() - rest.trim_start().len();
+    let sign = match rest[sign_at..].chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return format!("{}{:+}{}", &damage[..end], bonus, rest),
+    };
+    let digits = rest[sign_at + 1..].trim_start();
+    let digits_len = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    let Ok(existing) = digits[..digits_len].parse::<i32>() else {
+        return format!("{}{:+}{}", &damage[..end], bonus, rest);
+    };
+    let after = &digits[digits_len..];
+    match sign * existing + bonus {
+        0 => format!("{}{}", &damage[..end], after),
+        total => format!("{}{:+}{}", &damage[..end], total, after),
+    }
+}
+
+/// A damage roll of a weapon (`damage` as worked out for the attack); the
+/// weapon's own damage type is used when the damage string doesn't name one.
+pub fn weapon_damage(damage: &str, weapon_damage_type: &str) -> Result<DamageRoll, String> {
+    let mut damage = DamageRoll::parse(damage)?;
+    if damage.damage_type.is_none() && !weapon_damage_type.trim().is_empty() {
+        damage.damage_type = Some(weapon_damage_type.trim().to_string());
+    }
+    Ok(damage)
+}
+
+/// What a weapon shoots: "arrow", "bolt", "bullet", "needle", or
+/// "ammunition" for anything else
+pub fn ammunition_kind(weapon_name: &str) -> &'static str {
+    let name = weapon_name.to_lowercase();
+    if name.contains("crossbow") {
+        "bolt"
+    } else if name.contains("bow") {
+        "arrow"
+    } else if name.contains("sling") {
+        "bullet"
+    } else if name.contains("blowgun") {
+        "needle"
+    } else {
+        "ammunition"
+    }
+}
+
+/// What shooting a weapon did to the character's ammunition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmmunitionUse {
+    /// One was spent from this item, leaving this many
+    Spent { item: String, left: u32 },
+    /// None of this kind of ammunition is carried
+    OutOf(&'static str),
+}
+
+impl AmmunitionUse {
+    pub fn describe(&self) -> String {
+        match self {
+            AmmunitionUse::Spent { item, left } => format!("{} left: {}", item, left),
+            AmmunitionUse::OutOf(kind) => format!("Out of {}s", kind),
+        }
+    }
+}
+
+/// Spend one piece of ammunition for a weapon from the items; the item is
+/// removed when the last one is used.
+pub fn spend_ammunition(items: &mut Vec<InventoryItem>, weapon_name: &str) -> AmmunitionUse {
+    let kind = ammunition_kind(weapon_name);
+    let index = items.iter().position(|item| {
+        let name = item.name.to_lowercase();
+        item.quantity > 0
+            && (name.contains(kind) || (kind == "ammunition" && name.contains("ammo")))
+    });
+    let Some(index) = index else {
+        return AmmunitionUse::OutOf(kind);
+    };
+
+    let item = &mut items[index];
+    item.quantity -= 1;
+    let spent = AmmunitionUse::Spent {
+        item: item.name.clone(),
+        left: item.quantity,
+    };
+    if item.quantity == 0 {
+        items.remove(index);
+    }
+    spent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn weapon<'a>(
+        name: &'a str,
+        attack_bonus: i32,
+        damage: &'a str,
+        properties: &'a [String],
+    ) -> WeaponStats<'a> {
+        WeaponStats {
+            name,
+            attack_bonus,
+            damage,
+            properties,
+        }
+    }
+
+    #[test]
+    fn test_parse_properties() {
+        assert_eq!(WeaponProperty::parse("Finesse"), WeaponProperty::Finesse);
+        assert_eq!(
+            WeaponProperty::parse("two handed"),
+            WeaponProperty::TwoHanded
+        );
+        assert_eq!(
+            WeaponProperty::parse("Two-Handed"),
+            WeaponProperty::TwoHanded
+        );
+        assert_eq!(
+            WeaponProperty::parse("Thrown (range 20/60)"),
+            WeaponProperty::Thrown
+        );
+        assert_eq!(
+            WeaponProperty::parse("Versatile (1d10)"),
+            WeaponProperty::Versatile(Some("1d10".to_string()))
+        );
+        assert_eq!(
+            WeaponProperty::parse("Versatile"),
+            WeaponProperty::Versatile(None)
+        );
+        assert_eq!(
+            WeaponProperty::parse("Silvered"),
+            WeaponProperty::Other("Silvered".to_string())
+        );
+        assert_eq!(
+            WeaponProperty::parse("Mastery (Vex)"),
+            WeaponProperty::Mastery(WeaponMastery::Vex)
+        );
+        assert_eq!(
+            WeaponProperty::parse("topple"),
+            WeaponProperty::Mastery(WeaponMastery::Topple)
+        );
+    }
+
+    #[test]
+    fn test_attack_ability() {
+        let finesse = props(&["Finesse", "Light", "Thrown (range 20/60)"]);
+        let dagger = weapon("Dagger", 0, "1d4", &finesse);
+        let attack = WeaponAttack::new(dagger, AttackMode::Thrown, 1, 3, 2).unwrap();
+        assert_eq!((attack.ability, attack.attack_bonus), ("dexterity", 5));
+        assert_eq!(attack.damage, "1d4+3");
+
+        let ammunition = props(&["Ammunition (range 150/600)", "Heavy", "Two-Handed"]);
+        let longbow = weapon("+1 Longbow", 0, "1d8 piercing", &ammunition);
+        let attack = WeaponAttack::new(longbow, AttackMode::Normal, 4, 2, 3).unwrap();
+        assert_eq!((attack.ability, attack.attack_bonus), ("dexterity", 6));
+        assert_eq!(attack.damage, "1d8+3 piercing");
+        assert!(attack.uses_ammunition);
+
+        let thrown = props(&["Thrown (range 30/120)"]);
+        let javelin = weapon("Javelin", 0, "1d6", &thrown);
+        let attack = WeaponAttack::new(javelin, AttackMode::Thrown, 3, 4, 2).unwrap();
+        assert_eq!(attack.ability, "strength");
+        assert!(!attack.uses_ammunition);
+
+        // Stored bonuses are kept
+        let rapier = weapon("Rapier", 7, "1d8+4", &finesse);
+        let attack = WeaponAttack::new(rapier, AttackMode::Normal, 0, 4, 3).unwrap();
+        assert_eq!((attack.attack_bonus, attack.damage.as_str()), (7, "1d8+4"));
+
+        assert_eq!(
+            WeaponAttack::new(rapier, AttackMode::TwoHanded, 0, 4, 3).unwrap_err(),
+            "Rapier isn't versatile"
+        );
+        let none: Vec<String> = Vec::new();
+        let club = weapon("Club", 4, "1d4+2", &none);
+        assert_eq!(
+            WeaponAttack::new(club, AttackMode::Thrown, 2, 0, 2).unwrap_err(),
+            "Club can't be thrown"
+        );
+    }
+
+    #[test]
+    fn test_versatile_damage() {
+        let versatile = props(&["Versatile"]);
+        let longsword = weapon("Longsword", 5, "1d8+3 slashing", &versatile);
+        let attack = WeaponAttack::new(longsword, AttackMode::Normal, 3, 0, 2).unwrap();
+        assert_eq!(attack.damage, "1d8+3 slashing");
+        assert_eq!(attack.versatile_damage.as_deref(), Some("1d10+3 slashing"));
+
+        let attack = WeaponAttack::new(longsword, AttackMode::TwoHanded, 3, 0, 2).unwrap();
+        assert_eq!(attack.damage, "1d10+3 slashing");
+        assert_eq!(attack.versatile_damage, None);
+
+        let written = props(&["Versatile (2d4)"]);
+        let odd = weapon("Odd Blade", 5, "1d6+3", &written);
+        let attack = WeaponAttack::new(odd, AttackMode::TwoHanded, 3, 0, 2).unwrap();
+        assert_eq!(attack.damage, "2d4+3");
+    }
+
+    #[test]
+    fn test_two_weapon_fighting() {
+        let light = props(&["Finesse", "Light"]);
+        let shortsword = weapon("Shortsword", 0, "1d6 piercing", &light);
+        let versatile = props(&["Versatile (1d10)"]);
+        let longsword = weapon("Longsword", 0, "1d8", &versatile);
+        let heavy = props(&["Heavy", "Two-Handed"]);
+        let greataxe = weapon("Greataxe", 0, "1d12", &heavy);
+
+        assert!(check_two_weapon_fighting(shortsword, shortsword, false).is_ok());
+        assert_eq!(
+            check_two_weapon_fighting(longsword, shortsword, false).unwrap_err(),
+            "Longsword isn't a light weapon"
+        );
+        assert!(check_two_weapon_fighting(longsword, shortsword, true).is_ok());
+        assert_eq!(
+            check_two_weapon_fighting(shortsword, greataxe, true).unwrap_err(),
+            "Greataxe is two-handed"
+        );
+
+        let attack = WeaponAttack::new(shortsword, AttackMode::Normal, 1, 3, 2).unwrap();
+        assert_eq!(attack.clone().off_hand(true).damage, "1d6+3 piercing");
+        let off_hand = attack.off_hand(false);
+        assert_eq!(
+            (off_hand.attack_bonus, off_hand.damage.as_str()),
+            (5, "1d6 piercing")
+        );
+
+        // A negative modifier still counts, and magic bonuses stay
+        let attack = WeaponAttack::new(shortsword, AttackMode::Normal, -1, -1, 2).unwrap();
+        assert_eq!(attack.off_hand(false).damage, "1d6-1 piercing");
+        let magic = weapon("+1 Shortsword", 6, "1d6 + 4 piercing", &light);
+        let attack = WeaponAttack::new(magic, AttackMode::Normal, 1, 3, 2).unwrap();
+        assert_eq!(attack.off_hand(false).damage, "1d6+1 piercing");
+    }
+
+    #[test]
+    fn test_weapon_damage_falls_back_to_weapon_type() {
+        let damage = weapon_damage("1d8+3", "slashing").unwrap();
+        assert_eq!(damage.expression.to_string(), "1d8+3");
+        assert_eq!(damage.damage_type.as_deref(), Some("slashing"));
+
+        let damage = weapon_damage("1d8+3 radiant", "slashing").unwrap();
+        assert_eq!(damage.damage_type.as_deref(), Some("radiant"));
+        assert_eq!(weapon_damage("1d8", " ").unwrap().damage_type, None);
+
+        assert!(weapon_damage("see notes", "slashing").is_err());
+    }
+
+    #[test]
+    fn test_spend_ammunition() {
+        assert_eq!(ammunition_kind("Light Crossbow"), "bolt");
+        assert_eq!(ammunition_kind("Shortbow"), "arrow");
+
+        let mut items: Vec<InventoryItem> = ["Rope", "Arrows (3)", "Crossbow Bolts (1)"]
+            .into_iter()
+            .map(InventoryItem::parse)
+            .collect();
+        assert_eq!(
+            spend_ammunition(&mut items, "Longbow"),
+            AmmunitionUse::Spent {
+                item: "Arrows".to_string(),
+                left: 2
+            }
+        );
+        assert_eq!(items[1].to_string(), "Arrows (2)");
+        spend_ammunition(&mut items, "Longbow");
+        assert_eq!(items[1].to_string(), "Arrows");
+
+        let bolts = spend_ammunition(&mut items, "Hand Crossbow");
+        assert_eq!(bolts.describe(), "Crossbow Bolts left: 0");
+        assert_eq!(
+            items.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["Rope", "Arrows"]
+        );
+        assert_eq!(
+            spend_ammunition(&mut items, "Light Crossbow"),
+            AmmunitionUse::OutOf("bolt")
+        );
+    }
+}
@@ -10,9 +10,16 @@ pub struct DiceFxPlugin;
 
 impl Plugin for DiceFxPlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<crate::dice3d::types::DiceRollCompletedEvent>()
-            .init_resource::<DiceFxRollingTracker>()
-            .add_systems(Startup, crate::dice3d::init_dice_hanabi_fx_assets)
+        app.init_resource::<DiceFxRollingTracker>()
+            // The particle effects are built when the first roll starts, not at
+            // startup; dice only settle (and get effects) frames later.
+            .add_systems(
+                Update,
+                crate::dice3d::init_dice_hanabi_fx_assets.run_if(
+                    not(resource_exists::<crate::dice3d::DiceHanabiFxAssets>)
+                        .and(|roll_state: Res<crate::dice3d::RollState>| roll_state.rolling),
+                ),
+            )
             // Clear effects on roll start, apply on settle (effects persist until next roll).
             .add_systems(
                 Update,
@@ -24,15 +31,22 @@ impl Plugin for DiceFxPlugin {
             .add_systems(
                 Update,
                 crate::dice3d::apply_dice_fx_from_roll_complete
-                    .after(crate::dice3d::check_dice_settled),
+                    .after(crate::dice3d::check_dice_settled)
+                    .run_if(resource_exists::<crate::dice3d::DiceHanabiFxAssets>),
             )
             .add_systems(
                 Update,
-                crate::dice3d::update_dice_fx_loop_sfx
-                    .after(crate::dice3d::clear_dice_fx_on_roll_start)
-                    .after(crate::dice3d::apply_dice_fx_from_roll_complete),
+                crate::dice3d::spawn_electric_bolts
+                    .run_if(resource_exists::<crate::dice3d::DiceHanabiFxAssets>),
             )
-            .add_systems(Update, crate::dice3d::spawn_electric_bolts)
             .add_systems(Update, crate::dice3d::despawn_temporary_fx);
+
+        #[cfg(feature = "audio")]
+        app.add_systems(
+            Update,
+            crate::dice3d::update_dice_fx_loop_sfx
+                .after(crate::dice3d::clear_dice_fx_on_roll_start)
+                .after(crate::dice3d::apply_dice_fx_from_roll_complete),
+        );
     }
 }
@@ -0,0 +1,154 @@
+//! Reuse of generated dice meshes between rolls
+//!
+//! Every die used to get a freshly generated mesh, collider and a pair of
+//! number meshes per face, so a 10d6 roll added dozens of identical assets.
+//! The cache builds them the first time a die type (or face value) is needed
+//! and hands out the same handles afterwards. Colliders are cheap to clone:
+//! the shape behind them is shared.
+//!
+//! Die body materials are not cached because worn dice and other effects
+//! change them per die; the label materials never change and are shared, as
+//! are the number materials of dice skins (one per number color).
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::create_die_mesh_and_collider;
+use crate::dice3d::systems::rendering::{
+    create_digits_mesh, create_letter_mesh, create_number_mesh,
+};
+use crate::dice3d::types::{ColorSetting, DiceType};
+
+/// Mesh, collider and face normals shared by every die of one type
+#[derive(Clone)]
+pub struct CachedDieMesh {
+    pub mesh: Handle<Mesh>,
+    pub collider: Collider,
+    pub face_normals: Vec<(Vec3, u32)>,
+}
+
+/// Dice meshes, colliders, number meshes and label materials built so far
+#[derive(Resource, Default)]
+pub struct DiceMeshCache {
+    dice: HashMap<DiceType, CachedDieMesh>,
+    numbers: HashMap<u32, Handle<Mesh>>,
+    tens_numbers: HashMap<u32, Handle<Mesh>>,
+    letters: HashMap<char, Handle<Mesh>>,
+    label_materials: Option<(Handle<StandardMaterial>, Handle<StandardMaterial>)>,
+    number_materials: HashMap<[u8; 4], Handle<StandardMaterial>>,
+}
+
+impl DiceMeshCache {
+    /// The mesh and collider of a die type, generated on first use.
+    pub fn die(&mut self, die_type: DiceType, meshes: &mut ResMut<Assets<Mesh>>) -> CachedDieMesh {
+        // Custom dice are rolled with the d20 model, coins have their own
+        let key = match die_type {
+            DiceType::Custom(_) if !die_type.is_coin() => DiceType::D20,
+            other => other,
+        };
+        self.dice
+            .entry(key)
+            .or_insert_with(|| {
+                let (mesh, collider, face_normals) = create_die_mesh_and_collider(key);
+                CachedDieMesh {
+                    mesh: meshes.add(mesh),
+                    collider,
+                    face_normals,
+                }
+            })
+            .clone()
+    }
+
+    /// The mesh of a face number, used for both the label and its outline.
+    pub fn number(&mut self, value: u32, meshes: &mut ResMut<Assets<Mesh>>) -> Handle<Mesh> {
+        self.numbers
+            .entry(value)
+            .or_insert_with(|| create_number_mesh(value, meshes))
+            .clone()
+    }
+
+    /// The label of a percentile tens die face: "10" to "90", and "00" on
+    /// the tenth face.
+    pub fn tens_number(&mut self, face: u32, meshes: &mut ResMut<Assets<Mesh>>) -> Handle<Mesh> {
+        let tens = face % 10;
+        self.tens_numbers
+            .entry(tens)
+            .or_insert_with(|| meshes.add(create_digits_mesh(&[tens, 0])))
+            .clone()
+    }
+
+    /// The letter on a coin face: "H" for heads (2), "T" for tails (1).
+    pub fn coin_letter(&mut self, face: u32, meshes: &mut ResMut<Assets<Mesh>>) -> Handle<Mesh> {
+        self.letter(if face == 2 { 'H' } else { 'T' }, meshes)
+    }
+
+    /// The symbol on a Fudge die face: "+" (3) or "-" (1), `None` for the
+    /// blank faces (2).
+    pub fn fudge_symbol(
+        &mut self,
+        face: u32,
+        meshes: &mut ResMut<Assets<Mesh>>,
+    ) -> Option<Handle<Mesh>> {
+        match face {
+            1 => Some(self.letter('-', meshes)),
+            3 => Some(self.letter('+', meshes)),
+            _ => None,
+        }
+    }
+
+    fn letter(&mut self, letter: char, meshes: &mut ResMut<Assets<Mesh>>) -> Handle<Mesh> {
+        self.letters
+            .entry(letter)
+            .or_insert_with(|| meshes.add(create_letter_mesh(letter)))
+            .clone()
+    }
+
+    /// The black outline and white label materials of the face numbers.
+    pub fn label_materials(
+        &mut self,
+        materials: &mut ResMut<Assets<StandardMaterial>>,
+    ) -> (Handle<StandardMaterial>, Handle<StandardMaterial>) {
+        self.label_materials
+            .get_or_insert_with(|| {
+                let outline = materials.add(StandardMaterial {
+                    base_color: Color::srgb(0.0, 0.0, 0.0),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Opaque,
+                    ..default()
+                });
+                let label = materials.add(StandardMaterial {
+                    base_color: Color::srgb(1.0, 1.0, 1.0),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Opaque,
+                    ..default()
+                });
+                (outline, label)
+            })
+            .clone()
+    }
+
+    /// The face number material of a skin's number color (white is the shared label material).
+    pub fn number_material(
+        &mut self,
+        color: &ColorSetting,
+        materials: &mut ResMut<Assets<StandardMaterial>>,
+    ) -> Handle<StandardMaterial> {
+        let key = color.to_color().to_srgba().to_u8_array();
+        if key == [255, 255, 255, 255] {
+            return self.label_materials(materials).1;
+        }
+        self.number_materials
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color.to_color(),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Opaque,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
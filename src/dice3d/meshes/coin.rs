@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// A coin, the physical d2: heads (2) faces up along +Y, tails (1) down.
+pub fn create_coin() -> (Mesh, Collider, Vec<(Vec3, u32)>) {
+    let radius = 0.4;
+    let thickness = 0.08;
+
+    let face_normals = vec![(Vec3::Y, 2), (Vec3::NEG_Y, 1)];
+
+    let mesh = Mesh::from(Cylinder::new(radius, thickness));
+    let collider = Collider::cylinder(thickness / 2.0, radius);
+
+    (mesh, collider, face_normals)
+}
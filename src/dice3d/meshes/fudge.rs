@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::create_d6;
+
+/// A Fudge/FATE die: the d6 cube with `+` (3) faces on +Y and +X, `-` (1)
+/// faces opposite them, and blank (2) faces on ±Z.
+pub fn create_fudge() -> (Mesh, Collider, Vec<(Vec3, u32)>) {
+    let (mesh, collider, _) = create_d6();
+
+    let face_normals = vec![
+        (Vec3::Y, 3),
+        (Vec3::NEG_Y, 1),
+        (Vec3::X, 3),
+        (Vec3::NEG_X, 1),
+        (Vec3::Z, 2),
+        (Vec3::NEG_Z, 2),
+    ];
+
+    (mesh, collider, face_normals)
+}
@@ -1,22 +1,33 @@
+pub mod cache;
+pub mod coin;
 pub mod d10;
 pub mod d12;
 pub mod d20;
 pub mod d4;
 pub mod d6;
 pub mod d8;
+pub mod fudge;
 
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
 use crate::dice3d::types::DiceType;
 
+pub use cache::{CachedDieMesh, DiceMeshCache};
+pub use coin::create_coin;
 pub use d10::create_d10;
 pub use d12::create_d12;
 pub use d20::create_d20;
 pub use d4::{create_d4, get_d4_number_positions};
 pub use d6::create_d6;
 pub use d8::create_d8;
+pub use fudge::create_fudge;
 
+/// Mesh, collider and face normals for a die.
+///
+/// Fudge dice are d6 cubes with `+`, `-` and blank faces. Custom dice have
+/// no model of their own and get the d20's, except the d2, which is a coin;
+/// callers only spawn dice where [`DiceType::is_physical`] is true.
 pub fn create_die_mesh_and_collider(die_type: DiceType) -> (Mesh, Collider, Vec<(Vec3, u32)>) {
     match die_type {
         DiceType::D4 => create_d4(),
@@ -24,7 +35,9 @@ pub fn create_die_mesh_and_collider(die_type: DiceType) -> (Mesh, Collider, Vec<
         DiceType::D8 => create_d8(),
         DiceType::D10 => create_d10(),
         DiceType::D12 => create_d12(),
-        DiceType::D20 => create_d20(),
+        DiceType::Fudge => create_fudge(),
+        DiceType::Custom(2) => create_coin(),
+        DiceType::D20 | DiceType::Custom(_) => create_d20(),
     }
 }
 
@@ -96,6 +109,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_coin_has_heads_and_tails() {
+        let (_, _, face_normals) = create_coin();
+        assert_eq!(face_normals, vec![(Vec3::Y, 2), (Vec3::NEG_Y, 1)]);
+    }
+
+    #[test]
+    fn test_fudge_has_two_of_each_symbol() {
+        let (_, _, face_normals) = create_fudge();
+        assert_eq!(face_normals.len(), 6, "dF should have 6 face normals");
+        for symbol in 1..=3 {
+            let faces: Vec<Vec3> = face_normals
+                .iter()
+                .filter(|(_, value)| *value == symbol)
+                .map(|(normal, _)| *normal)
+                .collect();
+            assert_eq!(faces.len(), 2, "dF should have two faces of each symbol");
+        }
+        // `+` and `-` sit opposite each other
+        for (normal, value) in &face_normals {
+            let opposite = face_normals.iter().find(|(n, _)| *n == -*normal).unwrap();
+            assert_eq!(opposite.1, 4 - value);
+        }
+    }
+
     #[test]
     fn test_create_die_mesh_and_collider() {
         // Test that all dice types can be created
@@ -105,6 +143,8 @@ mod tests {
         let (_, _, d10_faces) = create_die_mesh_and_collider(DiceType::D10);
         let (_, _, d12_faces) = create_die_mesh_and_collider(DiceType::D12);
         let (_, _, d20_faces) = create_die_mesh_and_collider(DiceType::D20);
+        let (_, _, coin_faces) = create_die_mesh_and_collider(DiceType::Custom(2));
+        let (_, _, fudge_faces) = create_die_mesh_and_collider(DiceType::Fudge);
 
         assert_eq!(d4_faces.len(), 4);
         assert_eq!(d6_faces.len(), 6);
@@ -112,5 +152,7 @@ mod tests {
         assert_eq!(d10_faces.len(), 10);
         assert_eq!(d12_faces.len(), 12);
         assert_eq!(d20_faces.len(), 20);
+        assert_eq!(coin_faces.len(), 2);
+        assert_eq!(fudge_faces.len(), 6);
     }
 }
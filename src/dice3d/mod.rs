@@ -1,17 +1,43 @@
+#[cfg(feature = "gui")]
 pub mod box_highlight;
+#[cfg(feature = "hanabi")]
 pub mod dice_fx;
+#[cfg(feature = "gui")]
 pub mod embedded_assets;
+#[cfg(feature = "hanabi")]
 pub mod hanabi_fx;
+#[cfg(feature = "gui")]
 pub mod meshes;
+#[cfg(feature = "gui")]
+pub mod plugins;
+#[cfg(feature = "gui")]
+pub mod roll_events;
+#[cfg(feature = "gui")]
 pub mod systems;
+#[cfg(feature = "gui")]
 pub mod throw_control;
+#[cfg(feature = "gui")]
+pub mod tween;
 pub mod types;
 
+#[cfg(feature = "gui")]
 pub use box_highlight::*;
+#[cfg(feature = "hanabi")]
 pub use dice_fx::*;
+#[cfg(feature = "gui")]
 pub use embedded_assets::*;
+#[cfg(feature = "hanabi")]
 pub use hanabi_fx::*;
+#[cfg(feature = "gui")]
 pub use meshes::*;
+#[cfg(feature = "gui")]
+pub use plugins::*;
+#[cfg(feature = "gui")]
+pub use roll_events::*;
+#[cfg(feature = "gui")]
 pub use systems::*;
+#[cfg(feature = "gui")]
 pub use throw_control::*;
+#[cfg(feature = "gui")]
+pub use tween::*;
 pub use types::*;
@@ -0,0 +1,242 @@
+//! Character sheet plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet};
+use crate::dice3d::{
+    app_tab_first_opened, finalize_sqlite_conversion_if_done, handle_bulk_edit_clicks,
+    handle_cast_ritual_click, handle_cast_spell_click, handle_character_list_clicks,
+    handle_coin_click, handle_concentration_chip_click, handle_concentration_confirm_clicks,
+    handle_concentration_spell_click, handle_condition_toggle_click, handle_creation_kit_clicks,
+    handle_creation_method_click, handle_creation_nav_click, handle_creation_origin_clicks,
+    handle_creation_roll_click, handle_creation_score_clicks, handle_damage_rider_toggle_click,
+    handle_delete_click, handle_encryption_dialog_input, handle_exhaustion_click,
+    handle_expertise_toggle, handle_group_add_click, handle_group_edit_toggle,
+    handle_initiative_clicks, handle_inventory_toggle_click, handle_label_click,
+    handle_legacy_json_migration_clicks, handle_new_character_click, handle_new_entry_cancel,
+    handle_new_entry_confirm, handle_new_entry_input, handle_party_clicks,
+    handle_prepared_spell_click, handle_profile_switch_clicks, handle_reaction_confirm_clicks,
+    handle_ritual_spell_click, handle_roll_all_stats_click, handle_roll_attribute_click,
+    handle_roll_skill_click, handle_roll_spell_click, handle_save_click, handle_scroll_input,
+    handle_sheet_tab_clicks, handle_skill_ability_picker_click, handle_spell_slot_click,
+    handle_spell_slot_picker_click, handle_sqlite_conversion_no_click,
+    handle_sqlite_conversion_ok_click, handle_sqlite_conversion_yes_click, handle_stat_field_click,
+    handle_text_input, handle_travel_mode_clicks, init_character_manager, load_character_templates,
+    manage_bulk_edit_dialog, manage_concentration_confirm_dialog, manage_creation_wizard_dialog,
+    manage_encryption_dialog, manage_legacy_json_migration_dialog, manage_reaction_confirm_dialog,
+    manage_travel_mode_panel, open_unlock_dialog_if_locked, rebuild_character_list_on_change,
+    rebuild_character_panel_on_change, record_character_screen_roll_on_settle,
+    refresh_character_display, resolve_settled_rolls, run_sqlite_conversion_step,
+    setup_character_screen, setup_dnd_info_screen, setup_initiative_screen, setup_party_screen,
+    setup_tab_bar, start_legacy_json_migration_if_needed, start_sqlite_conversion_if_needed,
+    sync_character_screen_roll_result_texts, sync_skill_ability_picker_labels,
+    sync_spell_slot_picker_labels, update_character_list_modified_indicator,
+    update_damage_defenses_panel, update_editing_display, update_initiative_screen,
+    update_new_entry_input_display, update_party_screen, update_save_button_appearance,
+    update_sheet_tab_styles, update_sheet_tab_visibility, update_sqlite_conversion_dialog_ui,
+    update_turn_timer_label, AppTab,
+};
+
+/// The character screen, Initiative, Party and DnD Info tabs, the character database
+/// (profiles, encryption, legacy SQLite and JSON imports) and the party tools built
+/// on it.
+pub struct CharacterSheetPlugin;
+
+impl Plugin for CharacterSheetPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+
+        app.add_systems(
+            Startup,
+            (
+                (init_character_manager, open_unlock_dialog_if_locked)
+                    .chain()
+                    .in_set(AppStartupSet::LoadData),
+                (
+                    setup_character_screen,
+                    setup_initiative_screen,
+                    setup_party_screen,
+                )
+                    .chain()
+                    .after(setup_tab_bar)
+                    .in_set(AppStartupSet::SpawnUi),
+            ),
+        )
+        // The DnD Info markdown is read and laid out when the tab is opened
+        .add_systems(
+            Update,
+            setup_dnd_info_screen.run_if(app_tab_first_opened(AppTab::DndInfo)),
+        )
+        .add_systems(
+            Update,
+            (
+                // Legacy SQLite -> SurrealDB conversion (character screen)
+                start_sqlite_conversion_if_needed,
+                run_sqlite_conversion_step,
+                update_sqlite_conversion_dialog_ui,
+                handle_sqlite_conversion_ok_click,
+                handle_sqlite_conversion_yes_click,
+                handle_sqlite_conversion_no_click,
+                finalize_sqlite_conversion_if_done,
+                handle_character_list_clicks,
+                handle_new_character_click,
+                handle_save_click,
+            ),
+        )
+        // Legacy dnd_stats.json import, once the SQLite conversion is done
+        .add_systems(
+            Update,
+            (
+                start_legacy_json_migration_if_needed,
+                handle_legacy_json_migration_clicks,
+                manage_legacy_json_migration_dialog,
+            )
+                .chain()
+                .after(start_sqlite_conversion_if_needed)
+                .after(finalize_sqlite_conversion_if_done),
+        )
+        .add_systems(
+            Update,
+            record_character_screen_roll_on_settle.after(resolve_settled_rolls),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_concentration_chip_click,
+                handle_concentration_confirm_clicks,
+                manage_concentration_confirm_dialog,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (handle_bulk_edit_clicks, manage_bulk_edit_dialog)
+                .chain()
+                .before(rebuild_character_panel_on_change),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_creation_method_click,
+                handle_creation_score_clicks,
+                handle_creation_roll_click,
+                handle_creation_origin_clicks,
+                handle_creation_kit_clicks,
+                handle_creation_nav_click,
+                manage_creation_wizard_dialog,
+            )
+                .chain(),
+        )
+        .add_systems(Update, load_character_templates)
+        .add_systems(Update, handle_profile_switch_clicks)
+        .add_systems(
+            Update,
+            (
+                handle_initiative_clicks,
+                handle_reaction_confirm_clicks,
+                manage_reaction_confirm_dialog,
+                update_initiative_screen,
+                update_turn_timer_label,
+            )
+                .chain(),
+        )
+        .add_systems(Update, (handle_party_clicks, update_party_screen).chain())
+        .add_systems(
+            Update,
+            (handle_travel_mode_clicks, manage_travel_mode_panel).chain(),
+        )
+        .add_systems(
+            Update,
+            (handle_encryption_dialog_input, manage_encryption_dialog).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_skill_ability_picker_click,
+                sync_skill_ability_picker_labels,
+            )
+                .chain()
+                .after(rebuild_character_panel_on_change),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_spell_slot_picker_click,
+                sync_spell_slot_picker_labels,
+            )
+                .chain()
+                .after(rebuild_character_panel_on_change),
+        )
+        .add_systems(
+            Update,
+            (
+                // Character sheet tab systems
+                handle_sheet_tab_clicks,
+                update_sheet_tab_styles,
+                update_sheet_tab_visibility,
+                // Character editing systems - input handling
+                handle_scroll_input,
+                handle_stat_field_click,
+                handle_label_click,
+                handle_text_input,
+                handle_expertise_toggle,
+                handle_group_edit_toggle,
+                handle_group_add_click,
+                handle_delete_click,
+                handle_new_entry_confirm,
+                handle_new_entry_cancel,
+                handle_new_entry_input,
+                // Dice roll buttons for attributes
+                handle_roll_all_stats_click,
+                handle_roll_attribute_click,
+                handle_roll_skill_click,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                // Spells tab: slots, prepared spells, casting and spell rolls
+                handle_spell_slot_click,
+                handle_prepared_spell_click,
+                handle_concentration_spell_click,
+                handle_ritual_spell_click,
+                handle_cast_spell_click,
+                handle_cast_ritual_click,
+                handle_roll_spell_click,
+                // Combat tab: conditions, exhaustion and damage riders
+                handle_condition_toggle_click,
+                handle_exhaustion_click,
+                handle_damage_rider_toggle_click,
+                // Inventory tab: equipped and attuned items, and coins
+                handle_inventory_toggle_click,
+                handle_coin_click,
+            )
+                .before(rebuild_character_panel_on_change),
+        )
+        .add_systems(
+            Update,
+            update_damage_defenses_panel.after(rebuild_character_panel_on_change),
+        )
+        .add_systems(
+            Update,
+            (
+                // Character editing systems - display updates (run after input handling)
+                update_new_entry_input_display,
+                update_editing_display,
+                update_save_button_appearance,
+                update_character_list_modified_indicator,
+                refresh_character_display,
+                rebuild_character_list_on_change,
+                rebuild_character_panel_on_change,
+                sync_character_screen_roll_result_texts,
+            )
+                .chain()
+                .after(handle_new_entry_confirm)
+                .after(handle_delete_click)
+                .after(handle_roll_all_stats_click)
+                .after(handle_roll_attribute_click)
+                .after(handle_roll_skill_click)
+                .after(handle_text_input),
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Compendium plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet, GameRollsAppExt};
+use crate::dice3d::{
+    handle_compendium_input, handle_encounter_input, setup_compendium_screen,
+    setup_encounter_screen, sync_encounter_party, update_compendium_screen,
+    update_encounter_screen, CompendiumView, Encounter, EncounterBuilder, IconType,
+    MonsterCompendium, COMPENDIUM_TAB_ID, ENCOUNTER_TAB_ID,
+};
+
+/// The Monsters tab, which searches the bundled SRD monsters and rolls the
+/// attacks and saves of their stat blocks in the dice box, and the
+/// Encounter tab, which rates encounters built from them for the party and
+/// sends them to the Initiative tab. Built on the extension tab API like
+/// the Stats tab.
+pub struct CompendiumPlugin;
+
+impl Plugin for CompendiumPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+
+        app.add_app_tab(COMPENDIUM_TAB_ID, "Monsters", IconType::Info)
+            .add_app_tab(ENCOUNTER_TAB_ID, "Encounter", IconType::Roll)
+            .insert_resource(MonsterCompendium::srd())
+            .init_resource::<CompendiumView>()
+            .init_resource::<Encounter>()
+            .init_resource::<EncounterBuilder>()
+            .add_systems(
+                Startup,
+                (setup_compendium_screen, setup_encounter_screen).in_set(AppStartupSet::SpawnUi),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_compendium_input,
+                    update_compendium_screen,
+                    sync_encounter_party,
+                    handle_encounter_input,
+                    update_encounter_screen,
+                )
+                    .chain(),
+            );
+    }
+}
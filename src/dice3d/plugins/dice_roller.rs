@@ -0,0 +1,426 @@
+//! Dice roller plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet, GameRollsAppExt};
+#[cfg(feature = "hanabi")]
+use crate::dice3d::DiceFxPlugin;
+use crate::dice3d::{
+    advance_transform_tweens, aim_throw_with_gamepad, animate_container_shake, announce_quiz_rolls,
+    apply_crystal_material_to_container_models, apply_replayed_throws,
+    apply_spawn_points_to_dice_when_ready, assign_steered_results, cache_dice_box_lid_entity,
+    cancel_roll_on_escape, capture_throw_inputs, center_container_models_in_view,
+    check_dice_settled, collect_dice_spawn_points_from_gltf, dismiss_loading_splash,
+    drag_custom_quick_rolls, ensure_buttons_have_interaction, gather_settled_dice,
+    handle_command_history_item_clicks, handle_command_input, handle_custom_quick_roll_clicks,
+    handle_dice_box_rotate_click, handle_dice_box_shake_box_click,
+    handle_dice_box_toggle_container_click, handle_dice_palette_clicks, handle_input,
+    handle_key_bindings, handle_macros_screen_input, handle_quick_roll_clicks,
+    handle_quick_roll_dc_input, handle_quick_roll_editor_input, handle_safety_clicks,
+    handle_shake_slider_changes, handle_slider_group_drag, handle_strength_slider_changes,
+    handle_suggested_roll_clicks, handle_tab_clicks, handle_zoom_slider_changes,
+    load_automation_rules, load_damage_types, load_dice_macros, load_icons, load_inspiration_pool,
+    load_quiz_stats, load_ruleset, load_safety_tools, load_saved_parties, load_treasure_ledger,
+    manage_safety_pause_overlay, manage_safety_tools_panel, match_percentile_throws,
+    navigate_ui_focus, open_lid_on_roll_completed, pause_physics_when_unfocused,
+    play_background_music, play_macro_hotkeys, process_pending_roll_with_lid,
+    publish_hit_point_automation_events, publish_roll_automation_events,
+    rebuild_command_history_panel, rebuild_quick_roll_panel, record_macro_steps,
+    release_gathered_dice, remember_damage_rolls, remove_palette_die_on_right_click,
+    resolve_settled_rolls, rotate_camera, rumble_gamepads_on_dice, run_automation_rules,
+    run_concentration_command, run_damage_command, run_group_rolls, run_hoard_command,
+    run_initiative_command, run_inspiration_command, run_macro_command, run_macro_playback,
+    run_music_command, run_physics_repro_command, run_profile_command, run_quiz_command,
+    run_ritual_command, run_rule_command, run_ruleset_command, run_safety_command,
+    run_share_command, run_sync_command, run_template_command, run_timer_command,
+    run_travel_command, run_visibility_command, setup, setup_loading_splash, setup_macros_screen,
+    setup_tab_bar, spawn_colliders_from_gltf_guides, steer_dice_to_targets,
+    sync_dice_container_mode_text, sync_dice_container_toggle_icon, throw_exploding_dice,
+    tick_session_clock, tick_turn_timer, update_dice_box_highlight, update_dice_palette_badges,
+    update_focus_ring, update_macros_screen, update_results_display, update_suggested_rolls,
+    update_suggested_rolls_row, update_tab_styles, update_tab_visibility, update_throw_arrow,
+    update_throw_elevation, update_throw_from_mouse, update_ui_pointer_capture,
+    write_physics_repros, AutomationEvent, DiceBoxHighlightMaterial, DicePalette, FocusPause,
+    IconType, InspirationGranted, InspirationState, MacroEditor, PhysicsAnomaly, QuickRollDc,
+    QuickRollEditor, QuizState, RollEventsPlugin, SafetySignalSent, SafetyState, SuggestedRolls,
+    TreasureState, TurnTimerWarning, TweenPlugin, MACROS_TAB_ID,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::dice3d::{
+    broadcast_inspiration, broadcast_table_rolls, exchange_safety_signals, manage_table_feed_panel,
+    receive_table_rolls, send_client_safety_signals, settle_remote_dice, tint_remote_dice,
+    TableFeed,
+};
+#[cfg(feature = "audio")]
+use crate::dice3d::{
+    init_collision_sounds, play_dice_container_collision_sfx, play_roll_outcome_sfx,
+    play_turn_timer_sfx,
+};
+
+/// The dice box, rolling, the command input and quick rolls (with the
+/// user's own quick roll buttons), macros and their Macros tab, plus the app
+/// shell around them: the tab bar that switches between the tabs.
+pub struct DiceRollerPlugin;
+
+impl Plugin for DiceRollerPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+
+        app.add_plugins(bevy::pbr::MaterialPlugin::<DiceBoxHighlightMaterial>::default())
+            .add_plugins(TweenPlugin)
+            .add_plugins(RollEventsPlugin)
+            .add_message::<AutomationEvent>()
+            .add_message::<PhysicsAnomaly>()
+            .add_message::<TurnTimerWarning>()
+            .init_resource::<QuickRollDc>()
+            .init_resource::<QuickRollEditor>()
+            .init_resource::<DicePalette>()
+            .init_resource::<SuggestedRolls>()
+            .init_resource::<FocusPause>()
+            .init_resource::<QuizState>()
+            .init_resource::<TreasureState>()
+            .init_resource::<InspirationState>()
+            .add_message::<InspirationGranted>()
+            .init_resource::<SafetyState>()
+            .add_message::<SafetySignalSent>()
+            .add_app_tab(MACROS_TAB_ID, "Macros", IconType::Dice)
+            .init_resource::<MacroEditor>()
+            // Commands typed instead of a roll, tried in this order: `rules`
+            // lists the automation rules before `rules 2024` switches the ruleset
+            .add_typed_command(run_concentration_command)
+            .add_typed_command(run_ritual_command)
+            .add_typed_command(run_music_command)
+            .add_typed_command(run_travel_command)
+            .add_typed_command(run_initiative_command)
+            .add_typed_command(run_timer_command)
+            .add_typed_command(run_profile_command)
+            .add_typed_command(run_sync_command)
+            .add_typed_command(run_share_command)
+            .add_typed_command(run_visibility_command)
+            .add_typed_command(run_rule_command)
+            .add_typed_command(run_template_command)
+            .add_typed_command(run_macro_command)
+            .add_typed_command(run_physics_repro_command)
+            .add_typed_command(run_quiz_command)
+            .add_typed_command(run_hoard_command)
+            .add_typed_command(run_damage_command)
+            .add_typed_command(run_inspiration_command)
+            .add_typed_command(run_ruleset_command)
+            .add_typed_command(run_safety_command)
+            // Ensure UI Buttons spawned without ButtonBundle still receive click events
+            .add_systems(PreUpdate, ensure_buttons_have_interaction)
+            .add_systems(
+                Startup,
+                (
+                    (load_icons, load_automation_rules).in_set(AppStartupSet::PrepareResources),
+                    (setup, setup_tab_bar, setup_loading_splash)
+                        .chain()
+                        .in_set(AppStartupSet::SpawnUi),
+                    setup_macros_screen.in_set(AppStartupSet::SpawnUi),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Dice roller systems
+                    check_dice_settled,
+                    update_results_display.after(resolve_settled_rolls),
+                    handle_input,
+                    handle_command_input,
+                    rebuild_command_history_panel,
+                    handle_quick_roll_clicks,
+                    rebuild_quick_roll_panel,
+                    rotate_camera,
+                    handle_zoom_slider_changes,
+                    sync_dice_container_mode_text,
+                    sync_dice_container_toggle_icon,
+                    handle_dice_box_rotate_click,
+                    handle_dice_box_shake_box_click,
+                    animate_container_shake,
+                    handle_dice_box_toggle_container_click,
+                    // Mouse-controlled throw systems
+                    update_throw_from_mouse,
+                    update_dice_box_highlight,
+                    handle_strength_slider_changes,
+                    handle_shake_slider_changes,
+                    update_throw_arrow,
+                ),
+            )
+            .add_systems(
+                Update,
+                handle_quick_roll_dc_input.before(update_results_display),
+            )
+            .add_systems(
+                Update,
+                (handle_quick_roll_editor_input, drag_custom_quick_rolls)
+                    .chain()
+                    .before(rebuild_quick_roll_panel),
+            )
+            .add_systems(
+                Update,
+                update_ui_pointer_capture
+                    .before(handle_input)
+                    .before(update_throw_from_mouse)
+                    .before(update_dice_box_highlight),
+            )
+            .add_systems(Update, cache_dice_box_lid_entity)
+            .add_systems(
+                Update,
+                process_pending_roll_with_lid
+                    .after(cache_dice_box_lid_entity)
+                    .after(handle_input)
+                    .after(handle_quick_roll_clicks),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_dice_macros,
+                    record_macro_steps,
+                    play_macro_hotkeys,
+                    run_macro_playback
+                        .after(play_macro_hotkeys)
+                        .after(handle_command_input)
+                        .before(process_pending_roll_with_lid),
+                    load_saved_parties,
+                    run_group_rolls
+                        .after(run_macro_playback)
+                        .before(process_pending_roll_with_lid),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_custom_quick_roll_clicks,
+                    handle_dice_palette_clicks,
+                    handle_suggested_roll_clicks,
+                )
+                    .after(run_group_rolls)
+                    .before(process_pending_roll_with_lid),
+            )
+            .add_systems(
+                Update,
+                update_dice_palette_badges
+                    .after(handle_dice_palette_clicks)
+                    .after(remove_palette_die_on_right_click),
+            )
+            .add_systems(Update, remove_palette_die_on_right_click)
+            .add_systems(
+                Update,
+                (update_suggested_rolls, update_suggested_rolls_row).chain(),
+            )
+            .add_systems(
+                Update,
+                (handle_macros_screen_input, update_macros_screen)
+                    .chain()
+                    .after(load_dice_macros)
+                    .before(run_macro_playback),
+            )
+            .add_systems(
+                Update,
+                pause_physics_when_unfocused.before(check_dice_settled),
+            )
+            .add_systems(Update, open_lid_on_roll_completed.after(check_dice_settled))
+            .add_systems(Update, throw_exploding_dice.after(check_dice_settled))
+            .add_systems(
+                Update,
+                (
+                    gather_settled_dice.after(check_dice_settled),
+                    release_gathered_dice
+                        .after(handle_input)
+                        .after(handle_command_input)
+                        .after(handle_quick_roll_clicks)
+                        .after(process_pending_roll_with_lid)
+                        .after(run_macro_playback)
+                        .after(run_group_rolls)
+                        .before(advance_transform_tweens),
+                ),
+            )
+            .add_systems(
+                Update,
+                (assign_steered_results, steer_dice_to_targets)
+                    .chain()
+                    .before(check_dice_settled),
+            )
+            .add_systems(
+                Update,
+                match_percentile_throws
+                    .after(apply_spawn_points_to_dice_when_ready)
+                    .before(check_dice_settled),
+            )
+            // Physics debug capture: record throws after every system that
+            // starts or places them, save repros of rescued rolls
+            .add_systems(
+                Update,
+                (
+                    apply_replayed_throws
+                        .after(match_percentile_throws)
+                        .before(check_dice_settled),
+                    capture_throw_inputs
+                        .after(apply_replayed_throws)
+                        .after(handle_input)
+                        .after(handle_command_input)
+                        .after(process_pending_roll_with_lid)
+                        .before(check_dice_settled),
+                    write_physics_repros.after(check_dice_settled),
+                ),
+            )
+            .add_systems(
+                Update,
+                center_container_models_in_view
+                    .before(spawn_colliders_from_gltf_guides)
+                    .before(apply_crystal_material_to_container_models)
+                    .before(collect_dice_spawn_points_from_gltf)
+                    .before(apply_spawn_points_to_dice_when_ready)
+                    .before(update_dice_box_highlight),
+            )
+            // Separate to avoid Bevy's tuple-size limit, and ensure it runs before highlight tagging.
+            .add_systems(
+                Update,
+                spawn_colliders_from_gltf_guides
+                    .before(handle_dice_box_toggle_container_click)
+                    .before(update_dice_box_highlight),
+            )
+            .add_systems(
+                Update,
+                apply_crystal_material_to_container_models
+                    .before(handle_dice_box_toggle_container_click)
+                    .before(update_dice_box_highlight),
+            )
+            .add_systems(
+                Update,
+                collect_dice_spawn_points_from_gltf
+                    .before(handle_dice_box_toggle_container_click)
+                    .before(update_dice_box_highlight),
+            )
+            .add_systems(
+                Update,
+                apply_spawn_points_to_dice_when_ready
+                    .before(handle_dice_box_toggle_container_click)
+                    .before(update_dice_box_highlight),
+            )
+            .add_systems(Update, dismiss_loading_splash)
+            .add_systems(Update, handle_command_history_item_clicks)
+            .add_systems(Update, handle_slider_group_drag)
+            .add_systems(Update, tick_session_clock)
+            .add_systems(Update, tick_turn_timer)
+            .add_systems(Update, play_background_music)
+            .add_systems(
+                Update,
+                (
+                    aim_throw_with_gamepad
+                        .after(update_throw_from_mouse)
+                        .before(handle_input),
+                    update_throw_elevation
+                        .before(update_throw_arrow)
+                        .before(handle_input),
+                    cancel_roll_on_escape
+                        .before(check_dice_settled)
+                        .before(handle_input),
+                    rumble_gamepads_on_dice.after(check_dice_settled),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    publish_roll_automation_events,
+                    publish_hit_point_automation_events,
+                    run_automation_rules,
+                )
+                    .chain()
+                    .after(resolve_settled_rolls),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_quiz_stats.before(handle_command_input),
+                    announce_quiz_rolls.after(resolve_settled_rolls),
+                ),
+            )
+            .add_systems(Update, load_treasure_ledger.before(handle_command_input))
+            .add_systems(Update, load_inspiration_pool.before(handle_command_input))
+            .add_systems(Update, load_ruleset.before(handle_command_input))
+            .add_systems(
+                Update,
+                (
+                    load_safety_tools.before(handle_command_input),
+                    (
+                        handle_safety_clicks,
+                        manage_safety_tools_panel,
+                        manage_safety_pause_overlay,
+                    )
+                        .chain()
+                        .after(handle_command_input),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_damage_types.before(handle_command_input),
+                    remember_damage_rolls.after(resolve_settled_rolls),
+                ),
+            )
+            // App shell: switching between tabs
+            .add_systems(
+                Update,
+                (handle_tab_clicks, update_tab_visibility, update_tab_styles),
+            )
+            // Keyboard and gamepad navigation; presses land before this
+            // frame's click handlers
+            .add_systems(
+                PreUpdate,
+                (
+                    navigate_ui_focus.after(bevy::ui::UiSystems::Focus),
+                    handle_key_bindings.after(navigate_ui_focus),
+                ),
+            )
+            .add_systems(Update, update_focus_ring);
+
+        #[cfg(feature = "hanabi")]
+        app.add_plugins(DiceFxPlugin);
+
+        // `--serve`: only runs once main has inserted the TableServer
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, broadcast_table_rolls.after(resolve_settled_rolls))
+            .add_systems(Update, broadcast_inspiration.after(handle_command_input))
+            .add_systems(
+                Update,
+                exchange_safety_signals
+                    .after(handle_safety_clicks)
+                    .before(manage_safety_pause_overlay),
+            );
+
+        // `--join`: only runs once main has inserted the TableClient
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<TableFeed>()
+            .add_systems(
+                Update,
+                (
+                    receive_table_rolls,
+                    tint_remote_dice,
+                    settle_remote_dice,
+                    manage_table_feed_panel,
+                )
+                    .chain()
+                    .before(check_dice_settled)
+                    .before(manage_safety_pause_overlay),
+            )
+            .add_systems(
+                Update,
+                send_client_safety_signals.after(handle_safety_clicks),
+            );
+
+        #[cfg(feature = "audio")]
+        app.add_systems(
+            Startup,
+            init_collision_sounds.in_set(AppStartupSet::PrepareResources),
+        )
+        .add_systems(
+            Update,
+            (
+                play_dice_container_collision_sfx,
+                play_roll_outcome_sfx,
+                play_turn_timer_sfx,
+            ),
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Extension trait for other crates
+//!
+//! Another crate builds the app from the feature plugins and adds its own
+//! tabs, dice aliases, typed commands and integrations through
+//! `GameRollsAppExt`:
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use dndgamerolls::dice3d::*;
+//!
+//! struct DiscordPlugin;
+//!
+//! impl Plugin for DiscordPlugin {
+//!     fn build(&self, app: &mut App) {
+//!         // Post every resolved roll to the table's channel
+//!         app.add_systems(Update, post_resolved_rolls);
+//!     }
+//! }
+//!
+//! app.add_plugins((DiceRollerPlugin, CharacterSheetPlugin, SettingsPlugin, IntegrationsPlugin))
+//!     .add_app_tab("spells", "Spells", IconType::Info)
+//!     .add_systems(Startup, spawn_spells_tab.in_set(AppStartupSet::SpawnUi))
+//!     .add_dice_alias("stress", "1d10")
+//!     .add_typed_command(run_downtime_command)
+//!     .add_integration("Discord", DiscordPlugin);
+//! ```
+//!
+//! - Tabs: the root node of the tab's UI must carry `ExtensionTabRoot(id)`;
+//!   it is shown while the tab is active (`AppTab::Extension(id)`). Spawn it
+//!   in `AppStartupSet::SpawnUi`.
+//! - Dice aliases: typing the name in the command input rolls the
+//!   expression, alone or with `--checkon` and modifiers.
+//! - Typed commands: the command implements `TypedCommand`, and the system
+//!   running it takes `In<DowntimeCommand>`. Commands are tried in the order
+//!   they were added, before the text is rolled.
+//! - Integrations: read the roll lifecycle messages (`RollRequested`,
+//!   `DiceSettled`, `RollResolved`, ...) rather than the dice systems.
+
+use bevy::prelude::*;
+
+use crate::dice3d::{
+    DiceAliases, DiceExpr, ExtensionTab, ExtensionTabs, IconType, Integrations, TypedCommand,
+    TypedCommands,
+};
+
+/// Extension points of the 3D app, implemented for `App`
+///
+/// Call these while building the app; tabs added once the app is running do
+/// not show up in the tab bar.
+pub trait GameRollsAppExt {
+    /// Add a tab after the built-in ones. `id` must be unique.
+    fn add_app_tab(&mut self, id: &'static str, label: &str, icon: IconType) -> &mut Self;
+
+    /// Make `name` in the command input roll `expression` ("1d10", "2d6+3").
+    fn add_dice_alias(&mut self, name: &str, expression: &str) -> &mut Self;
+
+    /// Run the system `run` for text in the command input that `C` reads.
+    fn add_typed_command<C: TypedCommand, M>(
+        &mut self,
+        run: impl IntoSystem<In<C>, (), M> + 'static,
+    ) -> &mut Self;
+
+    /// Add an integration's plugin and list it under `name`.
+    fn add_integration(&mut self, name: &str, plugin: impl Plugin) -> &mut Self;
+}
+
+impl GameRollsAppExt for App {
+    fn add_app_tab(&mut self, id: &'static str, label: &str, icon: IconType) -> &mut Self {
+        let mut tabs = self.world_mut().get_resource_or_init::<ExtensionTabs>();
+        if tabs.position(id).is_some() {
+            warn!("Tab '{}' was already added", id);
+        } else {
+            tabs.tabs.push(ExtensionTab {
+                id,
+                label: label.to_string(),
+                icon,
+            });
+        }
+        self
+    }
+
+    fn add_dice_alias(&mut self, name: &str, expression: &str) -> &mut Self {
+        if let Err(e) = DiceExpr::parse(expression) {
+            warn!("Dice alias '{}' ignored: {}", name, e);
+            return self;
+        }
+        self.world_mut()
+            .get_resource_or_init::<DiceAliases>()
+            .insert(name, expression);
+        self
+    }
+
+    fn add_typed_command<C: TypedCommand, M>(
+        &mut self,
+        run: impl IntoSystem<In<C>, (), M> + 'static,
+    ) -> &mut Self {
+        let run = self.world_mut().register_system(run);
+        self.world_mut()
+            .get_resource_or_init::<TypedCommands>()
+            .insert(run);
+        self
+    }
+
+    fn add_integration(&mut self, name: &str, plugin: impl Plugin) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<Integrations>()
+            .names
+            .push(name.to_string());
+        self.add_plugins(plugin)
+    }
+}
@@ -0,0 +1,76 @@
+//! Integrations plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet};
+#[cfg(feature = "contributors")]
+use crate::dice3d::request_avatars;
+use crate::dice3d::{
+    app_tab_first_opened, handle_character_file_dialog_clicks, handle_import_character_click,
+    handle_platform_menu_requests, handle_qr_share_dialog_clicks, init_contributors,
+    install_platform_menu, manage_character_file_dialog, manage_qr_share_dialog,
+    open_pending_character_file, process_avatar_loads, setup_contributors_screen,
+    update_avatar_images, AppTab, Integrations,
+};
+
+/// Everything that talks to the outside world: the platform menu, character
+/// files and QR sharing, and the Contributors tab with its GitHub avatars.
+///
+/// Without the `integrations` feature there are no native file dialogs or
+/// macOS menu bar; without `contributors` the avatars aren't downloaded.
+pub struct IntegrationsPlugin;
+
+impl Plugin for IntegrationsPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+
+        app.add_systems(
+            Startup,
+            (
+                install_platform_menu.in_set(AppStartupSet::PrepareResources),
+                log_integrations.in_set(AppStartupSet::ApplySettings),
+            ),
+        )
+        // The Contributors tab (and its avatar downloads) waits until it's opened
+        .add_systems(
+            Update,
+            (init_contributors, setup_contributors_screen)
+                .chain()
+                .run_if(app_tab_first_opened(AppTab::Contributors)),
+        )
+        .add_systems(
+            Update,
+            (
+                // Avatar loading systems
+                process_avatar_loads,
+                update_avatar_images,
+            ),
+        )
+        .add_systems(Update, handle_platform_menu_requests)
+        .add_systems(
+            Update,
+            (
+                handle_import_character_click,
+                open_pending_character_file,
+                handle_character_file_dialog_clicks,
+                manage_character_file_dialog,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (handle_qr_share_dialog_clicks, manage_qr_share_dialog).chain(),
+        );
+
+        // Avatars are downloaded from GitHub
+        #[cfg(feature = "contributors")]
+        app.add_systems(Update, request_avatars);
+    }
+}
+
+/// Log the integrations other crates added.
+fn log_integrations(integrations: Res<Integrations>) {
+    if !integrations.names.is_empty() {
+        info!("Integrations: {}", integrations.names.join(", "));
+    }
+}
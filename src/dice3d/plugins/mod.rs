@@ -0,0 +1,147 @@
+//! Feature plugins
+//!
+//! The 3D app is assembled from one plugin per feature area:
+//! - `dice_roller` - `DiceRollerPlugin`: the dice box, rolls, command input,
+//!   macros (and the Macros tab) and the tab bar
+//! - `character_sheet` - `CharacterSheetPlugin`: the character screen,
+//!   Initiative and DnD Info tabs, profiles, encryption and party tools
+//! - `settings` - `SettingsPlugin`: the settings modals and their persistence
+//! - `integrations` - `IntegrationsPlugin`: platform menu, character files and
+//!   QR sharing, contributors
+//! - `stats` - `StatsPlugin`: saved rolls and the Stats tab
+//! - `compendium` - `CompendiumPlugin`: the Monsters tab (SRD stat blocks)
+//!   and the Encounter tab
+//! - `extension` - `GameRollsAppExt`, for other crates to add tabs, dice
+//!   aliases and integrations
+//!
+//! Startup systems of every plugin run in the `AppStartupSet` stages, so a
+//! plugin can be left out or added by another crate without losing the
+//! startup order.
+
+mod character_sheet;
+mod compendium;
+mod dice_roller;
+mod extension;
+mod integrations;
+mod settings;
+mod stats;
+
+pub use character_sheet::*;
+pub use compendium::*;
+pub use dice_roller::*;
+pub use extension::*;
+pub use integrations::*;
+pub use settings::*;
+pub use stats::*;
+
+use bevy::prelude::*;
+
+use crate::dice3d::meshes::DiceMeshCache;
+use crate::dice3d::types::ui::UiPointerCapture;
+use crate::dice3d::{
+    AddingEntryState, AutomationRules, AvatarLoader, CharacterCreationWizard, CharacterData,
+    CharacterFileDialogState, CharacterScreenRollBridge, CharacterTemplates, CommandHistory,
+    CommandInput, ConcentrationPrompt, ContainerShakeAnimation, ContainerShakeConfig,
+    DamageTypesState, DiceAliases, DiceBoxLidAnimationController, DiceConfig, DiceContainerStyle,
+    DiceMacros, DiceResults, DiceSpawnPoints, DiceSpawnPointsApplied, EncryptionDialogState,
+    EncryptionPassphraseInput, ExtensionTabs, GroupEditState, InitiativeTracker, Integrations,
+    LegacyJsonMigrationState, MacroPlayback, MacroRecorder, MusicPlayer, PendingCharacterFileOpen,
+    PendingExplosionThrows, PhysicsReproCapture, ProficiencyBulkEdit, QrScanState,
+    QrShareDialogState, ReactionPrompt, ReadiedAction, RollState, Ruleset, SaveVsAbility,
+    SessionClock, SettingsState, ShakeState, SkillAbilityOverrides, SpellSlotChoices, ThemeTokens,
+    ThrowControlState, TravelModePanel, TurnTimer, TypedCommands, UiFocus, UiState, ZoomState,
+};
+
+/// Startup stages shared by the plugins, run in this order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppStartupSet {
+    /// Open the database and read the settings
+    LoadData,
+    /// Load assets and build resources from the loaded data
+    PrepareResources,
+    /// Spawn the UI of every tab (extension tabs spawn theirs here too)
+    SpawnUi,
+    /// Apply the settings to the spawned UI
+    ApplySettings,
+}
+
+/// Order the startup stages and add the resources the plugins share.
+///
+/// Systems of one plugin read resources owned by another (the command input
+/// also handles character and sharing commands), so every plugin calls this.
+/// `init_resource` keeps a resource that was inserted first, such as the
+/// `DiceConfig` built from the command line.
+fn init_shared(app: &mut App) {
+    app.configure_sets(
+        Startup,
+        (
+            AppStartupSet::LoadData,
+            AppStartupSet::PrepareResources,
+            AppStartupSet::SpawnUi,
+            AppStartupSet::ApplySettings,
+        )
+            .chain(),
+    )
+    .init_resource::<DiceConfig>()
+    .init_resource::<CharacterData>()
+    .init_resource::<DiceResults>()
+    .init_resource::<PendingExplosionThrows>()
+    .init_resource::<RollState>()
+    .init_resource::<RollRng>()
+    .init_resource::<CommandInput>()
+    .init_resource::<CommandHistory>()
+    .init_resource::<ZoomState>()
+    .init_resource::<UiState>()
+    .init_resource::<DiceContainerStyle>()
+    .init_resource::<ShakeState>()
+    .init_resource::<ContainerShakeAnimation>()
+    .init_resource::<ContainerShakeConfig>()
+    .init_resource::<GroupEditState>()
+    .init_resource::<AddingEntryState>()
+    .init_resource::<SettingsState>()
+    .init_resource::<ThemeTokens>()
+    .init_resource::<MusicPlayer>()
+    .init_resource::<CharacterScreenRollBridge>()
+    .init_resource::<UiPointerCapture>()
+    .init_resource::<ThrowControlState>()
+    .init_resource::<DiceSpawnPoints>()
+    .init_resource::<DiceMeshCache>()
+    .init_resource::<DiceSpawnPointsApplied>()
+    .init_resource::<AvatarLoader>()
+    .init_resource::<DiceBoxLidAnimationController>()
+    .init_resource::<InitiativeTracker>()
+    .init_resource::<Ruleset>()
+    .init_resource::<TurnTimer>()
+    .init_resource::<SaveVsAbility>()
+    .init_resource::<ReadiedAction>()
+    .init_resource::<ReactionPrompt>()
+    .init_resource::<Parties>()
+    .init_resource::<PartySelection>()
+    .init_resource::<GroupRollQueue>()
+    .init_resource::<ConcentrationPrompt>()
+    .init_resource::<ProficiencyBulkEdit>()
+    .init_resource::<CharacterCreationWizard>()
+    .insert_resource(CharacterTemplates::srd())
+    .init_resource::<SessionClock>()
+    .init_resource::<SkillAbilityOverrides>()
+    .init_resource::<SpellSlotChoices>()
+    .init_resource::<TravelModePanel>()
+    .init_resource::<EncryptionDialogState>()
+    .init_resource::<EncryptionPassphraseInput>()
+    .init_resource::<CharacterFileDialogState>()
+    .init_resource::<LegacyJsonMigrationState>()
+    .init_resource::<QrShareDialogState>()
+    .init_resource::<QrScanState>()
+    .init_resource::<AutomationRules>()
+    .init_resource::<DiceMacros>()
+    .init_resource::<MacroRecorder>()
+    .init_resource::<MacroPlayback>()
+    .init_resource::<PhysicsReproCapture>()
+    .init_resource::<DamageTypesState>()
+    .init_resource::<PendingCharacterFileOpen>()
+    .init_resource::<DiceAliases>()
+    .init_resource::<TypedCommands>()
+    .init_resource::<ExtensionTabs>()
+    .init_resource::<Integrations>()
+    .init_resource::<UiFocus>();
+}
@@ -0,0 +1,183 @@
+//! Settings plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet};
+use crate::dice3d::{
+    apply_dice_scale_settings_to_existing_dice, apply_dice_skins, apply_dice_wear,
+    apply_editing_dice_scales_to_existing_dice_while_open, apply_initial_settings,
+    apply_initial_shake_config, apply_physics_timestep, apply_settings_search, apply_ui_scale,
+    autosave_and_apply_shake_config, capture_key_binding, drag_shake_curve_bezier_handle,
+    drag_shake_curve_point, fix_dice_scale_slider_thumb_hitbox,
+    handle_animation_speed_slider_changes, handle_audio_setting_changes, handle_chaos_dice_changes,
+    handle_character_sheet_die_type_select_change, handle_character_sheet_settings_button_click,
+    handle_character_sheet_settings_cancel_click, handle_character_sheet_settings_save_click,
+    handle_color_slider_changes, handle_color_text_input, handle_custom_theme_buttons,
+    handle_custom_theme_select_change, handle_default_roll_uses_shake_switch_change,
+    handle_dice_fx_param_slider_changes, handle_dice_roll_fx_mapping_select_change,
+    handle_dice_scale_slider_changes, handle_dice_skin_select_change,
+    handle_explosion_cap_slider_changes, handle_game_system_select_change,
+    handle_key_binding_buttons, handle_physics_switch_changes,
+    handle_quick_roll_die_type_select_change, handle_result_order_select_change,
+    handle_settings_button_click, handle_settings_cancel_click, handle_settings_export_click,
+    handle_settings_import_click, handle_settings_ok_click, handle_settings_reset_layout_click,
+    handle_shake_curve_bezier_handle_press, handle_shake_curve_chip_clicks,
+    handle_shake_curve_graph_click_to_add_point, handle_shake_curve_point_press,
+    handle_shake_duration_text_input, handle_theme_seed_select_change,
+    handle_theme_token_text_input, handle_ui_scale_setting_changes,
+    init_dice_scale_preview_render_target, init_settings_ui_images, load_settings_state_from_db,
+    manage_character_sheet_settings_modal, manage_dice_scale_preview_scene, manage_settings_modal,
+    navigate_ui_focus, open_unlock_dialog_if_locked, persist_settings_to_db,
+    refresh_scrollbar_colors_on_theme_change, refresh_theme_token_colors,
+    remember_settings_tab_scroll, sync_dice_scale_preview_dice, sync_shake_curve_chip_ui,
+    sync_shake_curve_graph_ui, sync_theme_tokens_from_settings, tint_recent_theme_dropdown_items,
+    update_animation_speed_ui, update_audio_volume_ui, update_color_ui, update_dice_fx_param_ui,
+    update_dice_scale_ui, update_explosion_cap_ui, update_key_bindings_ui, update_theme_token_ui,
+    update_ui_scale_setting_ui, SettingsTabScrollPositions,
+};
+
+/// The settings modal and the character sheet dice settings modal, and
+/// loading and saving the settings of the current profile.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+        app.init_resource::<SettingsTabScrollPositions>();
+
+        app.add_systems(
+            Startup,
+            (
+                load_settings_state_from_db
+                    .after(open_unlock_dialog_if_locked)
+                    .in_set(AppStartupSet::LoadData),
+                (
+                    init_dice_scale_preview_render_target,
+                    init_settings_ui_images,
+                    apply_initial_shake_config,
+                )
+                    .in_set(AppStartupSet::PrepareResources),
+                apply_initial_settings.in_set(AppStartupSet::ApplySettings),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                (
+                    // Settings systems
+                    (
+                        handle_settings_button_click,
+                        manage_settings_modal,
+                        manage_dice_scale_preview_scene,
+                        fix_dice_scale_slider_thumb_hitbox.after(manage_settings_modal),
+                        handle_settings_ok_click,
+                        handle_settings_cancel_click,
+                        handle_settings_reset_layout_click,
+                        handle_settings_export_click,
+                        handle_settings_import_click,
+                        handle_custom_theme_buttons,
+                    ),
+                    (
+                        (
+                            handle_quick_roll_die_type_select_change,
+                            handle_theme_seed_select_change,
+                            handle_custom_theme_select_change,
+                            handle_default_roll_uses_shake_switch_change,
+                            handle_physics_switch_changes,
+                            handle_color_slider_changes,
+                            handle_dice_scale_slider_changes,
+                            handle_dice_fx_param_slider_changes,
+                            handle_animation_speed_slider_changes,
+                            handle_explosion_cap_slider_changes,
+                            handle_audio_setting_changes,
+                            handle_ui_scale_setting_changes,
+                            handle_dice_roll_fx_mapping_select_change,
+                            handle_dice_skin_select_change,
+                            handle_game_system_select_change,
+                            handle_result_order_select_change,
+                            handle_color_text_input,
+                            handle_chaos_dice_changes,
+                            handle_theme_token_text_input,
+                            handle_shake_duration_text_input,
+                        ),
+                        (
+                            handle_shake_curve_chip_clicks,
+                            (
+                                handle_shake_curve_point_press,
+                                handle_shake_curve_bezier_handle_press,
+                                handle_shake_curve_graph_click_to_add_point,
+                                drag_shake_curve_bezier_handle,
+                                drag_shake_curve_point,
+                                sync_shake_curve_graph_ui,
+                            )
+                                .chain(),
+                            sync_shake_curve_chip_ui,
+                        ),
+                    ),
+                    (
+                        update_color_ui,
+                        update_theme_token_ui,
+                        update_dice_scale_ui,
+                        update_dice_fx_param_ui,
+                        update_animation_speed_ui,
+                        update_explosion_cap_ui,
+                        update_audio_volume_ui,
+                        update_ui_scale_setting_ui,
+                        sync_dice_scale_preview_dice,
+                        autosave_and_apply_shake_config.after(sync_shake_curve_graph_ui),
+                    ),
+                ),
+                (
+                    // Character sheet dice settings modal
+                    handle_character_sheet_settings_button_click,
+                    manage_character_sheet_settings_modal,
+                    handle_character_sheet_die_type_select_change,
+                    handle_character_sheet_settings_save_click,
+                    handle_character_sheet_settings_cancel_click,
+                ),
+            ),
+        )
+        .add_systems(
+            Update,
+            apply_editing_dice_scales_to_existing_dice_while_open
+                .after(handle_dice_scale_slider_changes),
+        )
+        .add_systems(
+            Update,
+            apply_dice_scale_settings_to_existing_dice.after(handle_settings_ok_click),
+        )
+        .add_systems(
+            Update,
+            refresh_scrollbar_colors_on_theme_change
+                .after(handle_color_text_input)
+                .after(handle_theme_seed_select_change)
+                .after(handle_settings_ok_click)
+                .after(handle_settings_cancel_click),
+        )
+        .add_systems(
+            Update,
+            remember_settings_tab_scroll.after(manage_settings_modal),
+        )
+        .add_systems(Update, apply_settings_search.after(manage_settings_modal))
+        // Keys tab: the key waited for is taken before the focus ring sees it
+        .add_systems(
+            PreUpdate,
+            capture_key_binding
+                .after(bevy::ui::UiSystems::Focus)
+                .before(navigate_ui_focus),
+        )
+        .add_systems(
+            Update,
+            (handle_key_binding_buttons, update_key_bindings_ui).chain(),
+        )
+        .add_systems(Update, apply_ui_scale)
+        .add_systems(Update, apply_physics_timestep)
+        .add_systems(Update, apply_dice_skins.before(apply_dice_wear))
+        .add_systems(PostUpdate, tint_recent_theme_dropdown_items)
+        .add_systems(
+            PostUpdate,
+            (sync_theme_tokens_from_settings, refresh_theme_token_colors).chain(),
+        )
+        .add_systems(PostUpdate, persist_settings_to_db);
+    }
+}
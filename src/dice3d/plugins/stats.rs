@@ -0,0 +1,43 @@
+//! Stats plugin
+
+use bevy::prelude::*;
+
+use super::{init_shared, AppStartupSet, GameRollsAppExt};
+use crate::dice3d::{
+    apply_dice_wear, handle_worn_dice_toggle, load_achievements, load_roll_stats,
+    record_resolved_rolls, resolve_settled_rolls, setup_stats_screen, track_death_saves,
+    unlock_roll_achievements, update_achievements_page, update_stats_screen, AchievementsState,
+    IconType, RollStatsState, STATS_TAB_ID,
+};
+
+/// The Stats tab: saves every resolved roll and shows the statistics of the
+/// saved rolls, gives much-rolled dice a worn finish and unlocks achievements
+/// (shown on the Contributors tab). Built on the extension tab API like a
+/// third-party tab.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        init_shared(app);
+
+        app.add_app_tab(STATS_TAB_ID, "Stats", IconType::Roll)
+            .init_resource::<RollStatsState>()
+            .init_resource::<AchievementsState>()
+            .add_systems(Startup, setup_stats_screen.in_set(AppStartupSet::SpawnUi))
+            .add_systems(
+                Update,
+                (
+                    load_roll_stats,
+                    load_achievements,
+                    record_resolved_rolls.after(resolve_settled_rolls),
+                    unlock_roll_achievements,
+                    track_death_saves,
+                    handle_worn_dice_toggle,
+                    update_stats_screen,
+                    update_achievements_page,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, apply_dice_wear);
+    }
+}
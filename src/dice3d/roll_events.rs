@@ -0,0 +1,25 @@
+//! Roll lifecycle events
+
+use bevy::prelude::*;
+
+use crate::dice3d::types::{DiceSettled, DiceSpawned, RollCancelled, RollRequested, RollResolved};
+
+pub struct RollEventsPlugin;
+
+impl Plugin for RollEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<RollRequested>()
+            .add_message::<DiceSpawned>()
+            .add_message::<DiceSettled>()
+            .add_message::<RollResolved>()
+            .add_message::<RollCancelled>()
+            .add_systems(
+                Update,
+                (
+                    crate::dice3d::record_requested_commands,
+                    crate::dice3d::publish_spawned_dice,
+                    crate::dice3d::resolve_settled_rolls.after(crate::dice3d::check_dice_settled),
+                ),
+            );
+    }
+}
@@ -0,0 +1,276 @@
+//! Achievements
+//!
+//! Unlocks the local achievements (see `Achievement`) as rolls resolve,
+//! announces each one in a snackbar and saves them to the database. Death
+//! saves rolled with `--checkon death` are marked on the current character's
+//! sheet, which is what unlocks Back from the Brink.
+//!
+//! The achievements page is a section of the Contributors tab; the tab spawns
+//! an [`AchievementsPageBody`] and it is filled in here.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::stats_screen::RollStatsState;
+use crate::dice3d::types::{
+    format_day, is_death_save_label, now_seconds, roll_achievements, Achievement, Achievements,
+    CharacterData, CharacterDatabase, DeathSaveOutcome, RollResolved, ACHIEVEMENTS_SETTING_KEY,
+};
+
+/// Unlocked achievements of the current profile
+#[derive(Resource)]
+pub struct AchievementsState {
+    pub achievements: Achievements,
+    /// When this run of the app started (seconds since the Unix epoch)
+    pub session_start: u64,
+}
+
+impl Default for AchievementsState {
+    fn default() -> Self {
+        Self {
+            achievements: Achievements::default(),
+            session_start: now_seconds(),
+        }
+    }
+}
+
+/// Node the achievements page is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct AchievementsPageBody;
+
+/// Load the unlocked achievements whenever the database is opened or the
+/// profile switches.
+pub fn load_achievements(db: Option<Res<CharacterDatabase>>, mut state: ResMut<AchievementsState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.achievements = db
+        .get_setting::<Achievements>(ACHIEVEMENTS_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Unlock the achievements earned by the saved rolls after each roll.
+pub fn unlock_roll_achievements(
+    mut resolved: MessageReader<RollResolved>,
+    stats: Res<RollStatsState>,
+    db: Option<Res<CharacterDatabase>>,
+    mut state: ResMut<AchievementsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if resolved.read().count() == 0 {
+        return;
+    }
+    let earned = roll_achievements(&stats.records, state.session_start);
+    for achievement in earned {
+        if state.achievements.is_unlocked(achievement) {
+            continue;
+        }
+        unlock(achievement, db.as_deref(), &mut state, &mut snackbar);
+    }
+}
+
+/// Mark death saves on the current character's sheet.
+pub fn track_death_saves(
+    mut resolved: MessageReader<RollResolved>,
+    mut character_data: ResMut<CharacterData>,
+    db: Option<Res<CharacterDatabase>>,
+    mut state: ResMut<AchievementsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for roll in resolved.read() {
+        if !is_death_save_label(&roll.label) {
+            continue;
+        }
+        let Some(face) = roll.natural_d20 else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+
+        let failures_before = sheet
+            .combat
+            .death_saves
+            .as_ref()
+            .map(|saves| saves.failures)
+            .unwrap_or(0);
+        let outcome = sheet.record_death_save(face);
+        let saves = sheet.combat.death_saves.clone().unwrap_or_default();
+        let message = match outcome {
+            DeathSaveOutcome::Success | DeathSaveOutcome::Failure => format!(
+                "Death save {}: {} of 3 successes, {} of 3 failures",
+                if outcome.survived() {
+                    "passed"
+                } else {
+                    "failed"
+                },
+                saves.successes,
+                saves.failures
+            ),
+            DeathSaveOutcome::Stable => format!("{} is stable", sheet.character.name),
+            DeathSaveOutcome::Dead => format!("{} has died", sheet.character.name),
+            DeathSaveOutcome::Revived => {
+                format!("Natural 20! {} is back up with 1 HP", sheet.character.name)
+            }
+        };
+        character_data.is_modified = true;
+        character_data.needs_refresh = true;
+        snackbar.write(ShowSnackbar::message(message).duration(3.0));
+
+        if failures_before == 2
+            && outcome.survived()
+            && !state
+                .achievements
+                .is_unlocked(Achievement::BackFromTheBrink)
+        {
+            unlock(
+                Achievement::BackFromTheBrink,
+                db.as_deref(),
+                &mut state,
+                &mut snackbar,
+            );
+        }
+    }
+}
+
+/// Unlock a locked achievement, announce it and save it.
+fn unlock(
+    achievement: Achievement,
+    db: Option<&CharacterDatabase>,
+    state: &mut AchievementsState,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+) {
+    state.achievements.unlock(achievement, now_seconds());
+    info!("Achievement unlocked: {}", achievement.title());
+    snackbar.write(
+        ShowSnackbar::message(format!("Achievement unlocked: {}", achievement.title()))
+            .duration(4.0),
+    );
+    if let Some(db) = db {
+        if let Err(e) = db.set_setting(ACHIEVEMENTS_SETTING_KEY, state.achievements.clone()) {
+            warn!("Failed to save achievements: {}", e);
+        }
+    }
+}
+
+/// Fill in the achievements page when it is spawned and whenever an
+/// achievement unlocks.
+pub fn update_achievements_page(
+    mut commands: Commands,
+    state: Res<AchievementsState>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<(Entity, Ref<AchievementsPageBody>)>,
+) {
+    let Ok((body, marker)) = bodies.single() else {
+        return;
+    };
+    if !state.is_changed() && !marker.is_added() {
+        return;
+    }
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        spawn_achievement_text(
+            body,
+            format!(
+                "Achievements ({} of {})",
+                state.achievements.count(),
+                Achievement::ALL.len()
+            ),
+            20.0,
+            theme.on_surface,
+        );
+        body.spawn(Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(12.0),
+            row_gap: Val::Px(12.0),
+            ..default()
+        })
+        .with_children(|row| {
+            for achievement in Achievement::ALL {
+                spawn_achievement_card(row, achievement, &state.achievements, &theme);
+            }
+        });
+    });
+}
+
+fn spawn_achievement_card(
+    parent: &mut ChildSpawnerCommands,
+    achievement: Achievement,
+    achievements: &Achievements,
+    theme: &MaterialTheme,
+) {
+    let unlocked = achievements.get(achievement);
+    let (background, title_color, text_color) = if unlocked.is_some() {
+        (
+            theme.primary_container,
+            theme.on_primary_container,
+            theme.on_primary_container,
+        )
+    } else {
+        (
+            theme.surface_container,
+            theme.on_surface_variant,
+            theme.on_surface_variant.with_alpha(0.85),
+        )
+    };
+
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(200.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(background),
+            BorderRadius::all(Val::Px(CornerRadius::MEDIUM)),
+        ))
+        .with_children(|card| {
+            spawn_achievement_text(card, achievement.title().to_string(), 16.0, title_color);
+            spawn_achievement_text(
+                card,
+                achievement.description().to_string(),
+                12.0,
+                text_color,
+            );
+            spawn_achievement_text(
+                card,
+                match unlocked {
+                    Some(unlocked) => format!("Unlocked {}", format_day(unlocked.day())),
+                    None => "Locked".to_string(),
+                },
+                11.0,
+                text_color,
+            );
+        });
+}
+
+fn spawn_achievement_text(
+    parent: &mut ChildSpawnerCommands,
+    text: String,
+    size: f32,
+    color: Color,
+) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
@@ -0,0 +1,214 @@
+//! Automation rule systems
+//!
+//! Publishers turn resolved rolls and hit point changes into
+//! `AutomationEvent`s; `run_automation_rules` checks every event against the
+//! profile's rules and carries out the actions. `rule ...` / `rules` in the
+//! command input manage the rules.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+use rand::Rng;
+
+use crate::dice3d::types::{
+    AutomationEvent, AutomationRule, AutomationRules, CharacterData, CharacterDatabase,
+    CharacterManager, DiceExpr, RollResolved, RollRng, RuleAction, TypedCommand,
+    AUTOMATION_RULES_DB_KEY,
+};
+
+/// A `rule` / `rules` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleCommand {
+    List,
+    /// `rule when <trigger> then <action>`
+    Add(String),
+    /// 1-based, as shown by `rules`
+    Remove(usize),
+    Clear,
+}
+
+impl TypedCommand for RuleCommand {
+    const NAME: &'static str = "rule";
+
+    /// Parse `rules`, `rule when ...`, `rule remove <n>` and `rules clear`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("rule") && !head.eq_ignore_ascii_case("rules") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (verb, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        match verb.to_lowercase().as_str() {
+            "" | "list" => Some(RuleCommand::List),
+            "clear" => Some(RuleCommand::Clear),
+            "remove" | "delete" => arg.trim().parse().ok().map(RuleCommand::Remove),
+            "when" => Some(RuleCommand::Add(rest.to_string())),
+            _ => None,
+        }
+    }
+}
+
+fn save_rules(rules: &AutomationRules, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(AUTOMATION_RULES_DB_KEY, rules.clone()) {
+        warn!("Failed to save automation rules: {}", e);
+    }
+}
+
+/// Apply a rule command, saving the rules and notifying the user.
+pub fn run_rule_command(
+    In(command): In<RuleCommand>,
+    mut rules: ResMut<AutomationRules>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        RuleCommand::List => rules.summary(),
+        RuleCommand::Add(text) => match AutomationRule::parse(&text) {
+            Ok(rule) => {
+                let message = format!("Rule added: {}", rule);
+                rules.rules.push(rule);
+                save_rules(&rules, &db);
+                message
+            }
+            Err(e) => e,
+        },
+        RuleCommand::Remove(number) => {
+            if (1..=rules.rules.len()).contains(&number) {
+                let rule = rules.rules.remove(number - 1);
+                save_rules(&rules, &db);
+                format!("Rule removed: {}", rule)
+            } else {
+                format!("No rule {}", number)
+            }
+        }
+        RuleCommand::Clear => {
+            rules.rules.clear();
+            save_rules(&rules, &db);
+            "Automation rules cleared".to_string()
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Load the current profile's rules once the database is open.
+pub fn load_automation_rules(
+    db: Option<Res<CharacterDatabase>>,
+    mut rules: ResMut<AutomationRules>,
+) {
+    let Some(db) = db else {
+        return;
+    };
+    match db.get_setting::<AutomationRules>(AUTOMATION_RULES_DB_KEY) {
+        Ok(Some(loaded)) => *rules = loaded,
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load automation rules: {}", e),
+    }
+}
+
+/// Publish an event for every resolved roll.
+pub fn publish_roll_automation_events(
+    mut resolved: MessageReader<RollResolved>,
+    mut events: MessageWriter<AutomationEvent>,
+) {
+    for roll in resolved.read() {
+        events.write(AutomationEvent::RollCompleted {
+            label: roll.label.clone(),
+            total: roll.total,
+            natural_d20: roll.natural_d20,
+        });
+    }
+}
+
+/// Publish an event when the current character's hit points change.
+///
+/// Switching characters only records the new character's hit points.
+pub fn publish_hit_point_automation_events(
+    character_data: Res<CharacterData>,
+    character_manager: Res<CharacterManager>,
+    mut last: Local<Option<(Option<i64>, i32)>>,
+    mut events: MessageWriter<AutomationEvent>,
+) {
+    if !character_data.is_changed() {
+        return;
+    }
+    let Some(hp) = character_data
+        .sheet
+        .as_ref()
+        .and_then(|sheet| sheet.combat.hit_points.as_ref())
+    else {
+        *last = None;
+        return;
+    };
+
+    let id = character_manager.current_character_id;
+    if let Some((last_id, previous)) = *last {
+        if last_id == id && previous != hp.current {
+            events.write(AutomationEvent::HitPointsChanged {
+                previous,
+                current: hp.current,
+                maximum: hp.maximum,
+            });
+        }
+    }
+    *last = Some((id, hp.current));
+}
+
+/// Roll a check ("perception") or dice ("1d6") for a rule.
+fn roll_for_rule(
+    what: &str,
+    character_data: &CharacterData,
+    rng: &mut RollRng,
+) -> Result<String, String> {
+    if let Ok(expression) = DiceExpr::parse(what) {
+        if !expression.dice().is_empty() {
+            let roll = expression.roll(|die| rng.random_range(1..=die.max_value()))?;
+            return Ok(format!("{}: {}", expression, roll.total));
+        }
+    }
+
+    let check = what.to_lowercase();
+    let modifier = match check.strip_suffix(" save") {
+        Some(ability) => character_data.get_saving_throw_modifier(ability.trim()),
+        None => character_data
+            .get_skill_modifier(&check)
+            .or_else(|| character_data.get_ability_modifier(&check)),
+    }
+    .ok_or_else(|| format!("Rule can't roll '{}'", what))?;
+    let d20 = rng.random_range(1..=20);
+    Ok(format!(
+        "{}: {} (d20 {} {:+})",
+        what,
+        d20 as i32 + modifier,
+        d20,
+        modifier
+    ))
+}
+
+/// Check published events against the rules and carry out their actions.
+pub fn run_automation_rules(
+    mut events: MessageReader<AutomationEvent>,
+    rules: Res<AutomationRules>,
+    character_data: Res<CharacterData>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in events.read() {
+        for action in rules.actions_for(event) {
+            let message = match action {
+                RuleAction::Remind(text) => text,
+                RuleAction::Roll(what) => match roll_for_rule(&what, &character_data, &mut rng) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        warn!("{}", e);
+                        e
+                    }
+                },
+            };
+            info!("Automation: {}", message);
+            snackbar.write(ShowSnackbar::message(message).duration(5.0));
+        }
+    }
+}
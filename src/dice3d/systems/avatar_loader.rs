@@ -98,6 +98,7 @@ impl AvatarLoader {
 }
 
 /// Load an avatar from a URL (runs in background thread)
+#[cfg(feature = "contributors")]
 fn load_avatar_from_url(url: &str) -> Option<CompletedAvatar> {
     // Use reqwest blocking client
     let response = reqwest::blocking::Client::new()
@@ -134,6 +135,12 @@ fn load_avatar_from_url(url: &str) -> Option<CompletedAvatar> {
     })
 }
 
+/// Builds without the `contributors` feature have no HTTP client.
+#[cfg(not(feature = "contributors"))]
+fn load_avatar_from_url(_url: &str) -> Option<CompletedAvatar> {
+    None
+}
+
 /// System to process completed avatar loads and create textures
 pub fn process_avatar_loads(
     mut avatar_loader: ResMut<AvatarLoader>,
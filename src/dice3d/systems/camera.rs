@@ -12,6 +12,7 @@ use bevy_material_ui::prelude::{MaterialSlider, SliderChangeEvent};
 pub fn rotate_camera(
     settings_state: Res<SettingsState>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    ui_focus: Res<UiFocus>,
     time: Res<Time>,
     mut camera_query: Query<&mut Transform, With<MainCamera>>,
     mut zoom_state: ResMut<ZoomState>,
@@ -23,15 +24,17 @@ pub fn rotate_camera(
 
     let rotation_speed = 1.0;
     let zoom_speed = 0.5;
+    // While the focus ring is shown the arrow keys move it instead
+    let arrow = |key: KeyCode| !ui_focus.visible && keyboard.pressed(key);
 
     let mut zoom_changed = false;
     for mut transform in camera_query.iter_mut() {
         let mut angle = 0.0;
 
-        if keyboard.pressed(KeyCode::KeyA) || keyboard.pressed(KeyCode::ArrowLeft) {
+        if keyboard.pressed(KeyCode::KeyA) || arrow(KeyCode::ArrowLeft) {
             angle += rotation_speed * time.delta_secs();
         }
-        if keyboard.pressed(KeyCode::KeyD) || keyboard.pressed(KeyCode::ArrowRight) {
+        if keyboard.pressed(KeyCode::KeyD) || arrow(KeyCode::ArrowRight) {
             angle -= rotation_speed * time.delta_secs();
         }
 
@@ -44,11 +47,11 @@ pub fn rotate_camera(
         }
 
         // Keyboard zoom with updated limits
-        if keyboard.pressed(KeyCode::KeyW) || keyboard.pressed(KeyCode::ArrowUp) {
+        if keyboard.pressed(KeyCode::KeyW) || arrow(KeyCode::ArrowUp) {
             zoom_state.level = (zoom_state.level - zoom_speed * time.delta_secs()).max(0.0);
             zoom_changed = true;
         }
-        if keyboard.pressed(KeyCode::KeyS) || keyboard.pressed(KeyCode::ArrowDown) {
+        if keyboard.pressed(KeyCode::KeyS) || arrow(KeyCode::ArrowDown) {
             zoom_state.level = (zoom_state.level + zoom_speed * time.delta_secs()).min(1.0);
             zoom_changed = true;
         }
@@ -0,0 +1,684 @@
+//! Shared character files
+//!
+//! `share` in the command input saves the current character as a `.dndchar`
+//! file (with an optional portrait) to send to the DM; `share qr` and `scan`
+//! move it through QR codes instead (see `qr_transfer`). Opening a character
+//! file shows it read-only, with buttons to import it as a character or as
+//! an NPC. "Import from file" in the character list opens one the same way;
+//! D&D Beyond character exports and Foundry VTT actors open too, and
+//! `share foundry` saves the current character as a Foundry VTT actor.
+//!
+//! Adding `players` (`share players`, `share qr players`) shares the sheet
+//! as the players see it, with the fields `visibility` hides from them left
+//! out.
+
+use std::path::PathBuf;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::ui::widget::ImageNode;
+use bevy_material_ui::prelude::*;
+
+use super::platform::PendingCharacterFileOpen;
+use crate::dice3d::types::{
+    redact_sheet, sheet_json_to_foundry, write_character_file, Attributes, CharacterData,
+    CharacterDatabase, CharacterFile, CharacterManager, FieldPermissions, FieldVisibility,
+    FocusTrap, ImportCharacterButton, SettingsState, SheetField, TypedCommand, Viewer,
+    CHARACTER_FILE_EXTENSION,
+};
+
+/// Size of the portrait shown in the dialog
+const PORTRAIT_DISPLAY_SIZE: f32 = 96.0;
+
+#[derive(Component)]
+pub struct CharacterFileDialogOverlay;
+
+#[derive(Component)]
+pub struct CharacterFileImportButton;
+
+#[derive(Component)]
+pub struct CharacterFileImportNpcButton;
+
+#[derive(Component)]
+pub struct CharacterFileCloseButton;
+
+/// The character file being shown; the dialog is open while this is set.
+#[derive(Resource, Default)]
+pub struct CharacterFileDialogState {
+    pub file: Option<CharacterFile>,
+    /// Why the last file couldn't be opened, shown instead of a file
+    pub error: Option<String>,
+}
+
+/// A `share` / `scan` command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareCommand {
+    /// `share`: save a `.dndchar` file
+    File(Viewer),
+    /// `share qr`: show QR codes
+    Qr(Viewer),
+    /// `share foundry`: save a Foundry VTT actor file
+    Foundry(Viewer),
+    /// `scan`: read QR codes from images
+    Scan,
+}
+
+impl TypedCommand for ShareCommand {
+    const NAME: &'static str = "share";
+
+    /// Parse `share`, `share qr`, `share foundry` (each optionally followed by
+    /// `players`) and `scan` (or `scan qr`).
+    fn parse(cmd: &str) -> Option<Self> {
+        let mut words: Vec<String> = cmd.split_whitespace().map(str::to_lowercase).collect();
+        let viewer = if words.len() > 1 && words.last().map(String::as_str) == Some("players") {
+            words.pop();
+            Viewer::Players
+        } else {
+            Viewer::Dm
+        };
+        match words
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            ["share"] => Some(ShareCommand::File(viewer)),
+            ["share", "qr"] => Some(ShareCommand::Qr(viewer)),
+            ["share", "foundry"] => Some(ShareCommand::Foundry(viewer)),
+            ["scan"] | ["scan", "qr"] if viewer == Viewer::Dm => Some(ShareCommand::Scan),
+            _ => None,
+        }
+    }
+}
+
+/// A `visibility` command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisibilityCommand {
+    /// `visibility`: list what players don't see
+    Show,
+    /// `visibility <field> everyone|summary|dm`
+    Set(SheetField, FieldVisibility),
+    /// `visibility npc`: hit point state and roll totals only
+    Npc,
+    /// `visibility reset`: share everything
+    Reset,
+}
+
+impl TypedCommand for VisibilityCommand {
+    const NAME: &'static str = "visibility";
+
+    /// Parse `visibility`, `visibility <field> <everyone|summary|dm>`,
+    /// `visibility npc` and `visibility reset`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let words: Vec<&str> = cmd.split_whitespace().collect();
+        let (head, rest) = words.split_first()?;
+        if !head.eq_ignore_ascii_case("visibility") {
+            return None;
+        }
+        match rest {
+            [] => Some(VisibilityCommand::Show),
+            [word] if word.eq_ignore_ascii_case("npc") => Some(VisibilityCommand::Npc),
+            [word] if word.eq_ignore_ascii_case("reset") => Some(VisibilityCommand::Reset),
+            [field, level] => Some(VisibilityCommand::Set(
+                SheetField::parse(field)?,
+                FieldVisibility::parse(level)?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Apply a `visibility` command to the current character, notifying the
+/// user.
+pub fn run_visibility_command(
+    In(command): In<VisibilityCommand>,
+    mut character_data: ResMut<CharacterData>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let character_data = &mut *character_data;
+    let Some(sheet) = character_data.sheet.as_mut() else {
+        snackbar.write(ShowSnackbar::message("No character loaded").duration(2.0));
+        return;
+    };
+    let permissions = &mut sheet.field_visibility;
+    let changed = match command {
+        VisibilityCommand::Show => Ok(false),
+        VisibilityCommand::Set(field, visibility) => {
+            permissions.set(field, visibility).map(|()| true)
+        }
+        VisibilityCommand::Npc => {
+            *permissions = FieldPermissions::npc_default();
+            Ok(true)
+        }
+        VisibilityCommand::Reset => {
+            *permissions = FieldPermissions::default();
+            Ok(true)
+        }
+    };
+    let message = match changed {
+        Ok(changed) => {
+            character_data.is_modified |= changed;
+            format!(
+                "{} visibility: {}",
+                sheet.character.name,
+                sheet.field_visibility.summary()
+            )
+        }
+        Err(e) => e,
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Save the current character as a character file chosen in a save dialog.
+///
+/// A portrait is picked first; cancelling that picker shares without one.
+pub fn share_current_character(
+    character_data: &CharacterData,
+    viewer: Viewer,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+) {
+    let Some(sheet) = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| redact_sheet(sheet, viewer))
+    else {
+        snackbar.write(ShowSnackbar::message("No character to share").duration(2.0));
+        return;
+    };
+    let name = sheet.character.name.clone();
+
+    let Some((portrait, path)) = pick_share_paths(&name) else {
+        return;
+    };
+
+    let result = match portrait {
+        Some(portrait) => std::fs::read(&portrait)
+            .map_err(|e| format!("Failed to read {}: {}", portrait.display(), e))
+            .and_then(|bytes| CharacterFile::new(sheet).with_portrait(&bytes)),
+        None => Ok(CharacterFile::new(sheet)),
+    }
+    .and_then(|file| write_character_file(&path, &file));
+
+    match result {
+        Ok(()) => {
+            info!("Shared {} as {}", name, path.display());
+            snackbar
+                .write(ShowSnackbar::message(format!("Saved {}", path.display())).duration(2.0));
+        }
+        Err(e) => {
+            warn!("{}", e);
+            snackbar.write(ShowSnackbar::message("Failed to share character").duration(2.0));
+        }
+    }
+}
+
+/// Save the current character as a Foundry VTT actor chosen in a save
+/// dialog, reporting the fields Foundry has no place for.
+pub fn export_current_character_foundry(
+    character_data: &CharacterData,
+    viewer: Viewer,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+) {
+    let Some(sheet) = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| redact_sheet(sheet, viewer))
+    else {
+        snackbar.write(ShowSnackbar::message("No character to share").duration(2.0));
+        return;
+    };
+    let name = sheet.character.name.clone();
+    let Some(path) = pick_foundry_export_path(&name) else {
+        return;
+    };
+
+    let result = serde_json::to_value(&sheet)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))
+        .and_then(|sheet| sheet_json_to_foundry(&sheet))
+        .and_then(|conversion| {
+            let json = serde_json::to_string_pretty(&conversion.json)
+                .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+            std::fs::write(&path, json)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            Ok(conversion.unmapped)
+        });
+
+    match result {
+        Ok(unmapped) if unmapped.is_empty() => {
+            info!("Exported {} to Foundry VTT as {}", name, path.display());
+            snackbar
+                .write(ShowSnackbar::message(format!("Saved {}", path.display())).duration(2.0));
+        }
+        Ok(unmapped) => {
+            info!(
+                "Exported {} to Foundry VTT as {}; not exported: {}",
+                name,
+                path.display(),
+                unmapped.join(", ")
+            );
+            snackbar.write(
+                ShowSnackbar::message(format!(
+                    "Saved {} (not exported: {})",
+                    path.display(),
+                    unmapped.join(", ")
+                ))
+                .duration(5.0),
+            );
+        }
+        Err(e) => {
+            warn!("{}", e);
+            snackbar.write(ShowSnackbar::message("Failed to export character").duration(2.0));
+        }
+    }
+}
+
+/// Destination of a Foundry VTT actor export.
+#[cfg(feature = "integrations")]
+fn pick_foundry_export_path(name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .set_title("Export to Foundry VTT")
+        .add_filter("Foundry VTT actor", &["json"])
+        .set_file_name(format!("fvtt-Actor-{}.json", name))
+        .save_file()
+}
+
+/// Without file dialogs the actor is saved in the working directory.
+#[cfg(not(feature = "integrations"))]
+fn pick_foundry_export_path(name: &str) -> Option<PathBuf> {
+    Some(PathBuf::from(format!("fvtt-Actor-{}.json", name)))
+}
+
+/// Portrait (optional) and destination of a shared character file.
+#[cfg(feature = "integrations")]
+fn pick_share_paths(name: &str) -> Option<(Option<PathBuf>, PathBuf)> {
+    let portrait = rfd::FileDialog::new()
+        .set_title("Choose a portrait (optional)")
+        .add_filter("Images", &["png", "jpg", "jpeg", "webp", "gif", "bmp"])
+        .pick_file();
+    let path = rfd::FileDialog::new()
+        .set_title("Share character")
+        .add_filter("Character file", &[CHARACTER_FILE_EXTENSION])
+        .set_file_name(format!("{}.{}", name, CHARACTER_FILE_EXTENSION))
+        .save_file()?;
+    Some((portrait, path))
+}
+
+/// Without file dialogs the file is saved in the working directory, without
+/// a portrait.
+#[cfg(not(feature = "integrations"))]
+fn pick_share_paths(name: &str) -> Option<(Option<PathBuf>, PathBuf)> {
+    Some((
+        None,
+        PathBuf::from(format!("{}.{}", name, CHARACTER_FILE_EXTENSION)),
+    ))
+}
+
+/// Pick a character file to import from the character list.
+pub fn handle_import_character_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<(), With<ImportCharacterButton>>,
+    settings_state: Res<SettingsState>,
+    mut pending: ResMut<PendingCharacterFileOpen>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        if buttons.get(event.entity).is_err() {
+            continue;
+        }
+        match pick_import_path() {
+            Ok(Some(path)) => pending.path = Some(path),
+            Ok(None) => {}
+            Err(e) => {
+                snackbar.write(ShowSnackbar::message(e).duration(3.0));
+            }
+        }
+    }
+}
+
+/// Character file to import, `None` when the dialog was cancelled.
+#[cfg(feature = "integrations")]
+fn pick_import_path() -> Result<Option<PathBuf>, String> {
+    Ok(rfd::FileDialog::new()
+        .set_title("Import character")
+        .add_filter(
+            "Character file, D&D Beyond export or Foundry VTT actor",
+            &[CHARACTER_FILE_EXTENSION, "json"],
+        )
+        .pick_file())
+}
+
+/// Without file dialogs, character files can only be opened with `--open`.
+#[cfg(not(feature = "integrations"))]
+fn pick_import_path() -> Result<Option<PathBuf>, String> {
+    Err("Importing needs file dialogs; open the file with --open instead".to_string())
+}
+
+fn spawn_dialog_text(parent: &mut ChildSpawnerCommands, text: &str, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_dialog_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+fn portrait_handle(file: &CharacterFile, images: &mut Assets<Image>) -> Option<Handle<Image>> {
+    let portrait = match file.portrait_image() {
+        Ok(portrait) => portrait?,
+        Err(e) => {
+            warn!("{}", e);
+            return None;
+        }
+    };
+    let (width, height) = portrait.dimensions();
+    let image = Image::new(
+        bevy::render::render_resource::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        bevy::render::render_resource::TextureDimension::D2,
+        portrait.into_raw(),
+        bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    );
+    Some(images.add(image))
+}
+
+/// Spawn the modal dialog and its scrim, returning the dialog to fill.
+fn spawn_dialog_shell(commands: &mut Commands, title: &str, theme: &MaterialTheme) -> Entity {
+    let dialog = MaterialDialog::new().title(title).open(true).modal(true);
+    let dialog_surface = dialog.surface_color(theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(520.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(theme, dialog_entity, true),
+            FocusTrap,
+            CharacterFileDialogOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+    dialog_entity
+}
+
+/// Show/hide and rebuild the read-only character view, or why a file
+/// couldn't be opened.
+pub fn manage_character_file_dialog(
+    mut commands: Commands,
+    dialog_state: Res<CharacterFileDialogState>,
+    theme: Option<Res<MaterialTheme>>,
+    mut images: ResMut<Assets<Image>>,
+    overlays: Query<Entity, With<CharacterFileDialogOverlay>>,
+) {
+    if !dialog_state.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    if let Some(error) = &dialog_state.error {
+        let dialog_entity = spawn_dialog_shell(&mut commands, "Can't Open Character", &theme);
+        commands.entity(dialog_entity).with_children(|dialog| {
+            spawn_dialog_text(dialog, error, 14.0, theme.on_surface);
+            dialog
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::FlexEnd,
+                    width: Val::Percent(100.0),
+                    ..default()
+                })
+                .with_children(|buttons| {
+                    spawn_dialog_button(buttons, "Close", true, CharacterFileCloseButton, &theme);
+                });
+        });
+        return;
+    }
+
+    let Some(file) = &dialog_state.file else {
+        return;
+    };
+
+    let sheet = &file.sheet;
+    let portrait = portrait_handle(file, &mut images);
+    let dialog_entity = spawn_dialog_shell(&mut commands, "Shared Character", &theme);
+
+    let hit_points = sheet
+        .combat
+        .hit_points
+        .as_ref()
+        .map(|hp| format!("HP {}/{}", hp.current, hp.maximum))
+        .unwrap_or_else(|| "HP -".to_string());
+    let combat_line = format!(
+        "AC {} · {} · Initiative {:+} · Speed {} · Proficiency {:+}",
+        sheet.combat.armor_class,
+        hit_points,
+        sheet.combat.initiative,
+        sheet.combat.speed,
+        sheet.proficiency_bonus
+    );
+    let abilities_line = sheet
+        .attributes
+        .as_vec()
+        .iter()
+        .map(|(name, score)| {
+            format!(
+                "{} {} ({:+})",
+                name[..3].to_uppercase(),
+                score,
+                Attributes::calculate_modifier(*score)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(16.0),
+                ..default()
+            })
+            .with_children(|header| {
+                if let Some(portrait) = portrait {
+                    header.spawn((
+                        ImageNode::new(portrait),
+                        Node {
+                            width: Val::Px(PORTRAIT_DISPLAY_SIZE),
+                            height: Val::Px(PORTRAIT_DISPLAY_SIZE),
+                            ..default()
+                        },
+                        BorderRadius::all(Val::Px(CornerRadius::MEDIUM)),
+                    ));
+                }
+
+                header
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|title| {
+                        spawn_dialog_text(title, &sheet.character.name, 20.0, theme.on_surface);
+                        spawn_dialog_text(title, &file.summary(), 14.0, theme.on_surface_variant);
+                        spawn_dialog_text(
+                            title,
+                            "Read-only. Import to keep a copy.",
+                            12.0,
+                            theme.on_surface_variant,
+                        );
+                    });
+            });
+
+        spawn_dialog_text(dialog, &combat_line, 14.0, theme.on_surface);
+        spawn_dialog_text(dialog, &abilities_line, 14.0, theme.on_surface);
+
+        if !sheet.features.is_empty() {
+            let features = sheet
+                .features
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            spawn_dialog_text(
+                dialog,
+                &format!("Features: {}", features),
+                13.0,
+                theme.on_surface_variant,
+            );
+        }
+
+        if !file.unmapped.is_empty() {
+            spawn_dialog_text(
+                dialog,
+                &format!("Not imported: {}", file.unmapped.join(", ")),
+                12.0,
+                theme.error,
+            );
+        }
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_dialog_button(buttons, "Close", false, CharacterFileCloseButton, &theme);
+                spawn_dialog_button(
+                    buttons,
+                    "Import as NPC",
+                    false,
+                    CharacterFileImportNpcButton,
+                    &theme,
+                );
+                spawn_dialog_button(buttons, "Import", true, CharacterFileImportButton, &theme);
+            });
+    });
+}
+
+/// Import or close the shown character file.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_character_file_dialog_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    import_buttons: Query<(), With<CharacterFileImportButton>>,
+    npc_buttons: Query<(), With<CharacterFileImportNpcButton>>,
+    close_buttons: Query<(), With<CharacterFileCloseButton>>,
+    mut dialog_state: ResMut<CharacterFileDialogState>,
+    db: Option<Res<CharacterDatabase>>,
+    mut character_manager: Option<ResMut<CharacterManager>>,
+    mut character_data: ResMut<CharacterData>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        let as_npc = if import_buttons.get(event.entity).is_ok() {
+            false
+        } else if npc_buttons.get(event.entity).is_ok() {
+            true
+        } else {
+            if close_buttons.get(event.entity).is_ok() {
+                dialog_state.file = None;
+                dialog_state.error = None;
+            }
+            continue;
+        };
+
+        let (Some(db), Some(character_manager)) = (db.as_ref(), character_manager.as_mut()) else {
+            continue;
+        };
+        let Some(file) = dialog_state.file.take() else {
+            continue;
+        };
+
+        let sheet = if as_npc {
+            file.npc_sheet()
+        } else {
+            file.sheet.clone()
+        };
+        match db.create_character(&sheet) {
+            Ok(id) => {
+                let name = sheet.character.name.clone();
+                character_manager.refresh_from_database(db);
+                character_manager.list_version += 1;
+                let message = if as_npc {
+                    // Keep the DM's current character selected
+                    format!("Imported {} as an NPC", name)
+                } else {
+                    character_manager.current_character_id = Some(id);
+                    character_data.sheet = Some(sheet);
+                    character_data.character_id = Some(id);
+                    character_data.is_modified = false;
+                    character_data.needs_refresh = true;
+                    format!("Imported {}", name)
+                };
+                info!("{}", message);
+                snackbar.write(ShowSnackbar::message(message).duration(2.0));
+            }
+            Err(e) => {
+                warn!("Failed to import character file: {}", e);
+                snackbar
+                    .write(ShowSnackbar::message("Failed to import character file").duration(2.0));
+                // Keep showing the file so the import can be retried
+                dialog_state.file = Some(file);
+            }
+        }
+    }
+}
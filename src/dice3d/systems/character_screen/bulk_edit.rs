@@ -0,0 +1,332 @@
+//! Bulk proficiency edit dialog.
+//!
+//! The "Bulk edit" button on the Skills and Saves tabs opens a compact grid of
+//! every skill and saving throw. Each cell cycles through none, proficient and
+//! (for skills) expertise; Apply writes all the changes to the sheet at once.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::tabs::camel_to_title_case;
+use crate::dice3d::types::{
+    ability_abbreviation, CharacterData, FocusTrap, ProficiencyGrid, ProficiencyLevel,
+    ProficiencyTarget, SettingsState,
+};
+
+/// Marker for the "Bulk edit" button on the Skills and Saves tabs
+#[derive(Component)]
+pub struct ProficiencyBulkEditButton;
+
+/// A skill or saving throw cell of the grid
+#[derive(Component)]
+pub struct ProficiencyGridCell {
+    pub target: ProficiencyTarget,
+}
+
+#[derive(Component)]
+pub struct ProficiencyBulkEditOverlay;
+
+#[derive(Component)]
+pub struct ProficiencyBulkEditApplyButton;
+
+#[derive(Component)]
+pub struct ProficiencyBulkEditCancelButton;
+
+/// Draft being edited in the bulk edit dialog (`None` while it is closed)
+#[derive(Resource, Default)]
+pub struct ProficiencyBulkEdit {
+    pub draft: Option<ProficiencyGrid>,
+}
+
+/// Spawn the "Bulk edit" button shown above the skill and save lists.
+pub fn spawn_bulk_edit_button(parent: &mut ChildSpawnerCommands, theme: &MaterialTheme) {
+    parent
+        .spawn((
+            MaterialButtonBuilder::new("Bulk edit proficiencies")
+                .text()
+                .build(theme),
+            ProficiencyBulkEditButton,
+        ))
+        .insert(Node {
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            align_self: AlignSelf::FlexStart,
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                ButtonLabel,
+                Text::new("Bulk edit proficiencies"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+            ));
+        });
+}
+
+/// Open the dialog, cycle the clicked cells, and apply or discard the draft.
+pub fn handle_bulk_edit_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    open_buttons: Query<(), With<ProficiencyBulkEditButton>>,
+    cells: Query<&ProficiencyGridCell>,
+    apply_buttons: Query<(), With<ProficiencyBulkEditApplyButton>>,
+    cancel_buttons: Query<(), With<ProficiencyBulkEditCancelButton>>,
+    mut bulk_edit: ResMut<ProficiencyBulkEdit>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    for event in click_events.read() {
+        if open_buttons.get(event.entity).is_ok() {
+            if settings_state.show_modal {
+                continue;
+            }
+            if let Some(sheet) = &character_data.sheet {
+                bulk_edit.draft = Some(ProficiencyGrid::from_sheet(sheet));
+            }
+        } else if let Ok(cell) = cells.get(event.entity) {
+            let Some(draft) = bulk_edit.draft.as_mut() else {
+                continue;
+            };
+            match &cell.target {
+                ProficiencyTarget::Skill(skill) => draft.cycle_skill(skill),
+                ProficiencyTarget::SavingThrow(ability) => draft.cycle_save(ability),
+            }
+        } else if apply_buttons.get(event.entity).is_ok() {
+            let Some(draft) = bulk_edit.draft.take() else {
+                continue;
+            };
+            if let Some(sheet) = character_data.sheet.as_mut() {
+                if draft.apply(sheet) > 0 {
+                    character_data.is_modified = true;
+                }
+            }
+        } else if cancel_buttons.get(event.entity).is_ok() {
+            bulk_edit.draft = None;
+        }
+    }
+}
+
+fn spawn_dialog_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// A grid cell: the level's letter and the skill or ability name, filled
+/// when proficient.
+fn spawn_grid_cell(
+    parent: &mut ChildSpawnerCommands,
+    name: &str,
+    level: ProficiencyLevel,
+    target: ProficiencyTarget,
+    width: f32,
+    theme: &MaterialTheme,
+) {
+    let label = format!("{}  {}", level.label(), name);
+    let builder = MaterialButtonBuilder::new(label.clone());
+    let (button, text_color) = match level {
+        ProficiencyLevel::None => (builder.outlined().build(theme), theme.on_surface_variant),
+        ProficiencyLevel::Proficient => (
+            builder.filled_tonal().build(theme),
+            theme.on_secondary_container,
+        ),
+        ProficiencyLevel::Expertise => (builder.filled().build(theme), theme.on_primary),
+    };
+
+    parent
+        .spawn((button, ProficiencyGridCell { target }))
+        .insert(Node {
+            width: Val::Px(width),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            justify_content: JustifyContent::FlexStart,
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                ButtonLabel,
+                Text::new(label),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(text_color),
+            ));
+        });
+}
+
+/// Show/hide the bulk edit dialog, rebuilding it whenever the draft changes.
+pub fn manage_bulk_edit_dialog(
+    mut commands: Commands,
+    bulk_edit: Res<ProficiencyBulkEdit>,
+    character_data: Res<CharacterData>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<ProficiencyBulkEditOverlay>>,
+) {
+    if !bulk_edit.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(draft) = bulk_edit.draft.as_ref() else {
+        return;
+    };
+    let Some(sheet) = character_data.sheet.as_ref() else {
+        return;
+    };
+    let changes = draft.changes(sheet);
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("Proficiencies")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(640.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            ProficiencyBulkEditOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        dialog.spawn((
+            Text::new("Proficiencies"),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(theme.on_surface),
+        ));
+
+        dialog.spawn((
+            Text::new("Click a cell to cycle: — none, P proficient, E expertise (skills only)"),
+            TextFont {
+                font_size: 13.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+
+        // Saving throws on one row
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(6.0),
+                row_gap: Val::Px(6.0),
+                ..default()
+            })
+            .with_children(|row| {
+                for (ability, level) in &draft.saves {
+                    spawn_grid_cell(
+                        row,
+                        &format!("{} save", ability_abbreviation(ability)),
+                        *level,
+                        ProficiencyTarget::SavingThrow(ability.clone()),
+                        92.0,
+                        &theme,
+                    );
+                }
+            });
+
+        // Skills, three to a row
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(6.0),
+                row_gap: Val::Px(6.0),
+                ..default()
+            })
+            .with_children(|grid| {
+                for (skill, level) in &draft.skills {
+                    spawn_grid_cell(
+                        grid,
+                        &camel_to_title_case(skill),
+                        *level,
+                        ProficiencyTarget::Skill(skill.clone()),
+                        188.0,
+                        &theme,
+                    );
+                }
+            });
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_dialog_button(
+                    buttons,
+                    "Cancel",
+                    false,
+                    ProficiencyBulkEditCancelButton,
+                    &theme,
+                );
+                let apply = match changes {
+                    0 => "Apply".to_string(),
+                    1 => "Apply 1 change".to_string(),
+                    n => format!("Apply {} changes", n),
+                };
+                spawn_dialog_button(
+                    buttons,
+                    &apply,
+                    true,
+                    ProficiencyBulkEditApplyButton,
+                    &theme,
+                );
+            });
+    });
+}
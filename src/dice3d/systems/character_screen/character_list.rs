@@ -55,6 +55,9 @@ pub fn spawn_character_list_panel(
             // New Character button
             spawn_new_character_button(panel, theme);
 
+            // Import from file button
+            spawn_import_character_button(panel, theme);
+
             // Divider
             panel.spawn((
                 Node {
@@ -209,6 +212,34 @@ fn spawn_new_character_button(panel: &mut ChildSpawnerCommands, theme: &Material
         });
 }
 
+fn spawn_import_character_button(panel: &mut ChildSpawnerCommands, theme: &MaterialTheme) {
+    let label = "Import from file";
+
+    panel
+        .spawn((
+            MaterialButtonBuilder::new(label).outlined().build(theme),
+            ImportCharacterButton,
+        ))
+        .insert(Node {
+            width: Val::Percent(100.0),
+            padding: UiRect::axes(Val::Px(Spacing::EXTRA_LARGE), Val::Px(Spacing::MEDIUM)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                bevy_material_ui::button::ButtonLabel,
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+            ));
+        });
+}
+
 fn spawn_character_list_items(
     panel: &mut ChildSpawnerCommands,
     character_manager: &CharacterManager,
@@ -320,9 +351,7 @@ pub fn handle_character_list_clicks(
 pub fn handle_new_character_click(
     mut click_events: MessageReader<ButtonClickEvent>,
     buttons: Query<(), With<NewCharacterButton>>,
-    mut character_manager: ResMut<CharacterManager>,
-    mut character_data: ResMut<CharacterData>,
-    _db: Res<CharacterDatabase>,
+    mut wizard: ResMut<CharacterCreationWizard>,
     settings_state: Res<SettingsState>,
 ) {
     if settings_state.show_modal {
@@ -334,14 +363,13 @@ pub fn handle_new_character_click(
             continue;
         }
 
-        // Create a new character in-memory.
+        // The wizard creates the character in-memory once the scores are set.
         // Saving immediately can fail (missing DB/table, locked file, etc.) and would look
         // like the button "does nothing".
-        let new_sheet = CharacterSheet::default();
-
-        character_manager.current_character_id = None;
-        character_data.sheet = Some(new_sheet);
-        character_data.is_modified = true;
+        *wizard = CharacterCreationWizard {
+            open: true,
+            ..Default::default()
+        };
     }
 }
 
@@ -351,6 +379,7 @@ pub fn handle_roll_all_stats_click(
     buttons: Query<(), With<RollAllStatsButton>>,
     mut character_data: ResMut<CharacterData>,
     settings_state: Res<SettingsState>,
+    mut rng: ResMut<RollRng>,
 ) {
     if settings_state.show_modal {
         return;
@@ -367,20 +396,20 @@ pub fn handle_roll_all_stats_click(
 
         // Roll 4d6 drop lowest for each attribute
         use rand::Rng;
-        let mut rng = rand::rng();
+        let rng = &mut *rng;
 
-        let roll_4d6_drop_lowest = |rng: &mut rand::rngs::ThreadRng| {
+        let roll_4d6_drop_lowest = |rng: &mut RollRng| {
             let mut rolls: Vec<i32> = (0..4).map(|_| rng.random_range(1..=6)).collect();
             rolls.sort();
             rolls.iter().skip(1).sum::<i32>()
         };
 
-        sheet.attributes.strength = roll_4d6_drop_lowest(&mut rng);
-        sheet.attributes.dexterity = roll_4d6_drop_lowest(&mut rng);
-        sheet.attributes.constitution = roll_4d6_drop_lowest(&mut rng);
-        sheet.attributes.intelligence = roll_4d6_drop_lowest(&mut rng);
-        sheet.attributes.wisdom = roll_4d6_drop_lowest(&mut rng);
-        sheet.attributes.charisma = roll_4d6_drop_lowest(&mut rng);
+        sheet.attributes.strength = roll_4d6_drop_lowest(rng);
+        sheet.attributes.dexterity = roll_4d6_drop_lowest(rng);
+        sheet.attributes.constitution = roll_4d6_drop_lowest(rng);
+        sheet.attributes.intelligence = roll_4d6_drop_lowest(rng);
+        sheet.attributes.wisdom = roll_4d6_drop_lowest(rng);
+        sheet.attributes.charisma = roll_4d6_drop_lowest(rng);
 
         // Update modifiers based on new attribute values
         sheet.modifiers.strength = Attributes::calculate_modifier(sheet.attributes.strength);
@@ -41,7 +41,7 @@ pub fn spawn_group_header(
             margin: UiRect::bottom(Val::Px(8.0)),
             ..default()
         })
-        .insert(BorderColor::from(MD3_OUTLINE_VARIANT))
+        .insert(ThemeToken::OutlineVariant.border())
         .with_children(|header| {
             // Title
             header.spawn((
@@ -50,7 +50,7 @@ pub fn spawn_group_header(
                     font_size: 16.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE),
+                ThemeToken::OnSurface.text(),
             ));
 
             // Edit toggle button
@@ -117,7 +117,7 @@ pub fn spawn_stat_field(
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE_VARIANT),
+                ThemeToken::OnSurfaceVariant.text(),
             ));
 
             // Value container with optional delete button
@@ -189,7 +189,7 @@ pub fn spawn_readonly_field(parent: &mut ChildSpawnerCommands, label: &str, valu
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE_VARIANT),
+                ThemeToken::OnSurfaceVariant.text(),
             ));
 
             row.spawn((
@@ -198,7 +198,7 @@ pub fn spawn_readonly_field(parent: &mut ChildSpawnerCommands, label: &str, valu
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE_VARIANT),
+                ThemeToken::OnSurfaceVariant.text(),
             ));
         });
 }
@@ -275,7 +275,7 @@ pub fn spawn_custom_field_row(
                         font_size: 14.0,
                         ..default()
                     },
-                    TextColor(MD3_ON_SURFACE_VARIANT),
+                    ThemeToken::OnSurfaceVariant.text(),
                 ));
             }
 
@@ -426,8 +426,8 @@ pub fn spawn_group_add_button(
                     border: UiRect::all(Val::Px(1.0)),
                     ..default()
                 },
-                BackgroundColor(MD3_PRIMARY.with_alpha(0.1)),
-                BorderColor::from(MD3_PRIMARY),
+                ThemeToken::Primary.background_alpha(0.1),
+                ThemeToken::Primary.border(),
                 BorderRadius::all(Val::Px(8.0)),
             ))
             .with_children(|row| {
@@ -439,8 +439,8 @@ pub fn spawn_group_add_button(
                         border: UiRect::all(Val::Px(1.0)),
                         ..default()
                     },
-                    BackgroundColor(MD3_SURFACE_CONTAINER_HIGH),
-                    BorderColor::from(MD3_OUTLINE),
+                    ThemeToken::SurfaceContainerHigh.background(),
+                    ThemeToken::Outline.border(),
                     BorderRadius::all(Val::Px(6.0)),
                     NewEntryInput {
                         group_type: group_type.clone(),
@@ -457,11 +457,12 @@ pub fn spawn_group_add_button(
                             font_size: 14.0,
                             ..default()
                         },
-                        TextColor(if adding_state.new_entry_name.is_empty() {
-                            MD3_ON_SURFACE_VARIANT
+                        (if adding_state.new_entry_name.is_empty() {
+                            ThemeToken::OnSurfaceVariant
                         } else {
-                            MD3_ON_SURFACE
-                        }),
+                            ThemeToken::OnSurface
+                        })
+                        .text(),
                     ));
                 });
 
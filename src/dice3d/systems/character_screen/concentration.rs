@@ -0,0 +1,342 @@
+//! Spell concentration indicator and conflict prompt.
+//!
+//! The character header shows a "Concentrating on X" chip while a concentration
+//! spell is active. Starting a second concentration spell (typed, or cast from
+//! the Spells tab) asks for confirmation before the previous effect is ended.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::{cast_spell_as_ritual, cast_spell_with_slot};
+use crate::dice3d::types::{CharacterData, FocusTrap, SessionClock, SettingsState, TypedCommand};
+
+/// Marker for the "Concentrating on X" chip in the character header.
+/// Clicking it ends concentration.
+#[derive(Component)]
+pub struct ConcentrationChip;
+
+#[derive(Component)]
+pub struct ConcentrationConfirmOverlay;
+
+#[derive(Component)]
+pub struct ConcentrationConfirmYesButton;
+
+#[derive(Component)]
+pub struct ConcentrationConfirmNoButton;
+
+/// Concentration spell waiting for the user to confirm ending the current one.
+#[derive(Resource, Default)]
+pub struct ConcentrationPrompt {
+    pub pending: Option<String>,
+    /// Spell slot level the pending spell is cast with from the Spells tab;
+    /// spent once confirmed
+    pub slot_level: Option<u32>,
+    /// The pending spell is cast as a ritual from the Spells tab; its timer
+    /// starts once confirmed
+    pub ritual: bool,
+}
+
+/// A concentration-related command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConcentrationCommand {
+    Start(String),
+    End,
+}
+
+impl TypedCommand for ConcentrationCommand {
+    const NAME: &'static str = "concentrate";
+
+    /// Parse `concentrate <spell>` / `concentrate end` commands.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("concentrate") && !head.eq_ignore_ascii_case("conc") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        if rest.is_empty() || rest.eq_ignore_ascii_case("end") || rest.eq_ignore_ascii_case("stop")
+        {
+            Some(ConcentrationCommand::End)
+        } else {
+            Some(ConcentrationCommand::Start(rest.to_string()))
+        }
+    }
+}
+
+/// Run a `concentrate` command.
+pub fn run_concentration_command(
+    In(command): In<ConcentrationCommand>,
+    mut character_data: ResMut<CharacterData>,
+    mut prompt: ResMut<ConcentrationPrompt>,
+) {
+    match command {
+        ConcentrationCommand::Start(spell) => {
+            request_concentration(&mut character_data, &mut prompt, &spell)
+        }
+        ConcentrationCommand::End => {
+            if let Some(sheet) = character_data.sheet.as_mut() {
+                sheet.end_concentration();
+            }
+            character_data.is_modified = true;
+        }
+    }
+}
+
+/// Begin concentrating on a spell, or queue a confirmation prompt when another
+/// concentration effect is already active.
+pub fn request_concentration(
+    character_data: &mut CharacterData,
+    prompt: &mut ConcentrationPrompt,
+    spell: &str,
+) {
+    let Some(sheet) = character_data.sheet.as_mut() else {
+        return;
+    };
+
+    if sheet.concentration_conflict(spell).is_some() {
+        prompt.pending = Some(spell.trim().to_string());
+        prompt.slot_level = None;
+        prompt.ritual = false;
+        return;
+    }
+
+    sheet.start_concentration(spell);
+    character_data.is_modified = true;
+}
+
+/// Spawn the "Concentrating on X" chip for the header row.
+pub fn spawn_concentration_chip(
+    parent: &mut ChildSpawnerCommands,
+    spell: &str,
+    theme: &MaterialTheme,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(theme.secondary_container),
+            BorderColor::from(theme.outline),
+            BorderRadius::all(Val::Px(8.0)),
+            ConcentrationChip,
+        ))
+        .with_children(|chip| {
+            chip.spawn((
+                Text::new(format!("Concentrating on {}  ✕", spell)),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(theme.on_secondary_container),
+            ));
+        });
+}
+
+/// End concentration when the header chip is clicked.
+pub fn handle_concentration_chip_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    chips: Query<(), With<ConcentrationChip>>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        if chips.get(event.entity).is_err() {
+            continue;
+        }
+
+        if let Some(sheet) = character_data.sheet.as_mut() {
+            if sheet.end_concentration().is_some() {
+                character_data.is_modified = true;
+            }
+        }
+    }
+}
+
+fn spawn_confirm_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Show/hide the confirmation dialog for replacing a concentration spell.
+pub fn manage_concentration_confirm_dialog(
+    mut commands: Commands,
+    prompt: Res<ConcentrationPrompt>,
+    character_data: Res<CharacterData>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<ConcentrationConfirmOverlay>>,
+) {
+    if !prompt.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(new_spell) = prompt.pending.as_deref() else {
+        return;
+    };
+    let Some(current) = character_data
+        .sheet
+        .as_ref()
+        .and_then(|s| s.concentration.as_deref())
+    else {
+        return;
+    };
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("Concentration")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            ConcentrationConfirmOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        dialog.spawn((
+            Text::new("Already concentrating"),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(theme.on_surface),
+        ));
+
+        dialog.spawn((
+            Text::new(format!(
+                "Casting {} will end your concentration on {}. Continue?",
+                new_spell, current
+            )),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_confirm_button(
+                    buttons,
+                    "Cancel",
+                    false,
+                    ConcentrationConfirmNoButton,
+                    &theme,
+                );
+                spawn_confirm_button(buttons, "Cast", true, ConcentrationConfirmYesButton, &theme);
+            });
+    });
+}
+
+/// Apply or discard the pending concentration spell.
+pub fn handle_concentration_confirm_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    yes_buttons: Query<(), With<ConcentrationConfirmYesButton>>,
+    no_buttons: Query<(), With<ConcentrationConfirmNoButton>>,
+    mut prompt: ResMut<ConcentrationPrompt>,
+    mut character_data: ResMut<CharacterData>,
+    mut clock: ResMut<SessionClock>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let character_data = &mut *character_data;
+    for event in click_events.read() {
+        if yes_buttons.get(event.entity).is_ok() {
+            let slot_level = prompt.slot_level.take();
+            let ritual = std::mem::take(&mut prompt.ritual);
+            let Some(spell) = prompt.pending.take() else {
+                continue;
+            };
+            let Some(sheet) = character_data.sheet.as_mut() else {
+                continue;
+            };
+            if ritual {
+                let message = cast_spell_as_ritual(sheet, &mut clock, &spell);
+                snackbar.write(ShowSnackbar::message(message).duration(3.0));
+            } else if let Some(level) = slot_level {
+                match cast_spell_with_slot(sheet, &spell, level) {
+                    Ok(message) => {
+                        snackbar.write(ShowSnackbar::message(message).duration(3.0));
+                    }
+                    Err(e) => {
+                        snackbar.write(ShowSnackbar::message(e).duration(3.0));
+                        continue;
+                    }
+                }
+            }
+            sheet.start_concentration(&spell);
+            character_data.is_modified = true;
+        } else if no_buttons.get(event.entity).is_ok() {
+            prompt.pending = None;
+            prompt.slot_level = None;
+            prompt.ritual = false;
+        }
+    }
+}
@@ -10,6 +10,7 @@ use std::collections::HashSet;
 
 use crate::dice3d::types::{
     sqlite_conversion, CharacterDatabase, CharacterListEntry, CharacterManager, CharacterSheet,
+    FocusTrap, DEFAULT_PROFILE_ID,
 };
 
 const IGNORE_LEGACY_SQLITE_SETTING_KEY: &str = "ignore_legacy_sqlite";
@@ -23,9 +24,12 @@ fn log_once(state: &mut HashSet<&'static str>, key: &'static str, message: impl
 
 fn find_legacy_sqlite_path(db: &CharacterDatabase) -> Option<std::path::PathBuf> {
     // Primary (historical) location: alongside the SurrealDB folder in app-data.
-    if let Some(p) = sqlite_conversion::legacy_sqlite_path() {
-        if p.exists() {
-            return Some(p);
+    // Only the default profile imports it; other profiles start empty.
+    if db.profile_id == DEFAULT_PROFILE_ID {
+        if let Some(p) = sqlite_conversion::legacy_sqlite_path() {
+            if p.exists() {
+                return Some(p);
+            }
         }
     }
 
@@ -39,8 +43,8 @@ fn find_legacy_sqlite_path(db: &CharacterDatabase) -> Option<std::path::PathBuf>
 }
 
 fn decode_legacy_character(data: &[u8]) -> Result<CharacterSheet, String> {
-    match bincode::deserialize::<CharacterSheet>(data) {
-        Ok(sheet) => Ok(sheet),
+    match bincode::deserialize::<sqlite_conversion::LegacyCharacterSheet>(data) {
+        Ok(sheet) => Ok(sheet.into()),
         Err(bincode_err) => {
             // Some legacy DBs stored TEXT payloads (RON or JSON) instead of bincode.
             let s = std::str::from_utf8(data).map_err(|utf8_err| {
@@ -192,6 +196,7 @@ fn spawn_conversion_dialog(commands: &mut Commands, theme: &MaterialTheme) -> En
     let scrim_entity = commands
         .spawn((
             create_dialog_scrim_for(theme, dialog_entity, true),
+            FocusTrap,
             SqliteConversionOverlay,
             ZIndex(9_999),
         ))
@@ -0,0 +1,1189 @@
+//! Character creation wizard.
+//!
+//! "New Character" opens a dialog that walks through the whole character:
+//! race, class, ability scores (4d6 drop lowest, standard array or
+//! point-buy), background, class skills, starting equipment and, for classes
+//! that cast at 1st level, cantrips and spells. The review shows the finished
+//! sheet before it's created.
+//!
+//! Homebrew races, classes and backgrounds are managed with `templates` (list),
+//! `template import <file.json>` and `template remove <name>` in the command
+//! input, and kept in the profile database.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    ability_abbreviation, class_summary, AbilityScores, AppTab, Attributes,
+    CharacterCreationWizard, CharacterData, CharacterDatabase, CharacterManager,
+    CharacterTemplates, CreationArrayButton, CreationBackgroundButton, CreationClassButton,
+    CreationEquipmentButton, CreationMethodButton, CreationNav, CreationNavButton,
+    CreationPointButton, CreationRaceButton, CreationRollButton, CreationSkillButton,
+    CreationSpellButton, CreationStep, CreationWizardOverlay, EquipmentPack, FocusTrap,
+    ScoreMethod, TemplateChoice, TypedCommand, UiState, ABILITY_NAMES, HOMEBREW_TEMPLATES_DB_KEY,
+    POINT_BUY_BUDGET,
+};
+
+/// A `templates` / `template ...` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateCommand {
+    List,
+    /// Path of a JSON file with `races`, `classes` and `backgrounds`
+    Import(String),
+    Remove(String),
+}
+
+impl TypedCommand for TemplateCommand {
+    const NAME: &'static str = "template";
+
+    /// Parse `templates`, `template import <file>` and `template remove <name>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("template") && !head.eq_ignore_ascii_case("templates") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (verb, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let arg = arg.trim().trim_matches('"');
+        match verb.to_lowercase().as_str() {
+            "" | "list" => Some(TemplateCommand::List),
+            "import" if !arg.is_empty() => Some(TemplateCommand::Import(arg.to_string())),
+            "remove" | "delete" if !arg.is_empty() => {
+                Some(TemplateCommand::Remove(arg.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn save_homebrew(templates: &CharacterTemplates, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(HOMEBREW_TEMPLATES_DB_KEY, templates.homebrew()) {
+        warn!("Failed to save homebrew templates: {}", e);
+    }
+}
+
+fn import_templates(path: &str) -> Result<CharacterTemplates, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let templates: CharacterTemplates = serde_json::from_str(&text)
+        .map_err(|e| format!("{} is not a template file: {}", path, e))?;
+    templates.validate()?;
+    Ok(templates)
+}
+
+/// Apply a template command, saving the homebrew templates and notifying
+/// the user.
+pub fn run_template_command(
+    In(command): In<TemplateCommand>,
+    mut templates: ResMut<CharacterTemplates>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        TemplateCommand::List => {
+            let races: Vec<&str> = templates.races.iter().map(|r| r.name.as_str()).collect();
+            let classes: Vec<&str> = templates.classes.iter().map(|c| c.name.as_str()).collect();
+            let backgrounds: Vec<&str> = templates
+                .backgrounds
+                .iter()
+                .map(|b| b.name.as_str())
+                .collect();
+            format!(
+                "Races: {}. Classes: {}. Backgrounds: {}",
+                races.join(", "),
+                classes.join(", "),
+                backgrounds.join(", ")
+            )
+        }
+        TemplateCommand::Import(path) => match import_templates(&path) {
+            Ok(homebrew) => {
+                let added = templates.add_homebrew(homebrew);
+                save_homebrew(&templates, &db);
+                format!("Imported {} homebrew template(s)", added)
+            }
+            Err(e) => e,
+        },
+        TemplateCommand::Remove(name) => {
+            if templates.remove_homebrew(&name) {
+                save_homebrew(&templates, &db);
+                format!("Removed homebrew template {}", name)
+            } else {
+                format!("No homebrew template named {}", name)
+            }
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Load the homebrew templates whenever the database is opened or the
+/// profile switches.
+pub fn load_character_templates(
+    db: Option<Res<CharacterDatabase>>,
+    mut templates: ResMut<CharacterTemplates>,
+) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    let homebrew = db
+        .get_setting::<CharacterTemplates>(HOMEBREW_TEMPLATES_DB_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+    *templates = CharacterTemplates::with_homebrew(homebrew);
+}
+
+/// Modifier text shown next to a score, e.g. "+2"
+fn modifier_label(score: i32) -> String {
+    format!("{:+}", Attributes::calculate_modifier(score))
+}
+
+fn spawn_wizard_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    selected: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if selected {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+fn spawn_wizard_chip<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    selected: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, label_color) = if selected {
+        (
+            builder.filled_tonal().build(theme),
+            theme.on_secondary_container,
+        )
+    } else {
+        (builder.outlined().build(theme), theme.on_surface_variant)
+    };
+    parent
+        .spawn((button, marker))
+        .insert(Node {
+            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                ButtonLabel,
+                Text::new(label),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(label_color),
+            ));
+        });
+}
+
+/// Wrapping row of chips
+fn chip_row() -> Node {
+    Node {
+        flex_direction: FlexDirection::Row,
+        flex_wrap: FlexWrap::Wrap,
+        column_gap: Val::Px(6.0),
+        row_gap: Val::Px(6.0),
+        ..default()
+    }
+}
+
+fn spawn_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_method_step(
+    parent: &mut ChildSpawnerCommands,
+    scores: &AbilityScores,
+    theme: &MaterialTheme,
+) {
+    for method in ScoreMethod::ALL {
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_wizard_button(
+                    row,
+                    method.label(),
+                    scores.method == method,
+                    CreationMethodButton { method },
+                    theme,
+                );
+                spawn_text(
+                    row,
+                    method.description().to_string(),
+                    13.0,
+                    theme.on_surface_variant,
+                );
+            });
+    }
+}
+
+fn spawn_scores_step(
+    parent: &mut ChildSpawnerCommands,
+    scores: &AbilityScores,
+    theme: &MaterialTheme,
+) {
+    let hint = match scores.method {
+        ScoreMethod::Roll => "Roll each ability in the dice view".to_string(),
+        ScoreMethod::StandardArray => {
+            let free = scores.unassigned();
+            if free.is_empty() {
+                "Every standard array value is assigned".to_string()
+            } else {
+                let free: Vec<String> = free.iter().map(|v| v.to_string()).collect();
+                format!("Still to assign: {}", free.join(", "))
+            }
+        }
+        ScoreMethod::PointBuy => format!(
+            "{} of {} points left",
+            scores.points_left(),
+            POINT_BUY_BUDGET
+        ),
+    };
+    spawn_text(parent, hint, 14.0, theme.on_surface_variant);
+
+    for (ability, name) in ABILITY_NAMES.iter().enumerate() {
+        let score = scores.scores[ability];
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(10.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(ability_abbreviation(name)),
+                    TextFont {
+                        font_size: 15.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                    Node {
+                        width: Val::Px(48.0),
+                        ..default()
+                    },
+                ));
+                row.spawn((
+                    Text::new(
+                        score
+                            .map(|s| format!("{} ({})", s, modifier_label(s)))
+                            .unwrap_or_else(|| "--".to_string()),
+                    ),
+                    TextFont {
+                        font_size: 15.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                    Node {
+                        width: Val::Px(72.0),
+                        ..default()
+                    },
+                ));
+
+                match scores.method {
+                    ScoreMethod::Roll => {
+                        let label = if score.is_some() {
+                            "Reroll"
+                        } else {
+                            "Roll 4d6"
+                        };
+                        spawn_wizard_button(
+                            row,
+                            label,
+                            false,
+                            CreationRollButton { ability },
+                            theme,
+                        );
+                        if let Some(dice) = scores.rolls[ability] {
+                            let faces: Vec<String> = dice.iter().map(|d| d.to_string()).collect();
+                            spawn_text(
+                                row,
+                                format!("[{}]", faces.join(", ")),
+                                13.0,
+                                theme.on_surface_variant,
+                            );
+                        }
+                    }
+                    ScoreMethod::StandardArray => {
+                        spawn_wizard_button(
+                            row,
+                            if score.is_some() { "Next" } else { "Assign" },
+                            false,
+                            CreationArrayButton { ability },
+                            theme,
+                        );
+                    }
+                    ScoreMethod::PointBuy => {
+                        spawn_wizard_button(
+                            row,
+                            "-",
+                            false,
+                            CreationPointButton { ability, delta: -1 },
+                            theme,
+                        );
+                        spawn_wizard_button(
+                            row,
+                            "+",
+                            false,
+                            CreationPointButton { ability, delta: 1 },
+                            theme,
+                        );
+                    }
+                }
+            });
+    }
+}
+
+fn spawn_race_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let race = wizard
+        .choice
+        .race
+        .as_deref()
+        .and_then(|r| templates.race(r));
+    spawn_text(
+        parent,
+        match race {
+            Some(race) if race.skills.is_empty() => {
+                format!("{}: speed {} ft", race.name, race.speed)
+            }
+            Some(race) => format!(
+                "{}: speed {} ft, proficient in {}",
+                race.name,
+                race.speed,
+                skill_list(&race.skills)
+            ),
+            None => "Pick a race".to_string(),
+        },
+        14.0,
+        theme.on_surface_variant,
+    );
+    parent.spawn(chip_row()).with_children(|chips| {
+        for template in &templates.races {
+            spawn_wizard_chip(
+                chips,
+                &template.name,
+                race.is_some_and(|r| r.name == template.name),
+                CreationRaceButton {
+                    race: template.name.clone(),
+                },
+                theme,
+            );
+        }
+    });
+}
+
+fn spawn_class_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let class = wizard
+        .choice
+        .class
+        .as_deref()
+        .and_then(|c| templates.class(c));
+    spawn_text(
+        parent,
+        match class {
+            Some(class) => format!("{}: {}", class.name, class_summary(class)),
+            None => "Pick a class".to_string(),
+        },
+        14.0,
+        theme.on_surface_variant,
+    );
+    parent.spawn(chip_row()).with_children(|chips| {
+        for template in &templates.classes {
+            spawn_wizard_chip(
+                chips,
+                &template.name,
+                class.is_some_and(|c| c.name == template.name),
+                CreationClassButton {
+                    class: template.name.clone(),
+                },
+                theme,
+            );
+        }
+    });
+    if let Some(casting) = class.and_then(|c| c.spellcasting.as_ref()) {
+        spawn_text(
+            parent,
+            format!(
+                "Casts with {} from 1st level",
+                ability_abbreviation(&casting.ability)
+            ),
+            13.0,
+            theme.on_surface_variant,
+        );
+    }
+}
+
+fn spawn_abilities_step(
+    parent: &mut ChildSpawnerCommands,
+    scores: &AbilityScores,
+    theme: &MaterialTheme,
+) {
+    spawn_method_step(parent, scores, theme);
+    spawn_scores_step(parent, scores, theme);
+}
+
+fn spawn_background_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let background = wizard
+        .choice
+        .background
+        .as_deref()
+        .and_then(|b| templates.background(b));
+    spawn_text(
+        parent,
+        match background {
+            Some(background) => format!(
+                "{}: proficient in {}, {} gp",
+                background.name,
+                skill_list(&background.skills),
+                background.gold
+            ),
+            None => "Pick a background".to_string(),
+        },
+        14.0,
+        theme.on_surface_variant,
+    );
+    parent.spawn(chip_row()).with_children(|chips| {
+        for template in &templates.backgrounds {
+            spawn_wizard_chip(
+                chips,
+                &template.name,
+                background.is_some_and(|b| b.name == template.name),
+                CreationBackgroundButton {
+                    background: template.name.clone(),
+                },
+                theme,
+            );
+        }
+    });
+    if let Some(background) = background.filter(|b| !b.items.is_empty()) {
+        spawn_text(
+            parent,
+            format!("Brings {}", background.items.join(", ")),
+            13.0,
+            theme.on_surface_variant,
+        );
+    }
+}
+
+fn spawn_skills_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let choice = &wizard.choice;
+    let Some(class) = choice.class.as_deref().and_then(|c| templates.class(c)) else {
+        return;
+    };
+    // Skills the race or background already gives aren't offered again
+    let mut granted: Vec<String> = Vec::new();
+    if let Some(race) = choice.race.as_deref().and_then(|r| templates.race(r)) {
+        granted.extend(race.skills.iter().cloned());
+    }
+    if let Some(background) = choice
+        .background
+        .as_deref()
+        .and_then(|b| templates.background(b))
+    {
+        granted.extend(background.skills.iter().cloned());
+    }
+    if !granted.is_empty() {
+        spawn_text(
+            parent,
+            format!("Already proficient in {}", skill_list(&granted)),
+            13.0,
+            theme.on_surface_variant,
+        );
+    }
+    spawn_text(
+        parent,
+        format!(
+            "{} skills: {} of {} picked",
+            class.name,
+            choice.skills.len(),
+            class.skill_count
+        ),
+        14.0,
+        theme.on_surface_variant,
+    );
+    parent.spawn(chip_row()).with_children(|chips| {
+        for skill in class.skill_choices.iter().filter(|s| !granted.contains(s)) {
+            spawn_wizard_chip(
+                chips,
+                &skill_label(skill),
+                choice.skills.contains(skill),
+                CreationSkillButton {
+                    skill: skill.clone(),
+                },
+                theme,
+            );
+        }
+    });
+}
+
+/// "Longsword, Light Crossbow · Chain Mail + shield · Dungeoneer's Pack"
+fn pack_summary(pack: &EquipmentPack) -> String {
+    let mut parts = Vec::new();
+    if !pack.weapons.is_empty() {
+        parts.push(pack.weapons.join(", "));
+    }
+    match (&pack.armor, pack.shield) {
+        (Some(armor), true) => parts.push(format!("{} + shield", armor)),
+        (Some(armor), false) => parts.push(armor.clone()),
+        (None, true) => parts.push("Shield".to_string()),
+        (None, false) => {}
+    }
+    if !pack.items.is_empty() {
+        parts.push(pack.items.join(", "));
+    }
+    if pack.gold > 0 {
+        parts.push(format!("{} gp", pack.gold));
+    }
+    parts.join(" · ")
+}
+
+fn spawn_equipment_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let Some(class) = wizard
+        .choice
+        .class
+        .as_deref()
+        .and_then(|c| templates.class(c))
+    else {
+        return;
+    };
+    if class.equipment.is_empty() {
+        spawn_text(
+            parent,
+            format!(
+                "{} has no starting packs; add gear on the sheet",
+                class.name
+            ),
+            14.0,
+            theme.on_surface_variant,
+        );
+        return;
+    }
+    for (index, pack) in class.equipment.iter().enumerate() {
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_wizard_button(
+                    row,
+                    &pack.name,
+                    wizard.choice.equipment == Some(index),
+                    CreationEquipmentButton { pack: index },
+                    theme,
+                );
+                spawn_text(row, pack_summary(pack), 13.0, theme.on_surface_variant);
+            });
+    }
+}
+
+fn spawn_spells_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let choice = &wizard.choice;
+    let Some(class) = choice.class.as_deref().and_then(|c| templates.class(c)) else {
+        return;
+    };
+    let Some(casting) = &class.spellcasting else {
+        return;
+    };
+    let scores = wizard.scores.validate().unwrap_or([10; 6]);
+    let (cantrips, spells) = TemplateChoice::spell_counts(class, scores);
+
+    let lists = [
+        (
+            "Cantrips",
+            true,
+            &casting.cantrip_list,
+            &choice.cantrips,
+            cantrips,
+        ),
+        (
+            if casting.spellbook {
+                "Spellbook"
+            } else {
+                "1st-level spells"
+            },
+            false,
+            &casting.spell_list,
+            &choice.spells,
+            spells,
+        ),
+    ];
+    for (title, cantrip, list, picked, count) in lists {
+        spawn_text(
+            parent,
+            format!("{}: {} of {} picked", title, picked.len(), count),
+            14.0,
+            theme.on_surface_variant,
+        );
+        parent.spawn(chip_row()).with_children(|chips| {
+            for spell in list {
+                spawn_wizard_chip(
+                    chips,
+                    spell,
+                    picked.contains(spell),
+                    CreationSpellButton {
+                        spell: spell.clone(),
+                        cantrip,
+                    },
+                    theme,
+                );
+            }
+        });
+    }
+}
+
+/// "sleightOfHand" as "Sleight Of Hand"
+fn skill_label(skill: &str) -> String {
+    let mut label = String::new();
+    for (i, c) in skill.chars().enumerate() {
+        if i == 0 {
+            label.extend(c.to_uppercase());
+        } else if c.is_uppercase() {
+            label.push(' ');
+            label.push(c);
+        } else {
+            label.push(c);
+        }
+    }
+    label
+}
+
+/// Skill keys as a readable list, e.g. "Arcana, Sleight Of Hand"
+fn skill_list(skills: &[String]) -> String {
+    let labels: Vec<String> = skills.iter().map(|s| skill_label(s)).collect();
+    labels.join(", ")
+}
+
+fn spawn_review_step(
+    parent: &mut ChildSpawnerCommands,
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+    theme: &MaterialTheme,
+) {
+    let built = wizard
+        .scores
+        .validate()
+        .and_then(|scores| wizard.choice.build(templates, scores));
+    let sheet = match built {
+        Ok(data) => data.sheet,
+        Err(problem) => {
+            spawn_text(parent, problem, 14.0, theme.error);
+            return;
+        }
+    };
+    let Some(sheet) = sheet else {
+        return;
+    };
+
+    spawn_text(
+        parent,
+        format!(
+            "{} {}, {}",
+            sheet.character.race,
+            sheet.character.class,
+            sheet.character.background.as_deref().unwrap_or_default()
+        ),
+        16.0,
+        theme.on_surface,
+    );
+    spawn_text(
+        parent,
+        format!(
+            "Scores from {}: {}",
+            wizard.scores.method.label(),
+            sheet
+                .attributes
+                .as_vec()
+                .iter()
+                .map(|(name, score)| format!(
+                    "{} {} ({})",
+                    ability_abbreviation(name),
+                    score,
+                    modifier_label(*score)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        14.0,
+        theme.on_surface_variant,
+    );
+    spawn_text(
+        parent,
+        format!(
+            "AC {} · HP {} · Speed {} ft",
+            sheet.combat.armor_class,
+            sheet
+                .combat
+                .hit_points
+                .as_ref()
+                .map(|hp| hp.maximum)
+                .unwrap_or_default(),
+            sheet.combat.speed
+        ),
+        14.0,
+        theme.on_surface,
+    );
+
+    let mut skills: Vec<String> = sheet
+        .skills
+        .iter()
+        .filter(|(_, skill)| skill.proficient)
+        .map(|(name, _)| name.clone())
+        .collect();
+    skills.sort();
+    if !skills.is_empty() {
+        spawn_text(
+            parent,
+            format!("Proficient in {}", skill_list(&skills)),
+            14.0,
+            theme.on_surface_variant,
+        );
+    }
+    if let Some(equipment) = &sheet.equipment {
+        let weapons: Vec<&str> = equipment.weapons.iter().map(|w| w.name.as_str()).collect();
+        if !weapons.is_empty() {
+            spawn_text(
+                parent,
+                format!("Weapons: {}", weapons.join(", ")),
+                14.0,
+                theme.on_surface_variant,
+            );
+        }
+        spawn_text(
+            parent,
+            format!(
+                "{} items, {}",
+                equipment.items.len(),
+                equipment.currency.describe()
+            ),
+            14.0,
+            theme.on_surface_variant,
+        );
+    }
+    if let Some(spells) = &sheet.spells {
+        spawn_text(
+            parent,
+            format!("Spells: {}", spells.known_spells.join(", ")),
+            14.0,
+            theme.on_surface_variant,
+        );
+    }
+}
+
+/// Show the wizard dialog while it's open and the character tab is active.
+pub fn manage_creation_wizard_dialog(
+    mut commands: Commands,
+    wizard: Res<CharacterCreationWizard>,
+    templates: Res<CharacterTemplates>,
+    ui_state: Res<UiState>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<CreationWizardOverlay>>,
+    mut shown: Local<bool>,
+) {
+    let visible = wizard.open && ui_state.active_tab == AppTab::CharacterSheet;
+    if !wizard.is_changed() && !templates.is_changed() && visible == *shown {
+        return;
+    }
+    *shown = visible;
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !visible {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("New Character")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(560.0),
+                max_height: Val::Percent(90.0),
+                overflow: Overflow::scroll_y(),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            CreationWizardOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    let step = wizard.step;
+    commands.entity(dialog_entity).with_children(|dialog| {
+        spawn_text(
+            dialog,
+            format!(
+                "Step {} of {}: {}",
+                step.number(),
+                CreationStep::COUNT,
+                step.title()
+            ),
+            20.0,
+            theme.on_surface,
+        );
+
+        match step {
+            CreationStep::Race => spawn_race_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Class => spawn_class_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Abilities => spawn_abilities_step(dialog, &wizard.scores, &theme),
+            CreationStep::Background => spawn_background_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Skills => spawn_skills_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Equipment => spawn_equipment_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Spells => spawn_spells_step(dialog, &wizard, &templates, &theme),
+            CreationStep::Review => spawn_review_step(dialog, &wizard, &templates, &theme),
+        }
+
+        if let Some(message) = &wizard.message {
+            spawn_text(dialog, message.clone(), 13.0, theme.error);
+        }
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_wizard_button(
+                    buttons,
+                    "Cancel",
+                    false,
+                    CreationNavButton {
+                        nav: CreationNav::Cancel,
+                    },
+                    &theme,
+                );
+                if step.previous().is_some() {
+                    spawn_wizard_button(
+                        buttons,
+                        "Back",
+                        false,
+                        CreationNavButton {
+                            nav: CreationNav::Back,
+                        },
+                        &theme,
+                    );
+                }
+                let next = if step == CreationStep::Review {
+                    "Create"
+                } else {
+                    "Next"
+                };
+                spawn_wizard_button(
+                    buttons,
+                    next,
+                    true,
+                    CreationNavButton {
+                        nav: CreationNav::Next,
+                    },
+                    &theme,
+                );
+            });
+    });
+}
+
+/// Pick how the ability scores are decided, starting the scores over.
+pub fn handle_creation_method_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&CreationMethodButton>,
+    mut wizard: ResMut<CharacterCreationWizard>,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        if wizard.scores.method != button.method {
+            wizard.scores = AbilityScores::new(button.method);
+        }
+        wizard.message = None;
+    }
+}
+
+/// Assign standard array values and spend point-buy points.
+pub fn handle_creation_score_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    array_buttons: Query<&CreationArrayButton>,
+    point_buttons: Query<&CreationPointButton>,
+    mut wizard: ResMut<CharacterCreationWizard>,
+) {
+    for event in click_events.read() {
+        if let Ok(button) = array_buttons.get(event.entity) {
+            wizard.scores.cycle_standard(button.ability);
+            wizard.message = None;
+        } else if let Ok(button) = point_buttons.get(event.entity) {
+            let result = wizard.scores.adjust_point_buy(button.ability, button.delta);
+            wizard.message = result.err();
+        }
+    }
+}
+
+/// Pick the race, class and class skills.
+pub fn handle_creation_origin_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    race_buttons: Query<&CreationRaceButton>,
+    class_buttons: Query<&CreationClassButton>,
+    skill_buttons: Query<&CreationSkillButton>,
+    templates: Res<CharacterTemplates>,
+    mut wizard: ResMut<CharacterCreationWizard>,
+) {
+    for event in click_events.read() {
+        if let Ok(button) = race_buttons.get(event.entity) {
+            if let Some(race) = templates.race(&button.race) {
+                wizard.choice.set_race(race);
+                wizard.message = None;
+            }
+        } else if let Ok(button) = class_buttons.get(event.entity) {
+            if let Some(class) = templates.class(&button.class) {
+                wizard.choice.set_class(class);
+                wizard.message = None;
+            }
+        } else if let Ok(button) = skill_buttons.get(event.entity) {
+            let Some(class) = wizard
+                .choice
+                .class
+                .as_deref()
+                .and_then(|c| templates.class(c))
+            else {
+                continue;
+            };
+            let result = wizard.choice.toggle_skill(class, &button.skill);
+            wizard.message = result.err();
+        }
+    }
+}
+
+/// Pick the background, starting equipment and spells.
+pub fn handle_creation_kit_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    background_buttons: Query<&CreationBackgroundButton>,
+    equipment_buttons: Query<&CreationEquipmentButton>,
+    spell_buttons: Query<&CreationSpellButton>,
+    templates: Res<CharacterTemplates>,
+    mut wizard: ResMut<CharacterCreationWizard>,
+) {
+    for event in click_events.read() {
+        if let Ok(button) = background_buttons.get(event.entity) {
+            if let Some(background) = templates.background(&button.background) {
+                wizard.choice.set_background(background);
+                wizard.message = None;
+            }
+            continue;
+        }
+
+        let Some(class) = wizard
+            .choice
+            .class
+            .as_deref()
+            .and_then(|c| templates.class(c))
+        else {
+            continue;
+        };
+        if let Ok(button) = equipment_buttons.get(event.entity) {
+            wizard.choice.equipment = Some(button.pack);
+            wizard.message = None;
+        } else if let Ok(button) = spell_buttons.get(event.entity) {
+            let Some(casting) = &class.spellcasting else {
+                continue;
+            };
+            let scores = wizard.scores.validate().unwrap_or([10; 6]);
+            let (cantrips, spells) = TemplateChoice::spell_counts(class, scores);
+            let limit = if button.cantrip { cantrips } else { spells };
+            let result = wizard
+                .choice
+                .toggle_spell(casting, &button.spell, button.cantrip, limit);
+            wizard.message = result.err();
+        }
+    }
+}
+
+/// Check the step before moving past it.
+fn check_step(
+    wizard: &CharacterCreationWizard,
+    templates: &CharacterTemplates,
+) -> Result<(), String> {
+    let choice = &wizard.choice;
+    match wizard.step {
+        CreationStep::Race => choice.check_race(templates),
+        CreationStep::Class => choice.check_class(templates),
+        CreationStep::Abilities => wizard.scores.validate().map(|_| ()),
+        CreationStep::Background => choice.check_background(templates),
+        CreationStep::Skills => choice.check_skills(templates),
+        CreationStep::Equipment => choice.check_equipment(templates),
+        CreationStep::Spells => {
+            let scores = wizard.scores.validate()?;
+            choice.check_spells(templates, scores)
+        }
+        CreationStep::Review => Ok(()),
+    }
+}
+
+/// Move between the wizard steps, creating the character after the review.
+pub fn handle_creation_nav_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&CreationNavButton>,
+    templates: Res<CharacterTemplates>,
+    mut wizard: ResMut<CharacterCreationWizard>,
+    mut character_manager: ResMut<CharacterManager>,
+    mut character_data: ResMut<CharacterData>,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+
+        // The spells step only applies to classes that cast at 1st level
+        let casts = wizard
+            .choice
+            .class
+            .as_deref()
+            .and_then(|c| templates.class(c))
+            .is_some_and(|c| c.spellcasting.is_some());
+        let skip = |step: &CreationStep| *step == CreationStep::Spells && !casts;
+
+        match button.nav {
+            CreationNav::Cancel => {
+                *wizard = CharacterCreationWizard::default();
+            }
+            CreationNav::Back => {
+                let mut previous = wizard.step.previous();
+                if previous.as_ref().is_some_and(skip) {
+                    previous = previous.and_then(|s| s.previous());
+                }
+                if let Some(previous) = previous {
+                    wizard.step = previous;
+                }
+                wizard.message = None;
+            }
+            CreationNav::Next if wizard.step == CreationStep::Review => {
+                let built = wizard
+                    .scores
+                    .validate()
+                    .and_then(|scores| wizard.choice.build(&templates, scores));
+                match built {
+                    Ok(data) => {
+                        // Kept in memory until saved, like any other new character.
+                        character_manager.current_character_id = None;
+                        character_data.sheet = data.sheet;
+                        character_data.is_modified = true;
+                        *wizard = CharacterCreationWizard::default();
+                    }
+                    Err(problem) => wizard.message = Some(problem),
+                }
+            }
+            CreationNav::Next => match check_step(&wizard, &templates) {
+                Ok(()) => {
+                    let mut next = wizard.step.next();
+                    if next.as_ref().is_some_and(skip) {
+                        next = next.and_then(|s| s.next());
+                    }
+                    if let Some(next) = next {
+                        wizard.step = next;
+                    }
+                    wizard.message = None;
+                }
+                Err(problem) => wizard.message = Some(problem),
+            },
+        }
+    }
+}
@@ -14,6 +14,7 @@ use bevy_rapier3d::prelude::Velocity;
 use pulldown_cmark::{Event as MdEvent, Parser, Tag, TagEnd};
 
 use super::*;
+use crate::dice3d::meshes::DiceMeshCache;
 use crate::dice3d::systems::dice_box_controls::start_container_shake;
 use crate::dice3d::types::*;
 
@@ -162,6 +163,7 @@ pub fn handle_stat_field_click(
             | EditingField::HitPointsCurrent
             | EditingField::HitPointsMaximum
             | EditingField::ProficiencyBonus
+            | EditingField::ExtraAttacks
             | EditingField::CustomCombat(_)
             | EditingField::CustomCombatLabel(_) => {
                 edit_state.editing_groups.contains(&GroupType::Combat)
@@ -172,6 +174,19 @@ pub fn handle_stat_field_click(
             EditingField::Skill(_) | EditingField::SkillLabel(_) => {
                 edit_state.editing_groups.contains(&GroupType::Skills)
             }
+            EditingField::SpellcastingAbility
+            | EditingField::SpellSaveDc
+            | EditingField::SpellAttackBonus
+            | EditingField::SpellSlots(_)
+            | EditingField::SpellDamage(_) => {
+                edit_state.editing_groups.contains(&GroupType::Spells)
+            }
+            EditingField::ItemQuantity(_)
+            | EditingField::ItemWeight(_)
+            | EditingField::ItemValue(_)
+            | EditingField::ItemBonuses(_)
+            | EditingField::AttunementSlots
+            | EditingField::Coins(_) => edit_state.editing_groups.contains(&GroupType::Inventory),
         };
 
         if is_group_editing {
@@ -288,9 +303,19 @@ pub fn handle_text_input(
                     EditingField::Initiative
                     | EditingField::ProficiencyBonus
                     | EditingField::Skill(_)
-                    | EditingField::SavingThrow(_) => {
+                    | EditingField::SavingThrow(_)
+                    | EditingField::SpellAttackBonus => {
                         key_code.is_ascii_digit() || key_code == '-' || key_code == '+'
                     }
+                    EditingField::SpellSaveDc
+                    | EditingField::SpellSlots(_)
+                    | EditingField::ExtraAttacks
+                    | EditingField::ItemQuantity(_)
+                    | EditingField::AttunementSlots
+                    | EditingField::Coins(_) => key_code.is_ascii_digit(),
+                    EditingField::ItemWeight(_) | EditingField::ItemValue(_) => {
+                        key_code.is_ascii_digit() || key_code == '.'
+                    }
                     _ => true,
                 };
 
@@ -375,6 +400,7 @@ impl KeyCodeToChar for KeyCode {
 /// Update the display of currently editing fields
 pub fn update_editing_display(
     text_input: Res<TextInputState>,
+    tokens: Res<ThemeTokens>,
     mut field_values: Query<(&StatFieldValue, &mut Text, &mut TextColor)>,
     mut label_texts: Query<
         (&EditableLabelText, &mut Text, &mut TextColor),
@@ -395,7 +421,7 @@ pub fn update_editing_display(
                 format!("{}|", text_input.current_text)
             };
             *text = Text::new(display);
-            color.0 = MD3_PRIMARY;
+            color.0 = tokens.color(ThemeToken::Primary);
         }
     }
 
@@ -408,7 +434,7 @@ pub fn update_editing_display(
                 format!("{}|", text_input.current_text)
             };
             *text = Text::new(display);
-            color.0 = MD3_PRIMARY;
+            color.0 = tokens.color(ThemeToken::Primary);
         }
     }
 }
@@ -520,6 +546,23 @@ pub fn handle_new_entry_confirm(
                             },
                         );
                     }
+                    GroupType::Spells => {
+                        let spells = sheet.spells.get_or_insert_with(Default::default);
+                        if !spells.known_spells.contains(&adding_state.new_entry_name) {
+                            spells
+                                .known_spells
+                                .push(adding_state.new_entry_name.clone());
+                        }
+                    }
+                    GroupType::Inventory => {
+                        // "Arrows (20)" adds 20; a name already carried adds to that stack
+                        let new_item = InventoryItem::parse(&adding_state.new_entry_name);
+                        let items = &mut sheet.equipment.get_or_insert_with(Default::default).items;
+                        match items.iter_mut().find(|item| item.name == new_item.name) {
+                            Some(item) => item.quantity += new_item.quantity,
+                            None => items.push(new_item),
+                        }
+                    }
                 }
                 character_data.is_modified = true;
             }
@@ -649,6 +692,16 @@ pub fn handle_delete_click(
                 GroupType::Skills => {
                     sheet.skills.remove(&button.entry_id);
                 }
+                GroupType::Spells => {
+                    if let Some(spells) = sheet.spells.as_mut() {
+                        spells.remove_spell(&button.entry_id);
+                    }
+                }
+                GroupType::Inventory => {
+                    if let Some(equipment) = sheet.equipment.as_mut() {
+                        equipment.items.retain(|item| item.name != button.entry_id);
+                    }
+                }
             }
             character_data.is_modified = true;
         }
@@ -755,11 +808,14 @@ pub struct CharacterSheetRollParams<'w, 's> {
     pub commands: Commands<'w, 's>,
     pub meshes: ResMut<'w, Assets<Mesh>>,
     pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
     pub dice_config: ResMut<'w, DiceConfig>,
     pub dice_results: ResMut<'w, DiceResults>,
     pub roll_state: ResMut<'w, RollState>,
     pub ui_state: ResMut<'w, UiState>,
     pub snackbar: MessageWriter<'w, ShowSnackbar>,
+    pub roll_requested: MessageWriter<'w, RollRequested>,
     pub dice_query: Query<'w, 's, Entity, With<Die>>,
     pub settings_state: Res<'w, SettingsState>,
 
@@ -770,6 +826,45 @@ pub struct CharacterSheetRollParams<'w, 's> {
     pub shake_config: Res<'w, ContainerShakeConfig>,
     pub shake_anim: ResMut<'w, ContainerShakeAnimation>,
     pub container_query: Query<'w, 's, (Entity, &'static Transform), With<DiceBox>>,
+    pub ability_overrides: Res<'w, SkillAbilityOverrides>,
+    pub ruleset: Res<'w, Ruleset>,
+}
+
+/// Cycle the ability a skill is rolled with when its picker is clicked
+pub fn handle_skill_ability_picker_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&SkillAbilityPickerButton>,
+    mut overrides: ResMut<SkillAbilityOverrides>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        overrides.cycle(&button.skill);
+    }
+}
+
+/// Keep skill ability picker labels in sync with the chosen overrides
+/// (including after the character panel is rebuilt).
+pub fn sync_skill_ability_picker_labels(
+    overrides: Res<SkillAbilityOverrides>,
+    mut labels: Query<(Ref<SkillAbilityPickerText>, &mut Text)>,
+) {
+    for (label, mut text) in labels.iter_mut() {
+        if !overrides.is_changed() && !label.is_added() {
+            continue;
+        }
+        let ability = overrides.ability_for(&label.skill).unwrap_or("");
+        let abbreviation = ability_abbreviation(ability);
+        if text.0 != abbreviation {
+            text.0 = abbreviation.to_string();
+        }
+    }
 }
 
 /// Handle clicking on attribute roll buttons
@@ -816,23 +911,20 @@ pub fn handle_roll_attribute_click(
                 .character_sheet_default_die
                 .to_dice_type();
 
-            let die_scale = params
-                .settings_state
-                .settings
-                .dice_scales
-                .scale_for(die_type);
-
             let use_shake = params.settings_state.settings.default_roll_uses_shake;
 
             start_character_sheet_roll(
                 &mut params.commands,
                 &mut params.meshes,
                 &mut params.materials,
+                &mut params.mesh_cache,
+                &mut params.roll_rng,
                 &mut params.dice_config,
                 &mut params.dice_results,
                 &mut params.roll_state,
                 &mut params.ui_state,
                 &mut params.bridge,
+                &mut params.roll_requested,
                 &params.character_manager,
                 &params.dice_query,
                 &params.container_style,
@@ -842,11 +934,21 @@ pub fn handle_roll_attribute_click(
                 &mut params.shake_anim,
                 &params.container_query,
                 use_shake,
-                die_type,
-                die_scale,
-                modifier,
-                format!("{} Check", button.attribute),
-                CharacterScreenRollTarget::Attribute(button.attribute.clone()),
+                &params.settings_state.settings.dice_scales,
+                DiceConfig {
+                    dice_to_roll: vec![die_type],
+                    modifier,
+                    modifier_name: format!("{} Check", button.attribute),
+                    expression: None,
+                }
+                .with_condition_effect(
+                    &sheet
+                        .conditions
+                        .effect_on(&D20Test::AbilityCheck, *params.ruleset),
+                ),
+                Some(CharacterScreenRollTarget::Attribute(
+                    button.attribute.clone(),
+                )),
             );
 
             params
@@ -878,11 +980,44 @@ pub fn handle_roll_skill_click(
             continue;
         };
 
-        let modifier = sheet
-            .skills
+        // A non-default ability chosen with the picker recomputes the modifier
+        // from that ability plus proficiency.
+        let override_ability = params
+            .ability_overrides
+            .overrides
             .get(&button.skill)
-            .map(|s| s.modifier)
-            .unwrap_or(0);
+            .cloned();
+        let (modifier, modifier_name) = match override_ability.as_deref().and_then(|ability| {
+            params
+                .character_data
+                .get_skill_modifier_with_ability(&button.skill, ability)
+                .map(|m| (m, ability))
+        }) {
+            Some((m, ability)) => (
+                m,
+                format!("{} ({}) Skill", button.skill, ability_abbreviation(ability)),
+            ),
+            None => (
+                sheet
+                    .skills
+                    .get(&button.skill)
+                    .map(|s| s.modifier)
+                    .unwrap_or(0),
+                format!("{} Skill", button.skill),
+            ),
+        };
+
+        // Tool/skill synergy: flat bonuses are added here; advantage throws a
+        // second d20, cancelled by disadvantage from conditions.
+        let synergy = sheet.tool_synergy_for_skill(&button.skill);
+        let (modifier, modifier_name) = if synergy.applies() {
+            (
+                modifier + synergy.bonus,
+                format!("{} [{}]", modifier_name, synergy.describe()),
+            )
+        } else {
+            (modifier, modifier_name)
+        };
 
         let die_type = params
             .settings_state
@@ -890,11 +1025,161 @@ pub fn handle_roll_skill_click(
             .character_sheet_default_die
             .to_dice_type();
 
-        let die_scale = params
-            .settings_state
-            .settings
-            .dice_scales
-            .scale_for(die_type);
+        let use_shake = params.settings_state.settings.default_roll_uses_shake;
+
+        start_character_sheet_roll(
+            &mut params.commands,
+            &mut params.meshes,
+            &mut params.materials,
+            &mut params.mesh_cache,
+            &mut params.roll_rng,
+            &mut params.dice_config,
+            &mut params.dice_results,
+            &mut params.roll_state,
+            &mut params.ui_state,
+            &mut params.bridge,
+            &mut params.roll_requested,
+            &params.character_manager,
+            &params.dice_query,
+            &params.container_style,
+            &mut params.lid_ctrl,
+            &params.shake_state,
+            &params.shake_config,
+            &mut params.shake_anim,
+            &params.container_query,
+            use_shake,
+            &params.settings_state.settings.dice_scales,
+            DiceConfig {
+                dice_to_roll: vec![die_type],
+                modifier,
+                modifier_name,
+                expression: None,
+            }
+            .with_advantage_and_conditions(
+                synergy.advantage,
+                &sheet
+                    .conditions
+                    .effect_on(&D20Test::AbilityCheck, *params.ruleset),
+            ),
+            Some(CharacterScreenRollTarget::Skill(button.skill.clone())),
+        );
+
+        params
+            .snackbar
+            .write(ShowSnackbar::message("Dice roll started").duration(2.0));
+    }
+}
+
+/// Roll 4d6, dropping the lowest, for an ability in the creation wizard
+pub fn handle_creation_roll_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&CreationRollButton>,
+    mut params: CharacterSheetRollParams,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Ok(expression) = DiceExpr::parse("4d6kh3") else {
+            continue;
+        };
+
+        let (dice_to_roll, expression) = DiceConfig::from_expression(&expression);
+        let use_shake = params.settings_state.settings.default_roll_uses_shake;
+
+        start_character_sheet_roll(
+            &mut params.commands,
+            &mut params.meshes,
+            &mut params.materials,
+            &mut params.mesh_cache,
+            &mut params.roll_rng,
+            &mut params.dice_config,
+            &mut params.dice_results,
+            &mut params.roll_state,
+            &mut params.ui_state,
+            &mut params.bridge,
+            &mut params.roll_requested,
+            &params.character_manager,
+            &params.dice_query,
+            &params.container_style,
+            &mut params.lid_ctrl,
+            &params.shake_state,
+            &params.shake_config,
+            &mut params.shake_anim,
+            &params.container_query,
+            use_shake,
+            &params.settings_state.settings.dice_scales,
+            DiceConfig {
+                dice_to_roll,
+                modifier: 0,
+                modifier_name: format!(
+                    "{} (4d6 drop lowest)",
+                    ability_abbreviation(ABILITY_NAMES[button.ability])
+                ),
+                expression,
+            },
+            Some(CharacterScreenRollTarget::CreationScore(button.ability)),
+        );
+    }
+}
+
+/// Handle clicking on spell attack and spell damage roll buttons
+pub fn handle_roll_spell_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&RollSpellButton>,
+    mut params: CharacterSheetRollParams,
+) {
+    if params.settings_state.show_modal {
+        return;
+    }
+
+    if params.ui_state.active_tab != AppTab::CharacterSheet {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = &params.character_data.sheet else {
+            continue;
+        };
+
+        let config = match button.kind {
+            SpellRollKind::Attack => DiceConfig {
+                dice_to_roll: vec![DiceType::D20],
+                modifier: sheet.spell_attack_bonus(),
+                modifier_name: format!("{} Spell Attack", button.spell),
+                expression: None,
+            }
+            .with_condition_effect(
+                &sheet
+                    .conditions
+                    .effect_on(&D20Test::AttackRoll, *params.ruleset),
+            ),
+            SpellRollKind::Damage => {
+                let damage = match sheet.spell_damage_roll(&button.spell) {
+                    Some(Ok(damage)) => damage,
+                    Some(Err(e)) => {
+                        params
+                            .snackbar
+                            .write(ShowSnackbar::message(e).duration(3.0));
+                        continue;
+                    }
+                    None => continue,
+                };
+                let explosion_cap = params.settings_state.settings.explosion_cap();
+                let (dice_to_roll, expression) = DiceConfig::from_expression(
+                    &damage.expression.with_explosion_cap(explosion_cap),
+                );
+                DiceConfig {
+                    dice_to_roll,
+                    modifier: 0,
+                    modifier_name: damage_roll_label(&button.spell, damage.damage_type.as_deref()),
+                    expression,
+                }
+            }
+        };
 
         let use_shake = params.settings_state.settings.default_roll_uses_shake;
 
@@ -902,11 +1187,14 @@ pub fn handle_roll_skill_click(
             &mut params.commands,
             &mut params.meshes,
             &mut params.materials,
+            &mut params.mesh_cache,
+            &mut params.roll_rng,
             &mut params.dice_config,
             &mut params.dice_results,
             &mut params.roll_state,
             &mut params.ui_state,
             &mut params.bridge,
+            &mut params.roll_requested,
             &params.character_manager,
             &params.dice_query,
             &params.container_style,
@@ -916,11 +1204,9 @@ pub fn handle_roll_skill_click(
             &mut params.shake_anim,
             &params.container_query,
             use_shake,
-            die_type,
-            die_scale,
-            modifier,
-            format!("{} Skill", button.skill),
-            CharacterScreenRollTarget::Skill(button.skill.clone()),
+            &params.settings_state.settings.dice_scales,
+            config,
+            None,
         );
 
         params
@@ -929,15 +1215,486 @@ pub fn handle_roll_skill_click(
     }
 }
 
+/// Spend or get back a spell slot from the Spells tab
+pub fn handle_spell_slot_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&SpellSlotButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(spells) = character_data
+            .sheet
+            .as_mut()
+            .and_then(|sheet| sheet.spells.as_mut())
+        else {
+            continue;
+        };
+        if button.expend {
+            if !spells.expend_slot(&button.level) {
+                continue;
+            }
+        } else {
+            spells.restore_slot(&button.level);
+        }
+        character_data.is_modified = true;
+    }
+}
+
+/// Cycle the slot level a spell is cast with when its picker is clicked
+pub fn handle_spell_slot_picker_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&SpellSlotPickerButton>,
+    mut choices: ResMut<SpellSlotChoices>,
+    character_data: Res<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(spells) = character_data
+            .sheet
+            .as_ref()
+            .and_then(|sheet| sheet.spells.as_ref())
+        else {
+            continue;
+        };
+        choices.cycle(&button.spell, spells);
+    }
+}
+
+/// Keep spell slot picker labels in sync with the picked levels and the
+/// slots left (including after the character panel is rebuilt).
+pub fn sync_spell_slot_picker_labels(
+    choices: Res<SpellSlotChoices>,
+    character_data: Res<CharacterData>,
+    mut labels: Query<(Ref<SpellSlotPickerText>, &mut Text)>,
+) {
+    let Some(spells) = character_data
+        .sheet
+        .as_ref()
+        .and_then(|sheet| sheet.spells.as_ref())
+    else {
+        return;
+    };
+    for (label, mut text) in labels.iter_mut() {
+        if !choices.is_changed() && !character_data.is_changed() && !label.is_added() {
+            continue;
+        }
+        let picked = choices.label(&label.spell, spells);
+        if text.0 != picked {
+            text.0 = picked;
+        }
+    }
+}
+
+/// Spend a slot of `level` on a known spell, starting concentration on it when
+/// it needs concentration. Returns the message to show.
+pub fn cast_spell_with_slot(
+    sheet: &mut CharacterSheet,
+    spell: &str,
+    level: u32,
+) -> Result<String, String> {
+    let Some(spells) = sheet.spells.as_mut() else {
+        return Err(format!("No spell slots left for {}", spell));
+    };
+    spells.spend_slot_for(level, spell)?;
+    let message = format!(
+        "{} cast with a level {} slot ({} left)",
+        spell,
+        level,
+        spells.slots_remaining(&level.to_string())
+    );
+    if spells.needs_concentration(spell) {
+        sheet.start_concentration(spell);
+    }
+    Ok(message)
+}
+
+/// Start casting a known spell as a ritual: no slot is spent and the spell
+/// takes effect when the session clock's 10-minute ritual timer runs out.
+/// Starts concentration on it when it needs concentration. Returns the
+/// message to show.
+pub fn cast_spell_as_ritual(
+    sheet: &mut CharacterSheet,
+    clock: &mut SessionClock,
+    spell: &str,
+) -> String {
+    clock.start_ritual(spell);
+    info!("Ritual casting started: {}", spell);
+    if sheet
+        .spells
+        .as_ref()
+        .is_some_and(|spells| spells.needs_concentration(spell))
+    {
+        sheet.start_concentration(spell);
+    }
+    format!("Casting {} as a ritual (10 minutes)", spell)
+}
+
+/// Cast a spell from the Spells tab, spending a slot of the level picked for
+/// it; refused when no slots are left. Casting a concentration spell while
+/// concentrating on another asks first, and the slot is only spent once
+/// confirmed
+pub fn handle_cast_spell_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&CastSpellButton>,
+    choices: Res<SpellSlotChoices>,
+    mut character_data: ResMut<CharacterData>,
+    mut prompt: ResMut<ConcentrationPrompt>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    let character_data = &mut *character_data;
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let Some(spells) = sheet.spells.as_ref() else {
+            continue;
+        };
+        let Some(level) = choices.level_for(&button.spell, spells) else {
+            snackbar.write(
+                ShowSnackbar::message(format!("No spell slots left for {}", button.spell))
+                    .duration(3.0),
+            );
+            continue;
+        };
+
+        if spells.needs_concentration(&button.spell)
+            && sheet.concentration_conflict(&button.spell).is_some()
+        {
+            prompt.pending = Some(button.spell.clone());
+            prompt.slot_level = Some(level);
+            prompt.ritual = false;
+            continue;
+        }
+
+        match cast_spell_with_slot(sheet, &button.spell, level) {
+            Ok(message) => {
+                snackbar.write(ShowSnackbar::message(message).duration(3.0));
+                character_data.is_modified = true;
+            }
+            Err(e) => {
+                snackbar.write(ShowSnackbar::message(e).duration(3.0));
+            }
+        }
+    }
+}
+
+/// Cast a ritual spell as a ritual from the Spells tab, starting its
+/// 10-minute timer on the session clock. A concentration ritual cast while
+/// concentrating on another spell asks first
+pub fn handle_cast_ritual_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&CastRitualButton>,
+    mut character_data: ResMut<CharacterData>,
+    mut prompt: ResMut<ConcentrationPrompt>,
+    mut clock: ResMut<SessionClock>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    let character_data = &mut *character_data;
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let Some(spells) = sheet.spells.as_ref() else {
+            continue;
+        };
+
+        if spells.needs_concentration(&button.spell)
+            && sheet.concentration_conflict(&button.spell).is_some()
+        {
+            prompt.pending = Some(button.spell.clone());
+            prompt.slot_level = None;
+            prompt.ritual = true;
+            continue;
+        }
+
+        let message = cast_spell_as_ritual(sheet, &mut clock, &button.spell);
+        snackbar.write(ShowSnackbar::message(message).duration(3.0));
+        character_data.is_modified = true;
+    }
+}
+
+/// Give a known spell the ritual tag, or take it away
+pub fn handle_ritual_spell_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&RitualSpellButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(spells) = character_data
+            .sheet
+            .as_mut()
+            .and_then(|sheet| sheet.spells.as_mut())
+        else {
+            continue;
+        };
+        spells.toggle_ritual(&button.spell);
+        character_data.is_modified = true;
+    }
+}
+
+/// Mark a known spell as needing concentration, or unmark it
+pub fn handle_concentration_spell_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&ConcentrationSpellButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(spells) = character_data
+            .sheet
+            .as_mut()
+            .and_then(|sheet| sheet.spells.as_mut())
+        else {
+            continue;
+        };
+        spells.toggle_concentration(&button.spell);
+        character_data.is_modified = true;
+    }
+}
+
+/// Toggle whether a known spell is prepared
+pub fn handle_prepared_spell_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&PreparedSpellButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(spells) = character_data
+            .sheet
+            .as_mut()
+            .and_then(|sheet| sheet.spells.as_mut())
+        else {
+            continue;
+        };
+        spells.toggle_prepared(&button.spell);
+        character_data.is_modified = true;
+    }
+}
+
+/// Apply or remove a condition from the Combat tab
+pub fn handle_condition_toggle_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&ConditionToggleButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        sheet.conditions.toggle(button.condition);
+        character_data.is_modified = true;
+    }
+}
+
+/// Give the character a damage rider or take it away from the Combat tab
+pub fn handle_damage_rider_toggle_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&DamageRiderToggleButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let riders = &mut sheet.combat.damage_riders;
+        if riders.contains(&button.rider) {
+            riders.retain(|rider| *rider != button.rider);
+        } else {
+            riders.push(button.rider.clone());
+        }
+        character_data.is_modified = true;
+    }
+}
+
+/// Equip an inventory item, attune to it, or mark it as needing attunement
+/// from the Inventory tab
+pub fn handle_inventory_toggle_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&InventoryToggleButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let slots = sheet.attunement_slots();
+        let Some(equipment) = sheet.equipment.as_mut() else {
+            continue;
+        };
+        if button.toggle == InventoryToggle::Attuned {
+            if let Err(e) = toggle_attunement(&mut equipment.items, &button.item, slots) {
+                snackbar.write(ShowSnackbar::message(e).duration(3.0));
+                continue;
+            }
+        } else {
+            let Some(item) = equipment
+                .items
+                .iter_mut()
+                .find(|item| item.name == button.item)
+            else {
+                continue;
+            };
+            if button.toggle == InventoryToggle::Equipped {
+                item.equipped = !item.equipped;
+            } else {
+                item.requires_attunement = !item.requires_attunement;
+                // Attunement ends with the requirement
+                item.attuned &= item.requires_attunement;
+            }
+        }
+        character_data.is_modified = true;
+    }
+}
+
+/// Add or spend a coin from the Inventory tab; spending one that isn't there
+/// breaks a larger coin
+pub fn handle_coin_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&CoinButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let currency = &mut sheet
+            .equipment
+            .get_or_insert_with(Default::default)
+            .currency;
+        if button.delta > 0 {
+            currency.add(button.coin, button.delta);
+        } else if let Err(e) = currency.spend(button.coin, -button.delta) {
+            snackbar.write(ShowSnackbar::message(e).duration(3.0));
+            continue;
+        }
+        character_data.is_modified = true;
+    }
+}
+
+/// Raise or lower the exhaustion level from the Combat tab
+pub fn handle_exhaustion_click(
+    mut click_events: MessageReader<IconButtonClickEvent>,
+    buttons: Query<&ExhaustionButton>,
+    mut character_data: ResMut<CharacterData>,
+    settings_state: Res<SettingsState>,
+) {
+    if settings_state.show_modal {
+        return;
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let Some(sheet) = character_data.sheet.as_mut() else {
+            continue;
+        };
+        let level = sheet.conditions.exhaustion as i32 + button.delta;
+        sheet.conditions.set_exhaustion(level);
+        character_data.is_modified = true;
+    }
+}
+
 fn start_character_sheet_roll(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    mesh_cache: &mut DiceMeshCache,
+    roll_rng: &mut RollRng,
     dice_config: &mut ResMut<DiceConfig>,
     dice_results: &mut ResMut<DiceResults>,
     roll_state: &mut ResMut<RollState>,
     ui_state: &mut ResMut<UiState>,
     bridge: &mut ResMut<CharacterScreenRollBridge>,
+    roll_requested: &mut MessageWriter<RollRequested>,
     character_manager: &CharacterManager,
     dice_query: &Query<Entity, With<Die>>,
     container_style: &DiceContainerStyle,
@@ -947,35 +1704,33 @@ fn start_character_sheet_roll(
     shake_anim: &mut ResMut<ContainerShakeAnimation>,
     container_query: &Query<(Entity, &Transform), With<DiceBox>>,
     use_shake: bool,
-    die_type: DiceType,
-    die_scale: f32,
-    modifier: i32,
-    modifier_name: String,
-    target: CharacterScreenRollTarget,
+    dice_scales: &DiceScaleSettings,
+    config: DiceConfig,
+    target: Option<CharacterScreenRollTarget>,
 ) {
     // Bridge: remember what to write back (do this even if the roll is gated behind the lid).
-    bridge.pending = Some(target);
+    bridge.pending = target;
     bridge.last_character_id = character_manager.current_character_id;
 
     // Switch to dice roller so the user can see the roll.
     ui_state.active_tab = AppTab::DiceRoller;
 
+    roll_requested.write(RollRequested {
+        config: config.clone(),
+        source: RollSource::CharacterSheet,
+        command: None,
+    });
+
     // Box style: queue the roll and let the lid controller close first.
     if *container_style == DiceContainerStyle::Box {
         if lid_ctrl.pending_roll.is_none() {
-            lid_ctrl.pending_roll = Some(PendingRollRequest::StartNewRoll {
-                config: DiceConfig {
-                    dice_to_roll: vec![die_type],
-                    modifier,
-                    modifier_name,
-                },
-            });
-
             #[cfg(debug_assertions)]
             info!(
-                "Queued pending_roll: StartNewRoll(die={:?}, mod={}) (character sheet)",
-                die_type, modifier
+                "Queued pending_roll: StartNewRoll(dice={:?}, mod={}) (character sheet)",
+                config.dice_to_roll, config.modifier
             );
+
+            lid_ctrl.pending_roll = Some(PendingRollRequest::StartNewRoll { config });
         }
         return;
     }
@@ -986,24 +1741,34 @@ fn start_character_sheet_roll(
     }
 
     // Update config
-    dice_config.dice_to_roll.clear();
-    dice_config.dice_to_roll.push(die_type);
-    dice_config.modifier = modifier;
-    dice_config.modifier_name = modifier_name;
-    dice_results.results.clear();
+    **dice_config = config;
+    dice_results.clear();
 
     // Spawn new dice
-    let position = super::super::calculate_dice_position(0, 1);
-    let die_entity =
-        super::super::spawn_die(commands, meshes, materials, die_type, die_scale, position);
+    let physical_dice = dice_config.physical_dice();
+    for (i, die_type) in physical_dice.iter().enumerate() {
+        let position = super::super::calculate_dice_position(i, physical_dice.len());
+        let die_entity = super::super::spawn_die(
+            commands,
+            meshes,
+            materials,
+            mesh_cache,
+            *die_type,
+            dice_scales.scale_for(*die_type),
+            position,
+            roll_rng,
+        );
 
-    if use_shake {
-        // No initial impulse; the shake animation will provide motion.
-        commands.entity(die_entity).insert(Velocity {
-            linvel: Vec3::ZERO,
-            angvel: Vec3::ZERO,
-        });
+        if use_shake {
+            // No initial impulse; the shake animation will provide motion.
+            commands.entity(die_entity).insert(Velocity {
+                linvel: Vec3::ZERO,
+                angvel: Vec3::ZERO,
+            });
+        }
+    }
 
+    if use_shake {
         let _started =
             start_container_shake(shake_state, shake_config, shake_anim, container_query);
     }
@@ -1014,23 +1779,15 @@ fn start_character_sheet_roll(
 
 /// When a dice roll finishes, record the final total for any pending character-screen roll.
 pub fn record_character_screen_roll_on_settle(
-    roll_state: Res<RollState>,
-    dice_results: Res<DiceResults>,
+    mut resolved_events: MessageReader<RollResolved>,
     mut character_data: ResMut<CharacterData>,
     character_manager: Res<CharacterManager>,
     mut bridge: ResMut<CharacterScreenRollBridge>,
-    mut was_rolling: Local<bool>,
+    mut wizard: ResMut<CharacterCreationWizard>,
 ) {
-    let finished_this_frame = *was_rolling && !roll_state.rolling;
-    *was_rolling = roll_state.rolling;
-
-    if !finished_this_frame {
-        return;
-    }
-
-    if dice_results.results.is_empty() {
+    let Some(resolved) = resolved_events.read().last() else {
         return;
-    }
+    };
 
     let Some(target) = bridge.pending.clone() else {
         return;
@@ -1042,7 +1799,7 @@ pub fn record_character_screen_roll_on_settle(
         return;
     }
 
-    let dice_total: i32 = dice_results.results.iter().map(|(_, v)| *v as i32).sum();
+    let dice_total = resolved.dice_total;
 
     match target {
         CharacterScreenRollTarget::Attribute(attr) => {
@@ -1108,6 +1865,23 @@ pub fn record_character_screen_roll_on_settle(
             // Keep the roll-result text in sync (store dice-only total).
             bridge.last_skill_totals.insert(skill, dice_total);
         }
+        CharacterScreenRollTarget::CreationScore(ability) => {
+            let faces: Vec<u32> = resolved
+                .results
+                .iter()
+                .filter(|(die, _)| *die == DiceType::D6)
+                .map(|(_, face)| *face)
+                .collect();
+            match <[u32; 4]>::try_from(faces) {
+                Ok(dice) => wizard.scores.record_roll(ability, dice),
+                // Fall back to the kept total when the faces didn't come through
+                Err(_) => {
+                    wizard.scores.rolls[ability] = None;
+                    wizard.scores.scores[ability] = Some(dice_total);
+                }
+            }
+            wizard.message = None;
+        }
     }
 
     bridge.pending = None;
@@ -1230,6 +2004,7 @@ fn spawn_character_sheet_settings_modal(
     let scrim_entity = commands
         .spawn((
             create_dialog_scrim_for(theme, dialog_entity, true),
+            FocusTrap,
             CharacterSheetSettingsModalOverlay,
         ))
         .id();
@@ -1581,6 +2356,7 @@ fn get_field_value(character_data: &CharacterData, field: &EditingField) -> Stri
             .map(|hp| hp.maximum.to_string())
             .unwrap_or_default(),
         EditingField::ProficiencyBonus => format!("+{}", sheet.proficiency_bonus),
+        EditingField::ExtraAttacks => sheet.combat.extra_attacks.to_string(),
         EditingField::Skill(name) => sheet
             .skills
             .get(name)
@@ -1609,9 +2385,55 @@ fn get_field_value(character_data: &CharacterData, field: &EditingField) -> Stri
         | EditingField::CustomBasicInfoLabel(name)
         | EditingField::CustomAttributeLabel(name)
         | EditingField::CustomCombatLabel(name) => name.clone(),
+        EditingField::SpellcastingAbility => sheet
+            .spells
+            .as_ref()
+            .and_then(|spells| spells.spellcasting_ability.clone())
+            .unwrap_or_default(),
+        EditingField::SpellSaveDc => sheet.sheet_spell_save_dc().to_string(),
+        EditingField::SpellAttackBonus => format_modifier(sheet.sheet_spell_attack_bonus()),
+        EditingField::SpellSlots(level) => sheet
+            .spells
+            .as_ref()
+            .and_then(|spells| spells.spell_slots.get(level))
+            .copied()
+            .unwrap_or(0)
+            .to_string(),
+        EditingField::SpellDamage(spell) => sheet
+            .spells
+            .as_ref()
+            .and_then(|spells| spells.spell_damage.get(spell).cloned())
+            .unwrap_or_default(),
+        EditingField::ItemQuantity(name) => inventory_item(sheet, name)
+            .map(|item| item.quantity.to_string())
+            .unwrap_or_default(),
+        EditingField::ItemWeight(name) => inventory_item(sheet, name)
+            .map(|item| item.weight.to_string())
+            .unwrap_or_default(),
+        EditingField::ItemValue(name) => inventory_item(sheet, name)
+            .map(|item| item.value.to_string())
+            .unwrap_or_default(),
+        EditingField::ItemBonuses(name) => inventory_item(sheet, name)
+            .map(|item| format_item_bonuses(&item.bonuses))
+            .unwrap_or_default(),
+        EditingField::AttunementSlots => sheet.attunement_slots().to_string(),
+        EditingField::Coins(coin) => sheet
+            .equipment
+            .as_ref()
+            .map(|equipment| equipment.currency.get(*coin))
+            .unwrap_or(0)
+            .to_string(),
     }
 }
 
+/// The inventory item with this name
+fn inventory_item<'a>(sheet: &'a CharacterSheet, name: &str) -> Option<&'a InventoryItem> {
+    sheet
+        .equipment
+        .as_ref()
+        .and_then(|equipment| equipment.items.iter().find(|item| item.name == name))
+}
+
 /// Apply a new value to a field
 fn apply_field_value(
     character_data: &mut CharacterData,
@@ -1706,6 +2528,11 @@ fn apply_field_value(
                     sheet.proficiency_bonus = v;
                 }
             }
+            EditingField::ExtraAttacks => {
+                if let Ok(v) = value.parse() {
+                    sheet.combat.extra_attacks = v;
+                }
+            }
             EditingField::Skill(name) => {
                 if let Some(skill) = sheet.skills.get_mut(name) {
                     if let Ok(v) = parse_modifier(value) {
@@ -1733,6 +2560,95 @@ fn apply_field_value(
             EditingField::CustomCombat(name) => {
                 sheet.custom_combat.insert(name.clone(), value.to_string());
             }
+            EditingField::SpellcastingAbility => {
+                let spells = sheet.spells.get_or_insert_with(Default::default);
+                spells.spellcasting_ability = (!value.is_empty()).then(|| value.to_string());
+            }
+            EditingField::SpellSaveDc => {
+                if let Ok(v) = value.parse() {
+                    sheet
+                        .spells
+                        .get_or_insert_with(Default::default)
+                        .spell_save_dc = Some(v);
+                }
+            }
+            EditingField::SpellAttackBonus => {
+                if let Ok(v) = parse_modifier(value) {
+                    sheet
+                        .spells
+                        .get_or_insert_with(Default::default)
+                        .spell_attack_bonus = Some(v);
+                }
+            }
+            EditingField::SpellSlots(level) => {
+                if let Ok(v) = value.parse::<i32>() {
+                    let spells = sheet.spells.get_or_insert_with(Default::default);
+                    if v > 0 {
+                        spells.spell_slots.insert(level.clone(), v);
+                    } else {
+                        spells.spell_slots.remove(level);
+                        spells.used_slots.remove(level);
+                    }
+                }
+            }
+            EditingField::SpellDamage(spell) => {
+                let spells = sheet.spells.get_or_insert_with(Default::default);
+                if value.is_empty() {
+                    spells.spell_damage.remove(spell);
+                } else {
+                    spells.spell_damage.insert(spell.clone(), value.to_string());
+                }
+            }
+            EditingField::ItemQuantity(name)
+            | EditingField::ItemWeight(name)
+            | EditingField::ItemValue(name)
+            | EditingField::ItemBonuses(name) => {
+                let item = sheet
+                    .equipment
+                    .as_mut()
+                    .and_then(|equipment| equipment.items.iter_mut().find(|i| i.name == *name));
+                if let Some(item) = item {
+                    match field {
+                        EditingField::ItemQuantity(_) => {
+                            if let Ok(v) = value.parse() {
+                                item.quantity = v;
+                            }
+                        }
+                        EditingField::ItemWeight(_) => {
+                            if let Ok(v) = value.parse::<f32>() {
+                                item.weight = v.max(0.0);
+                            }
+                        }
+                        EditingField::ItemBonuses(_) => {
+                            if let Ok(bonuses) = parse_item_bonuses(value) {
+                                item.bonuses = bonuses;
+                            }
+                        }
+                        _ => {
+                            if let Ok(v) = value.parse::<f32>() {
+                                item.value = v.max(0.0);
+                            }
+                        }
+                    }
+                }
+            }
+            EditingField::Coins(coin) => {
+                if let Ok(v) = value.parse() {
+                    sheet
+                        .equipment
+                        .get_or_insert_with(Default::default)
+                        .currency
+                        .set(*coin, v);
+                }
+            }
+            EditingField::AttunementSlots => {
+                if let Ok(v) = value.parse::<u32>() {
+                    sheet
+                        .equipment
+                        .get_or_insert_with(Default::default)
+                        .attunement_slots = (v as usize != DEFAULT_ATTUNEMENT_SLOTS).then_some(v);
+                }
+            }
             _ => {} // Label fields handled separately
         }
     }
@@ -1798,7 +2714,13 @@ fn flush_markdown_buffer(
             3 => 16.0,
             _ => 14.0,
         };
-        spawn_rich_text_line(parent, text, size, MD3_ON_SURFACE, Some(icon_font.clone()));
+        spawn_rich_text_line(
+            parent,
+            text,
+            size,
+            ThemeToken::OnSurface,
+            Some(icon_font.clone()),
+        );
         state.buffer.clear();
         return;
     }
@@ -1817,7 +2739,7 @@ fn flush_markdown_buffer(
                     row,
                     &format!("• {}", text),
                     14.0,
-                    MD3_ON_SURFACE_VARIANT,
+                    ThemeToken::OnSurfaceVariant,
                     Some(icon_font.clone()),
                 );
             });
@@ -1830,7 +2752,7 @@ fn flush_markdown_buffer(
         parent,
         text,
         14.0,
-        MD3_ON_SURFACE_VARIANT,
+        ThemeToken::OnSurfaceVariant,
         Some(icon_font.clone()),
     );
     state.buffer.clear();
@@ -1850,7 +2772,7 @@ fn flush_code_block(parent: &mut ChildSpawnerCommands, state: &mut MarkdownRende
                 padding: UiRect::all(Val::Px(10.0)),
                 ..default()
             },
-            BackgroundColor(MD3_SURFACE_CONTAINER),
+            ThemeToken::SurfaceContainer.background(),
             BorderRadius::all(Val::Px(8.0)),
         ))
         .with_children(|code| {
@@ -1860,7 +2782,7 @@ fn flush_code_block(parent: &mut ChildSpawnerCommands, state: &mut MarkdownRende
                     font_size: 13.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE),
+                ThemeToken::OnSurface.text(),
             ));
         });
 
@@ -1871,7 +2793,7 @@ fn spawn_rich_text_line(
     parent: &mut ChildSpawnerCommands,
     text: &str,
     font_size: f32,
-    color: Color,
+    token: ThemeToken,
     icon_font: Option<Handle<Font>>,
 ) {
     // Inline icon syntax: :icon(name):
@@ -1883,7 +2805,7 @@ fn spawn_rich_text_line(
                 font_size,
                 ..default()
             },
-            TextColor(color),
+            token.text(),
         ));
         return;
     }
@@ -1910,7 +2832,7 @@ fn spawn_rich_text_line(
                             font_size,
                             ..default()
                         },
-                        TextColor(color),
+                        token.text(),
                     ));
                 }
 
@@ -1923,7 +2845,7 @@ fn spawn_rich_text_line(
                             font_size,
                             ..default()
                         },
-                        TextColor(color),
+                        token.text(),
                     ));
                     return;
                 };
@@ -1939,7 +2861,7 @@ fn spawn_rich_text_line(
                         font_size,
                         ..default()
                     },
-                    TextColor(color),
+                    token.text(),
                 ));
 
                 rest = &after_start[end + "):".len()..];
@@ -1952,7 +2874,7 @@ fn spawn_rich_text_line(
                         font_size,
                         ..default()
                     },
-                    TextColor(color),
+                    token.text(),
                 ));
             }
         });
@@ -2078,7 +3000,7 @@ pub fn setup_dnd_info_screen(mut commands: Commands, icon_font: Res<MaterialIcon
                 overflow: Overflow::clip_y(),
                 ..default()
             },
-            BackgroundColor(MD3_SURFACE),
+            ThemeToken::Surface.background(),
             Visibility::Hidden,
             DndInfoScreenRoot,
         ))
@@ -2113,6 +3035,15 @@ pub fn init_character_manager(mut commands: Commands) {
         }
     };
 
+    // Bring in characters edited on other machines (encrypted databases
+    // aren't synced)
+    if CloudSyncConfig::load(&db).is_some() && !db.encryption_enabled() {
+        match db.sync_characters() {
+            Ok(summary) => info!("Cloud sync: {}", summary),
+            Err(e) => warn!("Cloud sync failed: {}", e),
+        }
+    }
+
     // Get character list from database
     let characters = db.list_characters().unwrap_or_default();
 
@@ -0,0 +1,348 @@
+//! Legacy `dnd_stats.json` migration assistant for the Character Sheet.
+//!
+//! On first run, `dnd_stats.json` files in the working directory or the
+//! Documents folder are listed in a modal dialog with a preview of each
+//! character. "Import" adds them to the database (characters whose name is
+//! already taken are left out), "Not now" asks again next time and "Don't
+//! ask again" forgets about the files.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    find_legacy_json_files, legacy_json_search_dirs, CharacterDatabase, CharacterManager,
+    FocusTrap, LegacyJsonCandidate, LegacyJsonMigration, LEGACY_JSON_SETTING_KEY,
+};
+
+use super::SqliteConversionState;
+
+#[derive(Component)]
+pub struct LegacyJsonMigrationOverlay;
+
+#[derive(Component)]
+pub struct LegacyJsonImportButton;
+
+#[derive(Component)]
+pub struct LegacyJsonNotNowButton;
+
+#[derive(Component)]
+pub struct LegacyJsonDismissButton;
+
+/// A legacy file offered for import.
+#[derive(Debug, Clone)]
+pub struct LegacyJsonEntry {
+    pub candidate: LegacyJsonCandidate,
+    /// A character with the same name is already in the database
+    pub duplicate: bool,
+}
+
+impl LegacyJsonEntry {
+    fn importable(&self) -> bool {
+        self.candidate.sheet.is_ok() && !self.duplicate
+    }
+}
+
+/// Legacy files waiting for an answer; the dialog is open while there are any.
+#[derive(Resource, Default)]
+pub struct LegacyJsonMigrationState {
+    pub entries: Vec<LegacyJsonEntry>,
+}
+
+fn load_migration(db: &CharacterDatabase) -> LegacyJsonMigration {
+    db.get_setting::<LegacyJsonMigration>(LEGACY_JSON_SETTING_KEY)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Look for legacy files once per run, after the database is unlocked and
+/// any SQLite conversion is done.
+pub fn start_legacy_json_migration_if_needed(
+    db: Option<Res<CharacterDatabase>>,
+    sqlite_conversion: Option<Res<SqliteConversionState>>,
+    mut state: ResMut<LegacyJsonMigrationState>,
+    mut checked: Local<bool>,
+) {
+    if *checked || sqlite_conversion.is_some() {
+        return;
+    }
+    let Some(db) = db else {
+        return;
+    };
+    if db.is_locked() {
+        return;
+    }
+    *checked = true;
+
+    let migration = load_migration(&db);
+    let entries: Vec<LegacyJsonEntry> = find_legacy_json_files(&legacy_json_search_dirs())
+        .iter()
+        .filter(|path| !migration.is_handled(path))
+        .map(|path| {
+            let candidate = LegacyJsonCandidate::load(path);
+            let duplicate = candidate
+                .sheet
+                .as_ref()
+                .is_ok_and(|sheet| db.name_exists(&sheet.character.name, None).unwrap_or(false));
+            LegacyJsonEntry {
+                candidate,
+                duplicate,
+            }
+        })
+        .collect();
+
+    if !entries.is_empty() {
+        info!("Found {} legacy dnd_stats.json file(s)", entries.len());
+        state.entries = entries;
+    }
+}
+
+fn spawn_text(parent: &mut ChildSpawnerCommands, text: &str, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Show/hide and rebuild the migration assistant.
+pub fn manage_legacy_json_migration_dialog(
+    mut commands: Commands,
+    state: Res<LegacyJsonMigrationState>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<LegacyJsonMigrationOverlay>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if state.entries.is_empty() {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let dialog = MaterialDialog::new()
+        .title("Import Characters")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(600.0),
+                max_height: Val::Percent(80.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            LegacyJsonMigrationOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    let importable = state.entries.iter().filter(|e| e.importable()).count();
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        spawn_text(
+            dialog,
+            "Characters from dnd_stats.json",
+            20.0,
+            theme.on_surface,
+        );
+        spawn_text(
+            dialog,
+            "Characters are now kept in the app's database. These files from the \
+             file-based version can be imported; the files themselves are left as they are.",
+            13.0,
+            theme.on_surface_variant,
+        );
+
+        dialog
+            .spawn((
+                ScrollContainer::vertical(),
+                ScrollPosition::default(),
+                Node {
+                    width: Val::Percent(100.0),
+                    min_height: Val::Px(0.0),
+                    flex_shrink: 1.0,
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+            ))
+            .with_children(|scroll| {
+                scroll
+                    .spawn(Node {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(10.0),
+                        padding: UiRect::right(Val::Px(18.0)),
+                        ..default()
+                    })
+                    .with_children(|list| {
+                        for entry in &state.entries {
+                            let path = entry.candidate.path.display().to_string();
+                            spawn_text(list, &path, 12.0, theme.on_surface_variant);
+                            let (note, color) = match &entry.candidate.sheet {
+                                Err(_) => (entry.candidate.preview(), theme.error),
+                                Ok(_) if entry.duplicate => (
+                                    format!(
+                                        "{} (already in the database, not imported)",
+                                        entry.candidate.preview()
+                                    ),
+                                    theme.on_surface_variant,
+                                ),
+                                Ok(_) => (entry.candidate.preview(), theme.on_surface),
+                            };
+                            spawn_text(list, &note, 14.0, color);
+                        }
+                    });
+            });
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_button(
+                    buttons,
+                    "Don't ask again",
+                    false,
+                    LegacyJsonDismissButton,
+                    &theme,
+                );
+                spawn_button(buttons, "Not now", false, LegacyJsonNotNowButton, &theme);
+                if importable > 0 {
+                    spawn_button(buttons, "Import", true, LegacyJsonImportButton, &theme);
+                }
+            });
+    });
+}
+
+/// Import, postpone or dismiss the listed files.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_legacy_json_migration_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    import_buttons: Query<(), With<LegacyJsonImportButton>>,
+    not_now_buttons: Query<(), With<LegacyJsonNotNowButton>>,
+    dismiss_buttons: Query<(), With<LegacyJsonDismissButton>>,
+    mut state: ResMut<LegacyJsonMigrationState>,
+    db: Option<Res<CharacterDatabase>>,
+    mut character_manager: Option<ResMut<CharacterManager>>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        let import = if import_buttons.get(event.entity).is_ok() {
+            true
+        } else if dismiss_buttons.get(event.entity).is_ok() {
+            false
+        } else {
+            if not_now_buttons.get(event.entity).is_ok() {
+                state.entries.clear();
+            }
+            continue;
+        };
+
+        let Some(db) = db.as_ref() else {
+            continue;
+        };
+        let entries = std::mem::take(&mut state.entries);
+        let mut migration = load_migration(db);
+
+        // Files that failed to import are offered again next time
+        let mut failed = Vec::new();
+        if import {
+            let mut imported = 0;
+            for entry in entries.iter().filter(|e| e.importable()) {
+                let Ok(sheet) = &entry.candidate.sheet else {
+                    continue;
+                };
+                match db.create_character(sheet) {
+                    Ok(_) => imported += 1,
+                    Err(e) => {
+                        warn!("Failed to import {}: {}", entry.candidate.path.display(), e);
+                        failed.push(entry.candidate.path.clone());
+                    }
+                }
+            }
+
+            if let Some(character_manager) = character_manager.as_mut() {
+                character_manager.refresh_from_database(db);
+                character_manager.list_version += 1;
+            }
+            let message = if failed.is_empty() {
+                format!("Imported {} character(s)", imported)
+            } else {
+                format!(
+                    "Imported {} character(s), {} failed",
+                    imported,
+                    failed.len()
+                )
+            };
+            snackbar.write(ShowSnackbar::message(message).duration(3.0));
+        }
+
+        for entry in entries
+            .iter()
+            .filter(|e| !failed.contains(&e.candidate.path))
+        {
+            migration.mark_handled(&entry.candidate.path);
+        }
+        if let Err(e) = db.set_setting(LEGACY_JSON_SETTING_KEY, migration) {
+            warn!("Failed to save the legacy JSON migration: {}", e);
+        }
+    }
+}
@@ -7,6 +7,11 @@
 //! - `mod.rs` - Main module with shared constants, types, and re-exports
 //! - `tab_bar.rs` - App-level tab bar (Dice Roller, Character, DnD Info, Contributors)
 //! - `character_list.rs` - Character list panel (left side)
+//! - `bulk_edit.rs` - Bulk edit grid of skill and save proficiencies
+//! - `concentration.rs` - Concentration chip and spell conflict prompt
+//! - `json_migration.rs` - First-run import of legacy `dnd_stats.json` files
+//! - `creation_wizard.rs` - New character wizard (race, class, abilities,
+//!   background, skills, equipment, spells)
 //! - `tabs/` - Character sheet content tabs
 //!   - `mod.rs` - MaterialTabs container and content switching
 //!   - `basic_info.rs` - Basic character info (name, class, race, etc.)
@@ -14,74 +19,38 @@
 //!   - `combat.rs` - Combat stats (HP, AC, initiative, etc.)
 //!   - `saving_throws.rs` - Saving throw proficiencies
 //!   - `skills.rs` - Skills and proficiencies
+//!   - `spells.rs` - Spell slots, known/prepared spells and spell rolls
+//!   - `inventory.rs` - Items, attunement, carried weight and encumbrance
 //! - `components.rs` - Shared UI components (stat fields, group headers, etc.)
 //! - `handlers.rs` - Input and event handlers
 
 use bevy::prelude::*;
+use std::collections::HashMap;
 
 // Submodules
+mod bulk_edit;
 mod character_list;
 mod components;
+mod concentration;
 mod conversion_dialog;
+mod creation_wizard;
 mod handlers;
+mod json_migration;
 mod tab_bar;
 pub mod tabs;
 
 // Re-export submodule contents
+pub use bulk_edit::*;
 pub use character_list::*;
 pub use components::*;
+pub use concentration::*;
 pub use conversion_dialog::*;
+pub use creation_wizard::*;
 pub use handlers::*;
+pub use json_migration::*;
 pub use tab_bar::*;
 pub use tabs::*;
 
-// ============================================================================
-// Material Design 3 Theme Colors
-// ============================================================================
-
-/// Primary color for accents and active states
-pub const MD3_PRIMARY: Color = Color::srgb(0.4, 0.6, 0.9);
-/// On-primary color for text/icons on primary backgrounds
-pub const MD3_ON_PRIMARY: Color = Color::WHITE;
-/// Surface color for cards and panels
-pub const MD3_SURFACE: Color = Color::srgb(0.11, 0.11, 0.14);
-/// Surface container for nested surfaces
-pub const MD3_SURFACE_CONTAINER: Color = Color::srgb(0.14, 0.14, 0.18);
-/// Surface container high for elevated surfaces
-pub const MD3_SURFACE_CONTAINER_HIGH: Color = Color::srgb(0.17, 0.17, 0.22);
-/// On-surface for primary text
-pub const MD3_ON_SURFACE: Color = Color::WHITE;
-/// On-surface-variant for secondary text
-pub const MD3_ON_SURFACE_VARIANT: Color = Color::srgb(0.7, 0.7, 0.75);
-/// Outline color for borders
-pub const MD3_OUTLINE: Color = Color::srgb(0.3, 0.3, 0.35);
-/// Outline variant for subtle borders
-pub const MD3_OUTLINE_VARIANT: Color = Color::srgb(0.2, 0.2, 0.25);
-/// Secondary color
-pub const MD3_SECONDARY: Color = Color::srgb(0.6, 0.7, 0.85);
-/// Tertiary color for accents
-pub const MD3_TERTIARY: Color = Color::srgb(0.5, 0.8, 0.7);
-/// Error color
-pub const MD3_ERROR: Color = Color::srgb(0.9, 0.3, 0.3);
-/// Success/proficient color
-pub const MD3_SUCCESS: Color = Color::srgb(0.3, 0.8, 0.4);
-/// Warning/modified color
-pub const MD3_WARNING: Color = Color::srgb(0.9, 0.7, 0.3);
-
-// Legacy color aliases for gradual migration
-pub const TAB_ACTIVE_BG: Color = MD3_PRIMARY;
-pub const TAB_INACTIVE_BG: Color = MD3_SURFACE_CONTAINER;
-pub const TAB_HOVER_BG: Color = Color::srgb(0.25, 0.35, 0.55);
-pub const PANEL_BG: Color = MD3_SURFACE;
-pub const GROUP_BG: Color = MD3_SURFACE_CONTAINER;
-pub const FIELD_BG: Color = Color::srgb(0.08, 0.08, 0.12);
-pub const FIELD_MODIFIED_BG: Color = Color::srgb(0.2, 0.15, 0.08);
-pub const BUTTON_BG: Color = Color::srgb(0.2, 0.5, 0.3);
-pub const TEXT_PRIMARY: Color = MD3_ON_SURFACE;
-pub const TEXT_SECONDARY: Color = MD3_ON_SURFACE_VARIANT;
-pub const TEXT_MUTED: Color = Color::srgb(0.5, 0.5, 0.5);
-pub const PROFICIENT_COLOR: Color = MD3_SUCCESS;
-
 // Icon constants (Unicode fallbacks)
 pub const ICON_EDIT: &str = "✎";
 pub const ICON_CHECK: &str = "✓";
@@ -106,6 +75,8 @@ pub enum CharacterSheetTab {
     Combat,
     SavingThrows,
     Skills,
+    Spells,
+    Inventory,
 }
 
 impl CharacterSheetTab {
@@ -117,6 +88,8 @@ impl CharacterSheetTab {
             Self::Combat => "Combat",
             Self::SavingThrows => "Saves",
             Self::Skills => "Skills",
+            Self::Spells => "Spells",
+            Self::Inventory => "Inventory",
         }
     }
 
@@ -128,6 +101,8 @@ impl CharacterSheetTab {
             Self::Combat,
             Self::SavingThrows,
             Self::Skills,
+            Self::Spells,
+            Self::Inventory,
         ]
     }
 
@@ -139,6 +114,8 @@ impl CharacterSheetTab {
             Self::Combat => "shield",
             Self::SavingThrows => "security",
             Self::Skills => "psychology",
+            Self::Spells => "auto_fix_high",
+            Self::Inventory => "backpack",
         }
     }
 }
@@ -167,4 +144,7 @@ pub struct CharacterSheetTabText;
 #[derive(Resource, Default)]
 pub struct SelectedCharacterSheetTab {
     pub current: CharacterSheetTab,
+    /// Scroll offset (the content's `top`) each tab was left at, restored
+    /// when switching back to it
+    pub scroll_offsets: HashMap<CharacterSheetTab, f32>,
 }
@@ -1,4 +1,5 @@
-//! App-level tab bar (Dice Roller, Character, DnD Info, Contributors)
+//! App-level tab bar (Dice Roller, Character, Initiative, Party, DnD Info,
+//! Contributors, then any tabs added by extensions)
 //!
 //! This module handles the main navigation tabs at the top of the application
 //! using bevy_material_ui's MaterialTabs and MaterialTab components.
@@ -13,24 +14,39 @@ use crate::dice3d::types::*;
 // Tab Bar Setup
 // ============================================================================
 
+/// Number of built-in tabs; extension tabs follow them
+const BUILT_IN_TAB_COUNT: usize = 6;
+
 /// Get the AppTab for a given index
-fn app_tab_from_index(index: usize) -> AppTab {
+fn app_tab_from_index(index: usize, extension_tabs: &ExtensionTabs) -> AppTab {
     match index {
         0 => AppTab::DiceRoller,
         1 => AppTab::CharacterSheet,
-        2 => AppTab::DndInfo,
-        3 => AppTab::Contributors,
-        _ => AppTab::DiceRoller,
+        2 => AppTab::Initiative,
+        3 => AppTab::Party,
+        4 => AppTab::DndInfo,
+        5 => AppTab::Contributors,
+        _ => extension_tabs
+            .tabs
+            .get(index - BUILT_IN_TAB_COUNT)
+            .map(|tab| AppTab::Extension(tab.id))
+            .unwrap_or(AppTab::DiceRoller),
     }
 }
 
 /// Get the index for a given AppTab
-fn index_from_app_tab(tab: AppTab) -> usize {
+fn index_from_app_tab(tab: AppTab, extension_tabs: &ExtensionTabs) -> usize {
     match tab {
         AppTab::DiceRoller => 0,
         AppTab::CharacterSheet => 1,
-        AppTab::DndInfo => 2,
-        AppTab::Contributors => 3,
+        AppTab::Initiative => 2,
+        AppTab::Party => 3,
+        AppTab::DndInfo => 4,
+        AppTab::Contributors => 5,
+        AppTab::Extension(id) => extension_tabs
+            .position(id)
+            .map(|position| BUILT_IN_TAB_COUNT + position)
+            .unwrap_or(0),
     }
 }
 
@@ -39,8 +55,10 @@ pub fn setup_tab_bar(
     mut commands: Commands,
     icon_assets: Res<IconAssets>,
     theme: Option<Res<MaterialTheme>>,
+    extension_tabs: Option<Res<ExtensionTabs>>,
 ) {
     let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let extension_tabs = extension_tabs.map(|t| t.clone()).unwrap_or_default();
 
     commands
         .spawn((
@@ -57,7 +75,7 @@ pub fn setup_tab_bar(
                 align_items: AlignItems::Stretch,
                 ..default()
             },
-            BackgroundColor(MD3_SURFACE),
+            ThemeToken::Surface.background(),
             ZIndex(100),
             TabBar,
             AppTabBar, // Marker to identify this as the app-level tab bar
@@ -71,6 +89,7 @@ pub fn setup_tab_bar(
                 "Dice Roller",
                 IconType::Dice,
                 0,
+                app_tab_from_index(0, &extension_tabs),
                 true,
             );
             // Character Sheet Tab
@@ -81,6 +100,29 @@ pub fn setup_tab_bar(
                 "Character",
                 IconType::Character,
                 1,
+                app_tab_from_index(1, &extension_tabs),
+                false,
+            );
+            // Initiative Tab
+            spawn_app_tab(
+                parent,
+                &icon_assets,
+                &theme,
+                "Initiative",
+                IconType::Check,
+                2,
+                app_tab_from_index(2, &extension_tabs),
+                false,
+            );
+            // Party Tab
+            spawn_app_tab(
+                parent,
+                &icon_assets,
+                &theme,
+                "Party",
+                IconType::Roll,
+                3,
+                app_tab_from_index(3, &extension_tabs),
                 false,
             );
             // DnD Info Tab
@@ -90,7 +132,8 @@ pub fn setup_tab_bar(
                 &theme,
                 "DnD Info",
                 IconType::Info,
-                2,
+                4,
+                app_tab_from_index(4, &extension_tabs),
                 false,
             );
             // Contributors Tab
@@ -100,9 +143,23 @@ pub fn setup_tab_bar(
                 &theme,
                 "Contributors",
                 IconType::Character,
-                3,
+                5,
+                app_tab_from_index(5, &extension_tabs),
                 false,
             );
+            // Extension tabs
+            for (i, tab) in extension_tabs.tabs.iter().enumerate() {
+                spawn_app_tab(
+                    parent,
+                    &icon_assets,
+                    &theme,
+                    &tab.label,
+                    tab.icon,
+                    BUILT_IN_TAB_COUNT + i,
+                    AppTab::Extension(tab.id),
+                    false,
+                );
+            }
         });
 }
 
@@ -113,10 +170,10 @@ fn spawn_app_tab(
     label: &str,
     icon_type: IconType,
     index: usize,
+    tab: AppTab,
     is_selected: bool,
 ) {
     let icon_handle = icon_assets.icons.get(&icon_type).cloned();
-    let tab = app_tab_from_index(index);
 
     parent
         .spawn((
@@ -162,6 +219,18 @@ fn spawn_app_tab(
         });
 }
 
+/// Run condition that is true once, the first time `tab` becomes the active
+/// tab, so a tab's screen can be spawned on first use instead of at startup.
+pub fn app_tab_first_opened(tab: AppTab) -> impl FnMut(Res<UiState>, Local<bool>) -> bool + Clone {
+    move |ui_state: Res<UiState>, mut opened: Local<bool>| {
+        if *opened || ui_state.active_tab != tab {
+            return false;
+        }
+        *opened = true;
+        true
+    }
+}
+
 // ============================================================================
 // Tab Change Event Handling
 // ============================================================================
@@ -170,12 +239,12 @@ fn spawn_app_tab(
 pub fn handle_tab_clicks(
     mut tab_events: MessageReader<TabChangeEvent>,
     mut ui_state: ResMut<UiState>,
-    app_tab_query: Query<&AppTabButton>,
+    app_tab_query: Query<&TabButton, With<AppTabButton>>,
 ) {
     for event in tab_events.read() {
         // Check if this is an app-level tab by looking up the entity
-        if let Ok(app_tab) = app_tab_query.get(event.tab_entity) {
-            ui_state.active_tab = app_tab_from_index(app_tab.index);
+        if let Ok(tab_button) = app_tab_query.get(event.tab_entity) {
+            ui_state.active_tab = tab_button.tab;
         }
     }
 }
@@ -187,13 +256,15 @@ pub fn update_tab_styles(
     mut tab_query: Query<(&AppTabButton, &mut MaterialTab)>,
     mut text_query: Query<&mut TextColor, With<AppTabText>>,
     theme: Option<Res<MaterialTheme>>,
+    extension_tabs: Option<Res<ExtensionTabs>>,
 ) {
     if !ui_state.is_changed() {
         return;
     }
 
     let theme = theme.map(|t| t.clone()).unwrap_or_default();
-    let selected_index = index_from_app_tab(ui_state.active_tab);
+    let extension_tabs = extension_tabs.map(|t| t.clone()).unwrap_or_default();
+    let selected_index = index_from_app_tab(ui_state.active_tab, &extension_tabs);
 
     // Update the MaterialTabs selected index
     for mut tabs in tabs_query.iter_mut() {
@@ -221,8 +292,11 @@ pub fn update_tab_visibility(
         (
             With<DiceRollerRoot>,
             Without<CharacterScreenRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<PartyScreenRoot>,
             Without<DndInfoScreenRoot>,
             Without<ContributorsScreenRoot>,
+            Without<ExtensionTabRoot>,
         ),
     >,
     mut character_screen: Query<
@@ -230,8 +304,35 @@ pub fn update_tab_visibility(
         (
             With<CharacterScreenRoot>,
             Without<DiceRollerRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<PartyScreenRoot>,
+            Without<DndInfoScreenRoot>,
+            Without<ContributorsScreenRoot>,
+            Without<ExtensionTabRoot>,
+        ),
+    >,
+    mut initiative_screen: Query<
+        &mut Visibility,
+        (
+            With<InitiativeScreenRoot>,
+            Without<DiceRollerRoot>,
+            Without<CharacterScreenRoot>,
+            Without<PartyScreenRoot>,
             Without<DndInfoScreenRoot>,
             Without<ContributorsScreenRoot>,
+            Without<ExtensionTabRoot>,
+        ),
+    >,
+    mut party_screen: Query<
+        &mut Visibility,
+        (
+            With<PartyScreenRoot>,
+            Without<DiceRollerRoot>,
+            Without<CharacterScreenRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<DndInfoScreenRoot>,
+            Without<ContributorsScreenRoot>,
+            Without<ExtensionTabRoot>,
         ),
     >,
     mut dnd_info_screen: Query<
@@ -240,7 +341,10 @@ pub fn update_tab_visibility(
             With<DndInfoScreenRoot>,
             Without<DiceRollerRoot>,
             Without<CharacterScreenRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<PartyScreenRoot>,
             Without<ContributorsScreenRoot>,
+            Without<ExtensionTabRoot>,
         ),
     >,
     mut contributors_screen: Query<
@@ -249,11 +353,37 @@ pub fn update_tab_visibility(
             With<ContributorsScreenRoot>,
             Without<DiceRollerRoot>,
             Without<CharacterScreenRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<PartyScreenRoot>,
+            Without<DndInfoScreenRoot>,
+            Without<ExtensionTabRoot>,
+        ),
+    >,
+    mut extension_screens: Query<
+        (&ExtensionTabRoot, &mut Visibility),
+        (
+            Without<DiceRollerRoot>,
+            Without<CharacterScreenRoot>,
+            Without<InitiativeScreenRoot>,
+            Without<PartyScreenRoot>,
             Without<DndInfoScreenRoot>,
+            Without<ContributorsScreenRoot>,
         ),
     >,
+    // Screens spawned on first use appear after the tab was switched to
+    added_screens: Query<
+        (),
+        Or<(
+            Added<CharacterScreenRoot>,
+            Added<InitiativeScreenRoot>,
+            Added<PartyScreenRoot>,
+            Added<DndInfoScreenRoot>,
+            Added<ContributorsScreenRoot>,
+            Added<ExtensionTabRoot>,
+        )>,
+    >,
 ) {
-    if !ui_state.is_changed() {
+    if !ui_state.is_changed() && added_screens.is_empty() {
         return;
     }
 
@@ -275,6 +405,24 @@ pub fn update_tab_visibility(
         };
     }
 
+    // Initiative screen visibility
+    for mut visibility in initiative_screen.iter_mut() {
+        *visibility = if ui_state.active_tab == AppTab::Initiative {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    // Party screen visibility
+    for mut visibility in party_screen.iter_mut() {
+        *visibility = if ui_state.active_tab == AppTab::Party {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
     // DnD Info screen visibility
     for mut visibility in dnd_info_screen.iter_mut() {
         *visibility = if ui_state.active_tab == AppTab::DndInfo {
@@ -292,4 +440,13 @@ pub fn update_tab_visibility(
             Visibility::Hidden
         };
     }
+
+    // Extension tab visibility
+    for (root, mut visibility) in extension_screens.iter_mut() {
+        *visibility = if ui_state.active_tab == AppTab::Extension(root.0) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
 }
@@ -227,7 +227,7 @@ fn spawn_attribute_row(
                         font_size: 14.0,
                         ..default()
                     },
-                    TextColor(MD3_ON_SURFACE_VARIANT),
+                    ThemeToken::OnSurfaceVariant.text(),
                 ));
             });
 
@@ -283,7 +283,7 @@ fn spawn_attribute_row(
                         font_size: 14.0,
                         ..default()
                     },
-                    TextColor(MD3_ON_SURFACE_VARIANT),
+                    ThemeToken::OnSurfaceVariant.text(),
                 ));
 
                 // Last roll result (filled when the dice roller completes)
@@ -425,7 +425,7 @@ fn spawn_custom_attribute_row(
                             font_size: 14.0,
                             ..default()
                         },
-                        TextColor(MD3_ON_SURFACE_VARIANT),
+                        ThemeToken::OnSurfaceVariant.text(),
                     ));
                 }
             });
@@ -482,7 +482,7 @@ fn spawn_custom_attribute_row(
                         font_size: 14.0,
                         ..default()
                     },
-                    TextColor(MD3_ON_SURFACE_VARIANT),
+                    ThemeToken::OnSurfaceVariant.text(),
                 ));
 
                 // Last roll result (filled when the dice roller completes)
@@ -1,12 +1,16 @@
 //! Combat tab content
 //!
 //! This module contains the UI for the Combat section of the character sheet,
-//! including AC, initiative, speed, HP, and proficiency bonus.
+//! including AC, initiative, speed, HP, proficiency bonus, damage defenses,
+//! the conditions the character is under and the damage riders offered on
+//! weapon hits.
 
 use bevy::prelude::*;
 use bevy_material_ui::prelude::*;
 
 use super::super::*;
+use super::spells::spawn_small_icon_button;
+use crate::dice3d::systems::damage::DamageDefensesPanel;
 use crate::dice3d::types::*;
 
 /// Spawn the Combat tab content
@@ -118,11 +122,42 @@ pub fn spawn_combat_content(
                 theme,
             );
 
+            // Extra Attack
+            spawn_stat_field(
+                card,
+                "Extra Attacks",
+                &sheet.combat.extra_attacks.to_string(),
+                EditingField::ExtraAttacks,
+                true,
+                is_editing,
+                Some(group_type.clone()),
+                Some("extra_attacks"),
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+
             // Hit Points
             if let Some(hp) = &sheet.combat.hit_points {
                 spawn_hp_field(card, hp, is_editing, theme);
             }
 
+            // Damage resistances, immunities and vulnerabilities
+            card.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                DamageDefensesPanel,
+            ));
+
+            // Conditions and exhaustion
+            spawn_conditions(card, &sheet.conditions, icon_font.clone(), theme);
+
+            // Sneak Attack, Divine Smite and the like
+            spawn_damage_riders(card, sheet, theme);
+
             // Custom combat stats
             for (stat_name, stat_value) in sheet.custom_combat.iter() {
                 spawn_custom_field_row(
@@ -151,6 +186,196 @@ pub fn spawn_combat_content(
         });
 }
 
+/// Spawn the condition toggles and the exhaustion level
+fn spawn_conditions(
+    parent: &mut ChildSpawnerCommands,
+    conditions: &Conditions,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    parent.spawn((
+        Text::new("Conditions"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        ThemeToken::OnSurfaceVariant.text(),
+    ));
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: Val::Px(6.0),
+            row_gap: Val::Px(6.0),
+            ..default()
+        })
+        .with_children(|chips| {
+            for condition in Condition::ALL {
+                let active = conditions.has(condition);
+                let builder = MaterialButtonBuilder::new(condition.name());
+                let (button, label_color) = if active {
+                    (
+                        builder.filled_tonal().build(theme),
+                        theme.on_secondary_container,
+                    )
+                } else {
+                    (builder.outlined().build(theme), theme.on_surface_variant)
+                };
+                chips
+                    .spawn((button, ConditionToggleButton { condition }))
+                    .insert(Node {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                        ..default()
+                    })
+                    .with_children(|btn| {
+                        btn.spawn((
+                            bevy_material_ui::button::ButtonLabel,
+                            Text::new(condition.name()),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            TextColor(label_color),
+                        ));
+                    });
+            }
+        });
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Exhaustion"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                ThemeToken::OnSurfaceVariant.text(),
+            ));
+
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                spawn_small_icon_button(
+                    right,
+                    "remove",
+                    "-",
+                    ExhaustionButton { delta: -1 },
+                    icon_font.clone(),
+                    theme,
+                );
+                right.spawn((
+                    Text::new(format!("{} / {}", conditions.exhaustion, MAX_EXHAUSTION)),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(if conditions.exhaustion > 0 {
+                        theme.error
+                    } else {
+                        theme.on_surface
+                    }),
+                ));
+                spawn_small_icon_button(
+                    right,
+                    "add",
+                    "+",
+                    ExhaustionButton { delta: 1 },
+                    icon_font,
+                    theme,
+                );
+            });
+        });
+}
+
+/// Spawn the damage rider toggles: the built-in riders with their dice at
+/// the character's level, and any custom riders from the sheet
+fn spawn_damage_riders(
+    parent: &mut ChildSpawnerCommands,
+    sheet: &CharacterSheet,
+    theme: &MaterialTheme,
+) {
+    parent.spawn((
+        Text::new("Damage Riders"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        ThemeToken::OnSurfaceVariant.text(),
+    ));
+
+    let riders = &sheet.combat.damage_riders;
+    let custom = riders
+        .iter()
+        .filter(|rider| !DamageRider::BUILT_IN.contains(rider));
+    let smite_slot = sheet
+        .spells
+        .as_ref()
+        .and_then(|spells| spells.lowest_slot_remaining())
+        .unwrap_or(1);
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: Val::Px(6.0),
+            row_gap: Val::Px(6.0),
+            ..default()
+        })
+        .with_children(|chips| {
+            for rider in DamageRider::BUILT_IN.iter().chain(custom) {
+                let active = riders.contains(rider);
+                let label = format!(
+                    "{} {}",
+                    rider.name(),
+                    rider.dice(sheet.character.level, smite_slot)
+                );
+                let builder = MaterialButtonBuilder::new(label.clone());
+                let (button, label_color) = if active {
+                    (
+                        builder.filled_tonal().build(theme),
+                        theme.on_secondary_container,
+                    )
+                } else {
+                    (builder.outlined().build(theme), theme.on_surface_variant)
+                };
+                chips
+                    .spawn((
+                        button,
+                        DamageRiderToggleButton {
+                            rider: rider.clone(),
+                        },
+                    ))
+                    .insert(Node {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                        ..default()
+                    })
+                    .with_children(|btn| {
+                        btn.spawn((
+                            bevy_material_ui::button::ButtonLabel,
+                            Text::new(label),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            TextColor(label_color),
+                        ));
+                    });
+            }
+        });
+}
+
 /// Spawn the HP field with current/maximum display
 fn spawn_hp_field(
     parent: &mut ChildSpawnerCommands,
@@ -174,7 +399,7 @@ fn spawn_hp_field(
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE_VARIANT),
+                ThemeToken::OnSurfaceVariant.text(),
             ));
 
             // Value container
@@ -187,10 +412,10 @@ fn spawn_hp_field(
             .with_children(|values| {
                 // Current HP
                 let hp_current_field = EditingField::HitPointsCurrent;
-                let current_color = if hp.current < hp.maximum / 2 {
-                    MD3_ERROR
+                let current_token = if hp.current < hp.maximum / 2 {
+                    ThemeToken::Error
                 } else {
-                    MD3_ON_SURFACE
+                    ThemeToken::OnSurface
                 };
 
                 let current_text = hp.current.to_string();
@@ -212,22 +437,24 @@ fn spawn_hp_field(
                         ..default()
                     })
                     .with_children(|field| {
-                        field.spawn((
-                            bevy_material_ui::button::ButtonLabel,
-                            Text::new(current_text),
-                            TextFont {
-                                font_size: 16.0,
-                                ..default()
-                            },
-                            TextColor(if is_editing {
-                                theme.on_surface_variant
-                            } else {
-                                current_color
-                            }),
-                            StatFieldValue {
-                                field: hp_current_field,
-                            },
-                        ));
+                        field
+                            .spawn((
+                                bevy_material_ui::button::ButtonLabel,
+                                Text::new(current_text),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(if is_editing {
+                                    theme.on_surface_variant
+                                } else {
+                                    current_token.default_color()
+                                }),
+                                StatFieldValue {
+                                    field: hp_current_field,
+                                },
+                            ))
+                            .insert_if(ThemedText(current_token), || !is_editing);
                     });
 
                 // Separator
@@ -237,7 +464,7 @@ fn spawn_hp_field(
                         font_size: 16.0,
                         ..default()
                     },
-                    TextColor(MD3_ON_SURFACE_VARIANT),
+                    ThemeToken::OnSurfaceVariant.text(),
                 ));
 
                 // Max HP
@@ -283,7 +510,7 @@ fn spawn_hp_field(
                             font_size: 12.0,
                             ..default()
                         },
-                        TextColor(MD3_SECONDARY),
+                        ThemeToken::Secondary.text(),
                     ));
                 }
             });
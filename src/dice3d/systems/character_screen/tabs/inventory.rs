@@ -0,0 +1,485 @@
+//! Inventory tab content
+//!
+//! This module contains the UI for the Inventory section of the character
+//! sheet: the carried items with their quantity, weight, value, equipped and
+//! attuned state and the bonuses they give, the carrying capacity and
+//! encumbrance they add up to, the attunement slots, and the coin purse.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::super::*;
+use super::spells::{spawn_section_title, spawn_small_icon_button};
+use crate::dice3d::types::*;
+
+/// Spawn the Inventory tab content
+pub fn spawn_inventory_content(
+    parent: &mut ChildSpawnerCommands,
+    sheet: &CharacterSheet,
+    edit_state: &GroupEditState,
+    adding_state: &AddingEntryState,
+    icon_assets: &IconAssets,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let group_type = GroupType::Inventory;
+    let is_editing = edit_state.editing_groups.contains(&group_type);
+    let items = sheet
+        .equipment
+        .as_ref()
+        .map(|equipment| equipment.items.as_slice())
+        .unwrap_or(&[]);
+
+    // Card container
+    parent
+        .spawn((
+            CardBuilder::new().outlined().padding(16.0).build(theme),
+            StatGroup {
+                name: "Inventory".to_string(),
+                group_type: group_type.clone(),
+            },
+        ))
+        .insert(Node {
+            width: Val::Px(440.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        })
+        .with_children(|card| {
+            // Group header
+            spawn_group_header(
+                card,
+                "Inventory",
+                group_type.clone(),
+                edit_state,
+                icon_font.clone(),
+                theme,
+            );
+
+            spawn_summary(card, sheet, items, is_editing, theme);
+
+            spawn_section_title(card, "Items");
+
+            if items.is_empty() && !is_editing {
+                card.spawn((
+                    Text::new("No items yet. Use the edit button to add some."),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    ThemeToken::OnSurfaceVariant.text(),
+                ));
+            }
+            for item in items {
+                spawn_item_row(
+                    card,
+                    item,
+                    is_editing,
+                    icon_assets,
+                    icon_font.clone(),
+                    theme,
+                );
+            }
+
+            // Add button (shown when editing); "Arrows (20)" adds a stack
+            if is_editing {
+                spawn_group_add_button(
+                    card,
+                    group_type,
+                    adding_state,
+                    icon_assets,
+                    icon_font.clone(),
+                    theme,
+                );
+            }
+
+            spawn_section_title(card, "Coins");
+            let currency = sheet
+                .equipment
+                .as_ref()
+                .map(|equipment| equipment.currency.clone())
+                .unwrap_or_default();
+            for coin in Coin::ALL.into_iter().rev() {
+                spawn_coin_row(card, coin, &currency, is_editing, icon_font.clone(), theme);
+            }
+            card.spawn((
+                Text::new(format!("Total: {} gp", currency.total_gold())),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                ThemeToken::OnSurfaceVariant.text(),
+            ));
+        });
+}
+
+/// Spawn one coin of the purse: spend and add buttons around the count
+fn spawn_coin_row(
+    parent: &mut ChildSpawnerCommands,
+    coin: Coin,
+    currency: &Currency,
+    is_editing: bool,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new(format!("{} ({})", coin.name(), coin.abbreviation())),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                ThemeToken::OnSurfaceVariant.text(),
+            ));
+
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                spawn_small_icon_button(
+                    right,
+                    "remove",
+                    "-",
+                    CoinButton { coin, delta: -1 },
+                    icon_font.clone(),
+                    theme,
+                );
+                spawn_item_field(
+                    right,
+                    currency.get(coin).to_string(),
+                    EditingField::Coins(coin),
+                    is_editing,
+                    theme,
+                );
+                spawn_small_icon_button(
+                    right,
+                    "add",
+                    "+",
+                    CoinButton { coin, delta: 1 },
+                    icon_font,
+                    theme,
+                );
+            });
+        });
+}
+
+/// Spawn the carried weight against the carrying capacity, the total value,
+/// the attunement slots in use (the slot count is editable) and the
+/// encumbrance and attunement warnings
+fn spawn_summary(
+    parent: &mut ChildSpawnerCommands,
+    sheet: &CharacterSheet,
+    items: &[InventoryItem],
+    is_editing: bool,
+    theme: &MaterialTheme,
+) {
+    let slots = sheet.attunement_slots();
+    for (label, value) in [
+        (
+            "Carrying",
+            format!(
+                "{} / {} lb",
+                sheet.carried_weight(),
+                carrying_capacity(sheet.attributes.strength)
+            ),
+        ),
+        ("Value", format!("{} gp", inventory_value(items))),
+    ] {
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                padding: UiRect::vertical(Val::Px(2.0)),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    ThemeToken::OnSurfaceVariant.text(),
+                ));
+                row.spawn((
+                    Text::new(value),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                ));
+            });
+    }
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Attuned"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                ThemeToken::OnSurfaceVariant.text(),
+            ));
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                right.spawn((
+                    Text::new(format!("{} /", attuned_count(items))),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                ));
+                spawn_item_field(
+                    right,
+                    slots.to_string(),
+                    EditingField::AttunementSlots,
+                    is_editing,
+                    theme,
+                );
+            });
+        });
+
+    let warnings = [
+        sheet.encumbrance().warning().map(str::to_string),
+        attunement_warning(items, slots),
+    ];
+    for warning in warnings.into_iter().flatten() {
+        parent.spawn((
+            Text::new(warning),
+            TextFont {
+                font_size: 13.0,
+                ..default()
+            },
+            TextColor(theme.error),
+        ));
+    }
+}
+
+/// Spawn an item: equipped toggle, name, quantity, weight and value fields,
+/// attunement toggle and the delete button while editing, with its bonuses
+/// field below.
+fn spawn_item_row(
+    parent: &mut ChildSpawnerCommands,
+    item: &InventoryItem,
+    is_editing: bool,
+    icon_assets: &IconAssets,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let toggle = |kind| InventoryToggleButton {
+        item: item.name.clone(),
+        toggle: kind,
+    };
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(3.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            // Left: equipped toggle and name
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(8.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|left| {
+                spawn_small_icon_button(
+                    left,
+                    if item.equipped {
+                        "check_box"
+                    } else {
+                        "check_box_outline_blank"
+                    },
+                    if item.equipped { "[x]" } else { "[ ]" },
+                    toggle(InventoryToggle::Equipped),
+                    icon_font.clone(),
+                    theme,
+                );
+                left.spawn((
+                    Text::new(item.name.as_str()),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    (if item.equipped {
+                        ThemeToken::Success
+                    } else {
+                        ThemeToken::OnSurfaceVariant
+                    })
+                    .text(),
+                ));
+            });
+
+            // Right: quantity, weight, value, attunement and delete
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                spawn_item_field(
+                    right,
+                    format!("×{}", item.quantity),
+                    EditingField::ItemQuantity(item.name.clone()),
+                    is_editing,
+                    theme,
+                );
+                spawn_item_field(
+                    right,
+                    format!("{} lb", item.weight),
+                    EditingField::ItemWeight(item.name.clone()),
+                    is_editing,
+                    theme,
+                );
+                spawn_item_field(
+                    right,
+                    format!("{} gp", item.value),
+                    EditingField::ItemValue(item.name.clone()),
+                    is_editing,
+                    theme,
+                );
+
+                if is_editing {
+                    spawn_small_icon_button(
+                        right,
+                        if item.requires_attunement {
+                            "auto_fix_high"
+                        } else {
+                            "auto_fix_off"
+                        },
+                        if item.requires_attunement { "A" } else { "-" },
+                        toggle(InventoryToggle::RequiresAttunement),
+                        icon_font.clone(),
+                        theme,
+                    );
+                    spawn_delete_button(
+                        right,
+                        GroupType::Inventory,
+                        &item.name,
+                        icon_assets,
+                        icon_font.clone(),
+                        theme,
+                    );
+                } else if item.requires_attunement {
+                    spawn_small_icon_button(
+                        right,
+                        if item.attuned { "link" } else { "link_off" },
+                        if item.attuned { "[A]" } else { "[ ]" },
+                        toggle(InventoryToggle::Attuned),
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+            });
+        });
+
+    // Bonuses, highlighted while they apply; typed as "+1 saves, +1 attacks"
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(6.0),
+            align_items: AlignItems::Center,
+            padding: UiRect::left(Val::Px(32.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Bonuses"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                (if item.is_active() && !item.bonuses.is_empty() {
+                    ThemeToken::Success
+                } else {
+                    ThemeToken::OnSurfaceVariant
+                })
+                .text(),
+            ));
+            spawn_item_field(
+                row,
+                if item.bonuses.is_empty() {
+                    "none".to_string()
+                } else {
+                    format_item_bonuses(&item.bonuses)
+                },
+                EditingField::ItemBonuses(item.name.clone()),
+                is_editing,
+                theme,
+            );
+        });
+}
+
+/// Spawn a compact editable field of an item or coin
+fn spawn_item_field(
+    parent: &mut ChildSpawnerCommands,
+    text: String,
+    field: EditingField,
+    is_editing: bool,
+    theme: &MaterialTheme,
+) {
+    parent
+        .spawn((
+            MaterialButtonBuilder::new(text.clone())
+                .outlined()
+                .disabled(is_editing)
+                .build(theme),
+            StatField {
+                field: field.clone(),
+                is_numeric: true,
+            },
+        ))
+        .insert(Node {
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            min_width: Val::Px(44.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                bevy_material_ui::button::ButtonLabel,
+                Text::new(text),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface),
+                StatFieldValue { field },
+            ));
+        });
+}
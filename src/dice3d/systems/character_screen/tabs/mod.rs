@@ -13,15 +13,20 @@ use crate::dice3d::types::*;
 mod attributes;
 mod basic_info;
 mod combat;
+mod inventory;
 mod saving_throws;
 mod skills;
+mod spells;
 
 // Re-export tab content builders
 pub use attributes::spawn_attributes_content;
 pub use basic_info::spawn_basic_info_content;
 pub use combat::spawn_combat_content;
+pub use inventory::spawn_inventory_content;
 pub use saving_throws::spawn_saving_throws_content;
+pub(crate) use skills::camel_to_title_case;
 pub use skills::spawn_skills_content;
+pub use spells::spawn_spells_content;
 
 // ============================================================================
 // Character Sheet Tab Container
@@ -52,7 +57,7 @@ pub fn setup_character_screen(
                 flex_direction: FlexDirection::Row,
                 ..default()
             },
-            BackgroundColor(MD3_SURFACE),
+            ThemeToken::Surface.background(),
             Visibility::Hidden,
             CharacterScreenRoot,
         ))
@@ -135,8 +140,8 @@ fn spawn_character_sheet_tabs(parent: &mut ChildSpawnerCommands, theme: &Materia
                 border: UiRect::bottom(Val::Px(1.0)),
                 ..default()
             },
-            BackgroundColor(MD3_SURFACE_CONTAINER),
-            BorderColor::from(MD3_OUTLINE_VARIANT),
+            ThemeToken::SurfaceContainer.background(),
+            ThemeToken::OutlineVariant.border(),
             CharacterSheetTabBar, // Marker component
         ))
         .with_children(|tabs| {
@@ -267,7 +272,7 @@ fn spawn_header_row(
                 margin: UiRect::bottom(Val::Px(8.0)),
                 ..default()
             },
-            BorderColor::from(MD3_OUTLINE_VARIANT),
+            ThemeToken::OutlineVariant.border(),
         ))
         .with_children(|header| {
             // Character name
@@ -277,9 +282,14 @@ fn spawn_header_row(
                     font_size: 24.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE),
+                ThemeToken::OnSurface.text(),
             ));
 
+            // Concentration indicator
+            if let Some(spell) = &sheet.concentration {
+                spawn_concentration_chip(header, spell, theme);
+            }
+
             // Save button
             // Use a Material button so `ButtonClickEvent` + disabling behavior are consistent.
             // Styling/disabled state will be handled by `update_save_button_appearance`.
@@ -454,6 +464,56 @@ fn spawn_all_tab_contents(
                 theme,
             );
         });
+
+    // Spells tab content
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                display: Display::None,
+                ..default()
+            },
+            CharacterSheetTabContent {
+                tab: CharacterSheetTab::Spells,
+            },
+        ))
+        .with_children(|content| {
+            spawn_spells_content(
+                content,
+                sheet,
+                edit_state,
+                adding_state,
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+        });
+
+    // Inventory tab content
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                display: Display::None,
+                ..default()
+            },
+            CharacterSheetTabContent {
+                tab: CharacterSheetTab::Inventory,
+            },
+        ))
+        .with_children(|content| {
+            spawn_inventory_content(
+                content,
+                sheet,
+                edit_state,
+                adding_state,
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+        });
 }
 
 /// Spawn the "no character" message with create button
@@ -475,7 +535,7 @@ fn spawn_no_character_message(parent: &mut ChildSpawnerCommands) {
                     font_size: 18.0,
                     ..default()
                 },
-                TextColor(MD3_ON_SURFACE_VARIANT),
+                ThemeToken::OnSurfaceVariant.text(),
                 Node {
                     margin: UiRect::bottom(Val::Px(16.0)),
                     ..default()
@@ -492,7 +552,7 @@ fn spawn_no_character_message(parent: &mut ChildSpawnerCommands) {
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    BackgroundColor(MD3_PRIMARY),
+                    ThemeToken::Primary.background(),
                     BorderRadius::all(Val::Px(12.0)),
                     NewCharacterButton,
                 ))
@@ -503,7 +563,7 @@ fn spawn_no_character_message(parent: &mut ChildSpawnerCommands) {
                             font_size: 16.0,
                             ..default()
                         },
-                        TextColor(MD3_ON_PRIMARY),
+                        ThemeToken::OnPrimary.text(),
                     ));
                 });
         });
@@ -518,12 +578,27 @@ pub fn handle_sheet_tab_clicks(
     mut tab_events: MessageReader<TabChangeEvent>,
     mut selected_tab: ResMut<SelectedCharacterSheetTab>,
     sheet_tab_query: Query<&CharacterSheetTabButton>,
+    scrollable_query: Query<&Node, With<ScrollableContent>>,
 ) {
     for event in tab_events.read() {
         // Check if this is a character sheet tab by looking up the entity
-        if let Ok(sheet_tab) = sheet_tab_query.get(event.tab_entity) {
-            selected_tab.current = sheet_tab.tab;
+        let Ok(sheet_tab) = sheet_tab_query.get(event.tab_entity) else {
+            continue;
+        };
+        if sheet_tab.tab == selected_tab.current {
+            continue;
+        }
+
+        // Remember where the tab being left was scrolled to
+        if let Some(node) = scrollable_query.iter().next() {
+            let top = match node.top {
+                Val::Px(px) => px,
+                _ => 0.0,
+            };
+            let previous = selected_tab.current;
+            selected_tab.scroll_offsets.insert(previous, top);
         }
+        selected_tab.current = sheet_tab.tab;
     }
 }
 
@@ -582,9 +657,14 @@ pub fn update_sheet_tab_visibility(
         };
     }
 
-    // When switching tabs, reset scroll so the newly selected tab isn't hidden
-    // due to a previous tab's scroll offset.
+    // The tabs share one scrollable area; put it back where the newly
+    // selected tab was left (the top for a tab not visited yet).
+    let top = selected_tab
+        .scroll_offsets
+        .get(&selected_tab.current)
+        .copied()
+        .unwrap_or(0.0);
     for mut node in scrollable_query.iter_mut() {
-        node.top = Val::Px(0.0);
+        node.top = Val::Px(top);
     }
 }
@@ -50,6 +50,9 @@ pub fn spawn_saving_throws_content(
                 theme,
             );
 
+            // All skill and save proficiencies in one grid
+            spawn_bulk_edit_button(card, theme);
+
             // Standard abilities
             let abilities = [
                 "strength",
@@ -155,16 +158,13 @@ fn spawn_saving_throw_row(
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    BackgroundColor(if has_proficiency {
-                        MD3_SUCCESS
-                    } else {
-                        Color::NONE
-                    }),
-                    BorderColor::from(if has_proficiency {
-                        MD3_SUCCESS
+                    ThemeToken::Success.background_alpha(if has_proficiency { 1.0 } else { 0.0 }),
+                    (if has_proficiency {
+                        ThemeToken::Success
                     } else {
-                        MD3_OUTLINE
-                    }),
+                        ThemeToken::Outline
+                    })
+                    .border(),
                     BorderRadius::all(Val::Px(4.0)),
                     ProficiencyCheckbox {
                         target: ProficiencyTarget::SavingThrow(ability_owned.clone()),
@@ -199,12 +199,13 @@ fn spawn_saving_throw_row(
                                 ..default()
                             },
                             TextColor(if has_proficiency {
-                                MD3_SUCCESS
+                                ThemeToken::Success.default_color()
                             } else {
                                 theme.on_surface_variant
                             }),
                             EditableLabelText { field: label_field },
-                        ));
+                        ))
+                        .insert_if(ThemedText(ThemeToken::Success), || has_proficiency);
                     });
                 } else {
                     left.spawn((
@@ -213,11 +214,12 @@ fn spawn_saving_throw_row(
                             font_size: 14.0,
                             ..default()
                         },
-                        TextColor(if has_proficiency {
-                            MD3_SUCCESS
+                        (if has_proficiency {
+                            ThemeToken::Success
                         } else {
-                            MD3_ON_SURFACE_VARIANT
-                        }),
+                            ThemeToken::OnSurfaceVariant
+                        })
+                        .text(),
                     ));
                 }
             });
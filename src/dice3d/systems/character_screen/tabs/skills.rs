@@ -50,6 +50,9 @@ pub fn spawn_skills_content(
                 theme,
             );
 
+            // All skill and save proficiencies in one grid
+            spawn_bulk_edit_button(card, theme);
+
             // Sort skills alphabetically
             let mut skills: Vec<_> = sheet.skills.iter().collect();
             skills.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
@@ -173,6 +176,38 @@ fn spawn_skill_row(
                     });
                 }
 
+                // Ability picker (e.g. roll Intimidation with STR)
+                let ability_label =
+                    ability_abbreviation(default_skill_ability(skill_name).unwrap_or(""));
+                left.spawn((
+                    MaterialButtonBuilder::new(ability_label)
+                        .text()
+                        .build(theme),
+                    SkillAbilityPickerButton {
+                        skill: skill_name_owned.clone(),
+                    },
+                ))
+                .insert(Node {
+                    padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                    min_width: Val::Px(36.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .with_children(|btn| {
+                    btn.spawn((
+                        bevy_material_ui::button::ButtonLabel,
+                        Text::new(ability_label),
+                        TextFont {
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        TextColor(theme.primary),
+                        SkillAbilityPickerText {
+                            skill: skill_name_owned.clone(),
+                        },
+                    ));
+                });
+
                 // Proficiency indicator
                 left.spawn((
                     Node {
@@ -183,16 +218,13 @@ fn spawn_skill_row(
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    BackgroundColor(if has_proficiency {
-                        MD3_SUCCESS
+                    ThemeToken::Success.background_alpha(if has_proficiency { 1.0 } else { 0.0 }),
+                    (if has_proficiency {
+                        ThemeToken::Success
                     } else {
-                        Color::NONE
-                    }),
-                    BorderColor::from(if has_proficiency {
-                        MD3_SUCCESS
-                    } else {
-                        MD3_OUTLINE
-                    }),
+                        ThemeToken::Outline
+                    })
+                    .border(),
                     BorderRadius::all(Val::Px(4.0)),
                     ProficiencyCheckbox {
                         target: ProficiencyTarget::Skill(skill_name_owned.clone()),
@@ -227,12 +259,13 @@ fn spawn_skill_row(
                                 ..default()
                             },
                             TextColor(if has_proficiency {
-                                MD3_SUCCESS
+                                ThemeToken::Success.default_color()
                             } else {
                                 theme.on_surface_variant
                             }),
                             EditableLabelText { field: label_field },
-                        ));
+                        ))
+                        .insert_if(ThemedText(ThemeToken::Success), || has_proficiency);
                     });
                 } else {
                     left.spawn((
@@ -241,11 +274,12 @@ fn spawn_skill_row(
                             font_size: 13.0,
                             ..default()
                         },
-                        TextColor(if has_proficiency {
-                            MD3_SUCCESS
+                        (if has_proficiency {
+                            ThemeToken::Success
                         } else {
-                            MD3_ON_SURFACE_VARIANT
-                        }),
+                            ThemeToken::OnSurfaceVariant
+                        })
+                        .text(),
                     ));
                 }
             });
@@ -324,7 +358,7 @@ fn spawn_skill_row(
 }
 
 /// Convert camelCase to Title Case
-fn camel_to_title_case(s: &str) -> String {
+pub(crate) fn camel_to_title_case(s: &str) -> String {
     let mut result = String::new();
     for (i, c) in s.chars().enumerate() {
         if i == 0 {
@@ -0,0 +1,587 @@
+//! Spells tab content
+//!
+//! This module contains the UI for the Spells section of the character sheet:
+//! spellcasting ability, save DC and attack bonus, spell slots per level and
+//! the known spells with their prepared state, concentration and ritual marks,
+//! slot picker, cast and cast-as-ritual buttons, and attack/damage roll
+//! buttons.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::super::*;
+use crate::dice3d::types::*;
+
+/// Spawn the Spells tab content
+pub fn spawn_spells_content(
+    parent: &mut ChildSpawnerCommands,
+    sheet: &CharacterSheet,
+    edit_state: &GroupEditState,
+    adding_state: &AddingEntryState,
+    icon_assets: &IconAssets,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let group_type = GroupType::Spells;
+    let is_editing = edit_state.editing_groups.contains(&group_type);
+    let spells = sheet.spells.clone().unwrap_or_default();
+
+    // Card container
+    parent
+        .spawn((
+            CardBuilder::new().outlined().padding(16.0).build(theme),
+            StatGroup {
+                name: "Spells".to_string(),
+                group_type: group_type.clone(),
+            },
+        ))
+        .insert(Node {
+            width: Val::Px(400.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        })
+        .with_children(|card| {
+            // Group header
+            spawn_group_header(
+                card,
+                "Spells",
+                group_type.clone(),
+                edit_state,
+                icon_font.clone(),
+                theme,
+            );
+
+            spawn_stat_field(
+                card,
+                "Spellcasting Ability",
+                spells.spellcasting_ability.as_deref().unwrap_or("-"),
+                EditingField::SpellcastingAbility,
+                false,
+                is_editing,
+                None,
+                None,
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+
+            spawn_stat_field(
+                card,
+                "Spell Save DC",
+                &sheet.spell_save_dc().to_string(),
+                EditingField::SpellSaveDc,
+                true,
+                is_editing,
+                None,
+                None,
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+
+            let attack_bonus = sheet.spell_attack_bonus();
+            spawn_stat_field(
+                card,
+                "Spell Attack Bonus",
+                &if attack_bonus >= 0 {
+                    format!("+{}", attack_bonus)
+                } else {
+                    attack_bonus.to_string()
+                },
+                EditingField::SpellAttackBonus,
+                true,
+                is_editing,
+                None,
+                None,
+                icon_assets,
+                icon_font.clone(),
+                theme,
+            );
+
+            // Spell slots per level
+            spawn_section_title(card, "Spell Slots");
+            for level in 1..=9 {
+                spawn_slot_row(
+                    card,
+                    &level.to_string(),
+                    &spells,
+                    is_editing,
+                    icon_font.clone(),
+                    theme,
+                );
+            }
+
+            // Known spells
+            spawn_section_title(card, "Known Spells");
+            if spells.known_spells.is_empty() && !is_editing {
+                card.spawn((
+                    Text::new("No spells yet. Use the edit button to add some."),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    ThemeToken::OnSurfaceVariant.text(),
+                ));
+            }
+            for spell in &spells.known_spells {
+                spawn_spell_row(
+                    card,
+                    spell,
+                    &spells,
+                    is_editing,
+                    icon_assets,
+                    icon_font.clone(),
+                    theme,
+                );
+            }
+
+            // Add button (shown when editing)
+            if is_editing {
+                spawn_group_add_button(
+                    card,
+                    group_type,
+                    adding_state,
+                    icon_assets,
+                    icon_font,
+                    theme,
+                );
+            }
+        });
+}
+
+/// Spawn a small section title inside the card
+pub(super) fn spawn_section_title(parent: &mut ChildSpawnerCommands, title: &str) {
+    parent.spawn((
+        Text::new(title),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        ThemeToken::OnSurface.text(),
+        Node {
+            margin: UiRect::top(Val::Px(8.0)),
+            ..default()
+        },
+    ));
+}
+
+/// Spawn the slots of one spell level: remaining pips, spend/restore
+/// buttons and the editable total.
+fn spawn_slot_row(
+    parent: &mut ChildSpawnerCommands,
+    level: &str,
+    spells: &SpellCasting,
+    is_editing: bool,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let total = spells.spell_slots.get(level).copied().unwrap_or(0);
+    let remaining = spells.slots_remaining(level);
+    let pips = format!(
+        "{}{}",
+        "●".repeat(remaining as usize),
+        "○".repeat((total - remaining).max(0) as usize)
+    );
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new(format!("Level {}", level)),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                (if total > 0 {
+                    ThemeToken::OnSurfaceVariant
+                } else {
+                    ThemeToken::Outline
+                })
+                .text(),
+            ));
+
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                if total > 0 {
+                    right.spawn((
+                        Text::new(pips),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(theme.primary),
+                    ));
+                    spawn_small_icon_button(
+                        right,
+                        "remove",
+                        "-",
+                        SpellSlotButton {
+                            level: level.to_string(),
+                            expend: true,
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                    spawn_small_icon_button(
+                        right,
+                        "add",
+                        "+",
+                        SpellSlotButton {
+                            level: level.to_string(),
+                            expend: false,
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+
+                // Total slots of this level
+                let field = EditingField::SpellSlots(level.to_string());
+                let total_text = total.to_string();
+                right
+                    .spawn((
+                        MaterialButtonBuilder::new(total_text.clone())
+                            .outlined()
+                            .disabled(is_editing)
+                            .build(theme),
+                        StatField {
+                            field: field.clone(),
+                            is_numeric: true,
+                        },
+                    ))
+                    .insert(Node {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                        min_width: Val::Px(44.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    })
+                    .with_children(|btn| {
+                        btn.spawn((
+                            bevy_material_ui::button::ButtonLabel,
+                            Text::new(total_text),
+                            TextFont {
+                                font_size: 13.0,
+                                ..default()
+                            },
+                            TextColor(theme.on_surface),
+                            StatFieldValue { field },
+                        ));
+                    });
+            });
+        });
+}
+
+/// Spawn a known spell: prepared toggle, name, slot picker and cast button,
+/// attack roll, damage and damage roll.
+fn spawn_spell_row(
+    parent: &mut ChildSpawnerCommands,
+    spell: &str,
+    spells: &SpellCasting,
+    is_editing: bool,
+    icon_assets: &IconAssets,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let is_prepared = spells.is_prepared(spell);
+    let needs_concentration = spells.needs_concentration(spell);
+    let is_ritual = spells.is_ritual(spell);
+    let damage = spells.spell_damage.get(spell).cloned();
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::vertical(Val::Px(3.0)),
+            ..default()
+        })
+        .with_children(|row| {
+            // Left: prepared toggle and name
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(8.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|left| {
+                spawn_small_icon_button(
+                    left,
+                    if is_prepared {
+                        "check_box"
+                    } else {
+                        "check_box_outline_blank"
+                    },
+                    if is_prepared { "[x]" } else { "[ ]" },
+                    PreparedSpellButton {
+                        spell: spell.to_string(),
+                    },
+                    icon_font.clone(),
+                    theme,
+                );
+                left.spawn((
+                    Text::new(format!(
+                        "{}{}{}",
+                        spell,
+                        if needs_concentration { " (C)" } else { "" },
+                        if is_ritual { " (R)" } else { "" }
+                    )),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    (if is_prepared {
+                        ThemeToken::Success
+                    } else {
+                        ThemeToken::OnSurfaceVariant
+                    })
+                    .text(),
+                ));
+                if is_editing {
+                    spawn_small_icon_button(
+                        left,
+                        if needs_concentration {
+                            "center_focus_strong"
+                        } else {
+                            "center_focus_weak"
+                        },
+                        if needs_concentration { "(C)" } else { "( )" },
+                        ConcentrationSpellButton {
+                            spell: spell.to_string(),
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                    spawn_small_icon_button(
+                        left,
+                        if is_ritual {
+                            "bookmark"
+                        } else {
+                            "bookmark_border"
+                        },
+                        if is_ritual { "(R)" } else { "( )" },
+                        RitualSpellButton {
+                            spell: spell.to_string(),
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+            });
+
+            // Right: rolls, damage and optional delete button
+            row.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|right| {
+                // Slot picker and cast button, for casters with slots
+                if spells.spell_slots.values().any(|total| *total > 0) {
+                    spawn_slot_picker(right, spell, spells, theme);
+                    spawn_small_icon_button(
+                        right,
+                        "auto_fix_high",
+                        "✨",
+                        CastSpellButton {
+                            spell: spell.to_string(),
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+
+                // Rituals can also be cast without a slot, taking 10 minutes
+                if is_ritual {
+                    spawn_small_icon_button(
+                        right,
+                        "hourglass_top",
+                        "⏳",
+                        CastRitualButton {
+                            spell: spell.to_string(),
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+
+                spawn_small_icon_button(
+                    right,
+                    "casino",
+                    "🎲",
+                    RollSpellButton {
+                        spell: spell.to_string(),
+                        kind: SpellRollKind::Attack,
+                    },
+                    icon_font.clone(),
+                    theme,
+                );
+
+                let field = EditingField::SpellDamage(spell.to_string());
+                let damage_text = damage.clone().unwrap_or_else(|| "Damage".to_string());
+                right
+                    .spawn((
+                        MaterialButtonBuilder::new(damage_text.clone())
+                            .outlined()
+                            .disabled(is_editing)
+                            .build(theme),
+                        StatField {
+                            field: field.clone(),
+                            is_numeric: false,
+                        },
+                    ))
+                    .insert(Node {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                        min_width: Val::Px(72.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    })
+                    .with_children(|btn| {
+                        btn.spawn((
+                            bevy_material_ui::button::ButtonLabel,
+                            Text::new(damage_text),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            TextColor(if damage.is_some() {
+                                theme.on_surface
+                            } else {
+                                theme.on_surface_variant
+                            }),
+                            StatFieldValue { field },
+                        ));
+                    });
+
+                if damage.is_some() {
+                    spawn_small_icon_button(
+                        right,
+                        "local_fire_department",
+                        "🔥",
+                        RollSpellButton {
+                            spell: spell.to_string(),
+                            kind: SpellRollKind::Damage,
+                        },
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+
+                if is_editing {
+                    spawn_delete_button(
+                        right,
+                        GroupType::Spells,
+                        spell,
+                        icon_assets,
+                        icon_font.clone(),
+                        theme,
+                    );
+                }
+            });
+        });
+}
+
+/// Spawn the button that cycles the slot level a spell is cast with; its
+/// label is kept up to date by `sync_spell_slot_picker_labels`.
+fn spawn_slot_picker(
+    parent: &mut ChildSpawnerCommands,
+    spell: &str,
+    spells: &SpellCasting,
+    theme: &MaterialTheme,
+) {
+    let label = SpellSlotChoices::default().label(spell, spells);
+    parent
+        .spawn((
+            MaterialButtonBuilder::new(label.clone())
+                .text()
+                .build(theme),
+            SpellSlotPickerButton {
+                spell: spell.to_string(),
+            },
+        ))
+        .insert(Node {
+            padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+            min_width: Val::Px(56.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|btn| {
+            btn.spawn((
+                bevy_material_ui::button::ButtonLabel,
+                Text::new(label),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+                SpellSlotPickerText {
+                    spell: spell.to_string(),
+                },
+            ));
+        });
+}
+
+/// Spawn a compact icon button, falling back to `fallback` text when the
+/// icon font doesn't have the icon.
+pub(super) fn spawn_small_icon_button(
+    parent: &mut ChildSpawnerCommands,
+    icon_name: &str,
+    fallback: &str,
+    marker: impl Bundle,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let icon_color = MaterialIconButton::new(icon_name)
+        .with_variant(IconButtonVariant::Standard)
+        .icon_color(theme);
+
+    parent
+        .spawn((
+            IconButtonBuilder::new(icon_name).standard().build(theme),
+            marker,
+        ))
+        .insert(Node {
+            width: Val::Px(28.0),
+            height: Val::Px(28.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|btn| match MaterialIcon::from_name(icon_name) {
+            Some(icon) => {
+                btn.spawn((
+                    Text::new(icon.as_str()),
+                    TextFont {
+                        font: icon_font,
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(icon_color),
+                ));
+            }
+            None => {
+                btn.spawn((
+                    Text::new(fallback),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(icon_color),
+                ));
+            }
+        });
+}
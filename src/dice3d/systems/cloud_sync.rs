@@ -0,0 +1,79 @@
+//! Cloud sync command
+//!
+//! `sync` in the command input syncs the characters with the server set up
+//! with `dndgamerolls sync --endpoint ...`, and `sync status` shows which
+//! server that is. The app also syncs once at startup
+//! (`init_character_manager`).
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{
+    CharacterData, CharacterDatabase, CharacterManager, CloudSyncConfig, TypedCommand,
+};
+
+/// A `sync` command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncCommand {
+    Run,
+    Status,
+}
+
+impl TypedCommand for SyncCommand {
+    const NAME: &'static str = "sync";
+
+    /// Parse `sync` and `sync status`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let mut words = cmd.split_whitespace().map(str::to_lowercase);
+        if words.next()? != "sync" {
+            return None;
+        }
+        match (words.next().as_deref(), words.next()) {
+            (None, _) => Some(SyncCommand::Run),
+            (Some("status"), None) => Some(SyncCommand::Status),
+            _ => None,
+        }
+    }
+}
+
+/// Run a sync command, reloading the character list (and the open character,
+/// unless it has unsaved edits) when the sync changed them.
+pub fn run_sync_command(
+    In(command): In<SyncCommand>,
+    db: Res<CharacterDatabase>,
+    mut character_data: ResMut<CharacterData>,
+    mut character_manager: Option<ResMut<CharacterManager>>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        SyncCommand::Status => match CloudSyncConfig::load(&db) {
+            Some(config) if config.last_sync == 0 => {
+                format!("Syncing with {} (not synced yet)", config.endpoint)
+            }
+            Some(config) => format!(
+                "Syncing with {} ({}/{})",
+                config.endpoint, config.namespace, config.database
+            ),
+            None => "Cloud sync isn't set up (see `dndgamerolls sync`)".to_string(),
+        },
+        SyncCommand::Run => match db.sync_characters() {
+            Ok(summary) => {
+                if let Some(character_manager) = character_manager.as_deref_mut() {
+                    character_manager.refresh_from_database(&db);
+                    character_manager.list_version += 1;
+                }
+                if let Some(id) = character_data.character_id {
+                    if !character_data.is_modified {
+                        if let Ok(sheet) = db.load_character(id) {
+                            character_data.sheet = Some(sheet);
+                            character_data.needs_refresh = true;
+                        }
+                    }
+                }
+                format!("Synced: {}", summary)
+            }
+            Err(e) => format!("Sync failed: {}", e),
+        },
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
@@ -1,4 +1,10 @@
-//! Plays positional collision sound effects when dice hit the active container.
+//! Dice sound effects
+//!
+//! Dice hitting the active container play its recorded sample (wooden box
+//! or glass cup), dice knocking into each other a shorter click, both louder
+//! the faster the dice move. A clack plays when a roll settles and a sting
+//! on a natural 20, and beeps when a timed turn runs low. Everything follows the volume and mute of the Audio
+//! settings tab.
 
 use bevy::audio::{AudioPlayer, AudioSource, PlaybackSettings, Volume};
 use bevy::prelude::*;
@@ -7,13 +13,22 @@ use std::collections::HashMap;
 
 use crate::dice3d::embedded_assets::{DICE_GLASS_CUP_SFX_PATH, DICE_WOODEN_BOX_SFX_PATH};
 use crate::dice3d::types::{
-    DiceContainerProceduralCollider, DiceContainerStyle, DiceContainerVoxelCollider, Die,
+    impact_volume, DiceContainerProceduralCollider, DiceContainerStyle, DiceContainerVoxelCollider,
+    DiceSettled, DiceSound, DiceType, Die, RollResolved, SettingsState, TurnTimerWarning,
 };
 
 #[derive(Resource, Clone)]
 pub struct DiceCollisionSfx {
     pub cup: Handle<AudioSource>,
     pub box_: Handle<AudioSource>,
+    /// Dice knocking into each other
+    pub dice: Handle<AudioSource>,
+    /// A roll coming to rest
+    pub settle: Handle<AudioSource>,
+    /// A natural 20
+    pub critical: Handle<AudioSource>,
+    /// A timed turn running low or out of time
+    pub turn_warning: Handle<AudioSource>,
 }
 
 /// Debounce state to avoid spamming collision SFX when Rapier reports multiple contact events
@@ -26,10 +41,26 @@ pub struct DiceCollisionSfxDebounce {
     pub min_interval_s: f32,
 }
 
-pub fn init_collision_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn init_collision_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
     let cup = asset_server.load(DICE_GLASS_CUP_SFX_PATH);
     let box_ = asset_server.load(DICE_WOODEN_BOX_SFX_PATH);
-    commands.insert_resource(DiceCollisionSfx { cup, box_ });
+    let mut generated = |sound: DiceSound| {
+        audio_sources.add(AudioSource {
+            bytes: sound.wav_bytes().into(),
+        })
+    };
+    commands.insert_resource(DiceCollisionSfx {
+        cup,
+        box_,
+        dice: generated(DiceSound::DiceClick),
+        settle: generated(DiceSound::SettleClack),
+        critical: generated(DiceSound::CriticalSting),
+        turn_warning: generated(DiceSound::TurnWarning),
+    });
     commands.insert_resource(DiceCollisionSfxDebounce {
         // "A little bit" of debounce: enough to stop contact spam, short enough to still feel responsive.
         min_interval_s: 0.10,
@@ -37,9 +68,23 @@ pub fn init_collision_sounds(mut commands: Commands, asset_server: Res<AssetServ
     });
 }
 
+/// Play a one-shot sound.
+///
+/// Non-spatial: collision SFX were easy to miss when the camera/listener is far
+/// from the container (attenuation can make them effectively silent).
+fn play_sound(commands: &mut Commands, sound: Handle<AudioSource>, volume: f32) {
+    commands.spawn((
+        AudioPlayer(sound),
+        PlaybackSettings::DESPAWN
+            .with_spatial(false)
+            .with_volume(Volume::Linear(volume)),
+    ));
+}
+
 pub fn play_dice_container_collision_sfx(
     mut commands: Commands,
     style: Res<DiceContainerStyle>,
+    settings_state: Res<SettingsState>,
     sfx: Res<DiceCollisionSfx>,
     mut debounce: ResMut<DiceCollisionSfxDebounce>,
     mut collision_events: MessageReader<CollisionEvent>,
@@ -56,6 +101,11 @@ pub fn play_dice_container_collision_sfx(
     time: Res<Time>,
 ) {
     let now_s = time.elapsed_secs();
+    let gain = settings_state.settings.sound_gain();
+    if gain <= 0.0 {
+        collision_events.clear();
+        return;
+    }
 
     for ev in collision_events.read() {
         let CollisionEvent::Started(e1, e2, _flags) = *ev else {
@@ -107,10 +157,11 @@ pub fn play_dice_container_collision_sfx(
             }
         }
 
-        let (sound, variant_gain, _variant_name) = match *style {
+        let (sound, variant_gain, _variant_name) = match (other_die, *style) {
+            (Some(_), _) => (sfx.dice.clone(), 1.2_f32, "dice"),
             // The wooden box sample tends to read quieter than the glass cup sample.
-            DiceContainerStyle::Box => (sfx.box_.clone(), 2.2_f32, "box"),
-            DiceContainerStyle::Cup => (sfx.cup.clone(), 1.6_f32, "cup"),
+            (None, DiceContainerStyle::Box) => (sfx.box_.clone(), 2.2_f32, "box"),
+            (None, DiceContainerStyle::Cup) => (sfx.cup.clone(), 1.6_f32, "cup"),
         };
 
         debounce.last_played_s.insert(primary_die, now_s);
@@ -129,20 +180,9 @@ pub fn play_dice_container_collision_sfx(
             strength += strength_for(other);
         }
 
-        // Map strength -> volume. Keep a small floor so quiet collisions are still audible.
-        // Tunables:
-        // - `strength_ref`: roughly the velocity magnitude that should sound "full volume".
-        // - `min_volume`: audible floor.
-        // Slightly lower reference so typical die-die contacts are audible.
-        let strength_ref = 3.6_f32;
-        let min_volume = 0.08_f32;
-        let max_volume = 1.0_f32;
-        let t = (strength / strength_ref).clamp(0.0, 1.0);
-        let volume = min_volume + (max_volume - min_volume) * t.powf(0.7);
-
         // Global gain bump: collision SFX are easy to end up too quiet on some Windows setups.
         // Keep a clamp to avoid clipping when collisions are strong.
-        let volume = (volume * variant_gain).clamp(0.0, 1.0);
+        let volume = (impact_volume(strength) * variant_gain).clamp(0.0, 1.0) * gain;
 
         #[cfg(debug_assertions)]
         {
@@ -153,13 +193,51 @@ pub fn play_dice_container_collision_sfx(
             );
         }
 
-        // Non-spatial: collision SFX were easy to miss when the camera/listener is far
-        // from the container (attenuation can make them effectively silent).
-        commands.spawn((
-            AudioPlayer(sound),
-            PlaybackSettings::DESPAWN
-                .with_spatial(false)
-                .with_volume(Volume::Linear(volume)),
-        ));
+        play_sound(&mut commands, sound, volume);
+    }
+}
+
+/// Clack when the dice of a roll come to rest, and a sting when a d20 shows
+/// a natural 20.
+pub fn play_roll_outcome_sfx(
+    mut commands: Commands,
+    settings_state: Res<SettingsState>,
+    sfx: Res<DiceCollisionSfx>,
+    mut settled: MessageReader<DiceSettled>,
+    mut resolved: MessageReader<RollResolved>,
+) {
+    let gain = settings_state.settings.sound_gain();
+    let settled_count = settled.read().count();
+    let critical = resolved.read().any(|roll| {
+        roll.results
+            .iter()
+            .any(|(die_type, value)| *die_type == DiceType::D20 && *value == 20)
+    });
+    if gain <= 0.0 {
+        return;
+    }
+
+    if settled_count > 0 {
+        play_sound(&mut commands, sfx.settle.clone(), 0.7 * gain);
+    }
+    if critical {
+        play_sound(&mut commands, sfx.critical.clone(), gain);
+    }
+}
+
+/// Beep when a timed turn is about to run out, louder when it has.
+pub fn play_turn_timer_sfx(
+    mut commands: Commands,
+    settings_state: Res<SettingsState>,
+    sfx: Res<DiceCollisionSfx>,
+    mut warnings: MessageReader<TurnTimerWarning>,
+) {
+    let gain = settings_state.settings.sound_gain();
+    for warning in warnings.read() {
+        if gain <= 0.0 {
+            continue;
+        }
+        let volume = if warning.expired { 1.0 } else { 0.6 };
+        play_sound(&mut commands, sfx.turn_warning.clone(), volume * gain);
     }
 }
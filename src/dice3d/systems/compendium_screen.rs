@@ -0,0 +1,445 @@
+//! Monsters tab
+//!
+//! Searches the SRD monster compendium (`MonsterCompendium`) by name or
+//! type and shows the picked monster's stat block. Its attacks, their damage
+//! and its saving throws roll in the 3D scene with one click, through the
+//! same queue as macros. The tab is an extension tab (`COMPENDIUM_TAB_ID`)
+//! added by `CompendiumPlugin`.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::{start_queued_roll, MacroPlaybackParams};
+use crate::dice3d::types::*;
+
+/// Id of the Monsters tab
+pub const COMPENDIUM_TAB_ID: &str = "monsters";
+
+/// Most search results listed at once
+const MAX_RESULTS: usize = 40;
+
+/// The search and the monster shown on the Monsters tab
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CompendiumView {
+    pub query: String,
+    /// Name of the monster whose stat block is shown
+    pub selected: Option<String>,
+}
+
+/// Node the tab's content is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct CompendiumScreenBody;
+
+/// The tab's search field
+#[derive(Component)]
+pub struct CompendiumSearchField;
+
+/// Buttons of the Monsters tab
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum CompendiumButton {
+    /// Show the stat block of the monster with this name
+    Select(String),
+    /// Roll the attack of the shown monster's action at this index
+    Attack(usize),
+    /// Roll the damage of the shown monster's action at this index
+    Damage(usize),
+    /// Roll the shown monster's saving throw of this ability
+    Save(&'static str),
+    /// Add one of the shown monster to the Encounter tab's encounter
+    AddToEncounter,
+}
+
+/// Spawn the (hidden) Monsters tab.
+pub fn setup_compendium_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                row_gap: Val::Px(12.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            ExtensionTabRoot(COMPENDIUM_TAB_ID),
+        ))
+        .with_children(|parent| {
+            spawn_compendium_text(
+                parent,
+                "Monster Compendium".to_string(),
+                28.0,
+                theme.on_surface,
+            );
+            parent
+                .spawn(Node {
+                    width: Val::Px(360.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label("Search monsters")
+                        .placeholder("goblin, undead, dragon...")
+                        .auto_focus(false)
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(slot, &theme, builder, CompendiumSearchField);
+                });
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        CompendiumScreenBody,
+                    ));
+                });
+            spawn_compendium_text(
+                parent,
+                SRD_ATTRIBUTION.to_string(),
+                12.0,
+                theme.on_surface_variant,
+            );
+        });
+}
+
+/// Track the search, show picked monsters and roll their attacks and saves.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_compendium_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    search_fields: Query<(), With<CompendiumSearchField>>,
+    buttons: Query<&CompendiumButton>,
+    compendium: Res<MonsterCompendium>,
+    mut view: ResMut<CompendiumView>,
+    mut encounter: ResMut<Encounter>,
+    mut ui_state: ResMut<UiState>,
+    mut params: MacroPlaybackParams,
+) {
+    for ev in change_events.read() {
+        if search_fields.get(ev.entity).is_ok() && view.query != ev.value {
+            view.query = ev.value.clone();
+        }
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        if let CompendiumButton::Select(name) = button {
+            view.selected = Some(name.clone());
+            continue;
+        }
+        let Some(monster) = view
+            .selected
+            .as_deref()
+            .and_then(|name| compendium.find(name).ok())
+        else {
+            continue;
+        };
+        let config = match button {
+            CompendiumButton::Attack(index) => monster
+                .actions
+                .get(*index)
+                .and_then(|action| monster.attack_config(action))
+                .map(Ok),
+            CompendiumButton::Damage(index) => monster
+                .actions
+                .get(*index)
+                .and_then(|action| monster.damage_config(action)),
+            CompendiumButton::Save(ability) => monster.save_config(ability).map(Ok),
+            CompendiumButton::AddToEncounter => {
+                encounter.add(&monster.name, monster.xp);
+                params.snackbar.write(
+                    ShowSnackbar::message(format!(
+                        "{} added to the encounter ({} monsters)",
+                        monster.name,
+                        encounter.monster_count()
+                    ))
+                    .duration(2.0),
+                );
+                None
+            }
+            CompendiumButton::Select(_) => None,
+        };
+        let config = match config {
+            Some(Ok(config)) => config,
+            Some(Err(e)) => {
+                params
+                    .snackbar
+                    .write(ShowSnackbar::message(e).duration(2.5));
+                continue;
+            }
+            None => continue,
+        };
+        if params.roll_state.rolling || params.lid_ctrl.pending_roll.is_some() {
+            params
+                .snackbar
+                .write(ShowSnackbar::message("Wait for the dice to settle").duration(2.0));
+            continue;
+        }
+        start_queued_roll(&mut params, config, RollSource::Compendium);
+        // Watch the dice; the stat block stays on the Monsters tab
+        ui_state.active_tab = AppTab::DiceRoller;
+    }
+}
+
+/// Rebuild the search results and the stat block when the view changes.
+pub fn update_compendium_screen(
+    mut commands: Commands,
+    view: Res<CompendiumView>,
+    compendium: Res<MonsterCompendium>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<CompendiumScreenBody>>,
+    mut built: Local<bool>,
+) {
+    if *built && !view.is_changed() && !compendium.is_changed() {
+        return;
+    }
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    *built = true;
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let results = compendium.search(&view.query);
+    let selected = view
+        .selected
+        .as_deref()
+        .and_then(|name| compendium.find(name).ok());
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        if results.is_empty() {
+            spawn_compendium_text(
+                body,
+                format!("No monsters match '{}'.", view.query.trim()),
+                15.0,
+                theme.on_surface_variant,
+            );
+        }
+        spawn_button_row(body, |row| {
+            for monster in results.iter().take(MAX_RESULTS) {
+                spawn_compendium_button(
+                    row,
+                    &format!("{} (CR {})", monster.name, monster.cr),
+                    selected.is_some_and(|s| s.name == monster.name),
+                    CompendiumButton::Select(monster.name.clone()),
+                    &theme,
+                );
+            }
+        });
+        if results.len() > MAX_RESULTS {
+            spawn_compendium_text(
+                body,
+                format!(
+                    "{} more; type more of the name to narrow the list.",
+                    results.len() - MAX_RESULTS
+                ),
+                14.0,
+                theme.on_surface_variant,
+            );
+        }
+
+        if let Some(monster) = selected {
+            spawn_stat_block(body, monster, &theme);
+        }
+    });
+}
+
+fn spawn_stat_block(parent: &mut ChildSpawnerCommands, monster: &Monster, theme: &MaterialTheme) {
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(theme.surface_container),
+            BorderRadius::all(Val::Px(CornerRadius::MEDIUM)),
+        ))
+        .with_children(|card| {
+            spawn_compendium_text(card, monster.name.clone(), 24.0, theme.primary);
+            spawn_compendium_text(card, monster.type_line(), 14.0, theme.on_surface_variant);
+            for (label, value) in [
+                ("Armor Class", monster.armor_line()),
+                (
+                    "Hit Points",
+                    format!("{} ({})", monster.hit_points, monster.hit_dice),
+                ),
+                ("Speed", monster.speed.clone()),
+            ] {
+                spawn_compendium_text(
+                    card,
+                    format!("{}: {}", label, value),
+                    15.0,
+                    theme.on_surface,
+                );
+            }
+            spawn_compendium_text(
+                card,
+                monster.ability_scores().join("   "),
+                15.0,
+                theme.on_surface,
+            );
+            for (label, value) in monster.details() {
+                spawn_compendium_text(
+                    card,
+                    format!("{}: {}", label, value),
+                    15.0,
+                    theme.on_surface,
+                );
+            }
+            spawn_compendium_text(
+                card,
+                format!("Challenge: {}", monster.challenge_line()),
+                15.0,
+                theme.on_surface,
+            );
+            spawn_button_row(card, |row| {
+                spawn_compendium_button(
+                    row,
+                    "Add to Encounter",
+                    false,
+                    CompendiumButton::AddToEncounter,
+                    theme,
+                );
+            });
+
+            spawn_compendium_text(card, "Saving Throws".to_string(), 18.0, theme.primary);
+            spawn_button_row(card, |row| {
+                for ability in ABILITY_NAMES {
+                    let bonus = monster.save_bonus(ability).unwrap_or_default();
+                    spawn_compendium_button(
+                        row,
+                        &format!("{} {:+}", ability_abbreviation(ability), bonus),
+                        false,
+                        CompendiumButton::Save(ability),
+                        theme,
+                    );
+                }
+            });
+
+            for feature in &monster.traits {
+                spawn_compendium_text(
+                    card,
+                    format!("{}. {}", feature.name, feature.text),
+                    14.0,
+                    theme.on_surface,
+                );
+            }
+
+            spawn_compendium_text(card, "Actions".to_string(), 18.0, theme.primary);
+            for (index, action) in monster.actions.iter().enumerate() {
+                spawn_compendium_text(
+                    card,
+                    format!("{}. {}", action.name, action.text),
+                    14.0,
+                    theme.on_surface,
+                );
+                if !action.is_rollable() {
+                    continue;
+                }
+                spawn_button_row(card, |row| {
+                    if let Some(bonus) = action.attack_bonus {
+                        spawn_compendium_button(
+                            row,
+                            &format!("{} {:+}", action.name, bonus),
+                            true,
+                            CompendiumButton::Attack(index),
+                            theme,
+                        );
+                    }
+                    if let Some(damage) = &action.damage {
+                        spawn_compendium_button(
+                            row,
+                            &format!("Damage {}", damage),
+                            false,
+                            CompendiumButton::Damage(index),
+                            theme,
+                        );
+                    }
+                });
+            }
+        });
+}
+
+fn spawn_button_row(
+    parent: &mut ChildSpawnerCommands,
+    children: impl FnOnce(&mut ChildSpawnerCommands),
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_compendium_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    button: CompendiumButton,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (material_button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent
+        .spawn((material_button, button))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(text_color),
+                ButtonLabel,
+            ));
+        });
+}
+
+fn spawn_compendium_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
@@ -1,7 +1,9 @@
 //! Contributors screen system
 //!
-//! This module displays GitHub contributors loaded from the bundled contributors asset.
+//! This module displays GitHub contributors loaded from the bundled contributors asset,
+//! and holds the achievements page (filled in by the `achievements` systems).
 
+use super::achievements::AchievementsPageBody;
 use super::avatar_loader::AvatarImage;
 use crate::dice3d::types::{
     ContributorCard, ContributorsData, ContributorsList, ContributorsScreenRoot, ContributorsState,
@@ -127,6 +129,19 @@ pub fn setup_contributors_screen(
                     ));
                 });
 
+            // Achievements page
+            parent.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+                AchievementsPageBody,
+            ));
+
             // Divider
             parent
                 .spawn(Node {
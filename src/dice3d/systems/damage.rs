@@ -0,0 +1,406 @@
+//! Damage types and damage application
+//!
+//! The campaign's damage types (see `DamageTypeRegistry`) are loaded per
+//! profile and edited from the command input:
+//! - `damagetypes` lists them
+//! - `damagetype <name> <#RRGGBB> [icon]` defines one or restyles it
+//! - `damagetype remove <name>` removes a custom one
+//! - `resist`, `immune` or `vulnerable <type>` toggles a defense of the
+//!   current character
+//! - `damage <amount> [type]` applies damage to the current character, and
+//!   `damage` on its own applies the last damage roll
+//!
+//! The Combat tab spawns a [`DamageDefensesPanel`]; it is filled in here with
+//! the character's defenses in their damage type's color and icon.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::icons::MaterialIconFont;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    normalize_damage_type, parse_damage_label, CharacterData, CharacterDatabase, DamageType,
+    DamageTypeRegistry, DefenseKind, RollResolved, TypedCommand, DAMAGE_TYPES_SETTING_KEY,
+};
+
+/// The current profile's damage types and the last damage rolled
+#[derive(Resource, Default)]
+pub struct DamageTypesState {
+    pub registry: DamageTypeRegistry,
+    /// Total and damage type of the last damage roll, for `damage`
+    pub last_roll: Option<(i32, Option<String>)>,
+}
+
+/// Node the Combat tab spawns for the character's damage defenses
+#[derive(Component)]
+pub struct DamageDefensesPanel;
+
+/// A damage command typed into the command input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DamageCommand {
+    /// Damage to the current character; `None` applies the last damage roll
+    Apply {
+        amount: Option<i32>,
+        damage_type: Option<String>,
+    },
+    ListTypes,
+    DefineType {
+        name: String,
+        color: String,
+        icon: Option<String>,
+    },
+    RemoveType(String),
+    ToggleDefense {
+        kind: DefenseKind,
+        damage_type: String,
+    },
+}
+
+impl TypedCommand for DamageCommand {
+    const NAME: &'static str = "damage";
+
+    /// Parse `damage [<amount> [type]]`, `damagetypes`, `damagetype ...` and
+    /// `resist`/`immune`/`vulnerable <type>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let rest = rest.trim();
+
+        match head.to_lowercase().as_str() {
+            "damage" => {
+                if rest.is_empty() {
+                    return Some(DamageCommand::Apply {
+                        amount: None,
+                        damage_type: None,
+                    });
+                }
+                let (amount, damage_type) =
+                    rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let amount: i32 = amount.parse().ok()?;
+                let damage_type = damage_type.trim();
+                Some(DamageCommand::Apply {
+                    amount: Some(amount),
+                    damage_type: (!damage_type.is_empty()).then(|| damage_type.to_string()),
+                })
+            }
+            "damagetypes" if rest.is_empty() => Some(DamageCommand::ListTypes),
+            "damagetype" => {
+                if rest.is_empty() {
+                    return Some(DamageCommand::ListTypes);
+                }
+                if let Some(name) = rest
+                    .strip_prefix("remove ")
+                    .or_else(|| rest.strip_prefix("delete "))
+                {
+                    return Some(DamageCommand::RemoveType(name.trim().to_string()));
+                }
+                // The name may have spaces; the color starts with '#'
+                let words: Vec<&str> = rest.split_whitespace().collect();
+                match words.iter().position(|w| w.starts_with('#')) {
+                    Some(index) => Some(DamageCommand::DefineType {
+                        name: words[..index].join(" "),
+                        color: words[index].to_string(),
+                        icon: words.get(index + 1).map(|icon| icon.to_string()),
+                    }),
+                    None => Some(DamageCommand::DefineType {
+                        name: rest.to_string(),
+                        color: String::new(),
+                        icon: None,
+                    }),
+                }
+            }
+            "resist" | "resistance" if !rest.is_empty() => Some(DamageCommand::ToggleDefense {
+                kind: DefenseKind::Resistance,
+                damage_type: rest.to_string(),
+            }),
+            "immune" | "immunity" if !rest.is_empty() => Some(DamageCommand::ToggleDefense {
+                kind: DefenseKind::Immunity,
+                damage_type: rest.to_string(),
+            }),
+            "vulnerable" | "vulnerability" if !rest.is_empty() => {
+                Some(DamageCommand::ToggleDefense {
+                    kind: DefenseKind::Vulnerability,
+                    damage_type: rest.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn unknown_type_message(damage_type: &str) -> String {
+    let name = normalize_damage_type(damage_type);
+    format!(
+        "Unknown damage type '{}'; define it with damagetype {} #RRGGBB",
+        name, name
+    )
+}
+
+fn save_registry(registry: &DamageTypeRegistry, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(DAMAGE_TYPES_SETTING_KEY, registry.clone()) {
+        warn!("Failed to save damage types: {}", e);
+    }
+}
+
+/// Apply a damage command, notifying the user.
+pub fn run_damage_command(
+    In(command): In<DamageCommand>,
+    mut state: ResMut<DamageTypesState>,
+    mut character_data: ResMut<CharacterData>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let character_data = &mut *character_data;
+    let message = match command {
+        DamageCommand::ListTypes => {
+            let names: Vec<String> = state.registry.all().into_iter().map(|t| t.name).collect();
+            format!("Damage types: {}", names.join(", "))
+        }
+        DamageCommand::DefineType { name, color, icon } => {
+            match state.registry.define(&name, &color, icon.as_deref()) {
+                Ok(is_new) => {
+                    save_registry(&state.registry, &db);
+                    format!(
+                        "{} damage type {}",
+                        if is_new { "Added" } else { "Restyled" },
+                        normalize_damage_type(&name)
+                    )
+                }
+                Err(e) => e,
+            }
+        }
+        DamageCommand::RemoveType(name) => {
+            let name = normalize_damage_type(&name);
+            if state.registry.remove(&name) {
+                save_registry(&state.registry, &db);
+                if DamageTypeRegistry::is_standard(&name) {
+                    format!("{} has its standard look again", name)
+                } else {
+                    format!("Removed damage type {}", name)
+                }
+            } else {
+                format!("No custom damage type named {}", name)
+            }
+        }
+        DamageCommand::ToggleDefense { kind, damage_type } => {
+            if !state.registry.is_known(&damage_type) {
+                unknown_type_message(&damage_type)
+            } else if let Some(sheet) = character_data.sheet.as_mut() {
+                let added = sheet.damage_defenses.toggle(kind, &damage_type);
+                character_data.is_modified = true;
+                character_data.needs_refresh = true;
+                format!(
+                    "{} {} {} {}",
+                    sheet.character.name,
+                    if added { "gains" } else { "loses" },
+                    normalize_damage_type(&damage_type),
+                    kind.name()
+                )
+            } else {
+                "No character loaded".to_string()
+            }
+        }
+        DamageCommand::Apply {
+            amount,
+            damage_type,
+        } => apply_damage(amount, damage_type, &state, character_data),
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Damage the current character; returns the message to show.
+fn apply_damage(
+    amount: Option<i32>,
+    damage_type: Option<String>,
+    state: &DamageTypesState,
+    character_data: &mut CharacterData,
+) -> String {
+    let (amount, damage_type) = match amount {
+        Some(amount) => (amount, damage_type),
+        None => match state.last_roll.clone() {
+            Some(last_roll) => last_roll,
+            None => return "No damage roll to apply yet".to_string(),
+        },
+    };
+    if let Some(damage_type) = &damage_type {
+        if !state.registry.is_known(damage_type) {
+            return unknown_type_message(damage_type);
+        }
+    }
+    let Some(sheet) = character_data.sheet.as_mut() else {
+        return "No character loaded".to_string();
+    };
+    if sheet.combat.hit_points.is_none() {
+        return format!("{} has no hit points to lose", sheet.character.name);
+    }
+
+    let taken = sheet.take_damage(amount, damage_type.as_deref());
+    character_data.is_modified = true;
+    character_data.needs_refresh = true;
+
+    let hp = sheet.combat.hit_points.clone().unwrap_or_default();
+    let mut message = format!(
+        "{} takes {}{} damage",
+        sheet.character.name,
+        taken.adjusted.amount,
+        damage_type
+            .map(|t| format!(" {}", normalize_damage_type(&t)))
+            .unwrap_or_default()
+    );
+    if let Some(note) = taken.adjusted.note() {
+        message.push_str(&format!(" ({})", note));
+    }
+    message.push_str(&format!(": {}/{} HP", hp.current, hp.maximum));
+    if taken.from_temporary > 0 {
+        message.push_str(&format!(", {} from temporary HP", taken.from_temporary));
+    }
+    if hp.current == 0 && taken.from_hit_points > 0 {
+        message.push_str(&format!(". {} is down", sheet.character.name));
+    }
+    message
+}
+
+/// Load the damage types whenever the database is opened or the profile
+/// switches.
+pub fn load_damage_types(db: Option<Res<CharacterDatabase>>, mut state: ResMut<DamageTypesState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.registry = db
+        .get_setting::<DamageTypeRegistry>(DAMAGE_TYPES_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Remember the last damage roll so `damage` can apply it.
+pub fn remember_damage_rolls(
+    mut resolved: MessageReader<RollResolved>,
+    mut state: ResMut<DamageTypesState>,
+) {
+    for roll in resolved.read() {
+        if let Some((_, damage_type)) = parse_damage_label(&roll.label) {
+            state.last_roll = Some((roll.total.max(0), damage_type));
+        }
+    }
+}
+
+/// Fill in the damage defenses of the Combat tab when it is spawned and
+/// whenever the damage types change.
+pub fn update_damage_defenses_panel(
+    mut commands: Commands,
+    state: Res<DamageTypesState>,
+    character_data: Res<CharacterData>,
+    icon_font: Res<MaterialIconFont>,
+    theme: Option<Res<MaterialTheme>>,
+    panels: Query<(Entity, Ref<DamageDefensesPanel>)>,
+) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let Some(sheet) = character_data.sheet.as_ref() else {
+        return;
+    };
+
+    for (panel, marker) in panels.iter() {
+        if !state.is_changed() && !marker.is_added() {
+            continue;
+        }
+        commands.entity(panel).despawn_related::<Children>();
+        commands.entity(panel).with_children(|panel| {
+            for kind in [
+                DefenseKind::Resistance,
+                DefenseKind::Immunity,
+                DefenseKind::Vulnerability,
+            ] {
+                let names = sheet.damage_defenses.list(kind);
+                if names.is_empty() {
+                    continue;
+                }
+                panel
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(6.0),
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(kind.label()),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(theme.on_surface_variant),
+                        ));
+                        for name in names {
+                            let damage_type = state.registry.get(name);
+                            spawn_damage_type_chip(
+                                row,
+                                name,
+                                damage_type.as_ref(),
+                                icon_font.0.clone(),
+                                &theme,
+                            );
+                        }
+                    });
+            }
+        });
+    }
+}
+
+/// A damage type in its color, with its icon when the icon font has it.
+fn spawn_damage_type_chip(
+    parent: &mut ChildSpawnerCommands,
+    name: &str,
+    damage_type: Option<&DamageType>,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let color = damage_type
+        .and_then(DamageType::rgb)
+        .map(|[r, g, b]| Color::srgb_u8(r, g, b))
+        .unwrap_or(theme.outline);
+    let icon = damage_type.and_then(|t| MaterialIcon::from_name(&t.icon));
+
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(4.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(color.with_alpha(0.15)),
+            BorderRadius::all(Val::Px(CornerRadius::SMALL)),
+        ))
+        .with_children(|chip| {
+            if let Some(icon) = icon {
+                chip.spawn((
+                    Text::new(icon.as_str()),
+                    TextFont {
+                        font: icon_font,
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
+            chip.spawn((
+                Text::new(name),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+            ));
+        });
+}
@@ -4,28 +4,278 @@
 //! determining dice results, and updating the results display.
 
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy::winit::{UpdateMode, WinitSettings};
+use bevy_material_ui::prelude::MaterialTheme;
 use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+use std::time::Duration;
 
+use super::setup::{calculate_dice_position, spawn_die, PERCENTILE_PAIR_SPACING};
+use crate::dice3d::meshes::DiceMeshCache;
 use crate::dice3d::types::*;
 
 /// Maximum time allowed for dice to roll before forcing a reset (in seconds)
 const MAX_ROLL_TIME: f32 = 10.0;
 
+/// A coin face tilted less than ~60 degrees from straight up counts as lying flat
+const COIN_FLAT_DOT: f32 = 0.5;
+/// Spin (rad/s) given to a coin standing on its rim to tip it over
+const COIN_TIP_SPIN: f32 = 4.0;
+
 /// Boundaries for detecting out-of-bounds dice
 const BOUNDS_MIN_Y: f32 = -5.0; // If dice falls below this, it's out of bounds
 const BOUNDS_MAX_DISTANCE: f32 = 10.0; // If dice is this far from center, it's out of bounds
 
+/// Switch Rapier between stepping once per frame and stepping at a fixed rate.
+///
+/// In fixed mode the elapsed time is split into equal steps (rendered with
+/// interpolation), so a throw simulates the same way at 30 or 144 fps.
+/// Seeded runs (`--seed`) always use fixed mode so their throws replay.
+pub fn apply_physics_timestep(
+    settings_state: Res<SettingsState>,
+    roll_rng: Res<RollRng>,
+    mut timestep: ResMut<TimestepMode>,
+    mut applied: Local<Option<bool>>,
+) {
+    let fixed = settings_state.settings.fixed_timestep_physics || roll_rng.is_seeded();
+    if *applied == Some(fixed) {
+        return;
+    }
+
+    *timestep = if fixed {
+        TimestepMode::Interpolated {
+            dt: AppSettings::FIXED_PHYSICS_TIMESTEP,
+            time_scale: 1.0,
+            substeps: 1,
+        }
+    } else {
+        TimestepMode::default()
+    };
+    info!(
+        "Physics timestep: {}",
+        if fixed { "fixed" } else { "per frame" }
+    );
+    *applied = Some(fixed);
+}
+
+/// Pause the physics and dim the scene while the window is in the
+/// background, when `pause_when_unfocused` is on, and pick up where the dice
+/// left off once it has focus again. The app also updates less often in the
+/// background while the option is on.
+pub fn pause_physics_when_unfocused(
+    mut commands: Commands,
+    settings_state: Res<SettingsState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut rapier_config: Query<&mut RapierConfiguration>,
+    mut focus_pause: ResMut<FocusPause>,
+    winit_settings: Option<ResMut<WinitSettings>>,
+    overlays: Query<Entity, With<FocusPauseOverlay>>,
+    theme: Option<Res<MaterialTheme>>,
+) {
+    let enabled = settings_state.settings.pause_when_unfocused;
+    if settings_state.is_changed() {
+        if let Some(mut winit_settings) = winit_settings {
+            let unfocused_mode = if enabled {
+                UpdateMode::reactive_low_power(Duration::from_millis(250))
+            } else {
+                UpdateMode::Continuous
+            };
+            if winit_settings.unfocused_mode != unfocused_mode {
+                winit_settings.unfocused_mode = unfocused_mode;
+            }
+        }
+    }
+
+    let focused = windows.single().map(|w| w.focused).unwrap_or(true);
+    let paused = enabled && !focused;
+    if focus_pause.paused == paused {
+        return;
+    }
+    focus_pause.paused = paused;
+    for mut config in rapier_config.iter_mut() {
+        config.physics_pipeline_active = !paused;
+    }
+
+    if !paused {
+        for entity in overlays.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    let text_color = theme.map(|t| t.on_surface).unwrap_or(Color::WHITE);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                top: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.55)),
+            ZIndex(15_000),
+            Pickable::IGNORE,
+            FocusPauseOverlay,
+        ))
+        .with_children(|overlay| {
+            overlay.spawn((
+                Text::new("Paused in the background"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(text_color),
+            ));
+        });
+}
+
+/// How quickly a steered die's spin is pulled toward its target face.
+const STEER_GAIN: f32 = 9.0;
+/// Dice moving faster than this are left alone, so the throw still looks thrown.
+const STEER_MAX_SPEED: f32 = 5.0;
+/// Seconds into a roll after which dice are no longer steered, so one that
+/// could not be rolled over settles on the face it landed on.
+const STEER_GIVE_UP_SECONDS: f32 = 6.0;
+
+/// In "fair but pretty" mode, pick each new die's result before it lands.
+pub fn assign_steered_results(
+    mut commands: Commands,
+    dice: Query<(Entity, &Die), Added<Die>>,
+    settings_state: Res<SettingsState>,
+    mut rng: ResMut<RollRng>,
+) {
+    if !settings_state.settings.steered_dice {
+        return;
+    }
+    for (entity, die) in &dice {
+        let target = rng.random_range(1..=die.die_type.max_value());
+        commands.entity(entity).insert(SteeredDie { target });
+    }
+}
+
+/// The world direction of a die's target face and the rotation that would
+/// turn it up.
+fn target_face_up(face_normals: &[(Vec3, u32)], target: u32, rotation: Quat) -> Option<Quat> {
+    // A d4 lists one normal per value, other dice one per face
+    let normal = face_normals
+        .iter()
+        .find(|(_, value)| *value == target)
+        .map(|(normal, _)| rotation * *normal)?;
+    Some(Quat::from_rotation_arc(normal.normalize(), Vec3::Y))
+}
+
+/// Blend a die's spin toward the rotation that brings its `target` face up,
+/// harder the slower it moves.
+pub(super) fn steer_toward_face(
+    face_normals: &[(Vec3, u32)],
+    target: u32,
+    transform: &Transform,
+    velocity: &mut Velocity,
+    dt: f32,
+) {
+    let Some(correction) = target_face_up(face_normals, target, transform.rotation) else {
+        return;
+    };
+    let speed = velocity.linvel.length();
+    let weight = (1.0 - speed / STEER_MAX_SPEED).clamp(0.0, 1.0);
+    if weight <= 0.0 {
+        return;
+    }
+    let (axis, angle) = correction.to_axis_angle();
+    let wanted = if angle > 0.01 {
+        axis * angle * STEER_GAIN
+    } else {
+        Vec3::ZERO
+    };
+    let blend = (weight * STEER_GAIN * dt).min(1.0);
+    velocity.angvel = velocity.angvel.lerp(wanted, blend);
+}
+
+/// Steer dice with a preset result onto their target face as they slow down.
+///
+/// The spin is blended toward the rotation that brings the target face up,
+/// harder the slower the die moves, so fast tumbles stay natural and the die
+/// rolls over onto its face near the end instead of snapping. A die still on
+/// another face after `STEER_GIVE_UP_SECONDS` keeps the face it shows.
+pub fn steer_dice_to_targets(
+    roll_state: Res<RollState>,
+    mut dice: Query<(&Die, &SteeredDie, &Transform, &mut Velocity)>,
+    time: Res<Time>,
+) {
+    if !roll_state.rolling || roll_state.roll_timer > STEER_GIVE_UP_SECONDS {
+        return;
+    }
+    let dt = time.delta_secs();
+    for (die, steered, transform, mut velocity) in &mut dice {
+        steer_toward_face(
+            &die.face_normals,
+            steered.target,
+            transform,
+            &mut velocity,
+            dt,
+        );
+    }
+}
+
+/// Keep a d100's units die next to its tens die when a throw moves or
+/// launches the tens die after spawning, spinning it just as fast.
+pub fn match_percentile_throws(
+    mut dice: Query<(Ref<PercentileDie>, &mut Transform, &mut Velocity)>,
+) {
+    let throws: Vec<(Entity, Vec3, Velocity)> = dice
+        .iter()
+        .filter(|(percentile, ..)| percentile.is_added())
+        .filter_map(|(percentile, transform, velocity)| match *percentile {
+            PercentileDie::Tens { units } => Some((units, transform.translation, *velocity)),
+            PercentileDie::Units => None,
+        })
+        .collect();
+
+    for (units, tens_position, tens_velocity) in throws {
+        let Ok((_, mut transform, mut velocity)) = dice.get_mut(units) else {
+            continue;
+        };
+        transform.translation = tens_position + Vec3::X * PERCENTILE_PAIR_SPACING;
+        velocity.linvel = tens_velocity.linvel;
+        velocity.angvel = velocity.angvel.normalize_or_zero() * tens_velocity.angvel.length();
+    }
+}
+
 /// System to check if dice have settled and determine results
 /// Also handles dice that fall out of bounds or take too long
+#[allow(clippy::too_many_arguments)]
 pub fn check_dice_settled(
     mut roll_state: ResMut<RollState>,
     mut dice_results: ResMut<DiceResults>,
-    mut dice_query: Query<(Entity, &Die, &mut Velocity, &mut Transform)>,
+    dice_config: Res<DiceConfig>,
+    mut dice_query: Query<(
+        Entity,
+        &Die,
+        &mut Velocity,
+        &mut Transform,
+        Option<&ExplodingDie>,
+        Option<&PercentileDie>,
+    )>,
+    mut explosion_throws: ResMut<PendingExplosionThrows>,
     time: Res<Time>,
-    mut roll_complete_events: MessageWriter<DiceRollCompletedEvent>,
+    mut settled_events: MessageWriter<DiceSettled>,
+    mut anomalies: MessageWriter<PhysicsAnomaly>,
+    mut rng: ResMut<RollRng>,
+    mut rest_order: Local<Vec<Entity>>,
+    focus_pause: Res<FocusPause>,
 ) {
     if !roll_state.rolling {
         roll_state.roll_timer = 0.0;
+        rest_order.clear();
+        return;
+    }
+    // The dice hang where they are until the window has focus again
+    if focus_pause.paused {
         return;
     }
 
@@ -34,7 +284,7 @@ pub fn check_dice_settled(
 
     // Check for out-of-bounds dice and reset them
     let mut any_reset = false;
-    for (_, _, mut velocity, mut transform) in dice_query.iter_mut() {
+    for (_, _, mut velocity, mut transform, ..) in dice_query.iter_mut() {
         let pos = transform.translation;
 
         // Check if dice is out of bounds
@@ -46,9 +296,6 @@ pub fn check_dice_settled(
 
         if is_out_of_bounds {
             // Reset dice to center of box with random position
-            let mut rng = rand::rng();
-            use rand::Rng;
-
             transform.translation = Vec3::new(
                 rng.random_range(-0.5..0.5),
                 0.5, // Above the floor
@@ -73,16 +320,16 @@ pub fn check_dice_settled(
 
     // If we reset any dice, restart the settle timer
     if any_reset {
+        anomalies.write(PhysicsAnomaly {
+            reason: ReproReason::OutOfBounds,
+        });
         roll_state.settle_timer = 0.0;
         return;
     }
 
     // Check for timeout - if rolling too long, force dice to drop into box
     if roll_state.roll_timer > MAX_ROLL_TIME {
-        let mut rng = rand::rng();
-        use rand::Rng;
-
-        for (_, _, mut velocity, mut transform) in dice_query.iter_mut() {
+        for (_, _, mut velocity, mut transform, ..) in dice_query.iter_mut() {
             // Reset all dice to center with gentle drop
             transform.translation = Vec3::new(
                 rng.random_range(-0.5..0.5),
@@ -93,43 +340,245 @@ pub fn check_dice_settled(
             velocity.angvel = Vec3::ZERO;
         }
 
+        anomalies.write(PhysicsAnomaly {
+            reason: ReproReason::Timeout,
+        });
         roll_state.roll_timer = 0.0;
         roll_state.settle_timer = 0.0;
         return;
     }
 
+    // The order the dice come to rest in; a die knocked moving again goes
+    // to the back of the line when it stops
+    for (entity, _, vel, ..) in dice_query.iter() {
+        let at_rest = vel.linvel.length() < 0.1 && vel.angvel.length() < 0.1;
+        match (at_rest, rest_order.iter().position(|e| *e == entity)) {
+            (true, None) => rest_order.push(entity),
+            (false, Some(index)) => {
+                rest_order.remove(index);
+            }
+            _ => {}
+        }
+    }
+
     let all_settled = dice_query
         .iter()
-        .all(|(_, _, vel, _)| vel.linvel.length() < 0.1 && vel.angvel.length() < 0.1);
+        .all(|(_, _, vel, ..)| vel.linvel.length() < 0.1 && vel.angvel.length() < 0.1);
 
     if all_settled {
         roll_state.settle_timer += time.delta_secs();
 
         if roll_state.settle_timer > 0.5 {
+            // A coin resting on its rim is tipped over rather than read
+            let mut tipped = false;
+            for (_, die, mut velocity, transform, ..) in dice_query.iter_mut() {
+                if die.die_type.is_coin() && coin_on_edge(die, &transform) {
+                    let axis = (transform.rotation * Vec3::Y)
+                        .cross(Vec3::Y)
+                        .normalize_or(Vec3::X);
+                    velocity.angvel = axis * COIN_TIP_SPIN;
+                    tipped = true;
+                }
+            }
+            if tipped {
+                roll_state.settle_timer = 0.0;
+                return;
+            }
+
+            let faces: HashMap<Entity, u32> = dice_query
+                .iter()
+                .map(|(entity, die, _, transform, ..)| {
+                    (entity, determine_dice_result(die, transform))
+                })
+                .collect();
+
+            // A d100 is read from both of its dice; when its units die can't
+            // be read the tens die is thrown again instead of guessing a face
+            let unread_pairs: Vec<Entity> = dice_query
+                .iter()
+                .filter_map(|(entity, .., percentile)| match percentile {
+                    Some(PercentileDie::Tens { units }) if !faces.contains_key(units) => {
+                        warn!(
+                            "Units die {:?} of d100 tens die {:?} could not be read, throwing again",
+                            units, entity
+                        );
+                        Some(entity)
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !unread_pairs.is_empty() {
+                for (entity, _, mut velocity, mut transform, ..) in dice_query.iter_mut() {
+                    if !unread_pairs.contains(&entity) {
+                        continue;
+                    }
+                    transform.translation = Vec3::new(
+                        rng.random_range(-0.5..0.5),
+                        0.5,
+                        rng.random_range(-0.5..0.5),
+                    );
+                    velocity.linvel = Vec3::new(0.0, -1.0, 0.0);
+                    velocity.angvel = Vec3::new(
+                        rng.random_range(-2.0..2.0),
+                        rng.random_range(-2.0..2.0),
+                        rng.random_range(-2.0..2.0),
+                    );
+                }
+                roll_state.settle_timer = 0.0;
+                return;
+            }
+
             roll_state.rolling = false;
             roll_state.settle_timer = 0.0;
             roll_state.roll_timer = 0.0;
 
-            dice_results.results.clear();
+            // Settling after exploding dice were thrown again: keep the
+            // numeric rolls from before, re-read every physical die.
+            let rethrow_round = dice_query
+                .iter()
+                .any(|(_, _, _, _, exploding, ..)| exploding.is_some());
+            let kept_virtual: Vec<(DiceType, u32)> = dice_results
+                .results
+                .iter()
+                .copied()
+                .filter(|(die, _)| !die.is_physical())
+                .collect();
+            let kept_explosions: Vec<ExplosionResult> = dice_results
+                .explosions
+                .iter()
+                .copied()
+                .filter(|e| !e.die.is_physical())
+                .collect();
+            dice_results.clear();
 
             let mut outcomes: Vec<DieRollOutcome> = Vec::new();
-            for (entity, die, _, transform) in dice_query.iter() {
-                let result = determine_dice_result(die, transform);
-                dice_results.results.push((die.die_type, result));
+            let mut result_dice: Vec<Entity> = Vec::new();
+            let mut physical_explosions: Vec<(usize, ExplosionResult)> = Vec::new();
+            for (entity, die, _, _, exploding, percentile) in dice_query.iter() {
+                let face = faces[&entity];
+                // The two d10s of a d100 give one result, read with the tens die
+                let (die_type, result) = match percentile {
+                    None => (die.die_type, face),
+                    Some(PercentileDie::Units) => continue,
+                    Some(PercentileDie::Tens { units }) => {
+                        let value = percentile_result(face, faces[units]);
+                        outcomes.push(DieRollOutcome {
+                            entity: *units,
+                            die_type: DiceType::Custom(100),
+                            value,
+                        });
+                        (DiceType::Custom(100), value)
+                    }
+                };
+                match exploding {
+                    Some(exploding) => physical_explosions.push((
+                        exploding.roll,
+                        ExplosionResult {
+                            chain: exploding.chain,
+                            die: die_type,
+                            value: result,
+                        },
+                    )),
+                    None => {
+                        dice_results.results.push((die_type, result));
+                        result_dice.push(entity);
+                    }
+                }
                 outcomes.push(DieRollOutcome {
                     entity,
-                    die_type: die.die_type,
+                    die_type,
                     value: result,
                 });
             }
+            physical_explosions.sort_by_key(|(roll, e)| (e.chain, *roll));
+            dice_results.settle_order = rest_order
+                .drain(..)
+                .filter_map(|entity| result_dice.iter().position(|e| *e == entity))
+                .collect();
+
+            // Dice without a 3D model (d2, d3, ...) are rolled numerically.
+            if rethrow_round {
+                dice_results.results.extend(kept_virtual);
+                dice_results.explosions.extend(kept_explosions);
+            } else {
+                for die_type in dice_config.virtual_dice() {
+                    let value = rng.random_range(1..=die_type.max_value());
+                    dice_results.results.push((die_type, value));
+                }
+            }
+            dice_results
+                .explosions
+                .extend(physical_explosions.into_iter().map(|(_, e)| e));
+
+            if let Some(expression) = dice_config.expression.as_ref().filter(|e| e.explodes()) {
+                let DiceResults {
+                    results,
+                    explosions,
+                } = &mut *dice_results;
+                expression.roll_pending_explosions(results, explosions, |die| {
+                    (!die.is_physical()).then(|| rng.random_range(1..=die.max_value()))
+                });
 
-            roll_complete_events.write(DiceRollCompletedEvent { results: outcomes });
+                // Exploded physical dice are thrown again before the roll completes
+                let pending = expression.pending_explosions(results, explosions);
+                if !pending.is_empty() {
+                    for (chain, die) in pending {
+                        let roll = explosions.iter().filter(|e| e.chain == chain).count();
+                        explosion_throws
+                            .dice
+                            .push((ExplodingDie { chain, roll }, die));
+                    }
+                    roll_state.rolling = true;
+                    return;
+                }
+            }
+
+            settled_events.write(DiceSettled { results: outcomes });
         }
     } else {
         roll_state.settle_timer = 0.0;
     }
 }
 
+/// Throw exploding dice queued by `check_dice_settled` into the box.
+pub fn throw_exploding_dice(
+    mut commands: Commands,
+    mut explosion_throws: ResMut<PendingExplosionThrows>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut mesh_cache: ResMut<DiceMeshCache>,
+    settings_state: Res<SettingsState>,
+    mut rng: ResMut<RollRng>,
+) {
+    if explosion_throws.dice.is_empty() {
+        return;
+    }
+
+    let throws = std::mem::take(&mut explosion_throws.dice);
+    let count = throws.len();
+    for (i, (exploding, die_type)) in throws.into_iter().enumerate() {
+        let die_scale = settings_state.settings.dice_scales.scale_for(die_type);
+        let entity = spawn_die(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut mesh_cache,
+            die_type,
+            die_scale,
+            calculate_dice_position(i, count),
+            &mut *rng,
+        );
+        commands.entity(entity).insert(exploding);
+    }
+}
+
+/// Whether a coin came to rest on its rim: neither face points up.
+fn coin_on_edge(die: &Die, transform: &Transform) -> bool {
+    die.face_normals
+        .iter()
+        .all(|(normal, _)| (transform.rotation * *normal).dot(Vec3::Y) < COIN_FLAT_DOT)
+}
+
 /// Determine the upward-facing value of a die based on its rotation
 fn determine_dice_result(die: &Die, transform: &Transform) -> u32 {
     let up = Vec3::Y;
@@ -151,12 +600,26 @@ fn determine_dice_result(die: &Die, transform: &Transform) -> u32 {
 
 /// System to update the results display text
 pub fn update_results_display(
+    mut resolved_events: MessageReader<RollResolved>,
+    mut cancelled_events: MessageReader<RollCancelled>,
+    mut last_resolved: Local<Option<RollResolved>>,
+    mut last_cancelled: Local<Option<String>>,
     dice_results: Res<DiceResults>,
     roll_state: Res<RollState>,
     dice_config: Res<DiceConfig>,
     character_data: Res<CharacterData>,
+    settings_state: Res<SettingsState>,
+    quick_roll_dc: Res<QuickRollDc>,
     mut text_query: Query<&mut Text, With<ResultsText>>,
 ) {
+    if let Some(resolved) = resolved_events.read().last() {
+        *last_resolved = Some(resolved.clone());
+        *last_cancelled = None;
+    }
+    if let Some(cancelled) = cancelled_events.read().last() {
+        *last_cancelled = Some(cancelled.label());
+    }
+
     for mut text in text_query.iter_mut() {
         // Character info header
         let char_info = if let Some(sheet) = &character_data.sheet {
@@ -171,76 +634,214 @@ pub fn update_results_display(
             String::from("No character loaded\n")
         };
 
+        let resolved = last_resolved
+            .as_ref()
+            .filter(|_| !dice_results.results.is_empty());
         if roll_state.rolling {
             **text = format!("{}Rolling...", char_info);
-        } else if dice_results.results.is_empty() {
+        } else if let Some(label) = last_cancelled.as_ref().filter(|_| resolved.is_none()) {
+            **text = format!(
+                "{}Roll cancelled: {}\n\nType a command or pick a quick roll to roll again",
+                char_info, label
+            );
+        } else if let Some(resolved) = resolved {
+            **text = format!(
+                "{}{}",
+                char_info,
+                format_resolved_roll(
+                    resolved,
+                    settings_state.settings.result_order,
+                    quick_roll_dc.0
+                )
+            );
+        } else {
             let modifier_info = format_modifier_info(&dice_config);
             **text = format!(
                 "{}{}\nLeft-click inside the box to roll dice\nPress R to reset",
                 char_info, modifier_info
             );
+        }
+    }
+}
+
+/// Results text of a finished roll, its dice listed in `order`; a d20
+/// check is compared with `dc`, if there is one.
+fn format_resolved_roll(resolved: &RollResolved, order: ResultOrder, dc: Option<i32>) -> String {
+    if let Some(outcome) = resolved.pool {
+        return format_pool_roll(resolved, outcome);
+    }
+    let mut result_text = String::from("Results:\n");
+
+    if order == ResultOrder::GroupedByType {
+        result_text.push_str(&format_grouped_results(&resolved.results));
+    } else {
+        for (die_type, value) in order.arrange(&resolved.results, &resolved.settle_order) {
+            let face = if die_type.is_coin() {
+                coin_side(value).to_string()
+            } else if die_type == DiceType::Fudge {
+                die_type.face_text(value)
+            } else {
+                value.to_string()
+            };
+            result_text.push_str(&format!("{}: {}\n", die_type.name(), face));
+        }
+    }
+
+    let total = resolved.dice_total;
+    if let Some(expression) = &resolved.expression {
+        let details = resolved.details();
+        if details.is_empty() {
+            result_text.push_str(&format!("{} = {}\n", expression, total));
         } else {
-            let mut result_text = format!("{}Results:\n", char_info);
-            let mut total = 0i32;
-
-            // Group results by die type using BTreeMap for stable ordering
-            let mut grouped: std::collections::BTreeMap<u32, (DiceType, Vec<u32>)> =
-                std::collections::BTreeMap::new();
-            for (die_type, value) in &dice_results.results {
-                // Key by max_value for consistent ordering (D4=4, D6=6, etc.)
-                let key = die_type.max_value();
-                grouped
-                    .entry(key)
-                    .or_insert_with(|| (*die_type, Vec::new()))
-                    .1
-                    .push(*value);
-            }
+            result_text.push_str(&format!(
+                "{} = {} ({})\n",
+                expression,
+                total,
+                details.join("; ")
+            ));
+        }
+    }
 
-            // Sort values within each group for consistent display
-            for (_die_type, values) in grouped.values_mut() {
-                values.sort();
-            }
+    // Apply modifier
+    let modifier = resolved.modifier;
+    if modifier != 0 {
+        let sign = if modifier >= 0 { "+" } else { "" };
+        let mod_name = if !resolved.label.is_empty() {
+            format!(" ({})", resolved.label)
+        } else {
+            String::new()
+        };
+        result_text.push_str(&format!(
+            "\nDice Total: {}\nModifier{}: {}{}\n\nFINAL TOTAL: {}",
+            total, mod_name, sign, modifier, resolved.total
+        ));
+    } else {
+        result_text.push_str(&format!("\nTOTAL: {}", total));
+    }
 
-            for (die_type, values) in grouped.values() {
-                let sum: u32 = values.iter().sum();
-                total += sum as i32;
-                if values.len() == 1 {
-                    result_text.push_str(&format!("{}: {}\n", die_type.name(), values[0]));
-                } else {
-                    let values_str: Vec<String> = values.iter().map(|v| v.to_string()).collect();
-                    result_text.push_str(&format!(
-                        "{}x{}: {} = {}\n",
-                        values.len(),
-                        die_type.name(),
-                        values_str.join(" + "),
-                        sum
-                    ));
-                }
-            }
+    if let (Some(dc), Some(_)) = (dc, resolved.natural_d20) {
+        let check = DcResult::new(resolved.total, dc);
+        result_text.push_str(&format!("\n{}", check.describe()));
+    }
 
-            // Apply modifier
-            let modifier = dice_config.modifier;
-            let final_total = total + modifier;
+    result_text.push_str("\n\nLeft-click inside the box to roll again\nPress R to reset");
+    result_text
+}
 
-            if modifier != 0 {
-                let sign = if modifier >= 0 { "+" } else { "" };
-                let mod_name = if !dice_config.modifier_name.is_empty() {
-                    format!(" ({})", dice_config.modifier_name)
-                } else {
-                    String::new()
-                };
+/// One line per die type ("3xD6: 1 + 4 + 6 = 11"), smallest die first
+fn format_grouped_results(results: &[(DiceType, u32)]) -> String {
+    let mut result_text = String::new();
+
+    // Group results by die type using BTreeMap for stable ordering
+    let mut grouped: std::collections::BTreeMap<(u32, bool), (DiceType, Vec<u32>)> =
+        std::collections::BTreeMap::new();
+    for (die_type, value) in results {
+        // Key by max_value for consistent ordering (D4=4, D6=6, etc.); a
+        // Fudge die has three faces like a d3 but is its own group
+        let key = (die_type.max_value(), *die_type == DiceType::Fudge);
+        grouped
+            .entry(key)
+            .or_insert_with(|| (*die_type, Vec::new()))
+            .1
+            .push(*value);
+    }
+
+    // Sort values within each group for consistent display
+    for (_die_type, values) in grouped.values_mut() {
+        values.sort();
+    }
+
+    for (die_type, values) in grouped.values() {
+        let sum: u32 = values.iter().sum();
+        if die_type.is_coin() {
+            let sides: Vec<&str> = values.iter().map(|v| coin_side(*v)).collect();
+            if values.len() == 1 {
+                result_text.push_str(&format!("Coin: {}\n", sides[0]));
+            } else {
+                let heads = values.iter().filter(|v| **v == 2).count();
                 result_text.push_str(&format!(
-                    "\nDice Total: {}\nModifier{}: {}{}\n\nFINAL TOTAL: {}",
-                    total, mod_name, sign, modifier, final_total
+                    "{}xCoin: {} ({} heads)\n",
+                    values.len(),
+                    sides.join(", "),
+                    heads
                 ));
-            } else {
-                result_text.push_str(&format!("\nTOTAL: {}", total));
             }
-
-            result_text.push_str("\n\nLeft-click inside the box to roll again\nPress R to reset");
-            **text = result_text;
+        } else if *die_type == DiceType::Fudge {
+            let symbols: Vec<String> = values.iter().map(|v| die_type.face_text(*v)).collect();
+            let sum: i32 = values.iter().map(|v| die_type.face_value(*v)).sum();
+            result_text.push_str(&format!(
+                "{}x{}: {} = {:+}\n",
+                values.len(),
+                die_type.name(),
+                symbols.join(" "),
+                sum
+            ));
+        } else if values.len() == 1 {
+            result_text.push_str(&format!("{}: {}\n", die_type.name(), values[0]));
+        } else {
+            let values_str: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            result_text.push_str(&format!(
+                "{}x{}: {} = {}\n",
+                values.len(),
+                die_type.name(),
+                values_str.join(" + "),
+                sum
+            ));
         }
     }
+    result_text
+}
+
+/// Results text of a dice pool roll: the faces, highest first, and the
+/// successes instead of a total
+fn format_pool_roll(resolved: &RollResolved, outcome: PoolOutcome) -> String {
+    let mut faces: Vec<u32> = resolved
+        .results
+        .iter()
+        .map(|(_, value)| *value)
+        .chain(resolved.explosions.iter().map(|e| e.value))
+        .collect();
+    faces.sort_unstable_by(|a, b| b.cmp(a));
+    let die = resolved
+        .results
+        .first()
+        .map(|(die, _)| die.name())
+        .unwrap_or_default();
+    let faces: Vec<String> = faces.iter().map(|v| v.to_string()).collect();
+    let mut result_text = format!(
+        "Results:\n{}x{}: {}\n",
+        resolved.results.len(),
+        die,
+        faces.join(" ")
+    );
+    for detail in resolved.details() {
+        result_text.push_str(&format!("{}\n", detail));
+    }
+    if outcome.ones > 0 {
+        result_text.push_str(&format!(
+            "{} hits, {} ones cancel\n",
+            outcome.hits, outcome.ones
+        ));
+    }
+
+    let modifier = resolved.modifier;
+    if modifier != 0 {
+        let label = if resolved.label.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", resolved.label)
+        };
+        result_text.push_str(&format!("\nAutomatic successes{}: {:+}\n", label, modifier));
+    }
+
+    if outcome.is_botch() && resolved.total <= 0 {
+        result_text.push_str("\nBOTCH");
+    } else {
+        result_text.push_str(&format!("\nSUCCESSES: {}", resolved.total.max(0)));
+    }
+
+    result_text.push_str("\n\nLeft-click inside the box to roll again\nPress R to reset");
+    result_text
 }
 
 /// Format modifier information for display
@@ -482,7 +482,6 @@ pub fn handle_dice_box_toggle_container_click(
     mut style: ResMut<DiceContainerStyle>,
     _materials: Res<DiceContainerMaterials>,
     asset_server: Res<AssetServer>,
-    _meshes: ResMut<Assets<Mesh>>,
     walls: Query<Entity, With<DiceBoxWall>>,
     floors: Query<Entity, With<DiceBoxFloorCollider>>,
     ceilings: Query<Entity, With<DiceBoxCeiling>>,
@@ -490,6 +489,7 @@ pub fn handle_dice_box_toggle_container_click(
     mut dice_query: Query<(&mut Transform, &mut Velocity), With<Die>>,
     mut shake_anim: ResMut<ContainerShakeAnimation>,
     mut spawn_points_applied: ResMut<DiceSpawnPointsApplied>,
+    mut rng: ResMut<RollRng>,
 ) {
     if ui_state.active_tab != AppTab::DiceRoller {
         return;
@@ -707,8 +707,8 @@ pub fn handle_dice_box_toggle_container_click(
     }
 
     // Re-drop dice into the middle of the new container style.
-    // Put them above the floor so gravity drops them naturally.
-    let mut rng = rand::rng();
+    // Put them above the floor so gravity drops them naturally, drawing
+    // from the roll generator so a seeded session redrops them the same way.
     let spawn_radius = 0.30;
     for (mut transform, mut velocity) in dice_query.iter_mut() {
         transform.translation = Vec3::new(
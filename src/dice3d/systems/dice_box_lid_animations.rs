@@ -1,12 +1,11 @@
-use bevy::animation::prelude::{AnimationGraph, AnimationGraphHandle, AnimationNodeIndex};
-use bevy::gltf::Gltf;
 use bevy::prelude::MessageReader;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::Velocity;
 use rand::Rng;
 
-use crate::dice3d::embedded_assets::BOX_MODEL_GLTF_PATH;
+use crate::dice3d::meshes::DiceMeshCache;
 use crate::dice3d::systems::dice_box_controls::start_container_shake;
+use crate::dice3d::systems::tween::find_named_entity_under;
 use crate::dice3d::systems::{calculate_dice_position, spawn_die};
 use crate::dice3d::throw_control::ThrowControlState;
 use crate::dice3d::types::*;
@@ -35,45 +34,8 @@ pub struct PendingRollExecutionParams<'w, 's> {
     pub dice_entities: Query<'w, 's, Entity, With<Die>>,
     pub meshes: ResMut<'w, Assets<Mesh>>,
     pub materials: ResMut<'w, Assets<StandardMaterial>>,
-}
-
-fn find_first_animation_player_under(
-    root: Entity,
-    children: &Query<&Children>,
-    players: &Query<(), With<AnimationPlayer>>,
-) -> Option<Entity> {
-    let mut stack: Vec<Entity> = vec![root];
-    while let Some(e) = stack.pop() {
-        if players.get(e).is_ok() {
-            return Some(e);
-        }
-        if let Ok(ch) = children.get(e) {
-            for child in ch.iter() {
-                stack.push(child);
-            }
-        }
-    }
-    None
-}
-
-fn find_all_animation_players_under(
-    root: Entity,
-    children: &Query<&Children>,
-    players: &Query<(), With<AnimationPlayer>>,
-) -> Vec<Entity> {
-    let mut found: Vec<Entity> = Vec::new();
-    let mut stack: Vec<Entity> = vec![root];
-    while let Some(e) = stack.pop() {
-        if players.get(e).is_ok() {
-            found.push(e);
-        }
-        if let Ok(ch) = children.get(e) {
-            for child in ch.iter() {
-                stack.push(child);
-            }
-        }
-    }
-    found
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
 }
 
 #[cfg(debug_assertions)]
@@ -88,12 +50,8 @@ fn debug_trace_lid_controller(ctrl: &mut DiceBoxLidAnimationController) {
 
     if state_changed || roll_changed || open_changed {
         info!(
-            "Lid ctrl: state={:?} timer={:.3} pending_roll={} pending_open_after_roll={} animator={:?}",
-            lid_state,
-            ctrl.state_timer,
-            pending_roll_some,
-            pending_open,
-            ctrl.animator_entity
+            "Lid ctrl: state={:?} pending_roll={} pending_open_after_roll={} lid={:?}",
+            lid_state, pending_roll_some, pending_open, ctrl.lid_entity
         );
 
         ctrl.debug_last_lid_state = Some(lid_state);
@@ -102,277 +60,101 @@ fn debug_trace_lid_controller(ctrl: &mut DiceBoxLidAnimationController) {
     }
 }
 
-fn clip_duration_seconds(clips: &Assets<AnimationClip>, handle: &Handle<AnimationClip>) -> f32 {
-    clips
-        .get(handle)
-        .map(|c| c.duration())
-        .unwrap_or(0.6)
-        .max(0.01)
-}
-
-fn play_once(
-    ctrl: &mut DiceBoxLidAnimationController,
-    players: &mut Query<&mut AnimationPlayer>,
-    node: AnimationNodeIndex,
-    duration_seconds: f32,
-    new_state: DiceBoxLidState,
-) {
-    let Some(e) = ctrl.animator_entity else {
-        return;
-    };
-    let Ok(mut player) = players.get_mut(e) else {
-        ctrl.animator_entity = None;
-        return;
-    };
-
-    // Ensure only one lid animation is active at once.
-    // (If the AnimationPlayer has any lingering tracks, stopping only known nodes can miss them.)
-    player.stop_all();
-
-    // We are about to play a transient one-shot, so no idle should be considered active.
-    ctrl.active_idle_node = None;
-
-    // Play from the start, once.
-    player.start(node);
-
-    ctrl.lid_state = new_state;
-    ctrl.state_timer = duration_seconds.max(0.01);
-}
-
-fn ensure_idle_looping(
-    ctrl: &mut DiceBoxLidAnimationController,
-    players: &mut Query<&mut AnimationPlayer>,
-) {
-    let Some(e) = ctrl.animator_entity else {
-        return;
-    };
-    let Ok(mut player) = players.get_mut(e) else {
-        ctrl.animator_entity = None;
+/// Start tweening the lid open or closed.
+///
+/// Without a lid node (scene still loading) the lid jumps straight to the
+/// final state; `cache_dice_box_lid_entity` snaps the pose once it appears.
+fn tween_lid(commands: &mut Commands, ctrl: &mut DiceBoxLidAnimationController, open: bool) {
+    let Some(lid) = ctrl.lid_entity else {
+        ctrl.lid_state = if open {
+            DiceBoxLidState::Open
+        } else {
+            DiceBoxLidState::Closed
+        };
         return;
     };
 
-    // Only keep idle running when we're not actively opening/closing.
-    if ctrl.lid_state == DiceBoxLidState::Opening || ctrl.lid_state == DiceBoxLidState::Closing {
-        if let Some(idle_opened) = ctrl.idle_opened_node {
-            player.stop(idle_opened);
-        }
-        if let Some(idle_closed) = ctrl.idle_closed_node {
-            player.stop(idle_closed);
-        }
-
-        #[cfg(debug_assertions)]
-        {
-            if ctrl.debug_last_idle_node.take().is_some() {
-                info!("Lid idle stopped (lid_state={:?})", ctrl.lid_state);
-            }
-        }
-
-        ctrl.active_idle_node = None;
-        return;
-    }
-
-    let desired_idle = match ctrl.lid_state {
-        DiceBoxLidState::Open => ctrl.idle_opened_node,
-        DiceBoxLidState::Closed => ctrl.idle_closed_node,
-        DiceBoxLidState::Opening | DiceBoxLidState::Closing => None,
+    // Opening overshoots a little before settling against the hinge; closing
+    // must not overshoot into the box.
+    let tween = if open {
+        TransformTween::between(
+            DICE_BOX_LID_CLOSED_POSE,
+            DICE_BOX_LID_OPEN_POSE,
+            DICE_BOX_LID_TWEEN_SECONDS,
+            Easing::EaseOutBack,
+        )
+    } else {
+        TransformTween::between(
+            DICE_BOX_LID_OPEN_POSE,
+            DICE_BOX_LID_CLOSED_POSE,
+            DICE_BOX_LID_TWEEN_SECONDS,
+            Easing::EaseInOutCubic,
+        )
     };
+    commands.entity(lid).insert(tween);
 
-    let Some(desired_idle) = desired_idle else {
-        return;
+    ctrl.lid_state = if open {
+        DiceBoxLidState::Opening
+    } else {
+        DiceBoxLidState::Closing
     };
-
-    // Only start/restart the idle loop when switching which idle we want.
-    // This avoids restarting at loop boundaries, which can look like flicker.
-    if ctrl.active_idle_node != Some(desired_idle) {
-        player.stop_all();
-        player.play(desired_idle).repeat();
-        ctrl.active_idle_node = Some(desired_idle);
-    }
-
-    #[cfg(debug_assertions)]
-    {
-        let last = ctrl.debug_last_idle_node;
-        if last != Some(desired_idle) {
-            let which = if ctrl.idle_opened_node == Some(desired_idle) {
-                "LidIdleOpened"
-            } else if ctrl.idle_closed_node == Some(desired_idle) {
-                "LidIdleClosed"
-            } else {
-                "(unknown idle)"
-            };
-            info!("Lid idle -> {} (lid_state={:?})", which, ctrl.lid_state);
-            ctrl.debug_last_idle_node = Some(desired_idle);
-        }
-    }
 }
 
-pub fn ensure_dice_box_lid_animation_assets(
-    asset_server: Res<AssetServer>,
-    gltfs: Res<Assets<Gltf>>,
-    clips: Res<Assets<AnimationClip>>,
-    mut graphs: ResMut<Assets<AnimationGraph>>,
-    mut ctrl: ResMut<DiceBoxLidAnimationController>,
-) {
-    if ctrl.gltf_handle.is_none() {
-        ctrl.gltf_handle = Some(asset_server.load(BOX_MODEL_GLTF_PATH));
-    }
-
-    let Some(h) = ctrl.gltf_handle.as_ref() else {
-        return;
-    };
-    let Some(gltf) = gltfs.get(h) else {
-        return;
-    };
-
-    // Prefer name-based lookup (Blender Action names).
-    if ctrl.opening_clip.is_none() {
-        if let Some(h) = gltf.named_animations.get("LidOpening") {
-            ctrl.opening_clip = Some(h.clone());
-        }
-    }
-    if ctrl.closing_clip.is_none() {
-        if let Some(h) = gltf.named_animations.get("LidClosing") {
-            ctrl.closing_clip = Some(h.clone());
-        }
-    }
-    if ctrl.idle_opened_clip.is_none() {
-        if let Some(h) = gltf.named_animations.get("LidIdleOpened") {
-            ctrl.idle_opened_clip = Some(h.clone());
-        }
-    }
-    if ctrl.idle_closed_clip.is_none() {
-        if let Some(h) = gltf.named_animations.get("LidIdleClosed") {
-            ctrl.idle_closed_clip = Some(h.clone());
-        }
-    }
-
-    // Keep durations up-to-date once clips actually load.
-    // (We can discover the clip handles before `Assets<AnimationClip>` contains them.
-    // If we cached a fallback duration earlier, we'd incorrectly advance state early.)
-    if let Some(h) = ctrl.opening_clip.as_ref() {
-        let d = clip_duration_seconds(&clips, h);
-        if (ctrl.open_duration - d).abs() > 0.001 {
-            ctrl.open_duration = d;
-        }
-    }
-    if let Some(h) = ctrl.closing_clip.as_ref() {
-        let d = clip_duration_seconds(&clips, h);
-        if (ctrl.close_duration - d).abs() > 0.001 {
-            ctrl.close_duration = d;
-        }
-    }
-    if let Some(h) = ctrl.idle_opened_clip.as_ref() {
-        let d = clip_duration_seconds(&clips, h);
-        if (ctrl.idle_opened_duration - d).abs() > 0.001 {
-            ctrl.idle_opened_duration = d;
-        }
-    }
-    if let Some(h) = ctrl.idle_closed_clip.as_ref() {
-        let d = clip_duration_seconds(&clips, h);
-        if (ctrl.idle_closed_duration - d).abs() > 0.001 {
-            ctrl.idle_closed_duration = d;
-        }
-    }
-
-    if ctrl.animation_graph.is_none() {
-        let (Some(opening), Some(closing), Some(idle_opened), Some(idle_closed)) = (
-            ctrl.opening_clip.clone(),
-            ctrl.closing_clip.clone(),
-            ctrl.idle_opened_clip.clone(),
-            ctrl.idle_closed_clip.clone(),
-        ) else {
-            return;
-        };
-
-        let mut graph = AnimationGraph::new();
-        let opening_node = graph.add_clip(opening, 1.0, graph.root);
-        let closing_node = graph.add_clip(closing, 1.0, graph.root);
-        let idle_opened_node = graph.add_clip(idle_opened, 1.0, graph.root);
-        let idle_closed_node = graph.add_clip(idle_closed, 1.0, graph.root);
-        let graph_handle = graphs.add(graph);
-
-        ctrl.opening_node = Some(opening_node);
-        ctrl.closing_node = Some(closing_node);
-        ctrl.idle_opened_node = Some(idle_opened_node);
-        ctrl.idle_closed_node = Some(idle_closed_node);
-        ctrl.animation_graph = Some(graph_handle);
-    }
-}
-
-pub fn cache_dice_box_lid_animation_player(
+pub fn cache_dice_box_lid_entity(
     mut commands: Commands,
     mut ctrl: ResMut<DiceBoxLidAnimationController>,
     container_style: Res<DiceContainerStyle>,
     visual_roots: Query<Entity, With<DiceBoxVisualSceneRoot>>,
     children: Query<&Children>,
-    players: Query<(), With<AnimationPlayer>>,
-    player_graphs: Query<Option<&AnimationGraphHandle>, With<AnimationPlayer>>,
+    names: Query<&Name>,
 ) {
     if *container_style != DiceContainerStyle::Box {
-        ctrl.animator_entity = None;
+        ctrl.lid_entity = None;
+        return;
+    }
+
+    // The box scene is respawned when switching containers.
+    if ctrl.lid_entity.is_some_and(|lid| names.get(lid).is_err()) {
+        ctrl.lid_entity = None;
+    }
+    if ctrl.lid_entity.is_some() {
         return;
     }
 
     let Some(root) = visual_roots.iter().next() else {
         return;
     };
+    let Some(lid) = find_named_entity_under(root, DICE_BOX_LID_NODE_NAME, &children, &names) else {
+        return;
+    };
 
-    // Prefer selecting the AnimationPlayer that is already wired to our graph, if present.
-    // This avoids accidentally picking a different player in the same scene tree.
-    if ctrl.animator_entity.is_none() {
-        let all_players = find_all_animation_players_under(root, &children, &players);
-        let preferred = ctrl.animation_graph.clone().and_then(|graph| {
-            all_players.iter().copied().find(|e| {
-                player_graphs
-                    .get(*e)
-                    .ok()
-                    .flatten()
-                    .is_some_and(|h| h.0 == graph)
-            })
-        });
-
-        ctrl.animator_entity =
-            preferred.or_else(|| find_first_animation_player_under(root, &children, &players));
-
-        #[cfg(debug_assertions)]
-        {
-            if !ctrl.debug_logged_player_scan {
-                ctrl.debug_logged_player_scan = true;
-                info!(
-                    "DiceBox lid: found {} AnimationPlayer(s) under {:?}; using {:?}",
-                    all_players.len(),
-                    root,
-                    ctrl.animator_entity
-                );
-                if all_players.len() > 1 {
-                    info!("DiceBox lid: AnimationPlayers={:?}", all_players);
-                }
-            }
+    #[cfg(debug_assertions)]
+    {
+        if !ctrl.debug_logged_lid_scan {
+            ctrl.debug_logged_lid_scan = true;
+            info!("DiceBox lid: using {:?} under {:?}", lid, root);
         }
     }
 
-    // Important: the AnimationPlayer may be discovered before the AnimationGraph is built.
-    // Always ensure the player entity has the current graph handle once both are available.
-    if let (Some(animator), Some(graph)) = (ctrl.animator_entity, ctrl.animation_graph.clone()) {
-        let needs_insert = match player_graphs.get(animator) {
-            Ok(Some(existing)) => existing.0 != graph,
-            Ok(None) => true,
-            Err(_) => true,
-        };
-
-        if needs_insert {
-            commands
-                .entity(animator)
-                .insert(AnimationGraphHandle(graph));
+    // The scene spawns the lid at its rest (closed) pose; snap it to whatever
+    // state the controller is in, finishing any transition that had no lid.
+    let (state, pose) = match ctrl.lid_state {
+        DiceBoxLidState::Open | DiceBoxLidState::Opening => {
+            (DiceBoxLidState::Open, DICE_BOX_LID_OPEN_POSE)
         }
-    }
+        DiceBoxLidState::Closed | DiceBoxLidState::Closing => {
+            (DiceBoxLidState::Closed, DICE_BOX_LID_CLOSED_POSE)
+        }
+    };
+    commands.entity(lid).insert(pose).remove::<TransformTween>();
+    ctrl.lid_state = state;
+    ctrl.lid_entity = Some(lid);
 }
 
 pub fn process_pending_roll_with_lid(
     mut commands: Commands,
-    time: Res<Time>,
     container_style: Res<DiceContainerStyle>,
-    mut players: Query<&mut AnimationPlayer>,
+    mut tweens_completed: MessageReader<TweenCompleted>,
     mut ctrl: ResMut<DiceBoxLidAnimationController>,
 
     mut exec: PendingRollExecutionParams,
@@ -385,21 +167,21 @@ pub fn process_pending_roll_with_lid(
     #[cfg(debug_assertions)]
     debug_trace_lid_controller(&mut ctrl);
 
-    // Tick lid animation timer.
-    if ctrl.lid_state == DiceBoxLidState::Closing || ctrl.lid_state == DiceBoxLidState::Opening {
-        ctrl.state_timer = (ctrl.state_timer - time.delta_secs()).max(0.0);
-        if ctrl.state_timer <= 0.0001 {
-            ctrl.lid_state = match ctrl.lid_state {
-                DiceBoxLidState::Closing => DiceBoxLidState::Closed,
-                DiceBoxLidState::Opening => DiceBoxLidState::Open,
-                other => other,
-            };
+    // Finish the open/close once the lid tween reaches its last keyframe.
+    for completed in tweens_completed.read() {
+        if Some(completed.entity) != ctrl.lid_entity {
+            continue;
         }
+        ctrl.lid_state = match ctrl.lid_state {
+            DiceBoxLidState::Closing => DiceBoxLidState::Closed,
+            DiceBoxLidState::Opening => DiceBoxLidState::Open,
+            other => other,
+        };
     }
 
-    // If the last roll finished, we should open the lid and then remain in LidIdleOpened.
-    // We run this here (instead of directly in the roll-completed handler) so the opening
-    // animation still plays even if the event arrives before animation assets/nodes exist.
+    // If the last roll finished, we should open the lid and leave it open.
+    // We run this here (instead of only in the roll-completed handler) so the opening
+    // still plays when the event arrives while the lid is closing for a queued roll.
     if ctrl.pending_open_after_roll {
         match ctrl.lid_state {
             DiceBoxLidState::Open => {
@@ -411,22 +193,11 @@ pub fn process_pending_roll_with_lid(
             }
             DiceBoxLidState::Closed => {
                 if ctrl.pending_roll.is_none() {
-                    if let Some(node) = ctrl.opening_node {
-                        let duration = ctrl.open_duration;
-
-                        #[cfg(debug_assertions)]
-                        info!("Lid: play opening (duration={:.3})", duration);
-
-                        play_once(
-                            &mut ctrl,
-                            &mut players,
-                            node,
-                            duration,
-                            DiceBoxLidState::Opening,
-                        );
-                        ctrl.pending_open_after_roll = false;
-                    }
-                    // If the node isn't ready yet, keep the request queued.
+                    #[cfg(debug_assertions)]
+                    info!("Lid: opening");
+
+                    tween_lid(&mut commands, &mut ctrl, true);
+                    ctrl.pending_open_after_roll = false;
                 }
             }
             DiceBoxLidState::Closing => {
@@ -440,24 +211,10 @@ pub fn process_pending_roll_with_lid(
         // Don't interrupt an in-progress opening animation.
         // Wait until it's fully open, then close.
         if ctrl.lid_state == DiceBoxLidState::Open {
-            if let Some(node) = ctrl.closing_node {
-                let duration = ctrl.close_duration;
-
-                #[cfg(debug_assertions)]
-                info!("Lid: play closing (duration={:.3})", duration);
-
-                play_once(
-                    &mut ctrl,
-                    &mut players,
-                    node,
-                    duration,
-                    DiceBoxLidState::Closing,
-                );
-            } else {
-                // No animation clip available: treat as instantly closed.
-                ctrl.lid_state = DiceBoxLidState::Closed;
-                ctrl.state_timer = 0.0;
-            }
+            #[cfg(debug_assertions)]
+            info!("Lid: closing");
+
+            tween_lid(&mut commands, &mut ctrl, false);
         }
     }
 
@@ -474,9 +231,9 @@ pub fn process_pending_roll_with_lid(
                 }
 
                 exec.roll_state.rolling = true;
-                exec.dice_results.results.clear();
+                exec.dice_results.clear();
 
-                let mut rng = rand::rng();
+                let rng = &mut *exec.roll_rng;
                 let num_dice = exec.dice_config.dice_to_roll.len();
 
                 let use_shake = exec.settings_state.settings.default_roll_uses_shake;
@@ -507,7 +264,7 @@ pub fn process_pending_roll_with_lid(
                                 rng.random_range(-0.3..0.0),
                                 rng.random_range(-0.5..0.5),
                             );
-                        velocity.angvel = exec.throw_state.calculate_angular_velocity(&mut rng);
+                        velocity.angvel = exec.throw_state.calculate_angular_velocity(rng);
                     }
                 }
 
@@ -539,6 +296,7 @@ pub fn process_pending_roll_with_lid(
                 exec.dice_config.dice_to_roll.push(die_type);
                 exec.dice_config.modifier = modifier;
                 exec.dice_config.modifier_name = modifier_name.clone();
+                exec.dice_config.expression = None;
 
                 // Add to command history (matches old behavior)
                 let sign = if modifier >= 0 { "+" } else { "" };
@@ -553,20 +311,22 @@ pub fn process_pending_roll_with_lid(
 
                 // Trigger the roll
                 exec.roll_state.rolling = true;
-                exec.dice_results.results.clear();
+                exec.dice_results.clear();
 
                 let die_scale = exec.settings_state.settings.dice_scales.scale_for(die_type);
                 let die_entity = spawn_die(
                     &mut commands,
                     &mut exec.meshes,
                     &mut exec.materials,
+                    &mut exec.mesh_cache,
                     die_type,
                     die_scale,
                     calculate_dice_position(0, 1),
+                    &mut *exec.roll_rng,
                 );
 
                 let use_shake = exec.settings_state.settings.default_roll_uses_shake;
-                let mut rng = rand::rng();
+                let rng = &mut *exec.roll_rng;
                 let base_velocity = exec.throw_state.calculate_throw_velocity();
 
                 let transform = Transform::from_translation(Vec3::new(
@@ -595,7 +355,7 @@ pub fn process_pending_roll_with_lid(
                                 rng.random_range(-0.3..0.0),
                                 rng.random_range(-0.5..0.5),
                             ),
-                        angvel: exec.throw_state.calculate_angular_velocity(&mut rng),
+                        angvel: exec.throw_state.calculate_angular_velocity(rng),
                     }
                 };
 
@@ -622,23 +382,26 @@ pub fn process_pending_roll_with_lid(
 
                 // Apply config
                 *exec.dice_config = config;
-                exec.dice_results.results.clear();
+                exec.dice_results.clear();
 
                 let use_shake = exec.settings_state.settings.default_roll_uses_shake;
 
                 // Spawn new dice
                 let mut spawned: Vec<Entity> = Vec::new();
-                let total = exec.dice_config.dice_to_roll.len();
-                for (i, die_type) in exec.dice_config.dice_to_roll.iter().copied().enumerate() {
+                let physical_dice = exec.dice_config.physical_dice();
+                let total = physical_dice.len();
+                for (i, die_type) in physical_dice.into_iter().enumerate() {
                     let position = calculate_dice_position(i, total);
                     let die_scale = exec.settings_state.settings.dice_scales.scale_for(die_type);
                     let e = spawn_die(
                         &mut commands,
                         &mut exec.meshes,
                         &mut exec.materials,
+                        &mut exec.mesh_cache,
                         die_type,
                         die_scale,
                         position,
+                        &mut *exec.roll_rng,
                     );
                     spawned.push(e);
                 }
@@ -663,15 +426,12 @@ pub fn process_pending_roll_with_lid(
             }
         }
     }
-
-    // Whenever not opening/closing, keep the correct idle active.
-    ensure_idle_looping(&mut ctrl, &mut players);
 }
 
 pub fn open_lid_on_roll_completed(
-    mut events: MessageReader<DiceRollCompletedEvent>,
+    mut commands: Commands,
+    mut events: MessageReader<DiceSettled>,
     container_style: Res<DiceContainerStyle>,
-    mut players: Query<&mut AnimationPlayer>,
     mut ctrl: ResMut<DiceBoxLidAnimationController>,
 ) {
     if *container_style != DiceContainerStyle::Box {
@@ -682,22 +442,13 @@ pub fn open_lid_on_roll_completed(
         return;
     }
 
-    // Queue the opening so it reliably plays even if animation nodes are not ready yet.
-    // The actual playback happens in `process_pending_roll_with_lid`.
+    // Queue the opening so it waits for an in-progress close or queued roll.
+    // The actual tween is started in `process_pending_roll_with_lid`.
     ctrl.pending_open_after_roll = true;
 
-    // If we can play it immediately, do so (keeps behavior snappy).
+    // If we can open immediately, do so (keeps behavior snappy).
     if ctrl.pending_roll.is_none() && ctrl.lid_state == DiceBoxLidState::Closed {
-        if let Some(node) = ctrl.opening_node {
-            let duration = ctrl.open_duration;
-            play_once(
-                &mut ctrl,
-                &mut players,
-                node,
-                duration,
-                DiceBoxLidState::Opening,
-            );
-            ctrl.pending_open_after_roll = false;
-        }
+        tween_lid(&mut commands, &mut ctrl, true);
+        ctrl.pending_open_after_roll = false;
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "audio")]
 use bevy::audio::{AudioPlayer, AudioSource, PlaybackSettings, Volume};
 use bevy::prelude::*;
 use bevy_hanabi::prelude::*;
@@ -5,6 +6,7 @@ use rand::prelude::IndexedRandom;
 use rand::Rng;
 
 use crate::dice3d::dice_fx::DiceFxRollingTracker;
+#[cfg(feature = "audio")]
 use crate::dice3d::embedded_assets::{
     DICE_FX_ELECTRICITY_SFX_PATH, DICE_FX_EXPLOSION_SFX_PATH, DICE_FX_FIREWORKS_SFX_PATH,
     DICE_FX_FIRE_SFX_PATH, DICE_FX_PLASMABALL_SFX_PATH,
@@ -25,12 +27,15 @@ pub struct ElectricBoltEmitter {
 #[derive(Component, Clone, Copy, Debug)]
 pub struct FxDespawnAt(pub f32);
 
+#[cfg(feature = "audio")]
 #[derive(Component, Clone, Copy, Debug)]
 pub struct DiceFxElectricityLoopSfx;
 
+#[cfg(feature = "audio")]
 #[derive(Component, Clone, Copy, Debug)]
 pub struct DiceFxPlasmaLoopSfx;
 
+#[cfg(feature = "audio")]
 #[derive(Component, Clone, Copy, Debug)]
 pub struct DiceFxFireLoopSfx;
 
@@ -96,9 +101,10 @@ pub fn clear_dice_fx_on_roll_start(
     }
 }
 
+#[cfg_attr(not(feature = "audio"), allow(unused_variables))]
 pub fn apply_dice_fx_from_roll_complete(
     mut commands: Commands,
-    mut ev: MessageReader<DiceRollCompletedEvent>,
+    mut ev: MessageReader<DiceSettled>,
     time: Res<Time>,
     settings_state: Res<SettingsState>,
     hanabi_fx: Res<DiceHanabiFxAssets>,
@@ -173,23 +179,28 @@ pub fn apply_dice_fx_from_roll_complete(
                 }
 
                 // Fireworks and explosion SFX: play every time the effect fires (per die).
-                if fireworks {
-                    let sound: Handle<AudioSource> = asset_server.load(DICE_FX_FIREWORKS_SFX_PATH);
-                    commands.spawn((
-                        AudioPlayer(sound),
-                        PlaybackSettings::DESPAWN
-                            .with_spatial(false)
-                            .with_volume(Volume::Linear(1.0)),
-                    ));
-                }
-                if explosion {
-                    let sound: Handle<AudioSource> = asset_server.load(DICE_FX_EXPLOSION_SFX_PATH);
-                    commands.spawn((
-                        AudioPlayer(sound),
-                        PlaybackSettings::DESPAWN
-                            .with_spatial(false)
-                            .with_volume(Volume::Linear(1.0)),
-                    ));
+                #[cfg(feature = "audio")]
+                {
+                    if fireworks {
+                        let sound: Handle<AudioSource> =
+                            asset_server.load(DICE_FX_FIREWORKS_SFX_PATH);
+                        commands.spawn((
+                            AudioPlayer(sound),
+                            PlaybackSettings::DESPAWN
+                                .with_spatial(false)
+                                .with_volume(Volume::Linear(settings_state.settings.sound_gain())),
+                        ));
+                    }
+                    if explosion {
+                        let sound: Handle<AudioSource> =
+                            asset_server.load(DICE_FX_EXPLOSION_SFX_PATH);
+                        commands.spawn((
+                            AudioPlayer(sound),
+                            PlaybackSettings::DESPAWN
+                                .with_spatial(false)
+                                .with_volume(Volume::Linear(settings_state.settings.sound_gain())),
+                        ));
+                    }
                 }
             } else {
                 commands.entity(r.entity).remove::<DiceFxState>();
@@ -199,17 +210,21 @@ pub fn apply_dice_fx_from_roll_complete(
     }
 }
 
+#[cfg(feature = "audio")]
 pub fn update_dice_fx_loop_sfx(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    settings_state: Res<SettingsState>,
     dice_fx: Query<&DiceFxState, With<Die>>,
     electricity_loop: Query<Entity, With<DiceFxElectricityLoopSfx>>,
     plasma_loop: Query<Entity, With<DiceFxPlasmaLoopSfx>>,
     fire_loop: Query<Entity, With<DiceFxFireLoopSfx>>,
 ) {
-    let any_electric = dice_fx.iter().any(|s| s.electric);
-    let any_plasma = dice_fx.iter().any(|s| s.plasma);
-    let any_fire = dice_fx.iter().any(|s| s.fire);
+    // Muting stops the loops; a volume change applies to the next loop.
+    let gain = settings_state.settings.sound_gain();
+    let any_electric = gain > 0.0 && dice_fx.iter().any(|s| s.electric);
+    let any_plasma = gain > 0.0 && dice_fx.iter().any(|s| s.plasma);
+    let any_fire = gain > 0.0 && dice_fx.iter().any(|s| s.fire);
 
     if any_electric {
         if electricity_loop.is_empty() {
@@ -219,7 +234,7 @@ pub fn update_dice_fx_loop_sfx(
                 AudioPlayer(sound),
                 PlaybackSettings::LOOP
                     .with_spatial(false)
-                    .with_volume(Volume::Linear(0.7)),
+                    .with_volume(Volume::Linear(0.7 * gain)),
             ));
         }
     } else {
@@ -236,7 +251,7 @@ pub fn update_dice_fx_loop_sfx(
                 AudioPlayer(sound),
                 PlaybackSettings::LOOP
                     .with_spatial(false)
-                    .with_volume(Volume::Linear(0.7)),
+                    .with_volume(Volume::Linear(0.7 * gain)),
             ));
         }
     } else {
@@ -253,7 +268,7 @@ pub fn update_dice_fx_loop_sfx(
                 AudioPlayer(sound),
                 PlaybackSettings::LOOP
                     .with_spatial(false)
-                    .with_volume(Volume::Linear(0.7)),
+                    .with_volume(Volume::Linear(0.7 * gain)),
             ));
         }
     } else {
@@ -563,7 +578,7 @@ fn die_vertices_local(die_type: DiceType) -> Vec<Vec3> {
             let v3 = Vec3::new(-base_r * 0.866, base_y, -base_r * 0.5);
             vec![v0, v1, v2, v3]
         }
-        DiceType::D6 => {
+        DiceType::D6 | DiceType::Fudge => {
             let size = 0.6;
             let h = size / 2.0;
             vec![
@@ -660,6 +675,8 @@ fn die_vertices_local(die_type: DiceType) -> Vec<Vec3> {
                 Vec3::new(-phi, 0.0, -1.0) * s,
             ]
         }
+        // Coins have no corners; other custom dice are never spawned as entities.
+        DiceType::Custom(_) => Vec::new(),
     }
 }
 
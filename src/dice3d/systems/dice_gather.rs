@@ -0,0 +1,102 @@
+//! Dice gathering systems
+//!
+//! `gather_settled_dice` slides the dice of a roll that has just been read
+//! into rows at the front of the container (as seen from the camera),
+//! lowest value first; `release_gathered_dice` hands them back to physics
+//! when the next roll starts.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::dice3d::types::*;
+
+/// Once the dice are read, tween them into their row and hold them there.
+#[allow(clippy::type_complexity)]
+pub fn gather_settled_dice(
+    mut commands: Commands,
+    mut settled: MessageReader<DiceSettled>,
+    settings_state: Res<SettingsState>,
+    container_style: Res<DiceContainerStyle>,
+    camera: Query<&Transform, (With<MainCamera>, Without<Die>)>,
+    mut dice: Query<(&Die, &Transform, &mut Velocity, Option<&PercentileDie>)>,
+) {
+    let Some(event) = settled.read().last() else {
+        return;
+    };
+    if !settings_state.settings.gather_dice {
+        return;
+    }
+
+    // Lowest value first; the tens d10 of a d100 left of its units die
+    let mut order: Vec<(u32, bool, Entity)> = event
+        .results
+        .iter()
+        .filter(|outcome| dice.contains(outcome.entity))
+        .map(|outcome| {
+            let units = matches!(
+                dice.get(outcome.entity).map(|(.., p)| p.copied()),
+                Ok(Some(PercentileDie::Units))
+            );
+            (outcome.value, units, outcome.entity)
+        })
+        .collect();
+    order.sort_by_key(|(value, units, _)| (*value, *units));
+    if order.is_empty() {
+        return;
+    }
+
+    let scale = order
+        .iter()
+        .filter_map(|(.., entity)| dice.get(*entity).ok())
+        .map(|(die, ..)| settings_state.settings.dice_scales.scale_for(die.die_type))
+        .fold(1.0f32, f32::max);
+    let layout = GatherLayout::for_style(*container_style, GATHER_SPACING * scale);
+
+    // "Front" is the side of the floor facing the camera
+    let toward_camera = camera
+        .single()
+        .ok()
+        .map(|t| Vec2::new(t.translation.x, t.translation.z).normalize_or_zero())
+        .filter(|v| *v != Vec2::ZERO)
+        .unwrap_or(Vec2::Y);
+    let right = Vec2::new(toward_camera.y, -toward_camera.x);
+
+    for ((.., entity), spot) in order.iter().zip(layout.positions(order.len())) {
+        let Ok((_, transform, mut velocity, _)) = dice.get_mut(*entity) else {
+            continue;
+        };
+        let floor = right * spot.x + toward_camera * spot.y;
+        let target = Transform {
+            translation: Vec3::new(floor.x, transform.translation.y, floor.y),
+            ..*transform
+        };
+        velocity.linvel = Vec3::ZERO;
+        velocity.angvel = Vec3::ZERO;
+        commands.entity(*entity).insert((
+            GatheredDie,
+            RigidBody::KinematicPositionBased,
+            TransformTween::between(*transform, target, GATHER_DURATION, Easing::EaseInOutCubic),
+        ));
+    }
+}
+
+/// Give gathered dice back to physics when a roll starts, before their
+/// tween could move them again.
+pub fn release_gathered_dice(
+    mut commands: Commands,
+    roll_state: Res<RollState>,
+    mut gathered: Query<(Entity, Option<&mut TransformTween>), With<GatheredDie>>,
+) {
+    if !roll_state.rolling {
+        return;
+    }
+    for (entity, tween) in gathered.iter_mut() {
+        if let Some(mut tween) = tween {
+            tween.finished = true;
+        }
+        commands
+            .entity(entity)
+            .remove::<(GatheredDie, TransformTween)>()
+            .insert(RigidBody::Dynamic);
+    }
+}
@@ -0,0 +1,451 @@
+//! Dice macro systems
+//!
+//! `record_macro_steps` listens to `RollRequested` and character switches
+//! while a macro is being recorded; `run_macro_playback` replays a macro one
+//! step at a time, waiting for each roll's `RollResolved` before the next.
+//! `macro ...` / `macros` in the command input and the Macros tab
+//! (`macros_screen`) manage the macros, and Ctrl+1..9 plays the macro bound
+//! to that digit.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+use bevy_rapier3d::prelude::*;
+
+use super::dice_box_controls::start_container_shake;
+use super::setup::{calculate_dice_position, spawn_die};
+use crate::dice3d::meshes::DiceMeshCache;
+use crate::dice3d::types::*;
+
+/// A `macro` / `macros` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroCommand {
+    List,
+    Record,
+    /// Stop recording and save under a name, optionally bound to Ctrl+digit
+    Save {
+        name: String,
+        hotkey: Option<u8>,
+    },
+    Cancel,
+    /// Save a definition (`name = roll; roll`), for the current character
+    /// only or for every character
+    Define {
+        definition: String,
+        character_only: bool,
+    },
+    Play(String),
+    Stop,
+    Remove(String),
+}
+
+impl TypedCommand for MacroCommand {
+    const NAME: &'static str = "macro";
+
+    /// Parse `macros`, `macro record`, `macro save <name> [1-9]`, `macro cancel`,
+    /// `macro <name> = <roll>; <roll>`, `macro character <name> = <roll>`,
+    /// `macro play <name>` (or `macro <name>`), `macro stop` and
+    /// `macro remove <name>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("macro") && !head.eq_ignore_ascii_case("macros") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (verb, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let arg = arg.trim();
+        if rest.contains('=') {
+            let character_only = matches!(verb.to_lowercase().as_str(), "character" | "char");
+            return Some(MacroCommand::Define {
+                definition: if character_only { arg } else { rest }.to_string(),
+                character_only,
+            });
+        }
+        match verb.to_lowercase().as_str() {
+            "" | "list" => Some(MacroCommand::List),
+            "record" | "start" => Some(MacroCommand::Record),
+            "cancel" => Some(MacroCommand::Cancel),
+            "stop" => Some(MacroCommand::Stop),
+            "save" => {
+                // A trailing digit (`1` or `ctrl+1`) is the hotkey
+                let (name, hotkey) = match arg.rsplit_once(char::is_whitespace) {
+                    Some((name, key)) => match parse_hotkey(key) {
+                        Some(key) => (name.trim(), Some(key)),
+                        None => (arg, None),
+                    },
+                    None => (arg, None),
+                };
+                Some(MacroCommand::Save {
+                    name: name.to_string(),
+                    hotkey,
+                })
+            }
+            "play" | "run" if !arg.is_empty() => Some(MacroCommand::Play(arg.to_string())),
+            "remove" | "delete" if !arg.is_empty() => Some(MacroCommand::Remove(arg.to_string())),
+            _ => Some(MacroCommand::Play(rest.to_string())),
+        }
+    }
+}
+
+fn parse_hotkey(text: &str) -> Option<u8> {
+    let lower = text.to_lowercase();
+    let digit = lower.strip_prefix("ctrl+").unwrap_or(&lower);
+    digit.parse().ok()
+}
+
+pub(crate) fn save_macros(macros: &DiceMacros, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(DICE_MACROS_DB_KEY, macros.clone()) {
+        warn!("Failed to save macros: {}", e);
+    }
+}
+
+/// Apply a macro command, saving the macros and notifying the user.
+pub fn run_macro_command(
+    In(command): In<MacroCommand>,
+    mut macros: ResMut<DiceMacros>,
+    mut recorder: ResMut<MacroRecorder>,
+    mut playback: ResMut<MacroPlayback>,
+    character_data: Res<CharacterData>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let character = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| sheet.character.name.as_str());
+    let message = match command {
+        MacroCommand::List => macros.summary(character),
+        MacroCommand::Record => {
+            recorder.start(character);
+            "Recording a macro: roll anywhere, then `macro save <name> [1-9]`".to_string()
+        }
+        MacroCommand::Save { name, hotkey } => {
+            if !recorder.is_recording() {
+                "Not recording - start with `macro record`".to_string()
+            } else {
+                let steps = recorder.finish();
+                match macros.save(&name, hotkey, steps.clone()) {
+                    Ok(message) => {
+                        save_macros(&macros, &db);
+                        message
+                    }
+                    Err(e) => {
+                        // Keep recording so the macro can still be saved
+                        recorder.steps = Some(steps);
+                        e
+                    }
+                }
+            }
+        }
+        MacroCommand::Cancel => {
+            recorder.finish();
+            "Macro recording cancelled".to_string()
+        }
+        MacroCommand::Define {
+            character_only: true,
+            ..
+        } if character.is_none() => "Pick a character to save the macro for".to_string(),
+        MacroCommand::Define {
+            definition,
+            character_only,
+        } => match macros.define(&definition, character.filter(|_| character_only)) {
+            Ok(message) => {
+                save_macros(&macros, &db);
+                message
+            }
+            Err(e) => e,
+        },
+        MacroCommand::Play(_) if recorder.is_recording() => {
+            "Save or cancel the macro being recorded first".to_string()
+        }
+        MacroCommand::Play(name) => match macros.find(&name, character) {
+            Some(dice_macro) => {
+                playback.start(dice_macro);
+                format!("Playing macro {}", dice_macro.name)
+            }
+            None => format!("No macro named {}", name),
+        },
+        MacroCommand::Stop => {
+            playback.stop();
+            "Macro stopped".to_string()
+        }
+        MacroCommand::Remove(name) => match macros.remove(&name, character) {
+            Some(removed) => {
+                save_macros(&macros, &db);
+                format!("Macro removed: {}", removed.name)
+            }
+            None => format!("No macro named {}", name),
+        },
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Load the current profile's macros whenever the database (profile) changes.
+pub fn load_dice_macros(db: Option<Res<CharacterDatabase>>, mut macros: ResMut<DiceMacros>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    *macros = db
+        .get_setting::<DiceMacros>(DICE_MACROS_DB_KEY)
+        .unwrap_or_else(|e| {
+            warn!("Failed to load macros: {}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Record requested rolls and character switches while recording.
+pub fn record_macro_steps(
+    mut requests: MessageReader<RollRequested>,
+    character_manager: Res<CharacterManager>,
+    character_data: Res<CharacterData>,
+    mut last_character: Local<Option<i64>>,
+    mut recorder: ResMut<MacroRecorder>,
+) {
+    let current = character_manager.current_character_id;
+    let switched = *last_character != current;
+    *last_character = current;
+
+    if !recorder.is_recording() {
+        requests.clear();
+        return;
+    }
+
+    if switched {
+        if let Some(sheet) = &character_data.sheet {
+            recorder.record(MacroStep::SelectCharacter {
+                name: sheet.character.name.clone(),
+            });
+        }
+    }
+    for request in requests.read() {
+        // Steps of a replayed macro are not new actions, and group checks
+        // are rolled from the Party tab
+        if matches!(request.source, RollSource::Macro | RollSource::GroupCheck) {
+            continue;
+        }
+        recorder.record(MacroStep::roll(&request.config));
+    }
+}
+
+/// Play the macro bound to Ctrl+1..9.
+pub fn play_macro_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    macros: Res<DiceMacros>,
+    recorder: Res<MacroRecorder>,
+    mut playback: ResMut<MacroPlayback>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    const DIGITS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (key, code) in (1..=9).zip(DIGITS) {
+        if !keyboard.just_pressed(code) {
+            continue;
+        }
+        // Replaying while recording would record the macro into itself
+        if recorder.is_recording() || playback.is_playing() {
+            continue;
+        }
+        if let Some(dice_macro) = macros.by_hotkey(key) {
+            playback.start(dice_macro);
+            snackbar.write(
+                ShowSnackbar::message(format!("Playing macro {}", dice_macro.name)).duration(2.0),
+            );
+        }
+    }
+}
+
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct MacroPlaybackParams<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub db: Res<'w, CharacterDatabase>,
+    pub settings_state: Res<'w, SettingsState>,
+    pub character_manager: ResMut<'w, CharacterManager>,
+    pub character_data: ResMut<'w, CharacterData>,
+    pub dice_config: ResMut<'w, DiceConfig>,
+    pub dice_results: ResMut<'w, DiceResults>,
+    pub roll_state: ResMut<'w, RollState>,
+    pub roll_requested: MessageWriter<'w, RollRequested>,
+    pub snackbar: MessageWriter<'w, ShowSnackbar>,
+
+    pub container_style: Res<'w, DiceContainerStyle>,
+    pub lid_ctrl: ResMut<'w, DiceBoxLidAnimationController>,
+
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
+    pub dice_query: Query<'w, 's, Entity, With<Die>>,
+
+    pub shake_state: Res<'w, ShakeState>,
+    pub shake_config: Res<'w, ContainerShakeConfig>,
+    pub shake_anim: ResMut<'w, ContainerShakeAnimation>,
+    pub container_query: Query<'w, 's, (Entity, &'static Transform), With<DiceBox>>,
+}
+
+/// Replay the playing macro: switch characters straight away, and start each
+/// roll once the previous one has resolved.
+pub fn run_macro_playback(
+    mut resolved: MessageReader<RollResolved>,
+    mut cancelled: MessageReader<RollCancelled>,
+    mut playback: ResMut<MacroPlayback>,
+    mut params: MacroPlaybackParams,
+) {
+    if resolved.read().count() > 0 {
+        playback.waiting_for_roll = false;
+    }
+    if cancelled.read().count() > 0 && playback.is_playing() {
+        stop_playback(&mut playback, &mut params, "a roll was cancelled");
+        return;
+    }
+    if playback.waiting_for_roll
+        || params.roll_state.rolling
+        || params.lid_ctrl.pending_roll.is_some()
+    {
+        return;
+    }
+
+    while let Some(step) = playback.steps.pop_front() {
+        match &step {
+            MacroStep::SelectCharacter { name } => {
+                let entry = params
+                    .character_manager
+                    .characters
+                    .iter()
+                    .find(|entry| entry.name.eq_ignore_ascii_case(name))
+                    .map(|entry| entry.id);
+                let loaded = entry
+                    .ok_or_else(|| format!("no character named {}", name))
+                    .and_then(|id| params.db.load_character(id).map(|sheet| (id, sheet)));
+                match loaded {
+                    Ok((id, sheet)) => {
+                        params.character_manager.current_character_id = Some(id);
+                        params.character_data.sheet = Some(sheet);
+                        params.character_data.is_modified = false;
+                    }
+                    Err(e) => {
+                        stop_playback(&mut playback, &mut params, &e);
+                        return;
+                    }
+                }
+            }
+            MacroStep::Roll { .. } => match step.dice_config() {
+                Some(Ok(config)) => {
+                    start_queued_roll(&mut params, config, RollSource::Macro);
+                    playback.waiting_for_roll = true;
+                    return;
+                }
+                Some(Err(e)) => {
+                    stop_playback(&mut playback, &mut params, &e);
+                    return;
+                }
+                None => {}
+            },
+        }
+    }
+
+    if !playback.name.is_empty() {
+        params.snackbar.write(
+            ShowSnackbar::message(format!("Macro {} finished", playback.name)).duration(2.0),
+        );
+        playback.name.clear();
+    }
+}
+
+fn stop_playback(playback: &mut MacroPlayback, params: &mut MacroPlaybackParams, error: &str) {
+    params.snackbar.write(
+        ShowSnackbar::message(format!("Macro {} stopped: {}", playback.name, error)).duration(4.0),
+    );
+    playback.stop();
+    playback.name.clear();
+}
+
+/// Throw the dice of a queued roll: a macro step, a throw of a Party tab
+/// group check or a custom quick roll button.
+pub fn start_queued_roll(
+    params: &mut MacroPlaybackParams,
+    new_config: DiceConfig,
+    source: RollSource,
+) {
+    params.roll_requested.write(RollRequested {
+        config: new_config.clone(),
+        source,
+        command: None,
+    });
+
+    // Box style: gate roll start behind lid closing.
+    if *params.container_style == DiceContainerStyle::Box {
+        *params.dice_config = new_config.clone();
+        params.dice_results.clear();
+        params.lid_ctrl.pending_roll =
+            Some(PendingRollRequest::StartNewRoll { config: new_config });
+        return;
+    }
+
+    for entity in params.dice_query.iter() {
+        params.commands.entity(entity).despawn();
+    }
+
+    *params.dice_config = new_config;
+    params.dice_results.clear();
+
+    let use_shake = params.settings_state.settings.default_roll_uses_shake;
+
+    let mut spawned: Vec<Entity> = Vec::new();
+    let physical_dice = params.dice_config.physical_dice();
+    for (i, die_type) in physical_dice.iter().enumerate() {
+        let position = calculate_dice_position(i, physical_dice.len());
+        let die_scale = params
+            .settings_state
+            .settings
+            .dice_scales
+            .scale_for(*die_type);
+        let e = spawn_die(
+            &mut params.commands,
+            &mut params.meshes,
+            &mut params.materials,
+            &mut params.mesh_cache,
+            *die_type,
+            die_scale,
+            position,
+            &mut *params.roll_rng,
+        );
+        spawned.push(e);
+    }
+
+    if use_shake {
+        for e in spawned {
+            params.commands.entity(e).insert(Velocity {
+                linvel: Vec3::ZERO,
+                angvel: Vec3::ZERO,
+            });
+        }
+
+        let _started = start_container_shake(
+            &params.shake_state,
+            &params.shake_config,
+            &mut params.shake_anim,
+            &params.container_query,
+        );
+    }
+
+    params.roll_state.rolling = true;
+}
@@ -0,0 +1,116 @@
+//! Dice palette of the quick roll panel
+//!
+//! Clicking a die tile queues one more of that die, right-clicking takes one
+//! off, and a badge on each tile counts the queued dice. Roll throws them all
+//! together like a command roll and Clear empties the queue. The queue is
+//! kept in `DicePalette`, so it survives the panel being rebuilt.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::{start_queued_roll, MacroPlaybackParams};
+use crate::dice3d::types::*;
+
+/// Text under the palette: the queued dice, or how to queue some.
+pub fn palette_summary_text(palette: &DicePalette) -> String {
+    if palette.queued.is_empty() {
+        "Click dice to build a roll".to_string()
+    } else {
+        palette.summary()
+    }
+}
+
+/// Queue a clicked die, and throw or clear the queued dice.
+pub fn handle_dice_palette_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    dice: Query<&DicePaletteDie>,
+    buttons: Query<&DicePaletteButton>,
+    mut palette: ResMut<DicePalette>,
+    mut params: MacroPlaybackParams,
+) {
+    for event in click_events.read() {
+        if params.settings_state.show_modal {
+            continue;
+        }
+        if let Ok(die) = dice.get(event.entity) {
+            if !palette.add(die.0) {
+                params.snackbar.write(
+                    ShowSnackbar::message(format!("At most {} of each die", MAX_PALETTE_DICE))
+                        .duration(2.0),
+                );
+            }
+            continue;
+        }
+
+        match buttons.get(event.entity) {
+            Ok(DicePaletteButton::Clear) => palette.queued.clear(),
+            Ok(DicePaletteButton::Roll) => {
+                if palette.queued.is_empty() {
+                    params
+                        .snackbar
+                        .write(ShowSnackbar::message("Click some dice first").duration(2.0));
+                    continue;
+                }
+                if params.roll_state.rolling || params.lid_ctrl.pending_roll.is_some() {
+                    params
+                        .snackbar
+                        .write(ShowSnackbar::message("Wait for the dice to settle").duration(2.0));
+                    continue;
+                }
+                let config = palette.dice_config();
+                palette.queued.clear();
+                start_queued_roll(&mut params, config, RollSource::QuickRoll);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Take one die off the queue when its tile is right-clicked.
+pub fn remove_palette_die_on_right_click(
+    mouse: Res<ButtonInput<MouseButton>>,
+    tiles: Query<(&Interaction, &DicePaletteDie)>,
+    mut palette: ResMut<DicePalette>,
+) {
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+    for (interaction, die) in tiles.iter() {
+        if *interaction != Interaction::None {
+            palette.remove(die.0);
+        }
+    }
+}
+
+/// Keep the badges and the summary in step with the queue.
+pub fn update_dice_palette_badges(
+    palette: Res<DicePalette>,
+    mut badges: Query<(&DicePaletteBadge, &mut Node, &Children)>,
+    mut badge_texts: Query<&mut Text, Without<DicePaletteSummary>>,
+    mut summaries: Query<&mut Text, With<DicePaletteSummary>>,
+) {
+    if !palette.is_changed() {
+        return;
+    }
+
+    for (badge, mut node, children) in badges.iter_mut() {
+        let count = palette.count(badge.0);
+        let display = if count > 0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = badge_texts.get_mut(child) {
+                text.0 = count.to_string();
+            }
+        }
+    }
+
+    for mut text in summaries.iter_mut() {
+        text.0 = palette_summary_text(&palette);
+    }
+}
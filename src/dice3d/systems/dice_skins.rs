@@ -0,0 +1,60 @@
+//! Dice skins
+//!
+//! Newly spawned dice are drawn with the skin chosen for their type in the
+//! settings: the body color, finish and texture go on the die's own material,
+//! and the face numbers switch to the skin's number color. With chaos dice
+//! on, each die's body takes a random color from the chaos dice palette
+//! instead.
+
+use bevy::prelude::*;
+use rand::prelude::IndexedRandom;
+
+use crate::dice3d::meshes::DiceMeshCache;
+use crate::dice3d::types::*;
+
+/// Give newly spawned dice the skin of their type.
+pub fn apply_dice_skins(
+    dice: Query<(&Die, &MeshMaterial3d<StandardMaterial>, &Children), Added<Die>>,
+    mut labels: Query<&mut MeshMaterial3d<StandardMaterial>, Without<Die>>,
+    settings_state: Res<SettingsState>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut mesh_cache: ResMut<DiceMeshCache>,
+) {
+    let settings = &settings_state.settings;
+    let mut rng = rand::rng();
+    for (die, material, children) in &dice {
+        let skin = settings.dice_skin(die.die_type);
+
+        if let Some(material) = materials.get_mut(&material.0) {
+            let chaos_color = settings
+                .chaos_dice
+                .then(|| settings.chaos_dice_palette.choose(&mut rng))
+                .flatten();
+            let base_color = chaos_color.unwrap_or(&skin.base_color).to_color();
+            material.alpha_mode = if base_color.alpha() < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            };
+            material.base_color = base_color;
+            material.metallic = skin.metallic.clamp(0.0, 1.0);
+            material.perceptual_roughness = skin.roughness.clamp(0.0, 1.0);
+            material.base_color_texture = skin.texture.as_ref().map(|path| asset_server.load(path));
+        }
+
+        // Only the numbers change color; their black outlines stay.
+        let (_, white_label) = mesh_cache.label_materials(&mut materials);
+        let number_material = mesh_cache.number_material(&skin.number_color, &mut materials);
+        if number_material == white_label {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(child) {
+                if label.0 == white_label {
+                    label.0 = number_material.clone();
+                }
+            }
+        }
+    }
+}
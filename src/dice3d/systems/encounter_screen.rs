@@ -0,0 +1,471 @@
+//! Encounter tab
+//!
+//! The DM adds monsters from the SRD compendium to an encounter and sees its
+//! XP, adjusted for the number of monsters, rated against the party's XP
+//! thresholds (see `Encounter`). The party is the stored characters at their
+//! levels until the DM changes its size or level. One button rolls
+//! initiative for every monster, with its hit points, on the Initiative tab.
+//! The tab is an extension tab (`ENCOUNTER_TAB_ID`) added by
+//! `CompendiumPlugin`.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::*;
+
+/// Id of the Encounter tab
+pub const ENCOUNTER_TAB_ID: &str = "encounter";
+
+/// Most monsters offered for the search at once
+const MAX_SEARCH_RESULTS: usize = 24;
+
+/// Party size and level used when there are no stored characters
+const DEFAULT_PARTY: [u32; 4] = [1; 4];
+
+/// The party the encounter is rated for, and the monster search
+#[derive(Resource, Debug, Clone)]
+pub struct EncounterBuilder {
+    pub query: String,
+    /// Level of each character
+    pub levels: Vec<u32>,
+    /// The DM changed the party; stored characters no longer replace it
+    pub party_edited: bool,
+}
+
+impl Default for EncounterBuilder {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            levels: DEFAULT_PARTY.to_vec(),
+            party_edited: false,
+        }
+    }
+}
+
+/// Node the tab's content is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct EncounterScreenBody;
+
+/// The tab's monster search field
+#[derive(Component)]
+pub struct EncounterSearchField;
+
+/// Buttons of the Encounter tab
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum EncounterButton {
+    /// Add one of the monster with this name
+    Add(String),
+    /// Take one of the monster with this name out
+    Remove(String),
+    PartySmaller,
+    PartyLarger,
+    LevelDown,
+    LevelUp,
+    /// Rate the encounter for the stored characters again
+    UseCharacters,
+    SendToInitiative,
+    Clear,
+}
+
+/// Spawn the (hidden) Encounter tab.
+pub fn setup_encounter_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                row_gap: Val::Px(12.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            ExtensionTabRoot(ENCOUNTER_TAB_ID),
+        ))
+        .with_children(|parent| {
+            spawn_encounter_text(
+                parent,
+                "Encounter Builder".to_string(),
+                28.0,
+                theme.on_surface,
+            );
+            parent
+                .spawn(Node {
+                    width: Val::Px(360.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label("Add monsters")
+                        .placeholder("goblin, undead, dragon...")
+                        .auto_focus(false)
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(slot, &theme, builder, EncounterSearchField);
+                });
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        EncounterScreenBody,
+                    ));
+                });
+        });
+}
+
+/// Rate encounters for the stored characters at their levels, until the DM
+/// sets the party up by hand.
+pub fn sync_encounter_party(
+    character_manager: Res<CharacterManager>,
+    mut builder: ResMut<EncounterBuilder>,
+) {
+    if !character_manager.is_changed() || builder.party_edited {
+        return;
+    }
+    let levels = party_levels(&character_manager);
+    if builder.levels != levels {
+        builder.levels = levels;
+    }
+}
+
+fn party_levels(character_manager: &CharacterManager) -> Vec<u32> {
+    if character_manager.characters.is_empty() {
+        return DEFAULT_PARTY.to_vec();
+    }
+    character_manager
+        .characters
+        .iter()
+        .map(|c| c.level.clamp(1, 20) as u32)
+        .collect()
+}
+
+/// Track the search and handle the tab's buttons.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_encounter_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    search_fields: Query<(), With<EncounterSearchField>>,
+    buttons: Query<&EncounterButton>,
+    compendium: Res<MonsterCompendium>,
+    character_manager: Res<CharacterManager>,
+    mut builder: ResMut<EncounterBuilder>,
+    mut encounter: ResMut<Encounter>,
+    mut tracker: ResMut<InitiativeTracker>,
+    mut ui_state: ResMut<UiState>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for ev in change_events.read() {
+        if search_fields.get(ev.entity).is_ok() && builder.query != ev.value {
+            builder.query = ev.value.clone();
+        }
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        match button {
+            EncounterButton::Add(name) => {
+                if let Ok(monster) = compendium.find(name) {
+                    encounter.add(&monster.name, monster.xp);
+                }
+            }
+            EncounterButton::Remove(name) => encounter.remove(name),
+            EncounterButton::PartySmaller => {
+                if builder.levels.len() > 1 {
+                    builder.levels.pop();
+                    builder.party_edited = true;
+                }
+            }
+            EncounterButton::PartyLarger => {
+                let level = builder.levels.last().copied().unwrap_or(1);
+                builder.levels.push(level);
+                builder.party_edited = true;
+            }
+            EncounterButton::LevelDown | EncounterButton::LevelUp => {
+                let step: i32 = if *button == EncounterButton::LevelUp {
+                    1
+                } else {
+                    -1
+                };
+                for level in builder.levels.iter_mut() {
+                    *level = (*level as i32 + step).clamp(1, 20) as u32;
+                }
+                builder.party_edited = true;
+            }
+            EncounterButton::UseCharacters => {
+                builder.levels = party_levels(&character_manager);
+                builder.party_edited = false;
+            }
+            EncounterButton::Clear => encounter.monsters.clear(),
+            EncounterButton::SendToInitiative => {
+                let mut added = 0;
+                for entry in &encounter.monsters {
+                    let Ok(monster) = compendium.find(&entry.name) else {
+                        continue;
+                    };
+                    let modifier = monster.ability_modifier("dexterity").unwrap_or_default();
+                    for _ in 0..entry.count {
+                        let name = tracker.unique_name(&monster.name);
+                        tracker.roll_combatant(&monster.name, modifier, None, &mut *rng);
+                        tracker.set_hit_points(&name, Some(monster.hit_points));
+                        added += 1;
+                    }
+                }
+                if added == 0 {
+                    snackbar.write(ShowSnackbar::message("Add monsters first").duration(2.0));
+                    continue;
+                }
+                let message = format!("Rolled initiative for {} monsters", added);
+                info!("{}", message);
+                snackbar.write(ShowSnackbar::message(message).duration(2.5));
+                ui_state.active_tab = AppTab::Initiative;
+            }
+        }
+    }
+}
+
+/// Rebuild the tab when the encounter, the party or the search change.
+pub fn update_encounter_screen(
+    mut commands: Commands,
+    builder: Res<EncounterBuilder>,
+    encounter: Res<Encounter>,
+    compendium: Res<MonsterCompendium>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<EncounterScreenBody>>,
+    mut built: Local<bool>,
+) {
+    if *built && !builder.is_changed() && !encounter.is_changed() {
+        return;
+    }
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    *built = true;
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        spawn_button_row(body, |row| {
+            for monster in compendium
+                .search(&builder.query)
+                .iter()
+                .take(MAX_SEARCH_RESULTS)
+            {
+                spawn_encounter_button(
+                    row,
+                    &format!("+ {} (CR {}, {} XP)", monster.name, monster.cr, monster.xp),
+                    false,
+                    EncounterButton::Add(monster.name.clone()),
+                    &theme,
+                );
+            }
+        });
+
+        let levels = &builder.levels;
+        spawn_encounter_text(
+            body,
+            format!(
+                "Party: {} characters, level {}",
+                levels.len(),
+                describe_levels(levels)
+            ),
+            18.0,
+            theme.primary,
+        );
+        spawn_button_row(body, |row| {
+            spawn_encounter_button(row, "Size -", false, EncounterButton::PartySmaller, &theme);
+            spawn_encounter_button(row, "Size +", false, EncounterButton::PartyLarger, &theme);
+            spawn_encounter_button(row, "Level -", false, EncounterButton::LevelDown, &theme);
+            spawn_encounter_button(row, "Level +", false, EncounterButton::LevelUp, &theme);
+            if builder.party_edited {
+                spawn_encounter_button(
+                    row,
+                    "Use Stored Characters",
+                    false,
+                    EncounterButton::UseCharacters,
+                    &theme,
+                );
+            }
+        });
+        let [easy, medium, hard, deadly] = party_thresholds(levels);
+        spawn_encounter_text(
+            body,
+            format!(
+                "XP thresholds: easy {} · medium {} · hard {} · deadly {}",
+                easy, medium, hard, deadly
+            ),
+            15.0,
+            theme.on_surface_variant,
+        );
+
+        spawn_encounter_text(body, "Monsters".to_string(), 18.0, theme.primary);
+        if encounter.monsters.is_empty() {
+            spawn_encounter_text(
+                body,
+                "Search above and add monsters to the encounter.".to_string(),
+                15.0,
+                theme.on_surface_variant,
+            );
+            return;
+        }
+        for monster in &encounter.monsters {
+            spawn_button_row(body, |row| {
+                spawn_encounter_button(
+                    row,
+                    "-",
+                    false,
+                    EncounterButton::Remove(monster.name.clone()),
+                    &theme,
+                );
+                spawn_encounter_button(
+                    row,
+                    "+",
+                    false,
+                    EncounterButton::Add(monster.name.clone()),
+                    &theme,
+                );
+                spawn_encounter_text(
+                    row,
+                    format!(
+                        "{} × {} ({} XP)",
+                        monster.count,
+                        monster.name,
+                        monster.xp * monster.count
+                    ),
+                    16.0,
+                    theme.on_surface,
+                );
+            });
+        }
+
+        let difficulty = encounter.difficulty(levels);
+        spawn_encounter_text(
+            body,
+            format!(
+                "{} XP × {} for {} monsters = {} adjusted XP",
+                encounter.base_xp(),
+                encounter_multiplier(encounter.monster_count(), levels.len()),
+                encounter.monster_count(),
+                encounter.adjusted_xp(levels.len())
+            ),
+            15.0,
+            theme.on_surface,
+        );
+        spawn_encounter_text(
+            body,
+            format!("Difficulty: {}", difficulty.label()),
+            20.0,
+            match difficulty {
+                EncounterDifficulty::Deadly => theme.error,
+                EncounterDifficulty::Hard => theme.tertiary,
+                _ => theme.primary,
+            },
+        );
+        spawn_button_row(body, |row| {
+            spawn_encounter_button(
+                row,
+                "Send to Initiative",
+                true,
+                EncounterButton::SendToInitiative,
+                &theme,
+            );
+            spawn_encounter_button(row, "Clear", false, EncounterButton::Clear, &theme);
+        });
+    });
+}
+
+/// "3" for a party of one level, "2-5 (3, 3, 2, 5)" otherwise
+fn describe_levels(levels: &[u32]) -> String {
+    let (Some(low), Some(high)) = (levels.iter().min(), levels.iter().max()) else {
+        return "-".to_string();
+    };
+    if low == high {
+        return low.to_string();
+    }
+    let each: Vec<String> = levels.iter().map(u32::to_string).collect();
+    format!("{}-{} ({})", low, high, each.join(", "))
+}
+
+fn spawn_button_row(
+    parent: &mut ChildSpawnerCommands,
+    children: impl FnOnce(&mut ChildSpawnerCommands),
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_encounter_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    button: EncounterButton,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (material_button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent
+        .spawn((material_button, button))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(text_color),
+                ButtonLabel,
+            ));
+        });
+}
+
+fn spawn_encounter_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
@@ -0,0 +1,351 @@
+//! Encryption passphrase dialog
+//!
+//! Asks for the passphrase at startup when the profile's database is
+//! encrypted, and lets encryption be turned on or off from the settings
+//! Profiles tab. The passphrase is only held until the button is pressed.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{CharacterDatabase, FocusTrap, MIN_PASSPHRASE_LEN};
+
+#[derive(Component)]
+pub struct EncryptionDialogOverlay;
+
+#[derive(Component)]
+pub struct EncryptionPassphraseField;
+
+#[derive(Component)]
+pub struct EncryptionUnlockButton;
+
+#[derive(Component)]
+pub struct EncryptionEnableButton;
+
+#[derive(Component)]
+pub struct EncryptionDisableButton;
+
+#[derive(Component)]
+pub struct EncryptionCloseButton;
+
+/// "Encryption…" button in the settings Profiles tab
+#[derive(Component)]
+pub struct EncryptionSettingsButton;
+
+/// What the dialog is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncryptionDialogMode {
+    /// Enter the passphrase of a locked database
+    #[default]
+    Unlock,
+    /// Turn encryption on or off
+    Manage,
+}
+
+/// State of the encryption dialog
+#[derive(Resource, Default)]
+pub struct EncryptionDialogState {
+    pub open: bool,
+    pub mode: EncryptionDialogMode,
+}
+
+/// Passphrase typed so far; kept out of [`EncryptionDialogState`] so typing
+/// doesn't rebuild the dialog.
+#[derive(Resource, Default)]
+pub struct EncryptionPassphraseInput {
+    pub text: String,
+}
+
+/// Ask for the passphrase when the database starts out locked.
+pub fn open_unlock_dialog_if_locked(
+    db: Option<Res<CharacterDatabase>>,
+    mut dialog: ResMut<EncryptionDialogState>,
+) {
+    if db.is_some_and(|db| db.is_locked()) {
+        dialog.mode = EncryptionDialogMode::Unlock;
+        dialog.open = true;
+    }
+}
+
+fn spawn_dialog_text(parent: &mut ChildSpawnerCommands, text: &str, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_dialog_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Show/hide and rebuild the encryption dialog when its state changes.
+pub fn manage_encryption_dialog(
+    mut commands: Commands,
+    dialog_state: Res<EncryptionDialogState>,
+    db: Option<Res<CharacterDatabase>>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<EncryptionDialogOverlay>>,
+) {
+    if !dialog_state.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(db) = db else {
+        return;
+    };
+    if !dialog_state.open {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let mode = dialog_state.mode;
+    let enabled = db.encryption_enabled();
+    let locked = db.is_locked();
+
+    let title = match mode {
+        EncryptionDialogMode::Unlock => "Unlock Database",
+        EncryptionDialogMode::Manage => "Encryption",
+    };
+    let dialog = MaterialDialog::new().title(title).open(true).modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(460.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            EncryptionDialogOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    let profile = CharacterDatabase::current_profile();
+    let (message, needs_passphrase) = match (mode, enabled, locked) {
+        (EncryptionDialogMode::Unlock, _, _) => (
+            format!(
+                "Character sheets in profile \"{}\" are encrypted. Enter the passphrase to open them.",
+                profile.name
+            ),
+            true,
+        ),
+        (EncryptionDialogMode::Manage, false, _) => (
+            format!(
+                "Encrypt the character sheets in profile \"{}\" with a passphrase \
+                 (at least {} characters). Names, classes and levels stay visible in \
+                 the character list. A forgotten passphrase cannot be recovered.",
+                profile.name, MIN_PASSPHRASE_LEN
+            ),
+            true,
+        ),
+        (EncryptionDialogMode::Manage, true, true) => (
+            "Enter the passphrase to turn encryption off.".to_string(),
+            true,
+        ),
+        (EncryptionDialogMode::Manage, true, false) => (
+            format!(
+                "Character sheets in profile \"{}\" are encrypted.",
+                profile.name
+            ),
+            false,
+        ),
+    };
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        spawn_dialog_text(dialog, title, 20.0, theme.on_surface);
+        spawn_dialog_text(dialog, &message, 14.0, theme.on_surface_variant);
+
+        if needs_passphrase {
+            dialog
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label("Passphrase")
+                        .value("")
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(slot, &theme, builder, EncryptionPassphraseField);
+                });
+        }
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                let close_label = match mode {
+                    EncryptionDialogMode::Unlock => "Not now",
+                    EncryptionDialogMode::Manage => "Close",
+                };
+                spawn_dialog_button(buttons, close_label, false, EncryptionCloseButton, &theme);
+
+                match (mode, enabled) {
+                    (EncryptionDialogMode::Unlock, _) => {
+                        spawn_dialog_button(buttons, "Unlock", true, EncryptionUnlockButton, &theme)
+                    }
+                    (EncryptionDialogMode::Manage, false) => spawn_dialog_button(
+                        buttons,
+                        "Encrypt",
+                        true,
+                        EncryptionEnableButton,
+                        &theme,
+                    ),
+                    (EncryptionDialogMode::Manage, true) => spawn_dialog_button(
+                        buttons,
+                        "Turn Off Encryption",
+                        true,
+                        EncryptionDisableButton,
+                        &theme,
+                    ),
+                }
+            });
+    });
+}
+
+/// Track the passphrase, run the dialog's actions and open it from settings.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_encryption_dialog_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut submit_events: MessageReader<TextFieldSubmitEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    passphrase_fields: Query<(), With<EncryptionPassphraseField>>,
+    unlock_buttons: Query<(), With<EncryptionUnlockButton>>,
+    enable_buttons: Query<(), With<EncryptionEnableButton>>,
+    disable_buttons: Query<(), With<EncryptionDisableButton>>,
+    close_buttons: Query<(), With<EncryptionCloseButton>>,
+    settings_buttons: Query<(), With<EncryptionSettingsButton>>,
+    db: Option<Res<CharacterDatabase>>,
+    mut dialog_state: ResMut<EncryptionDialogState>,
+    mut passphrase: ResMut<EncryptionPassphraseInput>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for ev in change_events.read() {
+        if passphrase_fields.get(ev.entity).is_ok() {
+            passphrase.text = ev.value.clone();
+        }
+    }
+
+    // Enter in the passphrase field does the same as the dialog's main button
+    let mut submitted = false;
+    for ev in submit_events.read() {
+        if passphrase_fields.get(ev.entity).is_ok() {
+            passphrase.text = ev.value.clone();
+            submitted = true;
+        }
+    }
+
+    let Some(db) = db else {
+        return;
+    };
+
+    let mut action = submitted.then_some(dialog_state.mode);
+    for event in click_events.read() {
+        if settings_buttons.get(event.entity).is_ok() {
+            passphrase.text.clear();
+            dialog_state.mode = EncryptionDialogMode::Manage;
+            dialog_state.open = true;
+        } else if close_buttons.get(event.entity).is_ok() {
+            passphrase.text.clear();
+            dialog_state.open = false;
+        } else if unlock_buttons.get(event.entity).is_ok() {
+            action = Some(EncryptionDialogMode::Unlock);
+        } else if enable_buttons.get(event.entity).is_ok()
+            || disable_buttons.get(event.entity).is_ok()
+        {
+            action = Some(EncryptionDialogMode::Manage);
+        }
+    }
+
+    let Some(action) = action else {
+        return;
+    };
+    if !dialog_state.open {
+        return;
+    }
+
+    let text = std::mem::take(&mut passphrase.text);
+    let result = match action {
+        EncryptionDialogMode::Unlock => db.unlock(&text).map(|_| "Database unlocked"),
+        EncryptionDialogMode::Manage if !db.encryption_enabled() => db
+            .enable_encryption(&text)
+            .map(|_| "Character sheets encrypted"),
+        EncryptionDialogMode::Manage => {
+            let unlocked = if db.is_locked() {
+                db.unlock(&text)
+            } else {
+                Ok(())
+            };
+            unlocked
+                .and_then(|_| db.disable_encryption())
+                .map(|_| "Encryption turned off")
+        }
+    };
+
+    match result {
+        Ok(message) => {
+            info!("{}", message);
+            dialog_state.open = false;
+            snackbar.write(ShowSnackbar::message(message).duration(2.0));
+        }
+        Err(e) => {
+            warn!("Encryption: {}", e);
+            // Rebuild so the passphrase field starts empty again
+            dialog_state.set_changed();
+            snackbar.write(ShowSnackbar::message(e).duration(3.0));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Gamepad systems
+//!
+//! Lets a controller aim a throw with the left stick (A throws, Y resets the
+//! dice, handled with the mouse and keyboard in `handle_input`; the left
+//! trigger lobs it, in `update_throw_elevation`) and rumbles
+//! every connected gamepad when dice knock into the container or each other
+//! and when a roll settles.
+
+use std::time::Duration;
+
+use bevy::input::gamepad::{Gamepad, GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::dice3d::throw_control::{ThrowControlState, BOX_HALF_EXTENT};
+use crate::dice3d::types::{impact_volume, AppTab, DiceSettled, Die, SettingsState, UiState};
+
+/// Stick deflection below which the stick is taken as centred
+const STICK_DEADZONE: f32 = 0.2;
+
+/// Shortest gap between two collision rumbles, so a tumbling handful of
+/// dice reads as a few knocks rather than one long buzz
+const CONTACT_RUMBLE_INTERVAL_SECONDS: f32 = 0.08;
+
+/// Aim the throw with the left stick: the direction picks the target on the
+/// box floor and how far the stick is pushed sets the strength. A centred
+/// stick leaves the mouse aim alone.
+pub fn aim_throw_with_gamepad(
+    gamepads: Query<&Gamepad>,
+    ui_state: Res<UiState>,
+    settings_state: Res<SettingsState>,
+    mut throw_state: ResMut<ThrowControlState>,
+) {
+    if ui_state.active_tab != AppTab::DiceRoller || settings_state.show_modal {
+        return;
+    }
+
+    let Some(stick) = gamepads
+        .iter()
+        .map(Gamepad::left_stick)
+        .find(|stick| stick.length() > STICK_DEADZONE)
+    else {
+        return;
+    };
+
+    let push = stick.length().min(1.0);
+    // Pushing the stick up throws away from the camera
+    let direction = Vec3::new(stick.x, 0.0, -stick.y).normalize_or_zero();
+    throw_state.target_point = direction * push * BOX_HALF_EXTENT;
+    throw_state.throw_strength = push;
+}
+
+/// Send a rumble to every connected gamepad.
+fn rumble_all(
+    gamepads: &Query<Entity, With<Gamepad>>,
+    rumble: &mut MessageWriter<GamepadRumbleRequest>,
+    intensity: GamepadRumbleIntensity,
+    duration: Duration,
+) {
+    for gamepad in gamepads {
+        rumble.write(GamepadRumbleRequest::Add {
+            gamepad,
+            intensity,
+            duration,
+        });
+    }
+}
+
+/// Short rumble when dice hit something, stronger the faster they move, and
+/// a longer one when the roll comes to rest.
+pub fn rumble_gamepads_on_dice(
+    time: Res<Time>,
+    settings_state: Res<SettingsState>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    dice_query: Query<&Velocity, With<Die>>,
+    mut collision_events: MessageReader<CollisionEvent>,
+    mut settled: MessageReader<DiceSettled>,
+    mut rumble: MessageWriter<GamepadRumbleRequest>,
+    mut last_contact_s: Local<f32>,
+) {
+    let settled_count = settled.read().count();
+    if !settings_state.settings.gamepad_rumble || gamepads.is_empty() {
+        collision_events.clear();
+        return;
+    }
+
+    let now_s = time.elapsed_secs();
+    let mut strongest: Option<f32> = None;
+    for ev in collision_events.read() {
+        let CollisionEvent::Started(e1, e2, _flags) = *ev else {
+            continue;
+        };
+        if !(dice_query.contains(e1) || dice_query.contains(e2)) {
+            continue;
+        }
+        // Same impact strength as the collision sounds
+        let strength = [e1, e2]
+            .into_iter()
+            .filter_map(|e| dice_query.get(e).ok())
+            .map(|v| v.linvel.length() + 0.15 * v.angvel.length())
+            .sum::<f32>();
+        strongest = Some(strongest.map_or(strength, |s| s.max(strength)));
+    }
+
+    if let Some(strength) = strongest {
+        if now_s - *last_contact_s >= CONTACT_RUMBLE_INTERVAL_SECONDS {
+            *last_contact_s = now_s;
+            let level = impact_volume(strength);
+            rumble_all(
+                &gamepads,
+                &mut rumble,
+                GamepadRumbleIntensity {
+                    strong_motor: 0.0,
+                    weak_motor: level,
+                },
+                Duration::from_millis(60),
+            );
+        }
+    }
+
+    if settled_count > 0 {
+        rumble_all(
+            &gamepads,
+            &mut rumble,
+            GamepadRumbleIntensity {
+                strong_motor: 0.6,
+                weak_motor: 0.3,
+            },
+            Duration::from_millis(180),
+        );
+    }
+}
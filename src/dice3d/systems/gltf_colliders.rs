@@ -19,6 +19,7 @@ use crate::dice3d::types::{
     DiceBox, DiceBoxWall, DiceContainerCentered, DiceContainerColliderGuide,
     DiceContainerCrystalMaterialApplied, DiceContainerGeneratedCollider, DiceContainerMaterials,
     DiceContainerProceduralCollider, DiceContainerVisualRoot, DiceContainerVoxelCollider,
+    CONTAINER_VOXEL_SIZE,
 };
 
 fn abs_vec3(v: Vec3) -> Vec3 {
@@ -101,7 +102,6 @@ pub fn spawn_colliders_from_gltf_guides(
     procedural_colliders: Query<Entity, With<DiceContainerProceduralCollider>>,
     mut debug: Local<ColliderGuideDebugState>,
 ) {
-    const VOXEL_SIZE: f32 = 0.2;
     let mut root_iter = container_root.iter();
     let Some((container_root_entity, container_root_gt)) = root_iter.next() else {
         if !debug.logged_no_root {
@@ -267,7 +267,12 @@ pub fn spawn_colliders_from_gltf_guides(
                 continue;
             }
 
-            let shape = SharedShape::voxelized_mesh(&verts, &tris, VOXEL_SIZE, FillMode::default());
+            let shape = SharedShape::voxelized_mesh(
+                &verts,
+                &tris,
+                CONTAINER_VOXEL_SIZE,
+                FillMode::default(),
+            );
             let collider = Collider::from(shape);
 
             commands
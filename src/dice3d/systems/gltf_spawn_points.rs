@@ -4,7 +4,7 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::Velocity;
 use rand::Rng;
 
-use crate::dice3d::types::{DiceBox, DiceContainerStyle, Die};
+use crate::dice3d::types::{DiceBox, DiceContainerStyle, Die, RollRng};
 
 /// Resource holding spawn point world positions extracted from glTF scenes.
 #[derive(Resource, Default, Debug, Clone)]
@@ -117,6 +117,7 @@ pub fn apply_spawn_points_to_dice_when_ready(
     mut applied: ResMut<DiceSpawnPointsApplied>,
     mut dice_query: Query<(&mut Transform, &mut Velocity), With<Die>>,
     _container_query: Query<(), With<DiceBox>>,
+    mut rng: ResMut<RollRng>,
 ) {
     let (points, already_applied) = match *style {
         DiceContainerStyle::Box => (&spawn_points.box_points, applied.box_applied),
@@ -127,7 +128,6 @@ pub fn apply_spawn_points_to_dice_when_ready(
         return;
     }
 
-    let mut rng = rand::rng();
     for (i, (mut transform, mut velocity)) in dice_query.iter_mut().enumerate() {
         let p = points[i % points.len()] + Vec3::Y * 0.15;
         transform.translation = p;
@@ -0,0 +1,1292 @@
+//! Initiative tab
+//!
+//! Rolls initiative for the stored characters (d20 + DEX modifier) and for
+//! monsters, keeps them in turn order, advances turns and counts rounds. The
+//! turn order is the `InitiativeTracker` resource, so reactions, readied
+//! actions and the session clock follow the same combat.
+//!
+//! Named monsters are added with the `init <name> [modifier] [dc<N>] [hp<N>]
+//! [surprised]` command; a surprised monster loses its first turn, or rolls
+//! initiative with disadvantage under the 2024 rules. Combatants with a spell save DC (party members with spellcasting,
+//! monsters added with `dc15`) get "save vs" buttons on everyone else's row
+//! that roll a saving throw against that DC.
+//!
+//! Turns can be timed (`timer 60`, or the Turn Timer button): a beep warns
+//! when a turn is about to run out and, with auto-advance, the next combatant
+//! goes when it does. The chess clock keeps everyone's total decision time.
+//!
+//! Each row has buttons for the combatant's reaction: a plain reaction, an
+//! opportunity attack, readying the action typed in the "Readied action"
+//! field and taking it when its trigger happens. Spending a reaction that was
+//! already used this round asks for confirmation first.
+//!
+//! The encounter can be exported to Foundry VTT or Improved Initiative to carry
+//! on the fight there (see `vtt_export`).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    ability_abbreviation, export_encounter, format_clock, CharacterDatabase, ConditionEffect,
+    D20Test, FocusTrap, InitiativeScreenRoot, InitiativeTracker, ReactionCheck, RollEngine,
+    RollRng, Ruleset, TurnTimer, TurnTimerAlert, TurnTimerConfig, TurnTimerWarning, TypedCommand,
+    VttFormat, ABILITY_NAMES,
+};
+
+/// Name of combatants added with the Add Monster button
+const DEFAULT_MONSTER_NAME: &str = "Monster";
+
+/// Node the turn order is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct InitiativeScreenBody;
+
+/// Time left on the turn and the chess clock, updated every frame
+#[derive(Component)]
+pub struct TurnTimerLabel;
+
+/// Text field holding the action (and its trigger) the Ready buttons ready
+#[derive(Component)]
+pub struct ReadiedActionField;
+
+#[derive(Component)]
+pub struct ReactionConfirmOverlay;
+
+#[derive(Component)]
+pub struct ReactionConfirmYesButton;
+
+#[derive(Component)]
+pub struct ReactionConfirmNoButton;
+
+/// What a combatant spends its reaction on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionUse {
+    Reaction,
+    OpportunityAttack,
+    /// Take the readied action
+    ReadiedAction,
+}
+
+/// Buttons of the Initiative tab
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitiativeButton {
+    /// Roll for every stored character not in the turn order yet
+    AddParty,
+    AddMonster,
+    RerollAll,
+    StartCombat,
+    NextTurn,
+    EndCombat,
+    Clear,
+    /// Remove the combatant at this index
+    Remove(usize),
+    /// Cycle the ability "save vs" buttons roll
+    SaveAbility,
+    /// Roll a save for `target` against `caster`'s spell save DC
+    SaveVs {
+        target: usize,
+        caster: usize,
+    },
+    /// Save the encounter for a VTT
+    Export(VttFormat),
+    /// Cycle the turn length through `TURN_TIMER_PRESETS` and off
+    TimerLimit,
+    TimerAutoAdvance,
+    ChessClock,
+    /// Pause or resume the turn timer
+    TimerPause,
+    /// Spend the reaction of the combatant at `index`
+    Reaction {
+        index: usize,
+        reaction: ReactionUse,
+    },
+    /// Ready the action in the "Readied action" field
+    Ready(usize),
+}
+
+/// Action typed into the "Readied action" field
+#[derive(Resource, Default)]
+pub struct ReadiedAction(pub String);
+
+/// Reaction waiting for the user to confirm spending it a second time this
+/// round
+#[derive(Resource, Default)]
+pub struct ReactionPrompt {
+    pub pending: Option<PendingReaction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingReaction {
+    pub index: usize,
+    pub reaction: ReactionUse,
+    pub name: String,
+}
+
+/// Ability the "save vs" buttons roll, by index into `ABILITY_NAMES`
+#[derive(Resource)]
+pub struct SaveVsAbility(pub usize);
+
+impl Default for SaveVsAbility {
+    /// Wisdom, the most common save-or-suck save
+    fn default() -> Self {
+        Self(4)
+    }
+}
+
+impl SaveVsAbility {
+    pub fn name(&self) -> &'static str {
+        ABILITY_NAMES[self.0]
+    }
+}
+
+/// An `init` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitiativeCommand {
+    pub name: String,
+    pub modifier: i32,
+    /// Spell save DC, for monsters that cast spells
+    pub save_dc: Option<i32>,
+    /// Maximum hit points
+    pub hit_points: Option<i32>,
+    pub surprised: bool,
+}
+
+impl TypedCommand for InitiativeCommand {
+    const NAME: &'static str = "init";
+
+    /// Parse `init <name> [modifier] [dc<N>] [hp<N>] [surprised]` ("init goblin
+    /// +2", "init Young Dragon 0", "init Mage +2 dc14 hp40 surprised").
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("init") {
+            return None;
+        }
+
+        let mut words: Vec<&str> = rest.split_whitespace().collect();
+        let mut save_dc = None;
+        let mut hit_points = None;
+        let mut surprised = false;
+        while let Some(word) = words.last() {
+            if let Some(dc) = parse_prefixed_number(word, "dc") {
+                save_dc = Some(dc);
+            } else if let Some(hp) = parse_prefixed_number(word, "hp") {
+                hit_points = Some(hp);
+            } else if word.eq_ignore_ascii_case("surprised") {
+                surprised = true;
+            } else {
+                break;
+            }
+            words.pop();
+        }
+        let modifier = match words.last().and_then(|w| w.parse::<i32>().ok()) {
+            Some(modifier) => {
+                words.pop();
+                modifier
+            }
+            None => 0,
+        };
+        if words.is_empty() {
+            return None;
+        }
+        Some(InitiativeCommand {
+            name: words.join(" "),
+            modifier,
+            save_dc,
+            hit_points,
+            surprised,
+        })
+    }
+}
+
+/// `dc14` or `hp40` (any case) as a number
+fn parse_prefixed_number(word: &str, expected: &str) -> Option<i32> {
+    let (prefix, digits) = word.split_at_checked(expected.len())?;
+    if !prefix.eq_ignore_ascii_case(expected) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Roll initiative for the monster of an `init` command.
+pub fn run_initiative_command(
+    In(command): In<InitiativeCommand>,
+    mut tracker: ResMut<InitiativeTracker>,
+    ruleset: Res<Ruleset>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let ruleset = *ruleset;
+    let name = tracker.unique_name(&command.name);
+    let mut initiative = tracker.roll_combatant(&command.name, command.modifier, None, &mut *rng);
+    tracker.set_save_dc(&name, command.save_dc);
+    tracker.set_hit_points(&name, command.hit_points);
+    let mut message = format!("{} rolled {} for initiative", name, initiative);
+    if command.surprised {
+        initiative = tracker
+            .surprise(&name, ruleset, &mut *rng)
+            .unwrap_or(initiative);
+        message = if ruleset.surprise_disadvantage() {
+            format!(
+                "{} is surprised and rolled {} for initiative (disadvantage)",
+                name, initiative
+            )
+        } else {
+            format!("{}; surprised, it loses its first turn", message)
+        };
+    }
+    info!("{}", message);
+    snackbar.write(ShowSnackbar::message(message).duration(2.5));
+}
+
+/// A `timer` command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerCommand {
+    /// Show how turns are timed
+    Show,
+    /// Seconds per turn, `None` for untimed turns
+    Limit(Option<u32>),
+    AutoAdvance(bool),
+    ChessClock(bool),
+    Pause(bool),
+}
+
+impl TypedCommand for TimerCommand {
+    const NAME: &'static str = "timer";
+
+    /// Parse `timer`, `timer <seconds>`, `timer off`, `timer auto [off]`,
+    /// `timer chess [off]` and `timer pause` / `timer resume`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("timer") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (verb, switch) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let switch = switch.trim();
+        let on = if switch.is_empty() || switch.eq_ignore_ascii_case("on") {
+            true
+        } else if switch.eq_ignore_ascii_case("off") {
+            false
+        } else {
+            return None;
+        };
+
+        if verb.is_empty() {
+            Some(TimerCommand::Show)
+        } else if verb.eq_ignore_ascii_case("off") && switch.is_empty() {
+            Some(TimerCommand::Limit(None))
+        } else if verb.eq_ignore_ascii_case("auto") {
+            Some(TimerCommand::AutoAdvance(on))
+        } else if verb.eq_ignore_ascii_case("chess") {
+            Some(TimerCommand::ChessClock(on))
+        } else if verb.eq_ignore_ascii_case("pause") && switch.is_empty() {
+            Some(TimerCommand::Pause(true))
+        } else if verb.eq_ignore_ascii_case("resume") && switch.is_empty() {
+            Some(TimerCommand::Pause(false))
+        } else if switch.is_empty() {
+            let seconds = verb.strip_suffix(['s', 'S']).unwrap_or(verb);
+            seconds
+                .parse::<u32>()
+                .ok()
+                .filter(|&s| s > 0)
+                .map(|s| TimerCommand::Limit(Some(s)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Apply a `timer` command, notifying the user.
+pub fn run_timer_command(
+    In(command): In<TimerCommand>,
+    mut tracker: ResMut<InitiativeTracker>,
+    mut timer: ResMut<TurnTimer>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    match command {
+        TimerCommand::Show => {}
+        TimerCommand::Limit(limit) => tracker.timer.limit_seconds = limit,
+        TimerCommand::AutoAdvance(on) => tracker.timer.auto_advance = on,
+        TimerCommand::ChessClock(on) => tracker.timer.chess_clock = on,
+        TimerCommand::Pause(paused) => timer.paused = paused,
+    }
+    let mut message = describe_turn_timer(&tracker.timer);
+    if timer.paused && tracker.timer.is_enabled() {
+        message.push_str(" (paused)");
+    }
+    snackbar.write(ShowSnackbar::message(message).duration(2.5));
+}
+
+/// "Turn timer 60s, auto-advance, chess clock"
+fn describe_turn_timer(config: &TurnTimerConfig) -> String {
+    let mut parts = vec![match config.limit_seconds {
+        Some(limit) => format!("Turn timer {}s", limit),
+        None => "Turn timer off".to_string(),
+    }];
+    if config.limit_seconds.is_some() && config.auto_advance {
+        parts.push("auto-advance".to_string());
+    }
+    if config.chess_clock {
+        parts.push("chess clock".to_string());
+    }
+    parts.join(", ")
+}
+
+/// Time left on the turn, whether the timer is paused and the chess clock.
+fn turn_timer_text(tracker: &InitiativeTracker, timer: &TurnTimer) -> String {
+    let mut parts = Vec::new();
+    if let Some(remaining) = timer.remaining(&tracker.timer) {
+        parts.push(if remaining > 0.0 {
+            format!("{} left", format_clock(remaining))
+        } else {
+            "Time's up".to_string()
+        });
+    }
+    if timer.paused {
+        parts.push("paused".to_string());
+    }
+    if tracker.timer.chess_clock {
+        for combatant in &tracker.combatants {
+            parts.push(format!(
+                "{} {}",
+                combatant.name,
+                format_clock(timer.total(&combatant.name))
+            ));
+        }
+    }
+    parts.join(" · ")
+}
+
+/// Roll initiative for every stored character not in the turn order yet.
+///
+/// Returns how many were added.
+fn add_party(
+    tracker: &mut InitiativeTracker,
+    db: &CharacterDatabase,
+    rng: &mut RollRng,
+) -> Result<usize, String> {
+    let mut added = 0;
+    for entry in db.list_characters()? {
+        if tracker.has_character(entry.id) {
+            continue;
+        }
+        let sheet = db.load_character(entry.id)?;
+        let name = tracker.unique_name(&entry.name);
+        tracker.roll_combatant(&name, sheet.modifiers.dexterity, Some(entry.id), rng);
+        if sheet.spells.is_some() {
+            tracker.set_save_dc(&name, Some(sheet.spell_save_dc()));
+        }
+        added += 1;
+    }
+    Ok(added)
+}
+
+/// Sheet JSON of the stored characters in the encounter, by character id.
+fn party_sheets(
+    tracker: &InitiativeTracker,
+    db: Option<&CharacterDatabase>,
+) -> Result<HashMap<i64, serde_json::Value>, String> {
+    let mut sheets = HashMap::new();
+    let ids = tracker.combatants.iter().filter_map(|c| c.character_id);
+    for id in ids {
+        let db = db.ok_or("No character database")?;
+        let sheet = db.load_character(id)?;
+        let json = serde_json::to_value(&sheet)
+            .map_err(|e| format!("Failed to serialize {}: {}", sheet.character.name, e))?;
+        sheets.insert(id, json);
+    }
+    Ok(sheets)
+}
+
+/// Save the encounter as a file for `format`, returning where it went.
+///
+/// `Ok(None)` when the save dialog was cancelled.
+fn export_to_vtt(
+    format: VttFormat,
+    tracker: &InitiativeTracker,
+    db: Option<&CharacterDatabase>,
+) -> Result<Option<PathBuf>, String> {
+    let party = party_sheets(tracker, db)?;
+    let json = export_encounter(format, tracker, &party)?;
+    let Some(path) = pick_vtt_export_path(format) else {
+        return Ok(None);
+    };
+    let text = serde_json::to_string_pretty(&json)
+        .map_err(|e| format!("Failed to serialize the encounter: {}", e))?;
+    std::fs::write(&path, text)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(Some(path))
+}
+
+/// Destination of an encounter export.
+#[cfg(feature = "integrations")]
+fn pick_vtt_export_path(format: VttFormat) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .set_title(format!("Export encounter to {}", format.label()))
+        .add_filter(format.label(), &["json"])
+        .set_file_name(format.file_name())
+        .save_file()
+}
+
+/// Without file dialogs the encounter is saved in the working directory.
+#[cfg(not(feature = "integrations"))]
+fn pick_vtt_export_path(format: VttFormat) -> Option<PathBuf> {
+    Some(PathBuf::from(format.file_name()))
+}
+
+/// Save modifier of a combatant: a stored character's saving throw, or the
+/// initiative (DEX) modifier for a monster's Dexterity save. A stored
+/// character's conditions come with it, for what they do to the save.
+fn save_modifier(
+    tracker: &InitiativeTracker,
+    target: usize,
+    ability: &str,
+    db: Option<&CharacterDatabase>,
+    ruleset: Ruleset,
+) -> Result<(i32, ConditionEffect), String> {
+    let combatant = tracker
+        .combatants
+        .get(target)
+        .ok_or_else(|| "Unknown combatant".to_string())?;
+    match (combatant.character_id, db) {
+        (Some(id), Some(db)) => {
+            let sheet = db.load_character(id)?;
+            let modifier = sheet
+                .saving_throws
+                .get(ability)
+                .map(|save| save.modifier)
+                .or_else(|| sheet.modifiers.get(ability))
+                .unwrap_or(0);
+            let effect = sheet
+                .conditions
+                .effect_on(&D20Test::SavingThrow(ability.to_string()), ruleset);
+            Ok((modifier, effect))
+        }
+        _ if ability == "dexterity" => Ok((combatant.modifier, ConditionEffect::default())),
+        _ => Ok((0, ConditionEffect::default())),
+    }
+}
+
+/// Spend a combatant's reaction, or say whose reaction is already spent.
+///
+/// Taking a readied action costs the reaction too, but the tracker only
+/// checks it here so a spent reaction can be confirmed first.
+fn check_reaction(
+    tracker: &mut InitiativeTracker,
+    index: usize,
+    reaction: ReactionUse,
+) -> ReactionCheck {
+    match reaction {
+        ReactionUse::Reaction => tracker.use_reaction(index),
+        ReactionUse::OpportunityAttack => tracker.request_opportunity_attack(index),
+        ReactionUse::ReadiedAction => match tracker.combatants.get(index) {
+            Some(combatant) if combatant.reaction_used => ReactionCheck::AlreadySpent {
+                name: combatant.name.clone(),
+            },
+            Some(_) => ReactionCheck::Available,
+            None => ReactionCheck::UnknownCombatant,
+        },
+    }
+}
+
+/// Finish spending a reaction whose use was checked (or confirmed), returning
+/// what happened.
+fn take_reaction(
+    tracker: &mut InitiativeTracker,
+    index: usize,
+    reaction: ReactionUse,
+) -> Option<String> {
+    let name = tracker.combatants.get(index)?.name.clone();
+    Some(match reaction {
+        ReactionUse::Reaction => format!("{} uses a reaction", name),
+        ReactionUse::OpportunityAttack => format!("{} makes an opportunity attack", name),
+        ReactionUse::ReadiedAction => {
+            let action = tracker.trigger_readied_action(index)?;
+            format!("{} takes the readied action: {}", name, action)
+        }
+    })
+}
+
+/// Spawn the (hidden) Initiative tab.
+pub fn setup_initiative_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            InitiativeScreenRoot,
+        ))
+        .with_children(|parent| {
+            spawn_initiative_text(parent, "Initiative".to_string(), 28.0, theme.on_surface);
+            parent
+                .spawn(Node {
+                    width: Val::Px(520.0),
+                    margin: UiRect::top(Val::Px(12.0)),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label("Readied action")
+                        .placeholder("Attack the first goblin through the door")
+                        .auto_focus(false)
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(slot, &theme, builder, ReadiedActionField);
+                });
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        margin: UiRect::top(Val::Px(12.0)),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        InitiativeScreenBody,
+                    ));
+                });
+        });
+}
+
+/// Track the readied action and handle the Initiative tab's buttons.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_initiative_clicks(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    readied_fields: Query<(), With<ReadiedActionField>>,
+    buttons: Query<&InitiativeButton>,
+    db: Option<Res<CharacterDatabase>>,
+    mut tracker: ResMut<InitiativeTracker>,
+    mut save_ability: ResMut<SaveVsAbility>,
+    mut turn_timer: ResMut<TurnTimer>,
+    mut readied_action: ResMut<ReadiedAction>,
+    mut reaction_prompt: ResMut<ReactionPrompt>,
+    ruleset: Res<Ruleset>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for ev in change_events.read() {
+        if readied_fields.contains(ev.entity) && readied_action.0 != ev.value {
+            readied_action.0 = ev.value.clone();
+        }
+    }
+
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        match *button {
+            InitiativeButton::AddParty => {
+                let Some(db) = db.as_deref() else {
+                    continue;
+                };
+                match add_party(&mut tracker, db, &mut rng) {
+                    Ok(0) => {
+                        snackbar.write(ShowSnackbar::message("No characters to add").duration(2.0));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("Failed to add the party to initiative: {}", e);
+                        snackbar.write(ShowSnackbar::message("Failed to load party").duration(2.0));
+                    }
+                }
+            }
+            InitiativeButton::AddMonster => {
+                tracker.roll_combatant(DEFAULT_MONSTER_NAME, 0, None, &mut *rng);
+            }
+            InitiativeButton::RerollAll => tracker.reroll_all(&mut *rng),
+            InitiativeButton::StartCombat => tracker.start_combat(),
+            InitiativeButton::NextTurn => tracker.advance_turn(),
+            InitiativeButton::EndCombat => tracker.end_combat(),
+            InitiativeButton::Clear => tracker.clear(),
+            InitiativeButton::Remove(index) => {
+                tracker.remove_combatant(index);
+                if tracker.combatants.is_empty() {
+                    tracker.end_combat();
+                }
+            }
+            InitiativeButton::SaveAbility => {
+                save_ability.0 = (save_ability.0 + 1) % ABILITY_NAMES.len();
+            }
+            InitiativeButton::TimerLimit => {
+                tracker.timer.limit_seconds = tracker.timer.next_limit();
+            }
+            InitiativeButton::TimerAutoAdvance => {
+                tracker.timer.auto_advance = !tracker.timer.auto_advance;
+            }
+            InitiativeButton::ChessClock => {
+                tracker.timer.chess_clock = !tracker.timer.chess_clock;
+            }
+            InitiativeButton::TimerPause => turn_timer.paused = !turn_timer.paused,
+            InitiativeButton::Reaction { index, reaction } => {
+                match check_reaction(&mut tracker, index, reaction) {
+                    ReactionCheck::Available => {
+                        if let Some(message) = take_reaction(&mut tracker, index, reaction) {
+                            info!("{}", message);
+                            snackbar.write(ShowSnackbar::message(message).duration(2.5));
+                        }
+                    }
+                    ReactionCheck::AlreadySpent { name } => {
+                        reaction_prompt.pending = Some(PendingReaction {
+                            index,
+                            reaction,
+                            name,
+                        });
+                    }
+                    ReactionCheck::UnknownCombatant => {}
+                }
+            }
+            InitiativeButton::Ready(index) => {
+                let action = readied_action.0.trim();
+                if action.is_empty() {
+                    snackbar.write(
+                        ShowSnackbar::message("Type the readied action and its trigger first")
+                            .duration(2.5),
+                    );
+                    continue;
+                }
+                if tracker.ready_action(index, action) {
+                    let message = format!("{} readies: {}", tracker.combatants[index].name, action);
+                    snackbar.write(ShowSnackbar::message(message).duration(2.5));
+                }
+            }
+            InitiativeButton::SaveVs { target, caster } => {
+                let ability = save_ability.name();
+                let (modifier, effect) =
+                    match save_modifier(&tracker, target, ability, db.as_deref(), *ruleset) {
+                        Ok(save) => save,
+                        Err(e) => {
+                            warn!("Failed to load the save modifier: {}", e);
+                            snackbar.write(
+                                ShowSnackbar::message("Failed to load character").duration(2.0),
+                            );
+                            continue;
+                        }
+                    };
+                // Paralyzed, stunned or unconscious: Strength and Dexterity
+                // saves fail without a roll
+                if effect.fails_automatically() {
+                    let message = format!(
+                        "{}: {} save fails automatically ({})",
+                        tracker.combatants[target].name,
+                        ability_abbreviation(ability),
+                        effect.auto_fail.join(", ")
+                    );
+                    snackbar.write(ShowSnackbar::message(message).duration(3.0));
+                    continue;
+                }
+                let modifier = modifier + effect.penalty();
+                let (roll, _) = RollEngine::with_rng(&mut *rng)
+                    .roll_d20_test(effect.has_advantage(), effect.has_disadvantage());
+                if let Some(save) = tracker.save_against(target, caster, ability, modifier, roll) {
+                    let message =
+                        format!("{}: {}", tracker.combatants[target].name, save.describe());
+                    info!("{}", message);
+                    snackbar.write(ShowSnackbar::message(message).duration(3.0));
+                }
+            }
+            InitiativeButton::Export(format) => {
+                match export_to_vtt(format, &tracker, db.as_deref()) {
+                    Ok(Some(path)) => {
+                        info!(
+                            "Exported the encounter to {} as {}",
+                            format.label(),
+                            path.display()
+                        );
+                        snackbar.write(
+                            ShowSnackbar::message(format!("Saved {}", path.display()))
+                                .duration(2.0),
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("Failed to export the encounter: {}", e);
+                        snackbar.write(
+                            ShowSnackbar::message("Failed to export the encounter").duration(2.0),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rebuild the turn order when the tracker changes.
+pub fn update_initiative_screen(
+    mut commands: Commands,
+    tracker: Res<InitiativeTracker>,
+    save_ability: Res<SaveVsAbility>,
+    turn_timer: Res<TurnTimer>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<InitiativeScreenBody>>,
+    mut shown_paused: Local<bool>,
+) {
+    // The timer itself changes every frame; only pausing changes the buttons
+    if !tracker.is_changed() && !save_ability.is_changed() && *shown_paused == turn_timer.paused {
+        return;
+    }
+    *shown_paused = turn_timer.paused;
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        spawn_initiative_text(
+            body,
+            match tracker.current() {
+                Some(current) => format!("Round {} · {}'s turn", tracker.round, current.name),
+                None => "Combat has not started".to_string(),
+            },
+            18.0,
+            theme.primary,
+        );
+        if tracker.is_active() && tracker.timer.is_enabled() {
+            body.spawn((
+                Text::new(turn_timer_text(&tracker, &turn_timer)),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                TurnTimerLabel,
+            ));
+        }
+
+        body.spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|buttons| {
+            if tracker.is_active() {
+                spawn_initiative_button(
+                    buttons,
+                    "Next Turn",
+                    true,
+                    InitiativeButton::NextTurn,
+                    &theme,
+                );
+                spawn_initiative_button(
+                    buttons,
+                    "End Combat",
+                    false,
+                    InitiativeButton::EndCombat,
+                    &theme,
+                );
+            } else if !tracker.combatants.is_empty() {
+                spawn_initiative_button(
+                    buttons,
+                    "Start Combat",
+                    true,
+                    InitiativeButton::StartCombat,
+                    &theme,
+                );
+            }
+            spawn_initiative_button(
+                buttons,
+                "Add Party",
+                false,
+                InitiativeButton::AddParty,
+                &theme,
+            );
+            spawn_initiative_button(
+                buttons,
+                "Add Monster",
+                false,
+                InitiativeButton::AddMonster,
+                &theme,
+            );
+            if !tracker.combatants.is_empty() {
+                spawn_initiative_button(
+                    buttons,
+                    "Reroll All",
+                    false,
+                    InitiativeButton::RerollAll,
+                    &theme,
+                );
+                spawn_initiative_button(buttons, "Clear", false, InitiativeButton::Clear, &theme);
+                for format in [VttFormat::FoundryVtt, VttFormat::ImprovedInitiative] {
+                    spawn_initiative_button(
+                        buttons,
+                        &format!("Export to {}", format.label()),
+                        false,
+                        InitiativeButton::Export(format),
+                        &theme,
+                    );
+                }
+            }
+            if tracker.casters().next().is_some() {
+                spawn_initiative_button(
+                    buttons,
+                    &format!("Saves: {}", ability_abbreviation(save_ability.name())),
+                    false,
+                    InitiativeButton::SaveAbility,
+                    &theme,
+                );
+            }
+        });
+
+        if !tracker.combatants.is_empty() {
+            body.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(10.0),
+                row_gap: Val::Px(10.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                let on_off = |on: bool| if on { "On" } else { "Off" };
+                spawn_initiative_button(
+                    buttons,
+                    &match tracker.timer.limit_seconds {
+                        Some(limit) => format!("Turn Timer: {}s", limit),
+                        None => "Turn Timer: Off".to_string(),
+                    },
+                    false,
+                    InitiativeButton::TimerLimit,
+                    &theme,
+                );
+                if tracker.timer.limit_seconds.is_some() {
+                    spawn_initiative_button(
+                        buttons,
+                        &format!("Auto-advance: {}", on_off(tracker.timer.auto_advance)),
+                        false,
+                        InitiativeButton::TimerAutoAdvance,
+                        &theme,
+                    );
+                }
+                spawn_initiative_button(
+                    buttons,
+                    &format!("Chess Clock: {}", on_off(tracker.timer.chess_clock)),
+                    false,
+                    InitiativeButton::ChessClock,
+                    &theme,
+                );
+                if tracker.is_active() && tracker.timer.is_enabled() {
+                    spawn_initiative_button(
+                        buttons,
+                        if turn_timer.paused {
+                            "Resume Timer"
+                        } else {
+                            "Pause Timer"
+                        },
+                        false,
+                        InitiativeButton::TimerPause,
+                        &theme,
+                    );
+                }
+            });
+        }
+
+        if tracker.combatants.is_empty() {
+            spawn_initiative_text(
+                body,
+                "Add the party and monsters to roll initiative. Name monsters with `init goblin +2` in the dice roller.".to_string(),
+                15.0,
+                theme.on_surface_variant,
+            );
+            return;
+        }
+
+        let current = tracker.current().map(|_| tracker.current_turn);
+        for (index, combatant) in tracker.combatants.iter().enumerate() {
+            let is_current = current == Some(index);
+            body.spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(16.0),
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    ..default()
+                },
+                BackgroundColor(if is_current {
+                    theme.primary_container
+                } else {
+                    theme.surface_container
+                }),
+                BorderRadius::all(Val::Px(CornerRadius::MEDIUM)),
+            ))
+            .with_children(|row| {
+                let text_color = if is_current {
+                    theme.on_primary_container
+                } else {
+                    theme.on_surface
+                };
+                spawn_initiative_text(
+                    row,
+                    format!("{:>3}", combatant.initiative),
+                    20.0,
+                    text_color,
+                );
+                let mut details = format!("{} ({:+})", combatant.name, combatant.modifier);
+                if combatant.surprised {
+                    details.push_str(" · surprised");
+                }
+                if combatant.reaction_used {
+                    details.push_str(" · reaction used");
+                }
+                if let Some(action) = &combatant.readied_action {
+                    details.push_str(&format!(" · readied: {}", action));
+                }
+                if let Some(hp) = &combatant.hit_points {
+                    details.push_str(&format!(" · HP {}/{}", hp.current, hp.maximum));
+                }
+                if let Some(dc) = combatant.save_dc {
+                    details.push_str(&format!(" · save DC {}", dc));
+                }
+                if let Some(save) = &combatant.last_save {
+                    details.push_str(&format!(" · {}", save.describe()));
+                }
+                spawn_initiative_text(row, details, 16.0, text_color);
+                for (caster, caster_combatant) in tracker.casters() {
+                    if caster == index {
+                        continue;
+                    }
+                    spawn_initiative_button(
+                        row,
+                        &format!(
+                            "Save vs {} (DC {})",
+                            caster_combatant.name,
+                            caster_combatant.save_dc.unwrap_or_default()
+                        ),
+                        false,
+                        InitiativeButton::SaveVs {
+                            target: index,
+                            caster,
+                        },
+                        &theme,
+                    );
+                }
+                spawn_initiative_button(
+                    row,
+                    "Reaction",
+                    false,
+                    InitiativeButton::Reaction {
+                        index,
+                        reaction: ReactionUse::Reaction,
+                    },
+                    &theme,
+                );
+                spawn_initiative_button(
+                    row,
+                    "Opportunity Attack",
+                    false,
+                    InitiativeButton::Reaction {
+                        index,
+                        reaction: ReactionUse::OpportunityAttack,
+                    },
+                    &theme,
+                );
+                if combatant.readied_action.is_some() {
+                    spawn_initiative_button(
+                        row,
+                        "Take Readied Action",
+                        false,
+                        InitiativeButton::Reaction {
+                            index,
+                            reaction: ReactionUse::ReadiedAction,
+                        },
+                        &theme,
+                    );
+                } else {
+                    spawn_initiative_button(
+                        row,
+                        "Ready",
+                        false,
+                        InitiativeButton::Ready(index),
+                        &theme,
+                    );
+                }
+                spawn_initiative_button(
+                    row,
+                    "Remove",
+                    false,
+                    InitiativeButton::Remove(index),
+                    &theme,
+                );
+            });
+        }
+    });
+}
+
+/// Show/hide the confirmation dialog for spending a reaction twice in a round.
+pub fn manage_reaction_confirm_dialog(
+    mut commands: Commands,
+    prompt: Res<ReactionPrompt>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<ReactionConfirmOverlay>>,
+) {
+    if !prompt.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(pending) = prompt.pending.as_ref() else {
+        return;
+    };
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("Reaction")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            ReactionConfirmOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    let action = match pending.reaction {
+        ReactionUse::Reaction => "Use another reaction",
+        ReactionUse::OpportunityAttack => "Make the opportunity attack",
+        ReactionUse::ReadiedAction => "Take the readied action",
+    };
+    commands.entity(dialog_entity).with_children(|dialog| {
+        dialog.spawn((
+            Text::new("Reaction already used"),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(theme.on_surface),
+        ));
+
+        dialog.spawn((
+            Text::new(format!(
+                "{} has already used a reaction this round. {} anyway?",
+                pending.name, action
+            )),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_confirm_button(buttons, "Cancel", false, ReactionConfirmNoButton, &theme);
+                spawn_confirm_button(
+                    buttons,
+                    "Use Anyway",
+                    true,
+                    ReactionConfirmYesButton,
+                    &theme,
+                );
+            });
+    });
+}
+
+/// Spend or discard the pending reaction.
+pub fn handle_reaction_confirm_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    yes_buttons: Query<(), With<ReactionConfirmYesButton>>,
+    no_buttons: Query<(), With<ReactionConfirmNoButton>>,
+    mut prompt: ResMut<ReactionPrompt>,
+    mut tracker: ResMut<InitiativeTracker>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        if yes_buttons.get(event.entity).is_ok() {
+            let Some(pending) = prompt.pending.take() else {
+                continue;
+            };
+            if let Some(message) = take_reaction(&mut tracker, pending.index, pending.reaction) {
+                info!("{}", message);
+                snackbar.write(ShowSnackbar::message(message).duration(2.5));
+            }
+        } else if no_buttons.get(event.entity).is_ok() {
+            prompt.pending = None;
+        }
+    }
+}
+
+/// Time the current turn, warn when it runs low and, with auto-advance, move
+/// on to the next combatant when it runs out.
+pub fn tick_turn_timer(
+    time: Res<Time>,
+    mut tracker: ResMut<InitiativeTracker>,
+    mut turn_timer: ResMut<TurnTimer>,
+    mut warnings: MessageWriter<TurnTimerWarning>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let Some(alert) = turn_timer.tick(&tracker, time.delta_secs()) else {
+        return;
+    };
+    let message = match alert {
+        TurnTimerAlert::Warning { name, remaining } => {
+            warnings.write(TurnTimerWarning { expired: false });
+            format!("{}: {} seconds left", name, remaining)
+        }
+        TurnTimerAlert::Expired { name } => {
+            warnings.write(TurnTimerWarning { expired: true });
+            if tracker.timer.auto_advance {
+                tracker.advance_turn();
+                match tracker.current() {
+                    Some(next) => format!("{}'s time is up · {}'s turn", name, next.name),
+                    None => format!("{}'s time is up", name),
+                }
+            } else {
+                format!("{}'s time is up", name)
+            }
+        }
+    };
+    info!("{}", message);
+    snackbar.write(ShowSnackbar::message(message).duration(2.5));
+}
+
+/// Keep the time left on the turn and the chess clock current.
+pub fn update_turn_timer_label(
+    tracker: Res<InitiativeTracker>,
+    turn_timer: Res<TurnTimer>,
+    mut labels: Query<&mut Text, With<TurnTimerLabel>>,
+) {
+    for mut text in &mut labels {
+        let shown = turn_timer_text(&tracker, &turn_timer);
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+}
+
+fn spawn_initiative_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    button: InitiativeButton,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (material_button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent
+        .spawn((material_button, button))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(text_color),
+                ButtonLabel,
+            ));
+        });
+}
+
+fn spawn_confirm_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+fn spawn_initiative_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
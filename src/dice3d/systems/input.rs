@@ -7,11 +7,15 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use rand::Rng;
 
+use crate::dice3d::meshes::DiceMeshCache;
 use crate::dice3d::throw_control::ThrowControlState;
 use crate::dice3d::types::*;
-use bevy_material_ui::prelude::{ButtonClickEvent, MaterialTextField, TextFieldSubmitEvent};
+use bevy_material_ui::prelude::{
+    ButtonClickEvent, MaterialTextField, ShowSnackbar, TextFieldChangeEvent, TextFieldSubmitEvent,
+};
 
 use super::dice_box_controls::start_container_shake;
+use super::inspiration::take_inspiration_flag;
 
 use super::setup::{calculate_dice_position, spawn_die};
 
@@ -54,21 +58,32 @@ pub fn update_ui_pointer_capture(
 
 #[derive(bevy::ecs::system::SystemParam)]
 pub struct CommandInputParams<'w, 's> {
+    pub roll: CommandRollParams<'w, 's>,
+    pub typed_commands: Res<'w, TypedCommands>,
+    pub character_data: ResMut<'w, CharacterData>,
+    pub ruleset: Res<'w, Ruleset>,
+    pub dice_aliases: Res<'w, DiceAliases>,
+    pub snackbar: MessageWriter<'w, ShowSnackbar>,
+    pub ui_state: Res<'w, UiState>,
+}
+
+/// Starting the roll of a typed command (see `CommandRollParams::start`)
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct CommandRollParams<'w, 's> {
     pub commands: Commands<'w, 's>,
-    pub db: Res<'w, CharacterDatabase>,
     pub settings_state: Res<'w, crate::dice3d::types::SettingsState>,
-    pub command_history: ResMut<'w, CommandHistory>,
     pub dice_config: ResMut<'w, DiceConfig>,
     pub dice_results: ResMut<'w, DiceResults>,
     pub roll_state: ResMut<'w, RollState>,
-    pub character_data: Res<'w, CharacterData>,
-    pub ui_state: Res<'w, UiState>,
+    pub roll_requested: MessageWriter<'w, RollRequested>,
 
     pub container_style: Res<'w, DiceContainerStyle>,
     pub lid_ctrl: ResMut<'w, DiceBoxLidAnimationController>,
 
     pub meshes: ResMut<'w, Assets<Mesh>>,
     pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
     pub dice_query: Query<'w, 's, Entity, With<Die>>,
 
     pub shake_state: Res<'w, ShakeState>,
@@ -88,12 +103,17 @@ pub struct CommandHistoryRerollParams<'w, 's> {
     pub dice_results: ResMut<'w, DiceResults>,
     pub roll_state: ResMut<'w, RollState>,
     pub character_data: Res<'w, CharacterData>,
+    pub ruleset: Res<'w, Ruleset>,
+    pub roll_requested: MessageWriter<'w, RollRequested>,
+    pub snackbar: MessageWriter<'w, ShowSnackbar>,
 
     pub container_style: Res<'w, DiceContainerStyle>,
     pub lid_ctrl: ResMut<'w, DiceBoxLidAnimationController>,
 
     pub meshes: ResMut<'w, Assets<Mesh>>,
     pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
     pub dice_query: Query<'w, 's, Entity, With<Die>>,
 
     pub shake_state: Res<'w, ShakeState>,
@@ -105,12 +125,13 @@ pub struct CommandHistoryRerollParams<'w, 's> {
 #[derive(bevy::ecs::system::SystemParam)]
 pub struct QuickRollParams<'w, 's> {
     pub commands: Commands<'w, 's>,
-    pub db: Res<'w, CharacterDatabase>,
     pub dice_config: ResMut<'w, DiceConfig>,
     pub character_data: Res<'w, CharacterData>,
+    pub ruleset: Res<'w, Ruleset>,
     pub roll_state: ResMut<'w, RollState>,
     pub dice_results: ResMut<'w, DiceResults>,
-    pub command_history: ResMut<'w, CommandHistory>,
+    pub roll_requested: MessageWriter<'w, RollRequested>,
+    pub snackbar: MessageWriter<'w, ShowSnackbar>,
     pub throw_state: Res<'w, ThrowControlState>,
     pub settings_state: Res<'w, SettingsState>,
 
@@ -121,13 +142,17 @@ pub struct QuickRollParams<'w, 's> {
 
     pub meshes: ResMut<'w, Assets<Mesh>>,
     pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub mesh_cache: ResMut<'w, DiceMeshCache>,
+    pub roll_rng: ResMut<'w, RollRng>,
     pub dice_query: Query<'w, 's, Entity, With<Die>>,
 }
 
-/// Handle keyboard input for rolling and resetting dice
+/// Handle keyboard, mouse and gamepad input for rolling and resetting dice
 pub fn handle_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    ui_focus: Res<UiFocus>,
     ui_state: Res<UiState>,
     settings_state: Res<crate::dice3d::types::SettingsState>,
     mut roll_state: ResMut<RollState>,
@@ -138,11 +163,15 @@ pub fn handle_input(
     mut lid_ctrl: ResMut<DiceBoxLidAnimationController>,
     command_field: Query<&MaterialTextField, With<CommandInputField>>,
     throw_state: Res<ThrowControlState>,
-
-    shake_state: Res<ShakeState>,
-    shake_config: Res<ContainerShakeConfig>,
-    mut shake_anim: ResMut<ContainerShakeAnimation>,
-    container_query: Query<(Entity, &Transform), With<DiceBox>>,
+    mut roll_requested: MessageWriter<RollRequested>,
+
+    (shake_state, shake_config, mut shake_anim, container_query, mut rng): (
+        Res<ShakeState>,
+        Res<ContainerShakeConfig>,
+        ResMut<ContainerShakeAnimation>,
+        Query<(Entity, &Transform), With<DiceBox>>,
+        ResMut<RollRng>,
+    ),
 ) {
     if ui_state.active_tab != AppTab::DiceRoller {
         return;
@@ -161,21 +190,40 @@ pub fn handle_input(
         return;
     }
 
-    if mouse.just_pressed(MouseButton::Left) && throw_state.mouse_over_box && !roll_state.rolling {
+    // A on a gamepad throws wherever the mouse is; the left stick aims it
+    // (see `aim_throw_with_gamepad`). While the focus ring is shown the
+    // buttons belong to the UI instead.
+    let gamepad_pressed = |button: GamepadButton| {
+        !ui_focus.visible && gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+    };
+    let bindings = &settings_state.settings.key_bindings;
+    let throw_pressed = (mouse.just_pressed(MouseButton::Left) && throw_state.mouse_over_box)
+        || gamepad_pressed(GamepadButton::South)
+        || (!ui_focus.visible && bindings.just_pressed(KeyAction::Reroll, &keyboard));
+
+    // Nothing to throw once a roll has been cancelled and its dice removed
+    if throw_pressed && !roll_state.rolling && !dice_config.dice_to_roll.is_empty() {
+        if *container_style != DiceContainerStyle::Box || lid_ctrl.pending_roll.is_none() {
+            roll_requested.write(RollRequested {
+                config: dice_config.clone(),
+                source: RollSource::Reroll,
+                command: None,
+            });
+        }
+
         if *container_style == DiceContainerStyle::Box {
             if lid_ctrl.pending_roll.is_none() {
                 lid_ctrl.pending_roll = Some(PendingRollRequest::RerollExisting);
 
                 #[cfg(debug_assertions)]
-                info!("Queued pending_roll: RerollExisting (mouse click)");
+                info!("Queued pending_roll: RerollExisting (mouse click or gamepad)");
             }
             return;
         }
 
         roll_state.rolling = true;
-        dice_results.results.clear();
+        dice_results.clear();
 
-        let mut rng = rand::rng();
         let num_dice = dice_config.dice_to_roll.len();
 
         let use_shake = settings_state.settings.default_roll_uses_shake;
@@ -212,7 +260,7 @@ pub fn handle_input(
                         rng.random_range(-0.3..0.0),
                         rng.random_range(-0.5..0.5),
                     );
-                velocity.angvel = throw_state.calculate_angular_velocity(&mut rng);
+                velocity.angvel = throw_state.calculate_angular_velocity(&mut *rng);
             }
         }
 
@@ -226,9 +274,11 @@ pub fn handle_input(
         }
     }
 
-    if keyboard.just_pressed(KeyCode::KeyR) {
+    if bindings.just_pressed(KeyAction::ClearDice, &keyboard)
+        || gamepad_pressed(GamepadButton::North)
+    {
         roll_state.rolling = false;
-        dice_results.results.clear();
+        dice_results.clear();
 
         let num_dice = dice_config.dice_to_roll.len();
 
@@ -243,6 +293,76 @@ pub fn handle_input(
     }
 }
 
+/// Abort the roll in progress with Escape, when the wrong check was
+/// triggered: its dice are removed unread and it ends with `RollCancelled`
+/// instead of a result. A roll still waiting for the box lid to close is
+/// dropped and the dice of the last roll stay in the box.
+#[allow(clippy::too_many_arguments)]
+pub fn cancel_roll_on_escape(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    ui_state: Res<UiState>,
+    settings_state: Res<crate::dice3d::types::SettingsState>,
+    command_field: Query<&MaterialTextField, With<CommandInputField>>,
+    mut roll_state: ResMut<RollState>,
+    mut dice_results: ResMut<DiceResults>,
+    mut dice_config: ResMut<DiceConfig>,
+    mut explosion_throws: ResMut<PendingExplosionThrows>,
+    mut lid_ctrl: ResMut<DiceBoxLidAnimationController>,
+    dice_query: Query<Entity, With<Die>>,
+    mut cancelled: MessageWriter<RollCancelled>,
+) {
+    if ui_state.active_tab != AppTab::DiceRoller
+        || settings_state.show_modal
+        || !keyboard.just_pressed(KeyCode::Escape)
+    {
+        return;
+    }
+    // Escape in the command field only leaves the field
+    if command_field
+        .iter()
+        .any(|field| field.focused && !field.disabled)
+    {
+        return;
+    }
+    if !roll_state.rolling && lid_ctrl.pending_roll.is_none() {
+        return;
+    }
+
+    let config = match lid_ctrl.pending_roll.take() {
+        Some(PendingRollRequest::StartNewRoll { config }) => config,
+        Some(PendingRollRequest::QuickRollSingleDie {
+            die_type,
+            modifier,
+            modifier_name,
+        }) => DiceConfig {
+            dice_to_roll: vec![die_type],
+            modifier,
+            modifier_name,
+            expression: None,
+        },
+        Some(PendingRollRequest::RerollExisting) | None => dice_config.clone(),
+    };
+    lid_ctrl.pending_open_after_roll = true;
+
+    if roll_state.rolling {
+        for entity in dice_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        roll_state.rolling = false;
+        roll_state.settle_timer = 0.0;
+        roll_state.roll_timer = 0.0;
+        dice_results.clear();
+        explosion_throws.dice.clear();
+        dice_config.dice_to_roll.clear();
+        dice_config.expression = None;
+    }
+
+    let event = RollCancelled { config };
+    info!("Roll cancelled: {}", event.label());
+    cancelled.write(event);
+}
+
 /// Handle command input from the user
 pub fn handle_command_input(
     mut params: CommandInputParams,
@@ -254,7 +374,7 @@ pub fn handle_command_input(
     }
 
     // Modal dialog open: block interactions with the game world.
-    if params.settings_state.show_modal {
+    if params.roll.settings_state.show_modal {
         return;
     }
 
@@ -275,74 +395,39 @@ pub fn handle_command_input(
             continue;
         }
 
-        // Parse and apply the command
-        if let Some(new_config) = parse_command(&cmd, &params.character_data) {
-            // Add to command history (only unique commands)
-            params.command_history.add_command(cmd.clone());
-            let _ = params
-                .db
-                .save_command_history(&params.command_history.commands);
-
-            // Box style: gate roll start behind lid closing.
-            if *params.container_style == DiceContainerStyle::Box {
-                *params.dice_config = new_config.clone();
-                params.dice_results.results.clear();
-
-                if params.lid_ctrl.pending_roll.is_none() {
-                    params.lid_ctrl.pending_roll =
-                        Some(PendingRollRequest::StartNewRoll { config: new_config });
-                }
-            } else {
-                // Remove old dice
-                for entity in params.dice_query.iter() {
-                    params.commands.entity(entity).despawn();
-                }
-
-                // Update config
-                *params.dice_config = new_config;
-                params.dice_results.results.clear();
-
-                let use_shake = params.settings_state.settings.default_roll_uses_shake;
-
-                // Spawn new dice
-                let mut spawned: Vec<Entity> = Vec::new();
-                for (i, die_type) in params.dice_config.dice_to_roll.iter().enumerate() {
-                    let position =
-                        calculate_dice_position(i, params.dice_config.dice_to_roll.len());
-                    let die_scale = params
-                        .settings_state
-                        .settings
-                        .dice_scales
-                        .scale_for(*die_type);
-                    let e = spawn_die(
-                        &mut params.commands,
-                        &mut params.meshes,
-                        &mut params.materials,
-                        *die_type,
-                        die_scale,
-                        position,
-                    );
-                    spawned.push(e);
+        // Typed commands run in their own systems; anything else is a roll.
+        // `--inspiration` spends the character's inspiration for advantage
+        if !params
+            .typed_commands
+            .dispatch(&cmd, &mut params.roll.commands)
+        {
+            let (roll_cmd, inspired) = take_inspiration_flag(&cmd);
+            let rolled = parse_command(
+                &params.dice_aliases.expand(&roll_cmd),
+                &params.character_data,
+                params.roll.settings_state.settings.explosion_cap(),
+                params.roll.settings_state.settings.game_system.dice_pool(),
+                *params.ruleset,
+                inspired,
+            )
+            .and_then(|new_config| {
+                if inspired {
+                    let spent = match params.character_data.sheet.as_mut() {
+                        Some(sheet) => sheet.spend_inspiration(),
+                        None => Err("Load a character to spend inspiration".to_string()),
+                    };
+                    params.character_data.is_modified |= spent.is_ok();
+                    spent?;
                 }
-
-                if use_shake {
-                    for e in spawned {
-                        params.commands.entity(e).insert(Velocity {
-                            linvel: Vec3::ZERO,
-                            angvel: Vec3::ZERO,
-                        });
-                    }
-
-                    let _started = start_container_shake(
-                        &params.shake_state,
-                        &params.shake_config,
-                        &mut params.shake_anim,
-                        &params.container_query,
-                    );
+                Ok(new_config)
+            });
+            match rolled {
+                Ok(new_config) => params.roll.start(new_config, Some(roll_cmd)),
+                Err(e) => {
+                    params
+                        .snackbar
+                        .write(ShowSnackbar::message(e).duration(3.0));
                 }
-
-                // Start rolling immediately
-                params.roll_state.rolling = true;
             }
         }
 
@@ -358,6 +443,81 @@ pub fn handle_command_input(
     }
 }
 
+impl CommandRollParams<'_, '_> {
+    /// Roll the dice of a typed command, recording `command` in the history.
+    pub fn start(&mut self, new_config: DiceConfig, command: Option<String>) {
+        self.roll_requested.write(RollRequested {
+            config: new_config.clone(),
+            source: RollSource::Command,
+            command,
+        });
+
+        // Box style: gate roll start behind lid closing.
+        if *self.container_style == DiceContainerStyle::Box {
+            *self.dice_config = new_config.clone();
+            self.dice_results.clear();
+
+            if self.lid_ctrl.pending_roll.is_none() {
+                self.lid_ctrl.pending_roll =
+                    Some(PendingRollRequest::StartNewRoll { config: new_config });
+            }
+        } else {
+            // Remove old dice
+            for entity in self.dice_query.iter() {
+                self.commands.entity(entity).despawn();
+            }
+
+            // Update config
+            *self.dice_config = new_config;
+            self.dice_results.clear();
+
+            let use_shake = self.settings_state.settings.default_roll_uses_shake;
+
+            // Spawn new dice
+            let mut spawned: Vec<Entity> = Vec::new();
+            let physical_dice = self.dice_config.physical_dice();
+            for (i, die_type) in physical_dice.iter().enumerate() {
+                let position = calculate_dice_position(i, physical_dice.len());
+                let die_scale = self
+                    .settings_state
+                    .settings
+                    .dice_scales
+                    .scale_for(*die_type);
+                let e = spawn_die(
+                    &mut self.commands,
+                    &mut self.meshes,
+                    &mut self.materials,
+                    &mut self.mesh_cache,
+                    *die_type,
+                    die_scale,
+                    position,
+                    &mut *self.roll_rng,
+                );
+                spawned.push(e);
+            }
+
+            if use_shake {
+                for e in spawned {
+                    self.commands.entity(e).insert(Velocity {
+                        linvel: Vec3::ZERO,
+                        angvel: Vec3::ZERO,
+                    });
+                }
+
+                let _started = start_container_shake(
+                    &self.shake_state,
+                    &self.shake_config,
+                    &mut self.shake_anim,
+                    &self.container_query,
+                );
+            }
+
+            // Start rolling immediately
+            self.roll_state.rolling = true;
+        }
+    }
+}
+
 /// Handle clicks on command history items (reroll selected command)
 pub fn handle_command_history_item_clicks(
     mut click_events: MessageReader<ButtonClickEvent>,
@@ -384,80 +544,113 @@ pub fn handle_command_history_item_clicks(
 
         params.command_history.selected_index = Some(item.index);
 
-        if let Some(new_config) = parse_command(&cmd, &params.character_data) {
-            // Box style: gate roll start behind lid closing.
-            if *params.container_style == DiceContainerStyle::Box {
-                *params.dice_config = new_config.clone();
-                params.dice_results.results.clear();
-
-                if params.lid_ctrl.pending_roll.is_none() {
-                    params.lid_ctrl.pending_roll =
-                        Some(PendingRollRequest::StartNewRoll { config: new_config });
-                }
+        let new_config = match parse_command(
+            &cmd,
+            &params.character_data,
+            params.settings_state.settings.explosion_cap(),
+            params.settings_state.settings.game_system.dice_pool(),
+            *params.ruleset,
+            false,
+        ) {
+            Ok(new_config) => new_config,
+            Err(e) => {
+                params
+                    .snackbar
+                    .write(ShowSnackbar::message(e).duration(3.0));
                 continue;
             }
+        };
 
-            // Remove old dice
-            for entity in params.dice_query.iter() {
-                params.commands.entity(entity).despawn();
+        params.roll_requested.write(RollRequested {
+            config: new_config.clone(),
+            source: RollSource::History,
+            command: None,
+        });
+
+        // Box style: gate roll start behind lid closing.
+        if *params.container_style == DiceContainerStyle::Box {
+            *params.dice_config = new_config.clone();
+            params.dice_results.clear();
+
+            if params.lid_ctrl.pending_roll.is_none() {
+                params.lid_ctrl.pending_roll =
+                    Some(PendingRollRequest::StartNewRoll { config: new_config });
             }
+            continue;
+        }
 
-            // Update config
-            *params.dice_config = new_config;
-            params.dice_results.results.clear();
+        // Remove old dice
+        for entity in params.dice_query.iter() {
+            params.commands.entity(entity).despawn();
+        }
 
-            let use_shake = params.settings_state.settings.default_roll_uses_shake;
+        // Update config
+        *params.dice_config = new_config;
+        params.dice_results.clear();
 
-            // Spawn new dice
-            let mut spawned: Vec<Entity> = Vec::new();
-            for (i, die_type) in params.dice_config.dice_to_roll.iter().enumerate() {
-                let position = calculate_dice_position(i, params.dice_config.dice_to_roll.len());
-                let die_scale = params
-                    .settings_state
-                    .settings
-                    .dice_scales
-                    .scale_for(*die_type);
-                let e = spawn_die(
-                    &mut params.commands,
-                    &mut params.meshes,
-                    &mut params.materials,
-                    *die_type,
-                    die_scale,
-                    position,
-                );
-                spawned.push(e);
-            }
+        let use_shake = params.settings_state.settings.default_roll_uses_shake;
 
-            if use_shake {
-                for e in spawned {
-                    params.commands.entity(e).insert(Velocity {
-                        linvel: Vec3::ZERO,
-                        angvel: Vec3::ZERO,
-                    });
-                }
+        // Spawn new dice
+        let mut spawned: Vec<Entity> = Vec::new();
+        let physical_dice = params.dice_config.physical_dice();
+        for (i, die_type) in physical_dice.iter().enumerate() {
+            let position = calculate_dice_position(i, physical_dice.len());
+            let die_scale = params
+                .settings_state
+                .settings
+                .dice_scales
+                .scale_for(*die_type);
+            let e = spawn_die(
+                &mut params.commands,
+                &mut params.meshes,
+                &mut params.materials,
+                &mut params.mesh_cache,
+                *die_type,
+                die_scale,
+                position,
+                &mut *params.roll_rng,
+            );
+            spawned.push(e);
+        }
 
-                let _started = start_container_shake(
-                    &params.shake_state,
-                    &params.shake_config,
-                    &mut params.shake_anim,
-                    &params.container_query,
-                );
+        if use_shake {
+            for e in spawned {
+                params.commands.entity(e).insert(Velocity {
+                    linvel: Vec3::ZERO,
+                    angvel: Vec3::ZERO,
+                });
             }
 
-            // Start rolling immediately
-            params.roll_state.rolling = true;
+            let _started = start_container_shake(
+                &params.shake_state,
+                &params.shake_config,
+                &mut params.shake_anim,
+                &params.container_query,
+            );
         }
+
+        // Start rolling immediately
+        params.roll_state.rolling = true;
     }
 }
 
-/// Parse a command string into a DiceConfig
-fn parse_command(cmd: &str, character_data: &CharacterData) -> Option<DiceConfig> {
+/// Parse a command string into a DiceConfig, with advantage from spent
+/// `inspiration`. A check the character's conditions fail without rolling
+/// (a paralyzed character's Dexterity save) is an error saying so.
+fn parse_command(
+    cmd: &str,
+    character_data: &CharacterData,
+    explosion_cap: u32,
+    dice_pool: Option<DicePool>,
+    ruleset: Ruleset,
+    inspiration: bool,
+) -> Result<DiceConfig, String> {
     let parts: Vec<&str> = cmd.split_whitespace().collect();
     if parts.is_empty() {
-        return None;
+        return Err("Nothing to roll".to_string());
     }
 
-    let mut dice_to_roll = Vec::new();
+    let mut expressions: Vec<DiceExpr> = Vec::new();
     let mut modifier = 0i32;
     let mut modifier_name = String::new();
     let mut checkon: Option<String> = None;
@@ -469,10 +662,8 @@ fn parse_command(cmd: &str, character_data: &CharacterData) -> Option<DiceConfig
         if part == "--dice" || part == "-d" {
             if i + 1 < parts.len() {
                 i += 1;
-                if let Some((count, die_type)) = parse_dice_str(parts[i]) {
-                    for _ in 0..count {
-                        dice_to_roll.push(die_type);
-                    }
+                if let Ok(expression) = DiceExpr::parse(parts[i]) {
+                    expressions.push(expression);
                 }
             }
         } else if part == "--checkon" {
@@ -488,11 +679,9 @@ fn parse_command(cmd: &str, character_data: &CharacterData) -> Option<DiceConfig
                 }
             }
         } else if part.contains('d') && !part.starts_with('-') {
-            // Direct dice notation like "2d6"
-            if let Some((count, die_type)) = parse_dice_str(part) {
-                for _ in 0..count {
-                    dice_to_roll.push(die_type);
-                }
+            // Direct dice notation like "2d6" or "2d6+1d8+3"
+            if let Ok(expression) = DiceExpr::parse(part) {
+                expressions.push(expression);
             }
         }
 
@@ -500,52 +689,99 @@ fn parse_command(cmd: &str, character_data: &CharacterData) -> Option<DiceConfig
     }
 
     // Apply checkon modifier (skill / ability / saving throw) similar to the CLI.
+    let mut d20_test = None;
     if let Some(check) = checkon {
         let check_lower = check.to_lowercase();
 
         if let Some(skill_mod) = character_data.get_skill_modifier(&check_lower) {
             modifier += skill_mod;
             modifier_name = check;
+            d20_test = Some(D20Test::AbilityCheck);
         } else if let Some(ability_mod) = character_data.get_ability_modifier(&check_lower) {
             modifier += ability_mod;
             modifier_name = format!("{} check", check);
+            d20_test = Some(D20Test::AbilityCheck);
         } else if let Some(save_mod) = character_data.get_saving_throw_modifier(&check_lower) {
             modifier += save_mod;
             modifier_name = format!("{} save", check);
+            d20_test = Some(D20Test::SavingThrow(check_lower));
         } else {
             // Unknown label: keep the name for display, but don't change the modifier.
             modifier_name = check;
         }
     }
 
-    // Default to 1d20 if no dice specified.
-    if dice_to_roll.is_empty() {
-        dice_to_roll.push(DiceType::D20);
-    }
+    // Default to 1d20 if no dice specified. A dice pool rolls its dice again
+    // on 10s (10-again) when the game system calls for it.
+    let (dice_to_roll, expression) = match DiceExpr::sum(expressions) {
+        Some(expression) if !expression.dice().is_empty() => {
+            let expression = match dice_pool {
+                Some(pool) => pool.prepare(expression),
+                None => expression,
+            };
+            DiceConfig::from_expression(&expression.with_explosion_cap(explosion_cap))
+        }
+        _ => (vec![DiceType::D20], None),
+    };
 
-    Some(DiceConfig {
+    let config = DiceConfig {
         dice_to_roll,
         modifier,
         modifier_name,
-    })
-}
-
-/// Parse a dice string like "2d6" into a count and die type
-fn parse_dice_str(s: &str) -> Option<(usize, DiceType)> {
-    let s = s.to_lowercase();
+        expression,
+    };
 
-    let (count_str, die_str) = if s.starts_with('d') {
-        ("1", s.as_str())
-    } else if let Some(pos) = s.find('d') {
-        (&s[..pos], &s[pos..])
-    } else {
-        return None;
+    // Conditions on the character give the check advantage or disadvantage,
+    // and so does spending inspiration.
+    let mut effect = match (d20_test, &character_data.sheet) {
+        (Some(test), Some(sheet)) => sheet.conditions.effect_on(&test, ruleset),
+        _ => ConditionEffect::default(),
     };
+    if let Some(failure) = automatic_failure(&config.modifier_name, &effect) {
+        return Err(failure);
+    }
+    if inspiration {
+        effect.advantage.push("Inspiration".to_string());
+    }
+    Ok(config.with_condition_effect(&effect))
+}
 
-    let count: usize = count_str.parse().ok()?;
-    let die_type = DiceType::parse(die_str)?;
+/// "Dexterity save fails automatically (Paralyzed)" when the character's
+/// conditions fail the d20 test without a roll.
+fn automatic_failure(label: &str, effect: &ConditionEffect) -> Option<String> {
+    effect.fails_automatically().then(|| {
+        format!(
+            "{} fails automatically ({})",
+            label,
+            effect.auto_fail.join(", ")
+        )
+    })
+}
 
-    Some((count, die_type))
+/// Track the DC typed in the quick roll panel; an empty field clears it.
+pub fn handle_quick_roll_dc_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    dc_fields: Query<(), With<QuickRollDcField>>,
+    mut quick_roll_dc: ResMut<QuickRollDc>,
+) {
+    for ev in change_events.read() {
+        if dc_fields.get(ev.entity).is_err() {
+            continue;
+        }
+        let text = ev.value.trim();
+        let dc = if text.is_empty() {
+            None
+        } else {
+            // Keep the last DC while a number is half typed
+            match text.parse::<i32>() {
+                Ok(dc) => Some(dc),
+                Err(_) => continue,
+            }
+        };
+        if quick_roll_dc.0 != dc {
+            quick_roll_dc.0 = dc;
+        }
+    }
 }
 
 /// Handle quick roll button clicks
@@ -564,8 +800,9 @@ pub fn handle_quick_roll_clicks(
         let Ok(quick_roll) = quick_roll_query.get(event.entity) else {
             continue;
         };
-        // Get the modifier based on roll type
-        let (modifier, modifier_name) = match &quick_roll.roll_type {
+        // Get the modifier based on roll type, and advantage from a tool
+        // proficiency's synergy
+        let (modifier, modifier_name, d20_test, tool_advantage) = match &quick_roll.roll_type {
             QuickRollType::Skill(skill_name) => {
                 let mod_val = params
                     .character_data
@@ -577,7 +814,28 @@ pub fn handle_quick_roll_clicks(
                     .enumerate()
                     .map(|(i, c)| if i == 0 { c.to_ascii_uppercase() } else { c })
                     .collect::<String>();
-                (mod_val, display_name)
+
+                // Tool/skill synergy from tagged tool proficiencies
+                let synergy = params
+                    .character_data
+                    .sheet
+                    .as_ref()
+                    .map(|sheet| sheet.tool_synergy_for_skill(skill_name))
+                    .unwrap_or_default();
+                let (mod_val, display_name) = if synergy.applies() {
+                    (
+                        mod_val + synergy.bonus,
+                        format!("{} [{}]", display_name, synergy.describe()),
+                    )
+                } else {
+                    (mod_val, display_name)
+                };
+                (
+                    mod_val,
+                    display_name,
+                    Some(D20Test::AbilityCheck),
+                    synergy.advantage,
+                )
             }
             QuickRollType::AbilityCheck(ability_name) => {
                 let mod_val = params
@@ -585,7 +843,7 @@ pub fn handle_quick_roll_clicks(
                     .get_ability_modifier(ability_name)
                     .unwrap_or(0);
                 let display_name = format!("{} check", capitalize_first(ability_name));
-                (mod_val, display_name)
+                (mod_val, display_name, Some(D20Test::AbilityCheck), false)
             }
             QuickRollType::SavingThrow(ability_name) => {
                 let mod_val = params
@@ -593,116 +851,164 @@ pub fn handle_quick_roll_clicks(
                     .get_saving_throw_modifier(ability_name)
                     .unwrap_or(0);
                 let display_name = format!("{} save", capitalize_first(ability_name));
-                (mod_val, display_name)
+                (
+                    mod_val,
+                    display_name,
+                    Some(D20Test::SavingThrow(ability_name.clone())),
+                    false,
+                )
             }
+            QuickRollType::CoinFlip => (0, "Coin flip".to_string(), None, false),
         };
 
-        let die_type = params
-            .settings_state
-            .settings
-            .quick_roll_default_die
-            .to_dice_type();
+        // Conditions give the check advantage or disadvantage (a second d20
+        // when quick rolls throw a d20, like a tool's advantage) and any
+        // penalty; a check they fail outright is not rolled at all.
+        let effect = match (&d20_test, &params.character_data.sheet) {
+            (Some(test), Some(sheet)) => sheet.conditions.effect_on(test, *params.ruleset),
+            _ => ConditionEffect::default(),
+        };
+        if let Some(failure) = automatic_failure(&modifier_name, &effect) {
+            params
+                .snackbar
+                .write(ShowSnackbar::message(failure).duration(4.0));
+            continue;
+        }
+
+        let die_type = if d20_test.is_some() {
+            params
+                .settings_state
+                .settings
+                .quick_roll_default_die
+                .to_dice_type()
+        } else {
+            DiceType::Custom(2)
+        };
+        let config = DiceConfig {
+            dice_to_roll: vec![die_type],
+            modifier,
+            modifier_name,
+            expression: None,
+        }
+        .with_advantage_and_conditions(tool_advantage, &effect);
+
+        let command = if d20_test.is_some() {
+            let dice = match &config.expression {
+                Some(expression) => expression.to_string(),
+                None => format!("1d{}", die_type.max_value()),
+            };
+            let sign = if config.modifier >= 0 { "+" } else { "" };
+            format!(
+                "{} --checkon {} ({}{})",
+                dice, config.modifier_name, sign, config.modifier
+            )
+        } else {
+            "1coin".to_string()
+        };
+
+        params.roll_requested.write(RollRequested {
+            config: config.clone(),
+            source: RollSource::QuickRoll,
+            command: Some(command),
+        });
 
         if *container_style == DiceContainerStyle::Box {
             if lid_ctrl.pending_roll.is_none() {
-                lid_ctrl.pending_roll = Some(PendingRollRequest::QuickRollSingleDie {
-                    die_type,
-                    modifier,
-                    modifier_name: modifier_name.clone(),
-                });
+                // Advantage or disadvantage throws both d20s
+                if config.dice_to_roll.len() > 1 {
+                    *params.dice_config = config.clone();
+                    params.dice_results.clear();
+                    lid_ctrl.pending_roll = Some(PendingRollRequest::StartNewRoll { config });
+                    continue;
+                }
 
                 #[cfg(debug_assertions)]
                 info!(
                     "Queued pending_roll: QuickRollSingleDie(die={:?}, mod={}, name={})",
-                    die_type, modifier, modifier_name
+                    die_type, config.modifier, config.modifier_name
                 );
+
+                lid_ctrl.pending_roll = Some(PendingRollRequest::QuickRollSingleDie {
+                    die_type,
+                    modifier: config.modifier,
+                    modifier_name: config.modifier_name,
+                });
             }
             continue;
         }
 
-        let die_scale = params
-            .settings_state
-            .settings
-            .dice_scales
-            .scale_for(die_type);
-
-        // Remove old dice (Quick Rolls always uses exactly one die)
+        // Remove old dice
         for entity in params.dice_query.iter() {
             params.commands.entity(entity).despawn();
         }
 
         // Update dice config
-        params.dice_config.dice_to_roll.clear();
-        params.dice_config.dice_to_roll.push(die_type);
-        params.dice_config.modifier = modifier;
-        params.dice_config.modifier_name = modifier_name.clone();
-
-        // Add to command history
-        let sign = if modifier >= 0 { "+" } else { "" };
-        params.command_history.add_command(format!(
-            "1d{} --checkon {} ({}{})",
-            die_type.max_value(),
-            modifier_name,
-            sign,
-            modifier
-        ));
-        let _ = params
-            .db
-            .save_command_history(&params.command_history.commands);
+        *params.dice_config = config;
 
         // Trigger the roll
         params.roll_state.rolling = true;
-        params.dice_results.results.clear();
-
-        // Spawn the single die and override its transform/velocity using throw control.
-        let die_entity = spawn_die(
-            &mut params.commands,
-            &mut params.meshes,
-            &mut params.materials,
-            die_type,
-            die_scale,
-            calculate_dice_position(0, 1),
-        );
+        params.dice_results.clear();
 
         let use_shake = params.settings_state.settings.default_roll_uses_shake;
-
-        let mut rng = rand::rng();
         let base_velocity = params.throw_state.calculate_throw_velocity();
 
-        let transform = Transform::from_translation(Vec3::new(
-            rng.random_range(-0.5..0.5),
-            1.0,
-            rng.random_range(-0.5..0.5),
-        ))
-        .with_rotation(Quat::from_euler(
-            EulerRot::XYZ,
-            rng.random_range(0.0..std::f32::consts::TAU),
-            rng.random_range(0.0..std::f32::consts::TAU),
-            rng.random_range(0.0..std::f32::consts::TAU),
-        ))
-        .with_scale(Vec3::splat(die_scale));
-
-        let velocity = if use_shake {
-            Velocity {
-                linvel: Vec3::ZERO,
-                angvel: Vec3::ZERO,
-            }
-        } else {
-            Velocity {
-                linvel: base_velocity
-                    + Vec3::new(
-                        rng.random_range(-0.5..0.5),
-                        rng.random_range(-0.3..0.0),
-                        rng.random_range(-0.5..0.5),
-                    ),
-                angvel: params.throw_state.calculate_angular_velocity(&mut rng),
-            }
-        };
+        // Spawn the die (both d20s with advantage or disadvantage) and
+        // override its transform/velocity using throw control.
+        let physical_dice = params.dice_config.physical_dice();
+        for (i, die_type) in physical_dice.iter().enumerate() {
+            let die_scale = params
+                .settings_state
+                .settings
+                .dice_scales
+                .scale_for(*die_type);
+            let position = calculate_dice_position(i, physical_dice.len());
+            let die_entity = spawn_die(
+                &mut params.commands,
+                &mut params.meshes,
+                &mut params.materials,
+                &mut params.mesh_cache,
+                *die_type,
+                die_scale,
+                position,
+                &mut *params.roll_rng,
+            );
+
+            let rng = &mut *params.roll_rng;
+            let transform = Transform::from_translation(Vec3::new(
+                position.x + rng.random_range(-0.5..0.5),
+                1.0,
+                position.z + rng.random_range(-0.5..0.5),
+            ))
+            .with_rotation(Quat::from_euler(
+                EulerRot::XYZ,
+                rng.random_range(0.0..std::f32::consts::TAU),
+                rng.random_range(0.0..std::f32::consts::TAU),
+                rng.random_range(0.0..std::f32::consts::TAU),
+            ))
+            .with_scale(Vec3::splat(die_scale));
+
+            let velocity = if use_shake {
+                Velocity {
+                    linvel: Vec3::ZERO,
+                    angvel: Vec3::ZERO,
+                }
+            } else {
+                Velocity {
+                    linvel: base_velocity
+                        + Vec3::new(
+                            rng.random_range(-0.5..0.5),
+                            rng.random_range(-0.3..0.0),
+                            rng.random_range(-0.5..0.5),
+                        ),
+                    angvel: params.throw_state.calculate_angular_velocity(rng),
+                }
+            };
 
-        params
-            .commands
-            .entity(die_entity)
-            .insert((transform, velocity));
+            params
+                .commands
+                .entity(die_entity)
+                .insert((transform, velocity));
+        }
 
         if use_shake {
             let _started = start_container_shake(
@@ -0,0 +1,171 @@
+//! Table inspiration systems
+//!
+//! `inspire <character>` in the command input hands out inspiration from the
+//! table's pool, `inspiration pool <n>` refills the pool and `inspiration`
+//! sums it up. Grants go to the `--serve` table; a roll command with
+//! `--inspiration` spends the loaded character's inspiration for advantage.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{
+    CharacterData, CharacterDatabase, InspirationGrant, InspirationPool, TypedCommand,
+    INSPIRATION_POOL_SETTING_KEY,
+};
+
+/// The table's inspiration pool, as saved in the database
+#[derive(Resource, Default)]
+pub struct InspirationState {
+    pub pool: InspirationPool,
+}
+
+/// The DM gave a character inspiration from the pool
+#[derive(Message, Clone, Debug)]
+pub struct InspirationGranted(pub InspirationGrant);
+
+/// An `inspire` or `inspiration` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InspirationCommand {
+    Status,
+    Pool(u32),
+    Grant(String),
+}
+
+impl TypedCommand for InspirationCommand {
+    const NAME: &'static str = "inspiration";
+
+    /// Parse `inspire <character>`, `inspiration` and `inspiration pool <n>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let rest = rest.trim();
+
+        if head.eq_ignore_ascii_case("inspire") {
+            return Some(InspirationCommand::Grant(rest.to_string()));
+        }
+        if !head.eq_ignore_ascii_case("inspiration") {
+            return None;
+        }
+        if rest.is_empty() {
+            return Some(InspirationCommand::Status);
+        }
+        let (sub, size) = rest.split_once(char::is_whitespace)?;
+        if !sub.eq_ignore_ascii_case("pool") {
+            return None;
+        }
+        size.trim().parse().ok().map(InspirationCommand::Pool)
+    }
+}
+
+/// Remove the `--inspiration` flag from a roll command, returning the
+/// command left and whether the flag was there.
+pub fn take_inspiration_flag(cmd: &str) -> (String, bool) {
+    let mut spend = false;
+    let parts: Vec<&str> = cmd
+        .split_whitespace()
+        .filter(|part| {
+            let flag = part.eq_ignore_ascii_case("--inspiration");
+            spend |= flag;
+            !flag
+        })
+        .collect();
+    (parts.join(" "), spend)
+}
+
+fn save_pool(pool: &InspirationPool, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(INSPIRATION_POOL_SETTING_KEY, pool.clone()) {
+        warn!("Failed to save the inspiration pool: {}", e);
+    }
+}
+
+/// Apply an inspiration command, notifying the user.
+///
+/// A grant to the loaded character goes straight onto their sheet; every
+/// grant is also sent to the table.
+pub fn run_inspiration_command(
+    In(command): In<InspirationCommand>,
+    mut state: ResMut<InspirationState>,
+    mut character_data: ResMut<CharacterData>,
+    db: Res<CharacterDatabase>,
+    mut granted: MessageWriter<InspirationGranted>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        InspirationCommand::Status => {
+            let mut summary = format!("Table: {}", state.pool.summary());
+            if let Some(sheet) = character_data.sheet.as_ref().filter(|s| s.inspiration) {
+                summary.push_str(&format!("; {} has inspiration", sheet.character.name));
+            }
+            summary
+        }
+        InspirationCommand::Pool(size) => {
+            state.pool.reset(size);
+            save_pool(&state.pool, &db);
+            format!("Table inspiration pool refilled: {}", state.pool.summary())
+        }
+        InspirationCommand::Grant(character) => match state.pool.grant(&character) {
+            Ok(grant) => {
+                save_pool(&state.pool, &db);
+                if let Some(sheet) = character_data.sheet.as_mut() {
+                    if grant.is_for(sheet) {
+                        sheet.inspiration = true;
+                        character_data.is_modified = true;
+                    }
+                }
+                let message = format!(
+                    "{} has inspiration ({} left in the pool)",
+                    grant.character, grant.remaining
+                );
+                granted.write(InspirationGranted(grant));
+                message
+            }
+            Err(e) => e,
+        },
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Give the loaded character inspiration the DM sent to the table, telling
+/// the player. Grants for other characters are only announced.
+pub fn receive_inspiration(
+    grant: &InspirationGrant,
+    character_data: &mut CharacterData,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+) {
+    let message = match character_data.sheet.as_mut() {
+        Some(sheet) if grant.is_for(sheet) => {
+            sheet.inspiration = true;
+            character_data.is_modified = true;
+            format!(
+                "The DM gave {} inspiration! Add --inspiration to a roll to spend it",
+                grant.character
+            )
+        }
+        _ => format!("The DM gave {} inspiration", grant.character),
+    };
+    info!("{}", message);
+    snackbar.write(ShowSnackbar::message(message).duration(6.0));
+}
+
+/// Load the inspiration pool whenever the database is opened or the profile
+/// switches.
+pub fn load_inspiration_pool(
+    db: Option<Res<CharacterDatabase>>,
+    mut state: ResMut<InspirationState>,
+) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.pool = db
+        .get_setting::<InspirationPool>(INSPIRATION_POOL_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
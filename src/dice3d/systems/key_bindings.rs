@@ -0,0 +1,188 @@
+//! Remappable keyboard shortcuts
+//!
+//! The Keys tab of the settings lists every `KeyAction` with its shortcut.
+//! Clicking a shortcut waits for the next key (`capture_key_binding`), which
+//! is kept from every other system that frame. Rerolling, clearing the dice
+//! and switching tabs read their shortcuts where the input is handled
+//! (`handle_input`, `navigate_ui_focus`); `handle_key_bindings` does the
+//! rest by pressing the button the action stands for, like the focus ring's
+//! Enter does.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::settings::open_settings_modal;
+use super::settings_tabs::keys::conflict_text;
+use crate::dice3d::types::*;
+
+const MODIFIER_KEYS: [KeyCode; 8] = [
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+];
+
+/// Give the action waiting in the Keys tab the key pressed now, with the
+/// modifiers held. Escape cancels and Backspace leaves the action unbound.
+pub fn capture_key_binding(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut settings_state: ResMut<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let Some(action) = settings_state.capturing_key_binding else {
+        return;
+    };
+    if !settings_state.show_modal {
+        settings_state.capturing_key_binding = None;
+        return;
+    }
+    let Some(key) = keyboard
+        .get_just_pressed()
+        .copied()
+        .find(|key| !MODIFIER_KEYS.contains(key))
+    else {
+        return;
+    };
+    keyboard.clear_just_pressed(key);
+
+    match key {
+        KeyCode::Escape => {}
+        KeyCode::Backspace => settings_state.editing_key_bindings.set(action, None),
+        _ if key_name(key).is_none() => {
+            snackbar.write(
+                ShowSnackbar::message(format!("{:?} can't be used as a shortcut", key))
+                    .duration(2.5),
+            );
+            return;
+        }
+        _ => {
+            let chord = KeyChord::pressed_with(key, &keyboard);
+            settings_state.editing_key_bindings.set(action, Some(chord));
+        }
+    }
+    settings_state.capturing_key_binding = None;
+}
+
+/// Start capturing a shortcut, or put one or all of them back on their
+/// defaults.
+pub fn handle_key_binding_buttons(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    bind_buttons: Query<&KeyBindingButton>,
+    reset_buttons: Query<&KeyBindingResetButton>,
+    reset_all_buttons: Query<(), With<ResetKeyBindingsButton>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    for event in click_events.read() {
+        if let Ok(button) = bind_buttons.get(event.entity) {
+            settings_state.capturing_key_binding = Some(button.0);
+        } else if let Ok(button) = reset_buttons.get(event.entity) {
+            settings_state.editing_key_bindings.reset(button.0);
+            settings_state.capturing_key_binding = None;
+        } else if reset_all_buttons.contains(event.entity) {
+            settings_state.editing_key_bindings = KeyBindings::default();
+            settings_state.capturing_key_binding = None;
+        }
+    }
+}
+
+/// Show the edited shortcuts and their conflicts in the Keys tab.
+pub fn update_key_bindings_ui(
+    settings_state: Res<SettingsState>,
+    mut labels: Query<(&KeyBindingButtonLabel, &mut Text), Without<KeyBindingConflictText>>,
+    mut conflicts: Query<(&KeyBindingConflictText, &mut Text), Without<KeyBindingButtonLabel>>,
+) {
+    if !settings_state.is_changed() {
+        return;
+    }
+    let bindings = &settings_state.editing_key_bindings;
+
+    for (label, mut text) in labels.iter_mut() {
+        let shown = if settings_state.capturing_key_binding == Some(label.0) {
+            "Press a key…".to_string()
+        } else {
+            bindings.describe(label.0)
+        };
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+    for (conflict, mut text) in conflicts.iter_mut() {
+        let shown = conflict_text(bindings, conflict.0);
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+}
+
+/// Open the settings, switch the container and press quick roll buttons
+/// from their shortcuts.
+///
+/// Runs after `navigate_ui_focus`, and presses buttons the same way, so the
+/// click handlers see the press this frame.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_key_bindings(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    ui_state: Res<UiState>,
+    mut settings_state: ResMut<SettingsState>,
+    shake_config: Res<ContainerShakeConfig>,
+    mut focus: ResMut<UiFocus>,
+    text_fields: Query<&MaterialTextField>,
+    toggle_buttons: Query<Entity, With<DiceBoxToggleContainerButton>>,
+    panels: Query<Entity, With<QuickRollPanel>>,
+    children: Query<&Children>,
+    quick_roll_buttons: Query<
+        (),
+        (
+            Or<(With<QuickRollButton>, With<CustomQuickRollButton>)>,
+            Without<SuggestedRollButton>,
+        ),
+    >,
+    mut interactions: Query<&mut Interaction>,
+) {
+    if settings_state.show_modal || settings_state.capturing_key_binding.is_some() {
+        return;
+    }
+    let typing = text_fields
+        .iter()
+        .any(|field| field.focused && !field.disabled);
+    if typing {
+        return;
+    }
+    let bindings = settings_state.settings.key_bindings.clone();
+
+    if bindings.just_pressed(KeyAction::OpenSettings, &keyboard) {
+        open_settings_modal(&mut settings_state, &shake_config);
+        return;
+    }
+    if ui_state.active_tab != AppTab::DiceRoller || focus.pressed.is_some() {
+        return;
+    }
+
+    let target = if bindings.just_pressed(KeyAction::ToggleContainer, &keyboard) {
+        toggle_buttons.iter().next()
+    } else {
+        (1..=9)
+            .find(|n| bindings.just_pressed(KeyAction::QuickRoll(*n), &keyboard))
+            .and_then(|n| {
+                // The panel's buttons from the top: custom ones, the coin,
+                // then the character's checks. Suggested rolls change after
+                // every roll, so they aren't numbered.
+                let panel = panels.iter().next()?;
+                children
+                    .iter_descendants_depth_first(panel)
+                    .filter(|entity| quick_roll_buttons.contains(*entity))
+                    .nth(n as usize - 1)
+            })
+    };
+    let Some(entity) = target else {
+        return;
+    };
+    if let Ok(mut interaction) = interactions.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
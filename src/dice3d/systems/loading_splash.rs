@@ -0,0 +1,90 @@
+//! Loading splash
+//!
+//! The dice container model streams in from the embedded assets after the
+//! window opens, which can take a few seconds on a cold start from an HDD.
+//! Until it (and everything it depends on) has loaded, a splash covers the
+//! empty dice box. Dialogs opened at startup, such as the unlock dialog of an
+//! encrypted database, stay on top of it.
+
+use bevy::asset::RecursiveDependencyLoadState;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{DiceContainerVisualRoot, LoadingSplashRoot};
+
+/// Longest the splash stays up, even if the model never finishes loading
+const LOADING_SPLASH_TIMEOUT_SECS: f32 = 15.0;
+
+/// Spawn the splash over the dice roller.
+pub fn setup_loading_splash(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            // Above the tab bar, below dialogs
+            ZIndex(1_000),
+            LoadingSplashRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("DnD Game Rolls"),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface),
+            ));
+            parent.spawn((
+                Text::new("Loading dice..."),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+            ));
+        });
+}
+
+/// Remove the splash once the dice container model has loaded (or failed to).
+pub fn dismiss_loading_splash(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    splash: Query<Entity, With<LoadingSplashRoot>>,
+    containers: Query<&SceneRoot, With<DiceContainerVisualRoot>>,
+) {
+    let Ok(splash) = splash.single() else {
+        return;
+    };
+
+    let loaded = !containers.is_empty()
+        && containers.iter().all(|scene| {
+            matches!(
+                asset_server.get_recursive_dependency_load_state(scene.0.id()),
+                Some(
+                    RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_)
+                )
+            )
+        });
+    let timed_out = time.elapsed_secs() > LOADING_SPLASH_TIMEOUT_SECS;
+
+    if loaded || timed_out {
+        if timed_out && !loaded {
+            warn!("Dice container model is still loading; hiding the splash");
+        }
+        commands.entity(splash).despawn();
+    }
+}
@@ -0,0 +1,338 @@
+//! Macros tab
+//!
+//! Lists the roll macros the current character can play, each with Play and
+//! Remove buttons, and saves macro definitions (`sneak_attack = 1d20+7 adv;
+//! 3d6+4d6`, see `parse_macro_definition`) for every character or for the
+//! current one. Playing a macro switches to the dice roller, where
+//! `run_macro_playback` throws its rolls. The tab is an extension tab
+//! (`MACROS_TAB_ID`) added by `DiceRollerPlugin`.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::save_macros;
+use crate::dice3d::types::*;
+
+/// Id of the Macros tab
+pub const MACROS_TAB_ID: &str = "macros";
+
+/// The definition typed into the tab
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MacroEditor {
+    pub definition: String,
+}
+
+/// Node the tab's content is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct MacrosScreenBody;
+
+/// The tab's definition field
+#[derive(Component)]
+pub struct MacroDefinitionField;
+
+/// Buttons of the Macros tab
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum MacrosButton {
+    SaveForEveryone,
+    SaveForCharacter,
+    /// Play the macro with this name and owner
+    Play {
+        name: String,
+        character: Option<String>,
+    },
+    Remove {
+        name: String,
+        character: Option<String>,
+    },
+}
+
+/// Spawn the (hidden) Macros tab.
+pub fn setup_macros_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                row_gap: Val::Px(12.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            ExtensionTabRoot(MACROS_TAB_ID),
+        ))
+        .with_children(|parent| {
+            spawn_macros_text(parent, "Roll Macros".to_string(), 28.0, theme.on_surface);
+            spawn_macros_text(
+                parent,
+                "Name the rolls and separate them with ';'. Label a roll with 'Label:' and add 'adv' or 'dis' to roll its d20 twice.".to_string(),
+                15.0,
+                theme.on_surface_variant,
+            );
+            parent
+                .spawn(Node {
+                    width: Val::Px(520.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label("Macro")
+                        .placeholder("sneak_attack = 1d20+7 adv; Damage: 1d6+4+3d6")
+                        .auto_focus(false)
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(slot, &theme, builder, MacroDefinitionField);
+                });
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        MacrosScreenBody,
+                    ));
+                });
+        });
+}
+
+/// Track the definition and handle the tab's buttons.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_macros_screen_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    definition_fields: Query<(), With<MacroDefinitionField>>,
+    buttons: Query<&MacrosButton>,
+    db: Res<CharacterDatabase>,
+    character_data: Res<CharacterData>,
+    recorder: Res<MacroRecorder>,
+    mut editor: ResMut<MacroEditor>,
+    mut macros: ResMut<DiceMacros>,
+    mut playback: ResMut<MacroPlayback>,
+    mut ui_state: ResMut<UiState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for ev in change_events.read() {
+        if definition_fields.contains(ev.entity) && editor.definition != ev.value {
+            editor.definition = ev.value.clone();
+        }
+    }
+
+    let character = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| sheet.character.name.as_str());
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        let message = match button {
+            MacrosButton::SaveForEveryone | MacrosButton::SaveForCharacter => {
+                let owner = character.filter(|_| *button == MacrosButton::SaveForCharacter);
+                match macros.define(&editor.definition, owner) {
+                    Ok(message) => {
+                        save_macros(&macros, &db);
+                        message
+                    }
+                    Err(e) => e,
+                }
+            }
+            MacrosButton::Play { .. } if recorder.is_recording() => {
+                "Save or cancel the macro being recorded first".to_string()
+            }
+            MacrosButton::Play { .. } if playback.is_playing() => {
+                format!("Macro {} is still playing", playback.name)
+            }
+            MacrosButton::Play { name, character } => {
+                let Some(dice_macro) = macros
+                    .macros
+                    .iter()
+                    .find(|m| m.name == *name && m.character == *character)
+                else {
+                    continue;
+                };
+                playback.start(dice_macro);
+                ui_state.active_tab = AppTab::DiceRoller;
+                format!("Playing macro {}", dice_macro.name)
+            }
+            MacrosButton::Remove { name, character } => {
+                let Some(index) = macros
+                    .macros
+                    .iter()
+                    .position(|m| m.name == *name && m.character == *character)
+                else {
+                    continue;
+                };
+                let removed = macros.macros.remove(index);
+                save_macros(&macros, &db);
+                format!("Macro removed: {}", removed.name)
+            }
+        };
+        snackbar.write(ShowSnackbar::message(message).duration(3.0));
+    }
+}
+
+/// Rebuild the tab when the macros or the current character change.
+pub fn update_macros_screen(
+    mut commands: Commands,
+    macros: Res<DiceMacros>,
+    character_data: Res<CharacterData>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<MacrosScreenBody>>,
+    mut shown_for: Local<Option<Option<String>>>,
+) {
+    let character = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| sheet.character.name.clone());
+    if !macros.is_changed() && shown_for.as_ref() == Some(&character) {
+        return;
+    }
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        spawn_button_row(body, |row| {
+            spawn_macros_button(
+                row,
+                "Save for Everyone",
+                true,
+                MacrosButton::SaveForEveryone,
+                &theme,
+            );
+            if let Some(name) = &character {
+                spawn_macros_button(
+                    row,
+                    &format!("Save for {}", name),
+                    false,
+                    MacrosButton::SaveForCharacter,
+                    &theme,
+                );
+            }
+        });
+
+        let available = macros.available(character.as_deref());
+        if available.is_empty() {
+            spawn_macros_text(
+                body,
+                "No macros yet. Define one above, or record one with `macro record` in the command input.".to_string(),
+                15.0,
+                theme.on_surface_variant,
+            );
+            return;
+        }
+        for dice_macro in available {
+            spawn_button_row(body, |row| {
+                spawn_macros_button(
+                    row,
+                    "Play",
+                    true,
+                    MacrosButton::Play {
+                        name: dice_macro.name.clone(),
+                        character: dice_macro.character.clone(),
+                    },
+                    &theme,
+                );
+                spawn_macros_button(
+                    row,
+                    "Remove",
+                    false,
+                    MacrosButton::Remove {
+                        name: dice_macro.name.clone(),
+                        character: dice_macro.character.clone(),
+                    },
+                    &theme,
+                );
+                spawn_macros_text(
+                    row,
+                    DiceMacros::describe(dice_macro),
+                    16.0,
+                    theme.on_surface,
+                );
+            });
+        }
+    });
+    *shown_for = Some(character);
+}
+
+fn spawn_button_row(
+    parent: &mut ChildSpawnerCommands,
+    children: impl FnOnce(&mut ChildSpawnerCommands),
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_macros_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    button: MacrosButton,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (material_button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent
+        .spawn((material_button, button))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(text_color),
+                ButtonLabel,
+            ));
+        });
+}
+
+fn spawn_macros_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
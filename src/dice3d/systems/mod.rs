@@ -6,52 +6,178 @@
 //! - `setup`: Scene initialization (camera, lights, dice box, dice, UI)
 //! - `camera`: Camera rotation and zoom controls
 //! - `dice`: Dice settlement detection and result determination
+//! - `dice_gather`: Lining up the dice of a read roll at the front of the container
+//! - `dice_macros`: Recording, saving and replaying roll macros
+//! - `dice_palette`: Queueing dice by clicking the quick roll panel's dice palette
+//! - `dice_skins`: Drawing spawned dice with the skin chosen for their type
 //! - `input`: Keyboard input handling and command parsing
 //! - `rendering`: Number mesh generation for dice labels
 //! - `character_screen`: Character sheet UI and tab navigation
+//! - `character_file_dialog`: Sharing characters as `.dndchar` files and viewing them read-only
+//! - `cloud_sync`: The `sync` command syncing characters with a remote SurrealDB server
 //! - `settings`: Settings UI and persistence
 //! - `contributors_screen`: GitHub contributors display
+//! - `compendium_screen`: Monsters tab (SRD stat blocks and their attack and save rolls)
+//! - `achievements`: Unlocking achievements, death saves and the achievements page
+//! - `automation`: Publishing automation events and running the rules they trigger
+//! - `damage`: Damage type and defense commands, applying damage and the Combat
+//!   tab's damage defenses
 //! - `avatar_loader`: Async loading of profile images from URLs
+//! - `encounter_screen`: Encounter tab (XP budget and difficulty, sending monsters to initiative)
+//! - `encryption_dialog`: Passphrase dialog for encrypted databases
+//! - `gamepad`: Aiming throws with a controller and rumble on dice impacts
+//! - `initiative_screen`: Initiative tab (rolling initiative, turn order and rounds)
+//! - `key_bindings`: Remapping keyboard shortcuts and acting on them
+//! - `inspiration`: The table's inspiration pool and spending inspiration on rolls
+//! - `loading_splash`: Splash shown until the dice container model has loaded
+//! - `macros_screen`: Macros tab (defining, playing and removing roll macros)
+//! - `music`: Background music playback, crossfades and the `music` command
+//! - `party_screen`: Party tab (group checks against a DC, thrown in the 3D scene)
+//! - `physics_repro`: Debug capture of throws, repro files and replaying them
+//! - `platform`: App ID, macOS menu bar and character file opening
+//! - `profiles`: Database profile switching
+//! - `quick_roll_editor`: Custom quick roll buttons (editing, drag reordering and rolling them)
+//! - `qr_transfer`: Share via QR and scanning QR codes from images
+//! - `quiz`: Probability quiz commands and demonstration rolls
+//! - `ruleset`: Showing and switching the campaign's rules edition (`ruleset`)
+//! - `safety_tools`: The X-card, lines and veils, and pausing the scene
+//! - `roll_events`: Publishing the roll lifecycle events and recording requested commands
+//! - `session_clock`: In-game time and ritual casting timers
+//! - `stats_screen`: Saving resolved rolls and the Stats tab
+//! - `suggested_rolls`: The quick roll panel's suggested rolls, learned from the saved rolls
+//! - `table_client`: The `--join` shared roll feed and replayed remote throws
+//! - `table_server`: Broadcasting resolved rolls to `--serve` subscribers
+//! - `travel_mode`: Travel mode panel (passive Perception and group Stealth)
+//! - `treasure`: Rolling treasure hoards into the party's treasure ledger
+//! - `tween`: Keyframed transform tweens and scene node lookup helpers
+//! - `ui_focus`: Keyboard and gamepad navigation and the focus ring
+//! - `ui_scale`: UI scale override and per-monitor DPI handling
 
+mod achievements;
+mod automation;
 mod avatar_loader;
 mod box_highlight;
 mod camera;
+mod character_file_dialog;
 pub mod character_screen;
+mod cloud_sync;
+#[cfg(feature = "audio")]
 mod collision_sfx;
+mod compendium_screen;
 mod container_centering;
 mod contributors_screen;
+mod damage;
 mod dice;
 pub mod dice_box_controls;
 pub mod dice_box_lid_animations;
+#[cfg(feature = "hanabi")]
 pub mod dice_fx;
+mod dice_gather;
+mod dice_macros;
+mod dice_palette;
+mod dice_skins;
+mod encounter_screen;
+mod encryption_dialog;
+mod gamepad;
 mod gltf_colliders;
 mod gltf_spawn_points;
+mod initiative_screen;
 mod input;
+mod inspiration;
+mod key_bindings;
+mod loading_splash;
+mod macros_screen;
+mod music;
+mod party_screen;
+mod physics_repro;
+mod platform;
+mod profiles;
+mod qr_transfer;
+mod quick_roll_editor;
+mod quiz;
 pub mod rendering;
+mod roll_events;
+mod ruleset;
+mod safety_tools;
 mod select_theme_preview;
+mod session_clock;
 mod settings;
 pub mod settings_tabs;
 mod setup;
 mod slider_group;
+mod stats_screen;
+mod suggested_rolls;
+#[cfg(not(target_arch = "wasm32"))]
+mod table_client;
+#[cfg(not(target_arch = "wasm32"))]
+mod table_server;
 mod theme_refresh;
+mod travel_mode;
+mod treasure;
+mod tween;
+mod ui_focus;
+mod ui_scale;
 
 // Re-export all public systems
+pub use achievements::*;
+pub use automation::*;
 pub use avatar_loader::*;
 pub use box_highlight::*;
 pub use camera::*;
+pub use character_file_dialog::*;
 pub use character_screen::*;
+pub use cloud_sync::*;
+#[cfg(feature = "audio")]
 pub use collision_sfx::*;
+pub use compendium_screen::*;
 pub use container_centering::*;
 pub use contributors_screen::*;
+pub use damage::*;
 pub use dice::*;
 pub use dice_box_controls::*;
 pub use dice_box_lid_animations::*;
+#[cfg(feature = "hanabi")]
 pub use dice_fx::*;
+pub use dice_gather::*;
+pub use dice_macros::*;
+pub use dice_palette::*;
+pub use dice_skins::*;
+pub use encounter_screen::*;
+pub use encryption_dialog::*;
+pub use gamepad::*;
 pub use gltf_colliders::*;
 pub use gltf_spawn_points::*;
+pub use initiative_screen::*;
 pub use input::*;
+pub use inspiration::*;
+pub use key_bindings::*;
+pub use loading_splash::*;
+pub use macros_screen::*;
+pub use music::*;
+pub use party_screen::*;
+pub use physics_repro::*;
+pub use platform::*;
+pub use profiles::*;
+pub use qr_transfer::*;
+pub use quick_roll_editor::*;
+pub use quiz::*;
+pub use roll_events::*;
+pub use ruleset::*;
+pub use safety_tools::*;
 pub use select_theme_preview::*;
+pub use session_clock::*;
 pub use settings::*;
 pub use setup::*;
 pub use slider_group::*;
+pub use stats_screen::*;
+pub use suggested_rolls::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use table_client::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use table_server::*;
 pub use theme_refresh::*;
+pub use travel_mode::*;
+pub use treasure::*;
+pub use tween::*;
+pub use ui_focus::*;
+pub use ui_scale::*;
@@ -0,0 +1,190 @@
+//! Background music systems
+//!
+//! Plays the tracks of the music folder chosen in the Audio settings tab one
+//! after another, crossfading between them, and handles the `music` /
+//! `music next` commands.
+
+use std::path::Path;
+
+use bevy::audio::{AudioPlayer, AudioSink, AudioSinkPlayback, PlaybackSettings, Volume};
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{
+    scan_music_folder, track_name, MusicPlayer, MusicPlaylist, MusicTrack, SettingsState,
+    TypedCommand,
+};
+
+/// Seconds a track may go without an audio sink before it is taken as
+/// undecodable and dropped from the playlist
+const TRACK_START_TIMEOUT_SECONDS: f32 = 2.0;
+
+/// A music command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicCommand {
+    /// Show the track playing
+    Show,
+    /// Crossfade to the next track
+    Next,
+}
+
+impl TypedCommand for MusicCommand {
+    const NAME: &'static str = "music";
+
+    /// Parse `music` / `music next` (or `music skip`) commands.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("music") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        if rest.is_empty() {
+            Some(MusicCommand::Show)
+        } else if rest.eq_ignore_ascii_case("next") || rest.eq_ignore_ascii_case("skip") {
+            Some(MusicCommand::Next)
+        } else {
+            None
+        }
+    }
+}
+
+/// Apply a music command, notifying the user.
+pub fn run_music_command(
+    In(command): In<MusicCommand>,
+    mut player: ResMut<MusicPlayer>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let settings = &settings_state.settings;
+    let message = if !settings.music_enabled {
+        "Background music is off (Settings > Audio)".to_string()
+    } else if settings.music_folder.trim().is_empty() {
+        "Choose a music folder in Settings > Audio".to_string()
+    } else {
+        match (command, player.playlist.current()) {
+            (_, None) => format!("No music tracks in {}", settings.music_folder.trim()),
+            (MusicCommand::Show, Some(path)) => format!(
+                "Playing {} ({} tracks{})",
+                track_name(path),
+                player.playlist.len(),
+                if settings.music_shuffle {
+                    ", shuffled"
+                } else {
+                    ""
+                }
+            ),
+            (MusicCommand::Next, Some(_)) => {
+                player.skip_requested = true;
+                "Next track".to_string()
+            }
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(2.5));
+}
+
+/// Keep the background music going: read the music folder, start the next
+/// track when the current one ends (or `music next` was typed) and fade the
+/// tracks in and out at the music volume.
+pub fn play_background_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings_state: Res<SettingsState>,
+    mut player: ResMut<MusicPlayer>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+) {
+    let settings = &settings_state.settings;
+    let folder = settings.music_folder.trim();
+    let playing = settings.music_enabled && !folder.is_empty();
+    let volume = settings.music_volume.clamp(0.0, 1.0);
+    let dt = time.delta_secs();
+
+    if !playing {
+        // Read the folder again when the music is turned back on
+        player.folder = None;
+    } else if player.folder.as_deref() != Some(folder) {
+        player.playlist = match scan_music_folder(Path::new(folder)) {
+            Ok(found) => {
+                info!("Music: {} tracks in {}", found.len(), folder);
+                MusicPlaylist::new(found)
+            }
+            Err(e) => {
+                warn!("Music: {}", e);
+                MusicPlaylist::default()
+            }
+        };
+        player.folder = Some(folder.to_string());
+        // Fade out whatever was playing from the previous folder
+        player.skip_requested = true;
+    }
+
+    let mut current_playing = false;
+    for (entity, mut track, sink) in &mut tracks {
+        track.elapsed += dt;
+        if let Some(fading) = track.fade_out.as_mut() {
+            *fading += dt;
+        } else if !playing || player.skip_requested {
+            track.fade_out = Some(0.0);
+        }
+
+        let finished = match &sink {
+            Some(sink) => sink.empty(),
+            None => track.elapsed > TRACK_START_TIMEOUT_SECONDS,
+        };
+        if sink.is_none() && finished && track.fade_out.is_none() {
+            warn!("Music: could not play {}", track.name);
+            player.playlist.remove_current();
+        }
+        if finished || track.is_silent() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(track.gain() * volume));
+        }
+        current_playing |= track.fade_out.is_none();
+    }
+    player.skip_requested = false;
+
+    if !playing || current_playing {
+        return;
+    }
+
+    // Start the next track, passing over files that cannot be read
+    let mut rng = rand::rng();
+    for _ in 0..player.playlist.len() {
+        let Some(path) = player
+            .playlist
+            .advance(settings.music_shuffle, &mut rng)
+            .map(Path::to_path_buf)
+        else {
+            break;
+        };
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let name = track_name(&path);
+                info!("Music: playing {}", name);
+                let handle = audio_sources.add(AudioSource {
+                    bytes: bytes.into(),
+                });
+                commands.spawn((
+                    MusicTrack::new(name),
+                    AudioPlayer(handle),
+                    PlaybackSettings::ONCE
+                        .with_spatial(false)
+                        .with_volume(Volume::Linear(0.0)),
+                ));
+                break;
+            }
+            Err(e) => {
+                warn!("Music: failed to read {}: {}", path.display(), e);
+                player.playlist.remove_current();
+            }
+        }
+    }
+}
@@ -0,0 +1,467 @@
+//! Party tab
+//!
+//! The DM selects stored characters (one by one, or a party saved with the
+//! CLI's `party` command), picks a check and a DC, and rolls the group check
+//! with one button. `run_group_rolls` throws it in the 3D scene through the
+//! same queue as macros: one character after another, or all of their d20s
+//! together. Results show up on the tab as they are read, passed or failed
+//! against the DC, with the group's verdict at the end.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::{start_queued_roll, MacroPlaybackParams};
+use crate::dice3d::types::*;
+
+/// Node the tab's content is spawned under, rebuilt when it changes
+#[derive(Component)]
+pub struct PartyScreenBody;
+
+/// Buttons of the Party tab
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum PartyButton {
+    /// Select or deselect the character with this id
+    ToggleCharacter(i64),
+    /// Select the members of the saved party at this index
+    SavedParty(usize),
+    SelectAll,
+    ClearSelection,
+    Check(GroupCheck),
+    DcDown,
+    DcUp,
+    /// Switch between one-at-a-time and combined throws
+    Throw,
+    Roll,
+    Stop,
+}
+
+/// Load the current profile's saved parties whenever the database (profile)
+/// changes.
+pub fn load_saved_parties(db: Option<Res<CharacterDatabase>>, mut parties: ResMut<Parties>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    *parties = db
+        .get_setting::<Parties>(PARTIES_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("Failed to load parties: {}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Spawn the (hidden) Party tab.
+pub fn setup_party_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            PartyScreenRoot,
+        ))
+        .with_children(|parent| {
+            spawn_party_text(parent, "Party".to_string(), 28.0, theme.on_surface);
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        margin: UiRect::top(Val::Px(12.0)),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        PartyScreenBody,
+                    ));
+                });
+        });
+}
+
+/// Handle the Party tab's buttons.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_party_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&PartyButton>,
+    db: Option<Res<CharacterDatabase>>,
+    character_manager: Res<CharacterManager>,
+    parties: Res<Parties>,
+    ruleset: Res<Ruleset>,
+    mut selection: ResMut<PartySelection>,
+    mut queue: ResMut<GroupRollQueue>,
+    mut ui_state: ResMut<UiState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        match button {
+            PartyButton::ToggleCharacter(id) => selection.toggle(*id),
+            PartyButton::SavedParty(index) => {
+                let Some(party) = parties.parties.get(*index) else {
+                    continue;
+                };
+                selection.characters = character_manager
+                    .characters
+                    .iter()
+                    .filter(|c| {
+                        party
+                            .members
+                            .iter()
+                            .any(|m| m.eq_ignore_ascii_case(&c.name))
+                    })
+                    .map(|c| c.id)
+                    .collect();
+            }
+            PartyButton::SelectAll => {
+                selection.characters = character_manager.characters.iter().map(|c| c.id).collect();
+            }
+            PartyButton::ClearSelection => selection.characters.clear(),
+            PartyButton::Check(check) => selection.check = check.clone(),
+            PartyButton::DcDown => selection.dc = (selection.dc - 1).max(1),
+            PartyButton::DcUp => selection.dc = (selection.dc + 1).min(40),
+            PartyButton::Throw => selection.throw = selection.throw.toggled(),
+            PartyButton::Stop => queue.stop(),
+            PartyButton::Roll => {
+                if queue.is_running() {
+                    snackbar.write(
+                        ShowSnackbar::message("A group check is already rolling").duration(2.0),
+                    );
+                    continue;
+                }
+                let Some(db) = db.as_deref() else {
+                    continue;
+                };
+                let mut members = Vec::new();
+                for id in &selection.characters {
+                    let member = db
+                        .load_character(*id)
+                        .and_then(|sheet| selection.check.member(&sheet, *ruleset, false, false));
+                    match member {
+                        Ok(member) => members.push(member),
+                        Err(e) => {
+                            warn!("Left out of the group check: {}", e);
+                            snackbar.write(ShowSnackbar::message(e).duration(2.5));
+                        }
+                    }
+                }
+                if members.is_empty() {
+                    snackbar.write(
+                        ShowSnackbar::message("Select characters to roll for").duration(2.0),
+                    );
+                    continue;
+                }
+                queue.start(&selection.check, selection.dc, members, selection.throw);
+                // Watch the dice; the results wait on the Party tab
+                ui_state.active_tab = AppTab::DiceRoller;
+            }
+        }
+    }
+}
+
+/// Throw the queued group check one throw at a time, reading each before
+/// the next, and announce the group's verdict.
+pub fn run_group_rolls(
+    mut resolved: MessageReader<RollResolved>,
+    mut cancelled: MessageReader<RollCancelled>,
+    mut queue: ResMut<GroupRollQueue>,
+    playback: Res<MacroPlayback>,
+    mut params: MacroPlaybackParams,
+) {
+    if cancelled.read().count() > 0 && queue.is_running() {
+        queue.stop();
+        params.snackbar.write(
+            ShowSnackbar::message(format!(
+                "Group {} stopped: a roll was cancelled",
+                queue.label
+            ))
+            .duration(4.0),
+        );
+        return;
+    }
+
+    let mut read = false;
+    for roll in resolved.read() {
+        read |= queue.resolve(&roll.label, &roll.results);
+    }
+    if read && !queue.is_running() {
+        let message = format!("Group {}: {}", queue.label, queue.summary());
+        info!("{}", message);
+        params
+            .snackbar
+            .write(ShowSnackbar::message(message).duration(4.0));
+        return;
+    }
+
+    if queue.is_waiting()
+        || playback.is_playing()
+        || params.roll_state.rolling
+        || params.lid_ctrl.pending_roll.is_some()
+    {
+        return;
+    }
+    if let Some(config) = queue.next_throw() {
+        start_queued_roll(&mut params, config, RollSource::GroupCheck);
+    }
+}
+
+/// Rebuild the tab when the selection, the results or the characters change.
+#[allow(clippy::too_many_arguments)]
+pub fn update_party_screen(
+    mut commands: Commands,
+    selection: Res<PartySelection>,
+    queue: Res<GroupRollQueue>,
+    parties: Res<Parties>,
+    character_manager: Res<CharacterManager>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<PartyScreenBody>>,
+) {
+    if !selection.is_changed()
+        && !queue.is_changed()
+        && !parties.is_changed()
+        && !character_manager.is_changed()
+    {
+        return;
+    }
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        spawn_party_text(body, "Characters".to_string(), 18.0, theme.primary);
+        if character_manager.characters.is_empty() {
+            spawn_party_text(
+                body,
+                "Create characters on the Character tab to roll group checks.".to_string(),
+                15.0,
+                theme.on_surface_variant,
+            );
+            return;
+        }
+        spawn_button_row(body, |row| {
+            for character in &character_manager.characters {
+                spawn_party_button(
+                    row,
+                    &character.name,
+                    selection.is_selected(character.id),
+                    PartyButton::ToggleCharacter(character.id),
+                    &theme,
+                );
+            }
+        });
+        spawn_button_row(body, |row| {
+            spawn_party_button(row, "Select All", false, PartyButton::SelectAll, &theme);
+            spawn_party_button(row, "Clear", false, PartyButton::ClearSelection, &theme);
+            for (index, party) in parties.parties.iter().enumerate() {
+                spawn_party_button(
+                    row,
+                    &format!("Party: {}", party.name),
+                    false,
+                    PartyButton::SavedParty(index),
+                    &theme,
+                );
+            }
+        });
+
+        spawn_party_text(
+            body,
+            format!("Check: {}", selection.check.label()),
+            18.0,
+            theme.primary,
+        );
+        let checks = [
+            ABILITY_NAMES.map(GroupCheck::Ability).to_vec(),
+            ABILITY_NAMES.map(GroupCheck::Save).to_vec(),
+            STANDARD_SKILLS
+                .iter()
+                .map(|&(skill, _)| GroupCheck::Skill { skill, with: None })
+                .chain([GroupCheck::Initiative])
+                .collect(),
+        ];
+        for group in checks {
+            spawn_button_row(body, |row| {
+                for check in group {
+                    let selected = check == selection.check;
+                    let label = match &check {
+                        GroupCheck::Save(ability) => {
+                            format!("{} Save", ability_abbreviation(ability))
+                        }
+                        GroupCheck::Ability(ability) => ability_abbreviation(ability).to_string(),
+                        other => other.label(),
+                    };
+                    spawn_party_button(row, &label, selected, PartyButton::Check(check), &theme);
+                }
+            });
+        }
+
+        spawn_button_row(body, |row| {
+            spawn_party_button(row, "DC -", false, PartyButton::DcDown, &theme);
+            spawn_party_text(row, format!("DC {}", selection.dc), 18.0, theme.on_surface);
+            spawn_party_button(row, "DC +", false, PartyButton::DcUp, &theme);
+            spawn_party_button(
+                row,
+                &format!("Throw: {}", selection.throw.label()),
+                false,
+                PartyButton::Throw,
+                &theme,
+            );
+            if queue.is_running() {
+                spawn_party_button(row, "Stop", false, PartyButton::Stop, &theme);
+            } else {
+                spawn_party_button(
+                    row,
+                    &format!(
+                        "Roll {} for {}",
+                        selection.check.label(),
+                        selection.characters.len()
+                    ),
+                    true,
+                    PartyButton::Roll,
+                    &theme,
+                );
+            }
+        });
+
+        if queue.results.is_empty() {
+            return;
+        }
+        spawn_party_text(
+            body,
+            format!("{} · {}", queue.label, queue.summary()),
+            18.0,
+            match queue.passed() {
+                Some(true) => theme.primary,
+                Some(false) => theme.error,
+                None => theme.on_surface,
+            },
+        );
+        let mut results = queue.results.clone();
+        rank_group_rolls(&mut results);
+        for roll in &results {
+            let passed = roll.passes(queue.dc);
+            body.spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(16.0),
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    ..default()
+                },
+                BackgroundColor(theme.surface_container),
+                BorderRadius::all(Val::Px(CornerRadius::MEDIUM)),
+            ))
+            .with_children(|row| {
+                let total = if roll.auto_fail {
+                    "  —".to_string()
+                } else {
+                    format!("{:>3}", roll.total)
+                };
+                spawn_party_text(row, total, 20.0, theme.on_surface);
+                let details = if roll.auto_fail {
+                    format!("{} · fails automatically", roll.name)
+                } else {
+                    let mut details = format!("{} · d20 {}", roll.name, roll.die);
+                    if let Some(dropped) = roll.dropped {
+                        details.push_str(&format!(" ({} dropped)", dropped));
+                    }
+                    details.push_str(&format!(" {:+}", roll.modifier));
+                    details
+                };
+                spawn_party_text(row, details, 16.0, theme.on_surface);
+                spawn_party_text(
+                    row,
+                    if passed { "Pass" } else { "Fail" }.to_string(),
+                    16.0,
+                    if passed { theme.primary } else { theme.error },
+                );
+            });
+        }
+    });
+}
+
+fn spawn_button_row(
+    parent: &mut ChildSpawnerCommands,
+    children: impl FnOnce(&mut ChildSpawnerCommands),
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_party_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    button: PartyButton,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (material_button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent
+        .spawn((material_button, button))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(text_color),
+                ButtonLabel,
+            ));
+        });
+}
+
+fn spawn_party_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
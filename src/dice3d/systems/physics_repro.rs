@@ -0,0 +1,296 @@
+//! Physics debug capture systems
+//!
+//! `capture_throw_inputs` keeps the starting state of each thrown die while
+//! debug capture is on; `write_physics_repros` saves it when
+//! `check_dice_settled` reports a die out of the container or a roll that
+//! timed out. `misread` in the command input saves the last roll on demand,
+//! and `replay <file>` throws the dice of a repro file again.
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+use bevy_rapier3d::prelude::*;
+
+use super::dice_box_controls::start_container_shake;
+use super::input::CommandRollParams;
+use super::setup::{calculate_dice_position, spawn_die};
+use crate::dice3d::types::*;
+
+/// A `misread` / `replay` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhysicsReproCommand {
+    /// Save the last roll: its dice show something else than was read
+    Misread,
+    /// Throw the dice of a repro file again
+    Replay(PathBuf),
+}
+
+impl TypedCommand for PhysicsReproCommand {
+    const NAME: &'static str = "physics repro";
+
+    /// Parse `misread` and `replay <file>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let rest = rest.trim().trim_matches('"');
+        match head.to_lowercase().as_str() {
+            "misread" if rest.is_empty() => Some(PhysicsReproCommand::Misread),
+            "replay" if !rest.is_empty() => Some(PhysicsReproCommand::Replay(PathBuf::from(rest))),
+            _ => None,
+        }
+    }
+}
+
+/// The container and physics settings throws currently run with.
+fn current_setup(settings: &AppSettings, style: DiceContainerStyle) -> ContainerSetup {
+    ContainerSetup {
+        style,
+        shake: settings.default_roll_uses_shake,
+        fixed_timestep: settings.fixed_timestep_physics,
+        steered_dice: settings.steered_dice,
+    }
+}
+
+/// Write a repro file to the `physics-repros` folder next to the database.
+fn save_repro(repro: &PhysicsRepro, db: Option<&CharacterDatabase>) -> Result<PathBuf, String> {
+    let folder = db
+        .and_then(|db| db.db_path.parent())
+        .map(|dir| dir.join(PHYSICS_REPRO_FOLDER))
+        .unwrap_or_else(|| PathBuf::from(PHYSICS_REPRO_FOLDER));
+    std::fs::create_dir_all(&folder)
+        .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = folder.join(repro.file_name(timestamp));
+    std::fs::write(&path, repro.to_json()?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Record the starting state of the dice of each roll while debug capture
+/// is on: every die when a roll starts, and dice added during it (exploded
+/// dice thrown again).
+pub fn capture_throw_inputs(
+    settings_state: Res<SettingsState>,
+    roll_state: Res<RollState>,
+    container_style: Res<DiceContainerStyle>,
+    mut capture: ResMut<PhysicsReproCapture>,
+    dice: Query<(
+        Entity,
+        Ref<Die>,
+        &Transform,
+        &Velocity,
+        Option<&PercentileDie>,
+    )>,
+    mut was_rolling: Local<bool>,
+) {
+    let started = roll_state.rolling && !*was_rolling;
+    *was_rolling = roll_state.rolling;
+
+    if !settings_state.settings.physics_debug_capture {
+        if capture.container.is_some() {
+            *capture = PhysicsReproCapture::default();
+        }
+        return;
+    }
+    if started {
+        capture.start_roll(current_setup(&settings_state.settings, *container_style));
+    } else if !roll_state.rolling {
+        return;
+    }
+
+    for (entity, die, transform, velocity, percentile) in dice.iter() {
+        if !started && !die.is_added() {
+            continue;
+        }
+        let throw = match percentile {
+            // Recorded with its tens die
+            Some(PercentileDie::Units) => continue,
+            Some(PercentileDie::Tens { units }) => DieThrow {
+                die_type: DiceType::Custom(100),
+                body: BodyState::new(transform, velocity.linvel, velocity.angvel),
+                units: dice
+                    .get(*units)
+                    .ok()
+                    .map(|(_, _, t, v, _)| BodyState::new(t, v.linvel, v.angvel)),
+            },
+            None => DieThrow {
+                die_type: die.die_type,
+                body: BodyState::new(transform, velocity.linvel, velocity.angvel),
+                units: None,
+            },
+        };
+        capture.record(entity, throw);
+    }
+}
+
+/// Save a repro of the current roll the first time `check_dice_settled`
+/// has to rescue its dice.
+pub fn write_physics_repros(
+    mut anomalies: MessageReader<PhysicsAnomaly>,
+    settings_state: Res<SettingsState>,
+    mut capture: ResMut<PhysicsReproCapture>,
+    db: Option<Res<CharacterDatabase>>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let Some(anomaly) = anomalies.read().last().copied() else {
+        return;
+    };
+    if !settings_state.settings.physics_debug_capture || capture.written {
+        return;
+    }
+    let Some(repro) = capture.repro(anomaly.reason) else {
+        return;
+    };
+
+    capture.written = true;
+    let message = match save_repro(&repro, db.as_deref()) {
+        Ok(path) => format!(
+            "Physics repro saved ({}): {}",
+            anomaly.reason.describe(),
+            path.display()
+        ),
+        Err(e) => e,
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(6.0));
+}
+
+/// Put replayed dice in their recorded starting state once spawned, after
+/// spawn points and the percentile pairing have moved them.
+pub fn apply_replayed_throws(
+    mut commands: Commands,
+    replayed: Query<(Entity, &ReplayedThrow, Option<&PercentileDie>)>,
+    mut bodies: Query<(&mut Transform, &mut Velocity)>,
+) {
+    for (entity, ReplayedThrow(throw), percentile) in replayed.iter() {
+        let mut states = vec![(entity, throw.body)];
+        if let (Some(PercentileDie::Tens { units }), Some(units_body)) = (percentile, throw.units) {
+            states.push((*units, units_body));
+        }
+        for (target, state) in states {
+            if let Ok((mut transform, mut velocity)) = bodies.get_mut(target) {
+                *transform = state.transform();
+                velocity.linvel = state.linvel();
+                velocity.angvel = state.angvel();
+            }
+        }
+        commands.entity(entity).remove::<ReplayedThrow>();
+    }
+}
+
+/// What the `misread` and `replay` commands work with
+#[derive(SystemParam)]
+pub struct PhysicsReproParams<'w, 's> {
+    pub roll: CommandRollParams<'w, 's>,
+    pub physics_repro: ResMut<'w, PhysicsReproCapture>,
+    pub db: Res<'w, CharacterDatabase>,
+}
+
+/// Apply a `misread` / `replay` command, notifying the user.
+pub fn run_physics_repro_command(
+    In(command): In<PhysicsReproCommand>,
+    mut params: PhysicsReproParams,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        PhysicsReproCommand::Misread => flag_misread(&mut params),
+        PhysicsReproCommand::Replay(path) => replay(&mut params, &path),
+    };
+    let message = message.unwrap_or_else(|e| e);
+    snackbar.write(ShowSnackbar::message(message).duration(6.0));
+}
+
+fn flag_misread(params: &mut PhysicsReproParams) -> Result<String, String> {
+    if !params.roll.settings_state.settings.physics_debug_capture {
+        return Err("Turn on debug capture in the Dice settings to record throws".to_string());
+    }
+    if params.roll.roll_state.rolling {
+        return Err("Wait for the dice to settle".to_string());
+    }
+    let mut repro = params
+        .physics_repro
+        .repro(ReproReason::Misread)
+        .ok_or_else(|| "No throw recorded yet".to_string())?;
+    repro.results = params.roll.dice_results.results.clone();
+
+    let path = save_repro(&repro, Some(&*params.db))?;
+    params.physics_repro.written = true;
+    Ok(format!("Physics repro saved: {}", path.display()))
+}
+
+fn replay(params: &mut PhysicsReproParams, path: &std::path::Path) -> Result<String, String> {
+    if params.roll.roll_state.rolling {
+        return Err("Wait for the dice to settle".to_string());
+    }
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let repro = PhysicsRepro::from_json(&text)?;
+    let current = current_setup(
+        &params.roll.settings_state.settings,
+        *params.roll.container_style,
+    );
+    let differences = repro.setup_differences(&current)?;
+
+    for entity in params.roll.dice_query.iter() {
+        params.roll.commands.entity(entity).despawn();
+    }
+    *params.roll.dice_config = DiceConfig {
+        dice_to_roll: repro.throws.iter().map(|t| t.die_type).collect(),
+        modifier: 0,
+        modifier_name: "Replay".to_string(),
+        expression: None,
+    };
+    params.roll.dice_results.clear();
+
+    for (i, throw) in repro.throws.iter().enumerate() {
+        let die_scale = params
+            .roll
+            .settings_state
+            .settings
+            .dice_scales
+            .scale_for(throw.die_type);
+        let entity = spawn_die(
+            &mut params.roll.commands,
+            &mut params.roll.meshes,
+            &mut params.roll.materials,
+            &mut params.roll.mesh_cache,
+            throw.die_type,
+            die_scale,
+            calculate_dice_position(i, repro.throws.len()),
+            &mut *params.roll.roll_rng,
+        );
+        params
+            .roll
+            .commands
+            .entity(entity)
+            .insert(ReplayedThrow(throw.clone()));
+    }
+
+    if repro.container.shake {
+        let _started = start_container_shake(
+            &params.roll.shake_state,
+            &params.roll.shake_config,
+            &mut params.roll.shake_anim,
+            &params.roll.container_query,
+        );
+    }
+    params.roll.roll_state.rolling = true;
+
+    let mut message = format!(
+        "Replaying {} dice ({})",
+        repro.throws.len(),
+        repro.reason.describe()
+    );
+    if !differences.is_empty() {
+        message.push_str(&format!("; when recorded {}", differences.join(", ")));
+    }
+    Ok(message)
+}
@@ -0,0 +1,196 @@
+//! Platform integration
+//!
+//! - Application ID (Wayland `app_id` / X11 `WM_CLASS`) matching the shipped
+//!   `.desktop` file, so compositors pick up the icon and group windows
+//! - macOS menu bar with About / Preferences… / Hide / Quit; Preferences opens
+//!   the settings modal and Quit exits through Bevy
+//! - Opening `.dndchar` character files passed on the command line (file
+//!   associations launch the app with `--open <file>`)
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::prelude::*;
+use bevy::winit::WinitWindows;
+
+use super::character_file_dialog::CharacterFileDialogState;
+use super::settings::open_settings_modal;
+use crate::dice3d::types::{read_character_file, ContainerShakeConfig, SettingsState};
+
+/// Application ID; must match the `.desktop` file name under `assets/linux`.
+pub const APP_ID: &str = "io.github.edgarhsanchez.dndgamerolls";
+
+/// Set by the macOS menu bar (AppKit callbacks run outside the ECS) and
+/// drained by [`handle_platform_menu_requests`].
+static PREFERENCES_REQUESTED: AtomicBool = AtomicBool::new(false);
+static QUIT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// A character file given on the command line, shown once the app is up.
+#[derive(Resource, Default)]
+pub struct PendingCharacterFileOpen {
+    pub path: Option<PathBuf>,
+}
+
+/// Show the character file given on the command line, or why it can't be
+/// opened.
+pub fn open_pending_character_file(
+    mut pending: ResMut<PendingCharacterFileOpen>,
+    mut dialog: ResMut<CharacterFileDialogState>,
+) {
+    let Some(path) = pending.path.take() else {
+        return;
+    };
+
+    match read_character_file(&path) {
+        Ok(file) => {
+            info!("Opened character file {}", path.display());
+            dialog.file = Some(file);
+            dialog.error = None;
+        }
+        Err(e) => {
+            warn!("{}", e);
+            dialog.error = Some(e);
+        }
+    }
+}
+
+/// Install the native menu bar.
+///
+/// Takes `WinitWindows` so the system runs on the main thread, which AppKit
+/// requires. Does nothing on platforms without an application menu bar.
+pub fn install_platform_menu(_windows: Option<NonSend<WinitWindows>>) {
+    #[cfg(all(target_os = "macos", feature = "integrations"))]
+    macos::install_menu_bar();
+}
+
+/// Act on menu bar selections.
+pub fn handle_platform_menu_requests(
+    mut settings_state: ResMut<SettingsState>,
+    shake_config: Res<ContainerShakeConfig>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    if QUIT_REQUESTED.swap(false, Ordering::Relaxed) {
+        app_exit.write(AppExit::Success);
+        return;
+    }
+
+    if PREFERENCES_REQUESTED.swap(false, Ordering::Relaxed) && !settings_state.show_modal {
+        open_settings_modal(&mut settings_state, &shake_config);
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "integrations"))]
+mod macos {
+    use std::sync::atomic::Ordering;
+
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, NSObject, Sel};
+    use objc2::{define_class, msg_send, sel, MainThreadMarker, MainThreadOnly};
+    use objc2_app_kit::{NSApplication, NSMenu, NSMenuItem};
+    use objc2_foundation::NSString;
+
+    use super::{PREFERENCES_REQUESTED, QUIT_REQUESTED};
+
+    define_class!(
+        // SAFETY: NSObject has no subclassing requirements and MenuTarget
+        // does not implement Drop.
+        #[unsafe(super(NSObject))]
+        #[thread_kind = MainThreadOnly]
+        #[name = "DndGameRollsMenuTarget"]
+        struct MenuTarget;
+
+        impl MenuTarget {
+            #[unsafe(method(showPreferences:))]
+            fn show_preferences(&self, _sender: Option<&AnyObject>) {
+                PREFERENCES_REQUESTED.store(true, Ordering::Relaxed);
+            }
+
+            #[unsafe(method(quit:))]
+            fn quit(&self, _sender: Option<&AnyObject>) {
+                QUIT_REQUESTED.store(true, Ordering::Relaxed);
+            }
+        }
+    );
+
+    impl MenuTarget {
+        fn new(mtm: MainThreadMarker) -> Retained<Self> {
+            let this = Self::alloc(mtm).set_ivars(());
+            unsafe { msg_send![super(this), init] }
+        }
+    }
+
+    fn menu_item(
+        mtm: MainThreadMarker,
+        title: &str,
+        action: Sel,
+        key: &str,
+        target: Option<&AnyObject>,
+    ) -> Retained<NSMenuItem> {
+        let item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                NSMenuItem::alloc(mtm),
+                &NSString::from_str(title),
+                Some(action),
+                &NSString::from_str(key),
+            )
+        };
+        unsafe { item.setTarget(target) };
+        item
+    }
+
+    /// Replace winit's default menu with one that has a Preferences item.
+    pub(super) fn install_menu_bar() {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+
+        let app = NSApplication::sharedApplication(mtm);
+        let target = MenuTarget::new(mtm);
+        let target_obj: &AnyObject = &target;
+
+        let app_menu = NSMenu::new(mtm);
+        // nil target: these go to NSApplication through the responder chain.
+        app_menu.addItem(&menu_item(
+            mtm,
+            "About DnD Game Rolls",
+            sel!(orderFrontStandardAboutPanel:),
+            "",
+            None,
+        ));
+        app_menu.addItem(&NSMenuItem::separatorItem(mtm));
+        app_menu.addItem(&menu_item(
+            mtm,
+            "Preferences…",
+            sel!(showPreferences:),
+            ",",
+            Some(target_obj),
+        ));
+        app_menu.addItem(&NSMenuItem::separatorItem(mtm));
+        app_menu.addItem(&menu_item(
+            mtm,
+            "Hide DnD Game Rolls",
+            sel!(hide:),
+            "h",
+            None,
+        ));
+        app_menu.addItem(&NSMenuItem::separatorItem(mtm));
+        app_menu.addItem(&menu_item(
+            mtm,
+            "Quit DnD Game Rolls",
+            sel!(quit:),
+            "q",
+            Some(target_obj),
+        ));
+
+        let app_menu_item = NSMenuItem::new(mtm);
+        app_menu_item.setSubmenu(Some(&app_menu));
+
+        let menu_bar = NSMenu::new(mtm);
+        menu_bar.addItem(&app_menu_item);
+        app.setMainMenu(Some(&menu_bar));
+
+        // Menu item targets are weak references; the target lives as long as
+        // the app.
+        std::mem::forget(target);
+    }
+}
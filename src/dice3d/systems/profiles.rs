@@ -0,0 +1,138 @@
+//! Database profile switching
+//!
+//! `profile` / `profiles` in the command input lists the profiles and
+//! `profile <name>` switches to one (creating it if needed); the settings
+//! Profiles tab has a Switch button per profile. Characters, settings and
+//! history are all loaded at startup, so switching saves the new active
+//! profile and restarts the app.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{CharacterDatabase, DatabaseProfile, TypedCommand};
+
+/// Switch button for one profile in the settings Profiles tab
+#[derive(Component)]
+pub struct ProfileSwitchButton {
+    pub profile_id: String,
+}
+
+/// A `profile` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileCommand {
+    List,
+    Switch(String),
+}
+
+impl TypedCommand for ProfileCommand {
+    const NAME: &'static str = "profile";
+
+    /// Parse `profile`, `profiles` and `profile <name>` commands.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let head = head.to_lowercase();
+        let rest = rest.trim();
+
+        match head.as_str() {
+            "profiles" if rest.is_empty() => Some(ProfileCommand::List),
+            "profile" if rest.is_empty() => Some(ProfileCommand::List),
+            "profile" => Some(ProfileCommand::Switch(rest.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// One-line summary of the profiles for a snackbar.
+pub fn profile_list_summary() -> String {
+    let registry = CharacterDatabase::load_profiles();
+    let current = CharacterDatabase::current_profile();
+    let names = registry
+        .profiles
+        .iter()
+        .map(|p| {
+            if p.id == current.id {
+                format!("{} (current)", p.name)
+            } else {
+                p.name.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("Profiles: {}", names)
+}
+
+/// Make a profile (created if it doesn't exist) the active one.
+///
+/// Returns `Ok(None)` when it is already the profile in use.
+pub fn activate_profile(name: &str) -> Result<Option<DatabaseProfile>, String> {
+    let mut registry = CharacterDatabase::load_profiles();
+    let profile = registry.find_or_create(name)?;
+    if profile.id == CharacterDatabase::current_profile().id {
+        return Ok(None);
+    }
+    registry.set_active(&profile.id);
+    CharacterDatabase::save_profiles(&registry)?;
+    Ok(Some(profile))
+}
+
+/// Start a fresh copy of the app (which opens the active profile) and exit.
+pub fn restart_app(app_exit: &mut MessageWriter<AppExit>) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find executable: {}", e))?;
+    std::process::Command::new(exe)
+        .spawn()
+        .map_err(|e| format!("Failed to restart: {}", e))?;
+    app_exit.write(AppExit::Success);
+    Ok(())
+}
+
+/// Switch to a profile and restart, reporting problems in a snackbar.
+pub fn switch_profile(
+    name: &str,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+    app_exit: &mut MessageWriter<AppExit>,
+) {
+    match activate_profile(name).and_then(|switched| match switched {
+        Some(profile) => restart_app(app_exit).map(|_| Some(profile)),
+        None => Ok(None),
+    }) {
+        Ok(Some(profile)) => info!("Switching to profile '{}'", profile.name),
+        Ok(None) => {
+            snackbar.write(ShowSnackbar::message("Already using that profile").duration(2.0));
+        }
+        Err(e) => {
+            warn!("Failed to switch profile: {}", e);
+            snackbar.write(ShowSnackbar::message("Failed to switch profile").duration(2.0));
+        }
+    }
+}
+
+/// Run a `profile` command: list the profiles or switch to one.
+pub fn run_profile_command(
+    In(command): In<ProfileCommand>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    match command {
+        ProfileCommand::List => {
+            snackbar.write(ShowSnackbar::message(profile_list_summary()).duration(4.0));
+        }
+        ProfileCommand::Switch(name) => switch_profile(&name, &mut snackbar, &mut app_exit),
+    }
+}
+
+/// Handle Switch buttons in the settings Profiles tab.
+pub fn handle_profile_switch_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&ProfileSwitchButton>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    for event in click_events.read() {
+        if let Ok(button) = buttons.get(event.entity) {
+            switch_profile(&button.profile_id, &mut snackbar, &mut app_exit);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! Share via QR
+//!
+//! `share qr` in the command input shows the current character as one or
+//! more QR codes. `scan` on the receiving side picks photos or screenshots of
+//! the codes; once every code has been read the character opens in the
+//! read-only character file view, ready to import. There is no live camera
+//! capture: take a picture of each code and import the images.
+
+use std::path::PathBuf;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::widget::ImageNode;
+use bevy_material_ui::prelude::*;
+
+use super::character_file_dialog::{
+    export_current_character_foundry, share_current_character, CharacterFileDialogState,
+    ShareCommand,
+};
+use crate::dice3d::types::{
+    encode_qr_payloads, qr_modules, redact_sheet, scan_qr_payloads, CharacterData, FocusTrap,
+    QrTransferAssembler, Viewer,
+};
+
+/// Screen pixels per QR module; whole pixels keep the code sharp.
+const QR_PIXELS_PER_MODULE: usize = 4;
+
+#[derive(Component)]
+pub struct QrShareOverlay;
+
+#[derive(Component)]
+pub struct QrSharePreviousButton;
+
+#[derive(Component)]
+pub struct QrShareNextButton;
+
+#[derive(Component)]
+pub struct QrShareCloseButton;
+
+/// Codes being shown; the dialog is open while `payloads` is not empty.
+#[derive(Resource, Default)]
+pub struct QrShareDialogState {
+    pub name: String,
+    pub payloads: Vec<String>,
+    pub index: usize,
+}
+
+/// Codes scanned so far on the receiving side
+#[derive(Resource, Default)]
+pub struct QrScanState {
+    pub assembler: QrTransferAssembler,
+}
+
+/// Resources the `share` and `scan` commands work with
+#[derive(SystemParam)]
+pub struct CharacterShareParams<'w> {
+    pub qr_share: ResMut<'w, QrShareDialogState>,
+    pub qr_scan: ResMut<'w, QrScanState>,
+    pub file_dialog: ResMut<'w, CharacterFileDialogState>,
+}
+
+impl CharacterShareParams<'_> {
+    /// Run a `share` / `share qr` / `share foundry` / `scan` command.
+    pub fn run(
+        &mut self,
+        command: ShareCommand,
+        character_data: &CharacterData,
+        snackbar: &mut MessageWriter<ShowSnackbar>,
+    ) {
+        match command {
+            ShareCommand::File(viewer) => share_current_character(character_data, viewer, snackbar),
+            ShareCommand::Qr(viewer) => self.show_codes(character_data, viewer, snackbar),
+            ShareCommand::Foundry(viewer) => {
+                export_current_character_foundry(character_data, viewer, snackbar)
+            }
+            ShareCommand::Scan => self.scan_images(snackbar),
+        }
+    }
+
+    fn show_codes(
+        &mut self,
+        character_data: &CharacterData,
+        viewer: Viewer,
+        snackbar: &mut MessageWriter<ShowSnackbar>,
+    ) {
+        let Some(sheet) = &character_data.sheet else {
+            snackbar.write(ShowSnackbar::message("No character to share").duration(2.0));
+            return;
+        };
+        match encode_qr_payloads(&redact_sheet(sheet, viewer)) {
+            Ok(payloads) => {
+                *self.qr_share = QrShareDialogState {
+                    name: sheet.character.name.clone(),
+                    payloads,
+                    index: 0,
+                };
+            }
+            Err(e) => {
+                warn!("{}", e);
+                snackbar.write(ShowSnackbar::message("Failed to create QR codes").duration(2.0));
+            }
+        }
+    }
+
+    fn scan_images(&mut self, snackbar: &mut MessageWriter<ShowSnackbar>) {
+        let Some(paths) = pick_qr_images(snackbar) else {
+            return;
+        };
+
+        let assembler = &mut self.qr_scan.assembler;
+        let mut found = 0;
+        for path in &paths {
+            let image = match image::open(path) {
+                Ok(image) => image,
+                Err(e) => {
+                    warn!("Failed to open {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for payload in scan_qr_payloads(&image) {
+                if assembler.add(&payload).is_ok() {
+                    found += 1;
+                }
+            }
+        }
+
+        if found == 0 {
+            snackbar.write(ShowSnackbar::message("No character QR codes found").duration(2.0));
+            return;
+        }
+        if !assembler.is_complete() {
+            let (scanned, total) = assembler.progress();
+            snackbar.write(
+                ShowSnackbar::message(format!(
+                    "Scanned {} of {} QR codes; scan the rest",
+                    scanned, total
+                ))
+                .duration(3.0),
+            );
+            return;
+        }
+
+        let result = assembler.finish();
+        *assembler = QrTransferAssembler::default();
+        match result {
+            Ok(file) => {
+                info!("Received {} via QR", file.sheet.character.name);
+                self.file_dialog.file = Some(file);
+                self.file_dialog.error = None;
+            }
+            Err(e) => {
+                warn!("{}", e);
+                self.file_dialog.error = Some(e);
+            }
+        }
+    }
+}
+
+/// Run a `share` or `scan` command typed into the command input.
+pub fn run_share_command(
+    In(command): In<ShareCommand>,
+    mut share: CharacterShareParams,
+    character_data: Res<CharacterData>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    share.run(command, &character_data, &mut snackbar);
+}
+
+/// Photos or screenshots of the QR codes, picked in a file dialog.
+#[cfg(feature = "integrations")]
+fn pick_qr_images(_snackbar: &mut MessageWriter<ShowSnackbar>) -> Option<Vec<PathBuf>> {
+    rfd::FileDialog::new()
+        .set_title("Choose photos or screenshots of the QR codes")
+        .add_filter("Images", &["png", "jpg", "jpeg", "webp", "bmp"])
+        .pick_files()
+}
+
+/// Builds without file dialogs can't pick the images.
+#[cfg(not(feature = "integrations"))]
+fn pick_qr_images(snackbar: &mut MessageWriter<ShowSnackbar>) -> Option<Vec<PathBuf>> {
+    snackbar.write(
+        ShowSnackbar::message("Scanning needs a build with the integrations feature").duration(3.0),
+    );
+    None
+}
+
+fn spawn_dialog_text(parent: &mut ChildSpawnerCommands, text: &str, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_dialog_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Render one payload as a black-on-white QR image. Returns the image and
+/// its side length in pixels.
+fn qr_image(payload: &str) -> Result<(Image, usize), String> {
+    let (side, modules) = qr_modules(payload)?;
+    let pixels = side * QR_PIXELS_PER_MODULE;
+
+    let mut data = Vec::with_capacity(pixels * pixels * 4);
+    for y in 0..pixels {
+        for x in 0..pixels {
+            let dark = modules[(y / QR_PIXELS_PER_MODULE) * side + x / QR_PIXELS_PER_MODULE];
+            let v = if dark { 0 } else { 255 };
+            data.extend_from_slice(&[v, v, v, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: pixels as u32,
+            height: pixels as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Keep module edges crisp when the UI is scaled
+    image.sampler = ImageSampler::nearest();
+    Ok((image, pixels))
+}
+
+/// Show/hide and rebuild the QR dialog when its state changes.
+pub fn manage_qr_share_dialog(
+    mut commands: Commands,
+    dialog_state: Res<QrShareDialogState>,
+    theme: Option<Res<MaterialTheme>>,
+    mut images: ResMut<Assets<Image>>,
+    overlays: Query<Entity, With<QrShareOverlay>>,
+) {
+    if !dialog_state.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(payload) = dialog_state.payloads.get(dialog_state.index) else {
+        return;
+    };
+    let (image, pixels) = match qr_image(payload) {
+        Ok(image) => image,
+        Err(e) => {
+            warn!("{}", e);
+            return;
+        }
+    };
+    let image = images.add(image);
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let total = dialog_state.payloads.len();
+
+    let dialog = MaterialDialog::new()
+        .title("Share via QR")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            QrShareOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        spawn_dialog_text(dialog, "Share via QR", 20.0, theme.on_surface);
+        spawn_dialog_text(
+            dialog,
+            &format!(
+                "{} · code {} of {}",
+                dialog_state.name,
+                dialog_state.index + 1,
+                total
+            ),
+            14.0,
+            theme.on_surface_variant,
+        );
+
+        dialog.spawn((
+            ImageNode::new(image),
+            Node {
+                width: Val::Px(pixels as f32),
+                height: Val::Px(pixels as f32),
+                ..default()
+            },
+        ));
+
+        spawn_dialog_text(
+            dialog,
+            "Photograph every code, then type \"scan\" on the other device and pick the photos.",
+            13.0,
+            theme.on_surface_variant,
+        );
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_dialog_button(buttons, "Close", false, QrShareCloseButton, &theme);
+                if total > 1 {
+                    spawn_dialog_button(buttons, "Previous", false, QrSharePreviousButton, &theme);
+                    spawn_dialog_button(buttons, "Next", true, QrShareNextButton, &theme);
+                }
+            });
+    });
+}
+
+/// Page through the codes or close the dialog.
+pub fn handle_qr_share_dialog_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    previous_buttons: Query<(), With<QrSharePreviousButton>>,
+    next_buttons: Query<(), With<QrShareNextButton>>,
+    close_buttons: Query<(), With<QrShareCloseButton>>,
+    mut dialog_state: ResMut<QrShareDialogState>,
+) {
+    for event in click_events.read() {
+        let total = dialog_state.payloads.len();
+        if total == 0 {
+            return;
+        }
+
+        if close_buttons.get(event.entity).is_ok() {
+            *dialog_state = QrShareDialogState::default();
+        } else if next_buttons.get(event.entity).is_ok() {
+            dialog_state.index = (dialog_state.index + 1) % total;
+        } else if previous_buttons.get(event.entity).is_ok() {
+            dialog_state.index = (dialog_state.index + total - 1) % total;
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Custom buttons of the quick roll panel
+//!
+//! The panel's edit mode (the pencil next to its title) lists the buttons
+//! the user added, each with a dice expression, label, color and icon. Rows
+//! are reordered with their arrows or by dragging their handle onto another
+//! row, and the buttons are kept in `AppSettings::custom_quick_rolls`, so
+//! they are saved with the other settings. Clicking a custom button throws
+//! its dice like a command roll would.
+
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::{start_queued_roll, MacroPlaybackParams};
+use crate::dice3d::types::*;
+
+/// Track the fields of the button being added and handle the edit mode's
+/// buttons.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_quick_roll_editor_input(
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    mut icon_click_events: MessageReader<IconButtonClickEvent>,
+    draft_fields: Query<&QuickRollDraftField>,
+    buttons: Query<&QuickRollEditButton>,
+    mut editor: ResMut<QuickRollEditor>,
+    mut settings_state: ResMut<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for ev in change_events.read() {
+        let Ok(field) = draft_fields.get(ev.entity) else {
+            continue;
+        };
+        let text = match field {
+            QuickRollDraftField::Label => &mut editor.label,
+            QuickRollDraftField::Expression => &mut editor.expression,
+            QuickRollDraftField::Color => &mut editor.color,
+            QuickRollDraftField::Icon => &mut editor.icon,
+        };
+        if *text != ev.value {
+            *text = ev.value.clone();
+        }
+    }
+
+    let clicked: Vec<Entity> = click_events
+        .read()
+        .map(|ev| ev.entity)
+        .chain(icon_click_events.read().map(|ev| ev.entity))
+        .collect();
+    for entity in clicked {
+        let Ok(button) = buttons.get(entity) else {
+            continue;
+        };
+        let rolls = &mut settings_state.settings.custom_quick_rolls;
+        let changed = match *button {
+            QuickRollEditButton::Toggle => {
+                editor.editing = !editor.editing;
+                editor.dragging = None;
+                false
+            }
+            QuickRollEditButton::Add => {
+                let icon = editor.icon.trim();
+                if !icon.is_empty() && MaterialIcon::from_name(icon).is_none() {
+                    snackbar.write(
+                        ShowSnackbar::message(format!("No icon named '{}'", icon)).duration(3.0),
+                    );
+                    continue;
+                }
+                match CustomQuickRoll::new(
+                    &editor.label,
+                    &editor.expression,
+                    &editor.color,
+                    &editor.icon,
+                ) {
+                    Ok(roll) => {
+                        snackbar.write(
+                            ShowSnackbar::message(format!("Added {}", roll.label)).duration(2.0),
+                        );
+                        rolls.push(roll);
+                        editor.label.clear();
+                        editor.expression.clear();
+                        editor.color.clear();
+                        editor.icon.clear();
+                        true
+                    }
+                    Err(e) => {
+                        snackbar.write(ShowSnackbar::message(e).duration(3.0));
+                        false
+                    }
+                }
+            }
+            QuickRollEditButton::MoveUp(index) => {
+                index > 0 && move_custom_quick_roll(rolls, index, index - 1)
+            }
+            QuickRollEditButton::MoveDown(index) => move_custom_quick_roll(rolls, index, index + 1),
+            QuickRollEditButton::Remove(index) => {
+                if index < rolls.len() {
+                    rolls.remove(index);
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if changed {
+            settings_state.is_modified = true;
+        }
+    }
+}
+
+/// Reorder the custom buttons by dragging a row's handle onto another row,
+/// shading the dragged row and the row it would be dropped on.
+pub fn drag_custom_quick_rolls(
+    mouse: Res<ButtonInput<MouseButton>>,
+    handles: Query<(&Interaction, &CustomQuickRollDragHandle), Changed<Interaction>>,
+    mut rows: Query<(
+        &CustomQuickRollRow,
+        &RelativeCursorPosition,
+        &mut BackgroundColor,
+    )>,
+    theme: Option<Res<MaterialTheme>>,
+    mut editor: ResMut<QuickRollEditor>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    for (interaction, handle) in handles.iter() {
+        if *interaction == Interaction::Pressed && editor.dragging.is_none() {
+            editor.dragging = Some(handle.0);
+        }
+    }
+    let Some(from) = editor.dragging else {
+        return;
+    };
+
+    let target = rows
+        .iter()
+        .find(|(_, cursor, _)| cursor.cursor_over())
+        .map(|(row, _, _)| row.0);
+
+    if mouse.just_released(MouseButton::Left) || !mouse.pressed(MouseButton::Left) {
+        editor.dragging = None;
+        for (_, _, mut background) in rows.iter_mut() {
+            background.0 = Color::NONE;
+        }
+        if let Some(to) = target {
+            if move_custom_quick_roll(&mut settings_state.settings.custom_quick_rolls, from, to) {
+                settings_state.is_modified = true;
+            }
+        }
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    for (row, _, mut background) in rows.iter_mut() {
+        let color = if row.0 == from {
+            theme.surface_container_highest
+        } else if Some(row.0) == target {
+            theme.secondary_container
+        } else {
+            Color::NONE
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+/// Throw the dice of a clicked custom quick roll button.
+pub fn handle_custom_quick_roll_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&CustomQuickRollButton>,
+    mut params: MacroPlaybackParams,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        if params.settings_state.show_modal {
+            continue;
+        }
+        let Some(roll) = params
+            .settings_state
+            .settings
+            .custom_quick_rolls
+            .get(button.0)
+        else {
+            continue;
+        };
+        let config = match roll.dice_config() {
+            Ok(config) => config,
+            Err(e) => {
+                params
+                    .snackbar
+                    .write(ShowSnackbar::message(e).duration(2.5));
+                continue;
+            }
+        };
+        if params.roll_state.rolling || params.lid_ctrl.pending_roll.is_some() {
+            params
+                .snackbar
+                .write(ShowSnackbar::message("Wait for the dice to settle").duration(2.0));
+            continue;
+        }
+        start_queued_roll(&mut params, config, RollSource::QuickRoll);
+    }
+}
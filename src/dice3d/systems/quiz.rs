@@ -0,0 +1,167 @@
+//! Probability quiz systems
+//!
+//! `quiz` in the command input asks a question (see `QuizQuestion`),
+//! `quiz <percent>` answers it and rolls the dice to demonstrate, and
+//! `quiz stats` / `quiz reset` show or clear the estimation accuracy.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use super::input::CommandRollParams;
+use crate::dice3d::types::{
+    parse_estimate, CharacterDatabase, DiceConfig, QuizQuestion, QuizStats, RollResolved,
+    TypedCommand, QUIZ_STATS_SETTING_KEY,
+};
+
+/// The open question and the accuracy so far
+#[derive(Resource, Default)]
+pub struct QuizState {
+    pub stats: QuizStats,
+    /// Question waiting for an answer
+    pub current: Option<QuizQuestion>,
+    /// Answered question whose demonstration roll hasn't resolved yet
+    pub demonstrating: Option<QuizQuestion>,
+}
+
+/// A `quiz` command typed into the command input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuizCommand {
+    Ask,
+    /// Estimated chance, in percent
+    Answer(f64),
+    Stats,
+    Reset,
+}
+
+impl TypedCommand for QuizCommand {
+    const NAME: &'static str = "quiz";
+
+    /// Parse `quiz`, `quiz <percent>`, `quiz stats` and `quiz reset`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("quiz") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        match rest.to_lowercase().as_str() {
+            "" | "new" | "next" => Some(QuizCommand::Ask),
+            "stats" => Some(QuizCommand::Stats),
+            "reset" => Some(QuizCommand::Reset),
+            _ => parse_estimate(rest).map(QuizCommand::Answer),
+        }
+    }
+}
+
+/// Label of a quiz demonstration roll
+fn quiz_roll_label(question: &QuizQuestion) -> String {
+    format!("Quiz: {} vs DC {}", question.expression(), question.dc)
+}
+
+fn save_stats(stats: &QuizStats, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(QUIZ_STATS_SETTING_KEY, stats.clone()) {
+        warn!("Failed to save quiz stats: {}", e);
+    }
+}
+
+/// Apply a quiz command, notifying the user and rolling the dice of an
+/// answered question to demonstrate it.
+pub fn run_quiz_command(
+    In(command): In<QuizCommand>,
+    mut state: ResMut<QuizState>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+    mut roll: CommandRollParams,
+) {
+    let mut demonstration = None;
+    let message = match command {
+        QuizCommand::Ask => {
+            let question = QuizQuestion::random(&mut rand::rng());
+            state.current = Some(question);
+            format!("{} Answer with quiz <percent>", question.prompt())
+        }
+        QuizCommand::Answer(estimate) => match state.current.take() {
+            Some(question) => {
+                let chance = question.chance();
+                let error = state.stats.record(estimate, chance);
+                save_stats(&state.stats, &db);
+                state.demonstrating = Some(question);
+                demonstration = Some(DiceConfig {
+                    dice_to_roll: vec![question.die; question.count as usize],
+                    modifier: question.modifier,
+                    modifier_name: quiz_roll_label(&question),
+                    expression: None,
+                });
+                format!(
+                    "{} meets DC {} {:.1}% of the time; you said {}% (off by {:.1} points). Rolling it...",
+                    question.expression(),
+                    question.dc,
+                    chance * 100.0,
+                    estimate,
+                    error
+                )
+            }
+            None => "No quiz question yet: type quiz to get one".to_string(),
+        },
+        QuizCommand::Stats => format!("Quiz: {}", state.stats.summary()),
+        QuizCommand::Reset => {
+            state.stats = QuizStats::default();
+            save_stats(&state.stats, &db);
+            "Quiz stats cleared".to_string()
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(8.0));
+    if let Some(config) = demonstration {
+        roll.start(config, None);
+    }
+}
+
+/// Load the quiz accuracy whenever the database is opened or the profile
+/// switches.
+pub fn load_quiz_stats(db: Option<Res<CharacterDatabase>>, mut state: ResMut<QuizState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.stats = db
+        .get_setting::<QuizStats>(QUIZ_STATS_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Say whether a quiz demonstration roll met the DC.
+pub fn announce_quiz_rolls(
+    mut resolved: MessageReader<RollResolved>,
+    mut state: ResMut<QuizState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for roll in resolved.read() {
+        let Some(question) = state.demonstrating else {
+            continue;
+        };
+        if roll.label != quiz_roll_label(&question) {
+            continue;
+        }
+        state.demonstrating = None;
+        let outcome = if question.succeeds(roll.total) {
+            "meets"
+        } else {
+            "misses"
+        };
+        snackbar.write(
+            ShowSnackbar::message(format!(
+                "Rolled {}: {} DC {}",
+                roll.total, outcome, question.dc
+            ))
+            .duration(4.0),
+        );
+    }
+}
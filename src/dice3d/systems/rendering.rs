@@ -11,12 +11,14 @@ use crate::dice3d::types::DiceType;
 pub fn get_label_offset(die_type: DiceType) -> f32 {
     // Offset from center of die - place label on face surface
     match die_type {
-        DiceType::D4 => 0.22,  // Modern D4 - numbers at vertices
-        DiceType::D6 => 0.33,  // Cube
-        DiceType::D8 => 0.25,  // Octahedron
-        DiceType::D10 => 0.28, // Pentagonal trapezohedron
-        DiceType::D12 => 0.28, // Dodecahedron
-        DiceType::D20 => 0.28, // Icosahedron
+        DiceType::D4 => 0.22,                   // Modern D4 - numbers at vertices
+        DiceType::D6 | DiceType::Fudge => 0.33, // Cube
+        DiceType::D8 => 0.25,                   // Octahedron
+        DiceType::D10 => 0.28,                  // Pentagonal trapezohedron
+        DiceType::D12 => 0.28,                  // Dodecahedron
+        DiceType::D20 => 0.28,                  // Icosahedron
+        DiceType::Custom(2) => 0.045,           // Coin - just above its faces
+        DiceType::Custom(_) => 0.28,
     }
 }
 
@@ -25,11 +27,13 @@ pub fn get_label_scale(die_type: DiceType) -> f32 {
     // Scale for number labels - clear and readable
     match die_type {
         DiceType::D4 => 0.08, // Small numbers for 3-per-face layout
-        DiceType::D6 => 0.24,
+        DiceType::D6 | DiceType::Fudge => 0.24,
         DiceType::D8 => 0.18,
         DiceType::D10 => 0.15,
         DiceType::D12 => 0.09,
         DiceType::D20 => 0.11,
+        DiceType::Custom(2) => 0.3,
+        DiceType::Custom(_) => 0.11,
     }
 }
 
@@ -67,11 +71,27 @@ pub fn create_number_mesh(value: u32, meshes: &mut ResMut<Assets<Mesh>>) -> Hand
 
 /// Create a 3D mesh for a number value
 pub fn create_digit_mesh(value: u32) -> Mesh {
-    use bevy::asset::RenderAssetUsages;
-    use bevy::mesh::{Indices, PrimitiveTopology};
-
     // Create 3D box geometry for numbers
     let (positions, indices) = generate_number_geometry(value);
+    create_label_mesh(positions, indices)
+}
+
+/// Create a 3D mesh spelling out `digits` as given, leading zeros included
+/// (the "00" of a percentile tens die).
+pub fn create_digits_mesh(digits: &[u32]) -> Mesh {
+    let (positions, indices) = generate_digits_geometry(digits);
+    create_label_mesh(positions, indices)
+}
+
+/// Create a 3D mesh for a coin face letter: `H` (heads) or `T` (tails).
+pub fn create_letter_mesh(letter: char) -> Mesh {
+    let (positions, indices) = get_letter_geometry(letter);
+    create_label_mesh(positions, indices)
+}
+
+fn create_label_mesh(positions: Vec<[f32; 3]>, indices: Vec<u32>) -> Mesh {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, PrimitiveTopology};
 
     // Generate proper normals for 3D boxes
     // Each box has 6 faces with 4 vertices each = 24 vertices per segment
@@ -126,9 +146,6 @@ pub fn create_digit_mesh(value: u32) -> Mesh {
 
 /// Generate the vertex positions and indices for a multi-digit number
 pub fn generate_number_geometry(value: u32) -> (Vec<[f32; 3]>, Vec<u32>) {
-    let mut positions = Vec::new();
-    let mut indices = Vec::new();
-
     let digits: Vec<u32> = if value == 0 {
         vec![0]
     } else {
@@ -141,6 +158,13 @@ pub fn generate_number_geometry(value: u32) -> (Vec<[f32; 3]>, Vec<u32>) {
         d.reverse();
         d
     };
+    generate_digits_geometry(&digits)
+}
+
+/// Generate the vertex positions and indices for a row of digits
+pub fn generate_digits_geometry(digits: &[u32]) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
 
     let num_digits = digits.len();
     let digit_width = 0.6;
@@ -417,3 +441,47 @@ pub fn get_digit_geometry(digit: u32, offset_x: f32) -> (Vec<[f32; 3]>, Vec<u32>
 
     (positions, indices)
 }
+
+/// Generate the geometry of a coin face letter (H, T) or a Fudge die symbol
+/// (+, -), drawn with the same strokes as the digits
+pub fn get_letter_geometry(letter: char) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let stroke_width = 0.12;
+    let h = 0.5; // Half height
+    let w = 0.35; // Half width
+    let d = 0.02; // Depth
+
+    let strokes: &[(f32, f32, f32, f32)] = match letter {
+        'H' => &[
+            (-w * 0.6, h, -w * 0.6, -h),
+            (w * 0.6, h, w * 0.6, -h),
+            (-w * 0.6, 0.0, w * 0.6, 0.0),
+        ],
+        'T' => &[(-w * 0.7, h, w * 0.7, h), (0.0, h, 0.0, -h)],
+        '+' => &[(-w, 0.0, w, 0.0), (0.0, w, 0.0, -w)],
+        '-' => &[(-w, 0.0, w, 0.0)],
+        _ => &[],
+    };
+
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for &(x1, y1, x2, y2) in strokes {
+        let dx = x2 - x1;
+        let dy = y2 - y1;
+        let len = (dx * dx + dy * dy).sqrt();
+        let px = -dy / len * stroke_width / 2.0;
+        let py = dx / len * stroke_width / 2.0;
+
+        // Front and back quads along the stroke
+        for (z, sign) in [(d / 2.0, 1.0), (-d / 2.0, -1.0)] {
+            let base_idx = positions.len() as u32;
+            positions.push([x1 - px * sign, y1 - py * sign, z]);
+            positions.push([x1 + px * sign, y1 + py * sign, z]);
+            positions.push([x2 + px * sign, y2 + py * sign, z]);
+            positions.push([x2 - px * sign, y2 - py * sign, z]);
+            indices.extend_from_slice(&[base_idx, base_idx + 1, base_idx + 2]);
+            indices.extend_from_slice(&[base_idx, base_idx + 2, base_idx + 3]);
+        }
+    }
+
+    (positions, indices)
+}
@@ -0,0 +1,63 @@
+//! Roll lifecycle systems
+//!
+//! Publish `DiceSpawned` and `RollResolved`, and keep the command history in
+//! step with `RollRequested`. `RollRequested` is written where rolls are
+//! asked for and `DiceSettled` by `check_dice_settled`.
+
+use bevy::prelude::*;
+
+use crate::dice3d::types::{
+    CharacterDatabase, CommandHistory, DiceConfig, DiceResults, DiceSettled, DiceSpawned, Die,
+    RollRequested, RollResolved, SettingsState,
+};
+
+/// Publish the dice added to the box this frame, wherever they were spawned.
+pub fn publish_spawned_dice(
+    added: Query<(Entity, &Die), Added<Die>>,
+    mut spawned: MessageWriter<DiceSpawned>,
+) {
+    let dice: Vec<_> = added
+        .iter()
+        .map(|(entity, die)| (entity, die.die_type))
+        .collect();
+    if !dice.is_empty() {
+        spawned.write(DiceSpawned { dice });
+    }
+}
+
+/// Total every settled roll once, counting successes for a dice pool.
+pub fn resolve_settled_rolls(
+    mut settled: MessageReader<DiceSettled>,
+    dice_config: Res<DiceConfig>,
+    dice_results: Res<DiceResults>,
+    settings_state: Res<SettingsState>,
+    mut resolved: MessageWriter<RollResolved>,
+) {
+    let dice_pool = settings_state.settings.game_system.dice_pool();
+    for _ in settled.read() {
+        resolved.write(RollResolved::new(&dice_config, &dice_results).with_dice_pool(dice_pool));
+    }
+}
+
+/// Remember requested commands in the command history.
+pub fn record_requested_commands(
+    mut requests: MessageReader<RollRequested>,
+    mut command_history: ResMut<CommandHistory>,
+    db: Option<Res<CharacterDatabase>>,
+) {
+    let mut added = false;
+    for request in requests.read() {
+        if let Some(command) = &request.command {
+            // Only unique commands are kept
+            command_history.add_command(command.clone());
+            added = true;
+        }
+    }
+
+    if !added {
+        return;
+    }
+    if let Some(db) = db {
+        let _ = db.save_command_history(&command_history.commands);
+    }
+}
@@ -0,0 +1,82 @@
+//! Rules edition systems
+//!
+//! `ruleset` in the command input shows which rules the campaign plays and
+//! `ruleset 2014` or `ruleset 2024` switches them, saving the choice in the
+//! profile's settings.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{CharacterDatabase, Ruleset, TypedCommand, RULESET_SETTING_KEY};
+
+/// A `ruleset` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesetCommand {
+    Show,
+    Switch(Ruleset),
+    Unknown(String),
+}
+
+impl TypedCommand for RulesetCommand {
+    const NAME: &'static str = "ruleset";
+
+    /// Parse `ruleset` and `ruleset <2014|2024>`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("ruleset") && !head.eq_ignore_ascii_case("rules") {
+            return None;
+        }
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Some(RulesetCommand::Show);
+        }
+        Some(match Ruleset::from_name(rest) {
+            Some(ruleset) => RulesetCommand::Switch(ruleset),
+            None => RulesetCommand::Unknown(rest.to_string()),
+        })
+    }
+}
+
+/// Apply a ruleset command, notifying the user.
+pub fn run_ruleset_command(
+    In(command): In<RulesetCommand>,
+    mut ruleset: ResMut<Ruleset>,
+    db: Res<CharacterDatabase>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        RulesetCommand::Show => format!("This campaign plays the {}", ruleset.label()),
+        RulesetCommand::Switch(new) => match db.set_setting(RULESET_SETTING_KEY, new) {
+            Ok(()) => {
+                *ruleset = new;
+                format!("Switched this campaign to the {}", new.label())
+            }
+            Err(e) => format!("Failed to save the ruleset: {}", e),
+        },
+        RulesetCommand::Unknown(name) => {
+            format!("Unknown ruleset '{}': use 2014 or 2024", name)
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Load the campaign's ruleset whenever the database is opened or the
+/// profile switches.
+pub fn load_ruleset(db: Option<Res<CharacterDatabase>>, mut ruleset: ResMut<Ruleset>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    *ruleset = db
+        .get_setting::<Ruleset>(RULESET_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
@@ -0,0 +1,428 @@
+//! Safety tools systems
+//!
+//! `safety on` shows the safety tools panel in the dice view: the X-card
+//! button and the campaign's lines and veils, which `safety line <text>`,
+//! `safety veil <text>` and `safety remove <line|veil> <n>` edit. Tapping the
+//! X-card (or typing `xcard`) pauses the scene; in a shared session the
+//! pause goes to everyone at the table without saying who asked for it.
+
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    AppTab, CharacterDatabase, DiceRollerRoot, FocusTrap, SafetyList, SafetySignal, SafetyTools,
+    TypedCommand, UiState, SAFETY_TOOLS_SETTING_KEY,
+};
+
+/// The campaign's safety tools, and whether the scene is paused
+#[derive(Resource, Default)]
+pub struct SafetyState {
+    pub tools: SafetyTools,
+    /// Someone tapped the X-card and the pause hasn't been lifted yet
+    pub paused: bool,
+}
+
+/// The X-card was tapped on this device; goes to the table when shared
+#[derive(Message, Clone, Copy, Debug)]
+pub struct SafetySignalSent(pub SafetySignal);
+
+#[derive(Component)]
+pub struct SafetyToolsPanel;
+
+#[derive(Component)]
+pub struct XCardButton;
+
+#[derive(Component)]
+pub struct SafetyPauseOverlay;
+
+#[derive(Component)]
+pub struct SafetyResumeButton;
+
+/// A `safety` or `xcard` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafetyCommand {
+    Show,
+    Enable(bool),
+    Add(SafetyList, String),
+    Remove(SafetyList, usize),
+    XCard,
+}
+
+impl TypedCommand for SafetyCommand {
+    const NAME: &'static str = "safety";
+
+    /// Parse `safety`, `safety on|off`, `safety line <text>`,
+    /// `safety veil <text>`, `safety remove <line|veil> <n>` and `xcard`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if head.eq_ignore_ascii_case("xcard") || head.eq_ignore_ascii_case("x-card") {
+            return rest.trim().is_empty().then_some(SafetyCommand::XCard);
+        }
+        if !head.eq_ignore_ascii_case("safety") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Some(SafetyCommand::Show);
+        }
+        let (verb, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let arg = arg.trim();
+        match verb.to_lowercase().as_str() {
+            "on" => Some(SafetyCommand::Enable(true)),
+            "off" => Some(SafetyCommand::Enable(false)),
+            "remove" | "rm" => {
+                let (list, number) = arg.split_once(char::is_whitespace)?;
+                Some(SafetyCommand::Remove(
+                    SafetyList::from_name(list)?,
+                    number.trim().parse().ok()?,
+                ))
+            }
+            list => Some(SafetyCommand::Add(
+                SafetyList::from_name(list)?,
+                arg.to_string(),
+            )),
+        }
+    }
+}
+
+fn save_tools(tools: &SafetyTools, db: &CharacterDatabase) -> Result<(), String> {
+    db.set_setting(SAFETY_TOOLS_SETTING_KEY, tools.clone())
+        .map_err(|e| format!("Failed to save the safety tools: {}", e))
+}
+
+/// Pause the scene for the X-card tapped on this device.
+fn tap_x_card(state: &mut SafetyState, sent: &mut MessageWriter<SafetySignalSent>) {
+    state.paused = true;
+    sent.write(SafetySignalSent(SafetySignal::XCard));
+}
+
+/// Apply a safety command, notifying the user.
+pub fn run_safety_command(
+    In(command): In<SafetyCommand>,
+    mut state: ResMut<SafetyState>,
+    db: Res<CharacterDatabase>,
+    mut sent: MessageWriter<SafetySignalSent>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let mut tools = state.tools.clone();
+    let result = match command {
+        SafetyCommand::Show => {
+            let summary = [SafetyList::Lines, SafetyList::Veils]
+                .into_iter()
+                .map(|list| match tools.list(list) {
+                    [] => format!("{}: none", list.label()),
+                    entries => format!("{}: {}", list.label(), entries.join(", ")),
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            snackbar.write(ShowSnackbar::message(summary).duration(6.0));
+            return;
+        }
+        SafetyCommand::XCard => {
+            tap_x_card(&mut state, &mut sent);
+            return;
+        }
+        SafetyCommand::Enable(enabled) => {
+            tools.enabled = enabled;
+            Ok(if enabled {
+                "Safety tools panel shown".to_string()
+            } else {
+                "Safety tools panel hidden".to_string()
+            })
+        }
+        SafetyCommand::Add(list, entry) => tools
+            .add(list, &entry)
+            .map(|()| format!("Added to {}: {}", list.label(), entry.trim())),
+        SafetyCommand::Remove(list, number) => tools
+            .remove(list, number)
+            .map(|entry| format!("Removed from {}: {}", list.label(), entry)),
+    };
+
+    let message = match result.and_then(|message| save_tools(&tools, &db).map(|()| message)) {
+        Ok(message) => {
+            state.tools = tools;
+            message
+        }
+        Err(e) => e,
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(4.0));
+}
+
+/// Pause the scene for an X-card tapped somewhere else at the table.
+pub fn receive_safety_signal(
+    signal: SafetySignal,
+    state: &mut SafetyState,
+    snackbar: &mut MessageWriter<ShowSnackbar>,
+) {
+    match signal {
+        SafetySignal::XCard => {
+            // The table also echoes our own tap back
+            if state.paused {
+                return;
+            }
+            state.paused = true;
+            info!("Safety: the X-card was tapped");
+            snackbar.write(ShowSnackbar::message("Someone tapped the X-card").duration(4.0));
+        }
+    }
+}
+
+/// Load the campaign's safety tools whenever the database is opened or the
+/// profile switches.
+pub fn load_safety_tools(db: Option<Res<CharacterDatabase>>, mut state: ResMut<SafetyState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.tools = db
+        .get_setting::<SafetyTools>(SAFETY_TOOLS_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
+
+/// Tap the X-card, or lift the pause.
+pub fn handle_safety_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    x_cards: Query<(), With<XCardButton>>,
+    resume_buttons: Query<(), With<SafetyResumeButton>>,
+    mut state: ResMut<SafetyState>,
+    mut sent: MessageWriter<SafetySignalSent>,
+) {
+    for event in click_events.read() {
+        if x_cards.get(event.entity).is_ok() {
+            tap_x_card(&mut state, &mut sent);
+        } else if resume_buttons.get(event.entity).is_ok() {
+            state.paused = false;
+        }
+    }
+}
+
+fn spawn_list(
+    panel: &mut ChildSpawnerCommands,
+    list: SafetyList,
+    tools: &SafetyTools,
+    theme: &MaterialTheme,
+) {
+    panel.spawn((
+        Text::new(list.label()),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(theme.primary),
+    ));
+    let entries = tools.list(list);
+    if entries.is_empty() {
+        panel.spawn((
+            Text::new(format!(
+                "None yet: safety {} <text>",
+                list.label().to_lowercase()
+            )),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        panel.spawn((
+            Text::new(format!("{}. {}", i + 1, entry)),
+            TextFont {
+                font_size: 13.0,
+                ..default()
+            },
+            TextColor(theme.on_surface),
+        ));
+    }
+}
+
+/// Show the safety tools panel in the dice view while the campaign has it
+/// turned on.
+pub fn manage_safety_tools_panel(
+    mut commands: Commands,
+    state: Res<SafetyState>,
+    ui_state: Res<UiState>,
+    theme: Option<Res<MaterialTheme>>,
+    panels: Query<Entity, With<SafetyToolsPanel>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for entity in panels.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !state.tools.enabled {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(110.0),
+                width: Val::Px(240.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(theme.surface_container),
+            BorderRadius::all(Val::Px(12.0)),
+            ZIndex(12),
+            if ui_state.active_tab == AppTab::DiceRoller {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            },
+            DiceRollerRoot,
+            SafetyToolsPanel,
+            Interaction::None,
+            FocusPolicy::Block,
+        ))
+        .with_children(|panel| {
+            panel
+                .spawn((
+                    MaterialButtonBuilder::new("X-card").filled().build(&theme),
+                    XCardButton,
+                ))
+                .insert(Node {
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .insert(BackgroundColor(theme.error))
+                .with_children(|btn| {
+                    btn.spawn((
+                        ButtonLabel,
+                        Text::new("X-card"),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(theme.on_error),
+                    ));
+                });
+
+            spawn_list(panel, SafetyList::Lines, &state.tools, &theme);
+            spawn_list(panel, SafetyList::Veils, &state.tools, &theme);
+        });
+}
+
+/// Cover the app while the scene is paused, until someone lifts the pause.
+pub fn manage_safety_pause_overlay(
+    mut commands: Commands,
+    state: Res<SafetyState>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<SafetyPauseOverlay>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    if state.paused != overlays.is_empty() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !state.paused {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("Scene paused")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            SafetyPauseOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        dialog.spawn((
+            Text::new("Scene paused"),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(theme.on_surface),
+        ));
+        dialog.spawn((
+            Text::new(
+                "Someone tapped the X-card. No one needs to explain why: \
+                 skip or change what is happening, then carry on.",
+            ),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                buttons
+                    .spawn((
+                        MaterialButtonBuilder::new("Resume").filled().build(&theme),
+                        SafetyResumeButton,
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((
+                            Text::new("Resume"),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(theme.on_primary),
+                            ButtonLabel,
+                        ));
+                    });
+            });
+    });
+}
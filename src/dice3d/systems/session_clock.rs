@@ -0,0 +1,112 @@
+//! Session clock systems
+//!
+//! Advances the in-game `SessionClock` (real time outside combat, 6 seconds per
+//! initiative round during combat) and announces timed effects such as ritual
+//! casting when they complete.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{InitiativeTracker, SessionClock, TypedCommand};
+
+/// A ritual-related command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RitualCommand {
+    Start(String),
+    /// Interrupt a ritual in progress (the most recent one when no name is given)
+    Interrupt(Option<String>),
+}
+
+impl TypedCommand for RitualCommand {
+    const NAME: &'static str = "ritual";
+
+    /// Parse `ritual <spell>` / `ritual cancel [spell]` commands.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("ritual") {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (verb, target) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if verb.eq_ignore_ascii_case("cancel") || verb.eq_ignore_ascii_case("interrupt") {
+            let target = target.trim();
+            return Some(RitualCommand::Interrupt(
+                (!target.is_empty()).then(|| target.to_string()),
+            ));
+        }
+
+        if rest.is_empty() {
+            return None;
+        }
+        Some(RitualCommand::Start(rest.to_string()))
+    }
+}
+
+/// Apply a ritual command to the session clock, notifying the user.
+pub fn run_ritual_command(
+    In(command): In<RitualCommand>,
+    mut clock: ResMut<SessionClock>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    match command {
+        RitualCommand::Start(spell) => {
+            clock.start_ritual(&spell);
+            info!("Ritual casting started: {}", spell);
+            snackbar.write(
+                ShowSnackbar::message(format!("Casting {} as a ritual (10 minutes)", spell))
+                    .duration(3.0),
+            );
+        }
+        RitualCommand::Interrupt(spell) => match clock.interrupt(spell.as_deref()) {
+            Some(timer) => {
+                info!(
+                    "Ritual casting interrupted: {} ({:.0}s remaining)",
+                    timer.label, timer.remaining_seconds
+                );
+                snackbar.write(
+                    ShowSnackbar::message(format!(
+                        "Ritual interrupted: {} was not cast",
+                        timer.label
+                    ))
+                    .duration(3.0),
+                );
+            }
+            None => {
+                snackbar.write(ShowSnackbar::message("No ritual in progress").duration(2.0));
+            }
+        },
+    }
+}
+
+/// Advance the session clock and announce completed timers.
+pub fn tick_session_clock(
+    time: Res<Time>,
+    tracker: Res<InitiativeTracker>,
+    mut clock: ResMut<SessionClock>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+    mut last_round: Local<u32>,
+) {
+    let completed = if tracker.is_active() {
+        // In combat, time is measured in rounds rather than wall-clock time.
+        let rounds = tracker.round.saturating_sub((*last_round).max(1));
+        *last_round = tracker.round;
+        if rounds == 0 {
+            return;
+        }
+        clock.advance_rounds(rounds)
+    } else {
+        *last_round = 0;
+        clock.advance(time.delta_secs())
+    };
+
+    for label in completed {
+        info!("Ritual casting complete: {}", label);
+        snackbar.write(
+            ShowSnackbar::message(format!("Ritual complete: {} takes effect", label)).duration(4.0),
+        );
+    }
+}
@@ -13,6 +13,7 @@ use bevy::window::PrimaryWindow;
 use bevy_material_ui::prelude::*;
 use bevy_material_ui::theme::ThemeMode;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 
 use crate::dice3d::types::*;
 use bevy_material_ui::prelude::SwitchChangeEvent;
@@ -343,28 +344,7 @@ pub fn load_settings_state_from_db(
                 loaded.background_color.to_hex()
             );
 
-            settings_state.settings = loaded.clone();
-            settings_state.is_modified = false;
-
-            settings_state.character_sheet_editing_die = loaded.character_sheet_default_die;
-            settings_state.quick_roll_editing_die = loaded.quick_roll_default_die;
-            settings_state.default_roll_uses_shake_editing = loaded.default_roll_uses_shake;
-
-            settings_state.editing_color = loaded.background_color.clone();
-            settings_state.editing_highlight_color = loaded.dice_box_highlight_color.clone();
-            settings_state.editing_shake_config = loaded.shake_config.to_runtime();
-            settings_state.last_saved_shake_config = loaded.shake_config.clone();
-
-            settings_state.editing_dice_scales = loaded.dice_scales.clone();
-            settings_state.editing_dice_roll_fx_mappings = loaded.dice_roll_fx_mappings.clone();
-            settings_state.editing_dice_fx_surface_opacity = loaded.dice_fx_surface_opacity;
-            settings_state.editing_dice_fx_plume_height_multiplier =
-                loaded.dice_fx_plume_height_multiplier;
-            settings_state.editing_dice_fx_plume_radius_multiplier =
-                loaded.dice_fx_plume_radius_multiplier;
-
-            settings_state.color_input_text.clear();
-            settings_state.highlight_input_text.clear();
+            settings_state.replace_settings(loaded);
 
             // Apply theme override (if any) before UI setup.
             apply_theme_override(&settings_state.settings, &mut theme);
@@ -511,6 +491,7 @@ fn spawn_settings_modal(
     let scrim_entity = commands
         .spawn((
             create_dialog_scrim_for(theme, dialog_entity, true),
+            FocusTrap,
             SettingsModalOverlay,
         ))
         .id();
@@ -532,16 +513,47 @@ fn spawn_settings_modal(
                 let editing_color = &settings_state.editing_color;
                 let editing_highlight_color = &settings_state.editing_highlight_color;
 
-                content.spawn((
-                    Text::new("Settings"),
-                    TextFont {
-                        font_size: 24.0,
+                // Title, and the search across every tab
+                content
+                    .spawn(Node {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(16.0),
                         ..default()
-                    },
-                    TextColor(theme.on_surface),
-                ));
+                    })
+                    .with_children(|header| {
+                        header.spawn((
+                            Text::new("Settings"),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(theme.on_surface),
+                        ));
+                        header
+                            .spawn(Node {
+                                width: Val::Px(280.0),
+                                ..default()
+                            })
+                            .with_children(|slot| {
+                                let builder = TextFieldBuilder::new()
+                                    .outlined()
+                                    .label("Search settings")
+                                    .placeholder("volume, theme, dice...")
+                                    .auto_focus(false)
+                                    .width(Val::Percent(100.0));
+                                spawn_text_field_control_with(
+                                    slot,
+                                    theme,
+                                    builder,
+                                    SettingsSearchField,
+                                );
+                            });
+                    });
 
-                // Tabs: Dice / Colors / Shake Curve / Layout
+                // Tabs: Dice / Colors / Shake Curve / Layout / Profiles
                 let mut tabs_cmd = content.spawn((
                     MaterialTabs::new()
                         .with_variant(TabVariant::Secondary)
@@ -554,13 +566,15 @@ fn spawn_settings_modal(
                         ..default()
                     },
                     BackgroundColor(theme.surface),
+                    SettingsTabBar,
                 ));
                 let tabs_entity = tabs_cmd.id();
                 tabs_cmd.with_children(|tabs| {
                     fn spawn_tab_label(
                         t: &mut ChildSpawnerCommands,
                         theme: &MaterialTheme,
-                        label: &str,
+                        index: usize,
+                        label: &'static str,
                     ) {
                         t.spawn((
                             Text::new(label),
@@ -569,6 +583,7 @@ fn spawn_settings_modal(
                                 ..default()
                             },
                             TextColor(theme.on_surface_variant),
+                            SettingsTabLabel { index, name: label },
                         ));
                     }
 
@@ -583,7 +598,7 @@ fn spawn_settings_modal(
                         },
                         BackgroundColor(Color::NONE),
                     ))
-                    .with_children(|t| spawn_tab_label(t, theme, "Dice"));
+                    .with_children(|t| spawn_tab_label(t, theme, 0, "Dice"));
 
                     tabs.spawn((
                         MaterialTab::new(1, "Colors"),
@@ -596,7 +611,7 @@ fn spawn_settings_modal(
                         },
                         BackgroundColor(Color::NONE),
                     ))
-                    .with_children(|t| spawn_tab_label(t, theme, "Colors"));
+                    .with_children(|t| spawn_tab_label(t, theme, 1, "Colors"));
 
                     tabs.spawn((
                         MaterialTab::new(2, "Shake Curve"),
@@ -609,7 +624,7 @@ fn spawn_settings_modal(
                         },
                         BackgroundColor(Color::NONE),
                     ))
-                    .with_children(|t| spawn_tab_label(t, theme, "Shake Curve"));
+                    .with_children(|t| spawn_tab_label(t, theme, 2, "Shake Curve"));
 
                     tabs.spawn((
                         MaterialTab::new(3, "Layout"),
@@ -622,7 +637,59 @@ fn spawn_settings_modal(
                         },
                         BackgroundColor(Color::NONE),
                     ))
-                    .with_children(|t| spawn_tab_label(t, theme, "Layout"));
+                    .with_children(|t| spawn_tab_label(t, theme, 3, "Layout"));
+
+                    tabs.spawn((
+                        MaterialTab::new(4, "Profiles"),
+                        Button,
+                        Node {
+                            flex_grow: 1.0,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::NONE),
+                    ))
+                    .with_children(|t| spawn_tab_label(t, theme, 4, "Profiles"));
+
+                    tabs.spawn((
+                        MaterialTab::new(5, "Theme"),
+                        Button,
+                        Node {
+                            flex_grow: 1.0,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::NONE),
+                    ))
+                    .with_children(|t| spawn_tab_label(t, theme, 5, "Theme"));
+
+                    tabs.spawn((
+                        MaterialTab::new(6, "Audio"),
+                        Button,
+                        Node {
+                            flex_grow: 1.0,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::NONE),
+                    ))
+                    .with_children(|t| spawn_tab_label(t, theme, 6, "Audio"));
+
+                    tabs.spawn((
+                        MaterialTab::new(7, "Keys"),
+                        Button,
+                        Node {
+                            flex_grow: 1.0,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::NONE),
+                    ))
+                    .with_children(|t| spawn_tab_label(t, theme, 7, "Keys"));
                 });
 
                 // Scrollable content area. Each tab is a scroll container.
@@ -671,6 +738,8 @@ fn spawn_settings_modal(
                                     editing_highlight_color,
                                     &settings_state.theme_seed_input_text,
                                     &settings_state.settings.recent_theme_seeds,
+                                    settings_state.chaos_dice_editing,
+                                    &settings_state.chaos_dice_palette_input,
                                 );
                             },
                         );
@@ -696,7 +765,57 @@ fn spawn_settings_modal(
                             3,
                             false,
                             |tab| {
-                                settings_tabs::layout::build_layout_tab(tab, theme);
+                                settings_tabs::layout::build_layout_tab(tab, theme, settings_state);
+                            },
+                        );
+
+                        settings_tabs::spawn_scrollable_tab_content(
+                            tab_area,
+                            tabs_entity,
+                            4,
+                            false,
+                            |tab| {
+                                settings_tabs::profiles::build_profiles_tab(tab, theme);
+                            },
+                        );
+
+                        settings_tabs::spawn_scrollable_tab_content(
+                            tab_area,
+                            tabs_entity,
+                            5,
+                            false,
+                            |tab| {
+                                settings_tabs::theme::build_theme_tab(
+                                    tab,
+                                    theme,
+                                    &settings_state.editing_theme_tokens,
+                                    &settings_state.custom_theme_name_input,
+                                    &settings_state.settings.custom_themes,
+                                );
+                            },
+                        );
+
+                        settings_tabs::spawn_scrollable_tab_content(
+                            tab_area,
+                            tabs_entity,
+                            6,
+                            false,
+                            |tab| {
+                                settings_tabs::audio::build_audio_tab(tab, theme, settings_state);
+                            },
+                        );
+
+                        settings_tabs::spawn_scrollable_tab_content(
+                            tab_area,
+                            tabs_entity,
+                            7,
+                            false,
+                            |tab| {
+                                settings_tabs::keys::build_keys_tab(
+                                    tab,
+                                    theme,
+                                    &settings_state.editing_key_bindings,
+                                );
                             },
                         );
                     });
@@ -893,61 +1012,96 @@ pub fn handle_settings_button_click(
             continue;
         }
 
-        settings_state.show_modal = true;
-        settings_state.modal_kind = crate::dice3d::types::ActiveModalKind::DiceRollerSettings;
-        settings_state.editing_color = settings_state.settings.background_color.clone();
-        settings_state.color_input_text = settings_state.editing_color.to_hex();
-
-        settings_state.editing_highlight_color =
-            settings_state.settings.dice_box_highlight_color.clone();
-        settings_state.highlight_input_text = settings_state.editing_highlight_color.to_hex();
-
-        settings_state.quick_roll_editing_die = settings_state.settings.quick_roll_default_die;
-        settings_state.default_roll_uses_shake_editing =
-            settings_state.settings.default_roll_uses_shake;
-
-        settings_state.editing_dice_scales = settings_state.settings.dice_scales.clone();
-
-        settings_state.editing_dice_roll_fx_mappings =
-            settings_state.settings.dice_roll_fx_mappings.clone();
-
-        settings_state.editing_dice_fx_surface_opacity =
-            settings_state.settings.dice_fx_surface_opacity;
-        settings_state.editing_dice_fx_plume_height_multiplier =
-            settings_state.settings.dice_fx_plume_height_multiplier;
-        settings_state.editing_dice_fx_plume_radius_multiplier =
-            settings_state.settings.dice_fx_plume_radius_multiplier;
-
-        // Copy current shake settings into an editable staging area.
-        settings_state.editing_shake_config = shake_config.clone();
+        open_settings_modal(&mut settings_state, &shake_config);
+    }
+}
 
-        // Keep autosave snapshot aligned so opening the modal doesn't immediately rewrite.
-        settings_state.last_saved_shake_config = settings_state.settings.shake_config.clone();
+/// Open the dice roller settings modal with its editing state copied from the
+/// saved settings.
+///
+/// Used by the settings button and the platform Preferences menu item.
+pub fn open_settings_modal(
+    settings_state: &mut SettingsState,
+    shake_config: &ContainerShakeConfig,
+) {
+    settings_state.show_modal = true;
+    settings_state.modal_kind = crate::dice3d::types::ActiveModalKind::DiceRollerSettings;
+    settings_state.editing_color = settings_state.settings.background_color.clone();
+    settings_state.color_input_text = settings_state.editing_color.to_hex();
+
+    settings_state.editing_highlight_color =
+        settings_state.settings.dice_box_highlight_color.clone();
+    settings_state.highlight_input_text = settings_state.editing_highlight_color.to_hex();
+
+    settings_state.quick_roll_editing_die = settings_state.settings.quick_roll_default_die;
+    settings_state.default_roll_uses_shake_editing =
+        settings_state.settings.default_roll_uses_shake;
+    settings_state.fixed_timestep_physics_editing = settings_state.settings.fixed_timestep_physics;
+    settings_state.steered_dice_editing = settings_state.settings.steered_dice;
+    settings_state.gather_dice_editing = settings_state.settings.gather_dice;
+    settings_state.physics_debug_capture_editing = settings_state.settings.physics_debug_capture;
+    settings_state.pause_when_unfocused_editing = settings_state.settings.pause_when_unfocused;
+    settings_state.gamepad_rumble_editing = settings_state.settings.gamepad_rumble;
+
+    settings_state.editing_dice_scales = settings_state.settings.dice_scales.clone();
+
+    settings_state.editing_dice_roll_fx_mappings =
+        settings_state.settings.dice_roll_fx_mappings.clone();
+    settings_state.editing_dice_skins = settings_state.settings.dice_skins.clone();
+    settings_state.chaos_dice_editing = settings_state.settings.chaos_dice;
+    settings_state.chaos_dice_palette_input =
+        format_color_palette(&settings_state.settings.chaos_dice_palette);
+    settings_state.editing_theme_tokens = settings_state.settings.theme_tokens.clone();
+
+    settings_state.editing_dice_fx_surface_opacity =
+        settings_state.settings.dice_fx_surface_opacity;
+    settings_state.editing_dice_fx_plume_height_multiplier =
+        settings_state.settings.dice_fx_plume_height_multiplier;
+    settings_state.editing_dice_fx_plume_radius_multiplier =
+        settings_state.settings.dice_fx_plume_radius_multiplier;
+    settings_state.editing_animation_speed = settings_state.settings.animation_speed;
+    settings_state.editing_ui_scale_override = settings_state.settings.ui_scale_override;
+    settings_state.editing_explosion_cap = settings_state.settings.explosion_cap;
+    settings_state.game_system_editing = settings_state.settings.game_system;
+    settings_state.result_order_editing = settings_state.settings.result_order;
+    settings_state.editing_sound_volume = settings_state.settings.sound_volume;
+    settings_state.sound_muted_editing = settings_state.settings.sound_muted;
+    settings_state.music_enabled_editing = settings_state.settings.music_enabled;
+    settings_state.editing_music_folder = settings_state.settings.music_folder.clone();
+    settings_state.editing_music_volume = settings_state.settings.music_volume;
+    settings_state.music_shuffle_editing = settings_state.settings.music_shuffle;
+    settings_state.editing_key_bindings = settings_state.settings.key_bindings.clone();
+    settings_state.capturing_key_binding = None;
+
+    // Copy current shake settings into an editable staging area.
+    settings_state.editing_shake_config = shake_config.clone();
+
+    // Keep autosave snapshot aligned so opening the modal doesn't immediately rewrite.
+    settings_state.last_saved_shake_config = settings_state.settings.shake_config.clone();
 
-        settings_state.shake_duration_input_text = format!(
-            "{:.3}",
-            settings_state
-                .editing_shake_config
-                .duration_seconds
-                .max(0.0)
-        );
+    settings_state.shake_duration_input_text = format!(
+        "{:.3}",
+        settings_state
+            .editing_shake_config
+            .duration_seconds
+            .max(0.0)
+    );
 
-        // Theme seed staging.
-        settings_state.theme_seed_input_text = settings_state
-            .settings
-            .theme_seed_hex
-            .clone()
-            .unwrap_or_default();
-        settings_state.editing_theme_seed_override = settings_state
-            .settings
-            .theme_seed_hex
-            .as_deref()
-            .and_then(ColorSetting::parse)
-            .map(|mut c| {
-                c.a = 1.0;
-                c
-            });
-    }
+    // Theme seed staging.
+    settings_state.theme_seed_input_text = settings_state
+        .settings
+        .theme_seed_hex
+        .clone()
+        .unwrap_or_default();
+    settings_state.editing_theme_seed_override = settings_state
+        .settings
+        .theme_seed_hex
+        .as_deref()
+        .and_then(ColorSetting::parse)
+        .map(|mut c| {
+            c.a = 1.0;
+            c
+        });
 }
 
 /// Persist shake curve changes immediately (every edit) and apply them to runtime.
@@ -1023,6 +1177,175 @@ pub fn manage_settings_modal(
     }
 }
 
+/// Keep each settings tab's scroll position while switching tabs and
+/// reopening the modal.
+///
+/// A hidden tab is laid out with no size, which clamps its scroll position
+/// to the top, so the position is saved while the tab is shown and put back
+/// once it is laid out again.
+pub fn remember_settings_tab_scroll(
+    mut positions: ResMut<SettingsTabScrollPositions>,
+    mut scrolls: Query<(
+        Entity,
+        &SettingsTabScroll,
+        &ComputedNode,
+        &mut ScrollPosition,
+    )>,
+    mut shown: Local<HashSet<Entity>>,
+) {
+    shown.retain(|entity| scrolls.contains(*entity));
+
+    for (entity, tab, computed, mut scroll) in scrolls.iter_mut() {
+        if computed.size().y <= 0.0 {
+            shown.remove(&entity);
+            continue;
+        }
+        if shown.insert(entity) {
+            if let Some(saved) = positions.0.get(&tab.0) {
+                scroll.0 = *saved;
+            }
+        } else if positions.0.get(&tab.0) != Some(&scroll.0) {
+            positions.0.insert(tab.0, scroll.0);
+        }
+    }
+}
+
+/// Filter the settings modal by its search field.
+///
+/// Each entry of a tab (a child of its content root) is matched against the
+/// text it shows: entries that don't match are hidden and the ones that do
+/// are outlined, each tab label counts its matches, and when the open tab has
+/// none the first tab that has some is opened. Clearing the field shows
+/// everything again.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_settings_search(
+    mut commands: Commands,
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    search_fields: Query<(), With<SettingsSearchField>>,
+    new_search_fields: Query<(), Added<SettingsSearchField>>,
+    roots: Query<(&SettingsTabContentRoot, &Children)>,
+    changed_roots: Query<(), (With<SettingsTabContentRoot>, Changed<Children>)>,
+    children_query: Query<&Children>,
+    texts: Query<&Text, Without<SettingsTabLabel>>,
+    mut entries: Query<(
+        &mut Node,
+        Option<&SettingsSearchHidden>,
+        Has<SettingsSearchMatch>,
+    )>,
+    mut labels: Query<(&SettingsTabLabel, &mut Text, &ChildOf)>,
+    mut tab_buttons: Query<&mut MaterialTab>,
+    mut tab_bars: Query<&mut MaterialTabs, With<SettingsTabBar>>,
+    theme: Res<MaterialTheme>,
+    mut search: Local<String>,
+) {
+    // A reopened modal starts without a search
+    if !new_search_fields.is_empty() {
+        search.clear();
+    }
+
+    let mut changed = false;
+    for event in change_events.read() {
+        if search_fields.contains(event.entity) && *search != event.value {
+            *search = event.value.clone();
+            changed = true;
+        }
+    }
+    if !changed && changed_roots.is_empty() {
+        return;
+    }
+
+    let searching = !search.trim().is_empty();
+    let mut match_counts: Vec<usize> = Vec::new();
+    for (root, children) in roots.iter() {
+        if match_counts.len() <= root.0 {
+            match_counts.resize(root.0 + 1, 0);
+        }
+        for entry in children.iter() {
+            let Ok((mut node, hidden, outlined)) = entries.get_mut(entry) else {
+                continue;
+            };
+            let mut text = String::new();
+            collect_settings_text(entry, &children_query, &texts, &mut text);
+            let matched = searching && settings_search_matches(&text, &search);
+            if matched {
+                match_counts[root.0] += 1;
+            }
+
+            // Hide what doesn't match, remembering how to show it again
+            if searching && !matched {
+                if hidden.is_none() {
+                    commands
+                        .entity(entry)
+                        .insert(SettingsSearchHidden(node.display));
+                    node.display = Display::None;
+                }
+            } else if let Some(hidden) = hidden {
+                node.display = hidden.0;
+                commands.entity(entry).remove::<SettingsSearchHidden>();
+            }
+
+            if matched && !outlined {
+                commands.entity(entry).insert((
+                    SettingsSearchMatch,
+                    Outline::new(Val::Px(2.0), Val::Px(2.0), theme.primary),
+                ));
+            } else if !matched && outlined {
+                commands
+                    .entity(entry)
+                    .remove::<(SettingsSearchMatch, Outline)>();
+            }
+        }
+    }
+
+    let count = |index: usize| match_counts.get(index).copied().unwrap_or(0);
+    for (label, mut text, _) in labels.iter_mut() {
+        text.0 = if searching {
+            format!("{} ({})", label.name, count(label.index))
+        } else {
+            label.name.to_string()
+        };
+    }
+
+    // Open a tab with matches when the current one has none
+    if !searching {
+        return;
+    }
+    let Ok(mut tab_bar) = tab_bars.single_mut() else {
+        return;
+    };
+    if count(tab_bar.selected) > 0 {
+        return;
+    }
+    let Some(index) = (0..match_counts.len()).find(|index| count(*index) > 0) else {
+        return;
+    };
+    tab_bar.selected = index;
+    for (label, _, parent) in labels.iter() {
+        if let Ok(mut tab) = tab_buttons.get_mut(parent.parent()) {
+            tab.selected = label.index == index;
+        }
+    }
+}
+
+/// Append the text shown under `entity` to `out`.
+fn collect_settings_text(
+    entity: Entity,
+    children_query: &Query<&Children>,
+    texts: &Query<&Text, Without<SettingsTabLabel>>,
+    out: &mut String,
+) {
+    if let Ok(text) = texts.get(entity) {
+        out.push_str(&text.0);
+        out.push(' ');
+    }
+    let Ok(children) = children_query.get(entity) else {
+        return;
+    };
+    for child in children.iter() {
+        collect_settings_text(child, children_query, texts, out);
+    }
+}
+
 /// Handle OK button click
 pub fn handle_settings_ok_click(
     mut click_events: MessageReader<ButtonClickEvent>,
@@ -1058,15 +1381,56 @@ pub fn handle_settings_ok_click(
             .editing_dice_fx_plume_radius_multiplier
             .clamp(0.25, 3.0);
 
+        settings_state.settings.animation_speed = settings_state.editing_animation_speed.clamp(
+            AppSettings::MIN_ANIMATION_SPEED,
+            AppSettings::MAX_ANIMATION_SPEED,
+        );
+        settings_state.settings.ui_scale_override = settings_state
+            .editing_ui_scale_override
+            .map(|v| v.clamp(AppSettings::MIN_UI_SCALE, AppSettings::MAX_UI_SCALE));
+        settings_state.settings.explosion_cap = settings_state.editing_explosion_cap.clamp(
+            AppSettings::MIN_EXPLOSION_CAP,
+            AppSettings::MAX_EXPLOSION_CAP,
+        );
+        settings_state.settings.game_system = settings_state.game_system_editing;
+        settings_state.settings.result_order = settings_state.result_order_editing;
+
         // Apply per-die/per-face Dice Roll FX mappings.
         let mut mappings = settings_state.editing_dice_roll_fx_mappings.clone();
         for m in &mut mappings {
             m.normalize_len();
         }
         settings_state.settings.dice_roll_fx_mappings = mappings;
+        settings_state.settings.dice_skins = settings_state.editing_dice_skins.clone();
+
+        // Chaos dice keep their last palette while the typed one doesn't parse.
+        settings_state.settings.chaos_dice = settings_state.chaos_dice_editing;
+        if let Ok(palette) = parse_color_palette(&settings_state.chaos_dice_palette_input) {
+            settings_state.settings.chaos_dice_palette = palette;
+        }
+
+        // Widget tokens; `sync_theme_tokens_from_settings` keeps them once the modal closes.
+        settings_state.settings.theme_tokens = settings_state.editing_theme_tokens.clone();
 
         settings_state.settings.default_roll_uses_shake =
             settings_state.default_roll_uses_shake_editing;
+        settings_state.settings.fixed_timestep_physics =
+            settings_state.fixed_timestep_physics_editing;
+        settings_state.settings.steered_dice = settings_state.steered_dice_editing;
+        settings_state.settings.gather_dice = settings_state.gather_dice_editing;
+        settings_state.settings.physics_debug_capture =
+            settings_state.physics_debug_capture_editing;
+        settings_state.settings.pause_when_unfocused = settings_state.pause_when_unfocused_editing;
+        settings_state.settings.gamepad_rumble = settings_state.gamepad_rumble_editing;
+        settings_state.settings.sound_volume = settings_state.editing_sound_volume.clamp(0.0, 1.0);
+        settings_state.settings.sound_muted = settings_state.sound_muted_editing;
+        settings_state.settings.music_enabled = settings_state.music_enabled_editing;
+        settings_state.settings.music_folder =
+            settings_state.editing_music_folder.trim().to_string();
+        settings_state.settings.music_volume = settings_state.editing_music_volume.clamp(0.0, 1.0);
+        settings_state.settings.music_shuffle = settings_state.music_shuffle_editing;
+        settings_state.settings.key_bindings = settings_state.editing_key_bindings.clone();
+        settings_state.capturing_key_binding = None;
 
         // Update the clear color
         clear_color.0 = settings_state.settings.background_color.to_color();
@@ -1189,6 +1553,170 @@ pub fn handle_dice_fx_param_slider_changes(
     }
 }
 
+/// Handle animation speed slider changes (Dice Roller settings modal).
+pub fn handle_animation_speed_slider_changes(
+    mut events: MessageReader<SliderChangeEvent>,
+    slider_query: Query<(), With<AnimationSpeedSlider>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in events.read() {
+        if slider_query.get(event.entity).is_err() {
+            continue;
+        }
+        settings_state.editing_animation_speed = event.value.clamp(
+            AppSettings::MIN_ANIMATION_SPEED,
+            AppSettings::MAX_ANIMATION_SPEED,
+        );
+    }
+}
+
+/// Handle the dice sound and music controls of the Audio tab.
+pub fn handle_audio_setting_changes(
+    mut slider_events: MessageReader<SliderChangeEvent>,
+    mut switch_events: MessageReader<SwitchChangeEvent>,
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut submit_events: MessageReader<TextFieldSubmitEvent>,
+    sound_sliders: Query<(), With<SoundVolumeSlider>>,
+    music_sliders: Query<(), With<MusicVolumeSlider>>,
+    mute_switches: Query<(), With<SoundMutedSwitch>>,
+    music_switches: Query<(), With<MusicEnabledSwitch>>,
+    shuffle_switches: Query<(), With<MusicShuffleSwitch>>,
+    folder_fields: Query<(), With<MusicFolderInput>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in slider_events.read() {
+        if sound_sliders.contains(event.entity) {
+            settings_state.editing_sound_volume = event.value.clamp(0.0, 1.0);
+        } else if music_sliders.contains(event.entity) {
+            settings_state.editing_music_volume = event.value.clamp(0.0, 1.0);
+        }
+    }
+    for event in switch_events.read() {
+        if mute_switches.contains(event.entity) {
+            settings_state.sound_muted_editing = event.selected;
+        } else if music_switches.contains(event.entity) {
+            settings_state.music_enabled_editing = event.selected;
+        } else if shuffle_switches.contains(event.entity) {
+            settings_state.music_shuffle_editing = event.selected;
+        }
+    }
+
+    let edits = change_events
+        .read()
+        .map(|ev| (ev.entity, ev.value.clone()))
+        .chain(submit_events.read().map(|ev| (ev.entity, ev.value.clone())));
+    for (entity, value) in edits {
+        if folder_fields.contains(entity) {
+            settings_state.editing_music_folder = value;
+        }
+    }
+}
+
+/// Handle the chaos dice switch and palette field in the Colors tab,
+/// redrawing the palette's swatches as it is typed.
+pub fn handle_chaos_dice_changes(
+    mut commands: Commands,
+    mut switch_events: MessageReader<SwitchChangeEvent>,
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    switches: Query<(), With<ChaosDiceSwitch>>,
+    palette_fields: Query<(), With<ChaosDicePaletteInput>>,
+    swatches: Query<Entity, With<ChaosDicePaletteSwatches>>,
+    theme: Res<MaterialTheme>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in switch_events.read() {
+        if switches.contains(event.entity) {
+            settings_state.chaos_dice_editing = event.selected;
+        }
+    }
+    for event in change_events.read() {
+        if !palette_fields.contains(event.entity)
+            || settings_state.chaos_dice_palette_input == event.value
+        {
+            continue;
+        }
+        settings_state.chaos_dice_palette_input = event.value.clone();
+        for row in swatches.iter() {
+            commands.entity(row).despawn_related::<Children>();
+            commands.entity(row).with_children(|row| {
+                settings_tabs::colors::spawn_palette_swatches(row, &theme, &event.value);
+            });
+        }
+    }
+}
+
+/// Handle explosion cap slider changes (Dice Roller settings modal).
+pub fn handle_explosion_cap_slider_changes(
+    mut events: MessageReader<SliderChangeEvent>,
+    slider_query: Query<(), With<ExplosionCapSlider>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in events.read() {
+        if slider_query.get(event.entity).is_err() {
+            continue;
+        }
+        settings_state.editing_explosion_cap = (event.value.round() as u32).clamp(
+            AppSettings::MIN_EXPLOSION_CAP,
+            AppSettings::MAX_EXPLOSION_CAP,
+        );
+    }
+}
+
+/// Handle UI scale override slider changes and the "Auto" reset button.
+pub fn handle_ui_scale_setting_changes(
+    mut slider_events: MessageReader<SliderChangeEvent>,
+    mut click_events: MessageReader<ButtonClickEvent>,
+    slider_query: Query<(), With<UiScaleSlider>>,
+    auto_query: Query<(), With<UiScaleAutoButton>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in slider_events.read() {
+        if slider_query.get(event.entity).is_ok() {
+            settings_state.editing_ui_scale_override = Some(
+                event
+                    .value
+                    .clamp(AppSettings::MIN_UI_SCALE, AppSettings::MAX_UI_SCALE),
+            );
+        }
+    }
+
+    for event in click_events.read() {
+        if auto_query.get(event.entity).is_ok() {
+            settings_state.editing_ui_scale_override = None;
+        }
+    }
+}
+
 /// Handle Dice Roll FX mapping dropdown changes (per die type, per rolled value).
 pub fn handle_dice_roll_fx_mapping_select_change(
     mut events: MessageReader<SelectChangeEvent>,
@@ -1250,17 +1778,110 @@ pub fn handle_dice_roll_fx_mapping_select_change(
         let Ok(tag) = tag_query.get(tag_entity) else {
             continue;
         };
-
-        let Some(value) = ev.option.value.as_deref() else {
+
+        let Some(value) = ev.option.value.as_deref() else {
+            continue;
+        };
+
+        let kind = parse_kind(value);
+        let mapping = ensure_mapping_mut(
+            &mut settings_state.editing_dice_roll_fx_mappings,
+            tag.die_type,
+        );
+        mapping.set(tag.value, kind);
+    }
+}
+
+/// Handle a skin being picked for a die type in the Dice tab.
+pub fn handle_dice_skin_select_change(
+    mut events: MessageReader<SelectChangeEvent>,
+    tag_query: Query<&DiceSkinSelect>,
+    parents: Query<&ChildOf>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for ev in events.read() {
+        // The tag sits on the slot around the select, a few levels up.
+        let tag = std::iter::once(ev.entity)
+            .chain(parents.iter_ancestors(ev.entity))
+            .find_map(|entity| tag_query.get(entity).ok());
+        let Some(tag) = tag else {
+            continue;
+        };
+        // Custom skins (edited in the settings file) have no preset to switch to
+        let Some(preset) = ev
+            .option
+            .value
+            .as_deref()
+            .and_then(DiceSkinPreset::from_name)
+        else {
             continue;
         };
 
-        let kind = parse_kind(value);
-        let mapping = ensure_mapping_mut(
-            &mut settings_state.editing_dice_roll_fx_mappings,
-            tag.die_type,
+        let die_type = tag.die_type;
+        set_dice_skin(
+            &mut settings_state.editing_dice_skins,
+            die_type,
+            preset.skin(die_type),
         );
-        mapping.set(tag.value, kind);
+    }
+}
+
+/// Handle the game system select (Dice tab).
+pub fn handle_game_system_select_change(
+    mut events: MessageReader<SelectChangeEvent>,
+    tag_query: Query<(), With<GameSystemSelect>>,
+    parents: Query<&ChildOf>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for ev in events.read() {
+        // The tag sits on the slot around the select, a few levels up.
+        let tagged = std::iter::once(ev.entity)
+            .chain(parents.iter_ancestors(ev.entity))
+            .any(|entity| tag_query.contains(entity));
+        if !tagged {
+            continue;
+        }
+        if let Some(system) = ev.option.value.as_deref().and_then(GameSystem::parse) {
+            settings_state.game_system_editing = system;
+        }
+    }
+}
+
+/// Handle the result order select (Dice tab).
+pub fn handle_result_order_select_change(
+    mut events: MessageReader<SelectChangeEvent>,
+    tag_query: Query<(), With<ResultOrderSelect>>,
+    parents: Query<&ChildOf>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for ev in events.read() {
+        let tagged = std::iter::once(ev.entity)
+            .chain(parents.iter_ancestors(ev.entity))
+            .any(|entity| tag_query.contains(entity));
+        if !tagged {
+            continue;
+        }
+        if let Some(order) = ev.option.value.as_deref().and_then(ResultOrder::parse) {
+            settings_state.result_order_editing = order;
+        }
     }
 }
 
@@ -1332,6 +1953,82 @@ pub fn update_dice_fx_param_ui(
     }
 }
 
+/// Sync the animation speed value label from the current editing state.
+pub fn update_animation_speed_ui(
+    settings_state: Res<SettingsState>,
+    mut label_query: Query<&mut Text, With<AnimationSpeedValueLabel>>,
+) {
+    if !settings_state.is_changed() {
+        return;
+    }
+
+    for mut text in label_query.iter_mut() {
+        *text = Text::new(format!("{:.2}x", settings_state.editing_animation_speed));
+    }
+}
+
+/// Sync the dice sound and music volume labels from the current editing state.
+pub fn update_audio_volume_ui(
+    settings_state: Res<SettingsState>,
+    mut label_queries: ParamSet<(
+        Query<&mut Text, With<SoundVolumeValueLabel>>,
+        Query<&mut Text, With<MusicVolumeValueLabel>>,
+    )>,
+) {
+    if !settings_state.is_changed() {
+        return;
+    }
+
+    for mut text in label_queries.p0().iter_mut() {
+        *text = Text::new(settings_tabs::audio::volume_label(
+            settings_state.editing_sound_volume,
+        ));
+    }
+    for mut text in label_queries.p1().iter_mut() {
+        *text = Text::new(settings_tabs::audio::volume_label(
+            settings_state.editing_music_volume,
+        ));
+    }
+}
+
+/// Sync the explosion cap value label from the current editing state.
+pub fn update_explosion_cap_ui(
+    settings_state: Res<SettingsState>,
+    mut label_query: Query<&mut Text, With<ExplosionCapValueLabel>>,
+) {
+    if !settings_state.is_changed() {
+        return;
+    }
+
+    for mut text in label_query.iter_mut() {
+        *text = Text::new(settings_state.editing_explosion_cap.to_string());
+    }
+}
+
+/// Sync the UI scale slider + value label from the current editing state.
+pub fn update_ui_scale_setting_ui(
+    settings_state: Res<SettingsState>,
+    mut slider_query: Query<&mut MaterialSlider, With<UiScaleSlider>>,
+    mut label_query: Query<&mut Text, With<UiScaleValueLabel>>,
+) {
+    if !settings_state.is_changed() {
+        return;
+    }
+
+    let value = settings_state.editing_ui_scale_override;
+    for mut slider in slider_query.iter_mut() {
+        let v = value
+            .unwrap_or(1.0)
+            .clamp(AppSettings::MIN_UI_SCALE, AppSettings::MAX_UI_SCALE);
+        if (slider.value - v).abs() > f32::EPSILON {
+            slider.value = v;
+        }
+    }
+    for mut text in label_query.iter_mut() {
+        *text = Text::new(settings_tabs::layout::ui_scale_label(value));
+    }
+}
+
 /// Ensure the slider thumb is always inside the slider entity's hit-test area.
 ///
 /// The underlying slider places the thumb centered on the track endpoints.
@@ -1444,6 +2141,42 @@ pub fn handle_default_roll_uses_shake_switch_change(
     }
 }
 
+/// Handle the physics switches (fixed timestep, fair but pretty, gather
+/// dice, debug capture, pause when unfocused) and the gamepad rumble switch
+/// in the dice roller settings modal.
+pub fn handle_physics_switch_changes(
+    mut events: MessageReader<SwitchChangeEvent>,
+    mut settings_state: ResMut<SettingsState>,
+    fixed_timestep_switches: Query<(), With<FixedTimestepPhysicsSwitch>>,
+    steered_switches: Query<(), With<SteeredDiceSwitch>>,
+    gather_switches: Query<(), With<GatherDiceSwitch>>,
+    capture_switches: Query<(), With<PhysicsDebugCaptureSwitch>>,
+    pause_switches: Query<(), With<PauseWhenUnfocusedSwitch>>,
+    rumble_switches: Query<(), With<GamepadRumbleSwitch>>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in events.read() {
+        if fixed_timestep_switches.contains(event.entity) {
+            settings_state.fixed_timestep_physics_editing = event.selected;
+        } else if steered_switches.contains(event.entity) {
+            settings_state.steered_dice_editing = event.selected;
+        } else if gather_switches.contains(event.entity) {
+            settings_state.gather_dice_editing = event.selected;
+        } else if capture_switches.contains(event.entity) {
+            settings_state.physics_debug_capture_editing = event.selected;
+        } else if pause_switches.contains(event.entity) {
+            settings_state.pause_when_unfocused_editing = event.selected;
+        } else if rumble_switches.contains(event.entity) {
+            settings_state.gamepad_rumble_editing = event.selected;
+        }
+    }
+}
+
 /// Handle selection changes in the dice roller settings modal (Quick Rolls die).
 pub fn handle_quick_roll_die_type_select_change(
     mut events: MessageReader<SelectChangeEvent>,
@@ -1516,6 +2249,155 @@ pub fn handle_theme_seed_select_change(
     }
 }
 
+/// Live-preview edits to the widget token inputs of the Theme tab, and keep
+/// the custom theme name.
+pub fn handle_theme_token_text_input(
+    mut settings_state: ResMut<SettingsState>,
+    mut change_events: MessageReader<TextFieldChangeEvent>,
+    mut submit_events: MessageReader<TextFieldSubmitEvent>,
+    mut token_fields: Query<(&ThemeTokenTextInput, &mut MaterialTextField)>,
+    name_fields: Query<(), With<CustomThemeNameInput>>,
+    mut tokens: ResMut<ThemeTokens>,
+) {
+    if !settings_state.show_modal {
+        return;
+    }
+
+    let edits = change_events
+        .read()
+        .map(|ev| (ev.entity, ev.value.clone()))
+        .chain(submit_events.read().map(|ev| (ev.entity, ev.value.clone())));
+    for (entity, value) in edits {
+        if name_fields.contains(entity) {
+            settings_state.custom_theme_name_input = value;
+            continue;
+        }
+        let Ok((input, mut field)) = token_fields.get_mut(entity) else {
+            continue;
+        };
+        match ColorSetting::parse(&value) {
+            Some(parsed) => {
+                settings_state
+                    .editing_theme_tokens
+                    .set(input.token, parsed.clone());
+                tokens.set(input.token, parsed);
+                field.error = false;
+                field.error_text = None;
+            }
+            None => {
+                field.error = true;
+                field.error_text = Some("Invalid color (hex or name)".to_string());
+            }
+        }
+    }
+}
+
+/// Save the edited tokens as a custom theme, or reset them to the built-in
+/// colors.
+pub fn handle_custom_theme_buttons(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    save_query: Query<(), With<SaveCustomThemeButton>>,
+    reset_query: Query<(), With<ResetThemeTokensButton>>,
+    mut settings_state: ResMut<SettingsState>,
+    mut tokens: ResMut<ThemeTokens>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        if save_query.contains(event.entity) {
+            let state = &mut *settings_state;
+            let message = match save_custom_theme(
+                &mut state.settings.custom_themes,
+                &state.custom_theme_name_input,
+                &state.editing_theme_tokens,
+            ) {
+                Ok(message) => {
+                    state.is_modified = true;
+                    message
+                }
+                Err(e) => e,
+            };
+            snackbar.write(ShowSnackbar::message(message).duration(2.0));
+        } else if reset_query.contains(event.entity) {
+            settings_state.editing_theme_tokens = ThemeTokens::default();
+            *tokens = ThemeTokens::default();
+        }
+    }
+}
+
+/// Load a saved custom theme into the Theme tab.
+pub fn handle_custom_theme_select_change(
+    mut events: MessageReader<SelectChangeEvent>,
+    selects: Query<&MaterialSelect>,
+    mut settings_state: ResMut<SettingsState>,
+    mut tokens: ResMut<ThemeTokens>,
+) {
+    if !settings_state.show_modal {
+        return;
+    }
+
+    for event in events.read() {
+        let Ok(select) = selects.get(event.entity) else {
+            continue;
+        };
+        if select.label.as_deref() != Some("Custom themes") {
+            continue;
+        }
+
+        let name = event
+            .option
+            .value
+            .clone()
+            .unwrap_or_else(|| event.option.label.clone());
+        let Some(custom) = find_custom_theme(&settings_state.settings.custom_themes, &name) else {
+            continue;
+        };
+        let loaded = custom.tokens.clone();
+        settings_state.custom_theme_name_input = custom.name.clone();
+        settings_state.editing_theme_tokens = loaded.clone();
+        *tokens = loaded;
+    }
+}
+
+/// Sync the swatches and hex inputs of the Theme tab with the edited tokens.
+pub fn update_theme_token_ui(
+    settings_state: Res<SettingsState>,
+    mut swatches: Query<(&ThemeTokenSwatch, &mut BackgroundColor)>,
+    mut token_fields: Query<(&ThemeTokenTextInput, &mut MaterialTextField)>,
+    mut name_fields: Query<
+        &mut MaterialTextField,
+        (With<CustomThemeNameInput>, Without<ThemeTokenTextInput>),
+    >,
+) {
+    if !settings_state.is_changed() || !settings_state.show_modal {
+        return;
+    }
+    let editing = &settings_state.editing_theme_tokens;
+
+    for (swatch, mut bg) in swatches.iter_mut() {
+        bg.0 = editing.color(swatch.token);
+    }
+
+    // Avoid stomping while the user is typing
+    for (input, mut field) in token_fields.iter_mut() {
+        if field.focused {
+            continue;
+        }
+        let hex = editing.get(input.token).to_hex();
+        if field.value != hex {
+            field.value = hex;
+            field.has_content = true;
+            field.error = false;
+            field.error_text = None;
+        }
+    }
+    for mut field in name_fields.iter_mut() {
+        if !field.focused && field.value != settings_state.custom_theme_name_input {
+            field.value = settings_state.custom_theme_name_input.clone();
+            field.has_content = !field.value.is_empty();
+        }
+    }
+}
+
 fn sort_curve_points(points: &mut [ShakeCurvePoint]) {
     points.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap_or(Ordering::Equal));
 }
@@ -2609,7 +3491,8 @@ pub fn handle_settings_cancel_click(
             continue;
         }
 
-        // Revert any live theme preview changes.
+        // Revert any live theme preview changes (the widget tokens go back
+        // in `sync_theme_tokens_from_settings`).
         apply_theme_override(&settings_state.settings, &mut theme);
 
         // Revert any live dice scale preview changes.
@@ -2631,12 +3514,7 @@ pub fn handle_settings_reset_layout_click(
     mut click_events: MessageReader<ButtonClickEvent>,
     reset_query: Query<(), With<SettingsResetLayoutButton>>,
     mut settings_state: ResMut<SettingsState>,
-    mut panel_nodes: ParamSet<(
-        Query<&mut Node, With<crate::dice3d::types::SliderGroupRoot>>,
-        Query<&mut Node, With<crate::dice3d::types::QuickRollPanel>>,
-        Query<&mut Node, With<crate::dice3d::types::CommandHistoryPanelRoot>>,
-        Query<&mut Node, With<crate::dice3d::types::ResultsPanelRoot>>,
-    )>,
+    mut panel_nodes: LayoutPanelNodes,
 ) {
     if !(settings_state.show_modal
         && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
@@ -2665,24 +3543,201 @@ pub fn handle_settings_reset_layout_click(
         settings_state.settings.quick_roll_panel_position.y = y;
 
         // Immediately move any spawned panels so the user sees it.
-        if let Some(mut node) = panel_nodes.p3().iter_mut().next() {
-            node.left = Val::Px(settings_state.settings.results_panel_position.x);
-            node.top = Val::Px(settings_state.settings.results_panel_position.y);
+        move_panels_to_settings(&settings_state.settings, &mut panel_nodes);
+
+        settings_state.is_modified = true;
+    }
+}
+
+/// Nodes of the draggable panels whose positions are saved in the settings.
+type LayoutPanelNodes<'w, 's> = ParamSet<
+    'w,
+    's,
+    (
+        Query<'w, 's, &'static mut Node, With<crate::dice3d::types::SliderGroupRoot>>,
+        Query<'w, 's, &'static mut Node, With<crate::dice3d::types::QuickRollPanel>>,
+        Query<'w, 's, &'static mut Node, With<crate::dice3d::types::CommandHistoryPanelRoot>>,
+        Query<'w, 's, &'static mut Node, With<crate::dice3d::types::ResultsPanelRoot>>,
+        Query<'w, 's, &'static mut Node, With<crate::dice3d::types::DiceBoxControlsPanelRoot>>,
+    ),
+>;
+
+/// Move any spawned draggable panels to their saved positions.
+fn move_panels_to_settings(settings: &AppSettings, panel_nodes: &mut LayoutPanelNodes) {
+    fn place(node: Option<Mut<Node>>, position: &UiPositionSetting) {
+        if let Some(mut node) = node {
+            node.left = Val::Px(position.x);
+            node.top = Val::Px(position.y);
         }
-        if let Some(mut node) = panel_nodes.p0().iter_mut().next() {
-            node.left = Val::Px(settings_state.settings.slider_group_position.x);
-            node.top = Val::Px(settings_state.settings.slider_group_position.y);
+    }
+
+    place(
+        panel_nodes.p3().iter_mut().next(),
+        &settings.results_panel_position,
+    );
+    place(
+        panel_nodes.p0().iter_mut().next(),
+        &settings.slider_group_position,
+    );
+    place(
+        panel_nodes.p2().iter_mut().next(),
+        &settings.command_history_panel_position,
+    );
+    place(
+        panel_nodes.p1().iter_mut().next(),
+        &settings.quick_roll_panel_position,
+    );
+    place(
+        panel_nodes.p4().iter_mut().next(),
+        &settings.dice_box_controls_panel_position,
+    );
+}
+
+/// Save the current settings to a JSON file chosen by the user.
+pub fn handle_settings_export_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    export_query: Query<(), With<SettingsExportButton>>,
+    settings_state: Res<SettingsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in click_events.read() {
+        if export_query.get(event.entity).is_err() {
+            continue;
         }
-        if let Some(mut node) = panel_nodes.p2().iter_mut().next() {
-            node.left = Val::Px(settings_state.settings.command_history_panel_position.x);
-            node.top = Val::Px(settings_state.settings.command_history_panel_position.y);
+        let Some(path) = pick_settings_export_path() else {
+            continue;
+        };
+
+        let result = settings_state.settings.to_export_json().and_then(|json| {
+            std::fs::write(&path, json)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        });
+        match result {
+            Ok(()) => {
+                info!("Exported settings to {}", path.display());
+                snackbar.write(
+                    ShowSnackbar::message(format!("Saved {}", path.display())).duration(2.0),
+                );
+            }
+            Err(e) => {
+                warn!("{}", e);
+                snackbar.write(ShowSnackbar::message("Failed to export settings").duration(2.0));
+            }
         }
-        if let Some(mut node) = panel_nodes.p1().iter_mut().next() {
-            node.left = Val::Px(settings_state.settings.quick_roll_panel_position.x);
-            node.top = Val::Px(settings_state.settings.quick_roll_panel_position.y);
+    }
+}
+
+/// Replace the settings with a JSON file chosen by the user, apply and save them.
+pub fn handle_settings_import_click(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    import_query: Query<(), With<SettingsImportButton>>,
+    mut settings_state: ResMut<SettingsState>,
+    mut clear_color: ResMut<ClearColor>,
+    mut shake_config: ResMut<ContainerShakeConfig>,
+    mut theme: ResMut<MaterialTheme>,
+    mut panel_nodes: LayoutPanelNodes,
+    db: Option<Res<CharacterDatabase>>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    if !(settings_state.show_modal
+        && settings_state.modal_kind == crate::dice3d::types::ActiveModalKind::DiceRollerSettings)
+    {
+        return;
+    }
+
+    for event in click_events.read() {
+        if import_query.get(event.entity).is_err() {
+            continue;
         }
+        let path = match pick_settings_import_path() {
+            Ok(Some(path)) => path,
+            Ok(None) => continue,
+            Err(e) => {
+                snackbar.write(ShowSnackbar::message(e).duration(3.0));
+                continue;
+            }
+        };
+
+        let imported = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|json| AppSettings::from_export_json(&json));
+        let imported = match imported {
+            Ok(imported) => imported,
+            Err(e) => {
+                warn!("{}", e);
+                snackbar.write(ShowSnackbar::message(e).duration(3.0));
+                continue;
+            }
+        };
+
+        settings_state.replace_settings(imported);
+        clear_color.0 = settings_state.settings.background_color.to_color();
+        *shake_config = settings_state.editing_shake_config.clone();
+        apply_theme_override(&settings_state.settings, &mut theme);
+        move_panels_to_settings(&settings_state.settings, &mut panel_nodes);
 
         settings_state.is_modified = true;
+        if let Some(db) = db.as_deref() {
+            match settings_state.settings.save_to_db(db) {
+                Ok(()) => settings_state.is_modified = false,
+                Err(e) => warn!("Failed to persist imported settings: {}", e),
+            }
+        }
+
+        info!("Imported settings from {}", path.display());
+        snackbar.write(
+            ShowSnackbar::message(format!("Imported settings from {}", path.display()))
+                .duration(2.0),
+        );
+
+        // Close the modal so it is rebuilt from the imported values next time.
+        settings_state.show_modal = false;
+        settings_state.modal_kind = crate::dice3d::types::ActiveModalKind::None;
+    }
+}
+
+/// Destination of a settings export, `None` when the dialog was cancelled.
+#[cfg(feature = "integrations")]
+fn pick_settings_export_path() -> Option<std::path::PathBuf> {
+    rfd::FileDialog::new()
+        .set_title("Export settings")
+        .add_filter("Settings", &["json"])
+        .set_file_name(SETTINGS_EXPORT_FILE_NAME)
+        .save_file()
+}
+
+/// Without file dialogs the settings are saved in the working directory.
+#[cfg(not(feature = "integrations"))]
+fn pick_settings_export_path() -> Option<std::path::PathBuf> {
+    Some(std::path::PathBuf::from(SETTINGS_EXPORT_FILE_NAME))
+}
+
+/// Settings file to import, `None` when the dialog was cancelled.
+#[cfg(feature = "integrations")]
+fn pick_settings_import_path() -> Result<Option<std::path::PathBuf>, String> {
+    Ok(rfd::FileDialog::new()
+        .set_title("Import settings")
+        .add_filter("Settings", &["json"])
+        .pick_file())
+}
+
+/// Without file dialogs the settings are read from the working directory.
+#[cfg(not(feature = "integrations"))]
+fn pick_settings_import_path() -> Result<Option<std::path::PathBuf>, String> {
+    let path = std::path::PathBuf::from(SETTINGS_EXPORT_FILE_NAME);
+    if path.exists() {
+        Ok(Some(path))
+    } else {
+        Err(format!(
+            "Importing needs file dialogs, or {} in the working directory",
+            SETTINGS_EXPORT_FILE_NAME
+        ))
     }
 }
 
@@ -0,0 +1,174 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice::spawn_tagged_switch;
+use crate::dice3d::types::{
+    MusicEnabledSwitch, MusicFolderInput, MusicShuffleSwitch, MusicVolumeSlider,
+    MusicVolumeValueLabel, SettingsState, SoundMutedSwitch, SoundVolumeSlider,
+    SoundVolumeValueLabel,
+};
+
+pub fn build_audio_tab(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    settings_state: &SettingsState,
+) {
+    parent.spawn((
+        Text::new("Dice sounds"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Dice hitting the container and each other, the clack when a roll \
+             settles, the natural 20 sting and the Dice FX sounds.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_volume_row(
+        parent,
+        theme,
+        settings_state.editing_sound_volume,
+        SoundVolumeSlider,
+        SoundVolumeValueLabel,
+    );
+
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.sound_muted_editing,
+        SoundMutedSwitch,
+        "Mute",
+    );
+
+    parent.spawn((
+        Text::new("Background music"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(12.0)),
+            ..default()
+        },
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Plays the MP3, Ogg and WAV files of a folder for the whole session, \
+             fading from one track into the next. Type `music next` to skip a track.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.music_enabled_editing,
+        MusicEnabledSwitch,
+        "Play background music",
+    );
+
+    parent
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            min_width: Val::Px(0.0),
+            ..default()
+        })
+        .with_children(|slot| {
+            let builder = TextFieldBuilder::new()
+                .outlined()
+                .label("Music folder")
+                .value(settings_state.editing_music_folder.clone())
+                .supporting_text("Full path of a folder with audio files")
+                .width(Val::Percent(100.0));
+            spawn_text_field_control_with(slot, theme, builder, MusicFolderInput);
+        });
+
+    spawn_volume_row(
+        parent,
+        theme,
+        settings_state.editing_music_volume,
+        MusicVolumeSlider,
+        MusicVolumeValueLabel,
+    );
+
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.music_shuffle_editing,
+        MusicShuffleSwitch,
+        "Shuffle",
+    );
+}
+
+/// "Volume" label, a 0-100% slider and its value label.
+fn spawn_volume_row(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    volume: f32,
+    slider_marker: impl Component,
+    label_marker: impl Component,
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            height: Val::Px(30.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Volume"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+            ));
+
+            row.spawn(Node {
+                width: Val::Px(260.0),
+                height: Val::Px(30.0),
+                ..default()
+            })
+            .with_children(|slot| {
+                let slider = MaterialSlider::new(0.0, 1.0)
+                    .with_value(volume.clamp(0.0, 1.0))
+                    .track_height(6.0)
+                    .thumb_radius(8.0);
+                spawn_slider_control_with(slot, theme, slider, slider_marker);
+            });
+
+            row.spawn((
+                Text::new(volume_label(volume)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                label_marker,
+            ));
+        });
+}
+
+/// "80%"
+pub fn volume_label(volume: f32) -> String {
+    format!("{:.0}%", volume.clamp(0.0, 1.0) * 100.0)
+}
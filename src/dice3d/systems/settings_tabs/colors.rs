@@ -2,8 +2,10 @@ use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::prelude::*;
 use bevy_material_ui::prelude::*;
 
+use super::dice::spawn_tagged_switch;
 use crate::dice3d::systems::settings::spawn_color_slider;
 use crate::dice3d::types::{
+    parse_color_palette, ChaosDicePaletteInput, ChaosDicePaletteSwatches, ChaosDiceSwitch,
     ColorComponent, ColorPreview, ColorSetting, ColorTextInput, HighlightColorPreview,
     HighlightColorTextInput, ThemeSeedTextInput,
 };
@@ -15,6 +17,8 @@ pub fn build_colors_tab(
     editing_highlight_color: &ColorSetting,
     theme_seed_input_text: &str,
     recent_theme_seeds: &[String],
+    chaos_dice: bool,
+    chaos_dice_palette_input: &str,
 ) {
     parent.spawn((
         Text::new("Background Color"),
@@ -167,6 +171,64 @@ pub fn build_colors_tab(
             });
         });
 
+    parent.spawn((
+        Text::new("Chaos Dice"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(12.0)),
+            ..default()
+        },
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Every die of every roll comes out in a random color from the palette, \
+             whatever its skin.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_tagged_switch(parent, theme, chaos_dice, ChaosDiceSwitch, "Chaos dice");
+
+    parent
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            min_width: Val::Px(0.0),
+            ..default()
+        })
+        .with_children(|slot| {
+            let builder = TextFieldBuilder::new()
+                .outlined()
+                .label("Palette")
+                .value(chaos_dice_palette_input)
+                .supporting_text("Colors separated by commas: #FF8844, teal, gold")
+                .width(Val::Percent(100.0));
+            spawn_text_field_control_with(slot, theme, builder, ChaosDicePaletteInput);
+        });
+
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(6.0),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            ChaosDicePaletteSwatches,
+        ))
+        .with_children(|row| {
+            spawn_palette_swatches(row, theme, chaos_dice_palette_input);
+        });
+
     parent.spawn(Node {
         height: Val::Px(16.0),
         ..default()
@@ -243,3 +305,38 @@ pub fn build_colors_tab(
             });
         });
 }
+
+/// A swatch per color of the typed palette, or the reason it doesn't parse.
+pub fn spawn_palette_swatches(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    palette_input: &str,
+) {
+    match parse_color_palette(palette_input) {
+        Ok(palette) => {
+            for color in palette {
+                parent.spawn((
+                    Node {
+                        width: Val::Px(24.0),
+                        height: Val::Px(24.0),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    BackgroundColor(color.to_color()),
+                    BorderColor::from(theme.outline_variant),
+                    BorderRadius::all(Val::Px(12.0)),
+                ));
+            }
+        }
+        Err(e) => {
+            parent.spawn((
+                Text::new(e),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(theme.error),
+            ));
+        }
+    }
+}
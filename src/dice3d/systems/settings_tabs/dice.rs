@@ -5,8 +5,12 @@ use bevy_material_ui::tokens::CornerRadius;
 
 use crate::dice3d::systems::settings::spawn_dice_scale_slider;
 use crate::dice3d::types::{
+    dice_skin_for, AnimationSpeedSlider, AnimationSpeedValueLabel, AppSettings,
     DefaultRollUsesShakeSwitch, DiceFxParamKind, DiceFxParamSlider, DiceFxParamValueLabel,
-    DiceRollFxKind, DiceRollFxMappingSelect, DiceScaleSettings, DiceType, SettingsState,
+    DiceRollFxKind, DiceRollFxMappingSelect, DiceScaleSettings, DiceSkinPreset, DiceSkinSelect,
+    DiceType, ExplosionCapSlider, ExplosionCapValueLabel, FixedTimestepPhysicsSwitch, GameSystem,
+    GameSystemSelect, GamepadRumbleSwitch, GatherDiceSwitch, PauseWhenUnfocusedSwitch,
+    PhysicsDebugCaptureSwitch, ResultOrder, ResultOrderSelect, SettingsState, SteeredDiceSwitch,
 };
 
 pub fn build_dice_tab(
@@ -159,68 +163,260 @@ pub fn build_dice_tab(
         TextColor(theme.on_surface_variant),
     ));
 
-    // Custom spawn so we can tag the actual switch (track) entity.
-    let switch = MaterialSwitch::new().selected(default_roll_uses_shake);
-    let bg_color = switch.track_color(theme);
-    let border_color = switch.track_outline_color(theme);
-    let handle_color = switch.handle_color(theme);
-    let handle_size = switch.handle_size();
-    let has_border = !switch.selected;
-    let justify = if switch.selected {
-        JustifyContent::FlexEnd
-    } else {
-        JustifyContent::FlexStart
-    };
+    spawn_tagged_switch(
+        parent,
+        theme,
+        default_roll_uses_shake,
+        DefaultRollUsesShakeSwitch,
+        "Use shake for all rolls",
+    );
+
+    parent.spawn((
+        Text::new("Physics"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.fixed_timestep_physics_editing,
+        FixedTimestepPhysicsSwitch,
+        "Fixed-timestep physics (same throw, same result at any frame rate)",
+    );
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.steered_dice_editing,
+        SteeredDiceSwitch,
+        "Fair but pretty: results are picked at random first, then the dice are steered onto them",
+    );
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.gather_dice_editing,
+        GatherDiceSwitch,
+        "Gather dice: after a roll is read, line the dice up at the front, lowest first",
+    );
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.physics_debug_capture_editing,
+        PhysicsDebugCaptureSwitch,
+        "Debug capture: save a repro file when a die gets stuck or a roll is flagged with `misread`",
+    );
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.pause_when_unfocused_editing,
+        PauseWhenUnfocusedSwitch,
+        "Pause in the background: stop the dice and dim the scene while the window isn't focused",
+    );
+
+    parent.spawn((
+        Text::new("Controller"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_tagged_switch(
+        parent,
+        theme,
+        settings_state.gamepad_rumble_editing,
+        GamepadRumbleSwitch,
+        "Gamepad rumble: shake the controller when dice hit the container and when they settle",
+    );
 
+    // Animation speed (dice box lid and other animated transitions)
     parent
         .spawn(Node {
             flex_direction: FlexDirection::Row,
             align_items: AlignItems::Center,
-            column_gap: Val::Px(12.0),
+            column_gap: Val::Px(10.0),
+            height: Val::Px(30.0),
             ..default()
         })
         .with_children(|row| {
-            // Switch track (touch target)
             row.spawn((
-                DefaultRollUsesShakeSwitch,
-                switch,
-                Button,
-                Interaction::None,
-                RippleHost::new(),
+                Text::new("Animation speed"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+            ));
+
+            row.spawn(Node {
+                width: Val::Px(260.0),
+                height: Val::Px(30.0),
+                ..default()
+            })
+            .with_children(|slot| {
+                let slider = MaterialSlider::new(
+                    AppSettings::MIN_ANIMATION_SPEED,
+                    AppSettings::MAX_ANIMATION_SPEED,
+                )
+                .with_value(settings_state.editing_animation_speed.clamp(
+                    AppSettings::MIN_ANIMATION_SPEED,
+                    AppSettings::MAX_ANIMATION_SPEED,
+                ))
+                .track_height(6.0)
+                .thumb_radius(8.0);
+                spawn_slider_control_with(slot, theme, slider, AnimationSpeedSlider);
+            });
+
+            row.spawn((
+                Text::new(format!("{:.2}x", settings_state.editing_animation_speed)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                AnimationSpeedValueLabel,
+            ));
+        });
+
+    // Explosion cap (extra rolls per exploding die, `1d6!`)
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            height: Val::Px(30.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Explosion cap"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+            ));
+
+            row.spawn(Node {
+                width: Val::Px(260.0),
+                height: Val::Px(30.0),
+                ..default()
+            })
+            .with_children(|slot| {
+                let slider = MaterialSlider::new(
+                    AppSettings::MIN_EXPLOSION_CAP as f32,
+                    AppSettings::MAX_EXPLOSION_CAP as f32,
+                )
+                .with_value(settings_state.editing_explosion_cap.clamp(
+                    AppSettings::MIN_EXPLOSION_CAP,
+                    AppSettings::MAX_EXPLOSION_CAP,
+                ) as f32)
+                .track_height(6.0)
+                .thumb_radius(8.0);
+                spawn_slider_control_with(slot, theme, slider, ExplosionCapSlider);
+            });
+
+            row.spawn((
+                Text::new(settings_state.editing_explosion_cap.to_string()),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                ExplosionCapValueLabel,
+            ));
+        });
+
+    // Game system: add the dice up, or count successes in a dice pool
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new("Game system"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+            ));
+
+            row.spawn((
                 Node {
-                    width: Val::Px(SWITCH_TRACK_WIDTH),
-                    height: Val::Px(SWITCH_TRACK_HEIGHT),
-                    justify_content: justify,
-                    align_items: AlignItems::Center,
-                    padding: UiRect::horizontal(Val::Px(2.0)),
-                    border: UiRect::all(Val::Px(if has_border { 2.0 } else { 0.0 })),
+                    width: Val::Px(340.0),
+                    height: Val::Px(32.0),
                     ..default()
                 },
-                BackgroundColor(bg_color),
-                BorderColor::all(border_color),
-                BorderRadius::all(Val::Px(CornerRadius::FULL)),
+                GameSystemSelect,
             ))
-            .with_children(|track| {
-                track.spawn((
-                    SwitchHandle,
-                    Node {
-                        width: Val::Px(handle_size),
-                        height: Val::Px(handle_size),
-                        ..default()
-                    },
-                    BackgroundColor(handle_color),
-                    BorderRadius::all(Val::Px(handle_size / 2.0)),
-                ));
+            .with_children(|slot| {
+                let options = GameSystem::ALL
+                    .iter()
+                    .map(|system| SelectOption::new(system.label()).value(system.name()))
+                    .collect();
+                let selected = GameSystem::ALL
+                    .iter()
+                    .position(|system| *system == settings_state.game_system_editing)
+                    .unwrap_or(0);
+                let builder = SelectBuilder::new(options)
+                    .outlined()
+                    .label("")
+                    .selected(selected)
+                    .width(Val::Px(340.0));
+                slot.spawn_select_with(theme, builder);
             });
+        });
 
+    // How the dice of a roll are listed in the results panel
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|row| {
             row.spawn((
-                Text::new("Use shake for all rolls"),
+                Text::new("List results"),
                 TextFont {
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(theme.on_surface),
+                TextColor(theme.on_surface_variant),
             ));
+
+            row.spawn((
+                Node {
+                    width: Val::Px(340.0),
+                    height: Val::Px(32.0),
+                    ..default()
+                },
+                ResultOrderSelect,
+            ))
+            .with_children(|slot| {
+                let options = ResultOrder::ALL
+                    .iter()
+                    .map(|order| SelectOption::new(order.label()).value(order.name()))
+                    .collect();
+                let selected = ResultOrder::ALL
+                    .iter()
+                    .position(|order| *order == settings_state.result_order_editing)
+                    .unwrap_or(0);
+                let builder = SelectBuilder::new(options)
+                    .outlined()
+                    .label("")
+                    .selected(selected)
+                    .width(Val::Px(340.0));
+                slot.spawn_select_with(theme, builder);
+            });
         });
 
     parent.spawn(Node {
@@ -345,6 +541,107 @@ pub fn build_dice_tab(
             });
         });
 
+    // ---------------------------------------------------------------------
+    // Dice Skins (one per die type)
+    // ---------------------------------------------------------------------
+
+    parent.spawn(Node {
+        height: Val::Px(18.0),
+        ..default()
+    });
+
+    parent.spawn((
+        Text::new("Dice Skins"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Colors and finish of each die type. Custom skins (number color, texture) can be \
+             set in an exported settings file.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: Val::Px(22.0),
+            row_gap: Val::Px(12.0),
+            width: Val::Percent(100.0),
+            min_width: Val::Px(0.0),
+            ..default()
+        })
+        .with_children(|wrap| {
+            for die_type in [
+                DiceType::D4,
+                DiceType::D6,
+                DiceType::D8,
+                DiceType::D10,
+                DiceType::D12,
+                DiceType::D20,
+            ] {
+                let skin = dice_skin_for(&settings_state.editing_dice_skins, die_type);
+                let mut options: Vec<SelectOption> = DiceSkinPreset::ALL
+                    .iter()
+                    .map(|preset| SelectOption::new(preset.label()).value(preset.label()))
+                    .collect();
+                let selected = match DiceSkinPreset::from_name(&skin.name) {
+                    Some(preset) => DiceSkinPreset::ALL
+                        .iter()
+                        .position(|p| *p == preset)
+                        .unwrap_or(0),
+                    None => {
+                        options.push(SelectOption::new(format!("Custom: {}", skin.name)));
+                        options.len() - 1
+                    }
+                };
+
+                wrap.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(format!("{}:", die_type.name())),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(theme.on_surface_variant),
+                    ));
+
+                    row.spawn((
+                        Node {
+                            width: Val::Px(170.0),
+                            height: Val::Px(32.0),
+                            ..default()
+                        },
+                        DiceSkinSelect { die_type },
+                    ))
+                    .with_children(|slot| {
+                        let builder = SelectBuilder::new(options)
+                            .outlined()
+                            .label("")
+                            .selected(selected)
+                            .width(Val::Px(170.0));
+                        slot.spawn_select_with(theme, builder);
+                    });
+                });
+            }
+        });
+
     // ---------------------------------------------------------------------
     // Dice Roll Effects (hardcoded FX, mapped per die face value)
     // ---------------------------------------------------------------------
@@ -489,3 +786,76 @@ pub fn build_dice_tab(
             });
     }
 }
+
+/// A labelled switch whose track entity carries `marker`, so change events can
+/// be matched to the setting they edit.
+pub(crate) fn spawn_tagged_switch(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    selected: bool,
+    marker: impl Component,
+    label: &str,
+) {
+    let switch = MaterialSwitch::new().selected(selected);
+    let bg_color = switch.track_color(theme);
+    let border_color = switch.track_outline_color(theme);
+    let handle_color = switch.handle_color(theme);
+    let handle_size = switch.handle_size();
+    let has_border = !switch.selected;
+    let justify = if switch.selected {
+        JustifyContent::FlexEnd
+    } else {
+        JustifyContent::FlexStart
+    };
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(12.0),
+            ..default()
+        })
+        .with_children(|row| {
+            // Switch track (touch target)
+            row.spawn((
+                marker,
+                switch,
+                Button,
+                Interaction::None,
+                RippleHost::new(),
+                Node {
+                    width: Val::Px(SWITCH_TRACK_WIDTH),
+                    height: Val::Px(SWITCH_TRACK_HEIGHT),
+                    justify_content: justify,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::horizontal(Val::Px(2.0)),
+                    border: UiRect::all(Val::Px(if has_border { 2.0 } else { 0.0 })),
+                    ..default()
+                },
+                BackgroundColor(bg_color),
+                BorderColor::all(border_color),
+                BorderRadius::all(Val::Px(CornerRadius::FULL)),
+            ))
+            .with_children(|track| {
+                track.spawn((
+                    SwitchHandle,
+                    Node {
+                        width: Val::Px(handle_size),
+                        height: Val::Px(handle_size),
+                        ..default()
+                    },
+                    BackgroundColor(handle_color),
+                    BorderRadius::all(Val::Px(handle_size / 2.0)),
+                ));
+            });
+
+            row.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface),
+            ));
+        });
+}
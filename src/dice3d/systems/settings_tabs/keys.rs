@@ -0,0 +1,138 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    KeyAction, KeyBindingButton, KeyBindingButtonLabel, KeyBindingConflictText,
+    KeyBindingResetButton, KeyBindings, ResetKeyBindingsButton,
+};
+
+pub fn build_keys_tab(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    bindings: &KeyBindings,
+) {
+    parent.spawn((
+        Text::new("Keyboard shortcuts"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Click a shortcut and press the new key, holding Ctrl, Shift or Alt \
+             if it needs them. Escape keeps the old shortcut and Backspace \
+             leaves the action without one. Quick rolls press the quick roll \
+             panel's buttons from the top.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    for action in KeyAction::ALL {
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(12.0),
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                min_width: Val::Px(0.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(action.label()),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                    Node {
+                        width: Val::Px(170.0),
+                        ..default()
+                    },
+                ));
+
+                row.spawn((
+                    MaterialButtonBuilder::new(bindings.describe(action))
+                        .outlined()
+                        .build(theme),
+                    KeyBindingButton(action),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(bindings.describe(action)),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(theme.primary),
+                        ButtonLabel,
+                        KeyBindingButtonLabel(action),
+                    ));
+                });
+
+                row.spawn((
+                    MaterialButtonBuilder::new("Default").text().build(theme),
+                    KeyBindingResetButton(action),
+                    TooltipTrigger::new("Use the default shortcut").top(),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Default"),
+                        TextFont {
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextColor(theme.primary),
+                        ButtonLabel,
+                    ));
+                });
+
+                row.spawn((
+                    Text::new(conflict_text(bindings, action)),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    TextColor(theme.error),
+                    KeyBindingConflictText(action),
+                ));
+            });
+    }
+
+    parent
+        .spawn((
+            MaterialButtonBuilder::new("Reset all shortcuts")
+                .outlined()
+                .build(theme),
+            ResetKeyBindingsButton,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new("Reset all shortcuts"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+                ButtonLabel,
+            ));
+        });
+}
+
+/// "Also: Clear the dice" for an action sharing its shortcut, empty otherwise.
+pub fn conflict_text(bindings: &KeyBindings, action: KeyAction) -> String {
+    let conflicts = bindings.conflicts(action);
+    if conflicts.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = conflicts.iter().map(KeyAction::label).collect();
+    format!("Also: {}", labels.join(", "))
+}
@@ -2,9 +2,24 @@ use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::prelude::*;
 use bevy_material_ui::prelude::*;
 
-use crate::dice3d::types::SettingsResetLayoutButton;
+use crate::dice3d::types::{
+    AppSettings, SettingsExportButton, SettingsImportButton, SettingsResetLayoutButton,
+    SettingsState, UiScaleAutoButton, UiScaleSlider, UiScaleValueLabel,
+};
 
-pub fn build_layout_tab(parent: &mut ChildSpawnerCommands, theme: &MaterialTheme) {
+/// Label for the UI scale value ("Auto" when following the monitor).
+pub fn ui_scale_label(value: Option<f32>) -> String {
+    match value {
+        Some(v) => format!("{:.2}x", v),
+        None => "Auto".to_string(),
+    }
+}
+
+pub fn build_layout_tab(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    settings_state: &SettingsState,
+) {
     parent.spawn((
         Text::new("Layout"),
         TextFont {
@@ -48,4 +63,149 @@ pub fn build_layout_tab(parent: &mut ChildSpawnerCommands, theme: &MaterialTheme
                 ));
             });
         });
+
+    parent.spawn(Node {
+        height: Val::Px(16.0),
+        ..default()
+    });
+
+    parent.spawn((
+        Text::new("Backup"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Save theme, colors, dice, shake curve and layout to a JSON file, or load them on another machine.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(12.0),
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_settings_file_button(row, theme, "Export settings", SettingsExportButton);
+            spawn_settings_file_button(row, theme, "Import settings", SettingsImportButton);
+        });
+
+    parent.spawn(Node {
+        height: Val::Px(16.0),
+        ..default()
+    });
+
+    parent.spawn((
+        Text::new("UI Scale"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new("Auto follows each monitor's scale factor. Drag to override."),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    let override_value = settings_state.editing_ui_scale_override;
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            height: Val::Px(36.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn(Node {
+                width: Val::Px(260.0),
+                height: Val::Px(30.0),
+                ..default()
+            })
+            .with_children(|slot| {
+                let slider =
+                    MaterialSlider::new(AppSettings::MIN_UI_SCALE, AppSettings::MAX_UI_SCALE)
+                        .with_value(
+                            override_value
+                                .unwrap_or(1.0)
+                                .clamp(AppSettings::MIN_UI_SCALE, AppSettings::MAX_UI_SCALE),
+                        )
+                        .track_height(6.0)
+                        .thumb_radius(8.0);
+                spawn_slider_control_with(slot, theme, slider, UiScaleSlider);
+            });
+
+            row.spawn((
+                Text::new(ui_scale_label(override_value)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                UiScaleValueLabel,
+            ));
+
+            row.spawn((
+                MaterialButtonBuilder::new("Auto").text().build(theme),
+                UiScaleAutoButton,
+            ))
+            .with_children(|btn| {
+                btn.spawn((
+                    Text::new("Auto"),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(theme.primary),
+                    ButtonLabel,
+                ));
+            });
+        });
+}
+
+fn spawn_settings_file_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    label: &str,
+    marker: M,
+) {
+    parent
+        .spawn(Node {
+            width: Val::Px(200.0),
+            height: Val::Px(36.0),
+            ..default()
+        })
+        .with_children(|slot| {
+            slot.spawn((
+                MaterialButtonBuilder::new(label).outlined().build(theme),
+                marker,
+            ))
+            .with_children(|btn| {
+                btn.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(theme.primary),
+                    ButtonLabel,
+                ));
+            });
+        });
 }
@@ -2,14 +2,23 @@ use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::prelude::*;
 use bevy_material_ui::prelude::*;
 
+use crate::dice3d::types::{SettingsTabContentRoot, SettingsTabScroll};
+
+pub mod audio;
 pub mod colors;
 pub mod dice;
+pub mod keys;
 pub mod layout;
+pub mod profiles;
 pub mod shake_curve;
+pub mod theme;
 
 /// Creates a per-tab scrollable content panel.
 ///
-/// Runs `build` inside the tab's scrollable content root.
+/// Runs `build` inside the tab's scrollable content root. The scroll
+/// container is tagged with `SettingsTabScroll` so the tab keeps its scroll
+/// position, and the content root with `SettingsTabContentRoot` so the
+/// settings search filters what `build` spawns.
 pub fn spawn_scrollable_tab_content(
     parent: &mut ChildSpawnerCommands,
     tabs_entity: Entity,
@@ -49,6 +58,7 @@ pub fn spawn_scrollable_tab_content(
             .spawn((
                 ScrollContainer::vertical(),
                 ScrollPosition::default(),
+                SettingsTabScroll(tab_index),
                 Node {
                     width: Val::Percent(100.0),
                     flex_grow: 1.0,
@@ -62,23 +72,26 @@ pub fn spawn_scrollable_tab_content(
             .with_children(|scroll| {
                 // Actual content root (child of ScrollContent wrapper created by plugin).
                 scroll
-                    .spawn(Node {
-                        width: Val::Percent(100.0),
-                        min_width: Val::Px(0.0),
-                        flex_direction: FlexDirection::Column,
-                        row_gap: Val::Px(12.0),
-                        // Leave a little gutter so the vertical scrollbar doesn't overlap content.
-                        padding: UiRect {
-                            left: Val::Px(2.0),
-                            right: Val::Px(18.0),
-                            top: Val::Px(2.0),
-                            // Give the bottom of long tabs room so final controls
-                            // (like the Dice FX curve editor graph) aren't hidden
-                            // behind the modal button row.
-                            bottom: Val::Px(72.0),
+                    .spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            min_width: Val::Px(0.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(12.0),
+                            // Leave a little gutter so the vertical scrollbar doesn't overlap content.
+                            padding: UiRect {
+                                left: Val::Px(2.0),
+                                right: Val::Px(18.0),
+                                top: Val::Px(2.0),
+                                // Give the bottom of long tabs room so final controls
+                                // (like the Dice FX curve editor graph) aren't hidden
+                                // behind the modal button row.
+                                bottom: Val::Px(72.0),
+                            },
+                            ..default()
                         },
-                        ..default()
-                    })
+                        SettingsTabContentRoot(tab_index),
+                    ))
                     .with_children(build);
             });
     });
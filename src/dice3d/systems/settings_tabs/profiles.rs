@@ -0,0 +1,132 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::systems::{EncryptionSettingsButton, ProfileSwitchButton};
+use crate::dice3d::types::CharacterDatabase;
+
+pub fn build_profiles_tab(parent: &mut ChildSpawnerCommands, theme: &MaterialTheme) {
+    let registry = CharacterDatabase::load_profiles();
+    let current = CharacterDatabase::current_profile();
+
+    parent.spawn((
+        Text::new("Profiles"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Each profile has its own characters, settings and history. \
+             Switching restarts the app. Type \"profile <name>\" in the command \
+             input to create a new one.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    for profile in &registry.profiles {
+        let is_current = profile.id == current.id;
+
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(10.0),
+                height: Val::Px(36.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(if is_current {
+                        format!("{} (current)", profile.name)
+                    } else {
+                        profile.name.clone()
+                    }),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(if is_current {
+                        theme.primary
+                    } else {
+                        theme.on_surface
+                    }),
+                    Node {
+                        width: Val::Px(240.0),
+                        ..default()
+                    },
+                ));
+
+                if !is_current {
+                    row.spawn((
+                        MaterialButtonBuilder::new("Switch").outlined().build(theme),
+                        ProfileSwitchButton {
+                            profile_id: profile.id.clone(),
+                        },
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((
+                            Text::new("Switch"),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(theme.primary),
+                            ButtonLabel,
+                        ));
+                    });
+                }
+            });
+    }
+
+    parent.spawn((
+        Text::new("Encryption"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(16.0)),
+            ..default()
+        },
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Encrypt this profile's character sheets with a passphrase, asked for \
+             each time the app starts.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent
+        .spawn((
+            MaterialButtonBuilder::new("Encryption…")
+                .outlined()
+                .build(theme),
+            EncryptionSettingsButton,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new("Encryption…"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+                ButtonLabel,
+            ));
+        });
+}
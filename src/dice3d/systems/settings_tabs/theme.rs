@@ -0,0 +1,290 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    CustomTheme, CustomThemeNameInput, ResetThemeTokensButton, SaveCustomThemeButton, ThemeToken,
+    ThemeTokenSwatch, ThemeTokenTextInput, ThemeTokens,
+};
+
+pub fn build_theme_tab(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    tokens: &ThemeTokens,
+    custom_theme_name: &str,
+    custom_themes: &[CustomTheme],
+) {
+    parent.spawn((
+        Text::new("Widget colors"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    parent.spawn((
+        Text::new(
+            "Colors of the character sheet and its panels. Edits preview right \
+             away and are kept when you press OK.",
+        ),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+    ));
+
+    spawn_preview_card(parent);
+
+    for token in ThemeToken::ALL {
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(12.0),
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                min_width: Val::Px(0.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Node {
+                        width: Val::Px(40.0),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(tokens.color(token)),
+                    BorderColor::from(theme.outline_variant),
+                    BorderRadius::all(Val::Px(6.0)),
+                    ThemeTokenSwatch { token },
+                ));
+
+                row.spawn(Node {
+                    flex_grow: 1.0,
+                    min_width: Val::Px(0.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    let builder = TextFieldBuilder::new()
+                        .outlined()
+                        .label(token.label())
+                        .value(tokens.get(token).to_hex())
+                        .width(Val::Percent(100.0));
+                    spawn_text_field_control_with(
+                        slot,
+                        theme,
+                        builder,
+                        ThemeTokenTextInput { token },
+                    );
+                });
+            });
+    }
+
+    parent.spawn((
+        Text::new("Custom themes"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(16.0)),
+            ..default()
+        },
+    ));
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(12.0),
+            align_items: AlignItems::Center,
+            width: Val::Percent(100.0),
+            min_width: Val::Px(0.0),
+            ..default()
+        })
+        .with_children(|row| {
+            if !custom_themes.is_empty() {
+                let options: Vec<SelectOption> = custom_themes
+                    .iter()
+                    .map(|t| SelectOption::new(&t.name).value(&t.name))
+                    .collect();
+                let builder = SelectBuilder::new(options)
+                    .outlined()
+                    .label("Custom themes");
+
+                row.spawn(Node {
+                    width: Val::Px(210.0),
+                    ..default()
+                })
+                .with_children(|slot| {
+                    slot.spawn_select_with(theme, builder.width(Val::Px(210.0)));
+                });
+            }
+
+            row.spawn(Node {
+                flex_grow: 1.0,
+                min_width: Val::Px(0.0),
+                ..default()
+            })
+            .with_children(|slot| {
+                let builder = TextFieldBuilder::new()
+                    .outlined()
+                    .label("Theme name")
+                    .value(custom_theme_name)
+                    .supporting_text("Saving under an existing name replaces it")
+                    .width(Val::Percent(100.0));
+                spawn_text_field_control_with(slot, theme, builder, CustomThemeNameInput);
+            });
+        });
+
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_button(row, theme, "Save theme", SaveCustomThemeButton);
+            spawn_button(row, theme, "Reset colors", ResetThemeTokensButton);
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    theme: &MaterialTheme,
+    label: &str,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            MaterialButtonBuilder::new(label).outlined().build(theme),
+            marker,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+                ButtonLabel,
+            ));
+        });
+}
+
+/// A card drawn with the tokens, recolored as they are edited.
+fn spawn_preview_card(parent: &mut ChildSpawnerCommands) {
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                width: Val::Percent(100.0),
+                ..default()
+            },
+            ThemeToken::SurfaceContainer.background(),
+            ThemeToken::OutlineVariant.border(),
+            BorderRadius::all(Val::Px(8.0)),
+        ))
+        .with_children(|card| {
+            card.spawn((
+                Text::new("Preview"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                ThemeToken::OnSurface.text(),
+            ));
+            card.spawn((
+                Text::new("Secondary text, like field labels"),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                ThemeToken::OnSurfaceVariant.text(),
+            ));
+
+            card.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|chips| {
+                chips
+                    .spawn((
+                        Node {
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        ThemeToken::Primary.background(),
+                        BorderRadius::all(Val::Px(12.0)),
+                    ))
+                    .with_children(|chip| {
+                        chip.spawn((
+                            Text::new("Active"),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            ThemeToken::OnPrimary.text(),
+                        ));
+                    });
+
+                for (token, label) in [
+                    (ThemeToken::Secondary, "Secondary"),
+                    (ThemeToken::Tertiary, "Tertiary"),
+                    (ThemeToken::Success, "Proficient"),
+                    (ThemeToken::Warning, "Modified"),
+                    (ThemeToken::Error, "Bloodied"),
+                ] {
+                    chips
+                        .spawn((
+                            Node {
+                                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                border: UiRect::all(Val::Px(1.0)),
+                                ..default()
+                            },
+                            ThemeToken::Surface.background(),
+                            ThemeToken::Outline.border(),
+                            BorderRadius::all(Val::Px(12.0)),
+                        ))
+                        .with_children(|chip| {
+                            chip.spawn((
+                                Text::new(label),
+                                TextFont {
+                                    font_size: 12.0,
+                                    ..default()
+                                },
+                                token.text(),
+                            ));
+                        });
+                }
+            });
+
+            card.spawn((
+                Node {
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                ThemeToken::SurfaceContainerHigh.background(),
+                BorderRadius::all(Val::Px(6.0)),
+            ))
+            .with_children(|field| {
+                field.spawn((
+                    Text::new("Elevated surface"),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    ThemeToken::OnSurface.text(),
+                ));
+            });
+        });
+}
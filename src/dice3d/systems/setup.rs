@@ -5,7 +5,7 @@
 
 use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::prelude::*;
-use bevy::ui::FocusPolicy;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
 use bevy_material_ui::icons::MaterialIconFont;
 use bevy_material_ui::prelude::*;
 use bevy_rapier3d::prelude::*;
@@ -15,7 +15,7 @@ use bevy::post_process::bloom::Bloom;
 use bevy::render::view::Hdr;
 
 use crate::dice3d::embedded_assets::{BOX_MODEL_SCENE_PATH, CUP_MODEL_SCENE_PATH};
-use crate::dice3d::meshes::create_die_mesh_and_collider;
+use crate::dice3d::meshes::{CachedDieMesh, DiceMeshCache};
 use crate::dice3d::throw_control::{
     spawn_throw_arrow, StrengthSlider, ThrowControlState, BOX_HALF_EXTENT, BOX_WALL_HEIGHT,
     CUP_RADIUS, ORIGINAL_BOX_HALF_EXTENT,
@@ -24,7 +24,8 @@ use crate::dice3d::types::*;
 
 use bevy::audio::SpatialListener;
 
-use super::rendering::{create_number_mesh, get_label_offset, get_label_rotation, get_label_scale};
+use super::dice_palette::palette_summary_text;
+use super::rendering::{get_label_offset, get_label_rotation, get_label_scale};
 
 /// Main setup system - initializes the entire 3D scene
 pub fn setup(
@@ -32,6 +33,7 @@ pub fn setup(
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut mesh_cache: ResMut<DiceMeshCache>,
     dice_config: Res<DiceConfig>,
     character_data: Res<CharacterData>,
     zoom_state: Res<ZoomState>,
@@ -42,6 +44,7 @@ pub fn setup(
     icon_font: Res<MaterialIconFont>,
     theme: Res<MaterialTheme>,
     container_style: Res<DiceContainerStyle>,
+    mut roll_rng: ResMut<RollRng>,
 ) {
     // Camera - position based on zoom state (closer by default)
     let camera_distance = zoom_state.get_distance();
@@ -275,18 +278,17 @@ pub fn setup(
         });
 
     // Spawn dice based on configuration
-    let dice_to_spawn = &dice_config.dice_to_roll;
+    let dice_to_spawn = dice_config.physical_dice();
     let num_dice = dice_to_spawn.len();
 
-    let mut rng = rand::rng();
     for (i, die_type) in dice_to_spawn.iter().enumerate() {
         let position = match *container_style {
             DiceContainerStyle::Box => calculate_dice_position(i, num_dice),
             DiceContainerStyle::Cup => {
                 // Spawn inside the cup and let gravity "drop" the dice.
                 let radius = 1.2;
-                let x = rng.random_range(-radius..radius);
-                let z = rng.random_range(-radius..radius);
+                let x = roll_rng.random_range(-radius..radius);
+                let z = roll_rng.random_range(-radius..radius);
                 Vec3::new(x, 1.25, z)
             }
         };
@@ -295,9 +297,11 @@ pub fn setup(
             &mut commands,
             &mut meshes,
             &mut materials,
+            &mut mesh_cache,
             *die_type,
             die_scale,
             position,
+            &mut *roll_rng,
         );
     }
 
@@ -1159,6 +1163,10 @@ pub fn setup(
         &theme,
         icon_font.0.clone(),
         settings_state.settings.quick_roll_panel_position,
+        None,
+        &settings_state.settings.custom_quick_rolls,
+        &QuickRollEditor::default(),
+        &DicePalette::default(),
     );
 
     // Spawn the settings button
@@ -1182,14 +1190,67 @@ pub fn calculate_dice_position(index: usize, total: usize) -> Vec3 {
     )
 }
 
-/// Spawn a single die entity with physics and number labels
+/// Distance between the tens and units d10 of a d100
+pub const PERCENTILE_PAIR_SPACING: f32 = 0.35;
+
+/// Spawn a single die entity with physics and number labels, thrown with a
+/// random spin and velocity drawn from `rng`.
+///
+/// A d100 is thrown as a tens and a units d10 side by side. The tens die is
+/// returned; the units die follows its throw (`match_percentile_throws`).
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_die(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    mesh_cache: &mut DiceMeshCache,
+    die_type: DiceType,
+    die_scale: f32,
+    position: Vec3,
+    rng: &mut impl Rng,
+) -> Entity {
+    if !die_type.is_percentile() {
+        return spawn_die_entity(
+            commands, meshes, materials, mesh_cache, die_type, die_scale, position, None, rng,
+        );
+    }
+
+    let offset = Vec3::X * PERCENTILE_PAIR_SPACING / 2.0;
+    let units = spawn_die_entity(
+        commands,
+        meshes,
+        materials,
+        mesh_cache,
+        DiceType::D10,
+        die_scale,
+        position + offset,
+        Some(PercentileDie::Units),
+        rng,
+    );
+    spawn_die_entity(
+        commands,
+        meshes,
+        materials,
+        mesh_cache,
+        DiceType::D10,
+        die_scale,
+        position - offset,
+        Some(PercentileDie::Tens { units }),
+        rng,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_die_entity(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    mesh_cache: &mut DiceMeshCache,
     die_type: DiceType,
     die_scale: f32,
     position: Vec3,
+    percentile: Option<PercentileDie>,
+    rng: &mut impl Rng,
 ) -> Entity {
     use crate::dice3d::meshes::get_d4_number_positions;
 
@@ -1202,15 +1263,17 @@ pub fn spawn_die(
         ..default()
     });
 
-    let mut rng = rand::rng();
-
     let angular_vel = Vec3::new(
         rng.random_range(-8.0..8.0),
         rng.random_range(-8.0..8.0),
         rng.random_range(-8.0..8.0),
     );
 
-    let (mesh, collider, face_normals) = create_die_mesh_and_collider(die_type);
+    let CachedDieMesh {
+        mesh,
+        collider,
+        face_normals,
+    } = mesh_cache.die(die_type, meshes);
 
     let throw_vel = Vec3::new(
         rng.random_range(-1.5..1.5),
@@ -1218,19 +1281,7 @@ pub fn spawn_die(
         rng.random_range(-1.5..1.5),
     );
 
-    let outline_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.0, 0.0, 0.0),
-        unlit: true,
-        alpha_mode: AlphaMode::Opaque,
-        ..default()
-    });
-
-    let label_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(1.0, 1.0, 1.0),
-        unlit: true,
-        alpha_mode: AlphaMode::Opaque,
-        ..default()
-    });
+    let (outline_material, label_material) = mesh_cache.label_materials(materials);
 
     let face_normals_clone = face_normals.clone();
 
@@ -1240,7 +1291,7 @@ pub fn spawn_die(
     let corrected_scale = die_scale * die_type.uniform_size_scale_factor();
 
     let mut entity_commands = commands.spawn((
-        Mesh3d(meshes.add(mesh)),
+        Mesh3d(mesh),
         MeshMaterial3d(die_material),
         Transform::from_translation(position)
             .with_rotation(Quat::from_euler(
@@ -1251,6 +1302,8 @@ pub fn spawn_die(
             ))
             .with_scale(Vec3::splat(corrected_scale)),
         RigidBody::Dynamic,
+        // Smooths rendering between steps when physics runs at a fixed rate.
+        TransformInterpolation::default(),
         // Prevent fast dice from tunneling through the walls/ceiling.
         Ccd::enabled(),
         collider,
@@ -1268,6 +1321,10 @@ pub fn spawn_die(
         },
     ));
 
+    if let Some(percentile) = percentile {
+        entity_commands.insert(percentile);
+    }
+
     let die_entity = entity_commands.id();
 
     entity_commands.with_children(|parent| {
@@ -1279,7 +1336,7 @@ pub fn spawn_die(
                 let normal = pos.normalize();
 
                 // Spawn black outline
-                let outline_mesh = create_number_mesh(value, meshes);
+                let outline_mesh = mesh_cache.number(value, meshes);
                 let outline_pos = pos - normal * 0.002;
                 parent.spawn((
                     Mesh3d(outline_mesh),
@@ -1290,7 +1347,7 @@ pub fn spawn_die(
                 ));
 
                 // Spawn white number
-                let label_mesh = create_number_mesh(value, meshes);
+                let label_mesh = mesh_cache.number(value, meshes);
                 parent.spawn((
                     Mesh3d(label_mesh),
                     MeshMaterial3d(label_material.clone()),
@@ -1307,8 +1364,23 @@ pub fn spawn_die(
                 let scale = get_label_scale(die_type);
                 let label_pos = *normal * offset;
 
+                // Percentile dice read 00-90 (tens) and 0-9 (units), coins H
+                // and T, and Fudge dice + and - with blank faces left bare
+                let number_mesh = match percentile {
+                    Some(PercentileDie::Tens { .. }) => mesh_cache.tens_number(*value, meshes),
+                    Some(PercentileDie::Units) => mesh_cache.number(*value % 10, meshes),
+                    None if die_type.is_coin() => mesh_cache.coin_letter(*value, meshes),
+                    None if die_type == DiceType::Fudge => {
+                        match mesh_cache.fudge_symbol(*value, meshes) {
+                            Some(symbol) => symbol,
+                            None => continue,
+                        }
+                    }
+                    None => mesh_cache.number(*value, meshes),
+                };
+
                 // Spawn black outline first
-                let outline_mesh = create_number_mesh(*value, meshes);
+                let outline_mesh = number_mesh.clone();
                 let outline_pos = *normal * (offset - 0.005);
                 parent.spawn((
                     Mesh3d(outline_mesh),
@@ -1319,9 +1391,8 @@ pub fn spawn_die(
                 ));
 
                 // Spawn white number on top
-                let label_mesh = create_number_mesh(*value, meshes);
                 parent.spawn((
-                    Mesh3d(label_mesh),
+                    Mesh3d(number_mesh),
                     MeshMaterial3d(label_material.clone()),
                     Transform::from_translation(label_pos)
                         .with_rotation(label_rotation)
@@ -1334,13 +1405,21 @@ pub fn spawn_die(
     die_entity
 }
 
-/// Spawn the quick roll panel on the right side of the dice roller view
+/// Spawn the quick roll panel on the right side of the dice roller view.
+///
+/// In edit mode the panel lists the custom buttons for reordering and
+/// removal, with a form for adding one, in place of the rolls.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_quick_roll_panel(
     commands: &mut Commands,
     character_data: &CharacterData,
     theme: &MaterialTheme,
     icon_font: Handle<Font>,
     position: UiPositionSetting,
+    dc: Option<i32>,
+    custom_rolls: &[CustomQuickRoll],
+    editor: &QuickRollEditor,
+    palette: &DicePalette,
 ) -> Entity {
     commands
         .spawn((
@@ -1419,16 +1498,110 @@ pub fn spawn_quick_roll_panel(
                             ..default()
                         })
                         .with_children(|card| {
-                            // Title
+                            // Title, with the edit mode toggle
+                            card.spawn(Node {
+                                width: Val::Percent(100.0),
+                                flex_direction: FlexDirection::Row,
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                row.spawn((
+                                    Text::new("Quick Rolls"),
+                                    TextFont {
+                                        font_size: 16.0,
+                                        ..default()
+                                    },
+                                    TextColor(theme.primary),
+                                ));
+                                let (icon, tooltip) = if editor.editing {
+                                    ("check", "Done")
+                                } else {
+                                    ("edit", "Customize buttons")
+                                };
+                                row.spawn((
+                                    IconButtonBuilder::new(icon).standard().build(theme),
+                                    TooltipTrigger::new(tooltip).bottom(),
+                                    QuickRollEditButton::Toggle,
+                                ))
+                                .insert(quick_roll_icon_button_node())
+                                .with_children(|btn| {
+                                    spawn_quick_roll_icon(
+                                        btn,
+                                        icon,
+                                        "...",
+                                        icon_font.clone(),
+                                        theme.primary,
+                                    );
+                                });
+                            });
+
+                            if editor.editing {
+                                spawn_quick_roll_editor(
+                                    card,
+                                    custom_rolls,
+                                    editor,
+                                    icon_font.clone(),
+                                    theme,
+                                );
+                                return;
+                            }
+
+                            // DC the checks below are compared with
+                            card.spawn(Node {
+                                width: Val::Percent(100.0),
+                                ..default()
+                            })
+                            .with_children(|slot| {
+                                let builder = TextFieldBuilder::new()
+                                    .outlined()
+                                    .label("DC")
+                                    .placeholder("none")
+                                    .value(dc.map(|dc| dc.to_string()).unwrap_or_default())
+                                    .auto_focus(false)
+                                    .width(Val::Percent(100.0));
+                                spawn_text_field_control_with(
+                                    slot,
+                                    theme,
+                                    builder,
+                                    QuickRollDcField,
+                                );
+                            });
+
+                            spawn_dice_palette(card, palette, theme);
+
+                            // Filled by `update_suggested_rolls_row`
                             card.spawn((
-                                Text::new("Quick Rolls"),
-                                TextFont {
-                                    font_size: 16.0,
+                                Node {
+                                    width: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(6.0),
                                     ..default()
                                 },
-                                TextColor(theme.primary),
+                                SuggestedRollsRow,
                             ));
 
+                            // The user's own buttons come first
+                            for (index, roll) in custom_rolls.iter().enumerate() {
+                                spawn_custom_quick_roll_button(
+                                    card,
+                                    index,
+                                    roll,
+                                    icon_font.clone(),
+                                    theme,
+                                );
+                            }
+
+                            // Available with or without a character
+                            spawn_quick_roll_button(
+                                card,
+                                "Flip a coin",
+                                QuickRollType::CoinFlip,
+                                icon_font.clone(),
+                                theme,
+                            );
+
                             if let Some(sheet) = &character_data.sheet {
                                 // Ability Checks section
                                 card.spawn((
@@ -1609,6 +1782,401 @@ fn spawn_quick_roll_button(
         });
 }
 
+/// Spawn the dice palette: a tile per die, with a badge counting how many
+/// are queued, and the buttons throwing or clearing the queued dice.
+fn spawn_dice_palette(
+    card: &mut ChildSpawnerCommands,
+    palette: &DicePalette,
+    theme: &MaterialTheme,
+) {
+    card.spawn((
+        Text::new("Dice"),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(6.0)),
+            ..default()
+        },
+    ));
+
+    card.spawn(Node {
+        width: Val::Percent(100.0),
+        flex_direction: FlexDirection::Row,
+        flex_wrap: FlexWrap::Wrap,
+        column_gap: Val::Px(6.0),
+        row_gap: Val::Px(6.0),
+        ..default()
+    })
+    .with_children(|tiles| {
+        for die in DicePalette::DICE {
+            let label = format!("d{}", die.max_value());
+            let count = palette.count(die);
+            tiles
+                .spawn((
+                    MaterialButtonBuilder::new(&label)
+                        .filled_tonal()
+                        .build(theme),
+                    TooltipTrigger::new("Click to add, right-click to remove").top(),
+                    DicePaletteDie(die),
+                ))
+                .insert(Node {
+                    width: Val::Px(50.0),
+                    height: Val::Px(34.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|tile| {
+                    tile.spawn((
+                        Text::new(label.clone()),
+                        TextFont {
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextColor(theme.on_secondary_container),
+                        ButtonLabel,
+                    ));
+                    tile.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(-5.0),
+                            right: Val::Px(-5.0),
+                            min_width: Val::Px(16.0),
+                            height: Val::Px(16.0),
+                            padding: UiRect::horizontal(Val::Px(3.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            display: if count > 0 {
+                                Display::Flex
+                            } else {
+                                Display::None
+                            },
+                            ..default()
+                        },
+                        BackgroundColor(theme.primary),
+                        BorderRadius::all(Val::Px(8.0)),
+                        DicePaletteBadge(die),
+                    ))
+                    .with_children(|badge| {
+                        badge.spawn((
+                            Text::new(count.to_string()),
+                            TextFont {
+                                font_size: 10.0,
+                                ..default()
+                            },
+                            TextColor(theme.on_primary),
+                        ));
+                    });
+                });
+        }
+    });
+
+    card.spawn((
+        Text::new(palette_summary_text(palette)),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(theme.on_surface),
+        DicePaletteSummary,
+    ));
+
+    card.spawn(Node {
+        width: Val::Percent(100.0),
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(6.0),
+        ..default()
+    })
+    .with_children(|buttons| {
+        for (label, button, filled) in [
+            ("Roll", DicePaletteButton::Roll, true),
+            ("Clear", DicePaletteButton::Clear, false),
+        ] {
+            let builder = MaterialButtonBuilder::new(label);
+            let (bundle, text_color) = if filled {
+                (builder.filled().build(theme), theme.on_primary)
+            } else {
+                (builder.text().build(theme), theme.primary)
+            };
+            buttons
+                .spawn((bundle, button))
+                .insert(Node {
+                    flex_grow: 1.0,
+                    height: Val::Px(28.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(text_color),
+                        ButtonLabel,
+                    ));
+                });
+        }
+    });
+}
+
+/// Spawn a custom quick roll button, in its color and with its icon
+fn spawn_custom_quick_roll_button(
+    parent: &mut ChildSpawnerCommands,
+    index: usize,
+    roll: &CustomQuickRoll,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    let color = roll
+        .color
+        .as_ref()
+        .map(ColorSetting::to_color)
+        .unwrap_or(theme.primary);
+
+    parent
+        .spawn((
+            MaterialButtonBuilder::new(&roll.label).text().build(theme),
+            TooltipTrigger::new(roll.expression.clone()).top(),
+            CustomQuickRollButton(index),
+        ))
+        .insert(Node {
+            width: Val::Percent(100.0),
+            height: Val::Px(28.0),
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::FlexStart,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            padding: UiRect::horizontal(Val::Px(8.0)),
+            ..default()
+        })
+        .with_children(|btn| {
+            if let Some(icon) = roll.icon.as_deref().and_then(MaterialIcon::from_name) {
+                btn.spawn((
+                    Text::new(icon.as_str()),
+                    TextFont {
+                        font: icon_font,
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
+            btn.spawn((
+                Text::new(roll.label.clone()),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+                ButtonLabel,
+            ));
+        });
+}
+
+/// Spawn the edit mode of the quick roll panel: a row per custom button,
+/// dragged by its handle or moved with its arrows, then the form adding one.
+fn spawn_quick_roll_editor(
+    card: &mut ChildSpawnerCommands,
+    custom_rolls: &[CustomQuickRoll],
+    editor: &QuickRollEditor,
+    icon_font: Handle<Font>,
+    theme: &MaterialTheme,
+) {
+    if custom_rolls.is_empty() {
+        card.spawn((
+            Text::new("No buttons of your own yet"),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(theme.on_surface_variant),
+        ));
+    }
+
+    for (index, roll) in custom_rolls.iter().enumerate() {
+        let color = roll
+            .color
+            .as_ref()
+            .map(ColorSetting::to_color)
+            .unwrap_or(theme.on_surface);
+        card.spawn((
+            Node {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(2.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            BorderRadius::all(Val::Px(6.0)),
+            RelativeCursorPosition::default(),
+            CustomQuickRollRow(index),
+        ))
+        .with_children(|row| {
+            row.spawn((
+                Button,
+                CustomQuickRollDragHandle(index),
+                quick_roll_icon_button_node(),
+                Interaction::None,
+                FocusPolicy::Block,
+            ))
+            .with_children(|handle| {
+                spawn_quick_roll_icon(
+                    handle,
+                    "drag_indicator",
+                    "::",
+                    icon_font.clone(),
+                    theme.on_surface_variant,
+                );
+            });
+            row.spawn((
+                Text::new(roll.label.clone()),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+                Node {
+                    flex_grow: 1.0,
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+            ));
+            for (icon, fallback, button) in [
+                ("arrow_upward", "^", QuickRollEditButton::MoveUp(index)),
+                ("arrow_downward", "v", QuickRollEditButton::MoveDown(index)),
+                ("delete", "x", QuickRollEditButton::Remove(index)),
+            ] {
+                row.spawn((IconButtonBuilder::new(icon).standard().build(theme), button))
+                    .insert(quick_roll_icon_button_node())
+                    .with_children(|btn| {
+                        spawn_quick_roll_icon(
+                            btn,
+                            icon,
+                            fallback,
+                            icon_font.clone(),
+                            theme.on_surface_variant,
+                        );
+                    });
+            }
+        });
+    }
+
+    card.spawn((
+        Text::new("Add a button"),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(theme.on_surface_variant),
+        Node {
+            margin: UiRect::top(Val::Px(6.0)),
+            ..default()
+        },
+    ));
+    for (field, label, placeholder, value) in [
+        (
+            QuickRollDraftField::Label,
+            "Label",
+            "Greatsword",
+            &editor.label,
+        ),
+        (
+            QuickRollDraftField::Expression,
+            "Dice",
+            "2d6+3 or 1d20+5 adv",
+            &editor.expression,
+        ),
+        (
+            QuickRollDraftField::Color,
+            "Color",
+            "#FF8844 or red",
+            &editor.color,
+        ),
+        (QuickRollDraftField::Icon, "Icon", "swords", &editor.icon),
+    ] {
+        card.spawn(Node {
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_children(|slot| {
+            let builder = TextFieldBuilder::new()
+                .outlined()
+                .label(label)
+                .placeholder(placeholder)
+                .value(value.clone())
+                .auto_focus(false)
+                .width(Val::Percent(100.0));
+            spawn_text_field_control_with(slot, theme, builder, field);
+        });
+    }
+    card.spawn((
+        MaterialButtonBuilder::new("Add").filled().build(theme),
+        QuickRollEditButton::Add,
+    ))
+    .with_children(|btn| {
+        btn.spawn((
+            Text::new("Add"),
+            TextFont {
+                font_size: 13.0,
+                ..default()
+            },
+            TextColor(theme.on_primary),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Node of the small icon buttons of the quick roll panel
+fn quick_roll_icon_button_node() -> Node {
+    Node {
+        width: Val::Px(24.0),
+        height: Val::Px(24.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+/// Spawn a Material icon, or `fallback` text when the font doesn't have it
+fn spawn_quick_roll_icon(
+    parent: &mut ChildSpawnerCommands,
+    icon_name: &str,
+    fallback: &str,
+    icon_font: Handle<Font>,
+    color: Color,
+) {
+    match MaterialIcon::from_name(icon_name) {
+        Some(icon) => {
+            parent.spawn((
+                Text::new(icon.as_str()),
+                TextFont {
+                    font: icon_font,
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(color),
+            ));
+        }
+        None => {
+            parent.spawn((
+                Text::new(fallback),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+            ));
+        }
+    }
+}
+
 /// Format skill name from camelCase to Title Case
 fn format_skill_name(name: &str) -> String {
     let mut result = String::new();
@@ -1625,7 +2193,8 @@ fn format_skill_name(name: &str) -> String {
     result
 }
 
-/// Rebuild the quick roll panel when character data changes
+/// Rebuild the quick roll panel when character data, its custom buttons or
+/// its edit mode change
 pub fn rebuild_quick_roll_panel(
     mut commands: Commands,
     character_data: Res<CharacterData>,
@@ -1633,11 +2202,20 @@ pub fn rebuild_quick_roll_panel(
     ui_state: Res<UiState>,
     settings_state: Res<SettingsState>,
     icon_font: Res<MaterialIconFont>,
+    quick_roll_dc: Res<QuickRollDc>,
+    editor: Res<QuickRollEditor>,
+    palette: Res<DicePalette>,
     panel_query: Query<Entity, With<QuickRollPanel>>,
+    mut shown: Local<(bool, Vec<CustomQuickRoll>)>,
 ) {
-    if !character_data.is_changed() && !theme.is_changed() {
+    // The custom buttons and edit mode, compared by value so typing in the
+    // editor's fields or saving other settings doesn't rebuild the panel
+    let custom_rolls = &settings_state.settings.custom_quick_rolls;
+    let layout_changed = shown.0 != editor.editing || shown.1 != *custom_rolls;
+    if !character_data.is_changed() && !theme.is_changed() && !layout_changed {
         return;
     }
+    *shown = (editor.editing, custom_rolls.clone());
 
     // Despawn existing panel (and its descendants)
     for entity in panel_query.iter() {
@@ -1651,6 +2229,10 @@ pub fn rebuild_quick_roll_panel(
         &theme,
         icon_font.0.clone(),
         settings_state.settings.quick_roll_panel_position,
+        quick_roll_dc.0,
+        custom_rolls,
+        &editor,
+        &palette,
     );
     commands
         .entity(panel)
@@ -0,0 +1,448 @@
+//! Stats tab
+//!
+//! Saves every resolved roll to the database and shows the statistics of the
+//! saved rolls (see `RollStats`): the face distribution of each die, the
+//! average d20, natural 20s and 1s, and each character's d20 luck per day.
+//! The tab is an extension tab (`STATS_TAB_ID`) added by `StatsPlugin`.
+//!
+//! Lifetime roll counts per die unlock worn dice finishes (`DiceWear`), which
+//! can be turned off from the tab.
+
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    format_day, lifetime_rolls, CharacterData, CharacterDatabase, DiceType, DiceWear, Die,
+    DieDistribution, ExtensionTabRoot, RollRecord, RollResolved, RollStats, SettingsState,
+    EXPECTED_D20_AVERAGE,
+};
+
+/// Id of the Stats tab
+pub const STATS_TAB_ID: &str = "stats";
+
+/// Most faces drawn as bars; bigger dice only show their averages
+const MAX_BAR_FACES: usize = 20;
+/// Height of the tallest bar
+const BAR_HEIGHT: f32 = 80.0;
+/// Days of luck shown per character
+const LUCK_DAYS_SHOWN: usize = 14;
+
+/// Saved rolls of the current profile
+#[derive(Resource, Default)]
+pub struct RollStatsState {
+    pub records: Vec<RollRecord>,
+}
+
+/// Node the statistics are spawned under, rebuilt when they change
+#[derive(Component)]
+pub struct StatsScreenBody;
+
+/// Button turning worn dice finishes on and off
+#[derive(Component)]
+pub struct WornDiceToggleButton;
+
+/// Load the saved rolls whenever the database is opened or the profile
+/// switches.
+pub fn load_roll_stats(db: Option<Res<CharacterDatabase>>, mut state: ResMut<RollStatsState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.records = db.load_rolls().unwrap_or_else(|e| {
+        warn!("{}", e);
+        Vec::new()
+    });
+}
+
+/// Save each resolved roll for the current character.
+///
+/// Announces when a die type reaches a new wear level.
+pub fn record_resolved_rolls(
+    mut resolved: MessageReader<RollResolved>,
+    db: Option<Res<CharacterDatabase>>,
+    character_data: Res<CharacterData>,
+    mut state: ResMut<RollStatsState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let character = character_data
+        .sheet
+        .as_ref()
+        .map(|sheet| sheet.character.name.as_str())
+        .unwrap_or("");
+    for roll in resolved.read() {
+        if roll.results.is_empty() {
+            continue;
+        }
+        let record = RollRecord::new(character, &roll.label, roll.results.clone(), roll.total);
+        if let Some(db) = &db {
+            if let Err(e) = db.record_roll(&record) {
+                warn!("{}", e);
+            }
+        }
+
+        let dice = distinct_dice(std::slice::from_ref(&record));
+        let before: Vec<u32> = dice
+            .iter()
+            .map(|die| lifetime_rolls(&state.records, *die))
+            .collect();
+        state.records.push(record);
+        for (die, before) in dice.iter().zip(before) {
+            let after = lifetime_rolls(&state.records, *die);
+            let wear = DiceWear::from_rolls(after);
+            if wear > DiceWear::from_rolls(before) {
+                snackbar.write(
+                    ShowSnackbar::message(format!(
+                        "Your {} is {} after {} rolls",
+                        die.name(),
+                        wear.label().to_lowercase(),
+                        after
+                    ))
+                    .duration(3.0),
+                );
+            }
+        }
+    }
+}
+
+/// Give newly spawned dice the worn finish their lifetime rolls unlocked.
+pub fn apply_dice_wear(
+    dice: Query<(&Die, &MeshMaterial3d<StandardMaterial>), Added<Die>>,
+    state: Res<RollStatsState>,
+    settings_state: Res<SettingsState>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings_state.settings.worn_dice {
+        return;
+    }
+    for (die, material) in &dice {
+        let wear = DiceWear::from_rolls(lifetime_rolls(&state.records, die.die_type));
+        if wear == DiceWear::New {
+            continue;
+        }
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        // Dull the polished crystal: fade the color and roughen the surface.
+        let amount = wear.amount();
+        let base = material.base_color.to_srgba();
+        let faded = base.mix(&Srgba::new(0.45, 0.42, 0.38, base.alpha), amount * 0.5);
+        material.base_color = Color::Srgba(faded);
+        material.perceptual_roughness += (0.85 - material.perceptual_roughness) * amount;
+        material.reflectance *= 1.0 - 0.6 * amount;
+    }
+}
+
+/// Turn worn dice finishes on or off.
+pub fn handle_worn_dice_toggle(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<(), With<WornDiceToggleButton>>,
+    mut settings_state: ResMut<SettingsState>,
+) {
+    for event in click_events.read() {
+        if buttons.get(event.entity).is_err() {
+            continue;
+        }
+        settings_state.settings.worn_dice = !settings_state.settings.worn_dice;
+        settings_state.is_modified = true;
+    }
+}
+
+/// Spawn the (hidden) Stats tab.
+pub fn setup_stats_screen(mut commands: Commands, theme: Option<Res<MaterialTheme>>) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(theme.surface),
+            Visibility::Hidden,
+            ExtensionTabRoot(STATS_TAB_ID),
+        ))
+        .with_children(|parent| {
+            spawn_stats_text(
+                parent,
+                "Roll Statistics".to_string(),
+                28.0,
+                theme.on_surface,
+            );
+            parent
+                .spawn((
+                    ScrollContainer::vertical(),
+                    ScrollPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        flex_basis: Val::Px(0.0),
+                        min_height: Val::Px(0.0),
+                        margin: UiRect::top(Val::Px(12.0)),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                ))
+                .with_children(|scroll| {
+                    scroll.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(16.0),
+                            padding: UiRect::right(Val::Px(18.0)),
+                            ..default()
+                        },
+                        StatsScreenBody,
+                    ));
+                });
+        });
+}
+
+/// Rebuild the statistics when the saved rolls or the worn dice setting
+/// change.
+pub fn update_stats_screen(
+    mut commands: Commands,
+    state: Res<RollStatsState>,
+    settings_state: Res<SettingsState>,
+    theme: Option<Res<MaterialTheme>>,
+    bodies: Query<Entity, With<StatsScreenBody>>,
+    mut shown_worn_dice: Local<Option<bool>>,
+) {
+    let worn_dice = settings_state.settings.worn_dice;
+    if !state.is_changed() && *shown_worn_dice == Some(worn_dice) {
+        return;
+    }
+    let Ok(body) = bodies.single() else {
+        return;
+    };
+    *shown_worn_dice = Some(worn_dice);
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let stats = RollStats::from_records(&state.records);
+
+    commands.entity(body).despawn_related::<Children>();
+    commands.entity(body).with_children(|body| {
+        if stats.rolls == 0 {
+            spawn_stats_text(
+                body,
+                "No rolls yet. Rolls made in the dice roller show up here.".to_string(),
+                16.0,
+                theme.on_surface_variant,
+            );
+            return;
+        }
+
+        spawn_stats_text(
+            body,
+            format!(
+                "{} rolls · {} d20 rolls · average d20 {} · natural 20s: {} · natural 1s: {}",
+                stats.rolls,
+                stats.d20_rolls,
+                stats
+                    .average_d20
+                    .map(|avg| format!("{:.2}", avg))
+                    .unwrap_or_else(|| "-".to_string()),
+                stats.natural_20s,
+                stats.natural_1s
+            ),
+            16.0,
+            theme.on_surface,
+        );
+
+        spawn_lifetime_dice(body, &state.records, worn_dice, &theme);
+
+        spawn_stats_text(body, "Distribution".to_string(), 20.0, theme.primary);
+        for distribution in &stats.distributions {
+            spawn_distribution(body, distribution, &theme);
+        }
+
+        if !stats.luck.is_empty() {
+            spawn_stats_text(body, "Luck over time".to_string(), 20.0, theme.primary);
+            for luck in &stats.luck {
+                let name = if luck.character.is_empty() {
+                    "No character"
+                } else {
+                    luck.character.as_str()
+                };
+                let color = if luck.luck() >= 0.0 {
+                    theme.primary
+                } else {
+                    theme.error
+                };
+                spawn_stats_text(
+                    body,
+                    format!(
+                        "{}: average d20 {:.2} ({:+.2}) over {} rolls",
+                        name,
+                        luck.average_d20,
+                        luck.luck(),
+                        luck.d20_rolls
+                    ),
+                    15.0,
+                    color,
+                );
+                let shown = luck.days.len().saturating_sub(LUCK_DAYS_SHOWN);
+                for point in &luck.days[shown..] {
+                    spawn_stats_text(
+                        body,
+                        format!(
+                            "    {}  {:>5.2}  {} ({} rolls)",
+                            format_day(point.day),
+                            point.average_d20,
+                            luck_marker(point.average_d20),
+                            point.d20_rolls
+                        ),
+                        13.0,
+                        theme.on_surface_variant,
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// `+` per point above a fair d20, `-` per point below.
+fn luck_marker(average_d20: f64) -> String {
+    let points = (average_d20 - EXPECTED_D20_AVERAGE).round() as i64;
+    let symbol = if points >= 0 { "+" } else { "-" };
+    symbol.repeat(points.unsigned_abs() as usize)
+}
+
+/// Lifetime rolls of each die, its wear and the worn dice toggle.
+fn spawn_lifetime_dice(
+    parent: &mut ChildSpawnerCommands,
+    records: &[RollRecord],
+    worn_dice: bool,
+    theme: &MaterialTheme,
+) {
+    spawn_stats_text(parent, "Lifetime dice".to_string(), 20.0, theme.primary);
+
+    let mut dice = distinct_dice(records);
+    dice.sort_by_key(|die| die.max_value());
+    for die in dice {
+        let rolls = lifetime_rolls(records, die);
+        let wear = DiceWear::from_rolls(rolls);
+        let next = match DiceWear::next(rolls) {
+            Some((threshold, next)) => format!(" · {} at {}", next.label(), threshold),
+            None => String::new(),
+        };
+        spawn_stats_text(
+            parent,
+            format!("{}: {} rolls · {}{}", die.name(), rolls, wear.label(), next),
+            15.0,
+            theme.on_surface,
+        );
+    }
+
+    let label = if worn_dice {
+        "Worn dice: On"
+    } else {
+        "Worn dice: Off"
+    };
+    parent
+        .spawn((
+            MaterialButtonBuilder::new(label).outlined().build(theme),
+            WornDiceToggleButton,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.primary),
+                ButtonLabel,
+            ));
+        });
+}
+
+/// Die types in the rolls, in the order first rolled.
+fn distinct_dice(records: &[RollRecord]) -> Vec<DiceType> {
+    let mut dice = Vec::new();
+    for (die, _) in records.iter().flat_map(|r| r.dice.iter()) {
+        if !dice.contains(die) {
+            dice.push(*die);
+        }
+    }
+    dice
+}
+
+fn spawn_distribution(
+    parent: &mut ChildSpawnerCommands,
+    distribution: &DieDistribution,
+    theme: &MaterialTheme,
+) {
+    spawn_stats_text(
+        parent,
+        format!(
+            "{}: {} rolls · average {:.2} (fair {:.1})",
+            distribution.die.name(),
+            distribution.rolls(),
+            distribution.average(),
+            distribution.expected_average()
+        ),
+        15.0,
+        theme.on_surface,
+    );
+    if distribution.counts.len() > MAX_BAR_FACES {
+        return;
+    }
+
+    let most = distribution
+        .counts
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::FlexEnd,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|bars| {
+            for (i, count) in distribution.counts.iter().enumerate() {
+                bars.spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    width: Val::Px(28.0),
+                    ..default()
+                })
+                .with_children(|column| {
+                    spawn_stats_text(column, count.to_string(), 10.0, theme.on_surface_variant);
+                    column.spawn((
+                        Node {
+                            width: Val::Px(20.0),
+                            height: Val::Px(BAR_HEIGHT * *count as f32 / most as f32),
+                            ..default()
+                        },
+                        BackgroundColor(theme.primary),
+                        BorderRadius::top(Val::Px(3.0)),
+                    ));
+                    spawn_stats_text(column, (i + 1).to_string(), 11.0, theme.on_surface);
+                });
+            }
+        });
+}
+
+fn spawn_stats_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
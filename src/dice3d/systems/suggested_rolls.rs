@@ -0,0 +1,261 @@
+//! Suggested rolls
+//!
+//! The "Suggested" row of the quick roll panel, worked out by
+//! `suggest_rolls` from the saved rolls whenever they or the character
+//! change. Skills, checks and saves are rolled like the panel's own buttons,
+//! initiative and spell damage from the sheet, and anything else like the
+//! last roll with that label this run; suggestions that can't be rolled are
+//! left out.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use super::dice_macros::{start_queued_roll, MacroPlaybackParams};
+use super::stats_screen::RollStatsState;
+use crate::dice3d::types::{
+    base_roll_label, damage_roll_label, now_seconds, parse_damage_label, suggest_rolls,
+    CharacterData, CharacterSheet, D20Test, DiceConfig, DiceType, QuickRollButton, QuickRollType,
+    RollRequested, RollSource, Ruleset, SettingsState, SuggestedRoll, SuggestedRollButton,
+    SuggestedRolls, SuggestedRollsRow, ABILITY_NAMES, INITIATIVE_LABEL, MAX_SUGGESTED_ROLLS,
+};
+
+/// How a suggested roll is rolled
+enum SuggestedRollAction {
+    QuickRoll(QuickRollType),
+    Dice(DiceConfig),
+}
+
+/// The quick roll button rolling `label` ("Perception", "Strength check",
+/// "Dexterity save"), if the sheet has one.
+fn quick_roll_type(label: &str, sheet: &CharacterSheet) -> Option<QuickRollType> {
+    let lower = label.to_ascii_lowercase();
+    if let Some(ability) = lower.strip_suffix(" check") {
+        return ABILITY_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(sheet.custom_attributes.keys().cloned())
+            .find(|name| name.eq_ignore_ascii_case(ability))
+            .map(QuickRollType::AbilityCheck);
+    }
+    if let Some(ability) = lower.strip_suffix(" save") {
+        return sheet
+            .saving_throws
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(ability))
+            .cloned()
+            .map(QuickRollType::SavingThrow);
+    }
+    sheet
+        .skills
+        .keys()
+        .find(|name| name.eq_ignore_ascii_case(&lower))
+        .cloned()
+        .map(QuickRollType::Skill)
+}
+
+fn suggested_roll_action(
+    label: &str,
+    sheet: Option<&CharacterSheet>,
+    recipes: &HashMap<String, DiceConfig>,
+    ruleset: Ruleset,
+    explosion_cap: u32,
+) -> Option<SuggestedRollAction> {
+    if let Some(sheet) = sheet {
+        if let Some(roll_type) = quick_roll_type(label, sheet) {
+            return Some(SuggestedRollAction::QuickRoll(roll_type));
+        }
+        if label.eq_ignore_ascii_case(INITIATIVE_LABEL) {
+            let config = DiceConfig {
+                dice_to_roll: vec![DiceType::D20],
+                modifier: sheet.combat.initiative,
+                modifier_name: INITIATIVE_LABEL.to_string(),
+                expression: None,
+            };
+            let effect = sheet.conditions.effect_on(&D20Test::AbilityCheck, ruleset);
+            return Some(SuggestedRollAction::Dice(
+                config.with_condition_effect(&effect),
+            ));
+        }
+    }
+    if let Some(config) = recipes.get(&label.to_lowercase()) {
+        return Some(SuggestedRollAction::Dice(config.clone()));
+    }
+
+    let (spell, _) = parse_damage_label(label)?;
+    let damage = sheet?.spell_damage_roll(&spell)?.ok()?;
+    let (dice_to_roll, expression) =
+        DiceConfig::from_expression(&damage.expression.with_explosion_cap(explosion_cap));
+    Some(SuggestedRollAction::Dice(DiceConfig {
+        dice_to_roll,
+        modifier: 0,
+        modifier_name: damage_roll_label(&spell, damage.damage_type.as_deref()),
+        expression,
+    }))
+}
+
+/// Remember each requested roll by its label, and work the suggestions out
+/// again when the saved rolls or the character change.
+pub fn update_suggested_rolls(
+    mut requested: MessageReader<RollRequested>,
+    stats: Option<Res<RollStatsState>>,
+    character_data: Res<CharacterData>,
+    settings_state: Res<SettingsState>,
+    ruleset: Res<Ruleset>,
+    mut suggested: ResMut<SuggestedRolls>,
+) {
+    for request in requested.read() {
+        let label = base_roll_label(&request.config.modifier_name);
+        if !label.is_empty() {
+            suggested
+                .bypass_change_detection()
+                .recipes
+                .insert(label.to_lowercase(), request.config.clone());
+        }
+    }
+
+    let stats_changed = stats.as_ref().is_some_and(|stats| stats.is_changed());
+    if !stats_changed && !character_data.is_changed() && !ruleset.is_changed() {
+        return;
+    }
+
+    let records = stats
+        .as_ref()
+        .map(|stats| stats.records.as_slice())
+        .unwrap_or(&[]);
+    let sheet = character_data.sheet.as_ref();
+    let character = sheet
+        .map(|sheet| sheet.character.name.as_str())
+        .unwrap_or("");
+    let explosion_cap = settings_state.settings.explosion_cap();
+
+    let suggestions: Vec<SuggestedRoll> = suggest_rolls(records, character, now_seconds())
+        .into_iter()
+        .filter_map(|label| {
+            let action =
+                suggested_roll_action(&label, sheet, &suggested.recipes, *ruleset, explosion_cap)?;
+            let quick_roll = match action {
+                SuggestedRollAction::QuickRoll(roll_type) => Some(roll_type),
+                SuggestedRollAction::Dice(_) => None,
+            };
+            Some(SuggestedRoll { label, quick_roll })
+        })
+        .take(MAX_SUGGESTED_ROLLS)
+        .collect();
+
+    if suggested.suggestions != suggestions {
+        suggested.suggestions = suggestions;
+    }
+}
+
+/// Fill the quick roll panel's "Suggested" row when it is spawned and
+/// whenever the suggestions change.
+pub fn update_suggested_rolls_row(
+    mut commands: Commands,
+    suggested: Res<SuggestedRolls>,
+    theme: Option<Res<MaterialTheme>>,
+    rows: Query<(Entity, Ref<SuggestedRollsRow>)>,
+) {
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    for (row, marker) in rows.iter() {
+        if !suggested.is_changed() && !marker.is_added() {
+            continue;
+        }
+        commands.entity(row).despawn_related::<Children>();
+        if suggested.suggestions.is_empty() {
+            continue;
+        }
+        commands.entity(row).with_children(|row| {
+            row.spawn((
+                Text::new("Suggested"),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(theme.on_surface_variant),
+                Node {
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                },
+            ));
+
+            for (index, suggestion) in suggested.suggestions.iter().enumerate() {
+                let mut button = row.spawn((
+                    MaterialButtonBuilder::new(&suggestion.label)
+                        .text()
+                        .build(&theme),
+                    SuggestedRollButton(index),
+                ));
+                // Skills, checks and saves roll like the panel's own buttons
+                if let Some(roll_type) = &suggestion.quick_roll {
+                    button.insert(QuickRollButton {
+                        roll_type: roll_type.clone(),
+                    });
+                }
+                button
+                    .insert(Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(28.0),
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::FlexStart,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(6.0),
+                        padding: UiRect::horizontal(Val::Px(8.0)),
+                        ..default()
+                    })
+                    .with_children(|btn| {
+                        btn.spawn((
+                            Text::new(suggestion.label.clone()),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            TextColor(theme.primary),
+                            ButtonLabel,
+                        ));
+                    });
+            }
+        });
+    }
+}
+
+/// Roll a clicked suggestion that isn't a quick roll button.
+pub fn handle_suggested_roll_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    buttons: Query<&SuggestedRollButton, Without<QuickRollButton>>,
+    suggested: Res<SuggestedRolls>,
+    ruleset: Res<Ruleset>,
+    mut params: MacroPlaybackParams,
+) {
+    for event in click_events.read() {
+        let Ok(button) = buttons.get(event.entity) else {
+            continue;
+        };
+        if params.settings_state.show_modal {
+            continue;
+        }
+        let Some(suggestion) = suggested.suggestions.get(button.0) else {
+            continue;
+        };
+        let explosion_cap = params.settings_state.settings.explosion_cap();
+        let action = suggested_roll_action(
+            &suggestion.label,
+            params.character_data.sheet.as_ref(),
+            &suggested.recipes,
+            *ruleset,
+            explosion_cap,
+        );
+        let Some(SuggestedRollAction::Dice(config)) = action else {
+            continue;
+        };
+        if params.roll_state.rolling || params.lid_ctrl.pending_roll.is_some() {
+            params
+                .snackbar
+                .write(ShowSnackbar::message("Wait for the dice to settle").duration(2.0));
+            continue;
+        }
+        start_queued_roll(&mut params, config, RollSource::QuickRoll);
+    }
+}
@@ -0,0 +1,303 @@
+//! Table client systems
+//!
+//! With `--join`, rolls from the host's table server are listed in a shared
+//! feed panel in the dice view, and inspiration the DM hands out reaches the
+//! loaded character, and an X-card tapped anywhere at the table pauses the
+//! scene. With `--replay-throws`, each roll is also
+//! thrown in the box as ghost dice in the roller's dice color, steered onto
+//! the faces they rolled; these dice never count toward a local roll.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy_material_ui::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::dice::steer_toward_face;
+use super::inspiration::receive_inspiration;
+use super::safety_tools::{receive_safety_signal, SafetySignalSent, SafetyState};
+use super::setup::{calculate_dice_position, spawn_die};
+use crate::dice3d::meshes::DiceMeshCache;
+use crate::dice3d::types::{
+    AppTab, CharacterData, DiceRollerRoot, DiceSpawned, Die, RemoteDie, SettingsState, SharedRoll,
+    TableClient, TableMessage, UiState,
+};
+
+/// Rolls kept in the feed panel
+const FEED_LENGTH: usize = 8;
+
+/// Seconds replayed dice stay in the box before they are cleared
+const REPLAY_SECONDS: f32 = 8.0;
+
+#[derive(Component)]
+pub struct TableFeedPanel;
+
+/// Rolls received from the table server, newest last
+#[derive(Resource, Default)]
+pub struct TableFeed {
+    pub rolls: VecDeque<SharedRoll>,
+    pub connected: bool,
+    /// Throw received rolls in the box (`--replay-throws`)
+    pub replay_throws: bool,
+    replayed: Vec<Entity>,
+    replayed_at: f32,
+}
+
+impl TableFeed {
+    pub fn new(replay_throws: bool) -> Self {
+        Self {
+            connected: true,
+            replay_throws,
+            ..default()
+        }
+    }
+}
+
+/// Take the rolls, inspiration and safety signals that arrived from the
+/// table server,
+/// replaying the newest roll in the box when that is turned on.
+#[allow(clippy::too_many_arguments)]
+pub fn receive_table_rolls(
+    mut commands: Commands,
+    client: Option<Res<TableClient>>,
+    mut feed: ResMut<TableFeed>,
+    mut character_data: ResMut<CharacterData>,
+    mut safety: ResMut<SafetyState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+    settings_state: Res<SettingsState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut mesh_cache: ResMut<DiceMeshCache>,
+    time: Res<Time>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+
+    let connected = client.is_connected();
+    if feed.connected != connected {
+        if !connected {
+            warn!("Table client: lost the connection to {}", client.url());
+        }
+        feed.connected = connected;
+    }
+
+    let mut rolls = Vec::new();
+    for message in client.receive() {
+        match message {
+            TableMessage::Roll(roll) => rolls.push(roll),
+            TableMessage::Inspiration { inspiration } => {
+                receive_inspiration(&inspiration, &mut character_data, &mut snackbar)
+            }
+            TableMessage::Safety { safety: signal } => {
+                receive_safety_signal(signal, &mut safety, &mut snackbar)
+            }
+        }
+    }
+    if rolls.is_empty() {
+        return;
+    }
+    for roll in &rolls {
+        info!("Table roll: {}", roll.feed_line());
+    }
+
+    if feed.replay_throws {
+        if let Some(roll) = rolls.iter().rev().find(|roll| !roll.dice.is_empty()) {
+            for entity in feed.replayed.drain(..) {
+                commands.entity(entity).despawn();
+            }
+            let color = roll
+                .color
+                .as_deref()
+                .and_then(|hex| Srgba::hex(hex).ok())
+                .map(Color::from);
+
+            // A d100 is thrown as a pair of d10s, which a replay can't steer
+            let dice: Vec<_> = roll
+                .dice
+                .iter()
+                .filter(|(die_type, _)| !die_type.is_percentile())
+                .collect();
+            for (i, (die_type, value)) in dice.iter().enumerate() {
+                let die_scale = settings_state.settings.dice_scales.scale_for(*die_type);
+                let entity = spawn_die(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &mut mesh_cache,
+                    *die_type,
+                    die_scale,
+                    calculate_dice_position(i, dice.len()),
+                    &mut rand::rng(),
+                );
+                let face_normals = mesh_cache.die(*die_type, &mut meshes).face_normals;
+                commands.entity(entity).remove::<Die>().insert(RemoteDie {
+                    face_normals,
+                    target: *value,
+                    color,
+                });
+                feed.replayed.push(entity);
+            }
+            feed.replayed_at = time.elapsed_secs();
+        }
+    }
+
+    feed.rolls.extend(rolls);
+    while feed.rolls.len() > FEED_LENGTH {
+        feed.rolls.pop_front();
+    }
+}
+
+/// Paint replayed dice in the roller's color.
+pub fn tint_remote_dice(
+    dice: Query<(&RemoteDie, &MeshMaterial3d<StandardMaterial>), Added<RemoteDie>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (die, material) in &dice {
+        let Some(color) = die.color else {
+            continue;
+        };
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.alpha_mode = if color.alpha() < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            };
+            material.base_color = color;
+        }
+    }
+}
+
+/// Steer replayed dice onto the faces the other player rolled, and clear
+/// them after a while or as soon as local dice are thrown.
+pub fn settle_remote_dice(
+    mut commands: Commands,
+    mut feed: ResMut<TableFeed>,
+    mut dice: Query<(&RemoteDie, &Transform, &mut Velocity)>,
+    mut spawned: MessageReader<DiceSpawned>,
+    time: Res<Time>,
+) {
+    if feed.replayed.is_empty() {
+        spawned.clear();
+        return;
+    }
+
+    let local_roll = spawned.read().count() > 0;
+    if local_roll || time.elapsed_secs() - feed.replayed_at > REPLAY_SECONDS {
+        for entity in feed.replayed.drain(..) {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let dt = time.delta_secs();
+    for (die, transform, mut velocity) in &mut dice {
+        steer_toward_face(&die.face_normals, die.target, transform, &mut velocity, dt);
+    }
+}
+
+/// Show the shared roll feed in the dice view while `--join` is running.
+pub fn manage_table_feed_panel(
+    mut commands: Commands,
+    client: Option<Res<TableClient>>,
+    feed: Res<TableFeed>,
+    ui_state: Res<UiState>,
+    theme: Option<Res<MaterialTheme>>,
+    panels: Query<Entity, With<TableFeedPanel>>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    if !feed.is_changed() && !panels.is_empty() {
+        return;
+    }
+
+    for entity in panels.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+    let status = if feed.connected {
+        format!("Table: {}", client.url())
+    } else {
+        "Table: disconnected".to_string()
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(110.0),
+                width: Val::Px(280.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(theme.surface_container),
+            BorderRadius::all(Val::Px(12.0)),
+            ZIndex(12),
+            if ui_state.active_tab == AppTab::DiceRoller {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            },
+            DiceRollerRoot,
+            TableFeedPanel,
+            Interaction::None,
+            FocusPolicy::Block,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(status),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(if feed.connected {
+                    theme.primary
+                } else {
+                    theme.error
+                }),
+            ));
+            if feed.rolls.is_empty() {
+                panel.spawn((
+                    Text::new("No rolls yet"),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface_variant),
+                ));
+            }
+            for roll in feed.rolls.iter().rev() {
+                panel.spawn((
+                    Text::new(roll.feed_line()),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    TextColor(theme.on_surface),
+                ));
+            }
+        });
+}
+
+/// Send an X-card tapped here to the host, who passes it on to the table.
+pub fn send_client_safety_signals(
+    client: Option<Res<TableClient>>,
+    mut sent: MessageReader<SafetySignalSent>,
+) {
+    let Some(client) = client else {
+        sent.clear();
+        return;
+    };
+
+    for SafetySignalSent(signal) in sent.read() {
+        if let Err(e) = client.send_safety_signal(*signal) {
+            warn!("Table client: {}", e);
+        }
+    }
+}
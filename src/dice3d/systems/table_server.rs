@@ -0,0 +1,94 @@
+//! Table server systems
+//!
+//! Sends each resolved roll to the `--serve` WebSocket subscribers, redacted
+//! the way the loaded character's sheet shows rolls to players, with the
+//! color of the first die so `--join` clients can replay the throw, the
+//! inspiration the DM hands out, and X-card pauses from anyone at the table.
+
+use bevy::prelude::*;
+
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use super::{receive_safety_signal, InspirationGranted, SafetySignalSent, SafetyState};
+use crate::dice3d::types::{
+    CharacterData, FieldPermissions, RollResolved, SettingsState, TableServer, Viewer,
+};
+
+/// Broadcast every resolved roll while `--serve` is running.
+pub fn broadcast_table_rolls(
+    server: Option<Res<TableServer>>,
+    character_data: Res<CharacterData>,
+    settings_state: Res<SettingsState>,
+    mut resolved: MessageReader<RollResolved>,
+) {
+    let Some(server) = server else {
+        resolved.clear();
+        return;
+    };
+
+    let sheet = character_data.sheet.as_ref();
+    let character = sheet.map(|sheet| sheet.character.name.as_str());
+    let default_permissions = FieldPermissions::default();
+    let permissions = sheet
+        .map(|sheet| &sheet.field_visibility)
+        .unwrap_or(&default_permissions);
+
+    for roll in resolved.read() {
+        if roll.results.is_empty() {
+            continue;
+        }
+        let Some(mut shared) = roll
+            .to_shared(character)
+            .for_viewer(permissions, Viewer::Players)
+        else {
+            continue;
+        };
+        if let Some((die, _)) = roll.results.first() {
+            let skin = settings_state.settings.dice_skin(*die);
+            shared.color = Some(skin.base_color.to_color().to_srgba().to_hex());
+        }
+        if let Err(e) = server.broadcast(&shared) {
+            warn!("Table server: {}", e);
+        }
+    }
+}
+
+/// Tell the table about inspiration the DM gave while `--serve` is running.
+pub fn broadcast_inspiration(
+    server: Option<Res<TableServer>>,
+    mut granted: MessageReader<InspirationGranted>,
+) {
+    let Some(server) = server else {
+        granted.clear();
+        return;
+    };
+
+    for InspirationGranted(grant) in granted.read() {
+        if let Err(e) = server.grant_inspiration(grant) {
+            warn!("Table server: {}", e);
+        }
+    }
+}
+
+/// Send an X-card tapped here to the table, and pause for one a subscriber
+/// tapped (the server has already passed it on to the others).
+pub fn exchange_safety_signals(
+    server: Option<Res<TableServer>>,
+    mut sent: MessageReader<SafetySignalSent>,
+    mut safety: ResMut<SafetyState>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let Some(server) = server else {
+        sent.clear();
+        return;
+    };
+
+    for SafetySignalSent(signal) in sent.read() {
+        if let Err(e) = server.send_safety_signal(*signal) {
+            warn!("Table server: {}", e);
+        }
+    }
+    for signal in server.receive_safety_signals() {
+        receive_safety_signal(signal, &mut safety, &mut snackbar);
+    }
+}
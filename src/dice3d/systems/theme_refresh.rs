@@ -4,6 +4,10 @@ use bevy_material_ui::prelude::{
     ScrollbarTrackVertical,
 };
 
+use crate::dice3d::types::{
+    SettingsState, ThemeTokens, ThemedBackground, ThemedBorder, ThemedText,
+};
+
 pub fn refresh_scrollbar_colors_on_theme_change(
     theme: Res<MaterialTheme>,
     mut parts: Query<
@@ -40,3 +44,43 @@ pub fn refresh_scrollbar_colors_on_theme_change(
         }
     }
 }
+
+/// Recolor widgets spawned with a theme token: all of them when the tokens
+/// change, new ones (spawned with the built-in color) as they appear.
+pub fn refresh_theme_token_colors(
+    tokens: Res<ThemeTokens>,
+    mut texts: Query<(Ref<ThemedText>, &mut TextColor)>,
+    mut backgrounds: Query<(Ref<ThemedBackground>, &mut BackgroundColor)>,
+    mut borders: Query<(Ref<ThemedBorder>, &mut BorderColor)>,
+) {
+    let all = tokens.is_changed();
+
+    for (themed, mut color) in texts.iter_mut() {
+        if all || themed.is_added() {
+            color.0 = tokens.color(themed.0);
+        }
+    }
+    for (themed, mut color) in backgrounds.iter_mut() {
+        if all || themed.is_added() {
+            color.0 = tokens.color(themed.token).with_alpha(themed.alpha);
+        }
+    }
+    for (themed, mut border) in borders.iter_mut() {
+        if all || themed.is_added() {
+            *border = BorderColor::all(tokens.color(themed.0));
+        }
+    }
+}
+
+/// Keep the tokens in line with the saved settings (loaded, switched
+/// profile, imported, or edits cancelled) while the settings modal is
+/// closed; while it is open the Theme tab previews its edits instead.
+pub fn sync_theme_tokens_from_settings(
+    settings_state: Res<SettingsState>,
+    mut tokens: ResMut<ThemeTokens>,
+) {
+    if !settings_state.is_changed() || settings_state.show_modal {
+        return;
+    }
+    tokens.set_if_neq(settings_state.settings.theme_tokens.clone());
+}
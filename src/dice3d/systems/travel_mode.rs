@@ -0,0 +1,393 @@
+//! Travel mode panel
+//!
+//! Opened with the `travel [party] [vs passive perception]` command. Shows a
+//! saved party's passive Perception line and group Stealth (slowest member
+//! highlighted) and rolls a group Stealth check against the monsters' passive
+//! Perception. The party buttons switch to another saved party (or `all`).
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    load_travel_party, CharacterDatabase, FocusTrap, GroupStealthResult, Parties, RollRng,
+    TravelParty, TypedCommand, ALL_CHARACTERS_PARTY, DEFAULT_MONSTER_PASSIVE_PERCEPTION,
+};
+
+#[derive(Component)]
+pub struct TravelModeOverlay;
+
+#[derive(Component)]
+pub struct TravelModeRollButton;
+
+#[derive(Component)]
+pub struct TravelModeCloseButton;
+
+/// Switches the panel to a saved party (or `all`).
+#[derive(Component)]
+pub struct TravelModePartyButton(pub String);
+
+/// State of the travel mode panel
+#[derive(Resource, Default)]
+pub struct TravelModePanel {
+    pub open: bool,
+    /// Saved party shown (or `all`); empty until the panel first opens
+    pub party_name: String,
+    pub party: TravelParty,
+    pub vs_passive_perception: i32,
+    pub last_result: Option<GroupStealthResult>,
+}
+
+/// A `travel` command typed into the command input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TravelCommand {
+    /// Saved party to travel with; the one shown last (or the first saved
+    /// party) when not given
+    pub party: Option<String>,
+    /// Monsters' passive Perception; [`DEFAULT_MONSTER_PASSIVE_PERCEPTION`]
+    /// when not given
+    pub vs_passive_perception: Option<i32>,
+}
+
+impl TypedCommand for TravelCommand {
+    const NAME: &'static str = "travel";
+
+    /// Parse `travel [party] [vs] [passive perception]` commands.
+    fn parse(cmd: &str) -> Option<Self> {
+        let mut words = cmd.split_whitespace();
+        if !words.next()?.eq_ignore_ascii_case("travel") {
+            return None;
+        }
+
+        let mut words: Vec<&str> = words.collect();
+        let vs_passive_perception = match words.last().and_then(|w| w.parse::<i32>().ok()) {
+            Some(vs) => {
+                words.pop();
+                if words.last().is_some_and(|w| w.eq_ignore_ascii_case("vs")) {
+                    words.pop();
+                }
+                Some(vs)
+            }
+            None if words.last().is_some_and(|w| w.eq_ignore_ascii_case("vs")) => return None,
+            None => None,
+        };
+        let party = (!words.is_empty()).then(|| words.join(" "));
+        Some(TravelCommand {
+            party,
+            vs_passive_perception,
+        })
+    }
+}
+
+/// Show `party` in the panel, returning why it couldn't be loaded.
+fn show_travel_party(
+    panel: &mut TravelModePanel,
+    db: &CharacterDatabase,
+    parties: &Parties,
+    party: &str,
+) -> Result<(), String> {
+    let loaded = load_travel_party(db, parties, party)?;
+    if loaded.members.is_empty() {
+        return Err("No characters to travel with".to_string());
+    }
+    panel.party_name = parties
+        .get(party)
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| ALL_CHARACTERS_PARTY.to_string());
+    panel.party = loaded;
+    panel.last_result = None;
+    Ok(())
+}
+
+/// Open the travel mode panel for a saved party on a `travel` command.
+pub fn run_travel_command(
+    In(command): In<TravelCommand>,
+    mut panel: ResMut<TravelModePanel>,
+    db: Res<CharacterDatabase>,
+    parties: Res<Parties>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let party = match command.party {
+        Some(party) => party,
+        None if !panel.party_name.is_empty() => panel.party_name.clone(),
+        None => parties.default_name().to_string(),
+    };
+    match show_travel_party(&mut panel, &db, &parties, &party) {
+        Ok(()) => {
+            panel.vs_passive_perception = command
+                .vs_passive_perception
+                .unwrap_or(DEFAULT_MONSTER_PASSIVE_PERCEPTION);
+            panel.open = true;
+        }
+        Err(e) => {
+            warn!("Failed to load travel party '{}': {}", party, e);
+            snackbar.write(ShowSnackbar::message(e).duration(2.0));
+        }
+    }
+}
+
+fn spawn_panel_text(parent: &mut ChildSpawnerCommands, text: String, size: f32, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn spawn_panel_button<M: Component>(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    filled: bool,
+    marker: M,
+    theme: &MaterialTheme,
+) {
+    let builder = MaterialButtonBuilder::new(label);
+    let (button, text_color) = if filled {
+        (builder.filled().build(theme), theme.on_primary)
+    } else {
+        (builder.outlined().build(theme), theme.primary)
+    };
+
+    parent.spawn((button, marker)).with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(text_color),
+            ButtonLabel,
+        ));
+    });
+}
+
+/// Show/hide and rebuild the travel mode panel when its state changes.
+pub fn manage_travel_mode_panel(
+    mut commands: Commands,
+    panel: Res<TravelModePanel>,
+    parties: Res<Parties>,
+    theme: Option<Res<MaterialTheme>>,
+    overlays: Query<Entity, With<TravelModeOverlay>>,
+) {
+    if !panel.is_changed() && !(panel.open && parties.is_changed()) {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !panel.open {
+        return;
+    }
+
+    let theme = theme.map(|t| t.clone()).unwrap_or_default();
+
+    let dialog = MaterialDialog::new()
+        .title("Travel Mode")
+        .open(true)
+        .modal(true);
+    let dialog_surface = dialog.surface_color(&theme);
+
+    let dialog_entity = commands
+        .spawn((
+            dialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(460.0),
+                padding: UiRect::all(Val::Px(Spacing::EXTRA_LARGE)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(dialog_surface),
+            BorderRadius::all(Val::Px(CornerRadius::EXTRA_LARGE)),
+            BoxShadow::default(),
+            ZIndex(10_000),
+        ))
+        .id();
+
+    let scrim_entity = commands
+        .spawn((
+            create_dialog_scrim_for(&theme, dialog_entity, true),
+            FocusTrap,
+            TravelModeOverlay,
+            ZIndex(9_999),
+        ))
+        .id();
+    commands.entity(scrim_entity).add_child(dialog_entity);
+
+    let party = &panel.party;
+    let slowest = party.slowest_index();
+
+    commands.entity(dialog_entity).with_children(|dialog| {
+        spawn_panel_text(dialog, "Travel Mode".to_string(), 20.0, theme.on_surface);
+
+        // Party picker: `all` and every saved party, the shown one filled
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|row| {
+                let names = std::iter::once(ALL_CHARACTERS_PARTY)
+                    .chain(parties.parties.iter().map(|p| p.name.as_str()));
+                for name in names {
+                    spawn_panel_button(
+                        row,
+                        name,
+                        name.eq_ignore_ascii_case(&panel.party_name),
+                        TravelModePartyButton(name.to_string()),
+                        &theme,
+                    );
+                }
+            });
+
+        // Passive Perception line
+        spawn_panel_text(
+            dialog,
+            "Passive Perception".to_string(),
+            14.0,
+            theme.primary,
+        );
+        for member in party.perception_line() {
+            spawn_panel_text(
+                dialog,
+                format!("{}  {}", member.passive_perception, member.name),
+                14.0,
+                theme.on_surface,
+            );
+        }
+
+        // Group Stealth: the slowest member sets the pace
+        spawn_panel_text(dialog, "Group Stealth".to_string(), 14.0, theme.primary);
+        for (index, member) in party.members.iter().enumerate() {
+            let is_slowest = slowest == Some(index);
+            spawn_panel_text(
+                dialog,
+                format!(
+                    "{:+}  {} ({} ft){}",
+                    member.stealth_modifier,
+                    member.name,
+                    member.speed,
+                    if is_slowest { "  ← slowest" } else { "" }
+                ),
+                14.0,
+                if is_slowest {
+                    theme.tertiary
+                } else {
+                    theme.on_surface
+                },
+            );
+        }
+
+        spawn_panel_text(
+            dialog,
+            format!(
+                "vs monsters' passive Perception {}",
+                panel.vs_passive_perception
+            ),
+            13.0,
+            theme.on_surface_variant,
+        );
+
+        if let Some(result) = panel.last_result.as_ref() {
+            let rolls = result
+                .rolls
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{} {}{}",
+                        r.name,
+                        r.total,
+                        if r.success { "" } else { " ✕" }
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            spawn_panel_text(dialog, rolls, 13.0, theme.on_surface_variant);
+            spawn_panel_text(
+                dialog,
+                format!(
+                    "{} ({}/{} succeeded)",
+                    if result.success {
+                        "The party slips by unnoticed"
+                    } else {
+                        "The party is spotted"
+                    },
+                    result.successes,
+                    result.rolls.len()
+                ),
+                16.0,
+                if result.success {
+                    theme.primary
+                } else {
+                    theme.error
+                },
+            );
+        }
+
+        dialog
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                spawn_panel_button(buttons, "Close", false, TravelModeCloseButton, &theme);
+                spawn_panel_button(
+                    buttons,
+                    "Roll Group Stealth",
+                    true,
+                    TravelModeRollButton,
+                    &theme,
+                );
+            });
+    });
+}
+
+/// Roll the group Stealth check, switch parties or close the panel.
+pub fn handle_travel_mode_clicks(
+    mut click_events: MessageReader<ButtonClickEvent>,
+    roll_buttons: Query<(), With<TravelModeRollButton>>,
+    close_buttons: Query<(), With<TravelModeCloseButton>>,
+    party_buttons: Query<&TravelModePartyButton>,
+    mut panel: ResMut<TravelModePanel>,
+    db: Option<Res<CharacterDatabase>>,
+    parties: Res<Parties>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    for event in click_events.read() {
+        if roll_buttons.get(event.entity).is_ok() {
+            let result = panel
+                .party
+                .roll_group_stealth(panel.vs_passive_perception, &mut *rng);
+            info!(
+                "Group Stealth vs {}: {}/{} succeeded",
+                result.vs_passive_perception,
+                result.successes,
+                result.rolls.len()
+            );
+            panel.last_result = Some(result);
+        } else if let Ok(TravelModePartyButton(party)) = party_buttons.get(event.entity) {
+            let Some(db) = db.as_deref() else {
+                continue;
+            };
+            if let Err(e) = show_travel_party(&mut panel, db, &parties, party) {
+                warn!("Failed to load travel party '{}': {}", party, e);
+                snackbar.write(ShowSnackbar::message(e).duration(2.0));
+            }
+        } else if close_buttons.get(event.entity).is_ok() {
+            panel.open = false;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Treasure hoard systems
+//!
+//! `hoard <cr>` in the command input rolls a treasure hoard for a monster of
+//! that challenge rating and adds it to the party's treasure ledger;
+//! `hoard ledger` sums up the ledger and `hoard clear` empties it.
+
+use bevy::prelude::*;
+use bevy_material_ui::prelude::ShowSnackbar;
+
+use crate::dice3d::types::{
+    CharacterDatabase, HoardTier, RollRng, TreasureHoard, TreasureLedger, TypedCommand,
+    TREASURE_LEDGER_SETTING_KEY,
+};
+
+/// The party's treasure ledger, as saved in the database
+#[derive(Resource, Default)]
+pub struct TreasureState {
+    pub ledger: TreasureLedger,
+}
+
+/// A `hoard` command typed into the command input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoardCommand {
+    Roll(HoardTier),
+    Ledger,
+    Clear,
+}
+
+impl TypedCommand for HoardCommand {
+    const NAME: &'static str = "hoard";
+
+    /// Parse `hoard <cr>`, `hoard ledger` and `hoard clear`.
+    fn parse(cmd: &str) -> Option<Self> {
+        let trimmed = cmd.trim();
+        let (head, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        if !head.eq_ignore_ascii_case("hoard") {
+            return None;
+        }
+
+        match rest.trim().to_lowercase().as_str() {
+            "" | "ledger" => Some(HoardCommand::Ledger),
+            "clear" => Some(HoardCommand::Clear),
+            cr => HoardTier::parse_cr(cr).map(HoardCommand::Roll),
+        }
+    }
+}
+
+fn save_ledger(ledger: &TreasureLedger, db: &CharacterDatabase) {
+    if let Err(e) = db.set_setting(TREASURE_LEDGER_SETTING_KEY, ledger.clone()) {
+        warn!("Failed to save the treasure ledger: {}", e);
+    }
+}
+
+/// Apply a hoard command, notifying the user.
+pub fn run_hoard_command(
+    In(command): In<HoardCommand>,
+    mut state: ResMut<TreasureState>,
+    db: Res<CharacterDatabase>,
+    mut rng: ResMut<RollRng>,
+    mut snackbar: MessageWriter<ShowSnackbar>,
+) {
+    let message = match command {
+        HoardCommand::Roll(tier) => {
+            let hoard = TreasureHoard::roll(tier, &mut *rng);
+            let summary = hoard.summary();
+            state.ledger.add(hoard);
+            save_ledger(&state.ledger, &db);
+            format!("{} (added to the treasure ledger)", summary)
+        }
+        HoardCommand::Ledger => state.ledger.summary(),
+        HoardCommand::Clear => {
+            state.ledger = TreasureLedger::default();
+            save_ledger(&state.ledger, &db);
+            "Treasure ledger cleared".to_string()
+        }
+    };
+    snackbar.write(ShowSnackbar::message(message).duration(10.0));
+}
+
+/// Load the treasure ledger whenever the database is opened or the profile
+/// switches.
+pub fn load_treasure_ledger(db: Option<Res<CharacterDatabase>>, mut state: ResMut<TreasureState>) {
+    let Some(db) = db else {
+        return;
+    };
+    if !db.is_changed() {
+        return;
+    }
+    state.ledger = db
+        .get_setting::<TreasureLedger>(TREASURE_LEDGER_SETTING_KEY)
+        .unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+        .unwrap_or_default();
+}
@@ -0,0 +1,53 @@
+//! Tween / animation systems
+//!
+//! Advances [`TransformTween`] components (scaled by the animation speed
+//! setting) and provides helpers for finding the scene nodes they drive.
+
+use bevy::prelude::*;
+
+use crate::dice3d::types::{SettingsState, TransformTween, TweenCompleted};
+
+/// Advance all transform tweens and write the sampled pose.
+pub fn advance_transform_tweens(
+    time: Res<Time>,
+    settings_state: Res<SettingsState>,
+    mut tweens: Query<(Entity, &mut TransformTween, &mut Transform)>,
+    mut completed: MessageWriter<TweenCompleted>,
+) {
+    let delta = time.delta_secs() * settings_state.settings.animation_speed();
+
+    for (entity, mut tween, mut transform) in tweens.iter_mut() {
+        if tween.finished {
+            continue;
+        }
+        let just_finished = tween.tick(delta);
+        *transform = tween.current();
+        if just_finished {
+            completed.write(TweenCompleted { entity });
+        }
+    }
+}
+
+/// Find the entity named `name` in the hierarchy under `root`.
+///
+/// glTF nodes are spawned with their node name, so this is how features find
+/// the part of a scene they want to tween (e.g. the dice box lid).
+pub(crate) fn find_named_entity_under(
+    root: Entity,
+    name: &str,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) -> Option<Entity> {
+    let mut stack: Vec<Entity> = vec![root];
+    while let Some(e) = stack.pop() {
+        if names.get(e).is_ok_and(|n| n.as_str() == name) {
+            return Some(e);
+        }
+        if let Ok(ch) = children.get(e) {
+            for child in ch.iter() {
+                stack.push(child);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,285 @@
+//! Keyboard and gamepad navigation
+//!
+//! Moves `UiFocus` between the clickable elements on screen and presses them
+//! without a mouse:
+//!
+//! | Keyboard | Gamepad | Action |
+//! |----------|---------|--------|
+//! | Tab / Shift+Tab | D-pad | Move the focus ring |
+//! | Arrow keys (ring shown) | D-pad | Move the ring that way, or nudge a focused slider |
+//! | Enter / Space (ring shown) | A | Press the focused element |
+//! | Escape | B | Hide the ring |
+//! | Ctrl+Tab / Ctrl+Shift+Tab | RB / LB | Next / previous app tab |
+//!
+//! The app tab shortcuts can be remapped in the Keys tab of the settings
+//! (see `KeyBindings`). Moving the mouse hides the ring again. Keys are left
+//! alone while a text field has focus so typing still works.
+
+use bevy::picking::Pickable;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, UiGlobalTransform};
+use bevy::window::PrimaryWindow;
+use bevy_material_ui::prelude::*;
+
+use crate::dice3d::types::{
+    move_focus, nudge_slider, AppTab, AppTabButton, FocusMove, FocusRing, FocusTrap, KeyAction,
+    SettingsState, TabButton, UiFocus, UiState,
+};
+
+/// Width of the focus ring in logical pixels
+const RING_WIDTH: f32 = 3.0;
+
+/// Nodes covering more than this share of the window both ways are panels
+/// and backdrops rather than controls, and are passed over
+const MAX_FOCUSABLE_SHARE: f32 = 0.9;
+
+/// Move focus, press the focused element and switch app tabs from the
+/// keyboard or a gamepad.
+///
+/// Runs after Bevy's UI focus pass so a press set here is seen by this
+/// frame's click handlers; it is released again on the next frame.
+pub fn navigate_ui_focus(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut cursor_moved: MessageReader<CursorMoved>,
+    mut focus: ResMut<UiFocus>,
+    mut ui_state: ResMut<UiState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    focusables: Query<
+        (
+            Entity,
+            &ComputedNode,
+            &UiGlobalTransform,
+            &InheritedVisibility,
+        ),
+        (
+            Or<(With<Interaction>, With<MaterialSlider>)>,
+            Without<FocusRing>,
+            Without<FocusTrap>,
+        ),
+    >,
+    traps: Query<(), With<FocusTrap>>,
+    parents: Query<&ChildOf>,
+    mut interactions: Query<&mut Interaction>,
+    mut sliders: Query<&mut MaterialSlider>,
+    text_fields: Query<&MaterialTextField>,
+    app_tabs: Query<(&TabButton, &AppTabButton)>,
+    settings_state: Res<SettingsState>,
+    mut slider_changes: MessageWriter<SliderChangeEvent>,
+) {
+    // Release last frame's press
+    if let Some(pressed) = focus.pressed.take() {
+        if let Ok(mut interaction) = interactions.get_mut(pressed) {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    if cursor_moved.read().count() > 0 {
+        focus.visible = false;
+    }
+
+    let typing = text_fields
+        .iter()
+        .any(|field| field.focused && !field.disabled);
+    let key = |code: KeyCode| !typing && keyboard.just_pressed(code);
+    let pad = |button: GamepadButton| gamepads.iter().any(|g| g.just_pressed(button));
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let arrows = focus.visible;
+
+    let bindings = &settings_state.settings.key_bindings;
+    let shortcut = |action: KeyAction| !typing && bindings.just_pressed(action, &keyboard);
+    let tab_step = if shortcut(KeyAction::NextTab) || pad(GamepadButton::RightTrigger) {
+        Some(1)
+    } else if shortcut(KeyAction::PreviousTab) || pad(GamepadButton::LeftTrigger) {
+        Some(-1)
+    } else {
+        None
+    };
+    let hide = key(KeyCode::Escape) || pad(GamepadButton::East);
+    let step = if key(KeyCode::Tab) {
+        Some(if shift {
+            FocusMove::Previous
+        } else {
+            FocusMove::Next
+        })
+    } else if pad(GamepadButton::DPadUp) || (arrows && key(KeyCode::ArrowUp)) {
+        Some(FocusMove::Up)
+    } else if pad(GamepadButton::DPadDown) || (arrows && key(KeyCode::ArrowDown)) {
+        Some(FocusMove::Down)
+    } else if pad(GamepadButton::DPadLeft) || (arrows && key(KeyCode::ArrowLeft)) {
+        Some(FocusMove::Left)
+    } else if pad(GamepadButton::DPadRight) || (arrows && key(KeyCode::ArrowRight)) {
+        Some(FocusMove::Right)
+    } else {
+        None
+    };
+    let activate =
+        focus.visible && (key(KeyCode::Enter) || key(KeyCode::Space) || pad(GamepadButton::South));
+
+    if let Some(tab_step) = tab_step {
+        let mut tabs: Vec<(usize, AppTab)> = app_tabs
+            .iter()
+            .map(|(button, app_tab)| (app_tab.index, button.tab))
+            .collect();
+        tabs.sort_by_key(|(index, _)| *index);
+        if let Some(current) = tabs.iter().position(|(_, tab)| *tab == ui_state.active_tab) {
+            let next = (current as i32 + tab_step).rem_euclid(tabs.len() as i32) as usize;
+            ui_state.active_tab = tabs[next].1;
+            focus.focused = None;
+        }
+        for action in [KeyAction::NextTab, KeyAction::PreviousTab] {
+            if let Some(chord) = bindings.chord(action) {
+                keyboard.clear_just_pressed(chord.key);
+            }
+        }
+        return;
+    }
+    if hide {
+        focus.visible = false;
+        return;
+    }
+    if step.is_none() && !activate {
+        return;
+    }
+
+    // Everything clickable on screen, kept inside an open dialog
+    let window_size = windows
+        .single()
+        .map(|window| {
+            Vec2::new(
+                window.physical_width() as f32,
+                window.physical_height() as f32,
+            )
+        })
+        .unwrap_or(Vec2::INFINITY);
+    let trapped = !traps.is_empty();
+    let targets: Vec<(Entity, Rect)> = focusables
+        .iter()
+        .filter(|(_, computed, _, visibility)| {
+            let size = computed.size();
+            visibility.get()
+                && size.x >= 1.0
+                && size.y >= 1.0
+                && !(size.x > window_size.x * MAX_FOCUSABLE_SHARE
+                    && size.y > window_size.y * MAX_FOCUSABLE_SHARE)
+        })
+        .filter(|(entity, ..)| {
+            !trapped
+                || parents
+                    .iter_ancestors(*entity)
+                    .any(|ancestor| traps.contains(ancestor))
+        })
+        .map(|(entity, computed, transform, _)| {
+            (
+                entity,
+                Rect::from_center_size(transform.translation, computed.size()),
+            )
+        })
+        .collect();
+    let on_screen = |entity: Entity| targets.iter().any(|(target, _)| *target == entity);
+
+    if let Some(step) = step {
+        for code in [
+            KeyCode::Tab,
+            KeyCode::ArrowUp,
+            KeyCode::ArrowDown,
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowRight,
+        ] {
+            keyboard.clear_just_pressed(code);
+        }
+
+        let current = focus.focused.filter(|entity| on_screen(*entity));
+        let nudge = match step {
+            FocusMove::Left => -1,
+            FocusMove::Right => 1,
+            _ => 0,
+        };
+        if let Some(entity) = current.filter(|_| focus.visible && nudge != 0) {
+            if let Ok(mut slider) = sliders.get_mut(entity) {
+                let value = nudge_slider(slider.value, slider.min, slider.max, nudge);
+                slider.value = value;
+                slider_changes.write(SliderChangeEvent { entity, value });
+                return;
+            }
+        }
+
+        // The first move only brings back a ring that was hidden
+        focus.focused = if focus.visible || current.is_none() {
+            move_focus(current, &targets, step)
+        } else {
+            current
+        };
+        focus.visible = focus.focused.is_some();
+    }
+
+    if activate {
+        keyboard.clear_just_pressed(KeyCode::Enter);
+        keyboard.clear_just_pressed(KeyCode::Space);
+        let Some(entity) = focus.focused.filter(|entity| on_screen(*entity)) else {
+            return;
+        };
+        if let Ok(mut interaction) = interactions.get_mut(entity) {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(entity);
+        }
+    }
+}
+
+/// Draw the focus ring around the focused element while it is shown.
+pub fn update_focus_ring(
+    mut commands: Commands,
+    focus: Res<UiFocus>,
+    theme: Option<Res<MaterialTheme>>,
+    targets: Query<(&ComputedNode, &UiGlobalTransform, &InheritedVisibility), Without<FocusRing>>,
+    mut rings: Query<(&mut Node, &mut BorderColor), With<FocusRing>>,
+) {
+    let color = theme.map(|theme| theme.primary).unwrap_or(Color::WHITE);
+    let Ok((mut node, mut border)) = rings.single_mut() else {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                border: UiRect::all(Val::Px(RING_WIDTH)),
+                ..default()
+            },
+            BorderColor::all(color),
+            BorderRadius::all(Val::Px(8.0)),
+            FocusPolicy::Pass,
+            Pickable::IGNORE,
+            ZIndex(20_000),
+            FocusRing,
+        ));
+        return;
+    };
+
+    let shown = focus
+        .focused
+        .filter(|_| focus.visible)
+        .and_then(|entity| targets.get(entity).ok())
+        .filter(|(_, _, visibility)| visibility.get());
+    let Some((computed, transform, _)) = shown else {
+        if node.display != Display::None {
+            node.display = Display::None;
+        }
+        return;
+    };
+
+    // Layout works in physical pixels, `Val::Px` in logical ones
+    let inverse_scale = computed.inverse_scale_factor();
+    let size = computed.size() * inverse_scale;
+    let center = transform.translation * inverse_scale;
+    let gap = RING_WIDTH + 2.0;
+    node.set_if_neq(Node {
+        position_type: PositionType::Absolute,
+        left: Val::Px(center.x - size.x * 0.5 - gap),
+        top: Val::Px(center.y - size.y * 0.5 - gap),
+        width: Val::Px(size.x + gap * 2.0),
+        height: Val::Px(size.y + gap * 2.0),
+        border: UiRect::all(Val::Px(RING_WIDTH)),
+        ..default()
+    });
+    border.set_if_neq(BorderColor::all(color));
+}
@@ -0,0 +1,75 @@
+//! UI scale systems
+//!
+//! Bevy lays the UI out in logical pixels, so it already follows each monitor's
+//! scale factor. These systems apply the manual UI scale override from settings
+//! and, when the window moves to a monitor with a different scale factor, pull
+//! the draggable panels back inside the (now differently sized) window.
+
+use bevy::prelude::*;
+use bevy::ui::UiScale;
+use bevy::window::{PrimaryWindow, WindowScaleFactorChanged};
+
+use crate::dice3d::types::{
+    CommandHistoryPanelRoot, DiceBoxControlsPanelRoot, QuickRollPanel, ResultsPanelRoot,
+    SettingsState, SliderGroupRoot,
+};
+
+/// Height of the app tab bar; panels are kept below it.
+const TAB_BAR_HEIGHT: f32 = 48.0;
+
+type DraggablePanelFilter = Or<(
+    With<SliderGroupRoot>,
+    With<QuickRollPanel>,
+    With<CommandHistoryPanelRoot>,
+    With<ResultsPanelRoot>,
+    With<DiceBoxControlsPanelRoot>,
+)>;
+
+/// Apply the UI scale setting and keep panels on screen after DPI changes.
+pub fn apply_ui_scale(
+    settings_state: Res<SettingsState>,
+    mut scale_changes: MessageReader<WindowScaleFactorChanged>,
+    mut ui_scale: ResMut<UiScale>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut panels: Query<(&mut Node, &ComputedNode), DraggablePanelFilter>,
+) {
+    let mut dpi_changed = false;
+    for event in scale_changes.read() {
+        info!("Window scale factor changed to {:.2}", event.scale_factor);
+        dpi_changed = true;
+    }
+
+    let desired = settings_state.settings.ui_scale();
+    let scale_changed = (ui_scale.0 - desired).abs() > f32::EPSILON;
+    if scale_changed {
+        ui_scale.0 = desired;
+    }
+
+    if !(dpi_changed || scale_changed) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    // Panel positions are in UI units, so divide the logical window size by the UI scale.
+    let win_w = window.resolution.width() / desired;
+    let win_h = window.resolution.height() / desired;
+
+    for (mut node, computed) in panels.iter_mut() {
+        let size = computed.size() * computed.inverse_scale_factor();
+        if let Val::Px(left) = node.left {
+            let clamped = left.clamp(0.0, (win_w - size.x).max(0.0));
+            if clamped != left {
+                node.left = Val::Px(clamped);
+            }
+        }
+        if let Val::Px(top) = node.top {
+            let clamped = top.clamp(TAB_BAR_HEIGHT, (win_h - size.y).max(TAB_BAR_HEIGHT));
+            if clamped != top {
+                node.top = Val::Px(clamped);
+            }
+        }
+    }
+}
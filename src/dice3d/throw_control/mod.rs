@@ -2,7 +2,9 @@
 //!
 //! This module handles mouse-controlled dice throwing mechanics.
 //! The mouse position over the dice box determines the direction
-//! and strength of dice throws. A 3D arrow indicates the throw target.
+//! and strength of dice throws, and holding Shift raises the throw into a
+//! lob. A dotted line traces the predicted arc to a 3D arrow where the dice
+//! come down.
 
 mod state;
 mod systems;
@@ -27,6 +27,22 @@ pub const BOX_WALL_HEIGHT: f32 = 1.5;
 pub const BOX_TOP_Y: f32 = BOX_FLOOR_Y + BOX_WALL_HEIGHT;
 pub const BOX_CENTER: Vec3 = Vec3::new(0.0, 0.0, 0.0);
 
+/// Launch angle of a full lob, above the horizontal (radians)
+pub const MAX_LOB_ANGLE: f32 = 0.95;
+
+/// How fast the arc rises while the lob modifier is held, and falls back
+/// to a skim once it is let go (full range per second)
+pub const ELEVATION_RATE: f32 = 2.5;
+
+/// Where the throw preview starts: the middle of the spawned dice
+pub const THROW_ORIGIN: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+/// Gravity the throw preview assumes (Rapier's default)
+pub const THROW_GRAVITY: f32 = 9.81;
+
+/// Points sampled along the predicted arc
+pub const TRAJECTORY_SAMPLES: usize = 16;
+
 /// Resource for tracking mouse-controlled throw state
 #[derive(Resource)]
 pub struct ThrowControlState {
@@ -44,6 +60,10 @@ pub struct ThrowControlState {
 
     /// Whether the mouse is currently over the dice box
     pub mouse_over_box: bool,
+
+    /// How high the throw is launched: 0.0 skims the dice across the floor,
+    /// 1.0 lobs them at [`MAX_LOB_ANGLE`]
+    pub elevation: f32,
 }
 
 impl Default for ThrowControlState {
@@ -54,6 +74,7 @@ impl Default for ThrowControlState {
             max_strength: 8.0,
             min_strength: 2.0,
             mouse_over_box: false,
+            elevation: 0.0,
         }
     }
 }
@@ -78,11 +99,60 @@ impl ThrowControlState {
             Vec3::new(0.0, 0.0, -1.0)
         };
 
-        Vec3::new(
+        let skim = Vec3::new(
             dir.x * strength,
             -0.3 * strength.min(3.0), // Slight downward component, capped
             dir.z * strength,
-        )
+        );
+        if self.elevation <= 0.0 {
+            return skim;
+        }
+
+        // Raise the same throw from the skim's angle towards a lob
+        let speed = skim.length();
+        let skim_angle = skim.y.atan2(strength);
+        let angle = skim_angle + (MAX_LOB_ANGLE - skim_angle) * self.elevation.min(1.0);
+        let horizontal = speed * angle.cos();
+        Vec3::new(dir.x * horizontal, speed * angle.sin(), dir.z * horizontal)
+    }
+
+    /// Points along the arc of a die thrown from `origin` with
+    /// [`Self::calculate_throw_velocity`], ending where it lands on the
+    /// floor or reaches a wall `half_extent` from the center, whichever
+    /// comes first. Ignores spin, bounces and the dice knocking together.
+    pub fn predict_trajectory(&self, origin: Vec3, half_extent: f32) -> Vec<Vec3> {
+        let velocity = self.calculate_throw_velocity();
+        let position_at = |t: f32| origin + velocity * t - Vec3::Y * (0.5 * THROW_GRAVITY * t * t);
+
+        // Landing: origin.y + vy*t - g*t^2/2 = floor
+        let drop = origin.y - BOX_FLOOR_Y;
+        let landing = (velocity.y + (velocity.y * velocity.y + 2.0 * THROW_GRAVITY * drop).sqrt())
+            / THROW_GRAVITY;
+
+        // Reaching a wall, for a throw fast enough to get there first
+        let horizontal = Vec2::new(velocity.x, velocity.z);
+        let reach = (half_extent - Vec2::new(origin.x, origin.z).length()).max(0.0);
+        let at_wall = if horizontal.length() > 0.001 {
+            reach / horizontal.length()
+        } else {
+            f32::INFINITY
+        };
+
+        let end = landing.min(at_wall).max(0.0);
+        (0..TRAJECTORY_SAMPLES)
+            .map(|i| position_at(end * i as f32 / (TRAJECTORY_SAMPLES - 1) as f32))
+            .collect()
+    }
+
+    /// Raise the arc while the lob modifier is held and lower it again
+    /// once it is let go.
+    pub fn step_elevation(&mut self, lob_held: bool, delta_secs: f32) {
+        let step = ELEVATION_RATE * delta_secs;
+        self.elevation = if lob_held {
+            (self.elevation + step).min(1.0)
+        } else {
+            (self.elevation - step).max(0.0)
+        };
     }
 
     /// Calculate angular velocity based on throw strength
@@ -127,6 +197,11 @@ pub struct StrengthSlider;
 #[derive(Component)]
 pub struct ThrowDirectionArrow;
 
+/// One of the dots tracing the predicted arc of the throw, `0` nearest
+/// the dice
+#[derive(Component)]
+pub struct ThrowTrajectoryDot(pub usize);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,4 +230,49 @@ mod tests {
         assert_eq!(clamped.x, BOX_MAX_X);
         assert_eq!(clamped.z, BOX_MIN_Z);
     }
+
+    #[test]
+    fn test_elevation_raises_the_arc() {
+        let mut state = ThrowControlState {
+            target_point: Vec3::new(0.0, 0.0, -2.0),
+            ..default()
+        };
+        let skim = state.calculate_throw_velocity();
+        assert!(skim.y < 0.0);
+
+        state.step_elevation(true, 1.0);
+        assert_eq!(state.elevation, 1.0);
+        let lob = state.calculate_throw_velocity();
+        assert!(lob.y > 0.0);
+        // Same speed, launched higher
+        assert!((lob.length() - skim.length()).abs() < 1e-3);
+        assert!((lob.y / lob.z.abs() - MAX_LOB_ANGLE.tan()).abs() < 1e-3);
+
+        state.step_elevation(false, 1.0);
+        assert_eq!(state.elevation, 0.0);
+        assert_eq!(state.calculate_throw_velocity(), skim);
+    }
+
+    #[test]
+    fn test_predicted_arc_rises_then_lands() {
+        let mut state = ThrowControlState {
+            target_point: Vec3::new(0.0, 0.0, -2.0),
+            max_strength: 1.0,
+            ..default()
+        };
+        let skim = state.predict_trajectory(THROW_ORIGIN, BOX_HALF_EXTENT);
+        assert_eq!(skim.len(), TRAJECTORY_SAMPLES);
+        assert_eq!(skim[0], THROW_ORIGIN);
+        assert!(skim.windows(2).all(|w| w[1].y <= w[0].y));
+        assert!((skim.last().unwrap().y - BOX_FLOOR_Y).abs() < 1e-3);
+
+        state.elevation = 1.0;
+        let lob = state.predict_trajectory(THROW_ORIGIN, BOX_HALF_EXTENT);
+        let peak = lob.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!(peak > THROW_ORIGIN.y);
+        // Heading away from the camera, and stopped at the wall at the latest
+        assert!(lob
+            .iter()
+            .all(|p| p.z <= 0.0 && p.z >= -BOX_HALF_EXTENT - 1e-3));
+    }
 }
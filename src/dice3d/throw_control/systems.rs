@@ -1,7 +1,8 @@
 //! Throw Control Systems
 //!
 //! Contains systems for tracking mouse position, raycasting to the box floor,
-//! updating the 3D arrow indicator, and handling the strength slider.
+//! raising the throw into a lob while Shift is held, drawing the predicted
+//! arc and landing arrow, and handling the strength slider.
 
 use super::state::*;
 use bevy::prelude::*;
@@ -178,50 +179,97 @@ pub fn update_throw_from_mouse(
         (Vec2::new(target.x, target.z).length() / max_distance.max(0.0001)).clamp(0.0, 1.0);
 }
 
-/// System to update the 3D arrow indicator position and rotation
+/// Raise the throw into a lob while Shift (or the gamepad's left trigger)
+/// is held, and let it fall back to a skim once released.
+pub fn update_throw_elevation(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    time: Res<Time>,
+    command_input: Res<crate::dice3d::types::CommandInput>,
+    mut throw_state: ResMut<ThrowControlState>,
+) {
+    let lob_held = !command_input.active
+        && (keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad.pressed(GamepadButton::LeftTrigger2)));
+    throw_state.step_elevation(lob_held, time.delta_secs());
+}
+
+/// System to trace the predicted arc of the throw with dots, and point the
+/// arrow along the arc where the dice come down
+#[allow(clippy::type_complexity)]
 pub fn update_throw_arrow(
     throw_state: Res<ThrowControlState>,
     mut arrow_query: Query<(&mut Transform, &mut Visibility), With<ThrowDirectionArrow>>,
+    mut dot_query: Query<
+        (&ThrowTrajectoryDot, &mut Transform, &mut Visibility),
+        Without<ThrowDirectionArrow>,
+    >,
     ui_state: Res<crate::dice3d::types::UiState>,
     shake_anim: Res<ContainerShakeAnimation>,
+    container_style: Res<DiceContainerStyle>,
 ) {
-    for (mut transform, mut visibility) in arrow_query.iter_mut() {
-        // Only show arrow on dice roller tab
-        if ui_state.active_tab != crate::dice3d::types::AppTab::DiceRoller {
-            *visibility = Visibility::Hidden;
-            continue;
-        }
+    // Only on the dice roller tab, and not during container shaking so it
+    // doesn't distract / look wrong.
+    let shown =
+        ui_state.active_tab == crate::dice3d::types::AppTab::DiceRoller && !shake_anim.active;
+    let visibility_now = if shown {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
 
-        // Hide arrow during container shaking so it doesn't distract / look wrong.
-        if shake_anim.active {
-            *visibility = Visibility::Hidden;
-            continue;
+    let half_extent = match *container_style {
+        DiceContainerStyle::Box => BOX_HALF_EXTENT,
+        DiceContainerStyle::Cup => CUP_RADIUS,
+    };
+    let arc = throw_state.predict_trajectory(THROW_ORIGIN, half_extent);
+    let (Some(&landing), Some(&before_landing)) = (arc.last(), arc.iter().rev().nth(1)) else {
+        return;
+    };
+
+    for (dot, mut transform, mut visibility) in dot_query.iter_mut() {
+        // The first sample sits among the dice and the last under the arrow
+        match arc.get(dot.0 + 1).filter(|_| dot.0 + 2 < arc.len()) {
+            Some(point) if shown => {
+                *visibility = Visibility::Visible;
+                transform.translation = *point;
+            }
+            _ => *visibility = Visibility::Hidden,
         }
+    }
 
-        *visibility = Visibility::Visible;
-
-        // Position arrow at target point, slightly above floor
-        transform.translation = Vec3::new(
-            throw_state.target_point.x,
-            BOX_FLOOR_Y + 0.1,
-            throw_state.target_point.z,
-        );
-
-        // Rotate arrow to point from center toward target
-        let direction = throw_state.target_point - BOX_CENTER;
-        if direction.length() > 0.01 {
-            let angle = direction.z.atan2(direction.x);
-            // Arrow mesh points in +X by default, rotate to point toward target
-            transform.rotation = Quat::from_rotation_y(-angle + std::f32::consts::FRAC_PI_2);
+    for (mut transform, mut visibility) in arrow_query.iter_mut() {
+        *visibility = visibility_now;
+        if !shown {
+            continue;
         }
 
         // Scale arrow based on strength
         let scale = 0.3 + throw_state.throw_strength * 0.5;
-        transform.scale = Vec3::new(scale, scale, scale);
+        transform.scale = Vec3::splat(scale);
+
+        // Arrow mesh points in +Z; turn it along the arc's last stretch
+        // (flat along the throw when the arc has no length) so its tip
+        // ends where the dice land, slightly above the floor.
+        let direction = (landing - before_landing)
+            .try_normalize()
+            .or_else(|| {
+                (throw_state.target_point - BOX_CENTER)
+                    .with_y(0.0)
+                    .try_normalize()
+            })
+            .unwrap_or(Vec3::NEG_Z);
+        transform.rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+        transform.translation = landing - direction * ARROW_LENGTH * scale + Vec3::Y * 0.1;
     }
 }
 
-/// Spawn the 3D arrow indicator mesh
+/// Length of the arrow mesh, tail to tip, at scale 1
+const ARROW_LENGTH: f32 = 0.625;
+
+/// Spawn the 3D arrow indicator mesh and the dots of the predicted arc
 pub fn spawn_throw_arrow(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
@@ -244,6 +292,18 @@ pub fn spawn_throw_arrow(
         height: 0.25,
     });
 
+    // Dots tracing the arc, from just past the dice to just before the arrow
+    let dot_mesh = meshes.add(Sphere::new(0.04));
+    for index in 0..TRAJECTORY_SAMPLES - 2 {
+        commands.spawn((
+            Mesh3d(dot_mesh.clone()),
+            MeshMaterial3d(arrow_material.clone()),
+            Transform::from_translation(THROW_ORIGIN),
+            Visibility::Hidden,
+            ThrowTrajectoryDot(index),
+        ));
+    }
+
     // Spawn arrow as a parent entity with children
     commands
         .spawn((
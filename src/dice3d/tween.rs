@@ -0,0 +1,12 @@
+//! Keyframed transform tweens
+
+use bevy::prelude::*;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<crate::dice3d::types::TweenCompleted>()
+            .add_systems(Update, crate::dice3d::advance_transform_tweens);
+    }
+}
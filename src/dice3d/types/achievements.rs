@@ -0,0 +1,243 @@
+//! Achievements
+//!
+//! Small local milestones unlocked by rolling: the first natural 20, a
+//! hundred saved rolls, every physical die in one session and surviving a
+//! death save that would have been the third failure. Unlocked achievements
+//! are kept per profile in the database's settings table under
+//! [`ACHIEVEMENTS_SETTING_KEY`].
+
+use serde::{Deserialize, Serialize};
+
+use super::roll_stats::SECONDS_PER_DAY;
+use super::{DiceType, RollRecord};
+
+/// Database setting holding the unlocked achievements
+pub const ACHIEVEMENTS_SETTING_KEY: &str = "achievements";
+
+/// Saved rolls that unlock [`Achievement::HundredRolls`]
+pub const HUNDRED_ROLLS: usize = 100;
+
+/// Dice that make up a full set for [`Achievement::FullSet`]
+pub const FULL_SET: [DiceType; 6] = [
+    DiceType::D4,
+    DiceType::D6,
+    DiceType::D8,
+    DiceType::D10,
+    DiceType::D12,
+    DiceType::D20,
+];
+
+/// Everything that can be unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstNatural20,
+    HundredRolls,
+    FullSet,
+    BackFromTheBrink,
+}
+
+impl Achievement {
+    /// Every achievement, in the order the achievements page lists them
+    pub const ALL: [Achievement; 4] = [
+        Achievement::FirstNatural20,
+        Achievement::HundredRolls,
+        Achievement::FullSet,
+        Achievement::BackFromTheBrink,
+    ];
+
+    /// Stable id saved in the database
+    pub fn id(&self) -> &'static str {
+        match self {
+            Achievement::FirstNatural20 => "first_natural_20",
+            Achievement::HundredRolls => "hundred_rolls",
+            Achievement::FullSet => "full_set",
+            Achievement::BackFromTheBrink => "back_from_the_brink",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstNatural20 => "Natural 20",
+            Achievement::HundredRolls => "Centurion",
+            Achievement::FullSet => "Full Set",
+            Achievement::BackFromTheBrink => "Back from the Brink",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstNatural20 => "Roll your first natural 20",
+            Achievement::HundredRolls => "Make 100 rolls",
+            Achievement::FullSet => "Roll a d4, d6, d8, d10, d12 and d20 in one session",
+            Achievement::BackFromTheBrink => {
+                "Pass a death save that would have been your third failure"
+            }
+        }
+    }
+}
+
+/// An achievement and when it was unlocked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockedAchievement {
+    /// [`Achievement::id`]; ids this build doesn't know are kept as they are
+    pub id: String,
+    /// Seconds since the Unix epoch
+    pub unlocked_at: u64,
+}
+
+impl UnlockedAchievement {
+    /// Days since the Unix epoch (UTC) of the unlock.
+    pub fn day(&self) -> u64 {
+        self.unlocked_at / SECONDS_PER_DAY
+    }
+}
+
+/// The achievements unlocked so far
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Achievements {
+    #[serde(default)]
+    pub unlocked: Vec<UnlockedAchievement>,
+}
+
+impl Achievements {
+    pub fn get(&self, achievement: Achievement) -> Option<&UnlockedAchievement> {
+        self.unlocked.iter().find(|u| u.id == achievement.id())
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.get(achievement).is_some()
+    }
+
+    /// Unlock an achievement; returns `false` when it already was.
+    pub fn unlock(&mut self, achievement: Achievement, at: u64) -> bool {
+        if self.is_unlocked(achievement) {
+            return false;
+        }
+        self.unlocked.push(UnlockedAchievement {
+            id: achievement.id().to_string(),
+            unlocked_at: at,
+        });
+        true
+    }
+
+    /// Number of known achievements unlocked.
+    pub fn count(&self) -> usize {
+        Achievement::ALL
+            .iter()
+            .filter(|a| self.is_unlocked(**a))
+            .count()
+    }
+}
+
+/// Roll achievements earned by the saved rolls. Rolls made at or after
+/// `session_start` (seconds since the Unix epoch) count as this session.
+pub fn roll_achievements(records: &[RollRecord], session_start: u64) -> Vec<Achievement> {
+    let mut earned = Vec::new();
+
+    if records
+        .iter()
+        .flat_map(|r| r.dice.iter())
+        .any(|(die, face)| *die == DiceType::D20 && *face == 20)
+    {
+        earned.push(Achievement::FirstNatural20);
+    }
+    if records.len() >= HUNDRED_ROLLS {
+        earned.push(Achievement::HundredRolls);
+    }
+    let session: Vec<DiceType> = records
+        .iter()
+        .filter(|r| r.rolled_at >= session_start)
+        .flat_map(|r| r.dice.iter().map(|(die, _)| *die))
+        .collect();
+    if FULL_SET.iter().all(|die| session.contains(die)) {
+        earned.push(Achievement::FullSet);
+    }
+    earned
+}
+
+/// Whether a roll's label marks it as a death save (`--checkon death`).
+pub fn is_death_save_label(label: &str) -> bool {
+    let label = label.trim().to_lowercase().replace(['-', '_'], " ");
+    matches!(
+        label.as_str(),
+        "death" | "death save" | "deathsave" | "death saving throw"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(rolled_at: u64, dice: Vec<(DiceType, u32)>) -> RollRecord {
+        RollRecord {
+            character: String::new(),
+            label: String::new(),
+            total: dice.iter().map(|(_, face)| *face as i32).sum(),
+            dice,
+            rolled_at,
+        }
+    }
+
+    #[test]
+    fn test_roll_achievements() {
+        assert!(roll_achievements(&[], 0).is_empty());
+
+        let mut records = vec![
+            record(10, vec![(DiceType::D20, 20)]),
+            record(10, vec![(DiceType::D4, 1), (DiceType::D6, 2)]),
+            record(100, vec![(DiceType::D8, 3), (DiceType::D10, 4)]),
+            record(100, vec![(DiceType::D12, 5), (DiceType::D20, 6)]),
+        ];
+        assert_eq!(
+            roll_achievements(&records, 0),
+            vec![Achievement::FirstNatural20, Achievement::FullSet]
+        );
+        // The d4 and d6 were rolled before this session
+        assert_eq!(
+            roll_achievements(&records, 50),
+            vec![Achievement::FirstNatural20]
+        );
+
+        records.truncate(1);
+        records.resize(HUNDRED_ROLLS, record(10, vec![(DiceType::D6, 1)]));
+        assert!(roll_achievements(&records, 0).contains(&Achievement::HundredRolls));
+        // A natural 20 on another die doesn't count
+        assert!(roll_achievements(&[record(0, vec![(DiceType::Custom(100), 20)])], 0).is_empty());
+    }
+
+    #[test]
+    fn test_unlock_once() {
+        let mut achievements = Achievements::default();
+        assert!(achievements.unlock(Achievement::FullSet, 86_400 * 3 + 5));
+        assert!(!achievements.unlock(Achievement::FullSet, 86_400 * 9));
+        assert_eq!(achievements.get(Achievement::FullSet).unwrap().day(), 3);
+        assert_eq!(achievements.count(), 1);
+
+        // Ids from a newer build survive a round trip but don't count
+        let mut saved = serde_json::to_value(&achievements).unwrap();
+        saved["unlocked"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "id": "dragon_slayer", "unlocked_at": 1 }));
+        let loaded: Achievements = serde_json::from_value(saved).unwrap();
+        assert_eq!(loaded.unlocked.len(), 2);
+        assert_eq!(loaded.count(), 1);
+        assert_eq!(
+            Achievement::from_id("back_from_the_brink"),
+            Some(Achievement::BackFromTheBrink)
+        );
+    }
+
+    #[test]
+    fn test_death_save_labels() {
+        assert!(is_death_save_label("death"));
+        assert!(is_death_save_label("Death Save"));
+        assert!(is_death_save_label("death-save"));
+        assert!(!is_death_save_label("Dexterity save"));
+        assert!(!is_death_save_label(""));
+    }
+}
@@ -0,0 +1,322 @@
+//! Automation rules
+//!
+//! Rules react to events that other parts of the app publish on the
+//! automation event bus: "when HP drops below half, remind me about the
+//! Wounded house rule", "when initiative is rolled, also roll Perception".
+//! Rules are typed into the command input:
+//!
+//! ```text
+//! rule when hp below half then remind Wounded: -1 to attack rolls
+//! rule when roll initiative then roll perception
+//! rule when nat20 then remind Inspiration for the table
+//! ```
+//!
+//! They are stored in the profile database, so each campaign (profile) has
+//! its own set.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Database setting key holding the rules of the current profile
+pub const AUTOMATION_RULES_DB_KEY: &str = "automation_rules";
+
+/// Something that happened which rules can react to
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub enum AutomationEvent {
+    /// A roll settled. `label` is what it was for ("perception",
+    /// "initiative"), empty for plain dice.
+    RollCompleted {
+        label: String,
+        total: i32,
+        /// Face of the d20 when the roll was a single d20
+        natural_d20: Option<u32>,
+    },
+    /// The current character's hit points changed
+    HitPointsChanged {
+        previous: i32,
+        current: i32,
+        maximum: i32,
+    },
+}
+
+/// When a rule fires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleTrigger {
+    /// Hit points drop below this percentage of the maximum
+    HitPointsBelow(u32),
+    /// A roll for this label completes (case-insensitive)
+    Roll(String),
+    NaturalTwenty,
+    NaturalOne,
+}
+
+/// What a rule does when it fires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleAction {
+    /// Show a reminder
+    Remind(String),
+    /// Roll a check ("perception") or dice ("1d6") alongside
+    Roll(String),
+}
+
+/// A trigger and the action it sets off
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomationRule {
+    pub trigger: RuleTrigger,
+    pub action: RuleAction,
+}
+
+impl RuleTrigger {
+    /// Whether `event` sets off this trigger.
+    ///
+    /// HP triggers fire when hit points cross the threshold, not on every
+    /// change while below it.
+    pub fn matches(&self, event: &AutomationEvent) -> bool {
+        match (self, event) {
+            (
+                RuleTrigger::HitPointsBelow(percent),
+                AutomationEvent::HitPointsChanged {
+                    previous,
+                    current,
+                    maximum,
+                },
+            ) => {
+                if *maximum <= 0 {
+                    return false;
+                }
+                // Compare in hundredths to avoid rounding the threshold
+                let below = |hp: i32| (hp as i64) * 100 < (*maximum as i64) * (*percent as i64);
+                below(*current) && !below(*previous)
+            }
+            (RuleTrigger::Roll(name), AutomationEvent::RollCompleted { label, .. }) => {
+                !label.is_empty() && label.eq_ignore_ascii_case(name)
+            }
+            (RuleTrigger::NaturalTwenty, AutomationEvent::RollCompleted { natural_d20, .. }) => {
+                *natural_d20 == Some(20)
+            }
+            (RuleTrigger::NaturalOne, AutomationEvent::RollCompleted { natural_d20, .. }) => {
+                *natural_d20 == Some(1)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RuleTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleTrigger::HitPointsBelow(50) => write!(f, "hp below half"),
+            RuleTrigger::HitPointsBelow(percent) => write!(f, "hp below {}%", percent),
+            RuleTrigger::Roll(label) => write!(f, "roll {}", label),
+            RuleTrigger::NaturalTwenty => write!(f, "nat20"),
+            RuleTrigger::NaturalOne => write!(f, "nat1"),
+        }
+    }
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleAction::Remind(text) => write!(f, "remind {}", text),
+            RuleAction::Roll(what) => write!(f, "roll {}", what),
+        }
+    }
+}
+
+impl fmt::Display for AutomationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "when {} then {}", self.trigger, self.action)
+    }
+}
+
+impl AutomationRule {
+    /// Parse `when <trigger> then <action>`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let usage = "Use: rule when <trigger> then <action>";
+        let text = text.trim();
+        let rest = strip_word(text, "when").ok_or(usage)?;
+        let (trigger, action) = split_word(rest, "then").ok_or(usage)?;
+        Ok(Self {
+            trigger: parse_trigger(trigger)?,
+            action: parse_action(action)?,
+        })
+    }
+}
+
+/// `text` without a leading `word` (case-insensitive), if it starts with it.
+fn strip_word<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let (head, rest) = text.split_once(char::is_whitespace)?;
+    head.eq_ignore_ascii_case(word).then(|| rest.trim())
+}
+
+/// Split around the first standalone `word` (case-insensitive).
+fn split_word<'a>(text: &'a str, word: &str) -> Option<(&'a str, &'a str)> {
+    let lower = text.to_ascii_lowercase();
+    let needle = format!(" {} ", word);
+    let at = lower.find(&needle)?;
+    Some((text[..at].trim(), text[at + needle.len()..].trim()))
+}
+
+fn parse_trigger(text: &str) -> Result<RuleTrigger, String> {
+    let lower = text.to_ascii_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    match words.as_slice() {
+        ["hp", "below", "half"] => Ok(RuleTrigger::HitPointsBelow(50)),
+        ["hp", "below", amount] => amount
+            .trim_end_matches('%')
+            .parse::<u32>()
+            .ok()
+            .filter(|p| (1..=100).contains(p))
+            .map(RuleTrigger::HitPointsBelow)
+            .ok_or_else(|| format!("Expected a percentage, got '{}'", amount)),
+        ["nat20"] | ["natural", "20"] => Ok(RuleTrigger::NaturalTwenty),
+        ["nat1"] | ["natural", "1"] => Ok(RuleTrigger::NaturalOne),
+        ["roll", ..] if words.len() > 1 => {
+            let label = text.trim()[4..].trim();
+            Ok(RuleTrigger::Roll(label.to_string()))
+        }
+        _ => Err(format!(
+            "Unknown trigger '{}'; use hp below <n>%, roll <check>, nat20 or nat1",
+            text
+        )),
+    }
+}
+
+fn parse_action(text: &str) -> Result<RuleAction, String> {
+    let text = text.trim();
+    if let Some(reminder) = strip_word(text, "remind") {
+        if !reminder.is_empty() {
+            return Ok(RuleAction::Remind(reminder.to_string()));
+        }
+    }
+    if let Some(what) = strip_word(text, "roll") {
+        if !what.is_empty() {
+            return Ok(RuleAction::Roll(what.to_string()));
+        }
+    }
+    Err(format!(
+        "Unknown action '{}'; use remind <text> or roll <check>",
+        text
+    ))
+}
+
+/// The rules of the current profile
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomationRules {
+    pub rules: Vec<AutomationRule>,
+}
+
+impl AutomationRules {
+    /// Actions of every rule `event` sets off, in rule order.
+    pub fn actions_for(&self, event: &AutomationEvent) -> Vec<RuleAction> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.trigger.matches(event))
+            .map(|rule| rule.action.clone())
+            .collect()
+    }
+
+    /// Numbered list for display ("1. when nat20 then remind ...").
+    pub fn summary(&self) -> String {
+        if self.rules.is_empty() {
+            return "No automation rules".to_string();
+        }
+        self.rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| format!("{}. {}", i + 1, rule))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hp(previous: i32, current: i32) -> AutomationEvent {
+        AutomationEvent::HitPointsChanged {
+            previous,
+            current,
+            maximum: 40,
+        }
+    }
+
+    fn roll(label: &str, natural_d20: Option<u32>) -> AutomationEvent {
+        AutomationEvent::RollCompleted {
+            label: label.to_string(),
+            total: 12,
+            natural_d20,
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule =
+            AutomationRule::parse("when HP below half then remind Wounded: -1 to attacks").unwrap();
+        assert_eq!(rule.trigger, RuleTrigger::HitPointsBelow(50));
+        assert_eq!(
+            rule.action,
+            RuleAction::Remind("Wounded: -1 to attacks".to_string())
+        );
+        assert_eq!(
+            rule.to_string(),
+            "when hp below half then remind Wounded: -1 to attacks"
+        );
+
+        let rule = AutomationRule::parse("when roll Initiative then roll perception").unwrap();
+        assert_eq!(rule.trigger, RuleTrigger::Roll("Initiative".to_string()));
+        assert_eq!(rule.action, RuleAction::Roll("perception".to_string()));
+
+        assert_eq!(
+            AutomationRule::parse("when hp below 25% then roll 1d6")
+                .unwrap()
+                .trigger,
+            RuleTrigger::HitPointsBelow(25)
+        );
+        for bad in [
+            "",
+            "hp below half then remind x",
+            "when hp below half",
+            "when hp below 0% then remind x",
+            "when sunrise then remind x",
+            "when nat20 then dance",
+        ] {
+            assert!(
+                AutomationRule::parse(bad).is_err(),
+                "'{}' should not parse",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_hit_point_rules_fire_on_crossing() {
+        let trigger = RuleTrigger::HitPointsBelow(50);
+        assert!(trigger.matches(&hp(25, 19)));
+        assert!(!trigger.matches(&hp(19, 12)));
+        assert!(!trigger.matches(&hp(30, 20)));
+        assert!(!trigger.matches(&roll("initiative", None)));
+    }
+
+    #[test]
+    fn test_roll_rules() {
+        let rules = AutomationRules {
+            rules: vec![
+                AutomationRule::parse("when roll initiative then roll perception").unwrap(),
+                AutomationRule::parse("when nat20 then remind Inspiration").unwrap(),
+            ],
+        };
+        assert_eq!(
+            rules.actions_for(&roll("Initiative", Some(7))),
+            vec![RuleAction::Roll("perception".to_string())]
+        );
+        assert_eq!(
+            rules.actions_for(&roll("", Some(20))),
+            vec![RuleAction::Remind("Inspiration".to_string())]
+        );
+        assert!(rules.actions_for(&roll("stealth", Some(3))).is_empty());
+        assert!(rules.summary().starts_with("1. when roll initiative"));
+    }
+}
@@ -3,10 +3,24 @@
 //! This module contains all types related to loading, saving, and accessing
 //! character data persisted via the app's database layer.
 
-use bevy::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tracing::info;
+
+pub use dndgamerolls_core::abilities::{ability_abbreviation, normalize_ability_name};
+
+use super::conditions::Conditions;
+use super::currency::Currency;
+use super::damage_riders::{riders_for, DamageRider, SlotPool};
+use super::damage_types::{AdjustedDamage, DamageDefenses};
+use super::dice_expression::DamageRoll;
+use super::field_permissions::FieldPermissions;
+use super::inventory::{
+    deserialize_items, inventory_weight, item_bonus, BonusRoll, Encumbrance, InventoryItem,
+    DEFAULT_ATTUNEMENT_SLOTS,
+};
+use super::weapon_properties::{check_two_weapon_fighting, AttackMode, WeaponAttack, WeaponStats};
 
 // ============================================================================
 // Character Schema Types - Full D&D 5e Character Sheet
@@ -39,6 +53,365 @@ pub struct CharacterSheet {
     /// Custom combat stats (name -> value as string)
     #[serde(rename = "customCombat", default)]
     pub custom_combat: HashMap<String, String>,
+    /// Spell the character is currently concentrating on, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concentration: Option<String>,
+    /// Tool proficiencies, tagged with the skills they support
+    #[serde(rename = "toolProficiencies", default)]
+    pub tool_proficiencies: Vec<ToolProficiency>,
+    /// Damage types the character resists, is immune or vulnerable to
+    #[serde(
+        rename = "damageDefenses",
+        default,
+        skip_serializing_if = "DamageDefenses::is_empty"
+    )]
+    pub damage_defenses: DamageDefenses,
+    /// Conditions the character is under, and their exhaustion level
+    #[serde(default, skip_serializing_if = "Conditions::is_empty")]
+    pub conditions: Conditions,
+    /// The character has inspiration to spend on a roll
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inspiration: bool,
+    /// Which fields other participants of a session see
+    #[serde(
+        rename = "fieldVisibility",
+        default,
+        skip_serializing_if = "FieldPermissions::is_empty"
+    )]
+    pub field_visibility: FieldPermissions,
+}
+
+impl CharacterSheet {
+    /// Start concentrating on a spell.
+    ///
+    /// A character can only concentrate on one spell at a time, so any previous
+    /// concentration effect ends. Returns the spell that was ended, if any.
+    pub fn start_concentration(&mut self, spell: &str) -> Option<String> {
+        let previous = self.concentration.replace(spell.trim().to_string());
+        if let Some(prev) = &previous {
+            info!(
+                "{} stopped concentrating on {} (now concentrating on {})",
+                self.character.name,
+                prev,
+                spell.trim()
+            );
+        }
+        previous
+    }
+
+    /// End the current concentration effect. Returns the spell that was ended.
+    pub fn end_concentration(&mut self) -> Option<String> {
+        let previous = self.concentration.take();
+        if let Some(prev) = &previous {
+            info!("{} stopped concentrating on {}", self.character.name, prev);
+        }
+        previous
+    }
+
+    /// Whether starting concentration on `spell` would end a different effect.
+    pub fn concentration_conflict(&self, spell: &str) -> Option<&str> {
+        self.concentration
+            .as_deref()
+            .filter(|current| !current.eq_ignore_ascii_case(spell.trim()))
+    }
+
+    /// Mark a death save with the natural d20 `face` rolled.
+    ///
+    /// 10 or higher is a success and lower a failure; a natural 1 counts as
+    /// two failures and a natural 20 brings the character back with 1 hit
+    /// point. Three successes or coming back reset the tally.
+    pub fn record_death_save(&mut self, face: u32) -> DeathSaveOutcome {
+        let saves = self.combat.death_saves.get_or_insert_with(Default::default);
+        let outcome = match face {
+            20 => DeathSaveOutcome::Revived,
+            1 => {
+                saves.failures += 2;
+                DeathSaveOutcome::Failure
+            }
+            face if face >= 10 => {
+                saves.successes += 1;
+                if saves.successes >= 3 {
+                    DeathSaveOutcome::Stable
+                } else {
+                    DeathSaveOutcome::Success
+                }
+            }
+            _ => {
+                saves.failures += 1;
+                DeathSaveOutcome::Failure
+            }
+        };
+
+        match outcome {
+            DeathSaveOutcome::Revived => {
+                *saves = DeathSaves::default();
+                if let Some(hp) = self.combat.hit_points.as_mut() {
+                    hp.current = hp.current.max(1);
+                }
+            }
+            DeathSaveOutcome::Stable => *saves = DeathSaves::default(),
+            DeathSaveOutcome::Failure if saves.failures >= 3 => {
+                saves.failures = 3;
+                return DeathSaveOutcome::Dead;
+            }
+            _ => {}
+        }
+        outcome
+    }
+
+    /// Take `amount` damage of `damage_type` after the character's
+    /// resistances, immunities and vulnerabilities. Temporary hit points are
+    /// lost first; hit points don't go below 0.
+    pub fn take_damage(&mut self, amount: i32, damage_type: Option<&str>) -> DamageTaken {
+        let adjusted = self.damage_defenses.adjust(amount, damage_type);
+        let mut taken = DamageTaken {
+            adjusted,
+            from_temporary: 0,
+            from_hit_points: 0,
+        };
+        if let Some(hp) = self.combat.hit_points.as_mut() {
+            taken.from_temporary = adjusted.amount.min(hp.temporary.max(0));
+            hp.temporary -= taken.from_temporary;
+            taken.from_hit_points = (adjusted.amount - taken.from_temporary).min(hp.current.max(0));
+            hp.current -= taken.from_hit_points;
+        }
+        taken
+    }
+
+    /// Set the six ability scores (in `ABILITY_NAMES` order) and work out
+    /// the modifiers, saving throws and skills that follow from them.
+    pub fn set_ability_scores(&mut self, scores: [i32; 6]) {
+        let [strength, dexterity, constitution, intelligence, wisdom, charisma] = scores;
+        self.attributes = Attributes {
+            strength,
+            dexterity,
+            constitution,
+            intelligence,
+            wisdom,
+            charisma,
+        };
+        self.derive_from_ability_scores();
+    }
+
+    /// Work the ability modifiers out from the scores, and the saving throw
+    /// and standard skill modifiers from those plus proficiency (doubled
+    /// with expertise). Custom skills are left as they are.
+    pub fn derive_from_ability_scores(&mut self) {
+        let scores = &self.attributes;
+        self.modifiers = AttributeModifiers {
+            strength: Attributes::calculate_modifier(scores.strength),
+            dexterity: Attributes::calculate_modifier(scores.dexterity),
+            constitution: Attributes::calculate_modifier(scores.constitution),
+            intelligence: Attributes::calculate_modifier(scores.intelligence),
+            wisdom: Attributes::calculate_modifier(scores.wisdom),
+            charisma: Attributes::calculate_modifier(scores.charisma),
+        };
+
+        let proficiency = self.proficiency_bonus;
+        for ability in ABILITY_NAMES {
+            let modifier = self.modifiers.get(ability).unwrap_or(0);
+            let save = self.saving_throws.entry(ability.to_string()).or_default();
+            save.modifier = modifier + if save.proficient { proficiency } else { 0 };
+        }
+        for (name, skill) in self.skills.iter_mut() {
+            let Some(ability) = default_skill_ability(name) else {
+                continue;
+            };
+            let bonus = if skill.expertise.unwrap_or(false) {
+                proficiency * 2
+            } else if skill.proficient {
+                proficiency
+            } else {
+                0
+            };
+            skill.modifier = self.modifiers.get(ability).unwrap_or(0) + bonus;
+        }
+    }
+
+    /// Modifier of the spellcasting ability (0 without one).
+    pub fn spellcasting_modifier(&self) -> i32 {
+        let ability = self
+            .spells
+            .as_ref()
+            .and_then(|spells| spells.spellcasting_ability.as_deref())
+            .and_then(normalize_ability_name);
+        match ability {
+            Some("strength") => self.modifiers.strength,
+            Some("dexterity") => self.modifiers.dexterity,
+            Some("constitution") => self.modifiers.constitution,
+            Some("intelligence") => self.modifiers.intelligence,
+            Some("wisdom") => self.modifiers.wisdom,
+            Some("charisma") => self.modifiers.charisma,
+            _ => 0,
+        }
+    }
+
+    /// Spell attack bonus as the sheet has it, worked out from proficiency
+    /// and the spellcasting ability when the sheet doesn't set one.
+    pub fn sheet_spell_attack_bonus(&self) -> i32 {
+        self.spells
+            .as_ref()
+            .and_then(|spells| spells.spell_attack_bonus)
+            .unwrap_or_else(|| self.proficiency_bonus + self.spellcasting_modifier())
+    }
+
+    /// Spell attack bonus, with what magic items in use add to it
+    pub fn spell_attack_bonus(&self) -> i32 {
+        self.sheet_spell_attack_bonus() + self.item_bonus(BonusRoll::SpellAttack)
+    }
+
+    /// Damage a spell rolls, from `spells.spell_damage`; `None` when the
+    /// sheet doesn't list any.
+    pub fn spell_damage_roll(&self, spell: &str) -> Option<Result<DamageRoll, String>> {
+        self.spells
+            .as_ref()?
+            .spell_damage
+            .get(spell)
+            .map(|damage| DamageRoll::parse(damage))
+    }
+
+    /// Magic items the character can be attuned to at once
+    pub fn attunement_slots(&self) -> usize {
+        self.equipment
+            .as_ref()
+            .and_then(|equipment| equipment.attunement_slots)
+            .map_or(DEFAULT_ATTUNEMENT_SLOTS, |slots| slots as usize)
+    }
+
+    /// What the attuned and equipped magic items add to `roll`
+    pub fn item_bonus(&self, roll: BonusRoll) -> i32 {
+        self.equipment
+            .as_ref()
+            .map_or(0, |equipment| item_bonus(&equipment.items, roll))
+    }
+
+    /// An attack with a weapon, using the ability modifier its properties
+    /// call for.
+    pub fn weapon_attack(&self, weapon: &Weapon, mode: AttackMode) -> Result<WeaponAttack, String> {
+        let mut attack = WeaponAttack::new(
+            weapon.stats(),
+            mode,
+            self.modifiers.strength,
+            self.modifiers.dexterity,
+            self.proficiency_bonus,
+        )?;
+        attack.attack_bonus += self.item_bonus(BonusRoll::Attack);
+        Ok(attack)
+    }
+
+    /// Number of attacks the Attack action gives
+    pub fn attacks_per_action(&self) -> u32 {
+        1 + self.combat.extra_attacks
+    }
+
+    /// Whether one of the character's features, feats or fighting styles
+    /// has this name ("Dual Wielder" matches "Feat: Dual Wielder").
+    pub fn has_feature(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.features
+            .iter()
+            .any(|f| f.name.to_lowercase().contains(&name))
+    }
+
+    /// The off-hand attack when fighting with two weapons, checked against
+    /// the Dual Wielder feat and using the Two-Weapon Fighting style.
+    pub fn off_hand_attack(
+        &self,
+        main_hand: &Weapon,
+        off_hand: &Weapon,
+    ) -> Result<WeaponAttack, String> {
+        check_two_weapon_fighting(
+            main_hand.stats(),
+            off_hand.stats(),
+            self.has_feature("Dual Wielder"),
+        )?;
+        let attack = self.weapon_attack(off_hand, AttackMode::Normal)?;
+        Ok(attack.off_hand(self.has_feature("Two-Weapon Fighting")))
+    }
+
+    /// Pounds of items carried (weapons and armor have no weight on the sheet)
+    pub fn carried_weight(&self) -> f32 {
+        self.equipment
+            .as_ref()
+            .map_or(0.0, |equipment| inventory_weight(&equipment.items))
+    }
+
+    /// How weighed down the carried items leave the character
+    pub fn encumbrance(&self) -> Encumbrance {
+        Encumbrance::of(self.carried_weight(), self.attributes.strength)
+    }
+
+    /// The character's damage riders that can be added to a hit with
+    /// `attack`, leaving out once-a-turn riders already `used` this turn
+    pub fn damage_riders_for(
+        &self,
+        attack: &WeaponAttack,
+        used: &[DamageRider],
+    ) -> Vec<&DamageRider> {
+        riders_for(&self.combat.damage_riders, attack, used)
+    }
+
+    /// Spell save DC as the sheet has it, worked out as 8 + proficiency +
+    /// the spellcasting ability modifier when the sheet doesn't set one.
+    pub fn sheet_spell_save_dc(&self) -> i32 {
+        self.spells
+            .as_ref()
+            .and_then(|spells| spells.spell_save_dc)
+            .unwrap_or_else(|| 8 + self.proficiency_bonus + self.spellcasting_modifier())
+    }
+
+    /// Spell save DC, with what magic items in use add to it
+    pub fn spell_save_dc(&self) -> i32 {
+        self.sheet_spell_save_dc() + self.item_bonus(BonusRoll::SpellSaveDc)
+    }
+
+    /// Combined benefit of all tool proficiencies tagged for a skill
+    /// (Xanathar's tool/skill synergy).
+    pub fn tool_synergy_for_skill(&self, skill: &str) -> ToolSynergy {
+        let normalize = |s: &str| s.to_lowercase().replace([' ', '_'], "");
+        let skill_key = normalize(skill);
+
+        let mut synergy = ToolSynergy::default();
+        for tool in &self.tool_proficiencies {
+            if !tool.skills.iter().any(|s| normalize(s) == skill_key) {
+                continue;
+            }
+            match tool.benefit {
+                ToolSynergyBenefit::Advantage => synergy.advantage = true,
+                // Bonuses from multiple tools don't stack; use the best one.
+                ToolSynergyBenefit::Bonus(bonus) => synergy.bonus = synergy.bonus.max(bonus),
+            }
+            synergy.tools.push(tool.name.clone());
+        }
+        synergy
+    }
+}
+
+/// Smites spend the slots of the sheet's spells
+impl SlotPool for CharacterSheet {
+    fn slot_choices(&self) -> Vec<(u32, i32)> {
+        self.spells
+            .as_ref()
+            .map(|spells| spells.slot_choices())
+            .unwrap_or_default()
+    }
+
+    /// Spend the spell slot a rider takes (of `level`, or the lowest one
+    /// left, for Divine Smite) and return its level; 0 for riders that take
+    /// none.
+    fn spend_rider_slot(&mut self, rider: &DamageRider, level: Option<u32>) -> Result<u32, String> {
+        if !rider.spends_slot() {
+            return Ok(0);
+        }
+        let no_slots = || format!("No spell slots left for {}", rider.name());
+        let spells = self.spells.as_mut().ok_or_else(no_slots)?;
+        let level = match level {
+            Some(level) => level,
+            None => spells.lowest_slot_remaining().ok_or_else(no_slots)?,
+        };
+        spells.spend_slot_for(level, rider.name())?;
+        Ok(level)
+    }
 }
 
 /// Basic character information
@@ -80,7 +453,7 @@ pub struct Attributes {
 impl Attributes {
     /// Calculate modifier from ability score (standard D&D formula)
     pub fn calculate_modifier(score: i32) -> i32 {
-        (score - 10) / 2
+        (score - 10).div_euclid(2)
     }
 
     /// Get all attributes as a vec of (name, score) tuples
@@ -107,6 +480,21 @@ pub struct AttributeModifiers {
     pub charisma: i32,
 }
 
+impl AttributeModifiers {
+    /// Modifier of an ability by full name or abbreviation.
+    pub fn get(&self, ability: &str) -> Option<i32> {
+        match normalize_ability_name(ability)? {
+            "strength" => Some(self.strength),
+            "dexterity" => Some(self.dexterity),
+            "constitution" => Some(self.constitution),
+            "intelligence" => Some(self.intelligence),
+            "wisdom" => Some(self.wisdom),
+            "charisma" => Some(self.charisma),
+            _ => None,
+        }
+    }
+}
+
 /// Combat statistics
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Combat {
@@ -121,10 +509,20 @@ pub struct Combat {
     pub hit_dice: Option<HitDice>,
     #[serde(rename = "deathSaves", default)]
     pub death_saves: Option<DeathSaves>,
+    /// Attacks the Attack action gives beyond the first (Extra Attack)
+    #[serde(rename = "extraAttacks", default)]
+    pub extra_attacks: u32,
+    /// Sneak Attack, Divine Smite and the like, offered on weapon hits
+    #[serde(
+        rename = "damageRiders",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub damage_riders: Vec<DamageRider>,
 }
 
 /// Hit points tracking
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
 pub struct HitPoints {
     pub current: i32,
     pub maximum: i32,
@@ -146,6 +544,36 @@ pub struct DeathSaves {
     pub failures: i32,
 }
 
+/// Damage a character took (see [`CharacterSheet::take_damage`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageTaken {
+    pub adjusted: AdjustedDamage,
+    /// Temporary hit points lost
+    pub from_temporary: i32,
+    /// Hit points lost
+    pub from_hit_points: i32,
+}
+
+/// What a death save did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathSaveOutcome {
+    Success,
+    Failure,
+    /// Third success: the character is stable
+    Stable,
+    /// Third failure
+    Dead,
+    /// Natural 20: back up with 1 hit point
+    Revived,
+}
+
+impl DeathSaveOutcome {
+    /// Whether the character is still alive after the save.
+    pub fn survived(&self) -> bool {
+        !matches!(self, DeathSaveOutcome::Failure | DeathSaveOutcome::Dead)
+    }
+}
+
 /// Saving throw data
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct SavingThrow {
@@ -171,25 +599,45 @@ pub struct Equipment {
     pub weapons: Vec<Weapon>,
     #[serde(default)]
     pub armor: Option<Armor>,
-    #[serde(default)]
-    pub items: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_items")]
+    pub items: Vec<InventoryItem>,
     #[serde(default)]
     pub currency: Currency,
+    /// Magic items the character can attune to, when not the usual three
+    #[serde(
+        rename = "attunementSlots",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub attunement_slots: Option<u32>,
 }
 
 /// Weapon data
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Weapon {
     pub name: String,
-    #[serde(rename = "attackBonus")]
+    /// Total attack bonus; left out (0), it's worked out from the properties
+    #[serde(rename = "attackBonus", default)]
     pub attack_bonus: i32,
     pub damage: String,
     #[serde(rename = "damageType")]
     pub damage_type: String,
+    /// Property names: "Finesse", "Versatile (1d10)", "Thrown (range 20/60)"...
     #[serde(default)]
     pub properties: Vec<String>,
 }
 
+impl Weapon {
+    pub fn stats(&self) -> WeaponStats<'_> {
+        WeaponStats {
+            name: &self.name,
+            attack_bonus: self.attack_bonus,
+            damage: &self.damage,
+            properties: &self.properties,
+        }
+    }
+}
+
 /// Armor data
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Armor {
@@ -202,19 +650,53 @@ pub struct Armor {
     pub armor_type: Option<String>,
 }
 
-/// Currency tracking
+/// Tool proficiency (e.g. Thieves' Tools) tagged with the skills it helps with
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
-pub struct Currency {
-    #[serde(default)]
-    pub copper: i32,
-    #[serde(default)]
-    pub silver: i32,
-    #[serde(default)]
-    pub electrum: i32,
+pub struct ToolProficiency {
+    pub name: String,
+    /// Skill keys this tool is relevant to (e.g. "sleightOfHand", "investigation")
     #[serde(default)]
-    pub gold: i32,
+    pub skills: Vec<String>,
     #[serde(default)]
-    pub platinum: i32,
+    pub benefit: ToolSynergyBenefit,
+}
+
+/// What a relevant tool proficiency grants on a skill check
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ToolSynergyBenefit {
+    /// Roll the check with advantage (the Xanathar's default)
+    #[default]
+    Advantage,
+    /// Add a flat bonus to the check instead
+    Bonus(i32),
+}
+
+/// Combined effect of tool proficiencies on a single skill check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolSynergy {
+    pub advantage: bool,
+    pub bonus: i32,
+    /// Names of the tools that applied
+    pub tools: Vec<String>,
+}
+
+impl ToolSynergy {
+    pub fn applies(&self) -> bool {
+        !self.tools.is_empty()
+    }
+
+    /// Short description for roll labels, e.g. "Thieves' Tools: advantage, +2"
+    pub fn describe(&self) -> String {
+        let mut effects = Vec::new();
+        if self.advantage {
+            effects.push("advantage".to_string());
+        }
+        if self.bonus != 0 {
+            effects.push(format!("{:+}", self.bonus));
+        }
+        format!("{}: {}", self.tools.join(", "), effects.join(", "))
+    }
 }
 
 /// Character feature or trait
@@ -239,6 +721,184 @@ pub struct SpellCasting {
     pub spell_slots: HashMap<String, i32>,
     #[serde(rename = "knownSpells", default)]
     pub known_spells: Vec<String>,
+    /// Slots spent since the last long rest, by spell level ("1".."9")
+    #[serde(
+        rename = "usedSlots",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub used_slots: HashMap<String, i32>,
+    /// Known spells that are currently prepared
+    #[serde(rename = "preparedSpells", default)]
+    pub prepared_spells: Vec<String>,
+    /// Damage rolled by a spell (spell name -> "8d6 fire")
+    #[serde(rename = "spellDamage", default)]
+    pub spell_damage: HashMap<String, String>,
+    /// Known spells that need concentration once cast
+    #[serde(
+        rename = "concentrationSpells",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub concentration_spells: Vec<String>,
+    /// Known spells with the ritual tag, which can be cast as a ritual
+    #[serde(
+        rename = "ritualSpells",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub ritual_spells: Vec<String>,
+}
+
+impl SpellCasting {
+    /// Slots of a spell level left to spend.
+    pub fn slots_remaining(&self, level: &str) -> i32 {
+        let total = self.spell_slots.get(level).copied().unwrap_or(0);
+        let used = self.used_slots.get(level).copied().unwrap_or(0);
+        (total - used).max(0)
+    }
+
+    /// Spend a slot of a spell level; returns `false` when none are left.
+    pub fn expend_slot(&mut self, level: &str) -> bool {
+        if self.slots_remaining(level) == 0 {
+            return false;
+        }
+        *self.used_slots.entry(level.to_string()).or_insert(0) += 1;
+        true
+    }
+
+    /// Lowest spell level with a slot left to spend.
+    pub fn lowest_slot_remaining(&self) -> Option<u32> {
+        (1..=9).find(|level| self.slots_remaining(&level.to_string()) > 0)
+    }
+
+    /// Spell levels with slots left to spend, as `(level, remaining)`; what
+    /// a smite or an upcast spell can be cast with.
+    pub fn slot_choices(&self) -> Vec<(u32, i32)> {
+        (1..=9)
+            .map(|level| (level, self.slots_remaining(&level.to_string())))
+            .filter(|(_, remaining)| *remaining > 0)
+            .collect()
+    }
+
+    /// Spend a slot of `level` on `what` ("Divine Smite", "Fireball"),
+    /// refusing when none of that level are left.
+    pub fn spend_slot_for(&mut self, level: u32, what: &str) -> Result<(), String> {
+        if self.expend_slot(&level.to_string()) {
+            Ok(())
+        } else if self.slot_choices().is_empty() {
+            Err(format!("No spell slots left for {}", what))
+        } else {
+            Err(format!("No level {} spell slots left for {}", level, what))
+        }
+    }
+
+    /// Get a spent slot of a spell level back.
+    pub fn restore_slot(&mut self, level: &str) {
+        if let Some(used) = self.used_slots.get_mut(level) {
+            *used -= 1;
+            if *used <= 0 {
+                self.used_slots.remove(level);
+            }
+        }
+    }
+
+    /// The known spell called `name`, ignoring case.
+    pub fn known_spell(&self, name: &str) -> Option<&str> {
+        self.known_spells
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case(name.trim()))
+            .map(String::as_str)
+    }
+
+    pub fn is_prepared(&self, spell: &str) -> bool {
+        self.prepared_spells
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(spell))
+    }
+
+    /// Prepare a spell, or unprepare it when it already is.
+    pub fn toggle_prepared(&mut self, spell: &str) {
+        if self.is_prepared(spell) {
+            self.prepared_spells
+                .retain(|s| !s.eq_ignore_ascii_case(spell));
+        } else {
+            self.prepared_spells.push(spell.to_string());
+        }
+    }
+
+    /// Whether casting a spell starts concentration.
+    pub fn needs_concentration(&self, spell: &str) -> bool {
+        self.concentration_spells
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(spell))
+    }
+
+    /// Mark a spell as needing concentration, or unmark it.
+    pub fn toggle_concentration(&mut self, spell: &str) {
+        if self.needs_concentration(spell) {
+            self.concentration_spells
+                .retain(|s| !s.eq_ignore_ascii_case(spell));
+        } else {
+            self.concentration_spells.push(spell.to_string());
+        }
+    }
+
+    /// Whether a spell can be cast as a ritual.
+    pub fn is_ritual(&self, spell: &str) -> bool {
+        self.ritual_spells
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(spell))
+    }
+
+    /// Give a spell the ritual tag, or take it away.
+    pub fn toggle_ritual(&mut self, spell: &str) {
+        if self.is_ritual(spell) {
+            self.ritual_spells
+                .retain(|s| !s.eq_ignore_ascii_case(spell));
+        } else {
+            self.ritual_spells.push(spell.to_string());
+        }
+    }
+
+    /// Forget a known spell along with its prepared state, damage and
+    /// concentration and ritual marks.
+    pub fn remove_spell(&mut self, spell: &str) {
+        self.known_spells.retain(|s| s != spell);
+        self.prepared_spells.retain(|s| s != spell);
+        self.concentration_spells.retain(|s| s != spell);
+        self.ritual_spells.retain(|s| s != spell);
+        self.spell_damage.remove(spell);
+    }
+}
+
+// ============================================================================
+// Ability / Skill Helpers
+// ============================================================================
+
+/// The six standard abilities, in sheet order
+pub const ABILITY_NAMES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intelligence",
+    "wisdom",
+    "charisma",
+];
+
+/// The ability a standard skill uses by default (PHB pairing).
+///
+/// Accepts camelCase keys ("sleightOfHand") as well as spaced names.
+pub fn default_skill_ability(skill: &str) -> Option<&'static str> {
+    let key = skill.to_lowercase().replace([' ', '_'], "");
+    match key.as_str() {
+        "athletics" => Some("strength"),
+        "acrobatics" | "sleightofhand" | "stealth" => Some("dexterity"),
+        "arcana" | "history" | "investigation" | "nature" | "religion" => Some("intelligence"),
+        "animalhandling" | "insight" | "medicine" | "perception" | "survival" => Some("wisdom"),
+        "deception" | "intimidation" | "performance" | "persuasion" => Some("charisma"),
+        _ => None,
+    }
 }
 
 // ============================================================================
@@ -259,7 +919,8 @@ pub struct CharacterListEntry {
 }
 
 /// Resource for managing available characters
-#[derive(Resource, Default)]
+#[derive(Default)]
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
 pub struct CharacterManager {
     /// Characters loaded from database
     pub characters: Vec<CharacterListEntry>,
@@ -296,7 +957,8 @@ impl CharacterManager {
 // ============================================================================
 
 /// Resource containing the loaded character data
-#[derive(Resource, Default)]
+#[derive(Default)]
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
 pub struct CharacterData {
     pub sheet: Option<CharacterSheet>,
     /// Database ID for this character (None if not yet saved to database)
@@ -345,14 +1007,15 @@ impl CharacterData {
         let mut rng = rand::rng();
 
         // Roll d20 for each core attribute
-        let strength = rng.random_range(1..=20);
-        let dexterity = rng.random_range(1..=20);
-        let constitution = rng.random_range(1..=20);
-        let intelligence = rng.random_range(1..=20);
-        let wisdom = rng.random_range(1..=20);
-        let charisma = rng.random_range(1..=20);
-
-        // Calculate modifiers from attributes (D&D formula: (score - 10) / 2)
+        Self::create_with_scores(std::array::from_fn(|_| rng.random_range(1..=20)))
+    }
+
+    /// Create a new level 1 character with the given ability scores (in
+    /// `ABILITY_NAMES` order), e.g. from the character creation wizard.
+    pub fn create_with_scores(scores: [i32; 6]) -> Self {
+        let [strength, dexterity, constitution, intelligence, wisdom, charisma] = scores;
+
+        // Calculate modifiers from attributes
         let str_mod = Attributes::calculate_modifier(strength);
         let dex_mod = Attributes::calculate_modifier(dexterity);
         let con_mod = Attributes::calculate_modifier(constitution);
@@ -482,15 +1145,24 @@ impl CharacterData {
         skills
     }
 
-    /// Get the modifier for a skill by name
+    /// Get the modifier for a skill by name, with magic item bonuses
     pub fn get_skill_modifier(&self, skill: &str) -> Option<i32> {
-        self.sheet
-            .as_ref()
-            .and_then(|s| s.skills.get(skill).map(|sk| sk.modifier))
+        self.sheet.as_ref().and_then(|s| {
+            s.skills
+                .get(skill)
+                .map(|sk| sk.modifier + s.item_bonus(BonusRoll::Skill(skill)))
+        })
     }
 
-    /// Get the modifier for an ability by name
+    /// Get the modifier for an ability check by ability name, with magic
+    /// item bonuses
     pub fn get_ability_modifier(&self, ability: &str) -> Option<i32> {
+        let sheet = self.sheet.as_ref()?;
+        Some(self.base_ability_modifier(ability)? + sheet.item_bonus(BonusRoll::AbilityCheck))
+    }
+
+    /// The ability's modifier alone
+    fn base_ability_modifier(&self, ability: &str) -> Option<i32> {
         self.sheet.as_ref().map(|s| {
             let key = ability.to_lowercase();
             match key.as_str() {
@@ -519,12 +1191,43 @@ impl CharacterData {
         })
     }
 
-    /// Get the modifier for a saving throw by ability name
+    /// Get the modifier for a skill rolled with a non-default ability
+    /// (e.g. Intimidation with Strength).
+    ///
+    /// The modifier is the chosen ability's modifier plus proficiency
+    /// (doubled with expertise) when the character is proficient in the skill,
+    /// plus magic item bonuses.
+    pub fn get_skill_modifier_with_ability(&self, skill: &str, ability: &str) -> Option<i32> {
+        let sheet = self.sheet.as_ref()?;
+        let ability = normalize_ability_name(ability)?;
+        let items = sheet.item_bonus(BonusRoll::Skill(skill));
+        let skill = sheet.skills.get(skill).or_else(|| {
+            sheet
+                .skills
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(skill))
+                .map(|(_, s)| s)
+        })?;
+
+        let ability_mod = self.base_ability_modifier(ability)?;
+        let proficiency = if skill.expertise.unwrap_or(false) {
+            sheet.proficiency_bonus * 2
+        } else if skill.proficient {
+            sheet.proficiency_bonus
+        } else {
+            0
+        };
+
+        Some(ability_mod + proficiency + items)
+    }
+
+    /// Get the modifier for a saving throw by ability name, with magic item
+    /// bonuses
     pub fn get_saving_throw_modifier(&self, ability: &str) -> Option<i32> {
         self.sheet.as_ref().and_then(|s| {
             s.saving_throws
                 .get(&ability.to_lowercase())
-                .map(|st| st.modifier)
+                .map(|st| st.modifier + s.item_bonus(BonusRoll::Save(ability)))
         })
     }
 }
@@ -548,5 +1251,435 @@ mod tests {
         assert_eq!(Attributes::calculate_modifier(20), 5);
         assert_eq!(Attributes::calculate_modifier(8), -1);
         assert_eq!(Attributes::calculate_modifier(15), 2);
+        assert_eq!(Attributes::calculate_modifier(9), -1);
+        assert_eq!(Attributes::calculate_modifier(3), -4);
+    }
+
+    #[test]
+    fn test_concentration_replaces_previous_spell() {
+        let mut sheet = CharacterSheet::default();
+        assert!(sheet.concentration_conflict("Bless").is_none());
+        assert_eq!(sheet.start_concentration("Bless"), None);
+
+        assert_eq!(sheet.concentration_conflict("Haste"), Some("Bless"));
+        assert!(sheet.concentration_conflict("bless").is_none());
+
+        assert_eq!(
+            sheet.start_concentration("Haste"),
+            Some("Bless".to_string())
+        );
+        assert_eq!(sheet.concentration.as_deref(), Some("Haste"));
+
+        assert_eq!(sheet.end_concentration(), Some("Haste".to_string()));
+        assert!(sheet.concentration.is_none());
+    }
+
+    #[test]
+    fn test_record_death_saves() {
+        let mut sheet = CharacterSheet::default();
+        assert_eq!(sheet.record_death_save(12), DeathSaveOutcome::Success);
+        assert_eq!(sheet.record_death_save(1), DeathSaveOutcome::Failure);
+        let saves = sheet.combat.death_saves.clone().unwrap();
+        assert_eq!((saves.successes, saves.failures), (1, 2));
+
+        assert_eq!(sheet.record_death_save(10), DeathSaveOutcome::Success);
+        assert_eq!(sheet.record_death_save(15), DeathSaveOutcome::Stable);
+        assert_eq!(sheet.combat.death_saves.as_ref().unwrap().successes, 0);
+
+        sheet.combat.hit_points = Some(HitPoints {
+            current: 0,
+            maximum: 30,
+            temporary: 0,
+        });
+        sheet.record_death_save(5);
+        assert_eq!(sheet.record_death_save(20), DeathSaveOutcome::Revived);
+        assert_eq!(sheet.combat.hit_points.as_ref().unwrap().current, 1);
+        assert_eq!(sheet.combat.death_saves.as_ref().unwrap().failures, 0);
+
+        sheet.record_death_save(3);
+        sheet.record_death_save(4);
+        assert_eq!(sheet.record_death_save(1), DeathSaveOutcome::Dead);
+        assert_eq!(sheet.combat.death_saves.as_ref().unwrap().failures, 3);
+        assert!(!DeathSaveOutcome::Dead.survived());
+        assert!(DeathSaveOutcome::Stable.survived());
+    }
+
+    #[test]
+    fn test_weapon_attack() {
+        let mut sheet = CharacterSheet {
+            proficiency_bonus: 2,
+            ..Default::default()
+        };
+        sheet.modifiers.strength = 1;
+        sheet.modifiers.dexterity = 3;
+        let weapon: Weapon = serde_json::from_str(
+            r#"{"name":"Rapier","damage":"1d8","damageType":"piercing","properties":["Finesse"]}"#,
+        )
+        .unwrap();
+        assert_eq!(weapon.attack_bonus, 0);
+
+        let attack = sheet.weapon_attack(&weapon, AttackMode::Normal).unwrap();
+        assert_eq!(attack.ability, "dexterity");
+        assert_eq!((attack.attack_bonus, attack.damage.as_str()), (5, "1d8+3"));
+    }
+
+    #[test]
+    fn test_off_hand_attack() {
+        let mut sheet = CharacterSheet {
+            proficiency_bonus: 2,
+            ..Default::default()
+        };
+        sheet.modifiers.dexterity = 3;
+        sheet.combat.extra_attacks = 1;
+        assert_eq!(sheet.attacks_per_action(), 2);
+
+        let weapon = |name: &str, properties: &[&str]| Weapon {
+            name: name.to_string(),
+            damage: "1d6".to_string(),
+            damage_type: "piercing".to_string(),
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+        let shortsword = weapon("Shortsword", &["Finesse", "Light"]);
+        let rapier = weapon("Rapier", &["Finesse"]);
+
+        let attack = sheet.off_hand_attack(&shortsword, &shortsword).unwrap();
+        assert_eq!((attack.attack_bonus, attack.damage.as_str()), (5, "1d6"));
+        assert!(sheet.off_hand_attack(&rapier, &shortsword).is_err());
+
+        sheet.features.push(Feature {
+            name: "Fighting Style: Two-Weapon Fighting".to_string(),
+            description: String::new(),
+            damage: None,
+        });
+        sheet.features.push(Feature {
+            name: "Dual Wielder".to_string(),
+            description: String::new(),
+            damage: None,
+        });
+        let attack = sheet.off_hand_attack(&rapier, &shortsword).unwrap();
+        assert_eq!(attack.damage, "1d6+3");
+    }
+
+    #[test]
+    fn test_inventory_and_encumbrance() {
+        let equipment: Equipment = serde_json::from_str(
+            r#"{"items":["Torch (10)",{"name":"Chain Mail Spare","weight":55,"value":75}]}"#,
+        )
+        .unwrap();
+        assert_eq!(equipment.items[0].quantity, 10);
+
+        let mut sheet = CharacterSheet {
+            equipment: Some(equipment),
+            ..Default::default()
+        };
+        sheet.attributes.strength = 10;
+        assert_eq!(sheet.carried_weight(), 55.0);
+        assert_eq!(sheet.encumbrance(), Encumbrance::Encumbered);
+        sheet.attributes.strength = 11;
+        assert_eq!(sheet.encumbrance(), Encumbrance::Unencumbered);
+    }
+
+    #[test]
+    fn test_attuned_item_bonuses() {
+        let equipment: Equipment = serde_json::from_str(
+            r#"{"attunementSlots":4,"items":[
+                {"name":"Cloak of Protection","requiresAttunement":true,"attuned":true,
+                 "bonuses":["+1 saves"]},
+                {"name":"Rod of the Pact Keeper","requiresAttunement":true,
+                 "bonuses":["+1 spell attacks","+1 spell dc"]},
+                {"name":"Luckstone","equipped":true,"bonuses":["+1 checks"]}
+            ]}"#,
+        )
+        .unwrap();
+        let mut sheet = CharacterSheet {
+            equipment: Some(equipment),
+            proficiency_bonus: 2,
+            ..Default::default()
+        };
+        sheet.modifiers.dexterity = 3;
+        sheet.saving_throws.insert(
+            "dexterity".to_string(),
+            SavingThrow {
+                proficient: false,
+                modifier: 3,
+            },
+        );
+        sheet.skills.insert(
+            "stealth".to_string(),
+            Skill {
+                modifier: 3,
+                ..Default::default()
+            },
+        );
+        assert_eq!(sheet.attunement_slots(), 4);
+        // The rod isn't attuned, so it adds nothing yet
+        assert_eq!(sheet.spell_attack_bonus(), 2);
+
+        let data = CharacterData {
+            sheet: Some(sheet),
+            ..Default::default()
+        };
+        assert_eq!(data.get_saving_throw_modifier("dexterity"), Some(4));
+        assert_eq!(data.get_skill_modifier("stealth"), Some(4));
+        assert_eq!(data.get_ability_modifier("dex"), Some(4));
+        assert_eq!(
+            data.get_skill_modifier_with_ability("stealth", "dex"),
+            Some(4)
+        );
+
+        let mut sheet = data.sheet.unwrap();
+        sheet.equipment.as_mut().unwrap().items[1].attuned = true;
+        assert_eq!(sheet.spell_attack_bonus(), 3);
+        assert_eq!(sheet.spell_save_dc(), 11);
+        assert_eq!(sheet.sheet_spell_save_dc(), 10);
+    }
+
+    #[test]
+    fn test_take_damage() {
+        let mut sheet = CharacterSheet::default();
+        sheet.combat.hit_points = Some(HitPoints {
+            current: 20,
+            maximum: 30,
+            temporary: 5,
+        });
+        sheet
+            .damage_defenses
+            .toggle(super::super::damage_types::DefenseKind::Resistance, "void");
+
+        let taken = sheet.take_damage(9, Some("Void"));
+        assert_eq!(taken.adjusted.amount, 4);
+        assert_eq!((taken.from_temporary, taken.from_hit_points), (4, 0));
+
+        let taken = sheet.take_damage(30, Some("fire"));
+        assert_eq!((taken.from_temporary, taken.from_hit_points), (1, 20));
+        let hp = sheet.combat.hit_points.as_ref().unwrap();
+        assert_eq!((hp.current, hp.temporary), (0, 0));
+    }
+
+    #[test]
+    fn test_conditions_serde() {
+        let mut sheet = CharacterSheet::default();
+        let json = serde_json::to_string(&sheet).unwrap();
+        assert!(!json.contains("conditions"));
+
+        sheet
+            .conditions
+            .toggle(super::super::conditions::Condition::Prone);
+        sheet.conditions.set_exhaustion(1);
+        let json = serde_json::to_string(&sheet).unwrap();
+        let loaded: CharacterSheet = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.conditions, sheet.conditions);
+    }
+
+    #[test]
+    fn test_spell_slots_and_derived_stats() {
+        let mut sheet = CharacterSheet {
+            proficiency_bonus: 3,
+            ..Default::default()
+        };
+        sheet.modifiers.intelligence = 4;
+        sheet.spells = Some(SpellCasting {
+            spellcasting_ability: Some("INT".to_string()),
+            spell_slots: HashMap::from([("1".to_string(), 2)]),
+            known_spells: vec!["Fireball".to_string(), "Shield".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(sheet.spell_attack_bonus(), 7);
+        assert_eq!(sheet.spell_save_dc(), 15);
+
+        let spells = sheet.spells.as_mut().unwrap();
+        assert!(spells.expend_slot("1"));
+        assert!(spells.expend_slot("1"));
+        assert!(!spells.expend_slot("1"));
+        assert!(!spells.expend_slot("3"));
+        assert_eq!(spells.slots_remaining("1"), 0);
+        spells.restore_slot("1");
+        spells.restore_slot("3");
+        assert_eq!(spells.slots_remaining("1"), 1);
+
+        assert_eq!(spells.known_spell("fireball"), Some("Fireball"));
+        assert_eq!(spells.known_spell("Wish"), None);
+        spells.toggle_prepared("Fireball");
+        assert!(spells.is_prepared("fireball"));
+        spells.toggle_prepared("fireball");
+        assert!(!spells.is_prepared("Fireball"));
+
+        spells.toggle_concentration("Fireball");
+        assert!(spells.needs_concentration("FIREBALL"));
+        assert!(!spells.needs_concentration("Shield"));
+        spells.toggle_ritual("Fireball");
+        assert!(spells.is_ritual("fireball"));
+        spells.toggle_ritual("fireball");
+        assert!(!spells.is_ritual("Fireball"));
+        spells.toggle_ritual("Fireball");
+
+        spells.toggle_prepared("Fireball");
+        spells
+            .spell_damage
+            .insert("Fireball".to_string(), "8d6 fire".to_string());
+        spells.remove_spell("Fireball");
+        assert_eq!(spells.known_spells, vec!["Shield"]);
+        assert!(spells.prepared_spells.is_empty());
+        assert!(spells.concentration_spells.is_empty());
+        assert!(spells.ritual_spells.is_empty());
+        assert!(spells.spell_damage.is_empty());
+
+        // Values set on the sheet win over the derived ones
+        spells.spell_save_dc = Some(13);
+        assert_eq!(sheet.spell_save_dc(), 13);
+    }
+
+    #[test]
+    fn test_set_ability_scores_derives_saves_and_skills() {
+        let mut data = CharacterData::create_with_scores([15, 14, 13, 12, 10, 8]);
+        let sheet = data.sheet.as_mut().unwrap();
+        assert_eq!(sheet.modifiers.charisma, -1);
+        assert_eq!(sheet.combat.initiative, 2);
+
+        sheet.saving_throws.get_mut("strength").unwrap().proficient = true;
+        let stealth = sheet.skills.get_mut("stealth").unwrap();
+        stealth.proficient = true;
+        stealth.expertise = Some(true);
+        sheet.skills.get_mut("athletics").unwrap().proficient = true;
+
+        sheet.set_ability_scores([8, 16, 10, 10, 9, 10]);
+        assert_eq!(sheet.modifiers.get("dex"), Some(3));
+        assert_eq!(sheet.saving_throws["strength"].modifier, 1);
+        assert_eq!(sheet.saving_throws["wisdom"].modifier, -1);
+        assert_eq!(sheet.skills["stealth"].modifier, 7);
+        assert_eq!(sheet.skills["athletics"].modifier, 1);
+        assert_eq!(sheet.skills["perception"].modifier, -1);
+    }
+
+    #[test]
+    fn test_damage_riders() {
+        let mut sheet = CharacterSheet::default();
+        sheet.combat.damage_riders = vec![DamageRider::SneakAttack, DamageRider::DivineSmite];
+        let weapon: Weapon = serde_json::from_str(
+            r#"{"name":"Rapier","damage":"1d8","damageType":"piercing","properties":["Finesse"]}"#,
+        )
+        .unwrap();
+        let attack = sheet.weapon_attack(&weapon, AttackMode::Normal).unwrap();
+        assert_eq!(sheet.damage_riders_for(&attack, &[]).len(), 2);
+        assert_eq!(
+            sheet.damage_riders_for(&attack, &[DamageRider::SneakAttack]),
+            vec![&DamageRider::DivineSmite]
+        );
+
+        // Divine Smite takes the slot picked for it, or the lowest one left
+        let smite = DamageRider::DivineSmite;
+        assert_eq!(
+            sheet.spend_rider_slot(&DamageRider::SneakAttack, None),
+            Ok(0)
+        );
+        assert!(sheet.spend_rider_slot(&smite, None).is_err());
+        sheet.spells = Some(SpellCasting {
+            spell_slots: HashMap::from([
+                ("1".to_string(), 1),
+                ("2".to_string(), 1),
+                ("3".to_string(), 1),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(sheet.spend_rider_slot(&smite, Some(3)), Ok(3));
+        assert_eq!(
+            sheet.spend_rider_slot(&smite, Some(3)),
+            Err("No level 3 spell slots left for Divine Smite".to_string())
+        );
+        assert_eq!(
+            sheet.spells.as_ref().unwrap().slot_choices(),
+            vec![(1, 1), (2, 1)]
+        );
+        assert_eq!(sheet.spend_rider_slot(&smite, None), Ok(1));
+        assert_eq!(sheet.spend_rider_slot(&smite, None), Ok(2));
+        assert_eq!(
+            sheet.spend_rider_slot(&smite, Some(1)),
+            Err("No spell slots left for Divine Smite".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_skill_ability() {
+        assert_eq!(default_skill_ability("intimidation"), Some("charisma"));
+        assert_eq!(default_skill_ability("sleightOfHand"), Some("dexterity"));
+        assert_eq!(default_skill_ability("animal handling"), Some("wisdom"));
+        assert_eq!(default_skill_ability("basketWeaving"), None);
+    }
+
+    #[test]
+    fn test_skill_modifier_with_ability() {
+        let mut sheet = CharacterSheet {
+            proficiency_bonus: 3,
+            ..Default::default()
+        };
+        sheet.modifiers.strength = 4;
+        sheet.modifiers.charisma = -1;
+        sheet.skills.insert(
+            "intimidation".to_string(),
+            Skill {
+                proficient: true,
+                modifier: 2,
+                ..Default::default()
+            },
+        );
+        let data = CharacterData {
+            sheet: Some(sheet),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            data.get_skill_modifier_with_ability("intimidation", "str"),
+            Some(7)
+        );
+        assert_eq!(
+            data.get_skill_modifier_with_ability("Intimidation", "charisma"),
+            Some(2)
+        );
+        assert_eq!(
+            data.get_skill_modifier_with_ability("intimidation", "luck"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tool_synergy_for_skill() {
+        let sheet = CharacterSheet {
+            tool_proficiencies: vec![
+                ToolProficiency {
+                    name: "Thieves' Tools".to_string(),
+                    skills: vec!["sleightOfHand".to_string(), "investigation".to_string()],
+                    benefit: ToolSynergyBenefit::Advantage,
+                },
+                ToolProficiency {
+                    name: "Herbalism Kit".to_string(),
+                    skills: vec!["medicine".to_string(), "nature".to_string()],
+                    benefit: ToolSynergyBenefit::Bonus(2),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let synergy = sheet.tool_synergy_for_skill("sleight of hand");
+        assert!(synergy.advantage);
+        assert_eq!(synergy.bonus, 0);
+        assert_eq!(synergy.tools, vec!["Thieves' Tools".to_string()]);
+
+        let synergy = sheet.tool_synergy_for_skill("medicine");
+        assert!(!synergy.advantage);
+        assert_eq!(synergy.bonus, 2);
+        assert_eq!(synergy.describe(), "Herbalism Kit: +2");
+
+        assert!(!sheet.tool_synergy_for_skill("stealth").applies());
+    }
+
+    #[test]
+    fn test_tool_benefit_serde() {
+        let json = r#"{"name":"Smith's Tools","skills":["athletics"],"benefit":{"bonus":1}}"#;
+        let tool: ToolProficiency = serde_json::from_str(json).unwrap();
+        assert_eq!(tool.benefit, ToolSynergyBenefit::Bonus(1));
+
+        let tool: ToolProficiency = serde_json::from_str(r#"{"name":"Lute"}"#).unwrap();
+        assert_eq!(tool.benefit, ToolSynergyBenefit::Advantage);
     }
 }
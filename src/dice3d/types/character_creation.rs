@@ -0,0 +1,266 @@
+//! Ability scores for new characters
+//!
+//! The character creation wizard sets the six ability scores one of three
+//! ways: 4d6 with the lowest die dropped for each ability, the standard array
+//! (15, 14, 13, 12, 10, 8) assigned to the abilities, or 27-point point-buy
+//! with every score between 8 and 15.
+
+use super::character::{ability_abbreviation, ABILITY_NAMES};
+
+/// Scores handed out by the standard array
+pub const STANDARD_ARRAY: [i32; 6] = [15, 14, 13, 12, 10, 8];
+/// Points to spend in point-buy
+pub const POINT_BUY_BUDGET: i32 = 27;
+/// Lowest score point-buy allows (costs nothing)
+pub const POINT_BUY_MIN: i32 = 8;
+/// Highest score point-buy allows
+pub const POINT_BUY_MAX: i32 = 15;
+
+/// How the ability scores of a new character are decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreMethod {
+    /// 4d6 per ability, dropping the lowest die
+    #[default]
+    Roll,
+    StandardArray,
+    PointBuy,
+}
+
+impl ScoreMethod {
+    pub const ALL: [ScoreMethod; 3] = [
+        ScoreMethod::Roll,
+        ScoreMethod::StandardArray,
+        ScoreMethod::PointBuy,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreMethod::Roll => "Roll 4d6",
+            ScoreMethod::StandardArray => "Standard Array",
+            ScoreMethod::PointBuy => "Point Buy",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ScoreMethod::Roll => "Roll 4d6 for each ability and drop the lowest die",
+            ScoreMethod::StandardArray => "Assign 15, 14, 13, 12, 10 and 8 to the abilities",
+            ScoreMethod::PointBuy => "Spend 27 points on scores from 8 to 15",
+        }
+    }
+}
+
+/// Points a score costs in point-buy; `None` outside 8 to 15.
+pub fn point_buy_cost(score: i32) -> Option<i32> {
+    match score {
+        8..=13 => Some(score - POINT_BUY_MIN),
+        14 => Some(7),
+        15 => Some(9),
+        _ => None,
+    }
+}
+
+/// Total of four d6 without the lowest one
+pub fn drop_lowest(dice: [u32; 4]) -> i32 {
+    let lowest = dice.iter().min().copied().unwrap_or(0);
+    (dice.iter().sum::<u32>() - lowest) as i32
+}
+
+/// Ability scores being decided for a new character, in `ABILITY_NAMES`
+/// order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbilityScores {
+    pub method: ScoreMethod,
+    /// Score of each ability; `None` until rolled or assigned
+    pub scores: [Option<i32>; 6],
+    /// The four d6 rolled for each ability
+    pub rolls: [Option<[u32; 4]>; 6],
+}
+
+impl Default for AbilityScores {
+    fn default() -> Self {
+        Self::new(ScoreMethod::default())
+    }
+}
+
+impl AbilityScores {
+    /// Nothing rolled or assigned yet; point-buy starts every score at 8.
+    pub fn new(method: ScoreMethod) -> Self {
+        let start = match method {
+            ScoreMethod::PointBuy => Some(POINT_BUY_MIN),
+            ScoreMethod::Roll | ScoreMethod::StandardArray => None,
+        };
+        Self {
+            method,
+            scores: [start; 6],
+            rolls: [None; 6],
+        }
+    }
+
+    /// Record the 4d6 rolled for an ability; its score drops the lowest die.
+    pub fn record_roll(&mut self, ability: usize, dice: [u32; 4]) {
+        self.rolls[ability] = Some(dice);
+        self.scores[ability] = Some(drop_lowest(dice));
+    }
+
+    /// Standard array values no ability has been given yet.
+    pub fn unassigned(&self) -> Vec<i32> {
+        let mut left = STANDARD_ARRAY.to_vec();
+        for score in self.scores.iter().flatten() {
+            if let Some(index) = left.iter().position(|value| value == score) {
+                left.remove(index);
+            }
+        }
+        left
+    }
+
+    /// Give an ability the next standard array value after its current one
+    /// that's still free, going back to unassigned after the lowest.
+    pub fn cycle_standard(&mut self, ability: usize) -> Option<i32> {
+        let current = self.scores[ability].take();
+        let free = self.unassigned();
+        let next = match current {
+            Some(score) => free.into_iter().find(|value| *value < score),
+            None => free.first().copied(),
+        };
+        self.scores[ability] = next;
+        next
+    }
+
+    /// Points spent so far in point-buy.
+    pub fn points_spent(&self) -> i32 {
+        self.scores
+            .iter()
+            .flatten()
+            .filter_map(|score| point_buy_cost(*score))
+            .sum()
+    }
+
+    /// Points left to spend in point-buy.
+    pub fn points_left(&self) -> i32 {
+        POINT_BUY_BUDGET - self.points_spent()
+    }
+
+    /// Raise or lower a point-buy score, refusing to leave 8 to 15 or to go
+    /// over the budget.
+    pub fn adjust_point_buy(&mut self, ability: usize, delta: i32) -> Result<i32, String> {
+        let name = ability_abbreviation(ABILITY_NAMES[ability]);
+        let score = self.scores[ability].unwrap_or(POINT_BUY_MIN) + delta;
+        let cost = point_buy_cost(score).ok_or_else(|| {
+            format!(
+                "Point-buy scores go from {} to {}",
+                POINT_BUY_MIN, POINT_BUY_MAX
+            )
+        })?;
+        let current = self.scores[ability].and_then(point_buy_cost).unwrap_or(0);
+        if self.points_spent() - current + cost > POINT_BUY_BUDGET {
+            return Err(format!(
+                "Not enough points left to raise {} to {}",
+                name, score
+            ));
+        }
+        self.scores[ability] = Some(score);
+        Ok(score)
+    }
+
+    /// The finished scores, or what's still missing or wrong with them.
+    pub fn validate(&self) -> Result<[i32; 6], String> {
+        let mut scores = [0; 6];
+        for (i, score) in self.scores.iter().enumerate() {
+            scores[i] = score.ok_or_else(|| match self.method {
+                ScoreMethod::Roll => {
+                    format!("Roll {} first", ability_abbreviation(ABILITY_NAMES[i]))
+                }
+                _ => format!("Give {} a score", ability_abbreviation(ABILITY_NAMES[i])),
+            })?;
+        }
+        match self.method {
+            ScoreMethod::Roll => {}
+            ScoreMethod::StandardArray => {
+                let mut sorted = scores;
+                sorted.sort_unstable_by(|a, b| b.cmp(a));
+                if sorted != STANDARD_ARRAY {
+                    return Err("Use each standard array value once".to_string());
+                }
+            }
+            ScoreMethod::PointBuy => {
+                if scores.iter().any(|score| point_buy_cost(*score).is_none()) {
+                    return Err(format!(
+                        "Point-buy scores go from {} to {}",
+                        POINT_BUY_MIN, POINT_BUY_MAX
+                    ));
+                }
+                let left = self.points_left();
+                if left < 0 {
+                    return Err(format!("{} points over the budget", -left));
+                }
+            }
+        }
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_buy() {
+        assert_eq!(point_buy_cost(8), Some(0));
+        assert_eq!(point_buy_cost(13), Some(5));
+        assert_eq!(point_buy_cost(15), Some(9));
+        assert_eq!(point_buy_cost(16), None);
+
+        let mut scores = AbilityScores::new(ScoreMethod::PointBuy);
+        assert_eq!(scores.points_left(), 27);
+        assert!(scores.adjust_point_buy(0, -1).is_err());
+        for _ in 0..7 {
+            scores.adjust_point_buy(0, 1).unwrap();
+        }
+        assert_eq!(scores.scores[0], Some(15));
+        assert!(scores.adjust_point_buy(0, 1).is_err());
+        for ability in 1..3 {
+            for _ in 0..7 {
+                scores.adjust_point_buy(ability, 1).unwrap();
+            }
+        }
+        // 15, 15, 15 spends all 27
+        assert_eq!(scores.points_left(), 0);
+        assert_eq!(
+            scores.adjust_point_buy(3, 1),
+            Err("Not enough points left to raise INT to 9".to_string())
+        );
+        assert_eq!(scores.validate(), Ok([15, 15, 15, 8, 8, 8]));
+    }
+
+    #[test]
+    fn test_standard_array() {
+        let mut scores = AbilityScores::new(ScoreMethod::StandardArray);
+        assert!(scores.validate().is_err());
+        assert_eq!(scores.cycle_standard(0), Some(15));
+        assert_eq!(scores.cycle_standard(1), Some(14));
+        // 15 is taken, so strength steps down past it
+        assert_eq!(scores.cycle_standard(0), Some(13));
+        assert_eq!(scores.unassigned(), vec![15, 12, 10, 8]);
+        for ability in 2..6 {
+            scores.cycle_standard(ability);
+        }
+        assert_eq!(scores.validate(), Ok([13, 14, 15, 12, 10, 8]));
+        // After the lowest value free an ability goes back to unassigned
+        assert_eq!(scores.cycle_standard(5), None);
+        assert_eq!(scores.cycle_standard(5), Some(8));
+    }
+
+    #[test]
+    fn test_rolled_scores() {
+        assert_eq!(drop_lowest([6, 1, 4, 3]), 13);
+        let mut scores = AbilityScores::new(ScoreMethod::Roll);
+        scores.record_roll(0, [6, 6, 6, 1]);
+        assert_eq!(scores.scores[0], Some(18));
+        assert_eq!(scores.validate(), Err("Roll DEX first".to_string()));
+        for ability in 1..6 {
+            scores.record_roll(ability, [3, 3, 3, 3]);
+        }
+        assert_eq!(scores.validate(), Ok([18, 9, 9, 9, 9, 9]));
+    }
+}
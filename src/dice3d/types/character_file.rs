@@ -0,0 +1,295 @@
+//! Shareable character files (`.dndchar`)
+//!
+//! A character file is a single compact JSON document holding a character
+//! sheet and, optionally, a small portrait, so a player can send their
+//! character to the DM. The receiving app shows it read-only or imports it,
+//! either as a regular character or as an NPC. Files that hold just a
+//! character sheet (the original `.dndchar` contents) still open, and so do
+//! D&D Beyond character exports and Foundry VTT actors.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use dndgamerolls_core::check_character_sheet;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::Path;
+
+use super::{
+    ddb_to_sheet_json, foundry_to_sheet_json, is_ddb_character, is_foundry_actor, CharacterSheet,
+    FieldPermissions,
+};
+
+/// File extension registered for character files.
+pub const CHARACTER_FILE_EXTENSION: &str = "dndchar";
+
+/// Value of the `format` field identifying a character file
+pub const CHARACTER_FILE_FORMAT: &str = "dndgamerolls-character";
+
+/// Newest character file version this build reads and writes
+pub const CHARACTER_FILE_VERSION: u32 = 1;
+
+/// Longest side of an embedded portrait, in pixels
+pub const PORTRAIT_MAX_SIZE: u32 = 256;
+
+/// Custom Basic Info field marking characters imported as NPCs
+pub const NPC_ROLE_FIELD: &str = "Role";
+pub const NPC_ROLE_VALUE: &str = "NPC";
+
+/// A character sheet packaged for sharing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterFile {
+    pub format: String,
+    pub version: u32,
+    pub sheet: CharacterSheet,
+    /// PNG portrait, base64 encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portrait: Option<String>,
+    /// Fields of an imported VTT actor that had no place on the sheet
+    #[serde(skip)]
+    pub unmapped: Vec<String>,
+}
+
+impl CharacterFile {
+    pub fn new(sheet: CharacterSheet) -> Self {
+        Self {
+            format: CHARACTER_FILE_FORMAT.to_string(),
+            version: CHARACTER_FILE_VERSION,
+            sheet,
+            portrait: None,
+            unmapped: Vec::new(),
+        }
+    }
+
+    /// Embed a portrait from any image the `image` crate can decode.
+    ///
+    /// The image is scaled down to fit [`PORTRAIT_MAX_SIZE`] and stored as PNG
+    /// to keep the file small.
+    pub fn with_portrait(mut self, image_bytes: &[u8]) -> Result<Self, String> {
+        let mut img = image::load_from_memory(image_bytes)
+            .map_err(|e| format!("Unsupported portrait image: {}", e))?;
+        if img.width() > PORTRAIT_MAX_SIZE || img.height() > PORTRAIT_MAX_SIZE {
+            img = img.thumbnail(PORTRAIT_MAX_SIZE, PORTRAIT_MAX_SIZE);
+        }
+
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode portrait: {}", e))?;
+        self.portrait = Some(BASE64.encode(png));
+        Ok(self)
+    }
+
+    /// Decode the embedded portrait, if there is one.
+    pub fn portrait_image(&self) -> Result<Option<image::RgbaImage>, String> {
+        let Some(encoded) = &self.portrait else {
+            return Ok(None);
+        };
+        let png = BASE64
+            .decode(encoded)
+            .map_err(|e| format!("Invalid portrait encoding: {}", e))?;
+        let img =
+            image::load_from_memory(&png).map_err(|e| format!("Invalid portrait image: {}", e))?;
+        Ok(Some(img.to_rgba8()))
+    }
+
+    /// Serialize as compact JSON.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize character: {}", e))
+    }
+
+    /// Parse a character file, accepting plain character sheet JSON, D&D
+    /// Beyond character exports and Foundry VTT actor exports too.
+    ///
+    /// Sheets are checked against the character sheet JSON Schema first, so
+    /// the error lists each bad field by path instead of a serde message.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        if is_ddb_character(&value) {
+            let sheet = serde_json::from_value::<CharacterSheet>(ddb_to_sheet_json(&value)?)
+                .map_err(|e| format!("Invalid D&D Beyond character: {}", e))?;
+            return Ok(Self::new(sheet));
+        }
+
+        if is_foundry_actor(&value) {
+            let conversion = foundry_to_sheet_json(&value)?;
+            let sheet = serde_json::from_value::<CharacterSheet>(conversion.json)
+                .map_err(|e| format!("Invalid Foundry VTT actor: {}", e))?;
+            let mut file = Self::new(sheet);
+            file.unmapped = conversion.unmapped;
+            return Ok(file);
+        }
+
+        let Some(format) = value.get("format") else {
+            check_character_sheet(&value)
+                .map_err(|report| format!("Invalid character sheet:\n{}", report))?;
+            let sheet = serde_json::from_value::<CharacterSheet>(value)
+                .map_err(|e| format!("Invalid character sheet: {}", e))?;
+            return Ok(Self::new(sheet));
+        };
+
+        if format.as_str() != Some(CHARACTER_FILE_FORMAT) {
+            return Err(format!("Not a character file (format {})", format));
+        }
+        if let Some(sheet) = value.get("sheet") {
+            check_character_sheet(sheet)
+                .map_err(|report| format!("Invalid character sheet:\n{}", report))?;
+        }
+        let file = serde_json::from_value::<CharacterFile>(value)
+            .map_err(|e| format!("Invalid character file: {}", e))?;
+        if file.version > CHARACTER_FILE_VERSION {
+            return Err(format!(
+                "Character file version {} needs a newer version of the app",
+                file.version
+            ));
+        }
+        Ok(file)
+    }
+
+    /// "Level 5 Elf Wizard"
+    pub fn summary(&self) -> String {
+        let info = &self.sheet.character;
+        format!("Level {} {} {}", info.level, info.race, info.class)
+    }
+
+    /// The sheet to store when importing the character as an NPC.
+    ///
+    /// NPCs are tagged through a custom Basic Info field and don't carry over
+    /// the player's concentration. Unless the file says otherwise, players
+    /// only see their hit point state and roll totals.
+    pub fn npc_sheet(&self) -> CharacterSheet {
+        let mut sheet = self.sheet.clone();
+        sheet
+            .custom_basic_info
+            .insert(NPC_ROLE_FIELD.to_string(), NPC_ROLE_VALUE.to_string());
+        sheet.concentration = None;
+        if sheet.field_visibility.is_empty() {
+            sheet.field_visibility = FieldPermissions::npc_default();
+        }
+        sheet
+    }
+}
+
+/// Whether a path looks like a character file.
+pub fn is_character_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(CHARACTER_FILE_EXTENSION))
+}
+
+/// Read a character file (a shared character or a plain character sheet).
+pub fn read_character_file(path: &Path) -> Result<CharacterFile, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    CharacterFile::from_json(&text)
+        .map_err(|e| format!("Invalid character file {}: {}", path.display(), e))
+}
+
+/// Write a character file.
+pub fn write_character_file(path: &Path, file: &CharacterFile) -> Result<(), String> {
+    std::fs::write(path, file.to_json()?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::sheet_json_to_foundry;
+
+    fn test_sheet() -> CharacterSheet {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Elara".to_string();
+        sheet.character.race = "Elf".to_string();
+        sheet.character.class = "Wizard".to_string();
+        sheet.character.level = 5;
+        sheet
+    }
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let img = image::RgbaImage::from_pixel(width, height, image::Rgba([200, 40, 40, 255]));
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_character_file_round_trip() {
+        let file = CharacterFile::new(test_sheet())
+            .with_portrait(&png_bytes(600, 300))
+            .unwrap();
+        let json = file.to_json().unwrap();
+        assert!(!json.contains('\n'));
+
+        let parsed = CharacterFile::from_json(&json).unwrap();
+        assert_eq!(parsed.sheet.character.name, "Elara");
+        assert_eq!(parsed.summary(), "Level 5 Elf Wizard");
+
+        // Portrait is scaled down to fit, keeping its aspect ratio
+        let portrait = parsed.portrait_image().unwrap().unwrap();
+        assert_eq!(portrait.dimensions(), (256, 128));
+    }
+
+    #[test]
+    fn test_plain_sheet_still_opens() {
+        let json = serde_json::to_string(&test_sheet()).unwrap();
+        let parsed = CharacterFile::from_json(&json).unwrap();
+        assert_eq!(parsed.sheet.character.name, "Elara");
+        assert!(parsed.portrait_image().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invalid_sheet_lists_schema_errors() {
+        let mut sheet = serde_json::to_value(test_sheet()).unwrap();
+        sheet["character"]["level"] = serde_json::json!("five");
+        let err = CharacterFile::from_json(&sheet.to_string()).unwrap_err();
+        assert_eq!(
+            err,
+            "Invalid character sheet:\n- character.level: expected a whole number from 0 to 20, found text \"five\" (e.g. 5)"
+        );
+
+        let mut file = serde_json::to_value(CharacterFile::new(test_sheet())).unwrap();
+        file["sheet"]["attributes"]["wisdom"] = serde_json::json!(-3);
+        let err = CharacterFile::from_json(&file.to_string()).unwrap_err();
+        assert!(err.contains("- attributes.wisdom: expected a whole number from 0 to 30, found -3"));
+    }
+
+    #[test]
+    fn test_foundry_actor_opens_with_unmapped_fields() {
+        let sheet = serde_json::to_value(test_sheet()).unwrap();
+        let mut actor = sheet_json_to_foundry(&sheet).unwrap().json;
+        actor["effects"] = serde_json::json!([{ "name": "Mage Armor" }]);
+
+        let parsed = CharacterFile::from_json(&actor.to_string()).unwrap();
+        assert_eq!(parsed.sheet.character.name, "Elara");
+        assert_eq!(parsed.unmapped, vec!["effects (1 active effects)"]);
+    }
+
+    #[test]
+    fn test_rejects_other_formats_and_newer_versions() {
+        assert!(CharacterFile::from_json(r#"{"format":"something-else","version":1}"#).is_err());
+
+        let mut file = CharacterFile::new(test_sheet());
+        file.version = CHARACTER_FILE_VERSION + 1;
+        assert!(CharacterFile::from_json(&file.to_json().unwrap()).is_err());
+        assert!(CharacterFile::new(test_sheet())
+            .with_portrait(b"not an image")
+            .is_err());
+    }
+
+    #[test]
+    fn test_npc_sheet_is_tagged() {
+        let mut sheet = test_sheet();
+        sheet.concentration = Some("Haste".to_string());
+        let npc = CharacterFile::new(sheet).npc_sheet();
+        assert_eq!(
+            npc.custom_basic_info
+                .get(NPC_ROLE_FIELD)
+                .map(String::as_str),
+            Some(NPC_ROLE_VALUE)
+        );
+        assert!(npc.concentration.is_none());
+        assert_eq!(npc.field_visibility, FieldPermissions::npc_default());
+    }
+}
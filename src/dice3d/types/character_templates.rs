@@ -0,0 +1,924 @@
+//! Race, class and background templates for new characters
+//!
+//! The character creation wizard pre-fills speed, hit die, saving throw
+//! proficiencies and skill choices from a race and a class, and the
+//! background, equipment and spells from the options in `creation_options`.
+//! The SRD templates are built in; homebrew templates are imported from JSON
+//! and kept in the profile database:
+//!
+//! ```json
+//! {
+//!   "races": [{ "name": "Centaur", "speed": 40, "skills": ["survival"] }],
+//!   "classes": [{
+//!     "name": "Blood Hunter", "hitDie": 10,
+//!     "savingThrows": ["strength", "wisdom"],
+//!     "skillChoices": ["acrobatics", "athletics", "insight"], "skillCount": 3
+//!   }]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::character::{
+    ability_abbreviation, default_skill_ability, normalize_ability_name, Attributes, CharacterData,
+    CharacterSheet, HitDice, HitPoints, ABILITY_NAMES,
+};
+use super::creation_options::{
+    srd_backgrounds, srd_equipment, srd_spellcasting, validate_background, validate_class_options,
+    BackgroundTemplate, ClassSpellcasting, EquipmentPack,
+};
+
+/// Database setting key holding the homebrew templates of the current profile
+pub const HOMEBREW_TEMPLATES_DB_KEY: &str = "homebrew_templates";
+
+/// Speed and skill proficiencies a race gives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaceTemplate {
+    pub name: String,
+    pub speed: i32,
+    /// Skills the race is always proficient in (camelCase keys)
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub homebrew: bool,
+}
+
+/// Hit die, saving throws and skill choices of a class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassTemplate {
+    pub name: String,
+    /// Sides of the hit die (d10 is 10)
+    pub hit_die: u32,
+    /// Abilities whose saving throws the class is proficient in
+    pub saving_throws: Vec<String>,
+    /// Skills the class picks from (camelCase keys)
+    pub skill_choices: Vec<String>,
+    /// How many of `skill_choices` are picked
+    pub skill_count: usize,
+    /// Starting equipment packs, one of which is picked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub equipment: Vec<EquipmentPack>,
+    /// Cantrips and 1st-level spells, for classes that cast at 1st level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spellcasting: Option<ClassSpellcasting>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub homebrew: bool,
+}
+
+/// Every skill, for classes that pick any of them (the Bard)
+const ALL_SKILLS: [&str; 18] = [
+    "acrobatics",
+    "animalHandling",
+    "arcana",
+    "athletics",
+    "deception",
+    "history",
+    "insight",
+    "intimidation",
+    "investigation",
+    "medicine",
+    "nature",
+    "perception",
+    "performance",
+    "persuasion",
+    "religion",
+    "sleightOfHand",
+    "stealth",
+    "survival",
+];
+
+fn race(name: &str, speed: i32, skills: &[&str]) -> RaceTemplate {
+    RaceTemplate {
+        name: name.to_string(),
+        speed,
+        skills: skills.iter().map(|s| s.to_string()).collect(),
+        homebrew: false,
+    }
+}
+
+fn class(
+    name: &str,
+    hit_die: u32,
+    saves: [&str; 2],
+    skills: &[&str],
+    count: usize,
+) -> ClassTemplate {
+    ClassTemplate {
+        name: name.to_string(),
+        hit_die,
+        saving_throws: saves.iter().map(|s| s.to_string()).collect(),
+        skill_choices: skills.iter().map(|s| s.to_string()).collect(),
+        skill_count: count,
+        equipment: srd_equipment(name),
+        spellcasting: srd_spellcasting(name),
+        homebrew: false,
+    }
+}
+
+/// The races, classes and backgrounds the wizard offers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
+pub struct CharacterTemplates {
+    #[serde(default)]
+    pub races: Vec<RaceTemplate>,
+    #[serde(default)]
+    pub classes: Vec<ClassTemplate>,
+    #[serde(default)]
+    pub backgrounds: Vec<BackgroundTemplate>,
+}
+
+impl CharacterTemplates {
+    /// The SRD races, classes and backgrounds.
+    pub fn srd() -> Self {
+        Self {
+            races: vec![
+                race("Dragonborn", 30, &[]),
+                race("Dwarf", 25, &[]),
+                race("Elf", 30, &["perception"]),
+                race("Gnome", 25, &[]),
+                race("Half-Elf", 30, &[]),
+                race("Half-Orc", 30, &["intimidation"]),
+                race("Halfling", 25, &[]),
+                race("Human", 30, &[]),
+                race("Tiefling", 30, &[]),
+            ],
+            classes: vec![
+                class(
+                    "Barbarian",
+                    12,
+                    ["strength", "constitution"],
+                    &[
+                        "animalHandling",
+                        "athletics",
+                        "intimidation",
+                        "nature",
+                        "perception",
+                        "survival",
+                    ],
+                    2,
+                ),
+                class("Bard", 8, ["dexterity", "charisma"], &ALL_SKILLS, 3),
+                class(
+                    "Cleric",
+                    8,
+                    ["wisdom", "charisma"],
+                    &["history", "insight", "medicine", "persuasion", "religion"],
+                    2,
+                ),
+                class(
+                    "Druid",
+                    8,
+                    ["intelligence", "wisdom"],
+                    &[
+                        "arcana",
+                        "animalHandling",
+                        "insight",
+                        "medicine",
+                        "nature",
+                        "perception",
+                        "religion",
+                        "survival",
+                    ],
+                    2,
+                ),
+                class(
+                    "Fighter",
+                    10,
+                    ["strength", "constitution"],
+                    &[
+                        "acrobatics",
+                        "animalHandling",
+                        "athletics",
+                        "history",
+                        "insight",
+                        "intimidation",
+                        "perception",
+                        "survival",
+                    ],
+                    2,
+                ),
+                class(
+                    "Monk",
+                    8,
+                    ["strength", "dexterity"],
+                    &[
+                        "acrobatics",
+                        "athletics",
+                        "history",
+                        "insight",
+                        "religion",
+                        "stealth",
+                    ],
+                    2,
+                ),
+                class(
+                    "Paladin",
+                    10,
+                    ["wisdom", "charisma"],
+                    &[
+                        "athletics",
+                        "insight",
+                        "intimidation",
+                        "medicine",
+                        "persuasion",
+                        "religion",
+                    ],
+                    2,
+                ),
+                class(
+                    "Ranger",
+                    10,
+                    ["strength", "dexterity"],
+                    &[
+                        "animalHandling",
+                        "athletics",
+                        "insight",
+                        "investigation",
+                        "nature",
+                        "perception",
+                        "stealth",
+                        "survival",
+                    ],
+                    3,
+                ),
+                class(
+                    "Rogue",
+                    8,
+                    ["dexterity", "intelligence"],
+                    &[
+                        "acrobatics",
+                        "athletics",
+                        "deception",
+                        "insight",
+                        "intimidation",
+                        "investigation",
+                        "perception",
+                        "performance",
+                        "persuasion",
+                        "sleightOfHand",
+                        "stealth",
+                    ],
+                    4,
+                ),
+                class(
+                    "Sorcerer",
+                    6,
+                    ["constitution", "charisma"],
+                    &[
+                        "arcana",
+                        "deception",
+                        "insight",
+                        "intimidation",
+                        "persuasion",
+                        "religion",
+                    ],
+                    2,
+                ),
+                class(
+                    "Warlock",
+                    8,
+                    ["wisdom", "charisma"],
+                    &[
+                        "arcana",
+                        "deception",
+                        "history",
+                        "intimidation",
+                        "investigation",
+                        "nature",
+                        "religion",
+                    ],
+                    2,
+                ),
+                class(
+                    "Wizard",
+                    6,
+                    ["intelligence", "wisdom"],
+                    &[
+                        "arcana",
+                        "history",
+                        "insight",
+                        "investigation",
+                        "medicine",
+                        "religion",
+                    ],
+                    2,
+                ),
+            ],
+            backgrounds: srd_backgrounds(),
+        }
+    }
+
+    /// The SRD templates plus the given homebrew ones.
+    pub fn with_homebrew(homebrew: CharacterTemplates) -> Self {
+        let mut templates = Self::srd();
+        templates.add_homebrew(homebrew);
+        templates
+    }
+
+    /// Check homebrew templates read from a file, naming the first problem.
+    pub fn validate(&self) -> Result<(), String> {
+        for race in &self.races {
+            if race.name.trim().is_empty() {
+                return Err("A race has no name".to_string());
+            }
+            if race.speed <= 0 {
+                return Err(format!("{} needs a speed above 0", race.name));
+            }
+            if let Some(skill) = race
+                .skills
+                .iter()
+                .find(|s| default_skill_ability(s).is_none())
+            {
+                return Err(format!("{} has an unknown skill '{}'", race.name, skill));
+            }
+        }
+        for class in &self.classes {
+            if class.name.trim().is_empty() {
+                return Err("A class has no name".to_string());
+            }
+            if ![6, 8, 10, 12].contains(&class.hit_die) {
+                return Err(format!("{} needs a d6, d8, d10 or d12 hit die", class.name));
+            }
+            if let Some(save) = class
+                .saving_throws
+                .iter()
+                .find(|s| normalize_ability_name(s).is_none())
+            {
+                return Err(format!(
+                    "{} has an unknown saving throw '{}'",
+                    class.name, save
+                ));
+            }
+            if let Some(skill) = class
+                .skill_choices
+                .iter()
+                .find(|s| default_skill_ability(s).is_none())
+            {
+                return Err(format!("{} has an unknown skill '{}'", class.name, skill));
+            }
+            if class.skill_count > class.skill_choices.len() {
+                return Err(format!(
+                    "{} picks {} skills from only {}",
+                    class.name,
+                    class.skill_count,
+                    class.skill_choices.len()
+                ));
+            }
+            validate_class_options(&class.name, &class.equipment, class.spellcasting.as_ref())?;
+        }
+        for background in &self.backgrounds {
+            validate_background(background)?;
+        }
+        Ok(())
+    }
+
+    /// Add homebrew templates, replacing any with the same name (SRD ones
+    /// included). Returns how many were added.
+    pub fn add_homebrew(&mut self, homebrew: CharacterTemplates) -> usize {
+        let added = homebrew.races.len() + homebrew.classes.len() + homebrew.backgrounds.len();
+        for mut race in homebrew.races {
+            race.homebrew = true;
+            self.races
+                .retain(|r| !r.name.eq_ignore_ascii_case(&race.name));
+            self.races.push(race);
+        }
+        for mut class in homebrew.classes {
+            class.homebrew = true;
+            self.classes
+                .retain(|c| !c.name.eq_ignore_ascii_case(&class.name));
+            self.classes.push(class);
+        }
+        for mut background in homebrew.backgrounds {
+            background.homebrew = true;
+            self.backgrounds
+                .retain(|b| !b.name.eq_ignore_ascii_case(&background.name));
+            self.backgrounds.push(background);
+        }
+        added
+    }
+
+    /// Remove a homebrew race, class or background by name. A homebrew template that
+    /// replaced an SRD one brings the SRD one back.
+    pub fn remove_homebrew(&mut self, name: &str) -> bool {
+        let mut homebrew = self.homebrew();
+        let count = |t: &CharacterTemplates| t.races.len() + t.classes.len() + t.backgrounds.len();
+        let before = count(&homebrew);
+        homebrew
+            .races
+            .retain(|r| !r.name.eq_ignore_ascii_case(name));
+        homebrew
+            .classes
+            .retain(|c| !c.name.eq_ignore_ascii_case(name));
+        homebrew
+            .backgrounds
+            .retain(|b| !b.name.eq_ignore_ascii_case(name));
+        if count(&homebrew) == before {
+            return false;
+        }
+        *self = Self::with_homebrew(homebrew);
+        true
+    }
+
+    /// Only the homebrew templates, as stored in the database.
+    pub fn homebrew(&self) -> CharacterTemplates {
+        CharacterTemplates {
+            races: self.races.iter().filter(|r| r.homebrew).cloned().collect(),
+            classes: self
+                .classes
+                .iter()
+                .filter(|c| c.homebrew)
+                .cloned()
+                .collect(),
+            backgrounds: self
+                .backgrounds
+                .iter()
+                .filter(|b| b.homebrew)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn race(&self, name: &str) -> Option<&RaceTemplate> {
+        self.races
+            .iter()
+            .find(|r| r.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn class(&self, name: &str) -> Option<&ClassTemplate> {
+        self.classes
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn background(&self, name: &str) -> Option<&BackgroundTemplate> {
+        self.backgrounds
+            .iter()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Everything picked in the wizard besides the ability scores
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateChoice {
+    pub race: Option<String>,
+    pub class: Option<String>,
+    pub background: Option<String>,
+    /// Class skill picks; the race's and background's skills come on top
+    pub skills: Vec<String>,
+    /// Index of the picked pack in the class's `equipment`
+    pub equipment: Option<usize>,
+    pub cantrips: Vec<String>,
+    pub spells: Vec<String>,
+}
+
+/// Modifier of `ability` among scores in `ABILITY_NAMES` order
+fn score_modifier(scores: [i32; 6], ability: &str) -> i32 {
+    let ability = normalize_ability_name(ability).unwrap_or(ability);
+    ABILITY_NAMES
+        .iter()
+        .position(|name| *name == ability)
+        .map(|i| Attributes::calculate_modifier(scores[i]))
+        .unwrap_or(0)
+}
+
+fn pick_more(count: usize, what: &str) -> String {
+    format!(
+        "Pick {} more {}{}",
+        count,
+        what,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+impl TemplateChoice {
+    /// Pick a race, dropping class skills it already gives.
+    pub fn set_race(&mut self, race: &RaceTemplate) {
+        self.skills.retain(|s| !race.skills.contains(s));
+        self.race = Some(race.name.clone());
+    }
+
+    /// Pick a class, dropping skills it can't choose. A different class
+    /// starts the equipment and spells over.
+    pub fn set_class(&mut self, class: &ClassTemplate) {
+        self.skills.retain(|s| class.skill_choices.contains(s));
+        self.skills.truncate(class.skill_count);
+        if self.class.as_deref() != Some(class.name.as_str()) {
+            self.equipment = None;
+            self.cantrips.clear();
+            self.spells.clear();
+        }
+        self.class = Some(class.name.clone());
+    }
+
+    /// Pick a background, dropping class skills it already gives.
+    pub fn set_background(&mut self, background: &BackgroundTemplate) {
+        self.skills.retain(|s| !background.skills.contains(s));
+        self.background = Some(background.name.clone());
+    }
+
+    /// Pick or unpick a class skill, refusing more than the class allows.
+    pub fn toggle_skill(&mut self, class: &ClassTemplate, skill: &str) -> Result<(), String> {
+        if let Some(index) = self.skills.iter().position(|s| s == skill) {
+            self.skills.remove(index);
+            return Ok(());
+        }
+        if !class.skill_choices.iter().any(|s| s == skill) {
+            return Err(format!("{} can't pick {}", class.name, skill));
+        }
+        if self.skills.len() >= class.skill_count {
+            return Err(format!(
+                "{} picks {} skills; unpick one first",
+                class.name, class.skill_count
+            ));
+        }
+        self.skills.push(skill.to_string());
+        Ok(())
+    }
+
+    /// Pick or unpick a cantrip (or 1st-level spell), refusing more than
+    /// `limit`.
+    pub fn toggle_spell(
+        &mut self,
+        casting: &ClassSpellcasting,
+        spell: &str,
+        cantrip: bool,
+        limit: usize,
+    ) -> Result<(), String> {
+        let (picked, list) = if cantrip {
+            (&mut self.cantrips, &casting.cantrip_list)
+        } else {
+            (&mut self.spells, &casting.spell_list)
+        };
+        if let Some(index) = picked.iter().position(|s| s == spell) {
+            picked.remove(index);
+            return Ok(());
+        }
+        if !list.iter().any(|s| s == spell) {
+            return Err(format!("{} isn't on the class's spell list", spell));
+        }
+        if picked.len() >= limit {
+            return Err(format!(
+                "Only {} {}; unpick one first",
+                limit,
+                if cantrip { "cantrips" } else { "spells" }
+            ));
+        }
+        picked.push(spell.to_string());
+        Ok(())
+    }
+
+    /// Cantrips and 1st-level spells the class picks with these scores;
+    /// none for a class that doesn't cast at 1st level.
+    pub fn spell_counts(class: &ClassTemplate, scores: [i32; 6]) -> (usize, usize) {
+        match &class.spellcasting {
+            Some(casting) => (
+                casting.cantrips,
+                casting.spell_count(score_modifier(scores, &casting.ability)),
+            ),
+            None => (0, 0),
+        }
+    }
+
+    pub fn check_race(&self, templates: &CharacterTemplates) -> Result<(), String> {
+        match self.race.as_deref().and_then(|r| templates.race(r)) {
+            Some(_) => Ok(()),
+            None => Err("Pick a race".to_string()),
+        }
+    }
+
+    pub fn check_class(&self, templates: &CharacterTemplates) -> Result<(), String> {
+        match self.class.as_deref().and_then(|c| templates.class(c)) {
+            Some(_) => Ok(()),
+            None => Err("Pick a class".to_string()),
+        }
+    }
+
+    pub fn check_background(&self, templates: &CharacterTemplates) -> Result<(), String> {
+        match self
+            .background
+            .as_deref()
+            .and_then(|b| templates.background(b))
+        {
+            Some(_) => Ok(()),
+            None => Err("Pick a background".to_string()),
+        }
+    }
+
+    pub fn check_skills(&self, templates: &CharacterTemplates) -> Result<(), String> {
+        let Some(class) = self.class.as_deref().and_then(|c| templates.class(c)) else {
+            return Err("Pick a class".to_string());
+        };
+        if self.skills.len() < class.skill_count {
+            return Err(pick_more(
+                class.skill_count - self.skills.len(),
+                &format!("{} skill", class.name),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_equipment(&self, templates: &CharacterTemplates) -> Result<(), String> {
+        let Some(class) = self.class.as_deref().and_then(|c| templates.class(c)) else {
+            return Err("Pick a class".to_string());
+        };
+        if !class.equipment.is_empty()
+            && self
+                .equipment
+                .and_then(|i| class.equipment.get(i))
+                .is_none()
+        {
+            return Err("Pick the starting equipment".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn check_spells(
+        &self,
+        templates: &CharacterTemplates,
+        scores: [i32; 6],
+    ) -> Result<(), String> {
+        let Some(class) = self.class.as_deref().and_then(|c| templates.class(c)) else {
+            return Err("Pick a class".to_string());
+        };
+        let (cantrips, spells) = Self::spell_counts(class, scores);
+        if self.cantrips.len() < cantrips {
+            return Err(pick_more(cantrips - self.cantrips.len(), "cantrip"));
+        }
+        if self.spells.len() < spells {
+            return Err(pick_more(spells - self.spells.len(), "1st-level spell"));
+        }
+        if self.spells.len() > spells {
+            return Err(format!("Only {} 1st-level spells; unpick some", spells));
+        }
+        Ok(())
+    }
+
+    /// What's still missing before the character can be created.
+    pub fn validate(&self, templates: &CharacterTemplates, scores: [i32; 6]) -> Result<(), String> {
+        self.check_race(templates)?;
+        self.check_class(templates)?;
+        self.check_background(templates)?;
+        self.check_skills(templates)?;
+        self.check_equipment(templates)?;
+        self.check_spells(templates, scores)
+    }
+
+    /// The finished level 1 character: scores, race, class, background,
+    /// skills, equipment and spells.
+    pub fn build(
+        &self,
+        templates: &CharacterTemplates,
+        scores: [i32; 6],
+    ) -> Result<CharacterData, String> {
+        self.validate(templates, scores)?;
+        let race = self.race.as_deref().and_then(|r| templates.race(r));
+        let class = self.class.as_deref().and_then(|c| templates.class(c));
+        let background = self
+            .background
+            .as_deref()
+            .and_then(|b| templates.background(b));
+        let (Some(race), Some(class), Some(background)) = (race, class, background) else {
+            return Err("Pick a race, class and background".to_string());
+        };
+
+        let mut data = CharacterData::create_with_scores(scores);
+        if let Some(sheet) = data.sheet.as_mut() {
+            sheet.apply_templates(race, class, &self.skills);
+            sheet.apply_background(background);
+            if let Some(pack) = self.equipment.and_then(|i| class.equipment.get(i)) {
+                sheet.apply_equipment_pack(pack);
+            }
+            if let Some(casting) = &class.spellcasting {
+                sheet.apply_spellcasting(casting, &self.cantrips, &self.spells);
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Short description of a class, e.g. "d10 · STR/CON saves · 2 skills"
+pub fn class_summary(class: &ClassTemplate) -> String {
+    let saves: Vec<&str> = class
+        .saving_throws
+        .iter()
+        .map(|s| ability_abbreviation(s))
+        .collect();
+    format!(
+        "d{} · {} saves · {} skills",
+        class.hit_die,
+        saves.join("/"),
+        class.skill_count
+    )
+}
+
+impl CharacterSheet {
+    /// Fill in what a level 1 character gets from its race and class: speed,
+    /// race and class skill proficiencies, saving throw proficiencies, hit
+    /// dice and maximum hit points. Modifiers are worked out again after.
+    pub fn apply_templates(
+        &mut self,
+        race: &RaceTemplate,
+        class: &ClassTemplate,
+        class_skills: &[String],
+    ) {
+        self.character.race = race.name.clone();
+        self.character.class = class.name.clone();
+        self.combat.speed = race.speed;
+
+        for save in self.saving_throws.values_mut() {
+            save.proficient = false;
+        }
+        for ability in class
+            .saving_throws
+            .iter()
+            .filter_map(|s| normalize_ability_name(s))
+        {
+            self.saving_throws
+                .entry(ability.to_string())
+                .or_default()
+                .proficient = true;
+        }
+        for skill in self.skills.values_mut() {
+            skill.proficient = false;
+        }
+        for skill in race.skills.iter().chain(class_skills) {
+            self.skills.entry(skill.clone()).or_default().proficient = true;
+        }
+
+        self.derive_from_ability_scores();
+
+        let level = self.character.level.max(1);
+        self.combat.hit_dice = Some(HitDice {
+            total: format!("{}d{}", level, class.hit_die),
+            current: level,
+        });
+        // Max hit die at level 1, then the average (rounded up) per level
+        let con = self.modifiers.constitution;
+        let maximum =
+            (class.hit_die as i32 + con + (level - 1) * (class.hit_die as i32 / 2 + 1 + con))
+                .max(1);
+        self.combat.hit_points = Some(HitPoints {
+            current: maximum,
+            maximum,
+            temporary: 0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srd_templates_are_valid() {
+        let srd = CharacterTemplates::srd();
+        assert_eq!(srd.validate(), Ok(()));
+        assert_eq!(srd.races.len(), 9);
+        assert_eq!(srd.classes.len(), 12);
+        assert_eq!(srd.class("rogue").unwrap().skill_count, 4);
+        assert_eq!(
+            class_summary(srd.class("Fighter").unwrap()),
+            "d10 · STR/CON saves · 2 skills"
+        );
+    }
+
+    #[test]
+    fn test_homebrew_replaces_and_restores_srd() {
+        let homebrew: CharacterTemplates = serde_json::from_str(
+            r#"{"races":[{"name":"Dwarf","speed":30},{"name":"Centaur","speed":40,"skills":["survival"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(homebrew.validate(), Ok(()));
+
+        let mut templates = CharacterTemplates::with_homebrew(homebrew);
+        assert_eq!(templates.race("dwarf").unwrap().speed, 30);
+        assert_eq!(templates.homebrew().races.len(), 2);
+
+        assert!(templates.remove_homebrew("Dwarf"));
+        assert_eq!(templates.race("Dwarf").unwrap().speed, 25);
+        assert!(!templates.remove_homebrew("Elf"));
+        assert!(templates.race("Centaur").unwrap().homebrew);
+
+        let bad: CharacterTemplates =
+            serde_json::from_str(r#"{"classes":[{"name":"X","hitDie":7,"savingThrows":[],"skillChoices":[],"skillCount":0}]}"#)
+                .unwrap();
+        assert_eq!(
+            bad.validate(),
+            Err("X needs a d6, d8, d10 or d12 hit die".to_string())
+        );
+    }
+
+    #[test]
+    fn test_class_skill_choices() {
+        let srd = CharacterTemplates::srd();
+        let fighter = srd.class("Fighter").unwrap();
+        let mut choice = TemplateChoice {
+            race: Some("Elf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(choice.check_class(&srd), Err("Pick a class".to_string()));
+        choice.set_class(fighter);
+        assert!(choice.toggle_skill(fighter, "arcana").is_err());
+        choice.toggle_skill(fighter, "athletics").unwrap();
+        assert_eq!(
+            choice.check_skills(&srd),
+            Err("Pick 1 more Fighter skill".to_string())
+        );
+        choice.toggle_skill(fighter, "survival").unwrap();
+        assert!(choice.toggle_skill(fighter, "history").is_err());
+        assert_eq!(choice.check_skills(&srd), Ok(()));
+
+        // A class that can't pick survival drops it
+        choice.set_class(srd.class("Monk").unwrap());
+        assert_eq!(choice.skills, vec!["athletics".to_string()]);
+    }
+
+    #[test]
+    fn test_apply_templates() {
+        let srd = CharacterTemplates::srd();
+        let mut data = CharacterData::create_with_scores([15, 14, 13, 12, 10, 8]);
+        let sheet = data.sheet.as_mut().unwrap();
+        sheet.apply_templates(
+            srd.race("Elf").unwrap(),
+            srd.class("Fighter").unwrap(),
+            &["athletics".to_string(), "survival".to_string()],
+        );
+        assert_eq!(sheet.character.race, "Elf");
+        assert_eq!(sheet.combat.speed, 30);
+        assert_eq!(sheet.saving_throws["strength"].modifier, 4);
+        assert_eq!(sheet.saving_throws["dexterity"].modifier, 2);
+        assert_eq!(sheet.skills["perception"].modifier, 2);
+        assert_eq!(sheet.skills["athletics"].modifier, 4);
+        assert_eq!(sheet.combat.hit_dice.as_ref().unwrap().total, "1d10");
+        assert_eq!(sheet.combat.hit_points.as_ref().unwrap().maximum, 11);
+    }
+
+    #[test]
+    fn test_build_complete_character() {
+        let srd = CharacterTemplates::srd();
+        let scores = [8, 14, 13, 15, 12, 10];
+        let mut choice = TemplateChoice::default();
+        choice.set_race(srd.race("Elf").unwrap());
+        let wizard = srd.class("Wizard").unwrap();
+        choice.set_class(wizard);
+        choice.set_background(srd.background("Sage").unwrap());
+        assert_eq!(
+            choice.validate(&srd, scores),
+            Err("Pick 2 more Wizard skills".to_string())
+        );
+
+        // Sage already gives arcana, so picking it for the class is undone
+        choice.toggle_skill(wizard, "arcana").unwrap();
+        choice.set_background(srd.background("Sage").unwrap());
+        assert!(choice.skills.is_empty());
+        choice.toggle_skill(wizard, "investigation").unwrap();
+        choice.toggle_skill(wizard, "medicine").unwrap();
+        choice.equipment = Some(0);
+
+        let casting = wizard.spellcasting.as_ref().unwrap();
+        assert_eq!(TemplateChoice::spell_counts(wizard, scores), (3, 6));
+        for cantrip in ["Fire Bolt", "Light", "Mage Hand"] {
+            choice.toggle_spell(casting, cantrip, true, 3).unwrap();
+        }
+        assert!(choice.toggle_spell(casting, "Message", true, 3).is_err());
+        assert!(choice
+            .toggle_spell(casting, "Cure Wounds", false, 6)
+            .is_err());
+        assert_eq!(
+            choice.validate(&srd, scores),
+            Err("Pick 6 more 1st-level spells".to_string())
+        );
+        for spell in [
+            "Shield",
+            "Sleep",
+            "Magic Missile",
+            "Mage Armor",
+            "Alarm",
+            "Jump",
+        ] {
+            choice.toggle_spell(casting, spell, false, 6).unwrap();
+        }
+
+        let data = choice.build(&srd, scores).unwrap();
+        let sheet = data.sheet.unwrap();
+        assert_eq!(sheet.character.class, "Wizard");
+        assert_eq!(sheet.character.background.as_deref(), Some("Sage"));
+        assert!(sheet.skills["history"].proficient);
+        assert!(sheet.skills["perception"].proficient);
+        assert_eq!(sheet.combat.armor_class, 12);
+        assert_eq!(sheet.combat.hit_points.as_ref().unwrap().maximum, 7);
+        let spells = sheet.spells.unwrap();
+        assert_eq!(spells.known_spells.len(), 9);
+        assert_eq!(spells.prepared_spells.len(), 6);
+
+        // A Fighter doesn't pick spells, and a new class clears the old picks
+        choice.set_class(srd.class("Fighter").unwrap());
+        assert!(choice.cantrips.is_empty() && choice.equipment.is_none());
+        assert_eq!(
+            TemplateChoice::spell_counts(srd.class("Fighter").unwrap(), scores),
+            (0, 0)
+        );
+    }
+}
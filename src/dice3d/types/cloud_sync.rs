@@ -0,0 +1,345 @@
+//! Cloud sync of characters with a remote SurrealDB server
+//!
+//! Besides the embedded datastore, characters can be kept in step with a
+//! user-run SurrealDB server (`ws://`, `wss://`, `http://` or `https://`), so
+//! the same characters are usable on two machines. The server is set up with
+//! `dndgamerolls sync --endpoint ...` and saved in the `cloud_sync` setting;
+//! the app syncs once at startup and on the `sync` command.
+//!
+//! Every character carries a `uid` shared across machines and the time of its
+//! last edit, and the setting remembers the version of every character both
+//! sides held after the previous sync. A sync compares both sides against
+//! those versions: a character changed on one side only is copied to the
+//! other, one missing on a side that was synced before and is unchanged on
+//! the other was deleted there, and one changed on both sides is a conflict.
+//! The newer edit wins everywhere and the older one is kept as a
+//! "(conflicted copy)" character, so no edit is lost. A character the
+//! previous sync never saw is always copied, never deleted, whatever the
+//! clocks of the two machines say.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::character::CharacterSheet;
+use super::database::CharacterDatabase;
+
+/// `setting` key holding the [`CloudSyncConfig`].
+pub const CLOUD_SYNC_SETTING_KEY: &str = "cloud_sync";
+
+/// Suffix of the character kept from the losing side of a conflict.
+pub const CONFLICTED_COPY_SUFFIX: &str = " (conflicted copy)";
+
+/// Remote server the characters are synced with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloudSyncConfig {
+    /// Server address, e.g. `wss://surreal.example.com`
+    pub endpoint: String,
+    pub namespace: String,
+    pub database: String,
+    /// Sign-in user; empty for a server without authentication
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// Milliseconds since the epoch of the last successful sync (0 = never)
+    #[serde(default)]
+    pub last_sync: u64,
+    /// Edit time of every character after the last successful sync, by uid
+    #[serde(default)]
+    pub synced: HashMap<String, u64>,
+}
+
+impl CloudSyncConfig {
+    /// The saved sync server, if sync is set up.
+    pub fn load(db: &CharacterDatabase) -> Option<Self> {
+        db.get_setting::<Self>(CLOUD_SYNC_SETTING_KEY)
+            .ok()
+            .flatten()
+            .filter(|config| !config.endpoint.is_empty())
+    }
+
+    /// Check the endpoint scheme and that a namespace and database are set.
+    pub fn validate(&self) -> Result<(), String> {
+        let supported = ["ws://", "wss://", "http://", "https://"];
+        if !supported
+            .iter()
+            .any(|scheme| self.endpoint.starts_with(scheme))
+        {
+            return Err(format!(
+                "Sync endpoint '{}' must start with ws://, wss://, http:// or https://",
+                self.endpoint
+            ));
+        }
+        if self.namespace.trim().is_empty() || self.database.trim().is_empty() {
+            return Err("Sync needs a namespace and a database".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Milliseconds since the epoch, as stored in `updated_at` and `last_sync`.
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// A new id for a character, shared by every machine it is synced to.
+pub fn new_character_uid() -> String {
+    format!(
+        "{:016x}{:016x}",
+        rand::random::<u64>(),
+        rand::random::<u64>()
+    )
+}
+
+/// A character as stored on the sync server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub uid: String,
+    pub updated_at: u64,
+    pub sheet: CharacterSheet,
+}
+
+impl SyncRecord {
+    /// A new character holding this version, for the losing side of a
+    /// conflict.
+    pub fn conflicted_copy(&self) -> SyncRecord {
+        let mut sheet = self.sheet.clone();
+        sheet.character.name.push_str(CONFLICTED_COPY_SUFFIX);
+        SyncRecord {
+            uid: new_character_uid(),
+            updated_at: now_millis(),
+            sheet,
+        }
+    }
+}
+
+/// Which version of a character one side holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncStamp {
+    pub uid: String,
+    pub updated_at: u64,
+}
+
+/// What a sync does with one character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Upload the local version
+    Push(String),
+    /// Download the remote version
+    Pull(String),
+    /// Deleted on the server since the last sync
+    DeleteLocal(String),
+    /// Deleted on this machine since the last sync
+    DeleteRemote(String),
+    /// Changed on both sides: the newer edit wins and the older is kept as a
+    /// conflicted copy
+    Conflict { uid: String, local_wins: bool },
+}
+
+/// Decide what to do with every character, given the local and remote
+/// versions and the versions after the previous sync (empty for the first
+/// one).
+///
+/// Only a character the previous sync saw, and that is unchanged on the
+/// side still holding it, is deleted; any other character missing on a side
+/// is copied over.
+pub fn plan_sync(
+    local: &[SyncStamp],
+    remote: &[SyncStamp],
+    synced: &HashMap<String, u64>,
+) -> Vec<SyncAction> {
+    let remote_by_uid: HashMap<&str, u64> = remote
+        .iter()
+        .map(|stamp| (stamp.uid.as_str(), stamp.updated_at))
+        .collect();
+    let changed = |stamp: &SyncStamp, updated_at: u64| synced.get(&stamp.uid) != Some(&updated_at);
+
+    let mut actions = Vec::new();
+    for stamp in local {
+        let action = match remote_by_uid.get(stamp.uid.as_str()) {
+            None if changed(stamp, stamp.updated_at) => SyncAction::Push(stamp.uid.clone()),
+            None => SyncAction::DeleteLocal(stamp.uid.clone()),
+            Some(&remote_at) if remote_at == stamp.updated_at => continue,
+            Some(&remote_at) if changed(stamp, stamp.updated_at) && changed(stamp, remote_at) => {
+                SyncAction::Conflict {
+                    uid: stamp.uid.clone(),
+                    local_wins: stamp.updated_at > remote_at,
+                }
+            }
+            Some(_) if changed(stamp, stamp.updated_at) => SyncAction::Push(stamp.uid.clone()),
+            Some(_) => SyncAction::Pull(stamp.uid.clone()),
+        };
+        actions.push(action);
+    }
+
+    for stamp in remote {
+        if local.iter().any(|l| l.uid == stamp.uid) {
+            continue;
+        }
+        actions.push(if changed(stamp, stamp.updated_at) {
+            SyncAction::Pull(stamp.uid.clone())
+        } else {
+            SyncAction::DeleteRemote(stamp.uid.clone())
+        });
+    }
+
+    actions
+}
+
+/// What a sync changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub deleted: usize,
+    pub conflicts: usize,
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} uploaded, {} downloaded, {} deleted",
+            self.pushed, self.pulled, self.deleted
+        )?;
+        if self.conflicts > 0 {
+            write!(f, ", {} conflicted copies kept", self.conflicts)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(uid: &str, updated_at: u64) -> SyncStamp {
+        SyncStamp {
+            uid: uid.to_string(),
+            updated_at,
+        }
+    }
+
+    fn synced(stamps: &[(&str, u64)]) -> HashMap<String, u64> {
+        stamps
+            .iter()
+            .map(|(uid, updated_at)| (uid.to_string(), *updated_at))
+            .collect()
+    }
+
+    #[test]
+    fn test_first_sync_copies_both_ways() {
+        let actions = plan_sync(&[stamp("a", 10)], &[stamp("b", 20)], &HashMap::new());
+        assert_eq!(
+            actions,
+            vec![
+                SyncAction::Push("a".to_string()),
+                SyncAction::Pull("b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changes_since_last_sync() {
+        let local = [
+            stamp("same", 50),
+            stamp("edited-here", 150),
+            stamp("edited-there", 50),
+            stamp("deleted-there", 50),
+            stamp("new-here", 150),
+        ];
+        let remote = [
+            stamp("same", 50),
+            stamp("edited-here", 50),
+            stamp("edited-there", 160),
+            stamp("deleted-here", 50),
+            stamp("new-there", 160),
+        ];
+        let previous = synced(&[
+            ("same", 50),
+            ("edited-here", 50),
+            ("edited-there", 50),
+            ("deleted-there", 50),
+            ("deleted-here", 50),
+        ]);
+        let actions = plan_sync(&local, &remote, &previous);
+        assert_eq!(
+            actions,
+            vec![
+                SyncAction::Push("edited-here".to_string()),
+                SyncAction::Pull("edited-there".to_string()),
+                SyncAction::DeleteLocal("deleted-there".to_string()),
+                SyncAction::Push("new-here".to_string()),
+                SyncAction::DeleteRemote("deleted-here".to_string()),
+                SyncAction::Pull("new-there".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edits_on_both_sides_conflict() {
+        let actions = plan_sync(
+            &[stamp("a", 150), stamp("b", 170)],
+            &[stamp("a", 160), stamp("b", 120)],
+            &synced(&[("a", 50), ("b", 50)]),
+        );
+        assert_eq!(
+            actions,
+            vec![
+                SyncAction::Conflict {
+                    uid: "a".to_string(),
+                    local_wins: false
+                },
+                SyncAction::Conflict {
+                    uid: "b".to_string(),
+                    local_wins: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unseen_characters_are_never_deleted() {
+        // Saved on machines whose clocks lag behind the previous sync
+        let actions = plan_sync(
+            &[stamp("a", 50), stamp("old-here", 10)],
+            &[stamp("a", 50), stamp("old-there", 20)],
+            &synced(&[("a", 50)]),
+        );
+        assert_eq!(
+            actions,
+            vec![
+                SyncAction::Push("old-here".to_string()),
+                SyncAction::Pull("old-there".to_string()),
+            ]
+        );
+
+        // Edited here after it was deleted there: the edit is kept
+        let actions = plan_sync(&[stamp("a", 60)], &[], &synced(&[("a", 50)]));
+        assert_eq!(actions, vec![SyncAction::Push("a".to_string())]);
+    }
+
+    #[test]
+    fn test_validate_endpoint() {
+        let mut config = CloudSyncConfig {
+            endpoint: "wss://db.example.com".to_string(),
+            namespace: "dnd".to_string(),
+            database: "party".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.endpoint = "db.example.com".to_string();
+        assert!(config.validate().is_err());
+
+        config.endpoint = "http://localhost:8000".to_string();
+        config.database.clear();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! Custom container validation
+//!
+//! A custom dice tray or tower model is checked before it is used as the
+//! container: test dice are dropped into it in a headless physics world whose
+//! colliders are built the way the app builds them (voxelized from the
+//! model's render meshes, `COLLIDER_*` guides left out), and every drop that
+//! goes wrong is reported with where it happened:
+//!
+//! - Gaps: a die fell through or out of the model.
+//! - Flat spots: a die came to rest on a ledge or rim well above the floor,
+//!   or balanced on an edge.
+//! - A die that was still moving when the drop timed out.
+//!
+//! The drops are seeded, so the same model always gets the same report.
+//! `dndgamerolls validate-container tower.glb` runs the check from the
+//! command line.
+
+use std::path::Path;
+
+use bevy::math::{Mat4, Vec3};
+use bevy_rapier3d::prelude::FillMode;
+use bevy_rapier3d::rapier::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{DiceType, DiceTypeExt};
+
+/// Voxel size of the colliders generated from container meshes
+pub const CONTAINER_VOXEL_SIZE: f32 = 0.2;
+
+/// Test dice dropped by default
+pub const DEFAULT_TEST_DROPS: u32 = 40;
+
+/// Seed of the test drops
+const TEST_DROP_SEED: u64 = 0x00d1_ce70;
+
+/// Physics steps a test die gets to settle (8 seconds at 60 Hz)
+const MAX_STEPS: u32 = 480;
+
+/// Steps a die must stay still to count as settled
+const SETTLE_STEPS: u32 = 30;
+
+/// Speed under which a die counts as still
+const STILL_SPEED: f32 = 0.05;
+
+/// How closely a resting die's face must point up (cosine); less and it is
+/// balanced on an edge
+const FLAT_FACE_COSINE: f32 = 0.95;
+
+/// Share of drops that may end on a flat spot or time out before the model
+/// is refused
+const MAX_PROBLEM_SHARE: f32 = 0.1;
+
+/// Triangles of a container model, in model space
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerMesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl ContainerMesh {
+    /// Read the triangles of a glTF/GLB model's default scene, with node
+    /// transforms applied. `COLLIDER_*` guide meshes are left out, as the
+    /// app leaves them out of the container's colliders.
+    pub fn from_gltf(path: &Path) -> Result<Self, String> {
+        let (document, buffers, _) = gltf::import(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| format!("'{}' has no scene", path.display()))?;
+
+        let mut mesh = Self::default();
+        for node in scene.nodes() {
+            mesh.add_node(&node, Mat4::IDENTITY, &buffers);
+        }
+        if mesh.triangles.is_empty() {
+            return Err(format!("'{}' has no triangle meshes", path.display()));
+        }
+        Ok(mesh)
+    }
+
+    fn add_node(&mut self, node: &gltf::Node, parent: Mat4, buffers: &[gltf::buffer::Data]) {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        let guide = node.name().is_some_and(|n| n.starts_with("COLLIDER_"));
+
+        if let Some(mesh) = node.mesh().filter(|_| !guide) {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    continue;
+                }
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let Some(positions) = reader.read_positions() else {
+                    continue;
+                };
+                let offset = self.vertices.len() as u32;
+                self.vertices.extend(
+                    positions.map(|p| transform.transform_point3(Vec3::from(p)).to_array()),
+                );
+                let count = self.vertices.len() as u32 - offset;
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..count).collect(),
+                };
+                self.triangles.extend(
+                    indices
+                        .chunks_exact(3)
+                        .filter(|t| t.iter().all(|&i| i < count))
+                        .map(|t| [t[0] + offset, t[1] + offset, t[2] + offset]),
+                );
+            }
+        }
+
+        for child in node.children() {
+            self.add_node(&child, transform, buffers);
+        }
+    }
+
+    /// Smallest and largest corner of the model's bounding box
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut points = self.vertices.iter().map(|v| Vec3::from(*v));
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), p| (min.min(p), max.max(p))))
+    }
+}
+
+/// What went wrong with a test drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerProblemKind {
+    /// The die left the model through a gap
+    Gap,
+    /// The die came to rest on a ledge or rim, or balanced on an edge
+    FlatSpot,
+    /// The die was still moving when the drop timed out
+    NeverSettled,
+}
+
+impl ContainerProblemKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ContainerProblemKind::Gap => "escaped through a gap",
+            ContainerProblemKind::FlatSpot => "balanced on a flat spot",
+            ContainerProblemKind::NeverSettled => "never settled",
+        }
+    }
+}
+
+/// A test drop that went wrong, and where (model space)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContainerProblem {
+    pub kind: ContainerProblemKind,
+    pub position: [f32; 3],
+}
+
+/// Outcome of validating a container model
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerReport {
+    pub drops: u32,
+    pub problems: Vec<ContainerProblem>,
+}
+
+impl ContainerReport {
+    pub fn count(&self, kind: ContainerProblemKind) -> usize {
+        self.problems.iter().filter(|p| p.kind == kind).count()
+    }
+
+    /// Whether the model can be used as a container: no die may escape,
+    /// and only a few may end on a flat spot or keep moving.
+    pub fn is_acceptable(&self) -> bool {
+        if self.count(ContainerProblemKind::Gap) > 0 {
+            return false;
+        }
+        self.problems.len() as f32 <= self.drops as f32 * MAX_PROBLEM_SHARE
+    }
+
+    /// One line per kind of problem, e.g. "3 of 40 dice escaped through a
+    /// gap (near 1.20, -0.40, 0.85)".
+    pub fn summary(&self) -> Vec<String> {
+        [
+            ContainerProblemKind::Gap,
+            ContainerProblemKind::FlatSpot,
+            ContainerProblemKind::NeverSettled,
+        ]
+        .into_iter()
+        .filter_map(|kind| {
+            let first = self.problems.iter().find(|p| p.kind == kind)?;
+            let [x, y, z] = first.position;
+            Some(format!(
+                "{} of {} dice {} (near {:.2}, {:.2}, {:.2})",
+                self.count(kind),
+                self.drops,
+                kind.describe(),
+                x,
+                y,
+                z
+            ))
+        })
+        .collect()
+    }
+}
+
+/// How a single test drop ended
+enum DropOutcome {
+    Escaped(Vec3),
+    Rested { position: Vec3, flat: bool },
+    Moving(Vec3),
+}
+
+/// Drop `drops` test d6s into the container and report what went wrong.
+pub fn validate_container(mesh: &ContainerMesh, drops: u32) -> Result<ContainerReport, String> {
+    let (min, max) = mesh
+        .bounds()
+        .ok_or_else(|| "The model has no vertices".to_string())?;
+    let vertices: Vec<Point<f32>> = mesh
+        .vertices
+        .iter()
+        .map(|[x, y, z]| Point::new(*x, *y, *z))
+        .collect();
+    let shape = SharedShape::voxelized_mesh(
+        &vertices,
+        &mesh.triangles,
+        CONTAINER_VOXEL_SIZE,
+        FillMode::default(),
+    );
+
+    // The d6 as the app spawns it at the default dice scale
+    let half_extent = 0.3 * DiceType::D6.uniform_size_scale_factor();
+    let mut rng = StdRng::seed_from_u64(TEST_DROP_SEED);
+    let outcomes: Vec<DropOutcome> = (0..drops)
+        .map(|_| drop_test_die(&shape, min, max, half_extent, &mut rng))
+        .collect();
+
+    // The floor is where most dice end up: the lowest resting height
+    let floor = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            DropOutcome::Rested { position, .. } => Some(position.y),
+            _ => None,
+        })
+        .fold(f32::INFINITY, f32::min);
+
+    let problems = outcomes
+        .into_iter()
+        .filter_map(|outcome| {
+            let (kind, position) = match outcome {
+                DropOutcome::Escaped(position) => (ContainerProblemKind::Gap, position),
+                DropOutcome::Rested { position, flat }
+                    if !flat || position.y - floor > half_extent * 1.5 =>
+                {
+                    (ContainerProblemKind::FlatSpot, position)
+                }
+                DropOutcome::Rested { .. } => return None,
+                DropOutcome::Moving(position) => (ContainerProblemKind::NeverSettled, position),
+            };
+            Some(ContainerProblem {
+                kind,
+                position: position.to_array(),
+            })
+        })
+        .collect();
+
+    Ok(ContainerReport { drops, problems })
+}
+
+/// Drop one die from above the middle of the model, thrown the way the app
+/// throws dice, and follow it until it rests, escapes or times out.
+fn drop_test_die(
+    container: &SharedShape,
+    min: Vec3,
+    max: Vec3,
+    half_extent: f32,
+    rng: &mut StdRng,
+) -> DropOutcome {
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+    colliders.insert(
+        ColliderBuilder::new(container.clone())
+            .restitution(0.2)
+            .friction(0.8)
+            .build(),
+    );
+
+    let center = (min + max) / 2.0;
+    let spread = (max - min) / 4.0;
+    // Just under a lid, or above an open tray's floor
+    let start = Vec3::new(
+        center.x + rng.random_range(-1.0f32..=1.0) * spread.x,
+        (max.y - half_extent * 2.0).max(min.y + half_extent * 4.0),
+        center.z + rng.random_range(-1.0f32..=1.0) * spread.z,
+    );
+    let die = bodies.insert(
+        RigidBodyBuilder::dynamic()
+            .translation(vector![start.x, start.y, start.z])
+            .rotation(vector![
+                rng.random_range(0.0..std::f32::consts::TAU),
+                rng.random_range(0.0..std::f32::consts::TAU),
+                rng.random_range(0.0..std::f32::consts::TAU)
+            ])
+            .linvel(vector![
+                rng.random_range(-1.5..1.5),
+                rng.random_range(-0.5..0.0),
+                rng.random_range(-1.5..1.5)
+            ])
+            .angvel(vector![
+                rng.random_range(-8.0..8.0),
+                rng.random_range(-8.0..8.0),
+                rng.random_range(-8.0..8.0)
+            ])
+            .ccd_enabled(true)
+            .build(),
+    );
+    colliders.insert_with_parent(
+        ColliderBuilder::cuboid(half_extent, half_extent, half_extent)
+            .restitution(0.15)
+            .friction(0.7)
+            .density(DiceType::D6.density())
+            .build(),
+        die,
+        &mut bodies,
+    );
+
+    let gravity = vector![0.0, -9.81, 0.0];
+    let params = IntegrationParameters::default();
+    let mut pipeline = PhysicsPipeline::new();
+    let mut islands = IslandManager::new();
+    let mut broad_phase = DefaultBroadPhase::new();
+    let mut narrow_phase = NarrowPhase::new();
+    let mut impulse_joints = ImpulseJointSet::new();
+    let mut multibody_joints = MultibodyJointSet::new();
+    let mut ccd_solver = CCDSolver::new();
+
+    let margin = half_extent * 2.0;
+    let mut still_steps = 0;
+    for _ in 0..MAX_STEPS {
+        pipeline.step(
+            &gravity,
+            &params,
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            &(),
+            &(),
+        );
+
+        let body = &bodies[die];
+        let t = body.translation();
+        let position = Vec3::new(t.x, t.y, t.z);
+        if position.y < min.y - margin
+            || position.x < min.x - margin
+            || position.x > max.x + margin
+            || position.z < min.z - margin
+            || position.z > max.z + margin
+        {
+            return DropOutcome::Escaped(position.clamp(min, max));
+        }
+
+        let still = body.linvel().norm() < STILL_SPEED && body.angvel().norm() < STILL_SPEED;
+        still_steps = if still || body.is_sleeping() {
+            still_steps + 1
+        } else {
+            0
+        };
+        if still_steps >= SETTLE_STEPS {
+            // A cube rests flat when one of its axes points straight up
+            let up = body.rotation().inverse() * Vector::y();
+            let flat = up.x.abs().max(up.y.abs()).max(up.z.abs()) >= FLAT_FACE_COSINE;
+            return DropOutcome::Rested { position, flat };
+        }
+    }
+
+    let t = bodies[die].translation();
+    DropOutcome::Moving(Vec3::new(t.x, t.y, t.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An open-topped box: a floor and (optionally) four walls, as quads of
+    /// two triangles
+    fn tray(w: f32, with_walls: bool) -> ContainerMesh {
+        let mut mesh = ContainerMesh::default();
+        let mut quad = |corners: [[f32; 3]; 4]| {
+            let offset = mesh.vertices.len() as u32;
+            mesh.vertices.extend(corners);
+            mesh.triangles.push([offset, offset + 1, offset + 2]);
+            mesh.triangles.push([offset, offset + 2, offset + 3]);
+        };
+        let h = 1.5;
+        quad([[-w, 0.0, -w], [w, 0.0, -w], [w, 0.0, w], [-w, 0.0, w]]);
+        if with_walls {
+            quad([[-w, 0.0, -w], [w, 0.0, -w], [w, h, -w], [-w, h, -w]]);
+            quad([[-w, 0.0, w], [w, 0.0, w], [w, h, w], [-w, h, w]]);
+            quad([[-w, 0.0, -w], [-w, 0.0, w], [-w, h, w], [-w, h, -w]]);
+            quad([[w, 0.0, -w], [w, 0.0, w], [w, h, w], [w, h, -w]]);
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_bounds() {
+        let (min, max) = tray(2.0, true).bounds().unwrap();
+        assert_eq!(min, Vec3::new(-2.0, 0.0, -2.0));
+        assert_eq!(max, Vec3::new(2.0, 1.5, 2.0));
+        assert_eq!(ContainerMesh::default().bounds(), None);
+    }
+
+    #[test]
+    fn test_report_acceptance() {
+        let mut report = ContainerReport {
+            drops: 20,
+            problems: vec![ContainerProblem {
+                kind: ContainerProblemKind::FlatSpot,
+                position: [0.5, 1.0, 0.0],
+            }],
+        };
+        assert!(report.is_acceptable());
+        assert_eq!(
+            report.summary(),
+            vec!["1 of 20 dice balanced on a flat spot (near 0.50, 1.00, 0.00)".to_string()]
+        );
+
+        report.problems.push(ContainerProblem {
+            kind: ContainerProblemKind::Gap,
+            position: [2.0, 0.0, 0.0],
+        });
+        assert!(!report.is_acceptable());
+    }
+
+    #[test]
+    fn test_walled_tray_keeps_its_dice() {
+        let report = validate_container(&tray(2.0, true), 8).unwrap();
+        assert_eq!(report.count(ContainerProblemKind::Gap), 0);
+
+        // Without walls the dice roll off the edge of a small floor
+        let report = validate_container(&tray(0.5, false), 8).unwrap();
+        assert!(report.count(ContainerProblemKind::Gap) > 0);
+    }
+}
@@ -0,0 +1,972 @@
+//! Backgrounds, starting equipment and 1st-level spells for new characters
+//!
+//! The later steps of the character creation wizard: a background adds two
+//! skill proficiencies, some gear and gold; a class offers a choice of
+//! starting equipment packs; and a spellcasting class picks its cantrips and
+//! 1st-level spells. The SRD options are built in. Homebrew backgrounds go in
+//! the template file next to races and classes, and a homebrew class carries
+//! its own packs and spell list:
+//!
+//! ```json
+//! {
+//!   "backgrounds": [{ "name": "Bounty Hunter", "skills": ["insight", "stealth"],
+//!                     "items": ["Manacles"], "gold": 20 }],
+//!   "classes": [{
+//!     "name": "Blood Hunter", "hitDie": 10, "...": "...",
+//!     "equipment": [{ "name": "Longsword", "weapons": ["Longsword"], "armor": "Scale Mail" }],
+//!     "spellcasting": { "ability": "wisdom", "cantrips": 2, "spellsKnown": 2, "slots": 2,
+//!                       "cantripList": ["Guidance"], "spellList": ["Bless"] }
+//!   }]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::character::{
+    default_skill_ability, normalize_ability_name, Armor, CharacterSheet, Equipment, SpellCasting,
+    Weapon,
+};
+use super::currency::Coin;
+use super::inventory::InventoryItem;
+
+/// Skill proficiencies, gear and gold a background gives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundTemplate {
+    pub name: String,
+    /// Skills the background is proficient in (camelCase keys)
+    pub skills: Vec<String>,
+    /// Items in the "Name (N)" form
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub gold: i32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub homebrew: bool,
+}
+
+/// One of a class's starting equipment choices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipmentPack {
+    pub name: String,
+    /// Weapon names from the SRD weapon table
+    #[serde(default)]
+    pub weapons: Vec<String>,
+    /// Armor name from the SRD armor table
+    #[serde(default)]
+    pub armor: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shield: bool,
+    /// Items in the "Name (N)" form
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub gold: i32,
+}
+
+/// What a class casts at 1st level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassSpellcasting {
+    pub ability: String,
+    /// Cantrips known
+    pub cantrips: usize,
+    /// 1st-level spells known; left out, the class prepares its spellcasting
+    /// modifier + 1 of them (Cleric, Druid)
+    #[serde(default)]
+    pub spells_known: Option<usize>,
+    /// The known spells are a spellbook, of which modifier + 1 are prepared
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub spellbook: bool,
+    /// 1st-level spell slots
+    pub slots: i32,
+    pub cantrip_list: Vec<String>,
+    pub spell_list: Vec<String>,
+}
+
+impl ClassSpellcasting {
+    /// 1st-level spells to pick with this spellcasting ability modifier.
+    pub fn spell_count(&self, modifier: i32) -> usize {
+        self.spells_known
+            .unwrap_or_else(|| (modifier + 1).max(1) as usize)
+    }
+
+    /// Spells prepared out of `picked`.
+    pub fn prepared(&self, picked: &[String], modifier: i32) -> Vec<String> {
+        if self.spellbook {
+            picked
+                .iter()
+                .take((modifier + 1).max(1) as usize)
+                .cloned()
+                .collect()
+        } else {
+            picked.to_vec()
+        }
+    }
+}
+
+/// Name, damage, damage type and properties of the SRD weapons the packs use
+const SRD_WEAPONS: [(&str, &str, &str, &[&str]); 16] = [
+    (
+        "Dagger",
+        "1d4",
+        "piercing",
+        &["Finesse", "Light", "Thrown (range 20/60)"],
+    ),
+    ("Greataxe", "1d12", "slashing", &["Heavy", "Two-Handed"]),
+    ("Greatsword", "2d6", "slashing", &["Heavy", "Two-Handed"]),
+    (
+        "Handaxe",
+        "1d6",
+        "slashing",
+        &["Light", "Thrown (range 20/60)"],
+    ),
+    ("Javelin", "1d6", "piercing", &["Thrown (range 30/120)"]),
+    (
+        "Light Crossbow",
+        "1d8",
+        "piercing",
+        &["Ammunition (range 80/320)", "Loading", "Two-Handed"],
+    ),
+    (
+        "Longbow",
+        "1d8",
+        "piercing",
+        &["Ammunition (range 150/600)", "Heavy", "Two-Handed"],
+    ),
+    ("Longsword", "1d8", "slashing", &["Versatile (1d10)"]),
+    ("Mace", "1d6", "bludgeoning", &[]),
+    ("Quarterstaff", "1d6", "bludgeoning", &["Versatile (1d8)"]),
+    ("Rapier", "1d8", "piercing", &["Finesse"]),
+    ("Scimitar", "1d6", "slashing", &["Finesse", "Light"]),
+    (
+        "Shortbow",
+        "1d6",
+        "piercing",
+        &["Ammunition (range 80/320)", "Two-Handed"],
+    ),
+    ("Shortsword", "1d6", "piercing", &["Finesse", "Light"]),
+    (
+        "Spear",
+        "1d6",
+        "piercing",
+        &["Thrown (range 20/60)", "Versatile (1d8)"],
+    ),
+    ("Warhammer", "1d8", "bludgeoning", &["Versatile (1d10)"]),
+];
+
+/// Name, base AC and type of the SRD armor the packs use
+const SRD_ARMOR: [(&str, i32, &str); 5] = [
+    ("Leather", 11, "light"),
+    ("Studded Leather", 12, "light"),
+    ("Hide", 12, "medium"),
+    ("Scale Mail", 14, "medium"),
+    ("Chain Mail", 16, "heavy"),
+];
+
+/// An SRD weapon by name. The attack bonus is left at 0 so it's worked out
+/// from the properties.
+pub fn srd_weapon(name: &str) -> Option<Weapon> {
+    SRD_WEAPONS
+        .iter()
+        .find(|(weapon, ..)| weapon.eq_ignore_ascii_case(name))
+        .map(|(weapon, damage, damage_type, properties)| Weapon {
+            name: weapon.to_string(),
+            attack_bonus: 0,
+            damage: damage.to_string(),
+            damage_type: damage_type.to_string(),
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+        })
+}
+
+/// SRD armor by name, with its AC for a Dexterity modifier.
+pub fn srd_armor(name: &str, dexterity: i32) -> Option<Armor> {
+    SRD_ARMOR
+        .iter()
+        .find(|(armor, ..)| armor.eq_ignore_ascii_case(name))
+        .map(|(armor, base, armor_type)| Armor {
+            name: armor.to_string(),
+            armor_class: *base,
+            armor_class_with_dex: Some(match *armor_type {
+                "light" => base + dexterity,
+                "medium" => base + dexterity.min(2),
+                _ => *base,
+            }),
+            armor_type: Some(armor_type.to_string()),
+        })
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn background(name: &str, skills: [&str; 2], items: &[&str], gold: i32) -> BackgroundTemplate {
+    BackgroundTemplate {
+        name: name.to_string(),
+        skills: strings(&skills),
+        items: strings(items),
+        gold,
+        homebrew: false,
+    }
+}
+
+/// The backgrounds the wizard offers out of the box
+pub fn srd_backgrounds() -> Vec<BackgroundTemplate> {
+    vec![
+        background(
+            "Acolyte",
+            ["insight", "religion"],
+            &["Holy Symbol", "Prayer Book", "Incense (5)", "Vestments"],
+            15,
+        ),
+        background(
+            "Charlatan",
+            ["deception", "sleightOfHand"],
+            &["Fine Clothes", "Disguise Kit"],
+            15,
+        ),
+        background(
+            "Criminal",
+            ["deception", "stealth"],
+            &["Crowbar", "Dark Common Clothes"],
+            15,
+        ),
+        background(
+            "Entertainer",
+            ["acrobatics", "performance"],
+            &["Musical Instrument", "Costume"],
+            15,
+        ),
+        background(
+            "Folk Hero",
+            ["animalHandling", "survival"],
+            &["Artisan's Tools", "Shovel", "Iron Pot", "Common Clothes"],
+            10,
+        ),
+        background(
+            "Guild Artisan",
+            ["insight", "persuasion"],
+            &[
+                "Artisan's Tools",
+                "Letter of Introduction",
+                "Traveler's Clothes",
+            ],
+            15,
+        ),
+        background(
+            "Hermit",
+            ["medicine", "religion"],
+            &["Scroll Case of Notes", "Winter Blanket", "Herbalism Kit"],
+            5,
+        ),
+        background(
+            "Noble",
+            ["history", "persuasion"],
+            &["Fine Clothes", "Signet Ring", "Scroll of Pedigree"],
+            25,
+        ),
+        background(
+            "Outlander",
+            ["athletics", "survival"],
+            &["Staff", "Hunting Trap", "Traveler's Clothes"],
+            10,
+        ),
+        background(
+            "Sage",
+            ["arcana", "history"],
+            &["Bottle of Ink", "Quill", "Small Knife", "Common Clothes"],
+            10,
+        ),
+        background(
+            "Sailor",
+            ["athletics", "perception"],
+            &["Belaying Pin", "Silk Rope", "Lucky Charm", "Common Clothes"],
+            10,
+        ),
+        background(
+            "Soldier",
+            ["athletics", "intimidation"],
+            &["Insignia of Rank", "Dice Set", "Common Clothes"],
+            10,
+        ),
+        background(
+            "Urchin",
+            ["sleightOfHand", "stealth"],
+            &["Small Knife", "Map of Home City", "Common Clothes"],
+            10,
+        ),
+    ]
+}
+
+fn pack(
+    name: &str,
+    weapons: &[&str],
+    armor: Option<&str>,
+    shield: bool,
+    items: &[&str],
+) -> EquipmentPack {
+    EquipmentPack {
+        name: name.to_string(),
+        weapons: strings(weapons),
+        armor: armor.map(str::to_string),
+        shield,
+        items: strings(items),
+        gold: 0,
+    }
+}
+
+/// The starting equipment choices of an SRD class
+pub fn srd_equipment(class: &str) -> Vec<EquipmentPack> {
+    match class {
+        "Barbarian" => vec![
+            pack(
+                "Greataxe and handaxe",
+                &["Greataxe", "Handaxe", "Javelin"],
+                None,
+                false,
+                &["Explorer's Pack", "Javelin (4)"],
+            ),
+            pack(
+                "Greatsword",
+                &["Greatsword", "Javelin"],
+                None,
+                false,
+                &["Explorer's Pack", "Javelin (4)"],
+            ),
+        ],
+        "Bard" => vec![
+            pack(
+                "Rapier and lute",
+                &["Rapier", "Dagger"],
+                Some("Leather"),
+                false,
+                &["Diplomat's Pack", "Lute"],
+            ),
+            pack(
+                "Longsword and lute",
+                &["Longsword", "Dagger"],
+                Some("Leather"),
+                false,
+                &["Entertainer's Pack", "Lute"],
+            ),
+        ],
+        "Cleric" => vec![
+            pack(
+                "Mace, scale mail and shield",
+                &["Mace", "Light Crossbow"],
+                Some("Scale Mail"),
+                true,
+                &["Priest's Pack", "Holy Symbol", "Crossbow Bolts (20)"],
+            ),
+            pack(
+                "Warhammer, leather and shield",
+                &["Warhammer"],
+                Some("Leather"),
+                true,
+                &["Explorer's Pack", "Holy Symbol"],
+            ),
+        ],
+        "Druid" => vec![
+            pack(
+                "Scimitar and wooden shield",
+                &["Scimitar"],
+                Some("Leather"),
+                true,
+                &["Explorer's Pack", "Druidic Focus"],
+            ),
+            pack(
+                "Quarterstaff",
+                &["Quarterstaff"],
+                Some("Leather"),
+                false,
+                &["Explorer's Pack", "Druidic Focus"],
+            ),
+        ],
+        "Fighter" => vec![
+            pack(
+                "Chain mail, longsword and shield",
+                &["Longsword", "Light Crossbow"],
+                Some("Chain Mail"),
+                true,
+                &["Dungeoneer's Pack", "Crossbow Bolts (20)"],
+            ),
+            pack(
+                "Leather, longbow and shortsword",
+                &["Longbow", "Shortsword"],
+                Some("Leather"),
+                false,
+                &["Explorer's Pack", "Arrows (20)"],
+            ),
+        ],
+        "Monk" => vec![
+            pack(
+                "Shortsword",
+                &["Shortsword"],
+                None,
+                false,
+                &["Dungeoneer's Pack", "Darts (10)"],
+            ),
+            pack(
+                "Spear",
+                &["Spear"],
+                None,
+                false,
+                &["Explorer's Pack", "Darts (10)"],
+            ),
+        ],
+        "Paladin" => vec![
+            pack(
+                "Longsword and shield",
+                &["Longsword", "Javelin"],
+                Some("Chain Mail"),
+                true,
+                &["Priest's Pack", "Holy Symbol", "Javelin (5)"],
+            ),
+            pack(
+                "Greatsword",
+                &["Greatsword", "Javelin"],
+                Some("Chain Mail"),
+                false,
+                &["Explorer's Pack", "Holy Symbol", "Javelin (5)"],
+            ),
+        ],
+        "Ranger" => vec![
+            pack(
+                "Scale mail and longbow",
+                &["Shortsword", "Longbow"],
+                Some("Scale Mail"),
+                false,
+                &["Explorer's Pack", "Arrows (20)"],
+            ),
+            pack(
+                "Leather and longbow",
+                &["Shortsword", "Longbow"],
+                Some("Leather"),
+                false,
+                &["Dungeoneer's Pack", "Arrows (20)"],
+            ),
+        ],
+        "Rogue" => vec![
+            pack(
+                "Rapier and shortbow",
+                &["Rapier", "Shortbow", "Dagger"],
+                Some("Leather"),
+                false,
+                &[
+                    "Burglar's Pack",
+                    "Thieves' Tools",
+                    "Dagger (2)",
+                    "Arrows (20)",
+                ],
+            ),
+            pack(
+                "Shortsword and shortbow",
+                &["Shortsword", "Shortbow", "Dagger"],
+                Some("Leather"),
+                false,
+                &[
+                    "Explorer's Pack",
+                    "Thieves' Tools",
+                    "Dagger (2)",
+                    "Arrows (20)",
+                ],
+            ),
+        ],
+        "Sorcerer" => vec![
+            pack(
+                "Light crossbow",
+                &["Light Crossbow", "Dagger"],
+                None,
+                false,
+                &[
+                    "Dungeoneer's Pack",
+                    "Component Pouch",
+                    "Dagger (2)",
+                    "Crossbow Bolts (20)",
+                ],
+            ),
+            pack(
+                "Quarterstaff",
+                &["Quarterstaff", "Dagger"],
+                None,
+                false,
+                &["Explorer's Pack", "Arcane Focus", "Dagger (2)"],
+            ),
+        ],
+        "Warlock" => vec![
+            pack(
+                "Light crossbow",
+                &["Light Crossbow", "Dagger"],
+                Some("Leather"),
+                false,
+                &[
+                    "Scholar's Pack",
+                    "Component Pouch",
+                    "Dagger (2)",
+                    "Crossbow Bolts (20)",
+                ],
+            ),
+            pack(
+                "Quarterstaff",
+                &["Quarterstaff", "Dagger"],
+                Some("Leather"),
+                false,
+                &["Dungeoneer's Pack", "Arcane Focus", "Dagger (2)"],
+            ),
+        ],
+        "Wizard" => vec![
+            pack(
+                "Quarterstaff",
+                &["Quarterstaff"],
+                None,
+                false,
+                &["Scholar's Pack", "Component Pouch", "Spellbook"],
+            ),
+            pack(
+                "Dagger",
+                &["Dagger"],
+                None,
+                false,
+                &["Explorer's Pack", "Arcane Focus", "Spellbook"],
+            ),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+const ARCANE_CANTRIPS: [&str; 14] = [
+    "Acid Splash",
+    "Chill Touch",
+    "Dancing Lights",
+    "Fire Bolt",
+    "Light",
+    "Mage Hand",
+    "Mending",
+    "Message",
+    "Minor Illusion",
+    "Poison Spray",
+    "Prestidigitation",
+    "Ray of Frost",
+    "Shocking Grasp",
+    "True Strike",
+];
+
+fn spellcasting(
+    ability: &str,
+    cantrips: usize,
+    spells_known: Option<usize>,
+    slots: i32,
+    cantrip_list: &[&str],
+    spell_list: &[&str],
+) -> ClassSpellcasting {
+    ClassSpellcasting {
+        ability: ability.to_string(),
+        cantrips,
+        spells_known,
+        spellbook: false,
+        slots,
+        cantrip_list: strings(cantrip_list),
+        spell_list: strings(spell_list),
+    }
+}
+
+/// What an SRD class casts at 1st level; Paladins and Rangers start casting
+/// at 2nd.
+pub fn srd_spellcasting(class: &str) -> Option<ClassSpellcasting> {
+    let casting = match class {
+        "Bard" => spellcasting(
+            "charisma",
+            2,
+            Some(4),
+            2,
+            &[
+                "Dancing Lights",
+                "Light",
+                "Mage Hand",
+                "Mending",
+                "Message",
+                "Minor Illusion",
+                "Prestidigitation",
+                "True Strike",
+                "Vicious Mockery",
+            ],
+            &[
+                "Charm Person",
+                "Comprehend Languages",
+                "Cure Wounds",
+                "Detect Magic",
+                "Disguise Self",
+                "Faerie Fire",
+                "Feather Fall",
+                "Healing Word",
+                "Heroism",
+                "Hideous Laughter",
+                "Identify",
+                "Longstrider",
+                "Silent Image",
+                "Sleep",
+                "Speak with Animals",
+                "Thunderwave",
+                "Unseen Servant",
+            ],
+        ),
+        "Cleric" => spellcasting(
+            "wisdom",
+            3,
+            None,
+            2,
+            &[
+                "Guidance",
+                "Light",
+                "Mending",
+                "Resistance",
+                "Sacred Flame",
+                "Spare the Dying",
+                "Thaumaturgy",
+            ],
+            &[
+                "Bane",
+                "Bless",
+                "Command",
+                "Create or Destroy Water",
+                "Cure Wounds",
+                "Detect Evil and Good",
+                "Detect Magic",
+                "Detect Poison and Disease",
+                "Guiding Bolt",
+                "Healing Word",
+                "Inflict Wounds",
+                "Protection from Evil and Good",
+                "Purify Food and Drink",
+                "Sanctuary",
+                "Shield of Faith",
+            ],
+        ),
+        "Druid" => spellcasting(
+            "wisdom",
+            2,
+            None,
+            2,
+            &[
+                "Druidcraft",
+                "Guidance",
+                "Mending",
+                "Poison Spray",
+                "Produce Flame",
+                "Resistance",
+                "Shillelagh",
+            ],
+            &[
+                "Charm Person",
+                "Create or Destroy Water",
+                "Cure Wounds",
+                "Detect Magic",
+                "Detect Poison and Disease",
+                "Entangle",
+                "Faerie Fire",
+                "Fog Cloud",
+                "Healing Word",
+                "Jump",
+                "Longstrider",
+                "Purify Food and Drink",
+                "Speak with Animals",
+                "Thunderwave",
+            ],
+        ),
+        "Sorcerer" => spellcasting(
+            "charisma",
+            4,
+            Some(2),
+            2,
+            &ARCANE_CANTRIPS,
+            &[
+                "Burning Hands",
+                "Charm Person",
+                "Color Spray",
+                "Comprehend Languages",
+                "Detect Magic",
+                "Disguise Self",
+                "Expeditious Retreat",
+                "False Life",
+                "Feather Fall",
+                "Fog Cloud",
+                "Jump",
+                "Mage Armor",
+                "Magic Missile",
+                "Shield",
+                "Silent Image",
+                "Sleep",
+                "Thunderwave",
+            ],
+        ),
+        "Warlock" => spellcasting(
+            "charisma",
+            2,
+            Some(2),
+            1,
+            &[
+                "Chill Touch",
+                "Eldritch Blast",
+                "Mage Hand",
+                "Minor Illusion",
+                "Poison Spray",
+                "Prestidigitation",
+                "True Strike",
+            ],
+            &[
+                "Charm Person",
+                "Comprehend Languages",
+                "Expeditious Retreat",
+                "Hellish Rebuke",
+                "Illusory Script",
+                "Protection from Evil and Good",
+                "Unseen Servant",
+            ],
+        ),
+        "Wizard" => ClassSpellcasting {
+            spellbook: true,
+            ..spellcasting(
+                "intelligence",
+                3,
+                Some(6),
+                2,
+                &ARCANE_CANTRIPS,
+                &[
+                    "Alarm",
+                    "Burning Hands",
+                    "Charm Person",
+                    "Color Spray",
+                    "Comprehend Languages",
+                    "Detect Magic",
+                    "Disguise Self",
+                    "Expeditious Retreat",
+                    "False Life",
+                    "Feather Fall",
+                    "Find Familiar",
+                    "Floating Disk",
+                    "Fog Cloud",
+                    "Grease",
+                    "Hideous Laughter",
+                    "Identify",
+                    "Illusory Script",
+                    "Jump",
+                    "Longstrider",
+                    "Mage Armor",
+                    "Magic Missile",
+                    "Protection from Evil and Good",
+                    "Shield",
+                    "Silent Image",
+                    "Sleep",
+                    "Thunderwave",
+                    "Unseen Servant",
+                ],
+            )
+        },
+        _ => return None,
+    };
+    Some(casting)
+}
+
+/// Check a homebrew background, naming the first problem.
+pub fn validate_background(background: &BackgroundTemplate) -> Result<(), String> {
+    if background.name.trim().is_empty() {
+        return Err("A background has no name".to_string());
+    }
+    if let Some(skill) = background
+        .skills
+        .iter()
+        .find(|s| default_skill_ability(s).is_none())
+    {
+        return Err(format!(
+            "{} has an unknown skill '{}'",
+            background.name, skill
+        ));
+    }
+    Ok(())
+}
+
+/// Check a homebrew class's packs and spellcasting, naming the first problem.
+pub fn validate_class_options(
+    class: &str,
+    equipment: &[EquipmentPack],
+    spellcasting: Option<&ClassSpellcasting>,
+) -> Result<(), String> {
+    for pack in equipment {
+        if let Some(weapon) = pack.weapons.iter().find(|w| srd_weapon(w).is_none()) {
+            return Err(format!(
+                "{} pack {} has an unknown weapon '{}'",
+                class, pack.name, weapon
+            ));
+        }
+        if let Some(armor) = pack.armor.as_deref().filter(|a| srd_armor(a, 0).is_none()) {
+            return Err(format!(
+                "{} pack {} has an unknown armor '{}'",
+                class, pack.name, armor
+            ));
+        }
+    }
+    if let Some(casting) = spellcasting {
+        if normalize_ability_name(&casting.ability).is_none() {
+            return Err(format!(
+                "{} has an unknown spellcasting ability '{}'",
+                class, casting.ability
+            ));
+        }
+        if casting.cantrips > casting.cantrip_list.len() {
+            return Err(format!(
+                "{} knows {} cantrips from only {}",
+                class,
+                casting.cantrips,
+                casting.cantrip_list.len()
+            ));
+        }
+        if casting.spells_known.unwrap_or(0) > casting.spell_list.len() {
+            return Err(format!("{} knows more spells than its list has", class));
+        }
+    }
+    Ok(())
+}
+
+impl CharacterSheet {
+    /// Add a background's skill proficiencies, gear and gold. Skill
+    /// modifiers are worked out again after.
+    pub fn apply_background(&mut self, background: &BackgroundTemplate) {
+        self.character.background = Some(background.name.clone());
+        for skill in &background.skills {
+            self.skills.entry(skill.clone()).or_default().proficient = true;
+        }
+        self.derive_from_ability_scores();
+
+        let equipment = self.equipment.get_or_insert_with(Equipment::default);
+        equipment
+            .items
+            .extend(background.items.iter().map(|i| InventoryItem::parse(i)));
+        equipment.currency.add(Coin::Gold, background.gold);
+    }
+
+    /// Give the character a starting equipment pack: its weapons, worn armor
+    /// and shield (setting the armor class), items and gold.
+    pub fn apply_equipment_pack(&mut self, pack: &EquipmentPack) {
+        let dexterity = self.modifiers.dexterity;
+        let armor = pack
+            .armor
+            .as_deref()
+            .and_then(|name| srd_armor(name, dexterity));
+        let worn = armor
+            .as_ref()
+            .and_then(|a| a.armor_class_with_dex)
+            .unwrap_or(10 + dexterity);
+        self.combat.armor_class = worn + if pack.shield { 2 } else { 0 };
+
+        let equipment = self.equipment.get_or_insert_with(Equipment::default);
+        equipment
+            .weapons
+            .extend(pack.weapons.iter().filter_map(|w| srd_weapon(w)));
+        equipment.armor = armor;
+        let mut items: Vec<InventoryItem> =
+            pack.items.iter().map(|i| InventoryItem::parse(i)).collect();
+        if pack.shield {
+            items.push(InventoryItem {
+                equipped: true,
+                ..InventoryItem::new("Shield")
+            });
+        }
+        equipment.items.extend(items);
+        equipment.currency.add(Coin::Gold, pack.gold);
+    }
+
+    /// Set up 1st-level spellcasting with the picked cantrips and spells.
+    pub fn apply_spellcasting(
+        &mut self,
+        casting: &ClassSpellcasting,
+        cantrips: &[String],
+        spells: &[String],
+    ) {
+        let ability = normalize_ability_name(&casting.ability).unwrap_or("intelligence");
+        let modifier = self.modifiers.get(ability).unwrap_or(0);
+        let mut known = cantrips.to_vec();
+        known.extend(spells.iter().cloned());
+        let mut prepared = cantrips.to_vec();
+        prepared.extend(casting.prepared(spells, modifier));
+        self.spells = Some(SpellCasting {
+            spellcasting_ability: Some(ability.to_string()),
+            spell_slots: [("1".to_string(), casting.slots)].into_iter().collect(),
+            known_spells: known,
+            prepared_spells: prepared,
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::character::CharacterData;
+
+    #[test]
+    fn test_srd_options_are_valid() {
+        for background in srd_backgrounds() {
+            assert_eq!(validate_background(&background), Ok(()));
+        }
+        for class in ["Fighter", "Rogue", "Wizard", "Cleric", "Ranger"] {
+            assert_eq!(
+                validate_class_options(
+                    class,
+                    &srd_equipment(class),
+                    srd_spellcasting(class).as_ref()
+                ),
+                Ok(())
+            );
+        }
+        assert!(srd_spellcasting("Paladin").is_none());
+    }
+
+    #[test]
+    fn test_armor_class_from_pack() {
+        assert_eq!(
+            srd_armor("scale mail", 3).unwrap().armor_class_with_dex,
+            Some(16)
+        );
+
+        let mut data = CharacterData::create_with_scores([15, 14, 13, 12, 10, 8]);
+        let sheet = data.sheet.as_mut().unwrap();
+        sheet.apply_equipment_pack(&srd_equipment("Fighter")[0]);
+        assert_eq!(sheet.combat.armor_class, 18);
+        let equipment = sheet.equipment.as_ref().unwrap();
+        assert_eq!(equipment.weapons.len(), 2);
+        assert!(equipment.items.iter().any(|i| i.name == "Shield"));
+        assert!(equipment
+            .items
+            .iter()
+            .any(|i| i.name == "Crossbow Bolts" && i.quantity == 20));
+
+        sheet.apply_background(&srd_backgrounds()[0]);
+        assert!(sheet.skills["religion"].proficient);
+        assert_eq!(
+            sheet.equipment.as_ref().unwrap().currency.get(Coin::Gold),
+            15
+        );
+    }
+
+    #[test]
+    fn test_spellcasting_counts() {
+        let cleric = srd_spellcasting("Cleric").unwrap();
+        assert_eq!(cleric.spell_count(3), 4);
+        assert_eq!(cleric.spell_count(-1), 1);
+
+        let wizard = srd_spellcasting("Wizard").unwrap();
+        assert_eq!(wizard.spell_count(3), 6);
+        let book = strings(&["Shield", "Sleep", "Alarm", "Grease", "Jump", "Identify"]);
+        assert_eq!(wizard.prepared(&book, 2).len(), 3);
+
+        let mut data = CharacterData::create_with_scores([8, 14, 13, 16, 12, 10]);
+        let sheet = data.sheet.as_mut().unwrap();
+        sheet.apply_spellcasting(&wizard, &strings(&["Fire Bolt"]), &book);
+        let spells = sheet.spells.as_ref().unwrap();
+        assert_eq!(spells.known_spells.len(), 7);
+        assert_eq!(spells.prepared_spells.len(), 5);
+        assert_eq!(spells.slots_remaining("1"), 2);
+        assert_eq!(sheet.sheet_spell_save_dc(), 13);
+    }
+}
@@ -5,32 +5,65 @@
 //! - app settings
 //! - command history
 //!
+//! Character sheets can optionally be encrypted at rest with a passphrase (see
+//! `encryption`), and synced with a remote SurrealDB server (see
+//! `cloud_sync`). Records saved by older builds are upgraded on opening (see
+//! `db_migrations`).
+//!
 //! The embedded database is stored in the same app-data folder previously used for the
-//! legacy SQLite `characters.db` file.
+//! legacy SQLite `characters.db` file. Each database profile (see `profiles`) has its
+//! own datastore folder there.
 
-use bevy::prelude::*;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
 
+use surrealdb::engine::any::{self, Any};
 use surrealdb::engine::local::{Db, Mem, SurrealKv};
+use surrealdb::opt::auth::{Database as DatabaseAuth, Root};
 use surrealdb::Surreal;
 
-use super::character::{CharacterListEntry, CharacterSheet};
+use super::character::{CharacterInfo, CharacterListEntry, CharacterSheet};
+use super::cloud_sync::{
+    new_character_uid, now_millis, plan_sync, CloudSyncConfig, SyncAction, SyncRecord, SyncStamp,
+    SyncSummary, CLOUD_SYNC_SETTING_KEY,
+};
+use super::db_migrations::{
+    diagnose_setting_record, latest_schema_version, migrate_record, repair_character_record,
+    tables_to_migrate, DoctorFinding, DoctorReport, RawRecord, RecordFix, QUARANTINE_TABLE,
+    SCHEMA_VERSION_SETTING_KEY,
+};
+use super::encryption::{DatabaseKey, EncryptedText, EncryptionConfig};
+use super::profiles::{DatabaseProfile, ProfileRegistry, DEFAULT_PROFILE_ID};
+use super::roll_stats::RollRecord;
 
 /// Legacy SQLite database file name (for one-time migration).
 const LEGACY_SQLITE_FILE: &str = "characters.db";
-/// SurrealDB folder name (embedded database).
-const DATABASE_FOLDER: &str = "characters.surrealdb";
+/// SurrealDB folder name (embedded database) of the default profile.
+pub(crate) const DATABASE_FOLDER: &str = "characters.surrealdb";
+/// Profile list and active profile.
+const PROFILES_FILE: &str = "profiles.json";
 /// App data folder name.
 const APP_DATA_FOLDER: &str = "DnDGameRolls";
 
 const NS: &str = "dndgamerolls";
 const DB: &str = "dndgamerolls";
 
+/// `setting` key holding the [`EncryptionConfig`] of an encrypted database.
+const ENCRYPTION_SETTING_KEY: &str = "encryption";
+
+/// How long to wait for the cloud sync server before giving up.
+const SYNC_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Profile chosen on the command line for this run only (overrides the
+/// active profile in `profiles.json`).
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CharacterDocument {
     /// Stable internal ID (never changes).
@@ -41,22 +74,38 @@ struct CharacterDocument {
     /// the same characters.
     #[serde(default)]
     legacy_sqlite_id: Option<i64>,
+    /// Id shared with the other machines this character is synced to (empty
+    /// until first saved or synced).
+    #[serde(default)]
+    uid: String,
+    /// Milliseconds since the epoch of the last edit, for cloud sync.
+    #[serde(default)]
+    updated_at: u64,
     /// Convenience fields for listing/indexing.
     name: String,
     class: String,
     race: String,
     level: i32,
-    /// Full character sheet.
+    /// Full character sheet (only the listing fields when `sealed_sheet` is set).
     sheet: CharacterSheet,
+    /// Encrypted JSON of the full sheet when the database is encrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_sheet: Option<EncryptedText>,
 }
 
 /// Resource for managing the character database.
-#[derive(Resource)]
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
 pub struct CharacterDatabase {
     rt: tokio::runtime::Runtime,
     db: Mutex<Surreal<Db>>,
     /// Path to the embedded datastore.
     pub db_path: PathBuf,
+    /// Id of the profile this database belongs to.
+    pub profile_id: String,
+    /// Present when character sheets are encrypted at rest.
+    encryption: Mutex<Option<EncryptionConfig>>,
+    /// Key for the encrypted sheets once unlocked (memory only).
+    key: Mutex<Option<DatabaseKey>>,
 }
 
 impl CharacterDatabase {
@@ -140,6 +189,8 @@ impl CharacterDatabase {
             DEFINE TABLE setting SCHEMALESS;
 
             DEFINE TABLE command_history SCHEMALESS;
+
+            DEFINE TABLE quarantine SCHEMALESS;
         "#;
 
         db.query(schema)
@@ -149,10 +200,49 @@ impl CharacterDatabase {
         Ok(())
     }
 
-    /// Open or create the database.
+    fn profiles_path() -> Result<PathBuf, String> {
+        Ok(Self::get_data_dir()?.join(PROFILES_FILE))
+    }
+
+    /// Load the profile list (just the default profile if none was saved yet).
+    pub fn load_profiles() -> ProfileRegistry {
+        match Self::profiles_path() {
+            Ok(path) => ProfileRegistry::load_from(&path),
+            Err(_) => ProfileRegistry::default(),
+        }
+    }
+
+    /// Save the profile list.
+    pub fn save_profiles(registry: &ProfileRegistry) -> Result<(), String> {
+        registry.save_to(&Self::profiles_path()?)
+    }
+
+    /// Use a profile for the rest of this run without changing the saved
+    /// active profile. Must be called before the database is first opened.
+    pub fn use_profile_for_this_run(profile_id: &str) {
+        let _ = PROFILE_OVERRIDE.set(profile_id.to_string());
+    }
+
+    /// The profile `open()` will use.
+    pub fn current_profile() -> DatabaseProfile {
+        let registry = Self::load_profiles();
+        PROFILE_OVERRIDE
+            .get()
+            .and_then(|id| registry.get(id))
+            .unwrap_or_else(|| registry.active_profile())
+            .clone()
+    }
+
+    /// Open or create the database of the current profile.
     pub fn open() -> Result<Self, String> {
+        Self::open_profile(&Self::current_profile())
+    }
+
+    /// Open or create the database of a specific profile.
+    pub fn open_profile(profile: &DatabaseProfile) -> Result<Self, String> {
         let data_dir = Self::get_data_dir()?;
-        let db_path = data_dir.join(DATABASE_FOLDER);
+        let datastore_folder = profile.datastore_folder();
+        let db_path = data_dir.join(&datastore_folder);
 
         let rt = Self::make_runtime()?;
         // Ensure the app data directory exists and is writable.
@@ -166,7 +256,7 @@ impl CharacterDatabase {
                 .duration_since(UNIX_EPOCH)
                 .map(|d| d.as_secs())
                 .unwrap_or(0);
-            let backup_path = data_dir.join(format!("{}.file.bak-{}", DATABASE_FOLDER, ts));
+            let backup_path = data_dir.join(format!("{}.file.bak-{}", datastore_folder, ts));
             warn!(
                 "Database path {:?} is a file; backing up to {:?} and recreating as a directory",
                 db_path, backup_path
@@ -209,7 +299,7 @@ impl CharacterDatabase {
                         .map(|d| d.as_secs())
                         .unwrap_or(0);
 
-                    let backup_path = data_dir.join(format!("{}.bak-{}", DATABASE_FOLDER, ts));
+                    let backup_path = data_dir.join(format!("{}.bak-{}", datastore_folder, ts));
 
                     warn!(
                         "SurrealDB datastore appears unreadable at {:?} ({}). Backing up to {:?} and recreating.",
@@ -247,7 +337,12 @@ impl CharacterDatabase {
             rt,
             db: Mutex::new(db),
             db_path: db_path.clone(),
+            profile_id: profile.id.clone(),
+            encryption: Mutex::new(None),
+            key: Mutex::new(None),
         };
+        this.upgrade_old_records();
+        this.load_encryption_config()?;
 
         Ok(this)
     }
@@ -306,11 +401,18 @@ impl CharacterDatabase {
 
         rt.block_on(Self::init(&db))?;
 
-        Ok(Self {
+        let this = Self {
             rt,
             db: Mutex::new(db),
             db_path: path,
-        })
+            profile_id: DEFAULT_PROFILE_ID.to_string(),
+            encryption: Mutex::new(None),
+            key: Mutex::new(None),
+        };
+        this.upgrade_old_records();
+        this.load_encryption_config()?;
+
+        Ok(this)
     }
 
     /// "In-memory" database for testing: uses a unique temp folder.
@@ -326,6 +428,9 @@ impl CharacterDatabase {
             rt,
             db: Mutex::new(db),
             db_path: PathBuf::new(),
+            profile_id: DEFAULT_PROFILE_ID.to_string(),
+            encryption: Mutex::new(None),
+            key: Mutex::new(None),
         })
     }
 
@@ -334,6 +439,225 @@ impl CharacterDatabase {
         f(&db)
     }
 
+    /// Schema version the stored records were last upgraded to (0 before
+    /// migrations existed).
+    pub fn schema_version(&self) -> Result<u32, String> {
+        Ok(self
+            .get_setting::<u32>(SCHEMA_VERSION_SETTING_KEY)?
+            .unwrap_or(0))
+    }
+
+    /// Run the migrations newer than the stored schema version over the
+    /// records of their tables; returns how many records were upgraded.
+    fn run_migrations(&self) -> Result<usize, String> {
+        let version = self.schema_version()?;
+        let latest = latest_schema_version();
+        if version > latest {
+            warn!(
+                "Database schema version {} is newer than this build's ({}); leaving records as they are",
+                version, latest
+            );
+            return Ok(0);
+        }
+        if version == latest {
+            return Ok(0);
+        }
+
+        let mut upgraded = 0;
+        for table in tables_to_migrate(version) {
+            for mut raw in self.raw_records(table)? {
+                if migrate_record(table, &mut raw.record, version) {
+                    self.write_raw_record(table, &raw.key, &raw.record)?;
+                    upgraded += 1;
+                }
+            }
+        }
+        self.set_setting(SCHEMA_VERSION_SETTING_KEY, latest)?;
+        Ok(upgraded)
+    }
+
+    /// Run the pending migrations on opening; a failure is logged and retried
+    /// next time rather than keeping the database from opening.
+    fn upgrade_old_records(&self) {
+        match self.run_migrations() {
+            Ok(0) => {}
+            Ok(upgraded) => info!("Upgraded {} record(s) saved by an older version", upgraded),
+            Err(e) => warn!("Failed to upgrade the database records: {}", e),
+        }
+    }
+
+    /// Every record of `table` as stored, with its record key.
+    fn raw_records(&self, table: &str) -> Result<Vec<RawRecord>, String> {
+        let table = table.to_owned();
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let mut response = db
+                    .query(
+                        "SELECT *, record::id(id) AS record_key OMIT id FROM type::table($table)",
+                    )
+                    .bind(("table", table.clone()))
+                    .await
+                    .map_err(|e| format!("Failed to query {} records: {}", table, e))?;
+                let rows: Vec<JsonValue> = response
+                    .take(0)
+                    .map_err(|e| format!("Failed to read {} records: {}", table, e))?;
+                Ok(rows
+                    .into_iter()
+                    .filter_map(|mut record| {
+                        let key = record.as_object_mut()?.remove("record_key")?;
+                        Some(RawRecord { key, record })
+                    })
+                    .collect())
+            })
+        })
+    }
+
+    fn write_raw_record(
+        &self,
+        table: &str,
+        key: &JsonValue,
+        record: &JsonValue,
+    ) -> Result<(), String> {
+        let table = table.to_owned();
+        let (key, record) = (key.clone(), record.clone());
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                db.query("UPSERT type::thing($table, $key) CONTENT $record")
+                    .bind(("table", table.clone()))
+                    .bind(("key", key))
+                    .bind(("record", record))
+                    .await
+                    .and_then(|response| response.check())
+                    .map_err(|e| format!("Failed to save {} record: {}", table, e))?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Move a record that can't be repaired to the quarantine table.
+    fn quarantine_record(&self, table: &str, raw: &RawRecord, problem: &str) -> Result<(), String> {
+        #[derive(Serialize)]
+        struct QuarantinedRecord {
+            table: String,
+            key: JsonValue,
+            record: JsonValue,
+            problem: String,
+            quarantined_at: u64,
+        }
+
+        let table = table.to_owned();
+        let key = raw.key.clone();
+        let entry = QuarantinedRecord {
+            table: table.clone(),
+            key: key.clone(),
+            record: raw.record.clone(),
+            problem: problem.to_string(),
+            quarantined_at: now_millis(),
+        };
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                db.query(
+                    "BEGIN TRANSACTION; \
+                     CREATE type::table($quarantine) CONTENT $entry; \
+                     DELETE type::thing($table, $key); \
+                     COMMIT TRANSACTION;",
+                )
+                .bind(("quarantine", QUARANTINE_TABLE))
+                .bind(("entry", entry))
+                .bind(("table", table.clone()))
+                .bind(("key", key))
+                .await
+                .and_then(|response| response.check())
+                .map_err(|e| format!("Failed to quarantine {} record: {}", table, e))?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Why a record can't be read by this build, and how to fix it.
+    ///
+    /// Sealed sheets are only checked with the database `key`; without it
+    /// just the stored document is.
+    fn diagnose_record(
+        table: &str,
+        raw: &RawRecord,
+        key: Option<&DatabaseKey>,
+    ) -> Option<(String, RecordFix)> {
+        match table {
+            "character" => {
+                let doc = match serde_json::from_value::<CharacterDocument>(raw.record.clone()) {
+                    Ok(doc) => doc,
+                    Err(e) => {
+                        let mut repaired = raw.record.clone();
+                        repair_character_record(&raw.key, &mut repaired);
+                        let fix =
+                            match serde_json::from_value::<CharacterDocument>(repaired.clone()) {
+                                Ok(_) => RecordFix::Rewrite(repaired),
+                                Err(_) => RecordFix::Quarantine,
+                            };
+                        return Some((e.to_string(), fix));
+                    }
+                };
+                let problem = key?
+                    .decrypt(doc.sealed_sheet.as_ref()?)
+                    .and_then(|json| {
+                        serde_json::from_str::<CharacterSheet>(&json)
+                            .map_err(|e| format!("encrypted sheet: {}", e))
+                    })
+                    .err()?;
+                Some((problem, RecordFix::Quarantine))
+            }
+            "setting" => diagnose_setting_record(&raw.record),
+            "roll" => serde_json::from_value::<RollRecord>(raw.record.clone())
+                .err()
+                .map(|e| (e.to_string(), RecordFix::Quarantine)),
+            _ => None,
+        }
+    }
+
+    /// Check every character, setting and roll record against what this
+    /// build reads (`db doctor`). With `repair`, pending migrations run
+    /// first, then fixable records are rewritten and the rest quarantined.
+    /// Sealed sheets of a locked database are listed as not checked.
+    pub fn doctor(&self, repair: bool) -> Result<DoctorReport, String> {
+        let mut report = DoctorReport {
+            schema_version: self.schema_version()?,
+            ..Default::default()
+        };
+        if repair {
+            report.migrated = self.run_migrations()?;
+        }
+
+        let key = self.unlocked_key().ok();
+        for table in ["character", "setting", "roll"] {
+            for raw in self.raw_records(table)? {
+                let sealed = raw.record.get("sealed_sheet").is_some_and(|v| !v.is_null());
+                if table == "character" && sealed && key.is_none() {
+                    report.locked.push(raw.key_text());
+                }
+                let Some((problem, fix)) = Self::diagnose_record(table, &raw, key.as_ref()) else {
+                    continue;
+                };
+                if repair {
+                    match &fix {
+                        RecordFix::Rewrite(record) => {
+                            self.write_raw_record(table, &raw.key, record)?
+                        }
+                        RecordFix::Quarantine => self.quarantine_record(table, &raw, &problem)?,
+                    }
+                }
+                report.findings.push(DoctorFinding {
+                    table: table.to_string(),
+                    key: raw.key_text(),
+                    problem,
+                    fix,
+                });
+            }
+        }
+        report.repaired = repair;
+        Ok(report)
+    }
+
     fn next_character_id(&self) -> Result<i64, String> {
         self.with_db(|db| {
             self.rt.block_on(async {
@@ -350,21 +674,73 @@ impl CharacterDatabase {
     }
 
     fn to_doc(
+        &self,
         sid: i64,
         sheet: &CharacterSheet,
         legacy_sqlite_id: Option<i64>,
-    ) -> CharacterDocument {
-        CharacterDocument {
+        uid: String,
+        updated_at: u64,
+    ) -> Result<CharacterDocument, String> {
+        let (stored_sheet, sealed_sheet) = if self.encryption_enabled() {
+            let key = self.unlocked_key()?;
+            let json = serde_json::to_string(sheet)
+                .map_err(|e| format!("Failed to serialize character: {}", e))?;
+            // Only the listing fields stay readable.
+            let listing = CharacterSheet {
+                character: CharacterInfo {
+                    name: sheet.character.name.clone(),
+                    class: sheet.character.class.clone(),
+                    race: sheet.character.race.clone(),
+                    level: sheet.character.level,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            (listing, Some(key.encrypt(&json)?))
+        } else {
+            (sheet.clone(), None)
+        };
+
+        Ok(CharacterDocument {
             sid,
             legacy_sqlite_id,
+            uid,
+            updated_at,
             name: sheet.character.name.clone(),
             class: sheet.character.class.clone(),
             race: sheet.character.race.clone(),
             level: sheet.character.level,
-            sheet: sheet.clone(),
+            sheet: stored_sheet,
+            sealed_sheet,
+        })
+    }
+
+    /// The full sheet stored in a document, decrypting it if needed.
+    fn sheet_from_doc(&self, doc: CharacterDocument) -> Result<CharacterSheet, String> {
+        match doc.sealed_sheet {
+            Some(sealed) => {
+                let json = self.unlocked_key()?.decrypt(&sealed)?;
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Failed to decode encrypted character: {}", e))
+            }
+            None => Ok(doc.sheet),
         }
     }
 
+    fn write_doc(&self, doc: CharacterDocument) -> Result<(), String> {
+        let sid = doc.sid;
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let _: Option<CharacterDocument> = db
+                    .upsert(("character", sid))
+                    .content(doc)
+                    .await
+                    .map_err(|e| format!("Failed to save character: {}", e))?;
+                Ok(())
+            })
+        })
+    }
+
     /// Save a new character and return its ID.
     pub fn create_character(&self, sheet: &CharacterSheet) -> Result<i64, String> {
         let sid = self.next_character_id()?;
@@ -373,18 +749,24 @@ impl CharacterDatabase {
     }
 
     fn upsert_character(&self, sid: i64, sheet: &CharacterSheet) -> Result<(), String> {
-        let doc = Self::to_doc(sid, sheet, None);
+        let uid = self.character_uid(sid)?;
+        let doc = self.to_doc(sid, sheet, None, uid, now_millis())?;
+        self.write_doc(doc)
+    }
 
-        self.with_db(|db| {
+    /// The sync uid of a stored character, or a new one.
+    fn character_uid(&self, sid: i64) -> Result<String, String> {
+        let doc: Option<CharacterDocument> = self.with_db(|db| {
             self.rt.block_on(async {
-                let _: Option<CharacterDocument> = db
-                    .upsert(("character", sid))
-                    .content(doc)
+                db.select(("character", sid))
                     .await
-                    .map_err(|e| format!("Failed to save character: {}", e))?;
-                Ok(())
+                    .map_err(|e| format!("Failed to load character: {}", e))
             })
-        })
+        })?;
+        Ok(doc
+            .map(|doc| doc.uid)
+            .filter(|uid| !uid.is_empty())
+            .unwrap_or_else(new_character_uid))
     }
 
     /// Upsert a character converted from legacy SQLite.
@@ -396,18 +778,9 @@ impl CharacterDatabase {
         legacy_id: i64,
         sheet: &CharacterSheet,
     ) -> Result<(), String> {
-        let doc = Self::to_doc(legacy_id, sheet, Some(legacy_id));
-
-        self.with_db(|db| {
-            self.rt.block_on(async {
-                let _: Option<CharacterDocument> = db
-                    .upsert(("character", legacy_id))
-                    .content(doc)
-                    .await
-                    .map_err(|e| format!("Failed to save legacy character: {}", e))?;
-                Ok(())
-            })
-        })
+        let uid = self.character_uid(legacy_id)?;
+        let doc = self.to_doc(legacy_id, sheet, Some(legacy_id), uid, now_millis())?;
+        self.write_doc(doc)
     }
 
     /// Update an existing character by ID.
@@ -442,7 +815,7 @@ impl CharacterDatabase {
             return Err(format!("Character with id {} not found", id));
         };
 
-        Ok(decoded.sheet)
+        self.sheet_from_doc(decoded)
     }
 
     /// Delete a character by ID.
@@ -558,6 +931,136 @@ impl CharacterDatabase {
         })
     }
 
+    fn load_encryption_config(&self) -> Result<(), String> {
+        let config = self.get_setting::<EncryptionConfig>(ENCRYPTION_SETTING_KEY)?;
+        *self.encryption.lock().map_err(|e| e.to_string())? = config;
+        Ok(())
+    }
+
+    fn unlocked_key(&self) -> Result<DatabaseKey, String> {
+        self.key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "Database is locked; enter the passphrase first".to_string())
+    }
+
+    fn all_character_docs(&self) -> Result<Vec<CharacterDocument>, String> {
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let mut response = db
+                    .query("SELECT * OMIT id FROM character")
+                    .await
+                    .map_err(|e| format!("Failed to query characters: {}", e))?;
+                response
+                    .take::<Vec<CharacterDocument>>(0)
+                    .map_err(|e| format!("Failed to decode characters: {}", e))
+            })
+        })
+    }
+
+    /// Rewrite every character with the current encryption state.
+    fn rewrite_all_characters(
+        &self,
+        sheets: Vec<(CharacterDocument, CharacterSheet)>,
+    ) -> Result<(), String> {
+        for (doc, sheet) in sheets {
+            let rewritten = self.to_doc(
+                doc.sid,
+                &sheet,
+                doc.legacy_sqlite_id,
+                doc.uid,
+                doc.updated_at,
+            )?;
+            self.write_doc(rewritten)?;
+        }
+        Ok(())
+    }
+
+    /// Decrypt every character (needs the key when encrypted).
+    fn load_all_sheets(&self) -> Result<Vec<(CharacterDocument, CharacterSheet)>, String> {
+        self.all_character_docs()?
+            .into_iter()
+            .map(|doc| {
+                let sheet = self.sheet_from_doc(doc.clone())?;
+                Ok((doc, sheet))
+            })
+            .collect()
+    }
+
+    /// Whether character sheets are encrypted at rest.
+    pub fn encryption_enabled(&self) -> bool {
+        self.encryption
+            .lock()
+            .map(|config| config.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Encrypted and no passphrase entered yet.
+    pub fn is_locked(&self) -> bool {
+        self.encryption_enabled() && self.key.lock().map(|k| k.is_none()).unwrap_or(true)
+    }
+
+    /// Enter the passphrase of an encrypted database.
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        let config = self
+            .encryption
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "Database is not encrypted".to_string())?;
+        let key = config.unlock(passphrase)?;
+        *self.key.lock().map_err(|e| e.to_string())? = Some(key);
+        Ok(())
+    }
+
+    /// Forget the key until the passphrase is entered again.
+    pub fn lock(&self) {
+        if let Ok(mut key) = self.key.lock() {
+            *key = None;
+        }
+    }
+
+    /// Encrypt all character sheets with a new passphrase.
+    pub fn enable_encryption(&self, passphrase: &str) -> Result<(), String> {
+        if self.encryption_enabled() {
+            return Err("Database is already encrypted".to_string());
+        }
+
+        let sheets = self.load_all_sheets()?;
+        let (config, key) = EncryptionConfig::new(passphrase)?;
+
+        // Save the config first so sheets sealed below can always be opened.
+        self.set_setting(ENCRYPTION_SETTING_KEY, config.clone())?;
+        *self.encryption.lock().map_err(|e| e.to_string())? = Some(config);
+        *self.key.lock().map_err(|e| e.to_string())? = Some(key);
+
+        self.rewrite_all_characters(sheets)
+    }
+
+    /// Decrypt all character sheets and turn encryption off (must be unlocked).
+    pub fn disable_encryption(&self) -> Result<(), String> {
+        if !self.encryption_enabled() {
+            return Ok(());
+        }
+
+        let sheets = self.load_all_sheets()?;
+
+        *self.encryption.lock().map_err(|e| e.to_string())? = None;
+        self.rewrite_all_characters(sheets)?;
+        self.lock();
+
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let _: Option<JsonValue> = db
+                    .delete(("setting", ENCRYPTION_SETTING_KEY))
+                    .await
+                    .map_err(|e| format!("Failed to remove encryption setting: {}", e))?;
+                Ok(())
+            })
+        })
+    }
+
     pub fn load_command_history(&self) -> Result<Vec<String>, String> {
         #[derive(Serialize, Deserialize, Default)]
         struct Doc {
@@ -594,13 +1097,243 @@ impl CharacterDatabase {
             })
         })
     }
+
+    /// Save a resolved roll for the roll statistics.
+    pub fn record_roll(&self, record: &RollRecord) -> Result<(), String> {
+        let record = record.clone();
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let _: Option<JsonValue> = db
+                    .create("roll")
+                    .content(record)
+                    .await
+                    .map_err(|e| format!("Failed to save roll: {}", e))?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Every saved roll, oldest first.
+    pub fn load_rolls(&self) -> Result<Vec<RollRecord>, String> {
+        self.with_db(|db| {
+            self.rt.block_on(async {
+                let mut response = db
+                    .query("SELECT * OMIT id FROM roll ORDER BY rolled_at ASC")
+                    .await
+                    .map_err(|e| format!("Failed to query rolls: {}", e))?;
+                response
+                    .take::<Vec<RollRecord>>(0)
+                    .map_err(|e| format!("Failed to decode rolls: {}", e))
+            })
+        })
+    }
+
+    /// Sync the characters with the server in the `cloud_sync` setting (see
+    /// `cloud_sync`).
+    pub fn sync_characters(&self) -> Result<SyncSummary, String> {
+        let mut config = CloudSyncConfig::load(self)
+            .ok_or_else(|| "Cloud sync isn't set up (see `dndgamerolls sync`)".to_string())?;
+        if self.encryption_enabled() {
+            return Err("Cloud sync isn't available for an encrypted database".to_string());
+        }
+
+        let remote = self.rt.block_on(connect_remote(&config))?;
+        let summary = self.sync_characters_with(&remote, &mut config.synced)?;
+
+        config.last_sync = now_millis();
+        self.set_setting(CLOUD_SYNC_SETTING_KEY, config)?;
+        Ok(summary)
+    }
+
+    /// Sync the characters with a connected server, given the versions after
+    /// the previous sync, which are updated to the ones both sides now hold.
+    fn sync_characters_with(
+        &self,
+        remote: &Surreal<Any>,
+        synced: &mut HashMap<String, u64>,
+    ) -> Result<SyncSummary, String> {
+        // Characters saved before sync existed get their uid now
+        let mut docs = self.all_character_docs()?;
+        for doc in docs.iter_mut().filter(|doc| doc.uid.is_empty()) {
+            doc.uid = new_character_uid();
+            doc.updated_at = now_millis();
+            self.write_doc(doc.clone())?;
+        }
+        docs.sort_by_key(|doc| doc.sid);
+
+        let records: Vec<SyncRecord> = self.rt.block_on(async {
+            let mut response = remote
+                .query("SELECT * OMIT id FROM character")
+                .await
+                .map_err(|e| format!("Failed to query the sync server: {}", e))?;
+            response
+                .take(0)
+                .map_err(|e| format!("Failed to decode the sync server's characters: {}", e))
+        })?;
+
+        let local_stamps: Vec<SyncStamp> = docs
+            .iter()
+            .map(|doc| SyncStamp {
+                uid: doc.uid.clone(),
+                updated_at: doc.updated_at,
+            })
+            .collect();
+        let remote_stamps: Vec<SyncStamp> = records
+            .iter()
+            .map(|record| SyncStamp {
+                uid: record.uid.clone(),
+                updated_at: record.updated_at,
+            })
+            .collect();
+        let local: HashMap<&str, &CharacterDocument> =
+            docs.iter().map(|doc| (doc.uid.as_str(), doc)).collect();
+        let remote_by_uid: HashMap<&str, &SyncRecord> = records
+            .iter()
+            .map(|record| (record.uid.as_str(), record))
+            .collect();
+
+        let mut summary = SyncSummary::default();
+        for action in plan_sync(&local_stamps, &remote_stamps, synced) {
+            match action {
+                SyncAction::Push(uid) => {
+                    let record = self.sync_record(local[uid.as_str()])?;
+                    self.push_record(remote, &record)?;
+                    summary.pushed += 1;
+                }
+                SyncAction::Pull(uid) => {
+                    let existing = local.get(uid.as_str()).copied();
+                    self.store_synced_character(existing, remote_by_uid[uid.as_str()])?;
+                    summary.pulled += 1;
+                }
+                SyncAction::DeleteLocal(uid) => {
+                    self.delete_character(local[uid.as_str()].sid)?;
+                    summary.deleted += 1;
+                }
+                SyncAction::DeleteRemote(uid) => {
+                    self.rt.block_on(async {
+                        let _: Option<SyncRecord> = remote
+                            .delete(("character", uid.clone()))
+                            .await
+                            .map_err(|e| format!("Failed to delete synced character: {}", e))?;
+                        Ok::<_, String>(())
+                    })?;
+                    summary.deleted += 1;
+                }
+                SyncAction::Conflict { uid, local_wins } => {
+                    let doc = local[uid.as_str()];
+                    let ours = self.sync_record(doc)?;
+                    let theirs = remote_by_uid[uid.as_str()].clone();
+                    let (winner, loser) = if local_wins {
+                        (ours, theirs)
+                    } else {
+                        (theirs, ours)
+                    };
+                    let copy = loser.conflicted_copy();
+
+                    self.store_synced_character(Some(doc), &winner)?;
+                    self.push_record(remote, &winner)?;
+                    self.store_synced_character(None, &copy)?;
+                    self.push_record(remote, &copy)?;
+                    summary.conflicts += 1;
+                }
+            }
+        }
+
+        *synced = self
+            .all_character_docs()?
+            .into_iter()
+            .map(|doc| (doc.uid, doc.updated_at))
+            .collect();
+        Ok(summary)
+    }
+
+    /// A stored character as sent to the sync server.
+    fn sync_record(&self, doc: &CharacterDocument) -> Result<SyncRecord, String> {
+        Ok(SyncRecord {
+            uid: doc.uid.clone(),
+            updated_at: doc.updated_at,
+            sheet: self.sheet_from_doc(doc.clone())?,
+        })
+    }
+
+    fn push_record(&self, remote: &Surreal<Any>, record: &SyncRecord) -> Result<(), String> {
+        let record = record.clone();
+        self.rt.block_on(async {
+            let _: Option<SyncRecord> = remote
+                .upsert(("character", record.uid.clone()))
+                .content(record)
+                .await
+                .map_err(|e| format!("Failed to upload character: {}", e))?;
+            Ok(())
+        })
+    }
+
+    /// Save a character from the sync server over `existing`, or as a new
+    /// character, keeping the server's uid and edit time.
+    fn store_synced_character(
+        &self,
+        existing: Option<&CharacterDocument>,
+        record: &SyncRecord,
+    ) -> Result<(), String> {
+        let (sid, legacy_sqlite_id) = match existing {
+            Some(doc) => (doc.sid, doc.legacy_sqlite_id),
+            None => (self.next_character_id()?, None),
+        };
+        let doc = self.to_doc(
+            sid,
+            &record.sheet,
+            legacy_sqlite_id,
+            record.uid.clone(),
+            record.updated_at,
+        )?;
+        self.write_doc(doc)
+    }
+}
+
+/// Connect and sign in to a cloud sync server.
+async fn connect_remote(config: &CloudSyncConfig) -> Result<Surreal<Any>, String> {
+    config.validate()?;
+    let remote = tokio::time::timeout(SYNC_CONNECT_TIMEOUT, any::connect(config.endpoint.as_str()))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}", config.endpoint))?
+        .map_err(|e| format!("Failed to connect to {}: {}", config.endpoint, e))?;
+
+    if !config.username.is_empty() {
+        // A database user is the usual setup; fall back to a root user
+        let database_user = remote
+            .signin(DatabaseAuth {
+                namespace: &config.namespace,
+                database: &config.database,
+                username: &config.username,
+                password: &config.password,
+            })
+            .await;
+        if database_user.is_err() {
+            remote
+                .signin(Root {
+                    username: &config.username,
+                    password: &config.password,
+                })
+                .await
+                .map_err(|e| format!("Failed to sign in to {}: {}", config.endpoint, e))?;
+        }
+    }
+
+    remote
+        .use_ns(config.namespace.as_str())
+        .use_db(config.database.as_str())
+        .await
+        .map_err(|e| format!("Failed to select the sync namespace/database: {}", e))?;
+    Ok(remote)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::dice3d::types::character::{Attributes, CharacterInfo, Combat};
+    #[cfg(feature = "gui")]
     use crate::dice3d::types::settings::{AppSettings, ColorSetting};
+    use crate::dice3d::types::DiceType;
 
     fn create_test_sheet(name: &str) -> CharacterSheet {
         CharacterSheet {
@@ -628,6 +1361,53 @@ mod tests {
         }
     }
 
+    fn notes(sheet: &CharacterSheet) -> Option<&str> {
+        sheet.custom_basic_info.get("Notes").map(String::as_str)
+    }
+
+    #[test]
+    fn test_encryption_enable_lock_unlock_disable() {
+        let db = CharacterDatabase::open_in_memory().unwrap();
+        let mut sheet = create_test_sheet("Vex");
+        sheet
+            .custom_basic_info
+            .insert("Notes".to_string(), "The duke is the lich".to_string());
+        let id = db.create_character(&sheet).unwrap();
+
+        db.enable_encryption("correct horse").unwrap();
+        assert!(db.encryption_enabled());
+        assert!(!db.is_locked());
+        assert_eq!(
+            notes(&db.load_character(id).unwrap()),
+            Some("The duke is the lich")
+        );
+
+        // Stored document only keeps the listing fields in the clear
+        let docs = db.all_character_docs().unwrap();
+        assert!(docs[0].sealed_sheet.is_some());
+        assert!(docs[0].sheet.custom_basic_info.is_empty());
+        assert_eq!(db.list_characters().unwrap()[0].name, "Vex");
+
+        db.lock();
+        assert!(db.is_locked());
+        assert!(db.load_character(id).is_err());
+        assert!(db.create_character(&create_test_sheet("Other")).is_err());
+        assert!(db.unlock("wrong horse").is_err());
+        db.unlock("correct horse").unwrap();
+        assert_eq!(
+            notes(&db.load_character(id).unwrap()),
+            Some("The duke is the lich")
+        );
+
+        db.disable_encryption().unwrap();
+        assert!(!db.encryption_enabled());
+        assert!(db.all_character_docs().unwrap()[0].sealed_sheet.is_none());
+        assert_eq!(
+            notes(&db.load_character(id).unwrap()),
+            Some("The duke is the lich")
+        );
+    }
+
     #[test]
     fn test_create_and_load_character() {
         let db = CharacterDatabase::open_in_memory().unwrap();
@@ -680,6 +1460,165 @@ mod tests {
         assert_eq!(db.character_count().unwrap(), 0);
     }
 
+    #[test]
+    fn test_record_and_load_rolls() {
+        let db = CharacterDatabase::open_in_memory().unwrap();
+        assert!(db.load_rolls().unwrap().is_empty());
+
+        let mut first = RollRecord::new("Thorin", "Perception", vec![(DiceType::D20, 17)], 20);
+        first.rolled_at = 100;
+        let second = RollRecord::new("", "", vec![(DiceType::Custom(100), 42)], 42);
+        db.record_roll(&second).unwrap();
+        db.record_roll(&first).unwrap();
+
+        assert_eq!(db.load_rolls().unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_migrations_upgrade_old_records() {
+        let db = CharacterDatabase::open_in_memory().unwrap();
+        let mut character = serde_json::to_value(CharacterDocument {
+            sid: 1,
+            legacy_sqlite_id: None,
+            uid: String::new(),
+            updated_at: 0,
+            name: "Thorin".to_string(),
+            class: "Fighter".to_string(),
+            race: "Human".to_string(),
+            level: 1,
+            sheet: create_test_sheet("Thorin"),
+            sealed_sheet: None,
+        })
+        .unwrap();
+        character.as_object_mut().unwrap().remove("uid");
+        db.write_raw_record("character", &serde_json::json!(1), &character)
+            .unwrap();
+        db.write_raw_record(
+            "setting",
+            &serde_json::json!("volume"),
+            &serde_json::json!({ "level": 3 }),
+        )
+        .unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), 0);
+        assert_eq!(db.run_migrations().unwrap(), 2);
+        assert_eq!(db.schema_version().unwrap(), latest_schema_version());
+        // Nothing left to upgrade
+        assert_eq!(db.run_migrations().unwrap(), 0);
+
+        let characters = db.raw_records("character").unwrap();
+        assert_eq!(characters[0].record["uid"].as_str().unwrap().len(), 32);
+        let settings = db.raw_records("setting").unwrap();
+        let volume = settings
+            .iter()
+            .find(|raw| raw.key == serde_json::json!("volume"))
+            .unwrap();
+        assert_eq!(volume.record["value"], serde_json::json!("{\"level\":3}"));
+    }
+
+    #[test]
+    fn test_doctor_repairs_malformed_records() {
+        let db = CharacterDatabase::open_in_memory().unwrap();
+        let sheet = serde_json::to_value(create_test_sheet("Thorin")).unwrap();
+        // Missing its listing fields: repaired from the sheet
+        db.write_raw_record(
+            "character",
+            &serde_json::json!(1),
+            &serde_json::json!({ "sheet": sheet }),
+        )
+        .unwrap();
+        // Unreadable sheet: quarantined
+        db.write_raw_record(
+            "character",
+            &serde_json::json!(2),
+            &serde_json::json!({
+                "sid": 2, "name": "Broken", "class": "", "race": "", "level": 1,
+                "sheet": "not a sheet",
+            }),
+        )
+        .unwrap();
+        db.write_raw_record(
+            "roll",
+            &serde_json::json!("bad"),
+            &serde_json::json!({ "character": 5 }),
+        )
+        .unwrap();
+        db.write_raw_record(
+            "setting",
+            &serde_json::json!("old"),
+            &serde_json::json!({ "enabled": true }),
+        )
+        .unwrap();
+
+        let report = db.doctor(false).unwrap();
+        assert!(!report.is_healthy());
+        let fixes: Vec<(&str, &str, bool)> = report
+            .findings
+            .iter()
+            .map(|f| {
+                let rewrite = matches!(f.fix, RecordFix::Rewrite(_));
+                (f.table.as_str(), f.key.as_str(), rewrite)
+            })
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                ("character", "1", true),
+                ("character", "2", false),
+                ("setting", "old", true),
+                ("roll", "bad", false),
+            ]
+        );
+        // Only reported
+        assert!(db.load_rolls().is_err());
+
+        let report = db.doctor(true).unwrap();
+        assert!(report.repaired);
+        assert_eq!(report.findings.len(), 4);
+
+        let report = db.doctor(false).unwrap();
+        assert!(report.is_healthy());
+        assert_eq!(db.load_character(1).unwrap().character.name, "Thorin");
+        assert_eq!(db.list_characters().unwrap().len(), 1);
+        assert!(db.load_rolls().unwrap().is_empty());
+        assert_eq!(db.raw_records(QUARANTINE_TABLE).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_doctor_checks_sealed_sheets() {
+        let db = CharacterDatabase::open_in_memory().unwrap();
+        db.create_character(&create_test_sheet("Vex")).unwrap();
+        db.enable_encryption("correct horse").unwrap();
+
+        // Same document, but its sealed sheet doesn't decode
+        let mut doc = db.all_character_docs().unwrap().remove(0);
+        doc.sid = 2;
+        doc.sealed_sheet = Some(db.unlocked_key().unwrap().encrypt("not a sheet").unwrap());
+        db.write_raw_record(
+            "character",
+            &serde_json::json!(2),
+            &serde_json::to_value(&doc).unwrap(),
+        )
+        .unwrap();
+
+        db.lock();
+        let report = db.doctor(false).unwrap();
+        assert!(report.findings.is_empty());
+        assert_eq!(report.locked, vec!["1".to_string(), "2".to_string()]);
+
+        db.unlock("correct horse").unwrap();
+        let report = db.doctor(true).unwrap();
+        assert!(report.locked.is_empty());
+        let found: Vec<(&str, bool)> = report
+            .findings
+            .iter()
+            .map(|f| (f.key.as_str(), f.fix == RecordFix::Quarantine))
+            .collect();
+        assert_eq!(found, vec![("2", true)]);
+        assert_eq!(db.list_characters().unwrap().len(), 1);
+        assert!(db.doctor(false).unwrap().is_healthy());
+    }
+
     #[test]
     fn test_name_exists() {
         let db = CharacterDatabase::open_in_memory().unwrap();
@@ -710,6 +1649,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "gui")]
     fn test_settings_round_trip_includes_background_color() {
         fn approx_eq(a: f32, b: f32) -> bool {
             (a - b).abs() < 1e-6
@@ -749,6 +1689,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "gui")]
     fn test_settings_persist_to_disk_round_trip() {
         // Use a unique folder under the OS temp dir.
         let ts = SystemTime::now()
@@ -785,4 +1726,75 @@ mod tests {
         // Best-effort cleanup.
         let _ = std::fs::remove_dir_all(&path);
     }
+
+    #[test]
+    fn test_sync_between_two_machines() {
+        let home = CharacterDatabase::open_in_memory().unwrap();
+        let work = CharacterDatabase::open_in_memory().unwrap();
+        let remote = home.rt.block_on(async {
+            let remote = any::connect("mem://").await.unwrap();
+            remote.use_ns("sync").use_db("sync").await.unwrap();
+            remote
+        });
+        // Keep edits and syncs on distinct milliseconds
+        let sync = |db: &CharacterDatabase, synced: &mut HashMap<String, u64>| {
+            std::thread::sleep(Duration::from_millis(5));
+            let summary = db.sync_characters_with(&remote, synced).unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+            summary
+        };
+        let (mut home_synced, mut work_synced) = (HashMap::new(), HashMap::new());
+
+        let id = home.create_character(&create_test_sheet("Vex")).unwrap();
+        assert_eq!(sync(&home, &mut home_synced).pushed, 1);
+        assert_eq!(sync(&work, &mut work_synced).pulled, 1);
+        let work_id = work.list_characters().unwrap()[0].id;
+
+        // An edit on one machine reaches the other
+        let mut sheet = work.load_character(work_id).unwrap();
+        sheet
+            .custom_basic_info
+            .insert("Notes".to_string(), "Owes the guild".to_string());
+        work.update_character(work_id, &sheet).unwrap();
+        assert_eq!(sync(&work, &mut work_synced).pushed, 1);
+        assert_eq!(sync(&home, &mut home_synced).pulled, 1);
+        assert_eq!(
+            notes(&home.load_character(id).unwrap()),
+            Some("Owes the guild")
+        );
+
+        // Edited on both: the newer edit wins, the older is kept as a copy
+        sheet
+            .custom_basic_info
+            .insert("Notes".to_string(), "Home notes".to_string());
+        home.update_character(id, &sheet).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        sheet
+            .custom_basic_info
+            .insert("Notes".to_string(), "Work notes".to_string());
+        work.update_character(work_id, &sheet).unwrap();
+        assert_eq!(sync(&home, &mut home_synced).pushed, 1);
+        assert_eq!(sync(&work, &mut work_synced).conflicts, 1);
+        assert_eq!(sync(&home, &mut home_synced).pulled, 2);
+
+        let names: Vec<String> = home
+            .list_characters()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["Vex", "Vex (conflicted copy)"]);
+        assert_eq!(notes(&home.load_character(id).unwrap()), Some("Work notes"));
+        let copy_id = home.list_characters().unwrap()[1].id;
+        assert_eq!(
+            notes(&home.load_character(copy_id).unwrap()),
+            Some("Home notes")
+        );
+
+        // A deletion on one machine reaches the other
+        home.delete_character(copy_id).unwrap();
+        assert_eq!(sync(&home, &mut home_synced).deleted, 1);
+        assert_eq!(sync(&work, &mut work_synced).deleted, 1);
+        assert_eq!(work.character_count().unwrap(), 1);
+    }
 }
@@ -0,0 +1,297 @@
+//! Versioned migrations of the database records
+//!
+//! The database remembers the schema version its records were last upgraded
+//! to (the `schema_version` setting). When a build changes the shape of a
+//! character, setting or roll record, it adds a [`Migration`] to
+//! [`MIGRATIONS`] that upgrades one stored record as raw JSON; on opening,
+//! the database runs the migrations newer than its version over every record
+//! of their table and saves the new version, so old records read fine
+//! instead of failing to deserialize. Migrations must leave records that are
+//! already upgraded as they are.
+//!
+//! `dndgamerolls db doctor` checks every record against what this build
+//! reads and, with `--repair`, rewrites the records it can fix and moves the
+//! rest to the `quarantine` table so they stop breaking the rest.
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::cloud_sync::new_character_uid;
+
+/// `setting` key holding the schema version of the stored records.
+pub const SCHEMA_VERSION_SETTING_KEY: &str = "schema_version";
+
+/// Table the records `db doctor` can't repair are moved to.
+pub const QUARANTINE_TABLE: &str = "quarantine";
+
+/// One upgrade of the records of a table
+pub struct Migration {
+    /// Schema version once this migration has run
+    pub version: u32,
+    pub description: &'static str,
+    /// Table whose records it upgrades
+    pub table: &'static str,
+    /// Upgrade one record in place; returns whether it changed
+    pub upgrade: fn(&mut JsonValue) -> bool,
+}
+
+/// Every migration, oldest first.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Store settings saved as plain records as a JSON string under `value`",
+        table: "setting",
+        upgrade: wrap_legacy_setting,
+    },
+    Migration {
+        version: 2,
+        description: "Give characters saved before cloud sync a sync uid and edit time",
+        table: "character",
+        upgrade: add_character_sync_fields,
+    },
+];
+
+/// The schema version records are at once every migration has run.
+pub fn latest_schema_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// The migrations a database at `version` still needs, oldest first.
+pub fn pending_migrations(version: u32) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(move |migration| migration.version > version)
+}
+
+/// The tables with records to upgrade from `version`, each listed once.
+pub fn tables_to_migrate(version: u32) -> Vec<&'static str> {
+    let mut tables = Vec::new();
+    for migration in pending_migrations(version) {
+        if !tables.contains(&migration.table) {
+            tables.push(migration.table);
+        }
+    }
+    tables
+}
+
+/// Upgrade a record of `table` from `version` to the latest; returns whether
+/// it changed.
+pub fn migrate_record(table: &str, record: &mut JsonValue, version: u32) -> bool {
+    let mut changed = false;
+    for migration in pending_migrations(version).filter(|m| m.table == table) {
+        changed |= (migration.upgrade)(record);
+    }
+    changed
+}
+
+/// Old builds stored a setting's fields as the record itself; settings are
+/// now a JSON string under `value`.
+fn wrap_legacy_setting(record: &mut JsonValue) -> bool {
+    if record.get("value").is_some_and(JsonValue::is_string) {
+        return false;
+    }
+    let json = record.to_string();
+    *record = JsonValue::Object(Map::from_iter([(
+        "value".to_string(),
+        JsonValue::String(json),
+    )]));
+    true
+}
+
+fn add_character_sync_fields(record: &mut JsonValue) -> bool {
+    let Some(fields) = record.as_object_mut() else {
+        return false;
+    };
+    let mut changed = false;
+    if !fields
+        .get("uid")
+        .and_then(JsonValue::as_str)
+        .is_some_and(|uid| !uid.is_empty())
+    {
+        fields.insert("uid".to_string(), new_character_uid().into());
+        changed = true;
+    }
+    if !fields.get("updated_at").is_some_and(JsonValue::is_u64) {
+        fields.insert("updated_at".to_string(), 0.into());
+        changed = true;
+    }
+    changed
+}
+
+/// A record as stored, with the key of its record id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawRecord {
+    pub key: JsonValue,
+    pub record: JsonValue,
+}
+
+impl RawRecord {
+    /// The key as shown to the user: `3` or `app_settings`.
+    pub fn key_text(&self) -> String {
+        match &self.key {
+            JsonValue::String(key) => key.clone(),
+            key => key.to_string(),
+        }
+    }
+}
+
+/// Fill in what a character record needs around its sheet: the `sid` from
+/// the record id, the listing fields from the sheet and the sync fields.
+/// Returns whether it changed.
+pub fn repair_character_record(key: &JsonValue, record: &mut JsonValue) -> bool {
+    let mut changed = add_character_sync_fields(record);
+    let Some(fields) = record.as_object_mut() else {
+        return changed;
+    };
+
+    if let Some(sid) = key.as_i64() {
+        if fields.get("sid").and_then(JsonValue::as_i64) != Some(sid) {
+            fields.insert("sid".to_string(), sid.into());
+            changed = true;
+        }
+    }
+
+    let info = fields
+        .get("sheet")
+        .and_then(|sheet| sheet.get("character"))
+        .cloned()
+        .unwrap_or(JsonValue::Null);
+    for field in ["name", "class", "race"] {
+        if !fields.get(field).is_some_and(JsonValue::is_string) {
+            let value = info
+                .get(field)
+                .filter(|value| value.is_string())
+                .cloned()
+                .unwrap_or_else(|| JsonValue::String(String::new()));
+            fields.insert(field.to_string(), value);
+            changed = true;
+        }
+    }
+    if !fields.get("level").is_some_and(JsonValue::is_i64) {
+        let level = info.get("level").and_then(JsonValue::as_i64).unwrap_or(1);
+        fields.insert("level".to_string(), level.into());
+        changed = true;
+    }
+    changed
+}
+
+/// What `db doctor` does with a malformed record
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordFix {
+    /// Save this repaired record in its place
+    Rewrite(JsonValue),
+    /// Move it to the quarantine table
+    Quarantine,
+}
+
+/// Why a setting record can't be read, and how to fix it.
+pub fn diagnose_setting_record(record: &JsonValue) -> Option<(String, RecordFix)> {
+    match record.get("value") {
+        Some(JsonValue::String(json)) => match serde_json::from_str::<JsonValue>(json) {
+            Ok(_) => None,
+            Err(e) => Some((format!("value isn't JSON: {}", e), RecordFix::Quarantine)),
+        },
+        _ => {
+            let mut repaired = record.clone();
+            wrap_legacy_setting(&mut repaired);
+            Some((
+                "saved in the old settings format".to_string(),
+                RecordFix::Rewrite(repaired),
+            ))
+        }
+    }
+}
+
+/// A malformed record found by `db doctor`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoctorFinding {
+    pub table: String,
+    pub key: String,
+    pub problem: String,
+    pub fix: RecordFix,
+}
+
+/// What `db doctor` found, and whether it repaired it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DoctorReport {
+    /// Schema version the records were at
+    pub schema_version: u32,
+    /// Records upgraded by pending migrations (with `--repair`)
+    pub migrated: usize,
+    pub findings: Vec<DoctorFinding>,
+    /// Keys of encrypted characters whose sheets weren't checked because
+    /// the database is locked
+    pub locked: Vec<String>,
+    pub repaired: bool,
+}
+
+impl DoctorReport {
+    /// Nothing to upgrade or repair.
+    pub fn is_healthy(&self) -> bool {
+        self.schema_version >= latest_schema_version() && self.findings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrations_are_in_order() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(latest_schema_version(), *versions.last().unwrap());
+        assert_eq!(tables_to_migrate(0), vec!["setting", "character"]);
+        assert!(tables_to_migrate(latest_schema_version()).is_empty());
+    }
+
+    #[test]
+    fn test_migrate_old_records() {
+        let mut setting = json!({ "theme": "dark", "volume": 0.5 });
+        assert!(migrate_record("setting", &mut setting, 0));
+        let value: JsonValue = serde_json::from_str(setting["value"].as_str().unwrap()).unwrap();
+        assert_eq!(value, json!({ "theme": "dark", "volume": 0.5 }));
+        // Already upgraded records are left alone
+        assert!(!migrate_record("setting", &mut setting, 0));
+
+        let mut character = json!({ "sid": 1, "name": "Thorin" });
+        assert!(migrate_record("character", &mut character, 1));
+        assert_eq!(character["uid"].as_str().unwrap().len(), 32);
+        assert_eq!(character["updated_at"], json!(0));
+        assert!(!migrate_record("character", &mut character, 1));
+
+        let mut character = json!({ "sid": 2, "name": "Mira" });
+        assert!(!migrate_record("character", &mut character, 2));
+    }
+
+    #[test]
+    fn test_repair_character_record() {
+        let mut record = json!({
+            "sheet": { "character": { "name": "Thorin", "race": "Dwarf", "level": 5 } },
+            "name": 7,
+        });
+        assert!(repair_character_record(&json!(3), &mut record));
+        assert_eq!(record["sid"], json!(3));
+        assert_eq!(record["name"], json!("Thorin"));
+        assert_eq!(record["race"], json!("Dwarf"));
+        assert_eq!(record["class"], json!(""));
+        assert_eq!(record["level"], json!(5));
+        assert!(!repair_character_record(&json!(3), &mut record));
+    }
+
+    #[test]
+    fn test_diagnose_setting_record() {
+        assert_eq!(diagnose_setting_record(&json!({ "value": "true" })), None);
+        assert!(matches!(
+            diagnose_setting_record(&json!({ "value": "{not json" })),
+            Some((_, RecordFix::Quarantine))
+        ));
+        assert_eq!(
+            diagnose_setting_record(&json!({ "enabled": true })),
+            Some((
+                "saved in the old settings format".to_string(),
+                RecordFix::Rewrite(json!({ "value": "{\"enabled\":true}" }))
+            ))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Checks against a DC and contested checks
+//!
+//! A check succeeds when its total meets or beats the DC; a natural 20 or 1
+//! makes no difference, as the Player's Handbook has it for ability checks
+//! and saving throws. In a contest the higher total wins, and a tie leaves
+//! the situation as it was.
+
+/// A check's total compared with a DC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DcResult {
+    pub total: i32,
+    pub dc: i32,
+}
+
+impl DcResult {
+    pub fn new(total: i32, dc: i32) -> Self {
+        Self { total, dc }
+    }
+
+    /// Meeting the DC succeeds
+    pub fn success(&self) -> bool {
+        self.total >= self.dc
+    }
+
+    /// How far the total is over (positive) or under (negative) the DC
+    pub fn margin(&self) -> i32 {
+        self.total - self.dc
+    }
+
+    /// "SUCCESS" or "FAILURE"
+    pub fn verdict(&self) -> &'static str {
+        if self.success() {
+            "SUCCESS"
+        } else {
+            "FAILURE"
+        }
+    }
+
+    /// e.g. "SUCCESS by 3 (DC 15)", "FAILURE by 2 (DC 15)"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} by {} (DC {})",
+            self.verdict(),
+            self.margin().abs(),
+            self.dc
+        )
+    }
+}
+
+/// Who wins a contested check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContestOutcome {
+    /// The first total is higher
+    Wins,
+    /// The second total is higher
+    Loses,
+    /// A tie: the situation stays the same
+    Tie,
+}
+
+impl ContestOutcome {
+    /// Compare the totals of the two sides of a contest.
+    pub fn of(total: i32, opposing_total: i32) -> Self {
+        match total.cmp(&opposing_total) {
+            std::cmp::Ordering::Greater => Self::Wins,
+            std::cmp::Ordering::Less => Self::Loses,
+            std::cmp::Ordering::Equal => Self::Tie,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dc_result() {
+        let result = DcResult::new(18, 15);
+        assert!(result.success());
+        assert_eq!(result.describe(), "SUCCESS by 3 (DC 15)");
+
+        // Meeting the DC is enough
+        assert_eq!(DcResult::new(15, 15).describe(), "SUCCESS by 0 (DC 15)");
+
+        let result = DcResult::new(13, 15);
+        assert!(!result.success());
+        assert_eq!(result.margin(), -2);
+        assert_eq!(result.describe(), "FAILURE by 2 (DC 15)");
+    }
+
+    #[test]
+    fn test_contest() {
+        assert_eq!(ContestOutcome::of(14, 9), ContestOutcome::Wins);
+        assert_eq!(ContestOutcome::of(9, 14), ContestOutcome::Loses);
+        assert_eq!(ContestOutcome::of(12, 12), ContestOutcome::Tie);
+    }
+}
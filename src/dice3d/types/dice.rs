@@ -1,34 +1,97 @@
 //! Dice-related types and components
 //!
-//! This module contains all types related to dice: DiceType, Die component,
-//! DiceBox, DiceResults, DiceConfig, and RollState.
+//! This module contains all types related to dice: the Die component,
+//! DiceBox, DiceResults, DiceConfig, and RollState. `DiceType` comes from
+//! `dndgamerolls-core`; `DiceTypeExt` adds how each die looks and handles in
+//! the 3D view.
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+use tracing::warn;
+
+pub use dndgamerolls_core::dice::{
+    coin_side, fudge_symbol, percentile_result, DiceType, MAX_CUSTOM_DIE_SIDES,
+};
+pub use dndgamerolls_core::engine::{RollEngine, RollRequest, RollResult};
+
+use super::{ConditionEffect, DiceExpr, ExplosionResult};
 
 /// Component attached to each die entity
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct Die {
     pub die_type: DiceType,
     pub face_normals: Vec<(Vec3, u32)>,
 }
 
+/// A die thrown again because an earlier die exploded
+#[cfg(feature = "gui")]
+#[derive(Component)]
+pub struct ExplodingDie {
+    /// Which die of the expression exploded
+    pub chain: usize,
+    /// Position among that die's explosions
+    pub roll: usize,
+}
+
+/// The face a die was told to land on, when results are picked before the
+/// throw ("fair but pretty" mode) and the physics is steered onto them
+#[cfg(feature = "gui")]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SteeredDie {
+    pub target: u32,
+}
+
+/// A die replaying another player's throw (`--join`). It is not a [`Die`],
+/// so it never counts toward the local roll; it is steered onto the face
+/// the other player rolled.
+#[cfg(feature = "gui")]
+#[derive(Component, Clone, Debug)]
+pub struct RemoteDie {
+    pub face_normals: Vec<(Vec3, u32)>,
+    pub target: u32,
+    /// The other player's dice color, when they sent one
+    pub color: Option<Color>,
+}
+
+/// One of the two d10s a d100 is thrown as. The tens die shows 00-90 and
+/// is read together with its units die (0-9) into one 1-100 result.
+#[cfg(feature = "gui")]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PercentileDie {
+    Tens { units: Entity },
+    Units,
+}
+
+/// Exploding dice waiting to be thrown into the box
+#[cfg(feature = "gui")]
+#[derive(Resource, Default)]
+pub struct PendingExplosionThrows {
+    pub dice: Vec<(ExplodingDie, DiceType)>,
+}
+
 /// Marker component for the dice box/container
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceBox;
 
 /// Marker component for the dice container floor collider.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceBoxFloorCollider;
 
 /// Marker component for dice container wall segments.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceBoxWall;
 
 /// Marker component for the dice container ceiling collider.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceBoxCeiling;
 
 /// Marker component for the container's visual root entity (spawned via `SceneRoot`).
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerVisualRoot;
 
@@ -36,44 +99,53 @@ pub struct DiceContainerVisualRoot;
 ///
 /// This is used to ensure other systems (spawn points, colliders) only run once
 /// the visual model has been moved into its final position.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerCentered;
 
 /// Marker component for any mesh/material entity that is part of the container visual.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerVisualPart;
 
 /// Marker component for glTF nodes whose name starts with `COLLIDER_`.
 ///
 /// These meshes are authored in Blender as guides and should not be rendered.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerColliderGuide;
 
 /// Marker component for Rapier colliders generated from glTF collider guides.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerGeneratedCollider;
 
 /// Marker component for generated colliders that came from voxelizing the glTF render meshes.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerVoxelCollider;
 
 /// Marker for the legacy/procedural container colliders (floor/walls/ceiling).
 ///
 /// When glTF collider guides are present and processed, these are despawned to avoid double-collisions.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerProceduralCollider;
 
 /// Marker component for container visual mesh entities that have had their material overridden
 /// to the game's crystal material.
+#[cfg(feature = "gui")]
 #[derive(Component)]
 pub struct DiceContainerCrystalMaterialApplied;
 
 /// Stores the original emissive color for a container visual part, so hover highlighting can be reverted.
+#[cfg(feature = "gui")]
 #[derive(Component, Clone, Copy)]
 pub struct ContainerOriginalEmissive(pub bevy::color::LinearRgba);
 
 /// Visual/physics style for the dice container.
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub enum DiceContainerStyle {
     #[default]
     Box,
@@ -81,46 +153,44 @@ pub enum DiceContainerStyle {
 }
 
 /// Materials used for spawning the dice container visuals.
+#[cfg(feature = "gui")]
 #[derive(Resource, Clone)]
 pub struct DiceContainerMaterials {
     pub crystal: Handle<StandardMaterial>,
 }
 
-/// All supported dice types
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
-pub enum DiceType {
-    D4,
-    D6,
-    D8,
-    D10,
-    D12,
-    D20,
-}
+/// How a die looks and handles in the 3D view. [`DiceType`] itself lives in
+/// `dndgamerolls-core`, shared with the command lines.
+pub trait DiceTypeExt {
+    #[cfg(feature = "gui")]
+    fn color(&self) -> Color;
 
-impl DiceType {
-    pub fn max_value(&self) -> u32 {
-        match self {
-            DiceType::D4 => 4,
-            DiceType::D6 => 6,
-            DiceType::D8 => 8,
-            DiceType::D10 => 10,
-            DiceType::D12 => 12,
-            DiceType::D20 => 20,
-        }
-    }
+    /// Get the physical density of the die for physics simulation.
+    /// Larger dice are heavier, affecting how they roll and bounce.
+    /// Density is based on realistic proportions where D20 is heaviest.
+    fn density(&self) -> f32;
 
-    pub fn name(&self) -> &'static str {
-        match self {
-            DiceType::D4 => "D4",
-            DiceType::D6 => "D6",
-            DiceType::D8 => "D8",
-            DiceType::D10 => "D10",
-            DiceType::D12 => "D12",
-            DiceType::D20 => "D20",
-        }
-    }
+    /// Get the scale factor for the die mesh.
+    /// This affects both visual size and collision volume.
+    fn scale(&self) -> f32;
+
+    /// Approximate base mesh radius (distance from origin to the furthest vertex)
+    /// for each die mesh at `Transform::scale = 1.0`.
+    ///
+    /// This is used to normalize sizes so that equal user scale values produce
+    /// equal visual sizes across different dice shapes.
+    fn mesh_base_radius(&self) -> f32;
+
+    /// Scale correction to normalize mesh sizes to a common reference.
+    ///
+    /// With this applied, two dice with the same user scale should appear
+    /// roughly the same overall size.
+    fn uniform_size_scale_factor(&self) -> f32;
+}
 
-    pub fn color(&self) -> Color {
+impl DiceTypeExt for DiceType {
+    #[cfg(feature = "gui")]
+    fn color(&self) -> Color {
         // Slightly translucent crystal-like colors
         match self {
             DiceType::D4 => Color::srgba(0.3, 0.4, 0.9, 0.92), // Blue crystal
@@ -129,45 +199,40 @@ impl DiceType {
             DiceType::D10 => Color::srgba(0.95, 0.95, 0.95, 0.92), // White/clear crystal
             DiceType::D12 => Color::srgba(0.95, 0.5, 0.1, 0.92), // Orange crystal
             DiceType::D20 => Color::srgba(0.95, 0.85, 0.2, 0.92), // Yellow crystal
-        }
-    }
-
-    pub fn parse(s: &str) -> Option<DiceType> {
-        match s.to_lowercase().as_str() {
-            "d4" => Some(DiceType::D4),
-            "d6" => Some(DiceType::D6),
-            "d8" => Some(DiceType::D8),
-            "d10" => Some(DiceType::D10),
-            "d12" => Some(DiceType::D12),
-            "d20" => Some(DiceType::D20),
-            _ => None,
+            DiceType::Fudge => Color::srgba(0.9, 0.88, 0.78, 0.95), // Ivory
+            DiceType::Custom(2) => Color::srgba(0.85, 0.6, 0.25, 0.97), // Brass coin
+            DiceType::Custom(_) => Color::srgba(0.2, 0.75, 0.6, 0.92), // Teal crystal
         }
     }
 
     /// Get the physical density of the die for physics simulation.
     /// Larger dice are heavier, affecting how they roll and bounce.
     /// Density is based on realistic proportions where D20 is heaviest.
-    pub fn density(&self) -> f32 {
+    fn density(&self) -> f32 {
         match self {
-            DiceType::D4 => 1.0,  // Lightest - small tetrahedron
-            DiceType::D6 => 1.5,  // Standard cube
-            DiceType::D8 => 1.8,  // Octahedron
-            DiceType::D10 => 2.0, // Medium
-            DiceType::D12 => 2.5, // Larger dodecahedron
-            DiceType::D20 => 3.0, // Heaviest - large icosahedron
+            DiceType::D4 => 1.0,                   // Lightest - small tetrahedron
+            DiceType::D6 | DiceType::Fudge => 1.5, // Standard cube
+            DiceType::D8 => 1.8,                   // Octahedron
+            DiceType::D10 => 2.0,                  // Medium
+            DiceType::D12 => 2.5,                  // Larger dodecahedron
+            DiceType::D20 => 3.0,                  // Heaviest - large icosahedron
+            DiceType::Custom(2) => 4.0,            // Thin metal coin
+            DiceType::Custom(_) => 3.0,
         }
     }
 
     /// Get the scale factor for the die mesh.
     /// This affects both visual size and collision volume.
-    pub fn scale(&self) -> f32 {
+    fn scale(&self) -> f32 {
         match self {
-            DiceType::D4 => 0.9,   // Smaller
-            DiceType::D6 => 1.0,   // Standard
-            DiceType::D8 => 1.0,   // Standard
-            DiceType::D10 => 1.05, // Slightly larger
-            DiceType::D12 => 1.1,  // Larger
-            DiceType::D20 => 1.2,  // Largest
+            DiceType::D4 => 0.9,                   // Smaller
+            DiceType::D6 | DiceType::Fudge => 1.0, // Standard
+            DiceType::D8 => 1.0,                   // Standard
+            DiceType::D10 => 1.05,                 // Slightly larger
+            DiceType::D12 => 1.1,                  // Larger
+            DiceType::D20 => 1.2,                  // Largest
+            DiceType::Custom(2) => 1.0,
+            DiceType::Custom(_) => 1.2,
         }
     }
 
@@ -176,12 +241,12 @@ impl DiceType {
     ///
     /// This is used to normalize sizes so that equal user scale values produce
     /// equal visual sizes across different dice shapes.
-    pub fn mesh_base_radius(&self) -> f32 {
+    fn mesh_base_radius(&self) -> f32 {
         match self {
             // d4.rs uses edge length a=0.5, giving radius ~= 0.306186.
             DiceType::D4 => 0.306_186,
             // d6.rs uses Cuboid::new(0.6,0.6,0.6) => vertex radius = sqrt(3)*(0.6/2).
-            DiceType::D6 => 0.519_615,
+            DiceType::D6 | DiceType::Fudge => 0.519_615,
             // d8.rs uses vertices at ±0.5 on axes.
             DiceType::D8 => 0.5,
             // d10.rs top is at y=0.45.
@@ -190,6 +255,10 @@ impl DiceType {
             DiceType::D12 => 0.303_108,
             // d20.rs uses s=0.175; vertices are length sqrt(1+phi^2)*s.
             DiceType::D20 => 0.332_870,
+            // coin.rs is a disc of radius 0.4, 0.08 thick.
+            DiceType::Custom(2) => 0.401_995,
+            // No mesh; matches the d20 so size settings stay consistent.
+            DiceType::Custom(_) => 0.332_870,
         }
     }
 
@@ -197,7 +266,7 @@ impl DiceType {
     ///
     /// With this applied, two dice with the same user scale should appear
     /// roughly the same overall size.
-    pub fn uniform_size_scale_factor(&self) -> f32 {
+    fn uniform_size_scale_factor(&self) -> f32 {
         // Most meshes use a `size = 0.5` convention; use that as the reference.
         const REFERENCE_RADIUS: f32 = 0.5;
         REFERENCE_RADIUS / self.mesh_base_radius()
@@ -205,13 +274,28 @@ impl DiceType {
 }
 
 /// Resource storing the results of dice rolls
-#[derive(Resource, Default)]
+#[derive(Default)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct DiceResults {
     pub results: Vec<(DiceType, u32)>,
+    /// Dice thrown again because they exploded (`1d6!`)
+    pub explosions: Vec<ExplosionResult>,
+    /// Indices into `results` in the order the dice came to rest
+    pub settle_order: Vec<usize>,
+}
+
+impl DiceResults {
+    /// Forget the previous roll.
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.explosions.clear();
+        self.settle_order.clear();
+    }
 }
 
 /// Resource tracking the current roll state
-#[derive(Resource, Default)]
+#[derive(Default)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct RollState {
     pub rolling: bool,
     pub settle_timer: f32,
@@ -219,12 +303,118 @@ pub struct RollState {
     pub roll_timer: f32,
 }
 
+/// Whether the physics is paused because the window is in the background
+/// (`AppSettings::pause_when_unfocused`); a paused roll doesn't time out.
+#[derive(Default)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct FocusPause {
+    pub paused: bool,
+}
+
 /// Configuration for which dice to spawn
-#[derive(Resource, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct DiceConfig {
     pub dice_to_roll: Vec<DiceType>,
     pub modifier: i32,
     pub modifier_name: String,
+    /// Expression the dice came from when it does more than add them up
+    /// (`2d6+3`, `1d20-1d4`); `None` totals the dice plainly.
+    pub expression: Option<DiceExpr>,
+}
+
+impl DiceConfig {
+    /// Dice that get a physics body, in roll order.
+    pub fn physical_dice(&self) -> Vec<DiceType> {
+        self.dice_to_roll
+            .iter()
+            .copied()
+            .filter(DiceType::is_physical)
+            .collect()
+    }
+
+    /// Roll the dice an expression needs, keeping the expression only when
+    /// a plain sum of the dice wouldn't give the same total.
+    pub fn from_expression(expression: &DiceExpr) -> (Vec<DiceType>, Option<DiceExpr>) {
+        let dice = expression.dice();
+        if expression.is_dice_sum() {
+            (dice, None)
+        } else {
+            (dice, Some(expression.clone()))
+        }
+    }
+
+    /// Roll a lone d20 twice and keep the higher (advantage) or lower
+    /// (disadvantage) die; both together cancel out. Other dice are left
+    /// as they are.
+    pub fn with_advantage(mut self, advantage: bool, disadvantage: bool) -> Self {
+        let lone_d20 = self.dice_to_roll == [DiceType::D20] && self.expression.is_none();
+        if advantage == disadvantage || !lone_d20 {
+            return self;
+        }
+        let keep = if advantage { "2d20kh1" } else { "2d20kl1" };
+        if let Ok(expression) = DiceExpr::parse(keep) {
+            (self.dice_to_roll, self.expression) = Self::from_expression(&expression);
+        }
+        self
+    }
+
+    /// Apply what the character's conditions do to this roll: advantage or
+    /// disadvantage on a lone d20, any penalty on the modifier, and a note on
+    /// the label.
+    pub fn with_condition_effect(self, effect: &ConditionEffect) -> Self {
+        self.with_advantage_and_conditions(false, effect)
+    }
+
+    /// [`Self::with_condition_effect`] with advantage from another source
+    /// too, such as a tool proficiency's synergy; disadvantage from the
+    /// conditions cancels it as usual.
+    pub fn with_advantage_and_conditions(self, advantage: bool, effect: &ConditionEffect) -> Self {
+        let mut config = self.with_advantage(
+            advantage || !effect.advantage.is_empty(),
+            !effect.disadvantage.is_empty(),
+        );
+        if !effect.is_empty() {
+            config.modifier += effect.penalty();
+            config.modifier_name = format!("{} [{}]", config.modifier_name, effect.describe());
+        }
+        config
+    }
+
+    /// Total of the rolled dice, before `modifier`.
+    pub fn dice_total(&self, results: &[(DiceType, u32)]) -> i32 {
+        self.dice_total_with_explosions(results, &[])
+    }
+
+    /// Total of the rolled dice and the explosions they set off, before
+    /// `modifier`.
+    pub fn dice_total_with_explosions(
+        &self,
+        results: &[(DiceType, u32)],
+        explosions: &[ExplosionResult],
+    ) -> i32 {
+        let plain_sum = || results.iter().map(|(d, v)| d.face_value(*v)).sum();
+        match &self.expression {
+            Some(expression) => expression
+                .roll_with_explosions(results, explosions)
+                .map(|roll| roll.total)
+                .map(|total| total as i32)
+                .unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    plain_sum()
+                }),
+            None => plain_sum(),
+        }
+    }
+
+    /// Dice rolled numerically once the physical dice settle.
+    pub fn virtual_dice(&self) -> Vec<DiceType> {
+        self.dice_to_roll
+            .iter()
+            .copied()
+            .filter(|d| !d.is_physical())
+            .collect()
+    }
 }
 
 impl Default for DiceConfig {
@@ -233,6 +423,7 @@ impl Default for DiceConfig {
             dice_to_roll: vec![DiceType::D20],
             modifier: 0,
             modifier_name: String::new(),
+            expression: None,
         }
     }
 }
@@ -242,30 +433,137 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_dice_type_max_value() {
-        assert_eq!(DiceType::D4.max_value(), 4);
-        assert_eq!(DiceType::D6.max_value(), 6);
-        assert_eq!(DiceType::D8.max_value(), 8);
-        assert_eq!(DiceType::D10.max_value(), 10);
-        assert_eq!(DiceType::D12.max_value(), 12);
-        assert_eq!(DiceType::D20.max_value(), 20);
+    fn test_dice_config_splits_virtual_dice() {
+        let config = DiceConfig {
+            dice_to_roll: vec![DiceType::D20, DiceType::Custom(3), DiceType::D6],
+            ..Default::default()
+        };
+        assert_eq!(config.physical_dice(), vec![DiceType::D20, DiceType::D6]);
+        assert_eq!(config.virtual_dice(), vec![DiceType::Custom(3)]);
+    }
+
+    #[test]
+    fn test_fudge_die() {
+        assert_eq!(DiceType::parse("dF"), Some(DiceType::Fudge));
+        assert_eq!(DiceType::parse("df"), Some(DiceType::Fudge));
+        assert!(DiceType::Fudge.is_physical());
+        assert_eq!(DiceType::Fudge.name(), "dF");
+        assert_eq!(
+            [1, 2, 3].map(|face| DiceType::Fudge.face_value(face)),
+            [-1, 0, 1]
+        );
+        assert_eq!(DiceType::Fudge.face_text(1), "-");
+        assert_eq!(DiceType::D6.face_value(4), 4);
+
+        let results = [
+            (DiceType::Fudge, 1),
+            (DiceType::Fudge, 1),
+            (DiceType::Fudge, 2),
+            (DiceType::Fudge, 3),
+        ];
+        assert_eq!(DiceConfig::default().dice_total(&results), -1);
+    }
+
+    #[test]
+    fn test_dice_config_expression_total() {
+        let expression = DiceExpr::parse("1d20-1d4+2").unwrap();
+        let (dice_to_roll, expression) = DiceConfig::from_expression(&expression);
+        let config = DiceConfig {
+            dice_to_roll,
+            expression,
+            ..Default::default()
+        };
+        let results = [(DiceType::D4, 3), (DiceType::D20, 15)];
+        assert_eq!(config.dice_total(&results), 14);
+
+        // Plain sums don't keep the expression
+        let (_, expression) = DiceConfig::from_expression(&DiceExpr::parse("2d6").unwrap());
+        assert!(expression.is_none());
+        assert_eq!(DiceConfig::default().dice_total(&results), 18);
+    }
+
+    #[test]
+    fn test_dice_config_with_advantage() {
+        let config = DiceConfig::default().with_advantage(false, true);
+        assert_eq!(config.dice_to_roll, vec![DiceType::D20, DiceType::D20]);
+        assert_eq!(
+            config.dice_total(&[(DiceType::D20, 4), (DiceType::D20, 17)]),
+            4
+        );
+        let config = DiceConfig::default().with_advantage(true, false);
+        assert_eq!(
+            config.dice_total(&[(DiceType::D20, 4), (DiceType::D20, 17)]),
+            17
+        );
+
+        assert_eq!(
+            DiceConfig::default()
+                .with_advantage(true, true)
+                .dice_to_roll,
+            vec![DiceType::D20]
+        );
+        let damage = DiceConfig {
+            dice_to_roll: vec![DiceType::D8],
+            ..Default::default()
+        };
+        assert!(damage.with_advantage(true, false).expression.is_none());
     }
 
     #[test]
-    fn test_dice_type_name() {
-        assert_eq!(DiceType::D4.name(), "D4");
-        assert_eq!(DiceType::D6.name(), "D6");
-        assert_eq!(DiceType::D20.name(), "D20");
+    fn test_dice_config_with_condition_effect() {
+        let effect = ConditionEffect {
+            disadvantage: vec!["Poisoned".to_string()],
+            ..Default::default()
+        };
+        let config = DiceConfig {
+            modifier_name: "Stealth Skill".to_string(),
+            ..Default::default()
+        }
+        .with_condition_effect(&effect);
+        assert_eq!(
+            config.modifier_name,
+            "Stealth Skill [Poisoned: disadvantage]"
+        );
+        assert_eq!(config.dice_to_roll.len(), 2);
+
+        let effect = ConditionEffect {
+            penalties: vec![("Exhaustion 1".to_string(), -2)],
+            ..Default::default()
+        };
+        let config = DiceConfig {
+            modifier: 5,
+            ..Default::default()
+        }
+        .with_condition_effect(&effect);
+        assert_eq!(config.modifier, 3);
+        assert_eq!(config.dice_to_roll, vec![DiceType::D20]);
+
+        let config = DiceConfig::default().with_condition_effect(&ConditionEffect::default());
+        assert!(config.modifier_name.is_empty());
     }
 
     #[test]
-    fn test_dice_type_parse() {
-        assert_eq!(DiceType::parse("d4"), Some(DiceType::D4));
-        assert_eq!(DiceType::parse("D4"), Some(DiceType::D4));
-        assert_eq!(DiceType::parse("d20"), Some(DiceType::D20));
-        assert_eq!(DiceType::parse("D20"), Some(DiceType::D20));
-        assert_eq!(DiceType::parse("invalid"), None);
-        assert_eq!(DiceType::parse("d100"), None);
+    fn test_dice_config_with_advantage_and_conditions() {
+        let config =
+            DiceConfig::default().with_advantage_and_conditions(true, &ConditionEffect::default());
+        assert_eq!(config.dice_to_roll, vec![DiceType::D20, DiceType::D20]);
+        assert!(config.modifier_name.is_empty());
+
+        // A tool's advantage and the poisoned disadvantage cancel out
+        let poisoned = ConditionEffect {
+            disadvantage: vec!["Poisoned".to_string()],
+            ..Default::default()
+        };
+        let config = DiceConfig {
+            modifier_name: "Sleight of Hand Skill".to_string(),
+            ..Default::default()
+        }
+        .with_advantage_and_conditions(true, &poisoned);
+        assert_eq!(config.dice_to_roll, vec![DiceType::D20]);
+        assert_eq!(
+            config.modifier_name,
+            "Sleight of Hand Skill [Poisoned: disadvantage]"
+        );
     }
 
     #[test]
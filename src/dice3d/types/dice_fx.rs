@@ -1,20 +1,5 @@
 use bevy::prelude::*;
 
-use super::DiceType;
-
-/// Fired when a dice roll has fully settled and face values have been determined.
-#[derive(Message, Clone, Debug, Default)]
-pub struct DiceRollCompletedEvent {
-    pub results: Vec<DieRollOutcome>,
-}
-
-#[derive(Clone, Copy, Debug)]
-pub struct DieRollOutcome {
-    pub entity: Entity,
-    pub die_type: DiceType,
-    pub value: u32,
-}
-
 /// Stores the last settled roll value for a die.
 #[derive(Component, Clone, Copy, Debug)]
 pub struct DieLastRoll {
@@ -0,0 +1,90 @@
+//! Gathering settled dice
+//!
+//! With "Gather dice" on, the dice of a roll that has been read slide into
+//! rows at the front of the container, lowest value on the left, so the
+//! result is easy to read or photograph. They stay there until the next
+//! roll picks them up again.
+
+use bevy::prelude::*;
+
+use super::DiceContainerStyle;
+use crate::dice3d::throw_control::{BOX_HALF_EXTENT, CUP_RADIUS};
+
+/// Seconds the dice take to slide into their row
+pub const GATHER_DURATION: f32 = 0.6;
+
+/// Distance between gathered dice at dice scale 1
+pub const GATHER_SPACING: f32 = 0.7;
+
+/// Keep gathered dice this far from the walls
+const WALL_MARGIN: f32 = 0.4;
+
+/// A die moved into the gathered row; physics leaves it alone (it is
+/// kinematic) until the next roll throws it again.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GatheredDie;
+
+/// Where gathered dice go: rows across the front of a round floor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GatherLayout {
+    /// Radius of the floor the rows must fit on
+    pub radius: f32,
+    pub spacing: f32,
+}
+
+impl GatherLayout {
+    /// The floor of a container, treating the box as the circle inside it
+    /// so rows fit whichever side the camera looks from.
+    pub fn for_style(style: DiceContainerStyle, spacing: f32) -> Self {
+        let half_extent = match style {
+            DiceContainerStyle::Box => BOX_HALF_EXTENT,
+            DiceContainerStyle::Cup => CUP_RADIUS,
+        };
+        Self {
+            radius: (half_extent - WALL_MARGIN).max(spacing),
+            spacing,
+        }
+    }
+
+    /// Floor positions for `count` dice, in order: `x` runs left to right
+    /// and `y` towards the viewer. The first row is near the front wall;
+    /// each further row is one spacing further back and as wide as the
+    /// floor allows there.
+    pub fn positions(&self, count: usize) -> Vec<Vec2> {
+        let mut positions = Vec::with_capacity(count);
+        let mut front = self.radius * 0.65;
+        while positions.len() < count {
+            let half_width = (self.radius * self.radius - front * front).max(0.0).sqrt();
+            let fits = ((half_width * 2.0 / self.spacing) as usize + 1).max(1);
+            let in_row = fits.min(count - positions.len());
+            let start = -(in_row as f32 - 1.0) * self.spacing / 2.0;
+            positions
+                .extend((0..in_row).map(|i| Vec2::new(start + i as f32 * self.spacing, front)));
+            front -= self.spacing;
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_fit_the_floor() {
+        let layout = GatherLayout::for_style(DiceContainerStyle::Box, GATHER_SPACING);
+        let positions = layout.positions(3);
+        assert_eq!(positions.len(), 3);
+        // One centered row, lowest value on the left
+        assert!(positions.iter().all(|p| p.y == positions[0].y));
+        assert!(positions[0].x < positions[1].x && positions[1].x < positions[2].x);
+        assert!((positions[1].x).abs() < 1e-5);
+
+        // Many dice wrap into rows further back, all on the floor
+        let layout = GatherLayout::for_style(DiceContainerStyle::Cup, GATHER_SPACING);
+        let positions = layout.positions(12);
+        assert_eq!(positions.len(), 12);
+        assert!(positions.last().unwrap().y < positions[0].y);
+        assert!(positions.iter().all(|p| p.length() <= layout.radius + 1e-3));
+    }
+}
@@ -0,0 +1,612 @@
+//! Dice macros
+//!
+//! A macro is a recorded sequence of actions - switching to a character and
+//! the rolls made after it - replayed in order, each roll waiting for the
+//! previous one to settle. Recording listens to the roll events, so rolls
+//! from the command input, quick rolls and the character sheet are all
+//! captured, whichever tab they were made on:
+//!
+//! ```text
+//! macro record
+//! (pick Elara, roll Stealth on her sheet, roll 2d6+3 damage)
+//! macro save sneak 1        # replay with `macro play sneak` or Ctrl+1
+//! ```
+//!
+//! A macro can also be written out as a definition, its rolls separated by
+//! `;`, each with an optional label and `adv` / `dis` for its d20:
+//!
+//! ```text
+//! sneak_attack = 1d20+7 adv; Damage: 1d6+4+3d6
+//! ```
+//!
+//! Defined macros belong to every character or to one of them. Macros are
+//! stored in the profile database.
+
+#[cfg(feature = "gui")]
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::{BinaryOp, DiceConfig, DiceExpr, DiceType, KeepRule};
+
+/// Database setting key holding the macros of the current profile
+pub const DICE_MACROS_DB_KEY: &str = "dice_macros";
+
+/// One recorded action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "camelCase")]
+pub enum MacroStep {
+    /// Switch to the character with this name
+    SelectCharacter { name: String },
+    /// Roll `dice` plus `modifier`, labelled like the original roll
+    Roll {
+        label: String,
+        dice: String,
+        modifier: i32,
+    },
+}
+
+impl MacroStep {
+    /// A roll written as `[label:] dice [adv|dis]` (`1d20+7 adv`,
+    /// `Damage: 1d6+4+3d6`); unlabelled rolls are labelled `default_label`.
+    /// A constant added at the end of the dice becomes the modifier.
+    pub fn parse_roll(text: &str, default_label: &str) -> Result<Self, String> {
+        let (label, rest) = match text.split_once(':') {
+            Some((label, rest)) => (label.trim(), rest.trim()),
+            None => (default_label.trim(), text.trim()),
+        };
+        let (dice, edge) = match rest.rsplit_once(char::is_whitespace) {
+            Some((dice, word)) => match word.to_lowercase().as_str() {
+                "adv" | "advantage" => (dice, Some(KeepRule::KeepHighest(1))),
+                "dis" | "disadvantage" => (dice, Some(KeepRule::KeepLowest(1))),
+                _ => (rest, None),
+            },
+            None => (rest, None),
+        };
+
+        let mut expression = DiceExpr::parse(dice)?;
+        if expression.dice().is_empty() {
+            return Err(format!("No dice in '{}'", dice.trim()));
+        }
+        if let Some(keep) = edge {
+            if !roll_d20_twice(&mut expression, keep) {
+                return Err(format!(
+                    "'{}' has no d20 to roll with advantage or disadvantage",
+                    dice.trim()
+                ));
+            }
+        }
+        let (expression, modifier) = split_modifier(expression);
+        Ok(MacroStep::Roll {
+            label: label.to_string(),
+            dice: expression.to_string(),
+            modifier,
+        })
+    }
+
+    /// The roll step of a requested roll.
+    pub fn roll(config: &DiceConfig) -> Self {
+        let dice = match &config.expression {
+            Some(expression) => expression.to_string(),
+            None => {
+                // Group the plain dice into `NdX` terms, keeping their order
+                let mut terms: Vec<(u32, u32)> = Vec::new();
+                for die in &config.dice_to_roll {
+                    let sides = die.max_value();
+                    match terms.iter_mut().find(|(s, _)| *s == sides) {
+                        Some((_, count)) => *count += 1,
+                        None => terms.push((sides, 1)),
+                    }
+                }
+                terms
+                    .iter()
+                    .map(|(sides, count)| format!("{}d{}", count, sides))
+                    .collect::<Vec<_>>()
+                    .join("+")
+            }
+        };
+        MacroStep::Roll {
+            label: config.modifier_name.clone(),
+            dice,
+            modifier: config.modifier,
+        }
+    }
+
+    /// The dice to throw for a roll step, `None` for other steps.
+    pub fn dice_config(&self) -> Option<Result<DiceConfig, String>> {
+        let MacroStep::Roll {
+            label,
+            dice,
+            modifier,
+        } = self
+        else {
+            return None;
+        };
+        Some(DiceExpr::parse(dice).map(|expression| {
+            let (dice_to_roll, expression) = DiceConfig::from_expression(&expression);
+            DiceConfig {
+                dice_to_roll,
+                modifier: *modifier,
+                modifier_name: label.clone(),
+                expression,
+            }
+        }))
+    }
+}
+
+/// Roll the first lone d20 of `expression` twice, keeping the die `keep`
+/// picks; `false` when there is none.
+fn roll_d20_twice(expression: &mut DiceExpr, keep: KeepRule) -> bool {
+    match expression {
+        DiceExpr::Dice {
+            count: count @ 1,
+            die: DiceType::D20,
+            keep: rule @ None,
+            explode: None,
+        } => {
+            *count = 2;
+            *rule = Some(keep);
+            true
+        }
+        DiceExpr::Neg(inner) => roll_d20_twice(inner, keep),
+        DiceExpr::Binary { lhs, rhs, .. } => roll_d20_twice(lhs, keep) || roll_d20_twice(rhs, keep),
+        _ => false,
+    }
+}
+
+/// `1d20+7` -> (`1d20`, 7); other expressions keep their constants.
+fn split_modifier(expression: DiceExpr) -> (DiceExpr, i32) {
+    if let DiceExpr::Binary { op, lhs, rhs } = &expression {
+        if let (BinaryOp::Add | BinaryOp::Sub, DiceExpr::Constant(value)) = (op, rhs.as_ref()) {
+            if let Ok(value) = i32::try_from(*value) {
+                let modifier = if *op == BinaryOp::Sub { -value } else { value };
+                return (lhs.as_ref().clone(), modifier);
+            }
+        }
+    }
+    (expression, 0)
+}
+
+/// Parse a macro definition, `name = roll; roll; ...`, into its name and
+/// steps (see `MacroStep::parse_roll`).
+pub fn parse_macro_definition(text: &str) -> Result<(String, Vec<MacroStep>), String> {
+    let (name, rolls) = text
+        .split_once('=')
+        .ok_or_else(|| "Define a macro as <name> = <roll>; <roll>".to_string())?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name the macro: <name> = <roll>; <roll>".to_string());
+    }
+    let steps = rolls
+        .split(';')
+        .map(str::trim)
+        .filter(|roll| !roll.is_empty())
+        .map(|roll| MacroStep::parse_roll(roll, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    if steps.is_empty() {
+        return Err(format!("Macro {} has no rolls", name));
+    }
+    Ok((name.to_string(), steps))
+}
+
+impl fmt::Display for MacroStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacroStep::SelectCharacter { name } => write!(f, "select {}", name),
+            MacroStep::Roll {
+                label,
+                dice,
+                modifier,
+            } => {
+                if label.is_empty() {
+                    write!(f, "roll {}", dice)?;
+                } else {
+                    write!(f, "roll {} ({})", label, dice)?;
+                }
+                if *modifier != 0 {
+                    write!(f, " {:+}", modifier)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A saved macro
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiceMacro {
+    pub name: String,
+    /// Digit 1-9, played with Ctrl+digit
+    #[serde(default)]
+    pub hotkey: Option<u8>,
+    /// Name of the character the macro belongs to; `None` for every
+    /// character
+    #[serde(default)]
+    pub character: Option<String>,
+    pub steps: Vec<MacroStep>,
+}
+
+impl DiceMacro {
+    /// Whether `character` (the current character, if any) may play it.
+    pub fn is_available_to(&self, character: Option<&str>) -> bool {
+        match (&self.character, character) {
+            (None, _) => true,
+            (Some(owner), Some(name)) => owner.eq_ignore_ascii_case(name),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// The macros of the current profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct DiceMacros {
+    pub macros: Vec<DiceMacro>,
+}
+
+impl DiceMacros {
+    /// Save recorded steps as a macro for every character, replacing one
+    /// with the same name.
+    ///
+    /// A hotkey already bound to another macro moves to this one.
+    pub fn save(
+        &mut self,
+        name: &str,
+        hotkey: Option<u8>,
+        steps: Vec<MacroStep>,
+    ) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Name the macro: macro save <name> [1-9]".to_string());
+        }
+        if !steps
+            .iter()
+            .any(|step| matches!(step, MacroStep::Roll { .. }))
+        {
+            return Err("Nothing to save: no rolls were recorded".to_string());
+        }
+        if let Some(key) = hotkey {
+            if !(1..=9).contains(&key) {
+                return Err(format!("Hotkey {} is not 1-9", key));
+            }
+            for other in &mut self.macros {
+                if other.hotkey == Some(key) {
+                    other.hotkey = None;
+                }
+            }
+        }
+
+        let saved = DiceMacro {
+            name: name.to_string(),
+            hotkey,
+            character: None,
+            steps,
+        };
+        let message = format!("Macro saved: {}", Self::describe(&saved));
+        match self
+            .macros
+            .iter_mut()
+            .find(|m| m.character.is_none() && m.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => *existing = saved,
+            None => self.macros.push(saved),
+        }
+        Ok(message)
+    }
+
+    /// Save a macro definition (`name = roll; roll`) for `character`, or for
+    /// every character without one. It replaces their macro of the same
+    /// name, keeping its hotkey.
+    pub fn define(&mut self, definition: &str, character: Option<&str>) -> Result<String, String> {
+        let (name, steps) = parse_macro_definition(definition)?;
+        let character = character.map(str::to_string);
+        let existing = self.macros.iter().position(|m| {
+            m.name.eq_ignore_ascii_case(&name)
+                && match (&m.character, &character) {
+                    (Some(owner), Some(character)) => owner.eq_ignore_ascii_case(character),
+                    (owner, character) => owner.is_none() && character.is_none(),
+                }
+        });
+        let defined = DiceMacro {
+            name,
+            hotkey: existing.and_then(|index| self.macros[index].hotkey),
+            character,
+            steps,
+        };
+        let message = format!("Macro saved: {}", Self::describe(&defined));
+        match existing {
+            Some(index) => self.macros[index] = defined,
+            None => self.macros.push(defined),
+        }
+        Ok(message)
+    }
+
+    /// Where the macro `character` plays by this name is: their own, or
+    /// else the one every character shares.
+    fn position(&self, name: &str, character: Option<&str>) -> Option<usize> {
+        let name = name.trim();
+        let matches = |m: &DiceMacro| m.name.eq_ignore_ascii_case(name);
+        self.macros
+            .iter()
+            .position(|m| m.character.is_some() && m.is_available_to(character) && matches(m))
+            .or_else(|| {
+                self.macros
+                    .iter()
+                    .position(|m| m.character.is_none() && matches(m))
+            })
+    }
+
+    /// The macro with this name (case-insensitive) that `character` plays.
+    pub fn find(&self, name: &str, character: Option<&str>) -> Option<&DiceMacro> {
+        self.position(name, character)
+            .map(|index| &self.macros[index])
+    }
+
+    /// The macros `character` may play.
+    pub fn available(&self, character: Option<&str>) -> Vec<&DiceMacro> {
+        self.macros
+            .iter()
+            .filter(|m| m.is_available_to(character))
+            .collect()
+    }
+
+    /// The macro bound to Ctrl+`key`.
+    pub fn by_hotkey(&self, key: u8) -> Option<&DiceMacro> {
+        self.macros.iter().find(|m| m.hotkey == Some(key))
+    }
+
+    /// Remove the macro `character` plays by this name, returning it.
+    pub fn remove(&mut self, name: &str, character: Option<&str>) -> Option<DiceMacro> {
+        let index = self.position(name, character)?;
+        Some(self.macros.remove(index))
+    }
+
+    /// "sneak (Ctrl+1): select Elara, roll Stealth (1d20) +7", with the
+    /// character a macro belongs to: "rage (Brom): ..."
+    pub fn describe(dice_macro: &DiceMacro) -> String {
+        let steps = dice_macro
+            .steps
+            .iter()
+            .map(MacroStep::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let notes: Vec<String> = dice_macro
+            .character
+            .iter()
+            .cloned()
+            .chain(dice_macro.hotkey.map(|key| format!("Ctrl+{}", key)))
+            .collect();
+        if notes.is_empty() {
+            format!("{}: {}", dice_macro.name, steps)
+        } else {
+            format!("{} ({}): {}", dice_macro.name, notes.join(", "), steps)
+        }
+    }
+
+    /// One line per macro `character` may play, for display.
+    pub fn summary(&self, character: Option<&str>) -> String {
+        let available = self.available(character);
+        if available.is_empty() {
+            return "No macros - define one with `macro <name> = <roll>; <roll>` or record one with `macro record`".to_string();
+        }
+        available
+            .into_iter()
+            .map(Self::describe)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Steps captured since `macro record`, `None` when not recording
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct MacroRecorder {
+    pub steps: Option<Vec<MacroStep>>,
+}
+
+impl MacroRecorder {
+    /// Start recording, beginning with the current character so the macro
+    /// replays for them.
+    pub fn start(&mut self, character: Option<&str>) {
+        self.steps = Some(
+            character
+                .map(|name| MacroStep::SelectCharacter {
+                    name: name.to_string(),
+                })
+                .into_iter()
+                .collect(),
+        );
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    /// Add a step while recording; switching character twice in a row
+    /// keeps only the last switch.
+    pub fn record(&mut self, step: MacroStep) {
+        let Some(steps) = self.steps.as_mut() else {
+            return;
+        };
+        if matches!(step, MacroStep::SelectCharacter { .. })
+            && matches!(steps.last(), Some(MacroStep::SelectCharacter { .. }))
+        {
+            steps.pop();
+        }
+        steps.push(step);
+    }
+
+    /// Stop recording, returning the steps.
+    pub fn finish(&mut self) -> Vec<MacroStep> {
+        self.steps.take().unwrap_or_default()
+    }
+}
+
+/// The macro being replayed
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct MacroPlayback {
+    pub name: String,
+    pub steps: VecDeque<MacroStep>,
+    /// A roll of the macro is in the box; the next step waits for it
+    pub waiting_for_roll: bool,
+}
+
+impl MacroPlayback {
+    pub fn start(&mut self, dice_macro: &DiceMacro) {
+        self.name = dice_macro.name.clone();
+        self.steps = dice_macro.steps.iter().cloned().collect();
+        self.waiting_for_roll = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.steps.is_empty() || self.waiting_for_roll
+    }
+
+    pub fn stop(&mut self) {
+        self.steps.clear();
+        self.waiting_for_roll = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::DiceType;
+
+    fn config(dice: &str, modifier: i32, label: &str) -> DiceConfig {
+        let (dice_to_roll, expression) =
+            DiceConfig::from_expression(&DiceExpr::parse(dice).unwrap());
+        DiceConfig {
+            dice_to_roll,
+            modifier,
+            modifier_name: label.to_string(),
+            expression,
+        }
+    }
+
+    #[test]
+    fn test_roll_steps_round_trip() {
+        let step = MacroStep::roll(&config("1d20", 7, "Stealth"));
+        assert_eq!(
+            step,
+            MacroStep::Roll {
+                label: "Stealth".to_string(),
+                dice: "1d20".to_string(),
+                modifier: 7,
+            }
+        );
+        assert_eq!(step.to_string(), "roll Stealth (1d20) +7");
+
+        let replayed = step.dice_config().unwrap().unwrap();
+        assert_eq!(replayed.dice_to_roll, vec![DiceType::D20]);
+        assert_eq!(replayed.modifier, 7);
+        assert_eq!(replayed.modifier_name, "Stealth");
+
+        let damage = MacroStep::roll(&config("2d6+1d8", 0, ""));
+        let replayed = damage.dice_config().unwrap().unwrap();
+        assert_eq!(replayed.dice_to_roll.len(), 3);
+        assert!(MacroStep::SelectCharacter {
+            name: "Elara".to_string()
+        }
+        .dice_config()
+        .is_none());
+    }
+
+    #[test]
+    fn test_recorder_starts_with_character_and_merges_switches() {
+        let mut recorder = MacroRecorder::default();
+        recorder.record(MacroStep::roll(&config("1d20", 0, "")));
+        assert!(!recorder.is_recording());
+
+        recorder.start(Some("Elara"));
+        recorder.record(MacroStep::SelectCharacter {
+            name: "Brom".to_string(),
+        });
+        recorder.record(MacroStep::roll(&config("1d20", 3, "Athletics")));
+        let steps = recorder.finish();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].to_string(), "select Brom");
+        assert!(!recorder.is_recording());
+    }
+
+    #[test]
+    fn test_save_replaces_and_moves_hotkeys() {
+        let mut macros = DiceMacros::default();
+        let steps = vec![MacroStep::roll(&config("1d20", 5, "Perception"))];
+
+        assert!(macros.save("look", Some(1), Vec::new()).is_err());
+        assert!(macros.save("look", Some(10), steps.clone()).is_err());
+        assert_eq!(
+            macros.save("look", Some(1), steps.clone()).unwrap(),
+            "Macro saved: look (Ctrl+1): roll Perception (1d20) +5"
+        );
+        macros.save("sneak", Some(1), steps.clone()).unwrap();
+        assert_eq!(macros.by_hotkey(1).unwrap().name, "sneak");
+        assert_eq!(macros.find("LOOK", None).unwrap().hotkey, None);
+
+        macros.save("Look", None, steps).unwrap();
+        assert_eq!(macros.macros.len(), 2);
+        assert!(macros.remove("look", None).is_some());
+        assert!(macros.find("look", None).is_none());
+    }
+
+    #[test]
+    fn test_parse_macro_definition() {
+        let (name, steps) =
+            parse_macro_definition("sneak_attack = 1d20+7 adv; Damage: 1d6+4+3d6").unwrap();
+        assert_eq!(name, "sneak_attack");
+        assert_eq!(steps[0].to_string(), "roll sneak_attack (2d20kh1) +7");
+        assert_eq!(steps[1].to_string(), "roll Damage (1d6+4+3d6)");
+
+        let attack = steps[0].dice_config().unwrap().unwrap();
+        assert_eq!(attack.dice_to_roll, vec![DiceType::D20, DiceType::D20]);
+        assert_eq!(attack.modifier, 7);
+
+        let (_, steps) = parse_macro_definition("save = 1d20-1 dis").unwrap();
+        assert_eq!(steps[0].to_string(), "roll save (2d20kl1) -1");
+
+        assert!(parse_macro_definition("1d20+7").is_err());
+        assert!(parse_macro_definition(" = 1d20").is_err());
+        assert!(parse_macro_definition("empty = ;").is_err());
+        assert!(parse_macro_definition("flat = 7").is_err());
+        assert!(parse_macro_definition("smite = 2d8 adv").is_err());
+    }
+
+    #[test]
+    fn test_character_macros() {
+        let mut macros = DiceMacros::default();
+        macros.define("rage = 1d20+5", None).unwrap();
+        assert_eq!(
+            macros.define("rage = 1d20+7 adv", Some("Brom")).unwrap(),
+            "Macro saved: rage (Brom): roll rage (2d20kh1) +7"
+        );
+        assert_eq!(macros.macros.len(), 2);
+
+        // A character plays their own macro over everyone's
+        let rage = macros.find("rage", Some("brom")).unwrap();
+        assert_eq!(rage.character.as_deref(), Some("Brom"));
+        assert!(macros
+            .find("rage", Some("Elara"))
+            .unwrap()
+            .character
+            .is_none());
+        assert_eq!(macros.available(Some("Elara")).len(), 1);
+        assert_eq!(macros.available(Some("Brom")).len(), 2);
+
+        // Redefining replaces the macro for the same character only
+        macros.define("rage = 1d20+8", Some("Brom")).unwrap();
+        assert_eq!(macros.macros.len(), 2);
+        assert!(macros
+            .remove("rage", Some("Brom"))
+            .unwrap()
+            .character
+            .is_some());
+        assert!(macros
+            .find("rage", Some("Brom"))
+            .unwrap()
+            .character
+            .is_none());
+    }
+}
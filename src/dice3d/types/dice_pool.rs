@@ -0,0 +1,256 @@
+//! Dice pool game systems
+//!
+//! Storyteller and Shadowrun style games roll a handful of one kind of die
+//! and count the dice that reach a target number instead of adding them up.
+//! The game system chosen in the Dice settings decides which die forms the
+//! pool, the target, whether a die showing its maximum is rolled again
+//! (10-again) and whether ones take successes away. Rolls that are not a
+//! plain pool (`1d20+5` in World of Darkness) are still totalled as usual.
+
+use serde::{Deserialize, Serialize};
+
+use super::{BinaryOp, DiceExpr, DiceType, DEFAULT_EXPLOSION_CAP};
+
+/// How rolls are read: totalled, or counted as a dice pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GameSystem {
+    /// Dice are added up (D&D and most d20 games)
+    #[default]
+    Standard,
+    /// d10 pool, 8 or more succeeds, 10s are rolled again
+    WorldOfDarkness,
+    /// d10 pool, 6 or more succeeds, each 1 cancels a success
+    ClassicWorldOfDarkness,
+    /// d6 pool, 5 or 6 is a hit
+    Shadowrun,
+}
+
+/// The dice pool rules of a game system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DicePool {
+    pub die: DiceType,
+    /// Lowest face that counts as a success
+    pub target: u32,
+    /// A die showing its maximum is rolled again and can succeed again
+    pub explodes: bool,
+    /// Each 1 takes away a success
+    pub ones_cancel: bool,
+}
+
+/// Successes counted in a dice pool roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOutcome {
+    /// Dice at or above the target
+    pub hits: u32,
+    /// Ones, when they cancel successes
+    pub ones: u32,
+}
+
+impl PoolOutcome {
+    /// Hits less the ones that cancel them; below zero is a botch
+    pub fn successes(&self) -> i32 {
+        self.hits as i32 - self.ones as i32
+    }
+
+    /// No hits and at least one 1 (classic World of Darkness)
+    pub fn is_botch(&self) -> bool {
+        self.hits == 0 && self.ones > 0
+    }
+}
+
+impl GameSystem {
+    pub const ALL: [GameSystem; 4] = [
+        GameSystem::Standard,
+        GameSystem::WorldOfDarkness,
+        GameSystem::ClassicWorldOfDarkness,
+        GameSystem::Shadowrun,
+    ];
+
+    /// Name used in the settings select ("wod")
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameSystem::Standard => "standard",
+            GameSystem::WorldOfDarkness => "wod",
+            GameSystem::ClassicWorldOfDarkness => "classic-wod",
+            GameSystem::Shadowrun => "shadowrun",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameSystem::Standard => "Add up the dice (D&D)",
+            GameSystem::WorldOfDarkness => "World of Darkness (d10 pool, 8+, 10-again)",
+            GameSystem::ClassicWorldOfDarkness => "Classic World of Darkness (d10 pool, 6+)",
+            GameSystem::Shadowrun => "Shadowrun (d6 pool, 5+)",
+        }
+    }
+
+    /// Parse a name or common abbreviation ("cofd", "sr").
+    pub fn parse(text: &str) -> Option<GameSystem> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "standard" | "sum" | "dnd" | "d&d" | "5e" => Some(GameSystem::Standard),
+            "wod" | "nwod" | "cofd" | "chronicles" => Some(GameSystem::WorldOfDarkness),
+            "classic-wod" | "cwod" | "owod" => Some(GameSystem::ClassicWorldOfDarkness),
+            "shadowrun" | "sr" => Some(GameSystem::Shadowrun),
+            _ => None,
+        }
+    }
+
+    /// The dice pool rules, `None` when dice are added up.
+    pub fn dice_pool(&self) -> Option<DicePool> {
+        match self {
+            GameSystem::Standard => None,
+            GameSystem::WorldOfDarkness => Some(DicePool {
+                die: DiceType::D10,
+                target: 8,
+                explodes: true,
+                ones_cancel: false,
+            }),
+            GameSystem::ClassicWorldOfDarkness => Some(DicePool {
+                die: DiceType::D10,
+                target: 6,
+                explodes: false,
+                ones_cancel: true,
+            }),
+            GameSystem::Shadowrun => Some(DicePool {
+                die: DiceType::D6,
+                target: 5,
+                explodes: false,
+                ones_cancel: false,
+            }),
+        }
+    }
+}
+
+impl DicePool {
+    /// Whether an expression rolls a pool: only dice of the pool's die added
+    /// together (`5d10`, `3d10+2d10`), exploding or not.
+    pub fn matches(&self, expression: &DiceExpr) -> bool {
+        match expression {
+            DiceExpr::Dice { die, keep, .. } => *die == self.die && keep.is_none(),
+            DiceExpr::Binary {
+                op: BinaryOp::Add,
+                lhs,
+                rhs,
+            } => self.matches(lhs) && self.matches(rhs),
+            _ => false,
+        }
+    }
+
+    /// The expression to roll for a pool: with 10-again every die of the
+    /// pool explodes. Other expressions are returned unchanged.
+    pub fn prepare(&self, expression: DiceExpr) -> DiceExpr {
+        if !self.explodes || !self.matches(&expression) {
+            return expression;
+        }
+        explode_all(expression)
+    }
+
+    /// Count the successes among the faces rolled (explosions included).
+    pub fn count(&self, values: impl IntoIterator<Item = u32>) -> PoolOutcome {
+        let mut outcome = PoolOutcome { hits: 0, ones: 0 };
+        for value in values {
+            if value >= self.target {
+                outcome.hits += 1;
+            } else if value == 1 && self.ones_cancel {
+                outcome.ones += 1;
+            }
+        }
+        outcome
+    }
+}
+
+fn explode_all(expression: DiceExpr) -> DiceExpr {
+    match expression {
+        DiceExpr::Dice {
+            count,
+            die,
+            keep,
+            explode,
+        } => DiceExpr::Dice {
+            count,
+            die,
+            keep,
+            explode: explode.or(Some(DEFAULT_EXPLOSION_CAP)),
+        },
+        DiceExpr::Binary { op, lhs, rhs } => DiceExpr::Binary {
+            op,
+            lhs: Box::new(explode_all(*lhs)),
+            rhs: Box::new(explode_all(*rhs)),
+        },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(system: GameSystem) -> DicePool {
+        system.dice_pool().unwrap()
+    }
+
+    #[test]
+    fn test_pool_matches_plain_pools_only() {
+        let wod = pool(GameSystem::WorldOfDarkness);
+        for pool_roll in ["5d10", "3d10+2d10", "4d10!"] {
+            assert!(
+                wod.matches(&DiceExpr::parse(pool_roll).unwrap()),
+                "{}",
+                pool_roll
+            );
+        }
+        for other in ["1d20+5", "5d10+2", "5d10kh3", "2d6", "5d10-1d10"] {
+            assert!(!wod.matches(&DiceExpr::parse(other).unwrap()), "{}", other);
+        }
+        assert!(GameSystem::Standard.dice_pool().is_none());
+    }
+
+    #[test]
+    fn test_ten_again_explodes_the_pool() {
+        let wod = pool(GameSystem::WorldOfDarkness);
+        let prepared = wod.prepare(DiceExpr::parse("3d10").unwrap());
+        assert!(prepared.explodes());
+        let roll = prepared.roll_from_results(&[
+            (DiceType::D10, 10),
+            (DiceType::D10, 8),
+            (DiceType::D10, 3),
+        ]);
+        // The 10 still has to roll again
+        assert!(roll.is_err());
+
+        let d20 = DiceExpr::parse("1d20+5").unwrap();
+        assert_eq!(wod.prepare(d20.clone()), d20);
+        let no_again = pool(GameSystem::Shadowrun).prepare(DiceExpr::parse("6d6").unwrap());
+        assert!(!no_again.explodes());
+    }
+
+    #[test]
+    fn test_count_successes() {
+        let wod = pool(GameSystem::WorldOfDarkness);
+        let outcome = wod.count([10, 9, 8, 7, 1, 1]);
+        assert_eq!(outcome.successes(), 3);
+        assert!(!outcome.is_botch());
+
+        let classic = pool(GameSystem::ClassicWorldOfDarkness);
+        assert_eq!(classic.count([6, 9, 1, 3]).successes(), 1);
+        let botch = classic.count([1, 4, 5]);
+        assert_eq!(botch.successes(), -1);
+        assert!(botch.is_botch());
+
+        assert_eq!(
+            pool(GameSystem::Shadowrun).count([6, 5, 4, 1]).successes(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_game_system_names() {
+        for system in GameSystem::ALL {
+            assert_eq!(GameSystem::parse(system.name()), Some(system));
+        }
+        assert_eq!(GameSystem::parse("CofD"), Some(GameSystem::WorldOfDarkness));
+        assert_eq!(GameSystem::parse("chess"), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Generated dice sounds
+//!
+//! Dice hitting the container play the recorded box and cup samples. The
+//! shorter sounds (dice knocking into each other, the clack of the last die
+//! coming to rest, the sting of a natural 20 and the turn timer's warning
+//! beeps) are synthesized at startup instead: a few damped sine partials over a burst of noise, written as a
+//! 16-bit mono WAV so they play like any other audio asset.
+
+/// Sample rate of the generated sounds
+pub const DICE_SOUND_SAMPLE_RATE: u32 = 44_100;
+
+/// Sounds generated at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceSound {
+    /// Two dice knocking into each other
+    DiceClick,
+    /// The last die of a roll coming to rest
+    SettleClack,
+    /// A natural 20
+    CriticalSting,
+    /// A timed turn running low on time
+    TurnWarning,
+}
+
+/// A damped sine: frequency (Hz), amplitude and decay time constant (s)
+struct Partial {
+    frequency: f32,
+    amplitude: f32,
+    decay: f32,
+}
+
+impl DiceSound {
+    pub const ALL: [DiceSound; 4] = [
+        DiceSound::DiceClick,
+        DiceSound::SettleClack,
+        DiceSound::CriticalSting,
+        DiceSound::TurnWarning,
+    ];
+
+    /// Samples in [-1, 1] at `DICE_SOUND_SAMPLE_RATE`.
+    pub fn samples(&self) -> Vec<f32> {
+        match self {
+            DiceSound::DiceClick => knock(
+                0.06,
+                &[
+                    Partial {
+                        frequency: 2900.0,
+                        amplitude: 0.5,
+                        decay: 0.008,
+                    },
+                    Partial {
+                        frequency: 4700.0,
+                        amplitude: 0.3,
+                        decay: 0.006,
+                    },
+                ],
+                0.4,
+                0.002,
+            ),
+            DiceSound::SettleClack => knock(
+                0.14,
+                &[
+                    Partial {
+                        frequency: 900.0,
+                        amplitude: 0.6,
+                        decay: 0.025,
+                    },
+                    Partial {
+                        frequency: 1650.0,
+                        amplitude: 0.35,
+                        decay: 0.018,
+                    },
+                ],
+                0.5,
+                0.004,
+            ),
+            DiceSound::CriticalSting => sting(),
+            DiceSound::TurnWarning => beeps(),
+        }
+    }
+
+    /// The sound as a WAV file.
+    pub fn wav_bytes(&self) -> Vec<u8> {
+        wav_bytes(&self.samples())
+    }
+}
+
+/// Damped partials over a noise burst, normalized to a 0.8 peak.
+fn knock(duration: f32, partials: &[Partial], noise: f32, noise_decay: f32) -> Vec<f32> {
+    let count = (duration * DICE_SOUND_SAMPLE_RATE as f32) as usize;
+    let mut rng = NoiseSource(0x2545_f491);
+    let samples = (0..count)
+        .map(|i| {
+            let t = i as f32 / DICE_SOUND_SAMPLE_RATE as f32;
+            let tone: f32 = partials
+                .iter()
+                .map(|p| {
+                    p.amplitude
+                        * (-t / p.decay).exp()
+                        * (std::f32::consts::TAU * p.frequency * t).sin()
+                })
+                .sum();
+            tone + noise * (-t / noise_decay).exp() * rng.next()
+        })
+        .collect();
+    normalize(samples, 0.8)
+}
+
+/// A rising C major arpeggio, the last note ringing longest.
+fn sting() -> Vec<f32> {
+    const NOTES: [(f32, f32, f32); 4] = [
+        // (frequency, start, decay)
+        (523.25, 0.0, 0.25),
+        (659.25, 0.09, 0.25),
+        (783.99, 0.18, 0.3),
+        (1046.5, 0.27, 0.6),
+    ];
+    const ATTACK: f32 = 0.005;
+    let count = (1.4 * DICE_SOUND_SAMPLE_RATE as f32) as usize;
+    let samples = (0..count)
+        .map(|i| {
+            let t = i as f32 / DICE_SOUND_SAMPLE_RATE as f32;
+            NOTES
+                .iter()
+                .filter(|(_, start, _)| t >= *start)
+                .map(|(frequency, start, decay)| {
+                    let local = t - start;
+                    let envelope = (local / ATTACK).min(1.0) * (-local / decay).exp();
+                    let phase = std::f32::consts::TAU * frequency * local;
+                    envelope * (phase.sin() + 0.3 * (2.0 * phase).sin())
+                })
+                .sum()
+        })
+        .collect();
+    normalize(samples, 0.8)
+}
+
+/// Two short 880 Hz beeps.
+fn beeps() -> Vec<f32> {
+    const BEEP: f32 = 0.12;
+    const GAP: f32 = 0.08;
+    const RAMP: f32 = 0.01;
+    let count = ((2.0 * BEEP + GAP) * DICE_SOUND_SAMPLE_RATE as f32) as usize;
+    let samples = (0..count)
+        .map(|i| {
+            let t = i as f32 / DICE_SOUND_SAMPLE_RATE as f32;
+            let local = if t < BEEP { t } else { t - BEEP - GAP };
+            if !(0.0..BEEP).contains(&local) {
+                return 0.0;
+            }
+            // Short ramps at both ends keep the beeps from clicking
+            let envelope = (local / RAMP).min((BEEP - local) / RAMP).min(1.0);
+            envelope * (std::f32::consts::TAU * 880.0 * t).sin()
+        })
+        .collect();
+    normalize(samples, 0.8)
+}
+
+fn normalize(mut samples: Vec<f32>, peak: f32) -> Vec<f32> {
+    let max = samples.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+    if max > 0.0 {
+        for s in &mut samples {
+            *s *= peak / max;
+        }
+    }
+    samples
+}
+
+/// Xorshift noise: the same every run, so the sounds are too.
+struct NoiseSource(u32);
+
+impl NoiseSource {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+/// A 16-bit mono PCM WAV file of `samples`.
+pub fn wav_bytes(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&DICE_SOUND_SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(DICE_SOUND_SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        let value = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Volume of an impact whose dice move at `strength` (linear speed plus a
+/// share of the spin), before the sample's gain and the sound volume.
+///
+/// Quiet knocks keep a small floor so they are still heard; around 3.6 is
+/// full volume.
+pub fn impact_volume(strength: f32) -> f32 {
+    const STRENGTH_REF: f32 = 3.6;
+    const MIN_VOLUME: f32 = 0.08;
+    const MAX_VOLUME: f32 = 1.0;
+    let t = (strength / STRENGTH_REF).clamp(0.0, 1.0);
+    MIN_VOLUME + (MAX_VOLUME - MIN_VOLUME) * t.powf(0.7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sounds_are_normalized_and_repeatable() {
+        for sound in DiceSound::ALL {
+            let samples = sound.samples();
+            assert!(!samples.is_empty());
+            let peak = samples.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+            assert!((peak - 0.8).abs() < 1e-4, "{:?} peaks at {}", sound, peak);
+            assert_eq!(samples, sound.samples());
+        }
+        assert!(DiceSound::DiceClick.samples().len() < DiceSound::SettleClack.samples().len());
+        assert!(DiceSound::SettleClack.samples().len() < DiceSound::CriticalSting.samples().len());
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let bytes = wav_bytes(&[0.0, 1.0, -1.0]);
+        assert_eq!(bytes.len(), 44 + 6);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 42);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(
+            u32::from_le_bytes(bytes[24..28].try_into().unwrap()),
+            DICE_SOUND_SAMPLE_RATE
+        );
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(i16::from_le_bytes([bytes[46], bytes[47]]), i16::MAX);
+        assert_eq!(i16::from_le_bytes([bytes[48], bytes[49]]), -i16::MAX);
+    }
+
+    #[test]
+    fn test_impact_volume() {
+        assert!((impact_volume(0.0) - 0.08).abs() < 1e-6);
+        assert!(impact_volume(1.0) < impact_volume(2.0));
+        assert_eq!(impact_volume(3.6), 1.0);
+        assert_eq!(impact_volume(50.0), 1.0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Encounter building with the DMG's XP budget
+//!
+//! The DM picks monsters for an encounter; their XP is added up and
+//! multiplied for the number of monsters (more monsters make a harder fight
+//! than their XP suggests), and the adjusted XP is compared with the party's
+//! XP thresholds to rate the encounter easy, medium, hard or deadly. Parties
+//! of fewer than three or more than five characters move one step along the
+//! multipliers.
+
+#[cfg(feature = "gui")]
+use bevy::prelude::*;
+
+/// XP thresholds per character by level: easy, medium, hard, deadly
+const XP_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700],
+];
+
+/// Encounter multipliers, with the extra steps small and large parties use
+const MULTIPLIERS: [f32; 8] = [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0];
+
+/// How hard an encounter is for the party
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EncounterDifficulty {
+    /// Below the easy threshold
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl EncounterDifficulty {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EncounterDifficulty::Trivial => "Trivial",
+            EncounterDifficulty::Easy => "Easy",
+            EncounterDifficulty::Medium => "Medium",
+            EncounterDifficulty::Hard => "Hard",
+            EncounterDifficulty::Deadly => "Deadly",
+        }
+    }
+}
+
+/// The party's XP thresholds: easy, medium, hard and deadly
+pub fn party_thresholds(levels: &[u32]) -> [u32; 4] {
+    let mut thresholds = [0; 4];
+    for level in levels {
+        let row = XP_THRESHOLDS[(*level).clamp(1, 20) as usize - 1];
+        for (total, threshold) in thresholds.iter_mut().zip(row) {
+            *total += threshold;
+        }
+    }
+    thresholds
+}
+
+/// The multiplier for `monsters` monsters fighting a party of `party_size`.
+pub fn encounter_multiplier(monsters: u32, party_size: usize) -> f32 {
+    let step = match monsters {
+        0 | 1 => 1,
+        2 => 2,
+        3..=6 => 3,
+        7..=10 => 4,
+        11..=14 => 5,
+        _ => 6,
+    };
+    let step = match party_size {
+        0..=2 => step + 1,
+        3..=5 => step,
+        _ => step - 1,
+    };
+    MULTIPLIERS[step]
+}
+
+/// Monsters of one kind in an encounter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncounterMonster {
+    pub name: String,
+    /// XP of one of them
+    pub xp: u32,
+    pub count: u32,
+}
+
+/// The monsters of an encounter being built
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct Encounter {
+    pub monsters: Vec<EncounterMonster>,
+}
+
+impl Encounter {
+    /// Add one monster named `name` worth `xp`.
+    pub fn add(&mut self, name: &str, xp: u32) {
+        match self.monsters.iter_mut().find(|m| m.name == name) {
+            Some(monster) => monster.count += 1,
+            None => self.monsters.push(EncounterMonster {
+                name: name.to_string(),
+                xp,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Take one monster named `name` out.
+    pub fn remove(&mut self, name: &str) {
+        if let Some(index) = self.monsters.iter().position(|m| m.name == name) {
+            self.monsters[index].count -= 1;
+            if self.monsters[index].count == 0 {
+                self.monsters.remove(index);
+            }
+        }
+    }
+
+    pub fn monster_count(&self) -> u32 {
+        self.monsters.iter().map(|m| m.count).sum()
+    }
+
+    /// XP the monsters are worth
+    pub fn base_xp(&self) -> u32 {
+        self.monsters.iter().map(|m| m.xp * m.count).sum()
+    }
+
+    /// XP weighed for the number of monsters, for rating the encounter
+    pub fn adjusted_xp(&self, party_size: usize) -> u32 {
+        (self.base_xp() as f32 * encounter_multiplier(self.monster_count(), party_size)).round()
+            as u32
+    }
+
+    /// How hard the encounter is for a party of these levels.
+    pub fn difficulty(&self, levels: &[u32]) -> EncounterDifficulty {
+        if self.monsters.is_empty() {
+            return EncounterDifficulty::Trivial;
+        }
+        let adjusted = self.adjusted_xp(levels.len());
+        let [easy, medium, hard, deadly] = party_thresholds(levels);
+        if adjusted >= deadly {
+            EncounterDifficulty::Deadly
+        } else if adjusted >= hard {
+            EncounterDifficulty::Hard
+        } else if adjusted >= medium {
+            EncounterDifficulty::Medium
+        } else if adjusted >= easy {
+            EncounterDifficulty::Easy
+        } else {
+            EncounterDifficulty::Trivial
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipliers() {
+        assert_eq!(encounter_multiplier(1, 4), 1.0);
+        assert_eq!(encounter_multiplier(2, 4), 1.5);
+        assert_eq!(encounter_multiplier(6, 4), 2.0);
+        assert_eq!(encounter_multiplier(15, 4), 4.0);
+        // Small parties take the next multiplier up, large ones the next down
+        assert_eq!(encounter_multiplier(1, 2), 1.5);
+        assert_eq!(encounter_multiplier(1, 6), 0.5);
+        assert_eq!(encounter_multiplier(15, 1), 5.0);
+    }
+
+    #[test]
+    fn test_rate_encounter() {
+        // Four level 3 characters against a bugbear and two goblins
+        let levels = [3, 3, 3, 3];
+        assert_eq!(party_thresholds(&levels), [300, 600, 900, 1600]);
+
+        let mut encounter = Encounter::default();
+        encounter.add("Bugbear", 200);
+        encounter.add("Goblin", 50);
+        encounter.add("Goblin", 50);
+        assert_eq!(encounter.monster_count(), 3);
+        assert_eq!(encounter.base_xp(), 300);
+        assert_eq!(encounter.adjusted_xp(levels.len()), 600);
+        assert_eq!(encounter.difficulty(&levels), EncounterDifficulty::Medium);
+
+        encounter.remove("Goblin");
+        encounter.remove("Goblin");
+        assert_eq!(encounter.monsters.len(), 1);
+        assert_eq!(encounter.difficulty(&levels), EncounterDifficulty::Trivial);
+        assert_eq!(
+            Encounter::default().difficulty(&levels),
+            EncounterDifficulty::Trivial
+        );
+    }
+}
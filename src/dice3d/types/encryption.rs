@@ -0,0 +1,210 @@
+//! Passphrase-based encryption at rest
+//!
+//! When a profile's database is encrypted, every character sheet is stored
+//! sealed with ChaCha20-Poly1305 under a key derived from the passphrase
+//! (PBKDF2-HMAC-SHA256). Only the name, class, race and level stay readable
+//! so the character list works before the database is unlocked. The
+//! passphrase itself is never stored; a sealed verifier string is used to
+//! check it.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+/// PBKDF2 iterations for new databases (OWASP recommendation for SHA-256)
+pub const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Minimum passphrase length accepted when enabling encryption
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const VERIFIER_TEXT: &str = "dndgamerolls-encryption-verifier";
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate random bytes".to_string())?;
+    Ok(bytes)
+}
+
+fn decode_base64(value: &str, what: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(value)
+        .map_err(|e| format!("Invalid {} encoding: {}", what, e))
+}
+
+/// Text sealed with a [`DatabaseKey`] (base64 nonce + ciphertext with tag)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedText {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Symmetric key derived from the passphrase; kept in memory only.
+#[derive(Clone)]
+pub struct DatabaseKey([u8; KEY_LEN]);
+
+impl std::fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DatabaseKey(..)")
+    }
+}
+
+impl DatabaseKey {
+    fn derive(passphrase: &str, salt: &[u8], iterations: u32) -> Result<Self, String> {
+        let iterations = NonZeroU32::new(iterations)
+            .ok_or_else(|| "Invalid key derivation iterations".to_string())?;
+        let mut key = [0u8; KEY_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        Ok(Self(key))
+    }
+
+    fn aead_key(&self) -> Result<LessSafeKey, String> {
+        let unbound = UnboundKey::new(&CHACHA20_POLY1305, &self.0)
+            .map_err(|_| "Invalid encryption key".to_string())?;
+        Ok(LessSafeKey::new(unbound))
+    }
+
+    /// Seal text under this key with a fresh random nonce.
+    pub fn encrypt(&self, plaintext: &str) -> Result<EncryptedText, String> {
+        let nonce = random_bytes::<NONCE_LEN>()?;
+        let mut in_out = plaintext.as_bytes().to_vec();
+        self.aead_key()?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut in_out,
+            )
+            .map_err(|_| "Encryption failed".to_string())?;
+
+        Ok(EncryptedText {
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(in_out),
+        })
+    }
+
+    /// Open text sealed under this key.
+    pub fn decrypt(&self, sealed: &EncryptedText) -> Result<String, String> {
+        let nonce: [u8; NONCE_LEN] = decode_base64(&sealed.nonce, "nonce")?
+            .try_into()
+            .map_err(|_| "Invalid nonce length".to_string())?;
+        let mut in_out = decode_base64(&sealed.ciphertext, "ciphertext")?;
+
+        let plaintext = self
+            .aead_key()?
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut in_out,
+            )
+            .map_err(|_| "Wrong passphrase or corrupted data".to_string())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|e| format!("Invalid UTF-8: {}", e))
+    }
+}
+
+/// Stored per database when encryption is enabled (no secret material)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    pub salt: String,
+    pub iterations: u32,
+    pub verifier: EncryptedText,
+}
+
+impl EncryptionConfig {
+    /// Create a config (and its key) for a new passphrase.
+    pub fn new(passphrase: &str) -> Result<(Self, DatabaseKey), String> {
+        Self::with_iterations(passphrase, PBKDF2_ITERATIONS)
+    }
+
+    fn with_iterations(passphrase: &str, iterations: u32) -> Result<(Self, DatabaseKey), String> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(format!(
+                "Passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            ));
+        }
+
+        let salt = random_bytes::<SALT_LEN>()?;
+        let key = DatabaseKey::derive(passphrase, &salt, iterations)?;
+        let verifier = key.encrypt(VERIFIER_TEXT)?;
+
+        Ok((
+            Self {
+                salt: BASE64.encode(salt),
+                iterations,
+                verifier,
+            },
+            key,
+        ))
+    }
+
+    /// Derive the key for `passphrase`, checking it against the verifier.
+    pub fn unlock(&self, passphrase: &str) -> Result<DatabaseKey, String> {
+        let salt = decode_base64(&self.salt, "salt")?;
+        let key = DatabaseKey::derive(passphrase, &salt, self.iterations)?;
+        match key.decrypt(&self.verifier) {
+            Ok(text) if text == VERIFIER_TEXT => Ok(key),
+            _ => Err("Wrong passphrase".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keep tests fast; iteration count doesn't change the behavior under test.
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let (_config, key) =
+            EncryptionConfig::with_iterations("correct horse", TEST_ITERATIONS).unwrap();
+        let sealed = key.encrypt("The duke is the lich").unwrap();
+        assert!(!sealed.ciphertext.contains("lich"));
+        assert_eq!(key.decrypt(&sealed).unwrap(), "The duke is the lich");
+
+        // Fresh nonce every time
+        assert_ne!(key.encrypt("same").unwrap(), key.encrypt("same").unwrap());
+    }
+
+    #[test]
+    fn test_unlock_checks_passphrase() {
+        let (config, key) =
+            EncryptionConfig::with_iterations("correct horse", TEST_ITERATIONS).unwrap();
+        let sealed = key.encrypt("secret").unwrap();
+
+        let unlocked = config.unlock("correct horse").unwrap();
+        assert_eq!(unlocked.decrypt(&sealed).unwrap(), "secret");
+        assert!(config.unlock("wrong horse").is_err());
+    }
+
+    #[test]
+    fn test_short_passphrase_rejected() {
+        assert!(EncryptionConfig::with_iterations("short", TEST_ITERATIONS).is_err());
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_fails() {
+        let (_config, key) =
+            EncryptionConfig::with_iterations("correct horse", TEST_ITERATIONS).unwrap();
+        let mut sealed = key.encrypt("secret").unwrap();
+        let mut bytes = BASE64.decode(&sealed.ciphertext).unwrap();
+        bytes[0] ^= 0xff;
+        sealed.ciphertext = BASE64.encode(bytes);
+        assert!(key.decrypt(&sealed).is_err());
+    }
+}
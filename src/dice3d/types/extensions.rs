@@ -0,0 +1,145 @@
+//! Extension points for other crates
+//!
+//! Registered through the `GameRollsAppExt` trait; see `dice3d::plugins`.
+
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::IconType;
+
+/// An app-level tab added by an extension
+#[derive(Debug, Clone)]
+pub struct ExtensionTab {
+    /// Unique id; the tab's root node carries `ExtensionTabRoot(id)`
+    pub id: &'static str,
+    pub label: String,
+    pub icon: IconType,
+}
+
+/// Tabs added by extensions, shown after the built-in tabs in this order
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ExtensionTabs {
+    pub tabs: Vec<ExtensionTab>,
+}
+
+impl ExtensionTabs {
+    /// Position of the tab with `id` among the extension tabs.
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.id == id)
+    }
+}
+
+/// Marker for the root node of an extension tab; shown while that tab is
+/// active
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionTabRoot(pub &'static str);
+
+/// Named dice typed in the command input ("stress" for "1d10")
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DiceAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl DiceAliases {
+    /// Make `name` (case-insensitive) roll `expression`.
+    pub fn insert(&mut self, name: &str, expression: &str) {
+        self.aliases
+            .insert(name.to_lowercase(), expression.to_string());
+    }
+
+    /// `command` with every word that names an alias replaced by its
+    /// expression.
+    pub fn expand(&self, command: &str) -> String {
+        if self.aliases.is_empty() {
+            return command.to_string();
+        }
+        command
+            .split_whitespace()
+            .map(|word| {
+                self.aliases
+                    .get(&word.to_lowercase())
+                    .map(String::as_str)
+                    .unwrap_or(word)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A command typed in the command input instead of a roll ("sync",
+/// "music next", "init goblin +2")
+///
+/// Each feature reads its command in its own module and runs it with a
+/// system taking `In<Self>`, added with `GameRollsAppExt::add_typed_command`.
+pub trait TypedCommand: Sized + Send + 'static {
+    /// Name of the command, for the log
+    const NAME: &'static str;
+
+    /// Read the command from the typed text; `None` when the text is another
+    /// command or a roll.
+    fn parse(text: &str) -> Option<Self>;
+}
+
+type CommandDispatch = Box<dyn Fn(&str, &mut Commands<'_, '_>) -> bool + Send + Sync>;
+
+/// The typed commands, tried in the order they were added; text none of
+/// them reads is rolled
+#[derive(Resource, Default)]
+pub struct TypedCommands {
+    commands: Vec<(&'static str, CommandDispatch)>,
+}
+
+impl TypedCommands {
+    /// Run the system `run` for text that `C` reads.
+    pub fn insert<C: TypedCommand>(&mut self, run: SystemId<In<C>>) {
+        let dispatch: CommandDispatch = Box::new(move |text, commands| match C::parse(text) {
+            Some(command) => {
+                commands.run_system_with(run, command);
+                true
+            }
+            None => false,
+        });
+        self.commands.push((C::NAME, dispatch));
+    }
+
+    /// Queue the run of the first command that reads `text`; `false` when
+    /// none does.
+    pub fn dispatch(&self, text: &str, commands: &mut Commands) -> bool {
+        self.commands.iter().any(|(name, dispatch)| {
+            let read = dispatch(text, commands);
+            if read {
+                debug!("Running the {} command: {}", name, text);
+            }
+            read
+        })
+    }
+}
+
+/// Names of the integrations added by extensions, for logs and the UI
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Integrations {
+    pub names: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_dice_aliases() {
+        let mut aliases = DiceAliases::default();
+        assert_eq!(
+            aliases.expand("2d6  --checkon stealth"),
+            "2d6  --checkon stealth"
+        );
+
+        aliases.insert("Stress", "1d10");
+        assert_eq!(
+            aliases.expand("stress --checkon wisdom"),
+            "1d10 --checkon wisdom"
+        );
+        assert_eq!(aliases.expand("STRESS"), "1d10");
+        assert_eq!(aliases.expand("2d6"), "2d6");
+    }
+}
@@ -0,0 +1,432 @@
+//! Field visibility of shared character sheets
+//!
+//! A sheet says which of its fields the other participants of a session
+//! see. The DM sees everything; players see a field, only a summary of it,
+//! or nothing. Hit points summarize as a state ("Healthy", "Bloodied",
+//! "Down") and rolls as their total without the dice, so a monster's sheet
+//! can be shared with the table without giving its numbers away:
+//!
+//! ```text
+//! visibility npc                 # HP as bloodied state, the rest DM only
+//! visibility skills everyone
+//! share players                  # the sheet as the players see it
+//! ```
+//!
+//! Anything that sends a sheet or a roll to other participants goes through
+//! `redact_sheet` / `SharedRoll::for_viewer` first.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{CharacterSheet, DiceType, HitPoints};
+
+/// Custom combat field the hit point state is shown in when hit points are
+/// summarized
+pub const HEALTH_STATE_FIELD: &str = "Health";
+
+/// Who a sheet or roll is shared with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Viewer {
+    Dm,
+    Players,
+}
+
+/// How much of a field players see
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldVisibility {
+    #[default]
+    Everyone,
+    /// A summary instead of the value (only hit points and rolls have one)
+    Summary,
+    DmOnly,
+}
+
+impl FieldVisibility {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldVisibility::Everyone => "everyone",
+            FieldVisibility::Summary => "summary",
+            FieldVisibility::DmOnly => "DM only",
+        }
+    }
+
+    /// "everyone", "summary" or "dm"
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "everyone" | "all" | "players" => Some(FieldVisibility::Everyone),
+            "summary" | "state" => Some(FieldVisibility::Summary),
+            "dm" | "dm-only" | "dmonly" | "hidden" => Some(FieldVisibility::DmOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Sheet fields with their own visibility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SheetField {
+    HitPoints,
+    ArmorClass,
+    Attributes,
+    SavingThrows,
+    Skills,
+    Spells,
+    Equipment,
+    Features,
+    Defenses,
+    Conditions,
+    Rolls,
+}
+
+impl SheetField {
+    pub const ALL: [SheetField; 11] = [
+        SheetField::HitPoints,
+        SheetField::ArmorClass,
+        SheetField::Attributes,
+        SheetField::SavingThrows,
+        SheetField::Skills,
+        SheetField::Spells,
+        SheetField::Equipment,
+        SheetField::Features,
+        SheetField::Defenses,
+        SheetField::Conditions,
+        SheetField::Rolls,
+    ];
+
+    /// The name used by the `visibility` command
+    pub fn name(&self) -> &'static str {
+        match self {
+            SheetField::HitPoints => "hp",
+            SheetField::ArmorClass => "ac",
+            SheetField::Attributes => "attributes",
+            SheetField::SavingThrows => "saves",
+            SheetField::Skills => "skills",
+            SheetField::Spells => "spells",
+            SheetField::Equipment => "equipment",
+            SheetField::Features => "features",
+            SheetField::Defenses => "defenses",
+            SheetField::Conditions => "conditions",
+            SheetField::Rolls => "rolls",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        let aliased = match text.as_str() {
+            "hitpoints" | "hit-points" | "health" => "hp",
+            "armor" | "armorclass" | "armor-class" => "ac",
+            "abilities" | "stats" => "attributes",
+            "savingthrows" | "saving-throws" => "saves",
+            "inventory" | "items" => "equipment",
+            "resistances" => "defenses",
+            other => other,
+        };
+        SheetField::ALL.into_iter().find(|f| f.name() == aliased)
+    }
+
+    /// Whether players can be shown a summary of the field
+    pub fn has_summary(&self) -> bool {
+        matches!(self, SheetField::HitPoints | SheetField::Rolls)
+    }
+}
+
+/// Per-field visibility of a sheet; fields not listed are seen by everyone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldPermissions {
+    fields: BTreeMap<SheetField, FieldVisibility>,
+}
+
+impl FieldPermissions {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, field: SheetField) -> FieldVisibility {
+        self.fields.get(&field).copied().unwrap_or_default()
+    }
+
+    /// Set a field's visibility. Only hit points and rolls can be summarized.
+    pub fn set(&mut self, field: SheetField, visibility: FieldVisibility) -> Result<(), String> {
+        if visibility == FieldVisibility::Summary && !field.has_summary() {
+            return Err(format!(
+                "Only hp and rolls have a summary; use everyone or dm for {}",
+                field.name()
+            ));
+        }
+        if visibility == FieldVisibility::Everyone {
+            self.fields.remove(&field);
+        } else {
+            self.fields.insert(field, visibility);
+        }
+        Ok(())
+    }
+
+    /// The usual monster setup: players see whether it is bloodied and the
+    /// totals of its rolls, and nothing else of its sheet.
+    pub fn npc_default() -> Self {
+        let fields = SheetField::ALL
+            .into_iter()
+            .map(|field| {
+                let visibility = if field.has_summary() {
+                    FieldVisibility::Summary
+                } else {
+                    FieldVisibility::DmOnly
+                };
+                (field, visibility)
+            })
+            .collect();
+        Self { fields }
+    }
+
+    /// "hp: summary, skills: DM only", or "everything is shared"
+    pub fn summary(&self) -> String {
+        if self.fields.is_empty() {
+            return "everything is shared".to_string();
+        }
+        self.fields
+            .iter()
+            .map(|(field, visibility)| format!("{}: {}", field.name(), visibility.name()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// "Healthy", "Bloodied" (at or below half) or "Down"
+pub fn hit_point_state(hit_points: &HitPoints) -> &'static str {
+    if hit_points.current <= 0 {
+        "Down"
+    } else if hit_points.current * 2 <= hit_points.maximum {
+        "Bloodied"
+    } else {
+        "Healthy"
+    }
+}
+
+/// The sheet as `viewer` may see it.
+///
+/// Hidden fields are emptied; summarized hit points are replaced by their
+/// state in the `Health` custom combat field. The permissions stay on the
+/// copy so the receiving side knows what was left out.
+pub fn redact_sheet(sheet: &CharacterSheet, viewer: Viewer) -> CharacterSheet {
+    let mut shared = sheet.clone();
+    let permissions = &sheet.field_visibility;
+    if viewer == Viewer::Dm || permissions.is_empty() {
+        return shared;
+    }
+    let hidden = |field| permissions.get(field) != FieldVisibility::Everyone;
+
+    match permissions.get(SheetField::HitPoints) {
+        FieldVisibility::Everyone => {}
+        visibility => {
+            let hit_points = shared.combat.hit_points.take();
+            shared.combat.hit_dice = None;
+            shared.combat.death_saves = None;
+            if let (FieldVisibility::Summary, Some(hp)) = (visibility, hit_points) {
+                shared.custom_combat.insert(
+                    HEALTH_STATE_FIELD.to_string(),
+                    hit_point_state(&hp).to_string(),
+                );
+            }
+        }
+    }
+    if hidden(SheetField::ArmorClass) {
+        shared.combat.armor_class = 0;
+    }
+    if hidden(SheetField::Attributes) {
+        shared.attributes = Default::default();
+        shared.modifiers = Default::default();
+        shared.custom_attributes.clear();
+    }
+    if hidden(SheetField::SavingThrows) {
+        shared.saving_throws.clear();
+    }
+    if hidden(SheetField::Skills) {
+        shared.skills.clear();
+        shared.tool_proficiencies.clear();
+    }
+    if hidden(SheetField::Spells) {
+        shared.spells = None;
+        shared.concentration = None;
+    }
+    if hidden(SheetField::Equipment) {
+        shared.equipment = None;
+    }
+    if hidden(SheetField::Features) {
+        shared.features.clear();
+        shared.combat.damage_riders.clear();
+    }
+    if hidden(SheetField::Defenses) {
+        shared.damage_defenses = Default::default();
+    }
+    if hidden(SheetField::Conditions) {
+        shared.conditions = Default::default();
+    }
+    shared
+}
+
+/// A roll as sent to the other participants of a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedRoll {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
+    /// "Stealth", "Longsword attack", or the typed dice
+    pub label: String,
+    #[serde(default)]
+    pub dice: Vec<(DiceType, u32)>,
+    #[serde(default)]
+    pub modifier: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i32>,
+    /// Body color of the roller's dice ("#C81E1E"), for replaying the throw
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl SharedRoll {
+    /// The roll as `viewer` may see it, `None` when they don't see it at all.
+    ///
+    /// A summarized roll keeps its label and total but not the dice and
+    /// modifier the total was made of.
+    pub fn for_viewer(&self, permissions: &FieldPermissions, viewer: Viewer) -> Option<Self> {
+        if viewer == Viewer::Dm {
+            return Some(self.clone());
+        }
+        match permissions.get(SheetField::Rolls) {
+            FieldVisibility::Everyone => Some(self.clone()),
+            FieldVisibility::Summary => Some(Self {
+                dice: Vec::new(),
+                modifier: 0,
+                ..self.clone()
+            }),
+            FieldVisibility::DmOnly => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monster() -> CharacterSheet {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Ogre".to_string();
+        sheet.combat.armor_class = 11;
+        sheet.combat.hit_points = Some(HitPoints {
+            current: 30,
+            maximum: 59,
+            temporary: 0,
+        });
+        sheet.attributes.strength = 19;
+        sheet
+            .skills
+            .insert("perception".to_string(), Default::default());
+        sheet
+    }
+
+    #[test]
+    fn test_hit_point_state() {
+        let hp = |current| HitPoints {
+            current,
+            maximum: 40,
+            temporary: 0,
+        };
+        assert_eq!(hit_point_state(&hp(40)), "Healthy");
+        assert_eq!(hit_point_state(&hp(21)), "Healthy");
+        assert_eq!(hit_point_state(&hp(20)), "Bloodied");
+        assert_eq!(hit_point_state(&hp(0)), "Down");
+    }
+
+    #[test]
+    fn test_only_hp_and_rolls_have_a_summary() {
+        let mut permissions = FieldPermissions::default();
+        assert!(permissions
+            .set(SheetField::Skills, FieldVisibility::Summary)
+            .is_err());
+        permissions
+            .set(SheetField::HitPoints, FieldVisibility::Summary)
+            .unwrap();
+        permissions
+            .set(SheetField::Skills, FieldVisibility::DmOnly)
+            .unwrap();
+        assert_eq!(permissions.summary(), "hp: summary, skills: DM only");
+
+        permissions
+            .set(SheetField::Skills, FieldVisibility::Everyone)
+            .unwrap();
+        permissions
+            .set(SheetField::HitPoints, FieldVisibility::Everyone)
+            .unwrap();
+        assert!(permissions.is_empty());
+        assert_eq!(SheetField::parse("Hit-Points"), Some(SheetField::HitPoints));
+        assert_eq!(FieldVisibility::parse("DM"), Some(FieldVisibility::DmOnly));
+    }
+
+    #[test]
+    fn test_players_see_bloodied_state_only() {
+        let mut sheet = monster();
+        sheet.field_visibility = FieldPermissions::npc_default();
+
+        let dm = redact_sheet(&sheet, Viewer::Dm);
+        assert_eq!(dm.combat.hit_points, sheet.combat.hit_points);
+
+        let players = redact_sheet(&sheet, Viewer::Players);
+        assert_eq!(players.character.name, "Ogre");
+        assert!(players.combat.hit_points.is_none());
+        assert_eq!(
+            players
+                .custom_combat
+                .get(HEALTH_STATE_FIELD)
+                .map(String::as_str),
+            Some("Healthy")
+        );
+        assert_eq!(players.combat.armor_class, 0);
+        assert_eq!(players.attributes.strength, 0);
+        assert!(players.skills.is_empty());
+        assert_eq!(players.field_visibility, sheet.field_visibility);
+    }
+
+    #[test]
+    fn test_sheets_without_permissions_are_shared_whole() {
+        let sheet = monster();
+        let players = redact_sheet(&sheet, Viewer::Players);
+        assert_eq!(players.combat.hit_points, sheet.combat.hit_points);
+        assert_eq!(players.skills.len(), 1);
+
+        let json = serde_json::to_value(&sheet).unwrap();
+        assert!(json.get("fieldVisibility").is_none());
+    }
+
+    #[test]
+    fn test_rolls_for_players() {
+        let roll = SharedRoll {
+            character: Some("Ogre".to_string()),
+            label: "Greatclub attack".to_string(),
+            dice: vec![(DiceType::D20, 14)],
+            modifier: 6,
+            total: Some(20),
+            color: None,
+        };
+        let mut permissions = FieldPermissions::default();
+        assert_eq!(
+            roll.for_viewer(&permissions, Viewer::Players),
+            Some(roll.clone())
+        );
+
+        permissions
+            .set(SheetField::Rolls, FieldVisibility::Summary)
+            .unwrap();
+        let summary = roll.for_viewer(&permissions, Viewer::Players).unwrap();
+        assert!(summary.dice.is_empty());
+        assert_eq!(summary.total, Some(20));
+
+        permissions
+            .set(SheetField::Rolls, FieldVisibility::DmOnly)
+            .unwrap();
+        assert!(roll.for_viewer(&permissions, Viewer::Players).is_none());
+        assert_eq!(roll.for_viewer(&permissions, Viewer::Dm), Some(roll));
+    }
+}
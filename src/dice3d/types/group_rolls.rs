@@ -0,0 +1,297 @@
+//! Group rolls of the Party tab
+//!
+//! The DM picks stored characters, a check and a DC on the Party tab; the
+//! [`GroupRollQueue`] then throws the check in the 3D scene, either one
+//! character after another (each roll waits for the previous one to be
+//! read) or as one combined throw with everybody's d20s in the box at once.
+//! Each character's result is compared with the DC, and the group passes
+//! when at least half of them succeed.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{group_check_passes, DiceConfig, DiceType, GroupCheck, GroupMember, GroupRoll};
+
+/// DC a new group check starts at
+pub const DEFAULT_GROUP_DC: i32 = 10;
+
+/// How a group check is thrown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupThrow {
+    /// One character's d20 after another
+    #[default]
+    OneByOne,
+    /// Every character's d20s in a single throw, handed out in order
+    Combined,
+}
+
+impl GroupThrow {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OneByOne => "One at a time",
+            Self::Combined => "All together",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::OneByOne => Self::Combined,
+            Self::Combined => Self::OneByOne,
+        }
+    }
+}
+
+/// What the Party tab is set up to roll
+#[derive(Resource, Debug, Clone)]
+pub struct PartySelection {
+    /// Ids of the selected characters
+    pub characters: Vec<i64>,
+    pub check: GroupCheck,
+    pub dc: i32,
+    pub throw: GroupThrow,
+}
+
+impl Default for PartySelection {
+    fn default() -> Self {
+        Self {
+            characters: Vec::new(),
+            check: GroupCheck::Skill {
+                skill: "perception",
+                with: None,
+            },
+            dc: DEFAULT_GROUP_DC,
+            throw: GroupThrow::default(),
+        }
+    }
+}
+
+impl PartySelection {
+    pub fn toggle(&mut self, id: i64) {
+        match self.characters.iter().position(|c| *c == id) {
+            Some(index) => {
+                self.characters.remove(index);
+            }
+            None => self.characters.push(id),
+        }
+    }
+
+    pub fn is_selected(&self, id: i64) -> bool {
+        self.characters.contains(&id)
+    }
+}
+
+/// One throw of a group check and the characters whose dice are in it
+#[derive(Debug, Clone)]
+pub struct GroupThrowStep {
+    pub config: DiceConfig,
+    pub members: Vec<GroupMember>,
+}
+
+/// The group check being thrown and the results read so far
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GroupRollQueue {
+    /// Label of the check ("Stealth")
+    pub label: String,
+    pub dc: i32,
+    pub steps: VecDeque<GroupThrowStep>,
+    /// The throw in the box, waiting to be read
+    pub in_flight: Option<GroupThrowStep>,
+    pub results: Vec<GroupRoll>,
+}
+
+impl GroupRollQueue {
+    /// Queue the throws of a group check.
+    pub fn start(
+        &mut self,
+        check: &GroupCheck,
+        dc: i32,
+        members: Vec<GroupMember>,
+        throw: GroupThrow,
+    ) {
+        let label = check.label();
+        // Characters whose conditions fail the check have nothing to throw
+        self.results.clear();
+        let (failed, members): (Vec<_>, Vec<_>) =
+            members.into_iter().partition(|member| member.auto_fail);
+        self.results
+            .extend(failed.iter().map(GroupMember::automatic_failure));
+        self.steps = match throw {
+            GroupThrow::OneByOne => members
+                .into_iter()
+                .map(|member| GroupThrowStep {
+                    config: DiceConfig {
+                        dice_to_roll: vec![DiceType::D20],
+                        modifier: member.modifier,
+                        modifier_name: format!("{}: {}", member.name, label),
+                        expression: None,
+                    }
+                    .with_advantage(member.advantage, member.disadvantage),
+                    members: vec![member],
+                })
+                .collect(),
+            GroupThrow::Combined if members.is_empty() => VecDeque::new(),
+            GroupThrow::Combined => {
+                let dice = members.iter().map(GroupMember::dice_count).sum();
+                VecDeque::from([GroupThrowStep {
+                    config: DiceConfig {
+                        dice_to_roll: vec![DiceType::D20; dice],
+                        modifier: 0,
+                        modifier_name: format!("Group {}", label),
+                        expression: None,
+                    },
+                    members,
+                }])
+            }
+        };
+        self.label = label;
+        self.dc = dc;
+        self.in_flight = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.steps.is_empty() || self.in_flight.is_some()
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.in_flight.is_some()
+    }
+
+    /// Take the next throw off the queue; it waits for [`Self::resolve`].
+    pub fn next_throw(&mut self) -> Option<DiceConfig> {
+        let step = self.steps.pop_front()?;
+        let config = step.config.clone();
+        self.in_flight = Some(step);
+        Some(config)
+    }
+
+    /// Read the throw in flight when a roll labelled `label` resolves,
+    /// handing its d20 faces to the characters in order. Returns whether
+    /// the roll was this queue's.
+    pub fn resolve(&mut self, label: &str, results: &[(DiceType, u32)]) -> bool {
+        let Some(step) = self
+            .in_flight
+            .take_if(|step| step.config.modifier_name == label)
+        else {
+            return false;
+        };
+        let mut faces = results
+            .iter()
+            .filter(|(die, _)| *die == DiceType::D20)
+            .map(|(_, value)| *value as i32);
+        for member in &step.members {
+            let thrown: Vec<i32> = faces.by_ref().take(member.dice_count()).collect();
+            self.results.push(member.resolve(&thrown));
+        }
+        true
+    }
+
+    /// Abandon the check, with the results read so far.
+    pub fn stop(&mut self) {
+        self.steps.clear();
+        self.in_flight = None;
+        self.results.clear();
+    }
+
+    /// Whether the group passed, once every throw has been read
+    pub fn passed(&self) -> Option<bool> {
+        (!self.is_running() && !self.results.is_empty())
+            .then(|| group_check_passes(&self.results, self.dc))
+    }
+
+    /// "3 of 4 passed DC 12: the group succeeds"
+    pub fn summary(&self) -> String {
+        let passed = self.results.iter().filter(|r| r.passes(self.dc)).count();
+        let mut summary = format!("{} of {} passed DC {}", passed, self.results.len(), self.dc);
+        match self.passed() {
+            Some(true) => summary.push_str(": the group succeeds"),
+            Some(false) => summary.push_str(": the group fails"),
+            None => {}
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, modifier: i32, advantage: bool) -> GroupMember {
+        GroupMember {
+            name: name.to_string(),
+            modifier,
+            advantage,
+            disadvantage: false,
+            auto_fail: false,
+        }
+    }
+
+    #[test]
+    fn test_combined_throw_hands_out_dice_in_order() {
+        let mut queue = GroupRollQueue::default();
+        let check = GroupCheck::parse("stealth").unwrap();
+        let members = vec![member("Vex", 5, true), member("Grog", -1, false)];
+        queue.start(&check, 12, members, GroupThrow::Combined);
+
+        let config = queue.next_throw().unwrap();
+        assert_eq!(config.dice_to_roll.len(), 3);
+        assert!(queue.next_throw().is_none());
+
+        // Another roll finishing in between is not ours
+        assert!(!queue.resolve("Perception", &[(DiceType::D20, 20)]));
+        let faces = [(DiceType::D20, 3), (DiceType::D20, 9), (DiceType::D20, 15)];
+        assert!(queue.resolve("Group Stealth", &faces));
+
+        assert_eq!(queue.results[0].total, 14);
+        assert_eq!(queue.results[0].dropped, Some(3));
+        assert_eq!(queue.results[1].total, 14);
+        assert_eq!(queue.passed(), Some(true));
+        assert_eq!(queue.summary(), "2 of 2 passed DC 12: the group succeeds");
+    }
+
+    #[test]
+    fn test_one_by_one_throws_each_character() {
+        let mut queue = GroupRollQueue::default();
+        let check = GroupCheck::Save("dexterity");
+        let members = vec![member("Vex", 5, true), member("Grog", -1, false)];
+        queue.start(&check, 15, members, GroupThrow::OneByOne);
+
+        let first = queue.next_throw().unwrap();
+        assert_eq!(first.modifier_name, "Vex: Dexterity Save");
+        assert_eq!(first.modifier, 5);
+        assert_eq!(first.dice_to_roll, vec![DiceType::D20, DiceType::D20]);
+        assert!(queue.resolve(
+            &first.modifier_name,
+            &[(DiceType::D20, 4), (DiceType::D20, 11)]
+        ));
+        assert_eq!(queue.passed(), None);
+
+        let second = queue.next_throw().unwrap();
+        assert!(queue.resolve(&second.modifier_name, &[(DiceType::D20, 8)]));
+        assert_eq!(queue.passed(), Some(true));
+    }
+
+    #[test]
+    fn test_automatic_failures_throw_nothing() {
+        let mut queue = GroupRollQueue::default();
+        let check = GroupCheck::Save("dexterity");
+        let mut paralyzed = member("Grog", 2, false);
+        paralyzed.auto_fail = true;
+        queue.start(
+            &check,
+            10,
+            vec![paralyzed.clone(), member("Vex", 5, false)],
+            GroupThrow::Combined,
+        );
+        assert_eq!(queue.results.len(), 1);
+        assert!(queue.results[0].auto_fail);
+        let config = queue.next_throw().unwrap();
+        assert_eq!(config.dice_to_roll.len(), 1);
+        assert!(queue.resolve(&config.modifier_name, &[(DiceType::D20, 12)]));
+        assert_eq!(queue.summary(), "1 of 2 passed DC 10: the group succeeds");
+
+        queue.start(&check, 10, vec![paralyzed], GroupThrow::OneByOne);
+        assert!(queue.next_throw().is_none());
+        assert_eq!(queue.passed(), Some(false));
+    }
+}
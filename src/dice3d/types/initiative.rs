@@ -0,0 +1,942 @@
+//! Initiative tracker types
+//!
+//! This module contains the turn-order model used by the initiative tracker:
+//! combatants, their initiative rolls, the current round/turn, and per-round
+//! reaction bookkeeping (reactions spent and readied actions). Casters keep
+//! their spell save DC so the other combatants can roll saves against it, and
+//! monsters can carry hit points for exporting the encounter to a VTT. An
+//! optional turn timer limits how long each turn may take and can keep a
+//! chess clock of everyone's total decision time. Surprise follows the
+//! campaign's ruleset: a lost first turn under the 2014 rules, initiative
+//! rolled with disadvantage under 2024. It also holds the in-game session
+//! clock used for timed effects such as ritual casting.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::ruleset::Ruleset;
+use super::HitPoints;
+
+/// A single creature taking part in combat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Combatant {
+    pub name: String,
+    pub initiative: i32,
+    /// Initiative bonus (the DEX modifier for stored characters), used for
+    /// rerolls and to break ties
+    #[serde(default)]
+    pub modifier: i32,
+    /// Database id of the character this combatant represents, if any
+    #[serde(default)]
+    pub character_id: Option<i64>,
+    /// Whether the creature has used its reaction since the start of its last turn
+    #[serde(default)]
+    pub reaction_used: bool,
+    /// Trigger/action description for a readied action, if one is held
+    #[serde(default)]
+    pub readied_action: Option<String>,
+    /// Spell save DC, for combatants that cast spells
+    #[serde(default)]
+    pub save_dc: Option<i32>,
+    /// The last saving throw rolled against a caster's DC
+    #[serde(default)]
+    pub last_save: Option<SaveAgainst>,
+    /// Hit points of a monster; stored characters keep theirs on the sheet
+    #[serde(default)]
+    pub hit_points: Option<HitPoints>,
+    /// Surprised under the 2014 rules: the creature loses its first turn
+    #[serde(default)]
+    pub surprised: bool,
+}
+
+impl Combatant {
+    pub fn new(name: impl Into<String>, initiative: i32) -> Self {
+        Self {
+            name: name.into(),
+            initiative,
+            modifier: 0,
+            character_id: None,
+            reaction_used: false,
+            readied_action: None,
+            save_dc: None,
+            last_save: None,
+            hit_points: None,
+            surprised: false,
+        }
+    }
+
+    /// A combatant with a fresh initiative roll (d20 + `modifier`).
+    pub fn rolled(name: impl Into<String>, modifier: i32, rng: &mut impl Rng) -> Self {
+        Self {
+            modifier,
+            ..Self::new(name, rng.random_range(1..=20) + modifier)
+        }
+    }
+
+    /// Reset per-turn markers. Called when this creature's turn begins.
+    pub fn start_turn(&mut self) {
+        self.reaction_used = false;
+        // A readied action that was not triggered before the creature's next
+        // turn is lost.
+        self.readied_action = None;
+    }
+}
+
+/// A saving throw rolled against a caster's spell save DC
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveAgainst {
+    pub caster: String,
+    /// Canonical ability name ("wisdom")
+    pub ability: String,
+    /// The d20 face
+    pub roll: i32,
+    pub modifier: i32,
+    pub dc: i32,
+}
+
+impl SaveAgainst {
+    pub fn total(&self) -> i32 {
+        self.roll + self.modifier
+    }
+
+    /// Meeting the DC saves
+    pub fn succeeded(&self) -> bool {
+        self.total() >= self.dc
+    }
+
+    /// e.g. "WIS save 12 vs Mage's DC 15: failed"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} save {} vs {}'s DC {}: {}",
+            super::character::ability_abbreviation(&self.ability),
+            self.total(),
+            self.caster,
+            self.dc,
+            if self.succeeded() { "saved" } else { "failed" }
+        )
+    }
+}
+
+/// Outcome of asking a combatant to spend its reaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReactionCheck {
+    /// The reaction was available and is now marked as spent
+    Available,
+    /// The reaction has already been used this round; the caller should prompt
+    /// before rolling anyway
+    AlreadySpent { name: String },
+    /// No combatant at the requested index
+    UnknownCombatant,
+}
+
+/// Resource tracking turn order for the current encounter
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InitiativeTracker {
+    pub combatants: Vec<Combatant>,
+    /// Index into `combatants` of the creature whose turn it is
+    pub current_turn: usize,
+    /// 1-based round counter; 0 means combat has not started
+    pub round: u32,
+    #[serde(default)]
+    pub timer: TurnTimerConfig,
+}
+
+impl InitiativeTracker {
+    /// Add a combatant and keep the list sorted by initiative (highest first).
+    ///
+    /// Ties keep insertion order so manually-ordered ties are preserved.
+    pub fn add_combatant(&mut self, combatant: Combatant) {
+        let current_name = self.current().map(|c| c.name.clone());
+        let pos = self
+            .combatants
+            .iter()
+            .position(|c| c.initiative < combatant.initiative)
+            .unwrap_or(self.combatants.len());
+        self.combatants.insert(pos, combatant);
+
+        // Keep the same creature active after re-sorting.
+        if let Some(name) = current_name {
+            if let Some(idx) = self.combatants.iter().position(|c| c.name == name) {
+                self.current_turn = idx;
+            }
+        }
+    }
+
+    pub fn remove_combatant(&mut self, index: usize) -> Option<Combatant> {
+        if index >= self.combatants.len() {
+            return None;
+        }
+        let removed = self.combatants.remove(index);
+        if index < self.current_turn {
+            self.current_turn -= 1;
+        }
+        if self.current_turn >= self.combatants.len() {
+            self.current_turn = 0;
+        }
+        Some(removed)
+    }
+
+    pub fn current(&self) -> Option<&Combatant> {
+        if self.round == 0 {
+            return None;
+        }
+        self.combatants.get(self.current_turn)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.round > 0 && !self.combatants.is_empty()
+    }
+
+    /// Begin combat at round 1 with the highest initiative acting first.
+    pub fn start_combat(&mut self) {
+        if self.combatants.is_empty() {
+            return;
+        }
+        self.round = 1;
+        self.current_turn = 0;
+        for combatant in self.combatants.iter_mut() {
+            combatant.reaction_used = false;
+            combatant.readied_action = None;
+        }
+        self.skip_surprised();
+        self.combatants[self.current_turn].start_turn();
+    }
+
+    pub fn end_combat(&mut self) {
+        self.round = 0;
+        self.current_turn = 0;
+    }
+
+    /// Advance to the next creature's turn, wrapping into a new round.
+    ///
+    /// The creature whose turn begins regains its reaction; surprised
+    /// creatures lose their turn.
+    pub fn advance_turn(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        self.next_turn();
+        self.skip_surprised();
+        self.combatants[self.current_turn].start_turn();
+    }
+
+    fn next_turn(&mut self) {
+        self.current_turn += 1;
+        if self.current_turn >= self.combatants.len() {
+            self.current_turn = 0;
+            self.round += 1;
+        }
+    }
+
+    /// Pass over surprised creatures; surprise ends once their lost turn
+    /// has gone by.
+    fn skip_surprised(&mut self) {
+        while self.combatants[self.current_turn].surprised {
+            self.combatants[self.current_turn].surprised = false;
+            self.next_turn();
+        }
+    }
+
+    /// Mark the combatant's reaction as spent.
+    pub fn use_reaction(&mut self, index: usize) -> ReactionCheck {
+        let Some(combatant) = self.combatants.get_mut(index) else {
+            return ReactionCheck::UnknownCombatant;
+        };
+        if combatant.reaction_used {
+            return ReactionCheck::AlreadySpent {
+                name: combatant.name.clone(),
+            };
+        }
+        combatant.reaction_used = true;
+        ReactionCheck::Available
+    }
+
+    /// Check whether an opportunity attack can be made by the combatant.
+    ///
+    /// Opportunity attacks cost a reaction; when it is already spent the
+    /// reaction is left untouched so the caller can prompt for confirmation.
+    pub fn request_opportunity_attack(&mut self, index: usize) -> ReactionCheck {
+        self.use_reaction(index)
+    }
+
+    /// Hold an action until the given trigger occurs.
+    pub fn ready_action(&mut self, index: usize, trigger: impl Into<String>) -> bool {
+        match self.combatants.get_mut(index) {
+            Some(combatant) => {
+                combatant.readied_action = Some(trigger.into());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take a readied action. This consumes the combatant's reaction.
+    pub fn trigger_readied_action(&mut self, index: usize) -> Option<String> {
+        let combatant = self.combatants.get_mut(index)?;
+        let action = combatant.readied_action.take()?;
+        combatant.reaction_used = true;
+        Some(action)
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<usize> {
+        self.combatants
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// `name`, numbered ("Goblin 2") when a combatant already has it.
+    pub fn unique_name(&self, name: &str) -> String {
+        let name = name.trim();
+        if self.find_by_name(name).is_none() {
+            return name.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} {}", name, n))
+            .find(|candidate| self.find_by_name(candidate).is_none())
+            .expect("some number is free")
+    }
+
+    /// Roll initiative for a new combatant and add it in turn order.
+    ///
+    /// Returns the combatant's initiative.
+    pub fn roll_combatant(
+        &mut self,
+        name: &str,
+        modifier: i32,
+        character_id: Option<i64>,
+        rng: &mut impl Rng,
+    ) -> i32 {
+        let mut combatant = Combatant::rolled(self.unique_name(name), modifier, rng);
+        combatant.character_id = character_id;
+        let initiative = combatant.initiative;
+        self.add_combatant(combatant);
+        initiative
+    }
+
+    /// Surprise the combatant with this name. Under the 2014 rules it loses
+    /// its first turn; under 2024 it rolls initiative with disadvantage
+    /// instead, keeping the lower of its roll and a second d20.
+    ///
+    /// Returns the combatant's initiative, `None` for an unknown name.
+    pub fn surprise(&mut self, name: &str, ruleset: Ruleset, rng: &mut impl Rng) -> Option<i32> {
+        let index = self.find_by_name(name)?;
+        if !ruleset.surprise_disadvantage() {
+            self.combatants[index].surprised = true;
+            return Some(self.combatants[index].initiative);
+        }
+        let mut combatant = self.remove_combatant(index)?;
+        let second = rng.random_range(1..=20) + combatant.modifier;
+        combatant.initiative = combatant.initiative.min(second);
+        let initiative = combatant.initiative;
+        self.add_combatant(combatant);
+        Some(initiative)
+    }
+
+    /// Set (or clear) the spell save DC of the combatant with this name.
+    pub fn set_save_dc(&mut self, name: &str, dc: Option<i32>) -> bool {
+        match self.find_by_name(name) {
+            Some(index) => {
+                self.combatants[index].save_dc = dc;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Give the combatant with this name full hit points (or none).
+    pub fn set_hit_points(&mut self, name: &str, maximum: Option<i32>) -> bool {
+        match self.find_by_name(name) {
+            Some(index) => {
+                self.combatants[index].hit_points = maximum.map(|maximum| HitPoints {
+                    current: maximum,
+                    maximum,
+                    temporary: 0,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Combatants with a spell save DC, with their index.
+    pub fn casters(&self) -> impl Iterator<Item = (usize, &Combatant)> {
+        self.combatants
+            .iter()
+            .enumerate()
+            .filter(|(_, combatant)| combatant.save_dc.is_some())
+    }
+
+    /// Record a saving throw by `target` against `caster`'s spell save DC,
+    /// given the d20 face and the target's save modifier.
+    ///
+    /// `None` when either index is unknown or the caster has no DC.
+    pub fn save_against(
+        &mut self,
+        target: usize,
+        caster: usize,
+        ability: &str,
+        modifier: i32,
+        roll: i32,
+    ) -> Option<SaveAgainst> {
+        let caster = self.combatants.get(caster)?;
+        let save = SaveAgainst {
+            caster: caster.name.clone(),
+            ability: ability.to_string(),
+            roll,
+            modifier,
+            dc: caster.save_dc?,
+        };
+        self.combatants.get_mut(target)?.last_save = Some(save.clone());
+        Some(save)
+    }
+
+    /// Whether a stored character is already in the turn order.
+    pub fn has_character(&self, character_id: i64) -> bool {
+        self.combatants
+            .iter()
+            .any(|c| c.character_id == Some(character_id))
+    }
+
+    /// Roll initiative again for everyone and re-sort, ending the current
+    /// combat. Ties go to the higher modifier.
+    pub fn reroll_all(&mut self, rng: &mut impl Rng) {
+        for combatant in self.combatants.iter_mut() {
+            combatant.initiative = rng.random_range(1..=20) + combatant.modifier;
+        }
+        self.combatants
+            .sort_by(|a, b| (b.initiative, b.modifier).cmp(&(a.initiative, a.modifier)));
+        self.end_combat();
+    }
+
+    /// Remove every combatant and end combat.
+    pub fn clear(&mut self) {
+        self.combatants.clear();
+        self.end_combat();
+    }
+}
+
+// ============================================================================
+// Turn Timer
+// ============================================================================
+
+/// Turn lengths the Initiative tab's timer button cycles through
+pub const TURN_TIMER_PRESETS: [u32; 4] = [30, 60, 90, 120];
+
+/// Seconds before the end of a timed turn that the warning sounds (at most
+/// half the turn)
+pub const TURN_WARNING_SECONDS: f32 = 10.0;
+
+/// How turns are timed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTimerConfig {
+    /// Seconds per turn; `None` leaves turns untimed
+    #[serde(default)]
+    pub limit_seconds: Option<u32>,
+    /// Move on to the next combatant when a turn runs out of time
+    #[serde(default)]
+    pub auto_advance: bool,
+    /// Keep each combatant's total decision time
+    #[serde(default)]
+    pub chess_clock: bool,
+}
+
+impl TurnTimerConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.limit_seconds.is_some() || self.chess_clock
+    }
+
+    /// The preset after the current limit: off, 30, 60, 90, 120, off...
+    ///
+    /// A limit set by command that is not a preset moves to the next larger
+    /// preset.
+    pub fn next_limit(&self) -> Option<u32> {
+        match self.limit_seconds {
+            None => Some(TURN_TIMER_PRESETS[0]),
+            Some(limit) => TURN_TIMER_PRESETS.iter().copied().find(|&p| p > limit),
+        }
+    }
+}
+
+/// Something the turn timer wants announced
+#[derive(Debug, Clone, PartialEq)]
+pub enum TurnTimerAlert {
+    /// The turn is about to run out of time
+    Warning { name: String, remaining: u32 },
+    /// The turn has run out of time
+    Expired { name: String },
+}
+
+/// Played when a timed turn is about to run out or has run out
+#[derive(Message, Clone, Debug)]
+pub struct TurnTimerWarning {
+    pub expired: bool,
+}
+
+/// Time taken on the current turn and, with the chess clock, by everyone in
+/// the combat so far
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TurnTimer {
+    /// Round and turn index being timed
+    turn: Option<(u32, usize)>,
+    /// Seconds spent on the current turn
+    pub elapsed: f32,
+    warned: bool,
+    expired: bool,
+    pub paused: bool,
+    /// Total decision time by combatant name
+    pub totals: HashMap<String, f32>,
+}
+
+impl TurnTimer {
+    /// Count `seconds` towards the current turn.
+    ///
+    /// A new turn restarts the count; a new combat also clears the chess
+    /// clock. Returns the warning or time-out of the turn once each.
+    pub fn tick(&mut self, tracker: &InitiativeTracker, seconds: f32) -> Option<TurnTimerAlert> {
+        let Some(current) = tracker.current() else {
+            self.turn = None;
+            self.elapsed = 0.0;
+            return None;
+        };
+        let turn = (tracker.round, tracker.current_turn);
+        if self.turn != Some(turn) {
+            if self.turn.is_none() && turn == (1, 0) {
+                self.totals.clear();
+            }
+            self.turn = Some(turn);
+            self.elapsed = 0.0;
+            self.warned = false;
+            self.expired = false;
+        }
+        if self.paused {
+            return None;
+        }
+
+        self.elapsed += seconds;
+        if tracker.timer.chess_clock {
+            *self.totals.entry(current.name.clone()).or_default() += seconds;
+        }
+
+        let limit = tracker.timer.limit_seconds? as f32;
+        let remaining = limit - self.elapsed;
+        if remaining <= 0.0 {
+            if self.expired {
+                return None;
+            }
+            self.expired = true;
+            self.warned = true;
+            return Some(TurnTimerAlert::Expired {
+                name: current.name.clone(),
+            });
+        }
+        if !self.warned && remaining <= TURN_WARNING_SECONDS.min(limit / 2.0) {
+            self.warned = true;
+            return Some(TurnTimerAlert::Warning {
+                name: current.name.clone(),
+                remaining: remaining.ceil() as u32,
+            });
+        }
+        None
+    }
+
+    /// Seconds left on a timed turn (never below zero).
+    pub fn remaining(&self, config: &TurnTimerConfig) -> Option<f32> {
+        config
+            .limit_seconds
+            .map(|limit| (limit as f32 - self.elapsed).max(0.0))
+    }
+
+    /// Chess clock total of a combatant.
+    pub fn total(&self, name: &str) -> f32 {
+        self.totals.get(name).copied().unwrap_or(0.0)
+    }
+}
+
+/// "1:05" for 65 seconds, rounded up so a running timer shows 0:00 only
+/// when it has run out
+pub fn format_clock(seconds: f32) -> String {
+    let whole = seconds.max(0.0).ceil() as u32;
+    format!("{}:{:02}", whole / 60, whole % 60)
+}
+
+// ============================================================================
+// Session Clock
+// ============================================================================
+
+/// In-game seconds that pass per combat round
+pub const SECONDS_PER_ROUND: f32 = 6.0;
+
+/// Casting a spell as a ritual takes 10 minutes longer than normal
+pub const RITUAL_CASTING_SECONDS: f32 = 600.0;
+
+/// A running in-game timer (e.g. a ritual being cast)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectTimer {
+    pub label: String,
+    pub remaining_seconds: f32,
+}
+
+/// Resource tracking in-game time for the session.
+///
+/// Outside of combat the clock follows real time; during combat it advances by
+/// [`SECONDS_PER_ROUND`] each time the initiative tracker starts a new round.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionClock {
+    pub elapsed_seconds: f32,
+    pub timers: Vec<EffectTimer>,
+}
+
+impl SessionClock {
+    /// Start a 10-minute ritual casting timer. Restarts the timer if the same
+    /// ritual is already in progress.
+    pub fn start_ritual(&mut self, spell: &str) {
+        let label = spell.trim().to_string();
+        self.timers
+            .retain(|t| !t.label.eq_ignore_ascii_case(&label));
+        self.timers.push(EffectTimer {
+            label,
+            remaining_seconds: RITUAL_CASTING_SECONDS,
+        });
+    }
+
+    /// Advance the clock, returning the labels of timers that completed.
+    pub fn advance(&mut self, seconds: f32) -> Vec<String> {
+        if seconds <= 0.0 {
+            return Vec::new();
+        }
+        self.elapsed_seconds += seconds;
+
+        let mut completed = Vec::new();
+        self.timers.retain_mut(|timer| {
+            timer.remaining_seconds -= seconds;
+            if timer.remaining_seconds <= 0.0 {
+                completed.push(timer.label.clone());
+                false
+            } else {
+                true
+            }
+        });
+        completed
+    }
+
+    pub fn advance_rounds(&mut self, rounds: u32) -> Vec<String> {
+        self.advance(rounds as f32 * SECONDS_PER_ROUND)
+    }
+
+    /// Stop a timer before it completes (e.g. the caster was interrupted).
+    ///
+    /// With no label, the most recently started timer is interrupted.
+    pub fn interrupt(&mut self, label: Option<&str>) -> Option<EffectTimer> {
+        let index = match label {
+            Some(label) => self
+                .timers
+                .iter()
+                .position(|t| t.label.eq_ignore_ascii_case(label.trim()))?,
+            None => self.timers.len().checked_sub(1)?,
+        };
+        Some(self.timers.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> InitiativeTracker {
+        let mut t = InitiativeTracker::default();
+        t.add_combatant(Combatant::new("Goblin", 12));
+        t.add_combatant(Combatant::new("Thorin", 18));
+        t.add_combatant(Combatant::new("Elara", 15));
+        t
+    }
+
+    #[test]
+    fn test_combatants_sorted_by_initiative() {
+        let t = tracker();
+        let names: Vec<_> = t.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Thorin", "Elara", "Goblin"]);
+    }
+
+    #[test]
+    fn test_advance_turn_wraps_round() {
+        let mut t = tracker();
+        t.start_combat();
+        assert_eq!(t.round, 1);
+        t.advance_turn();
+        t.advance_turn();
+        t.advance_turn();
+        assert_eq!(t.round, 2);
+        assert_eq!(t.current().unwrap().name, "Thorin");
+    }
+
+    #[test]
+    fn test_reaction_resets_on_own_turn() {
+        let mut t = tracker();
+        t.start_combat();
+        let goblin = t.find_by_name("goblin").unwrap();
+        assert_eq!(t.use_reaction(goblin), ReactionCheck::Available);
+
+        // Still spent on other creatures' turns
+        t.advance_turn();
+        assert!(t.combatants[goblin].reaction_used);
+
+        // Goblin's turn starts: reaction regained
+        t.advance_turn();
+        assert!(!t.combatants[goblin].reaction_used);
+    }
+
+    #[test]
+    fn test_opportunity_attack_with_spent_reaction() {
+        let mut t = tracker();
+        t.start_combat();
+        assert_eq!(t.request_opportunity_attack(1), ReactionCheck::Available);
+        assert_eq!(
+            t.request_opportunity_attack(1),
+            ReactionCheck::AlreadySpent {
+                name: "Elara".to_string()
+            }
+        );
+        assert_eq!(
+            t.request_opportunity_attack(9),
+            ReactionCheck::UnknownCombatant
+        );
+    }
+
+    #[test]
+    fn test_readied_action_consumes_reaction() {
+        let mut t = tracker();
+        t.start_combat();
+        assert!(t.ready_action(0, "Attack when the goblin approaches"));
+        t.advance_turn();
+        assert_eq!(
+            t.trigger_readied_action(0).as_deref(),
+            Some("Attack when the goblin approaches")
+        );
+        assert!(t.combatants[0].reaction_used);
+        assert!(t.trigger_readied_action(0).is_none());
+    }
+
+    #[test]
+    fn test_unused_readied_action_expires_on_turn_start() {
+        let mut t = tracker();
+        t.start_combat();
+        t.ready_action(0, "Cast when door opens");
+        t.advance_turn();
+        t.advance_turn();
+        t.advance_turn();
+        assert!(t.combatants[0].readied_action.is_none());
+    }
+
+    #[test]
+    fn test_roll_combatant_numbers_duplicate_names() {
+        let mut t = InitiativeTracker::default();
+        let mut rng = rand::rng();
+        for _ in 0..3 {
+            let initiative = t.roll_combatant("Goblin", 2, None, &mut rng);
+            assert!((3..=22).contains(&initiative));
+        }
+        t.roll_combatant("Thorin", 1, Some(7), &mut rng);
+
+        let mut names: Vec<_> = t.combatants.iter().map(|c| c.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Goblin", "Goblin 2", "Goblin 3", "Thorin"]);
+        assert!(t.has_character(7));
+        assert!(t
+            .combatants
+            .windows(2)
+            .all(|pair| pair[0].initiative >= pair[1].initiative));
+    }
+
+    #[test]
+    fn test_save_against_caster_dc() {
+        let mut t = tracker();
+        assert!(t.set_save_dc("elara", Some(15)));
+        assert!(!t.set_save_dc("Nobody", Some(12)));
+        let casters: Vec<_> = t.casters().map(|(i, c)| (i, c.name.as_str())).collect();
+        assert_eq!(casters, vec![(1, "Elara")]);
+
+        let goblin = t.find_by_name("Goblin").unwrap();
+        let save = t.save_against(goblin, 1, "wisdom", -1, 15).unwrap();
+        assert!(!save.succeeded());
+        assert_eq!(save.describe(), "WIS save 14 vs Elara's DC 15: failed");
+        assert_eq!(t.combatants[goblin].last_save, Some(save));
+        assert!(t
+            .save_against(goblin, 1, "wisdom", 0, 15)
+            .unwrap()
+            .succeeded());
+
+        // Thorin casts nothing, so there's no DC to save against
+        assert!(t.save_against(goblin, 0, "wisdom", 0, 20).is_none());
+    }
+
+    #[test]
+    fn test_reroll_all_sorts_and_ends_combat() {
+        let mut t = tracker();
+        t.combatants[2].modifier = 5;
+        t.start_combat();
+        t.advance_turn();
+
+        t.reroll_all(&mut rand::rng());
+        assert_eq!(t.round, 0);
+        assert_eq!(t.combatants.len(), 3);
+        assert!(t.combatants.windows(2).all(|pair| {
+            (pair[0].initiative, pair[0].modifier) >= (pair[1].initiative, pair[1].modifier)
+        }));
+
+        t.clear();
+        assert!(t.combatants.is_empty());
+        assert!(!t.is_active());
+    }
+
+    #[test]
+    fn test_surprise_by_ruleset() {
+        // 2014: Thorin and Goblin lose their first turns
+        let mut t = tracker();
+        let mut rng = rand::rng();
+        assert_eq!(t.surprise("thorin", Ruleset::Rules2014, &mut rng), Some(18));
+        assert_eq!(t.surprise("Goblin", Ruleset::Rules2014, &mut rng), Some(12));
+        assert_eq!(t.surprise("Nobody", Ruleset::Rules2014, &mut rng), None);
+        t.start_combat();
+        assert_eq!(t.current().unwrap().name, "Elara");
+        t.advance_turn();
+        assert_eq!((t.round, t.current().unwrap().name.as_str()), (2, "Thorin"));
+        t.advance_turn();
+        t.advance_turn();
+        assert_eq!((t.round, t.current().unwrap().name.as_str()), (2, "Goblin"));
+
+        // 2024: initiative with disadvantage, and no lost turn
+        let mut t = tracker();
+        let initiative = t.surprise("Thorin", Ruleset::Rules2024, &mut rng).unwrap();
+        assert!(initiative <= 18);
+        assert!(t.combatants.iter().all(|c| !c.surprised));
+        assert!(t
+            .combatants
+            .windows(2)
+            .all(|pair| pair[0].initiative >= pair[1].initiative));
+    }
+
+    fn timed(limit: Option<u32>, chess_clock: bool) -> InitiativeTracker {
+        let mut t = tracker();
+        t.timer = TurnTimerConfig {
+            limit_seconds: limit,
+            auto_advance: false,
+            chess_clock,
+        };
+        t.start_combat();
+        t
+    }
+
+    #[test]
+    fn test_turn_timer_warns_then_expires_once() {
+        let t = timed(Some(30), false);
+        let mut timer = TurnTimer::default();
+        assert_eq!(timer.tick(&t, 15.0), None);
+        assert_eq!(
+            timer.tick(&t, 6.0),
+            Some(TurnTimerAlert::Warning {
+                name: "Thorin".to_string(),
+                remaining: 9
+            })
+        );
+        assert_eq!(timer.tick(&t, 5.0), None);
+        assert_eq!(
+            timer.tick(&t, 5.0),
+            Some(TurnTimerAlert::Expired {
+                name: "Thorin".to_string()
+            })
+        );
+        assert_eq!(timer.tick(&t, 5.0), None);
+        assert_eq!(timer.remaining(&t.timer), Some(0.0));
+    }
+
+    #[test]
+    fn test_turn_timer_restarts_each_turn_and_pauses() {
+        let mut t = timed(Some(60), false);
+        let mut timer = TurnTimer::default();
+        timer.tick(&t, 20.0);
+        t.advance_turn();
+        timer.tick(&t, 1.0);
+        assert_eq!(timer.elapsed, 1.0);
+
+        timer.paused = true;
+        assert_eq!(timer.tick(&t, 100.0), None);
+        assert_eq!(timer.remaining(&t.timer), Some(59.0));
+
+        // A short limit warns half way through
+        t.timer.limit_seconds = Some(10);
+        timer.paused = false;
+        assert!(matches!(
+            timer.tick(&t, 4.5),
+            Some(TurnTimerAlert::Warning { remaining: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_chess_clock_totals_reset_with_new_combat() {
+        let mut t = timed(None, true);
+        let mut timer = TurnTimer::default();
+        timer.tick(&t, 12.0);
+        t.advance_turn();
+        timer.tick(&t, 3.0);
+        t.advance_turn();
+        t.advance_turn();
+        timer.tick(&t, 8.0);
+        assert_eq!(timer.total("Thorin"), 20.0);
+        assert_eq!(timer.total("Elara"), 3.0);
+        assert_eq!(timer.total("Goblin"), 0.0);
+
+        t.end_combat();
+        assert_eq!(timer.tick(&t, 1.0), None);
+        t.start_combat();
+        timer.tick(&t, 1.0);
+        assert_eq!(timer.total("Thorin"), 1.0);
+        assert_eq!(timer.total("Elara"), 0.0);
+    }
+
+    #[test]
+    fn test_turn_timer_presets_and_clock_format() {
+        let mut config = TurnTimerConfig::default();
+        let mut cycle = Vec::new();
+        for _ in 0..5 {
+            config.limit_seconds = config.next_limit();
+            cycle.push(config.limit_seconds);
+        }
+        assert_eq!(cycle, [Some(30), Some(60), Some(90), Some(120), None]);
+        config.limit_seconds = Some(45);
+        assert_eq!(config.next_limit(), Some(60));
+
+        assert_eq!(format_clock(65.0), "1:05");
+        assert_eq!(format_clock(0.2), "0:01");
+        assert_eq!(format_clock(-3.0), "0:00");
+    }
+
+    #[test]
+    fn test_ritual_completes_after_ten_minutes() {
+        let mut clock = SessionClock::default();
+        clock.start_ritual("Detect Magic");
+        assert!(clock.advance(599.0).is_empty());
+        assert_eq!(clock.advance(1.0), vec!["Detect Magic".to_string()]);
+        assert!(clock.timers.is_empty());
+    }
+
+    #[test]
+    fn test_ritual_advances_by_rounds() {
+        let mut clock = SessionClock::default();
+        clock.start_ritual("Alarm");
+        assert!(clock.advance_rounds(99).is_empty());
+        assert_eq!(clock.advance_rounds(1), vec!["Alarm".to_string()]);
+    }
+
+    #[test]
+    fn test_ritual_interrupt() {
+        let mut clock = SessionClock::default();
+        clock.start_ritual("Identify");
+        clock.start_ritual("Alarm");
+        assert_eq!(clock.interrupt(Some("identify")).unwrap().label, "Identify");
+        assert_eq!(clock.interrupt(None).unwrap().label, "Alarm");
+        assert!(clock.interrupt(None).is_none());
+    }
+}
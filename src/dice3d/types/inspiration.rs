@@ -0,0 +1,150 @@
+//! Table inspiration
+//!
+//! In a shared session the DM hands out inspiration from a table-wide
+//! [`InspirationPool`] (`inspire <character>`); the grant goes to the table
+//! server's subscribers, and the player whose character it names gets the
+//! inspiration on their sheet. Spending it (`--inspiration` on a roll) gives
+//! the roll advantage.
+//!
+//! The pool is kept per profile in the database's settings table under
+//! [`INSPIRATION_POOL_SETTING_KEY`].
+
+use serde::{Deserialize, Serialize};
+
+use super::CharacterSheet;
+
+/// Database setting holding the table's inspiration pool
+pub const INSPIRATION_POOL_SETTING_KEY: &str = "inspiration_pool";
+
+/// Inspiration in a new pool
+pub const DEFAULT_INSPIRATION_POOL: u32 = 3;
+
+/// Inspiration the DM can still hand out, and who got it so far
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InspirationPool {
+    pub available: u32,
+    /// Characters given inspiration from this pool, oldest first
+    #[serde(default)]
+    pub granted: Vec<String>,
+}
+
+impl Default for InspirationPool {
+    fn default() -> Self {
+        Self {
+            available: DEFAULT_INSPIRATION_POOL,
+            granted: Vec::new(),
+        }
+    }
+}
+
+impl InspirationPool {
+    /// Refill the pool to `available` and forget earlier grants.
+    pub fn reset(&mut self, available: u32) {
+        self.available = available;
+        self.granted.clear();
+    }
+
+    /// Take one inspiration from the pool for `character`.
+    pub fn grant(&mut self, character: &str) -> Result<InspirationGrant, String> {
+        let character = character.trim();
+        if character.is_empty() {
+            return Err("Name the character to inspire".to_string());
+        }
+        if self.available == 0 {
+            return Err("The table's inspiration pool is empty".to_string());
+        }
+        self.available -= 1;
+        self.granted.push(character.to_string());
+        Ok(InspirationGrant {
+            character: character.to_string(),
+            remaining: self.available,
+        })
+    }
+
+    /// "2 inspiration left (given to Thorin, Elara)"
+    pub fn summary(&self) -> String {
+        if self.granted.is_empty() {
+            format!("{} inspiration left", self.available)
+        } else {
+            format!(
+                "{} inspiration left (given to {})",
+                self.available,
+                self.granted.join(", ")
+            )
+        }
+    }
+}
+
+/// Inspiration the DM gave a character, as sent to the table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InspirationGrant {
+    pub character: String,
+    /// Inspiration left in the pool after this grant
+    pub remaining: u32,
+}
+
+impl InspirationGrant {
+    /// Whether the grant is for `sheet`'s character.
+    pub fn is_for(&self, sheet: &CharacterSheet) -> bool {
+        self.character
+            .eq_ignore_ascii_case(sheet.character.name.trim())
+    }
+}
+
+impl CharacterSheet {
+    /// Use up the character's inspiration for advantage on a roll.
+    pub fn spend_inspiration(&mut self) -> Result<(), String> {
+        if !self.inspiration {
+            return Err(format!(
+                "{} has no inspiration to spend",
+                self.character.name
+            ));
+        }
+        self.inspiration = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_grants_until_empty() {
+        let mut pool = InspirationPool::default();
+        pool.reset(2);
+        assert_eq!(
+            pool.grant(" Thorin ").unwrap(),
+            InspirationGrant {
+                character: "Thorin".to_string(),
+                remaining: 1,
+            }
+        );
+        assert_eq!(pool.grant("Elara").unwrap().remaining, 0);
+        assert!(pool.grant("Gimli").is_err());
+        assert!(pool.grant("").is_err());
+        assert_eq!(
+            pool.summary(),
+            "0 inspiration left (given to Thorin, Elara)"
+        );
+
+        pool.reset(3);
+        assert_eq!(pool.summary(), "3 inspiration left");
+    }
+
+    #[test]
+    fn test_spend_inspiration() {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Thorin".to_string();
+        assert!(sheet.spend_inspiration().is_err());
+
+        let grant = InspirationGrant {
+            character: "thorin".to_string(),
+            remaining: 2,
+        };
+        assert!(grant.is_for(&sheet));
+        sheet.inspiration = true;
+        assert!(sheet.spend_inspiration().is_ok());
+        assert!(!sheet.inspiration);
+    }
+}
@@ -0,0 +1,394 @@
+//! Keyboard shortcuts and remapping them
+//!
+//! Each `KeyAction` has a default `KeyChord` (a key with Ctrl, Shift and Alt
+//! held or not); the Keys tab of the settings changes or clears it, and only
+//! the changed bindings are kept in the settings. A chord fires when its key
+//! is pressed with exactly its modifiers, so Ctrl+Tab and Ctrl+Shift+Tab are
+//! different shortcuts. Two actions on the same chord are listed as
+//! conflicts.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Something a keyboard shortcut does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyAction {
+    /// Throw the dice in the container again
+    Reroll,
+    /// Put the dice back on the floor
+    ClearDice,
+    NextTab,
+    PreviousTab,
+    /// Switch between the dice box and the dice cup
+    ToggleContainer,
+    OpenSettings,
+    /// Press the quick roll panel's button at this position (1-9)
+    QuickRoll(u8),
+}
+
+impl KeyAction {
+    /// Every action, in the order the Keys tab lists them
+    pub const ALL: [KeyAction; 15] = [
+        KeyAction::Reroll,
+        KeyAction::ClearDice,
+        KeyAction::NextTab,
+        KeyAction::PreviousTab,
+        KeyAction::ToggleContainer,
+        KeyAction::OpenSettings,
+        KeyAction::QuickRoll(1),
+        KeyAction::QuickRoll(2),
+        KeyAction::QuickRoll(3),
+        KeyAction::QuickRoll(4),
+        KeyAction::QuickRoll(5),
+        KeyAction::QuickRoll(6),
+        KeyAction::QuickRoll(7),
+        KeyAction::QuickRoll(8),
+        KeyAction::QuickRoll(9),
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            KeyAction::Reroll => "Throw the dice again".to_string(),
+            KeyAction::ClearDice => "Clear the dice".to_string(),
+            KeyAction::NextTab => "Next tab".to_string(),
+            KeyAction::PreviousTab => "Previous tab".to_string(),
+            KeyAction::ToggleContainer => "Switch box / cup".to_string(),
+            KeyAction::OpenSettings => "Open settings".to_string(),
+            KeyAction::QuickRoll(n) => format!("Quick roll {}", n),
+        }
+    }
+
+    /// The shortcut the action has until it is remapped.
+    pub fn default_chord(&self) -> Option<KeyChord> {
+        let chord = match self {
+            KeyAction::Reroll => KeyChord::new(KeyCode::Space),
+            KeyAction::ClearDice => KeyChord::new(KeyCode::KeyR),
+            KeyAction::NextTab => KeyChord::new(KeyCode::Tab).ctrl(),
+            KeyAction::PreviousTab => KeyChord::new(KeyCode::Tab).ctrl().shift(),
+            KeyAction::ToggleContainer => KeyChord::new(KeyCode::KeyC),
+            KeyAction::OpenSettings => KeyChord::new(KeyCode::Comma).ctrl(),
+            KeyAction::QuickRoll(n) => KeyChord::new(*DIGITS.get(*n as usize)?),
+        };
+        Some(chord)
+    }
+}
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Names of the keys a shortcut can use, as they are shown and typed
+const KEY_NAMES: [(&str, KeyCode); 71] = [
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Quote", KeyCode::Quote),
+    ("Minus", KeyCode::Minus),
+    ("Equal", KeyCode::Equal),
+    ("BracketLeft", KeyCode::BracketLeft),
+    ("BracketRight", KeyCode::BracketRight),
+];
+
+/// The name of a key shortcuts can use, `None` for other keys.
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, code)| *code == key)
+        .map(|(name, _)| *name)
+}
+
+/// A key pressed with exactly these modifiers, e.g. `Ctrl+Shift+Tab`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Parse `Ctrl+Shift+R`, `alt+f4` or `Space`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let key = KEY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, code)| *code)
+            .ok_or_else(|| format!("'{}' is not a key shortcuts can use", key))?;
+
+        let mut chord = Self::new(key);
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" | "option" => chord.alt = true,
+                _ => return Err(format!("'{}' is not Ctrl, Shift or Alt", modifier)),
+            }
+        }
+        Ok(chord)
+    }
+
+    /// The chord pressed this frame: `key` with the modifiers held now.
+    pub fn pressed_with(key: KeyCode, keyboard: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            key,
+            ctrl: keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            shift: keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+
+    /// The key was pressed this frame with exactly the chord's modifiers.
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>) -> bool {
+        keyboard.just_pressed(self.key) && Self::pressed_with(self.key, keyboard) == *self
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", key_name(self.key).unwrap_or("?"))
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text)
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+/// An action remapped away from its default; no chord leaves it unbound
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: KeyAction,
+    #[serde(default)]
+    pub chord: Option<KeyChord>,
+}
+
+/// The shortcuts that differ from the defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    #[serde(default)]
+    pub remapped: Vec<KeyBinding>,
+}
+
+impl KeyBindings {
+    /// The action's shortcut, `None` when it has none.
+    pub fn chord(&self, action: KeyAction) -> Option<KeyChord> {
+        match self.remapped.iter().find(|b| b.action == action) {
+            Some(binding) => binding.chord,
+            None => action.default_chord(),
+        }
+    }
+
+    /// Give the action a shortcut, or none.
+    pub fn set(&mut self, action: KeyAction, chord: Option<KeyChord>) {
+        self.remapped.retain(|b| b.action != action);
+        if chord != action.default_chord() {
+            self.remapped.push(KeyBinding { action, chord });
+        }
+    }
+
+    /// Put the action back on its default shortcut.
+    pub fn reset(&mut self, action: KeyAction) {
+        self.remapped.retain(|b| b.action != action);
+    }
+
+    /// The action's shortcut was pressed this frame.
+    pub fn just_pressed(&self, action: KeyAction, keyboard: &ButtonInput<KeyCode>) -> bool {
+        self.chord(action)
+            .is_some_and(|chord| chord.just_pressed(keyboard))
+    }
+
+    /// The other actions on the same shortcut as `action`.
+    pub fn conflicts(&self, action: KeyAction) -> Vec<KeyAction> {
+        let Some(chord) = self.chord(action) else {
+            return Vec::new();
+        };
+        KeyAction::ALL
+            .iter()
+            .copied()
+            .filter(|other| *other != action && self.chord(*other) == Some(chord))
+            .collect()
+    }
+
+    /// The action's shortcut as shown in the Keys tab.
+    pub fn describe(&self, action: KeyAction) -> String {
+        self.chord(action)
+            .map(|chord| chord.to_string())
+            .unwrap_or_else(|| "None".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_show_chords() {
+        let chord = KeyChord::parse("ctrl + shift + tab").unwrap();
+        assert_eq!(chord, KeyChord::new(KeyCode::Tab).ctrl().shift());
+        assert_eq!(chord.to_string(), "Ctrl+Shift+Tab");
+        assert_eq!(KeyChord::parse("Alt+F4").unwrap().to_string(), "Alt+F4");
+        assert_eq!(KeyChord::parse("r").unwrap(), KeyChord::new(KeyCode::KeyR));
+
+        assert!(KeyChord::parse("Hyper+R").is_err());
+        assert!(KeyChord::parse("Ctrl+").is_err());
+        assert!(KeyChord::parse("NoSuchKey").is_err());
+    }
+
+    #[test]
+    fn test_remap_and_conflicts() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.describe(KeyAction::ClearDice), "R");
+        assert_eq!(bindings.describe(KeyAction::QuickRoll(3)), "3");
+        assert!(KeyAction::ALL
+            .iter()
+            .all(|action| bindings.conflicts(*action).is_empty()));
+
+        // Clear the dice with 3, which quick roll 3 already uses
+        bindings.set(KeyAction::ClearDice, Some(KeyChord::new(KeyCode::Digit3)));
+        assert_eq!(
+            bindings.conflicts(KeyAction::ClearDice),
+            vec![KeyAction::QuickRoll(3)]
+        );
+        assert_eq!(
+            bindings.conflicts(KeyAction::QuickRoll(3)),
+            vec![KeyAction::ClearDice]
+        );
+
+        bindings.set(KeyAction::QuickRoll(3), None);
+        assert_eq!(bindings.describe(KeyAction::QuickRoll(3)), "None");
+        assert!(bindings.conflicts(KeyAction::ClearDice).is_empty());
+
+        // Only changed bindings are kept
+        bindings.set(KeyAction::NextTab, Some(KeyChord::new(KeyCode::Tab).ctrl()));
+        assert_eq!(bindings.remapped.len(), 2);
+        bindings.reset(KeyAction::ClearDice);
+        assert_eq!(
+            bindings.chord(KeyAction::ClearDice),
+            Some(KeyChord::new(KeyCode::KeyR))
+        );
+
+        let json = serde_json::to_string(&bindings).unwrap();
+        assert!(json.contains("\"quickRoll\":3"));
+        let loaded: KeyBindings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, bindings);
+    }
+}
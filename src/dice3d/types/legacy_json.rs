@@ -0,0 +1,204 @@
+//! Legacy `dnd_stats.json` character files
+//!
+//! Before characters were kept in the database, the app and the CLI read a
+//! single character sheet from a `dnd_stats.json` file. On first run the
+//! migration assistant on the character screen looks for those files in the
+//! working directory and the Documents folder, previews the characters they
+//! hold and imports them into the database. Files once imported or dismissed
+//! are remembered in the `legacy_json_migration` setting and not offered
+//! again.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_character_file, CharacterSheet};
+
+/// File name of the legacy character sheet.
+pub const LEGACY_JSON_FILE: &str = "dnd_stats.json";
+
+/// `setting` key holding the [`LegacyJsonMigration`].
+pub const LEGACY_JSON_SETTING_KEY: &str = "legacy_json_migration";
+
+/// Legacy files the migration assistant has already dealt with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacyJsonMigration {
+    /// Paths of the files imported or dismissed
+    #[serde(default)]
+    pub handled: Vec<String>,
+}
+
+impl LegacyJsonMigration {
+    pub fn is_handled(&self, path: &Path) -> bool {
+        self.handled.contains(&path_key(path))
+    }
+
+    pub fn mark_handled(&mut self, path: &Path) {
+        let key = path_key(path);
+        if !self.handled.contains(&key) {
+            self.handled.push(key);
+        }
+    }
+}
+
+/// The path a file is remembered by, the same however it was found.
+fn path_key(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// The user's Documents folder, if it can be resolved.
+pub fn documents_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(documents) = std::env::var("XDG_DOCUMENTS_DIR") {
+            return Some(PathBuf::from(documents));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    let home = std::env::var("USERPROFILE");
+    #[cfg(not(target_os = "windows"))]
+    let home = std::env::var("HOME");
+
+    home.ok().map(|home| PathBuf::from(home).join("Documents"))
+}
+
+/// Folders searched for legacy files: the working directory and Documents.
+pub fn legacy_json_search_dirs() -> Vec<PathBuf> {
+    std::env::current_dir()
+        .ok()
+        .into_iter()
+        .chain(documents_dir())
+        .collect()
+}
+
+/// The legacy files in `dirs`, each file listed once.
+pub fn find_legacy_json_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let file = dir.join(LEGACY_JSON_FILE);
+        if file.is_file() && !files.iter().any(|f| path_key(f) == path_key(&file)) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// A legacy file found on disk and the character it holds.
+#[derive(Debug, Clone)]
+pub struct LegacyJsonCandidate {
+    pub path: PathBuf,
+    /// The character, or why the file couldn't be read
+    pub sheet: Result<CharacterSheet, String>,
+}
+
+impl LegacyJsonCandidate {
+    /// Read the character in a legacy file.
+    pub fn load(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            sheet: read_character_file(path).map(|file| file.sheet),
+        }
+    }
+
+    /// One line describing the character:
+    /// "Thorin · Level 5 Dwarf Fighter · AC 16 · HP 38/44".
+    pub fn preview(&self) -> String {
+        let sheet = match &self.sheet {
+            Ok(sheet) => sheet,
+            Err(e) => return e.clone(),
+        };
+        let info = &sheet.character;
+        let hit_points = sheet
+            .combat
+            .hit_points
+            .as_ref()
+            .map(|hp| format!("HP {}/{}", hp.current, hp.maximum))
+            .unwrap_or_else(|| "HP -".to_string());
+        format!(
+            "{} · Level {} {} {} · AC {} · {}",
+            info.name, info.level, info.race, info.class, sheet.combat.armor_class, hit_points
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "dndgamerolls-legacy-json-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn test_sheet() -> CharacterSheet {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Thorin".to_string();
+        sheet.character.race = "Dwarf".to_string();
+        sheet.character.class = "Fighter".to_string();
+        sheet.character.level = 5;
+        sheet.combat.armor_class = 16;
+        sheet
+    }
+
+    #[test]
+    fn test_finds_and_previews_legacy_files() {
+        let with_file = temp_dir("found");
+        let without_file = temp_dir("empty");
+        std::fs::write(
+            with_file.join(LEGACY_JSON_FILE),
+            serde_json::to_string(&test_sheet()).unwrap(),
+        )
+        .unwrap();
+
+        // The same folder twice lists its file once
+        let files =
+            find_legacy_json_files(&[with_file.clone(), without_file.clone(), with_file.join(".")]);
+        assert_eq!(files, vec![with_file.join(LEGACY_JSON_FILE)]);
+
+        let candidate = LegacyJsonCandidate::load(&files[0]);
+        assert_eq!(
+            candidate.preview(),
+            "Thorin · Level 5 Dwarf Fighter · AC 16 · HP -"
+        );
+
+        std::fs::write(with_file.join(LEGACY_JSON_FILE), r#"{"name":"Thorin"}"#).unwrap();
+        let candidate = LegacyJsonCandidate::load(&files[0]);
+        assert!(candidate.sheet.is_err());
+        assert!(candidate.preview().contains("character: missing"));
+
+        let _ = std::fs::remove_dir_all(&with_file);
+        let _ = std::fs::remove_dir_all(&without_file);
+    }
+
+    #[test]
+    fn test_handled_files_are_remembered() {
+        let dir = temp_dir("handled");
+        let file = dir.join(LEGACY_JSON_FILE);
+        std::fs::write(&file, "{}").unwrap();
+
+        let mut migration = LegacyJsonMigration::default();
+        assert!(!migration.is_handled(&file));
+        migration.mark_handled(&dir.join(".").join(LEGACY_JSON_FILE));
+        migration.mark_handled(&file);
+        assert!(migration.is_handled(&file));
+        assert_eq!(migration.handled.len(), 1);
+
+        let json = serde_json::to_string(&migration).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LegacyJsonMigration>(&json).unwrap(),
+            migration
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
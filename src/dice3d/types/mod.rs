@@ -1,34 +1,281 @@
 //! Type definitions for the DnD Game Rolls 3D dice simulator
 //!
 //! This module is organized into submodules:
+//! - `achievements` - Local achievements unlocked by rolling
+//! - `automation` - Automation rules and the event bus they listen to
 //! - `dice` - Dice types, components, and roll state
+//! - `dice_expression` - Compound dice expressions (`2d6+1d8+3`), from
+//!   `dndgamerolls-core`
+//! - `dice_gather` - Lining up the dice of a read roll at the front of the
+//!   container
+//! - `dice_macros` - Recorded and defined roll macros and their hotkeys
+//! - `dice_pool` - Game systems that count successes in a dice pool
+//! - `dice_sounds` - Generated dice knock, settle and natural 20 sounds
 //! - `ui` - UI components for text displays, tabs, and controls
+//! - `ui_focus` - Keyboard and gamepad focus and the focus ring
 //! - `camera` - Camera-related components
 //! - `character` - Character sheet data structures and file management
+//! - `character_creation` - 4d6-drop-lowest, standard array and point-buy
+//!   ability scores for new characters
+//! - `character_templates` - SRD and homebrew race, class and background
+//!   templates for new characters
+//! - `creation_options` - Backgrounds, starting equipment packs and 1st-level
+//!   spells offered by the creation wizard
+//! - `character_file` - Shareable `.dndchar` character files
+//! - `cloud_sync` - Syncing characters with a remote SurrealDB server
+//! - `conditions` - Conditions and exhaustion, and the advantage or
+//!   disadvantage they give d20 tests, from `dndgamerolls-core`
+//! - `currency` - Coins, and adding, spending and exchanging them, from
+//!   `dndgamerolls-core`
+//! - `dc_check` - Checks against a DC and contested checks
+//! - `damage_riders` - Sneak Attack, Divine Smite, Hunter's Mark and custom
+//!   extra damage on weapon hits, from `dndgamerolls-core`
+//! - `container_validation` - Test drops checking a custom container model
+//!   for gaps and flat spots
+//! - `damage_types` - Standard and campaign-defined damage types, and damage
+//!   resistances, immunities and vulnerabilities, from `dndgamerolls-core`
+//! - `ddb_import` - Importing D&D Beyond character exports, from
+//!   `dndgamerolls-core`
+//! - `field_permissions` - Which sheet fields and rolls other participants
+//!   see
+//! - `suggested_rolls` - Rolls likely to come next, learned from the saved
+//!   rolls
+//! - `table_server` - WebSocket server broadcasting rolls to the table
+//!   (`--serve`, not in the wasm32 build)
+//! - `table_client` - Receiving another player's table server rolls
+//!   (`--join`, not in the wasm32 build)
+//! - `foundry` - Foundry VTT actor import and export, from
+//!   `dndgamerolls-core`
 //! - `database` - SQLite database for persistent character storage
+//!   (`web_database` in the wasm32 build, saved to IndexedDB)
+//! - `db_migrations` - Versioned upgrades of old database records and
+//!   `db doctor`
 //! - `settings` - Application settings and persistence
+//! - `theme_tokens` - Widget color tokens and saved custom themes
+//! - `encounter` - Rating encounters by the party's XP thresholds
+//! - `encryption` - Passphrase-based encryption of character sheets at rest
+//! - `extensions` - Tabs, dice aliases, typed commands and integrations
+//!   added by other crates
+//! - `profiles` - Database profiles (isolated characters, settings and history)
+//! - `proficiency_grid` - Bulk editing of skill and saving throw proficiencies
+//! - `qr_transfer` - Character transfer through QR codes
+//! - `quiz` - Probability quiz questions and estimation accuracy
+//! - `result_order` - The order multi-die results are listed in
+//! - `roll_events` - Roll lifecycle events (requested, spawned, settled, resolved)
+//! - `roll_rng` - The rolls' random number generator, seeded by `--seed`
+//! - `roll_stats` - Saved rolls and the statistics built from them
+//! - `ruleset` - The campaign's rules edition (2014 or 2024), from
+//!   `dndgamerolls-core`
+//! - `safety_tools` - Session-zero safety tools: the X-card, lines and veils
 //! - `icons` - Icon assets and icon button components
+//! - `key_bindings` - Remappable keyboard shortcuts
+//! - `initiative` - Initiative tracker turn order and reaction tracking
+//! - `legacy_json` - Finding and previewing legacy `dnd_stats.json` files to
+//!   import
+//! - `monsters` - The SRD monster compendium and the rolls of its stat blocks
+//! - `inspiration` - The table's inspiration pool and spending inspiration
+//! - `music` - Background music playlist and crossfades
+//! - `physics_repro` - Repro files of stuck and mis-read throws
+//! - `vtt_export` - Encounter export to Foundry VTT and Improved Initiative
+//! - `inventory` - Inventory items, attunement, carrying capacity and
+//!   encumbrance, from `dndgamerolls-core`
+//! - `party` - Saved parties and the group checks their members roll
+//! - `group_rolls` - The Party tab's selection and the queue throwing its
+//!   group checks in the 3D scene
+//! - `travel` - Travel mode passive Perception and group Stealth
+//! - `treasure` - DMG treasure hoards by challenge rating and the party's
+//!   treasure ledger
+//! - `weapon_mastery` - Weapon mastery properties of the 2024 rules, from
+//!   `dndgamerolls-core`
+//! - `weapon_properties` - Weapon properties, the attacks they give and the
+//!   ammunition they spend, from `dndgamerolls-core`
+//! - `tween` - Keyframed transform tweens and easing curves
 //! - `contributors` - GitHub contributors data and display
+//!
+//! Only the character, database, dice and file types build without the `gui`
+//! feature; the rest are Bevy resources and components.
 
+pub mod achievements;
+#[cfg(feature = "gui")]
+pub mod automation;
+#[cfg(feature = "gui")]
 pub mod camera;
 pub mod character;
+pub mod character_creation;
+pub mod character_file;
+pub mod character_templates;
+pub mod cloud_sync;
+pub use dndgamerolls_core::conditions;
+#[cfg(feature = "gui")]
+pub mod container_validation;
+#[cfg(feature = "gui")]
 pub mod contributors;
+pub mod creation_options;
+pub use dndgamerolls_core::currency;
+pub use dndgamerolls_core::damage_riders;
+pub use dndgamerolls_core::damage_types;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod database;
+#[cfg(target_arch = "wasm32")]
+#[path = "web_database.rs"]
 pub mod database;
+pub mod db_migrations;
+pub mod dc_check;
+pub use dndgamerolls_core::ddb_import;
 pub mod dice;
+pub use dndgamerolls_core::dice_expression;
+#[cfg(feature = "gui")]
 pub mod dice_fx;
+#[cfg(feature = "gui")]
+pub mod dice_gather;
+pub mod dice_macros;
+pub mod dice_pool;
+pub mod dice_sounds;
+pub mod encounter;
+pub mod encryption;
+#[cfg(feature = "gui")]
+pub mod extensions;
+pub mod field_permissions;
+pub use dndgamerolls_core::foundry;
+#[cfg(feature = "gui")]
+pub mod group_rolls;
+#[cfg(feature = "gui")]
 pub mod icons;
+#[cfg(feature = "gui")]
+pub mod initiative;
+pub mod inspiration;
+pub use dndgamerolls_core::inventory;
+#[cfg(feature = "gui")]
+pub mod key_bindings;
+pub mod legacy_json;
+pub mod monsters;
+#[cfg(feature = "gui")]
+pub mod music;
+pub mod party;
+#[cfg(feature = "gui")]
+pub mod physics_repro;
+pub mod proficiency_grid;
+pub mod profiles;
+pub mod qr_transfer;
+pub mod quiz;
+pub mod result_order;
+#[cfg(feature = "gui")]
+pub mod roll_events;
+#[cfg(feature = "gui")]
+pub mod roll_rng;
+pub mod roll_stats;
+pub use dndgamerolls_core::ruleset;
+pub mod safety_tools;
+#[cfg(feature = "gui")]
 pub mod settings;
 pub mod sqlite_conversion;
+pub mod suggested_rolls;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod table_client;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod table_server;
+#[cfg(feature = "gui")]
+pub mod theme_tokens;
+pub mod travel;
+pub mod treasure;
+#[cfg(feature = "gui")]
+pub mod tween;
+#[cfg(feature = "gui")]
 pub mod ui;
+#[cfg(feature = "gui")]
+pub mod ui_focus;
+#[cfg(feature = "gui")]
+pub mod vtt_export;
+pub use dndgamerolls_core::weapon_mastery;
+pub use dndgamerolls_core::weapon_properties;
 
 // Re-export all public types for convenient access
+pub use achievements::*;
+#[cfg(feature = "gui")]
+pub use automation::*;
+#[cfg(feature = "gui")]
 pub use camera::*;
 pub use character::*;
+pub use character_creation::*;
+pub use character_file::*;
+pub use character_templates::*;
+pub use cloud_sync::*;
+pub use conditions::*;
+#[cfg(feature = "gui")]
+pub use container_validation::*;
+#[cfg(feature = "gui")]
 pub use contributors::*;
+pub use creation_options::*;
+pub use currency::*;
+pub use damage_riders::*;
+pub use damage_types::*;
 pub use database::*;
+pub use db_migrations::*;
+pub use dc_check::*;
+pub use ddb_import::*;
 pub use dice::*;
+pub use dice_expression::*;
+#[cfg(feature = "gui")]
 pub use dice_fx::*;
+#[cfg(feature = "gui")]
+pub use dice_gather::*;
+pub use dice_macros::*;
+pub use dice_pool::*;
+pub use dice_sounds::*;
+pub use encounter::*;
+pub use encryption::*;
+#[cfg(feature = "gui")]
+pub use extensions::*;
+pub use field_permissions::*;
+pub use foundry::*;
+#[cfg(feature = "gui")]
+pub use group_rolls::*;
+#[cfg(feature = "gui")]
 pub use icons::*;
+#[cfg(feature = "gui")]
+pub use initiative::*;
+pub use inspiration::*;
+pub use inventory::*;
+#[cfg(feature = "gui")]
+pub use key_bindings::*;
+pub use legacy_json::*;
+pub use monsters::*;
+#[cfg(feature = "gui")]
+pub use music::*;
+pub use party::*;
+#[cfg(feature = "gui")]
+pub use physics_repro::*;
+pub use proficiency_grid::*;
+pub use profiles::*;
+pub use qr_transfer::*;
+pub use quiz::*;
+pub use result_order::*;
+#[cfg(feature = "gui")]
+pub use roll_events::*;
+#[cfg(feature = "gui")]
+pub use roll_rng::*;
+pub use roll_stats::*;
+pub use ruleset::*;
+pub use safety_tools::*;
+#[cfg(feature = "gui")]
 pub use settings::*;
 pub use sqlite_conversion::*;
+pub use suggested_rolls::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use table_client::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use table_server::*;
+#[cfg(feature = "gui")]
+pub use theme_tokens::*;
+pub use travel::*;
+pub use treasure::*;
+#[cfg(feature = "gui")]
+pub use tween::*;
+#[cfg(feature = "gui")]
 pub use ui::*;
+#[cfg(feature = "gui")]
+pub use ui_focus::*;
+#[cfg(feature = "gui")]
+pub use vtt_export::*;
+pub use weapon_mastery::*;
+pub use weapon_properties::*;
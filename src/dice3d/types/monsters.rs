@@ -0,0 +1,388 @@
+//! SRD monster compendium
+//!
+//! Stat blocks of common monsters from the System Reference Document 5.1,
+//! bundled as a RON asset (`assets/srd_monsters.ron`). The Monsters tab and
+//! the CLI's `lookup monster` command search them by name and roll a
+//! monster's attacks, their damage and its saving throws.
+
+#[cfg(feature = "gui")]
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::character::{ability_abbreviation, normalize_ability_name, ABILITY_NAMES};
+use super::dice::{DiceConfig, DiceType};
+use super::dice_expression::DamageRoll;
+
+/// Credit the SRD asks for wherever its content is shown
+pub const SRD_ATTRIBUTION: &str = "Monsters from the System Reference Document 5.1 by Wizards of the Coast LLC, licensed under CC BY 4.0.";
+
+/// A trait or action of a stat block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonsterFeature {
+    pub name: String,
+    pub text: String,
+}
+
+/// An action; attacks have a to-hit bonus, and most deal damage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonsterAction {
+    pub name: String,
+    pub text: String,
+    #[serde(default)]
+    pub attack_bonus: Option<i32>,
+    /// Main damage roll with its type ("1d6+2 slashing")
+    #[serde(default)]
+    pub damage: Option<String>,
+}
+
+impl MonsterAction {
+    pub fn damage_roll(&self) -> Option<Result<DamageRoll, String>> {
+        self.damage.as_deref().map(DamageRoll::parse)
+    }
+
+    /// Whether the action has anything to roll
+    pub fn is_rollable(&self) -> bool {
+        self.attack_bonus.is_some() || self.damage.is_some()
+    }
+}
+
+/// A monster's stat block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Monster {
+    pub name: String,
+    pub size: String,
+    /// Creature type ("humanoid (goblinoid)")
+    pub kind: String,
+    pub alignment: String,
+    pub armor_class: i32,
+    #[serde(default)]
+    pub armor: Option<String>,
+    pub hit_points: i32,
+    pub hit_dice: String,
+    pub speed: String,
+    /// STR, DEX, CON, INT, WIS, CHA
+    pub abilities: [i32; 6],
+    /// Saving throw bonuses the stat block lists, by ability
+    #[serde(default)]
+    pub saves: BTreeMap<String, i32>,
+    #[serde(default)]
+    pub skills: BTreeMap<String, i32>,
+    #[serde(default)]
+    pub damage_vulnerabilities: String,
+    #[serde(default)]
+    pub damage_resistances: String,
+    #[serde(default)]
+    pub damage_immunities: String,
+    #[serde(default)]
+    pub condition_immunities: String,
+    pub senses: String,
+    pub languages: String,
+    /// Challenge rating ("1/4", "5")
+    pub cr: String,
+    pub xp: u32,
+    #[serde(default)]
+    pub traits: Vec<MonsterFeature>,
+    #[serde(default)]
+    pub actions: Vec<MonsterAction>,
+}
+
+impl Monster {
+    /// "Small humanoid (goblinoid), neutral evil"
+    pub fn type_line(&self) -> String {
+        format!("{} {}, {}", self.size, self.kind, self.alignment)
+    }
+
+    /// "15 (leather armor, shield)"
+    pub fn armor_line(&self) -> String {
+        match &self.armor {
+            Some(armor) => format!("{} ({})", self.armor_class, armor),
+            None => self.armor_class.to_string(),
+        }
+    }
+
+    /// "1/4 (50 XP)"
+    pub fn challenge_line(&self) -> String {
+        format!("{} ({} XP)", self.cr, self.xp)
+    }
+
+    pub fn score(&self, ability: &str) -> Option<i32> {
+        let ability = normalize_ability_name(ability)?;
+        let index = ABILITY_NAMES.iter().position(|a| *a == ability)?;
+        Some(self.abilities[index])
+    }
+
+    pub fn ability_modifier(&self, ability: &str) -> Option<i32> {
+        self.score(ability).map(|score| (score - 10).div_euclid(2))
+    }
+
+    /// The listed save bonus, or the ability modifier for saves the stat
+    /// block leaves out.
+    pub fn save_bonus(&self, ability: &str) -> Option<i32> {
+        let ability = normalize_ability_name(ability)?;
+        self.saves
+            .get(ability)
+            .copied()
+            .or_else(|| self.ability_modifier(ability))
+    }
+
+    /// "STR 8 (-1)" for each ability
+    pub fn ability_scores(&self) -> Vec<String> {
+        ABILITY_NAMES
+            .iter()
+            .zip(self.abilities)
+            .map(|(ability, score)| {
+                format!(
+                    "{} {} ({:+})",
+                    ability_abbreviation(ability),
+                    score,
+                    (score - 10).div_euclid(2)
+                )
+            })
+            .collect()
+    }
+
+    /// The stat block's optional lines, label first: saves, skills,
+    /// defenses, senses and languages.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if !self.saves.is_empty() {
+            let saves: Vec<String> = ABILITY_NAMES
+                .iter()
+                .filter_map(|ability| {
+                    let bonus = self.saves.get(*ability)?;
+                    Some(format!("{} {:+}", ability_abbreviation(ability), bonus))
+                })
+                .collect();
+            details.push(("Saving Throws", saves.join(", ")));
+        }
+        if !self.skills.is_empty() {
+            let skills: Vec<String> = self
+                .skills
+                .iter()
+                .map(|(skill, bonus)| format!("{} {:+}", capitalize(skill), bonus))
+                .collect();
+            details.push(("Skills", skills.join(", ")));
+        }
+        for (label, value) in [
+            ("Damage Vulnerabilities", &self.damage_vulnerabilities),
+            ("Damage Resistances", &self.damage_resistances),
+            ("Damage Immunities", &self.damage_immunities),
+            ("Condition Immunities", &self.condition_immunities),
+        ] {
+            if !value.is_empty() {
+                details.push((label, value.clone()));
+            }
+        }
+        details.push(("Senses", self.senses.clone()));
+        details.push(("Languages", self.languages.clone()));
+        details
+    }
+
+    /// The rollable action named `name` (case-insensitive, or the only one
+    /// starting with it).
+    pub fn action(&self, name: &str) -> Result<&MonsterAction, String> {
+        let wanted = name.trim().to_lowercase();
+        if let Some(action) = self
+            .rollable_actions()
+            .find(|a| a.name.to_lowercase() == wanted)
+        {
+            return Ok(action);
+        }
+        let matches: Vec<&MonsterAction> = self
+            .rollable_actions()
+            .filter(|a| a.name.to_lowercase().starts_with(&wanted))
+            .collect();
+        match matches.as_slice() {
+            [action] => Ok(action),
+            _ => Err(format!(
+                "{} has no action '{}'; try {}",
+                self.name,
+                name.trim(),
+                self.rollable_actions()
+                    .map(|a| a.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    pub fn rollable_actions(&self) -> impl Iterator<Item = &MonsterAction> {
+        self.actions.iter().filter(|a| a.is_rollable())
+    }
+
+    /// The d20 attack roll of `action`, if it is an attack.
+    pub fn attack_config(&self, action: &MonsterAction) -> Option<DiceConfig> {
+        let bonus = action.attack_bonus?;
+        Some(DiceConfig {
+            dice_to_roll: vec![DiceType::D20],
+            modifier: bonus,
+            modifier_name: format!("{}: {}", self.name, action.name),
+            expression: None,
+        })
+    }
+
+    /// The damage roll of `action`, if it deals damage.
+    pub fn damage_config(&self, action: &MonsterAction) -> Option<Result<DiceConfig, String>> {
+        Some(action.damage_roll()?.map(|damage| {
+            let (dice_to_roll, expression) = DiceConfig::from_expression(&damage.expression);
+            DiceConfig {
+                dice_to_roll,
+                modifier: 0,
+                modifier_name: match &damage.damage_type {
+                    Some(kind) => format!("{}: {} ({})", self.name, action.name, kind),
+                    None => format!("{}: {}", self.name, action.name),
+                },
+                expression,
+            }
+        }))
+    }
+
+    /// The saving throw of `ability`.
+    pub fn save_config(&self, ability: &str) -> Option<DiceConfig> {
+        let bonus = self.save_bonus(ability)?;
+        Some(DiceConfig {
+            dice_to_roll: vec![DiceType::D20],
+            modifier: bonus,
+            modifier_name: format!("{}: {} Save", self.name, ability_abbreviation(ability)),
+            expression: None,
+        })
+    }
+}
+
+/// Compendium of monster stat blocks, sorted by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct MonsterCompendium {
+    pub monsters: Vec<Monster>,
+}
+
+impl MonsterCompendium {
+    /// The bundled SRD monsters.
+    pub fn srd() -> Self {
+        let text = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/assets/srd_monsters.ron"
+        ));
+        Self::parse(text).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse the SRD monsters: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut compendium: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        compendium.monsters.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(compendium)
+    }
+
+    /// Monsters whose name or type contains `query` (case-insensitive),
+    /// names starting with it first. An empty query lists every monster.
+    pub fn search(&self, query: &str) -> Vec<&Monster> {
+        let query = query.trim().to_lowercase();
+        let mut found: Vec<&Monster> = self
+            .monsters
+            .iter()
+            .filter(|m| m.name.to_lowercase().contains(&query) || m.kind.contains(&query))
+            .collect();
+        found.sort_by_key(|m| !m.name.to_lowercase().starts_with(&query));
+        found
+    }
+
+    /// The monster named `name`, or the only one the name matches.
+    pub fn find(&self, name: &str) -> Result<&Monster, String> {
+        if let Some(monster) = self
+            .monsters
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name.trim()))
+        {
+            return Ok(monster);
+        }
+        match self.search(name).as_slice() {
+            [monster] => Ok(monster),
+            [] => Err(format!("No monster named '{}'", name.trim())),
+            several => Err(format!(
+                "'{}' matches {}",
+                name.trim(),
+                several
+                    .iter()
+                    .map(|m| m.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// "perception" -> "Perception"
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srd_monsters_parse() {
+        let compendium = MonsterCompendium::srd();
+        assert!(compendium.monsters.len() >= 20);
+        for monster in &compendium.monsters {
+            for action in &monster.actions {
+                if let Some(damage) = action.damage_roll() {
+                    assert!(damage.is_ok(), "{}: {}", monster.name, action.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_and_find() {
+        let compendium = MonsterCompendium::srd();
+        let goblin = compendium.find("goblin").unwrap();
+        assert_eq!(
+            goblin.type_line(),
+            "Small humanoid (goblinoid), neutral evil"
+        );
+        assert_eq!(
+            compendium.find("red drag").unwrap().name,
+            "Young Red Dragon"
+        );
+        // "Wolf" and "Dire Wolf" both match; the exact name wins
+        assert_eq!(compendium.find("wolf").unwrap().name, "Wolf");
+        assert!(compendium.find("o").is_err());
+        assert!(compendium.find("tarrasque").is_err());
+
+        let undead = compendium.search("undead");
+        assert!(undead.iter().any(|m| m.name == "Zombie"));
+        assert_eq!(compendium.search("gob")[0].name, "Goblin");
+    }
+
+    #[test]
+    fn test_monster_rolls() {
+        let compendium = MonsterCompendium::srd();
+        let goblin = compendium.find("Goblin").unwrap();
+
+        let scimitar = goblin.action("scim").unwrap();
+        let attack = goblin.attack_config(scimitar).unwrap();
+        assert_eq!(attack.dice_to_roll, vec![DiceType::D20]);
+        assert_eq!(attack.modifier, 4);
+        assert_eq!(attack.modifier_name, "Goblin: Scimitar");
+
+        let damage = goblin.damage_config(scimitar).unwrap().unwrap();
+        assert_eq!(damage.dice_to_roll, vec![DiceType::D6]);
+        assert_eq!(damage.modifier_name, "Goblin: Scimitar (slashing)");
+        assert!(goblin.action("bite").is_err());
+
+        // Saves the stat block lists, and the ability modifier otherwise
+        let dragon = compendium.find("Young Red Dragon").unwrap();
+        assert_eq!(dragon.save_bonus("con"), Some(9));
+        assert_eq!(dragon.save_bonus("strength"), Some(6));
+        assert_eq!(goblin.save_config("dex").unwrap().modifier, 2);
+        assert_eq!(goblin.save_bonus("luck"), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Background music
+//!
+//! The Audio settings tab points at a folder of tracks (MP3, Ogg Vorbis or
+//! WAV). They play one after another for the whole session and start over
+//! at the end, in folder order or reshuffled on every pass. A new track
+//! fades in while the previous one fades out; `music next` in the command
+//! input skips ahead the same way.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// File extensions read from the music folder
+pub const MUSIC_EXTENSIONS: [&str; 3] = ["mp3", "ogg", "wav"];
+
+/// Seconds a new track takes to fade in over the previous one
+pub const MUSIC_CROSSFADE_SECONDS: f32 = 3.0;
+
+/// The tracks of a music folder, sorted by file name.
+pub fn scan_music_folder(folder: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(folder)
+        .map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?;
+    let mut tracks: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && is_music_file(path))
+        .collect();
+    tracks.sort();
+    if tracks.is_empty() {
+        return Err(format!("No music tracks in {}", folder.display()));
+    }
+    Ok(tracks)
+}
+
+/// Whether a path has one of the `MUSIC_EXTENSIONS`.
+pub fn is_music_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            MUSIC_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// "Tavern Night" for ".../Tavern Night.mp3"
+pub fn track_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Gain of a track fading in, `elapsed` seconds after it started.
+///
+/// Equal-power: together with `fade_out_gain` the loudness stays level
+/// through the crossfade.
+pub fn fade_in_gain(elapsed: f32) -> f32 {
+    let t = (elapsed / MUSIC_CROSSFADE_SECONDS).clamp(0.0, 1.0);
+    (t * std::f32::consts::FRAC_PI_2).sin()
+}
+
+/// Gain of a track fading out, `elapsed` seconds after the fade started.
+pub fn fade_out_gain(elapsed: f32) -> f32 {
+    let t = (elapsed / MUSIC_CROSSFADE_SECONDS).clamp(0.0, 1.0);
+    (t * std::f32::consts::FRAC_PI_2).cos()
+}
+
+/// Play order of the tracks of the music folder
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MusicPlaylist {
+    tracks: Vec<PathBuf>,
+    /// Track indices of the current pass
+    order: Vec<usize>,
+    /// Position in `order` of the next track
+    next: usize,
+    current: Option<usize>,
+}
+
+impl MusicPlaylist {
+    pub fn new(tracks: Vec<PathBuf>) -> Self {
+        Self {
+            tracks,
+            ..Default::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// The track playing, if any.
+    pub fn current(&self) -> Option<&Path> {
+        self.current.map(|i| self.tracks[i].as_path())
+    }
+
+    /// Move on to the next track, starting a new pass after the last one.
+    ///
+    /// With `shuffle` each pass is in a new random order, which never starts
+    /// with the track that just ended.
+    pub fn advance(&mut self, shuffle: bool, rng: &mut impl Rng) -> Option<&Path> {
+        if self.tracks.is_empty() {
+            return None;
+        }
+        if self.next >= self.order.len() {
+            self.order = (0..self.tracks.len()).collect();
+            if shuffle {
+                self.order.shuffle(rng);
+                if self.order.len() > 1 && self.current == Some(self.order[0]) {
+                    self.order.swap(0, 1);
+                }
+            }
+            self.next = 0;
+        }
+        self.current = Some(self.order[self.next]);
+        self.next += 1;
+        self.current()
+    }
+
+    /// Drop the current track (it could not be read); the next `advance`
+    /// continues with the track after it.
+    pub fn remove_current(&mut self) {
+        let Some(removed) = self.current.take() else {
+            return;
+        };
+        self.tracks.remove(removed);
+        self.order.retain(|&i| i != removed);
+        for i in &mut self.order {
+            if *i > removed {
+                *i -= 1;
+            }
+        }
+        self.next = self.next.saturating_sub(1);
+    }
+}
+
+/// The music folder being played and its playlist
+#[derive(Resource, Default)]
+pub struct MusicPlayer {
+    pub playlist: MusicPlaylist,
+    /// Folder the playlist was read from, `None` until one is read
+    pub folder: Option<String>,
+    /// `music next` was typed: fade over to the next track
+    pub skip_requested: bool,
+}
+
+/// A music track entity, playing or fading out
+#[derive(Component, Debug, Clone)]
+pub struct MusicTrack {
+    pub name: String,
+    /// Seconds since the track started
+    pub elapsed: f32,
+    /// Seconds since the track started fading out
+    pub fade_out: Option<f32>,
+}
+
+impl MusicTrack {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            elapsed: 0.0,
+            fade_out: None,
+        }
+    }
+
+    /// Crossfade gain, before the music volume.
+    pub fn gain(&self) -> f32 {
+        let gain = fade_in_gain(self.elapsed);
+        match self.fade_out {
+            Some(fading) => gain * fade_out_gain(fading),
+            None => gain,
+        }
+    }
+
+    /// Faded out completely and can be removed
+    pub fn is_silent(&self) -> bool {
+        self.fade_out
+            .is_some_and(|fading| fading >= MUSIC_CROSSFADE_SECONDS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn playlist(names: &[&str]) -> MusicPlaylist {
+        MusicPlaylist::new(names.iter().map(PathBuf::from).collect())
+    }
+
+    fn play(
+        playlist: &mut MusicPlaylist,
+        shuffle: bool,
+        rng: &mut StdRng,
+        n: usize,
+    ) -> Vec<String> {
+        (0..n)
+            .map(|_| {
+                playlist
+                    .advance(shuffle, rng)
+                    .map(|p| p.display().to_string())
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_playlist_loops_in_order() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut list = playlist(&["a.mp3", "b.mp3", "c.mp3"]);
+        assert!(list.current().is_none());
+        assert_eq!(
+            play(&mut list, false, &mut rng, 5),
+            ["a.mp3", "b.mp3", "c.mp3", "a.mp3", "b.mp3"]
+        );
+        assert!(MusicPlaylist::default().advance(false, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_shuffle_plays_every_track_each_pass() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut list = playlist(&["a", "b", "c", "d"]);
+        let mut previous_last: Option<String> = None;
+        for _ in 0..20 {
+            let pass = play(&mut list, true, &mut rng, 4);
+            assert_ne!(pass.first(), previous_last.as_ref());
+            previous_last = pass.last().cloned();
+
+            let mut sorted = pass;
+            sorted.sort();
+            assert_eq!(sorted, ["a", "b", "c", "d"]);
+        }
+    }
+
+    #[test]
+    fn test_unreadable_tracks_are_skipped() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut list = playlist(&["a", "b", "c"]);
+        assert_eq!(play(&mut list, false, &mut rng, 2), ["a", "b"]);
+        list.remove_current();
+        assert_eq!(list.len(), 2);
+        assert_eq!(play(&mut list, false, &mut rng, 3), ["c", "a", "c"]);
+    }
+
+    #[test]
+    fn test_crossfade_gains() {
+        assert_eq!(fade_in_gain(0.0), 0.0);
+        assert_eq!(fade_in_gain(MUSIC_CROSSFADE_SECONDS), 1.0);
+        assert_eq!(fade_out_gain(0.0), 1.0);
+        let half = MUSIC_CROSSFADE_SECONDS / 2.0;
+        let power = fade_in_gain(half).powi(2) + fade_out_gain(half).powi(2);
+        assert!((power - 1.0).abs() < 1e-5);
+
+        let mut track = MusicTrack::new("Tavern".to_string());
+        track.elapsed = 10.0;
+        assert_eq!(track.gain(), 1.0);
+        track.fade_out = Some(MUSIC_CROSSFADE_SECONDS);
+        assert!(track.is_silent());
+        assert!(track.gain() < 1e-6);
+    }
+
+    #[test]
+    fn test_music_files() {
+        assert!(is_music_file(Path::new("x/Tavern Night.MP3")));
+        assert!(is_music_file(Path::new("forest.ogg")));
+        assert!(!is_music_file(Path::new("cover.jpg")));
+        assert_eq!(track_name(Path::new("x/Tavern Night.mp3")), "Tavern Night");
+    }
+}
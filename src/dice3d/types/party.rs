@@ -0,0 +1,603 @@
+//! Parties and group checks
+//!
+//! A party is a named group of characters kept in the campaign's settings,
+//! so a whole group can roll the same check at once: `--party scouts skill
+//! perception` rolls Perception for every scout and compares the results.
+//! The name `all` always means every character in the database. As in the
+//! rules for group checks, the group passes when at least half succeed.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::character::{
+    ability_abbreviation, normalize_ability_name, CharacterData, CharacterListEntry, CharacterSheet,
+};
+use super::conditions::D20Test;
+use super::dice::RollEngine;
+use super::ruleset::Ruleset;
+
+/// Settings key of the campaign's saved parties
+pub const PARTIES_SETTING_KEY: &str = "parties";
+
+/// Party name that selects every character
+pub const ALL_CHARACTERS_PARTY: &str = "all";
+
+/// The standard skills: sheet key and display name
+pub const STANDARD_SKILLS: [(&str, &str); 18] = [
+    ("acrobatics", "Acrobatics"),
+    ("animalHandling", "Animal Handling"),
+    ("arcana", "Arcana"),
+    ("athletics", "Athletics"),
+    ("deception", "Deception"),
+    ("history", "History"),
+    ("insight", "Insight"),
+    ("intimidation", "Intimidation"),
+    ("investigation", "Investigation"),
+    ("medicine", "Medicine"),
+    ("nature", "Nature"),
+    ("perception", "Perception"),
+    ("performance", "Performance"),
+    ("persuasion", "Persuasion"),
+    ("religion", "Religion"),
+    ("sleightOfHand", "Sleight of Hand"),
+    ("stealth", "Stealth"),
+    ("survival", "Survival"),
+];
+
+/// Look up a standard skill by name ("sleight of hand", "animal", ...),
+/// returning its sheet key and display name.
+pub fn find_skill(name: &str) -> Option<(&'static str, &'static str)> {
+    let name = name.to_lowercase().replace([' ', '_'], "");
+    let name = match name.as_str() {
+        "animal" => "animalhandling",
+        "sleight" => "sleightofhand",
+        other => other,
+    };
+    STANDARD_SKILLS
+        .iter()
+        .find(|(key, _)| key.to_lowercase() == name)
+        .copied()
+}
+
+/// A saved group of characters, by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+/// The campaign's saved parties
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
+pub struct Parties {
+    pub parties: Vec<Party>,
+}
+
+impl Parties {
+    pub fn get(&self, name: &str) -> Option<&Party> {
+        self.parties
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Save a party, replacing one with the same name.
+    pub fn save(&mut self, name: &str, members: Vec<String>) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("A party needs a name".to_string());
+        }
+        if name.eq_ignore_ascii_case(ALL_CHARACTERS_PARTY) {
+            return Err(format!(
+                "'{}' already means every character",
+                ALL_CHARACTERS_PARTY
+            ));
+        }
+
+        let mut unique: Vec<String> = Vec::new();
+        for member in members.iter().map(|m| m.trim()).filter(|m| !m.is_empty()) {
+            if !unique.iter().any(|u| u.eq_ignore_ascii_case(member)) {
+                unique.push(member.to_string());
+            }
+        }
+        if unique.is_empty() {
+            return Err(format!("Party '{}' needs at least one character", name));
+        }
+
+        let party = Party {
+            name: name.to_string(),
+            members: unique,
+        };
+        match self
+            .parties
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => *existing = party,
+            None => self.parties.push(party),
+        }
+        Ok(())
+    }
+
+    /// The party used when none is named: the first saved one, or every
+    /// character when none are saved.
+    pub fn default_name(&self) -> &str {
+        self.parties
+            .first()
+            .map(|p| p.name.as_str())
+            .unwrap_or(ALL_CHARACTERS_PARTY)
+    }
+
+    /// Member names of a saved party, or of every character in `characters`
+    /// for [`ALL_CHARACTERS_PARTY`].
+    pub fn member_names(
+        &self,
+        name: &str,
+        characters: &[CharacterListEntry],
+    ) -> Result<Vec<String>, String> {
+        if name.trim().eq_ignore_ascii_case(ALL_CHARACTERS_PARTY) {
+            return Ok(characters.iter().map(|c| c.name.clone()).collect());
+        }
+        self.get(name)
+            .map(|p| p.members.clone())
+            .ok_or_else(|| format!("Party '{}' not found", name.trim()))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<Party, String> {
+        let index = self
+            .parties
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Party '{}' not found", name.trim()))?;
+        Ok(self.parties.remove(index))
+    }
+}
+
+/// A d20 check every member of a group rolls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupCheck {
+    /// An ability check ("strength", ...)
+    Ability(&'static str),
+    /// A skill check by sheet key, optionally with a non-default ability
+    Skill {
+        skill: &'static str,
+        with: Option<&'static str>,
+    },
+    /// A saving throw ("dexterity", ...)
+    Save(&'static str),
+    Initiative,
+}
+
+impl GroupCheck {
+    /// Read a check as typed: "perception", "dex", "dex save",
+    /// "intimidation with str" or "initiative".
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().to_lowercase();
+        if text == "initiative" || text == "init" {
+            return Ok(Self::Initiative);
+        }
+        if let Some(ability) = text
+            .strip_suffix(" save")
+            .or_else(|| text.strip_suffix(" saving throw"))
+        {
+            return normalize_ability_name(ability)
+                .map(Self::Save)
+                .ok_or_else(|| format!("Unknown ability '{}'", ability.trim()));
+        }
+        if let Some(ability) = normalize_ability_name(&text) {
+            return Ok(Self::Ability(ability));
+        }
+
+        let (skill, with) = match text.split_once(" with ") {
+            Some((skill, ability)) => {
+                let ability = normalize_ability_name(ability)
+                    .ok_or_else(|| format!("Unknown ability '{}'", ability.trim()))?;
+                (skill, Some(ability))
+            }
+            None => (text.as_str(), None),
+        };
+        let (skill, _) = find_skill(skill).ok_or_else(|| format!("Unknown check '{}'", skill))?;
+        Ok(Self::Skill { skill, with })
+    }
+
+    /// Display name: "Perception", "Intimidation (STR)", "Dexterity Save"
+    pub fn label(&self) -> String {
+        match self {
+            Self::Ability(ability) => capitalize(ability),
+            Self::Skill { skill, with } => {
+                let name = STANDARD_SKILLS
+                    .iter()
+                    .find(|(key, _)| key == skill)
+                    .map(|(_, name)| *name)
+                    .unwrap_or(skill);
+                match with {
+                    Some(ability) => format!("{} ({})", name, ability_abbreviation(ability)),
+                    None => name.to_string(),
+                }
+            }
+            Self::Save(ability) => format!("{} Save", capitalize(ability)),
+            Self::Initiative => "Initiative".to_string(),
+        }
+    }
+
+    /// The kind of d20 test, for conditions
+    pub fn d20_test(&self) -> D20Test {
+        match self {
+            Self::Save(ability) => D20Test::SavingThrow(ability.to_string()),
+            _ => D20Test::AbilityCheck,
+        }
+    }
+
+    /// The character's modifier for the check, with magic item bonuses;
+    /// `None` when the sheet lacks the skill or save.
+    pub fn modifier(&self, sheet: &CharacterSheet) -> Option<i32> {
+        let data = CharacterData {
+            sheet: Some(sheet.clone()),
+            ..Default::default()
+        };
+        match self {
+            Self::Ability(ability) => data.get_ability_modifier(ability),
+            Self::Skill { skill, with } => {
+                let synergy = sheet.tool_synergy_for_skill(skill);
+                let modifier = match with {
+                    Some(ability) => data.get_skill_modifier_with_ability(skill, ability),
+                    None => data.get_skill_modifier(skill),
+                };
+                modifier.map(|m| m + synergy.bonus)
+            }
+            Self::Save(ability) => data.get_saving_throw_modifier(ability),
+            Self::Initiative => Some(sheet.combat.initiative),
+        }
+    }
+
+    /// What one character rolls for the check. Advantage and disadvantage
+    /// from the caller, conditions and tool synergy cancel out as usual.
+    pub fn member(
+        &self,
+        sheet: &CharacterSheet,
+        ruleset: Ruleset,
+        advantage: bool,
+        disadvantage: bool,
+    ) -> Result<GroupMember, String> {
+        let name = sheet.character.name.clone();
+        let modifier = self
+            .modifier(sheet)
+            .ok_or_else(|| format!("{} has no {} on their sheet", name, self.label()))?;
+
+        let effect = sheet.conditions.effect_on(&self.d20_test(), ruleset);
+        let synergy_advantage = match self {
+            Self::Skill { skill, .. } => sheet.tool_synergy_for_skill(skill).advantage,
+            _ => false,
+        };
+        Ok(GroupMember {
+            name,
+            modifier: modifier + effect.penalty(),
+            advantage: advantage || synergy_advantage || !effect.advantage.is_empty(),
+            disadvantage: disadvantage || !effect.disadvantage.is_empty(),
+            auto_fail: effect.fails_automatically(),
+        })
+    }
+
+    /// Roll the check for one character.
+    pub fn roll(
+        &self,
+        sheet: &CharacterSheet,
+        ruleset: Ruleset,
+        advantage: bool,
+        disadvantage: bool,
+        rng: &mut impl Rng,
+    ) -> Result<GroupRoll, String> {
+        let member = self.member(sheet, ruleset, advantage, disadvantage)?;
+        Ok(member.roll(rng))
+    }
+
+    /// Roll the check for a character and the opponent they contest.
+    /// Advantage and disadvantage from the caller are the character's own;
+    /// the opponent rolls with only their conditions.
+    pub fn contest(
+        &self,
+        sheet: &CharacterSheet,
+        opponent: &CharacterSheet,
+        ruleset: Ruleset,
+        advantage: bool,
+        disadvantage: bool,
+        rng: &mut impl Rng,
+    ) -> Result<(GroupRoll, GroupRoll), String> {
+        let ours = self.roll(sheet, ruleset, advantage, disadvantage, rng)?;
+        let theirs = self.roll(opponent, ruleset, false, false, rng)?;
+        Ok((ours, theirs))
+    }
+}
+
+/// A character about to roll a group check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupMember {
+    pub name: String,
+    /// Modifier with the conditions' penalty
+    pub modifier: i32,
+    pub advantage: bool,
+    pub disadvantage: bool,
+    /// The conditions fail the check without a roll (a paralyzed
+    /// character's Dexterity save)
+    pub auto_fail: bool,
+}
+
+impl GroupMember {
+    /// d20s the member rolls: two with advantage or disadvantage, none when
+    /// the check fails automatically
+    pub fn dice_count(&self) -> usize {
+        if self.auto_fail {
+            0
+        } else if self.advantage != self.disadvantage {
+            2
+        } else {
+            1
+        }
+    }
+
+    pub fn roll(&self, rng: &mut impl Rng) -> GroupRoll {
+        if self.auto_fail {
+            return self.automatic_failure();
+        }
+        let (die, dropped) =
+            RollEngine::with_rng(rng).roll_d20_test(self.advantage, self.disadvantage);
+        self.result(die, dropped)
+    }
+
+    /// The result of d20 faces thrown for the member (`dice_count` of them).
+    pub fn resolve(&self, faces: &[i32]) -> GroupRoll {
+        if self.auto_fail {
+            return self.automatic_failure();
+        }
+        let first = faces.first().copied().unwrap_or(1);
+        match faces.get(1) {
+            Some(&second) if self.advantage && !self.disadvantage => {
+                self.result(first.max(second), Some(first.min(second)))
+            }
+            Some(&second) if self.disadvantage && !self.advantage => {
+                self.result(first.min(second), Some(first.max(second)))
+            }
+            _ => self.result(first, None),
+        }
+    }
+
+    fn result(&self, die: i32, dropped: Option<i32>) -> GroupRoll {
+        GroupRoll {
+            name: self.name.clone(),
+            die,
+            dropped,
+            modifier: self.modifier,
+            total: die + self.modifier,
+            auto_fail: false,
+        }
+    }
+
+    /// The failed check of a member whose conditions fail it
+    pub fn automatic_failure(&self) -> GroupRoll {
+        GroupRoll {
+            name: self.name.clone(),
+            die: 0,
+            dropped: None,
+            modifier: self.modifier,
+            total: 0,
+            auto_fail: true,
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// One character's result of a group check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupRoll {
+    pub name: String,
+    /// The kept d20
+    pub die: i32,
+    /// The other d20 with advantage or disadvantage
+    pub dropped: Option<i32>,
+    pub modifier: i32,
+    pub total: i32,
+    /// Failed without a roll because of the character's conditions
+    #[serde(default)]
+    pub auto_fail: bool,
+}
+
+impl GroupRoll {
+    /// Whether the result meets `dc`
+    pub fn passes(&self, dc: i32) -> bool {
+        !self.auto_fail && self.total >= dc
+    }
+}
+
+/// Whether the group passes a check against `dc`: as in the Player's
+/// Handbook, it does when at least half of its members succeed.
+pub fn group_check_passes(rolls: &[GroupRoll], dc: i32) -> bool {
+    let passed = rolls.iter().filter(|r| r.passes(dc)).count();
+    !rolls.is_empty() && passed * 2 >= rolls.len()
+}
+
+/// Order group results for comparison: highest total first, the higher
+/// die breaking ties, and automatic failures last.
+pub fn rank_group_rolls(rolls: &mut [GroupRoll]) {
+    rolls.sort_by(|a, b| {
+        a.auto_fail
+            .cmp(&b.auto_fail)
+            .then(b.total.cmp(&a.total))
+            .then(b.die.cmp(&a.die))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::character::{SavingThrow, Skill};
+    use crate::dice3d::types::conditions::Condition;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_parse_group_checks() {
+        assert_eq!(GroupCheck::parse("init"), Ok(GroupCheck::Initiative));
+        assert_eq!(
+            GroupCheck::parse("Dex Save"),
+            Ok(GroupCheck::Save("dexterity"))
+        );
+        assert_eq!(GroupCheck::parse("wis"), Ok(GroupCheck::Ability("wisdom")));
+        assert_eq!(
+            GroupCheck::parse("sleight of hand"),
+            Ok(GroupCheck::Skill {
+                skill: "sleightOfHand",
+                with: None
+            })
+        );
+        let check = GroupCheck::parse("intimidation with str").unwrap();
+        assert_eq!(check.label(), "Intimidation (STR)");
+        assert!(GroupCheck::parse("juggling").is_err());
+    }
+
+    #[test]
+    fn test_group_roll_uses_each_sheet() {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Vex".to_string();
+        sheet.skills.insert(
+            "perception".to_string(),
+            Skill {
+                proficient: true,
+                modifier: 5,
+                expertise: None,
+                proficiency_type: None,
+            },
+        );
+
+        let check = GroupCheck::parse("perception").unwrap();
+        let roll = check
+            .roll(
+                &sheet,
+                Ruleset::default(),
+                false,
+                false,
+                &mut StdRng::seed_from_u64(3),
+            )
+            .unwrap();
+        assert_eq!(roll.modifier, 5);
+        assert_eq!(roll.total, roll.die + 5);
+        assert!(roll.dropped.is_none());
+
+        let stealth = GroupCheck::parse("stealth").unwrap();
+        assert!(stealth
+            .roll(&sheet, Ruleset::default(), false, false, &mut rand::rng())
+            .is_err());
+    }
+
+    #[test]
+    fn test_contest_advantage_is_the_rollers_own() {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Vex".to_string();
+        let mut opponent = CharacterSheet::default();
+        opponent.character.name = "Goblin".to_string();
+
+        let check = GroupCheck::Ability("strength");
+        let mut rng = StdRng::seed_from_u64(3);
+        let (ours, theirs) = check
+            .contest(&sheet, &opponent, Ruleset::default(), true, false, &mut rng)
+            .unwrap();
+        assert!(ours.dropped.is_some());
+        assert!(theirs.dropped.is_none());
+
+        opponent.conditions.toggle(Condition::Poisoned);
+        let (ours, theirs) = check
+            .contest(
+                &sheet,
+                &opponent,
+                Ruleset::default(),
+                false,
+                false,
+                &mut rng,
+            )
+            .unwrap();
+        assert!(ours.dropped.is_none());
+        assert!(theirs.dropped.is_some());
+    }
+
+    #[test]
+    fn test_resolve_thrown_dice() {
+        let member = GroupMember {
+            name: "Vex".to_string(),
+            modifier: 3,
+            advantage: true,
+            disadvantage: false,
+            auto_fail: false,
+        };
+        assert_eq!(member.dice_count(), 2);
+        let roll = member.resolve(&[4, 17]);
+        assert_eq!((roll.die, roll.dropped, roll.total), (17, Some(4), 20));
+
+        let rolls = [roll, member.resolve(&[2, 1]), member.resolve(&[12, 3])];
+        assert!(group_check_passes(&rolls, 15));
+        assert!(!group_check_passes(&rolls, 16));
+        assert!(!group_check_passes(&[], 5));
+    }
+
+    #[test]
+    fn test_paralyzed_character_fails_dexterity_saves() {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Grog".to_string();
+        sheet.saving_throws.insert(
+            "dexterity".to_string(),
+            SavingThrow {
+                proficient: true,
+                modifier: 20,
+            },
+        );
+        sheet.conditions.toggle(Condition::Paralyzed);
+
+        let save = GroupCheck::Save("dexterity");
+        let member = save
+            .member(&sheet, Ruleset::default(), false, false)
+            .unwrap();
+        assert!(member.auto_fail);
+        assert_eq!(member.dice_count(), 0);
+
+        let roll = member.roll(&mut StdRng::seed_from_u64(3));
+        assert!(roll.auto_fail);
+        assert!(!roll.passes(1));
+        assert!(!group_check_passes(&[member.resolve(&[])], 1));
+    }
+
+    #[test]
+    fn test_saved_parties() {
+        let mut parties = Parties::default();
+        parties
+            .save(
+                "Scouts",
+                vec!["Vex".into(), "vex".into(), " Keyleth ".into()],
+            )
+            .unwrap();
+        assert_eq!(parties.get("scouts").unwrap().members, ["Vex", "Keyleth"]);
+
+        parties.save("scouts", vec!["Vax".into()]).unwrap();
+        assert_eq!(parties.parties.len(), 1);
+        assert!(parties.save("All", vec!["Vax".into()]).is_err());
+        assert!(parties.save("Empty", Vec::new()).is_err());
+
+        assert_eq!(parties.default_name(), "scouts");
+        let characters = vec![CharacterListEntry {
+            id: 1,
+            name: "Vax".into(),
+            class: "Rogue".into(),
+            level: 5,
+        }];
+        assert_eq!(parties.member_names("All", &characters).unwrap(), ["Vax"]);
+        assert_eq!(parties.member_names("Scouts", &[]).unwrap(), ["Vax"]);
+        assert!(parties.member_names("Archers", &characters).is_err());
+
+        assert_eq!(parties.remove("SCOUTS").unwrap().members, ["Vax"]);
+        assert!(parties.remove("scouts").is_err());
+        assert_eq!(parties.default_name(), ALL_CHARACTERS_PARTY);
+    }
+}
@@ -0,0 +1,347 @@
+//! Physics repro files
+//!
+//! With debug capture on (Dice settings, Physics), the starting state of
+//! every thrown die is kept for the current roll. When a die leaves the
+//! container, the dice never settle, or the roll is flagged with `misread`,
+//! that state is written to a small JSON file together with the container
+//! setup and the results that were read, so the throw can be replayed with
+//! `replay <file>`:
+//!
+//! ```text
+//! misread                                   # the d20 clearly shows 17
+//! replay physics-repros/physics-repro-1760600000-misread.json
+//! ```
+//!
+//! Throws draw from the thread RNG, so there is no seed to store; the
+//! position, rotation and velocities each die started with are what the RNG
+//! produced and replay the throw just as well. Replays are closest to the
+//! original with fixed timestep physics on.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{DiceContainerStyle, DiceType};
+
+/// Version written to new repro files; newer files are refused
+pub const PHYSICS_REPRO_VERSION: u32 = 1;
+
+/// Folder next to the database that repro files are written to
+pub const PHYSICS_REPRO_FOLDER: &str = "physics-repros";
+
+/// Why a repro file was written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReproReason {
+    /// A die fell out of the container and was put back
+    OutOfBounds,
+    /// The dice were still moving when the roll timed out
+    Timeout,
+    /// The roll was flagged with `misread`
+    Misread,
+}
+
+impl ReproReason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ReproReason::OutOfBounds => "a die left the container",
+            ReproReason::Timeout => "the dice never settled",
+            ReproReason::Misread => "flagged as mis-read",
+        }
+    }
+
+    fn slug(&self) -> &'static str {
+        match self {
+            ReproReason::OutOfBounds => "out-of-bounds",
+            ReproReason::Timeout => "timeout",
+            ReproReason::Misread => "misread",
+        }
+    }
+}
+
+/// Position, rotation, scale and velocities of a die's rigid body
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyState {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: f32,
+    pub linvel: [f32; 3],
+    pub angvel: [f32; 3],
+}
+
+impl BodyState {
+    pub fn new(transform: &Transform, linvel: Vec3, angvel: Vec3) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.x,
+            linvel: linvel.to_array(),
+            angvel: angvel.to_array(),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.translation))
+            .with_rotation(Quat::from_array(self.rotation).normalize())
+            .with_scale(Vec3::splat(self.scale))
+    }
+
+    pub fn linvel(&self) -> Vec3 {
+        Vec3::from_array(self.linvel)
+    }
+
+    pub fn angvel(&self) -> Vec3 {
+        Vec3::from_array(self.angvel)
+    }
+}
+
+/// The starting state of one thrown die
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DieThrow {
+    /// The rolled type: `Custom(100)` for a percentile pair
+    pub die_type: DiceType,
+    pub body: BodyState,
+    /// The units die of a percentile pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<BodyState>,
+}
+
+/// Container and physics settings the throw ran with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerSetup {
+    pub style: DiceContainerStyle,
+    /// The throw was started by shaking the container
+    pub shake: bool,
+    pub fixed_timestep: bool,
+    pub steered_dice: bool,
+}
+
+/// A repro file: everything needed to throw the same dice again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicsRepro {
+    pub version: u32,
+    pub app_version: String,
+    pub reason: ReproReason,
+    pub container: ContainerSetup,
+    /// Results read from the faces, empty when the dice never settled
+    #[serde(default)]
+    pub results: Vec<(DiceType, u32)>,
+    pub throws: Vec<DieThrow>,
+}
+
+impl PhysicsRepro {
+    pub fn new(reason: ReproReason, container: ContainerSetup, throws: Vec<DieThrow>) -> Self {
+        Self {
+            version: PHYSICS_REPRO_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            reason,
+            container,
+            results: Vec::new(),
+            throws,
+        }
+    }
+
+    /// "physics-repro-1760600000-timeout.json"
+    pub fn file_name(&self, timestamp: u64) -> String {
+        format!("physics-repro-{}-{}.json", timestamp, self.reason.slug())
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize the repro: {}", e))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let repro: Self =
+            serde_json::from_str(text).map_err(|e| format!("Invalid repro file: {}", e))?;
+        if repro.version > PHYSICS_REPRO_VERSION {
+            return Err(format!(
+                "Repro file version {} is newer than this app supports ({})",
+                repro.version, PHYSICS_REPRO_VERSION
+            ));
+        }
+        if repro.throws.is_empty() {
+            return Err("The repro file has no dice".to_string());
+        }
+        Ok(repro)
+    }
+
+    /// Settings that differ from the recording and may change the replay.
+    ///
+    /// A different container is an error: the dice would start inside walls.
+    pub fn setup_differences(&self, current: &ContainerSetup) -> Result<Vec<String>, String> {
+        if self.container.style != current.style {
+            return Err(format!(
+                "Recorded in the {:?}: switch the container first",
+                self.container.style
+            ));
+        }
+        let mut differences = Vec::new();
+        if self.container.fixed_timestep != current.fixed_timestep {
+            differences.push(format!(
+                "fixed timestep was {}",
+                if self.container.fixed_timestep {
+                    "on"
+                } else {
+                    "off"
+                }
+            ));
+        }
+        if self.container.steered_dice != current.steered_dice {
+            differences.push(format!(
+                "fair but pretty was {}",
+                if self.container.steered_dice {
+                    "on"
+                } else {
+                    "off"
+                }
+            ));
+        }
+        Ok(differences)
+    }
+}
+
+/// Starting states of the dice of the current roll, kept while debug
+/// capture is on
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PhysicsReproCapture {
+    pub container: Option<ContainerSetup>,
+    pub throws: Vec<(Entity, DieThrow)>,
+    /// A repro of this roll was already written
+    pub written: bool,
+}
+
+impl PhysicsReproCapture {
+    /// Forget the previous roll.
+    pub fn start_roll(&mut self, container: ContainerSetup) {
+        self.container = Some(container);
+        self.throws.clear();
+        self.written = false;
+    }
+
+    /// Record a die's starting state, replacing an earlier one (a die thrown
+    /// again by the same roll).
+    pub fn record(&mut self, entity: Entity, throw: DieThrow) {
+        match self.throws.iter_mut().find(|(e, _)| *e == entity) {
+            Some((_, existing)) => *existing = throw,
+            None => self.throws.push((entity, throw)),
+        }
+    }
+
+    /// The repro of the current roll, `None` when nothing was captured.
+    pub fn repro(&self, reason: ReproReason) -> Option<PhysicsRepro> {
+        let container = self.container?;
+        if self.throws.is_empty() {
+            return None;
+        }
+        let throws = self.throws.iter().map(|(_, t)| t.clone()).collect();
+        Some(PhysicsRepro::new(reason, container, throws))
+    }
+}
+
+/// A die was put back in the container, or the dice timed out; written by
+/// `check_dice_settled`
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PhysicsAnomaly {
+    pub reason: ReproReason,
+}
+
+/// Starting state a replayed die is put in once spawned
+#[derive(Component, Debug, Clone)]
+pub struct ReplayedThrow(pub DieThrow);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> ContainerSetup {
+        ContainerSetup {
+            style: DiceContainerStyle::Box,
+            shake: false,
+            fixed_timestep: true,
+            steered_dice: false,
+        }
+    }
+
+    fn throw(die_type: DiceType, x: f32) -> DieThrow {
+        let transform = Transform::from_xyz(x, 0.8, -0.2)
+            .with_rotation(Quat::from_rotation_y(1.0))
+            .with_scale(Vec3::splat(0.3));
+        DieThrow {
+            die_type,
+            body: BodyState::new(&transform, Vec3::new(1.0, -2.0, 0.5), Vec3::Y * 12.0),
+            units: None,
+        }
+    }
+
+    #[test]
+    fn test_repro_round_trip() {
+        let mut repro = PhysicsRepro::new(
+            ReproReason::Misread,
+            setup(),
+            vec![throw(DiceType::D20, 0.1), throw(DiceType::D6, -0.3)],
+        );
+        repro.results = vec![(DiceType::D20, 17), (DiceType::D6, 4)];
+
+        let parsed = PhysicsRepro::from_json(&repro.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, repro);
+        let body = parsed.throws[0].body;
+        assert_eq!(body.transform().translation, Vec3::new(0.1, 0.8, -0.2));
+        assert_eq!(body.transform().scale, Vec3::splat(0.3));
+        assert_eq!(body.angvel(), Vec3::Y * 12.0);
+        assert_eq!(
+            repro.file_name(1_760_600_000),
+            "physics-repro-1760600000-misread.json"
+        );
+    }
+
+    #[test]
+    fn test_newer_or_empty_repros_are_refused() {
+        let mut repro = PhysicsRepro::new(ReproReason::Timeout, setup(), Vec::new());
+        assert!(PhysicsRepro::from_json(&repro.to_json().unwrap()).is_err());
+
+        repro.throws.push(throw(DiceType::D8, 0.0));
+        repro.version = PHYSICS_REPRO_VERSION + 1;
+        assert!(PhysicsRepro::from_json(&repro.to_json().unwrap()).is_err());
+        assert!(PhysicsRepro::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_setup_differences() {
+        let repro = PhysicsRepro::new(ReproReason::OutOfBounds, setup(), Vec::new());
+        assert!(repro.setup_differences(&setup()).unwrap().is_empty());
+
+        let mut current = setup();
+        current.fixed_timestep = false;
+        current.shake = true;
+        assert_eq!(
+            repro.setup_differences(&current).unwrap(),
+            vec!["fixed timestep was on".to_string()]
+        );
+
+        current.style = DiceContainerStyle::Cup;
+        assert!(repro.setup_differences(&current).is_err());
+    }
+
+    #[test]
+    fn test_capture_keeps_one_throw_per_die() {
+        let mut capture = PhysicsReproCapture::default();
+        assert!(capture.repro(ReproReason::Misread).is_none());
+
+        capture.start_roll(setup());
+        let die = Entity::PLACEHOLDER;
+        capture.record(die, throw(DiceType::D6, 0.0));
+        capture.record(die, throw(DiceType::D6, 0.5));
+        let repro = capture.repro(ReproReason::Timeout).unwrap();
+        assert_eq!(repro.throws.len(), 1);
+        assert_eq!(repro.throws[0].body.translation[0], 0.5);
+
+        capture.written = true;
+        capture.start_roll(setup());
+        assert!(!capture.written);
+        assert!(capture.repro(ReproReason::Misread).is_none());
+    }
+}
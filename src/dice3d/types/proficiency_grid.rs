@@ -0,0 +1,229 @@
+//! Bulk proficiency editing
+//!
+//! The bulk edit grid lists every skill and saving throw of a sheet at once,
+//! so the proficiencies of an imported character can be cleaned up in one
+//! go instead of row by row. Clicks change a draft; applying it moves each
+//! modifier by the change in its proficiency bonus, so any other bonus
+//! already on the modifier is kept.
+
+use super::character::{CharacterSheet, ABILITY_NAMES};
+
+/// How proficient a character is in a skill or saving throw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProficiencyLevel {
+    #[default]
+    None,
+    Proficient,
+    Expertise,
+}
+
+impl ProficiencyLevel {
+    /// Short grid label ("—", "P", "E")
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProficiencyLevel::None => "—",
+            ProficiencyLevel::Proficient => "P",
+            ProficiencyLevel::Expertise => "E",
+        }
+    }
+
+    /// How many times the proficiency bonus is added
+    pub fn multiplier(&self) -> i32 {
+        match self {
+            ProficiencyLevel::None => 0,
+            ProficiencyLevel::Proficient => 1,
+            ProficiencyLevel::Expertise => 2,
+        }
+    }
+
+    /// The level a click moves to: none, proficient, then expertise when
+    /// `expertise` is allowed (skills, not saves), and back to none.
+    pub fn next(&self, expertise: bool) -> Self {
+        match self {
+            ProficiencyLevel::None => ProficiencyLevel::Proficient,
+            ProficiencyLevel::Proficient if expertise => ProficiencyLevel::Expertise,
+            _ => ProficiencyLevel::None,
+        }
+    }
+}
+
+/// Draft of a sheet's skill and save proficiencies
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProficiencyGrid {
+    /// Skills in alphabetical order
+    pub skills: Vec<(String, ProficiencyLevel)>,
+    /// Saving throws in ability order
+    pub saves: Vec<(String, ProficiencyLevel)>,
+}
+
+impl ProficiencyGrid {
+    pub fn from_sheet(sheet: &CharacterSheet) -> Self {
+        let mut skills: Vec<(String, ProficiencyLevel)> = sheet
+            .skills
+            .iter()
+            .map(|(name, skill)| {
+                let level = if skill.expertise.unwrap_or(false) {
+                    ProficiencyLevel::Expertise
+                } else if skill.proficient {
+                    ProficiencyLevel::Proficient
+                } else {
+                    ProficiencyLevel::None
+                };
+                (name.clone(), level)
+            })
+            .collect();
+        skills.sort_by_key(|(name, _)| name.to_lowercase());
+
+        let saves = ABILITY_NAMES
+            .iter()
+            .filter_map(|ability| {
+                let save = sheet.saving_throws.get(*ability)?;
+                let level = if save.proficient {
+                    ProficiencyLevel::Proficient
+                } else {
+                    ProficiencyLevel::None
+                };
+                Some((ability.to_string(), level))
+            })
+            .collect();
+
+        Self { skills, saves }
+    }
+
+    /// Click a skill's cell.
+    pub fn cycle_skill(&mut self, skill: &str) {
+        if let Some((_, level)) = self.skills.iter_mut().find(|(name, _)| name == skill) {
+            *level = level.next(true);
+        }
+    }
+
+    /// Click a saving throw's cell.
+    pub fn cycle_save(&mut self, ability: &str) {
+        if let Some((_, level)) = self.saves.iter_mut().find(|(name, _)| name == ability) {
+            *level = level.next(false);
+        }
+    }
+
+    /// How many skills and saves differ from `sheet`.
+    pub fn changes(&self, sheet: &CharacterSheet) -> usize {
+        let current = Self::from_sheet(sheet);
+        let differ = |a: &[(String, ProficiencyLevel)], b: &[(String, ProficiencyLevel)]| {
+            a.iter().filter(|entry| !b.contains(entry)).count()
+        };
+        differ(&self.skills, &current.skills) + differ(&self.saves, &current.saves)
+    }
+
+    /// Write the draft to `sheet`, moving each changed modifier by the
+    /// difference in proficiency bonus. Returns how many entries changed.
+    pub fn apply(&self, sheet: &mut CharacterSheet) -> usize {
+        let proficiency = sheet.proficiency_bonus;
+        let current = Self::from_sheet(sheet);
+        let mut changed = 0;
+
+        for (name, level) in &self.skills {
+            let Some((_, old)) = current.skills.iter().find(|(n, _)| n == name) else {
+                continue;
+            };
+            let Some(skill) = sheet.skills.get_mut(name) else {
+                continue;
+            };
+            if old == level {
+                continue;
+            }
+            skill.modifier += (level.multiplier() - old.multiplier()) * proficiency;
+            skill.proficient = *level != ProficiencyLevel::None;
+            skill.expertise = Some(*level == ProficiencyLevel::Expertise);
+            changed += 1;
+        }
+
+        for (ability, level) in &self.saves {
+            let Some((_, old)) = current.saves.iter().find(|(n, _)| n == ability) else {
+                continue;
+            };
+            let Some(save) = sheet.saving_throws.get_mut(ability) else {
+                continue;
+            };
+            if old == level {
+                continue;
+            }
+            save.modifier += (level.multiplier() - old.multiplier()) * proficiency;
+            save.proficient = *level != ProficiencyLevel::None;
+            changed += 1;
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::character::{SavingThrow, Skill};
+    use super::*;
+
+    fn sheet() -> CharacterSheet {
+        let mut sheet = CharacterSheet {
+            proficiency_bonus: 3,
+            ..Default::default()
+        };
+        sheet.skills.insert(
+            "stealth".to_string(),
+            Skill {
+                proficient: true,
+                modifier: 6,
+                ..Default::default()
+            },
+        );
+        sheet.skills.insert(
+            "arcana".to_string(),
+            Skill {
+                modifier: 1,
+                ..Default::default()
+            },
+        );
+        sheet.saving_throws.insert(
+            "wisdom".to_string(),
+            SavingThrow {
+                proficient: false,
+                modifier: 2,
+            },
+        );
+        sheet
+    }
+
+    #[test]
+    fn test_cycle_levels() {
+        let mut grid = ProficiencyGrid::from_sheet(&sheet());
+        assert_eq!(
+            grid.skills[0],
+            ("arcana".to_string(), ProficiencyLevel::None)
+        );
+
+        grid.cycle_skill("stealth");
+        grid.cycle_save("wisdom");
+        assert_eq!(grid.skills[1].1, ProficiencyLevel::Expertise);
+        assert_eq!(grid.saves[0].1, ProficiencyLevel::Proficient);
+        grid.cycle_save("wisdom");
+        assert_eq!(grid.saves[0].1, ProficiencyLevel::None);
+        grid.cycle_skill("stealth");
+        assert_eq!(grid.skills[1].1, ProficiencyLevel::None);
+    }
+
+    #[test]
+    fn test_apply_moves_modifiers() {
+        let mut sheet = sheet();
+        let mut grid = ProficiencyGrid::from_sheet(&sheet);
+        grid.cycle_skill("stealth");
+        grid.cycle_skill("arcana");
+        grid.cycle_save("wisdom");
+        assert_eq!(grid.changes(&sheet), 3);
+
+        assert_eq!(grid.apply(&mut sheet), 3);
+        assert_eq!(sheet.skills["stealth"].modifier, 9);
+        assert_eq!(sheet.skills["stealth"].expertise, Some(true));
+        assert_eq!(sheet.skills["arcana"].modifier, 4);
+        assert!(sheet.skills["arcana"].proficient);
+        assert_eq!(sheet.saving_throws["wisdom"].modifier, 5);
+        assert!(sheet.saving_throws["wisdom"].proficient);
+        assert_eq!(grid.changes(&sheet), 0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Database profiles
+//!
+//! A profile is an isolated local database (characters, settings and command
+//! history), e.g. "Home campaign" and "Work one-shots". The profile list and
+//! the active profile live in `profiles.json` next to the datastores, since
+//! they have to be known before any database is opened.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::database::DATABASE_FOLDER;
+
+/// Id of the profile that uses the original (pre-profiles) datastore
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// One named database profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseProfile {
+    /// Folder-safe id derived from the name
+    pub id: String,
+    /// Display name
+    pub name: String,
+}
+
+impl DatabaseProfile {
+    /// Datastore folder for this profile, relative to the app data directory.
+    ///
+    /// The default profile keeps the original datastore folder so existing
+    /// data shows up without migration.
+    pub fn datastore_folder(&self) -> String {
+        if self.id == DEFAULT_PROFILE_ID {
+            DATABASE_FOLDER.to_string()
+        } else {
+            format!("profiles/{}.surrealdb", self.id)
+        }
+    }
+}
+
+/// Turn a profile name into a folder-safe id ("Home Campaign!" -> "home-campaign").
+pub fn profile_id_from_name(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// The list of profiles and which one is active
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRegistry {
+    pub active: String,
+    pub profiles: Vec<DatabaseProfile>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![DatabaseProfile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+            }],
+        }
+    }
+}
+
+impl ProfileRegistry {
+    /// Load the registry, falling back to just the default profile when the
+    /// file is missing or unreadable.
+    pub fn load_from(path: &Path) -> Self {
+        let mut registry = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<ProfileRegistry>(&text).ok())
+            .unwrap_or_default();
+        registry.normalize();
+        registry
+    }
+
+    /// Write the registry as JSON.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Make sure the default profile exists and the active id points at a
+    /// known profile.
+    fn normalize(&mut self) {
+        if !self.profiles.iter().any(|p| p.id == DEFAULT_PROFILE_ID) {
+            self.profiles
+                .insert(0, ProfileRegistry::default().profiles.remove(0));
+        }
+        if self.get(&self.active).is_none() {
+            self.active = DEFAULT_PROFILE_ID.to_string();
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DatabaseProfile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Find a profile by id or (case-insensitive) name.
+    pub fn find(&self, id_or_name: &str) -> Option<&DatabaseProfile> {
+        let wanted = id_or_name.trim();
+        self.get(wanted)
+            .or_else(|| {
+                self.profiles
+                    .iter()
+                    .find(|p| p.name.eq_ignore_ascii_case(wanted))
+            })
+            .or_else(|| self.get(&profile_id_from_name(wanted)))
+    }
+
+    pub fn active_profile(&self) -> &DatabaseProfile {
+        self.get(&self.active)
+            .or_else(|| self.profiles.first())
+            .expect("profile registry always has a default profile")
+    }
+
+    /// Find a profile by id or name, creating it when it does not exist yet.
+    pub fn find_or_create(&mut self, name: &str) -> Result<DatabaseProfile, String> {
+        if let Some(existing) = self.find(name) {
+            return Ok(existing.clone());
+        }
+
+        let id = profile_id_from_name(name);
+        if id.is_empty() {
+            return Err(format!("'{}' is not a valid profile name", name.trim()));
+        }
+        let profile = DatabaseProfile {
+            id,
+            name: name.trim().to_string(),
+        };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    /// Make a profile the active one. Returns false if it does not exist.
+    pub fn set_active(&mut self, id: &str) -> bool {
+        if self.get(id).is_some() {
+            self.active = id.to_string();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_id_from_name() {
+        assert_eq!(profile_id_from_name("Home campaign"), "home-campaign");
+        assert_eq!(
+            profile_id_from_name("  Work: one-shots! "),
+            "work-one-shots"
+        );
+        assert_eq!(profile_id_from_name("!!!"), "");
+    }
+
+    #[test]
+    fn test_default_profile_keeps_original_datastore() {
+        let registry = ProfileRegistry::default();
+        assert_eq!(
+            registry.active_profile().datastore_folder(),
+            "characters.surrealdb"
+        );
+    }
+
+    #[test]
+    fn test_find_or_create_and_switch() {
+        let mut registry = ProfileRegistry::default();
+        let home = registry.find_or_create("Home campaign").unwrap();
+        assert_eq!(home.id, "home-campaign");
+        assert_eq!(home.datastore_folder(), "profiles/home-campaign.surrealdb");
+
+        // Looking it up again by name or id doesn't create a duplicate
+        assert_eq!(registry.find_or_create("home CAMPAIGN").unwrap(), home);
+        assert_eq!(registry.find("home-campaign"), Some(&home));
+        assert_eq!(registry.profiles.len(), 2);
+
+        assert!(registry.set_active("home-campaign"));
+        assert_eq!(registry.active_profile().name, "Home campaign");
+        assert!(!registry.set_active("missing"));
+        assert!(registry.find_or_create("???").is_err());
+    }
+
+    #[test]
+    fn test_normalize_repairs_registry() {
+        let mut registry = ProfileRegistry {
+            active: "gone".to_string(),
+            profiles: vec![],
+        };
+        registry.normalize();
+        assert_eq!(registry.active, DEFAULT_PROFILE_ID);
+        assert!(registry.get(DEFAULT_PROFILE_ID).is_some());
+    }
+}
@@ -0,0 +1,247 @@
+//! QR code character transfer
+//!
+//! A character sheet is deflate-compressed and split across one or more QR
+//! codes so it can be moved from a phone to a laptop at the table by showing
+//! the codes on one screen and scanning photos or screenshots of them on the
+//! other. Each code carries
+//!
+//! ```text
+//! DNDCHAR1:<checksum>:<part>/<total>:<base64url data>
+//! ```
+//!
+//! where the checksum (CRC-32 of the compressed sheet) tells transfers apart
+//! and verifies the reassembled data. Portraits are left out; they would need
+//! too many codes.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine as _;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use super::{CharacterFile, CharacterSheet};
+
+/// Prefix of every character transfer QR payload
+pub const QR_PAYLOAD_PREFIX: &str = "DNDCHAR1";
+
+/// Base64 characters per QR code; small enough for a phone camera to read
+/// the code off a laptop screen.
+pub const QR_CHUNK_CHARS: usize = 600;
+
+/// Light modules around a rendered code, as the QR spec requires
+pub const QR_QUIET_ZONE: usize = 4;
+
+/// Largest decompressed sheet accepted from scanned codes
+const MAX_SHEET_BYTES: u64 = 4 * 1024 * 1024;
+
+fn checksum(data: &[u8]) -> String {
+    let mut crc = Crc::new();
+    crc.update(data);
+    format!("{:08x}", crc.sum())
+}
+
+/// Split a character sheet into QR code payloads.
+pub fn encode_qr_payloads(sheet: &CharacterSheet) -> Result<Vec<String>, String> {
+    let json = CharacterFile::new(sheet.clone()).to_json()?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to compress character: {}", e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress character: {}", e))?;
+
+    let id = checksum(&compressed);
+    let data = BASE64_URL.encode(&compressed);
+    // Base64 is ASCII, so splitting on bytes keeps every chunk valid UTF-8
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(QR_CHUNK_CHARS)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+    let total = chunks.len();
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("{}:{}:{}/{}:{}", QR_PAYLOAD_PREFIX, id, i + 1, total, chunk))
+        .collect())
+}
+
+/// Render a payload as QR modules: the side length and row-major dark flags,
+/// including the quiet zone.
+pub fn qr_modules(payload: &str) -> Result<(usize, Vec<bool>), String> {
+    let code = qrcode::QrCode::with_error_correction_level(payload, qrcode::EcLevel::M)
+        .map_err(|e| format!("Failed to create QR code: {}", e))?;
+    let width = code.width();
+    let side = width + 2 * QR_QUIET_ZONE;
+
+    let mut modules = vec![false; side * side];
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        let (x, y) = (i % width + QR_QUIET_ZONE, i / width + QR_QUIET_ZONE);
+        modules[y * side + x] = color == qrcode::Color::Dark;
+    }
+    Ok((side, modules))
+}
+
+/// Find every QR code in a photo or screenshot and return the payloads of
+/// the ones that decode.
+pub fn scan_qr_payloads(image: &image::DynamicImage) -> Vec<String> {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+    let mut prepared =
+        rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+            luma.get_pixel(x as u32, y as u32).0[0]
+        });
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok().map(|(_meta, content)| content))
+        .collect()
+}
+
+/// Collects scanned payloads until a whole character has arrived.
+#[derive(Debug, Clone, Default)]
+pub struct QrTransferAssembler {
+    id: Option<String>,
+    total: usize,
+    parts: BTreeMap<usize, String>,
+}
+
+impl QrTransferAssembler {
+    /// Add one scanned payload.
+    ///
+    /// A payload from a different transfer starts over, so scanning a new
+    /// character never mixes in codes of an old one.
+    pub fn add(&mut self, payload: &str) -> Result<(), String> {
+        let not_ours = || "Not a character QR code".to_string();
+        let rest = payload
+            .trim()
+            .strip_prefix(QR_PAYLOAD_PREFIX)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(not_ours)?;
+        let mut fields = rest.splitn(3, ':');
+        let (Some(id), Some(position), Some(data)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(not_ours());
+        };
+        let (index, total) = position
+            .split_once('/')
+            .and_then(|(i, t)| Some((i.parse::<usize>().ok()?, t.parse::<usize>().ok()?)))
+            .filter(|(i, t)| (1..=*t).contains(i))
+            .ok_or_else(not_ours)?;
+
+        if self.id.as_deref() != Some(id) || self.total != total {
+            *self = Self {
+                id: Some(id.to_string()),
+                total,
+                parts: BTreeMap::new(),
+            };
+        }
+        self.parts.insert(index, data.to_string());
+        Ok(())
+    }
+
+    /// Codes scanned so far and codes in the transfer
+    pub fn progress(&self) -> (usize, usize) {
+        (self.parts.len(), self.total)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.parts.len() == self.total
+    }
+
+    /// Reassemble the character once every code has been scanned.
+    pub fn finish(&self) -> Result<CharacterFile, String> {
+        if !self.is_complete() {
+            let (scanned, total) = self.progress();
+            return Err(format!("Scanned {} of {} QR codes", scanned, total));
+        }
+
+        let data: String = self.parts.values().map(String::as_str).collect();
+        let compressed = BASE64_URL
+            .decode(data)
+            .map_err(|e| format!("Invalid QR data: {}", e))?;
+        if Some(checksum(&compressed).as_str()) != self.id.as_deref() {
+            return Err("QR data is corrupted; scan the codes again".to_string());
+        }
+
+        let mut json = String::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_SHEET_BYTES)
+            .read_to_string(&mut json)
+            .map_err(|e| format!("Failed to decompress character: {}", e))?;
+        CharacterFile::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_sheet() -> CharacterSheet {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Elara".to_string();
+        sheet.character.class = "Wizard".to_string();
+        // Enough text to need several codes even after compression
+        for i in 0..80u64 {
+            sheet.custom_basic_info.insert(
+                format!("Note {}", i),
+                format!("{:x}", (i + 7).pow(9) ^ 0x9e37_79b9_7f4a_7c15),
+            );
+        }
+        sheet
+    }
+
+    #[test]
+    fn test_payloads_reassemble_in_any_order() {
+        let payloads = encode_qr_payloads(&test_sheet()).unwrap();
+        assert!(payloads.len() > 1);
+        assert!(payloads[0].starts_with("DNDCHAR1:"));
+
+        let mut assembler = QrTransferAssembler::default();
+        for payload in payloads.iter().rev() {
+            assert!(!assembler.is_complete());
+            assembler.add(payload).unwrap();
+        }
+        assert!(assembler.is_complete());
+        let file = assembler.finish().unwrap();
+        assert_eq!(file.sheet.character.name, "Elara");
+        assert_eq!(file.sheet.custom_basic_info.len(), 80);
+    }
+
+    #[test]
+    fn test_incomplete_and_foreign_codes() {
+        let payloads = encode_qr_payloads(&test_sheet()).unwrap();
+        let mut assembler = QrTransferAssembler::default();
+        assembler.add(&payloads[0]).unwrap();
+        assert_eq!(assembler.progress(), (1, payloads.len()));
+        assert!(assembler.finish().is_err());
+
+        assert!(assembler.add("https://example.com").is_err());
+        assert!(assembler.add("DNDCHAR1:abc:0/2:xyz").is_err());
+
+        // A code from another transfer starts over
+        let mut other = test_sheet();
+        other.character.name = "Brom".to_string();
+        let other_payloads = encode_qr_payloads(&other).unwrap();
+        assembler.add(&other_payloads[0]).unwrap();
+        assert_eq!(assembler.progress(), (1, other_payloads.len()));
+    }
+
+    #[test]
+    fn test_rendered_code_scans_back() {
+        let payload = encode_qr_payloads(&CharacterSheet::default()).unwrap()[0].clone();
+        let (side, modules) = qr_modules(&payload).unwrap();
+
+        let scale = 4;
+        let img =
+            image::GrayImage::from_fn((side * scale) as u32, (side * scale) as u32, |x, y| {
+                let (mx, my) = (x as usize / scale, y as usize / scale);
+                image::Luma([if modules[my * side + mx] { 0 } else { 255 }])
+            });
+        let scanned = scan_qr_payloads(&image::DynamicImage::ImageLuma8(img));
+        assert_eq!(scanned, vec![payload]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Probability quiz
+//!
+//! Practice mode for new DMs learning to set DCs: "what's the chance 2d6+3
+//! meets DC 10?". The player answers with a percentage, sees the exact chance
+//! and how far off the estimate was, and the dice are rolled to demonstrate.
+//! Estimation accuracy is kept per profile in the database's settings table
+//! under [`QUIZ_STATS_SETTING_KEY`].
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::DiceType;
+
+/// Database setting holding the quiz accuracy
+pub const QUIZ_STATS_SETTING_KEY: &str = "probability_quiz";
+
+/// Estimates within this many percentage points of the answer count as close
+pub const CLOSE_ESTIMATE_POINTS: f64 = 10.0;
+
+/// Questions are only asked when the answer lies between these chances, so
+/// there is something to estimate.
+const MIN_CHANCE: f64 = 0.05;
+const MAX_CHANCE: f64 = 0.95;
+
+/// Dice the quiz asks about, with how high their modifier may go
+const QUESTION_DICE: [(u32, DiceType, i32); 9] = [
+    (1, DiceType::D20, 7),
+    (1, DiceType::D20, 7),
+    (1, DiceType::D20, 7),
+    (2, DiceType::D6, 5),
+    (3, DiceType::D6, 3),
+    (1, DiceType::D8, 5),
+    (2, DiceType::D8, 4),
+    (1, DiceType::D12, 4),
+    (4, DiceType::D4, 3),
+];
+
+/// "What's the chance `count`d`die`+`modifier` meets `dc`?"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuizQuestion {
+    pub count: u32,
+    pub die: DiceType,
+    pub modifier: i32,
+    pub dc: i32,
+}
+
+impl QuizQuestion {
+    /// A random question whose answer is between 5% and 95%.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let (count, die, max_modifier) = QUESTION_DICE[rng.random_range(0..QUESTION_DICE.len())];
+        let modifier = rng.random_range(0..=max_modifier);
+        let mut question = QuizQuestion {
+            count,
+            die,
+            modifier,
+            dc: 0,
+        };
+
+        let lowest = count as i32 + modifier;
+        let highest = (count * die.max_value()) as i32 + modifier;
+        let dcs: Vec<i32> = (lowest + 1..=highest)
+            .filter(|dc| {
+                let chance = QuizQuestion {
+                    dc: *dc,
+                    ..question
+                }
+                .chance();
+                (MIN_CHANCE..=MAX_CHANCE).contains(&chance)
+            })
+            .collect();
+        question.dc = dcs[rng.random_range(0..dcs.len())];
+        question
+    }
+
+    /// The dice as typed: "2d6+3", "1d20", "1d20-1"
+    pub fn expression(&self) -> String {
+        match self.modifier {
+            0 => format!("{}d{}", self.count, self.die.max_value()),
+            m => format!("{}d{}{:+}", self.count, self.die.max_value(), m),
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        format!(
+            "What's the chance {} meets DC {}?",
+            self.expression(),
+            self.dc
+        )
+    }
+
+    /// Whether a rolled total meets the DC.
+    pub fn succeeds(&self, total: i32) -> bool {
+        total >= self.dc
+    }
+
+    /// Exact chance (0 to 1) that the roll meets the DC.
+    pub fn chance(&self) -> f64 {
+        let counts = sum_distribution(self.count, self.die.max_value());
+        let outcomes: u64 = counts.iter().sum();
+        let successes: u64 = counts
+            .iter()
+            .enumerate()
+            .filter(|(sum, _)| self.succeeds(*sum as i32 + self.modifier))
+            .map(|(_, count)| count)
+            .sum();
+        successes as f64 / outcomes as f64
+    }
+}
+
+/// Number of ways `count` dice with `sides` sides add up to each sum
+/// (indexed by the sum).
+fn sum_distribution(count: u32, sides: u32) -> Vec<u64> {
+    let mut counts = vec![1u64];
+    for _ in 0..count {
+        let mut next = vec![0u64; counts.len() + sides as usize];
+        for (sum, ways) in counts.iter().enumerate() {
+            for face in 1..=sides as usize {
+                next[sum + face] += ways;
+            }
+        }
+        counts = next;
+    }
+    counts
+}
+
+/// Parse an estimate: "60", "60%" or "62.5 %" (percent, 0 to 100).
+pub fn parse_estimate(text: &str) -> Option<f64> {
+    let percent: f64 = text.trim().trim_end_matches('%').trim().parse().ok()?;
+    (0.0..=100.0).contains(&percent).then_some(percent)
+}
+
+/// How well the player has been estimating
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct QuizStats {
+    #[serde(default)]
+    pub answered: u32,
+    /// Sum of the estimates' distances from the answers, in percentage points
+    #[serde(default)]
+    pub total_error: f64,
+    /// Estimates within [`CLOSE_ESTIMATE_POINTS`] of the answer
+    #[serde(default)]
+    pub close: u32,
+}
+
+impl QuizStats {
+    /// Record an estimate (percent) of a chance (0 to 1); returns how far off
+    /// it was in percentage points.
+    pub fn record(&mut self, estimate: f64, chance: f64) -> f64 {
+        let error = (estimate - chance * 100.0).abs();
+        self.answered += 1;
+        self.total_error += error;
+        if error <= CLOSE_ESTIMATE_POINTS {
+            self.close += 1;
+        }
+        error
+    }
+
+    /// Average distance from the answers, in percentage points.
+    pub fn average_error(&self) -> Option<f64> {
+        (self.answered > 0).then(|| self.total_error / self.answered as f64)
+    }
+
+    /// "12 answered, 9 within 10 points, off by 6.3 points on average"
+    pub fn summary(&self) -> String {
+        match self.average_error() {
+            Some(average) => format!(
+                "{} answered, {} within {} points, off by {:.1} points on average",
+                self.answered, self.close, CLOSE_ESTIMATE_POINTS, average
+            ),
+            None => "No quiz questions answered yet".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(count: u32, die: DiceType, modifier: i32, dc: i32) -> QuizQuestion {
+        QuizQuestion {
+            count,
+            die,
+            modifier,
+            dc,
+        }
+    }
+
+    #[test]
+    fn test_exact_chances() {
+        assert_eq!(question(1, DiceType::D20, 0, 11).chance(), 0.5);
+        assert_eq!(question(1, DiceType::D20, 5, 25).chance(), 0.05);
+        // 2d6 needs 7 or more: 21 of 36 outcomes
+        assert_eq!(question(2, DiceType::D6, 3, 10).chance(), 21.0 / 36.0);
+        assert_eq!(question(2, DiceType::D6, 0, 2).chance(), 1.0);
+        assert_eq!(question(2, DiceType::D6, 0, 13).chance(), 0.0);
+        assert_eq!(question(2, DiceType::D6, 3, 10).expression(), "2d6+3");
+        assert_eq!(question(1, DiceType::D20, -1, 10).expression(), "1d20-1");
+    }
+
+    #[test]
+    fn test_random_questions_have_something_to_estimate() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let chance = QuizQuestion::random(&mut rng).chance();
+            assert!((MIN_CHANCE..=MAX_CHANCE).contains(&chance));
+        }
+    }
+
+    #[test]
+    fn test_estimates_and_stats() {
+        assert_eq!(parse_estimate("60"), Some(60.0));
+        assert_eq!(parse_estimate(" 62.5 % "), Some(62.5));
+        assert_eq!(parse_estimate("150"), None);
+        assert_eq!(parse_estimate("stats"), None);
+
+        let mut stats = QuizStats::default();
+        assert_eq!(stats.average_error(), None);
+        assert_eq!(stats.record(55.0, 0.5), 5.0);
+        assert_eq!(stats.record(20.0, 0.5), 30.0);
+        assert_eq!(stats.answered, 2);
+        assert_eq!(stats.close, 1);
+        assert_eq!(stats.average_error(), Some(17.5));
+    }
+}
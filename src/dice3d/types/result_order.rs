@@ -0,0 +1,151 @@
+//! The order multi-die results are listed in
+//!
+//! The results panel and the CLI's `Dice:` line list the dice of a roll
+//! grouped by die type (the default), in the order they were thrown, in the
+//! order they came to rest, or highest first. Only the listing changes; the
+//! totals are worked out from the dice as rolled.
+
+use serde::{Deserialize, Serialize};
+
+use super::DiceType;
+
+/// How the dice of a roll are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResultOrder {
+    /// Grouped by die type, smallest die first, lowest value first in
+    /// each group
+    #[default]
+    GroupedByType,
+    /// In the order the dice were thrown
+    SpawnOrder,
+    /// In the order the dice came to rest
+    SettleOrder,
+    /// Highest value first
+    Descending,
+}
+
+impl ResultOrder {
+    pub const ALL: [ResultOrder; 4] = [
+        ResultOrder::GroupedByType,
+        ResultOrder::SpawnOrder,
+        ResultOrder::SettleOrder,
+        ResultOrder::Descending,
+    ];
+
+    /// Name used in the settings select and by `--order` ("settle")
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResultOrder::GroupedByType => "grouped",
+            ResultOrder::SpawnOrder => "spawn",
+            ResultOrder::SettleOrder => "settle",
+            ResultOrder::Descending => "descending",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResultOrder::GroupedByType => "Grouped by die type",
+            ResultOrder::SpawnOrder => "Order thrown",
+            ResultOrder::SettleOrder => "Order settled",
+            ResultOrder::Descending => "Highest first",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "grouped" | "type" => Some(ResultOrder::GroupedByType),
+            "spawn" | "thrown" => Some(ResultOrder::SpawnOrder),
+            "settle" | "settled" => Some(ResultOrder::SettleOrder),
+            "descending" | "desc" | "highest" => Some(ResultOrder::Descending),
+            _ => None,
+        }
+    }
+
+    /// `results` (in throw order) listed in this order. `settle_order`
+    /// holds indices into `results` in the order the dice came to rest;
+    /// dice missing from it (rolled without a model, or rolled by the CLI)
+    /// follow in throw order.
+    pub fn arrange(
+        &self,
+        results: &[(DiceType, u32)],
+        settle_order: &[usize],
+    ) -> Vec<(DiceType, u32)> {
+        let mut listed = results.to_vec();
+        match self {
+            ResultOrder::GroupedByType => listed
+                .sort_by_key(|(die, value)| (die.max_value(), *die == DiceType::Fudge, *value)),
+            ResultOrder::SpawnOrder => {}
+            ResultOrder::SettleOrder => {
+                let mut indices: Vec<usize> = Vec::with_capacity(results.len());
+                for i in settle_order.iter().copied().chain(0..results.len()) {
+                    if i < results.len() && !indices.contains(&i) {
+                        indices.push(i);
+                    }
+                }
+                listed = indices.into_iter().map(|i| results[i]).collect();
+            }
+            ResultOrder::Descending => listed.sort_by(|a, b| b.1.cmp(&a.1)),
+        }
+        listed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THROWN: [(DiceType, u32); 4] = [
+        (DiceType::D20, 7),
+        (DiceType::D6, 5),
+        (DiceType::D4, 3),
+        (DiceType::D6, 2),
+    ];
+
+    #[test]
+    fn test_orders() {
+        assert_eq!(
+            ResultOrder::GroupedByType.arrange(&THROWN, &[]),
+            vec![
+                (DiceType::D4, 3),
+                (DiceType::D6, 2),
+                (DiceType::D6, 5),
+                (DiceType::D20, 7)
+            ]
+        );
+        assert_eq!(ResultOrder::SpawnOrder.arrange(&THROWN, &[2, 0]), THROWN);
+        assert_eq!(
+            ResultOrder::Descending.arrange(&THROWN, &[]),
+            vec![
+                (DiceType::D20, 7),
+                (DiceType::D6, 5),
+                (DiceType::D4, 3),
+                (DiceType::D6, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_settle_order_falls_back_to_throw_order() {
+        // The d4 and d20 came to rest first; the last two were never seen
+        // settling and keep their throw order
+        assert_eq!(
+            ResultOrder::SettleOrder.arrange(&THROWN, &[2, 0]),
+            vec![
+                (DiceType::D4, 3),
+                (DiceType::D20, 7),
+                (DiceType::D6, 5),
+                (DiceType::D6, 2)
+            ]
+        );
+        assert_eq!(ResultOrder::SettleOrder.arrange(&THROWN, &[]), THROWN);
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for order in ResultOrder::ALL {
+            assert_eq!(ResultOrder::parse(order.name()), Some(order));
+        }
+        assert_eq!(ResultOrder::parse("nope"), None);
+    }
+}
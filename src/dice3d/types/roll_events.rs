@@ -0,0 +1,357 @@
+//! Roll lifecycle events
+//!
+//! Every roll goes through the same messages, so features can hook rolls
+//! without touching the systems that throw and read the dice:
+//!
+//! 1. [`RollRequested`] - a roll was asked for (command, quick roll, ...)
+//! 2. [`DiceSpawned`] - dice were added to the box
+//! 3. [`DiceSettled`] - every die came to rest and its face was read
+//! 4. [`RollResolved`] - the roll's totals are known
+//!
+//! A roll aborted with Escape before its dice are read ends with
+//! [`RollCancelled`] instead, and never resolves.
+//!
+//! In box mode the lid closes between the request and the dice spawning, so
+//! a request is not always followed by the other events in the same frame.
+
+use bevy::prelude::*;
+
+use super::{
+    DiceConfig, DiceExpr, DicePool, DiceResults, DiceType, ExplosionResult, PoolOutcome, SharedRoll,
+};
+
+/// Where a roll was asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollSource {
+    /// Typed into the command input
+    Command,
+    /// Picked from the command history
+    History,
+    /// Quick roll panel button
+    QuickRoll,
+    /// The dice already in the box were thrown again
+    Reroll,
+    /// Dice button on the character sheet
+    CharacterSheet,
+    /// A step of a replayed macro
+    Macro,
+    /// A throw of a group check from the Party tab
+    GroupCheck,
+    /// A monster's attack, damage or save from the Monsters tab
+    Compendium,
+}
+
+/// A roll was asked for
+#[derive(Message, Clone, Debug)]
+pub struct RollRequested {
+    pub config: DiceConfig,
+    pub source: RollSource,
+    /// Command to remember in the command history, if any
+    pub command: Option<String>,
+}
+
+/// Dice entities were added to the box
+#[derive(Message, Clone, Debug, Default)]
+pub struct DiceSpawned {
+    pub dice: Vec<(Entity, DiceType)>,
+}
+
+/// Fired when a dice roll has fully settled and face values have been determined.
+#[derive(Message, Clone, Debug, Default)]
+pub struct DiceSettled {
+    pub results: Vec<DieRollOutcome>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DieRollOutcome {
+    pub entity: Entity,
+    pub die_type: DiceType,
+    pub value: u32,
+}
+
+/// A roll was aborted before its dice were read; it has no result
+#[derive(Message, Clone, Debug)]
+pub struct RollCancelled {
+    pub config: DiceConfig,
+}
+
+impl RollCancelled {
+    /// What the roll was for ("Stealth"), or its expression or dice
+    /// ("2d6+3", "2D6") for plain dice
+    pub fn label(&self) -> String {
+        if !self.config.modifier_name.is_empty() {
+            return self.config.modifier_name.clone();
+        }
+        if let Some(expression) = &self.config.expression {
+            return expression.to_string();
+        }
+        dice_label(self.config.dice_to_roll.iter().copied())
+    }
+}
+
+/// The dice rolled, counted by type ("2D6+1D8")
+fn dice_label(dice: impl Iterator<Item = DiceType>) -> String {
+    let mut counts: Vec<(DiceType, usize)> = Vec::new();
+    for die in dice {
+        match counts.iter_mut().find(|(counted, _)| *counted == die) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((die, 1)),
+        }
+    }
+    counts
+        .iter()
+        .map(|(die, count)| format!("{}{}", count, die.name()))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// The totals of a finished roll
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct RollResolved {
+    /// What the roll was for ("Perception", "Dexterity save"), empty for
+    /// plain dice
+    pub label: String,
+    pub results: Vec<(DiceType, u32)>,
+    /// Indices into `results` in the order the dice came to rest
+    pub settle_order: Vec<usize>,
+    pub explosions: Vec<ExplosionResult>,
+    pub expression: Option<DiceExpr>,
+    /// Total of the dice (after keep/drop rules and explosions)
+    pub dice_total: i32,
+    pub modifier: i32,
+    /// `dice_total + modifier`
+    pub total: i32,
+    /// Face of the d20 when the roll was a single d20
+    pub natural_d20: Option<u32>,
+    /// Successes of a dice pool roll, which are its dice total
+    pub pool: Option<PoolOutcome>,
+}
+
+impl RollResolved {
+    /// Resolve the dice in `results` as rolled for `config`.
+    pub fn new(config: &DiceConfig, results: &DiceResults) -> Self {
+        let dice_total = config.dice_total_with_explosions(&results.results, &results.explosions);
+        let natural_d20 = match results.results.as_slice() {
+            [(DiceType::D20, value)] if config.dice_to_roll == [DiceType::D20] => Some(*value),
+            _ => None,
+        };
+        Self {
+            label: config.modifier_name.clone(),
+            results: results.results.clone(),
+            settle_order: results.settle_order.clone(),
+            explosions: results.explosions.clone(),
+            expression: config.expression.clone(),
+            dice_total,
+            modifier: config.modifier,
+            total: dice_total + config.modifier,
+            natural_d20,
+            pool: None,
+        }
+    }
+
+    /// Count successes instead of adding up the dice when the roll is a
+    /// plain pool of `pool`'s die. The modifier adds automatic successes.
+    pub fn with_dice_pool(mut self, pool: Option<DicePool>) -> Self {
+        let Some(pool) = pool else {
+            return self;
+        };
+        let outcome = match &self.expression {
+            Some(expression) if pool.matches(expression) => expression
+                .roll_with_explosions(&self.results, &self.explosions)
+                .ok()
+                .map(|roll| pool.count(roll.rolls.iter().map(|r| r.value))),
+            None if !self.results.is_empty()
+                && self.results.iter().all(|(die, _)| *die == pool.die) =>
+            {
+                Some(pool.count(self.results.iter().map(|(_, value)| *value)))
+            }
+            _ => None,
+        };
+        if let Some(outcome) = outcome {
+            self.dice_total = outcome.successes();
+            self.total = self.dice_total + self.modifier;
+            self.pool = Some(outcome);
+        }
+        self
+    }
+
+    /// The roll as sent to other participants, before it is redacted for
+    /// them. Plain dice are labelled with their expression, or the dice
+    /// rolled ("2D6+1D8").
+    pub fn to_shared(&self, character: Option<&str>) -> SharedRoll {
+        let label = if !self.label.is_empty() {
+            self.label.clone()
+        } else if let Some(expression) = &self.expression {
+            expression.to_string()
+        } else {
+            dice_label(self.results.iter().map(|(die, _)| *die))
+        };
+        SharedRoll {
+            character: character
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            label,
+            dice: self.results.clone(),
+            modifier: self.modifier,
+            total: Some(self.total),
+            color: None,
+        }
+    }
+
+    /// Explosion and keep/drop details of an expression roll
+    /// ("exploded 6+2; kept 6+2, 5; dropped 1"), empty when there are none.
+    pub fn details(&self) -> Vec<String> {
+        self.expression
+            .as_ref()
+            .and_then(|expression| {
+                expression
+                    .roll_with_explosions(&self.results, &self.explosions)
+                    .ok()
+            })
+            .map(|roll| {
+                [roll.explosion_summary(), roll.keep_summary()]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::GameSystem;
+
+    fn config(dice: &str, modifier: i32) -> DiceConfig {
+        let (dice_to_roll, expression) =
+            DiceConfig::from_expression(&DiceExpr::parse(dice).unwrap());
+        DiceConfig {
+            dice_to_roll,
+            modifier,
+            modifier_name: "Stealth".to_string(),
+            expression,
+        }
+    }
+
+    #[test]
+    fn test_resolve_single_d20() {
+        let results = DiceResults {
+            results: vec![(DiceType::D20, 20)],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let resolved = RollResolved::new(&config("1d20", 5), &results);
+        assert_eq!(resolved.label, "Stealth");
+        assert_eq!(resolved.dice_total, 20);
+        assert_eq!(resolved.total, 25);
+        assert_eq!(resolved.natural_d20, Some(20));
+        assert!(resolved.details().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_expression() {
+        let results = DiceResults {
+            results: vec![
+                (DiceType::D6, 6),
+                (DiceType::D6, 1),
+                (DiceType::D6, 4),
+                (DiceType::D6, 3),
+            ],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let resolved = RollResolved::new(&config("4d6kh3", 0), &results);
+        assert_eq!(resolved.dice_total, 13);
+        assert_eq!(resolved.natural_d20, None);
+        assert_eq!(resolved.details(), vec!["kept 6, 4, 3; dropped 1"]);
+    }
+
+    #[test]
+    fn test_shared_roll() {
+        let results = DiceResults {
+            results: vec![(DiceType::D20, 14)],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let shared = RollResolved::new(&config("1d20", 3), &results).to_shared(Some("Thorin"));
+        assert_eq!(shared.character.as_deref(), Some("Thorin"));
+        assert_eq!(shared.label, "Stealth");
+        assert_eq!(shared.dice, vec![(DiceType::D20, 14)]);
+        assert_eq!(shared.modifier, 3);
+        assert_eq!(shared.total, Some(17));
+
+        let mut plain = config("2d6+1d8", 0);
+        plain.modifier_name.clear();
+        let results = DiceResults {
+            results: vec![(DiceType::D6, 2), (DiceType::D8, 7), (DiceType::D6, 5)],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let shared = RollResolved::new(&plain, &results).to_shared(Some(""));
+        assert_eq!(shared.character, None);
+        assert_eq!(shared.label, "2D6+1D8");
+    }
+
+    #[test]
+    fn test_cancelled_roll_label() {
+        let cancelled = RollCancelled {
+            config: config("1d20", 5),
+        };
+        assert_eq!(cancelled.label(), "Stealth");
+
+        let plain = DiceConfig {
+            dice_to_roll: vec![DiceType::D6, DiceType::D8, DiceType::D6],
+            modifier: 0,
+            modifier_name: String::new(),
+            expression: None,
+        };
+        assert_eq!(RollCancelled { config: plain }.label(), "2D6+1D8");
+    }
+
+    #[test]
+    fn test_resolve_dice_pool() {
+        let wod = GameSystem::WorldOfDarkness.dice_pool();
+        let pool_config = |dice: &str, modifier: i32| {
+            let expression = wod.unwrap().prepare(DiceExpr::parse(dice).unwrap());
+            let (dice_to_roll, expression) = DiceConfig::from_expression(&expression);
+            DiceConfig {
+                dice_to_roll,
+                modifier,
+                modifier_name: String::new(),
+                expression,
+            }
+        };
+        let results = DiceResults {
+            results: vec![(DiceType::D10, 10), (DiceType::D10, 8), (DiceType::D10, 2)],
+            explosions: vec![ExplosionResult {
+                chain: 0,
+                die: DiceType::D10,
+                value: 9,
+            }],
+            settle_order: Vec::new(),
+        };
+        let resolved = RollResolved::new(&pool_config("3d10", 1), &results).with_dice_pool(wod);
+        assert_eq!(resolved.pool.map(|p| p.successes()), Some(3));
+        assert_eq!(resolved.total, 4);
+
+        // Anything but a plain pool is added up as usual
+        let d20 = DiceResults {
+            results: vec![(DiceType::D20, 12)],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let resolved = RollResolved::new(&config("1d20", 5), &d20).with_dice_pool(wod);
+        assert_eq!(resolved.pool, None);
+        assert_eq!(resolved.total, 17);
+
+        let shadowrun = GameSystem::Shadowrun.dice_pool();
+        let sixes = DiceResults {
+            results: vec![(DiceType::D6, 6), (DiceType::D6, 5), (DiceType::D6, 2)],
+            explosions: Vec::new(),
+            settle_order: Vec::new(),
+        };
+        let resolved = RollResolved::new(&config("3d6", 0), &sixes).with_dice_pool(shadowrun);
+        assert_eq!(resolved.dice_total, 2);
+    }
+}
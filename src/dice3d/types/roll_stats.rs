@@ -0,0 +1,424 @@
+//! Roll statistics
+//!
+//! Every roll resolved in the 3D app is saved to the database as a
+//! [`RollRecord`]. [`RollStats`] sums the saved rolls up for the Stats tab
+//! and the `stats-report` command: how often each face of each die came up,
+//! the average d20, natural 20s and 1s, and each character's luck per day.
+//! Lifetime roll counts also drive [`DiceWear`], a cosmetic easter egg that
+//! makes much-used dice look worn.
+
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::DiceType;
+
+/// Average face of a fair d20
+pub const EXPECTED_D20_AVERAGE: f64 = 10.5;
+
+pub(crate) const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Seconds since the Unix epoch.
+pub fn now_seconds() -> u64 {
+    // The browser has no system clock in std.
+    #[cfg(target_arch = "wasm32")]
+    let now = (js_sys::Date::now() / 1000.0) as u64;
+    #[cfg(not(target_arch = "wasm32"))]
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    now
+}
+
+/// One saved roll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollRecord {
+    /// Character loaded when the roll was made, empty when there was none
+    #[serde(default)]
+    pub character: String,
+    /// What the roll was for ("Perception", "Dexterity save")
+    #[serde(default)]
+    pub label: String,
+    /// Every die rolled and its face
+    pub dice: Vec<(DiceType, u32)>,
+    pub total: i32,
+    /// Seconds since the Unix epoch
+    pub rolled_at: u64,
+}
+
+impl RollRecord {
+    /// A roll made now.
+    pub fn new(character: &str, label: &str, dice: Vec<(DiceType, u32)>, total: i32) -> Self {
+        Self {
+            character: character.to_string(),
+            label: label.to_string(),
+            dice,
+            total,
+            rolled_at: now_seconds(),
+        }
+    }
+
+    /// Days since the Unix epoch (UTC) of the roll.
+    pub fn day(&self) -> u64 {
+        self.rolled_at / SECONDS_PER_DAY
+    }
+
+    fn d20_faces(&self) -> impl Iterator<Item = u32> + '_ {
+        self.dice
+            .iter()
+            .filter(|(die, _)| *die == DiceType::D20)
+            .map(|(_, face)| *face)
+    }
+}
+
+/// How often each face of one die type came up
+#[derive(Debug, Clone, PartialEq)]
+pub struct DieDistribution {
+    pub die: DiceType,
+    /// `counts[face - 1]`
+    pub counts: Vec<u32>,
+}
+
+impl DieDistribution {
+    fn new(die: DiceType) -> Self {
+        Self {
+            die,
+            counts: vec![0; die.max_value() as usize],
+        }
+    }
+
+    /// Number of times the die was rolled.
+    pub fn rolls(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// Average face rolled.
+    pub fn average(&self) -> f64 {
+        let rolls = self.rolls();
+        if rolls == 0 {
+            return 0.0;
+        }
+        let sum: u64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i as u64 + 1) * *count as u64)
+            .sum();
+        sum as f64 / rolls as f64
+    }
+
+    /// Average face of a fair die of this type.
+    pub fn expected_average(&self) -> f64 {
+        (self.die.max_value() as f64 + 1.0) / 2.0
+    }
+}
+
+/// A character's d20 rolls on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuckPoint {
+    /// Days since the Unix epoch (UTC)
+    pub day: u64,
+    pub d20_rolls: u32,
+    pub average_d20: f64,
+}
+
+/// A character's d20 luck, overall and per day
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterLuck {
+    /// Character name, empty for rolls made without a character
+    pub character: String,
+    pub d20_rolls: u32,
+    pub average_d20: f64,
+    /// Days with d20 rolls, oldest first
+    pub days: Vec<LuckPoint>,
+}
+
+impl CharacterLuck {
+    /// How far the average d20 is above (lucky) or below (unlucky) a fair die.
+    pub fn luck(&self) -> f64 {
+        self.average_d20 - EXPECTED_D20_AVERAGE
+    }
+}
+
+/// Totals over a set of saved rolls
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RollStats {
+    pub rolls: usize,
+    /// One entry per die type rolled, fewest sides first
+    pub distributions: Vec<DieDistribution>,
+    pub d20_rolls: u32,
+    pub average_d20: Option<f64>,
+    pub natural_20s: u32,
+    pub natural_1s: u32,
+    /// One entry per character with d20 rolls, by name
+    pub luck: Vec<CharacterLuck>,
+}
+
+impl RollStats {
+    pub fn from_records(records: &[RollRecord]) -> Self {
+        let mut distributions: Vec<DieDistribution> = Vec::new();
+        for (die, face) in records.iter().flat_map(|r| r.dice.iter()) {
+            if *face == 0 || *face > die.max_value() {
+                continue;
+            }
+            let index = match distributions.iter().position(|d| d.die == *die) {
+                Some(index) => index,
+                None => {
+                    distributions.push(DieDistribution::new(*die));
+                    distributions.len() - 1
+                }
+            };
+            distributions[index].counts[*face as usize - 1] += 1;
+        }
+        distributions.sort_by_key(|d| d.die.max_value());
+
+        let d20 = distributions.iter().find(|d| d.die == DiceType::D20);
+        let d20_rolls = d20.map(DieDistribution::rolls).unwrap_or(0);
+
+        Self {
+            rolls: records.len(),
+            d20_rolls,
+            average_d20: d20.filter(|d| d.rolls() > 0).map(DieDistribution::average),
+            natural_20s: d20.map(|d| d.counts[19]).unwrap_or(0),
+            natural_1s: d20.map(|d| d.counts[0]).unwrap_or(0),
+            luck: character_luck(records),
+            distributions,
+        }
+    }
+
+    /// Distribution of one die type, if it was rolled.
+    pub fn distribution(&self, die: DiceType) -> Option<&DieDistribution> {
+        self.distributions.iter().find(|d| d.die == die)
+    }
+
+    /// Luck of one character (case-insensitive), if they rolled a d20.
+    pub fn character_luck(&self, name: &str) -> Option<&CharacterLuck> {
+        self.luck
+            .iter()
+            .find(|luck| luck.character.eq_ignore_ascii_case(name))
+    }
+}
+
+fn character_luck(records: &[RollRecord]) -> Vec<CharacterLuck> {
+    let mut names: Vec<&str> = records.iter().map(|r| r.character.as_str()).collect();
+    names.sort_unstable();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let mut rolls: Vec<(u64, u32)> = records
+                .iter()
+                .filter(|r| r.character == name)
+                .flat_map(|r| r.d20_faces().map(move |face| (r.day(), face)))
+                .collect();
+            if rolls.is_empty() {
+                return None;
+            }
+            rolls.sort_by_key(|(day, _)| *day);
+
+            let mut days: Vec<LuckPoint> = Vec::new();
+            for (day, face) in &rolls {
+                match days.last_mut() {
+                    Some(point) if point.day == *day => {
+                        point.average_d20 += *face as f64;
+                        point.d20_rolls += 1;
+                    }
+                    _ => days.push(LuckPoint {
+                        day: *day,
+                        d20_rolls: 1,
+                        average_d20: *face as f64,
+                    }),
+                }
+            }
+            for point in &mut days {
+                point.average_d20 /= point.d20_rolls as f64;
+            }
+
+            let sum: u64 = rolls.iter().map(|(_, face)| *face as u64).sum();
+            Some(CharacterLuck {
+                character: name.to_string(),
+                d20_rolls: rolls.len() as u32,
+                average_d20: sum as f64 / rolls.len() as f64,
+                days,
+            })
+        })
+        .collect()
+}
+
+/// Number of times a die type was rolled over all saved rolls.
+pub fn lifetime_rolls(records: &[RollRecord], die: DiceType) -> u32 {
+    records
+        .iter()
+        .flat_map(|r| r.dice.iter())
+        .filter(|(d, _)| *d == die)
+        .count() as u32
+}
+
+/// How worn a die looks after many rolls (cosmetic only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiceWear {
+    New,
+    Scuffed,
+    Worn,
+    Battered,
+}
+
+impl DiceWear {
+    /// Lifetime rolls that unlock each wear level, lowest first
+    pub const THRESHOLDS: [(u32, DiceWear); 3] = [
+        (100, DiceWear::Scuffed),
+        (500, DiceWear::Worn),
+        (2_000, DiceWear::Battered),
+    ];
+
+    /// Wear of a die rolled `rolls` times.
+    pub fn from_rolls(rolls: u32) -> Self {
+        Self::THRESHOLDS
+            .iter()
+            .rev()
+            .find(|(threshold, _)| rolls >= *threshold)
+            .map(|(_, wear)| *wear)
+            .unwrap_or(DiceWear::New)
+    }
+
+    /// The next wear level and the rolls it needs, `None` when fully worn.
+    pub fn next(rolls: u32) -> Option<(u32, DiceWear)> {
+        Self::THRESHOLDS
+            .iter()
+            .find(|(threshold, _)| rolls < *threshold)
+            .copied()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiceWear::New => "New",
+            DiceWear::Scuffed => "Scuffed",
+            DiceWear::Worn => "Worn",
+            DiceWear::Battered => "Battered",
+        }
+    }
+
+    /// How strongly the die's finish is dulled, 0 (new) to 1.
+    pub fn amount(&self) -> f32 {
+        match self {
+            DiceWear::New => 0.0,
+            DiceWear::Scuffed => 0.3,
+            DiceWear::Worn => 0.6,
+            DiceWear::Battered => 0.9,
+        }
+    }
+}
+
+/// `YYYY-MM-DD` of a day counted from the Unix epoch.
+pub fn format_day(day: u64) -> String {
+    // Civil-from-days (proleptic Gregorian calendar)
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(character: &str, day: u64, dice: Vec<(DiceType, u32)>) -> RollRecord {
+        let total = dice.iter().map(|(_, face)| *face as i32).sum();
+        RollRecord {
+            character: character.to_string(),
+            label: String::new(),
+            dice,
+            total,
+            rolled_at: day * SECONDS_PER_DAY + 3_600,
+        }
+    }
+
+    #[test]
+    fn test_roll_stats_from_records() {
+        let records = vec![
+            record("Thorin", 1, vec![(DiceType::D20, 20)]),
+            record("Thorin", 1, vec![(DiceType::D20, 10)]),
+            record("Thorin", 2, vec![(DiceType::D20, 1)]),
+            record("Lyra", 2, vec![(DiceType::D6, 6), (DiceType::D6, 2)]),
+            record("", 3, vec![(DiceType::D20, 13), (DiceType::D4, 4)]),
+        ];
+        let stats = RollStats::from_records(&records);
+
+        assert_eq!(stats.rolls, 5);
+        let dice: Vec<DiceType> = stats.distributions.iter().map(|d| d.die).collect();
+        assert_eq!(dice, vec![DiceType::D4, DiceType::D6, DiceType::D20]);
+        assert_eq!(stats.distribution(DiceType::D6).unwrap().counts[5], 1);
+        assert_eq!(stats.distribution(DiceType::D6).unwrap().average(), 4.0);
+
+        assert_eq!(stats.d20_rolls, 4);
+        assert_eq!(stats.average_d20, Some(11.0));
+        assert_eq!(stats.natural_20s, 1);
+        assert_eq!(stats.natural_1s, 1);
+
+        // Lyra never rolled a d20
+        assert_eq!(stats.luck.len(), 2);
+        let thorin = stats.character_luck("thorin").unwrap();
+        assert_eq!(thorin.d20_rolls, 3);
+        assert!((thorin.luck() - (31.0 / 3.0 - 10.5)).abs() < 1e-9);
+        assert_eq!(
+            thorin.days,
+            vec![
+                LuckPoint {
+                    day: 1,
+                    d20_rolls: 2,
+                    average_d20: 15.0
+                },
+                LuckPoint {
+                    day: 2,
+                    d20_rolls: 1,
+                    average_d20: 1.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roll_stats_empty() {
+        let stats = RollStats::from_records(&[]);
+        assert_eq!(stats, RollStats::default());
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19_723), "2024-01-01");
+        assert_eq!(format_day(19_782), "2024-02-29");
+    }
+
+    #[test]
+    fn test_lifetime_rolls() {
+        let records = vec![
+            record("", 1, vec![(DiceType::D6, 3), (DiceType::D6, 5)]),
+            record("", 1, vec![(DiceType::D20, 12), (DiceType::D6, 1)]),
+        ];
+        assert_eq!(lifetime_rolls(&records, DiceType::D6), 3);
+        assert_eq!(lifetime_rolls(&records, DiceType::D20), 1);
+        assert_eq!(lifetime_rolls(&records, DiceType::D4), 0);
+    }
+
+    #[test]
+    fn test_dice_wear_thresholds() {
+        assert_eq!(DiceWear::from_rolls(0), DiceWear::New);
+        assert_eq!(DiceWear::from_rolls(99), DiceWear::New);
+        assert_eq!(DiceWear::from_rolls(100), DiceWear::Scuffed);
+        assert_eq!(DiceWear::from_rolls(1_999), DiceWear::Worn);
+        assert_eq!(DiceWear::from_rolls(50_000), DiceWear::Battered);
+
+        assert_eq!(DiceWear::next(0), Some((100, DiceWear::Scuffed)));
+        assert_eq!(DiceWear::next(500), Some((2_000, DiceWear::Battered)));
+        assert_eq!(DiceWear::next(2_000), None);
+    }
+}
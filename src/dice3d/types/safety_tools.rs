@@ -0,0 +1,156 @@
+//! Safety tools
+//!
+//! Tools agreed on at session zero so everyone at the table stays
+//! comfortable:
+//!
+//! - The X-card: anyone can tap it to pause the scene without explaining
+//!   why. In a shared session the pause goes to every connected player as a
+//!   [`SafetySignal`] that doesn't say who sent it.
+//! - Lines: content that won't appear in the game at all.
+//! - Veils: content that can happen, but off screen.
+//!
+//! The tools are optional and kept per campaign in the database's settings
+//! table under [`SAFETY_TOOLS_SETTING_KEY`].
+
+use serde::{Deserialize, Serialize};
+
+/// Database setting holding the campaign's safety tools
+pub const SAFETY_TOOLS_SETTING_KEY: &str = "safety_tools";
+
+/// Longest line or veil accepted
+pub const MAX_SAFETY_ENTRY_CHARS: usize = 120;
+
+/// An anonymous signal sent to the whole table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SafetySignal {
+    /// Someone tapped the X-card: pause the scene
+    XCard,
+}
+
+/// Which of the campaign's lists an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafetyList {
+    Lines,
+    Veils,
+}
+
+impl SafetyList {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SafetyList::Lines => "Lines",
+            SafetyList::Veils => "Veils",
+        }
+    }
+
+    /// "line", "lines", "veil" or "veils"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "line" | "lines" => Some(SafetyList::Lines),
+            "veil" | "veils" => Some(SafetyList::Veils),
+            _ => None,
+        }
+    }
+}
+
+/// A campaign's safety tools: whether the panel is shown, and its lines
+/// and veils
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyTools {
+    /// Show the safety tools panel with the X-card
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub veils: Vec<String>,
+}
+
+impl SafetyTools {
+    pub fn list(&self, list: SafetyList) -> &[String] {
+        match list {
+            SafetyList::Lines => &self.lines,
+            SafetyList::Veils => &self.veils,
+        }
+    }
+
+    fn list_mut(&mut self, list: SafetyList) -> &mut Vec<String> {
+        match list {
+            SafetyList::Lines => &mut self.lines,
+            SafetyList::Veils => &mut self.veils,
+        }
+    }
+
+    /// Add an entry to a list; an entry already on either list (ignoring
+    /// case) is refused.
+    pub fn add(&mut self, list: SafetyList, entry: &str) -> Result<(), String> {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return Err(format!("Say what to add to the {}", list.label()));
+        }
+        if entry.chars().count() > MAX_SAFETY_ENTRY_CHARS {
+            return Err(format!(
+                "Keep it under {} characters",
+                MAX_SAFETY_ENTRY_CHARS
+            ));
+        }
+        for existing in [SafetyList::Lines, SafetyList::Veils] {
+            if self
+                .list(existing)
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(entry))
+            {
+                return Err(format!("'{}' is already a {}", entry, existing.label()));
+            }
+        }
+        self.list_mut(list).push(entry.to_string());
+        Ok(())
+    }
+
+    /// Remove the entry numbered `number` (from 1, as listed) from a list.
+    pub fn remove(&mut self, list: SafetyList, number: usize) -> Result<String, String> {
+        let entries = self.list_mut(list);
+        if number == 0 || number > entries.len() {
+            return Err(format!(
+                "{} has no entry {} ({} listed)",
+                list.label(),
+                number,
+                entries.len()
+            ));
+        }
+        Ok(entries.remove(number - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_and_veils() {
+        let mut tools = SafetyTools::default();
+        tools.add(SafetyList::Lines, " Harm to children ").unwrap();
+        tools.add(SafetyList::Veils, "Torture").unwrap();
+        assert_eq!(tools.lines, vec!["Harm to children".to_string()]);
+        assert!(tools.add(SafetyList::Veils, "torture").is_err());
+        assert!(tools.add(SafetyList::Lines, "TORTURE").is_err());
+        assert!(tools.add(SafetyList::Lines, "  ").is_err());
+        assert!(tools.add(SafetyList::Lines, &"x".repeat(200)).is_err());
+
+        assert!(tools.remove(SafetyList::Veils, 2).is_err());
+        assert_eq!(tools.remove(SafetyList::Veils, 1).unwrap(), "Torture");
+        assert!(tools.veils.is_empty());
+        assert_eq!(SafetyList::from_name("Veil"), Some(SafetyList::Veils));
+    }
+
+    #[test]
+    fn test_signal_and_settings_json() {
+        assert_eq!(
+            serde_json::to_value(SafetySignal::XCard).unwrap(),
+            serde_json::json!("x-card")
+        );
+        let tools: SafetyTools = serde_json::from_str(r#"{"lines":["Spiders"]}"#).unwrap();
+        assert!(!tools.enabled);
+        assert_eq!(tools.lines, vec!["Spiders".to_string()]);
+    }
+}
@@ -2,11 +2,15 @@
 //!
 //! This module handles loading and saving application settings.
 
-use super::DiceType;
+use super::{
+    CustomTheme, DiceConfig, DiceType, DiceTypeExt, GameSystem, KeyAction, KeyBindings, MacroStep,
+    ResultOrder, ThemeToken, ThemeTokens, DEFAULT_EXPLOSION_CAP,
+};
 use bevy::log::info;
 use bevy::prelude::*;
 use csscolorparser;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::database::CharacterDatabase;
 use super::ui::{
@@ -95,6 +99,250 @@ impl DiceRollFxMapping {
     }
 }
 
+// ============================================================================
+// Dice Skins
+// ============================================================================
+
+/// The look of one die type: body and number colors, surface finish and an
+/// optional texture image (a file path, or a path under `assets/`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiceSkin {
+    pub name: String,
+    pub base_color: ColorSetting,
+    pub number_color: ColorSetting,
+    #[serde(default = "default_skin_metallic")]
+    pub metallic: f32,
+    #[serde(default = "default_skin_roughness")]
+    pub roughness: f32,
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+fn default_skin_metallic() -> f32 {
+    0.1
+}
+
+fn default_skin_roughness() -> f32 {
+    0.15
+}
+
+/// Built-in skins offered in the Dice tab of the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceSkinPreset {
+    Crystal,
+    Obsidian,
+    Gold,
+    Bone,
+    Emerald,
+    Ruby,
+    Steel,
+}
+
+impl DiceSkinPreset {
+    pub const ALL: [DiceSkinPreset; 7] = [
+        DiceSkinPreset::Crystal,
+        DiceSkinPreset::Obsidian,
+        DiceSkinPreset::Gold,
+        DiceSkinPreset::Bone,
+        DiceSkinPreset::Emerald,
+        DiceSkinPreset::Ruby,
+        DiceSkinPreset::Steel,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiceSkinPreset::Crystal => "Crystal",
+            DiceSkinPreset::Obsidian => "Obsidian",
+            DiceSkinPreset::Gold => "Gold",
+            DiceSkinPreset::Bone => "Bone",
+            DiceSkinPreset::Emerald => "Emerald",
+            DiceSkinPreset::Ruby => "Ruby",
+            DiceSkinPreset::Steel => "Steel",
+        }
+    }
+
+    /// The preset with this name, if `name` is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.label().eq_ignore_ascii_case(name))
+    }
+
+    /// The skin this preset gives a die type (crystal keeps each type's own color).
+    pub fn skin(&self, die_type: DiceType) -> DiceSkin {
+        let white = ColorSetting::from_color(Color::WHITE);
+        let (base_color, number_color, metallic, roughness) = match self {
+            DiceSkinPreset::Crystal => (die_type.color(), white, 0.1, 0.15),
+            DiceSkinPreset::Obsidian => (
+                Color::srgba(0.04, 0.03, 0.06, 0.97),
+                ColorSetting::from_color(Color::srgb(0.85, 0.7, 1.0)),
+                0.2,
+                0.05,
+            ),
+            DiceSkinPreset::Gold => (
+                Color::srgb(0.83, 0.66, 0.22),
+                ColorSetting::from_color(Color::srgb(0.15, 0.1, 0.02)),
+                1.0,
+                0.3,
+            ),
+            DiceSkinPreset::Bone => (
+                Color::srgb(0.89, 0.85, 0.74),
+                ColorSetting::from_color(Color::srgb(0.25, 0.15, 0.1)),
+                0.0,
+                0.8,
+            ),
+            DiceSkinPreset::Emerald => (Color::srgba(0.05, 0.6, 0.3, 0.9), white, 0.1, 0.1),
+            DiceSkinPreset::Ruby => (Color::srgba(0.75, 0.05, 0.12, 0.9), white, 0.1, 0.1),
+            DiceSkinPreset::Steel => (
+                Color::srgb(0.6, 0.62, 0.65),
+                ColorSetting::from_color(Color::srgb(0.05, 0.05, 0.08)),
+                1.0,
+                0.45,
+            ),
+        };
+        DiceSkin {
+            name: self.label().to_string(),
+            base_color: ColorSetting::from_color(base_color),
+            number_color,
+            metallic,
+            roughness,
+            texture: None,
+        }
+    }
+}
+
+/// The skin chosen for one die type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiceSkinMapping {
+    pub die_type: DiceType,
+    pub skin: DiceSkin,
+}
+
+/// The skin of a die type in `mappings`, crystal when none was chosen.
+pub fn dice_skin_for(mappings: &[DiceSkinMapping], die_type: DiceType) -> DiceSkin {
+    mappings
+        .iter()
+        .find(|m| m.die_type == die_type)
+        .map(|m| m.skin.clone())
+        .unwrap_or_else(|| DiceSkinPreset::Crystal.skin(die_type))
+}
+
+/// Choose the skin of a die type in `mappings`.
+pub fn set_dice_skin(mappings: &mut Vec<DiceSkinMapping>, die_type: DiceType, skin: DiceSkin) {
+    match mappings.iter_mut().find(|m| m.die_type == die_type) {
+        Some(mapping) => mapping.skin = skin,
+        None => mappings.push(DiceSkinMapping { die_type, skin }),
+    }
+}
+
+// ============================================================================
+// Custom Quick Rolls
+// ============================================================================
+
+/// A button added to the quick roll panel: a labelled dice expression
+/// (`2d6+3`, `1d20+5 adv`) with an optional color and Material icon name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomQuickRoll {
+    pub label: String,
+    pub expression: String,
+    #[serde(default)]
+    pub color: Option<ColorSetting>,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+impl CustomQuickRoll {
+    /// A button from the fields of the editor; color and icon may be left
+    /// empty. The icon name is not checked here.
+    pub fn new(label: &str, expression: &str, color: &str, icon: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        if expression.is_empty() {
+            return Err("Enter the dice to roll".to_string());
+        }
+        let label = match label.trim() {
+            "" => expression,
+            label => label,
+        };
+        let color = match color.trim() {
+            "" => None,
+            text => Some(
+                ColorSetting::parse(text).ok_or_else(|| format!("'{}' is not a color", text))?,
+            ),
+        };
+        let icon = Some(icon.trim())
+            .filter(|icon| !icon.is_empty())
+            .map(str::to_string);
+
+        let roll = Self {
+            label: label.to_string(),
+            expression: expression.to_string(),
+            color,
+            icon,
+        };
+        roll.dice_config()?;
+        Ok(roll)
+    }
+
+    /// The dice to throw for the button.
+    pub fn dice_config(&self) -> Result<DiceConfig, String> {
+        if self.expression.contains(':') {
+            return Err(format!("'{}' is not a dice expression", self.expression));
+        }
+        MacroStep::parse_roll(&self.expression, &self.label)?
+            .dice_config()
+            .unwrap_or_else(|| Err(format!("'{}' is not a roll", self.expression)))
+    }
+}
+
+/// Move the custom quick roll at `from` to `to`, shifting the ones between.
+pub fn move_custom_quick_roll(rolls: &mut Vec<CustomQuickRoll>, from: usize, to: usize) -> bool {
+    if from == to || from >= rolls.len() || to >= rolls.len() {
+        return false;
+    }
+    let roll = rolls.remove(from);
+    rolls.insert(to, roll);
+    true
+}
+
+// ============================================================================
+// Chaos Dice
+// ============================================================================
+
+/// Colors chaos dice are painted with when the palette hasn't been edited.
+pub fn default_chaos_dice_palette() -> Vec<ColorSetting> {
+    [
+        "#E53935", "#FB8C00", "#FDD835", "#43A047", "#1E88E5", "#8E24AA",
+    ]
+    .iter()
+    .filter_map(|hex| ColorSetting::parse(hex))
+    .collect()
+}
+
+/// A palette typed as colors separated by commas (`#FF8844, teal, gold`).
+pub fn parse_color_palette(text: &str) -> Result<Vec<ColorSetting>, String> {
+    let palette = text
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            ColorSetting::parse(entry).ok_or_else(|| format!("'{}' is not a color", entry))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if palette.is_empty() {
+        return Err("The palette needs at least one color".to_string());
+    }
+    Ok(palette)
+}
+
+/// The palette as it is typed in the Colors tab.
+pub fn format_color_palette(palette: &[ColorSetting]) -> String {
+    palette
+        .iter()
+        .map(ColorSetting::to_hex)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // ============================================================================
 // Persistent Shake Curve Settings
 // ============================================================================
@@ -260,7 +508,7 @@ impl DiceTypeSetting {
 }
 
 /// Simple serializable RGBA color.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ColorSetting {
     #[serde(default)]
     pub a: f32,
@@ -525,6 +773,113 @@ pub struct AppSettings {
     /// Multiplier for the plume FX radius (fire/atomic).
     #[serde(default = "default_dice_fx_plume_radius_multiplier")]
     pub dice_fx_plume_radius_multiplier: f32,
+
+    /// Playback speed for UI/scene animations (dice box lid, tweens). 1.0 = normal.
+    #[serde(default = "default_animation_speed")]
+    pub animation_speed: f32,
+
+    /// Manual UI scale multiplier. `None` follows each monitor's scale factor.
+    #[serde(default)]
+    pub ui_scale_override: Option<f32>,
+
+    /// Most extra rolls one exploding die (`1d6!`) may add.
+    #[serde(default = "default_explosion_cap")]
+    pub explosion_cap: u32,
+
+    /// Whether rolls are added up or counted as a dice pool.
+    #[serde(default)]
+    pub game_system: GameSystem,
+
+    /// How the dice of a roll are listed in the results panel.
+    #[serde(default)]
+    pub result_order: ResultOrder,
+
+    /// Show much-rolled dice with a worn finish (see `DiceWear`).
+    #[serde(default = "default_worn_dice")]
+    pub worn_dice: bool,
+
+    /// Step the physics at a fixed rate instead of once per frame, so the
+    /// same throw lands the same way whatever the frame rate.
+    #[serde(default)]
+    pub fixed_timestep_physics: bool,
+
+    /// "Fair but pretty": each die's result is drawn from the random number
+    /// generator when it is thrown, and the die is steered onto that face.
+    #[serde(default)]
+    pub steered_dice: bool,
+
+    /// Once a roll is read, slide its dice into a row at the front of the
+    /// container, lowest value first (see `gather_settled_dice`).
+    #[serde(default)]
+    pub gather_dice: bool,
+
+    /// Record the physics inputs of each throw and write a repro file when
+    /// a die gets stuck or a roll is flagged as mis-read (see `PhysicsRepro`).
+    #[serde(default)]
+    pub physics_debug_capture: bool,
+
+    /// Pause the physics and dim the scene while the window is in the
+    /// background, so rolls don't resolve unseen (see `pause_when_unfocused`).
+    #[serde(default)]
+    pub pause_when_unfocused: bool,
+
+    /// Skin of each die type; types without one use their crystal look.
+    #[serde(default)]
+    pub dice_skins: Vec<DiceSkinMapping>,
+
+    /// Colors of the widget tokens (see `ThemeTokens`).
+    #[serde(default)]
+    pub theme_tokens: ThemeTokens,
+
+    /// Token colors saved under a name in the Theme tab.
+    #[serde(default)]
+    pub custom_themes: Vec<CustomTheme>,
+
+    /// Volume of the dice sounds (0 to 1).
+    #[serde(default = "default_sound_volume")]
+    pub sound_volume: f32,
+
+    /// Silence the dice sounds without losing the volume.
+    #[serde(default)]
+    pub sound_muted: bool,
+
+    /// Play the tracks of `music_folder` in the background.
+    #[serde(default)]
+    pub music_enabled: bool,
+
+    /// Folder of background music tracks (empty for none).
+    #[serde(default)]
+    pub music_folder: String,
+
+    /// Volume of the background music (0 to 1), separate from the dice sounds.
+    #[serde(default = "default_music_volume")]
+    pub music_volume: f32,
+
+    /// Play the music tracks in a new random order on every pass.
+    #[serde(default = "default_music_shuffle")]
+    pub music_shuffle: bool,
+
+    /// Rumble connected gamepads when dice hit something and when they settle.
+    #[serde(default = "default_gamepad_rumble")]
+    pub gamepad_rumble: bool,
+
+    /// Buttons added to the quick roll panel, in the order they are shown.
+    #[serde(default)]
+    pub custom_quick_rolls: Vec<CustomQuickRoll>,
+
+    /// Paint every spawned die a random color from `chaos_dice_palette`,
+    /// picked again on each roll, in place of its skin's body color.
+    #[serde(default)]
+    pub chaos_dice: bool,
+
+    /// Colors chaos dice are picked from.
+    #[serde(default = "default_chaos_dice_palette")]
+    pub chaos_dice_palette: Vec<ColorSetting>,
+
+    /// Keyboard shortcuts remapped in the Keys tab; the others keep their
+    /// defaults.
+    #[serde(default)]
+    pub key_bindings: KeyBindings,
 }
 
 fn default_dice_fx_surface_opacity() -> f32 {
@@ -539,6 +894,34 @@ fn default_dice_fx_plume_radius_multiplier() -> f32 {
     1.15
 }
 
+fn default_animation_speed() -> f32 {
+    1.0
+}
+
+fn default_sound_volume() -> f32 {
+    0.8
+}
+
+fn default_music_volume() -> f32 {
+    0.5
+}
+
+fn default_music_shuffle() -> bool {
+    true
+}
+
+fn default_explosion_cap() -> u32 {
+    DEFAULT_EXPLOSION_CAP
+}
+
+fn default_worn_dice() -> bool {
+    true
+}
+
+fn default_gamepad_rumble() -> bool {
+    true
+}
+
 /// Per-die scale settings.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DiceScaleSettings {
@@ -600,11 +983,13 @@ impl DiceScaleSettings {
     pub fn scale_for(&self, die_type: DiceType) -> f32 {
         match die_type {
             DiceType::D4 => self.d4,
-            DiceType::D6 => self.d6,
+            // A Fudge die is a d6 with symbols
+            DiceType::D6 | DiceType::Fudge => self.d6,
             DiceType::D8 => self.d8,
-            DiceType::D10 => self.d10,
+            // A d100 is thrown as two d10s
+            DiceType::D10 | DiceType::Custom(100) => self.d10,
             DiceType::D12 => self.d12,
-            DiceType::D20 => self.d20,
+            DiceType::D20 | DiceType::Custom(_) => self.d20,
         }
         .clamp(Self::MIN_SCALE, Self::MAX_SCALE)
     }
@@ -619,6 +1004,7 @@ impl DiceScaleSettings {
             DiceType::D10 => self.d10 = value,
             DiceType::D12 => self.d12 = value,
             DiceType::D20 => self.d20 = value,
+            DiceType::Fudge | DiceType::Custom(_) => {}
         }
     }
 
@@ -702,6 +1088,31 @@ impl Default for AppSettings {
             dice_fx_surface_opacity: default_dice_fx_surface_opacity(),
             dice_fx_plume_height_multiplier: default_dice_fx_plume_height_multiplier(),
             dice_fx_plume_radius_multiplier: default_dice_fx_plume_radius_multiplier(),
+            animation_speed: default_animation_speed(),
+            ui_scale_override: None,
+            explosion_cap: default_explosion_cap(),
+            game_system: GameSystem::default(),
+            result_order: ResultOrder::default(),
+            worn_dice: default_worn_dice(),
+            fixed_timestep_physics: false,
+            steered_dice: false,
+            gather_dice: false,
+            physics_debug_capture: false,
+            pause_when_unfocused: false,
+            dice_skins: Vec::new(),
+            theme_tokens: ThemeTokens::default(),
+            custom_themes: Vec::new(),
+            sound_volume: default_sound_volume(),
+            sound_muted: false,
+            music_enabled: false,
+            music_folder: String::new(),
+            music_volume: default_music_volume(),
+            music_shuffle: default_music_shuffle(),
+            gamepad_rumble: default_gamepad_rumble(),
+            custom_quick_rolls: Vec::new(),
+            chaos_dice: false,
+            chaos_dice_palette: default_chaos_dice_palette(),
+            key_bindings: KeyBindings::default(),
         }
     }
 }
@@ -709,6 +1120,56 @@ impl Default for AppSettings {
 impl AppSettings {
     const SETTINGS_DB_KEY: &'static str = "app_settings";
 
+    /// Slider range for the animation speed setting.
+    pub const MIN_ANIMATION_SPEED: f32 = 0.25;
+    pub const MAX_ANIMATION_SPEED: f32 = 3.0;
+
+    /// Animation speed multiplier, clamped to the supported range.
+    pub fn animation_speed(&self) -> f32 {
+        self.animation_speed
+            .clamp(Self::MIN_ANIMATION_SPEED, Self::MAX_ANIMATION_SPEED)
+    }
+
+    /// Slider range for the explosion cap.
+    pub const MIN_EXPLOSION_CAP: u32 = 1;
+    pub const MAX_EXPLOSION_CAP: u32 = 50;
+
+    /// Explosion cap, clamped to the supported range.
+    pub fn explosion_cap(&self) -> u32 {
+        self.explosion_cap
+            .clamp(Self::MIN_EXPLOSION_CAP, Self::MAX_EXPLOSION_CAP)
+    }
+
+    /// Gain applied to every dice sound: the volume, or 0 when muted.
+    pub fn sound_gain(&self) -> f32 {
+        if self.sound_muted {
+            0.0
+        } else {
+            self.sound_volume.clamp(0.0, 1.0)
+        }
+    }
+
+    /// The skin spawned dice of this type are drawn with.
+    pub fn dice_skin(&self, die_type: DiceType) -> DiceSkin {
+        dice_skin_for(&self.dice_skins, die_type)
+    }
+
+    /// Length of one physics step (seconds) when `fixed_timestep_physics` is on.
+    pub const FIXED_PHYSICS_TIMESTEP: f32 = 1.0 / 60.0;
+
+    /// Slider range for the manual UI scale override.
+    pub const MIN_UI_SCALE: f32 = 0.5;
+    pub const MAX_UI_SCALE: f32 = 2.0;
+
+    /// UI scale multiplier applied on top of the monitor's scale factor.
+    ///
+    /// 1.0 unless a manual override is set.
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale_override
+            .map(|s| s.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE))
+            .unwrap_or(1.0)
+    }
+
     pub fn roll_fx_for(&self, die_type: DiceType, value: u32) -> DiceRollFxKind {
         if value == 0 {
             return DiceRollFxKind::None;
@@ -792,6 +1253,53 @@ impl AppSettings {
 
         Ok(())
     }
+
+    /// Serialize the settings as a settings export file.
+    pub fn to_export_json(&self) -> Result<String, String> {
+        let file = SettingsExportFile {
+            format: SETTINGS_EXPORT_FORMAT.to_string(),
+            version: SETTINGS_EXPORT_VERSION,
+            settings: self.clone(),
+        };
+        serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))
+    }
+
+    /// Parse a settings export file.
+    ///
+    /// Settings missing from the file (older exports) fall back to defaults.
+    pub fn from_export_json(json: &str) -> Result<Self, String> {
+        let file: SettingsExportFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid settings file: {}", e))?;
+        if file.format != SETTINGS_EXPORT_FORMAT {
+            return Err(format!("Not a settings file (format \"{}\")", file.format));
+        }
+        if file.version > SETTINGS_EXPORT_VERSION {
+            return Err(format!(
+                "Settings file version {} is newer than this app supports ({})",
+                file.version, SETTINGS_EXPORT_VERSION
+            ));
+        }
+        Ok(file.settings)
+    }
+}
+
+/// `format` tag of settings export files.
+pub const SETTINGS_EXPORT_FORMAT: &str = "dndgamerolls-settings";
+
+/// Current version of the settings export file.
+pub const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// Default file name offered when exporting settings.
+pub const SETTINGS_EXPORT_FILE_NAME: &str = "dndgamerolls-settings.json";
+
+/// A settings export: theme, colors, dice, shake curve and panel layout in a
+/// single JSON file, for moving a setup between machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsExportFile {
+    format: String,
+    version: u32,
+    settings: AppSettings,
 }
 
 /// Tracks which modal dialog is currently active.
@@ -869,6 +1377,117 @@ pub struct SettingsState {
     pub editing_dice_fx_surface_opacity: f32,
     pub editing_dice_fx_plume_height_multiplier: f32,
     pub editing_dice_fx_plume_radius_multiplier: f32,
+
+    /// Editing value for the animation speed (applied on OK).
+    pub editing_animation_speed: f32,
+
+    /// Editing value for the UI scale override (applied on OK).
+    pub editing_ui_scale_override: Option<f32>,
+
+    /// Editing value for the explosion cap (applied on OK).
+    pub editing_explosion_cap: u32,
+
+    /// Editing value for the game system (applied on OK).
+    pub game_system_editing: GameSystem,
+
+    /// Editing value for the result order (applied on OK).
+    pub result_order_editing: ResultOrder,
+
+    /// Editing value for the fixed-timestep physics switch (applied on OK).
+    pub fixed_timestep_physics_editing: bool,
+
+    /// Editing value for the "fair but pretty" switch (applied on OK).
+    pub steered_dice_editing: bool,
+
+    /// Editing value for the gather dice switch (applied on OK).
+    pub gather_dice_editing: bool,
+
+    /// Editing value for the physics debug capture switch (applied on OK).
+    pub physics_debug_capture_editing: bool,
+
+    /// Editing value for the pause when unfocused switch (applied on OK).
+    pub pause_when_unfocused_editing: bool,
+
+    /// Editing value for the gamepad rumble switch (applied on OK).
+    pub gamepad_rumble_editing: bool,
+
+    /// Editing values for the dice skins (applied on OK).
+    pub editing_dice_skins: Vec<DiceSkinMapping>,
+
+    /// Editing values for chaos dice: the switch and the palette as typed
+    /// (applied on OK when the palette parses).
+    pub chaos_dice_editing: bool,
+    pub chaos_dice_palette_input: String,
+
+    /// Editing values for the widget tokens (previewed live, applied on OK).
+    pub editing_theme_tokens: ThemeTokens,
+
+    /// Name typed in the Theme tab for saving a custom theme.
+    pub custom_theme_name_input: String,
+
+    /// Editing values for the dice sound volume and mute (applied on OK).
+    pub editing_sound_volume: f32,
+    pub sound_muted_editing: bool,
+
+    /// Editing values for the background music (applied on OK).
+    pub music_enabled_editing: bool,
+    pub editing_music_folder: String,
+    pub editing_music_volume: f32,
+    pub music_shuffle_editing: bool,
+
+    /// Editing values for the keyboard shortcuts (applied on OK), and the
+    /// action whose button is waiting for a key.
+    pub editing_key_bindings: KeyBindings,
+    pub capturing_key_binding: Option<KeyAction>,
+}
+
+impl SettingsState {
+    /// Replace the settings and reset every editing value to match them.
+    pub fn replace_settings(&mut self, settings: AppSettings) {
+        self.character_sheet_editing_die = settings.character_sheet_default_die;
+        self.quick_roll_editing_die = settings.quick_roll_default_die;
+        self.default_roll_uses_shake_editing = settings.default_roll_uses_shake;
+
+        self.editing_color = settings.background_color.clone();
+        self.editing_highlight_color = settings.dice_box_highlight_color.clone();
+        self.editing_shake_config = settings.shake_config.to_runtime();
+        self.last_saved_shake_config = settings.shake_config.clone();
+
+        self.editing_dice_scales = settings.dice_scales.clone();
+        self.editing_dice_roll_fx_mappings = settings.dice_roll_fx_mappings.clone();
+        self.editing_dice_fx_surface_opacity = settings.dice_fx_surface_opacity;
+        self.editing_dice_fx_plume_height_multiplier = settings.dice_fx_plume_height_multiplier;
+        self.editing_dice_fx_plume_radius_multiplier = settings.dice_fx_plume_radius_multiplier;
+        self.editing_animation_speed = settings.animation_speed;
+        self.editing_ui_scale_override = settings.ui_scale_override;
+        self.editing_explosion_cap = settings.explosion_cap;
+        self.game_system_editing = settings.game_system;
+        self.result_order_editing = settings.result_order;
+        self.fixed_timestep_physics_editing = settings.fixed_timestep_physics;
+        self.steered_dice_editing = settings.steered_dice;
+        self.gather_dice_editing = settings.gather_dice;
+        self.physics_debug_capture_editing = settings.physics_debug_capture;
+        self.pause_when_unfocused_editing = settings.pause_when_unfocused;
+        self.gamepad_rumble_editing = settings.gamepad_rumble;
+        self.editing_dice_skins = settings.dice_skins.clone();
+        self.chaos_dice_editing = settings.chaos_dice;
+        self.chaos_dice_palette_input = format_color_palette(&settings.chaos_dice_palette);
+        self.editing_theme_tokens = settings.theme_tokens.clone();
+        self.editing_sound_volume = settings.sound_volume;
+        self.sound_muted_editing = settings.sound_muted;
+        self.music_enabled_editing = settings.music_enabled;
+        self.editing_music_folder = settings.music_folder.clone();
+        self.editing_music_volume = settings.music_volume;
+        self.music_shuffle_editing = settings.music_shuffle;
+        self.editing_key_bindings = settings.key_bindings.clone();
+        self.capturing_key_binding = None;
+
+        self.color_input_text.clear();
+        self.highlight_input_text.clear();
+
+        self.settings = settings;
+        self.is_modified = false;
+    }
 }
 
 impl Default for SettingsState {
@@ -890,6 +1509,28 @@ impl Default for SettingsState {
         let editing_dice_fx_surface_opacity = settings.dice_fx_surface_opacity;
         let editing_dice_fx_plume_height_multiplier = settings.dice_fx_plume_height_multiplier;
         let editing_dice_fx_plume_radius_multiplier = settings.dice_fx_plume_radius_multiplier;
+        let editing_animation_speed = settings.animation_speed;
+        let editing_ui_scale_override = settings.ui_scale_override;
+        let editing_explosion_cap = settings.explosion_cap;
+        let game_system_editing = settings.game_system;
+        let result_order_editing = settings.result_order;
+        let fixed_timestep_physics_editing = settings.fixed_timestep_physics;
+        let steered_dice_editing = settings.steered_dice;
+        let gather_dice_editing = settings.gather_dice;
+        let physics_debug_capture_editing = settings.physics_debug_capture;
+        let pause_when_unfocused_editing = settings.pause_when_unfocused;
+        let gamepad_rumble_editing = settings.gamepad_rumble;
+        let editing_dice_skins = settings.dice_skins.clone();
+        let chaos_dice_editing = settings.chaos_dice;
+        let chaos_dice_palette_input = format_color_palette(&settings.chaos_dice_palette);
+        let editing_theme_tokens = settings.theme_tokens.clone();
+        let editing_sound_volume = settings.sound_volume;
+        let sound_muted_editing = settings.sound_muted;
+        let music_enabled_editing = settings.music_enabled;
+        let editing_music_folder = settings.music_folder.clone();
+        let editing_music_volume = settings.music_volume;
+        let music_shuffle_editing = settings.music_shuffle;
+        let editing_key_bindings = settings.key_bindings.clone();
 
         Self {
             settings,
@@ -921,6 +1562,30 @@ impl Default for SettingsState {
             editing_dice_fx_surface_opacity,
             editing_dice_fx_plume_height_multiplier,
             editing_dice_fx_plume_radius_multiplier,
+            editing_animation_speed,
+            editing_ui_scale_override,
+            editing_explosion_cap,
+            game_system_editing,
+            result_order_editing,
+            fixed_timestep_physics_editing,
+            steered_dice_editing,
+            gather_dice_editing,
+            physics_debug_capture_editing,
+            pause_when_unfocused_editing,
+            gamepad_rumble_editing,
+            editing_dice_skins,
+            chaos_dice_editing,
+            chaos_dice_palette_input,
+            editing_theme_tokens,
+            custom_theme_name_input: String::new(),
+            editing_sound_volume,
+            sound_muted_editing,
+            music_enabled_editing,
+            editing_music_folder,
+            editing_music_volume,
+            music_shuffle_editing,
+            editing_key_bindings,
+            capturing_key_binding: None,
         }
     }
 }
@@ -945,6 +1610,78 @@ pub struct DiceFxParamValueLabel {
     pub kind: DiceFxParamKind,
 }
 
+/// Marker for the animation speed slider.
+#[derive(Component, Clone, Copy)]
+pub struct AnimationSpeedSlider;
+
+/// Marker for the animation speed value label.
+#[derive(Component, Clone, Copy)]
+pub struct AnimationSpeedValueLabel;
+
+/// Marker for the dice sound volume slider in the Audio tab.
+#[derive(Component, Clone, Copy)]
+pub struct SoundVolumeSlider;
+
+/// Marker for the dice sound volume value label.
+#[derive(Component, Clone, Copy)]
+pub struct SoundVolumeValueLabel;
+
+/// Marker for the chaos dice switch in the Colors tab.
+#[derive(Component)]
+pub struct ChaosDiceSwitch;
+
+/// Marker for the chaos dice palette text field in the Colors tab.
+#[derive(Component)]
+pub struct ChaosDicePaletteInput;
+
+/// Row of swatches previewing the chaos dice palette in the Colors tab.
+#[derive(Component)]
+pub struct ChaosDicePaletteSwatches;
+
+/// Marker for the mute switch in the Audio tab.
+#[derive(Component)]
+pub struct SoundMutedSwitch;
+
+/// Marker for the background music switch in the Audio tab.
+#[derive(Component)]
+pub struct MusicEnabledSwitch;
+
+/// Marker for the music folder text field in the Audio tab.
+#[derive(Component)]
+pub struct MusicFolderInput;
+
+/// Marker for the music volume slider in the Audio tab.
+#[derive(Component, Clone, Copy)]
+pub struct MusicVolumeSlider;
+
+/// Marker for the music volume value label.
+#[derive(Component, Clone, Copy)]
+pub struct MusicVolumeValueLabel;
+
+/// Marker for the music shuffle switch in the Audio tab.
+#[derive(Component)]
+pub struct MusicShuffleSwitch;
+
+/// Marker for the explosion cap slider in Dice Roller settings.
+#[derive(Component)]
+pub struct ExplosionCapSlider;
+
+/// Marker for the explosion cap value label in Dice Roller settings.
+#[derive(Component)]
+pub struct ExplosionCapValueLabel;
+
+/// Marker for the UI scale override slider.
+#[derive(Component, Clone, Copy)]
+pub struct UiScaleSlider;
+
+/// Marker for the UI scale value label.
+#[derive(Component, Clone, Copy)]
+pub struct UiScaleValueLabel;
+
+/// Button that clears the UI scale override (back to automatic).
+#[derive(Component, Clone, Copy)]
+pub struct UiScaleAutoButton;
+
 /// Color component for slider interaction
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorComponent {
@@ -970,6 +1707,53 @@ pub struct SettingsModalOverlay;
 #[derive(Component)]
 pub struct SettingsModal;
 
+/// Scroll container of the settings tab with this index
+#[derive(Component)]
+pub struct SettingsTabScroll(pub usize);
+
+/// Content root of the settings tab with this index; the search filters its
+/// children
+#[derive(Component)]
+pub struct SettingsTabContentRoot(pub usize);
+
+/// Marker for the settings modal's tab bar
+#[derive(Component)]
+pub struct SettingsTabBar;
+
+/// Label of the settings tab with this index, showing the search's matches
+#[derive(Component)]
+pub struct SettingsTabLabel {
+    pub index: usize,
+    pub name: &'static str,
+}
+
+/// Search field at the top of the settings modal
+#[derive(Component)]
+pub struct SettingsSearchField;
+
+/// A settings entry hidden by the search, with the display it had
+#[derive(Component)]
+pub struct SettingsSearchHidden(pub Display);
+
+/// A settings entry outlined as matching the search
+#[derive(Component)]
+pub struct SettingsSearchMatch;
+
+/// Whether a settings entry showing `text` matches the search `query`:
+/// every word of the query appears in it, ignoring case.
+pub fn settings_search_matches(text: &str, query: &str) -> bool {
+    let text = text.to_lowercase();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
+
+/// Scroll position each settings tab was left at, by tab index, kept while
+/// switching tabs and between openings of the modal
+#[derive(Resource, Default)]
+pub struct SettingsTabScrollPositions(pub HashMap<usize, Vec2>);
+
 /// Marker for color preview box
 #[derive(Component)]
 pub struct ColorPreview;
@@ -996,6 +1780,50 @@ pub struct HighlightColorTextInput;
 #[derive(Component)]
 pub struct ThemeSeedTextInput;
 
+/// Marker for the hex input of one widget token in the Theme tab.
+#[derive(Component, Clone, Copy)]
+pub struct ThemeTokenTextInput {
+    pub token: ThemeToken,
+}
+
+/// Marker for the color swatch of one widget token in the Theme tab.
+#[derive(Component, Clone, Copy)]
+pub struct ThemeTokenSwatch {
+    pub token: ThemeToken,
+}
+
+/// Marker for the custom theme name input in the Theme tab.
+#[derive(Component)]
+pub struct CustomThemeNameInput;
+
+/// Marker for the Save theme button in the Theme tab.
+#[derive(Component)]
+pub struct SaveCustomThemeButton;
+
+/// Marker for the button that resets the widget tokens to the built-in colors.
+#[derive(Component)]
+pub struct ResetThemeTokensButton;
+
+/// A button in the Keys tab: click to type a new shortcut for the action.
+#[derive(Component, Clone, Copy)]
+pub struct KeyBindingButton(pub KeyAction);
+
+/// Label of a `KeyBindingButton`, showing the shortcut or "Press a key…".
+#[derive(Component, Clone, Copy)]
+pub struct KeyBindingButtonLabel(pub KeyAction);
+
+/// Button putting an action back on its default shortcut.
+#[derive(Component, Clone, Copy)]
+pub struct KeyBindingResetButton(pub KeyAction);
+
+/// Text listing the other actions on the same shortcut as this one.
+#[derive(Component, Clone, Copy)]
+pub struct KeyBindingConflictText(pub KeyAction);
+
+/// Marker for the button putting every shortcut back on its default.
+#[derive(Component)]
+pub struct ResetKeyBindingsButton;
+
 /// Marker for the shake duration (seconds) text input in the shake curve tab.
 #[derive(Component)]
 pub struct ShakeDurationTextInput;
@@ -1012,6 +1840,45 @@ pub struct SettingsCancelButton;
 #[derive(Component)]
 pub struct DefaultRollUsesShakeSwitch;
 
+/// Marker for the switch that turns on fixed-timestep physics in the Dice tab.
+#[derive(Component)]
+pub struct FixedTimestepPhysicsSwitch;
+
+/// Marker for the "fair but pretty" (steered dice) switch in the Dice tab.
+#[derive(Component)]
+pub struct SteeredDiceSwitch;
+
+/// Marker for the gather dice switch in the Dice tab.
+#[derive(Component)]
+pub struct GatherDiceSwitch;
+
+/// Marker for the physics debug capture switch in the Dice tab.
+#[derive(Component)]
+pub struct PhysicsDebugCaptureSwitch;
+
+/// Marker for the switch that pauses the physics while the window is in the
+/// background, in the Dice tab.
+#[derive(Component)]
+pub struct PauseWhenUnfocusedSwitch;
+
+/// Marker for the gamepad rumble switch in the Dice tab.
+#[derive(Component)]
+pub struct GamepadRumbleSwitch;
+
+/// Marker for the slot around the game system select in the Dice tab.
+#[derive(Component)]
+pub struct GameSystemSelect;
+
+/// Marker for the slot around the result order select in the Dice tab.
+#[derive(Component)]
+pub struct ResultOrderSelect;
+
+/// Marker for the skin select of one die type in the Dice tab.
+#[derive(Component, Clone, Copy)]
+pub struct DiceSkinSelect {
+    pub die_type: DiceType,
+}
+
 /// Marker for a per-die/per-face roll-FX mapping select.
 #[derive(Component, Clone, Copy)]
 pub struct DiceRollFxMappingSelect {
@@ -1035,6 +1902,14 @@ pub struct DiceScaleValueLabel {
 #[derive(Component)]
 pub struct SettingsResetLayoutButton;
 
+/// Marker for the settings Export Settings button
+#[derive(Component)]
+pub struct SettingsExportButton;
+
+/// Marker for the settings Import Settings button
+#[derive(Component)]
+pub struct SettingsImportButton;
+
 // ============================================================================
 // Character Sheet Dice Settings UI Components
 // ============================================================================
@@ -1077,6 +1952,18 @@ mod tests {
         v as f32 / 255.0
     }
 
+    #[test]
+    fn test_settings_search_matches() {
+        assert!(settings_search_matches("Music folder", "music"));
+        assert!(settings_search_matches(
+            "Play background music",
+            "MUSIC play"
+        ));
+        assert!(!settings_search_matches("Mute", "music"));
+        // An empty search matches everything
+        assert!(settings_search_matches("Mute", "  "));
+    }
+
     #[test]
     fn test_color_setting_parse_hex_rgb() {
         let color = ColorSetting::parse("#FF8844").unwrap();
@@ -1158,4 +2045,207 @@ mod tests {
         let setting = ColorSetting::from_color(color);
         assert_eq!(setting.to_hex(), "#80FF8844");
     }
+
+    #[test]
+    fn test_animation_speed_defaults_and_clamps() {
+        let mut settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.animation_speed(), 1.0);
+
+        settings.animation_speed = 10.0;
+        assert_eq!(settings.animation_speed(), AppSettings::MAX_ANIMATION_SPEED);
+        settings.animation_speed = 0.0;
+        assert_eq!(settings.animation_speed(), AppSettings::MIN_ANIMATION_SPEED);
+    }
+
+    #[test]
+    fn test_game_system_defaults_to_adding_dice() {
+        let settings = AppSettings::default();
+        assert_eq!(settings.game_system, GameSystem::Standard);
+
+        let json = r#"{"game_system":"classicWorldOfDarkness"}"#;
+        let parsed: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.game_system, GameSystem::ClassicWorldOfDarkness);
+    }
+
+    #[test]
+    fn test_result_order_defaults_to_grouped() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.result_order, ResultOrder::GroupedByType);
+
+        let json = r#"{"result_order":"settleOrder"}"#;
+        let parsed: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.result_order, ResultOrder::SettleOrder);
+    }
+
+    #[test]
+    fn test_explosion_cap_defaults_and_clamps() {
+        let mut settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.explosion_cap(), DEFAULT_EXPLOSION_CAP);
+
+        settings.explosion_cap = 0;
+        assert_eq!(settings.explosion_cap(), AppSettings::MIN_EXPLOSION_CAP);
+        settings.explosion_cap = 1000;
+        assert_eq!(settings.explosion_cap(), AppSettings::MAX_EXPLOSION_CAP);
+    }
+
+    #[test]
+    fn test_sound_gain_defaults_clamps_and_mutes() {
+        let mut settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.sound_gain(), 0.8);
+
+        settings.sound_volume = 3.0;
+        assert_eq!(settings.sound_gain(), 1.0);
+        settings.sound_muted = true;
+        assert_eq!(settings.sound_gain(), 0.0);
+
+        // Music has its own volume and is off until a folder is picked
+        assert!(!settings.music_enabled);
+        assert_eq!(settings.music_volume, 0.5);
+        assert!(settings.music_shuffle);
+    }
+
+    #[test]
+    fn test_dice_skins_default_to_crystal_and_round_trip() {
+        let mut settings: AppSettings = serde_json::from_str("{}").unwrap();
+        let crystal = settings.dice_skin(DiceType::D8);
+        assert_eq!(crystal.name, "Crystal");
+        assert_eq!(
+            crystal.base_color,
+            ColorSetting::from_color(DiceType::D8.color())
+        );
+
+        let mut gold = DiceSkinPreset::Gold.skin(DiceType::D20);
+        gold.texture = Some("textures/gold.png".to_string());
+        set_dice_skin(&mut settings.dice_skins, DiceType::D20, gold.clone());
+        set_dice_skin(&mut settings.dice_skins, DiceType::D20, gold.clone());
+        assert_eq!(settings.dice_skins.len(), 1);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: AppSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.dice_skin(DiceType::D20), gold);
+        assert_eq!(loaded.dice_skin(DiceType::D4).name, "Crystal");
+        assert_eq!(
+            DiceSkinPreset::from_name("gold"),
+            Some(DiceSkinPreset::Gold)
+        );
+    }
+
+    #[test]
+    fn test_custom_quick_rolls() {
+        let roll = CustomQuickRoll::new(" Greatsword ", "2d6+3", "#FF8844", "swords").unwrap();
+        assert_eq!(roll.label, "Greatsword");
+        assert_eq!(roll.icon.as_deref(), Some("swords"));
+        let config = roll.dice_config().unwrap();
+        assert_eq!(config.dice_to_roll, vec![DiceType::D6, DiceType::D6]);
+        assert_eq!(config.modifier, 3);
+        assert_eq!(config.modifier_name, "Greatsword");
+
+        // The expression labels a button without one; adv rolls the d20 twice
+        let attack = CustomQuickRoll::new("", "1d20+5 adv", "", "").unwrap();
+        assert_eq!(attack.label, "1d20+5 adv");
+        assert_eq!(attack.color, None);
+        assert_eq!(attack.dice_config().unwrap().dice_to_roll.len(), 2);
+
+        assert!(CustomQuickRoll::new("Bad", "", "", "").is_err());
+        assert!(CustomQuickRoll::new("Bad", "2q6", "", "").is_err());
+        assert!(CustomQuickRoll::new("Bad", "1d6", "#nothex", "").is_err());
+
+        let mut settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert!(settings.custom_quick_rolls.is_empty());
+        settings.custom_quick_rolls = vec![roll.clone(), attack.clone()];
+        assert!(move_custom_quick_roll(
+            &mut settings.custom_quick_rolls,
+            1,
+            0
+        ));
+        assert!(!move_custom_quick_roll(
+            &mut settings.custom_quick_rolls,
+            0,
+            2
+        ));
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: AppSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.custom_quick_rolls, vec![attack, roll]);
+    }
+
+    #[test]
+    fn test_chaos_dice_palette() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.chaos_dice);
+        assert_eq!(settings.chaos_dice_palette, default_chaos_dice_palette());
+        assert_eq!(settings.chaos_dice_palette.len(), 6);
+
+        let palette = parse_color_palette("#FF8844, teal,, gold").unwrap();
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette[0], ColorSetting::parse("#FF8844").unwrap());
+        assert_eq!(
+            parse_color_palette(&format_color_palette(&palette)).unwrap(),
+            palette
+        );
+
+        assert!(parse_color_palette(" , ").is_err());
+        assert!(parse_color_palette("#FF8844, notacolor").is_err());
+    }
+
+    #[test]
+    fn test_fixed_timestep_physics_is_off_by_default() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.fixed_timestep_physics);
+        assert!(!SettingsState::default().fixed_timestep_physics_editing);
+        assert!(!settings.steered_dice);
+        assert!(!settings.gather_dice);
+        assert!(!settings.physics_debug_capture);
+        assert!(!settings.pause_when_unfocused);
+    }
+
+    #[test]
+    fn test_gamepad_rumble_is_on_by_default() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert!(settings.gamepad_rumble);
+        assert!(SettingsState::default().gamepad_rumble_editing);
+    }
+
+    #[test]
+    fn test_ui_scale_override() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.ui_scale(), 1.0);
+
+        settings.ui_scale_override = Some(1.25);
+        assert_eq!(settings.ui_scale(), 1.25);
+        settings.ui_scale_override = Some(8.0);
+        assert_eq!(settings.ui_scale(), AppSettings::MAX_UI_SCALE);
+    }
+
+    #[test]
+    fn test_settings_export_round_trip() {
+        let mut settings = AppSettings::default();
+        settings.theme_seed_hex = Some("#FF336699".to_string());
+        settings.results_panel_position = UiPositionSetting { x: 42.0, y: 7.0 };
+        settings.shake_config.duration_seconds = 2.5;
+
+        let json = settings.to_export_json().unwrap();
+        let imported = AppSettings::from_export_json(&json).unwrap();
+        assert_eq!(imported.theme_seed_hex.as_deref(), Some("#FF336699"));
+        assert_eq!(imported.results_panel_position.x, 42.0);
+        assert_eq!(imported.shake_config.duration_seconds, 2.5);
+    }
+
+    #[test]
+    fn test_settings_import_rejects_other_files() {
+        assert!(AppSettings::from_export_json("{}").is_err());
+        assert!(AppSettings::from_export_json(
+            r#"{"format":"something-else","version":1,"settings":{}}"#
+        )
+        .is_err());
+        assert!(AppSettings::from_export_json(
+            r#"{"format":"dndgamerolls-settings","version":99,"settings":{}}"#
+        )
+        .is_err());
+
+        let imported = AppSettings::from_export_json(
+            r#"{"format":"dndgamerolls-settings","version":1,"settings":{}}"#,
+        )
+        .unwrap();
+        assert_eq!(imported.explosion_cap(), DEFAULT_EXPLOSION_CAP);
+    }
 }
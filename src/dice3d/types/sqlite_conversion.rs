@@ -3,13 +3,85 @@
 //! This module contains the data-loading portion of the legacy character migration.
 //! The actual upsert into SurrealDB is performed via [`CharacterDatabase`].
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use base64::Engine;
+use serde::Deserialize;
 
+use crate::dice3d::types::character::{
+    AttributeModifiers, Attributes, CharacterInfo, CharacterSheet, Combat, Equipment, Feature,
+    SavingThrow, Skill, SpellCasting,
+};
 use crate::dice3d::types::database::CharacterDatabase;
 
+/// Character sheet layout as written by the legacy SQLite build.
+///
+/// bincode is not self-describing, so payloads must be decoded with the exact
+/// field list they were written with. Fields added to [`CharacterSheet`] since
+/// then are filled with their defaults by the `From` conversion below.
+#[derive(Debug, Deserialize)]
+pub struct LegacyCharacterSheet {
+    pub character: CharacterInfo,
+    pub attributes: Attributes,
+    pub modifiers: AttributeModifiers,
+    pub combat: Combat,
+    pub proficiency_bonus: i32,
+    pub saving_throws: HashMap<String, SavingThrow>,
+    pub skills: HashMap<String, Skill>,
+    pub equipment: Option<Equipment>,
+    pub features: Vec<Feature>,
+    pub spells: Option<LegacySpellCasting>,
+    pub custom_basic_info: HashMap<String, String>,
+    pub custom_attributes: HashMap<String, i32>,
+    pub custom_combat: HashMap<String, String>,
+}
+
+/// Spellcasting layout as written by the legacy SQLite build.
+#[derive(Debug, Deserialize)]
+pub struct LegacySpellCasting {
+    pub spellcasting_ability: Option<String>,
+    pub spell_save_dc: Option<i32>,
+    pub spell_attack_bonus: Option<i32>,
+    pub spell_slots: HashMap<String, i32>,
+    pub known_spells: Vec<String>,
+}
+
+impl From<LegacySpellCasting> for SpellCasting {
+    fn from(legacy: LegacySpellCasting) -> Self {
+        SpellCasting {
+            spellcasting_ability: legacy.spellcasting_ability,
+            spell_save_dc: legacy.spell_save_dc,
+            spell_attack_bonus: legacy.spell_attack_bonus,
+            spell_slots: legacy.spell_slots,
+            known_spells: legacy.known_spells,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<LegacyCharacterSheet> for CharacterSheet {
+    fn from(legacy: LegacyCharacterSheet) -> Self {
+        CharacterSheet {
+            character: legacy.character,
+            attributes: legacy.attributes,
+            modifiers: legacy.modifiers,
+            combat: legacy.combat,
+            proficiency_bonus: legacy.proficiency_bonus,
+            saving_throws: legacy.saving_throws,
+            skills: legacy.skills,
+            equipment: legacy.equipment,
+            features: legacy.features,
+            spells: legacy.spells.map(SpellCasting::from),
+            custom_basic_info: legacy.custom_basic_info,
+            custom_attributes: legacy.custom_attributes,
+            custom_combat: legacy.custom_combat,
+            ..Default::default()
+        }
+    }
+}
+
 fn decode_legacy_data_text(s: &str) -> Result<Vec<u8>, String> {
     let trimmed = s.trim();
     // Some encodings may prefix the payload.
@@ -39,6 +111,7 @@ pub fn legacy_sqlite_path() -> Option<PathBuf> {
 /// Load legacy character rows from the SQLite database.
 ///
 /// This reads the raw `data` blob for each row and does not deserialize it.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn load_legacy_character_rows(
     sqlite_path: &Path,
 ) -> Result<Vec<LegacySqliteCharacterRow>, String> {
@@ -118,6 +191,7 @@ pub struct LegacySqliteProbe {
 }
 
 /// Inspect table names and (if present) the columns of the `characters` table.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn probe_legacy_sqlite(sqlite_path: &Path) -> Result<LegacySqliteProbe, String> {
     let meta = std::fs::metadata(sqlite_path).ok();
     let file_size_bytes = meta.as_ref().map(|m| m.len());
@@ -184,6 +258,19 @@ pub fn probe_legacy_sqlite(sqlite_path: &Path) -> Result<LegacySqliteProbe, Stri
     })
 }
 
+/// The browser build has no SQLite (and no legacy database to find).
+#[cfg(target_arch = "wasm32")]
+pub fn load_legacy_character_rows(
+    _sqlite_path: &Path,
+) -> Result<Vec<LegacySqliteCharacterRow>, String> {
+    Err("Legacy SQLite import is not available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn probe_legacy_sqlite(_sqlite_path: &Path) -> Result<LegacySqliteProbe, String> {
+    Err("Legacy SQLite import is not available in the browser".to_string())
+}
+
 /// Rename the legacy SQLite database file so the app doesn't keep re-checking it.
 pub fn backup_legacy_sqlite(sqlite_path: &Path) -> Result<(), String> {
     let backup = sqlite_path.with_extension("db.bak");
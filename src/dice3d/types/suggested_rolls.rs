@@ -0,0 +1,208 @@
+//! Suggested rolls
+//!
+//! The quick roll panel shows a short "Suggested" row of the rolls likely to
+//! come next, learned from the saved roll log ([`RollRecord`]): the damage of
+//! the attack just rolled, the rolls that most often came right after the
+//! last one, and initiative when a session starts (the character's first
+//! roll, or the first after a long break) along with what past sessions
+//! opened with.
+
+use super::damage_types::{damage_roll_label, parse_damage_label};
+use super::roll_stats::RollRecord;
+
+/// Label of an initiative roll
+pub const INITIATIVE_LABEL: &str = "Initiative";
+
+/// Most suggestions shown at once
+pub const MAX_SUGGESTED_ROLLS: usize = 3;
+
+/// A roll made within this many seconds of another is taken to follow it
+pub const FOLLOW_UP_SECONDS: u64 = 5 * 60;
+
+/// A break between rolls this long starts a new session
+pub const SESSION_GAP_SECONDS: u64 = 3 * 60 * 60;
+
+/// A roll label without the notes added in brackets: "Stealth" for
+/// "Stealth [Disadvantage: Poisoned]".
+pub fn base_roll_label(label: &str) -> &str {
+    label.split(" [").next().unwrap_or(label).trim()
+}
+
+/// What an attack roll was made with: "Fire Bolt" for "Fire Bolt Spell
+/// Attack", "Longsword" for "Longsword attack"; `None` for other rolls.
+pub fn attack_source(label: &str) -> Option<&str> {
+    let label = base_roll_label(label);
+    let lower = label.to_ascii_lowercase();
+    [" spell attack", " attack"]
+        .iter()
+        .find(|suffix| lower.ends_with(*suffix))
+        .map(|suffix| label[..label.len() - suffix.len()].trim())
+        .filter(|source| !source.is_empty())
+}
+
+/// Labels of the rolls `character` is likely to make next, most likely
+/// first. Labels come without their bracketed notes and each is listed once.
+pub fn suggest_rolls(records: &[RollRecord], character: &str, now: u64) -> Vec<String> {
+    let history: Vec<&RollRecord> = records
+        .iter()
+        .filter(|record| record.character == character)
+        .filter(|record| !base_roll_label(&record.label).is_empty())
+        .collect();
+
+    let mut suggestions = Vec::new();
+    let last = history
+        .last()
+        .filter(|record| now.saturating_sub(record.rolled_at) < SESSION_GAP_SECONDS);
+    match last {
+        None => {
+            push_unique(&mut suggestions, INITIATIVE_LABEL);
+            let openers = history
+                .iter()
+                .enumerate()
+                .filter(|(i, record)| {
+                    *i == 0
+                        || record.rolled_at.saturating_sub(history[i - 1].rolled_at)
+                            >= SESSION_GAP_SECONDS
+                })
+                .map(|(_, record)| *record);
+            for label in most_frequent(openers) {
+                push_unique(&mut suggestions, label);
+            }
+        }
+        Some(last) => {
+            let last_label = base_roll_label(&last.label);
+            if let Some(source) = attack_source(last_label) {
+                // The damage as it was last rolled, which has its damage type
+                let damage = history
+                    .iter()
+                    .rev()
+                    .map(|record| base_roll_label(&record.label))
+                    .find(|label| {
+                        parse_damage_label(label)
+                            .is_some_and(|(damage_source, _)| damage_source == source)
+                    })
+                    .map(str::to_string)
+                    .unwrap_or_else(|| damage_roll_label(source, None));
+                push_unique(&mut suggestions, &damage);
+            }
+
+            let followers = history
+                .windows(2)
+                .filter(|pair| {
+                    base_roll_label(&pair[0].label).eq_ignore_ascii_case(last_label)
+                        && pair[1].rolled_at.saturating_sub(pair[0].rolled_at) <= FOLLOW_UP_SECONDS
+                })
+                .map(|pair| pair[1]);
+            for label in most_frequent(followers) {
+                push_unique(&mut suggestions, label);
+            }
+        }
+    }
+    suggestions
+}
+
+fn push_unique(suggestions: &mut Vec<String>, label: &str) {
+    if !suggestions.iter().any(|s| s.eq_ignore_ascii_case(label)) {
+        suggestions.push(label.to_string());
+    }
+}
+
+/// Labels of `records` (oldest first), the most rolled first and ties going
+/// to the most recent.
+fn most_frequent<'a>(records: impl Iterator<Item = &'a RollRecord>) -> Vec<&'a str> {
+    // (label, times rolled, last rolled at)
+    let mut counts: Vec<(&str, usize, u64)> = Vec::new();
+    for record in records {
+        let label = base_roll_label(&record.label);
+        match counts
+            .iter_mut()
+            .find(|(counted, _, _)| counted.eq_ignore_ascii_case(label))
+        {
+            Some(entry) => {
+                entry.0 = label;
+                entry.1 += 1;
+                entry.2 = record.rolled_at;
+            }
+            None => counts.push((label, 1, record.rolled_at)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+    counts.into_iter().map(|(label, _, _)| label).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(character: &str, label: &str, rolled_at: u64) -> RollRecord {
+        RollRecord {
+            character: character.to_string(),
+            label: label.to_string(),
+            dice: Vec::new(),
+            total: 0,
+            rolled_at,
+        }
+    }
+
+    #[test]
+    fn test_session_start_suggests_initiative() {
+        let day = 86_400;
+        let records = vec![
+            roll("Thorin", "Perception", 1_000),
+            roll("Thorin", "Athletics", 1_060),
+            roll("Thorin", "Perception", day),
+            roll("Mira", "Insight", 2 * day),
+        ];
+
+        assert_eq!(
+            suggest_rolls(&records, "Thorin", 3 * day),
+            vec!["Initiative", "Perception"]
+        );
+        assert_eq!(suggest_rolls(&[], "Thorin", 3 * day), vec!["Initiative"]);
+    }
+
+    #[test]
+    fn test_learns_what_follows_a_roll() {
+        let records = vec![
+            roll("Thorin", "Stealth", 100),
+            roll("Thorin", "Perception", 130),
+            roll("Thorin", "Stealth [Disadvantage: Poisoned]", 200),
+            roll("Thorin", "Perception", 220),
+            roll("Thorin", "Stealth", 300),
+            roll("Thorin", "Dexterity save", 320),
+            // Too long after to count as following
+            roll("Thorin", "Stealth", 1_000),
+            roll("Thorin", "Insight", 5_000),
+            roll("Thorin", "Stealth", 6_000),
+        ];
+
+        assert_eq!(
+            suggest_rolls(&records, "Thorin", 6_010),
+            vec!["Perception", "Dexterity save"]
+        );
+    }
+
+    #[test]
+    fn test_attack_suggests_its_damage() {
+        let records = vec![
+            roll("Mira", "Fire Bolt Spell Attack", 100),
+            roll("Mira", "Fire Bolt Damage (fire)", 110),
+            roll(
+                "Mira",
+                "Fire Bolt Spell Attack [Advantage: Inspiration]",
+                500,
+            ),
+        ];
+        assert_eq!(
+            suggest_rolls(&records, "Mira", 510),
+            vec!["Fire Bolt Damage (fire)"]
+        );
+
+        let records = vec![roll("Mira", "Longsword attack", 100)];
+        assert_eq!(
+            suggest_rolls(&records, "Mira", 110),
+            vec!["Longsword Damage"]
+        );
+        assert_eq!(attack_source("Attack"), None);
+    }
+}
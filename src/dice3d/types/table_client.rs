@@ -0,0 +1,494 @@
+//! Table client
+//!
+//! `--join` connects the app to another player's `--serve` table server and
+//! receives the rolls it broadcasts:
+//!
+//! ```text
+//! dndgamerolls --join ws://192.168.1.20:7878/?token=goblins
+//! ```
+//!
+//! The connection is read on its own thread; the app collects the rolls and
+//! inspiration grants that arrived since the last frame with
+//! `TableClient::receive`. After the handshake the only thing sent to the
+//! server is an anonymous safety signal (`TableClient::send_safety_signal`).
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use rand::Rng;
+
+use super::{websocket_accept_key, SafetySignal, SharedRoll, TableMessage};
+
+/// Port `--serve` listens on when a `--join` URL doesn't give one
+pub const DEFAULT_TABLE_PORT: u16 = 7878;
+
+/// Time the server has to accept the connection and answer the handshake
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest message accepted from the server
+const MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+
+/// A connection to a table server; messages it broadcasts pile up until
+/// `receive` takes them.
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
+pub struct TableClient {
+    url: String,
+    stream: Mutex<TcpStream>,
+    messages: Mutex<Receiver<TableMessage>>,
+    connected: Arc<AtomicBool>,
+}
+
+impl TableClient {
+    /// Connect to the table server at `url` ("ws://192.168.1.20:7878/?token=goblins").
+    pub fn connect(url: &str) -> Result<Self, String> {
+        let (host, target) = parse_table_url(url)?;
+        let addr = host
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to look up {}: {}", host, e))?
+            .next()
+            .ok_or_else(|| format!("Failed to look up {}", host))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+            .map_err(|e| format!("Failed to connect to {}: {}", host, e))?;
+        stream
+            .set_read_timeout(Some(CONNECT_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        handshake(&stream, &mut reader, &host, &target)?;
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+
+        let (sender, messages) = mpsc::channel();
+        let connected = Arc::new(AtomicBool::new(true));
+        let reading = Arc::clone(&connected);
+        let server = host.clone();
+        thread::Builder::new()
+            .name("table-client".to_string())
+            .spawn(move || {
+                loop {
+                    match read_message(&mut reader) {
+                        Ok(Some(text)) => match serde_json::from_str::<TableMessage>(&text) {
+                            Ok(message) => {
+                                if sender.send(message).is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) => tracing::warn!("Table client: unreadable message: {}", e),
+                        },
+                        Ok(None) => {
+                            tracing::info!("Table client: {} closed the connection", server);
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Table client: {}: {}", server, e);
+                            break;
+                        }
+                    }
+                }
+                reading.store(false, Ordering::Relaxed);
+            })
+            .map_err(|e| format!("Failed to start the table client: {}", e))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            stream: Mutex::new(stream),
+            messages: Mutex::new(messages),
+            connected,
+        })
+    }
+
+    /// The URL given to `connect`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Whether the server is still sending
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Messages received since the last call, oldest first.
+    pub fn receive(&self) -> Vec<TableMessage> {
+        self.messages
+            .lock()
+            .map(|messages| messages.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Ask the server to pass a safety signal on to the whole table.
+    pub fn send_safety_signal(&self, signal: SafetySignal) -> Result<(), String> {
+        let json = serde_json::to_string(&TableMessage::Safety { safety: signal })
+            .map_err(|e| format!("Failed to send the safety signal: {}", e))?;
+        let frame = masked_text_frame(&json, rand::rng().random());
+        self.stream
+            .lock()
+            .map_err(|_| "The table connection closed".to_string())?
+            .write_all(&frame)
+            .map_err(|e| format!("Failed to send the safety signal: {}", e))
+    }
+}
+
+/// A masked text frame, as a client must send them (RFC 6455 5.3).
+pub fn masked_text_frame(payload: &str, mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = super::text_frame(payload);
+    let header = match frame[1] {
+        126 => 4,
+        127 => 10,
+        _ => 2,
+    };
+    frame[1] |= 0x80;
+    let payload: Vec<u8> = frame
+        .split_off(header)
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ mask[i % 4])
+        .collect();
+    frame.extend_from_slice(&mask);
+    frame.extend(payload);
+    frame
+}
+
+impl SharedRoll {
+    /// One line of the shared roll feed: "Thorin: Stealth 17 (D20 14, +3)".
+    /// Summarized rolls show only their total.
+    pub fn feed_line(&self) -> String {
+        let mut line = match &self.character {
+            Some(character) => format!("{}: {}", character, self.label),
+            None => self.label.clone(),
+        };
+        if let Some(total) = self.total {
+            line.push_str(&format!(" {}", total));
+        }
+        let mut parts: Vec<String> = self
+            .dice
+            .iter()
+            .map(|(die, value)| format!("{} {}", die.name(), value))
+            .collect();
+        if self.modifier != 0 {
+            parts.push(format!("{:+}", self.modifier));
+        }
+        if !parts.is_empty() {
+            line.push_str(&format!(" ({})", parts.join(", ")));
+        }
+        line
+    }
+}
+
+/// Split a `ws://` URL into the `host:port` to connect to and the request
+/// target ("/?token=goblins").
+pub fn parse_table_url(url: &str) -> Result<(String, String), String> {
+    let url = url.trim();
+    let rest = if let Some(rest) = url.strip_prefix("ws://") {
+        rest
+    } else if url.starts_with("wss://") {
+        return Err("wss:// is not supported; use the ws:// address the host printed".to_string());
+    } else if url.contains("://") {
+        return Err(format!("Not a ws:// address: {}", url));
+    } else {
+        url
+    };
+
+    let split = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (host, target) = rest.split_at(split);
+    if host.is_empty() {
+        return Err(format!("No host in {}", url));
+    }
+    let host = if host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_TABLE_PORT)
+    };
+    let target = match target {
+        "" => "/".to_string(),
+        query if query.starts_with('?') => format!("/{}", query),
+        path => path.to_string(),
+    };
+    Ok((host, target))
+}
+
+/// Send the upgrade request and check the server's answer.
+fn handshake<R: BufRead>(
+    mut stream: &TcpStream,
+    reader: &mut R,
+    host: &str,
+    target: &str,
+) -> Result<(), String> {
+    let nonce: [u8; 16] = rand::rng().random();
+    let key = base64::engine::general_purpose::STANDARD.encode(nonce);
+    let request = format!(
+        "GET {} HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\n\
+         Sec-WebSocket-Version: 13\r\n\r\n",
+        target, host, key
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("{}: {}", host, e))?;
+
+    let mut status = String::new();
+    reader
+        .read_line(&mut status)
+        .map_err(|e| format!("{}: {}", host, e))?;
+    let mut accept = None;
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("{}: {}", host, e))?;
+        if read == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                accept = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    match status.split_whitespace().nth(1) {
+        Some("101") => {}
+        Some("403") => {
+            return Err(format!(
+                "{} refused the connection (wrong room token?)",
+                host
+            ))
+        }
+        _ => return Err(format!("{} is not a table server", host)),
+    }
+    if accept.as_deref() != Some(websocket_accept_key(&key).as_str()) {
+        return Err(format!("{} answered the handshake wrongly", host));
+    }
+    Ok(())
+}
+
+/// Read the next text message, joining fragments. `None` once the server
+/// closes the connection.
+pub fn read_message<R: Read>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut message = Vec::new();
+    loop {
+        let mut header = [0u8; 2];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let masked = header[1] & 0x80 != 0;
+        let len = match header[1] & 0x7F {
+            126 => {
+                let mut len = [0u8; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as usize
+            }
+            127 => {
+                let mut len = [0u8; 8];
+                reader.read_exact(&mut len)?;
+                u64::from_be_bytes(len) as usize
+            }
+            len => len as usize,
+        };
+        if message.len() + len > MAX_MESSAGE_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "message too long",
+            ));
+        }
+        let mut mask = [0u8; 4];
+        if masked {
+            reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+        if masked {
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+        }
+
+        match opcode {
+            // Close
+            0x8 => return Ok(None),
+            // Text, continuation
+            0x1 | 0x0 => message.extend_from_slice(&payload),
+            // Ping, pong and binary frames carry nothing for us
+            _ => continue,
+        }
+        if fin {
+            return String::from_utf8(message)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::{text_frame, DiceType, InspirationGrant, TableServer};
+
+    #[test]
+    fn test_parse_table_url() {
+        assert_eq!(
+            parse_table_url("ws://192.168.1.20:7878/?token=goblins").unwrap(),
+            (
+                "192.168.1.20:7878".to_string(),
+                "/?token=goblins".to_string()
+            )
+        );
+        assert_eq!(
+            parse_table_url("ws://table.local?token=goblins").unwrap(),
+            (
+                "table.local:7878".to_string(),
+                "/?token=goblins".to_string()
+            )
+        );
+        assert_eq!(
+            parse_table_url("localhost:9000").unwrap(),
+            ("localhost:9000".to_string(), "/".to_string())
+        );
+        assert!(parse_table_url("wss://table.local").is_err());
+        assert!(parse_table_url("http://table.local").is_err());
+        assert!(parse_table_url("ws:///?token=goblins").is_err());
+    }
+
+    #[test]
+    fn test_feed_line() {
+        let mut roll = SharedRoll {
+            character: Some("Thorin".to_string()),
+            label: "Stealth".to_string(),
+            dice: vec![(DiceType::D20, 14)],
+            modifier: 3,
+            total: Some(17),
+            color: None,
+        };
+        assert_eq!(roll.feed_line(), "Thorin: Stealth 17 (D20 14, +3)");
+
+        roll.dice.clear();
+        roll.modifier = 0;
+        assert_eq!(roll.feed_line(), "Thorin: Stealth 17");
+
+        roll.character = None;
+        roll.label = "2D6".to_string();
+        roll.dice = vec![(DiceType::D6, 2), (DiceType::D6, 5)];
+        roll.total = Some(7);
+        assert_eq!(roll.feed_line(), "2D6 7 (D6 2, D6 5)");
+    }
+
+    #[test]
+    fn test_read_message() {
+        let mut frames = text_frame("first");
+        // "second" in two fragments, the first masked
+        frames.extend_from_slice(&[0x01, 0x83, 1, 2, 3, 4]);
+        frames.extend(b"sec".iter().zip([1u8, 2, 3]).map(|(b, m)| b ^ m));
+        frames.extend_from_slice(&[0x80, 3, b'o', b'n', b'd']);
+        frames.extend_from_slice(&[0x88, 0]);
+        let mut reader = frames.as_slice();
+
+        assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("first"));
+        assert_eq!(
+            read_message(&mut reader).unwrap().as_deref(),
+            Some("second")
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+
+        let masked = masked_text_frame("x-card", [9, 8, 7, 6]);
+        assert_eq!(masked[1], 0x80 | 6);
+        assert_eq!(
+            read_message(&mut masked.as_slice()).unwrap().as_deref(),
+            Some("x-card")
+        );
+    }
+
+    #[test]
+    fn test_safety_signal_reaches_the_table() {
+        let server = TableServer::start("127.0.0.1:0", None).unwrap();
+        let url = format!("ws://{}/", server.local_addr());
+        let sender = TableClient::connect(&url).unwrap();
+        let other = TableClient::connect(&url).unwrap();
+        for _ in 0..100 {
+            if server.subscribers() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        sender.send_safety_signal(SafetySignal::XCard).unwrap();
+        let mut received = Vec::new();
+        let mut at_server = Vec::new();
+        for _ in 0..100 {
+            received.extend(other.receive());
+            at_server.extend(server.receive_safety_signals());
+            if !received.is_empty() && !at_server.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            received,
+            vec![TableMessage::Safety {
+                safety: SafetySignal::XCard
+            }]
+        );
+        assert_eq!(at_server, vec![SafetySignal::XCard]);
+    }
+
+    #[test]
+    fn test_client_receives_server_rolls() {
+        let server = TableServer::start("127.0.0.1:0", Some("goblins".to_string())).unwrap();
+        let url = format!("ws://{}/?token=goblins", server.local_addr());
+        assert!(TableClient::connect(&format!("ws://{}/", server.local_addr())).is_err());
+
+        let client = TableClient::connect(&url).unwrap();
+        for _ in 0..100 {
+            if server.subscribers() == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(client.is_connected());
+
+        let roll = SharedRoll {
+            character: Some("Thorin".to_string()),
+            label: "Stealth".to_string(),
+            dice: vec![(DiceType::D20, 14)],
+            modifier: 3,
+            total: Some(17),
+            color: Some("#C81E1E".to_string()),
+        };
+        server.broadcast(&roll).unwrap();
+        let grant = InspirationGrant {
+            character: "Thorin".to_string(),
+            remaining: 2,
+        };
+        server.grant_inspiration(&grant).unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            received.extend(client.receive());
+            if received.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            received,
+            vec![
+                TableMessage::Roll(roll),
+                TableMessage::Inspiration { inspiration: grant }
+            ]
+        );
+    }
+}
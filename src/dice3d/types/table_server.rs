@@ -0,0 +1,493 @@
+//! Table server
+//!
+//! `--serve` starts a small WebSocket server that sends every roll to
+//! whoever is listening (the other players at the table, or a stream
+//! overlay in OBS) as one JSON message per roll:
+//!
+//! ```text
+//! dndgamerolls --serve 0.0.0.0:7878 --room-token goblins
+//! ws://192.168.1.20:7878/?token=goblins
+//! {"character":"Thorin","label":"Stealth","dice":[["D20",14]],"modifier":3,"total":17,"color":"#C81E1E"}
+//! ```
+//!
+//! Rolls go out as the players see them (`SharedRoll::for_viewer`), so a
+//! sheet that keeps its rolls to the DM keeps them off the stream too.
+//! Inspiration the DM hands out goes to the table the same way:
+//!
+//! ```text
+//! {"inspiration":{"character":"Thorin","remaining":2}}
+//! ```
+//!
+//! With
+//! a room token, connections that don't give it in the `token` query
+//! parameter are refused. The only thing subscribers can send back is a
+//! safety signal (the X-card), which the server passes on to everyone,
+//! itself included, without saying who sent it:
+//!
+//! ```text
+//! {"safety":"x-card"}
+//! ```
+//!
+//! Anything else they send is ignored.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::{read_message, InspirationGrant, SafetySignal, SharedRoll};
+
+/// Appended to the client's key to form the handshake answer (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Time a connection has to send its upgrade request
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time a subscriber has to take a message before it is dropped, so one
+/// stalled client can't hold up the others
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Longest upgrade request accepted
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// A message sent to the table: a roll, inspiration the DM handed out, or
+/// an anonymous safety signal. Rolls are sent as a bare [`SharedRoll`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TableMessage {
+    Inspiration { inspiration: InspirationGrant },
+    Safety { safety: SafetySignal },
+    Roll(SharedRoll),
+}
+
+/// A running table server; rolls given to `broadcast` go to every
+/// subscriber connected at the time.
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
+pub struct TableServer {
+    addr: SocketAddr,
+    subscribers: Arc<Mutex<Vec<TcpStream>>>,
+    outgoing: Mutex<Sender<Vec<u8>>>,
+    signals: Mutex<Receiver<SafetySignal>>,
+}
+
+impl TableServer {
+    /// Listen on `addr` ("0.0.0.0:7878"), taking only connections that give
+    /// `room_token` when there is one.
+    pub fn start(addr: &str, room_token: Option<String>) -> Result<Self, String> {
+        let listener =
+            TcpListener::bind(addr).map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read the server address: {}", e))?;
+        let subscribers = Arc::new(Mutex::new(Vec::new()));
+
+        // Writes happen off the caller's thread so a slow subscriber never
+        // holds up a frame of the app
+        let (outgoing, frames) = mpsc::channel::<Vec<u8>>();
+        let (signal_sender, signals) = mpsc::channel::<SafetySignal>();
+
+        let accepted = Arc::clone(&subscribers);
+        let relay = outgoing.clone();
+        thread::Builder::new()
+            .name("table-server".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let accepted = Arc::clone(&accepted);
+                    let room_token = room_token.clone();
+                    let relay = relay.clone();
+                    let signal_sender = signal_sender.clone();
+                    thread::spawn(
+                        move || match accept_subscriber(stream, room_token.as_deref()) {
+                            Ok(stream) => {
+                                let reader = stream.try_clone();
+                                if let Ok(mut subscribers) = accepted.lock() {
+                                    subscribers.push(stream);
+                                }
+                                if let Ok(mut reader) = reader {
+                                    relay_safety_signals(&mut reader, &relay, &signal_sender);
+                                }
+                            }
+                            Err(e) => tracing::warn!("Table server: {}", e),
+                        },
+                    );
+                }
+            })
+            .map_err(|e| format!("Failed to start the table server: {}", e))?;
+
+        let writers = Arc::clone(&subscribers);
+        thread::Builder::new()
+            .name("table-server-send".to_string())
+            .spawn(move || {
+                for frame in frames {
+                    if let Ok(mut subscribers) = writers.lock() {
+                        subscribers.retain_mut(|stream| stream.write_all(&frame).is_ok());
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start the table server: {}", e))?;
+
+        Ok(Self {
+            addr,
+            subscribers,
+            outgoing: Mutex::new(outgoing),
+            signals: Mutex::new(signals),
+        })
+    }
+
+    /// Address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Subscribers connected now
+    pub fn subscribers(&self) -> usize {
+        self.subscribers
+            .lock()
+            .map(|subscribers| subscribers.len())
+            .unwrap_or(0)
+    }
+
+    /// Send a roll to every subscriber.
+    pub fn broadcast(&self, roll: &SharedRoll) -> Result<(), String> {
+        let json =
+            serde_json::to_string(roll).map_err(|e| format!("Failed to send roll: {}", e))?;
+        self.send(&json)
+    }
+
+    /// Tell every subscriber about inspiration the DM gave.
+    pub fn grant_inspiration(&self, grant: &InspirationGrant) -> Result<(), String> {
+        let message = TableMessage::Inspiration {
+            inspiration: grant.clone(),
+        };
+        let json = serde_json::to_string(&message)
+            .map_err(|e| format!("Failed to send inspiration: {}", e))?;
+        self.send(&json)
+    }
+
+    /// Send an anonymous safety signal to every subscriber.
+    pub fn send_safety_signal(&self, signal: SafetySignal) -> Result<(), String> {
+        let json = serde_json::to_string(&TableMessage::Safety { safety: signal })
+            .map_err(|e| format!("Failed to send the safety signal: {}", e))?;
+        self.send(&json)
+    }
+
+    /// Safety signals subscribers sent since the last call, oldest first.
+    pub fn receive_safety_signals(&self) -> Vec<SafetySignal> {
+        self.signals
+            .lock()
+            .map(|signals| signals.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn send(&self, json: &str) -> Result<(), String> {
+        self.outgoing
+            .lock()
+            .map_err(|_| "The table server stopped".to_string())?
+            .send(text_frame(json))
+            .map_err(|_| "The table server stopped".to_string())
+    }
+}
+
+/// Read a connection's upgrade request and answer it, returning the stream
+/// ready for messages.
+fn accept_subscriber(mut stream: TcpStream, room_token: Option<&str>) -> Result<TcpStream, String> {
+    let peer = stream
+        .peer_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|_| "unknown".to_string());
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut request = String::new();
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("{}: {}", peer, e))?;
+        if read == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        request.push_str(&line);
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(format!("{}: request too long", peer));
+        }
+    }
+
+    match check_handshake(&request, room_token) {
+        Ok(accept) => {
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\n\
+                 Upgrade: websocket\r\n\
+                 Connection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                accept
+            );
+            stream
+                .write_all(response.as_bytes())
+                .map_err(|e| format!("{}: {}", peer, e))?;
+            let _ = stream.set_nodelay(true);
+            stream
+                .set_write_timeout(Some(WRITE_TIMEOUT))
+                .map_err(|e| e.to_string())?;
+            tracing::info!("Table server: {} subscribed", peer);
+            Ok(stream)
+        }
+        Err(e) => {
+            let _ = stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n");
+            Err(format!("{}: {}", peer, e))
+        }
+    }
+}
+
+/// Read a subscriber's messages until it leaves, passing every safety
+/// signal on to the whole table and to the app. Who sent it isn't kept.
+fn relay_safety_signals(
+    reader: &mut TcpStream,
+    relay: &Sender<Vec<u8>>,
+    signals: &Sender<SafetySignal>,
+) {
+    if reader.set_read_timeout(None).is_err() {
+        return;
+    }
+    while let Ok(Some(text)) = read_message(reader) {
+        let Ok(TableMessage::Safety { safety }) = serde_json::from_str(&text) else {
+            continue;
+        };
+        if let Ok(json) = serde_json::to_string(&TableMessage::Safety { safety }) {
+            let _ = relay.send(text_frame(&json));
+        }
+        let _ = signals.send(safety);
+    }
+}
+
+/// Check a WebSocket upgrade request (and its room token) and return the
+/// `Sec-WebSocket-Accept` answer for it.
+pub fn check_handshake(request: &str, room_token: Option<&str>) -> Result<String, String> {
+    let mut lines = request.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    if request_line.next() != Some("GET") {
+        return Err("not a WebSocket request".to_string());
+    }
+    let target = request_line.next().unwrap_or("/");
+
+    let mut key = None;
+    let mut upgrade = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("sec-websocket-key") {
+            key = Some(value);
+        } else if name.eq_ignore_ascii_case("upgrade") {
+            upgrade = value.eq_ignore_ascii_case("websocket");
+        }
+    }
+    let key = key
+        .filter(|_| upgrade)
+        .ok_or_else(|| "not a WebSocket request".to_string())?;
+
+    if let Some(room_token) = room_token {
+        // Constant time, so the token can't be guessed one byte at a time
+        let given = query_param(target, "token").unwrap_or_default();
+        if ring::constant_time::verify_slices_are_equal(given.as_bytes(), room_token.as_bytes())
+            .is_err()
+        {
+            return Err("wrong room token".to_string());
+        }
+    }
+    Ok(websocket_accept_key(key))
+}
+
+/// The `Sec-WebSocket-Accept` answer to a client's `Sec-WebSocket-Key`.
+pub fn websocket_accept_key(client_key: &str) -> String {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{}{}", client_key.trim(), WEBSOCKET_GUID).as_bytes(),
+    );
+    base64::engine::general_purpose::STANDARD.encode(digest.as_ref())
+}
+
+/// An unmasked, unfragmented WebSocket text frame.
+pub fn text_frame(payload: &str) -> Vec<u8> {
+    let bytes = payload.as_bytes();
+    let mut frame = Vec::with_capacity(bytes.len() + 10);
+    // FIN + text opcode
+    frame.push(0x81);
+    match bytes.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(bytes);
+    frame
+}
+
+/// The percent-decoded value of a query parameter.
+fn query_param(target: &str, name: &str) -> Option<String> {
+    let (_, query) = target.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| percent_decode(value))
+}
+
+/// Decode `%XX` escapes and `+` (a space in query strings). `None` when an
+/// escape is malformed or the result isn't UTF-8.
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice3d::types::DiceType;
+    use std::io::Read;
+
+    fn upgrade_request(target: &str) -> String {
+        format!(
+            "GET {} HTTP/1.1\r\n\
+             Host: localhost\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+             Sec-WebSocket-Version: 13\r\n\r\n",
+            target
+        )
+    }
+
+    #[test]
+    fn test_accept_key_matches_rfc_example() {
+        assert_eq!(
+            websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kXGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_handshake_checks_room_token() {
+        assert!(check_handshake(&upgrade_request("/"), None).is_ok());
+        assert!(check_handshake(&upgrade_request("/?token=goblins"), Some("goblins")).is_ok());
+        assert!(check_handshake(&upgrade_request("/"), Some("goblins")).is_err());
+        assert!(check_handshake(&upgrade_request("/?token=orcs"), Some("goblins")).is_err());
+        assert!(check_handshake("GET / HTTP/1.1\r\nHost: localhost\r\n\r\n", None).is_err());
+    }
+
+    #[test]
+    fn test_handshake_decodes_room_token() {
+        let token = Some("goblins & orcs");
+        let request = upgrade_request("/?token=goblins%20%26%20orcs");
+        assert!(check_handshake(&request, token).is_ok());
+        assert!(check_handshake(&upgrade_request("/?token=goblins+%26+orcs"), token).is_ok());
+        assert!(check_handshake(&upgrade_request("/?token=goblins%20&%20orcs"), token).is_err());
+        assert!(check_handshake(&upgrade_request("/?token=goblins%2"), Some("goblins")).is_err());
+    }
+
+    #[test]
+    fn test_text_frame_lengths() {
+        assert_eq!(text_frame("hi"), vec![0x81, 2, b'h', b'i']);
+        let medium = text_frame(&"x".repeat(300));
+        assert_eq!(&medium[..4], &[0x81, 126, 1, 44]);
+        assert_eq!(medium.len(), 304);
+        let long = text_frame(&"x".repeat(70_000));
+        assert_eq!(long[1], 127);
+        assert_eq!(long.len(), 70_010);
+    }
+
+    #[test]
+    fn test_table_messages() {
+        let grant: TableMessage =
+            serde_json::from_str(r#"{"inspiration":{"character":"Thorin","remaining":2}}"#)
+                .unwrap();
+        assert_eq!(
+            grant,
+            TableMessage::Inspiration {
+                inspiration: InspirationGrant {
+                    character: "Thorin".to_string(),
+                    remaining: 2,
+                }
+            }
+        );
+        let roll: TableMessage = serde_json::from_str(r#"{"label":"Stealth","total":17}"#).unwrap();
+        assert!(matches!(roll, TableMessage::Roll(roll) if roll.total == Some(17)));
+    }
+
+    #[test]
+    fn test_subscriber_receives_rolls() {
+        let server = TableServer::start("127.0.0.1:0", Some("goblins".to_string())).unwrap();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        client
+            .write_all(upgrade_request("/?token=goblins").as_bytes())
+            .unwrap();
+
+        let mut response = Vec::new();
+        let mut byte = [0u8];
+        while !response.ends_with(b"\r\n\r\n") {
+            client.read_exact(&mut byte).unwrap();
+            response.push(byte[0]);
+        }
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+        for _ in 0..100 {
+            if server.subscribers() == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(server.subscribers(), 1);
+
+        let roll = SharedRoll {
+            character: Some("Thorin".to_string()),
+            label: "Stealth".to_string(),
+            dice: vec![(DiceType::D20, 14)],
+            modifier: 3,
+            total: Some(17),
+            color: None,
+        };
+        server.broadcast(&roll).unwrap();
+
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).unwrap();
+        assert_eq!(header[0], 0x81);
+        let mut payload = vec![0u8; header[1] as usize];
+        client.read_exact(&mut payload).unwrap();
+        let received: SharedRoll = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(received, roll);
+    }
+}
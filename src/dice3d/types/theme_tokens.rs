@@ -0,0 +1,336 @@
+//! Theme tokens
+//!
+//! The colors of the app's own widgets - the character sheet, its tabs and
+//! fields - are named tokens kept in the [`ThemeTokens`] resource instead of
+//! constants. Widgets spawned with [`ThemeToken::text`],
+//! [`ThemeToken::background`] or [`ThemeToken::border`] are recolored
+//! whenever the tokens change, so edits in the Theme settings tab preview
+//! live. Material widgets keep following the theme seed.
+//!
+//! The tokens are saved with the settings; named custom themes are kept
+//! alongside them and can be loaded back into the editor.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::ColorSetting;
+
+/// A named widget color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeToken {
+    /// Accents and active states
+    Primary,
+    /// Text and icons on primary backgrounds
+    OnPrimary,
+    /// Cards and panels
+    Surface,
+    /// Nested surfaces
+    SurfaceContainer,
+    /// Elevated surfaces
+    SurfaceContainerHigh,
+    /// Primary text
+    OnSurface,
+    /// Secondary text
+    OnSurfaceVariant,
+    /// Borders
+    Outline,
+    /// Subtle borders
+    OutlineVariant,
+    Secondary,
+    /// Accents
+    Tertiary,
+    Error,
+    /// Proficiency and success
+    Success,
+    /// Modified values and warnings
+    Warning,
+}
+
+impl ThemeToken {
+    pub const ALL: [ThemeToken; 14] = [
+        ThemeToken::Primary,
+        ThemeToken::OnPrimary,
+        ThemeToken::Surface,
+        ThemeToken::SurfaceContainer,
+        ThemeToken::SurfaceContainerHigh,
+        ThemeToken::OnSurface,
+        ThemeToken::OnSurfaceVariant,
+        ThemeToken::Outline,
+        ThemeToken::OutlineVariant,
+        ThemeToken::Secondary,
+        ThemeToken::Tertiary,
+        ThemeToken::Error,
+        ThemeToken::Success,
+        ThemeToken::Warning,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemeToken::Primary => "Primary",
+            ThemeToken::OnPrimary => "On primary",
+            ThemeToken::Surface => "Surface",
+            ThemeToken::SurfaceContainer => "Surface container",
+            ThemeToken::SurfaceContainerHigh => "Surface container high",
+            ThemeToken::OnSurface => "On surface",
+            ThemeToken::OnSurfaceVariant => "On surface variant",
+            ThemeToken::Outline => "Outline",
+            ThemeToken::OutlineVariant => "Outline variant",
+            ThemeToken::Secondary => "Secondary",
+            ThemeToken::Tertiary => "Tertiary",
+            ThemeToken::Error => "Error",
+            ThemeToken::Success => "Success",
+            ThemeToken::Warning => "Warning",
+        }
+    }
+
+    /// Built-in color of the token.
+    pub fn default_color(&self) -> Color {
+        match self {
+            ThemeToken::Primary => Color::srgb(0.4, 0.6, 0.9),
+            ThemeToken::OnPrimary => Color::WHITE,
+            ThemeToken::Surface => Color::srgb(0.11, 0.11, 0.14),
+            ThemeToken::SurfaceContainer => Color::srgb(0.14, 0.14, 0.18),
+            ThemeToken::SurfaceContainerHigh => Color::srgb(0.17, 0.17, 0.22),
+            ThemeToken::OnSurface => Color::WHITE,
+            ThemeToken::OnSurfaceVariant => Color::srgb(0.7, 0.7, 0.75),
+            ThemeToken::Outline => Color::srgb(0.3, 0.3, 0.35),
+            ThemeToken::OutlineVariant => Color::srgb(0.2, 0.2, 0.25),
+            ThemeToken::Secondary => Color::srgb(0.6, 0.7, 0.85),
+            ThemeToken::Tertiary => Color::srgb(0.5, 0.8, 0.7),
+            ThemeToken::Error => Color::srgb(0.9, 0.3, 0.3),
+            ThemeToken::Success => Color::srgb(0.3, 0.8, 0.4),
+            ThemeToken::Warning => Color::srgb(0.9, 0.7, 0.3),
+        }
+    }
+
+    /// Text colored with the token.
+    pub fn text(self) -> (TextColor, ThemedText) {
+        (TextColor(self.default_color()), ThemedText(self))
+    }
+
+    /// A background colored with the token.
+    pub fn background(self) -> (BackgroundColor, ThemedBackground) {
+        self.background_alpha(1.0)
+    }
+
+    /// A translucent background colored with the token.
+    pub fn background_alpha(self, alpha: f32) -> (BackgroundColor, ThemedBackground) {
+        (
+            BackgroundColor(self.default_color().with_alpha(alpha)),
+            ThemedBackground { token: self, alpha },
+        )
+    }
+
+    /// A border colored with the token.
+    pub fn border(self) -> (BorderColor, ThemedBorder) {
+        (BorderColor::from(self.default_color()), ThemedBorder(self))
+    }
+}
+
+/// Current colors of the widget tokens
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ThemeTokens {
+    pub primary: ColorSetting,
+    pub on_primary: ColorSetting,
+    pub surface: ColorSetting,
+    pub surface_container: ColorSetting,
+    pub surface_container_high: ColorSetting,
+    pub on_surface: ColorSetting,
+    pub on_surface_variant: ColorSetting,
+    pub outline: ColorSetting,
+    pub outline_variant: ColorSetting,
+    pub secondary: ColorSetting,
+    pub tertiary: ColorSetting,
+    pub error: ColorSetting,
+    pub success: ColorSetting,
+    pub warning: ColorSetting,
+}
+
+impl Default for ThemeTokens {
+    fn default() -> Self {
+        let color = |token: ThemeToken| ColorSetting::from_color(token.default_color());
+        Self {
+            primary: color(ThemeToken::Primary),
+            on_primary: color(ThemeToken::OnPrimary),
+            surface: color(ThemeToken::Surface),
+            surface_container: color(ThemeToken::SurfaceContainer),
+            surface_container_high: color(ThemeToken::SurfaceContainerHigh),
+            on_surface: color(ThemeToken::OnSurface),
+            on_surface_variant: color(ThemeToken::OnSurfaceVariant),
+            outline: color(ThemeToken::Outline),
+            outline_variant: color(ThemeToken::OutlineVariant),
+            secondary: color(ThemeToken::Secondary),
+            tertiary: color(ThemeToken::Tertiary),
+            error: color(ThemeToken::Error),
+            success: color(ThemeToken::Success),
+            warning: color(ThemeToken::Warning),
+        }
+    }
+}
+
+impl ThemeTokens {
+    pub fn get(&self, token: ThemeToken) -> &ColorSetting {
+        match token {
+            ThemeToken::Primary => &self.primary,
+            ThemeToken::OnPrimary => &self.on_primary,
+            ThemeToken::Surface => &self.surface,
+            ThemeToken::SurfaceContainer => &self.surface_container,
+            ThemeToken::SurfaceContainerHigh => &self.surface_container_high,
+            ThemeToken::OnSurface => &self.on_surface,
+            ThemeToken::OnSurfaceVariant => &self.on_surface_variant,
+            ThemeToken::Outline => &self.outline,
+            ThemeToken::OutlineVariant => &self.outline_variant,
+            ThemeToken::Secondary => &self.secondary,
+            ThemeToken::Tertiary => &self.tertiary,
+            ThemeToken::Error => &self.error,
+            ThemeToken::Success => &self.success,
+            ThemeToken::Warning => &self.warning,
+        }
+    }
+
+    pub fn set(&mut self, token: ThemeToken, color: ColorSetting) {
+        let slot = match token {
+            ThemeToken::Primary => &mut self.primary,
+            ThemeToken::OnPrimary => &mut self.on_primary,
+            ThemeToken::Surface => &mut self.surface,
+            ThemeToken::SurfaceContainer => &mut self.surface_container,
+            ThemeToken::SurfaceContainerHigh => &mut self.surface_container_high,
+            ThemeToken::OnSurface => &mut self.on_surface,
+            ThemeToken::OnSurfaceVariant => &mut self.on_surface_variant,
+            ThemeToken::Outline => &mut self.outline,
+            ThemeToken::OutlineVariant => &mut self.outline_variant,
+            ThemeToken::Secondary => &mut self.secondary,
+            ThemeToken::Tertiary => &mut self.tertiary,
+            ThemeToken::Error => &mut self.error,
+            ThemeToken::Success => &mut self.success,
+            ThemeToken::Warning => &mut self.warning,
+        };
+        *slot = color;
+    }
+
+    pub fn color(&self, token: ThemeToken) -> Color {
+        self.get(token).to_color()
+    }
+}
+
+/// A saved set of token colors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomTheme {
+    pub name: String,
+    pub tokens: ThemeTokens,
+}
+
+/// Save tokens as a custom theme, replacing one with the same name
+/// (case-insensitive).
+pub fn save_custom_theme(
+    themes: &mut Vec<CustomTheme>,
+    name: &str,
+    tokens: &ThemeTokens,
+) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name the theme before saving it".to_string());
+    }
+    let saved = CustomTheme {
+        name: name.to_string(),
+        tokens: tokens.clone(),
+    };
+    match themes
+        .iter_mut()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+    {
+        Some(existing) => {
+            *existing = saved;
+            Ok(format!("Theme updated: {}", name))
+        }
+        None => {
+            themes.push(saved);
+            Ok(format!("Theme saved: {}", name))
+        }
+    }
+}
+
+/// The custom theme with this name (case-insensitive).
+pub fn find_custom_theme<'a>(themes: &'a [CustomTheme], name: &str) -> Option<&'a CustomTheme> {
+    let name = name.trim();
+    themes.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+}
+
+/// Text recolored with its token
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedText(pub ThemeToken);
+
+/// Background recolored with its token, at a fixed alpha
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedBackground {
+    pub token: ThemeToken,
+    pub alpha: f32,
+}
+
+/// Border recolored with its token
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedBorder(pub ThemeToken);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_default_and_set() {
+        let mut tokens = ThemeTokens::default();
+        for token in ThemeToken::ALL {
+            assert_eq!(
+                tokens.color(token).to_srgba(),
+                token.default_color().to_srgba()
+            );
+        }
+
+        let red = ColorSetting::parse("#FFFF0000").unwrap();
+        tokens.set(ThemeToken::Warning, red.clone());
+        assert_eq!(tokens.get(ThemeToken::Warning), &red);
+        assert_eq!(tokens.get(ThemeToken::Error), &ThemeTokens::default().error);
+    }
+
+    #[test]
+    fn test_tokens_round_trip_and_fill_missing() {
+        let mut tokens = ThemeTokens::default();
+        tokens.set(
+            ThemeToken::Surface,
+            ColorSetting::parse("#FF202020").unwrap(),
+        );
+        let json = serde_json::to_string(&tokens).unwrap();
+        assert_eq!(serde_json::from_str::<ThemeTokens>(&json).unwrap(), tokens);
+
+        let partial: ThemeTokens =
+            serde_json::from_str(r#"{"primary":{"a":1.0,"r":1.0,"g":0.0,"b":0.0}}"#).unwrap();
+        assert_eq!(partial.primary.r, 1.0);
+        assert_eq!(partial.outline, ThemeTokens::default().outline);
+    }
+
+    #[test]
+    fn test_save_custom_theme_replaces_by_name() {
+        let mut themes = Vec::new();
+        let mut tokens = ThemeTokens::default();
+        assert!(save_custom_theme(&mut themes, "  ", &tokens).is_err());
+        assert_eq!(
+            save_custom_theme(&mut themes, "Parchment", &tokens).unwrap(),
+            "Theme saved: Parchment"
+        );
+
+        tokens.set(
+            ThemeToken::Primary,
+            ColorSetting::parse("#FF884422").unwrap(),
+        );
+        assert_eq!(
+            save_custom_theme(&mut themes, "parchment", &tokens).unwrap(),
+            "Theme updated: parchment"
+        );
+        assert_eq!(themes.len(), 1);
+        assert_eq!(
+            find_custom_theme(&themes, "PARCHMENT").unwrap().tokens,
+            tokens
+        );
+        assert!(find_custom_theme(&themes, "Night").is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Travel mode types
+//!
+//! Party-wide exploration numbers used while travelling: each member's passive
+//! Perception, the group Stealth line (the slowest member sets the pace), and
+//! the group Stealth check rolled against the monsters' passive Perception.
+//! The party is one of the saved parties (or `all`).
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::character::CharacterSheet;
+use super::database::CharacterDatabase;
+use super::party::Parties;
+
+/// Passive Perception of an average creature, used when none is given
+pub const DEFAULT_MONSTER_PASSIVE_PERCEPTION: i32 = 10;
+
+/// One party member's travel numbers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TravelMember {
+    pub name: String,
+    pub passive_perception: i32,
+    pub stealth_modifier: i32,
+    /// Walking speed in feet
+    pub speed: i32,
+}
+
+impl TravelMember {
+    /// Build travel numbers from a character sheet.
+    ///
+    /// Falls back to the raw ability modifier when the sheet has no
+    /// Perception/Stealth skill entry.
+    pub fn from_sheet(sheet: &CharacterSheet) -> Self {
+        let perception = sheet
+            .skills
+            .get("perception")
+            .map(|s| s.modifier)
+            .unwrap_or(sheet.modifiers.wisdom);
+        let stealth = sheet
+            .skills
+            .get("stealth")
+            .map(|s| s.modifier)
+            .unwrap_or(sheet.modifiers.dexterity);
+
+        Self {
+            name: sheet.character.name.clone(),
+            passive_perception: 10 + perception,
+            stealth_modifier: stealth,
+            speed: sheet.combat.speed,
+        }
+    }
+
+    fn roll_stealth_with(&self, die: i32, vs_passive_perception: i32) -> MemberStealthRoll {
+        let total = die + self.stealth_modifier;
+        MemberStealthRoll {
+            name: self.name.clone(),
+            die,
+            total,
+            // Passive Perception works like a DC: meeting it is enough.
+            success: total >= vs_passive_perception,
+        }
+    }
+}
+
+/// The party's travel line-up
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TravelParty {
+    pub members: Vec<TravelMember>,
+}
+
+impl TravelParty {
+    pub fn new(members: Vec<TravelMember>) -> Self {
+        Self { members }
+    }
+
+    /// Members ordered by passive Perception, highest first.
+    pub fn perception_line(&self) -> Vec<&TravelMember> {
+        let mut line: Vec<&TravelMember> = self.members.iter().collect();
+        line.sort_by(|a, b| b.passive_perception.cmp(&a.passive_perception));
+        line
+    }
+
+    /// The best passive Perception in the party (the one noticing threats).
+    pub fn best_passive_perception(&self) -> Option<&TravelMember> {
+        self.members.iter().max_by_key(|m| m.passive_perception)
+    }
+
+    /// Index of the slowest member, who sets the group's travel pace.
+    ///
+    /// Members with an unknown speed (0) are ignored.
+    pub fn slowest_index(&self) -> Option<usize> {
+        self.members
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.speed > 0)
+            .min_by_key(|(_, m)| m.speed)
+            .map(|(i, _)| i)
+    }
+
+    /// Index of the member with the lowest Stealth modifier.
+    pub fn weakest_stealth_index(&self) -> Option<usize> {
+        self.members
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, m)| m.stealth_modifier)
+            .map(|(i, _)| i)
+    }
+
+    /// Roll a group Stealth check: everyone rolls, and the group succeeds when
+    /// at least half of the members meet or beat the passive Perception.
+    pub fn roll_group_stealth(
+        &self,
+        vs_passive_perception: i32,
+        rng: &mut impl Rng,
+    ) -> GroupStealthResult {
+        let rolls = self
+            .members
+            .iter()
+            .map(|m| m.roll_stealth_with(rng.random_range(1..=20), vs_passive_perception))
+            .collect();
+        GroupStealthResult::new(rolls, vs_passive_perception)
+    }
+}
+
+/// One member's Stealth roll within a group check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberStealthRoll {
+    pub name: String,
+    pub die: i32,
+    pub total: i32,
+    pub success: bool,
+}
+
+/// Outcome of a group Stealth check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupStealthResult {
+    pub rolls: Vec<MemberStealthRoll>,
+    pub vs_passive_perception: i32,
+    pub successes: usize,
+    pub success: bool,
+}
+
+impl GroupStealthResult {
+    pub fn new(rolls: Vec<MemberStealthRoll>, vs_passive_perception: i32) -> Self {
+        let successes = rolls.iter().filter(|r| r.success).count();
+        let success = !rolls.is_empty() && successes * 2 >= rolls.len();
+        Self {
+            rolls,
+            vs_passive_perception,
+            successes,
+            success,
+        }
+    }
+}
+
+/// Load a saved party (every character for `all`) as a travel party.
+pub fn load_travel_party(
+    db: &CharacterDatabase,
+    parties: &Parties,
+    party: &str,
+) -> Result<TravelParty, String> {
+    let list = db.list_characters()?;
+    let mut members = Vec::new();
+    for name in parties.member_names(party, &list)? {
+        let entry = list
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&name))
+            .ok_or_else(|| format!("Character '{}' not found", name))?;
+        let sheet = db.load_character(entry.id)?;
+        members.push(TravelMember::from_sheet(&sheet));
+    }
+    Ok(TravelParty::new(members))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, passive: i32, stealth: i32, speed: i32) -> TravelMember {
+        TravelMember {
+            name: name.to_string(),
+            passive_perception: passive,
+            stealth_modifier: stealth,
+            speed,
+        }
+    }
+
+    fn party() -> TravelParty {
+        TravelParty::new(vec![
+            member("Thorin", 11, -1, 25),
+            member("Elara", 15, 5, 30),
+            member("Bram", 13, 2, 30),
+        ])
+    }
+
+    #[test]
+    fn test_member_from_sheet() {
+        let mut sheet = CharacterSheet::default();
+        sheet.character.name = "Elara".to_string();
+        sheet.modifiers.wisdom = 2;
+        sheet.modifiers.dexterity = 3;
+        sheet.combat.speed = 30;
+        sheet.skills.insert(
+            "perception".to_string(),
+            crate::dice3d::types::Skill {
+                proficient: true,
+                modifier: 4,
+                expertise: None,
+                proficiency_type: None,
+            },
+        );
+
+        let m = TravelMember::from_sheet(&sheet);
+        assert_eq!(m.passive_perception, 14);
+        // No stealth entry: falls back to the dexterity modifier
+        assert_eq!(m.stealth_modifier, 3);
+        assert_eq!(m.speed, 30);
+    }
+
+    #[test]
+    fn test_perception_line_and_slowest() {
+        let p = party();
+        let names: Vec<_> = p
+            .perception_line()
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Elara", "Bram", "Thorin"]);
+        assert_eq!(p.best_passive_perception().unwrap().name, "Elara");
+        assert_eq!(p.slowest_index(), Some(0));
+        assert_eq!(p.weakest_stealth_index(), Some(0));
+    }
+
+    #[test]
+    fn test_group_stealth_half_must_succeed() {
+        let p = party();
+        let rolls = vec![
+            p.members[0].roll_stealth_with(5, 12),
+            p.members[1].roll_stealth_with(10, 12),
+            p.members[2].roll_stealth_with(8, 12),
+        ];
+        // Thorin 4 (fail), Elara 15 (pass), Bram 10 (fail)
+        let result = GroupStealthResult::new(rolls, 12);
+        assert_eq!(result.successes, 1);
+        assert!(!result.success);
+
+        let rolls = vec![
+            p.members[0].roll_stealth_with(5, 12),
+            p.members[1].roll_stealth_with(10, 12),
+        ];
+        assert!(GroupStealthResult::new(rolls, 12).success);
+    }
+
+    #[test]
+    fn test_stealth_meeting_passive_perception_succeeds() {
+        let m = member("Bram", 13, 2, 30);
+        assert!(!m.roll_stealth_with(9, 12).success);
+        assert!(m.roll_stealth_with(10, 12).success);
+    }
+}
@@ -0,0 +1,566 @@
+//! Treasure hoards
+//!
+//! Rolls a treasure hoard from the Dungeon Master's Guide tables for the
+//! challenge rating of the monster guarding it: coins by CR tier, then a
+//! d100 on the tier's hoard table for gems or art objects and the magic item
+//! tables (A to I) to roll on. Magic item tables are recorded as the table
+//! and its d100 roll, to look up in the DMG.
+//!
+//! Every hoard goes into the party's [`TreasureLedger`], kept per profile in
+//! the database's settings table under [`TREASURE_LEDGER_SETTING_KEY`].
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::currency::{Coin, Currency};
+
+/// Database setting holding the party's treasure ledger
+pub const TREASURE_LEDGER_SETTING_KEY: &str = "treasure_ledger";
+
+/// The DMG's hoard tables, one per challenge rating band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HoardTier {
+    Cr0To4,
+    Cr5To10,
+    Cr11To16,
+    Cr17Plus,
+}
+
+/// Gems or art objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValuableKind {
+    Gem,
+    Art,
+}
+
+/// One of the DMG's magic item tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MagicItemTable {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+}
+
+/// `count` gems or art objects worth `value_gp` each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Valuables {
+    pub kind: ValuableKind,
+    pub count: u32,
+    pub value_gp: u32,
+}
+
+/// A d100 rolled on a magic item table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MagicItemRoll {
+    pub table: MagicItemTable,
+    pub roll: u32,
+}
+
+/// A rolled treasure hoard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasureHoard {
+    pub tier: HoardTier,
+    /// The d100 rolled on the tier's hoard table
+    pub roll: u32,
+    pub coins: Currency,
+    pub valuables: Option<Valuables>,
+    pub magic_items: Vec<MagicItemRoll>,
+}
+
+/// Every hoard the party has found
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasureLedger {
+    #[serde(default)]
+    pub hoards: Vec<TreasureHoard>,
+}
+
+/// Dice for a count: `(dice, sides)`, so `(2, 6)` is 2d6 and `(1, 1)` is
+/// just one
+type CountDice = (u32, u32);
+
+/// A range of the d100 on a hoard table
+struct HoardRow {
+    /// Highest d100 roll of the range
+    up_to: u32,
+    /// Gems or art objects, how many and their worth in gold pieces
+    valuables: Option<(ValuableKind, CountDice, u32)>,
+    magic_items: &'static [(MagicItemTable, CountDice)],
+}
+
+const fn row(
+    up_to: u32,
+    valuables: Option<(ValuableKind, CountDice, u32)>,
+    magic_items: &'static [(MagicItemTable, CountDice)],
+) -> HoardRow {
+    HoardRow {
+        up_to,
+        valuables,
+        magic_items,
+    }
+}
+
+const fn gems(count: CountDice, value_gp: u32) -> Option<(ValuableKind, CountDice, u32)> {
+    Some((ValuableKind::Gem, count, value_gp))
+}
+
+const fn art(count: CountDice, value_gp: u32) -> Option<(ValuableKind, CountDice, u32)> {
+    Some((ValuableKind::Art, count, value_gp))
+}
+
+use MagicItemTable::{A, B, C, D, E, F, G, H, I};
+
+const HOARD_CR_0_4: [HoardRow; 17] = [
+    row(6, None, &[]),
+    row(16, gems((2, 6), 10), &[]),
+    row(26, art((2, 4), 25), &[]),
+    row(36, gems((2, 6), 50), &[]),
+    row(44, gems((2, 6), 10), &[(A, (1, 6))]),
+    row(52, art((2, 4), 25), &[(A, (1, 6))]),
+    row(60, gems((2, 6), 50), &[(A, (1, 6))]),
+    row(65, gems((2, 6), 10), &[(B, (1, 4))]),
+    row(70, art((2, 4), 25), &[(B, (1, 4))]),
+    row(75, gems((2, 6), 50), &[(B, (1, 4))]),
+    row(78, gems((2, 6), 10), &[(C, (1, 4))]),
+    row(80, art((2, 4), 25), &[(C, (1, 4))]),
+    row(85, gems((2, 6), 50), &[(C, (1, 4))]),
+    row(92, art((2, 4), 25), &[(F, (1, 4))]),
+    row(97, gems((2, 6), 50), &[(F, (1, 4))]),
+    row(99, art((2, 4), 25), &[(G, (1, 1))]),
+    row(100, gems((2, 6), 50), &[(G, (1, 1))]),
+];
+
+const HOARD_CR_5_10: [HoardRow; 29] = [
+    row(4, None, &[]),
+    row(10, art((2, 4), 25), &[]),
+    row(16, gems((3, 6), 50), &[]),
+    row(22, gems((3, 6), 100), &[]),
+    row(28, art((2, 4), 250), &[]),
+    row(32, art((2, 4), 25), &[(A, (1, 6))]),
+    row(36, gems((3, 6), 50), &[(A, (1, 6))]),
+    row(40, gems((3, 6), 100), &[(A, (1, 6))]),
+    row(44, art((2, 4), 250), &[(A, (1, 6))]),
+    row(49, art((2, 4), 25), &[(B, (1, 4))]),
+    row(54, gems((3, 6), 50), &[(B, (1, 4))]),
+    row(59, gems((3, 6), 100), &[(B, (1, 4))]),
+    row(63, art((2, 4), 250), &[(B, (1, 4))]),
+    row(66, art((2, 4), 25), &[(C, (1, 4))]),
+    row(69, gems((3, 6), 50), &[(C, (1, 4))]),
+    row(72, gems((3, 6), 100), &[(C, (1, 4))]),
+    row(74, art((2, 4), 250), &[(C, (1, 4))]),
+    row(76, art((2, 4), 25), &[(D, (1, 1))]),
+    row(78, gems((3, 6), 50), &[(D, (1, 1))]),
+    row(79, gems((3, 6), 100), &[(D, (1, 1))]),
+    row(80, art((2, 4), 250), &[(D, (1, 1))]),
+    row(84, art((2, 4), 25), &[(F, (1, 4))]),
+    row(88, gems((3, 6), 50), &[(F, (1, 4))]),
+    row(91, gems((3, 6), 100), &[(F, (1, 4))]),
+    row(94, art((2, 4), 250), &[(F, (1, 4))]),
+    row(96, gems((3, 6), 100), &[(G, (1, 4))]),
+    row(98, art((2, 4), 250), &[(G, (1, 6))]),
+    row(99, gems((3, 6), 100), &[(H, (1, 1))]),
+    row(100, art((2, 4), 250), &[(H, (1, 1))]),
+];
+
+const HOARD_CR_11_16: [HoardRow; 33] = [
+    row(3, None, &[]),
+    row(6, art((2, 4), 250), &[]),
+    row(9, art((2, 4), 750), &[]),
+    row(12, gems((3, 6), 500), &[]),
+    row(15, gems((3, 6), 1000), &[]),
+    row(19, art((2, 4), 250), &[(A, (1, 4)), (B, (1, 6))]),
+    row(23, art((2, 4), 750), &[(A, (1, 4)), (B, (1, 6))]),
+    row(26, gems((3, 6), 500), &[(A, (1, 4)), (B, (1, 6))]),
+    row(29, gems((3, 6), 1000), &[(A, (1, 4)), (B, (1, 6))]),
+    row(35, art((2, 4), 250), &[(C, (1, 6))]),
+    row(40, art((2, 4), 750), &[(C, (1, 6))]),
+    row(45, gems((3, 6), 500), &[(C, (1, 6))]),
+    row(50, gems((3, 6), 1000), &[(C, (1, 6))]),
+    row(54, art((2, 4), 250), &[(D, (1, 4))]),
+    row(58, art((2, 4), 750), &[(D, (1, 4))]),
+    row(62, gems((3, 6), 500), &[(D, (1, 4))]),
+    row(66, gems((3, 6), 1000), &[(D, (1, 4))]),
+    row(68, art((2, 4), 250), &[(E, (1, 1))]),
+    row(70, art((2, 4), 750), &[(E, (1, 1))]),
+    row(72, gems((3, 6), 500), &[(E, (1, 1))]),
+    row(74, gems((3, 6), 1000), &[(E, (1, 1))]),
+    row(76, art((2, 4), 250), &[(F, (1, 1)), (G, (1, 4))]),
+    row(78, art((2, 4), 750), &[(F, (1, 1)), (G, (1, 4))]),
+    row(80, gems((3, 6), 500), &[(F, (1, 1)), (G, (1, 4))]),
+    row(82, gems((3, 6), 1000), &[(F, (1, 1)), (G, (1, 4))]),
+    row(85, art((2, 4), 250), &[(H, (1, 4))]),
+    row(88, art((2, 4), 750), &[(H, (1, 4))]),
+    row(90, gems((3, 6), 500), &[(H, (1, 4))]),
+    row(92, gems((3, 6), 1000), &[(H, (1, 4))]),
+    row(94, art((2, 4), 250), &[(I, (1, 1))]),
+    row(96, art((2, 4), 750), &[(I, (1, 1))]),
+    row(98, gems((3, 6), 500), &[(I, (1, 1))]),
+    row(100, gems((3, 6), 1000), &[(I, (1, 1))]),
+];
+
+const HOARD_CR_17_PLUS: [HoardRow; 25] = [
+    row(2, None, &[]),
+    row(5, gems((3, 6), 1000), &[(C, (1, 8))]),
+    row(8, art((1, 10), 2500), &[(C, (1, 8))]),
+    row(11, art((1, 4), 7500), &[(C, (1, 8))]),
+    row(14, gems((1, 8), 5000), &[(C, (1, 8))]),
+    row(22, gems((3, 6), 1000), &[(D, (1, 6))]),
+    row(30, art((1, 10), 2500), &[(D, (1, 6))]),
+    row(38, art((1, 4), 7500), &[(D, (1, 6))]),
+    row(46, gems((1, 8), 5000), &[(D, (1, 6))]),
+    row(52, gems((3, 6), 1000), &[(E, (1, 6))]),
+    row(58, art((1, 10), 2500), &[(E, (1, 6))]),
+    row(63, art((1, 4), 7500), &[(E, (1, 6))]),
+    row(68, gems((1, 8), 5000), &[(E, (1, 6))]),
+    row(69, gems((3, 6), 1000), &[(G, (1, 4))]),
+    row(70, art((1, 10), 2500), &[(G, (1, 4))]),
+    row(71, art((1, 4), 7500), &[(G, (1, 4))]),
+    row(72, gems((1, 8), 5000), &[(G, (1, 4))]),
+    row(74, gems((3, 6), 1000), &[(H, (1, 4))]),
+    row(76, art((1, 10), 2500), &[(H, (1, 4))]),
+    row(78, art((1, 4), 7500), &[(H, (1, 4))]),
+    row(80, gems((1, 8), 5000), &[(H, (1, 4))]),
+    row(85, gems((3, 6), 1000), &[(I, (1, 4))]),
+    row(90, art((1, 10), 2500), &[(I, (1, 4))]),
+    row(95, art((1, 4), 7500), &[(I, (1, 4))]),
+    row(100, gems((1, 8), 5000), &[(I, (1, 4))]),
+];
+
+fn roll_count(rng: &mut impl Rng, (dice, sides): CountDice) -> u32 {
+    (0..dice).map(|_| rng.random_range(1..=sides)).sum()
+}
+
+impl HoardTier {
+    pub const ALL: [HoardTier; 4] = [
+        HoardTier::Cr0To4,
+        HoardTier::Cr5To10,
+        HoardTier::Cr11To16,
+        HoardTier::Cr17Plus,
+    ];
+
+    /// The tier whose table a monster of challenge rating `cr` uses
+    pub fn for_cr(cr: u32) -> Self {
+        match cr {
+            0..=4 => HoardTier::Cr0To4,
+            5..=10 => HoardTier::Cr5To10,
+            11..=16 => HoardTier::Cr11To16,
+            _ => HoardTier::Cr17Plus,
+        }
+    }
+
+    /// Read a challenge rating: "7", "1/2" or "CR 12".
+    pub fn parse_cr(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text
+            .strip_prefix("CR")
+            .or_else(|| text.strip_prefix("cr"))
+            .unwrap_or(text)
+            .trim();
+        if matches!(text, "1/8" | "1/4" | "1/2") {
+            return Some(HoardTier::Cr0To4);
+        }
+        text.parse::<u32>().ok().map(Self::for_cr)
+    }
+
+    /// "CR 5-10"
+    pub fn label(&self) -> &'static str {
+        match self {
+            HoardTier::Cr0To4 => "CR 0-4",
+            HoardTier::Cr5To10 => "CR 5-10",
+            HoardTier::Cr11To16 => "CR 11-16",
+            HoardTier::Cr17Plus => "CR 17+",
+        }
+    }
+
+    /// Coins in every hoard of the tier: coin, d6s rolled and what they are
+    /// multiplied by
+    fn coin_dice(&self) -> &'static [(Coin, u32, i32)] {
+        match self {
+            HoardTier::Cr0To4 => &[
+                (Coin::Copper, 6, 100),
+                (Coin::Silver, 3, 100),
+                (Coin::Gold, 2, 10),
+            ],
+            HoardTier::Cr5To10 => &[
+                (Coin::Copper, 2, 100),
+                (Coin::Silver, 2, 1000),
+                (Coin::Gold, 6, 100),
+                (Coin::Platinum, 3, 10),
+            ],
+            HoardTier::Cr11To16 => &[(Coin::Gold, 4, 1000), (Coin::Platinum, 5, 100)],
+            HoardTier::Cr17Plus => &[(Coin::Gold, 12, 1000), (Coin::Platinum, 8, 1000)],
+        }
+    }
+
+    fn rows(&self) -> &'static [HoardRow] {
+        match self {
+            HoardTier::Cr0To4 => &HOARD_CR_0_4,
+            HoardTier::Cr5To10 => &HOARD_CR_5_10,
+            HoardTier::Cr11To16 => &HOARD_CR_11_16,
+            HoardTier::Cr17Plus => &HOARD_CR_17_PLUS,
+        }
+    }
+
+    fn row(&self, d100: u32) -> &'static HoardRow {
+        let rows = self.rows();
+        rows.iter()
+            .find(|row| d100 <= row.up_to)
+            .unwrap_or(&rows[rows.len() - 1])
+    }
+}
+
+impl MagicItemTable {
+    pub fn letter(&self) -> char {
+        match self {
+            MagicItemTable::A => 'A',
+            MagicItemTable::B => 'B',
+            MagicItemTable::C => 'C',
+            MagicItemTable::D => 'D',
+            MagicItemTable::E => 'E',
+            MagicItemTable::F => 'F',
+            MagicItemTable::G => 'G',
+            MagicItemTable::H => 'H',
+            MagicItemTable::I => 'I',
+        }
+    }
+}
+
+impl Valuables {
+    pub fn total_gp(&self) -> u32 {
+        self.count * self.value_gp
+    }
+
+    /// "7 × 50 gp gems" or "1 × 2500 gp art object"
+    pub fn describe(&self) -> String {
+        let kind = match (self.kind, self.count) {
+            (ValuableKind::Gem, 1) => "gem",
+            (ValuableKind::Gem, _) => "gems",
+            (ValuableKind::Art, 1) => "art object",
+            (ValuableKind::Art, _) => "art objects",
+        };
+        format!("{} × {} gp {}", self.count, self.value_gp, kind)
+    }
+}
+
+impl TreasureHoard {
+    /// Roll a hoard on the tier's tables.
+    pub fn roll(tier: HoardTier, rng: &mut impl Rng) -> Self {
+        let mut coins = Currency::default();
+        for (coin, d6s, multiplier) in tier.coin_dice() {
+            let amount = roll_count(rng, (*d6s, 6)) as i32 * multiplier;
+            coins.add(*coin, amount);
+        }
+
+        let roll = rng.random_range(1..=100);
+        let row = tier.row(roll);
+        let valuables = row.valuables.map(|(kind, count, value_gp)| Valuables {
+            kind,
+            count: roll_count(rng, count),
+            value_gp,
+        });
+        let mut magic_items = Vec::new();
+        for (table, count) in row.magic_items {
+            for _ in 0..roll_count(rng, *count) {
+                magic_items.push(MagicItemRoll {
+                    table: *table,
+                    roll: rng.random_range(1..=100),
+                });
+            }
+        }
+
+        TreasureHoard {
+            tier,
+            roll,
+            coins,
+            valuables,
+            magic_items,
+        }
+    }
+
+    /// Worth of the coins, gems and art objects in gold pieces; magic items
+    /// aren't priced
+    pub fn value_gp(&self) -> f64 {
+        self.coins.total_gold() + self.valuables.map_or(0, |v| v.total_gp()) as f64
+    }
+
+    /// One line: "CR 5-10 hoard (d100 37): 700 cp, ...; 9 × 100 gp gems;
+    /// Magic Item Table A ×3 (d100 12, 51, 97)"
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.coins.describe()];
+        if let Some(valuables) = &self.valuables {
+            parts.push(valuables.describe());
+        }
+        let mut tables: Vec<MagicItemTable> =
+            self.magic_items.iter().map(|item| item.table).collect();
+        tables.dedup();
+        for table in tables {
+            let rolls: Vec<String> = self
+                .magic_items
+                .iter()
+                .filter(|item| item.table == table)
+                .map(|item| item.roll.to_string())
+                .collect();
+            parts.push(format!(
+                "Magic Item Table {} ×{} (d100 {})",
+                table.letter(),
+                rolls.len(),
+                rolls.join(", ")
+            ));
+        }
+        format!(
+            "{} hoard (d100 {}): {}",
+            self.tier.label(),
+            self.roll,
+            parts.join("; ")
+        )
+    }
+}
+
+impl TreasureLedger {
+    pub fn add(&mut self, hoard: TreasureHoard) {
+        self.hoards.push(hoard);
+    }
+
+    /// Every coin found
+    pub fn coins(&self) -> Currency {
+        let mut coins = Currency::default();
+        for hoard in &self.hoards {
+            for coin in Coin::ALL {
+                coins.add(coin, hoard.coins.get(coin));
+            }
+        }
+        coins
+    }
+
+    /// Worth of the coins, gems and art objects found, in gold pieces
+    pub fn value_gp(&self) -> f64 {
+        self.hoards.iter().map(TreasureHoard::value_gp).sum()
+    }
+
+    pub fn magic_item_count(&self) -> usize {
+        self.hoards.iter().map(|h| h.magic_items.len()).sum()
+    }
+
+    /// "3 hoards worth 12345 gp (50 gp, 1000 cp); 4 magic item rolls"
+    pub fn summary(&self) -> String {
+        if self.hoards.is_empty() {
+            return "The treasure ledger is empty".to_string();
+        }
+        format!(
+            "{} hoard{} worth {:.0} gp ({}); {} magic item roll{}",
+            self.hoards.len(),
+            if self.hoards.len() == 1 { "" } else { "s" },
+            self.value_gp(),
+            self.coins().describe(),
+            self.magic_item_count(),
+            if self.magic_item_count() == 1 {
+                ""
+            } else {
+                "s"
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_hoard_tables_cover_the_d100() {
+        for tier in HoardTier::ALL {
+            let rows = tier.rows();
+            assert!(rows.windows(2).all(|w| w[0].up_to < w[1].up_to));
+            assert_eq!(rows.last().unwrap().up_to, 100, "{:?}", tier);
+            assert!(rows[0].valuables.is_none() && rows[0].magic_items.is_empty());
+        }
+        // 37-44 on the CR 0-4 table: 10 gp gems and Magic Item Table A
+        let row = HoardTier::Cr0To4.row(40);
+        assert_eq!(row.valuables, gems((2, 6), 10));
+        assert_eq!(row.magic_items, &[(A, (1, 6))]);
+        assert_eq!(HoardTier::Cr17Plus.row(100).magic_items, &[(I, (1, 4))]);
+    }
+
+    #[test]
+    fn test_challenge_rating_tiers() {
+        assert_eq!(HoardTier::parse_cr("1/2"), Some(HoardTier::Cr0To4));
+        assert_eq!(HoardTier::parse_cr("4"), Some(HoardTier::Cr0To4));
+        assert_eq!(HoardTier::parse_cr("CR 5"), Some(HoardTier::Cr5To10));
+        assert_eq!(HoardTier::parse_cr("16"), Some(HoardTier::Cr11To16));
+        assert_eq!(HoardTier::parse_cr("30"), Some(HoardTier::Cr17Plus));
+        assert_eq!(HoardTier::parse_cr("dragon"), None);
+    }
+
+    #[test]
+    fn test_rolled_hoards_stay_within_the_tables() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let hoard = TreasureHoard::roll(HoardTier::Cr5To10, &mut rng);
+            assert!((200..=1200).contains(&hoard.coins.copper));
+            assert!((2000..=12000).contains(&hoard.coins.silver));
+            assert!((600..=3600).contains(&hoard.coins.gold));
+            assert!((30..=180).contains(&hoard.coins.platinum));
+            assert_eq!(hoard.coins.electrum, 0);
+            assert!((1..=100).contains(&hoard.roll));
+            let row = HoardTier::Cr5To10.row(hoard.roll);
+            assert_eq!(hoard.valuables.is_some(), row.valuables.is_some());
+            assert!(hoard
+                .magic_items
+                .iter()
+                .all(|item| row.magic_items.iter().any(|(t, _)| *t == item.table)));
+        }
+    }
+
+    #[test]
+    fn test_ledger_totals() {
+        let hoard = TreasureHoard {
+            tier: HoardTier::Cr0To4,
+            roll: 40,
+            coins: Currency {
+                copper: 1000,
+                gold: 50,
+                ..Default::default()
+            },
+            valuables: Some(Valuables {
+                kind: ValuableKind::Gem,
+                count: 7,
+                value_gp: 10,
+            }),
+            magic_items: vec![
+                MagicItemRoll { table: A, roll: 12 },
+                MagicItemRoll { table: A, roll: 97 },
+            ],
+        };
+        assert_eq!(
+            hoard.summary(),
+            "CR 0-4 hoard (d100 40): 50 gp, 1000 cp; 7 × 10 gp gems; Magic Item Table A ×2 (d100 12, 97)"
+        );
+
+        let mut ledger = TreasureLedger::default();
+        assert_eq!(ledger.summary(), "The treasure ledger is empty");
+        ledger.add(hoard.clone());
+        ledger.add(hoard);
+        assert_eq!(ledger.coins().gold, 100);
+        assert_eq!(ledger.value_gp(), 2.0 * (10.0 + 50.0 + 70.0));
+        assert_eq!(ledger.magic_item_count(), 4);
+
+        let json = serde_json::to_string(&ledger).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TreasureLedger>(&json).unwrap(),
+            ledger
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Tween / keyframe animation types
+//!
+//! A small keyframed transform animation model with easing curves. Features such
+//! as the dice box lid, tower doors, tray swaps and camera moves attach a
+//! [`TransformTween`] to any entity with a `Transform`; playback follows the
+//! animation speed setting.
+
+use bevy::prelude::*;
+
+/// Easing curve applied to the normalized time of a tween segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    /// Overshoots slightly past the target before settling (lids, doors)
+    EaseOutBack,
+    SmoothStep,
+}
+
+impl Easing {
+    /// Map a normalized time `t` (0..1) through the easing curve.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInQuad => t * t,
+            Easing::EaseOutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::EaseInCubic => t * t * t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::EaseOutBack => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.0;
+                1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+            }
+            Easing::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A transform pose at a point in time.
+///
+/// `easing` shapes the segment that ends at this keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformKeyframe {
+    pub time: f32,
+    pub transform: Transform,
+    pub easing: Easing,
+}
+
+impl TransformKeyframe {
+    pub fn new(time: f32, transform: Transform) -> Self {
+        Self {
+            time,
+            transform,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// What a tween does when it reaches its last keyframe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TweenRepeat {
+    #[default]
+    Once,
+    Loop,
+    /// Play forwards then backwards, forever
+    PingPong,
+}
+
+/// Keyframed transform animation attached to an entity.
+///
+/// Advanced by `advance_transform_tweens`, scaled by the global animation
+/// speed setting. A [`TweenCompleted`] message is sent when a `Once` tween
+/// reaches its end.
+#[derive(Component, Debug, Clone)]
+pub struct TransformTween {
+    pub keyframes: Vec<TransformKeyframe>,
+    pub elapsed: f32,
+    pub repeat: TweenRepeat,
+    /// Per-tween speed multiplier (on top of the global animation speed)
+    pub speed: f32,
+    pub finished: bool,
+}
+
+impl TransformTween {
+    /// Create a tween from keyframes. Keyframes are sorted by time.
+    pub fn new(mut keyframes: Vec<TransformKeyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self {
+            keyframes,
+            elapsed: 0.0,
+            repeat: TweenRepeat::Once,
+            speed: 1.0,
+            finished: false,
+        }
+    }
+
+    /// Two-keyframe tween from one pose to another.
+    pub fn between(from: Transform, to: Transform, duration: f32, easing: Easing) -> Self {
+        Self::new(vec![
+            TransformKeyframe::new(0.0, from),
+            TransformKeyframe::new(duration.max(0.0), to).with_easing(easing),
+        ])
+    }
+
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Time of the last keyframe in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    /// Sample the interpolated transform at `time` seconds.
+    pub fn sample(&self, time: f32) -> Transform {
+        let Some(first) = self.keyframes.first() else {
+            return Transform::IDENTITY;
+        };
+        if time <= first.time {
+            return first.transform;
+        }
+
+        for pair in self.keyframes.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if time <= b.time {
+                let span = b.time - a.time;
+                let t = if span <= f32::EPSILON {
+                    1.0
+                } else {
+                    (time - a.time) / span
+                };
+                let e = b.easing.apply(t);
+                return Transform {
+                    translation: a.transform.translation.lerp(b.transform.translation, e),
+                    rotation: a.transform.rotation.slerp(b.transform.rotation, e),
+                    scale: a.transform.scale.lerp(b.transform.scale, e),
+                };
+            }
+        }
+
+        self.keyframes
+            .last()
+            .map(|k| k.transform)
+            .unwrap_or(first.transform)
+    }
+
+    /// Local time within the keyframes after applying the repeat mode.
+    fn local_time(&self) -> f32 {
+        let duration = self.duration();
+        if duration <= f32::EPSILON {
+            return duration;
+        }
+        match self.repeat {
+            TweenRepeat::Once => self.elapsed.min(duration),
+            TweenRepeat::Loop => self.elapsed.rem_euclid(duration),
+            TweenRepeat::PingPong => {
+                let t = self.elapsed.rem_euclid(duration * 2.0);
+                if t > duration {
+                    duration * 2.0 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+
+    /// The transform at the current playback position.
+    pub fn current(&self) -> Transform {
+        self.sample(self.local_time())
+    }
+
+    /// Advance playback. Returns `true` on the frame a `Once` tween finishes.
+    pub fn tick(&mut self, delta_seconds: f32) -> bool {
+        if self.finished {
+            return false;
+        }
+        self.elapsed += delta_seconds * self.speed.max(0.0);
+        if self.repeat == TweenRepeat::Once && self.elapsed >= self.duration() {
+            self.finished = true;
+            return true;
+        }
+        false
+    }
+}
+
+/// Sent when a `TweenRepeat::Once` tween reaches its last keyframe.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseInQuad,
+            Easing::EaseOutQuad,
+            Easing::EaseInOutQuad,
+            Easing::EaseInCubic,
+            Easing::EaseOutCubic,
+            Easing::EaseInOutCubic,
+            Easing::EaseOutBack,
+            Easing::SmoothStep,
+        ] {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{:?} at 0", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{:?} at 1", easing);
+        }
+        assert!(Easing::EaseInQuad.apply(0.5) < 0.5);
+        assert!(Easing::EaseOutQuad.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn test_sample_between_keyframes() {
+        let tween = TransformTween::new(vec![
+            TransformKeyframe::new(2.0, Transform::from_xyz(10.0, 0.0, 0.0)),
+            TransformKeyframe::new(0.0, Transform::from_xyz(0.0, 0.0, 0.0)),
+        ]);
+        assert_eq!(tween.duration(), 2.0);
+        assert_eq!(tween.sample(-1.0).translation.x, 0.0);
+        assert!((tween.sample(1.0).translation.x - 5.0).abs() < 1e-5);
+        assert_eq!(tween.sample(5.0).translation.x, 10.0);
+    }
+
+    #[test]
+    fn test_once_tween_finishes() {
+        let mut tween = TransformTween::between(
+            Transform::IDENTITY,
+            Transform::from_xyz(0.0, 1.0, 0.0),
+            1.0,
+            Easing::EaseOutCubic,
+        );
+        assert!(!tween.tick(0.5));
+        assert!(tween.tick(0.6));
+        assert!(tween.finished);
+        assert!(!tween.tick(0.1));
+        assert_eq!(tween.current().translation.y, 1.0);
+    }
+
+    #[test]
+    fn test_speed_and_ping_pong() {
+        let mut tween = TransformTween::between(
+            Transform::IDENTITY,
+            Transform::from_xyz(4.0, 0.0, 0.0),
+            1.0,
+            Easing::Linear,
+        )
+        .with_repeat(TweenRepeat::PingPong)
+        .with_speed(2.0);
+
+        // 0.75s at 2x speed = 1.5s: half way back
+        assert!(!tween.tick(0.75));
+        assert!((tween.current().translation.x - 2.0).abs() < 1e-4);
+        assert!(!tween.finished);
+    }
+}
@@ -7,10 +7,13 @@ use bevy::prelude::*;
 
 use std::collections::HashMap;
 
+use super::character::SpellCasting;
+use super::character_creation::{AbilityScores, ScoreMethod};
+use super::character_templates::TemplateChoice;
+use super::conditions::Condition;
+use super::damage_riders::DamageRider;
 use super::dice::{DiceConfig, DiceType};
 
-use bevy::animation::prelude::{AnimationGraph, AnimationNodeIndex};
-
 // ============================================================================
 // Tab Navigation
 // ============================================================================
@@ -21,8 +24,12 @@ pub enum AppTab {
     #[default]
     DiceRoller,
     CharacterSheet,
+    Initiative,
+    Party,
     DndInfo,
     Contributors,
+    /// A tab added by an extension, by `ExtensionTab::id`
+    Extension(&'static str),
 }
 
 /// Resource for UI state
@@ -63,6 +70,7 @@ pub enum EditingField {
     HitPointsCurrent,
     HitPointsMaximum,
     ProficiencyBonus,
+    ExtraAttacks,
     // Skills and saves are handled by name
     Skill(String),
     SavingThrow(String),
@@ -76,6 +84,19 @@ pub enum EditingField {
     CustomAttributeLabel(String), // Renaming custom attribute
     CustomCombat(String),         // Custom combat value
     CustomCombatLabel(String),    // Renaming custom combat
+    // Spellcasting
+    SpellcastingAbility,
+    SpellSaveDc,
+    SpellAttackBonus,
+    SpellSlots(String),  // Total slots of a spell level ("1".."9")
+    SpellDamage(String), // Damage of a known spell
+    // Inventory items, by name
+    ItemQuantity(String),
+    ItemWeight(String),  // Pounds each
+    ItemValue(String),   // Gold pieces each
+    ItemBonuses(String), // "+1 saves, +1 attacks"
+    AttunementSlots,
+    Coins(Coin),
 }
 
 // ============================================================================
@@ -106,6 +127,14 @@ pub struct AppTabButton {
 #[derive(Component)]
 pub struct AppTabText;
 
+// ============================================================================
+// Loading Splash
+// ============================================================================
+
+/// Marker for the splash shown until the dice container model has loaded
+#[derive(Component)]
+pub struct LoadingSplashRoot;
+
 // ============================================================================
 // Character Screen Components
 // ============================================================================
@@ -114,6 +143,22 @@ pub struct AppTabText;
 #[derive(Component)]
 pub struct CharacterScreenRoot;
 
+// ============================================================================
+// Initiative Screen Components
+// ============================================================================
+
+/// Marker for the initiative tracker screen root
+#[derive(Component)]
+pub struct InitiativeScreenRoot;
+
+// ============================================================================
+// Party Screen Components
+// ============================================================================
+
+/// Marker for the Party tab's screen root
+#[derive(Component)]
+pub struct PartyScreenRoot;
+
 // ============================================================================
 // DnD Info Screen Components
 // ============================================================================
@@ -148,6 +193,8 @@ pub enum GroupType {
     Combat,
     SavingThrows,
     Skills,
+    Spells,
+    Inventory,
 }
 
 /// Marker for stat group container (e.g., "Basic Info", "Attributes", etc.)
@@ -260,6 +307,11 @@ pub struct SaveButton;
 #[derive(Component)]
 pub struct NewCharacterButton;
 
+/// Marker for the button importing a character from a file (`.dndchar`,
+/// character sheet JSON or a D&D Beyond export)
+#[derive(Component)]
+pub struct ImportCharacterButton;
+
 /// Marker for the "Roll All Stats" button (rolls d20 for all attributes)
 #[derive(Component)]
 pub struct RollAllStatsButton;
@@ -628,15 +680,31 @@ pub struct ContainerShakeAnimation {
 }
 
 // ============================================================================
-// Dice Box Lid Animations (glTF clips)
+// Dice Box Lid Animations (transform tweens)
 // ============================================================================
 
 /// Marker on the spawned box glTF scene root entity.
 ///
-/// Used to find an `AnimationPlayer` to drive lid animations.
+/// Used to find the `BoxLid` node that the lid tweens drive.
 #[derive(Component)]
 pub struct DiceBoxVisualSceneRoot;
 
+/// Name of the lid node inside the box glTF scene.
+pub const DICE_BOX_LID_NODE_NAME: &str = "BoxLid";
+
+/// Lid pose relative to the box scene when closed (the node's rest pose).
+pub const DICE_BOX_LID_CLOSED_POSE: Transform = Transform::IDENTITY;
+
+/// Lid pose relative to the box scene when fully open, as authored in box.glb.
+pub const DICE_BOX_LID_OPEN_POSE: Transform = Transform {
+    translation: Vec3::new(0.0, 1.287_577_3, -0.792_462_7),
+    rotation: Quat::from_xyzw(-0.410_830_47, 0.0, 0.0, 0.911_711_75),
+    scale: Vec3::ONE,
+};
+
+/// Seconds the lid takes to open or close at 1x animation speed.
+pub const DICE_BOX_LID_TWEEN_SECONDS: f32 = 0.375;
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum DiceBoxLidState {
     Closed,
@@ -664,49 +732,20 @@ pub enum PendingRollRequest {
 
 #[derive(Resource, Default)]
 pub struct DiceBoxLidAnimationController {
-    pub gltf_handle: Option<Handle<bevy::gltf::Gltf>>,
-
-    pub opening_clip: Option<Handle<AnimationClip>>,
-    pub closing_clip: Option<Handle<AnimationClip>>,
-    pub idle_opened_clip: Option<Handle<AnimationClip>>,
-    pub idle_closed_clip: Option<Handle<AnimationClip>>,
-
-    pub animation_graph: Option<Handle<AnimationGraph>>,
-    pub opening_node: Option<AnimationNodeIndex>,
-    pub closing_node: Option<AnimationNodeIndex>,
-    pub idle_opened_node: Option<AnimationNodeIndex>,
-    pub idle_closed_node: Option<AnimationNodeIndex>,
-
-    pub open_duration: f32,
-    pub close_duration: f32,
-    pub idle_opened_duration: f32,
-    pub idle_closed_duration: f32,
-
-    pub animator_entity: Option<Entity>,
-
-    /// Which idle node we most recently started looping.
-    ///
-    /// Used to avoid re-starting idle every frame (which can cause visible flicker
-    /// if a looping clip wraps frequently).
-    pub active_idle_node: Option<AnimationNodeIndex>,
+    /// The `BoxLid` node under the box scene, once the scene has spawned.
+    pub lid_entity: Option<Entity>,
 
     pub lid_state: DiceBoxLidState,
-    /// Remaining seconds in the current open/close animation.
-    pub state_timer: f32,
 
     /// When set, a roll is waiting for the lid to close.
     pub pending_roll: Option<PendingRollRequest>,
 
-    /// When set, the next time we are able we should play LidOpening.
+    /// When set, the next time we are able we should open the lid.
     ///
-    /// This is queued by the roll-completed event so that if the event fires
-    /// before animation assets/nodes are ready, the opening still plays once
-    /// the assets load.
+    /// This is queued by the roll-completed event so that the opening waits
+    /// for any in-progress close (and any queued roll) to finish first.
     pub pending_open_after_roll: bool,
 
-    #[cfg(debug_assertions)]
-    pub debug_last_idle_node: Option<AnimationNodeIndex>,
-
     #[cfg(debug_assertions)]
     pub debug_last_lid_state: Option<DiceBoxLidState>,
 
@@ -717,7 +756,7 @@ pub struct DiceBoxLidAnimationController {
     pub debug_last_pending_open_after_roll: Option<bool>,
 
     #[cfg(debug_assertions)]
-    pub debug_logged_player_scan: bool,
+    pub debug_logged_lid_scan: bool,
 }
 
 /// Marker for the dice roller view root (to show/hide)
@@ -839,12 +878,18 @@ pub struct QuickRollPanelDragState {
     pub grab_offset: Vec2,
 }
 
+/// Dims the scene while the physics is paused in the background
+#[derive(Component)]
+pub struct FocusPauseOverlay;
+
 /// Types of quick roll actions
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QuickRollType {
     Skill(String),
     AbilityCheck(String),
     SavingThrow(String),
+    /// Flip a coin for a 50/50 call
+    CoinFlip,
 }
 
 /// Component for quick roll buttons
@@ -853,6 +898,173 @@ pub struct QuickRollButton {
     pub roll_type: QuickRollType,
 }
 
+/// DC text field of the quick roll panel
+#[derive(Component)]
+pub struct QuickRollDcField;
+
+/// DC typed in the quick roll panel; d20 checks are reported as a success
+/// or failure against it
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuickRollDc(pub Option<i32>);
+
+/// A roll in the quick roll panel's "Suggested" row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedRoll {
+    pub label: String,
+    /// The panel button rolling it, for skills, checks and saves; other
+    /// suggestions are rolled by `handle_suggested_roll_clicks`
+    pub quick_roll: Option<QuickRollType>,
+}
+
+/// Rolls suggested by the saved roll log (see `suggest_rolls`)
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SuggestedRolls {
+    pub suggestions: Vec<SuggestedRoll>,
+    /// The last roll requested with each label this run (lower-case label
+    /// without notes), rolled again when the label is suggested
+    pub recipes: HashMap<String, DiceConfig>,
+}
+
+/// Node of the quick roll panel the suggested rolls are spawned under
+#[derive(Component)]
+pub struct SuggestedRollsRow;
+
+/// Button rolling the suggested roll at this index
+#[derive(Component)]
+pub struct SuggestedRollButton(pub usize);
+
+/// Most dice of one type the dice palette queues
+pub const MAX_PALETTE_DICE: usize = 20;
+
+/// Dice queued by clicking the quick roll panel's dice palette, thrown
+/// together with its Roll button
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DicePalette {
+    pub queued: Vec<DiceType>,
+}
+
+impl DicePalette {
+    /// The dice offered by the palette, in display order
+    pub const DICE: [DiceType; 6] = [
+        DiceType::D4,
+        DiceType::D6,
+        DiceType::D8,
+        DiceType::D10,
+        DiceType::D12,
+        DiceType::D20,
+    ];
+
+    pub fn count(&self, die: DiceType) -> usize {
+        self.queued.iter().filter(|d| **d == die).count()
+    }
+
+    /// Queue one more die; false once [`MAX_PALETTE_DICE`] are queued.
+    pub fn add(&mut self, die: DiceType) -> bool {
+        if self.count(die) >= MAX_PALETTE_DICE {
+            return false;
+        }
+        self.queued.push(die);
+        true
+    }
+
+    /// Take one die off the queue; false when none was queued.
+    pub fn remove(&mut self, die: DiceType) -> bool {
+        match self.queued.iter().rposition(|d| *d == die) {
+            Some(index) => {
+                self.queued.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The queued dice grouped by type, e.g. "2d6 + 1d8".
+    pub fn summary(&self) -> String {
+        Self::DICE
+            .iter()
+            .filter(|die| self.count(**die) > 0)
+            .map(|die| format!("{}d{}", self.count(*die), die.max_value()))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// The dice to throw, grouped by type
+    pub fn dice_config(&self) -> DiceConfig {
+        DiceConfig {
+            dice_to_roll: Self::DICE
+                .iter()
+                .flat_map(|die| std::iter::repeat_n(*die, self.count(*die)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A die of the dice palette: click queues one, right-click removes one
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DicePaletteDie(pub DiceType);
+
+/// Badge on a palette die showing how many are queued
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DicePaletteBadge(pub DiceType);
+
+/// Roll and Clear buttons of the dice palette
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DicePaletteButton {
+    Roll,
+    Clear,
+}
+
+/// Text under the dice palette listing the queued dice
+#[derive(Component)]
+pub struct DicePaletteSummary;
+
+/// Edit mode of the quick roll panel, where custom buttons are added,
+/// removed and reordered, and the fields of the button being added.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct QuickRollEditor {
+    pub editing: bool,
+    pub label: String,
+    pub expression: String,
+    pub color: String,
+    pub icon: String,
+    /// Index of the custom button whose handle is being dragged
+    pub dragging: Option<usize>,
+}
+
+/// A custom button of the quick roll panel, by its index in
+/// `AppSettings::custom_quick_rolls`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CustomQuickRollButton(pub usize);
+
+/// Row of a custom button in the quick roll panel's edit mode
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CustomQuickRollRow(pub usize);
+
+/// Handle a custom button's row is dragged by to reorder it
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CustomQuickRollDragHandle(pub usize);
+
+/// Buttons of the quick roll panel's edit mode
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickRollEditButton {
+    /// Enter or leave edit mode
+    Toggle,
+    Add,
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+/// Fields of the custom button being added
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickRollDraftField {
+    Label,
+    Expression,
+    Color,
+    Icon,
+}
+
 // ============================================================================
 // Character Sheet Roll UI (dice buttons -> 3D dice roller)
 // ============================================================================
@@ -863,6 +1075,214 @@ pub struct RollSkillButton {
     pub skill: String,
 }
 
+/// Small button next to a skill's dice button that picks which ability the
+/// skill is rolled with (e.g. Intimidation with Strength).
+#[derive(Component)]
+pub struct SkillAbilityPickerButton {
+    pub skill: String,
+}
+
+/// Label inside a [`SkillAbilityPickerButton`] showing the chosen ability.
+#[derive(Component)]
+pub struct SkillAbilityPickerText {
+    pub skill: String,
+}
+
+/// Per-skill ability overrides chosen with the skill ability picker.
+///
+/// Skills without an entry use their default ability pairing.
+#[derive(Resource, Default)]
+pub struct SkillAbilityOverrides {
+    pub overrides: HashMap<String, String>,
+}
+
+impl SkillAbilityOverrides {
+    /// Ability the skill is currently rolled with, if known.
+    pub fn ability_for(&self, skill: &str) -> Option<&str> {
+        self.overrides
+            .get(skill)
+            .map(String::as_str)
+            .or_else(|| super::character::default_skill_ability(skill))
+    }
+
+    /// Step to the next ability. Returning to the skill's default ability
+    /// clears the override.
+    pub fn cycle(&mut self, skill: &str) -> &'static str {
+        use super::character::{default_skill_ability, normalize_ability_name, ABILITY_NAMES};
+
+        let current = self.ability_for(skill).and_then(normalize_ability_name);
+        let next_index = current
+            .and_then(|c| ABILITY_NAMES.iter().position(|a| *a == c))
+            .map(|i| (i + 1) % ABILITY_NAMES.len())
+            .unwrap_or(0);
+        let next = ABILITY_NAMES[next_index];
+
+        if default_skill_ability(skill) == Some(next) {
+            self.overrides.remove(skill);
+        } else {
+            self.overrides.insert(skill.to_string(), next.to_string());
+        }
+        next
+    }
+}
+
+/// Spends (or gets back) a spell slot of a level on the Spells tab.
+#[derive(Component)]
+pub struct SpellSlotButton {
+    pub level: String,
+    pub expend: bool,
+}
+
+/// Toggles whether a known spell is prepared.
+#[derive(Component)]
+pub struct PreparedSpellButton {
+    pub spell: String,
+}
+
+/// Marks a known spell as needing concentration (shown while editing).
+#[derive(Component)]
+pub struct ConcentrationSpellButton {
+    pub spell: String,
+}
+
+/// Gives a known spell the ritual tag (shown while editing).
+#[derive(Component)]
+pub struct RitualSpellButton {
+    pub spell: String,
+}
+
+/// Casts a known ritual spell as a ritual on the Spells tab: no slot is
+/// spent and the session clock's 10-minute ritual timer starts.
+#[derive(Component)]
+pub struct CastRitualButton {
+    pub spell: String,
+}
+
+/// Small button on a known spell that picks the spell slot level it's cast
+/// with (e.g. Cure Wounds upcast with a 3rd-level slot).
+#[derive(Component)]
+pub struct SpellSlotPickerButton {
+    pub spell: String,
+}
+
+/// Label inside a [`SpellSlotPickerButton`] showing the picked level and the
+/// slots left of it.
+#[derive(Component)]
+pub struct SpellSlotPickerText {
+    pub spell: String,
+}
+
+/// Casts a known spell on the Spells tab, spending a slot of the picked
+/// level.
+#[derive(Component)]
+pub struct CastSpellButton {
+    pub spell: String,
+}
+
+/// Spell slot levels picked with the slot picker, per spell.
+///
+/// Spells without a pick, or whose picked level has no slots left, are cast
+/// with the lowest slot left.
+#[derive(Resource, Default)]
+pub struct SpellSlotChoices {
+    pub levels: HashMap<String, u32>,
+}
+
+impl SpellSlotChoices {
+    /// Slot level `spell` is cast with; `None` when no slots are left.
+    pub fn level_for(&self, spell: &str, spells: &SpellCasting) -> Option<u32> {
+        let choices = spells.slot_choices();
+        self.levels
+            .get(spell)
+            .copied()
+            .filter(|level| choices.iter().any(|(l, _)| l == level))
+            .or_else(|| choices.first().map(|(level, _)| *level))
+    }
+
+    /// Step to the next level with slots left, wrapping back to the lowest.
+    pub fn cycle(&mut self, spell: &str, spells: &SpellCasting) -> Option<u32> {
+        let current = self.level_for(spell, spells)?;
+        let choices = spells.slot_choices();
+        let next = choices
+            .iter()
+            .map(|(level, _)| *level)
+            .find(|level| *level > current)
+            .unwrap_or(choices[0].0);
+        self.levels.insert(spell.to_string(), next);
+        Some(next)
+    }
+
+    /// Picker label: the level and the slots left of it.
+    pub fn label(&self, spell: &str, spells: &SpellCasting) -> String {
+        match self.level_for(spell, spells) {
+            Some(level) => format!(
+                "Lv {} ({})",
+                level,
+                spells.slots_remaining(&level.to_string())
+            ),
+            None => "No slots".to_string(),
+        }
+    }
+}
+
+/// Applies or removes a condition on the Combat tab.
+#[derive(Component)]
+pub struct ConditionToggleButton {
+    pub condition: Condition,
+}
+
+/// Raises (or lowers) the exhaustion level on the Combat tab.
+#[derive(Component)]
+pub struct ExhaustionButton {
+    pub delta: i32,
+}
+
+/// Gives the character a damage rider, or takes it away, on the Combat tab.
+#[derive(Component)]
+pub struct DamageRiderToggleButton {
+    pub rider: DamageRider,
+}
+
+/// What an inventory toggle button switches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryToggle {
+    Equipped,
+    Attuned,
+    /// Whether the item needs attunement (while editing)
+    RequiresAttunement,
+}
+
+/// Toggles a flag of an inventory item on the Inventory tab.
+#[derive(Component)]
+pub struct InventoryToggleButton {
+    pub item: String,
+    pub toggle: InventoryToggle,
+}
+
+/// Adds a coin to the purse on the Inventory tab, or spends one (breaking a
+/// larger coin when there are none).
+#[derive(Component)]
+pub struct CoinButton {
+    pub coin: Coin,
+    pub delta: i32,
+}
+
+/// What a spell roll button rolls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpellRollKind {
+    /// d20 + spell attack bonus
+    Attack,
+    /// The spell's damage dice
+    Damage,
+}
+
+/// Dice roll button for a spell (from the Spells tab).
+#[derive(Component)]
+pub struct RollSpellButton {
+    pub spell: String,
+    pub kind: SpellRollKind,
+}
+
 /// Text node that displays the last roll total for an attribute.
 #[derive(Component)]
 pub struct AttributeRollResultText {
@@ -879,6 +1299,8 @@ pub struct SkillRollResultText {
 pub enum CharacterScreenRollTarget {
     Attribute(String),
     Skill(String),
+    /// 4d6 for an ability (by index) in the character creation wizard
+    CreationScore(usize),
 }
 
 /// Bridges character-sheet dice buttons to the dice roller and back.
@@ -893,10 +1315,226 @@ pub struct CharacterScreenRollBridge {
     pub last_character_id: Option<i64>,
 }
 
+// ============================================================================
+// Character Creation Wizard
+// ============================================================================
+
+/// Steps of the character creation wizard, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CreationStep {
+    #[default]
+    Race,
+    Class,
+    /// How the scores are decided, then the scores themselves
+    Abilities,
+    Background,
+    /// Class skill picks
+    Skills,
+    Equipment,
+    /// Cantrips and 1st-level spells; skipped for classes that don't cast yet
+    Spells,
+    Review,
+}
+
+impl CreationStep {
+    pub const ALL: [CreationStep; 8] = [
+        CreationStep::Race,
+        CreationStep::Class,
+        CreationStep::Abilities,
+        CreationStep::Background,
+        CreationStep::Skills,
+        CreationStep::Equipment,
+        CreationStep::Spells,
+        CreationStep::Review,
+    ];
+    pub const COUNT: usize = Self::ALL.len();
+
+    pub fn number(&self) -> usize {
+        Self::ALL.iter().position(|step| step == self).unwrap_or(0) + 1
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            CreationStep::Race => "Race",
+            CreationStep::Class => "Class",
+            CreationStep::Abilities => "Ability Scores",
+            CreationStep::Background => "Background",
+            CreationStep::Skills => "Skills",
+            CreationStep::Equipment => "Equipment",
+            CreationStep::Spells => "Spells",
+            CreationStep::Review => "Review",
+        }
+    }
+
+    pub fn previous(&self) -> Option<CreationStep> {
+        Self::ALL.get(self.number().checked_sub(2)?).copied()
+    }
+
+    pub fn next(&self) -> Option<CreationStep> {
+        Self::ALL.get(self.number()).copied()
+    }
+}
+
+/// The character creation wizard opened by the "New Character" button.
+#[derive(Resource, Default)]
+pub struct CharacterCreationWizard {
+    pub open: bool,
+    pub step: CreationStep,
+    pub scores: AbilityScores,
+    pub choice: TemplateChoice,
+    /// Why the last change was refused (e.g. over the point-buy budget)
+    pub message: Option<String>,
+}
+
+/// Scrim and dialog of the character creation wizard
+#[derive(Component)]
+pub struct CreationWizardOverlay;
+
+/// Picks how the wizard decides the ability scores.
+#[derive(Component)]
+pub struct CreationMethodButton {
+    pub method: ScoreMethod,
+}
+
+/// Rolls 4d6, dropping the lowest, for an ability in the 3D view.
+#[derive(Component)]
+pub struct CreationRollButton {
+    pub ability: usize,
+}
+
+/// Gives an ability the next free standard array value.
+#[derive(Component)]
+pub struct CreationArrayButton {
+    pub ability: usize,
+}
+
+/// Raises or lowers a point-buy score.
+#[derive(Component)]
+pub struct CreationPointButton {
+    pub ability: usize,
+    pub delta: i32,
+}
+
+/// Picks the new character's race.
+#[derive(Component)]
+pub struct CreationRaceButton {
+    pub race: String,
+}
+
+/// Picks the new character's class.
+#[derive(Component)]
+pub struct CreationClassButton {
+    pub class: String,
+}
+
+/// Picks or unpicks one of the class's skills.
+#[derive(Component)]
+pub struct CreationSkillButton {
+    pub skill: String,
+}
+
+/// Picks the new character's background.
+#[derive(Component)]
+pub struct CreationBackgroundButton {
+    pub background: String,
+}
+
+/// Picks one of the class's starting equipment packs.
+#[derive(Component)]
+pub struct CreationEquipmentButton {
+    pub pack: usize,
+}
+
+/// Picks or unpicks a cantrip or 1st-level spell.
+#[derive(Component)]
+pub struct CreationSpellButton {
+    pub spell: String,
+    pub cantrip: bool,
+}
+
+/// Buttons along the bottom of the wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreationNav {
+    Cancel,
+    Back,
+    /// Next step, or create the character on the last one
+    Next,
+}
+
+#[derive(Component)]
+pub struct CreationNavButton {
+    pub nav: CreationNav,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_skill_ability_override_cycles_back_to_default() {
+        let mut overrides = SkillAbilityOverrides::default();
+        assert_eq!(overrides.ability_for("intimidation"), Some("charisma"));
+
+        // charisma -> strength
+        assert_eq!(overrides.cycle("intimidation"), "strength");
+        assert_eq!(overrides.ability_for("intimidation"), Some("strength"));
+
+        for _ in 0..5 {
+            overrides.cycle("intimidation");
+        }
+        assert_eq!(overrides.ability_for("intimidation"), Some("charisma"));
+        assert!(overrides.overrides.is_empty());
+    }
+
+    #[test]
+    fn test_spell_slot_choices_skip_spent_levels() {
+        let mut spells = SpellCasting {
+            spell_slots: HashMap::from([
+                ("1".to_string(), 2),
+                ("2".to_string(), 1),
+                ("3".to_string(), 1),
+            ]),
+            ..Default::default()
+        };
+        let mut choices = SpellSlotChoices::default();
+        assert_eq!(choices.label("Cure Wounds", &spells), "Lv 1 (2)");
+
+        assert_eq!(choices.cycle("Cure Wounds", &spells), Some(2));
+        spells.expend_slot("2");
+        // The picked level ran out; the lowest one left is used instead
+        assert_eq!(choices.level_for("Cure Wounds", &spells), Some(1));
+        assert_eq!(choices.cycle("Cure Wounds", &spells), Some(3));
+        assert_eq!(choices.cycle("Cure Wounds", &spells), Some(1));
+
+        spells.used_slots = spells.spell_slots.clone();
+        assert_eq!(choices.cycle("Cure Wounds", &spells), None);
+        assert_eq!(choices.label("Cure Wounds", &spells), "No slots");
+    }
+
+    #[test]
+    fn test_dice_palette_queues_and_removes_dice() {
+        let mut palette = DicePalette::default();
+        assert!(palette.add(DiceType::D8));
+        assert!(palette.add(DiceType::D6));
+        assert!(palette.add(DiceType::D6));
+        assert_eq!(palette.count(DiceType::D6), 2);
+        assert_eq!(palette.summary(), "2d6 + 1d8");
+        assert_eq!(
+            palette.dice_config().dice_to_roll,
+            vec![DiceType::D6, DiceType::D6, DiceType::D8]
+        );
+
+        assert!(palette.remove(DiceType::D6));
+        assert!(!palette.remove(DiceType::D20));
+        assert_eq!(palette.summary(), "1d6 + 1d8");
+
+        for _ in 0..MAX_PALETTE_DICE {
+            palette.add(DiceType::D4);
+        }
+        assert!(!palette.add(DiceType::D4));
+        assert_eq!(palette.count(DiceType::D4), MAX_PALETTE_DICE);
+    }
+
     #[test]
     fn test_command_history_add() {
         let mut history = CommandHistory::default();
@@ -0,0 +1,184 @@
+//! Keyboard and gamepad focus
+//!
+//! Everything that can be clicked can also be reached without a mouse: Tab
+//! (or the gamepad's D-pad) moves a focus ring between buttons, switches,
+//! selects and sliders, Enter or A presses the focused one and left/right
+//! nudge a focused slider. While a dialog is open, focus stays inside it.
+
+use bevy::prelude::*;
+
+/// How far the focus moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMove {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// The next element in reading order (Tab)
+    Next,
+    /// The previous element in reading order (Shift+Tab)
+    Previous,
+}
+
+/// The element with keyboard/gamepad focus
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub focused: Option<Entity>,
+    /// The ring is shown once focus is moved with the keyboard or a gamepad,
+    /// and hidden again when the mouse moves.
+    pub visible: bool,
+    /// Element pressed from the keyboard or gamepad last frame, released on
+    /// the next one
+    pub pressed: Option<Entity>,
+}
+
+/// Marker for the ring drawn around the focused element.
+#[derive(Component)]
+pub struct FocusRing;
+
+/// Marker for a dialog scrim that keeps focus inside it while it is open.
+#[derive(Component)]
+pub struct FocusTrap;
+
+/// Steps a focused slider is split into for left/right nudges
+pub const SLIDER_FOCUS_STEPS: f32 = 20.0;
+
+/// Where focus goes from `current` among the focusable elements on screen
+/// (entity and rectangle).
+///
+/// Without a current element, or when it has left the screen, focus starts
+/// at the first element in reading order (the last one for `Previous`).
+/// Directional moves pick the nearest element that way, favouring ones in
+/// line; with nothing that way, focus stays put.
+pub fn move_focus(
+    current: Option<Entity>,
+    targets: &[(Entity, Rect)],
+    step: FocusMove,
+) -> Option<Entity> {
+    let mut ordered: Vec<(Entity, Rect)> = targets.to_vec();
+    ordered.sort_by(|(_, a), (_, b)| {
+        a.min
+            .y
+            .total_cmp(&b.min.y)
+            .then(a.min.x.total_cmp(&b.min.x))
+    });
+
+    let Some((index, from)) = current.and_then(|current| {
+        ordered
+            .iter()
+            .position(|(entity, _)| *entity == current)
+            .map(|index| (index, ordered[index].1))
+    }) else {
+        let start = match step {
+            FocusMove::Previous => ordered.last(),
+            _ => ordered.first(),
+        };
+        return start.map(|(entity, _)| *entity);
+    };
+
+    let direction = match step {
+        FocusMove::Next => return Some(ordered[(index + 1) % ordered.len()].0),
+        FocusMove::Previous => return Some(ordered[(index + ordered.len() - 1) % ordered.len()].0),
+        // UI coordinates grow downwards
+        FocusMove::Up => Vec2::NEG_Y,
+        FocusMove::Down => Vec2::Y,
+        FocusMove::Left => Vec2::NEG_X,
+        FocusMove::Right => Vec2::X,
+    };
+
+    let origin = from.center();
+    ordered
+        .iter()
+        .filter(|(entity, _)| Some(*entity) != current)
+        .filter_map(|(entity, rect)| {
+            let offset = rect.center() - origin;
+            let along = offset.dot(direction);
+            if along <= 1.0 {
+                return None;
+            }
+            let across = (offset - direction * along).length();
+            Some((*entity, along + 2.0 * across))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+        .or(current)
+}
+
+/// A slider value nudged by `steps` of its range, kept inside the range.
+pub fn nudge_slider(value: f32, min: f32, max: f32, steps: i32) -> f32 {
+    let step = (max - min) / SLIDER_FOCUS_STEPS;
+    (value + step * steps as f32).clamp(min.min(max), max.max(min))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two rows of two buttons (1 2 / 3 4), given out of order
+    fn grid() -> ([Entity; 5], Vec<(Entity, Rect)>) {
+        let mut world = World::new();
+        let id: [Entity; 5] = std::array::from_fn(|_| world.spawn_empty().id());
+        let targets = vec![
+            (id[4], Rect::new(100.0, 50.0, 180.0, 80.0)),
+            (id[1], Rect::new(0.0, 0.0, 80.0, 30.0)),
+            (id[3], Rect::new(0.0, 50.0, 80.0, 80.0)),
+            (id[2], Rect::new(100.0, 0.0, 180.0, 30.0)),
+        ];
+        (id, targets)
+    }
+
+    #[test]
+    fn test_tab_walks_in_reading_order() {
+        let (id, targets) = grid();
+        assert_eq!(move_focus(None, &targets, FocusMove::Next), Some(id[1]));
+        assert_eq!(move_focus(None, &targets, FocusMove::Previous), Some(id[4]));
+        assert_eq!(
+            move_focus(Some(id[2]), &targets, FocusMove::Next),
+            Some(id[3])
+        );
+        assert_eq!(
+            move_focus(Some(id[4]), &targets, FocusMove::Next),
+            Some(id[1])
+        );
+        assert_eq!(
+            move_focus(Some(id[1]), &targets, FocusMove::Previous),
+            Some(id[4])
+        );
+    }
+
+    #[test]
+    fn test_directional_moves() {
+        let (id, targets) = grid();
+        assert_eq!(
+            move_focus(Some(id[1]), &targets, FocusMove::Right),
+            Some(id[2])
+        );
+        assert_eq!(
+            move_focus(Some(id[1]), &targets, FocusMove::Down),
+            Some(id[3])
+        );
+        assert_eq!(
+            move_focus(Some(id[4]), &targets, FocusMove::Up),
+            Some(id[2])
+        );
+        // Nothing further left: focus stays
+        assert_eq!(
+            move_focus(Some(id[3]), &targets, FocusMove::Left),
+            Some(id[3])
+        );
+        // A focused element that went away starts over
+        assert_eq!(
+            move_focus(Some(id[0]), &targets, FocusMove::Down),
+            Some(id[1])
+        );
+        assert_eq!(move_focus(None, &[], FocusMove::Next), None);
+    }
+
+    #[test]
+    fn test_nudge_slider_stays_in_range() {
+        assert!((nudge_slider(0.5, 0.0, 1.0, 1) - 0.55).abs() < 1e-6);
+        assert!((nudge_slider(0.5, 0.0, 1.0, -2) - 0.4).abs() < 1e-6);
+        assert_eq!(nudge_slider(0.98, 0.0, 1.0, 1), 1.0);
+        assert_eq!(nudge_slider(1.0, 1.0, 15.0, -1), 1.0);
+    }
+}
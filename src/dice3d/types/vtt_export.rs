@@ -0,0 +1,285 @@
+//! Encounter export to virtual tabletops
+//!
+//! Packages the initiative tracker - who is fighting, the turn order, the
+//! round and everyone's hit points - as JSON a VTT can pick up, so a session
+//! started here can move to a VTT midway through a fight:
+//!
+//! - Improved Initiative: a saved encounter (`Combatants`,
+//!   `ActiveCombatantId`, `RoundCounter`) with a minimal stat block per
+//!   combatant
+//! - Foundry VTT: the actors (party members through the same mapping as
+//!   `share foundry`, monsters as bare NPCs) and a Combat document whose
+//!   combatants point at them
+//!
+//! Party members take their hit points and armor class from their sheets,
+//! passed in as sheet JSON by character id; monsters use the hit points given
+//! with `init goblin +2 hp7`, or 1 when none were.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use super::{sheet_json_to_foundry, Combatant, HitPoints, InitiativeTracker};
+
+/// Improved Initiative version the exported encounter claims
+const IMPROVED_INITIATIVE_VERSION: &str = "3.0.0";
+
+/// Hit points of a monster exported without any (Improved Initiative's
+/// default stat block)
+const DEFAULT_MONSTER_HIT_POINTS: i32 = 1;
+
+/// A VTT the encounter can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VttFormat {
+    FoundryVtt,
+    ImprovedInitiative,
+}
+
+impl VttFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VttFormat::FoundryVtt => "Foundry VTT",
+            VttFormat::ImprovedInitiative => "Improved Initiative",
+        }
+    }
+
+    /// Suggested name of the exported file
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            VttFormat::FoundryVtt => "fvtt-Encounter.json",
+            VttFormat::ImprovedInitiative => "improved-initiative-encounter.json",
+        }
+    }
+}
+
+/// Export the encounter; `party` holds the sheet JSON of the stored
+/// characters in it, by character id.
+pub fn export_encounter(
+    format: VttFormat,
+    tracker: &InitiativeTracker,
+    party: &HashMap<i64, Value>,
+) -> Result<Value, String> {
+    if tracker.combatants.is_empty() {
+        return Err("No combatants to export".to_string());
+    }
+    match format {
+        VttFormat::FoundryVtt => encounter_to_foundry(tracker, party),
+        VttFormat::ImprovedInitiative => Ok(encounter_to_improved_initiative(tracker, party)),
+    }
+}
+
+/// Hit points and armor class of a combatant, from the sheet for party members.
+fn vitals(combatant: &Combatant, party: &HashMap<i64, Value>) -> (HitPoints, Option<i64>) {
+    let sheet = combatant.character_id.and_then(|id| party.get(&id));
+    match sheet {
+        Some(sheet) => {
+            let number = |pointer: &str| sheet.pointer(pointer).and_then(Value::as_i64);
+            let hit_points = HitPoints {
+                current: number("/combat/hitPoints/current").unwrap_or(0) as i32,
+                maximum: number("/combat/hitPoints/maximum").unwrap_or(0) as i32,
+                temporary: number("/combat/hitPoints/temporary").unwrap_or(0) as i32,
+            };
+            (hit_points, number("/combat/armorClass"))
+        }
+        None => {
+            let hit_points = combatant.hit_points.clone().unwrap_or(HitPoints {
+                current: DEFAULT_MONSTER_HIT_POINTS,
+                maximum: DEFAULT_MONSTER_HIT_POINTS,
+                temporary: 0,
+            });
+            (hit_points, None)
+        }
+    }
+}
+
+/// Index of the active combatant, `None` before combat starts.
+fn active_index(tracker: &InitiativeTracker) -> Option<usize> {
+    tracker.current().map(|_| tracker.current_turn)
+}
+
+/// An Improved Initiative saved encounter.
+pub fn encounter_to_improved_initiative(
+    tracker: &InitiativeTracker,
+    party: &HashMap<i64, Value>,
+) -> Value {
+    let id = |index: usize| format!("dndgamerolls-{}", index);
+    let combatants: Vec<Value> = tracker
+        .combatants
+        .iter()
+        .enumerate()
+        .map(|(index, combatant)| {
+            let (hit_points, armor_class) = vitals(combatant, party);
+            let player = if combatant.character_id.is_some() {
+                "player"
+            } else {
+                ""
+            };
+            json!({
+                "Id": id(index),
+                "StatBlock": {
+                    "Id": id(index),
+                    "Name": combatant.name,
+                    "Source": "dndgamerolls",
+                    "Type": "",
+                    "HP": { "Value": hit_points.maximum, "Notes": "" },
+                    "AC": { "Value": armor_class.unwrap_or(10), "Notes": "" },
+                    "InitiativeModifier": combatant.modifier,
+                    "Player": player,
+                    "Version": IMPROVED_INITIATIVE_VERSION,
+                },
+                "MaxHP": hit_points.maximum,
+                "CurrentHP": hit_points.current,
+                "TemporaryHP": hit_points.temporary,
+                "Initiative": combatant.initiative,
+                "Alias": "",
+                "IndexLabel": null,
+                "Tags": [],
+                "Hidden": false,
+                "RevealedAC": false,
+                "InterfaceVersion": IMPROVED_INITIATIVE_VERSION,
+            })
+        })
+        .collect();
+
+    json!({
+        "Combatants": combatants,
+        "ActiveCombatantId": active_index(tracker).map(id),
+        "RoundCounter": tracker.round,
+    })
+}
+
+/// Foundry VTT actors and the Combat document that fights with them.
+pub fn encounter_to_foundry(
+    tracker: &InitiativeTracker,
+    party: &HashMap<i64, Value>,
+) -> Result<Value, String> {
+    // Foundry ids are 16 alphanumeric characters
+    let actor_id = |index: usize| format!("dndgrActor{:06}", index);
+    let combatant_id = |index: usize| format!("dndgrCmbt{:07}", index);
+
+    let mut actors = Vec::new();
+    let mut combatants = Vec::new();
+    for (index, combatant) in tracker.combatants.iter().enumerate() {
+        let (hit_points, _) = vitals(combatant, party);
+        let sheet = combatant.character_id.and_then(|id| party.get(&id));
+        let mut actor = match sheet {
+            Some(sheet) => sheet_json_to_foundry(sheet)?.json,
+            None => json!({
+                "type": "npc",
+                "system": {
+                    "attributes": {
+                        "hp": {
+                            "value": hit_points.current,
+                            "max": hit_points.maximum,
+                            "temp": hit_points.temporary,
+                        },
+                        "init": { "bonus": combatant.modifier.to_string() },
+                    },
+                },
+            }),
+        };
+        actor["_id"] = json!(actor_id(index));
+        actor["name"] = json!(combatant.name);
+        actors.push(actor);
+
+        combatants.push(json!({
+            "_id": combatant_id(index),
+            "actorId": actor_id(index),
+            "name": combatant.name,
+            "initiative": combatant.initiative,
+            "hidden": false,
+            "defeated": hit_points.current <= 0,
+        }));
+    }
+
+    Ok(json!({
+        "actors": actors,
+        "combat": {
+            "round": tracker.round,
+            "turn": active_index(tracker),
+            "started": tracker.is_active(),
+            "combatants": combatants,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encounter() -> (InitiativeTracker, HashMap<i64, Value>) {
+        let mut tracker = InitiativeTracker::default();
+        let mut elara = Combatant::new("Elara", 18);
+        elara.modifier = 3;
+        elara.character_id = Some(7);
+        tracker.add_combatant(elara);
+        tracker.add_combatant(Combatant::new("Goblin", 12));
+        tracker.set_hit_points("Goblin", Some(7));
+        tracker.add_combatant(Combatant::new("Wolf", 9));
+        tracker.start_combat();
+        tracker.advance_turn();
+
+        let sheet = json!({
+            "character": { "name": "Elara", "level": 3 },
+            "attributes": { "dexterity": 16 },
+            "combat": {
+                "armorClass": 15,
+                "initiative": 3,
+                "hitPoints": { "current": 11, "maximum": 24, "temporary": 2 },
+            },
+        });
+        (tracker, HashMap::from([(7, sheet)]))
+    }
+
+    #[test]
+    fn test_improved_initiative_export() {
+        let (tracker, party) = encounter();
+        let json = export_encounter(VttFormat::ImprovedInitiative, &tracker, &party).unwrap();
+
+        assert_eq!(json["RoundCounter"], 1);
+        assert_eq!(json["ActiveCombatantId"], "dndgamerolls-1");
+        let combatants = json["Combatants"].as_array().unwrap();
+        assert_eq!(combatants.len(), 3);
+
+        let elara = &combatants[0];
+        assert_eq!(elara["StatBlock"]["Name"], "Elara");
+        assert_eq!(elara["StatBlock"]["Player"], "player");
+        assert_eq!(elara["StatBlock"]["AC"]["Value"], 15);
+        assert_eq!(elara["CurrentHP"], 11);
+        assert_eq!(elara["MaxHP"], 24);
+        assert_eq!(elara["TemporaryHP"], 2);
+        assert_eq!(elara["Initiative"], 18);
+
+        assert_eq!(combatants[1]["CurrentHP"], 7);
+        assert_eq!(combatants[2]["MaxHP"], DEFAULT_MONSTER_HIT_POINTS);
+    }
+
+    #[test]
+    fn test_foundry_export() {
+        let (tracker, party) = encounter();
+        let json = export_encounter(VttFormat::FoundryVtt, &tracker, &party).unwrap();
+
+        let actors = json["actors"].as_array().unwrap();
+        assert_eq!(actors.len(), 3);
+        assert_eq!(actors[0]["type"], "character");
+        assert_eq!(actors[0]["system"]["attributes"]["hp"]["value"], 11);
+        assert_eq!(actors[1]["type"], "npc");
+        assert_eq!(actors[1]["system"]["attributes"]["hp"]["max"], 7);
+
+        let combat = &json["combat"];
+        assert_eq!(combat["round"], 1);
+        assert_eq!(combat["turn"], 1);
+        assert_eq!(combat["started"], true);
+        let goblin = &combat["combatants"][1];
+        assert_eq!(goblin["name"], "Goblin");
+        assert_eq!(goblin["actorId"], actors[1]["_id"]);
+        assert_eq!(goblin["_id"].as_str().unwrap().len(), 16);
+        assert_eq!(goblin["defeated"], false);
+    }
+
+    #[test]
+    fn test_empty_encounter_is_not_exported() {
+        let tracker = InitiativeTracker::default();
+        assert!(export_encounter(VttFormat::FoundryVtt, &tracker, &HashMap::new()).is_err());
+    }
+}
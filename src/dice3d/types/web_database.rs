@@ -0,0 +1,720 @@
+//! Browser (wasm32) character & settings storage.
+//!
+//! Stands in for the embedded SurrealDB database in the web build, with the
+//! same `CharacterDatabase` API. SurrealKV needs a filesystem, and the
+//! browser's IndexedDB only answers asynchronously, while the app reads the
+//! database synchronously from its systems. So each profile's datastore is
+//! kept in memory and saved to IndexedDB as a JSON snapshot after every
+//! write; the snapshots are read once with [`preload_web_datastores`] before
+//! the app starts.
+//!
+//! There is no legacy SQLite database to import in the browser, and no cloud
+//! sync. Snapshots saved by older builds are upgraded with the same
+//! migrations as the native database (see `db_migrations`) when read.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use super::character::{CharacterInfo, CharacterListEntry, CharacterSheet};
+use super::cloud_sync::SyncSummary;
+use super::db_migrations::{
+    latest_schema_version, migrate_record, DoctorReport, SCHEMA_VERSION_SETTING_KEY,
+};
+use super::encryption::{DatabaseKey, EncryptedText, EncryptionConfig};
+use super::profiles::{DatabaseProfile, ProfileRegistry, DEFAULT_PROFILE_ID};
+use super::roll_stats::RollRecord;
+
+/// Datastore key of the default profile (same name as the native folder).
+pub(crate) const DATABASE_FOLDER: &str = "characters.surrealdb";
+/// Key of the profile list and active profile.
+const PROFILES_FILE: &str = "profiles.json";
+
+/// IndexedDB database and object store holding the snapshots.
+const INDEXED_DB_NAME: &str = "DnDGameRolls";
+const INDEXED_DB_STORE: &str = "datastores";
+
+/// `setting` key holding the [`EncryptionConfig`] of an encrypted database.
+const ENCRYPTION_SETTING_KEY: &str = "encryption";
+
+/// Profile chosen for this run only (overrides the saved active profile).
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Snapshots read from IndexedDB, updated on every save (key -> JSON).
+static SNAPSHOTS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+fn snapshots() -> &'static Mutex<HashMap<String, String>> {
+    SNAPSHOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached_snapshot(key: &str) -> Option<String> {
+    snapshots().lock().ok()?.get(key).cloned()
+}
+
+/// Keep `json` as the latest snapshot of `key` and write it to IndexedDB in
+/// the background.
+fn store_snapshot(key: &str, json: String) {
+    if let Ok(mut cache) = snapshots().lock() {
+        cache.insert(key.to_string(), json.clone());
+    }
+    let key = key.to_string();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = write_indexed_db(&key, &json).await {
+            warn!("Failed to save '{}' to IndexedDB: {}", key, e);
+        }
+    });
+}
+
+/// Read every saved datastore from IndexedDB. Call (and await) once before
+/// the database is first opened; without it the app starts empty.
+pub async fn preload_web_datastores() {
+    match read_indexed_db().await {
+        Ok(saved) => {
+            if let Ok(mut cache) = snapshots().lock() {
+                cache.extend(saved);
+            }
+        }
+        Err(e) => warn!("Failed to read saved data from IndexedDB: {}", e),
+    }
+}
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
+
+/// Resolve once the IndexedDB request succeeds, with its result.
+async fn request_result(request: &IdbRequest) -> Result<JsValue, String> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+            let result = succeeded.result().unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::NULL, &result);
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move |_: web_sys::Event| {
+            let error = failed
+                .error()
+                .ok()
+                .flatten()
+                .map(|e| JsValue::from_str(&e.message()))
+                .unwrap_or(JsValue::NULL);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+async fn open_indexed_db() -> Result<IdbDatabase, String> {
+    let factory = web_sys::window()
+        .ok_or_else(|| "No browser window".to_string())?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or_else(|| "IndexedDB is not available".to_string())?;
+    let request = factory
+        .open_with_u32(INDEXED_DB_NAME, 1)
+        .map_err(js_error)?;
+
+    let upgrading = request.clone();
+    let on_upgrade = Closure::once_into_js(move |_: web_sys::Event| {
+        if let Ok(db) = upgrading.result() {
+            let db: IdbDatabase = db.unchecked_into();
+            if !db.object_store_names().contains(INDEXED_DB_STORE) {
+                let _ = db.create_object_store(INDEXED_DB_STORE);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    Ok(request_result(&request).await?.unchecked_into())
+}
+
+fn object_store(db: &IdbDatabase, mode: IdbTransactionMode) -> Result<IdbObjectStore, String> {
+    db.transaction_with_str_and_mode(INDEXED_DB_STORE, mode)
+        .and_then(|tx| tx.object_store(INDEXED_DB_STORE))
+        .map_err(js_error)
+}
+
+async fn read_indexed_db() -> Result<HashMap<String, String>, String> {
+    let db = open_indexed_db().await?;
+    let store = object_store(&db, IdbTransactionMode::Readonly)?;
+    // Both lists come back in key order.
+    let keys_request = store.get_all_keys().map_err(js_error)?;
+    let values_request = store.get_all().map_err(js_error)?;
+    let keys: js_sys::Array = request_result(&keys_request).await?.unchecked_into();
+    let values: js_sys::Array = request_result(&values_request).await?.unchecked_into();
+
+    Ok(keys
+        .iter()
+        .zip(values.iter())
+        .filter_map(|(key, value)| Some((key.as_string()?, value.as_string()?)))
+        .collect())
+}
+
+async fn write_indexed_db(key: &str, json: &str) -> Result<(), String> {
+    let db = open_indexed_db().await?;
+    let store = object_store(&db, IdbTransactionMode::Readwrite)?;
+    let request = store
+        .put_with_key(&JsValue::from_str(json), &JsValue::from_str(key))
+        .map_err(js_error)?;
+    request_result(&request).await.map(|_| ())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CharacterDocument {
+    /// Stable internal ID (never changes).
+    sid: i64,
+    /// Legacy SQLite character ID, if this record was converted.
+    #[serde(default)]
+    legacy_sqlite_id: Option<i64>,
+    /// Convenience fields for listing.
+    name: String,
+    class: String,
+    race: String,
+    level: i32,
+    /// Full character sheet (only the listing fields when `sealed_sheet` is set).
+    sheet: CharacterSheet,
+    /// Encrypted JSON of the full sheet when the database is encrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_sheet: Option<EncryptedText>,
+}
+
+/// Everything one profile stores, saved as a single snapshot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WebDatastore {
+    #[serde(default)]
+    characters: BTreeMap<i64, CharacterDocument>,
+    /// Settings as JSON strings, like the `value` field of the native table.
+    #[serde(default)]
+    settings: BTreeMap<String, String>,
+    #[serde(default)]
+    command_history: Vec<String>,
+    #[serde(default)]
+    rolls: Vec<RollRecord>,
+}
+
+/// Run the migrations newer than a snapshot's schema version over its
+/// records; returns how many records were upgraded.
+fn migrate_snapshot(snapshot: &mut JsonValue) -> usize {
+    let version = snapshot
+        .pointer(&format!("/settings/{}", SCHEMA_VERSION_SETTING_KEY))
+        .and_then(JsonValue::as_str)
+        .and_then(|version| version.parse::<u32>().ok())
+        .unwrap_or(0);
+    let latest = latest_schema_version();
+    if version >= latest {
+        return 0;
+    }
+
+    let mut upgraded = 0;
+    if let Some(characters) = snapshot
+        .get_mut("characters")
+        .and_then(JsonValue::as_object_mut)
+    {
+        for record in characters.values_mut() {
+            upgraded += usize::from(migrate_record("character", record, version));
+        }
+    }
+    if let Some(rolls) = snapshot.get_mut("rolls").and_then(JsonValue::as_array_mut) {
+        for record in rolls.iter_mut() {
+            upgraded += usize::from(migrate_record("roll", record, version));
+        }
+    }
+    if let Some(settings) = snapshot
+        .get_mut("settings")
+        .and_then(JsonValue::as_object_mut)
+    {
+        // Settings are kept as the `value` string of a native setting record
+        for value in settings.values_mut() {
+            let mut record = serde_json::json!({ "value": value.take() });
+            upgraded += usize::from(migrate_record("setting", &mut record, version));
+            *value = record["value"].take();
+        }
+        settings.insert(
+            SCHEMA_VERSION_SETTING_KEY.to_string(),
+            JsonValue::String(latest.to_string()),
+        );
+    }
+    upgraded
+}
+
+/// Resource for managing the character database.
+#[cfg_attr(feature = "gui", derive(bevy::prelude::Resource))]
+pub struct CharacterDatabase {
+    data: Mutex<WebDatastore>,
+    /// IndexedDB key of the datastore.
+    pub db_path: PathBuf,
+    /// Id of the profile this database belongs to.
+    pub profile_id: String,
+    /// Present when character sheets are encrypted at rest.
+    encryption: Mutex<Option<EncryptionConfig>>,
+    /// Key for the encrypted sheets once unlocked (memory only).
+    key: Mutex<Option<DatabaseKey>>,
+    /// Whether writes are saved to IndexedDB (not for `open_in_memory`).
+    persist: bool,
+}
+
+impl CharacterDatabase {
+    /// Load the profile list (just the default profile if none was saved yet).
+    pub fn load_profiles() -> ProfileRegistry {
+        cached_snapshot(PROFILES_FILE)
+            .and_then(|json| serde_json::from_str::<ProfileRegistry>(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the profile list.
+    pub fn save_profiles(registry: &ProfileRegistry) -> Result<(), String> {
+        let json = serde_json::to_string(registry)
+            .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+        store_snapshot(PROFILES_FILE, json);
+        Ok(())
+    }
+
+    /// Use a profile for the rest of this run without changing the saved
+    /// active profile. Must be called before the database is first opened.
+    pub fn use_profile_for_this_run(profile_id: &str) {
+        let _ = PROFILE_OVERRIDE.set(profile_id.to_string());
+    }
+
+    /// The profile `open()` will use.
+    pub fn current_profile() -> DatabaseProfile {
+        let registry = Self::load_profiles();
+        PROFILE_OVERRIDE
+            .get()
+            .and_then(|id| registry.get(id))
+            .unwrap_or_else(|| registry.active_profile())
+            .clone()
+    }
+
+    /// Open or create the database of the current profile.
+    pub fn open() -> Result<Self, String> {
+        Self::open_profile(&Self::current_profile())
+    }
+
+    /// Open or create the database of a specific profile.
+    pub fn open_profile(profile: &DatabaseProfile) -> Result<Self, String> {
+        let key = profile.datastore_folder();
+        let data = match cached_snapshot(&key) {
+            Some(json) => serde_json::from_str::<JsonValue>(&json)
+                .and_then(|mut snapshot| {
+                    migrate_snapshot(&mut snapshot);
+                    serde_json::from_value(snapshot)
+                })
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Saved datastore '{}' is unreadable ({}); starting empty",
+                        key, e
+                    );
+                    WebDatastore::default()
+                }),
+            None => WebDatastore::default(),
+        };
+
+        let this = Self {
+            data: Mutex::new(data),
+            db_path: PathBuf::from(key),
+            profile_id: profile.id.clone(),
+            encryption: Mutex::new(None),
+            key: Mutex::new(None),
+            persist: true,
+        };
+        this.load_encryption_config()?;
+
+        Ok(this)
+    }
+
+    /// There is no legacy SQLite database in the browser.
+    pub fn legacy_sqlite_path() -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns true if a character record exists with the given id.
+    pub fn character_id_exists(&self, id: i64) -> Result<bool, String> {
+        self.read(|data| Ok(data.characters.contains_key(&id)))
+    }
+
+    /// Returns true if any character record has `legacy_sqlite_id == legacy_id`.
+    pub fn legacy_sqlite_id_exists(&self, legacy_id: i64) -> Result<bool, String> {
+        self.read(|data| {
+            Ok(data
+                .characters
+                .values()
+                .any(|doc| doc.legacy_sqlite_id == Some(legacy_id)))
+        })
+    }
+
+    /// Open a database under a specific key (for testing).
+    pub fn open_at(path: PathBuf) -> Result<Self, String> {
+        let profile = DatabaseProfile {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: String::new(),
+        };
+        let mut this = Self::open_profile(&profile)?;
+        this.db_path = path;
+        Ok(this)
+    }
+
+    /// Database that is never saved, for testing.
+    pub fn open_in_memory() -> Result<Self, String> {
+        Ok(Self {
+            data: Mutex::new(WebDatastore::default()),
+            db_path: PathBuf::new(),
+            profile_id: DEFAULT_PROFILE_ID.to_string(),
+            encryption: Mutex::new(None),
+            key: Mutex::new(None),
+            persist: false,
+        })
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&WebDatastore) -> Result<T, String>) -> Result<T, String> {
+        let data = self.data.lock().map_err(|e| e.to_string())?;
+        f(&data)
+    }
+
+    /// Change the datastore, then save its snapshot.
+    fn write<T>(
+        &self,
+        f: impl FnOnce(&mut WebDatastore) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut data = self.data.lock().map_err(|e| e.to_string())?;
+        let result = f(&mut data)?;
+        if self.persist {
+            let json = serde_json::to_string(&*data)
+                .map_err(|e| format!("Failed to serialize datastore: {}", e))?;
+            store_snapshot(&self.db_path.to_string_lossy(), json);
+        }
+        Ok(result)
+    }
+
+    fn to_doc(
+        &self,
+        sid: i64,
+        sheet: &CharacterSheet,
+        legacy_sqlite_id: Option<i64>,
+    ) -> Result<CharacterDocument, String> {
+        let (stored_sheet, sealed_sheet) = if self.encryption_enabled() {
+            let key = self.unlocked_key()?;
+            let json = serde_json::to_string(sheet)
+                .map_err(|e| format!("Failed to serialize character: {}", e))?;
+            // Only the listing fields stay readable.
+            let listing = CharacterSheet {
+                character: CharacterInfo {
+                    name: sheet.character.name.clone(),
+                    class: sheet.character.class.clone(),
+                    race: sheet.character.race.clone(),
+                    level: sheet.character.level,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            (listing, Some(key.encrypt(&json)?))
+        } else {
+            (sheet.clone(), None)
+        };
+
+        Ok(CharacterDocument {
+            sid,
+            legacy_sqlite_id,
+            name: sheet.character.name.clone(),
+            class: sheet.character.class.clone(),
+            race: sheet.character.race.clone(),
+            level: sheet.character.level,
+            sheet: stored_sheet,
+            sealed_sheet,
+        })
+    }
+
+    /// The full sheet stored in a document, decrypting it if needed.
+    fn sheet_from_doc(&self, doc: CharacterDocument) -> Result<CharacterSheet, String> {
+        match doc.sealed_sheet {
+            Some(sealed) => {
+                let json = self.unlocked_key()?.decrypt(&sealed)?;
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Failed to decode encrypted character: {}", e))
+            }
+            None => Ok(doc.sheet),
+        }
+    }
+
+    fn write_doc(&self, doc: CharacterDocument) -> Result<(), String> {
+        self.write(|data| {
+            data.characters.insert(doc.sid, doc);
+            Ok(())
+        })
+    }
+
+    /// Save a new character and return its ID.
+    pub fn create_character(&self, sheet: &CharacterSheet) -> Result<i64, String> {
+        let sid = self.read(|data| {
+            Ok(data
+                .characters
+                .keys()
+                .next_back()
+                .map(|sid| sid + 1)
+                .unwrap_or(1))
+        })?;
+        self.write_doc(self.to_doc(sid, sheet, None)?)?;
+        Ok(sid)
+    }
+
+    /// Upsert a character converted from legacy SQLite.
+    pub fn upsert_legacy_character(
+        &self,
+        legacy_id: i64,
+        sheet: &CharacterSheet,
+    ) -> Result<(), String> {
+        let doc = self.to_doc(legacy_id, sheet, Some(legacy_id))?;
+        self.write_doc(doc)
+    }
+
+    /// Update an existing character by ID.
+    pub fn update_character(&self, id: i64, sheet: &CharacterSheet) -> Result<(), String> {
+        // Ensure it exists, to preserve the old behavior.
+        self.load_character(id)?;
+        self.write_doc(self.to_doc(id, sheet, None)?)
+    }
+
+    /// Save character - creates if id is None, updates if id exists.
+    pub fn save_character(&self, id: Option<i64>, sheet: &CharacterSheet) -> Result<i64, String> {
+        match id {
+            Some(existing_id) => {
+                self.update_character(existing_id, sheet)?;
+                Ok(existing_id)
+            }
+            None => self.create_character(sheet),
+        }
+    }
+
+    /// Load a character by ID.
+    pub fn load_character(&self, id: i64) -> Result<CharacterSheet, String> {
+        let doc = self.read(|data| Ok(data.characters.get(&id).cloned()))?;
+        let Some(doc) = doc else {
+            return Err(format!("Character with id {} not found", id));
+        };
+        self.sheet_from_doc(doc)
+    }
+
+    /// Delete a character by ID.
+    pub fn delete_character(&self, id: i64) -> Result<(), String> {
+        self.write(|data| {
+            data.characters.remove(&id);
+            Ok(())
+        })
+    }
+
+    /// List all characters (for the character selection UI).
+    pub fn list_characters(&self) -> Result<Vec<CharacterListEntry>, String> {
+        self.read(|data| {
+            let mut list: Vec<CharacterListEntry> = data
+                .characters
+                .values()
+                .map(|doc| CharacterListEntry {
+                    id: doc.sid,
+                    name: doc.name.clone(),
+                    class: doc.class.clone(),
+                    level: doc.level,
+                })
+                .collect();
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(list)
+        })
+    }
+
+    /// Get character count.
+    pub fn character_count(&self) -> Result<i64, String> {
+        self.read(|data| Ok(data.characters.len() as i64))
+    }
+
+    /// Check if a character with the given name exists (excluding a specific ID).
+    pub fn name_exists(&self, name: &str, exclude_id: Option<i64>) -> Result<bool, String> {
+        let list = self.list_characters()?;
+        Ok(list
+            .iter()
+            .any(|c| c.name == name && exclude_id.map(|id| id != c.id).unwrap_or(true)))
+    }
+
+    /// Load a setting by key.
+    pub fn get_setting<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        let Some(json) = self.read(|data| Ok(data.settings.get(key).cloned()))? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Failed to decode setting '{}' from JSON string: {}", key, e))
+    }
+
+    /// Save a setting by key.
+    pub fn set_setting<T: Serialize + 'static>(&self, key: &str, value: T) -> Result<(), String> {
+        let json = serde_json::to_string(&value).map_err(|e| {
+            format!(
+                "Failed to serialize setting '{}' to JSON string: {}",
+                key, e
+            )
+        })?;
+        self.write(|data| {
+            data.settings.insert(key.to_string(), json);
+            Ok(())
+        })
+    }
+
+    fn load_encryption_config(&self) -> Result<(), String> {
+        let config = self.get_setting::<EncryptionConfig>(ENCRYPTION_SETTING_KEY)?;
+        *self.encryption.lock().map_err(|e| e.to_string())? = config;
+        Ok(())
+    }
+
+    fn unlocked_key(&self) -> Result<DatabaseKey, String> {
+        self.key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "Database is locked; enter the passphrase first".to_string())
+    }
+
+    /// Rewrite every character with the current encryption state.
+    fn rewrite_all_characters(
+        &self,
+        sheets: Vec<(CharacterDocument, CharacterSheet)>,
+    ) -> Result<(), String> {
+        for (doc, sheet) in sheets {
+            let rewritten = self.to_doc(doc.sid, &sheet, doc.legacy_sqlite_id)?;
+            self.write_doc(rewritten)?;
+        }
+        Ok(())
+    }
+
+    /// Decrypt every character (needs the key when encrypted).
+    fn load_all_sheets(&self) -> Result<Vec<(CharacterDocument, CharacterSheet)>, String> {
+        self.read(|data| Ok(data.characters.values().cloned().collect::<Vec<_>>()))?
+            .into_iter()
+            .map(|doc| {
+                let sheet = self.sheet_from_doc(doc.clone())?;
+                Ok((doc, sheet))
+            })
+            .collect()
+    }
+
+    /// Whether character sheets are encrypted at rest.
+    pub fn encryption_enabled(&self) -> bool {
+        self.encryption
+            .lock()
+            .map(|config| config.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Encrypted and no passphrase entered yet.
+    pub fn is_locked(&self) -> bool {
+        self.encryption_enabled() && self.key.lock().map(|k| k.is_none()).unwrap_or(true)
+    }
+
+    /// Enter the passphrase of an encrypted database.
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        let config = self
+            .encryption
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "Database is not encrypted".to_string())?;
+        let key = config.unlock(passphrase)?;
+        *self.key.lock().map_err(|e| e.to_string())? = Some(key);
+        Ok(())
+    }
+
+    /// Forget the key until the passphrase is entered again.
+    pub fn lock(&self) {
+        if let Ok(mut key) = self.key.lock() {
+            *key = None;
+        }
+    }
+
+    /// Encrypt all character sheets with a new passphrase.
+    pub fn enable_encryption(&self, passphrase: &str) -> Result<(), String> {
+        if self.encryption_enabled() {
+            return Err("Database is already encrypted".to_string());
+        }
+
+        let sheets = self.load_all_sheets()?;
+        let (config, key) = EncryptionConfig::new(passphrase)?;
+
+        // Save the config first so sheets sealed below can always be opened.
+        self.set_setting(ENCRYPTION_SETTING_KEY, config.clone())?;
+        *self.encryption.lock().map_err(|e| e.to_string())? = Some(config);
+        *self.key.lock().map_err(|e| e.to_string())? = Some(key);
+
+        self.rewrite_all_characters(sheets)
+    }
+
+    /// Decrypt all character sheets and turn encryption off (must be unlocked).
+    pub fn disable_encryption(&self) -> Result<(), String> {
+        if !self.encryption_enabled() {
+            return Ok(());
+        }
+
+        let sheets = self.load_all_sheets()?;
+
+        *self.encryption.lock().map_err(|e| e.to_string())? = None;
+        self.rewrite_all_characters(sheets)?;
+        self.lock();
+
+        self.write(|data| {
+            data.settings.remove(ENCRYPTION_SETTING_KEY);
+            Ok(())
+        })
+    }
+
+    pub fn load_command_history(&self) -> Result<Vec<String>, String> {
+        self.read(|data| Ok(data.command_history.clone()))
+    }
+
+    pub fn save_command_history(&self, commands: &[String]) -> Result<(), String> {
+        self.write(|data| {
+            data.command_history = commands.to_vec();
+            Ok(())
+        })
+    }
+
+    /// Save a resolved roll for the roll statistics.
+    pub fn record_roll(&self, record: &RollRecord) -> Result<(), String> {
+        self.write(|data| {
+            data.rolls.push(record.clone());
+            Ok(())
+        })
+    }
+
+    /// Every saved roll, oldest first.
+    pub fn load_rolls(&self) -> Result<Vec<RollRecord>, String> {
+        self.read(|data| {
+            let mut rolls = data.rolls.clone();
+            rolls.sort_by_key(|r| r.rolled_at);
+            Ok(rolls)
+        })
+    }
+
+    /// Cloud sync needs a WebSocket/HTTP SurrealDB client, which isn't part
+    /// of the browser build.
+    pub fn sync_characters(&self) -> Result<SyncSummary, String> {
+        Err("Cloud sync isn't available in the browser build".to_string())
+    }
+
+    /// Schema version the stored records were last upgraded to.
+    pub fn schema_version(&self) -> Result<u32, String> {
+        Ok(self
+            .get_setting::<u32>(SCHEMA_VERSION_SETTING_KEY)?
+            .unwrap_or(0))
+    }
+
+    /// A snapshot is read as a whole, so there are no single malformed
+    /// records for `db doctor` to repair in the browser.
+    pub fn doctor(&self, _repair: bool) -> Result<DoctorReport, String> {
+        Err("db doctor isn't available in the browser build".to_string())
+    }
+}
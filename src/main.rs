@@ -1,178 +1,55 @@
 // Hide console window on Windows for release builds (GUI app).
 // In debug builds, keep the console so panics/backtraces are visible.
 // CLI mode will re-attach to parent console if available.
-#![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
+#![cfg_attr(
+    all(windows, not(debug_assertions), feature = "gui"),
+    windows_subsystem = "windows"
+)]
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
 use bevy::winit::WinitWindows;
+#[cfg(feature = "hanabi")]
 use bevy_hanabi::prelude::HanabiPlugin;
+#[cfg(feature = "gui")]
 use bevy_material_ui::prelude::*;
+#[cfg(feature = "gui")]
 use bevy_rapier3d::prelude::*;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use rand::Rng;
+use std::io::{IsTerminal, Write};
+use std::sync::OnceLock;
 
 use dndgamerolls::dice3d::{
-    animate_container_shake,
-    apply_crystal_material_to_container_models,
-    apply_dice_scale_settings_to_existing_dice,
-    apply_editing_dice_scales_to_existing_dice_while_open,
-    apply_initial_settings,
-    apply_initial_shake_config,
-    apply_spawn_points_to_dice_when_ready,
-    autosave_and_apply_shake_config,
-    cache_dice_box_lid_animation_player,
-    center_container_models_in_view,
-    check_dice_settled,
-    collect_dice_spawn_points_from_gltf,
-    drag_shake_curve_bezier_handle,
-    drag_shake_curve_point,
-    ensure_buttons_have_interaction,
-    ensure_dice_box_lid_animation_assets,
-    // Legacy SQLite -> SurrealDB conversion (character screen)
-    finalize_sqlite_conversion_if_done,
-    fix_dice_scale_slider_thumb_hitbox,
-    handle_character_list_clicks,
-    handle_character_sheet_die_type_select_change,
-    handle_character_sheet_settings_button_click,
-    handle_character_sheet_settings_cancel_click,
-    handle_character_sheet_settings_save_click,
-    handle_color_slider_changes,
-    handle_color_text_input,
-    handle_command_history_item_clicks,
-    handle_command_input,
-    handle_default_roll_uses_shake_switch_change,
-    handle_delete_click,
-    handle_dice_box_rotate_click,
-    handle_dice_box_shake_box_click,
-    handle_dice_box_toggle_container_click,
-    handle_dice_fx_param_slider_changes,
-    handle_dice_roll_fx_mapping_select_change,
-    handle_dice_scale_slider_changes,
-    handle_expertise_toggle,
-    handle_group_add_click,
-    handle_group_edit_toggle,
-    handle_input,
-    handle_label_click,
-    handle_new_character_click,
-    handle_new_entry_cancel,
-    handle_new_entry_confirm,
-    handle_new_entry_input,
-    handle_quick_roll_clicks,
-    handle_quick_roll_die_type_select_change,
-    handle_roll_all_stats_click,
-    handle_roll_attribute_click,
-    handle_roll_skill_click,
-    handle_save_click,
-    handle_scroll_input,
-    handle_settings_button_click,
-    handle_settings_cancel_click,
-    handle_settings_ok_click,
-    handle_settings_reset_layout_click,
-    handle_shake_curve_bezier_handle_press,
-    handle_shake_curve_chip_clicks,
-    handle_shake_curve_graph_click_to_add_point,
-    handle_shake_curve_point_press,
-    handle_shake_duration_text_input,
-    handle_shake_slider_changes,
-    // Character sheet tab systems
-    handle_sheet_tab_clicks,
-    handle_slider_group_drag,
-    handle_sqlite_conversion_no_click,
-    handle_sqlite_conversion_ok_click,
-    handle_sqlite_conversion_yes_click,
-    handle_stat_field_click,
-    handle_strength_slider_changes,
-    handle_tab_clicks,
-    handle_text_input,
-    handle_theme_seed_select_change,
-    handle_zoom_slider_changes,
-    init_character_manager,
-    init_collision_sounds,
-    init_contributors,
-    init_dice_scale_preview_render_target,
-    init_settings_ui_images,
-    load_icons,
-    load_settings_state_from_db,
-    manage_character_sheet_settings_modal,
-    manage_dice_scale_preview_scene,
-    manage_settings_modal,
-    open_lid_on_roll_completed,
-    persist_settings_to_db,
-    play_dice_container_collision_sfx,
-    process_avatar_loads,
-    process_pending_roll_with_lid,
-    rebuild_character_list_on_change,
-    rebuild_character_panel_on_change,
-    rebuild_command_history_panel,
-    rebuild_quick_roll_panel,
-    record_character_screen_roll_on_settle,
-    refresh_character_display,
-    refresh_scrollbar_colors_on_theme_change,
-    request_avatars,
-    rotate_camera,
-    run_sqlite_conversion_step,
-    setup,
-    setup_character_screen,
-    setup_contributors_screen,
-    setup_dnd_info_screen,
-    setup_tab_bar,
-    spawn_colliders_from_gltf_guides,
-    start_sqlite_conversion_if_needed,
-    sync_character_screen_roll_result_texts,
-    sync_dice_container_mode_text,
-    sync_dice_container_toggle_icon,
-    sync_dice_scale_preview_dice,
-    sync_shake_curve_chip_ui,
-    sync_shake_curve_graph_ui,
-    tint_recent_theme_dropdown_items,
-    update_avatar_images,
-    update_character_list_modified_indicator,
-    update_color_ui,
-    update_dice_box_highlight,
-    update_dice_fx_param_ui,
-    update_dice_scale_ui,
-    update_editing_display,
-    update_new_entry_input_display,
-    update_results_display,
-    update_save_button_appearance,
-    update_sheet_tab_styles,
-    update_sheet_tab_visibility,
-    update_sqlite_conversion_dialog_ui,
-    update_tab_styles,
-    update_tab_visibility,
-    update_throw_arrow,
-    update_throw_from_mouse,
-    update_ui_pointer_capture,
-    AddingEntryState,
-    AvatarLoader,
-    CharacterData,
-    CharacterScreenRollBridge,
-    CommandHistory,
-    CommandInput,
-    ContainerShakeAnimation,
-    ContainerShakeConfig,
-    Dice3dEmbeddedAssetsPlugin,
-    DiceBoxHighlightMaterial,
-    DiceBoxLidAnimationController,
-    DiceConfig,
-    DiceContainerStyle,
-    DiceFxPlugin,
-    DiceResults,
-    DiceSpawnPoints,
-    DiceSpawnPointsApplied,
-    DiceType,
-    GroupEditState,
-    RollState,
-    SettingsState,
-    ShakeState,
-    ThrowControlState,
-    UiState,
-    ZoomState,
+    ability_abbreviation, carrying_capacity, coin_side, find_skill, format_day, group_check_passes,
+    inventory_value, latest_schema_version, normalize_ability_name, percentile_faces,
+    rank_group_rolls, redact_sheet, spend_ammunition, weapon_damage, write_character_file,
+    AmmunitionUse, AttackMode, CharacterData, CharacterFile, CloudSyncConfig, CoinAction,
+    ConditionEffect, ContestOutcome, D20Test, DamageRider, DamageRoll, DamageTypeRegistry,
+    DcResult, DefenseKind, DiceConfig, DiceExpr, DiceMacro, DiceMacros, DiceType, GroupCheck,
+    HoardTier, MacroStep, Monster, MonsterAction, MonsterCompendium, Parties, RecordFix,
+    ResultOrder, RollRecord, RollStats, Ruleset, TreasureHoard, TreasureLedger, Viewer,
+    WeaponMastery, CHARACTER_FILE_EXTENSION, CLOUD_SYNC_SETTING_KEY, DAMAGE_TYPES_SETTING_KEY,
+    DEFAULT_EXPLOSION_CAP, DICE_MACROS_DB_KEY, EXPECTED_D20_AVERAGE, PARTIES_SETTING_KEY,
+    RULESET_SETTING_KEY, SRD_ATTRIBUTION, TREASURE_LEDGER_SETTING_KEY,
+};
+#[cfg(feature = "gui")]
+use dndgamerolls::dice3d::{
+    is_character_file, CharacterSheetPlugin, CompendiumPlugin, Dice3dEmbeddedAssetsPlugin,
+    DiceRollerPlugin, IntegrationsPlugin, PendingCharacterFileOpen, RollRng, SettingsPlugin,
+    StatsPlugin, APP_ID,
 };
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+use dndgamerolls::dice3d::{TableClient, TableFeed, TableServer};
 
 use dndgamerolls::dice3d::types::database::CharacterDatabase;
-use dndgamerolls::dice3d::types::ui::UiPointerCapture;
+use dndgamerolls_core::cli::{
+    colored_damage_type, condition_edge, configure_output, pick_slot, print_condition_effect,
+    quiet, report_automatic_failure, roll_attack, roll_attack_sequence, roll_d20_test, roll_damage,
+    roll_engine, seed_rolls, CliRng, PlannedAttack, RiderOffer,
+};
+use dndgamerolls_core::say;
 
 /// DnD Game Rolls - CLI and 3D Visualization
 #[derive(Parser)]
@@ -190,17 +67,27 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Select a character by name from the local database (SurrealDB)
+    /// Select a character by name from the local database (SurrealDB);
+    /// repeat it to roll a check for several characters and compare them
     #[arg(long)]
-    character: Option<String>,
+    character: Vec<String>,
+
+    /// Roll the check for every member of a saved party (`all` for every
+    /// character) and compare the results
+    #[arg(long, value_name = "NAME")]
+    party: Option<String>,
 
     /// Select a character by id from the local database (SurrealDB)
     #[arg(long)]
     character_id: Option<i64>,
 
-    /// Dice to roll (e.g., "2d6", "1d20", "d8"). Can specify multiple.
+    /// Dice to roll (e.g., "2d6", "1d20", "2d6+1d8+3", "(1d6+2)*2", "1d6!"). Can specify multiple.
     #[arg(short, long, value_parser = parse_dice_arg)]
-    dice: Option<Vec<(usize, DiceType)>>,
+    dice: Option<Vec<DiceExpr>>,
+
+    /// Most extra rolls one exploding die ("1d6!") may add
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_EXPLOSION_CAP)]
+    explode_cap: u32,
 
     /// Check to apply modifier for (skill, ability, or save name)
     #[arg(long)]
@@ -217,6 +104,63 @@ struct Cli {
     /// Roll with disadvantage (roll twice, take lower)
     #[arg(short = 'D', long)]
     disadvantage: bool,
+
+    /// Compare a check with this DC and print SUCCESS or FAILURE and the
+    /// margin
+    #[arg(long, value_name = "DC")]
+    dc: Option<i32>,
+
+    /// Contest the check: another character rolls it too and the higher
+    /// total wins
+    #[arg(long, value_name = "CHARACTER", conflicts_with_all = ["dc", "party"])]
+    vs: Option<String>,
+
+    /// Print only the total of each roll, for scripts and pipelines
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print without colors (also when NO_COLOR is set)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// How the `Dice:` line lists several dice: spawn (as rolled), settle
+    /// (as rolled too, there is no dice box here), descending or grouped
+    /// (by die type)
+    #[arg(long, global = true, value_name = "ORDER", value_parser = parse_result_order, default_value = "spawn")]
+    order: ResultOrder,
+
+    /// Database profile to use for this run (created if it doesn't exist)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Open a character file (.dndchar) in the 3D app to view it read-only
+    /// or import it
+    #[arg(long, value_name = "FILE")]
+    open: Option<std::path::PathBuf>,
+
+    /// Broadcast every roll of the 3D app as JSON over WebSocket, for other
+    /// players or a stream overlay
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = "0.0.0.0:7878")]
+    serve: Option<String>,
+
+    /// Only let subscribers in that connect with `?token=<TOKEN>`
+    #[arg(long, value_name = "TOKEN", requires = "serve")]
+    room_token: Option<String>,
+
+    /// Show the rolls of another player's `--serve` table in a feed panel
+    /// (ws://HOST:PORT/?token=TOKEN)
+    #[arg(long, value_name = "URL")]
+    join: Option<String>,
+
+    /// Also throw the joined table's rolls in the dice box, in the
+    /// roller's dice color
+    #[arg(long, requires = "join")]
+    replay_throws: bool,
+
+    /// Seed the rolls (and the 3D app's throws), so the same commands land
+    /// the same dice every run (for bug reports, demos and tests)
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
 }
 
 #[derive(Subcommand)]
@@ -252,6 +196,10 @@ enum Commands {
     Skill {
         /// Skill name (e.g., stealth, perception, acrobatics)
         name: String,
+
+        /// Roll with a non-default ability (e.g., intimidation --with str)
+        #[arg(long = "with", value_name = "ABILITY")]
+        with: Option<String>,
     },
 
     /// Roll a saving throw
@@ -260,46 +208,299 @@ enum Commands {
         ability: String,
     },
 
-    /// Roll an attack
+    /// Roll an attack; ammunition weapons spend a piece of ammunition
     Attack {
         /// Weapon name
         weapon: String,
+
+        /// Wield a versatile weapon with two hands
+        #[arg(long, conflicts_with = "thrown")]
+        two_handed: bool,
+
+        /// Throw the weapon
+        #[arg(long)]
+        thrown: bool,
+
+        /// Make every attack of the Attack action (one plus Extra Attack)
+        #[arg(long)]
+        all: bool,
+
+        /// Follow up with an off-hand attack with this weapon (two-weapon
+        /// fighting)
+        #[arg(long, value_name = "WEAPON", conflicts_with = "two_handed")]
+        off_hand: Option<String>,
+
+        /// Add a damage rider to the hits it fits without asking ("Sneak
+        /// Attack", "Divine Smite", "Hunter's Mark", "Hex: 1d6 necrotic")
+        #[arg(long = "rider", value_name = "RIDER")]
+        riders: Vec<String>,
+
+        /// Don't offer the character's damage riders on hits
+        #[arg(long, conflicts_with = "riders")]
+        no_riders: bool,
+
+        /// Spell slot level Divine Smite spends (asked at a terminal; the
+        /// lowest left otherwise)
+        #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(1..=9))]
+        slot: Option<u32>,
+
+        /// Saving throw modifier of the target, for the saves weapon mastery
+        /// forces (Topple, 2024 rules)
+        #[arg(
+            long,
+            value_name = "MOD",
+            default_value_t = 0,
+            allow_hyphen_values = true
+        )]
+        target_save: i32,
+
+        /// Leave out the weapon's mastery property (2024 rules)
+        #[arg(long)]
+        no_mastery: bool,
+    },
+
+    /// Cast a known spell, spending a spell slot, and roll its damage
+    Cast {
+        /// Spell name
+        spell: String,
+
+        /// Spell slot level to cast it with, higher to upcast it (asked at a
+        /// terminal; the lowest left otherwise)
+        #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(1..=9))]
+        slot: Option<u32>,
+    },
+
+    /// Roll damage for a weapon, or a damage string like "1d8+3 slashing"
+    Damage {
+        /// Weapon name or damage string
+        weapon: String,
+
+        /// Critical hit: roll the damage dice twice
+        #[arg(long)]
+        crit: bool,
+
+        /// Wield a versatile weapon with two hands
+        #[arg(long)]
+        two_handed: bool,
     },
 
     /// Display character stats
     Stats,
+
+    /// Show the character's coins, or add, spend or exchange some
+    /// ("coins add 15gp", "coins spend 3gp 5sp", "coins convert 100cp --to gp")
+    Coins {
+        /// What to do with the coins (omit to show them)
+        #[arg(value_enum, requires = "amount")]
+        action: Option<CoinAction>,
+
+        /// Coins, e.g. 15gp or 3 gp 5 sp
+        amount: Vec<String>,
+
+        /// Coin to exchange for (with convert)
+        #[arg(long, value_name = "COIN", required_if_eq("action", "convert"))]
+        to: Option<String>,
+    },
+
+    /// List the standard damage types and the profile's custom ones
+    DamageTypes,
+
+    /// Summarize the rolls saved by the 3D app (use --character for one character)
+    StatsReport,
+
+    /// List the saved parties, save one ("party scouts Vex Keyleth") or
+    /// delete one ("party scouts --delete")
+    Party {
+        /// Party name (omit to list the parties)
+        name: Option<String>,
+
+        /// Characters in the party
+        #[arg(conflicts_with = "delete")]
+        members: Vec<String>,
+
+        /// Delete the party
+        #[arg(long, requires = "name")]
+        delete: bool,
+    },
+
+    /// Play a saved roll macro ("macro sneak_attack"), define one
+    /// ("macro 'sneak_attack = 1d20+7 adv; 3d6+4d6'") or list them (omit
+    /// the name). With --character, the character's own macros are used
+    /// and a macro defined is theirs alone
+    Macro {
+        /// Macro name, or a definition `<name> = <roll>; <roll>`
+        definition: Vec<String>,
+
+        /// Delete the macro
+        #[arg(long, requires = "definition")]
+        delete: bool,
+    },
+
+    /// Show the party's travel line-up and roll group Stealth
+    Travel {
+        /// Monsters' passive Perception to roll group Stealth against
+        #[arg(long = "vs", value_name = "PASSIVE_PERCEPTION")]
+        vs: Option<i32>,
+    },
+
+    /// Roll a DMG treasure hoard for a monster's challenge rating into the
+    /// party's treasure ledger (omit the CR to show the ledger)
+    Hoard {
+        /// Challenge rating of the monster guarding the hoard (e.g. 7 or 1/2)
+        #[arg(value_parser = parse_hoard_cr)]
+        cr: Option<HoardTier>,
+
+        /// Empty the treasure ledger
+        #[arg(long, conflicts_with = "cr")]
+        clear: bool,
+    },
+
+    /// Look up a stat block in the SRD compendium
+    Lookup {
+        #[command(subcommand)]
+        target: LookupTarget,
+    },
+
+    /// List database profiles (each has its own characters, settings and history)
+    Profiles,
+
+    /// Sync the profile's characters with a SurrealDB server, so they can be
+    /// used on another machine (set the server up once with --endpoint; the
+    /// password is read from DNDGAMEROLLS_SYNC_PASSWORD)
+    Sync {
+        /// Server address (ws://, wss://, http:// or https://)
+        #[arg(long, requires_all = ["namespace", "database"])]
+        endpoint: Option<String>,
+
+        /// Namespace on the server
+        #[arg(long, requires = "endpoint")]
+        namespace: Option<String>,
+
+        /// Database on the server
+        #[arg(long, requires = "endpoint")]
+        database: Option<String>,
+
+        /// User to sign in as (a database or root user)
+        #[arg(long, requires = "endpoint")]
+        user: Option<String>,
+
+        /// Stop syncing with the server
+        #[arg(long, conflicts_with = "endpoint")]
+        off: bool,
+    },
+
+    /// Check the profile's database for records this version can't read
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+
+    /// Save the character as a .dndchar file to send to the DM
+    Share {
+        /// File to write (defaults to "<name>.dndchar")
+        output: Option<std::path::PathBuf>,
+
+        /// Portrait image to include
+        #[arg(long, value_name = "IMAGE")]
+        portrait: Option<std::path::PathBuf>,
+
+        /// Leave out the fields the sheet hides from players
+        #[arg(long)]
+        players: bool,
+    },
+
+    /// Drop test dice into a custom tray or tower model (.glb/.gltf) and
+    /// report gaps they escape through and flat spots they balance on
+    ValidateContainer {
+        /// The container model
+        model: std::path::PathBuf,
+
+        /// Number of test dice to drop
+        #[arg(long, default_value_t = 40, value_parser = clap::value_parser!(u32).range(1..=1000))]
+        drops: u32,
+    },
 }
 
-fn parse_dice_arg(s: &str) -> Result<(usize, DiceType), String> {
-    let s = s.to_lowercase();
+/// What `lookup` looks up
+#[derive(Subcommand)]
+enum LookupTarget {
+    /// Print a monster's stat block ("lookup monster young red dragon") and
+    /// roll its attacks and saves
+    Monster {
+        /// Monster name, or enough of it to pick one
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+
+        /// Roll this action's attack and damage (e.g. scimitar)
+        #[arg(long, value_name = "ACTION")]
+        attack: Option<String>,
+
+        /// Roll this saving throw (e.g. dex)
+        #[arg(long, value_name = "ABILITY")]
+        save: Option<String>,
+    },
+}
 
-    let (count_str, die_str) = if s.starts_with('d') {
-        ("1", s.as_str())
-    } else if let Some(pos) = s.find('d') {
-        (&s[..pos], &s[pos..])
-    } else {
+/// What `db` does with the database
+#[derive(Subcommand)]
+enum DbAction {
+    /// Report malformed records; with --repair fix them, moving the ones
+    /// that can't be fixed to the quarantine table
+    Doctor {
+        /// Rewrite the records that can be fixed and quarantine the rest
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+fn parse_dice_arg(s: &str) -> Result<DiceExpr, String> {
+    let expression = DiceExpr::parse(s).map_err(|e| {
+        format!(
+            "{}. Use dice like '2d6', 'd20', '2d6+1d8+3', '4dF' or '1coin'",
+            e
+        )
+    })?;
+    if expression.dice().is_empty() {
         return Err(format!(
-            "Invalid dice format: {}. Use format like '2d6' or 'd20'",
+            "No dice in '{}'; use --modifier for a flat bonus",
             s
         ));
-    };
+    }
+    Ok(expression)
+}
 
-    let count: usize = count_str
-        .parse()
-        .map_err(|_| format!("Invalid count: {}", count_str))?;
-    let die_type = DiceType::parse(die_str).ok_or_else(|| {
+fn parse_result_order(s: &str) -> Result<ResultOrder, String> {
+    ResultOrder::parse(s).ok_or_else(|| {
         format!(
-            "Unknown die type: {}. Valid: d4, d6, d8, d10, d12, d20",
-            die_str
+            "'{}' is not a result order; use spawn, settle, descending or grouped",
+            s
         )
-    })?;
+    })
+}
 
-    Ok((count, die_type))
+fn parse_hoard_cr(s: &str) -> Result<HoardTier, String> {
+    HoardTier::parse_cr(s)
+        .ok_or_else(|| format!("'{}' is not a challenge rating; use e.g. 7 or 1/2", s))
 }
 
+/// Canvas the browser build draws into (see `web/index.html`)
+#[cfg(all(target_arch = "wasm32", feature = "gui"))]
+const WEB_CANVAS_SELECTOR: &str = "#dndgamerolls";
+
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
     let cli = Cli::parse();
 
+    configure_output(cli.quiet, cli.no_color);
+    let _ = RESULT_ORDER.set(cli.order);
+
+    if let Some(name) = &cli.profile {
+        if let Err(e) = select_profile_for_run(name) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
     // Determine mode: CLI subcommands, --cli flag with dice, or 3D mode
     if cli.command.is_some() || (cli.cli && (cli.dice.is_some() || cli.checkon.is_some())) {
         // Attach to parent console for CLI output (Windows only)
@@ -323,6 +524,16 @@ fn main() {
     }
 }
 
+/// The browser build only has the 3D app. IndexedDB can't be read
+/// synchronously, so the saved data is loaded before the app starts.
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    wasm_bindgen_futures::spawn_local(async {
+        dndgamerolls::dice3d::types::preload_web_datastores().await;
+        run_3d_mode(Cli::parse());
+    });
+}
+
 /// Attach to the parent process's console on Windows.
 /// This is needed because we use windows_subsystem = "windows" to hide the console,
 /// but CLI mode needs to output to the terminal.
@@ -356,12 +567,12 @@ fn attach_parent_console() {
 // 3D Mode
 // ============================================================================
 
+#[cfg(feature = "gui")]
 fn run_3d_mode(cli: Cli) {
     // Character persistence is database-backed; character selection/loading is handled
     // by the in-game character manager.
     let character_data = CharacterData::default();
 
-    let mut dice_to_roll = Vec::new();
     let mut modifier = cli.modifier;
     let mut modifier_name = String::new();
 
@@ -394,30 +605,25 @@ fn run_3d_mode(cli: Cli) {
             modifier_name = check.clone();
             eprintln!("Warning: '{}' not found in character sheet", check);
         }
-
-        if let Some(dice_args) = &cli.dice {
-            for (count, die_type) in dice_args {
-                for _ in 0..*count {
-                    dice_to_roll.push(*die_type);
-                }
-            }
-        }
-    } else if let Some(dice_args) = &cli.dice {
-        for (count, die_type) in dice_args {
-            for _ in 0..*count {
-                dice_to_roll.push(*die_type);
-            }
-        }
     }
 
+    let (mut dice_to_roll, expression) = match cli.dice.clone().and_then(DiceExpr::sum) {
+        Some(expression) => {
+            DiceConfig::from_expression(&expression.with_explosion_cap(cli.explode_cap))
+        }
+        None => (Vec::new(), None),
+    };
     if dice_to_roll.is_empty() {
         dice_to_roll.push(DiceType::D20);
     }
 
-    println!(
-        "Rolling: {:?}",
-        dice_to_roll.iter().map(|d| d.name()).collect::<Vec<_>>()
-    );
+    match &expression {
+        Some(expression) => println!("Rolling: {}", expression),
+        None => println!(
+            "Rolling: {:?}",
+            dice_to_roll.iter().map(|d| d.name()).collect::<Vec<_>>()
+        ),
+    }
     if modifier != 0 {
         let sign = if modifier >= 0 { "+" } else { "" };
         println!("Modifier: {}{} ({})", sign, modifier, modifier_name);
@@ -449,6 +655,12 @@ fn run_3d_mode(cli: Cli) {
             std::path::PathBuf::from("../assets/icon.ico"),
             // Installed path (icon in root of install folder)
             std::path::PathBuf::from("icon.ico"),
+            // Linux packages ship a PNG next to the .desktop file
+            std::path::PathBuf::from(format!("assets/linux/{}.png", APP_ID)),
+            std::path::PathBuf::from(format!(
+                "/usr/share/icons/hicolor/256x256/apps/{}.png",
+                APP_ID
+            )),
         ];
 
         // Add paths relative to executable location
@@ -476,370 +688,388 @@ fn run_3d_mode(cli: Cli) {
         }
     }
 
+    let pending_character_file = cli.open.clone();
+    if let Some(path) = &pending_character_file {
+        if !is_character_file(path) {
+            eprintln!(
+                "Warning: '{}' does not have a .dndchar extension; trying to open it anyway",
+                path.display()
+            );
+        }
+    }
+
     let dice_config = DiceConfig {
         dice_to_roll,
         modifier,
         modifier_name,
+        expression,
     };
 
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "DnD Game Rolls".to_string(),
-                        resolution: (1280u32, 720u32).into(),
-                        ..default()
-                    }),
-                    ..default()
-                })
-                // Keep app logs at info, but silence bevy_material_ui scroll spam.
-                .set(bevy::log::LogPlugin {
-                    level: bevy::log::Level::INFO,
-                    filter: "info,wgpu=error,bevy_material_ui=warn,bevy_material_ui::scroll=off"
-                        .to_string(),
+    let mut app = App::new();
+
+    let roll_rng = match cli.seed {
+        Some(seed) => {
+            println!("Rolling with seed {}", seed);
+            RollRng::seeded(seed)
+        }
+        None => RollRng::default(),
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(addr) = &cli.serve {
+        match TableServer::start(addr, cli.room_token.clone()) {
+            Ok(server) => {
+                println!("Serving rolls on ws://{}", server.local_addr());
+                app.insert_resource(server);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(url) = &cli.join {
+        match TableClient::connect(url) {
+            Ok(client) => {
+                println!("Joined the table at {}", client.url());
+                app.insert_resource(client)
+                    .insert_resource(TableFeed::new(cli.replay_throws));
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "DnD Game Rolls".to_string(),
+                    // Wayland app_id / X11 WM_CLASS: matches the .desktop file
+                    name: Some(APP_ID.to_string()),
+                    resolution: (1280u32, 720u32).into(),
+                    // In a web page, draw into the page's canvas and follow its size
+                    #[cfg(target_arch = "wasm32")]
+                    canvas: Some(WEB_CANVAS_SELECTOR.to_string()),
+                    #[cfg(target_arch = "wasm32")]
+                    fit_canvas_to_parent: true,
                     ..default()
                 }),
-        )
-        .add_plugins(HanabiPlugin)
-        .add_plugins(bevy::pbr::MaterialPlugin::<DiceBoxHighlightMaterial>::default())
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+                ..default()
+            })
+            // Keep app logs at info, but silence bevy_material_ui scroll spam.
+            .set(bevy::log::LogPlugin {
+                level: bevy::log::Level::INFO,
+                filter: "info,wgpu=error,bevy_material_ui=warn,bevy_material_ui::scroll=off"
+                    .to_string(),
+                ..default()
+            }),
+    );
+
+    #[cfg(feature = "hanabi")]
+    app.add_plugins(HanabiPlugin);
+
+    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugins(MaterialUiPlugin)
         .add_plugins(Dice3dEmbeddedAssetsPlugin)
-        .add_plugins(DiceFxPlugin)
-        // Ensure UI Buttons spawned without ButtonBundle still receive click events
-        .add_systems(PreUpdate, ensure_buttons_have_interaction)
+        .add_plugins((
+            DiceRollerPlugin,
+            CharacterSheetPlugin,
+            SettingsPlugin,
+            IntegrationsPlugin,
+            StatsPlugin,
+            CompendiumPlugin,
+        ))
         .insert_resource(dice_config)
         .insert_resource(character_data)
-        .insert_resource(DiceResults::default())
-        .insert_resource(RollState::default())
-        .insert_resource(CommandInput::default())
-        .insert_resource(CommandHistory::default())
-        .insert_resource(ZoomState::default())
-        .insert_resource(UiState::default())
-        .insert_resource(DiceContainerStyle::default())
-        .insert_resource(ShakeState::default())
-        .insert_resource(ContainerShakeAnimation::default())
-        .insert_resource(ContainerShakeConfig::default())
-        .insert_resource(GroupEditState::default())
-        .insert_resource(AddingEntryState::default())
-        .insert_resource(SettingsState::default())
-        .insert_resource(CharacterScreenRollBridge::default())
-        .insert_resource(UiPointerCapture::default())
-        .insert_resource(ThrowControlState::default())
-        .insert_resource(DiceSpawnPoints::default())
-        .insert_resource(DiceSpawnPointsApplied::default())
-        .insert_resource(AvatarLoader::default())
-        .insert_resource(DiceBoxLidAnimationController::default())
-        .add_systems(
-            Startup,
-            (
-                set_window_icon,
-                load_icons,
-                init_character_manager,
-                load_settings_state_from_db,
-                init_dice_scale_preview_render_target,
-                init_settings_ui_images,
-                init_contributors,
-                apply_initial_shake_config,
-                init_collision_sounds,
-                setup,
-                setup_tab_bar,
-                setup_character_screen,
-                setup_dnd_info_screen,
-                setup_contributors_screen,
-                apply_initial_settings,
-            )
-                .chain(),
-        )
-        .add_systems(
-            Update,
-            (
-                // Dice roller systems
-                check_dice_settled,
-                update_results_display,
-                handle_input,
-                handle_command_input,
-                rebuild_command_history_panel,
-                handle_quick_roll_clicks,
-                rebuild_quick_roll_panel,
-                rotate_camera,
-                handle_zoom_slider_changes,
-                sync_dice_container_mode_text,
-                sync_dice_container_toggle_icon,
-                handle_dice_box_rotate_click,
-                handle_dice_box_shake_box_click,
-                animate_container_shake,
-                handle_dice_box_toggle_container_click,
-                // Mouse-controlled throw systems
-                update_throw_from_mouse,
-                update_dice_box_highlight,
-                handle_strength_slider_changes,
-                handle_shake_slider_changes,
-                update_throw_arrow,
-            ),
-        )
-        .add_systems(
-            Update,
-            update_ui_pointer_capture
-                .before(handle_input)
-                .before(update_throw_from_mouse)
-                .before(update_dice_box_highlight),
-        )
-        .add_systems(Update, ensure_dice_box_lid_animation_assets)
-        .add_systems(
-            Update,
-            cache_dice_box_lid_animation_player.after(ensure_dice_box_lid_animation_assets),
-        )
-        .add_systems(
-            Update,
-            process_pending_roll_with_lid
-                .after(handle_input)
-                .after(handle_quick_roll_clicks),
-        )
-        .add_systems(Update, open_lid_on_roll_completed.after(check_dice_settled))
-        .add_systems(Update, play_dice_container_collision_sfx)
-        .add_systems(
-            Update,
-            center_container_models_in_view
-                .before(spawn_colliders_from_gltf_guides)
-                .before(apply_crystal_material_to_container_models)
-                .before(collect_dice_spawn_points_from_gltf)
-                .before(apply_spawn_points_to_dice_when_ready)
-                .before(update_dice_box_highlight),
-        )
-        // Separate to avoid Bevy's tuple-size limit, and ensure it runs before highlight tagging.
-        .add_systems(
-            Update,
-            spawn_colliders_from_gltf_guides
-                .before(handle_dice_box_toggle_container_click)
-                .before(update_dice_box_highlight),
-        )
-        .add_systems(
-            Update,
-            apply_crystal_material_to_container_models
-                .before(handle_dice_box_toggle_container_click)
-                .before(update_dice_box_highlight),
-        )
-        .add_systems(
-            Update,
-            collect_dice_spawn_points_from_gltf
-                .before(handle_dice_box_toggle_container_click)
-                .before(update_dice_box_highlight),
-        )
-        .add_systems(
-            Update,
-            apply_spawn_points_to_dice_when_ready
-                .before(handle_dice_box_toggle_container_click)
-                .before(update_dice_box_highlight),
-        )
-        .add_systems(Update, handle_command_history_item_clicks)
-        .add_systems(
-            Update,
-            (
-                // Avatar loading systems
-                request_avatars,
-                process_avatar_loads,
-                update_avatar_images,
-                // Tab and character screen systems
-                handle_tab_clicks,
-                update_tab_visibility,
-                // Legacy SQLite -> SurrealDB conversion (character screen)
-                start_sqlite_conversion_if_needed,
-                run_sqlite_conversion_step,
-                update_sqlite_conversion_dialog_ui,
-                handle_sqlite_conversion_ok_click,
-                handle_sqlite_conversion_yes_click,
-                handle_sqlite_conversion_no_click,
-                finalize_sqlite_conversion_if_done,
-                handle_character_list_clicks,
-                handle_new_character_click,
-                handle_save_click,
-            ),
-        )
-        .add_systems(
-            Update,
-            record_character_screen_roll_on_settle.after(check_dice_settled),
-        )
-        .add_systems(Update, handle_slider_group_drag)
-        .add_systems(
-            Update,
-            (
-                // Tab styling (separate to avoid tuple size limit)
-                update_tab_styles,
-                // Character sheet tab systems
-                handle_sheet_tab_clicks,
-                update_sheet_tab_styles,
-                update_sheet_tab_visibility,
-                // Character editing systems - input handling
-                handle_scroll_input,
-                handle_stat_field_click,
-                handle_label_click,
-                handle_text_input,
-                handle_expertise_toggle,
-                handle_group_edit_toggle,
-                handle_group_add_click,
-                handle_delete_click,
-                handle_new_entry_confirm,
-                handle_new_entry_cancel,
-                handle_new_entry_input,
-                // Dice roll buttons for attributes
-                handle_roll_all_stats_click,
-                handle_roll_attribute_click,
-                handle_roll_skill_click,
-            ),
-        )
-        .add_systems(
-            Update,
-            (
-                // Character editing systems - display updates (run after input handling)
-                update_new_entry_input_display,
-                update_editing_display,
-                update_save_button_appearance,
-                update_character_list_modified_indicator,
-                refresh_character_display,
-                rebuild_character_list_on_change,
-                rebuild_character_panel_on_change,
-                sync_character_screen_roll_result_texts,
-            )
-                .chain()
-                .after(handle_new_entry_confirm)
-                .after(handle_delete_click)
-                .after(handle_roll_all_stats_click)
-                .after(handle_roll_attribute_click)
-                .after(handle_roll_skill_click)
-                .after(handle_text_input),
-        )
-        .add_systems(
-            Update,
-            (
-                (
-                    // Settings systems
-                    (
-                        handle_settings_button_click,
-                        manage_settings_modal,
-                        manage_dice_scale_preview_scene,
-                        fix_dice_scale_slider_thumb_hitbox.after(manage_settings_modal),
-                        handle_settings_ok_click,
-                        handle_settings_cancel_click,
-                        handle_settings_reset_layout_click,
-                    ),
-                    (
-                        (
-                            handle_quick_roll_die_type_select_change,
-                            handle_theme_seed_select_change,
-                            handle_default_roll_uses_shake_switch_change,
-                            handle_color_slider_changes,
-                            handle_dice_scale_slider_changes,
-                            handle_dice_fx_param_slider_changes,
-                            handle_dice_roll_fx_mapping_select_change,
-                            handle_color_text_input,
-                            handle_shake_duration_text_input,
-                        ),
-                        (
-                            handle_shake_curve_chip_clicks,
-                            (
-                                handle_shake_curve_point_press,
-                                handle_shake_curve_bezier_handle_press,
-                                handle_shake_curve_graph_click_to_add_point,
-                                drag_shake_curve_bezier_handle,
-                                drag_shake_curve_point,
-                                sync_shake_curve_graph_ui,
-                            )
-                                .chain(),
-                            sync_shake_curve_chip_ui,
-                        ),
-                    ),
-                    (
-                        update_color_ui,
-                        update_dice_scale_ui,
-                        update_dice_fx_param_ui,
-                        sync_dice_scale_preview_dice,
-                        autosave_and_apply_shake_config.after(sync_shake_curve_graph_ui),
-                    ),
-                ),
-                (
-                    // Character sheet dice settings modal
-                    handle_character_sheet_settings_button_click,
-                    manage_character_sheet_settings_modal,
-                    handle_character_sheet_die_type_select_change,
-                    handle_character_sheet_settings_save_click,
-                    handle_character_sheet_settings_cancel_click,
-                ),
-            ),
-        )
-        .add_systems(
-            Update,
-            apply_editing_dice_scales_to_existing_dice_while_open
-                .after(handle_dice_scale_slider_changes),
-        )
-        .add_systems(
-            Update,
-            apply_dice_scale_settings_to_existing_dice.after(handle_settings_ok_click),
-        )
-        .add_systems(
-            Update,
-            refresh_scrollbar_colors_on_theme_change
-                .after(handle_color_text_input)
-                .after(handle_theme_seed_select_change)
-                .after(handle_settings_ok_click)
-                .after(handle_settings_cancel_click),
-        )
-        .add_systems(PostUpdate, tint_recent_theme_dropdown_items)
-        .add_systems(PostUpdate, persist_settings_to_db)
+        .insert_resource(roll_rng)
+        .insert_resource(PendingCharacterFileOpen {
+            path: pending_character_file,
+        })
+        .add_systems(Startup, set_window_icon)
         .run();
 }
 
+/// Builds without the `gui` feature have only the command line.
+#[cfg(not(feature = "gui"))]
+fn run_3d_mode(_cli: Cli) {
+    eprintln!(
+        "This build of dndgamerolls has no 3D app; use --cli or one of the subcommands (see --help)"
+    );
+    std::process::exit(1);
+}
+
 // ============================================================================
 // CLI Mode Functions
 // ============================================================================
 
+/// `--order`: how the `Dice:` line lists several dice
+static RESULT_ORDER: OnceLock<ResultOrder> = OnceLock::new();
+
+fn result_order() -> ResultOrder {
+    RESULT_ORDER
+        .get()
+        .copied()
+        .unwrap_or(ResultOrder::SpawnOrder)
+}
+
 fn run_cli_mode(cli: Cli) {
+    if let Some(seed) = cli.seed {
+        seed_rolls(seed);
+    }
+
     // If using --dice with --checkon (new unified syntax)
     if cli.dice.is_some() || cli.checkon.is_some() {
         run_cli_dice_roll(&cli);
         return;
     }
 
-    // Legacy subcommand mode
-    let sheet = match load_character_sheet(cli.character.as_deref(), cli.character_id) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("{} Failed to load character: {}", "Error:".red().bold(), e);
-            std::process::exit(1);
+    // Party-wide commands load a saved party instead of one character
+    if let Some(Commands::Travel { vs }) = cli.command {
+        run_cli_travel(&cli, vs);
+        return;
+    }
+
+    if let Some(Commands::Hoard { cr, clear }) = cli.command {
+        run_cli_hoard(cr, clear);
+        return;
+    }
+
+    if let Some(Commands::Profiles) = cli.command {
+        run_cli_profiles();
+        return;
+    }
+
+    if let Some(Commands::Sync {
+        endpoint,
+        namespace,
+        database,
+        user,
+        off,
+    }) = &cli.command
+    {
+        let server = endpoint.as_ref().map(|endpoint| CloudSyncConfig {
+            endpoint: endpoint.clone(),
+            namespace: namespace.clone().unwrap_or_default(),
+            database: database.clone().unwrap_or_default(),
+            username: user.clone().unwrap_or_default(),
+            password: std::env::var(SYNC_PASSWORD_ENV_VAR).unwrap_or_default(),
+            ..Default::default()
+        });
+        run_cli_sync(server, *off);
+        return;
+    }
+
+    if let Some(Commands::Db {
+        action: DbAction::Doctor { repair },
+    }) = &cli.command
+    {
+        run_cli_db_doctor(*repair);
+        return;
+    }
+
+    if let Some(Commands::Lookup {
+        target: LookupTarget::Monster { name, attack, save },
+    }) = &cli.command
+    {
+        run_cli_lookup_monster(
+            &name.join(" "),
+            attack.as_deref(),
+            save.as_deref(),
+            &cli,
+            &load_cli_damage_types(),
+        );
+        return;
+    }
+
+    if let Some(Commands::ValidateContainer { model, drops }) = &cli.command {
+        run_cli_validate_container(model, *drops);
+        return;
+    }
+
+    if let Some(Commands::Party {
+        name,
+        members,
+        delete,
+    }) = &cli.command
+    {
+        run_cli_party(name.as_deref(), members, *delete);
+        return;
+    }
+
+    if let Some(Commands::Macro { definition, delete }) = &cli.command {
+        run_cli_macro(&cli, &definition.join(" "), *delete);
+        return;
+    }
+
+    if let Some(Commands::StatsReport) = cli.command {
+        run_cli_stats_report(cli.character.first().map(String::as_str));
+        return;
+    }
+
+    // Two characters contest the check
+    if let Some(opponent) = &cli.vs {
+        match cli.command.as_ref().map(group_check_for) {
+            Some(Some(Ok(check))) => run_cli_contest(&cli, &check, opponent),
+            Some(Some(Err(e))) => {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+            _ => {
+                eprintln!(
+                    "{} --vs contests ability checks, skills, saves and initiative",
+                    "Error:".red().bold()
+                );
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Several characters roll the check together and compare results
+    if cli.party.is_some() || cli.character.len() > 1 {
+        match cli.command.as_ref().map(group_check_for) {
+            Some(Some(Ok(check))) => run_cli_group_check(&cli, &check),
+            Some(Some(Err(e))) => {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+            _ => {
+                eprintln!(
+                    "{} --party and repeated --character roll ability checks, skills, saves and initiative",
+                    "Error:".red().bold()
+                );
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(Commands::DamageTypes) = cli.command {
+        run_cli_damage_types(&load_cli_damage_types());
+        return;
+    }
+
+    // Damage types are shown in the campaign's colors
+    let damage_types = load_cli_damage_types();
+
+    // A damage string needs no character
+    if let Some(Commands::Damage { weapon, crit, .. }) = &cli.command {
+        if let Ok(damage) = DamageRoll::parse(weapon) {
+            if let Err(e) = roll_damage("Damage", damage, *crit, cli.explode_cap, &damage_types) {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+            return;
         }
+    }
+
+    // Legacy subcommand mode
+    let (character_id, mut sheet) =
+        match load_cli_character(cli.character.first().map(String::as_str), cli.character_id) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{} Failed to load character: {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+        };
+
+    // Ability checks and skills get advantage, disadvantage or a penalty
+    // from conditions, as the campaign's rules have them
+    let ruleset = load_cli_ruleset();
+    let check_edge = || {
+        condition_edge(
+            &sheet.conditions,
+            D20Test::AbilityCheck,
+            ruleset,
+            cli.advantage,
+            cli.disadvantage,
+        )
     };
 
     match cli.command {
         Some(Commands::Strength) => {
             let modifier = sheet.modifiers.strength;
-            roll_ability_check("Strength", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Strength",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Dexterity) => {
             let modifier = sheet.modifiers.dexterity;
-            roll_ability_check("Dexterity", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Dexterity",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Constitution) => {
             let modifier = sheet.modifiers.constitution;
-            roll_ability_check("Constitution", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Constitution",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Intelligence) => {
             let modifier = sheet.modifiers.intelligence;
-            roll_ability_check("Intelligence", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Intelligence",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Wisdom) => {
             let modifier = sheet.modifiers.wisdom;
-            roll_ability_check("Wisdom", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Wisdom",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Charisma) => {
             let modifier = sheet.modifiers.charisma;
-            roll_ability_check("Charisma", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Charisma",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
         Some(Commands::Initiative) => {
             let modifier = sheet.combat.initiative;
-            roll_ability_check("Initiative", modifier, cli.advantage, cli.disadvantage);
+            let (advantage, disadvantage, penalty) = check_edge();
+            roll_ability_check(
+                "Initiative",
+                modifier + penalty,
+                advantage,
+                disadvantage,
+                cli.dc,
+            );
         }
-        Some(Commands::Skill { name }) => {
+        Some(Commands::Skill { name, with }) => {
             if let Some((skill_name, skill)) = get_skill_by_name(&sheet.skills, &name) {
                 let proficiency_str = if skill.expertise.unwrap_or(false) {
                     " (Expertise)"
@@ -848,11 +1078,59 @@ fn run_cli_mode(cli: Cli) {
                 } else {
                     ""
                 };
+
+                let (modifier, ability_str) = match with.as_deref() {
+                    Some(ability) => {
+                        let Some(ability_key) =
+                            dndgamerolls::dice3d::types::normalize_ability_name(ability)
+                        else {
+                            eprintln!("{} Unknown ability '{}'", "Error:".red().bold(), ability);
+                            eprintln!("Use: str, dex, con, int, wis, cha");
+                            std::process::exit(1);
+                        };
+                        let ability_mod = match ability_key {
+                            "strength" => sheet.modifiers.strength,
+                            "dexterity" => sheet.modifiers.dexterity,
+                            "constitution" => sheet.modifiers.constitution,
+                            "intelligence" => sheet.modifiers.intelligence,
+                            "wisdom" => sheet.modifiers.wisdom,
+                            _ => sheet.modifiers.charisma,
+                        };
+                        let proficiency = if skill.expertise.unwrap_or(false) {
+                            sheet.proficiency_bonus * 2
+                        } else if skill.proficient {
+                            sheet.proficiency_bonus
+                        } else {
+                            0
+                        };
+                        (
+                            ability_mod + proficiency,
+                            format!(
+                                " ({})",
+                                dndgamerolls::dice3d::types::ability_abbreviation(ability_key)
+                            ),
+                        )
+                    }
+                    None => (skill.modifier, String::new()),
+                };
+
+                // Xanathar's tool/skill synergy from tagged tool proficiencies
+                let synergy = sheet.tool_synergy_for_skill(skill_name);
+                if synergy.applies() {
+                    say!(
+                        "{} {}",
+                        "Tool proficiency:".bold().white(),
+                        synergy.describe()
+                    );
+                }
+
+                let (advantage, disadvantage, penalty) = check_edge();
                 roll_ability_check(
-                    &format!("{}{}", skill_name, proficiency_str),
-                    skill.modifier,
-                    cli.advantage,
-                    cli.disadvantage,
+                    &format!("{}{}{}", skill_name, ability_str, proficiency_str),
+                    modifier + synergy.bonus + penalty,
+                    advantage || synergy.advantage,
+                    disadvantage,
+                    cli.dc,
                 );
             } else {
                 eprintln!("{} Unknown skill '{}'", "Error:".red().bold(), name);
@@ -889,40 +1167,342 @@ fn run_cli_mode(cli: Cli) {
             };
 
             let proficiency_str = if save.proficient { " (Proficient)" } else { "" };
-            roll_ability_check(
-                &format!("{} Save{}", save_name, proficiency_str),
-                save.modifier,
-                cli.advantage,
-                cli.disadvantage,
-            );
-        }
-        Some(Commands::Attack { weapon }) => {
-            let weapon_lower = weapon.to_lowercase();
-            let Some(equipment) = sheet.equipment.as_ref() else {
-                eprintln!(
-                    "{} No equipment found on this character",
+            let name = format!("{} Save{}", save_name, proficiency_str);
+            let test = D20Test::SavingThrow(key.to_string());
+            // Paralyzed, stunned or unconscious: Strength and Dexterity
+            // saves fail without a roll
+            let effect = sheet.conditions.effect_on(&test, ruleset);
+            if !report_automatic_failure(&name, &effect) {
+                let (advantage, disadvantage, penalty) = condition_edge(
+                    &sheet.conditions,
+                    test,
+                    ruleset,
+                    cli.advantage,
+                    cli.disadvantage,
+                );
+                roll_ability_check(
+                    &name,
+                    save.modifier + penalty,
+                    advantage,
+                    disadvantage,
+                    cli.dc,
+                );
+            }
+        }
+        Some(Commands::Attack {
+            weapon,
+            two_handed,
+            thrown,
+            all,
+            off_hand,
+            riders,
+            no_riders,
+            slot,
+            target_save,
+            no_mastery,
+        }) => {
+            let Some(equipment) = sheet.equipment.as_ref() else {
+                eprintln!(
+                    "{} No equipment found on this character",
                     "Error:".red().bold()
                 );
                 std::process::exit(1);
             };
 
-            if let Some(wpn) = equipment
-                .weapons
+            let find_weapon = |name: &str| {
+                let name_lower = name.to_lowercase();
+                let Some(wpn) = equipment
+                    .weapons
+                    .iter()
+                    .find(|w| w.name.to_lowercase() == name_lower)
+                else {
+                    eprintln!("{} Weapon '{}' not found", "Error:".red().bold(), name);
+                    eprintln!("Available weapons:");
+                    for wpn in &equipment.weapons {
+                        eprintln!("  - {}", wpn.name);
+                    }
+                    std::process::exit(1);
+                };
+                wpn.clone()
+            };
+            let wpn = find_weapon(&weapon);
+            let off_hand = off_hand.as_deref().map(find_weapon);
+
+            let fail = |e: String| -> ! {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            };
+            let mode = if thrown {
+                AttackMode::Thrown
+            } else if two_handed {
+                AttackMode::TwoHanded
+            } else {
+                AttackMode::Normal
+            };
+            let attack = sheet.weapon_attack(&wpn, mode).unwrap_or_else(|e| fail(e));
+
+            // The attacks of the Attack action, then the off-hand attack
+            let count = if all { sheet.attacks_per_action() } else { 1 };
+            let mut planned: Vec<PlannedAttack> = (1..=count)
+                .map(|n| PlannedAttack {
+                    label: format!("Attack {}", n),
+                    weapon: wpn.name.clone(),
+                    damage_type: wpn.damage_type.clone(),
+                    attack: attack.clone(),
+                    ammunition: None,
+                })
+                .collect();
+            if let Some(off_hand) = off_hand {
+                let attack = sheet
+                    .off_hand_attack(&wpn, &off_hand)
+                    .unwrap_or_else(|e| fail(e));
+                // Nick makes the off-hand attack part of the Attack action
+                let nick = ruleset.weapon_mastery()
+                    && !no_mastery
+                    && attack.mastery == Some(WeaponMastery::Nick);
+                planned.push(PlannedAttack {
+                    label: if nick { "Off Hand (Nick)" } else { "Off Hand" }.to_string(),
+                    weapon: off_hand.name,
+                    damage_type: off_hand.damage_type,
+                    attack,
+                    ammunition: None,
+                });
+            }
+
+            // Every shot spends a piece of ammunition from the items
+            let mut spent = false;
+            for step in planned.iter_mut().filter(|p| p.attack.uses_ammunition) {
+                let items = &mut sheet.equipment.get_or_insert_with(Default::default).items;
+                let ammunition = spend_ammunition(items, &step.weapon);
+                let out = matches!(ammunition, AmmunitionUse::OutOf(_));
+                if out && !spent {
+                    fail(ammunition.describe());
+                }
+                step.ammunition = Some(ammunition);
+                if out {
+                    break;
+                }
+                spent = true;
+            }
+            if spent {
+                if let Err(e) =
+                    open_cli_database().and_then(|db| db.save_character(Some(character_id), &sheet))
+                {
+                    eprintln!("Warning: failed to save ammunition: {}", e);
+                }
+            }
+
+            // The character's damage riders are offered on hits; --rider
+            // ones are added without asking
+            let always: Vec<DamageRider> = riders
                 .iter()
-                .find(|w| w.name.to_lowercase() == weapon_lower)
-            {
-                roll_attack(wpn, cli.advantage, cli.disadvantage);
+                .map(|name| {
+                    sheet
+                        .combat
+                        .damage_riders
+                        .iter()
+                        .find(|rider| rider.name().eq_ignore_ascii_case(name.trim()))
+                        .cloned()
+                        .map_or_else(|| DamageRider::parse(name), Ok)
+                })
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|e| fail(e));
+            let mut offered = if no_riders {
+                Vec::new()
             } else {
-                eprintln!("{} Weapon '{}' not found", "Error:".red().bold(), weapon);
-                eprintln!("Available weapons:");
-                for wpn in &equipment.weapons {
-                    eprintln!("  - {}", wpn.name);
+                sheet.combat.damage_riders.clone()
+            };
+            for rider in &always {
+                if !offered.contains(rider) {
+                    offered.push(rider.clone());
+                }
+            }
+
+            let (advantage, disadvantage, penalty) = condition_edge(
+                &sheet.conditions,
+                D20Test::AttackRoll,
+                ruleset,
+                cli.advantage,
+                cli.disadvantage,
+            );
+            for step in planned.iter_mut() {
+                step.attack.attack_bonus += penalty;
+            }
+            let mut riders = RiderOffer {
+                level: sheet.character.level,
+                proficiency_bonus: sheet.proficiency_bonus,
+                slots: &mut sheet,
+                riders: offered,
+                always,
+                slot,
+                ask: std::io::stdin().is_terminal() && !quiet(),
+                used: Vec::new(),
+                slots_spent: false,
+                mastery: ruleset.weapon_mastery() && !no_mastery,
+                target_save,
+                vexed: false,
+                cleaved: false,
+            };
+            match planned.as_slice() {
+                [single] => roll_attack(
+                    single,
+                    advantage,
+                    disadvantage,
+                    cli.explode_cap,
+                    &damage_types,
+                    &mut riders,
+                ),
+                _ => roll_attack_sequence(
+                    &planned,
+                    advantage,
+                    disadvantage,
+                    cli.explode_cap,
+                    &damage_types,
+                    &mut riders,
+                ),
+            }
+            if riders.slots_spent {
+                if let Err(e) =
+                    open_cli_database().and_then(|db| db.save_character(Some(character_id), &sheet))
+                {
+                    eprintln!("Warning: failed to save the spell slots: {}", e);
                 }
+            }
+        }
+        Some(Commands::Damage {
+            weapon,
+            crit,
+            two_handed,
+        }) => {
+            let weapon_lower = weapon.to_lowercase();
+            let Some(wpn) = sheet.equipment.as_ref().and_then(|e| {
+                e.weapons
+                    .iter()
+                    .find(|w| w.name.to_lowercase() == weapon_lower)
+            }) else {
+                eprintln!(
+                    "{} '{}' is neither a weapon nor a damage roll like 1d8+3",
+                    "Error:".red().bold(),
+                    weapon
+                );
                 std::process::exit(1);
+            };
+            let mode = if two_handed {
+                AttackMode::TwoHanded
+            } else {
+                AttackMode::Normal
+            };
+            let rolled = sheet
+                .weapon_attack(wpn, mode)
+                .and_then(|attack| weapon_damage(&attack.damage, &wpn.damage_type))
+                .and_then(|damage| {
+                    roll_damage(&wpn.name, damage, crit, cli.explode_cap, &damage_types)
+                });
+            if let Err(e) = rolled {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Cast { spell, slot }) => {
+            let fail = |e: String| -> ! {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            };
+            let Some(spells) = sheet.spells.as_mut() else {
+                fail(format!("{} doesn't cast spells", sheet.character.name));
+            };
+            let Some(spell) = spells.known_spell(&spell).map(str::to_string) else {
+                fail(format!("{} isn't one of the known spells", spell));
+            };
+            let choices = spells.slot_choices();
+            if choices.is_empty() {
+                fail(format!("No spell slots left for {}", spell));
+            }
+            let level = match slot {
+                Some(level) => level,
+                None if std::io::stdin().is_terminal() => {
+                    pick_slot(&choices, &spell).unwrap_or_else(|| std::process::exit(1))
+                }
+                None => choices[0].0,
+            };
+            spells
+                .spend_slot_for(level, &spell)
+                .unwrap_or_else(|e| fail(e));
+            println!(
+                "{} {} cast with a level {} slot ({} left)",
+                "✨".bold(),
+                spell.bold().yellow(),
+                level,
+                spells.slots_remaining(&level.to_string())
+            );
+            let damage = spells.spell_damage.get(&spell).cloned();
+            if let Err(e) =
+                open_cli_database().and_then(|db| db.save_character(Some(character_id), &sheet))
+            {
+                fail(format!("failed to save the spell slots: {}", e));
+            }
+            if let Some(damage) = damage {
+                DamageRoll::parse(&damage)
+                    .and_then(|damage| {
+                        roll_damage(&spell, damage, false, cli.explode_cap, &damage_types)
+                    })
+                    .unwrap_or_else(|e| fail(e));
             }
         }
         Some(Commands::Stats) => {
-            display_stats(&sheet);
+            display_stats(&sheet, &damage_types);
+        }
+        Some(Commands::Coins { action, amount, to }) => {
+            let currency = &mut sheet
+                .equipment
+                .get_or_insert_with(Default::default)
+                .currency;
+            if let Some(action) = action {
+                match currency.update(action, &amount.join(" "), to.as_deref()) {
+                    Ok(done) => println!("{}", done.green()),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red().bold(), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            println!(
+                "{} {} ({} gp in all)",
+                "Coins:".bold(),
+                currency.describe(),
+                currency.total_gold()
+            );
+            if action.is_some() {
+                if let Err(e) =
+                    open_cli_database().and_then(|db| db.save_character(Some(character_id), &sheet))
+                {
+                    eprintln!("{} failed to save the coins: {}", "Error:".red().bold(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Share {
+            output,
+            portrait,
+            players,
+        }) => {
+            if let Err(e) = run_cli_share(sheet, output, portrait, players) {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Travel { .. })
+        | Some(Commands::Party { .. })
+        | Some(Commands::Macro { .. })
+        | Some(Commands::Hoard { .. })
+        | Some(Commands::Lookup { .. })
+        | Some(Commands::Profiles)
+        | Some(Commands::Sync { .. })
+        | Some(Commands::Db { .. })
+        | Some(Commands::ValidateContainer { .. })
+        | Some(Commands::StatsReport)
+        | Some(Commands::DamageTypes) => {
+            unreachable!("handled before loading a character")
         }
         None => {
             eprintln!("{} No command specified", "Error:".red().bold());
@@ -933,97 +1513,118 @@ fn run_cli_mode(cli: Cli) {
 }
 
 fn run_cli_dice_roll(cli: &Cli) {
-    // Character persistence is SQLite-backed; the CLI dice roll path can still run
-    // without a loaded character sheet.
-    let character_data = CharacterData::default();
+    // Character persistence is SQLite-backed; --checkon uses the stored character
+    // when there is one, but the CLI dice roll path can still run without it.
+    let character_data = CharacterData {
+        sheet: cli.checkon.as_ref().and_then(|_| {
+            load_character_sheet(cli.character.first().map(String::as_str), cli.character_id).ok()
+        }),
+        ..Default::default()
+    };
 
     let mut total_modifier = cli.modifier;
     let mut modifier_name = String::new();
-    let mut dice_to_roll: Vec<DiceType> = Vec::new();
 
-    // Parse dice
-    if let Some(dice_args) = &cli.dice {
-        for (count, die_type) in dice_args {
-            for _ in 0..*count {
-                dice_to_roll.push(*die_type);
-            }
+    // Parse dice; compound expressions are totalled from the dice they roll
+    let (mut dice_to_roll, expression) = match cli.dice.clone().and_then(DiceExpr::sum) {
+        Some(expression) => {
+            DiceConfig::from_expression(&expression.with_explosion_cap(cli.explode_cap))
         }
-    }
+        None => (Vec::new(), None),
+    };
 
     // Apply checkon modifier
+    let mut d20_test = None;
     if let Some(check) = &cli.checkon {
         let check_lower = check.to_lowercase();
 
         if let Some(skill_mod) = character_data.get_skill_modifier(&check_lower) {
             total_modifier += skill_mod;
             modifier_name = check.clone();
+            d20_test = Some(D20Test::AbilityCheck);
         } else if let Some(ability_mod) = character_data.get_ability_modifier(&check_lower) {
             total_modifier += ability_mod;
             modifier_name = format!("{} check", check);
+            d20_test = Some(D20Test::AbilityCheck);
         } else if let Some(save_mod) = character_data.get_saving_throw_modifier(&check_lower) {
             total_modifier += save_mod;
             modifier_name = format!("{} save", check);
+            d20_test = Some(D20Test::SavingThrow(check_lower));
         } else {
             modifier_name = check.clone();
             eprintln!("Warning: '{}' not found in character sheet", check);
         }
     }
 
+    // Conditions on the character give the check advantage, disadvantage or
+    // a penalty
+    let effect = match (d20_test, &character_data.sheet) {
+        (Some(test), Some(sheet)) => sheet.conditions.effect_on(&test, load_cli_ruleset()),
+        _ => ConditionEffect::default(),
+    };
+    if report_automatic_failure(&modifier_name, &effect) {
+        return;
+    }
+    print_condition_effect(&effect);
+    total_modifier += effect.penalty();
+    let advantage = cli.advantage || !effect.advantage.is_empty();
+    let disadvantage = cli.disadvantage || !effect.disadvantage.is_empty();
+
     // Default to 1d20 if no dice specified
     if dice_to_roll.is_empty() {
         dice_to_roll.push(DiceType::D20);
     }
 
     // Roll the dice
-    let mut rng = rand::rng();
+    let mut engine = roll_engine();
     let mut results: Vec<(DiceType, u32)> = Vec::new();
     let mut total: i32 = 0;
 
     for die in &dice_to_roll {
-        let roll = rng.random_range(1..=die.max_value());
+        let roll = engine.roll_die(*die);
         results.push((*die, roll));
-        total += roll as i32;
+        total += die.face_value(roll);
     }
 
     // Handle advantage/disadvantage for d20 rolls
     if dice_to_roll.len() == 1 && dice_to_roll[0] == DiceType::D20 {
-        if cli.advantage && !cli.disadvantage {
-            let roll2 = rng.random_range(1..=20);
+        if advantage && !disadvantage {
+            let roll2 = engine.roll_die(DiceType::D20);
             let roll1 = results[0].1;
             let used = roll1.max(roll2);
             let dropped = roll1.min(roll2);
             results[0].1 = used;
             total = used as i32;
 
-            println!("\n{}", "═══════════════════════════════════════".cyan());
-            println!(
+            say!("\n{}", "═══════════════════════════════════════".cyan());
+            say!(
                 "{} {} {}",
                 "Rolling:".bold().white(),
                 modifier_name.yellow().bold(),
                 "(Advantage)".green()
             );
-            println!(
+            say!(
                 "{} {} (dropped {})",
                 "Dice:".bold().white(),
                 format!("[{}]", used).bright_green().bold(),
                 format!("[{}]", dropped).dimmed()
             );
-        } else if cli.disadvantage && !cli.advantage {
-            let roll2 = rng.random_range(1..=20);
+        } else if disadvantage && !advantage {
+            let roll2 = engine.roll_die(DiceType::D20);
             let roll1 = results[0].1;
             let used = roll1.min(roll2);
             let dropped = roll1.max(roll2);
             results[0].1 = used;
             total = used as i32;
 
-            println!("\n{}", "═══════════════════════════════════════".cyan());
-            println!(
+            say!("\n{}", "═══════════════════════════════════════".cyan());
+            say!(
                 "{} {} {}",
                 "Rolling:".bold().white(),
                 modifier_name.yellow().bold(),
                 "(Disadvantage)".red()
             );
-            println!(
+            say!(
                 "{} {} (dropped {})",
                 "Dice:".bold().white(),
                 format!("[{}]", used).bright_red().bold(),
@@ -1036,6 +1637,31 @@ fn run_cli_dice_roll(cli: &Cli) {
         print_normal_roll(&results, &modifier_name);
     }
 
+    if let Some(expression) = &expression {
+        // Exploding dice keep rolling while they show their maximum
+        let mut explosions = Vec::new();
+        expression
+            .roll_pending_explosions(&results, &mut explosions, |die| Some(engine.roll_die(die)));
+        match expression.roll_with_explosions(&results, &explosions) {
+            Ok(roll) => {
+                total = roll.total as i32;
+                say!(
+                    "{} {} = {}",
+                    "Expression:".bold().white(),
+                    expression,
+                    total
+                );
+                if let Some(exploded) = roll.explosion_summary() {
+                    say!("  {}", exploded.dimmed());
+                }
+                if let Some(kept) = roll.keep_summary() {
+                    say!("  {}", kept.dimmed());
+                }
+            }
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+    }
+
     // Print modifier and total
     if total_modifier != 0 {
         let modifier_str = if total_modifier >= 0 {
@@ -1043,10 +1669,22 @@ fn run_cli_dice_roll(cli: &Cli) {
         } else {
             format!("{}", total_modifier).cyan()
         };
-        println!("{} {}", "Modifier:".bold().white(), modifier_str);
+        say!("{} {}", "Modifier:".bold().white(), modifier_str);
     }
 
     let final_total = total + total_modifier;
+    if quiet() {
+        match cli.dc {
+            Some(dc) => println!(
+                "{} {}",
+                final_total,
+                DcResult::new(final_total, dc).verdict()
+            ),
+            None => println!("{}", final_total),
+        }
+        return;
+    }
+
     let d20_roll = if dice_to_roll.len() == 1 && dice_to_roll[0] == DiceType::D20 {
         Some(results[0].1)
     } else {
@@ -1076,27 +1714,32 @@ fn run_cli_dice_roll(cli: &Cli) {
         );
     }
 
+    if let Some(dc) = cli.dc {
+        print_dc_result(final_total, dc);
+    }
+
     println!("{}", "═══════════════════════════════════════".cyan());
 }
 
 fn print_normal_roll(results: &[(DiceType, u32)], modifier_name: &str) {
-    println!("\n{}", "═══════════════════════════════════════".cyan());
+    say!("\n{}", "═══════════════════════════════════════".cyan());
     if !modifier_name.is_empty() {
-        println!(
+        say!(
             "{} {}",
             "Rolling:".bold().white(),
             modifier_name.yellow().bold()
         );
     } else {
-        let dice_str: Vec<String> = results.iter().map(|(d, _)| d.name().to_string()).collect();
-        println!(
+        let dice_str: Vec<String> = results.iter().map(|(d, _)| d.name()).collect();
+        say!(
             "{} {}",
             "Rolling:".bold().white(),
             dice_str.join(", ").yellow().bold()
         );
     }
 
-    let rolls_str: Vec<String> = results
+    let rolls_str: Vec<String> = result_order()
+        .arrange(results, &[])
         .iter()
         .map(|(d, r)| {
             let roll_color = if d == &DiceType::D20 {
@@ -1108,34 +1751,34 @@ fn print_normal_roll(results: &[(DiceType, u32)], modifier_name: &str) {
             } else {
                 format!("[{}]", r).bright_white().bold().to_string()
             };
-            format!("{}: {}", d.name(), roll_color)
+            if d.is_coin() {
+                format!("{}: {}", d.name(), coin_side(*r).bright_white().bold())
+            } else if *d == DiceType::Fudge {
+                format!(
+                    "{}: {}",
+                    d.name(),
+                    format!("[{}]", d.face_text(*r)).bright_white().bold()
+                )
+            } else if d.is_percentile() {
+                let pair = format!("({})", percentile_faces(*r)).dimmed();
+                format!("{}: {} {}", d.name(), roll_color, pair)
+            } else {
+                format!("{}: {}", d.name(), roll_color)
+            }
         })
         .collect();
-    println!("{} {}", "Dice:".bold().white(), rolls_str.join(", "));
-}
-
-fn roll_d20() -> i32 {
-    rand::rng().random_range(1..=20)
+    say!("{} {}", "Dice:".bold().white(), rolls_str.join(", "));
 }
 
-fn roll_with_advantage_disadvantage(advantage: bool, disadvantage: bool) -> (i32, Option<i32>) {
-    if advantage && disadvantage {
-        (roll_d20(), None)
-    } else if advantage {
-        let roll1 = roll_d20();
-        let roll2 = roll_d20();
-        (roll1.max(roll2), Some(roll1.min(roll2)))
-    } else if disadvantage {
-        let roll1 = roll_d20();
-        let roll2 = roll_d20();
-        (roll1.min(roll2), Some(roll1.max(roll2)))
-    } else {
-        (roll_d20(), None)
-    }
-}
-
-fn roll_ability_check(name: &str, modifier: i32, advantage: bool, disadvantage: bool) {
-    let (dice_roll, dropped_roll) = roll_with_advantage_disadvantage(advantage, disadvantage);
+/// Roll a d20 check; with a DC it is reported as a success or failure.
+fn roll_ability_check(
+    name: &str,
+    modifier: i32,
+    advantage: bool,
+    disadvantage: bool,
+    dc: Option<i32>,
+) {
+    let (dice_roll, dropped_roll) = roll_d20_test(advantage, disadvantage);
     let total = dice_roll + modifier;
     display_roll_result(
         name,
@@ -1145,74 +1788,49 @@ fn roll_ability_check(name: &str, modifier: i32, advantage: bool, disadvantage:
         dropped_roll,
         advantage,
         disadvantage,
+        dc,
     );
 }
 
-fn roll_attack(weapon: &dndgamerolls::dice3d::types::Weapon, advantage: bool, disadvantage: bool) {
-    let (dice_roll, dropped_roll) = roll_with_advantage_disadvantage(advantage, disadvantage);
-    let total = dice_roll + weapon.attack_bonus;
+/// The campaign's damage types; the standard ones when the database can't
+/// be read.
+fn load_cli_damage_types() -> DamageTypeRegistry {
+    open_cli_database()
+        .and_then(|db| db.get_setting::<DamageTypeRegistry>(DAMAGE_TYPES_SETTING_KEY))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
 
-    println!("\n{}", "═══════════════════════════════════════".cyan());
-    println!("{} {} Attack", "⚔️".bold(), weapon.name.bold().yellow());
+/// The campaign's rules edition; the 2014 rules when the database can't be
+/// read.
+fn load_cli_ruleset() -> Ruleset {
+    open_cli_database()
+        .and_then(|db| db.get_setting::<Ruleset>(RULESET_SETTING_KEY))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
 
-    if let Some(dropped) = dropped_roll {
-        if advantage {
-            println!(
-                "{} {} {} (dropped {})",
-                "Attack Roll:".bold().white(),
-                format!("[{}]", dice_roll).bright_green().bold(),
-                "(advantage)".green(),
-                format!("[{}]", dropped).dimmed()
-            );
-        } else if disadvantage {
-            println!(
-                "{} {} {} (dropped {})",
-                "Attack Roll:".bold().white(),
-                format!("[{}]", dice_roll).bright_red().bold(),
-                "(disadvantage)".red(),
-                format!("[{}]", dropped).dimmed()
-            );
-        }
-    } else {
-        let dice_color = match dice_roll {
-            20 => format!("[{}]", dice_roll).bright_green().bold(),
-            1 => format!("[{}]", dice_roll).bright_red().bold(),
-            _ => format!("[{}]", dice_roll).bright_white().bold(),
+fn run_cli_damage_types(damage_types: &DamageTypeRegistry) {
+    println!("{}", "DAMAGE TYPES".bold().yellow());
+    for damage_type in damage_types.all() {
+        let custom = if DamageTypeRegistry::is_standard(&damage_type.name) {
+            ""
+        } else {
+            " (custom)"
         };
-        println!("{} {}", "Attack Roll:".bold().white(), dice_color);
-    }
-
-    let modifier_str = if weapon.attack_bonus >= 0 {
-        format!("+{}", weapon.attack_bonus).cyan()
-    } else {
-        format!("{}", weapon.attack_bonus).cyan()
-    };
-    println!("{} {}", "Attack Bonus:".bold().white(), modifier_str);
-
-    let total_color = if dice_roll == 20 {
-        format!("{}", total).bright_green().bold()
-    } else if dice_roll == 1 {
-        format!("{}", total).bright_red().bold()
-    } else {
-        format!("{}", total).white().bold()
-    };
-    println!("{} {}", "Total:".bold().white(), total_color);
-
-    if dice_roll == 20 {
-        println!("{}", "🎯 CRITICAL HIT! 🎯".bright_green().bold());
-    } else if dice_roll == 1 {
-        println!("{}", "💨 CRITICAL MISS! 💨".bright_red().bold());
+        println!(
+            "  {}  {} {}{}",
+            colored_damage_type(&damage_type.name, damage_types),
+            damage_type.color.dimmed(),
+            damage_type.icon.dimmed(),
+            custom.dimmed()
+        );
     }
-
-    println!(
-        "{} {} ({})",
-        "Damage:".bold().white(),
-        weapon.damage.yellow(),
-        weapon.damage_type.dimmed()
-    );
-    println!("{}", "═══════════════════════════════════════".cyan());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn display_roll_result(
     roll_type: &str,
     dice_roll: i32,
@@ -1221,7 +1839,16 @@ fn display_roll_result(
     dropped_roll: Option<i32>,
     advantage: bool,
     disadvantage: bool,
+    dc: Option<i32>,
 ) {
+    if quiet() {
+        match dc {
+            Some(dc) => println!("{} {}", total, DcResult::new(total, dc).verdict()),
+            None => println!("{}", total),
+        }
+        return;
+    }
+
     println!("\n{}", "═══════════════════════════════════════".cyan());
     println!(
         "{} {}",
@@ -1291,95 +1918,1225 @@ fn display_roll_result(
         );
     }
 
+    if let Some(dc) = dc {
+        print_dc_result(total, dc);
+    }
+
     println!("{}", "═══════════════════════════════════════".cyan());
 }
 
-fn load_character_sheet(
-    character_name: Option<&str>,
-    character_id: Option<i64>,
-) -> Result<dndgamerolls::dice3d::types::CharacterSheet, Box<dyn std::error::Error>> {
-    let db = CharacterDatabase::open().map_err(|e| format!("Failed to open database: {}", e))?;
-
-    if let Some(id) = character_id {
-        return Ok(db.load_character(id)?);
+/// The SUCCESS or FAILURE line of a check rolled with `--dc`
+fn print_dc_result(total: i32, dc: i32) {
+    let result = DcResult::new(total, dc);
+    if result.success() {
+        println!("{}", result.describe().bright_green().bold());
+    } else {
+        println!("{}", result.describe().bright_red().bold());
     }
+}
 
-    let list = db.list_characters()?;
-    if list.is_empty() {
-        return Err("No characters found in local database".into());
+/// Use the named profile for this run, creating it if needed.
+fn select_profile_for_run(name: &str) -> Result<(), String> {
+    let mut registry = CharacterDatabase::load_profiles();
+    let existed = registry.find(name).is_some();
+    let profile = registry.find_or_create(name)?;
+    if !existed {
+        CharacterDatabase::save_profiles(&registry)?;
+        eprintln!("Created profile '{}'", profile.name);
     }
+    CharacterDatabase::use_profile_for_this_run(&profile.id);
+    Ok(())
+}
 
-    if let Some(name) = character_name {
-        if let Some(entry) = list.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
-            return Ok(db.load_character(entry.id)?);
-        }
+fn run_cli_profiles() {
+    let registry = CharacterDatabase::load_profiles();
+    let current = CharacterDatabase::current_profile();
 
-        return Err(format!("Character '{}' not found", name).into());
+    println!("{}", "Database profiles:".bold().white());
+    for profile in &registry.profiles {
+        let mut line = format!("  {} ({})", profile.name, profile.id);
+        if profile.id == registry.active {
+            line.push_str("  [active]");
+        }
+        if profile.id == current.id {
+            println!("{}", line.green().bold());
+        } else {
+            println!("{}", line);
+        }
     }
-
-    Ok(db.load_character(list[0].id)?)
+    println!("\nUse {} to pick one for a run.", "--profile <NAME>".cyan());
 }
 
-fn get_skill_by_name<'a>(
-    skills: &'a std::collections::HashMap<String, dndgamerolls::dice3d::types::Skill>,
-    name: &str,
-) -> Option<(&'static str, &'a dndgamerolls::dice3d::types::Skill)> {
-    let name_lower = name.to_lowercase().replace(' ', "");
+/// Password of the cloud sync server user.
+const SYNC_PASSWORD_ENV_VAR: &str = "DNDGAMEROLLS_SYNC_PASSWORD";
 
-    match name_lower.as_str() {
-        "acrobatics" => skills.get("acrobatics").map(|s| ("Acrobatics", s)),
-        "animalhandling" | "animal" => skills.get("animalHandling").map(|s| ("Animal Handling", s)),
-        "arcana" => skills.get("arcana").map(|s| ("Arcana", s)),
-        "athletics" => skills.get("athletics").map(|s| ("Athletics", s)),
-        "deception" => skills.get("deception").map(|s| ("Deception", s)),
-        "history" => skills.get("history").map(|s| ("History", s)),
-        "insight" => skills.get("insight").map(|s| ("Insight", s)),
-        "intimidation" => skills.get("intimidation").map(|s| ("Intimidation", s)),
-        "investigation" => skills.get("investigation").map(|s| ("Investigation", s)),
-        "medicine" => skills.get("medicine").map(|s| ("Medicine", s)),
-        "nature" => skills.get("nature").map(|s| ("Nature", s)),
-        "perception" => skills.get("perception").map(|s| ("Perception", s)),
-        "performance" => skills.get("performance").map(|s| ("Performance", s)),
-        "persuasion" => skills.get("persuasion").map(|s| ("Persuasion", s)),
-        "religion" => skills.get("religion").map(|s| ("Religion", s)),
-        "sleightofhand" | "sleight" => skills.get("sleightOfHand").map(|s| ("Sleight of Hand", s)),
-        "stealth" => skills.get("stealth").map(|s| ("Stealth", s)),
-        "survival" => skills.get("survival").map(|s| ("Survival", s)),
-        _ => None,
+/// Save a new sync server (or turn sync off), then sync the characters.
+fn run_cli_sync(server: Option<CloudSyncConfig>, off: bool) {
+    let result = open_cli_database().and_then(|db| {
+        if off {
+            db.set_setting(CLOUD_SYNC_SETTING_KEY, CloudSyncConfig::default())?;
+            return Ok("Cloud sync turned off".to_string());
+        }
+        if let Some(mut server) = server {
+            server.validate()?;
+            // Setting up the same server again isn't a first sync
+            if let Some(previous) = CloudSyncConfig::load(&db) {
+                if (&previous.endpoint, &previous.namespace, &previous.database)
+                    == (&server.endpoint, &server.namespace, &server.database)
+                {
+                    server.last_sync = previous.last_sync;
+                    server.synced = previous.synced;
+                }
+            }
+            db.set_setting(CLOUD_SYNC_SETTING_KEY, server)?;
+        }
+        Ok(format!("Synced: {}", db.sync_characters()?))
+    });
+
+    match result {
+        Ok(message) => println!("{}", message.green()),
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
     }
 }
 
-fn display_stats(character: &dndgamerolls::dice3d::types::CharacterSheet) {
-    let info = &character.character;
-    println!("\n{}", "═══════════════════════════════════════".cyan());
-    println!("{}", "CHARACTER STATS".bold().yellow());
-    println!("{}", "═══════════════════════════════════════".cyan());
-
-    let name_display = if let Some(alter_ego) = &info.alter_ego {
-        format!("{} ({})", info.name, alter_ego)
-    } else {
-        info.name.clone()
+/// Report the records this version can't read, and repair them with
+/// `repair`. Sealed sheets of an encrypted database are only checked when
+/// `DNDGAMEROLLS_PASSPHRASE` is set; otherwise they are listed as not checked.
+fn run_cli_db_doctor(repair: bool) {
+    let open = || -> Result<CharacterDatabase, String> {
+        let db = CharacterDatabase::open()?;
+        if db.is_locked() {
+            if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+                db.unlock(&passphrase)?;
+            }
+        }
+        Ok(db)
     };
-    println!("{} {}", "Name:".bold().white(), name_display.green());
-
-    let class_display = if let Some(subclass) = &info.subclass {
-        format!("{} ({})", info.class, subclass)
-    } else {
-        info.class.clone()
+    let report = match open().and_then(|db| db.doctor(repair)) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
     };
-    println!(
-        "{} Level {} {} {}",
-        "Class:".bold().white(),
-        info.level,
-        class_display.cyan(),
-        info.race.yellow()
-    );
 
-    println!("\n{}", "ATTRIBUTES".bold().yellow());
+    let latest = latest_schema_version();
     println!(
-        "  {} {} ({:+})",
-        "STR:".bold(),
-        character.attributes.strength,
+        "{} {} (latest {})",
+        "Schema version:".bold().white(),
+        report.schema_version,
+        latest
+    );
+    if report.migrated > 0 {
+        println!(
+            "{}",
+            format!(
+                "Upgraded {} record(s) to version {}",
+                report.migrated, latest
+            )
+            .green()
+        );
+    }
+    for key in &report.locked {
+        println!(
+            "  {} {}: {}",
+            "character".cyan(),
+            key,
+            "not checked (locked)".yellow()
+        );
+    }
+    if !report.locked.is_empty() {
+        println!(
+            "Set {} to the passphrase to check encrypted sheets\n",
+            PASSPHRASE_ENV_VAR
+        );
+    }
+
+    if report.findings.is_empty() {
+        if report.is_healthy() || report.repaired {
+            println!("{}", "No malformed records".green().bold());
+        } else {
+            println!(
+                "No malformed records, but some need upgrading; run {} to upgrade them",
+                "db doctor --repair".cyan()
+            );
+        }
+        return;
+    }
+
+    for finding in &report.findings {
+        let fix = match (&finding.fix, report.repaired) {
+            (RecordFix::Rewrite(_), true) => "repaired".green(),
+            (RecordFix::Quarantine, true) => "moved to quarantine".yellow(),
+            (RecordFix::Rewrite(_), false) => "can be repaired".yellow(),
+            (RecordFix::Quarantine, false) => "would be quarantined".red(),
+        };
+        println!(
+            "  {} {}: {} ({})",
+            finding.table.cyan(),
+            finding.key,
+            finding.problem,
+            fix
+        );
+    }
+    if !report.repaired {
+        println!(
+            "\n{} malformed record(s); run {} to fix them",
+            report.findings.len(),
+            "db doctor --repair".cyan()
+        );
+    }
+}
+
+/// Drop test dice into a container model and print what went wrong; exits
+/// with an error when the model shouldn't be used as a container.
+#[cfg(feature = "gui")]
+fn run_cli_validate_container(model: &std::path::Path, drops: u32) {
+    use dndgamerolls::dice3d::types::{validate_container, ContainerMesh};
+
+    let report = match ContainerMesh::from_gltf(model).and_then(|mesh| {
+        say!("Dropping {} test dice into {}...", drops, model.display());
+        validate_container(&mesh, drops)
+    }) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+
+    for line in report.summary() {
+        say!("  {}", line.yellow());
+    }
+    if report.is_acceptable() {
+        println!("{}", "The model works as a dice container".green().bold());
+    } else {
+        println!(
+            "{}",
+            "The model can't be used as a dice container".red().bold()
+        );
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_cli_validate_container(_model: &std::path::Path, _drops: u32) {
+    eprintln!("This build of dndgamerolls has no physics; validate-container needs the 3D app");
+    std::process::exit(1);
+}
+
+fn run_cli_share(
+    sheet: dndgamerolls::dice3d::types::CharacterSheet,
+    output: Option<std::path::PathBuf>,
+    portrait: Option<std::path::PathBuf>,
+    players: bool,
+) -> Result<(), String> {
+    let sheet = if players {
+        redact_sheet(&sheet, Viewer::Players)
+    } else {
+        sheet
+    };
+    let output = output
+        .unwrap_or_else(|| format!("{}.{}", sheet.character.name, CHARACTER_FILE_EXTENSION).into());
+    let name = sheet.character.name.clone();
+
+    let mut file = CharacterFile::new(sheet);
+    if let Some(portrait) = portrait {
+        let bytes = std::fs::read(&portrait)
+            .map_err(|e| format!("Failed to read {}: {}", portrait.display(), e))?;
+        file = file.with_portrait(&bytes)?;
+    }
+    write_character_file(&output, &file)?;
+
+    println!(
+        "Saved {} to {}",
+        name.bold(),
+        output.display().to_string().cyan()
+    );
+    Ok(())
+}
+
+/// Travel with `--party` and every `--character`, or with the first saved
+/// party (every character when none are saved) when neither is given.
+fn run_cli_travel(cli: &Cli, vs: Option<i32>) {
+    use dndgamerolls::dice3d::types::{TravelMember, TravelParty};
+
+    let party = match open_cli_database().and_then(|db| {
+        let sheets = if cli.party.is_none() && cli.character.is_empty() {
+            let parties = db
+                .get_setting::<Parties>(PARTIES_SETTING_KEY)?
+                .unwrap_or_default();
+            load_cli_group(&db, Some(parties.default_name()), &[])?
+        } else {
+            load_cli_group(&db, cli.party.as_deref(), &cli.character)?
+        };
+        Ok(TravelParty::new(
+            sheets.iter().map(TravelMember::from_sheet).collect(),
+        ))
+    }) {
+        Ok(party) if !party.members.is_empty() => party,
+        Ok(_) => {
+            eprintln!(
+                "{} No characters found in local database",
+                "Error:".red().bold()
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{} Failed to load party: {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("{}", "  TRAVEL MODE".bold().white());
+    println!("{}", "═══════════════════════════════════════".cyan());
+
+    println!("\n{}", "Passive Perception:".bold().white());
+    for member in party.perception_line() {
+        println!("  {:>3}  {}", member.passive_perception, member.name);
+    }
+
+    println!("\n{}", "Group Stealth:".bold().white());
+    let slowest = party.slowest_index();
+    for (index, member) in party.members.iter().enumerate() {
+        let line = format!(
+            "  {:>+3}  {} ({} ft)",
+            member.stealth_modifier, member.name, member.speed
+        );
+        if slowest == Some(index) {
+            println!("{}  {}", line.yellow().bold(), "← slowest".yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    if let Some(vs) = vs {
+        let result = party.roll_group_stealth(vs, &mut CliRng);
+        println!(
+            "\n{} vs passive Perception {}",
+            "Group Stealth Roll".bold().white(),
+            vs
+        );
+        for roll in &result.rolls {
+            let total = if roll.success {
+                roll.total.to_string().green()
+            } else {
+                roll.total.to_string().red()
+            };
+            println!("  {}: [{}] = {}", roll.name, roll.die, total);
+        }
+        let verdict = if result.success {
+            "The party slips by unnoticed".green().bold()
+        } else {
+            "The party is spotted".red().bold()
+        };
+        println!(
+            "\n{} ({}/{} succeeded)",
+            verdict,
+            result.successes,
+            result.rolls.len()
+        );
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// The group check a subcommand rolls, if it is a d20 check every
+/// character can make.
+fn group_check_for(command: &Commands) -> Option<Result<GroupCheck, String>> {
+    let check = match command {
+        Commands::Strength => GroupCheck::Ability("strength"),
+        Commands::Dexterity => GroupCheck::Ability("dexterity"),
+        Commands::Constitution => GroupCheck::Ability("constitution"),
+        Commands::Intelligence => GroupCheck::Ability("intelligence"),
+        Commands::Wisdom => GroupCheck::Ability("wisdom"),
+        Commands::Charisma => GroupCheck::Ability("charisma"),
+        Commands::Initiative => GroupCheck::Initiative,
+        Commands::Skill { name, with } => {
+            let Some((skill, _)) = find_skill(name) else {
+                return Some(Err(format!("Unknown skill '{}'", name)));
+            };
+            let with = match with.as_deref() {
+                Some(ability) => match normalize_ability_name(ability) {
+                    Some(ability) => Some(ability),
+                    None => return Some(Err(format!("Unknown ability '{}'", ability))),
+                },
+                None => None,
+            };
+            GroupCheck::Skill { skill, with }
+        }
+        Commands::Save { ability } => match normalize_ability_name(ability) {
+            Some(ability) => GroupCheck::Save(ability),
+            None => return Some(Err(format!("Unknown ability '{}'", ability))),
+        },
+        _ => return None,
+    };
+    Some(Ok(check))
+}
+
+/// The characters of `--party` and every `--character`, each once.
+fn load_cli_group(
+    db: &CharacterDatabase,
+    party: Option<&str>,
+    characters: &[String],
+) -> Result<Vec<dndgamerolls::dice3d::types::CharacterSheet>, String> {
+    let list = db.list_characters()?;
+    let mut names: Vec<String> = match party {
+        Some(name) => db
+            .get_setting::<Parties>(PARTIES_SETTING_KEY)?
+            .unwrap_or_default()
+            .member_names(name, &list)?,
+        None => Vec::new(),
+    };
+    names.extend(characters.iter().cloned());
+
+    let mut sheets = Vec::new();
+    let mut ids = Vec::new();
+    for name in &names {
+        let entry = list
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Character '{}' not found", name))?;
+        if !ids.contains(&entry.id) {
+            ids.push(entry.id);
+            sheets.push(db.load_character(entry.id)?);
+        }
+    }
+    if sheets.is_empty() {
+        return Err("No characters found in local database".to_string());
+    }
+    Ok(sheets)
+}
+
+/// Roll one check for several characters and print them side by side,
+/// highest total first.
+fn run_cli_group_check(cli: &Cli, check: &GroupCheck) {
+    let sheets = match open_cli_database()
+        .and_then(|db| load_cli_group(&db, cli.party.as_deref(), &cli.character))
+    {
+        Ok(sheets) => sheets,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let ruleset = load_cli_ruleset();
+    let mut rng = CliRng;
+    let mut rolls = Vec::new();
+    for sheet in &sheets {
+        match check.roll(sheet, ruleset, cli.advantage, cli.disadvantage, &mut rng) {
+            Ok(roll) => rolls.push(roll),
+            Err(e) => eprintln!("{} {}", "Skipped:".yellow().bold(), e),
+        }
+    }
+    rank_group_rolls(&mut rolls);
+
+    if quiet() {
+        for roll in &rolls {
+            if roll.auto_fail {
+                println!("{} {} fails automatically", roll.total, roll.name);
+                continue;
+            }
+            match cli.dc {
+                Some(dc) => println!(
+                    "{} {} {}",
+                    roll.total,
+                    roll.name,
+                    DcResult::new(roll.total, dc).verdict()
+                ),
+                None => println!("{} {}", roll.total, roll.name),
+            }
+        }
+        return;
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!(
+        "  {} {}",
+        "GROUP CHECK:".bold().white(),
+        check.label().bold()
+    );
+    println!("{}", "═══════════════════════════════════════".cyan());
+
+    let width = rolls.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for roll in &rolls {
+        if roll.auto_fail {
+            println!(
+                "  {}  {:<width$}  {}",
+                "  —".bold(),
+                roll.name,
+                "fails automatically".red(),
+                width = width
+            );
+            continue;
+        }
+        let die = match roll.die {
+            20 => format!("[{:>2}]", roll.die).green().bold(),
+            1 => format!("[{:>2}]", roll.die).red().bold(),
+            _ => format!("[{:>2}]", roll.die).normal(),
+        };
+        let dropped = roll
+            .dropped
+            .map(|d| format!(" ({})", d).dimmed().to_string())
+            .unwrap_or_default();
+        let verdict = match cli.dc.map(|dc| DcResult::new(roll.total, dc)) {
+            Some(result) if result.success() => format!("  {}", result.describe()).green(),
+            Some(result) => format!("  {}", result.describe()).red(),
+            None => "".normal(),
+        };
+        println!(
+            "  {}  {:<width$}  {} {:+}{}{}",
+            format!("{:>3}", roll.total).bold(),
+            roll.name,
+            die,
+            roll.modifier,
+            dropped,
+            verdict,
+            width = width
+        );
+    }
+
+    // As a group check, half of the group succeeding is enough
+    if let Some(dc) = cli.dc {
+        let passed = rolls.iter().filter(|r| r.passes(dc)).count();
+        let verdict = if group_check_passes(&rolls, dc) {
+            "The group succeeds".green().bold()
+        } else {
+            "The group fails".red().bold()
+        };
+        println!("\n{} ({}/{} met DC {})", verdict, passed, rolls.len(), dc);
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// Roll a check for the character and `--vs` opponent and say who wins the
+/// contest.
+fn run_cli_contest(cli: &Cli, check: &GroupCheck, opponent: &str) {
+    let fail = |e: String| -> ! {
+        eprintln!("{} {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    };
+    let sheet = load_cli_character(cli.character.first().map(String::as_str), cli.character_id)
+        .map(|(_, sheet)| sheet)
+        .unwrap_or_else(|e| fail(format!("Failed to load character: {}", e)));
+    let opposing_sheet = load_cli_character(Some(opponent), None)
+        .map(|(_, sheet)| sheet)
+        .unwrap_or_else(|e| fail(format!("Failed to load {}: {}", opponent, e)));
+
+    let ruleset = load_cli_ruleset();
+    let (ours, theirs) = check
+        .contest(
+            &sheet,
+            &opposing_sheet,
+            ruleset,
+            cli.advantage,
+            cli.disadvantage,
+            &mut CliRng,
+        )
+        .unwrap_or_else(|e| fail(e));
+    let outcome = ContestOutcome::of(ours.total, theirs.total);
+
+    if quiet() {
+        println!("{} {}", ours.total, ours.name);
+        println!("{} {}", theirs.total, theirs.name);
+        return;
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("  {} {}", "CONTEST:".bold().white(), check.label().bold());
+    println!("{}", "═══════════════════════════════════════".cyan());
+    for roll in [&ours, &theirs] {
+        let dropped = roll
+            .dropped
+            .map(|d| format!(" ({})", d).dimmed().to_string())
+            .unwrap_or_default();
+        println!(
+            "  {}  {}  [{}] {:+}{}",
+            format!("{:>3}", roll.total).bold(),
+            roll.name,
+            roll.die,
+            roll.modifier,
+            dropped
+        );
+    }
+    let margin = (ours.total - theirs.total).abs();
+    let verdict = match outcome {
+        ContestOutcome::Wins => format!("{} wins by {}", ours.name, margin).green().bold(),
+        ContestOutcome::Loses => format!("{} wins by {}", theirs.name, margin).red().bold(),
+        ContestOutcome::Tie => "Tie: the situation stays the same".yellow().bold(),
+    };
+    println!("\n{}", verdict);
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// List, save or delete the campaign's parties.
+fn run_cli_party(name: Option<&str>, members: &[String], delete: bool) {
+    let result = open_cli_database().and_then(|db| {
+        let mut parties = db
+            .get_setting::<Parties>(PARTIES_SETTING_KEY)?
+            .unwrap_or_default();
+        let message = match name {
+            Some(name) if delete => {
+                let party = parties.remove(name)?;
+                Some(format!("Party '{}' deleted", party.name))
+            }
+            Some(name) if !members.is_empty() => {
+                let list = db.list_characters()?;
+                if let Some(missing) = members
+                    .iter()
+                    .find(|m| !list.iter().any(|c| c.name.eq_ignore_ascii_case(m)))
+                {
+                    return Err(format!("Character '{}' not found", missing));
+                }
+                parties.save(name, members.to_vec())?;
+                Some(format!("Party '{}' saved", name.trim()))
+            }
+            _ => None,
+        };
+        if message.is_some() {
+            db.set_setting(PARTIES_SETTING_KEY, parties.clone())?;
+        }
+        Ok((parties, message))
+    });
+
+    let (parties, message) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(message) = message {
+        println!("{}", message.green());
+        return;
+    }
+
+    let shown: Vec<_> = match name {
+        Some(name) => match parties.get(name) {
+            Some(party) => vec![party],
+            None => {
+                eprintln!("{} Party '{}' not found", "Error:".red().bold(), name);
+                std::process::exit(1);
+            }
+        },
+        None => parties.parties.iter().collect(),
+    };
+    if shown.is_empty() {
+        println!("No saved parties");
+    }
+    for party in shown {
+        println!("{} {}", party.name.bold().white(), party.members.join(", "));
+    }
+    println!(
+        "\nUse {} to roll a check for a party.",
+        "--party <NAME>".cyan()
+    );
+}
+
+/// List the profile's roll macros, define or delete one, or play one.
+/// `--character` picks whose macros are used; a macro defined with it
+/// belongs to that character only.
+fn run_cli_macro(cli: &Cli, text: &str, delete: bool) {
+    let result = open_cli_database().and_then(|db| {
+        // Use the stored spelling of the character's name
+        let character = match cli.character.first() {
+            Some(name) => Some(
+                db.list_characters()?
+                    .into_iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(name))
+                    .map(|c| c.name)
+                    .ok_or_else(|| format!("Character '{}' not found", name))?,
+            ),
+            None => None,
+        };
+        let mut macros = db
+            .get_setting::<DiceMacros>(DICE_MACROS_DB_KEY)?
+            .unwrap_or_default();
+        let message = if text.contains('=') {
+            Some(macros.define(text, character.as_deref())?)
+        } else if delete {
+            let removed = macros
+                .remove(text, character.as_deref())
+                .ok_or_else(|| format!("No macro named {}", text.trim()))?;
+            Some(format!("Macro removed: {}", removed.name))
+        } else {
+            None
+        };
+        if message.is_some() {
+            db.set_setting(DICE_MACROS_DB_KEY, macros.clone())?;
+        }
+        Ok((character, macros, message))
+    });
+
+    let (character, macros, message) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(message) = message {
+        println!("{}", message.green());
+        return;
+    }
+    if text.trim().is_empty() {
+        println!("{}", macros.summary(character.as_deref()));
+        return;
+    }
+    match macros.find(text, character.as_deref()) {
+        Some(dice_macro) => play_cli_macro(dice_macro, cli.explode_cap),
+        None => {
+            eprintln!("{} No macro named {}", "Error:".red().bold(), text.trim());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Roll each roll of a macro in order.
+fn play_cli_macro(dice_macro: &DiceMacro, explode_cap: u32) {
+    say!(
+        "\n{} {}",
+        "Macro:".bold().white(),
+        dice_macro.name.bold().yellow()
+    );
+    for step in &dice_macro.steps {
+        let MacroStep::Roll {
+            label,
+            dice,
+            modifier,
+        } = step
+        else {
+            if let MacroStep::SelectCharacter { name } = step {
+                say!("{} {}", "Character:".bold().white(), name);
+            }
+            continue;
+        };
+        let rolled = DiceExpr::parse(dice).and_then(|expression| {
+            let expression = expression.with_explosion_cap(explode_cap);
+            roll_engine()
+                .roll_expression(&expression)
+                .map(|roll| (expression, roll))
+        });
+        let (expression, roll) = match rolled {
+            Ok(rolled) => rolled,
+            Err(e) => {
+                eprintln!("{} {}: {}", "Error:".red().bold(), dice_macro.name, e);
+                std::process::exit(1);
+            }
+        };
+
+        let total = roll.total + *modifier as i64;
+        if quiet() {
+            println!("{}", total);
+            continue;
+        }
+        println!("{}", "═══════════════════════════════════════".cyan());
+        println!(
+            "{}",
+            if label.is_empty() { dice } else { label }.bold().yellow()
+        );
+        let rolls_str: Vec<String> = roll
+            .rolls
+            .iter()
+            .map(|r| {
+                let text = format!("[{}]", r.value);
+                if r.dropped {
+                    text.dimmed().to_string()
+                } else {
+                    text.bright_white().bold().to_string()
+                }
+            })
+            .collect();
+        println!("{} {}", "Dice:".bold().white(), rolls_str.join(" "));
+        if let Some(exploded) = roll.explosion_summary() {
+            println!("  {}", exploded.dimmed());
+        }
+        if let Some(kept) = roll.keep_summary() {
+            println!("  {}", kept.dimmed());
+        }
+        if *modifier != 0 {
+            println!(
+                "{} {} {:+}",
+                "Expression:".bold().white(),
+                expression,
+                modifier
+            );
+        } else {
+            println!("{} {}", "Expression:".bold().white(), expression);
+        }
+        println!(
+            "{} {}",
+            "Total:".bold().white(),
+            total.to_string().bright_green().bold()
+        );
+    }
+    say!("{}", "═══════════════════════════════════════".cyan());
+}
+
+fn run_cli_hoard(tier: Option<HoardTier>, clear: bool) {
+    let result = open_cli_database().and_then(|db| {
+        let mut ledger = db
+            .get_setting::<TreasureLedger>(TREASURE_LEDGER_SETTING_KEY)?
+            .unwrap_or_default();
+        if clear {
+            ledger = TreasureLedger::default();
+        } else if let Some(tier) = tier {
+            let hoard = TreasureHoard::roll(tier, &mut CliRng);
+            print_hoard(&hoard);
+            ledger.add(hoard);
+        }
+        if clear || tier.is_some() {
+            db.set_setting(TREASURE_LEDGER_SETTING_KEY, ledger.clone())?;
+        }
+        Ok(ledger)
+    });
+
+    match result {
+        Ok(_) if clear => println!("{}", "Treasure ledger cleared".green()),
+        Ok(ledger) => println!(
+            "\n{} {}",
+            "Treasure ledger:".bold().white(),
+            ledger.summary()
+        ),
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_hoard(hoard: &TreasureHoard) {
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!(
+        "{} {} {}",
+        "TREASURE HOARD".bold().yellow(),
+        hoard.tier.label().bold().white(),
+        format!("(d100 {})", hoard.roll).dimmed()
+    );
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("  {} {}", "Coins:".bold(), hoard.coins.describe());
+    if let Some(valuables) = &hoard.valuables {
+        println!(
+            "  {} {} ({} gp)",
+            "Valuables:".bold(),
+            valuables.describe(),
+            valuables.total_gp()
+        );
+    }
+    for item in &hoard.magic_items {
+        println!(
+            "  {} Magic Item Table {}, d100 {}",
+            "Magic item:".bold(),
+            item.table.letter(),
+            item.roll.to_string().bright_magenta()
+        );
+    }
+    println!(
+        "  {} {:.0} gp",
+        "Worth (without magic items):".bold(),
+        hoard.value_gp()
+    );
+}
+
+fn run_cli_lookup_monster(
+    name: &str,
+    attack: Option<&str>,
+    save: Option<&str>,
+    cli: &Cli,
+    damage_types: &DamageTypeRegistry,
+) {
+    let fail = |e: String| -> ! {
+        eprintln!("{} {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    };
+    let compendium = MonsterCompendium::srd();
+    let monster = compendium.find(name).unwrap_or_else(|e| fail(e));
+
+    if attack.is_none() && save.is_none() {
+        print_monster(monster);
+        if std::io::stdin().is_terminal() && !quiet() {
+            offer_monster_rolls(monster, cli, damage_types);
+        }
+        return;
+    }
+    if let Some(action) = attack {
+        let action = monster.action(action).unwrap_or_else(|e| fail(e));
+        roll_monster_action(monster, action, cli, damage_types);
+    }
+    if let Some(ability) = save {
+        let Some(ability) = normalize_ability_name(ability) else {
+            fail(format!(
+                "Unknown ability '{}'; use str, dex, con, int, wis or cha",
+                ability
+            ));
+        };
+        roll_monster_save(monster, ability, cli);
+    }
+}
+
+fn print_monster(monster: &Monster) {
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("{}", monster.name.to_uppercase().bold().yellow());
+    println!("{}", monster.type_line().italic().dimmed());
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("  {} {}", "Armor Class".bold(), monster.armor_line());
+    println!(
+        "  {} {} ({})",
+        "Hit Points".bold(),
+        monster.hit_points,
+        monster.hit_dice
+    );
+    println!("  {} {}", "Speed".bold(), monster.speed);
+    println!("  {}", monster.ability_scores().join("  "));
+    for (label, value) in monster.details() {
+        println!("  {} {}", label.bold(), value);
+    }
+    println!("  {} {}", "Challenge".bold(), monster.challenge_line());
+    for feature in &monster.traits {
+        println!(
+            "\n  {} {}",
+            format!("{}.", feature.name).bold().italic(),
+            feature.text
+        );
+    }
+    if !monster.actions.is_empty() {
+        println!("\n{}", "ACTIONS".bold().cyan());
+        for action in &monster.actions {
+            println!(
+                "  {} {}",
+                format!("{}.", action.name).bold().italic(),
+                action.text
+            );
+        }
+    }
+    println!("{}", "═══════════════════════════════════════".cyan());
+    println!("{}", SRD_ATTRIBUTION.dimmed());
+}
+
+/// Ask which of the monster's attacks and saves to roll until the answer is
+/// empty.
+fn offer_monster_rolls(monster: &Monster, cli: &Cli, damage_types: &DamageTypeRegistry) {
+    let actions: Vec<&MonsterAction> = monster.rollable_actions().collect();
+    println!("\n{}", "Roll against it:".bold());
+    for (index, action) in actions.iter().enumerate() {
+        let mut rolls = Vec::new();
+        if let Some(bonus) = action.attack_bonus {
+            rolls.push(format!("{:+} to hit", bonus));
+        }
+        if let Some(damage) = &action.damage {
+            rolls.push(damage.clone());
+        }
+        println!(
+            "  {} {} {}",
+            format!("{}.", index + 1).yellow(),
+            action.name,
+            format!("({})", rolls.join(", ")).dimmed()
+        );
+    }
+    println!("  {} saving throw", "str/dex/con/int/wis/cha".yellow());
+    loop {
+        print!("{} {} ", "Roll?".bold(), "[Enter to stop]".dimmed());
+        let _ = std::io::stdout().flush();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_err() {
+            return;
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return;
+        }
+        if let Some(action) = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| actions.get(n.wrapping_sub(1)))
+        {
+            roll_monster_action(monster, action, cli, damage_types);
+        } else if let Some(ability) = normalize_ability_name(answer) {
+            roll_monster_save(monster, ability, cli);
+        } else {
+            println!(
+                "{}",
+                format!("Pick 1-{} or an ability", actions.len()).yellow()
+            );
+        }
+    }
+}
+
+/// Roll an action's attack, then its damage (doubled dice on a natural 20).
+fn roll_monster_action(
+    monster: &Monster,
+    action: &MonsterAction,
+    cli: &Cli,
+    damage_types: &DamageTypeRegistry,
+) {
+    let label = format!("{} {}", monster.name, action.name);
+    let mut critical = false;
+    if let Some(bonus) = action.attack_bonus {
+        let (die, dropped) = roll_d20_test(cli.advantage, cli.disadvantage);
+        display_roll_result(
+            &label,
+            die,
+            bonus,
+            die + bonus,
+            dropped,
+            cli.advantage,
+            cli.disadvantage,
+            None,
+        );
+        critical = die == 20;
+    }
+    let rolled = action.damage_roll().map(|damage| {
+        damage
+            .and_then(|damage| roll_damage(&label, damage, critical, cli.explode_cap, damage_types))
+    });
+    if let Some(Err(e)) = rolled {
+        eprintln!("{} {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    }
+}
+
+fn roll_monster_save(monster: &Monster, ability: &str, cli: &Cli) {
+    let bonus = monster.save_bonus(ability).unwrap_or_default();
+    roll_ability_check(
+        &format!("{} {} Save", monster.name, ability_abbreviation(ability)),
+        bonus,
+        cli.advantage,
+        cli.disadvantage,
+        cli.dc,
+    );
+}
+
+fn run_cli_stats_report(character: Option<&str>) {
+    // Saved rolls aren't encrypted, so a locked database needs no passphrase
+    let records = match CharacterDatabase::open().and_then(|db| db.load_rolls()) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{} Failed to load rolls: {}", "Error:".red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+    let records: Vec<RollRecord> = match character {
+        Some(name) => records
+            .into_iter()
+            .filter(|r| r.character.eq_ignore_ascii_case(name))
+            .collect(),
+        None => records,
+    };
+    let stats = RollStats::from_records(&records);
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+    match character {
+        Some(name) => println!("{} {}", "  ROLL STATISTICS:".bold().white(), name.bold()),
+        None => println!("{}", "  ROLL STATISTICS".bold().white()),
+    }
+    println!("{}", "═══════════════════════════════════════".cyan());
+
+    if stats.rolls == 0 {
+        println!("No saved rolls. Rolls made in the 3D app are saved for this report.");
+        println!("{}", "═══════════════════════════════════════".cyan());
+        return;
+    }
+
+    println!("{} {}", "Rolls:".bold().white(), stats.rolls);
+    if let Some(average) = stats.average_d20 {
+        println!(
+            "{} {:.2} over {} rolls (fair {:.1})",
+            "Average d20:".bold().white(),
+            average,
+            stats.d20_rolls,
+            EXPECTED_D20_AVERAGE
+        );
+        println!(
+            "{} {}   {} {}",
+            "Natural 20s:".bold().white(),
+            stats.natural_20s.to_string().bright_green().bold(),
+            "Natural 1s:".bold().white(),
+            stats.natural_1s.to_string().bright_red().bold()
+        );
+    }
+
+    println!("\n{}", "Distribution:".bold().white());
+    for distribution in &stats.distributions {
+        println!(
+            "  {:<5} {:>5} rolls  average {:.2} (fair {:.1})",
+            distribution.die.name(),
+            distribution.rolls(),
+            distribution.average(),
+            distribution.expected_average()
+        );
+        if distribution.counts.len() > 20 {
+            continue;
+        }
+        let most = distribution
+            .counts
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (i, count) in distribution.counts.iter().enumerate() {
+            let bar = "█".repeat((count * 30).div_ceil(most) as usize);
+            println!("    {:>3} {:<30} {}", i + 1, bar.cyan(), count);
+        }
+    }
+
+    if !stats.luck.is_empty() {
+        println!("\n{}", "Luck over time (d20):".bold().white());
+        for luck in &stats.luck {
+            let name = if luck.character.is_empty() {
+                "No character"
+            } else {
+                luck.character.as_str()
+            };
+            let summary = format!("{:+.2}", luck.luck());
+            let summary = if luck.luck() >= 0.0 {
+                summary.green()
+            } else {
+                summary.red()
+            };
+            println!(
+                "  {}: average {:.2} ({}) over {} rolls",
+                name.bold(),
+                luck.average_d20,
+                summary,
+                luck.d20_rolls
+            );
+            for point in &luck.days {
+                println!(
+                    "    {}  {:>5.2}  ({} rolls)",
+                    format_day(point.day),
+                    point.average_d20,
+                    point.d20_rolls
+                );
+            }
+        }
+    }
+
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
+/// Environment variable holding the passphrase of an encrypted database in CLI mode
+const PASSPHRASE_ENV_VAR: &str = "DNDGAMEROLLS_PASSPHRASE";
+
+/// Open the active profile's database, unlocking it from
+/// `DNDGAMEROLLS_PASSPHRASE` when character sheets are encrypted.
+fn open_cli_database() -> Result<CharacterDatabase, String> {
+    let db = CharacterDatabase::open().map_err(|e| format!("Failed to open database: {}", e))?;
+    if db.is_locked() {
+        let passphrase = std::env::var(PASSPHRASE_ENV_VAR).map_err(|_| {
+            format!(
+                "Character sheets are encrypted; set {} to the passphrase",
+                PASSPHRASE_ENV_VAR
+            )
+        })?;
+        db.unlock(&passphrase)?;
+    }
+    Ok(db)
+}
+
+fn load_character_sheet(
+    character_name: Option<&str>,
+    character_id: Option<i64>,
+) -> Result<dndgamerolls::dice3d::types::CharacterSheet, Box<dyn std::error::Error>> {
+    load_cli_character(character_name, character_id).map(|(_, sheet)| sheet)
+}
+
+/// Load the CLI's character along with its database id, for commands that
+/// save changes to the sheet.
+fn load_cli_character(
+    character_name: Option<&str>,
+    character_id: Option<i64>,
+) -> Result<(i64, dndgamerolls::dice3d::types::CharacterSheet), Box<dyn std::error::Error>> {
+    let db = open_cli_database()?;
+
+    if let Some(id) = character_id {
+        return Ok((id, db.load_character(id)?));
+    }
+
+    let list = db.list_characters()?;
+    if list.is_empty() {
+        return Err("No characters found in local database".into());
+    }
+
+    let id = match character_name {
+        Some(name) => match list.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
+            Some(entry) => entry.id,
+            None => return Err(format!("Character '{}' not found", name).into()),
+        },
+        None => list[0].id,
+    };
+    Ok((id, db.load_character(id)?))
+}
+
+fn get_skill_by_name<'a>(
+    skills: &'a std::collections::HashMap<String, dndgamerolls::dice3d::types::Skill>,
+    name: &str,
+) -> Option<(&'static str, &'a dndgamerolls::dice3d::types::Skill)> {
+    let name_lower = name.to_lowercase().replace(' ', "");
+
+    match name_lower.as_str() {
+        "acrobatics" => skills.get("acrobatics").map(|s| ("Acrobatics", s)),
+        "animalhandling" | "animal" => skills.get("animalHandling").map(|s| ("Animal Handling", s)),
+        "arcana" => skills.get("arcana").map(|s| ("Arcana", s)),
+        "athletics" => skills.get("athletics").map(|s| ("Athletics", s)),
+        "deception" => skills.get("deception").map(|s| ("Deception", s)),
+        "history" => skills.get("history").map(|s| ("History", s)),
+        "insight" => skills.get("insight").map(|s| ("Insight", s)),
+        "intimidation" => skills.get("intimidation").map(|s| ("Intimidation", s)),
+        "investigation" => skills.get("investigation").map(|s| ("Investigation", s)),
+        "medicine" => skills.get("medicine").map(|s| ("Medicine", s)),
+        "nature" => skills.get("nature").map(|s| ("Nature", s)),
+        "perception" => skills.get("perception").map(|s| ("Perception", s)),
+        "performance" => skills.get("performance").map(|s| ("Performance", s)),
+        "persuasion" => skills.get("persuasion").map(|s| ("Persuasion", s)),
+        "religion" => skills.get("religion").map(|s| ("Religion", s)),
+        "sleightofhand" | "sleight" => skills.get("sleightOfHand").map(|s| ("Sleight of Hand", s)),
+        "stealth" => skills.get("stealth").map(|s| ("Stealth", s)),
+        "survival" => skills.get("survival").map(|s| ("Survival", s)),
+        _ => None,
+    }
+}
+
+fn display_stats(
+    character: &dndgamerolls::dice3d::types::CharacterSheet,
+    damage_types: &DamageTypeRegistry,
+) {
+    let info = &character.character;
+    println!("\n{}", "═══════════════════════════════════════".cyan());
+    println!("{}", "CHARACTER STATS".bold().yellow());
+    println!("{}", "═══════════════════════════════════════".cyan());
+
+    let name_display = if let Some(alter_ego) = &info.alter_ego {
+        format!("{} ({})", info.name, alter_ego)
+    } else {
+        info.name.clone()
+    };
+    println!("{} {}", "Name:".bold().white(), name_display.green());
+
+    let class_display = if let Some(subclass) = &info.subclass {
+        format!("{} ({})", info.class, subclass)
+    } else {
+        info.class.clone()
+    };
+    println!(
+        "{} Level {} {} {}",
+        "Class:".bold().white(),
+        info.level,
+        class_display.cyan(),
+        info.race.yellow()
+    );
+
+    println!("\n{}", "ATTRIBUTES".bold().yellow());
+    println!(
+        "  {} {} ({:+})",
+        "STR:".bold(),
+        character.attributes.strength,
         character.modifiers.strength
     );
     println!(
@@ -1428,6 +3185,13 @@ fn display_stats(character: &dndgamerolls::dice3d::types::CharacterSheet) {
         "Proficiency Bonus:".bold(),
         character.proficiency_bonus
     );
+    if character.combat.extra_attacks > 0 {
+        println!(
+            "  {} {} per Attack action",
+            "Attacks:".bold(),
+            character.attacks_per_action()
+        );
+    }
 
     println!("\n{}", "WEAPONS".bold().yellow());
     if let Some(equipment) = character.equipment.as_ref() {
@@ -1437,61 +3201,80 @@ fn display_stats(character: &dndgamerolls::dice3d::types::CharacterSheet) {
                 weapon.name.bold(),
                 weapon.attack_bonus,
                 weapon.damage,
-                weapon.damage_type.dimmed()
+                colored_damage_type(&weapon.damage_type, damage_types)
             );
         }
     }
 
-    println!("{}", "═══════════════════════════════════════".cyan());
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_roll_d20_in_range() {
-        for _ in 0..100 {
-            let roll = roll_d20();
-            assert!((1..=20).contains(&roll), "Roll {} out of range", roll);
+    if let Some(equipment) = character
+        .equipment
+        .as_ref()
+        .filter(|e| !e.items.is_empty() || e.currency.total_copper() > 0)
+    {
+        println!("\n{}", "INVENTORY".bold().yellow());
+        for item in &equipment.items {
+            let mut tags = Vec::new();
+            if item.equipped {
+                tags.push("equipped");
+            }
+            if item.attuned {
+                tags.push("attuned");
+            } else if item.requires_attunement {
+                tags.push("needs attunement");
+            }
+            if tags.is_empty() {
+                println!("  {}", item);
+            } else {
+                println!("  {} {}", item, format!("({})", tags.join(", ")).dimmed());
+            }
         }
-    }
-
-    #[test]
-    fn test_advantage_disadvantage_cancel() {
-        for _ in 0..10 {
-            let (_, dropped) = roll_with_advantage_disadvantage(true, true);
-            assert!(dropped.is_none(), "Should cancel out with no dropped roll");
+        println!(
+            "  {} {} / {} lb, {} gp",
+            "Carrying:".bold(),
+            character.carried_weight(),
+            carrying_capacity(character.attributes.strength),
+            inventory_value(&equipment.items)
+        );
+        if let Some(warning) = character.encumbrance().warning() {
+            println!("  {}", warning.red());
         }
+        println!("  {} {}", "Coins:".bold(), equipment.currency.describe());
     }
 
-    #[test]
-    fn test_normal_roll_no_dropped() {
-        for _ in 0..10 {
-            let (_, dropped) = roll_with_advantage_disadvantage(false, false);
-            assert!(dropped.is_none(), "Normal roll should have no dropped roll");
-        }
+    if !character.conditions.is_empty() {
+        println!("\n{}", "CONDITIONS".bold().yellow());
+        println!("  {}", character.conditions.names().join(", "));
     }
 
-    #[test]
-    fn test_advantage_has_dropped_roll() {
-        for _ in 0..10 {
-            let (used, dropped) = roll_with_advantage_disadvantage(true, false);
-            assert!(dropped.is_some(), "Advantage should have dropped roll");
-            let dropped_val = dropped.unwrap();
-            assert!(used >= dropped_val, "Advantage should use higher roll");
+    if !character.damage_defenses.is_empty() {
+        println!("\n{}", "DAMAGE DEFENSES".bold().yellow());
+        for kind in [
+            DefenseKind::Resistance,
+            DefenseKind::Immunity,
+            DefenseKind::Vulnerability,
+        ] {
+            let names = character.damage_defenses.list(kind);
+            if names.is_empty() {
+                continue;
+            }
+            let names: Vec<String> = names
+                .iter()
+                .map(|name| colored_damage_type(name, damage_types).to_string())
+                .collect();
+            println!(
+                "  {} {}",
+                format!("{}:", kind.label()).bold(),
+                names.join(", ")
+            );
         }
     }
 
-    #[test]
-    fn test_disadvantage_has_dropped_roll() {
-        for _ in 0..10 {
-            let (used, dropped) = roll_with_advantage_disadvantage(false, true);
-            assert!(dropped.is_some(), "Disadvantage should have dropped roll");
-            let dropped_val = dropped.unwrap();
-            assert!(used <= dropped_val, "Disadvantage should use lower roll");
-        }
-    }
+    println!("{}", "═══════════════════════════════════════".cyan());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_parse_dice_arg() {
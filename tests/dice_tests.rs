@@ -35,10 +35,15 @@ fn test_dice_type_parse_valid() {
     assert_eq!(DiceType::parse("D20"), Some(DiceType::D20));
 }
 
+#[test]
+fn test_dice_type_parse_custom() {
+    assert_eq!(DiceType::parse("d3"), Some(DiceType::Custom(3)));
+    assert_eq!(DiceType::parse("d100"), Some(DiceType::Custom(100)));
+}
+
 #[test]
 fn test_dice_type_parse_invalid() {
-    assert_eq!(DiceType::parse("d3"), None);
-    assert_eq!(DiceType::parse("d100"), None);
+    assert_eq!(DiceType::parse("d1"), None);
     assert_eq!(DiceType::parse("invalid"), None);
     assert_eq!(DiceType::parse(""), None);
 }